@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::bin_resolver::{self, ResolveCtx};
+use crate::config;
+use crate::demo::{self, BuildOptions};
+
+/// Builds a portable demo bundle from a project directory (`demo build`).
+#[derive(Clone, Debug)]
+pub struct BuildBundle {
+    pub project_root: PathBuf,
+    pub out: PathBuf,
+    pub tenant: Option<String>,
+    pub team: Option<String>,
+    pub allow_pack_dirs: bool,
+    pub only_used_providers: bool,
+    /// Gate the build on `greentic-pack doctor`.
+    pub run_doctor: bool,
+}
+
+impl BuildBundle {
+    pub fn new(project_root: impl Into<PathBuf>, out: impl Into<PathBuf>) -> Self {
+        Self {
+            project_root: project_root.into(),
+            out: out.into(),
+            tenant: None,
+            team: None,
+            allow_pack_dirs: false,
+            only_used_providers: false,
+            run_doctor: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BuiltBundle {
+    pub bundle: PathBuf,
+    pub doctor_ran: bool,
+}
+
+pub fn build_bundle(request: BuildBundle) -> anyhow::Result<BuiltBundle> {
+    let root = request.project_root;
+    let config = config::load_operator_config(&root)?;
+    let pack_command = if request.run_doctor {
+        let explicit = config::binary_override(config.as_ref(), "greentic-pack", &root);
+        Some(bin_resolver::resolve_binary(
+            "greentic-pack",
+            &ResolveCtx {
+                config_dir: root.clone(),
+                explicit_path: explicit,
+            },
+        )?)
+    } else {
+        None
+    };
+    let options = BuildOptions {
+        out_dir: request.out.clone(),
+        tenant: request.tenant,
+        team: request.team,
+        allow_pack_dirs: request.allow_pack_dirs,
+        only_used_providers: request.only_used_providers,
+        run_doctor: request.run_doctor,
+    };
+    demo::build_bundle(&root, options, pack_command.as_deref())?;
+    Ok(BuiltBundle {
+        bundle: request.out,
+        doctor_ran: request.run_doctor,
+    })
+}
@@ -0,0 +1,43 @@
+//! Embeddable operator API.
+//!
+//! Typed entry points for the main operator workflows. They return structured results
+//! instead of printing, so other Rust services can drive demo bundles in-process. The
+//! matching `demo` subcommands are thin wrappers that render these results.
+//!
+//! ```no_run
+//! use greentic_operator::api::{self, SendMessage};
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let mut request = SendMessage::new("demo-bundle", "messaging-telegram");
+//! request.text = Some("hello".to_string());
+//! request.to = vec!["@ops".to_string()];
+//! let sent = api::send_message(request)?;
+//! println!("sent via {} success={}", sent.provider_id, sent.success);
+//! # Ok(())
+//! # }
+//! ```
+
+mod build;
+mod offboard;
+mod onboard;
+mod send;
+mod setup;
+mod start;
+mod subscriptions;
+
+pub use build::{BuildBundle, BuiltBundle, build_bundle};
+pub use offboard::{
+    DELETE_WEBHOOK_OP, OffboardAction, OffboardReport, OffboardStatus, OffboardTenant,
+    offboard_tenant,
+};
+pub use onboard::{OnboardReport, OnboardTenant, OnboardedProvider, onboard_tenant};
+pub use send::{
+    Fanout, FanoutDelivery, HttpRequestPreview, PlannedMessage, PreviewedMessage, SendMessage,
+    SentMessage, plan_message, preview_message, send_fanout, send_message,
+};
+pub use setup::{RunSetup, SetupReport, run_setup};
+pub use start::{RunningDemo, StartDemo, start_demo};
+pub use subscriptions::{EnsureSubscription, EnsuredSubscription, ensure_subscription};
+
+pub(crate) use setup::discovered_domains;
+pub(crate) use start::start_ingress_server;
@@ -0,0 +1,418 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, anyhow};
+use greentic_secrets_lib::SecretError;
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::audit::{self, AuditEvent};
+use crate::cloudflared;
+use crate::config::load_demo_config_or_default;
+use crate::demo::runner_host::{DemoRunnerHost, OperatorContext};
+use crate::demo::webhook_watcher;
+use crate::discovery::{self, DiscoveryOptions, DiscoveryResult};
+use crate::domains::{self, Domain};
+use crate::operator_auth::{self, OperatorAction};
+use crate::project;
+use crate::runtime_state::RuntimePaths;
+use crate::secret_requirements::load_secret_keys_from_pack;
+use crate::secrets_backend::SecretsBackendKind;
+use crate::secrets_gate;
+use crate::secrets_setup::resolve_env;
+use crate::subscriptions_universal::{
+    Scheduler, SubscriptionService, SubscriptionState, SubscriptionStore, state_root,
+};
+use crate::tenant_transfer;
+
+/// Provider op that removes the webhooks registered for a tenant/team.
+pub const DELETE_WEBHOOK_OP: &str = "delete_webhook";
+
+/// Removes a tenant and what it left at its providers (`demo offboard`).
+#[derive(Clone, Debug)]
+pub struct OffboardTenant {
+    pub bundle: PathBuf,
+    pub tenant: String,
+    /// Report what would be removed without removing anything.
+    pub dry_run: bool,
+    /// Leave subscriptions, webhooks and secrets in external backends alone;
+    /// only the bundle is cleaned up.
+    pub keep_remote: bool,
+    pub runner_binary: Option<PathBuf>,
+}
+
+impl OffboardTenant {
+    pub fn new(bundle: impl Into<PathBuf>, tenant: impl Into<String>) -> Self {
+        Self {
+            bundle: bundle.into(),
+            tenant: tenant.into(),
+            dry_run: false,
+            keep_remote: false,
+            runner_binary: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct OffboardReport {
+    pub tenant: String,
+    pub dry_run: bool,
+    pub actions: Vec<OffboardAction>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct OffboardAction {
+    /// `subscription`, `webhook`, `secret` or `path`.
+    pub kind: &'static str,
+    pub target: String,
+    pub status: OffboardStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OffboardStatus {
+    Planned,
+    Done,
+    Skipped,
+}
+
+impl OffboardStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OffboardStatus::Planned => "planned",
+            OffboardStatus::Done => "done",
+            OffboardStatus::Skipped => "skipped",
+        }
+    }
+}
+
+/// Deletes the tenant's subscriptions through their providers, removes its webhooks
+/// where the provider has a [`DELETE_WEBHOOK_OP`], deletes its secrets from an
+/// external backend, and then removes its directories, gmaps, resolved manifests and
+/// dev-store secrets from the bundle.
+///
+/// The provider-side steps run first and stop at the first failure, so a tenant is
+/// never removed locally while its registrations are still live. Re-running after
+/// fixing the cause picks up where it stopped. A dry run only reads the bundle, so
+/// it needs no operator grants.
+pub fn offboard_tenant(request: &OffboardTenant) -> anyhow::Result<OffboardReport> {
+    let bundle = request.bundle.as_path();
+    let tenant = request.tenant.as_str();
+    if !request.dry_run {
+        operator_auth::authorize(bundle, OperatorAction::SubscriptionsDelete, tenant)?;
+        operator_auth::authorize(bundle, OperatorAction::Policy, tenant)?;
+        operator_auth::authorize(bundle, OperatorAction::SecretsWrite, tenant)?;
+    }
+
+    let mut actions = Vec::new();
+    let result = offboard(request, &mut actions);
+    let report = OffboardReport {
+        tenant: tenant.to_string(),
+        dry_run: request.dry_run,
+        actions,
+    };
+    if !request.dry_run {
+        audit::record(
+            bundle,
+            AuditEvent::new("tenant.offboard", tenant)
+                .scope(tenant, None)
+                .detail(json!({
+                    "keep_remote": request.keep_remote,
+                    "actions": report.actions,
+                }))
+                .result(&result),
+        );
+    }
+    result.map(|()| report)
+}
+
+fn offboard(request: &OffboardTenant, actions: &mut Vec<OffboardAction>) -> anyhow::Result<()> {
+    let bundle = request.bundle.as_path();
+    let tenant = request.tenant.as_str();
+    // `remove` validates the name and that the tenant exists; ask it first so
+    // nothing at a provider is touched for a typo.
+    let planned = tenant_transfer::remove(bundle, tenant, true)?;
+    let mut teams = vec![None];
+    teams.extend(project::list_teams(bundle, tenant)?.into_iter().map(Some));
+
+    let store = SubscriptionStore::new(state_root(bundle));
+    let subscriptions = store
+        .list_states()?
+        .into_iter()
+        .filter(|state| state.tenant == tenant)
+        .collect::<Vec<_>>();
+    if request.keep_remote {
+        for state in &subscriptions {
+            if !request.dry_run {
+                store.delete_state(state)?;
+            }
+            actions.push(action(
+                "subscription",
+                binding_label(state),
+                status(request.dry_run, OffboardStatus::Done),
+                Some("--keep-remote: stored binding only".to_string()),
+            ));
+        }
+    } else {
+        domains::register_bundle_domains(bundle)?;
+        domains::ensure_cbor_packs(bundle)?;
+        let discovery =
+            discovery::discover_with_options(bundle, DiscoveryOptions { cbor_only: true })?;
+        delete_subscriptions(request, &discovery, &store, &subscriptions, actions)?;
+        delete_webhooks(request, &discovery, &teams, actions)?;
+        delete_external_secrets(request, &discovery, &teams, actions)?;
+    }
+
+    let removal = if request.dry_run {
+        planned
+    } else {
+        tenant_transfer::remove(bundle, tenant, false)
+            .context("provider-side cleanup finished, but removing the tenant failed")?
+    };
+    let local = status(request.dry_run, OffboardStatus::Done);
+    for path in removal.paths {
+        actions.push(action("path", path.display().to_string(), local, None));
+    }
+    for uri in removal.secrets {
+        actions.push(action("secret", uri, local, Some("dev store".to_string())));
+    }
+    Ok(())
+}
+
+fn delete_subscriptions(
+    request: &OffboardTenant,
+    discovery: &DiscoveryResult,
+    store: &SubscriptionStore,
+    subscriptions: &[SubscriptionState],
+    actions: &mut Vec<OffboardAction>,
+) -> anyhow::Result<()> {
+    for state in subscriptions {
+        let label = binding_label(state);
+        if request.dry_run {
+            actions.push(action("subscription", label, OffboardStatus::Planned, None));
+            continue;
+        }
+        let (runner_host, ctx) = runner(request, discovery, state.team.clone())?;
+        Scheduler::new(SubscriptionService::new(runner_host, ctx), store.clone())
+            .delete_binding(state)
+            .with_context(|| format!("delete subscription {label}"))?;
+        actions.push(action("subscription", label, OffboardStatus::Done, None));
+    }
+    Ok(())
+}
+
+fn delete_webhooks(
+    request: &OffboardTenant,
+    discovery: &DiscoveryResult,
+    teams: &[Option<String>],
+    actions: &mut Vec<OffboardAction>,
+) -> anyhow::Result<()> {
+    let providers =
+        webhook_watcher::webhook_providers(discovery, &[Domain::Messaging, Domain::Events]);
+    if providers.is_empty() {
+        return Ok(());
+    }
+    for team in teams {
+        let (runner_host, ctx) = runner(request, discovery, team.clone())?;
+        let team_name = team.as_deref().unwrap_or("default");
+        let paths = RuntimePaths::new(request.bundle.join("state"), &request.tenant, team_name);
+        let public_base_url = cloudflared::current_public_url(&paths)?;
+        for target in &providers {
+            let label = format!("{}/{team_name}", target.provider);
+            if !runner_host.supports_op(target.domain, &target.provider, DELETE_WEBHOOK_OP) {
+                actions.push(action(
+                    "webhook",
+                    label,
+                    OffboardStatus::Skipped,
+                    Some(format!("provider has no {DELETE_WEBHOOK_OP} op")),
+                ));
+                continue;
+            }
+            if request.dry_run {
+                actions.push(action("webhook", label, OffboardStatus::Planned, None));
+                continue;
+            }
+            let payload = json!({
+                "id": target.provider,
+                "tenant": request.tenant,
+                "team": team_name,
+                "public_base_url": public_base_url,
+                "config": {
+                    "id": target.provider,
+                    "public_base_url": public_base_url,
+                },
+            });
+            let outcome = runner_host.invoke_provider_op(
+                target.domain,
+                &target.provider,
+                DELETE_WEBHOOK_OP,
+                &serde_json::to_vec(&payload)?,
+                &ctx,
+            )?;
+            if !outcome.success {
+                return Err(anyhow!(
+                    "{DELETE_WEBHOOK_OP} failed for {label}: {}",
+                    outcome
+                        .error
+                        .unwrap_or_else(|| "no error reported".to_string())
+                ));
+            }
+            let removed = outcome
+                .output
+                .as_ref()
+                .and_then(|output| output.get("deleted").or_else(|| output.get("removed")))
+                .map(Value::to_string);
+            actions.push(action("webhook", label, OffboardStatus::Done, removed));
+        }
+    }
+    Ok(())
+}
+
+/// Secrets in Vault or AWS are addressed by the keys the packs declare; the dev store
+/// is handled with the bundle, and env secrets cannot be deleted.
+fn delete_external_secrets(
+    request: &OffboardTenant,
+    discovery: &DiscoveryResult,
+    teams: &[Option<String>],
+    actions: &mut Vec<OffboardAction>,
+) -> anyhow::Result<()> {
+    let env = resolve_env(None);
+    let configured = SecretsBackendKind::from_config(
+        &load_demo_config_or_default(&request.bundle.join("greentic.demo.yaml")).secrets,
+    );
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("build secrets runtime")?;
+    for team in teams {
+        let handle = secrets_gate::resolve_secrets_manager(
+            &request.bundle,
+            &request.tenant,
+            team.as_deref(),
+        )?;
+        let kind = configured.or_else(|| handle.selection.kind().ok());
+        let external = matches!(
+            kind,
+            Some(SecretsBackendKind::Vault | SecretsBackendKind::Aws)
+        );
+        if !external || handle.dev_store_path.is_some() || handle.using_env_fallback {
+            continue;
+        }
+        let manager = handle.manager();
+        for provider in &discovery.providers {
+            for key in load_secret_keys_from_pack(&provider.pack_path)? {
+                let uri = secrets_gate::canonical_secret_uri(
+                    &env,
+                    &request.tenant,
+                    team.as_deref(),
+                    &provider.provider_id,
+                    &key,
+                );
+                if request.dry_run {
+                    actions.push(action("secret", uri, OffboardStatus::Planned, None));
+                    continue;
+                }
+                match runtime.block_on(manager.delete(&uri)) {
+                    Ok(()) => actions.push(action("secret", uri, OffboardStatus::Done, None)),
+                    Err(SecretError::NotFound { .. }) => actions.push(action(
+                        "secret",
+                        uri,
+                        OffboardStatus::Skipped,
+                        Some("not found".to_string()),
+                    )),
+                    Err(err) => return Err(anyhow!("failed to delete secret {uri}: {err}")),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn runner(
+    request: &OffboardTenant,
+    discovery: &DiscoveryResult,
+    team: Option<String>,
+) -> anyhow::Result<(DemoRunnerHost, OperatorContext)> {
+    let secrets_handle =
+        secrets_gate::resolve_secrets_manager(&request.bundle, &request.tenant, team.as_deref())?;
+    let runner_host = DemoRunnerHost::new(
+        request.bundle.clone(),
+        discovery,
+        request.runner_binary.clone(),
+        secrets_handle,
+        false,
+    )?;
+    let ctx = OperatorContext {
+        tenant: request.tenant.clone(),
+        team,
+        correlation_id: None,
+    };
+    Ok((runner_host, ctx))
+}
+
+fn binding_label(state: &SubscriptionState) -> String {
+    format!("{}/{}", state.provider, state.binding_id)
+}
+
+fn status(dry_run: bool, done: OffboardStatus) -> OffboardStatus {
+    if dry_run {
+        OffboardStatus::Planned
+    } else {
+        done
+    }
+}
+
+fn action(
+    kind: &'static str,
+    target: String,
+    status: OffboardStatus,
+    detail: Option<String>,
+) -> OffboardAction {
+    OffboardAction {
+        kind,
+        target,
+        status,
+        detail,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keep_remote_dry_run_lists_without_deleting() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let bundle = dir.path();
+        project::add_tenant(bundle, "acme")?;
+        project::add_team(bundle, "acme", "support")?;
+        project::add_tenant(bundle, "globex")?;
+
+        let mut request = OffboardTenant::new(bundle, "acme");
+        request.dry_run = true;
+        request.keep_remote = true;
+        let report = offboard_tenant(&request)?;
+        assert!(report.dry_run);
+        assert!(
+            report
+                .actions
+                .iter()
+                .all(|action| action.status == OffboardStatus::Planned)
+        );
+        assert!(
+            report
+                .actions
+                .iter()
+                .any(|action| action.kind == "path" && action.target == "tenants/acme")
+        );
+        assert!(bundle.join("tenants/acme").is_dir());
+
+        request.dry_run = false;
+        offboard_tenant(&request)?;
+        assert!(!bundle.join("tenants/acme").exists());
+        assert!(bundle.join("tenants/globex").is_dir());
+        assert!(offboard_tenant(&request).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,494 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, anyhow};
+use greentic_secrets_lib::DevStore;
+use serde::Serialize;
+use serde_json::{Value, json};
+use tokio::runtime::Runtime;
+
+use super::setup::{RunSetup, run_setup};
+use crate::audit::{self, AuditEvent};
+use crate::discovery::{self, DetectedProvider, DiscoveryOptions};
+use crate::domains;
+use crate::gmap::{self, Policy};
+use crate::interpolate::SecretScope;
+use crate::operator_auth::{self, OperatorAction};
+use crate::project;
+use crate::qa_persist;
+use crate::runtime_state::atomic_write;
+use crate::secrets_crypto;
+use crate::secrets_setup::resolve_env;
+use crate::setup_input::{SetupInputAnswers, collect_setup_answers, load_setup_input};
+use crate::setup_to_formspec;
+use crate::{dev_store_path, operator_log};
+
+/// Brings a tenant/team up with a set of provider packs (`demo onboard`).
+#[derive(Clone, Debug)]
+pub struct OnboardTenant {
+    pub bundle: PathBuf,
+    pub tenant: String,
+    pub team: Option<String>,
+    /// Provider pack ids, or the part after the domain prefix (`telegram` for
+    /// `messaging-telegram`).
+    pub packs: Vec<String>,
+    /// Answers for the packs' setup questions, keyed by provider id or shared.
+    pub setup_input: Option<PathBuf>,
+    pub runner_binary: Option<PathBuf>,
+}
+
+impl OnboardTenant {
+    pub fn new(bundle: impl Into<PathBuf>, tenant: impl Into<String>) -> Self {
+        Self {
+            bundle: bundle.into(),
+            tenant: tenant.into(),
+            team: None,
+            packs: Vec::new(),
+            setup_input: None,
+            runner_binary: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct OnboardReport {
+    pub tenant: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub team: Option<String>,
+    pub created_tenant: bool,
+    pub created_team: bool,
+    pub providers: Vec<OnboardedProvider>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct OnboardedProvider {
+    pub provider_id: String,
+    pub domain: String,
+    /// Setup answers written to the dev secrets store.
+    pub secrets: Vec<String>,
+}
+
+/// Creates the tenant/team, allows each pack in its gmap, seeds the packs' setup
+/// answers as secrets, re-resolves, and runs the packs' setup flows.
+///
+/// The setup input is checked against every pack before anything is written. If a
+/// later step fails, the tenant/team directories, the gmap, the dev secrets store
+/// and the resolved manifests are restored; what a setup flow already did at the
+/// provider (a registered webhook, for example) is not undone.
+pub fn onboard_tenant(request: &OnboardTenant) -> anyhow::Result<OnboardReport> {
+    let bundle = request.bundle.as_path();
+    let tenant = request.tenant.as_str();
+    let team = request.team.as_deref();
+    check_identifier("tenant", tenant)?;
+    if let Some(team) = team {
+        check_identifier("team", team)?;
+    }
+    if request.packs.is_empty() {
+        return Err(anyhow!("no packs given; pass at least one with --packs"));
+    }
+
+    domains::register_bundle_domains(bundle)?;
+    domains::ensure_cbor_packs(bundle)?;
+    let discovery = discovery::discover_with_options(bundle, DiscoveryOptions { cbor_only: true })?;
+    let mut providers: Vec<&DetectedProvider> = Vec::new();
+    for name in &request.packs {
+        let provider = match_pack(&discovery.providers, name)?;
+        if !providers
+            .iter()
+            .any(|known| known.provider_id == provider.provider_id)
+        {
+            providers.push(provider);
+        }
+    }
+
+    let store_path =
+        dev_store_path::override_path().unwrap_or_else(|| dev_store_path::default_path(bundle));
+    for provider in &providers {
+        operator_auth::authorize(bundle, OperatorAction::Policy, &provider.provider_id)?;
+    }
+    operator_auth::authorize(
+        bundle,
+        OperatorAction::SecretsWrite,
+        &store_path.display().to_string(),
+    )?;
+    operator_auth::authorize(bundle, OperatorAction::Setup, tenant)?;
+
+    let answers = collect_answers(request, &providers)?;
+
+    let snapshot = Snapshot::take(bundle, tenant, team, &store_path)?;
+    let result = apply(request, &providers, &answers);
+    let result = match result {
+        Ok(report) => Ok(report),
+        Err(err) => match snapshot.restore() {
+            Ok(()) => Err(err.context("onboarding failed; the bundle was rolled back")),
+            Err(restore_err) => Err(err.context(format!(
+                "onboarding failed and the rollback did not complete: {restore_err:#}"
+            ))),
+        },
+    };
+    audit::record(
+        bundle,
+        AuditEvent::new("tenant.onboard", tenant)
+            .scope(tenant, team)
+            .detail(json!({
+                "providers": providers
+                    .iter()
+                    .map(|provider| provider.provider_id.as_str())
+                    .collect::<Vec<_>>(),
+            }))
+            .result(&result),
+    );
+    result
+}
+
+/// Setup answers per provider, checked against each pack's required questions
+/// before the bundle is touched.
+fn collect_answers(
+    request: &OnboardTenant,
+    providers: &[&DetectedProvider],
+) -> anyhow::Result<Vec<Value>> {
+    let input = match &request.setup_input {
+        Some(path) => {
+            let raw = load_setup_input(
+                path,
+                Some(SecretScope {
+                    bundle_root: &request.bundle,
+                    tenant: &request.tenant,
+                    team: request.team.as_deref(),
+                }),
+            )?;
+            let ids = providers
+                .iter()
+                .map(|provider| provider.provider_id.clone())
+                .collect::<BTreeSet<_>>();
+            Some(SetupInputAnswers::new(raw, ids)?)
+        }
+        None => None,
+    };
+    providers
+        .iter()
+        .map(|provider| {
+            collect_setup_answers(
+                &provider.pack_path,
+                &provider.provider_id,
+                input.as_ref(),
+                false,
+            )
+            .with_context(|| format!("setup input for {}", provider.provider_id))
+        })
+        .collect()
+}
+
+fn apply(
+    request: &OnboardTenant,
+    providers: &[&DetectedProvider],
+    answers: &[Value],
+) -> anyhow::Result<OnboardReport> {
+    let bundle = request.bundle.as_path();
+    let tenant = request.tenant.as_str();
+    let team = request.team.as_deref();
+    let tenant_dir = bundle.join("tenants").join(tenant);
+    let created_tenant = !tenant_dir.exists();
+    let created_team = team.is_some_and(|team| !tenant_dir.join("teams").join(team).exists());
+
+    project::add_tenant(bundle, tenant)?;
+    if let Some(team) = team {
+        project::add_team(bundle, tenant, team)?;
+    }
+
+    let gmap_path = gmap_path(bundle, tenant, team);
+    for provider in providers {
+        let result = gmap::upsert_policy(&gmap_path, &provider.provider_id, Policy::Public);
+        audit::record_gmap_edit(
+            bundle,
+            tenant,
+            team,
+            &provider.provider_id,
+            &Policy::Public,
+            &result,
+        );
+        result?;
+    }
+
+    let secrets = seed_secrets(bundle, tenant, team, providers, answers)?;
+
+    project::sync_project(bundle)?;
+    project::publish_resolved_manifest(bundle, tenant, team)?;
+
+    for provider in providers {
+        let domain = domains::parse_domain(&provider.domain)
+            .ok_or_else(|| anyhow!("unknown domain {}", provider.domain))?;
+        operator_log::info(
+            module_path!(),
+            format!(
+                "[onboard] setup provider={} tenant={tenant} team={team:?}",
+                provider.provider_id
+            ),
+        );
+        let mut setup = RunSetup::new(bundle, tenant);
+        setup.team = request.team.clone();
+        setup.domains = vec![domain];
+        setup.provider = Some(provider.provider_id.clone());
+        setup.setup_input = request.setup_input.clone();
+        setup.runner_binary = request.runner_binary.clone();
+        run_setup(setup).with_context(|| format!("setup {}", provider.provider_id))?;
+    }
+
+    Ok(OnboardReport {
+        tenant: tenant.to_string(),
+        team: team.map(str::to_string),
+        created_tenant,
+        created_team,
+        providers: providers
+            .iter()
+            .zip(secrets)
+            .map(|(provider, secrets)| OnboardedProvider {
+                provider_id: provider.provider_id.clone(),
+                domain: provider.domain.clone(),
+                secrets,
+            })
+            .collect(),
+    })
+}
+
+/// Writes each provider's answers to the dev store under the tenant/team scope,
+/// the way the onboarding UI does once its form is submitted.
+fn seed_secrets(
+    bundle: &Path,
+    tenant: &str,
+    team: Option<&str>,
+    providers: &[&DetectedProvider],
+    answers: &[Value],
+) -> anyhow::Result<Vec<Vec<String>>> {
+    let env = resolve_env(None);
+    let store_path = dev_store_path::ensure_path(bundle)?;
+    let store = DevStore::with_path(&store_path).map_err(|err| {
+        anyhow!(
+            "failed to open dev secrets store {}: {err}",
+            store_path.display()
+        )
+    })?;
+    let key_source = secrets_crypto::configured_key_source(bundle)?;
+    let runtime = Runtime::new().context("failed to create secrets runtime")?;
+    let mut seeded = Vec::new();
+    for (provider, answers) in providers.iter().zip(answers) {
+        let Some(form_spec) =
+            setup_to_formspec::pack_to_form_spec(&provider.pack_path, &provider.provider_id)
+        else {
+            seeded.push(Vec::new());
+            continue;
+        };
+        let result = runtime.block_on(qa_persist::persist_qa_secrets(
+            &store,
+            &store_path,
+            key_source.as_ref(),
+            &env,
+            tenant,
+            team,
+            &provider.provider_id,
+            answers,
+            &form_spec,
+        ));
+        audit::record(
+            bundle,
+            AuditEvent::new("secrets.write", &provider.provider_id)
+                .scope(tenant, team)
+                .detail(json!({ "source": "onboard", "keys": result.as_ref().ok() }))
+                .result(&result),
+        );
+        seeded.push(result?);
+    }
+    Ok(seeded)
+}
+
+/// Resolves a `--packs` entry to a discovered provider: an exact provider id, or
+/// the single id that ends in `-<name>`.
+fn match_pack<'a>(
+    providers: &'a [DetectedProvider],
+    name: &str,
+) -> anyhow::Result<&'a DetectedProvider> {
+    if let Some(provider) = providers
+        .iter()
+        .find(|provider| provider.provider_id == name)
+    {
+        return Ok(provider);
+    }
+    let suffix = format!("-{name}");
+    let matches = providers
+        .iter()
+        .filter(|provider| provider.provider_id.ends_with(&suffix))
+        .collect::<Vec<_>>();
+    match matches.as_slice() {
+        [provider] => Ok(provider),
+        [] => Err(anyhow!(
+            "no provider pack in the bundle matches {name}; available: {}",
+            providers
+                .iter()
+                .map(|provider| provider.provider_id.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+        several => Err(anyhow!(
+            "{name} matches several provider packs ({}); pass the full id",
+            several
+                .iter()
+                .map(|provider| provider.provider_id.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+fn check_identifier(kind: &str, value: &str) -> anyhow::Result<()> {
+    let valid = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && !value.starts_with('-')
+        && !value.ends_with('-');
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "{kind} name {value:?} must be lowercase letters, digits and hyphens"
+        ))
+    }
+}
+
+fn gmap_path(bundle: &Path, tenant: &str, team: Option<&str>) -> PathBuf {
+    let tenant_dir = bundle.join("tenants").join(tenant);
+    match team {
+        Some(team) => tenant_dir.join("teams").join(team).join("team.gmap"),
+        None => tenant_dir.join("tenant.gmap"),
+    }
+}
+
+/// Files onboarding may change, with their contents beforehand (`None`: absent),
+/// and the directories it would create.
+struct Snapshot {
+    files: Vec<(PathBuf, Option<Vec<u8>>)>,
+    created_dirs: Vec<PathBuf>,
+}
+
+impl Snapshot {
+    fn take(
+        bundle: &Path,
+        tenant: &str,
+        team: Option<&str>,
+        store_path: &Path,
+    ) -> anyhow::Result<Self> {
+        let tenant_dir = bundle.join("tenants").join(tenant);
+        let mut created_dirs = Vec::new();
+        if !tenant_dir.exists() {
+            created_dirs.push(tenant_dir);
+        } else if let Some(team) = team {
+            let team_dir = tenant_dir.join("teams").join(team);
+            if !team_dir.exists() {
+                created_dirs.push(team_dir);
+            }
+        }
+
+        let mut paths = vec![gmap_path(bundle, tenant, team), store_path.to_path_buf()];
+        let mut manifests = vec![format!("{tenant}.yaml")];
+        if let Some(team) = team {
+            manifests.push(format!("{tenant}.{team}.yaml"));
+        }
+        for name in &manifests {
+            paths.push(bundle.join("resolved").join(name));
+            paths.push(bundle.join("state").join("resolved").join(name));
+        }
+        let files = paths
+            .into_iter()
+            .map(|path| {
+                let contents = match std::fs::read(&path) {
+                    Ok(bytes) => Some(bytes),
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+                    Err(err) => {
+                        return Err(anyhow::Error::new(err)
+                            .context(format!("failed to read {}", path.display())));
+                    }
+                };
+                Ok((path, contents))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self {
+            files,
+            created_dirs,
+        })
+    }
+
+    fn restore(&self) -> anyhow::Result<()> {
+        for (path, contents) in &self.files {
+            match contents {
+                Some(bytes) => atomic_write(path, bytes)?,
+                None if path.exists() => std::fs::remove_file(path)
+                    .with_context(|| format!("failed to remove {}", path.display()))?,
+                None => {}
+            }
+        }
+        for dir in &self.created_dirs {
+            if dir.exists() {
+                std::fs::remove_dir_all(dir)
+                    .with_context(|| format!("failed to remove {}", dir.display()))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::ProviderIdSource;
+
+    fn provider(id: &str) -> DetectedProvider {
+        DetectedProvider {
+            provider_id: id.to_string(),
+            domain: "messaging".to_string(),
+            pack_path: PathBuf::from(format!("providers/messaging/{id}.gtpack")),
+            id_source: ProviderIdSource::Manifest,
+        }
+    }
+
+    #[test]
+    fn packs_match_by_id_or_suffix() {
+        let providers = [
+            provider("messaging-telegram"),
+            provider("messaging-slack"),
+            provider("events-slack"),
+        ];
+        assert_eq!(
+            match_pack(&providers, "telegram").unwrap().provider_id,
+            "messaging-telegram"
+        );
+        assert_eq!(
+            match_pack(&providers, "events-slack").unwrap().provider_id,
+            "events-slack"
+        );
+        let err = match_pack(&providers, "slack").unwrap_err().to_string();
+        assert!(err.contains("several"), "{err}");
+        assert!(match_pack(&providers, "teams").is_err());
+    }
+
+    #[test]
+    fn snapshot_restores_files_and_removes_created_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path();
+        let store = bundle.join(".greentic/dev/.dev.secrets.env");
+        std::fs::create_dir_all(store.parent().unwrap()).unwrap();
+        std::fs::write(&store, "before").unwrap();
+
+        let snapshot = Snapshot::take(bundle, "acme", Some("sales"), &store).unwrap();
+        project::add_tenant(bundle, "acme").unwrap();
+        project::add_team(bundle, "acme", "sales").unwrap();
+        std::fs::write(&store, "after").unwrap();
+        let manifest = bundle.join("resolved/acme.sales.yaml");
+        std::fs::create_dir_all(manifest.parent().unwrap()).unwrap();
+        std::fs::write(&manifest, "tenant: acme").unwrap();
+
+        snapshot.restore().unwrap();
+        assert!(!bundle.join("tenants/acme").exists());
+        assert!(!manifest.exists());
+        assert_eq!(std::fs::read_to_string(&store).unwrap(), "before");
+    }
+}
@@ -0,0 +1,991 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use greentic_types::{ChannelMessageEnvelope, Destination, EnvId, TeamId, TenantCtx, TenantId};
+use serde::Serialize;
+use serde_json::{Map as JsonMap, Value as JsonValue, json};
+use uuid::Uuid;
+
+use crate::config::DemoHttpConfig;
+use crate::config_gate::{self, ConfigGateItem, ConfigValueSource};
+use crate::demo::card::{CardView, detect_adaptive_card_view};
+use crate::demo::runner_host::{
+    DemoRunnerHost, FlowOutcome, OperatorContext, primary_provider_type,
+};
+use crate::destinations::{self, DestinationBook};
+use crate::discovery::{self, discovery_map, provider_id_for_pack};
+use crate::domain_run::resolve_demo_provider_pack;
+use crate::domains::{self, Domain};
+use crate::messaging_universal::{
+    attachments,
+    dto::{EncodeInV1, EncodeOutV1, ProviderPayloadV1, RenderPlanOutV1},
+    egress,
+    http_transport::{self, HttpTransport},
+};
+use crate::operator_error::OperatorError;
+use crate::operator_i18n;
+use crate::operator_log;
+use crate::redaction;
+use crate::secrets_gate::{self, DynSecretsManager};
+use crate::secrets_manager;
+
+/// Sends one message through a messaging provider pack (`demo send`).
+#[derive(Clone, Debug)]
+pub struct SendMessage {
+    pub bundle: PathBuf,
+    pub provider: String,
+    pub tenant: String,
+    pub team: Option<String>,
+    pub text: Option<String>,
+    /// Copied into the envelope metadata.
+    pub args: JsonMap<String, JsonValue>,
+    /// Destination ids or `@name` entries from destinations.yaml, each optionally
+    /// prefixed with its kind (`channel:C123`). [`send_message`] puts them all on
+    /// one envelope; [`send_fanout`] sends to each separately.
+    pub to: Vec<String>,
+    pub to_kind: Option<String>,
+    pub card: Option<JsonValue>,
+    pub attachments: Vec<attachments::LocalAttachment>,
+    pub runner_binary: Option<PathBuf>,
+    pub env: String,
+    /// `None` uses the bundle's `http:` settings.
+    pub http: Option<DemoHttpConfig>,
+}
+
+impl SendMessage {
+    pub fn new(bundle: impl Into<PathBuf>, provider: impl Into<String>) -> Self {
+        Self {
+            bundle: bundle.into(),
+            provider: provider.into(),
+            tenant: "demo".to_string(),
+            team: Some("default".to_string()),
+            text: None,
+            args: JsonMap::new(),
+            to: Vec::new(),
+            to_kind: None,
+            card: None,
+            attachments: Vec::new(),
+            runner_binary: None,
+            env: "demo".to_string(),
+            http: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SentMessage {
+    pub provider_id: String,
+    pub provider_type: String,
+    pub pack_id: String,
+    pub pack_path: PathBuf,
+    /// Whether the provider reported the send as successful.
+    pub success: bool,
+    pub error: Option<String>,
+    /// `send_payload` output; secret errors carry the lookup context.
+    pub output: Option<JsonValue>,
+    pub raw: Option<String>,
+    /// Secrets the provider needs but the store does not have.
+    pub missing_secret_uris: Vec<String>,
+    pub secrets_store: Option<PathBuf>,
+    pub using_env_fallback: bool,
+    /// Adaptive cards seen in the provider op outputs.
+    #[serde(skip)]
+    pub card_views: Vec<CardView>,
+}
+
+/// How [`send_fanout`] walks the destinations.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Fanout {
+    /// One destination at a time, in `to` order.
+    #[default]
+    Serial,
+    /// All destinations at once.
+    Parallel,
+}
+
+/// One destination's result in a fan-out send.
+#[derive(Debug)]
+pub struct FanoutDelivery {
+    /// The `to` entry as given.
+    pub destination: String,
+    pub result: anyhow::Result<SentMessage>,
+}
+
+impl FanoutDelivery {
+    /// The provider accepted the send and no secrets were missing.
+    pub fn delivered(&self) -> bool {
+        matches!(&self.result, Ok(sent) if sent.success && sent.missing_secret_uris.is_empty())
+    }
+}
+
+/// Sends the same message to each entry of `request.to` as its own send, and
+/// returns one delivery per destination in `to` order. A serial fan-out stops at
+/// the first failed destination when `stop_on_failure` is set.
+pub fn send_fanout(
+    request: SendMessage,
+    fanout: Fanout,
+    stop_on_failure: bool,
+) -> Vec<FanoutDelivery> {
+    let request = &request;
+    let single = |destination: &String| {
+        let mut one = request.clone();
+        one.to = vec![destination.clone()];
+        FanoutDelivery {
+            destination: destination.clone(),
+            result: send_message(one),
+        }
+    };
+    match fanout {
+        Fanout::Serial => {
+            let mut deliveries = Vec::new();
+            for destination in &request.to {
+                let delivery = single(destination);
+                let failed = !delivery.delivered();
+                deliveries.push(delivery);
+                if failed && stop_on_failure {
+                    break;
+                }
+            }
+            deliveries
+        }
+        Fanout::Parallel => std::thread::scope(|scope| {
+            let handles = request
+                .to
+                .iter()
+                .map(|destination| scope.spawn(move || single(destination)))
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .zip(&request.to)
+                .map(|(handle, destination)| {
+                    handle.join().unwrap_or_else(|_| FanoutDelivery {
+                        destination: destination.clone(),
+                        result: Err(anyhow::anyhow!("send to {destination} panicked")),
+                    })
+                })
+                .collect()
+        }),
+    }
+}
+
+/// Everything a send needs before the provider ops run.
+struct PreparedSend {
+    bundle: PathBuf,
+    tenant: String,
+    team: Option<String>,
+    env: String,
+    pack: domains::ProviderPack,
+    provider_type: String,
+    provider_id: String,
+    runner_host: DemoRunnerHost,
+    secrets_handle: secrets_gate::SecretsManagerHandle,
+    context: OperatorContext,
+    message: JsonValue,
+    local_attachments: Vec<attachments::LocalAttachment>,
+    http: DemoHttpConfig,
+}
+
+fn prepare_send(request: SendMessage) -> anyhow::Result<PreparedSend> {
+    let SendMessage {
+        bundle,
+        provider,
+        tenant,
+        team,
+        text,
+        args,
+        to,
+        to_kind,
+        card,
+        attachments: local_attachments,
+        runner_binary,
+        env,
+        http,
+    } = request;
+    let team = team.filter(|value| !value.is_empty());
+    let text = text.or_else(|| card.as_ref().map(|_| "adaptive card".to_string()));
+    if text.is_none() && local_attachments.is_empty() {
+        return Err(anyhow::anyhow!(
+            "a message needs text, a card or an attachment"
+        ));
+    }
+
+    domains::ensure_cbor_packs(&bundle)?;
+    let pack = resolve_demo_provider_pack(
+        &bundle,
+        &tenant,
+        team.as_deref(),
+        &provider,
+        Domain::Messaging,
+    )?;
+    let provider_type = primary_provider_type(&pack.path)
+        .context("failed to determine provider type for demo send")?;
+    let discovery =
+        discovery::discover_with_options(&bundle, discovery::DiscoveryOptions { cbor_only: true })?;
+    let provider_map = discovery_map(&discovery.providers);
+    let provider_id = provider_id_for_pack(&pack.path, &pack.pack_id, Some(&provider_map));
+
+    let secrets_handle = secrets_gate::resolve_secrets_manager(&bundle, &tenant, team.as_deref())?;
+    let runner_host = DemoRunnerHost::new(
+        bundle.clone(),
+        &discovery,
+        runner_binary,
+        secrets_handle.clone(),
+        false,
+    )?;
+    let context = OperatorContext {
+        tenant: tenant.clone(),
+        team: team.clone(),
+        correlation_id: None,
+    };
+
+    let destinations =
+        DestinationBook::load(&bundle)?.resolve_all(&to, &provider, to_kind.as_deref())?;
+    log_send_config_gate(
+        &tenant,
+        team.as_deref(),
+        &env,
+        text.as_deref(),
+        &args,
+        &local_attachments,
+        &to,
+        to_kind.as_deref(),
+    );
+    let channel = provider_channel(&provider);
+    let mut message = build_demo_send_message(DemoSendMessageArgs {
+        text: text.as_deref(),
+        args: &args,
+        tenant: &tenant,
+        team: team.as_deref(),
+        destinations: &destinations,
+        provider_id: &provider,
+        channel: &channel,
+        card: card.as_ref(),
+    });
+    attachments::set_message_attachments(
+        &mut message,
+        local_attachments
+            .iter()
+            .map(attachments::LocalAttachment::descriptor)
+            .collect(),
+    );
+    log_debug_dump("initial message envelope", &message);
+    let http = http.unwrap_or_else(|| http_transport::bundle_http_config(&bundle));
+    Ok(PreparedSend {
+        bundle,
+        tenant,
+        team,
+        env,
+        pack,
+        provider_type,
+        provider_id,
+        runner_host,
+        secrets_handle,
+        context,
+        message,
+        local_attachments,
+        http,
+    })
+}
+
+/// A provider's `render_plan` for a message, without encoding or sending it.
+#[derive(Clone, Debug)]
+pub struct PlannedMessage {
+    pub provider_id: String,
+    pub provider_type: String,
+    /// The raw `render_plan` output.
+    pub output: JsonValue,
+    pub plan: RenderPlanOutV1,
+}
+
+/// Runs only the provider's `render_plan` op for `request`.
+pub fn plan_message(request: SendMessage) -> anyhow::Result<PlannedMessage> {
+    let prepared = prepare_send(request)?;
+    let input = egress::build_render_plan_input(prepared.message.clone());
+    let output = run_provider_component_op(
+        &prepared.runner_host,
+        &prepared.pack,
+        &prepared.provider_id,
+        &prepared.context,
+        "render_plan",
+        serde_json::to_value(&input)?,
+    )
+    .context("render_plan failed")?
+    .output
+    .unwrap_or_else(|| json!({}));
+    let plan = serde_json::from_value(output.clone()).context("render_plan output invalid")?;
+    Ok(PlannedMessage {
+        provider_id: prepared.provider_id,
+        provider_type: prepared.provider_type,
+        output,
+        plan,
+    })
+}
+
+/// What `demo send --dry-run` shows: the encoded payload and the request it stands
+/// for, without `send_payload` running.
+#[derive(Clone, Debug, Serialize)]
+pub struct PreviewedMessage {
+    pub provider_id: String,
+    pub provider_type: String,
+    pub payload: ProviderPayloadV1,
+    pub request: HttpRequestPreview,
+    #[serde(skip)]
+    pub card_views: Vec<CardView>,
+}
+
+/// The HTTP call an encoded payload describes. Providers that declare `method`,
+/// `url` and `headers` in the payload metadata get them shown; the body is the
+/// decoded `body_b64`.
+#[derive(Clone, Debug, Serialize)]
+pub struct HttpRequestPreview {
+    pub method: String,
+    /// `None` when the provider leaves the URL to `send_payload`.
+    pub url: Option<String>,
+    pub headers: BTreeMap<String, String>,
+    /// Parsed JSON when the body is JSON, otherwise text.
+    pub body: JsonValue,
+}
+
+impl HttpRequestPreview {
+    pub fn from_payload(payload: &ProviderPayloadV1) -> Self {
+        let request = http_transport::HttpRequest::from_payload(payload);
+        Self {
+            body: http_transport::body_value(&request.body),
+            method: request.method,
+            url: request.url,
+            headers: request.headers,
+        }
+    }
+}
+
+/// Runs `render_plan` and `encode` for `request` and stops before `send_payload`.
+pub fn preview_message(request: SendMessage) -> anyhow::Result<PreviewedMessage> {
+    let PreparedSend {
+        bundle,
+        tenant,
+        pack,
+        provider_type,
+        provider_id,
+        runner_host,
+        context,
+        message,
+        local_attachments,
+        ..
+    } = prepare_send(request)?;
+    let mut card_views = Vec::new();
+    let payload = render_and_encode_batched(
+        &runner_host,
+        &pack,
+        &provider_id,
+        &context,
+        message,
+        &local_attachments,
+        &attachments::staging_dir(&bundle, &tenant),
+        &mut card_views,
+    )?;
+    Ok(PreviewedMessage {
+        request: HttpRequestPreview::from_payload(&payload),
+        provider_id,
+        provider_type,
+        payload,
+        card_views,
+    })
+}
+
+pub fn send_message(request: SendMessage) -> anyhow::Result<SentMessage> {
+    let PreparedSend {
+        bundle,
+        tenant,
+        team,
+        env,
+        pack,
+        provider_type,
+        provider_id,
+        runner_host,
+        secrets_handle,
+        context,
+        message,
+        local_attachments,
+        http,
+    } = prepare_send(request)?;
+    let team = team.as_deref();
+    let breaker = runner_host.circuit_breaker();
+    if let Err(open) = breaker.admit(&tenant, team, &provider_id, chrono::Utc::now()) {
+        return Err(anyhow::Error::new(OperatorError::CircuitOpen {
+            provider: provider_id,
+            until: open.until.to_rfc3339(),
+        })
+        .context(open.to_string()));
+    }
+    let mut transport = HttpTransport::new(&http)?;
+
+    let mut card_views = Vec::new();
+    let payload = render_and_encode_batched(
+        &runner_host,
+        &pack,
+        &provider_id,
+        &context,
+        message,
+        &local_attachments,
+        &attachments::staging_dir(&bundle, &tenant),
+        &mut card_views,
+    )?;
+    if transport.operator_managed() {
+        let request = http_transport::HttpRequest::from_payload(&payload);
+        if request.url.is_some() || transport.replaying() {
+            let response = transport.execute(&request);
+            // Replayed answers say nothing about the provider's health.
+            if !transport.replaying() {
+                let error = match &response {
+                    Ok(response) if response.success() => None,
+                    Ok(response) => Some(format!("HTTP {}", response.status)),
+                    Err(err) => Some(format!("{err:#}")),
+                };
+                breaker.record(
+                    &tenant,
+                    team,
+                    &provider_id,
+                    error.as_deref(),
+                    chrono::Utc::now(),
+                );
+            }
+            let response =
+                response.with_context(|| format!("{provider_id}: operator HTTP send failed"))?;
+            return Ok(SentMessage {
+                provider_id,
+                provider_type,
+                pack_id: pack.pack_id,
+                pack_path: pack.path,
+                success: response.success(),
+                error: (!response.success()).then(|| format!("HTTP {}", response.status)),
+                output: Some(response.to_output()),
+                raw: None,
+                missing_secret_uris: Vec::new(),
+                secrets_store: secrets_handle.dev_store_path.clone(),
+                using_env_fallback: secrets_handle.using_env_fallback,
+                card_views,
+            });
+        }
+        operator_log::warn(
+            module_path!(),
+            format!("{provider_id} declares no request URL; sending through send_payload"),
+        );
+    }
+    let send_input = egress::build_send_payload(
+        payload,
+        provider_type.clone(),
+        tenant.clone(),
+        team.map(|value| value.to_string()),
+    );
+    let send_outcome = run_provider_component_op(
+        &runner_host,
+        &pack,
+        &provider_id,
+        &context,
+        "send_payload",
+        serde_json::to_value(&send_input)?,
+    )
+    .context("send_payload failed")?;
+    collect_card_view(&mut card_views, &send_outcome);
+
+    let mut missing_secret_uris = Vec::new();
+    let output = send_outcome.output.map(|value| {
+        if payload_contains_secret_error(&value) {
+            missing_secret_uris = gather_missing_secret_uris(
+                &secrets_handle.manager(),
+                &env,
+                &tenant,
+                team,
+                &pack.path,
+                &provider_id,
+                secrets_handle.dev_store_path.as_deref(),
+                secrets_handle.using_env_fallback,
+                Some(provider_type.as_str()),
+            );
+        }
+        enrich_secret_error_payload(
+            value,
+            &context,
+            &env,
+            &provider_id,
+            &pack.pack_id,
+            &pack.path,
+            &missing_secret_uris,
+            &secrets_handle.selection,
+            secrets_handle.dev_store_path.as_deref(),
+        )
+    });
+    Ok(SentMessage {
+        provider_id,
+        provider_type,
+        pack_id: pack.pack_id,
+        pack_path: pack.path,
+        success: send_outcome.success,
+        error: send_outcome.error,
+        output,
+        raw: send_outcome.raw,
+        missing_secret_uris,
+        secrets_store: secrets_handle.dev_store_path.clone(),
+        using_env_fallback: secrets_handle.using_env_fallback,
+        card_views,
+    })
+}
+
+/// [`render_and_encode`] with both ops run against one load of the provider pack.
+#[allow(clippy::too_many_arguments)]
+fn render_and_encode_batched(
+    runner_host: &DemoRunnerHost,
+    pack: &domains::ProviderPack,
+    provider_id: &str,
+    ctx: &OperatorContext,
+    message: JsonValue,
+    local_attachments: &[attachments::LocalAttachment],
+    staging_dir: &Path,
+    card_views: &mut Vec<CardView>,
+) -> anyhow::Result<ProviderPayloadV1> {
+    runner_host.invoke_provider_component_ops_batch(
+        Domain::Messaging,
+        pack,
+        provider_id,
+        ctx,
+        |invoke| {
+            render_and_encode(
+                message,
+                local_attachments,
+                staging_dir,
+                &mut |op: &str, payload: JsonValue| -> anyhow::Result<FlowOutcome> {
+                    let bytes = serde_json::to_vec(&payload)?;
+                    let outcome = invoke(op, &bytes)?;
+                    ensure_provider_op_success(provider_id, op, &outcome)?;
+                    collect_card_view(card_views, &outcome);
+                    Ok(outcome)
+                },
+            )
+        },
+    )
+}
+
+fn collect_card_view(card_views: &mut Vec<CardView>, outcome: &FlowOutcome) {
+    if let Some(value) = &outcome.output
+        && let Some(view) = detect_adaptive_card_view(value)
+    {
+        card_views.push(view);
+    }
+}
+
+/// `render_plan`, attachment delivery, and `encode`: everything before
+/// `send_payload`.
+fn render_and_encode(
+    mut message: JsonValue,
+    local_attachments: &[attachments::LocalAttachment],
+    staging_dir: &Path,
+    invoke: &mut dyn FnMut(&str, JsonValue) -> anyhow::Result<FlowOutcome>,
+) -> anyhow::Result<ProviderPayloadV1> {
+    // Compose a message plan and encode payload directly against the provider component (no flow resolution).
+    let render_plan_input = egress::build_render_plan_input(message.clone());
+    let plan_value = invoke("render_plan", serde_json::to_value(&render_plan_input)?)
+        .with_context(|| "render_plan failed")?
+        .output
+        .unwrap_or_else(|| json!({}));
+    let render_plan_out: RenderPlanOutV1 =
+        serde_json::from_value(plan_value.clone()).context("render_plan output invalid")?;
+    log_debug_dump("render_plan output", &render_plan_out);
+    if !render_plan_out.ok {
+        let err = render_plan_out
+            .error
+            .unwrap_or_else(|| "render_plan returned error".to_string());
+        return Err(anyhow::anyhow!(err));
+    }
+    if !local_attachments.is_empty() {
+        let caps = attachments::capabilities_from_plan(&render_plan_out);
+        let delivered = attachments::deliver_attachments(local_attachments, &caps, staging_dir)?;
+        attachments::set_message_attachments(&mut message, delivered);
+    }
+    let encode_input = egress::build_encode_input(message, plan_value);
+    log_debug_dump("encode input", &encode_input);
+    let payload_value = invoke("encode", serde_json::to_value(&encode_input)?)
+        .with_context(|| "encode failed")?
+        .output
+        .unwrap_or_else(|| json!({}));
+    let encode_out: EncodeOutV1 =
+        serde_json::from_value(payload_value).context("encode output invalid")?;
+    log_debug_dump("encode output", &encode_out);
+    if !encode_out.ok {
+        let err = encode_out
+            .error
+            .unwrap_or_else(|| "encode returned error".to_string());
+        return Err(anyhow::anyhow!(err));
+    }
+    encode_out
+        .payload
+        .ok_or_else(|| anyhow::anyhow!("encode output missing payload"))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn log_send_config_gate(
+    tenant: &str,
+    team: Option<&str>,
+    env: &str,
+    text: Option<&str>,
+    args: &JsonMap<String, JsonValue>,
+    local_attachments: &[attachments::LocalAttachment],
+    to: &[String],
+    to_kind: Option<&str>,
+) {
+    let mut config_items = vec![
+        ConfigGateItem::new(
+            "env",
+            Some(env.to_string()),
+            ConfigValueSource::Platform("GREENTIC_ENV"),
+            true,
+        ),
+        ConfigGateItem::new(
+            "tenant",
+            Some(tenant.to_string()),
+            ConfigValueSource::Platform("tenant"),
+            true,
+        ),
+        ConfigGateItem::new(
+            "team",
+            Some(team.unwrap_or("default").to_string()),
+            ConfigValueSource::Platform("team"),
+            true,
+        ),
+    ];
+    if let Some(text) = text {
+        config_items.push(ConfigGateItem::new(
+            "text",
+            Some(text.to_string()),
+            ConfigValueSource::Argument("--text"),
+            true,
+        ));
+    }
+    let mut arg_entries = args.iter().collect::<Vec<_>>();
+    arg_entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    for (key, value) in arg_entries {
+        config_items.push(ConfigGateItem::new(
+            key.as_str(),
+            Some(config_value_display(value)),
+            ConfigValueSource::Argument("--arg"),
+            true,
+        ));
+    }
+    for attachment in local_attachments {
+        config_items.push(ConfigGateItem::new(
+            "attach",
+            Some(format!(
+                "{} ({}, {} bytes)",
+                attachment.name,
+                attachment.mime_type,
+                attachment.size_bytes()
+            )),
+            ConfigValueSource::Argument("--attach"),
+            false,
+        ));
+    }
+    if !to.is_empty() {
+        config_items.push(ConfigGateItem::new(
+            "to",
+            Some(to.join(",")),
+            ConfigValueSource::Argument("--to"),
+            true,
+        ));
+    }
+    if let Some(kind) = to_kind {
+        config_items.push(ConfigGateItem::new(
+            "to-kind",
+            Some(kind.to_string()),
+            ConfigValueSource::Argument("--to-kind"),
+            false,
+        ));
+    }
+    config_gate::log_config_gate(Domain::Messaging, tenant, team, env, &config_items);
+}
+
+fn run_provider_component_op(
+    runner_host: &DemoRunnerHost,
+    pack: &domains::ProviderPack,
+    provider_id: &str,
+    ctx: &OperatorContext,
+    op: &str,
+    payload: JsonValue,
+) -> anyhow::Result<FlowOutcome> {
+    let bytes = serde_json::to_vec(&payload)?;
+    let outcome = runner_host.invoke_provider_component_op_direct(
+        Domain::Messaging,
+        pack,
+        provider_id,
+        op,
+        &bytes,
+        ctx,
+    )?;
+    ensure_provider_op_success(provider_id, op, &outcome)?;
+    Ok(outcome)
+}
+
+fn ensure_provider_op_success(
+    provider_id: &str,
+    op: &str,
+    outcome: &FlowOutcome,
+) -> anyhow::Result<()> {
+    if outcome.success {
+        return Ok(());
+    }
+    let detail = outcome
+        .error
+        .clone()
+        .or_else(|| outcome.raw.clone())
+        .unwrap_or_else(|| "unknown error".to_string());
+    Err(OperatorError::FlowFailed {
+        provider: provider_id.to_string(),
+        op: op.to_string(),
+        detail,
+    }
+    .into())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn enrich_secret_error_payload(
+    mut payload: JsonValue,
+    ctx: &OperatorContext,
+    env: &str,
+    provider_id: &str,
+    pack_id: &str,
+    pack_path: &Path,
+    missing_uris: &[String],
+    selection: &secrets_manager::SecretsManagerSelection,
+    dev_store_path: Option<&Path>,
+) -> JsonValue {
+    let team = secrets_manager::canonical_team(ctx.team.as_deref()).to_string();
+    let selection_desc = selection.description();
+    let dev_store_desc = dev_store_path
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|| "<default>".to_string());
+    let context_suffix = format!(
+        "env={} tenant={} team={} provider={} pack_id={} pack_path={} secrets_manager={} dev_store={}",
+        env,
+        ctx.tenant,
+        team,
+        provider_id,
+        pack_id,
+        pack_path.display(),
+        selection_desc,
+        dev_store_desc
+    );
+    if let JsonValue::Object(map) = &mut payload {
+        for key in ["message", "error"] {
+            if let Some(entry) = map.get_mut(key)
+                && let Some(text) = entry.as_str()
+                && text_contains_secret_error(text)
+            {
+                let suffix = secret_error_suffix(&context_suffix, missing_uris);
+                let enriched = format!("{text} ({suffix})");
+                *entry = JsonValue::String(enriched);
+            }
+        }
+    }
+    payload
+}
+
+fn payload_contains_secret_error(value: &JsonValue) -> bool {
+    for key in ["message", "error"] {
+        if let Some(text) = value.get(key).and_then(JsonValue::as_str)
+            && text_contains_secret_error(text)
+        {
+            return true;
+        }
+    }
+    false
+}
+
+fn text_contains_secret_error(text: &str) -> bool {
+    let lower = text.to_lowercase();
+    lower.contains("secret store error") || text.contains("SecretsError")
+}
+
+fn secret_error_suffix(context_suffix: &str, missing_uris: &[String]) -> String {
+    if missing_uris.is_empty() {
+        context_suffix.to_string()
+    } else {
+        let missing = missing_uris.join(", ");
+        format!("{context_suffix}; missing secrets: {missing}")
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn gather_missing_secret_uris(
+    manager: &DynSecretsManager,
+    env: &str,
+    tenant: &str,
+    team: Option<&str>,
+    pack_path: &Path,
+    provider_id: &str,
+    store_path: Option<&Path>,
+    using_env_fallback: bool,
+    provider_type: Option<&str>,
+) -> Vec<String> {
+    match secrets_gate::check_provider_secrets(
+        manager,
+        env,
+        tenant,
+        team,
+        pack_path,
+        provider_id,
+        provider_type,
+        store_path,
+        using_env_fallback,
+    ) {
+        Ok(Some(missing)) => missing,
+        Ok(None) => Vec::new(),
+        Err(err) => {
+            operator_log::warn(
+                module_path!(),
+                format!(
+                    "failed to check missing secrets for provider {}: {}",
+                    provider_id, err
+                ),
+            );
+            Vec::new()
+        }
+    }
+}
+
+struct DemoSendMessageArgs<'a> {
+    text: Option<&'a str>,
+    args: &'a JsonMap<String, JsonValue>,
+    tenant: &'a str,
+    team: Option<&'a str>,
+    destinations: &'a [destinations::ResolvedDestination],
+    provider_id: &'a str,
+    channel: &'a str,
+    card: Option<&'a JsonValue>,
+}
+
+fn build_demo_send_message(args: DemoSendMessageArgs<'_>) -> JsonValue {
+    let mut metadata = BTreeMap::new();
+    if let Some(card_value) = args.card
+        && let Ok(card_str) = serde_json::to_string(card_value)
+    {
+        metadata.insert("adaptive_card".to_string(), card_str);
+    }
+    for (key, value) in args.args {
+        metadata.insert(key.clone(), value.to_string());
+    }
+    let env_value = std::env::var("GREENTIC_ENV").unwrap_or_else(|_| "local".to_string());
+    let env = EnvId::try_from(env_value.clone())
+        .unwrap_or_else(|_| EnvId::try_from("local").expect("local env invalid"));
+    let tenant_id = TenantId::try_from(args.tenant.to_string())
+        .unwrap_or_else(|_| TenantId::try_from("demo").expect("demo tenant invalid"));
+    let mut tenant_ctx = TenantCtx::new(env, tenant_id.clone());
+    if let Some(team_value) = args.team
+        && let Ok(team_id) = TeamId::try_from(team_value.to_string())
+    {
+        tenant_ctx = tenant_ctx.with_team(Some(team_id));
+    }
+    tenant_ctx = tenant_ctx
+        .with_session(Uuid::new_v4().to_string())
+        .with_flow(Uuid::new_v4().to_string())
+        .with_node("demo".to_string())
+        .with_provider(args.provider_id.to_string())
+        .with_attempt(1);
+
+    let to = args
+        .destinations
+        .iter()
+        .map(|value| Destination {
+            id: value.id.clone(),
+            kind: value.kind.clone(),
+        })
+        .collect::<Vec<_>>();
+    let envelope = ChannelMessageEnvelope {
+        id: Uuid::new_v4().to_string(),
+        tenant: tenant_ctx,
+        channel: args.channel.to_string(),
+        session_id: Uuid::new_v4().to_string(),
+        reply_scope: None,
+        from: None,
+        to,
+        correlation_id: None,
+        text: args.text.map(|value| value.to_string()),
+        attachments: Vec::new(),
+        metadata,
+    };
+    serde_json::to_value(envelope).unwrap_or(JsonValue::Null)
+}
+
+fn provider_channel(provider: &str) -> String {
+    if let Some((domain, suffix)) = provider.split_once('-') {
+        format!("{domain}.{suffix}")
+    } else {
+        provider.replace('-', ".")
+    }
+}
+
+fn config_value_display(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(text) => text.clone(),
+        JsonValue::Number(number) => number.to_string(),
+        JsonValue::Bool(flag) => flag.to_string(),
+        JsonValue::Null => "<null>".to_string(),
+        other => serde_json::to_string(other).unwrap_or_else(|_| other.to_string()),
+    }
+}
+
+/// Writes a redacted dump of one stage of the send to `operator.log` at debug
+/// level. Serializing is skipped when nothing would be written.
+fn log_debug_dump(stage: &str, value: &impl Serialize) {
+    if !operator_log::enabled(operator_log::Level::Debug) {
+        return;
+    }
+    let message = match redaction::to_string_pretty(value) {
+        Ok(body) => format!("{stage}:\n{body}"),
+        Err(err) => format!("{stage}: failed to serialize: {err}"),
+    };
+    operator_log::debug(module_path!(), message);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{Engine as _, engine::general_purpose};
+
+    #[test]
+    fn request_preview_reads_declared_endpoint_and_decodes_body() {
+        let payload = ProviderPayloadV1 {
+            content_type: "application/json".to_string(),
+            body_b64: general_purpose::STANDARD.encode(br#"{"chat_id":42,"text":"hi"}"#),
+            metadata_json: None,
+            metadata: Some(json!({
+                "url": "https://api.telegram.org/bot123:abc/sendMessage",
+                "headers": [["Authorization", "Bearer abc"]]
+            })),
+        };
+        let preview = HttpRequestPreview::from_payload(&payload);
+        assert_eq!(preview.method, "POST");
+        assert_eq!(
+            preview.url.as_deref(),
+            Some("https://api.telegram.org/bot123:abc/sendMessage")
+        );
+        assert_eq!(preview.headers["Content-Type"], "application/json");
+        assert_eq!(preview.headers["Authorization"], "Bearer abc");
+        assert_eq!(preview.body, json!({"chat_id": 42, "text": "hi"}));
+    }
+
+    #[test]
+    fn provider_channel_splits_domain_prefix() {
+        assert_eq!(provider_channel("messaging-telegram"), "messaging.telegram");
+        assert_eq!(provider_channel("webchat"), "webchat");
+    }
+
+    #[test]
+    fn secret_errors_get_lookup_context() {
+        let payload = json!({ "ok": false, "error": "secret store error: not found" });
+        assert!(payload_contains_secret_error(&payload));
+        assert_eq!(
+            secret_error_suffix("env=demo", &["secrets://demo/x".to_string()]),
+            "env=demo; missing secrets: secrets://demo/x"
+        );
+        assert!(!payload_contains_secret_error(&json!({ "ok": true })));
+    }
+}
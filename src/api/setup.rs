@@ -0,0 +1,259 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use serde_json::json;
+
+use crate::audit::{self, AuditEvent};
+use crate::cloudflared;
+use crate::config;
+use crate::demo::runner_host::{DemoRunnerHost, OperatorContext};
+use crate::demo::webhook_adopt::{self, AdoptOutcome};
+use crate::demo::webhook_watcher;
+use crate::discovery::{self, DiscoveryResult};
+use crate::domain_run::{DomainRunArgs, DomainRunReport, run_domain_command};
+use crate::domains::{self, Domain, DomainAction};
+use crate::operator_auth::{self, OperatorAction};
+use crate::runtime_state::RuntimePaths;
+use crate::secrets_gate;
+use crate::subscriptions_universal::{SubscriptionStore, state_root};
+
+/// Runs provider setup flows for a demo bundle (`demo setup`).
+#[derive(Clone, Debug)]
+pub struct RunSetup {
+    pub bundle: PathBuf,
+    pub tenant: String,
+    pub team: Option<String>,
+    /// Domains to set up; empty means every discovered domain plus secrets.
+    pub domains: Vec<Domain>,
+    pub provider: Option<String>,
+    /// Return each domain's plan in [`SetupReport::runs`] instead of running it.
+    pub dry_run: bool,
+    pub parallel: usize,
+    pub allow_missing_setup: bool,
+    pub allow_contract_change: bool,
+    pub backup: bool,
+    pub online: bool,
+    /// Environment for greentic-secrets init; `None` skips the init.
+    pub secrets_env: Option<String>,
+    pub state_dir: Option<PathBuf>,
+    pub runner_binary: Option<PathBuf>,
+    pub setup_input: Option<PathBuf>,
+    /// Keep going after a failed flow or domain instead of returning the first error.
+    pub best_effort: bool,
+    /// Import webhook registrations providers already hold (see
+    /// [`webhook_adopt`]) and skip setup for the providers that had some.
+    pub adopt: bool,
+}
+
+impl RunSetup {
+    pub fn new(bundle: impl Into<PathBuf>, tenant: impl Into<String>) -> Self {
+        Self {
+            bundle: bundle.into(),
+            tenant: tenant.into(),
+            team: None,
+            domains: Vec::new(),
+            provider: None,
+            dry_run: false,
+            parallel: 1,
+            allow_missing_setup: false,
+            allow_contract_change: false,
+            backup: false,
+            online: false,
+            secrets_env: None,
+            state_dir: None,
+            runner_binary: None,
+            setup_input: None,
+            best_effort: false,
+            adopt: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SetupReport {
+    /// Domains whose setup plan ran (or was planned under `dry_run`), in order.
+    pub domains: Vec<Domain>,
+    /// What each domain's run skipped, and its plan under `dry_run`.
+    pub runs: Vec<DomainRunReport>,
+    /// Domains that failed under `best_effort`, with the error.
+    pub failed: Vec<(Domain, String)>,
+    /// Per-provider results of `adopt`.
+    pub adopted: Vec<AdoptOutcome>,
+}
+
+pub fn run_setup(request: RunSetup) -> anyhow::Result<SetupReport> {
+    if !request.dry_run {
+        operator_auth::authorize(&request.bundle, OperatorAction::Setup, &request.tenant)?;
+    }
+    domains::ensure_cbor_packs(&request.bundle)?;
+    let discovery = discovery::discover_with_options(
+        &request.bundle,
+        discovery::DiscoveryOptions { cbor_only: true },
+    )?;
+    discovery::persist(&request.bundle, &request.tenant, &discovery)?;
+    let selected = domains::order_domains_for_setup(
+        &request.bundle,
+        if request.domains.is_empty() {
+            discovered_domains(&discovery)
+        } else {
+            request.domains.clone()
+        },
+        &config::demo_provider_dependencies(&request.bundle)?,
+    )?;
+    let adopted = if request.adopt && !request.dry_run {
+        adopt_registrations(&request, &discovery, &selected)?
+    } else {
+        Vec::new()
+    };
+    let adopted_providers: BTreeSet<String> = adopted
+        .iter()
+        .filter(|outcome| !outcome.adopted.is_empty())
+        .map(|outcome| outcome.provider.clone())
+        .collect();
+    let mut runs = Vec::new();
+    let mut failed = Vec::new();
+    for domain in &selected {
+        let domain = *domain;
+        let discovered_providers = match domain {
+            Domain::Messaging | Domain::Events | Domain::Custom(_) => Some(
+                discovery
+                    .providers
+                    .iter()
+                    .filter(|provider| provider.domain == domains::domain_name(domain))
+                    .cloned()
+                    .collect(),
+            ),
+            Domain::Secrets => None,
+        };
+        let result = run_domain_command(DomainRunArgs {
+            root: request.bundle.clone(),
+            state_root: request.state_dir.clone(),
+            domain,
+            action: DomainAction::Setup,
+            tenant: request.tenant.clone(),
+            team: request.team.clone(),
+            provider_filter: request.provider.clone(),
+            dry_run: request.dry_run,
+            parallel: request.parallel,
+            allow_missing_setup: request.allow_missing_setup,
+            allow_contract_change: request.allow_contract_change,
+            backup: request.backup,
+            online: request.online,
+            secrets_env: request.secrets_env.clone(),
+            runner_binary: request.runner_binary.clone(),
+            best_effort: request.best_effort,
+            setup_input: request.setup_input.clone(),
+            allowed_providers: None,
+            preloaded_setup_answers: None,
+            public_base_url: None,
+            secrets_manager: None,
+            discovered_providers,
+            adopted_providers: adopted_providers.clone(),
+        });
+        if !request.dry_run {
+            audit::record(
+                &request.bundle,
+                AuditEvent::new("setup.run", domains::domain_name(domain))
+                    .scope(&request.tenant, request.team.as_deref())
+                    .detail(json!({ "provider": request.provider }))
+                    .result(&result),
+            );
+        }
+        match result {
+            Ok(run) => runs.push(run),
+            Err(err) if request.best_effort => failed.push((domain, format!("{err:#}"))),
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(SetupReport {
+        domains: selected,
+        runs,
+        failed,
+        adopted,
+    })
+}
+
+fn adopt_registrations(
+    request: &RunSetup,
+    discovery: &DiscoveryResult,
+    selected: &[Domain],
+) -> anyhow::Result<Vec<AdoptOutcome>> {
+    let providers: Vec<_> = webhook_watcher::webhook_providers(discovery, selected)
+        .into_iter()
+        .filter(|target| {
+            request
+                .provider
+                .as_deref()
+                .is_none_or(|filter| target.provider.contains(filter))
+        })
+        .collect();
+    if providers.is_empty() {
+        return Ok(Vec::new());
+    }
+    let team = request.team.as_deref().unwrap_or("default");
+    let paths = RuntimePaths::new(request.bundle.join("state"), &request.tenant, team);
+    let public_base_url = cloudflared::current_public_url(&paths)?;
+    let secrets_handle = secrets_gate::resolve_secrets_manager(
+        &request.bundle,
+        &request.tenant,
+        request.team.as_deref(),
+    )?;
+    let runner_host = DemoRunnerHost::new(
+        request.bundle.clone(),
+        discovery,
+        request.runner_binary.clone(),
+        secrets_handle,
+        false,
+    )?;
+    let ctx = OperatorContext {
+        tenant: request.tenant.clone(),
+        team: request.team.clone(),
+        correlation_id: None,
+    };
+    let outcomes = webhook_adopt::adopt(
+        &runner_host,
+        &SubscriptionStore::new(state_root(&request.bundle)),
+        &providers,
+        &ctx,
+        public_base_url.as_deref(),
+    );
+    for outcome in &outcomes {
+        let result = match &outcome.error {
+            Some(err) => Err(anyhow::anyhow!(err.clone())),
+            None => Ok(()),
+        };
+        audit::record(
+            &request.bundle,
+            AuditEvent::new("setup.adopt", &outcome.provider)
+                .scope(&request.tenant, request.team.as_deref())
+                .detail(json!({
+                    "op": outcome.op,
+                    "listed": outcome.listed,
+                    "adopted": outcome.adopted,
+                }))
+                .result(&result),
+        );
+    }
+    Ok(outcomes)
+}
+
+/// Messaging, events and custom domains when the bundle has providers for them,
+/// then secrets.
+pub(crate) fn discovered_domains(discovery: &DiscoveryResult) -> Vec<Domain> {
+    let mut enabled = Vec::new();
+    if discovery.domains.messaging {
+        enabled.push(Domain::Messaging);
+    }
+    if discovery.domains.events {
+        enabled.push(Domain::Events);
+    }
+    enabled.extend(
+        discovery
+            .domains
+            .custom
+            .iter()
+            .filter_map(|name| domains::parse_domain(name)),
+    );
+    enabled.push(Domain::Secrets);
+    enabled
+}
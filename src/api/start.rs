@@ -0,0 +1,213 @@
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Context;
+
+use crate::config::{self, DEMO_DEFAULT_TEAM, DemoConfig, load_demo_config_or_default};
+use crate::demo::http_ingress::{HttpIngressConfig, HttpIngressServer};
+use crate::demo::ingress_router::IngressRouter;
+use crate::demo::runner_host::DemoRunnerHost;
+use crate::demo::sidecars::Sidecars;
+use crate::demo::{self, NatsMode};
+use crate::discovery::{self, DiscoveryResult};
+use crate::domains::{self, Domain};
+use crate::instance;
+use crate::interpolate::SecretScope;
+use crate::runtime_state::RuntimePaths;
+use crate::secrets_gate::{self, SecretsManagerHandle};
+
+/// Starts a bundle's runtime services for one tenant/team (`demo start` without
+/// tunnels, provider setup, or the background schedulers).
+#[derive(Clone, Debug)]
+pub struct StartDemo {
+    pub bundle: PathBuf,
+    /// Treat `bundle` as read-only and keep everything the demo writes in this
+    /// directory instead; see [`crate::instance`].
+    pub instance: Option<PathBuf>,
+    pub tenant: String,
+    pub team: Option<String>,
+    pub nats: NatsMode,
+    pub nats_url: Option<String>,
+    /// Defaults to `<bundle>/logs`.
+    pub log_dir: Option<PathBuf>,
+    pub runner_binary: Option<PathBuf>,
+    /// Serve HTTP ingress on `services.gateway` from greentic.demo.yaml.
+    pub http_ingress: bool,
+    pub debug: bool,
+}
+
+impl StartDemo {
+    pub fn new(bundle: impl Into<PathBuf>, tenant: impl Into<String>) -> Self {
+        Self {
+            bundle: bundle.into(),
+            instance: None,
+            tenant: tenant.into(),
+            team: None,
+            nats: NatsMode::Off,
+            nats_url: None,
+            log_dir: None,
+            runner_binary: None,
+            http_ingress: true,
+            debug: false,
+        }
+    }
+}
+
+/// A started demo; its services keep running until [`RunningDemo::stop`].
+pub struct RunningDemo {
+    /// The instance directory when one was requested.
+    pub bundle: PathBuf,
+    pub tenant: String,
+    pub team: String,
+    /// Address of the HTTP ingress, when it was requested.
+    pub gateway: Option<SocketAddr>,
+    ingress: Option<HttpIngressServer>,
+    sidecars: Option<Sidecars>,
+}
+
+impl RunningDemo {
+    pub fn stop(mut self) -> anyhow::Result<()> {
+        if let Some(server) = self.ingress.take() {
+            server.stop()?;
+        }
+        if let Some(sidecars) = self.sidecars.take() {
+            sidecars.stop()?;
+        }
+        demo::demo_down_runtime(&self.bundle.join("state"), &self.tenant, &self.team, false)
+    }
+}
+
+pub fn start_demo(request: StartDemo) -> anyhow::Result<RunningDemo> {
+    let bundle = match &request.instance {
+        Some(dir) => instance::prepare(&request.bundle, dir)?,
+        None => request.bundle,
+    };
+    std::fs::create_dir_all(bundle.join("state"))?;
+    let log_dir = request.log_dir.unwrap_or_else(|| bundle.join("logs"));
+    std::fs::create_dir_all(&log_dir)?;
+    domains::ensure_cbor_packs(&bundle)?;
+    let discovery =
+        discovery::discover_with_options(&bundle, discovery::DiscoveryOptions { cbor_only: true })?;
+    discovery::persist(&bundle, &request.tenant, &discovery)?;
+    let demo_config = load_demo_config_or_default(&bundle.join("greentic.demo.yaml"));
+    let messaging_enabled = config::load_operator_config(&bundle)?
+        .and_then(|config| config.services)
+        .unwrap_or_default()
+        .messaging
+        .enabled
+        .is_enabled(discovery.domains.messaging);
+    let team = request
+        .team
+        .clone()
+        .unwrap_or_else(|| DEMO_DEFAULT_TEAM.to_string());
+    let sidecars = Sidecars::start(
+        &bundle,
+        RuntimePaths::new(bundle.join("state"), &request.tenant, &team),
+        &log_dir,
+        &demo_config.services.extra,
+    )?;
+    // Hold the sidecars in the handle first so a failed start tears them down too.
+    let mut running = RunningDemo {
+        bundle,
+        tenant: request.tenant,
+        team,
+        gateway: None,
+        ingress: None,
+        sidecars: Some(sidecars),
+    };
+    let started = demo::demo_up(
+        &running.bundle,
+        &running.tenant,
+        request.team.as_deref(),
+        request.nats_url.as_deref(),
+        request.nats,
+        &demo_config.services.nats.jetstream,
+        messaging_enabled,
+        None,
+        None,
+        &log_dir,
+        request.debug,
+    )
+    .and_then(|()| running.sidecars.as_ref().map_or(Ok(()), Sidecars::record));
+    if let Err(err) = started {
+        let _ = running.stop();
+        return Err(err);
+    }
+    if request.http_ingress {
+        let started = secrets_gate::resolve_secrets_manager(
+            &running.bundle,
+            &running.tenant,
+            Some(&running.team),
+        )
+        .and_then(|secrets_handle| {
+            start_ingress_server(
+                &running.bundle,
+                &running.tenant,
+                &running.team,
+                &discovery,
+                &demo_config,
+                &super::setup::discovered_domains(&discovery),
+                request.runner_binary,
+                request.debug,
+                secrets_handle,
+            )
+        });
+        match started {
+            Ok((server, addr)) => {
+                running.ingress = Some(server);
+                running.gateway = Some(addr);
+            }
+            Err(err) => {
+                // Don't leave the services behind when the caller never gets a handle.
+                let _ = running.stop();
+                return Err(err);
+            }
+        }
+    }
+    Ok(running)
+}
+
+/// HTTP ingress on `services.gateway`, routing to the providers of `domains`. Route
+/// tokens that are `${secret:...}` references are read in `tenant`/`team`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn start_ingress_server(
+    bundle: &Path,
+    tenant: &str,
+    team: &str,
+    discovery: &DiscoveryResult,
+    demo_config: &DemoConfig,
+    domains: &[Domain],
+    runner_binary: Option<PathBuf>,
+    debug_enabled: bool,
+    secrets_handle: SecretsManagerHandle,
+) -> anyhow::Result<(HttpIngressServer, SocketAddr)> {
+    let addr = format!(
+        "{}:{}",
+        demo_config.services.gateway.listen_addr, demo_config.services.gateway.port
+    );
+    let bind_addr: SocketAddr = addr
+        .parse()
+        .with_context(|| format!("invalid gateway listen address {addr}"))?;
+    let runner_host = Arc::new(DemoRunnerHost::new(
+        bundle.to_path_buf(),
+        discovery,
+        runner_binary,
+        secrets_handle,
+        debug_enabled,
+    )?);
+    let server = HttpIngressServer::start(HttpIngressConfig {
+        bind_addr,
+        domains: domains.to_vec(),
+        runner_host,
+        router: IngressRouter::new(
+            &demo_config.services.ingress,
+            Some(SecretScope {
+                bundle_root: bundle,
+                tenant,
+                team: Some(team),
+            }),
+        )?,
+    })?;
+    Ok((server, bind_addr))
+}
@@ -0,0 +1,171 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::audit::{self, AuditEvent};
+use crate::demo::runner_host::{DemoRunnerHost, OperatorContext};
+use crate::discovery::{self, discovery_map, provider_id_for_pack};
+use crate::domain_run::resolve_demo_provider_pack;
+use crate::domains::{self, Domain};
+use crate::secrets_gate;
+use crate::subscriptions_universal::{
+    service::{SubscriptionEnsureRequest, SubscriptionService},
+    state_root,
+    store::{AuthUserRefV1, SubscriptionState, SubscriptionStore},
+};
+
+/// Creates or refreshes a provider-managed subscription (`demo subscriptions ensure`).
+#[derive(Clone, Debug)]
+pub struct EnsureSubscription {
+    pub bundle: PathBuf,
+    pub provider: String,
+    pub tenant: String,
+    pub team: Option<String>,
+    /// Generated when not given.
+    pub binding_id: Option<String>,
+    pub resource: Option<String>,
+    /// Defaults to `created`.
+    pub change_types: Vec<String>,
+    pub notification_url: Option<String>,
+    pub client_state: Option<String>,
+    /// Delegated user; both the id and the token key are required.
+    pub user_id: Option<String>,
+    pub user_token_key: Option<String>,
+}
+
+impl EnsureSubscription {
+    pub fn new(
+        bundle: impl Into<PathBuf>,
+        provider: impl Into<String>,
+        tenant: impl Into<String>,
+    ) -> Self {
+        Self {
+            bundle: bundle.into(),
+            provider: provider.into(),
+            tenant: tenant.into(),
+            team: None,
+            binding_id: None,
+            resource: None,
+            change_types: Vec::new(),
+            notification_url: None,
+            client_state: None,
+            user_id: None,
+            user_token_key: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct EnsuredSubscription {
+    pub state: SubscriptionState,
+    pub state_path: PathBuf,
+}
+
+pub fn ensure_subscription(request: EnsureSubscription) -> anyhow::Result<EnsuredSubscription> {
+    let EnsureSubscription {
+        bundle,
+        provider,
+        tenant,
+        team,
+        binding_id,
+        resource,
+        change_types,
+        notification_url,
+        client_state,
+        user_id,
+        user_token_key,
+    } = request;
+    let team = team.filter(|value| !value.trim().is_empty());
+
+    domains::ensure_cbor_packs(&bundle)?;
+    let pack = resolve_demo_provider_pack(
+        &bundle,
+        &tenant,
+        team.as_deref(),
+        &provider,
+        Domain::Messaging,
+    )?;
+    let discovery =
+        discovery::discover_with_options(&bundle, discovery::DiscoveryOptions { cbor_only: true })?;
+    let provider_map = discovery_map(&discovery.providers);
+    let provider_id = provider_id_for_pack(&pack.path, &pack.pack_id, Some(&provider_map));
+
+    let secrets_handle = secrets_gate::resolve_secrets_manager(&bundle, &tenant, team.as_deref())?;
+    let runner_host = DemoRunnerHost::new(bundle.clone(), &discovery, None, secrets_handle, false)?;
+    let context = OperatorContext {
+        tenant: tenant.clone(),
+        team: team.clone(),
+        correlation_id: None,
+    };
+    let service = SubscriptionService::new(runner_host, context);
+
+    let binding_id = binding_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+    let ensure_request = build_subscription_request(
+        &binding_id,
+        resource,
+        change_types,
+        notification_url,
+        client_state,
+        user_id,
+        user_token_key,
+    );
+    let store = SubscriptionStore::new(state_root(&bundle));
+    let result = service
+        .ensure_once(&provider_id, &ensure_request)
+        .and_then(|state| {
+            store.write_state(&state)?;
+            Ok(state)
+        });
+    audit::record(
+        &bundle,
+        AuditEvent::new("subscriptions.ensure", &binding_id)
+            .scope(&tenant, team.as_deref())
+            .detail(json!({ "provider": provider_id }))
+            .result(&result),
+    );
+    let state = result?;
+    let state_path = store.state_path(
+        &state.provider,
+        &state.tenant,
+        state.team.as_deref(),
+        &state.binding_id,
+    );
+    Ok(EnsuredSubscription { state, state_path })
+}
+
+fn build_subscription_request(
+    binding_id: &str,
+    resource: Option<String>,
+    change_types: Vec<String>,
+    notification_url: Option<String>,
+    client_state: Option<String>,
+    user_id: Option<String>,
+    user_token_key: Option<String>,
+) -> SubscriptionEnsureRequest {
+    let change_types = if change_types.is_empty() {
+        vec!["created".to_string()]
+    } else {
+        change_types
+    };
+    let user = match (user_id, user_token_key) {
+        (Some(user_id), Some(token_key)) => Some(AuthUserRefV1 {
+            user_id,
+            token_key,
+            tenant_id: None,
+            email: None,
+            display_name: None,
+        }),
+        _ => None,
+    };
+    SubscriptionEnsureRequest {
+        binding_id: binding_id.to_string(),
+        resource,
+        change_types,
+        notification_url,
+        client_state,
+        user,
+        expiration_target_unix_ms: None,
+    }
+}
@@ -0,0 +1,296 @@
+//! Append-only audit trail of state-mutating operator actions.
+//!
+//! Setup runs, gmap edits, secrets writes, subscription changes, capability
+//! install records, and operator access checks each append one JSON line to
+//! `state/audit/<YYYY-MM-DD>.jsonl` in the bundle. Unlike operator.log, the trail
+//! holds nothing but mutations and is never rewritten; `demo audit tail|search`
+//! reads it back. Details pass through [`redaction::scrub_json`] first.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::gmap::Policy;
+use crate::operator_auth;
+use crate::operator_log;
+use crate::redaction;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Ok,
+    Failed,
+    Denied,
+}
+
+impl AuditOutcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuditOutcome::Ok => "ok",
+            AuditOutcome::Failed => "failed",
+            AuditOutcome::Denied => "denied",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub ts: String,
+    /// Dotted action name, e.g. `setup.run`, `gmap.allow`, `secrets.write`.
+    pub action: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team: Option<String>,
+    pub target: String,
+    pub outcome: AuditOutcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "JsonValue::is_null")]
+    pub detail: JsonValue,
+}
+
+impl AuditEvent {
+    pub fn new(action: &str, target: impl Into<String>) -> Self {
+        Self {
+            ts: Utc::now().to_rfc3339(),
+            action: action.to_string(),
+            actor: current_actor(),
+            tenant: None,
+            team: None,
+            target: target.into(),
+            outcome: AuditOutcome::Ok,
+            error: None,
+            detail: JsonValue::Null,
+        }
+    }
+
+    pub fn scope(mut self, tenant: &str, team: Option<&str>) -> Self {
+        self.tenant = Some(tenant.to_string());
+        self.team = team.map(str::to_string);
+        self
+    }
+
+    pub fn detail(mut self, detail: JsonValue) -> Self {
+        self.detail = redaction::scrub_json(&detail);
+        self
+    }
+
+    /// Marks the event failed when `result` is an error.
+    pub fn result<T>(mut self, result: &anyhow::Result<T>) -> Self {
+        if let Err(err) = result {
+            self.outcome = AuditOutcome::Failed;
+            self.error = Some(redaction::scrub_text(&format!("{err:#}")));
+        }
+        self
+    }
+
+    pub fn denied(mut self, reason: &str) -> Self {
+        self.outcome = AuditOutcome::Denied;
+        self.error = Some(reason.to_string());
+        self
+    }
+}
+
+/// The operator from `demo --as` (or a matched token), else the OS user.
+fn current_actor() -> Option<String> {
+    operator_auth::current_operator()
+        .map(str::to_string)
+        .or_else(|| {
+            ["USER", "USERNAME"]
+                .iter()
+                .find_map(|key| std::env::var(key).ok())
+        })
+        .filter(|value| !value.trim().is_empty())
+}
+
+pub fn audit_dir(bundle_root: &Path) -> PathBuf {
+    bundle_root.join("state").join("audit")
+}
+
+/// Appends `event`. Failures are logged, never propagated, so auditing cannot
+/// break the action being audited.
+pub fn record(bundle_root: &Path, event: AuditEvent) {
+    if let Err(err) = append(bundle_root, &event) {
+        operator_log::warn(
+            module_path!(),
+            format!("failed to write audit event {}: {err}", event.action),
+        );
+    }
+}
+
+/// `gmap.allow` / `gmap.forbid` for one upserted rule.
+pub fn record_gmap_edit(
+    bundle_root: &Path,
+    tenant: &str,
+    team: Option<&str>,
+    rule: &str,
+    policy: &Policy,
+    result: &anyhow::Result<()>,
+) {
+    let action = match policy {
+        Policy::Public => "gmap.allow",
+        Policy::Forbidden => "gmap.forbid",
+    };
+    record(
+        bundle_root,
+        AuditEvent::new(action, rule)
+            .scope(tenant, team)
+            .result(result),
+    );
+}
+
+fn append(bundle_root: &Path, event: &AuditEvent) -> anyhow::Result<()> {
+    let dir = audit_dir(bundle_root);
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let day = event.ts.get(..10).unwrap_or("unknown");
+    let path = dir.join(format!("{day}.jsonl"));
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(event)?)?;
+    Ok(())
+}
+
+/// Every event under `state/audit`, oldest first. Malformed lines are skipped.
+pub fn read_events(bundle_root: &Path) -> anyhow::Result<Vec<AuditEvent>> {
+    let dir = audit_dir(bundle_root);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut files = fs::read_dir(&dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("jsonl"))
+        .collect::<Vec<_>>();
+    files.sort();
+    let mut events = Vec::new();
+    for path in files {
+        let Ok(contents) = fs::read_to_string(&path) else {
+            continue;
+        };
+        events.extend(
+            contents
+                .lines()
+                .filter_map(|line| serde_json::from_str::<AuditEvent>(line).ok()),
+        );
+    }
+    events.sort_by(|a, b| a.ts.cmp(&b.ts));
+    Ok(events)
+}
+
+/// Filters applied by `demo audit search`.
+#[derive(Clone, Debug, Default)]
+pub struct AuditQuery {
+    /// Exact action or a dotted prefix (`secrets` matches `secrets.write`).
+    pub action: Option<String>,
+    pub actor: Option<String>,
+    pub tenant: Option<String>,
+    pub outcome: Option<AuditOutcome>,
+    /// Case-insensitive substring of the target, error, or detail.
+    pub text: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl AuditQuery {
+    pub fn matches(&self, event: &AuditEvent) -> bool {
+        if let Some(action) = &self.action
+            && event.action != *action
+            && !event.action.starts_with(&format!("{action}."))
+        {
+            return false;
+        }
+        if self
+            .actor
+            .as_deref()
+            .is_some_and(|actor| event.actor.as_deref() != Some(actor))
+        {
+            return false;
+        }
+        if self
+            .tenant
+            .as_deref()
+            .is_some_and(|tenant| event.tenant.as_deref() != Some(tenant))
+        {
+            return false;
+        }
+        if self.outcome.is_some_and(|outcome| event.outcome != outcome) {
+            return false;
+        }
+        if let Some(text) = &self.text {
+            let needle = text.to_ascii_lowercase();
+            let haystack = format!(
+                "{} {} {}",
+                event.target,
+                event.error.as_deref().unwrap_or_default(),
+                event.detail
+            )
+            .to_ascii_lowercase();
+            if !haystack.contains(&needle) {
+                return false;
+            }
+        }
+        if let Some(since) = self.since {
+            let ts = DateTime::parse_from_rfc3339(&event.ts).map(|ts| ts.with_timezone(&Utc));
+            if !ts.is_ok_and(|ts| ts >= since) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn events_append_and_filter_by_action_prefix() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        record(
+            dir.path(),
+            AuditEvent::new("gmap.allow", "messaging-telegram").scope("demo", None),
+        );
+        let failed: anyhow::Result<()> = Err(anyhow::anyhow!("store locked"));
+        record(
+            dir.path(),
+            AuditEvent::new("secrets.write", "telegram")
+                .scope("demo", Some("ops"))
+                .detail(json!({"keys": ["bot_token"], "bot_token": "123"}))
+                .result(&failed),
+        );
+
+        let events = read_events(dir.path())?;
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].outcome, AuditOutcome::Failed);
+        assert_eq!(events[1].detail["bot_token"], redaction::REDACTED);
+
+        let query = AuditQuery {
+            action: Some("secrets".to_string()),
+            ..AuditQuery::default()
+        };
+        let matched = events
+            .iter()
+            .filter(|event| query.matches(event))
+            .collect::<Vec<_>>();
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].error.as_deref(), Some("store locked"));
+
+        let query = AuditQuery {
+            text: Some("TELEGRAM".to_string()),
+            outcome: Some(AuditOutcome::Ok),
+            ..AuditQuery::default()
+        };
+        assert_eq!(events.iter().filter(|e| query.matches(e)).count(), 1);
+        Ok(())
+    }
+}
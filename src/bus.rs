@@ -0,0 +1,309 @@
+//! Publish/subscribe bus used by the demo ingress and egress paths.
+//!
+//! `demo start --nats=jetstream` persists a JetStream binding for the tenant/team and
+//! messages go to NATS. Otherwise everything stays inside the operator process on
+//! an [`InProcessBus`], so single-binary demos need no broker at all.
+//!
+//! Subjects use NATS syntax for both implementations:
+//! `greentic.<tenant>.<team>.ingress.<domain>.<provider>` and
+//! `greentic.<tenant>.<team>.egress.<provider>`; subscriptions accept the `*` and
+//! `>` wildcards.
+//!
+//! `subscribe` is a live view. Handlers that must see every message use `consume`,
+//! which on JetStream reads through a durable consumer and redelivers whatever was
+//! not acked with [`Subscription::ack`].
+
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::jetstream::{self, AckWriter, JetStreamBinding};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BusMessage {
+    pub subject: String,
+    pub payload: Value,
+    /// JetStream ack subject; `None` when there is nothing to ack.
+    pub ack: Option<String>,
+}
+
+pub struct Subscription {
+    receiver: Receiver<BusMessage>,
+    acks: Option<Arc<AckWriter>>,
+}
+
+impl Subscription {
+    pub fn new(receiver: Receiver<BusMessage>) -> Self {
+        Self {
+            receiver,
+            acks: None,
+        }
+    }
+
+    pub fn with_acks(receiver: Receiver<BusMessage>, acks: Arc<AckWriter>) -> Self {
+        Self {
+            receiver,
+            acks: Some(acks),
+        }
+    }
+
+    /// Marks `message` as handled so a durable consumer does not deliver it again.
+    /// A no-op for messages that need no ack.
+    pub fn ack(&self, message: &BusMessage) -> anyhow::Result<()> {
+        match (&self.acks, &message.ack) {
+            (Some(acks), Some(subject)) => acks.ack(subject),
+            _ => Ok(()),
+        }
+    }
+
+    /// Waits up to `timeout`; `Ok(None)` on timeout. Fails once the bus is gone (the
+    /// JetStream connection dropped), so callers stop or subscribe again.
+    pub fn recv_timeout(&self, timeout: Duration) -> anyhow::Result<Option<BusMessage>> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(message) => Ok(Some(message)),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err(anyhow::anyhow!("bus subscription closed")),
+        }
+    }
+
+    pub fn try_recv(&self) -> Option<BusMessage> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BusKind {
+    InProcess,
+    JetStream,
+}
+
+impl BusKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::InProcess => "in-process",
+            Self::JetStream => "jetstream",
+        }
+    }
+}
+
+pub trait MessageBus: Send + Sync {
+    fn kind(&self) -> BusKind;
+    fn publish(&self, subject: &str, payload: &Value) -> anyhow::Result<()>;
+    /// Live view of `pattern`: only messages published from now on, never redelivered.
+    fn subscribe(&self, pattern: &str) -> anyhow::Result<Subscription>;
+    /// Every message on `pattern` for the handler named `durable`, at least once:
+    /// messages not acked with [`Subscription::ack`] are delivered again.
+    fn consume(&self, pattern: &str, durable: &str) -> anyhow::Result<Subscription>;
+}
+
+/// Channel-based bus living inside the operator process.
+#[derive(Default)]
+pub struct InProcessBus {
+    subscribers: Mutex<Vec<(String, Sender<BusMessage>)>>,
+}
+
+impl InProcessBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl MessageBus for InProcessBus {
+    fn kind(&self) -> BusKind {
+        BusKind::InProcess
+    }
+
+    fn publish(&self, subject: &str, payload: &Value) -> anyhow::Result<()> {
+        let mut subscribers = self
+            .subscribers
+            .lock()
+            .map_err(|_| anyhow::anyhow!("in-process bus lock poisoned"))?;
+        // Dropped subscriptions show up as send errors; prune them while delivering.
+        subscribers.retain(|(pattern, sender)| {
+            if !subject_matches(pattern, subject) {
+                return true;
+            }
+            sender
+                .send(BusMessage {
+                    subject: subject.to_string(),
+                    payload: payload.clone(),
+                    ack: None,
+                })
+                .is_ok()
+        });
+        Ok(())
+    }
+
+    fn subscribe(&self, pattern: &str) -> anyhow::Result<Subscription> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers
+            .lock()
+            .map_err(|_| anyhow::anyhow!("in-process bus lock poisoned"))?
+            .push((pattern.to_string(), sender));
+        Ok(Subscription::new(receiver))
+    }
+
+    /// Nothing outlives the process here, so consuming is subscribing.
+    fn consume(&self, pattern: &str, _durable: &str) -> anyhow::Result<Subscription> {
+        self.subscribe(pattern)
+    }
+}
+
+pub struct JetStreamBus {
+    binding: JetStreamBinding,
+}
+
+impl JetStreamBus {
+    pub fn new(binding: JetStreamBinding) -> Self {
+        Self { binding }
+    }
+}
+
+impl MessageBus for JetStreamBus {
+    fn kind(&self) -> BusKind {
+        BusKind::JetStream
+    }
+
+    fn publish(&self, subject: &str, payload: &Value) -> anyhow::Result<()> {
+        jetstream::publish(&self.binding, subject, payload).map(|_| ())
+    }
+
+    fn subscribe(&self, pattern: &str) -> anyhow::Result<Subscription> {
+        jetstream::subscribe(&self.binding.url, pattern)
+    }
+
+    fn consume(&self, pattern: &str, durable: &str) -> anyhow::Result<Subscription> {
+        jetstream::consume(&self.binding, pattern, durable)
+    }
+}
+
+/// The bus shared by everything in this process when NATS is not in use.
+pub fn in_process() -> Arc<InProcessBus> {
+    static BUS: OnceLock<Arc<InProcessBus>> = OnceLock::new();
+    BUS.get_or_init(|| Arc::new(InProcessBus::new())).clone()
+}
+
+/// Picks JetStream when `demo start --nats=jetstream` set it up for this scope,
+/// otherwise the in-process bus.
+pub fn for_scope(bundle: &Path, tenant: &str, team: &str) -> Arc<dyn MessageBus> {
+    match jetstream::binding_for(bundle, tenant, team) {
+        Some(binding) => Arc::new(JetStreamBus::new(binding)),
+        None => in_process(),
+    }
+}
+
+pub fn ingress_subject(tenant: &str, team: &str, domain: &str, provider: &str) -> String {
+    format!(
+        "{}.ingress.{}.{}",
+        subject_prefix(tenant, team),
+        sanitize_token(domain),
+        sanitize_token(provider)
+    )
+}
+
+pub fn egress_subject(tenant: &str, team: &str, provider: &str) -> String {
+    format!(
+        "{}.egress.{}",
+        subject_prefix(tenant, team),
+        sanitize_token(provider)
+    )
+}
+
+pub(crate) fn subject_prefix(tenant: &str, team: &str) -> String {
+    format!(
+        "greentic.{}.{}",
+        sanitize_token(tenant),
+        sanitize_token(team)
+    )
+}
+
+/// Subject tokens and stream names may not contain `.`, `*`, `>` or whitespace.
+pub(crate) fn sanitize_token(value: &str) -> String {
+    let token: String = value
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if token.is_empty() {
+        "_".to_string()
+    } else {
+        token
+    }
+}
+
+/// NATS-style matching: `*` matches one token, a trailing `>` one or more.
+pub fn subject_matches(pattern: &str, subject: &str) -> bool {
+    let mut subject_tokens = subject.split('.');
+    for token in pattern.split('.') {
+        match (token, subject_tokens.next()) {
+            (">", Some(_)) => return true,
+            ("*", Some(_)) => {}
+            (expected, Some(actual)) if expected == actual => {}
+            _ => return false,
+        }
+    }
+    subject_tokens.next().is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn subject_matching_supports_wildcards() {
+        assert!(subject_matches("a.b.c", "a.b.c"));
+        assert!(subject_matches("a.*.c", "a.b.c"));
+        assert!(subject_matches("a.>", "a.b.c"));
+        assert!(!subject_matches("a.>", "a"));
+        assert!(!subject_matches("a.*", "a.b.c"));
+        assert!(!subject_matches("a.b", "a.c"));
+    }
+
+    #[test]
+    fn in_process_bus_delivers_to_matching_subscribers() {
+        let bus = InProcessBus::new();
+        let ingress = bus.subscribe("greentic.demo.default.ingress.>").unwrap();
+        let egress = bus.subscribe("greentic.demo.default.egress.*").unwrap();
+        let subject = ingress_subject("demo", "default", "messaging", "telegram");
+        bus.publish(&subject, &json!({"text": "hi"})).unwrap();
+
+        let message = ingress.try_recv().unwrap();
+        assert_eq!(
+            message.subject,
+            "greentic.demo.default.ingress.messaging.telegram"
+        );
+        assert_eq!(message.payload["text"], "hi");
+        assert!(egress.try_recv().is_none());
+    }
+
+    #[test]
+    fn closed_subscriptions_are_reported() {
+        let (sender, receiver) = mpsc::channel();
+        let subscription = Subscription::new(receiver);
+        assert!(
+            subscription
+                .recv_timeout(Duration::from_millis(1))
+                .unwrap()
+                .is_none()
+        );
+        drop(sender);
+        assert!(subscription.recv_timeout(Duration::from_millis(1)).is_err());
+    }
+
+    #[test]
+    fn dropped_subscriptions_are_pruned() {
+        let bus = InProcessBus::new();
+        drop(bus.subscribe(">").unwrap());
+        bus.publish("x", &json!(1)).unwrap();
+        assert!(bus.subscribers.lock().unwrap().is_empty());
+    }
+}
@@ -73,6 +73,60 @@ pub struct CapabilityOfferRecord {
     pub applies_to_ops: Vec<String>,
 }
 
+impl CapabilityOfferRecord {
+    /// `env=* tenant=demo team=*` style summary of the offer's scope restrictions.
+    pub fn scope_summary(&self) -> String {
+        let render = |values: &[String]| {
+            if values.is_empty() {
+                "*".to_string()
+            } else {
+                values.join(",")
+            }
+        };
+        format!(
+            "env={} tenant={} team={}",
+            render(&self.scope.envs),
+            render(&self.scope.tenants),
+            render(&self.scope.teams)
+        )
+    }
+}
+
+/// Why a candidate offer did or did not win resolution.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CandidateVerdict {
+    Selected,
+    /// Eligible, but an offer with a lower priority value (or stable id on ties) won.
+    Outranked {
+        by: String,
+    },
+    VersionMismatch {
+        requested: String,
+    },
+    /// The named scope field (`env`, `tenant`, `team`) is restricted and did not match.
+    ScopeMismatch {
+        field: &'static str,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct ResolveCandidate {
+    pub offer: CapabilityOfferRecord,
+    pub verdict: CandidateVerdict,
+}
+
+/// Full resolution decision for one capability, as printed by `demo capability resolve --explain`.
+#[derive(Clone, Debug)]
+pub struct ResolveExplanation {
+    pub cap_id: String,
+    pub scope: ResolveScope,
+    pub min_version: Option<String>,
+    /// Offers in resolution order (ascending priority, then stable id).
+    pub candidates: Vec<ResolveCandidate>,
+    pub winner: Option<String>,
+    pub reason: String,
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct CapabilityRegistry {
     by_cap_id: BTreeMap<String, Vec<CapabilityOfferRecord>>,
@@ -145,6 +199,69 @@ impl CapabilityRegistry {
             .unwrap_or_default()
     }
 
+    pub fn cap_ids(&self) -> impl Iterator<Item = &str> {
+        self.by_cap_id.keys().map(String::as_str)
+    }
+
+    /// Mirrors [`CapabilityRegistry::resolve`] but keeps the verdict for every offer.
+    pub fn explain_resolve(
+        &self,
+        cap_id: &str,
+        min_version: Option<&str>,
+        scope: &ResolveScope,
+    ) -> ResolveExplanation {
+        let offers = self.offers_for_capability(cap_id);
+        let mut winner: Option<&CapabilityOfferRecord> = None;
+        let mut eligible = 0usize;
+        let mut candidates = Vec::with_capacity(offers.len());
+        for offer in offers {
+            let verdict = if !version_matches(&offer.version, min_version) {
+                CandidateVerdict::VersionMismatch {
+                    requested: min_version.unwrap_or_default().to_string(),
+                }
+            } else if let Some(field) = scope_mismatch(&offer.scope, scope) {
+                CandidateVerdict::ScopeMismatch { field }
+            } else {
+                eligible += 1;
+                match winner {
+                    Some(selected) => CandidateVerdict::Outranked {
+                        by: selected.stable_id.clone(),
+                    },
+                    None => {
+                        winner = Some(offer);
+                        CandidateVerdict::Selected
+                    }
+                }
+            };
+            candidates.push(ResolveCandidate {
+                offer: offer.clone(),
+                verdict,
+            });
+        }
+        let reason = match winner {
+            None if offers.is_empty() => format!("no pack offers {cap_id}"),
+            None => format!(
+                "none of the {} offer(s) matched the requested version and scope",
+                offers.len()
+            ),
+            Some(selected) if eligible == 1 => {
+                format!("{} is the only eligible offer", selected.stable_id)
+            }
+            Some(selected) => format!(
+                "{} has the lowest priority value ({}) among {eligible} eligible offers",
+                selected.stable_id, selected.priority
+            ),
+        };
+        ResolveExplanation {
+            cap_id: cap_id.to_string(),
+            scope: scope.clone(),
+            min_version: min_version.map(str::to_string),
+            candidates,
+            winner: winner.map(|selected| selected.stable_id.clone()),
+            reason,
+        }
+    }
+
     pub fn resolve(
         &self,
         cap_id: &str,
@@ -313,6 +430,13 @@ pub fn is_binding_ready(
     Ok(record.status.eq_ignore_ascii_case("ready"))
 }
 
+/// The capabilities the pack at `path` requires.
+pub fn read_capability_requirements(path: &Path) -> anyhow::Result<Vec<CapabilityRequirement>> {
+    Ok(read_capabilities_extension(path)?
+        .map(|ext| ext.requires)
+        .unwrap_or_default())
+}
+
 fn read_capabilities_extension(path: &Path) -> anyhow::Result<Option<CapabilitiesExtensionV1>> {
     let file = std::fs::File::open(path)?;
     let mut archive = ZipArchive::new(file)?;
@@ -356,9 +480,19 @@ fn version_matches(version: &str, min_version: Option<&str>) -> bool {
 }
 
 fn scope_matches(offer_scope: &CapabilityScopeV1, scope: &ResolveScope) -> bool {
-    value_matches(&offer_scope.envs, scope.env.as_deref())
-        && value_matches(&offer_scope.tenants, scope.tenant.as_deref())
-        && value_matches(&offer_scope.teams, scope.team.as_deref())
+    scope_mismatch(offer_scope, scope).is_none()
+}
+
+fn scope_mismatch(offer_scope: &CapabilityScopeV1, scope: &ResolveScope) -> Option<&'static str> {
+    if !value_matches(&offer_scope.envs, scope.env.as_deref()) {
+        Some("env")
+    } else if !value_matches(&offer_scope.tenants, scope.tenant.as_deref()) {
+        Some("tenant")
+    } else if !value_matches(&offer_scope.teams, scope.team.as_deref()) {
+        Some("team")
+    } else {
+        None
+    }
 }
 
 fn value_matches(values: &[String], current: Option<&str>) -> bool {
@@ -377,6 +511,17 @@ struct CapabilitiesExtensionV1 {
     schema_version: u32,
     #[serde(default)]
     offers: Vec<CapabilityOfferV1>,
+    #[serde(default)]
+    requires: Vec<CapabilityRequirement>,
+}
+
+/// A capability a pack needs some pack of the bundle to offer (`requires` in the
+/// capabilities extension).
+#[derive(Clone, Debug, Deserialize, serde::Serialize, PartialEq, Eq)]
+pub struct CapabilityRequirement {
+    pub cap_id: String,
+    #[serde(default)]
+    pub version: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -466,6 +611,69 @@ mod tests {
         assert!(!value_matches(&["demo".to_string()], Some("prod")));
     }
 
+    fn offer(stable_id: &str, priority: i32, tenants: &[&str]) -> CapabilityOfferRecord {
+        CapabilityOfferRecord {
+            stable_id: stable_id.to_string(),
+            pack_id: "pack-test".to_string(),
+            domain: Domain::Messaging,
+            pack_path: PathBuf::from("pack-test.gtpack"),
+            cap_id: "greentic.cap.test".to_string(),
+            version: "v1".to_string(),
+            provider_component_ref: "component".to_string(),
+            provider_op: "invoke".to_string(),
+            priority,
+            requires_setup: false,
+            setup_qa_ref: None,
+            scope: CapabilityScopeV1 {
+                envs: Vec::new(),
+                tenants: tenants.iter().map(|value| value.to_string()).collect(),
+                teams: Vec::new(),
+            },
+            applies_to_ops: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn explain_resolve_reports_every_candidate() {
+        let mut by_cap_id = BTreeMap::new();
+        by_cap_id.insert(
+            "greentic.cap.test".to_string(),
+            vec![
+                offer("offer.a", 0, &["other"]),
+                offer("offer.b", 5, &[]),
+                offer("offer.c", 10, &["demo"]),
+            ],
+        );
+        let registry = CapabilityRegistry { by_cap_id };
+        let scope = ResolveScope {
+            env: None,
+            tenant: Some("demo".to_string()),
+            team: None,
+        };
+        let explanation = registry.explain_resolve("greentic.cap.test", None, &scope);
+        assert_eq!(explanation.winner.as_deref(), Some("offer.b"));
+        assert_eq!(
+            explanation.candidates[0].verdict,
+            CandidateVerdict::ScopeMismatch { field: "tenant" }
+        );
+        assert_eq!(
+            explanation.candidates[2].verdict,
+            CandidateVerdict::Outranked {
+                by: "offer.b".to_string()
+            }
+        );
+        assert_eq!(
+            registry
+                .resolve("greentic.cap.test", None, &scope)
+                .map(|binding| binding.stable_id),
+            explanation.winner
+        );
+
+        let missing = registry.explain_resolve("greentic.cap.other", None, &scope);
+        assert!(missing.winner.is_none());
+        assert!(missing.candidates.is_empty());
+    }
+
     #[test]
     fn install_record_roundtrip() {
         let tmp = tempdir().expect("tempdir");
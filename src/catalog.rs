@@ -0,0 +1,266 @@
+//! Provider catalog lookups for `catalog search` and `catalog show`.
+//!
+//! Entries come from the provider registry file the wizard reads, in either of its
+//! shapes (a `registry_version`/`items` document or a bare list of listings). Items
+//! may carry `description`, `domains`, and `secrets`; when they don't, the domain is
+//! inferred from the provider id and required secrets are read from the pack if a
+//! copy is on disk (a registry mirror or a local pack ref).
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, anyhow};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use crate::domains::{self, Domain};
+use crate::provider_registry::RegistryMirror;
+use crate::secret_requirements::load_secret_keys_from_pack;
+
+const KNOWN_DOMAINS: [Domain; 3] = [Domain::Messaging, Domain::Events, Domain::Secrets];
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CatalogEntry {
+    pub id: String,
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub domains: Vec<String>,
+    pub required_secrets: Vec<String>,
+    pub reference: String,
+    /// Local copy of the pack the details were read from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pack_path: Option<PathBuf>,
+}
+
+pub fn load_catalog(path: &Path) -> anyhow::Result<Vec<CatalogEntry>> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("read catalog file {}", path.display()))?;
+    let document: JsonValue = serde_json::from_str(&raw)
+        .or_else(|_| serde_yaml_bw::from_str(&raw))
+        .with_context(|| format!("parse catalog/provider registry file {}", path.display()))?;
+    let items = match &document {
+        JsonValue::Array(items) => items,
+        JsonValue::Object(map) => map
+            .get("items")
+            .and_then(JsonValue::as_array)
+            .ok_or_else(|| anyhow!("provider registry {} has no items", path.display()))?,
+        _ => {
+            return Err(anyhow!(
+                "provider registry {} is neither a list nor an items document",
+                path.display()
+            ));
+        }
+    };
+    items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            parse_entry(item).with_context(|| format!("{} item {}", path.display(), index + 1))
+        })
+        .collect()
+}
+
+fn parse_entry(item: &JsonValue) -> anyhow::Result<CatalogEntry> {
+    let id = item
+        .get("id")
+        .and_then(JsonValue::as_str)
+        .ok_or_else(|| anyhow!("missing id"))?
+        .to_string();
+    let reference = item
+        .get("ref")
+        .or_else(|| item.get("reference"))
+        .and_then(JsonValue::as_str)
+        .ok_or_else(|| anyhow!("{id}: missing ref"))?
+        .to_string();
+    let mut domains = string_list(item.get("domains").or_else(|| item.get("domain")));
+    if domains.is_empty() {
+        domains.extend(infer_domain(&id, &reference).map(str::to_string));
+    }
+    Ok(CatalogEntry {
+        label: item
+            .get("label")
+            .and_then(localized_text)
+            .unwrap_or_else(|| id.clone()),
+        description: item.get("description").and_then(localized_text),
+        domains,
+        required_secrets: string_list(item.get("secrets").or_else(|| item.get("required_secrets"))),
+        reference,
+        pack_path: None,
+        id,
+    })
+}
+
+/// A plain string or a `{i18n_key, fallback}` label.
+fn localized_text(value: &JsonValue) -> Option<String> {
+    match value {
+        JsonValue::String(text) => Some(text.clone()),
+        JsonValue::Object(map) => map
+            .get("fallback")
+            .and_then(JsonValue::as_str)
+            .map(str::to_string),
+        _ => None,
+    }
+}
+
+/// A string, a list of strings, or a list of `{key}` objects.
+fn string_list(value: Option<&JsonValue>) -> Vec<String> {
+    match value {
+        Some(JsonValue::String(value)) => vec![value.clone()],
+        Some(JsonValue::Array(values)) => values
+            .iter()
+            .filter_map(|value| match value {
+                JsonValue::String(value) => Some(value.clone()),
+                JsonValue::Object(map) => map
+                    .get("key")
+                    .and_then(JsonValue::as_str)
+                    .map(str::to_string),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// `messaging-telegram`, `messaging.telegram`, or a ref under `messaging/`.
+fn infer_domain(id: &str, reference: &str) -> Option<&'static str> {
+    KNOWN_DOMAINS
+        .iter()
+        .map(|domain| domains::domain_name(*domain))
+        .find(|name| {
+            id.strip_prefix(name)
+                .is_some_and(|rest| rest.starts_with(['-', '.']))
+                || reference.contains(&format!("/{name}/"))
+                || reference.contains(&format!("://{name}/"))
+        })
+}
+
+/// Fills required secrets from a local copy of the pack, when there is one and the
+/// registry item did not list them.
+pub fn enrich_from_pack(
+    entry: &mut CatalogEntry,
+    mirror: Option<&RegistryMirror>,
+) -> anyhow::Result<()> {
+    let pack_path = match mirror
+        .map(|mirror| mirror.pack(&entry.reference))
+        .transpose()?
+        .flatten()
+    {
+        Some((path, _)) => Some(path),
+        None => local_pack_path(&entry.reference),
+    };
+    let Some(pack_path) = pack_path else {
+        return Ok(());
+    };
+    if entry.required_secrets.is_empty() {
+        entry.required_secrets = load_secret_keys_from_pack(&pack_path)
+            .with_context(|| format!("read secret requirements from {}", pack_path.display()))?;
+    }
+    entry.pack_path = Some(pack_path);
+    Ok(())
+}
+
+fn local_pack_path(reference: &str) -> Option<PathBuf> {
+    let path = reference.strip_prefix("file://").unwrap_or(reference);
+    if reference.contains("://") && !reference.starts_with("file://") {
+        return None;
+    }
+    let path = PathBuf::from(path.trim());
+    path.is_file().then_some(path)
+}
+
+/// Entries whose id, label, description, domains, or ref contain `term`, ignoring
+/// case; exact id matches first.
+pub fn search<'a>(entries: &'a [CatalogEntry], term: &str) -> Vec<&'a CatalogEntry> {
+    let term = term.trim().to_ascii_lowercase();
+    let mut matches = entries
+        .iter()
+        .filter(|entry| {
+            [
+                Some(entry.id.as_str()),
+                Some(entry.label.as_str()),
+                entry.description.as_deref(),
+                Some(entry.reference.as_str()),
+            ]
+            .into_iter()
+            .flatten()
+            .chain(entry.domains.iter().map(String::as_str))
+            .any(|field| field.to_ascii_lowercase().contains(&term))
+        })
+        .collect::<Vec<_>>();
+    matches.sort_by_key(|entry| (!entry.id.eq_ignore_ascii_case(&term), entry.id.clone()));
+    matches
+}
+
+/// The entry with `id`; `messaging-telegram` also finds `messaging.telegram`.
+pub fn find<'a>(entries: &'a [CatalogEntry], id: &str) -> Option<&'a CatalogEntry> {
+    let normalize = |value: &str| value.trim().to_ascii_lowercase().replace('.', "-");
+    let wanted = normalize(id);
+    entries
+        .iter()
+        .find(|entry| entry.id == id)
+        .or_else(|| entries.iter().find(|entry| normalize(&entry.id) == wanted))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_registry(dir: &Path) -> PathBuf {
+        let path = dir.join("providers.json");
+        std::fs::write(
+            &path,
+            r#"{
+  "registry_version": "providers@1",
+  "items": [
+    {
+      "id": "messaging.telegram",
+      "label": {"i18n_key": "provider.telegram", "fallback": "Telegram"},
+      "description": "Bots over the Telegram Bot API",
+      "secrets": ["telegram_bot_token"],
+      "ref": "oci://ghcr.io/greentic/providers/messaging-telegram@0.6.0"
+    },
+    {
+      "id": "events-webhook",
+      "label": "Generic webhook",
+      "ref": "repo://events/providers/events-webhook@latest"
+    }
+  ]
+}"#,
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn registry_items_load_with_inferred_domains() {
+        let temp = tempfile::tempdir().unwrap();
+        let entries = load_catalog(&write_registry(temp.path())).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].label, "Telegram");
+        assert_eq!(entries[0].domains, vec!["messaging"]);
+        assert_eq!(entries[0].required_secrets, vec!["telegram_bot_token"]);
+        assert_eq!(entries[1].label, "Generic webhook");
+        assert_eq!(entries[1].domains, vec!["events"]);
+        assert_eq!(entries[1].description, None);
+    }
+
+    #[test]
+    fn search_matches_fields_and_find_normalizes_ids() {
+        let temp = tempfile::tempdir().unwrap();
+        let entries = load_catalog(&write_registry(temp.path())).unwrap();
+        let ids = |term| {
+            search(&entries, term)
+                .into_iter()
+                .map(|entry| entry.id.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids("BOT API"), vec!["messaging.telegram"]);
+        assert_eq!(ids("events"), vec!["events-webhook"]);
+        assert!(ids("slack").is_empty());
+        assert_eq!(
+            find(&entries, "messaging-telegram").map(|entry| entry.id.as_str()),
+            Some("messaging.telegram")
+        );
+        assert!(find(&entries, "messaging-slack").is_none());
+    }
+}
@@ -15,63 +15,103 @@ use serde::{Deserialize, Serialize};
 use serde_json::{Map as JsonMap, Value as JsonValue, json};
 
 use base64::Engine as _;
-use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+use clap::{ArgAction, Args, CommandFactory, Parser, Subcommand, ValueEnum};
 use tokio::runtime::Runtime;
 
+use crate::api;
+use crate::audit::{self, AuditEvent};
 use crate::bin_resolver::{self, ResolveCtx};
-use crate::capabilities::ResolveScope;
-use crate::config;
-use crate::config_gate::{self, ConfigGateItem, ConfigValueSource};
+use crate::bus;
+use crate::capabilities::{self, CandidateVerdict, ResolveScope};
+use crate::catalog;
+use crate::completions;
+use crate::config::{self, DEMO_DEFAULT_TEAM, load_demo_config_or_default};
+use crate::config_gate;
 use crate::demo::{
-    self, BuildOptions, DemoRepl, DemoRunner,
-    card::{detect_adaptive_card_view, print_card_summary},
-    http_ingress::{HttpIngressConfig, HttpIngressServer},
-    input as demo_input, pack_resolve,
-    runner_host::{DemoRunnerHost, FlowOutcome, OperatorContext, primary_provider_type},
+    self, DemoRepl, DemoRunner, HttpIngressServer,
+    capability_recording::CapabilityRecordingMode,
+    card::{self, print_card_summary},
+    control_api::{
+        ControlApiConfig, ControlApiServer, ControlBackend, PolicyAction, PolicyRequest,
+        RunFlowRequest, SendRequest,
+    },
+    conversation_state::{self, ConversationKey},
+    input as demo_input,
+    kafka_bridge::{self, KafkaBridge, KafkaBridgeConfig},
+    pack_resolve, provider_capabilities,
+    reload::{DemoSignal, ReloadListener, ReloadTrigger},
+    run_record,
+    run_report::RunReport,
+    runner_host::{DemoRunnerHost, FlowOutcome, OperatorContext},
+    scheduled_send,
     setup::{ProvidersInput, discover_tenants},
-    timer_scheduler::{TimerScheduler, TimerSchedulerConfig, discover_timer_handlers},
+    timer_scheduler::{
+        TimerHandlerConfig, TimerScheduler, TimerSchedulerConfig, apply_timer_overrides,
+        discover_timer_handlers, read_timer_state,
+    },
+    webhook_watcher::{self, WebhookWatcher, WebhookWatcherConfig},
 };
+use crate::destinations::{self, DestinationBook, DestinationEntry};
 use crate::dev_store_path;
 use crate::discovery;
+use crate::domain_run::{
+    DomainRunArgs, DomainRunReport, build_input_payload, demo_resolved_manifest_path,
+    provider_filter_matches, resolve_demo_provider_pack, resolved_manifest_filename,
+    run_domain_command,
+};
+pub use crate::domain_run::{demo_provider_pack_by_filter, demo_provider_packs};
 use crate::domains::{self, Domain, DomainAction};
+use crate::explain;
+use crate::flow_stream;
 use crate::gmap::{self, Policy};
+use crate::instance;
+use crate::interpolate::SecretScope;
+use crate::jetstream;
 use crate::messaging_universal::{
-    dto::{EncodeInV1, EncodeOutV1, RenderPlanOutV1, SendPayloadOutV1},
-    egress,
+    attachments, dlq as messaging_dlq, dto::SendPayloadOutV1, egress, emulate, http_transport,
+    retry::RetryPolicy, scenario,
 };
+use crate::migrate;
+use crate::operator_auth::{self, OperatorAction};
+use crate::operator_error::{self, OperatorError};
 use crate::operator_i18n;
 use crate::operator_log;
+use crate::profile;
+use crate::progress;
 use crate::project;
 use crate::provider_registry;
 use crate::qa_setup_wizard;
-use crate::runner_exec;
-use crate::runner_integration;
+use crate::redaction;
+use crate::registry_overlay;
+use crate::runner_pin;
 use crate::runtime_state::RuntimePaths;
+use crate::secret_requirements::load_secret_keys_from_pack;
+use crate::secrets_crypto;
 use crate::secrets_gate::{self, DynSecretsManager, SecretsManagerHandle};
-use crate::secrets_manager;
 use crate::secrets_setup::resolve_env;
+use crate::secrets_versions;
+use crate::self_update::{self, Channel};
+use crate::serve;
+use crate::services::{self, ProcessStatus};
 use crate::setup_input::{SetupInputAnswers, collect_setup_answers, load_setup_input};
-use crate::state_layout;
 use crate::subscriptions_universal::{
-    build_runner,
-    scheduler::Scheduler,
-    service::{SubscriptionEnsureRequest, SubscriptionService},
-    state_root,
-    store::{AuthUserRefV1, SubscriptionStore},
+    build_runner, scheduler::Scheduler, service::SubscriptionService, state_root,
+    store::SubscriptionStore,
 };
+use crate::tenant_transfer;
 use crate::wizard;
 use crate::wizard_executor;
 use crate::wizard_i18n;
+use crate::wizard_i18n_review;
 use crate::wizard_plan_builder;
 use crate::wizard_spec_builder;
+use crate::workspace;
 use greentic_qa_lib::{
     I18nConfig, QaLibError, QaRunner, ResolvedI18nMap, WizardDriver, WizardFrontend,
     WizardRunConfig,
 };
 use greentic_runner_host::secrets::default_manager;
-use greentic_types::{ChannelMessageEnvelope, Destination, EnvId, TeamId, TenantCtx, TenantId};
 use std::time::Duration;
-use uuid::Uuid;
 
 #[derive(Parser)]
 #[command(name = "greentic-operator")]
@@ -79,6 +119,19 @@ use uuid::Uuid;
 pub struct Cli {
     #[arg(long, global = true, help = "CLI locale (for translated output).")]
     locale: Option<String>,
+    #[arg(
+        long,
+        global = true,
+        help = "Print how long discovery, manifest parsing, secrets, flows and provider HTTP calls took when the command ends."
+    )]
+    profile: bool,
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILE",
+        help = "Also write the timings as a Chrome trace (chrome://tracing, Perfetto) to FILE; implies --profile."
+    )]
+    profile_trace: Option<PathBuf>,
     #[command(subcommand)]
     command: Command,
 }
@@ -90,12 +143,401 @@ enum Command {
         about = "Alias of demo wizard. Plan/create a demo bundle with pack refs and allow rules."
     )]
     Wizard(Box<DemoWizardArgs>),
+    #[command(about = "Inspect resolved tenant/team manifests.")]
+    Resolve(ResolveCommand),
+    #[command(about = "Run several demo bundles from a workspace.yaml.")]
+    Workspace(WorkspaceCommand),
+    #[command(about = "Run a bundle unattended, with liveness and readiness probes.")]
+    Serve(ServeArgs),
+    #[command(about = "Work with the provider registry the wizard installs from.")]
+    Registry(RegistryCommand),
+    #[command(about = "Search the provider registry the wizard installs from.")]
+    Catalog(CatalogCommand),
+    #[command(about = "Copy a tenant within or between bundles and projects.")]
+    Tenant(TenantCommand),
+    #[command(about = "Inspect the CLI locales available for --locale.")]
+    Locale(LocaleCommand),
+    #[command(about = "Explain an error code and how to fix it.")]
+    Explain(ExplainArgs),
+    #[command(about = "Print a shell completion script.")]
+    Completions(CompletionsArgs),
+    #[command(about = "Upgrade a bundle written by an older operator in place.")]
+    Migrate(MigrateArgs),
+    #[command(about = "Replace this binary with the latest operator release.")]
+    SelfUpdate(SelfUpdateArgs),
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Replace this binary with the latest operator release.",
+    long_about = "Reads the release manifest of the channel, checks its Ed25519 signature against the trusted release keys, downloads the build for this platform, verifies its signed SHA-256, and installs it over the running binary. Set GREENTIC_OPERATOR_RELEASE_URL to use another release endpoint ({channel} is substituted); release keys are built in, and only debug builds also read GREENTIC_OPERATOR_RELEASE_KEYS (key_id=<base64>,...) at run time.",
+    after_help = "Optional options:\n  --channel <stable|beta> (default: stable)\n  --check\n  --format <text|json>\n\nWith --check the command exits with 1 when a newer release is available."
+)]
+struct SelfUpdateArgs {
+    /// Release channel to follow.
+    #[arg(long, value_enum, default_value_t = Channel::Stable)]
+    channel: Channel,
+    /// Only report whether a newer release is available; exit 1 if it is.
+    #[arg(long)]
+    check: bool,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Run a bundle unattended, with liveness and readiness probes.",
+    long_about = "Runs the HTTP ingress, timer scheduler, Kafka bridge, and universal subscription renewal of a bundle without tunnels, provider setup, prompts, or a service summary, as in a container or under systemd. Options fall back to environment variables: GREENTIC_OPERATOR_BUNDLE, GREENTIC_OPERATOR_TENANT, GREENTIC_OPERATOR_TEAM, GREENTIC_OPERATOR_NATS_URL, and GREENTIC_OPERATOR_HEALTH_PORT; the gateway honors GREENTIC_OPERATOR_GATEWAY_PORT and GREENTIC_OPERATOR_GATEWAY_LISTEN_ADDR (default 0.0.0.0). SIGTERM and Ctrl+C stop it, SIGHUP reloads greentic.demo.yaml and the gmaps.",
+    after_help = "Main options:\n  --bundle <DIR>\n\nOptional options:\n  --tenant <TENANT> (default: demo)\n  --team <TEAM>\n  --nats-url <URL>\n  --health-port <PORT> (default: 9091)\n  --log-dir <DIR>\n  --verbose\n\nProbes:\n  GET /healthz  200 while health checks run\n  GET /readyz   200 when the ingress, NATS, and subscription renewal pass their last check, 503 otherwise"
+)]
+struct ServeArgs {
+    /// Bundle directory to serve.
+    #[arg(long)]
+    bundle: Option<PathBuf>,
+    #[arg(long)]
+    tenant: Option<String>,
+    #[arg(long)]
+    team: Option<String>,
+    /// NATS server with JetStream for ingress and egress; without it the bus is in-process.
+    #[arg(long, value_name = "URL")]
+    nats_url: Option<String>,
+    /// Port of the /healthz and /readyz probe server on all interfaces.
+    #[arg(long, value_name = "PORT")]
+    health_port: Option<u16>,
+    /// Directory for operator.log (default: <bundle>/logs).
+    #[arg(long, value_name = "DIR")]
+    log_dir: Option<PathBuf>,
+    #[arg(long)]
+    verbose: bool,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Upgrade a bundle written by an older operator in place.",
+    long_about = "Detects the bundle's layout version and brings its resolved manifests, state layout, and config keys up to what this operator expects. Files that change are first copied to state/migrations/<timestamp>/. Config keys that need a decision are reported, not rewritten.",
+    after_help = "Main options:\n  --bundle <DIR>\n\nOptional options:\n  --dry-run\n  --format <text|json>"
+)]
+struct MigrateArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    /// Report what would change without touching the bundle.
+    #[arg(long)]
+    dry_run: bool,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Explain an error code and how to fix it.",
+    long_about = "Prints what causes an error code (the `error code:` line of a failed command) and the steps that usually fix it, in the --locale language. Without a code, lists every code. When stdout is a terminal the text goes through $PAGER (default: less -FRX).",
+    after_help = "Examples:\n  greentic-operator explain\n  greentic-operator explain secret_missing\n  greentic-operator --locale de explain circuit_open"
+)]
+struct ExplainArgs {
+    /// Error code to explain, e.g. secret_missing.
+    #[arg(value_name = "CODE")]
+    code: Option<String>,
+    /// Print directly instead of through $PAGER.
+    #[arg(long)]
+    no_pager: bool,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Print a shell completion script.",
+    long_about = "Prints a script that registers completion for greentic-operator. Completions come from the installed binary, including provider ids, pack names, tenants, and teams found in the bundle given by --bundle (default: current directory).",
+    after_help = "Install:\n  bash:       echo 'source <(greentic-operator completions bash)' >> ~/.bashrc\n  zsh:        echo 'source <(greentic-operator completions zsh)' >> ~/.zshrc\n  fish:       greentic-operator completions fish > ~/.config/fish/completions/greentic-operator.fish\n  powershell: greentic-operator completions powershell | Out-String | Invoke-Expression"
+)]
+struct CompletionsArgs {
+    #[arg(value_enum)]
+    shell: completions::Shell,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Inspect the CLI locales available for --locale.",
+    long_about = "Built-in locales can be extended or overridden with <locale>.json or <locale>.ftl files in $GREENTIC_LOCALE_DIR or ./locales. Missing keys fall back along the locale chain (pt-BR -> pt -> en)."
+)]
+struct LocaleCommand {
+    #[command(subcommand)]
+    command: LocaleSubcommand,
+}
+
+#[derive(Subcommand)]
+enum LocaleSubcommand {
+    #[command(about = "List selectable locales and where each one is loaded from.")]
+    List(LocaleListArgs),
+}
+
+#[derive(Parser)]
+struct LocaleListArgs {
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Run several demo bundles from a workspace.yaml.",
+    long_about = "Each bundle listed in workspace.yaml runs its own demo start in the background. Gateway and NATS ports that do not conflict are allocated automatically."
+)]
+struct WorkspaceCommand {
+    #[arg(
+        long,
+        global = true,
+        default_value = workspace::WORKSPACE_FILE,
+        help = "Path to the workspace file."
+    )]
+    file: PathBuf,
+    #[command(subcommand)]
+    command: WorkspaceSubcommand,
+}
+
+#[derive(Subcommand)]
+enum WorkspaceSubcommand {
+    #[command(about = "List the bundles in the workspace.")]
+    List,
+    #[command(about = "Start all bundles, or the named ones, in the background.")]
+    Start(WorkspaceMembersArgs),
+    #[command(about = "Stop all bundles, or the named ones.")]
+    Stop(WorkspaceMembersArgs),
+    #[command(about = "Show which bundles are running and on which ports.")]
+    Status(WorkspaceStatusArgs),
+}
+
+#[derive(Parser)]
+struct WorkspaceMembersArgs {
+    #[arg(value_name = "NAME")]
+    names: Vec<String>,
+}
+
+#[derive(Parser)]
+struct WorkspaceStatusArgs {
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+struct RegistryCommand {
+    #[command(subcommand)]
+    command: RegistrySubcommand,
+}
+
+#[derive(Subcommand)]
+enum RegistrySubcommand {
+    #[command(about = "Download the registry catalog and its packs for offline use.")]
+    Mirror(RegistryMirrorArgs),
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Download the registry catalog and its packs for offline use.",
+    long_about = "Fetches the provider registry and every pack its catalog references into --out, pinned by digest in mirror.json. Point wizard at the directory with --registry-mirror or GTC_PROVIDER_REGISTRY_MIRROR to build bundles without network access; re-running replaces the mirror.",
+    after_help = "Main options:\n  --out <DIR>\n\nOptional options:\n  --provider-registry <REF> (default: $GTC_PROVIDER_REGISTRY_REF or the public registry)\n  --format <text|json> (default: text)"
+)]
+struct RegistryMirrorArgs {
+    #[arg(long, value_name = "DIR")]
+    out: PathBuf,
+    /// Registry to mirror (oci://, file://, or a local path).
+    #[arg(long = "provider-registry", value_name = "REF")]
+    provider_registry: Option<String>,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+struct TenantCommand {
+    #[command(subcommand)]
+    command: TenantSubcommand,
+}
+
+#[derive(Subcommand)]
+enum TenantSubcommand {
+    #[command(about = "Copy a tenant under a new name, in the same or another bundle.")]
+    Clone(TenantCloneArgs),
+    #[command(about = "Write a tenant to a .tar.gz archive.")]
+    Export(TenantExportArgs),
+    #[command(about = "Add a tenant from an archive written by tenant export.")]
+    Import(TenantImportArgs),
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Copy a tenant under a new name, in the same or another bundle.",
+    long_about = "Copies tenants/<SRC> (gmaps and teams), its resolved manifests, and its dev-store secrets to <DST>. Secrets are re-encrypted with the destination store's key, if it has one.",
+    after_help = "Main options:\n  <SRC> <DST>\n\nOptional options:\n  --bundle <DIR> (default: current directory)\n  --to-bundle <DIR> (default: --bundle)\n  --no-secrets\n  --force"
+)]
+struct TenantCloneArgs {
+    source: String,
+    target: String,
+    #[arg(long, default_value = ".")]
+    bundle: PathBuf,
+    /// Bundle or project to clone into.
+    #[arg(long, value_name = "DIR")]
+    to_bundle: Option<PathBuf>,
+    /// Copy gmaps and manifests only.
+    #[arg(long)]
+    no_secrets: bool,
+    /// Replace the target tenant if it already exists.
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Write a tenant to a .tar.gz archive.",
+    long_about = "Archives tenants/<TENANT> (gmaps and teams), its resolved manifests, and its dev-store secrets. With --passphrase-env the secret values are encrypted with a key derived from that variable, which must be set again on import; otherwise they are stored in plaintext.",
+    after_help = "Main options:\n  <TENANT>\n  --archive <PATH>\n\nOptional options:\n  --bundle <DIR> (default: current directory)\n  --passphrase-env <VAR>\n  --no-secrets"
+)]
+struct TenantExportArgs {
+    tenant: String,
+    #[arg(long, value_name = "PATH")]
+    archive: PathBuf,
+    #[arg(long, default_value = ".")]
+    bundle: PathBuf,
+    /// Environment variable holding the passphrase that encrypts secrets in the archive.
+    #[arg(long, value_name = "VAR", conflicts_with = "no_secrets")]
+    passphrase_env: Option<String>,
+    /// Leave secrets out of the archive.
+    #[arg(long)]
+    no_secrets: bool,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Add a tenant from an archive written by tenant export.",
+    long_about = "Writes the archived tenant directories, resolved manifests, and secrets into the bundle. Secrets are re-encrypted with the bundle's dev-store key, if it has one.",
+    after_help = "Main options:\n  --archive <PATH>\n\nOptional options:\n  --bundle <DIR> (default: current directory)\n  --as <TENANT>\n  --no-secrets\n  --force"
+)]
+struct TenantImportArgs {
+    #[arg(long, value_name = "PATH")]
+    archive: PathBuf,
+    #[arg(long, default_value = ".")]
+    bundle: PathBuf,
+    /// Import under this name instead of the archived one.
+    #[arg(long = "as", value_name = "TENANT")]
+    rename: Option<String>,
+    /// Skip the archived secrets.
+    #[arg(long)]
+    no_secrets: bool,
+    /// Replace the tenant if it already exists.
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Parser)]
+struct CatalogCommand {
+    #[command(subcommand)]
+    command: CatalogSubcommand,
+}
+
+#[derive(Subcommand)]
+enum CatalogSubcommand {
+    #[command(about = "List providers whose id, name, description, domain, or ref matches.")]
+    Search(CatalogSearchArgs),
+    #[command(about = "Show one provider's description, domains, secrets, and pack ref.")]
+    Show(CatalogShowArgs),
+}
+
+/// Where catalog commands read the registry from; resolved the same way as wizard.
+#[derive(Args)]
+struct CatalogSourceArgs {
+    /// Catalog JSON/YAML file to read instead of the provider registry.
+    #[arg(long = "catalog-file", value_name = "PATH")]
+    catalog_file: Option<PathBuf>,
+    /// Provider registry override (oci://, file://, or a local path).
+    #[arg(long = "provider-registry", value_name = "REF")]
+    provider_registry: Option<String>,
+    /// Directory written by registry mirror.
+    #[arg(long = "registry-mirror", value_name = "DIR")]
+    registry_mirror: Option<PathBuf>,
+    /// Only use a registry already in the cache.
+    #[arg(long)]
+    offline: bool,
+    /// Bundle whose provider registry cache is used.
+    #[arg(long, default_value = ".")]
+    bundle: PathBuf,
+    /// Merge this tenant's tenants/<TENANT>/registry.yaml from the bundle over the registry.
+    #[arg(long, value_name = "TENANT")]
+    tenant: Option<String>,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "List providers whose id, name, description, domain, or ref matches.",
+    long_about = "Reads the provider registry the wizard would use (--catalog-file, $GREENTIC_OPERATOR_WIZARD_CATALOG, --registry-mirror, then --provider-registry) and lists the providers matching TERM, ignoring case. An empty TERM lists every provider.",
+    after_help = "Main options:\n  <TERM>\n\nOptional options:\n  --catalog-file <PATH>\n  --provider-registry <REF> (default: $GTC_PROVIDER_REGISTRY_REF or the public registry)\n  --registry-mirror <DIR> (default: $GTC_PROVIDER_REGISTRY_MIRROR)\n  --offline\n  --bundle <DIR> (default: .)\n  --tenant <TENANT>\n  --format <text|json> (default: text)"
+)]
+struct CatalogSearchArgs {
+    #[arg(value_name = "TERM", default_value = "")]
+    term: String,
+    #[command(flatten)]
+    source: CatalogSourceArgs,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Show one provider's description, domains, secrets, and pack ref.",
+    long_about = "Looks up ID in the provider registry the wizard would use. Required secrets come from the registry entry, or from the pack itself when a copy is available in the registry mirror or at a local ref.",
+    after_help = "Main options:\n  <ID>\n\nOptional options:\n  --catalog-file <PATH>\n  --provider-registry <REF> (default: $GTC_PROVIDER_REGISTRY_REF or the public registry)\n  --registry-mirror <DIR> (default: $GTC_PROVIDER_REGISTRY_MIRROR)\n  --offline\n  --bundle <DIR> (default: .)\n  --tenant <TENANT>\n  --format <text|json> (default: text)"
+)]
+struct CatalogShowArgs {
+    #[arg(value_name = "ID")]
+    id: String,
+    #[command(flatten)]
+    source: CatalogSourceArgs,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+struct ResolveCommand {
+    #[command(subcommand)]
+    command: ResolveSubcommand,
+}
+
+#[derive(Subcommand)]
+enum ResolveSubcommand {
+    Explain(ResolveExplainArgs),
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Explain why a pack/flow is or isn't available to a tenant/team.",
+    long_about = "Shows the resolved manifest checked, which pack provides the pack id, every gmap rule matching the path, and whether the team or tenant gmap decided the policy.",
+    after_help = "Main options:\n  --tenant <TENANT>\n  --path <PACK[/FLOW[/NODE]]>\n\nOptional options:\n  --team <TEAM>\n  --bundle <DIR> (bundle or project root, default: current directory)\n  --format <text|json> (default: text)"
+)]
+struct ResolveExplainArgs {
+    #[arg(long)]
+    bundle: Option<PathBuf>,
+    #[arg(long)]
+    tenant: String,
+    #[arg(long)]
+    team: Option<String>,
+    #[arg(long, value_name = "PACK[/FLOW[/NODE]]")]
+    path: String,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
 }
 
 #[derive(Parser)]
 struct DemoCommand {
     #[arg(long, global = true)]
     debug: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "Print secrets and token-shaped values unmasked in logs and payload output."
+    )]
+    no_redact: bool,
+    #[arg(
+        long = "as",
+        global = true,
+        value_name = "OPERATOR",
+        help = "Operator from the bundle's operators.yaml to act as."
+    )]
+    as_operator: Option<String>,
     #[command(subcommand)]
     command: DemoSubcommand,
 }
@@ -106,7 +548,17 @@ enum DemoSubcommand {
     #[command(hide = true)]
     Up(DemoUpArgs),
     Start(DemoUpArgs),
+    #[command(about = "Reload greentic.demo.yaml and gmaps in a running demo start")]
+    Reload(DemoReloadArgs),
     Setup(DemoSetupArgs),
+    #[command(about = "Create a tenant/team, allow packs, seed secrets, and run setup in one step")]
+    Onboard(DemoOnboardArgs),
+    #[command(about = "Remove a tenant, its provider-side subscriptions, webhooks, and secrets")]
+    Offboard(DemoOffboardArgs),
+    #[command(about = "Run provider verify flows and report pass/fail per provider")]
+    Verify(DemoVerifyArgs),
+    #[command(hide = true)]
+    Bench(DemoBenchCommand),
     Send(DemoSendArgs),
     #[command(about = "Send a synthetic HTTP request through the messaging ingress pipeline")]
     Ingress(DemoIngressArgs),
@@ -114,18 +566,58 @@ enum DemoSubcommand {
     Status(DemoStatusArgs),
     Logs(DemoLogsArgs),
     Doctor(DemoDoctorArgs),
+    #[command(about = "Compare packs, providers, gmaps, and resolved manifests of two bundles")]
+    Diff(DemoDiffArgs),
+    #[command(about = "Run a bundle's ingress and timer test cases offline")]
+    Test(DemoTestArgs),
+    #[command(about = "Record or check render_plan/encode snapshots for a provider")]
+    Snapshot(DemoSnapshotCommand),
+    #[command(about = "Show the effective configuration a bundle's flows receive")]
+    Config(DemoConfigCommand),
     #[command(about = "Allow a tenant/team access to a pack/flow/node")]
     Allow(DemoPolicyArgs),
     #[command(about = "Forbid a tenant/team access to a pack/flow/node")]
     Forbid(DemoPolicyArgs),
+    #[command(about = "Inspect or roll back published resolved manifests")]
+    Policy(DemoPolicyCommand),
     #[command(about = "Manage demo subscriptions via provider components")]
     Subscriptions(DemoSubscriptionsCommand),
     #[command(about = "Manage capability resolution/invocation in demo bundles")]
     Capability(DemoCapabilityCommand),
+    #[command(about = "Manage named destinations used by demo send --to @name")]
+    Destinations(DemoDestinationsCommand),
+    #[command(about = "Validate or scaffold adaptive cards for demo send --card")]
+    Card(DemoCardCommand),
+    #[command(about = "Compare what the bundle's messaging providers can render")]
+    Providers(DemoProvidersCommand),
+    #[command(about = "Inspect recorded flow/op runs")]
+    Runs(DemoRunsCommand),
+    #[command(about = "Queue long-running actions and follow their progress")]
+    Jobs(DemoJobsCommand),
+    #[command(about = "Remove stale pidfiles, old runs, expired captures, and rotated logs")]
+    Gc(DemoGcArgs),
+    #[command(about = "List, replay, or purge dead-lettered egress messages")]
+    Dlq(DemoDlqCommand),
+    #[command(about = "Inspect events timer handlers and their schedules")]
+    Timers(DemoTimersCommand),
+    #[command(about = "Tail or publish events in the events domain")]
+    Events(DemoEventsCommand),
+    #[command(about = "Inspect or seed per-conversation state used by app flows")]
+    State(DemoStateCommand),
+    #[command(about = "Manage the local dev secrets store")]
+    Secrets(DemoSecretsCommand),
+    #[command(about = "Inspect the audit trail of state-mutating actions")]
+    Audit(DemoAuditCommand),
     #[command(about = "Run a pack/flow with inline input")]
     Run(DemoRunArgs),
     #[command(about = "List resolved packs from a bundle")]
     ListPacks(DemoListPacksArgs),
+    #[command(about = "Inspect a provider pack archive")]
+    Pack(DemoPackCommand),
+    #[command(about = "Emit the dependency graph of a bundle's packs, capabilities and flows")]
+    Graph(DemoGraphArgs),
+    #[command(about = "Generate files that run a demo bundle on a server")]
+    Export(DemoExportCommand),
     #[command(about = "List flows declared by a pack")]
     ListFlows(DemoListFlowsArgs),
     #[command(
@@ -136,11 +628,34 @@ enum DemoSubcommand {
     SetupWizard(DemoSetupWizardArgs),
 }
 
-#[derive(Clone, Copy, Debug, ValueEnum)]
-enum DomainArg {
-    Messaging,
-    Events,
-    Secrets,
+/// `--domain` value: messaging, events, secrets, or a domain declared under
+/// `domains:` in the bundle's greentic.demo.yaml, resolved once the bundle is known.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct DomainArg(String);
+
+impl std::str::FromStr for DomainArg {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim().to_ascii_lowercase();
+        if value.is_empty() {
+            return Err("domain must not be empty".to_string());
+        }
+        Ok(Self(value))
+    }
+}
+
+impl DomainArg {
+    fn resolve(&self, bundle: &Path) -> anyhow::Result<Domain> {
+        domains::register_bundle_domains(bundle)?;
+        domains::parse_domain(&self.0).ok_or_else(|| {
+            anyhow!(
+                "unknown domain {}; declare it under domains: in {}",
+                self.0,
+                bundle.join("greentic.demo.yaml").display()
+            )
+        })
+    }
 }
 
 #[derive(Clone, Copy, Debug, ValueEnum)]
@@ -150,6 +665,61 @@ enum PlanFormat {
     Yaml,
 }
 
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum ListFormat {
+    Text,
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum GraphFormat {
+    Dot,
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum ConfigFormat {
+    Yaml,
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum FanoutArg {
+    Serial,
+    Parallel,
+}
+
+impl From<FanoutArg> for api::Fanout {
+    fn from(value: FanoutArg) -> Self {
+        match value {
+            FanoutArg::Serial => api::Fanout::Serial,
+            FanoutArg::Parallel => api::Fanout::Parallel,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum HttpTransportArg {
+    Pack,
+    Operator,
+}
+
+impl From<HttpTransportArg> for config::HttpTransportKind {
+    fn from(value: HttpTransportArg) -> Self {
+        match value {
+            HttpTransportArg::Pack => config::HttpTransportKind::Pack,
+            HttpTransportArg::Operator => config::HttpTransportKind::Operator,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum RunStatusArg {
+    Success,
+    Failed,
+    Denied,
+}
+
 #[derive(Clone, Copy, Debug, ValueEnum)]
 enum CloudflaredModeArg {
     On,
@@ -177,7 +747,7 @@ enum RestartTarget {
 #[command(
     about = "Build a portable demo bundle.",
     long_about = "Copies packs/providers/tenants and writes resolved manifests under the output directory.",
-    after_help = "Main options:\n  --out <DIR>\n\nOptional options:\n  --tenant <TENANT>\n  --team <TEAM>\n  --allow-pack-dirs\n  --only-used-providers\n  --doctor\n  --skip-doctor\n  --project-root <PATH> (default: current directory)"
+    after_help = "Main options:\n  --out <DIR>\n\nOptional options:\n  --tenant <TENANT>\n  --team <TEAM>\n  --allow-pack-dirs\n  --only-used-providers\n  --doctor\n  --skip-doctor\n  --project-root <PATH> (default: current directory)\n  --verbose | --quiet"
 )]
 struct DemoBuildArgs {
     #[arg(long)]
@@ -196,6 +766,12 @@ struct DemoBuildArgs {
     skip_doctor: bool,
     #[arg(long)]
     project_root: Option<PathBuf>,
+    /// List every copied pack, not only warnings.
+    #[arg(long, conflicts_with = "quiet")]
+    verbose: bool,
+    /// Hide progress and summaries; warnings and errors are still printed.
+    #[arg(long, conflicts_with = "verbose")]
+    quiet: bool,
 }
 
 #[derive(Parser)]
@@ -210,14 +786,20 @@ struct DemoUpArgs {
         help = "Path to the bundle directory to run in bundle mode."
     )]
     bundle: Option<PathBuf>,
+    #[arg(
+        long,
+        requires = "bundle",
+        help_heading = "Optional options",
+        help = "Run --bundle as a read-only template and keep state, logs, secrets, and resolved manifests in this directory."
+    )]
+    instance: Option<PathBuf>,
     #[arg(
         long = "domains",
         alias = "domain",
-        value_enum,
         value_delimiter = ',',
         default_value = "all",
         help_heading = "Optional options",
-        help = "Domain(s) to operate on (messaging, events, secrets, all); defaults to auto-detect from the bundle."
+        help = "Domain(s) to operate on (messaging, events, secrets, a custom domain, all); defaults to auto-detect from the bundle."
     )]
     domain: DemoSetupDomainArg,
     #[arg(
@@ -257,7 +839,7 @@ struct DemoUpArgs {
         value_enum,
         default_value_t = NatsModeArg::Off,
         help_heading = "Optional options",
-        help = "Selects the NATS mode: off (default), on (legacy local NATS), or external (explicit URL)."
+        help = "Selects the NATS mode: off (default), on (legacy local NATS), external (explicit URL), or jetstream (durable per-tenant streams)."
     )]
     nats: NatsModeArg,
     #[arg(
@@ -287,6 +869,13 @@ struct DemoUpArgs {
         help = "Explicit path to the cloudflared binary used when cloudflared mode is on."
     )]
     cloudflared_binary: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "NAME",
+        help_heading = "Optional options",
+        help = "Run the named tunnel from services.cloudflared.tunnels instead of a quick tunnel, keeping a stable hostname."
+    )]
+    cloudflared_tunnel: Option<String>,
     #[arg(long, value_enum, default_value_t = NgrokModeArg::Off, help_heading = "Optional options", help = "Whether to start ngrok for webhook tunneling (alternative to cloudflared).")]
     ngrok: NgrokModeArg,
     #[arg(
@@ -373,47 +962,85 @@ struct DemoUpArgs {
         conflicts_with = "verbose"
     )]
     quiet: bool,
-}
-
-#[derive(Clone, Copy, Debug, ValueEnum)]
-enum DemoSetupDomainArg {
-    Messaging,
-    Events,
-    Secrets,
-    #[value(alias = "auto")]
-    All,
-}
-
-#[derive(Clone, Copy, Debug, ValueEnum)]
-enum NatsModeArg {
-    Off,
-    On,
-    External,
-}
-
-impl DemoSetupDomainArg {
-    fn resolve_domains(self, discovery: Option<&discovery::DiscoveryResult>) -> Vec<Domain> {
-        match self {
-            DemoSetupDomainArg::Messaging => vec![Domain::Messaging],
-            DemoSetupDomainArg::Events => vec![Domain::Events],
-            DemoSetupDomainArg::Secrets => vec![Domain::Secrets],
-            DemoSetupDomainArg::All => {
-                let mut enabled = Vec::new();
-                let has_messaging = discovery
-                    .map(|value| value.domains.messaging)
-                    .unwrap_or(true);
-                let has_events = discovery.map(|value| value.domains.events).unwrap_or(true);
-                if has_messaging {
-                    enabled.push(Domain::Messaging);
-                }
-                if has_events {
-                    enabled.push(Domain::Events);
-                }
-                enabled.push(Domain::Secrets);
-                enabled
-            }
-        }
-    }
+    #[arg(
+        long,
+        help_heading = "Optional options",
+        help = "Fail instead of falling back to GREENTIC_SECRET__* env vars when the secrets backend cannot be opened."
+    )]
+    strict_secrets: bool,
+    #[arg(
+        long,
+        value_name = "PORT",
+        help_heading = "Optional options",
+        help = "Gateway (HTTP ingress) port, overriding services.gateway.port."
+    )]
+    gateway_port: Option<u16>,
+    #[arg(
+        long,
+        value_name = "PORT",
+        help_heading = "Optional options",
+        help = "Port for the NATS server spawned by --nats=on|jetstream."
+    )]
+    nats_port: Option<u16>,
+    #[arg(
+        long,
+        help_heading = "Optional options",
+        help = "Pick the next free port instead of failing when the gateway or NATS port is taken."
+    )]
+    auto_port: bool,
+    #[arg(
+        long,
+        value_name = "PORT",
+        help_heading = "Optional options",
+        help = "Serve the local control API (status, logs, send, run, policy) on 127.0.0.1:<PORT>; 0 picks a free port."
+    )]
+    control_port: Option<u16>,
+    #[arg(
+        long,
+        help_heading = "Optional options",
+        help = "Run without a terminal, e.g. in a container: the gateway listens on all interfaces (0.0.0.0)."
+    )]
+    headless: bool,
+}
+
+#[derive(Clone, Debug)]
+enum DemoSetupDomainArg {
+    /// `all` or `auto`.
+    All,
+    One(DomainArg),
+}
+
+impl std::str::FromStr for DemoSetupDomainArg {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "all" | "auto" => Ok(Self::All),
+            _ => value.parse().map(Self::One),
+        }
+    }
+}
+
+impl DemoSetupDomainArg {
+    fn resolve_domains(
+        &self,
+        bundle: &Path,
+        discovery: &discovery::DiscoveryResult,
+    ) -> anyhow::Result<Vec<Domain>> {
+        match self {
+            DemoSetupDomainArg::All => Ok(api::discovered_domains(discovery)),
+            DemoSetupDomainArg::One(domain) => Ok(vec![domain.resolve(bundle)?]),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum NatsModeArg {
+    Off,
+    On,
+    External,
+    #[value(name = "jetstream")]
+    JetStream,
 }
 
 impl From<NatsModeArg> for demo::NatsMode {
@@ -422,6 +1049,7 @@ impl From<NatsModeArg> for demo::NatsMode {
             NatsModeArg::Off => demo::NatsMode::Off,
             NatsModeArg::On => demo::NatsMode::On,
             NatsModeArg::External => demo::NatsMode::External,
+            NatsModeArg::JetStream => demo::NatsMode::JetStream,
         }
     }
 }
@@ -430,7 +1058,7 @@ impl From<NatsModeArg> for demo::NatsMode {
 #[command(
     about = "Run provider setup flows against a demo bundle.",
     long_about = "Executes setup flows for provider packs included in the bundle.",
-    after_help = "Main options:\n  --bundle <DIR>\n  --tenant <TENANT>\n\nOptional options:\n  --team <TEAM>\n  --domain <messaging|events|secrets|all> (default: all)\n  --provider <FILTER>\n  --dry-run\n  --format <text|json|yaml> (default: text)\n  --parallel <N> (default: 1)\n  --allow-missing-setup\n  --allow-contract-change\n  --backup\n  --online\n  --secrets-env <ENV>\n  --skip-secrets-init\n  --setup-input <PATH>\n  --runner-binary <PATH>\n  --best-effort"
+    after_help = "Main options:\n  --bundle <DIR>\n  --tenant <TENANT>\n\nOptional options:\n  --team <TEAM>\n  --domain <messaging|events|secrets|CUSTOM|all> (default: all)\n  --provider <FILTER>\n  --dry-run\n  --format <text|json|yaml> (default: text)\n  --parallel <N> (default: 1)\n  --allow-missing-setup\n  --allow-contract-change\n  --backup\n  --online\n  --secrets-env <ENV>\n  --skip-secrets-init\n  --setup-input <PATH>\n  --adopt\n  --runner-binary <PATH>\n  --verbose | --quiet\n  --no-stream\n  --fail-fast | --best-effort"
 )]
 struct DemoSetupArgs {
     #[arg(long)]
@@ -439,7 +1067,7 @@ struct DemoSetupArgs {
     tenant: String,
     #[arg(long)]
     team: Option<String>,
-    #[arg(long, value_enum, default_value_t = DemoSetupDomainArg::All)]
+    #[arg(long, default_value = "all")]
     domain: DemoSetupDomainArg,
     #[arg(long)]
     provider: Option<String>,
@@ -467,14 +1095,268 @@ struct DemoSetupArgs {
     runner_binary: Option<PathBuf>,
     #[arg(long)]
     setup_input: Option<PathBuf>,
+    /// Import webhook registrations the provider already has and skip re-creating them.
+    #[arg(long, conflicts_with = "dry_run")]
+    adopt: bool,
+    /// Print every flow's input and result, not only warnings and failures.
+    #[arg(long, conflicts_with = "quiet")]
+    verbose: bool,
+    /// Hide progress and summaries; warnings and errors are still printed.
+    #[arg(long, conflicts_with = "verbose")]
+    quiet: bool,
+    /// Show each flow's output only when it finishes instead of its progress events as they arrive.
+    #[arg(long)]
+    no_stream: bool,
+    #[command(flatten)]
+    failure: FailureModeArgs,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Create a tenant/team, allow packs, seed secrets, and run setup in one step.",
+    long_about = "Creates the tenant and team, allows each pack in their gmap, writes the setup answers from --setup-input to the dev secrets store, re-resolves the bundle, and runs the packs' setup flows. The setup input is checked against every pack first. If a later step fails, the tenant/team directories, gmap, dev secrets store, and resolved manifests are restored; changes a setup flow made at the provider are not.",
+    after_help = "Main options:\n  --bundle <DIR>\n  --tenant <TENANT>\n  --packs <PACK,...>\n\nOptional options:\n  --team <TEAM>\n  --setup-input <PATH>\n  --runner-binary <PATH>\n  --format <text|json> (default: text)\n\nExample:\n  greentic-operator demo onboard --bundle . --tenant acme --team sales --packs telegram,slack --setup-input acme.yaml"
+)]
+struct DemoOnboardArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long)]
+    tenant: String,
+    #[arg(long)]
+    team: Option<String>,
+    /// Provider packs to allow and set up: full ids or the name after the domain prefix.
+    #[arg(long, value_delimiter = ',', required = true)]
+    packs: Vec<String>,
+    /// Setup answers (JSON or YAML), shared or keyed by provider id.
+    #[arg(long)]
+    setup_input: Option<PathBuf>,
+    #[arg(long)]
+    runner_binary: Option<PathBuf>,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Remove a tenant, its provider-side subscriptions, webhooks, and secrets.",
+    long_about = "Deletes the tenant's subscriptions through their providers, removes its webhooks where the provider has a delete_webhook op, and deletes its secrets from an external secrets backend. Then removes its directories and gmaps, its resolved manifests and their history, and its dev-store secrets from the bundle. The provider-side steps stop at the first failure, before anything is removed locally. An audit record lists every action.",
+    after_help = "Main options:\n  --bundle <DIR>\n  --tenant <TENANT>\n\nOptional options:\n  --dry-run\n  --keep-remote\n  --runner-binary <PATH>\n  --format <text|json> (default: text)\n\nExample:\n  greentic-operator demo offboard --bundle . --tenant acme --dry-run"
+)]
+struct DemoOffboardArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long)]
+    tenant: String,
+    /// List what would be removed without removing anything.
+    #[arg(long)]
+    dry_run: bool,
+    /// Leave provider-side subscriptions, webhooks, and external secrets in place.
+    #[arg(long)]
+    keep_remote: bool,
     #[arg(long)]
+    runner_binary: Option<PathBuf>,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+/// `--fail-fast`/`--best-effort`, shared by every command that runs several provider ops.
+/// Fail-fast is the default: the first failure stops the command and sets the exit code.
+#[derive(Args, Clone, Copy, Debug, Default)]
+pub(crate) struct FailureModeArgs {
+    #[arg(
+        long,
+        conflicts_with = "best_effort",
+        help = "Stop at the first failure (default)."
+    )]
+    fail_fast: bool,
+    #[arg(
+        long,
+        help = "Report failures and keep going; exit 0 unless the command itself cannot run."
+    )]
     best_effort: bool,
 }
 
+impl FailureModeArgs {
+    pub(crate) fn best_effort(self) -> bool {
+        self.best_effort && !self.fail_fast
+    }
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Run provider verify flows against a demo bundle.",
+    long_about = "Runs the domain's verify flows plus the ones each pack declares in meta.verify_flows, for every discovered provider, and reports pass/fail per provider. Every flow runs even after a failure; the command exits non-zero when any failed.",
+    after_help = "Main options:\n  --bundle <DIR>\n\nOptional options:\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)\n  --domain <messaging|events|secrets|CUSTOM|all> (default: all)\n  --provider <PROVIDER>\n  --runner-binary <PATH>\n  --format <text|json> (default: text)"
+)]
+struct DemoVerifyArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+    #[arg(long, default_value = "all")]
+    domain: DemoSetupDomainArg,
+    #[arg(long, help = "Only this provider id, pack id or pack file stem.")]
+    provider: Option<String>,
+    #[arg(long)]
+    runner_binary: Option<PathBuf>,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+#[command(about = "Measure the operator's own overhead.")]
+struct DemoBenchCommand {
+    #[command(subcommand)]
+    command: DemoBenchSubcommand,
+}
+
+#[derive(Subcommand)]
+enum DemoBenchSubcommand {
+    Invoke(DemoBenchInvokeArgs),
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Call one provider op repeatedly and report latency percentiles.",
+    long_about = "Invokes the op through the same runner host as demo send, after --warmup unmeasured calls, and prints min/p50/p95/max latency with the mean time per call spent spawning the runner or loading the pack, encoding payloads, on disk, inside the runner, and elsewhere in the operator. Every call writes a run record, like any other invocation.",
+    after_help = "Main options:\n  --bundle <DIR>\n  --provider <PROVIDER>\n  --op <OP>\n\nOptional options:\n  --iterations <N> (default: 50)\n  --warmup <N> (default: 2)\n  --payload-json <JSON> (default: {})\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)\n  --domain <DOMAIN> (default: messaging)\n  --runner-binary <PATH>\n  --format <text|json> (default: text)"
+)]
+struct DemoBenchInvokeArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long, help = "Provider id or pack id.")]
+    provider: String,
+    #[arg(
+        long,
+        help = "Op or flow to call; pick one that does no work of its own, such as requirements."
+    )]
+    op: String,
+    #[arg(long, default_value_t = 50, help = "Measured calls.")]
+    iterations: usize,
+    #[arg(long, default_value_t = 2, help = "Unmeasured calls made first.")]
+    warmup: usize,
+    #[arg(long, default_value = "{}")]
+    payload_json: String,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+    #[arg(long, default_value = "messaging")]
+    domain: DomainArg,
+    #[arg(long)]
+    runner_binary: Option<PathBuf>,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Emit the dependency graph of a demo bundle.",
+    long_about = "Links packs to the packs they depend on (meta.depends_on and providers.<id>.depends_on), to the capabilities they offer and require, and their flows to the packs and flows they call. Each capability points at the offer it resolves to for the given tenant/team/env. References the bundle cannot satisfy are drawn as missing nodes. Render the dot output with Graphviz, e.g. `| dot -Tsvg > bundle.svg`.",
+    after_help = "Main options:\n  --bundle <DIR>\n\nOptional options:\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)\n  --env <ENV>\n  --domain <messaging|events|secrets|CUSTOM|all> (default: all)\n  --format <dot|json> (default: dot)"
+)]
+struct DemoGraphArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+    #[arg(long)]
+    env: Option<String>,
+    #[arg(long, default_value = "all")]
+    domain: DemoSetupDomainArg,
+    #[arg(long, value_enum, default_value_t = GraphFormat::Dot)]
+    format: GraphFormat,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Generate files that run a demo bundle on a server.",
+    long_about = "The generated files run the operator image with demo start --headless and the bundle mounted into the container, so the server needs no Greentic toolchain."
+)]
+struct DemoExportCommand {
+    #[command(subcommand)]
+    command: DemoExportSubcommand,
+}
+
+#[derive(Subcommand)]
+enum DemoExportSubcommand {
+    Compose(DemoExportComposeArgs),
+    K8s(DemoExportK8sArgs),
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Write a Docker Compose file for a demo bundle.",
+    long_about = "Writes services for the operator (demo start --headless, bundle mounted at /bundle, gateway port published), a NATS server the operator connects to, and with --cloudflared a connector for a named Cloudflare tunnel. The bundle is mounted by a path relative to the output file when it lies under the output file's directory.",
+    after_help = "Main options:\n  --bundle <DIR>\n\nOptional options:\n  --out <FILE> (default: docker-compose.yaml)\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)\n  --image <IMAGE>\n  --env <ENV> (default: demo)\n  --cloudflared\n\nExample:\n  greentic-operator demo export compose --bundle demo-bundle --out docker-compose.yaml"
+)]
+struct DemoExportComposeArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long, default_value = "docker-compose.yaml")]
+    out: PathBuf,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+    /// Operator image; defaults to the release image of this version.
+    #[arg(long)]
+    image: Option<String>,
+    /// Environment used for secrets lookups.
+    #[arg(long, default_value = "demo")]
+    env: String,
+    /// Add a cloudflared connector for a named tunnel; needs CLOUDFLARED_TUNNEL_TOKEN and PUBLIC_BASE_URL when started.
+    #[arg(long)]
+    cloudflared: bool,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Write Kubernetes manifests for a demo bundle.",
+    long_about = "Writes a ConfigMap with greentic.demo.yaml, a Secret whose keys become env vars of the operator (GREENTIC_SECRET__* fallbacks, the dev store passphrase, cloud credentials), a Deployment running the operator (demo start --headless) and NATS in one pod, and a Service. With --host it adds an Ingress for the webhook routes and passes https://<host> as the public base URL. The bundle is mounted from --pvc, or else must be baked into the image at /bundle.",
+    after_help = "Main options:\n  --bundle <DIR>\n\nOptional options:\n  --out <FILE> (default: k8s.yaml)\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)\n  --image <IMAGE>\n  --env <ENV> (default: demo)\n  --name <NAME> (default: greentic-demo)\n  --namespace <NAMESPACE>\n  --pvc <CLAIM>\n  --host <HOST>\n  --ingress-class <CLASS>\n  --tls-secret <SECRET>\n\nExample:\n  greentic-operator demo export k8s --bundle demo-bundle --pvc demo-bundle --host demo.example.com"
+)]
+struct DemoExportK8sArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long, default_value = "k8s.yaml")]
+    out: PathBuf,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+    /// Operator image; defaults to the release image of this version.
+    #[arg(long)]
+    image: Option<String>,
+    /// Environment used for secrets lookups.
+    #[arg(long, default_value = "demo")]
+    env: String,
+    /// Prefix of the generated object names.
+    #[arg(long, default_value = "greentic-demo")]
+    name: String,
+    #[arg(long)]
+    namespace: Option<String>,
+    /// PersistentVolumeClaim holding the bundle; without it the image must contain the bundle at /bundle.
+    #[arg(long, value_name = "CLAIM")]
+    pvc: Option<String>,
+    /// Public host for the webhook Ingress; no Ingress is written without it.
+    #[arg(long)]
+    host: Option<String>,
+    #[arg(long, requires = "host")]
+    ingress_class: Option<String>,
+    /// TLS secret for the Ingress host.
+    #[arg(long, requires = "host")]
+    tls_secret: Option<String>,
+}
+
 #[derive(Parser)]
 #[command(
     long_about = "Updates the demo bundle's gmap, reruns the resolver, and copies the updated manifest so demo start sees the change immediately.",
-    after_help = "Main options:\n  --bundle <DIR>\n  --tenant <TENANT>\n  --path <PACK[/FLOW[/NODE]] (up to 3 segments)\n\nOptional options:\n  --team <TEAM>\n\nPaths use the same PACK[/FLOW[/NODE]] syntax as the dev allow/forbid commands (max 3 segments). The command modifies tenants/<tenant>[/teams/<team>]/(tenant|team).gmap, resolves state/resolved/<tenant>[.<team>].yaml, and overwrites resolved/<tenant>[.<team>].yaml so demo start picks it up without a rebuild."
+    after_help = "Main options:\n  --bundle <DIR>\n  --tenant <TENANT>\n  --path <PACK[/FLOW[/NODE]] (up to 3 segments)\n\nOptional options:\n  --team <TEAM>\n  --dry-run\n\nPaths use the same PACK[/FLOW[/NODE]] syntax as the dev allow/forbid commands (max 3 segments). The command modifies tenants/<tenant>[/teams/<team>]/(tenant|team).gmap, resolves state/resolved/<tenant>[.<team>].yaml, and overwrites resolved/<tenant>[.<team>].yaml so demo start picks it up without a rebuild. With --dry-run nothing is written; the command prints the rule it would write, the flows that would become allowed or forbidden, and every value of the resolved manifest that would change."
 )]
 struct DemoPolicyArgs {
     #[arg(long, help = "Path to the demo bundle directory.")]
@@ -485,15 +1367,49 @@ struct DemoPolicyArgs {
     team: Option<String>,
     #[arg(long, help = "Gmap path to allow or forbid.")]
     path: String,
+    #[arg(
+        long,
+        help = "Preview the gmap rule and resolved manifest changes without writing."
+    )]
+    dry_run: bool,
+}
+
+#[derive(Parser)]
+struct DemoPolicyCommand {
+    #[command(subcommand)]
+    command: DemoPolicySubcommand,
+}
+
+#[derive(Subcommand)]
+enum DemoPolicySubcommand {
+    Rollback(DemoPolicyRollbackArgs),
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Restore the previously published resolved manifest of a tenant/team.",
+    long_about = "Every allow/forbid or reload that changes resolved/<tenant>[.<team>].yaml keeps the manifest it replaced under state/resolved/history/<tenant>[.<team>]/ (the last 10). Rollback restores the newest of those to both resolved/ and state/resolved/ and drops it from the history, so running it again steps further back. The gmaps are not changed; the next allow/forbid re-resolves from them.",
+    after_help = "Main options:\n  --bundle <DIR>\n  --tenant <TENANT>\n\nOptional options:\n  --team <TEAM>"
+)]
+struct DemoPolicyRollbackArgs {
+    #[arg(long, help = "Path to the demo bundle directory.")]
+    bundle: PathBuf,
+    #[arg(long, help = "Tenant whose manifest to restore.")]
+    tenant: String,
+    #[arg(long, help = "Team whose manifest to restore.")]
+    team: Option<String>,
 }
 
 #[derive(Parser)]
 #[command(
     about = "Plan/create a demo bundle with pack refs and allow rules.",
-    long_about = "Builds a deterministic wizard plan first. Execution reuses the same gmap + resolver + resolved-copy lifecycle as demo allow.",
-    after_help = "Main options:\n  --mode <create|update|remove>\n  --bundle <DIR> (or provide in --qa-answers)\n\nOptional options:\n  --qa-answers <PATH>\n  --catalog-pack <ID> (repeatable)\n  --pack-ref <REF> (repeatable, oci://|repo://|store://)\n  --provider-registry <REF>\n  --locale <TAG> (default: detected from system locale)\n  --tenant <TENANT> (default: demo)\n  --team <TEAM>\n  --target <tenant[:team]> (repeatable)\n  --allow <PACK[/FLOW[/NODE]]> (repeatable)\n  --execute\n  --dry-run\n  --offline\n  --verbose\n  --run-setup"
+    long_about = "Builds a deterministic wizard plan first. Execution reuses the same gmap + resolver + resolved-copy lifecycle as demo allow. With --spec, the plan comes from a declarative bundle spec (packs, tenants/teams, access matrix, defaults, providers) and executes without QA or prompts; add --dry-run to only print it.",
+    after_help = "Main options:\n  --mode <create|update|remove>\n  --bundle <DIR> (or provide in --qa-answers or --spec)\n\nOptional options:\n  --spec <PATH>\n  --qa-answers <PATH>\n  --catalog-pack <ID> (repeatable)\n  --pack-ref <REF> (repeatable, oci://|repo://|store://)\n  --provider-registry <REF>\n  --registry-mirror <DIR> (default: $GTC_PROVIDER_REGISTRY_MIRROR)\n  --locale <TAG> (default: detected from system locale)\n  --tenant <TENANT> (default: demo)\n  --team <TEAM>\n  --target <tenant[:team]> (repeatable)\n  --allow <PACK[/FLOW[/NODE]]> (repeatable)\n  --execute\n  --dry-run\n  --offline\n  --verbose | --quiet\n  --run-setup\n  --fail-fast | --best-effort\n\nTranslations:\n  wizard i18n check --locale <TAG>\n  wizard i18n export --locale <TAG> [--out <CSV>]\n  wizard i18n import <CSV> --locale <TAG>",
+    args_conflicts_with_subcommands = true
 )]
 struct DemoWizardArgs {
+    #[command(subcommand)]
+    command: Option<DemoWizardSubcommand>,
     #[arg(long, value_enum, default_value_t = WizardModeArg::Create)]
     mode: WizardModeArg,
     #[arg(long, help = "Path to the demo bundle to create.")]
@@ -503,6 +1419,13 @@ struct DemoWizardArgs {
         help = "Optional JSON/YAML answers emitted by greentic-qa."
     )]
     qa_answers: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["qa_answers", "catalog_packs", "pack_refs", "targets", "allow_paths", "execute"],
+        help = "Declarative bundle spec (YAML/JSON); builds and executes the plan without QA."
+    )]
+    spec: Option<PathBuf>,
     #[arg(
         long = "catalog-pack",
         help = "Catalog pack id to include (repeatable)."
@@ -520,6 +1443,12 @@ struct DemoWizardArgs {
         help = "Provider registry override (file://<path> or local path)."
     )]
     provider_registry: Option<String>,
+    #[arg(
+        long = "registry-mirror",
+        value_name = "DIR",
+        help = "Directory written by registry mirror; supplies the catalog and its packs without network access."
+    )]
+    registry_mirror: Option<PathBuf>,
     #[arg(long, default_value = "demo", help = "Tenant for allow rules.")]
     tenant: String,
     #[arg(long, help = "Optional team for allow rules.")]
@@ -550,12 +1479,88 @@ struct DemoWizardArgs {
     offline: bool,
     #[arg(long, help = "Locale tag for wizard QA rendering.")]
     locale: Option<String>,
-    #[arg(long, help = "Print detailed plan step fields.")]
+    #[arg(
+        long,
+        conflicts_with = "quiet",
+        help = "Print detailed plan step fields."
+    )]
     verbose: bool,
+    #[arg(
+        long,
+        conflicts_with = "verbose",
+        help = "Hide progress bars and summaries."
+    )]
+    quiet: bool,
     #[arg(long, help = "Run existing provider setup flows after execution.")]
     run_setup: bool,
     #[arg(long, help = "Optional JSON/YAML setup-input passed to setup runner.")]
     setup_input: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Execute an update even if it overwrites or deletes manual edits found in the bundle."
+    )]
+    overwrite_drift: bool,
+    #[command(flatten)]
+    failure: FailureModeArgs,
+}
+
+#[derive(Subcommand)]
+enum DemoWizardSubcommand {
+    #[command(about = "Review the wizard's translations for a locale.")]
+    I18n(WizardI18nCommand),
+}
+
+#[derive(Parser)]
+struct WizardI18nCommand {
+    #[command(subcommand)]
+    command: WizardI18nSubcommand,
+}
+
+#[derive(Subcommand)]
+enum WizardI18nSubcommand {
+    #[command(
+        about = "Report wizard prompts that fall back to English or have mismatched {} placeholders."
+    )]
+    Check(WizardI18nCheckArgs),
+    #[command(about = "Write wizard prompts that need translating to CSV.")]
+    Export(WizardI18nExportArgs),
+    #[command(about = "Merge a translated CSV into the locale's i18n files.")]
+    Import(WizardI18nImportArgs),
+}
+
+#[derive(Parser)]
+struct WizardI18nCheckArgs {
+    /// Locale to review, e.g. nl or pt-BR.
+    #[arg(long)]
+    locale: String,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+struct WizardI18nExportArgs {
+    /// Locale to review, e.g. nl or pt-BR.
+    #[arg(long)]
+    locale: String,
+    /// CSV file to write (default: stdout).
+    #[arg(long, value_name = "PATH")]
+    out: Option<PathBuf>,
+    /// Export every entry, not only those that need work.
+    #[arg(long)]
+    all: bool,
+}
+
+#[derive(Parser)]
+struct WizardI18nImportArgs {
+    /// CSV from wizard i18n export with the translation column filled in.
+    #[arg(value_name = "CSV")]
+    csv: PathBuf,
+    /// Locale the CSV is translated into, e.g. nl or pt-BR.
+    #[arg(long)]
+    locale: String,
+    /// Directory holding operator_wizard/ and operator_cli/.
+    #[arg(long, value_name = "DIR", default_value = "i18n")]
+    i18n_dir: PathBuf,
 }
 
 #[derive(Parser)]
@@ -707,11 +1712,24 @@ enum WizardAccessChangeAnswer {
 }
 
 const DEFAULT_PROVIDER_REGISTRY_REF: &str = "oci://ghcr.io/greenticai/registries/providers:latest";
+#[derive(Parser)]
+#[command(
+    about = "Reload greentic.demo.yaml and gmaps in a running demo start.",
+    long_about = "Asks the demo start serving the bundle to re-read greentic.demo.yaml, re-resolve the gmaps into resolved manifests, and restart the HTTP ingress, timer scheduler, and Kafka bridge with the new config. NATS and the tunnels keep running. Goes through the control API when demo start has --control-port, otherwise sends SIGHUP to the demo start process.",
+    after_help = "Main options:\n  --bundle <DIR>\n\nOptional options:\n  --format <text|json> (default: text)"
+)]
+struct DemoReloadArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
 #[derive(Parser)]
 #[command(
     about = "Show demo service status using runtime state.",
-    long_about = "Lists pidfiles under state/pids for the selected tenant/team.",
-    after_help = "Main options:\n  (none)\n\nOptional options:\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)\n  --state-dir <PATH> (default: ./state or <bundle>/state)\n  --bundle <DIR> (legacy mode if --state-dir omitted)\n  --verbose\n  --no-nats"
+    long_about = "Lists pidfiles under state/pids for the selected tenant/team. With --deep, also reports each provider's pack digest, setup state, secret completeness, last successful send/ingress, and subscription count from the bundle's state files.",
+    after_help = "Main options:\n  (none)\n\nOptional options:\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)\n  --state-dir <PATH> (default: ./state or <bundle>/state)\n  --bundle <DIR> (legacy mode if --state-dir omitted)\n  --verbose\n  --json\n  --deep\n  --no-nats"
 )]
 struct DemoStatusArgs {
     #[arg(long)]
@@ -724,8 +1742,14 @@ struct DemoStatusArgs {
     state_dir: Option<PathBuf>,
     #[arg(long)]
     verbose: bool,
+    /// Print status as JSON.
     #[arg(long)]
-    no_nats: bool,
+    json: bool,
+    /// Include per-provider health (setup, secrets, last send/ingress, subscriptions).
+    #[arg(long)]
+    deep: bool,
+    #[arg(long)]
+    no_nats: bool,
 }
 
 #[derive(Parser)]
@@ -764,11 +1788,54 @@ struct DemoDoctorArgs {
     bundle: PathBuf,
 }
 
+#[derive(Parser)]
+#[command(
+    about = "Compare two bundles, or a bundle against its project root.",
+    long_about = "Diffs pack digests, provider lists, tenants and gmap rules, and resolved manifests between two directories. Either side may be a demo bundle or a project root. Useful for checking what wizard --mode update changed.",
+    after_help = "Main options:\n  <LEFT>\n  <RIGHT>\n\nOptional options:\n  --format <text|json> (default: text)\n  --exit-code"
+)]
+struct DemoDiffArgs {
+    #[arg(value_name = "LEFT")]
+    left: PathBuf,
+    #[arg(value_name = "RIGHT")]
+    right: PathBuf,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+    #[arg(long, help = "Exit with status 1 when the two sides differ.")]
+    exit_code: bool,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Run a bundle's ingress and timer test cases offline.",
+    long_about = "Each case in the spec sends one ingress request (or fires one timer handler) through ingest_http, the app flow, render_plan, and encode, then checks the HTTP status, the encoded provider payloads, and the outcome. Nothing is sent to the provider and capabilities answer from recordings.",
+    after_help = "Main options:\n  --bundle <DIR>\n  --spec <FILE>\n\nOptional options:\n  --case <NAME>...\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)\n  --app-pack <PACK>\n  --format <text|json> (default: text)"
+)]
+struct DemoTestArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long, value_name = "FILE")]
+    spec: PathBuf,
+    /// Only run the named case (repeatable).
+    #[arg(long = "case", value_name = "NAME")]
+    cases: Vec<String>,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+    #[arg(long)]
+    app_pack: Option<String>,
+    #[arg(long)]
+    runner_binary: Option<PathBuf>,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
 #[derive(Parser)]
 #[command(
     about = "Send a demo message via a provider pack.",
     long_about = "Runs provider requirements or sends a generic message payload.",
-    after_help = "Main options:\n  --bundle <DIR>\n  --provider <PROVIDER>\n\nOptional options:\n  --text <TEXT>\n  --card <FILE>\n  --arg <k=v>...\n  --args-json <JSON>\n  --env <ENV> (default: demo)\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)\n  --print-required-args"
+    after_help = "Main options:\n  --bundle <DIR>\n  --provider <PROVIDER>\n\nOptional options:\n  --text <TEXT>\n  --card <FILE>\n  --attach <FILE>...\n  --max-attachment-bytes <BYTES> (default: 10485760)\n  --to <[KIND:]ID|@NAME>...\n  --to-kind <KIND>\n  --fanout <serial|parallel> (default: serial)\n  --at <TIME>\n  --every <DURATION>\n  --count <N>\n  --dry-run\n  --transport <pack|operator>\n  --record <FILE> | --replay <FILE>\n  --proxy <URL>\n  --ca-cert <FILE>\n  --insecure-tls\n  --domain <messaging|events> (default: messaging)\n  --event-type <TYPE> (default: demo.event)\n  --arg <k=v>...\n  --args-json <JSON>\n  --env <ENV> (default: demo)\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)\n  --print-required-args\n  --strict-secrets\n  --fail-fast | --best-effort"
 )]
 struct DemoSendArgs {
     #[arg(long)]
@@ -791,7 +1858,16 @@ struct DemoSendArgs {
     runner_binary: Option<PathBuf>,
     #[arg(long, default_value = "demo")]
     env: String,
-    #[arg(long, help = "Destination identifier (repeatable).")]
+    #[arg(
+        long,
+        help = "Fail instead of falling back to GREENTIC_SECRET__* env vars when the secrets backend cannot be opened."
+    )]
+    strict_secrets: bool,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        help = "Destination id, kind:id, or @name from destinations.yaml (repeatable or comma-separated). Several destinations are sent to one by one."
+    )]
     to: Vec<String>,
     #[arg(
         long = "to-kind",
@@ -804,6 +1880,102 @@ struct DemoSendArgs {
         help = "JSON file containing the adaptive card to include in the message."
     )]
     card: Option<PathBuf>,
+    #[arg(
+        long = "attach",
+        value_name = "FILE",
+        help = "Image or document to attach to the message (repeatable)."
+    )]
+    attach: Vec<PathBuf>,
+    #[arg(
+        long,
+        value_name = "BYTES",
+        default_value_t = attachments::DEFAULT_MAX_ATTACHMENT_BYTES,
+        help = "Reject attachments larger than this before invoking the provider."
+    )]
+    max_attachment_bytes: u64,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = FanoutArg::Serial,
+        help = "With several --to destinations, send to them one at a time or all at once."
+    )]
+    fanout: FanoutArg,
+    #[arg(
+        long,
+        value_name = "TIME",
+        help = "Send later, at an RFC 3339 time (for example 2024-07-01T10:00Z), through the timer scheduler of `demo start`."
+    )]
+    at: Option<String>,
+    #[arg(
+        long,
+        value_name = "DURATION",
+        help = "Send repeatedly at this interval (90s, 5m, 2h, 1d), starting at --at or one interval from now."
+    )]
+    every: Option<String>,
+    #[arg(
+        long,
+        value_name = "N",
+        requires = "every",
+        help = "Stop after N sends; without it --every repeats until cancelled."
+    )]
+    count: Option<u32>,
+    #[arg(
+        long,
+        conflicts_with_all = ["at", "every"],
+        help = "Run render_plan and encode but not send_payload, and print the encoded payload and the HTTP request it describes (secrets redacted)."
+    )]
+    dry_run: bool,
+    #[arg(
+        long,
+        value_enum,
+        help = "Who makes the provider's HTTP request: the pack's send_payload, or the operator from the encoded payload (default: http.transport in greentic.demo.yaml, else pack)."
+    )]
+    transport: Option<HttpTransportArg>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with = "replay",
+        help = "Send through the operator transport and append each request/response to this cassette."
+    )]
+    record: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Answer sends from this cassette instead of the network."
+    )]
+    replay: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "URL",
+        help = "HTTP(S) proxy for operator transport sends (default: http.proxy, else HTTPS_PROXY/HTTP_PROXY)."
+    )]
+    proxy: Option<String>,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "PEM file with extra trusted root certificates for operator transport sends."
+    )]
+    ca_cert: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Skip TLS certificate verification for operator transport sends."
+    )]
+    insecure_tls: bool,
+    #[arg(
+        long,
+        default_value = "messaging",
+        help = "Provider domain: messaging, or events to publish one event through the provider's publish op."
+    )]
+    domain: DomainArg,
+    #[arg(
+        long,
+        value_name = "TYPE",
+        default_value = "demo.event",
+        help = "Event type of the event published with --domain events."
+    )]
+    event_type: String,
+    #[command(flatten)]
+    failure: FailureModeArgs,
 }
 
 #[derive(Parser)]
@@ -828,16 +2000,63 @@ struct DemoCapabilityCommand {
 
 #[derive(Subcommand)]
 enum DemoCapabilitySubcommand {
+    List(DemoCapabilityListArgs),
+    Resolve(DemoCapabilityResolveArgs),
     Invoke(DemoCapabilityInvokeArgs),
     SetupPlan(DemoCapabilitySetupPlanArgs),
     MarkReady(DemoCapabilityMarkReadyArgs),
     MarkFailed(DemoCapabilityMarkFailedArgs),
 }
 
+#[derive(Parser)]
+#[command(
+    about = "List capability offers and the packs providing them.",
+    long_about = "Groups offers by capability id in resolution order (ascending priority). `*` marks the offer that resolves for the given tenant/team/env.",
+    after_help = "Main options:\n  --bundle <DIR>\n\nOptional options:\n  --cap-id <CAP_ID>\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)\n  --env <ENV>\n  --format <text|json> (default: text)"
+)]
+struct DemoCapabilityListArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long)]
+    cap_id: Option<String>,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+    #[arg(long)]
+    env: Option<String>,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Show which offer a capability resolves to.",
+    long_about = "Resolves the capability for the tenant/team/env scope. With --explain, prints every candidate with the reason it won or was skipped.",
+    after_help = "Main options:\n  --bundle <DIR>\n  --cap-id <CAP_ID>\n\nOptional options:\n  --explain\n  --version <VERSION>\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)\n  --env <ENV>"
+)]
+struct DemoCapabilityResolveArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long)]
+    cap_id: String,
+    #[arg(long)]
+    version: Option<String>,
+    #[arg(long)]
+    explain: bool,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+    #[arg(long)]
+    env: Option<String>,
+}
+
 #[derive(Parser)]
 #[command(
     about = "Resolve and invoke a capability provider op.",
-    long_about = "Uses capability registry resolution and routes to the selected provider op."
+    long_about = "Uses capability registry resolution and routes to the selected provider op. --record stores the exchange for later --mock runs, which answer offline from the recording.",
+    after_help = "Main options:\n  --bundle <DIR>\n  --cap-id <CAP_ID>\n\nOptional options:\n  --op <OP>\n  --payload-json <JSON>\n  --record | --mock\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)\n  --env <ENV>"
 )]
 struct DemoCapabilityInvokeArgs {
     #[arg(long)]
@@ -854,6 +2073,12 @@ struct DemoCapabilityInvokeArgs {
     team: String,
     #[arg(long)]
     env: Option<String>,
+    /// Store the request/response under state/capability-recordings.
+    #[arg(long, conflicts_with = "mock")]
+    record: bool,
+    /// Answer from state/capability-recordings without calling the provider.
+    #[arg(long)]
+    mock: bool,
 }
 
 #[derive(Parser)]
@@ -924,6 +2149,21 @@ struct DemoRunArgs {
     flow: Option<String>,
     #[arg(long)]
     input: Option<String>,
+    /// Show nothing while the flow runs instead of its progress events.
+    #[arg(long)]
+    no_stream: bool,
+    /// Pause before this node runs; repeat for more nodes.
+    #[arg(long = "break", value_name = "NODE")]
+    breakpoints: Vec<String>,
+    /// Pause before every node.
+    #[arg(long)]
+    step: bool,
+    /// Run the flow once without the REPL and report the result as JSON.
+    #[arg(long, conflicts_with_all = ["breakpoints", "step"])]
+    no_repl: bool,
+    /// Write the --no-repl result to this file instead of stdout.
+    #[arg(long, value_name = "FILE", requires = "no_repl")]
+    output: Option<PathBuf>,
 }
 
 #[derive(Parser)]
@@ -934,7 +2174,7 @@ struct DemoRunArgs {
 struct DemoListPacksArgs {
     #[arg(long, default_value = ".")]
     bundle: PathBuf,
-    #[arg(long, value_enum, default_value_t = DomainArg::Messaging)]
+    #[arg(long, default_value = "messaging")]
     domain: DomainArg,
 }
 
@@ -948,60 +2188,206 @@ struct DemoListFlowsArgs {
     bundle: PathBuf,
     #[arg(long)]
     pack: String,
-    #[arg(long, value_enum, default_value_t = DomainArg::Messaging)]
+    #[arg(long, default_value = "messaging")]
     domain: DomainArg,
 }
 
+#[derive(Parser)]
+#[command(
+    about = "Manage named destinations in a demo bundle.",
+    long_about = "Maps friendly names to provider-specific chat/channel ids in destinations.yaml so demo send can use --to @name."
+)]
+struct DemoDestinationsCommand {
+    #[command(subcommand)]
+    command: DemoDestinationsSubcommand,
+}
+
 #[derive(Subcommand)]
-enum DemoSubscriptionsSubcommand {
-    Ensure(DemoSubscriptionsEnsureArgs),
-    Status(DemoSubscriptionsStatusArgs),
-    Renew(DemoSubscriptionsRenewArgs),
-    Delete(DemoSubscriptionsDeleteArgs),
+enum DemoDestinationsSubcommand {
+    Add(DemoDestinationsAddArgs),
+    List(DemoDestinationsListArgs),
+    Remove(DemoDestinationsRemoveArgs),
 }
 
 #[derive(Parser)]
 #[command(
-    about = "Ensure a subscription binding via a demo provider.",
-    long_about = "Invokes the provider's subscription_ensure flow, persists the binding state, and returns the binding_id."
+    about = "Add or replace a named destination for a provider.",
+    long_about = "Writes the provider-specific id (and optional kind) for NAME into destinations.yaml.",
+    after_help = "Main options:\n  --bundle <DIR>\n  --provider <PROVIDER>\n  --id <ID>\n  <NAME>\n\nOptional options:\n  --kind <KIND>"
 )]
-struct DemoSubscriptionsEnsureArgs {
+struct DemoDestinationsAddArgs {
     #[arg(long)]
     bundle: PathBuf,
+    #[arg(help = "Friendly name, referenced as @NAME.")]
+    name: String,
     #[arg(long)]
     provider: String,
-    #[arg(long, default_value = "demo")]
-    tenant: String,
-    #[arg(long, default_value = "default")]
-    team: String,
+    #[arg(
+        long,
+        help = "Provider-specific destination id (chat id, channel id, ...)."
+    )]
+    id: String,
+    #[arg(
+        long,
+        help = "Optional destination kind (chat, channel, room, email, etc.)."
+    )]
+    kind: Option<String>,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "List named destinations.",
+    long_about = "Prints every name/provider mapping stored in destinations.yaml."
+)]
+struct DemoDestinationsListArgs {
     #[arg(long)]
-    binding_id: Option<String>,
+    bundle: PathBuf,
     #[arg(long)]
-    resource: Option<String>,
-    #[arg(long = "change-type", action = ArgAction::Append)]
-    change_types: Vec<String>,
+    provider: Option<String>,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Remove a named destination.",
+    long_about = "Removes NAME entirely, or only its mapping for --provider."
+)]
+struct DemoDestinationsRemoveArgs {
     #[arg(long)]
-    notification_url: Option<String>,
+    bundle: PathBuf,
+    name: String,
     #[arg(long)]
-    client_state: Option<String>,
+    provider: Option<String>,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Validate or scaffold adaptive cards.",
+    long_about = "Checks cards before demo send --card, so a malformed card fails here with a pointer to the element instead of inside a provider's encode."
+)]
+struct DemoCardCommand {
+    #[command(subcommand)]
+    command: DemoCardSubcommand,
+}
+
+#[derive(Subcommand)]
+enum DemoCardSubcommand {
+    Validate(DemoCardValidateArgs),
+    New(DemoCardNewArgs),
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Validate an adaptive card, optionally against a bundle's providers.",
+    long_about = "Checks the card's structure against the schema version it declares. With --bundle, also runs each messaging provider's render_plan on the card and warns about schema versions and features the provider does not render. Errors fail the command; warnings do not.",
+    after_help = "Main options:\n  <FILE>\n\nOptional options:\n  --bundle <DIR>\n  --provider <PROVIDER>... (default: every messaging provider in the bundle)\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)\n  --format <text|json> (default: text)"
+)]
+struct DemoCardValidateArgs {
+    #[arg(value_name = "FILE")]
+    card: PathBuf,
     #[arg(long)]
-    user_id: Option<String>,
+    bundle: Option<PathBuf>,
+    #[arg(long = "provider", requires = "bundle")]
+    providers: Vec<String>,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
     #[arg(long)]
-    user_token_key: Option<String>,
+    runner_binary: Option<PathBuf>,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
 }
 
 #[derive(Parser)]
 #[command(
-    about = "List demo subscription bindings persisted by the operator.",
-    long_about = "Prints provider/tenant/team/binding info for demo-managed subscriptions."
+    about = "Write a starter adaptive card from a template.",
+    after_help = "Main options:\n  <FILE>\n\nOptional options:\n  --template <basic|form|approval|list> (default: basic)\n  --title <TITLE>\n  --version <VERSION> (default: 1.3)\n  --force"
 )]
-struct DemoSubscriptionsStatusArgs {
+struct DemoCardNewArgs {
+    #[arg(value_name = "FILE")]
+    out: PathBuf,
+    #[arg(long, default_value = "basic", value_parser = clap::builder::PossibleValuesParser::new(card::templates::TEMPLATES))]
+    template: String,
+    #[arg(long, default_value = "Hello from greentic")]
+    title: String,
+    #[arg(
+        long = "version",
+        default_value = "1.3",
+        help = "Schema version the card declares; 1.3 renders on most card-capable providers."
+    )]
+    schema_version: String,
+    #[arg(long, help = "Overwrite FILE if it exists.")]
+    force: bool,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Inspect the bundle's messaging providers.",
+    long_about = "Reports what each messaging provider pack declares it can render."
+)]
+struct DemoProvidersCommand {
+    #[command(subcommand)]
+    command: DemoProvidersSubcommand,
+}
+
+#[derive(Subcommand)]
+enum DemoProvidersSubcommand {
+    Capabilities(DemoProvidersCapabilitiesArgs),
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Print a matrix of features each messaging provider supports.",
+    long_about = "Runs each provider's requirements op and its render_plan on a probe message (text, an adaptive card with buttons, an attachment, and a thread reference), then reports cards, buttons, attachments, threads, and editing as declared. Nothing is encoded or sent. `?` means the provider does not say.",
+    after_help = "Main options:\n  --bundle <DIR>\n\nOptional options:\n  --provider <PROVIDER>... (default: every messaging provider)\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)\n  --env <ENV> (default: demo)\n  --format <text|json> (default: text)"
+)]
+struct DemoProvidersCapabilitiesArgs {
     #[arg(long)]
     bundle: PathBuf,
+    #[arg(long = "provider")]
+    providers: Vec<String>,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+    #[arg(long, default_value = "demo")]
+    env: String,
     #[arg(long)]
-    provider: Option<String>,
+    runner_binary: Option<PathBuf>,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Inspect or seed per-conversation state.",
+    long_about = "App flows get a conversation's state as `state` and write it back through `state_updates`. The files live under state/conversations/<tenant>.<team>/<provider>/."
+)]
+struct DemoStateCommand {
+    #[command(subcommand)]
+    command: DemoStateSubcommand,
+}
+
+#[derive(Subcommand)]
+enum DemoStateSubcommand {
+    Get(DemoStateGetArgs),
+    Set(DemoStateSetArgs),
+    List(DemoStateListArgs),
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Print a conversation's state, or one key of it.",
+    after_help = "Main options:\n  --bundle <DIR>\n  --provider <PROVIDER>\n  --chat <CHAT>\n\nOptional options:\n  <KEY>\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)"
+)]
+struct DemoStateGetArgs {
     #[arg(long)]
-    binding_id: Option<String>,
+    bundle: PathBuf,
+    #[arg(long)]
+    provider: String,
+    #[arg(long, help = "Conversation id (the session_id of its messages).")]
+    chat: String,
+    key: Option<String>,
     #[arg(long, default_value = "demo")]
     tenant: String,
     #[arg(long, default_value = "default")]
@@ -1010,56 +2396,2809 @@ struct DemoSubscriptionsStatusArgs {
 
 #[derive(Parser)]
 #[command(
-    about = "Renew stored subscriptions that are near expiry.",
-    long_about = "Runs the scheduler to renew eligible bindings or a single binding if --binding-id is provided."
+    about = "Set or remove one key of a conversation's state.",
+    long_about = "VALUE is parsed as JSON and stored as a string when it is not valid JSON. --unset removes the key.",
+    after_help = "Main options:\n  --bundle <DIR>\n  --provider <PROVIDER>\n  --chat <CHAT>\n  <KEY> <VALUE> | <KEY> --unset\n\nOptional options:\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)"
 )]
-struct DemoSubscriptionsRenewArgs {
+struct DemoStateSetArgs {
     #[arg(long)]
     bundle: PathBuf,
     #[arg(long)]
-    binding_id: Option<String>,
+    provider: String,
+    #[arg(long, help = "Conversation id (the session_id of its messages).")]
+    chat: String,
+    key: String,
+    #[arg(required_unless_present = "unset", conflicts_with = "unset")]
+    value: Option<String>,
     #[arg(long)]
-    provider: Option<String>,
+    unset: bool,
     #[arg(long, default_value = "demo")]
     tenant: String,
     #[arg(long, default_value = "default")]
     team: String,
-    #[arg(long, default_value = "10")]
-    skew_minutes: u64,
 }
 
 #[derive(Parser)]
 #[command(
-    about = "Delete a persisted demo subscription binding through the provider.",
-    long_about = "Invokes subscription_delete for the binding and removes the stored state file."
+    about = "List conversations with stored state.",
+    long_about = "Most recently updated first.",
+    after_help = "Main options:\n  --bundle <DIR>\n\nOptional options:\n  --provider <PROVIDER>\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)\n  --format <text|json> (default: text)"
 )]
-struct DemoSubscriptionsDeleteArgs {
+struct DemoStateListArgs {
     #[arg(long)]
     bundle: PathBuf,
     #[arg(long)]
-    binding_id: String,
-    #[arg(long)]
-    provider: String,
+    provider: Option<String>,
     #[arg(long, default_value = "demo")]
     tenant: String,
     #[arg(long, default_value = "default")]
     team: String,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
 }
 
-impl DemoSubscriptionsCommand {
-    fn run(self) -> anyhow::Result<()> {
-        match self.command {
-            DemoSubscriptionsSubcommand::Ensure(args) => args.run(),
-            DemoSubscriptionsSubcommand::Status(args) => args.run(),
-            DemoSubscriptionsSubcommand::Renew(args) => args.run(),
-            DemoSubscriptionsSubcommand::Delete(args) => args.run(),
-        }
-    }
+#[derive(Parser)]
+#[command(
+    about = "Inspect recorded flow/op runs.",
+    long_about = "Lists and shows the run records DemoRunnerHost writes to state/runs/<run_id>.json."
+)]
+struct DemoRunsCommand {
+    #[command(subcommand)]
+    command: DemoRunsSubcommand,
+}
+
+#[derive(Subcommand)]
+enum DemoRunsSubcommand {
+    List(DemoRunsListArgs),
+    Show(DemoRunsShowArgs),
+}
+
+#[derive(Parser)]
+#[command(
+    about = "List recorded runs, newest first.",
+    long_about = "Prints one line per run with status, provider, op, and duration. Retention is controlled by runs.max_runs / runs.max_age_days in greentic.demo.yaml.",
+    after_help = "Main options:\n  --bundle <DIR>\n\nOptional options:\n  --provider <PROVIDER>\n  --status <success|failed|denied>\n  --since <RFC3339|30m|12h|7d>\n  --limit <N> (default: 20)\n  --format <text|json> (default: text)"
+)]
+struct DemoRunsListArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long)]
+    provider: Option<String>,
+    #[arg(long, value_enum)]
+    status: Option<RunStatusArg>,
+    #[arg(
+        long,
+        help = "Only runs started after this RFC 3339 time or relative age (30m, 12h, 7d)."
+    )]
+    since: Option<String>,
+    #[arg(long, default_value_t = 20)]
+    limit: usize,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Show the step timeline of a recorded run.",
+    long_about = "Prints per-node timing, redacted inputs/outputs, and errors for a run."
+)]
+struct DemoRunsShowArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    run_id: String,
+    #[arg(long, help = "Print the raw run record as JSON.")]
+    json: bool,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Queue long-running actions and follow their progress.",
+    long_about = "Jobs are stored under state/jobs/ and run one at a time by the worker inside demo start, or by demo jobs run. Each job runs a greentic-operator command with the bundle as working directory; its output goes to state/jobs/<id>.log."
+)]
+struct DemoJobsCommand {
+    #[command(subcommand)]
+    command: DemoJobsSubcommand,
+}
+
+#[derive(Subcommand)]
+enum DemoJobsSubcommand {
+    Submit(DemoJobsSubmitArgs),
+    Status(DemoJobsStatusArgs),
+    Run(DemoJobsRunArgs),
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Queue a job and return right away.",
+    long_about = "Queues build (demo build), send (demo send), pull (registry mirror), or wizard (wizard) with the arguments after --. Relative paths in them are resolved against the bundle.",
+    after_help = "Main options:\n  <build|send|pull|wizard>\n  --bundle <DIR>\n\nOptional options:\n  -- <ARGS>...\n\nExample:\n  greentic-operator demo jobs submit build --bundle . -- --out dist/demo"
+)]
+struct DemoJobsSubmitArgs {
+    #[arg(value_enum)]
+    kind: demo::jobs::JobKind,
+    #[arg(long)]
+    bundle: PathBuf,
+    /// Arguments passed to the job's command.
+    #[arg(last = true, value_name = "ARGS")]
+    args: Vec<String>,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "List jobs, or show one job and the end of its log.",
+    after_help = "Main options:\n  --bundle <DIR>\n\nOptional options:\n  <ID>\n  --format <text|json> (default: text)"
+)]
+struct DemoJobsStatusArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    id: Option<String>,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Run queued jobs in the foreground.",
+    long_about = "Runs queued jobs until none is left. With --watch, keeps waiting for new jobs until Ctrl+C. Use it when no demo start is running for the bundle."
+)]
+struct DemoJobsRunArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    /// Keep running and pick up jobs as they are submitted.
+    #[arg(long)]
+    watch: bool,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Remove state a long-lived bundle no longer needs.",
+    long_about = "Removes pidfiles of dead processes, run records past retention, setup run directories older than the run age limit (the newest per flow is kept), capability recordings and staged uploads past the capture retention, rotated logs (*.log.*) past the log retention, and resolved manifests of tenants that no longer exist. Run retention defaults to runs.max_runs / runs.max_age_days in greentic.demo.yaml.",
+    after_help = "Main options:\n  --bundle <DIR>\n\nOptional options:\n  --dry-run\n  --max-runs <N> (default: runs.max_runs)\n  --max-age-days <DAYS> (default: runs.max_age_days)\n  --capture-max-age-days <DAYS> (default: 30, 0 keeps them)\n  --log-max-age-days <DAYS> (default: 7, 0 keeps them)\n  --format <text|json> (default: text)"
+)]
+struct DemoGcArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    /// List what would be removed and its size without deleting anything.
+    #[arg(long)]
+    dry_run: bool,
+    #[arg(long)]
+    max_runs: Option<usize>,
+    #[arg(long)]
+    max_age_days: Option<u64>,
+    #[arg(long, default_value_t = 30)]
+    capture_max_age_days: u64,
+    #[arg(long, default_value_t = 7)]
+    log_max_age_days: u64,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Inspect events timer handlers.",
+    long_about = "Shows the timer handlers declared by provider packs, with services.events.timers overrides from greentic.demo.yaml applied."
+)]
+struct DemoTimersCommand {
+    #[command(subcommand)]
+    command: DemoTimersSubcommand,
+}
+
+#[derive(Subcommand)]
+enum DemoTimersSubcommand {
+    List(DemoTimersListArgs),
+    Sends(DemoTimersSendsArgs),
+    Cancel(DemoTimersCancelArgs),
+}
+
+#[derive(Parser)]
+#[command(
+    about = "List timer handlers with their next fire time and last outcome.",
+    long_about = "Next fire times are computed from now in UTC. The last outcome comes from state/timers, or the newest matching run record under state/runs; `missed` counts windows elapsed since the last fire.",
+    after_help = "Main options:\n  --bundle <DIR>\n\nOptional options:\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)\n  --format <text|json> (default: text)"
+)]
+struct DemoTimersListArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "List sends scheduled with demo send --at/--every.",
+    long_about = "Pending sends come first, by next time. Finished ones stay listed with their last outcome until cancelled.",
+    after_help = "Main options:\n  --bundle <DIR>\n\nOptional options:\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)\n  --format <text|json> (default: text)"
+)]
+struct DemoTimersSendsArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Cancel a scheduled send.",
+    long_about = "Removes the send from state/timers; a running demo start picks the change up on its next check.",
+    after_help = "Main options:\n  --bundle <DIR>\n  <ID>\n\nOptional options:\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)"
+)]
+struct DemoTimersCancelArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(value_name = "ID")]
+    id: String,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+}
+
+#[derive(Parser)]
+#[command(about = "Inspect a provider pack archive.")]
+struct DemoPackCommand {
+    #[command(subcommand)]
+    command: DemoPackSubcommand,
+}
+
+#[derive(Subcommand)]
+enum DemoPackSubcommand {
+    Inspect(DemoPackInspectArgs),
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Dump a pack's decoded manifest as JSON and validate it.",
+    long_about = "Prints the archive members, the manifest as stored, the pack id and flow lists as discovery resolves them, and every schema issue with the CBOR path it was found at. Exits non-zero when the manifest has schema errors.",
+    after_help = "Main options:\n  <PACK> (.gtpack file or pack directory)"
+)]
+struct DemoPackInspectArgs {
+    #[arg(value_name = "PACK")]
+    pack: PathBuf,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Tail or publish events in the events domain.",
+    long_about = "Events travel on greentic.<tenant>.<team>.ingress.events.<provider>. Tailing from another process needs the JetStream bus of demo start --nats=jetstream."
+)]
+struct DemoEventsCommand {
+    #[command(subcommand)]
+    command: DemoEventsSubcommand,
+}
+
+#[derive(Subcommand)]
+enum DemoEventsSubcommand {
+    Tail(DemoEventsTailArgs),
+    Publish(DemoEventsPublishArgs),
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Print events as they arrive on the bus.",
+    long_about = "Subscribes to the tenant/team's events subjects and prints each event until interrupted or --limit events were printed. --type takes an exact type or a prefix ending in *.",
+    after_help = "Main options:\n  --bundle <DIR>\n\nOptional options:\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)\n  --type <TYPE|PREFIX*>\n  --provider <PROVIDER>\n  --contains <TEXT>\n  --limit <N>\n  --format <text|json> (default: text)"
+)]
+struct DemoEventsTailArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+    #[arg(long = "type", value_name = "TYPE")]
+    event_type: Option<String>,
+    #[arg(long)]
+    provider: Option<String>,
+    #[arg(
+        long,
+        help = "Only events whose JSON contains this text (case-insensitive)."
+    )]
+    contains: Option<String>,
+    #[arg(long, help = "Stop after this many matching events.")]
+    limit: Option<usize>,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Inject a synthetic event into the events pipeline.",
+    long_about = "Publishes an EventEnvelopeV1 on the events subject, where bridges and demo events tail see it, then runs it through the default events flow like an ingested event.",
+    after_help = "Main options:\n  --bundle <DIR>\n  --type <TYPE>\n\nOptional options:\n  --payload-json <JSON> (default: {})\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)\n  --provider <PROVIDER> (default: synthetic)\n  --correlation-id <ID>\n  --no-route\n  --format <text|json> (default: text)"
+)]
+struct DemoEventsPublishArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long = "type", value_name = "TYPE")]
+    event_type: String,
+    #[arg(long, default_value = "{}")]
+    payload_json: String,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+    #[arg(long, default_value = demo::event_viewer::SYNTHETIC_PROVIDER)]
+    provider: String,
+    #[arg(long)]
+    correlation_id: Option<String>,
+    #[arg(long, help = "Only publish on the bus; skip the default events flow.")]
+    no_route: bool,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Inspect the audit trail of state-mutating actions.",
+    long_about = "Reads state/audit/*.jsonl: setup runs, gmap edits, secrets writes, subscription changes, capability install records, and operator access checks."
+)]
+struct DemoAuditCommand {
+    #[command(subcommand)]
+    command: DemoAuditSubcommand,
+}
+
+#[derive(Subcommand)]
+enum DemoAuditSubcommand {
+    Tail(DemoAuditTailArgs),
+    Search(DemoAuditSearchArgs),
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Print the most recent audit events.",
+    long_about = "Prints the last N events, oldest first. With --follow, keeps printing events as they are appended.",
+    after_help = "Main options:\n  --bundle <DIR>\n\nOptional options:\n  -n, --lines <N> (default: 20)\n  --follow\n  --format <text|json> (default: text)"
+)]
+struct DemoAuditTailArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(short = 'n', long, default_value_t = 20)]
+    lines: usize,
+    #[arg(long, help = "Keep polling for new events until interrupted.")]
+    follow: bool,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Search audit events, newest first.",
+    long_about = "QUERY matches the target, error, or details case-insensitively. --action also accepts a prefix such as secrets or gmap.",
+    after_help = "Main options:\n  --bundle <DIR>\n\nOptional options:\n  <QUERY>\n  --action <ACTION>\n  --actor <NAME>\n  --tenant <TENANT>\n  --outcome <ok|failed|denied>\n  --since <RFC3339|30m|12h|7d>\n  --limit <N> (default: 50)\n  --format <text|json> (default: text)"
+)]
+struct DemoAuditSearchArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    query: Option<String>,
+    #[arg(long)]
+    action: Option<String>,
+    #[arg(long)]
+    actor: Option<String>,
+    #[arg(long)]
+    tenant: Option<String>,
+    #[arg(long, value_enum)]
+    outcome: Option<AuditOutcomeArg>,
+    #[arg(
+        long,
+        help = "Only events after this RFC 3339 time or relative age (30m, 12h, 7d)."
+    )]
+    since: Option<String>,
+    #[arg(long, default_value_t = 50)]
+    limit: usize,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum AuditOutcomeArg {
+    Ok,
+    Failed,
+    Denied,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Manage the local dev secrets store.",
+    long_about = "Operates on the bundle's .greentic/dev/.dev.secrets.env store (or $GREENTIC_DEV_SECRETS_PATH)."
+)]
+struct DemoSecretsCommand {
+    #[command(subcommand)]
+    command: DemoSecretsSubcommand,
+}
+
+#[derive(Subcommand)]
+enum DemoSecretsSubcommand {
+    Rekey(DemoSecretsRekeyArgs),
+    History(DemoSecretsHistoryArgs),
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum DevStoreEncryptionArg {
+    None,
+    Passphrase,
+    Keychain,
+}
+
+impl From<DevStoreEncryptionArg> for config::DevStoreEncryption {
+    fn from(value: DevStoreEncryptionArg) -> Self {
+        match value {
+            DevStoreEncryptionArg::None => Self::None,
+            DevStoreEncryptionArg::Passphrase => Self::Passphrase,
+            DevStoreEncryptionArg::Keychain => Self::Keychain,
+        }
+    }
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Encrypt, decrypt, or re-key the dev secrets store.",
+    long_about = "Rewrites every known secret under the target key. The target defaults to secrets.dev_store in greentic.demo.yaml; the current key comes from the store's .meta.json. Secrets written before the metadata existed are found through the provider packs' requirements for --tenant/--team.",
+    after_help = "Main options:\n  --bundle <DIR>\n\nOptional options:\n  --to <none|passphrase|keychain> (default: secrets.dev_store.encryption)\n  --passphrase-env <VAR> (default: secrets.dev_store.passphrase_env)\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)\n  --env <ENV> (default: $GREENTIC_ENV or dev)"
+)]
+struct DemoSecretsRekeyArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long, value_enum)]
+    to: Option<DevStoreEncryptionArg>,
+    #[arg(long)]
+    passphrase_env: Option<String>,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+    #[arg(long)]
+    env: Option<String>,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "List the recorded versions of a secret, or activate one.",
+    long_about = "Every setup write of a secret is also kept as <uri>@v<N>. --activate copies that version back onto the URI providers read, to roll back a rotated token or promote a staged one.",
+    after_help = "Main options:\n  <KEY>\n  --bundle <DIR>\n  --provider <PROVIDER>\n\nOptional options:\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)\n  --env <ENV> (default: $GREENTIC_ENV or dev)\n  --activate <VERSION>\n  --format <text|json> (default: text)"
+)]
+struct DemoSecretsHistoryArgs {
+    /// Secret key as declared by the provider pack (e.g. telegram_bot_token).
+    key: String,
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long)]
+    provider: String,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+    #[arg(long)]
+    env: Option<String>,
+    #[arg(long, value_name = "VERSION", value_parser = parse_secret_version)]
+    activate: Option<u32>,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+/// Accepts `3` or `v3`.
+fn parse_secret_version(value: &str) -> Result<u32, String> {
+    value
+        .trim_start_matches('v')
+        .parse::<u32>()
+        .ok()
+        .filter(|version| *version > 0)
+        .ok_or_else(|| format!("invalid secret version '{value}' (expected e.g. 2 or v2)"))
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Record or check render_plan/encode snapshots for a provider.",
+    long_about = "Runs each message of a corpus through the provider's render_plan and encode ops. record stores the outputs under snapshots/<provider>/; check fails when a pack update changed them."
+)]
+struct DemoSnapshotCommand {
+    #[command(subcommand)]
+    command: DemoSnapshotSubcommand,
+}
+
+#[derive(Subcommand)]
+enum DemoSnapshotSubcommand {
+    #[command(about = "Write snapshots for every corpus message")]
+    Record(DemoSnapshotArgs),
+    #[command(about = "Compare fresh outputs with the recorded snapshots")]
+    Check(DemoSnapshotArgs),
+}
+
+#[derive(Parser)]
+struct DemoSnapshotArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long)]
+    provider: String,
+    /// Corpus file (default: <bundle>/snapshots/corpus.yaml).
+    #[arg(long, value_name = "FILE")]
+    corpus: Option<PathBuf>,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+    #[arg(long)]
+    runner_binary: Option<PathBuf>,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Inspect the effective configuration of a bundle.",
+    long_about = "Works on greentic.yaml, greentic.demo.yaml, GREENTIC_* environment overrides, and the provider config envelopes written by setup."
+)]
+struct DemoConfigCommand {
+    #[command(subcommand)]
+    command: DemoConfigSubcommand,
+}
+
+#[derive(Subcommand)]
+enum DemoConfigSubcommand {
+    #[command(about = "Print the merged configuration with the source of every key")]
+    Show(DemoConfigShowArgs),
+    #[command(about = "Check greentic.yaml and greentic.demo.yaml against their schemas")]
+    Validate(DemoConfigValidateArgs),
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Print the merged configuration with the source of every key.",
+    long_about = "Assembles operator config, demo config, env overrides, and per-provider config envelopes for one tenant/team into a single document. Provider config is merged team over tenant over bundle default. Each value is annotated with where it came from (greentic.yaml, greentic.demo.yaml, env(...), envelope(<level>: ...), or default); secrets are redacted unless --no-redact is set. --explain instead lists each provider config key and required secret with the level that supplies it.",
+    after_help = "Main options:\n  --bundle <DIR>\n\nOptional options:\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)\n  --explain\n  --format <yaml|json> (default: yaml)"
+)]
+struct DemoConfigShowArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+    /// Show which level (team, tenant, or bundle) supplies each provider config key and secret.
+    #[arg(long)]
+    explain: bool,
+    #[arg(long, value_enum, default_value_t = ConfigFormat::Yaml)]
+    format: ConfigFormat,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Check greentic.yaml and greentic.demo.yaml against their schemas.",
+    long_about = "Reports YAML syntax and type errors, and keys the schema does not know (which are otherwise silently ignored, so a typo like listen_adr leaves the default in place), with line numbers. Fails on errors; --strict also fails on unknown keys.",
+    after_help = "Main options:\n  --bundle <DIR>\n\nOptional options:\n  --strict\n  --format <text|json> (default: text)"
+)]
+struct DemoConfigValidateArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    /// Treat unknown keys as errors.
+    #[arg(long)]
+    strict: bool,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Manage the egress dead-letter queue.",
+    long_about = "Operates on the DLQ log written when egress sends exhaust their retry policy."
+)]
+struct DemoDlqCommand {
+    #[command(subcommand)]
+    command: DemoDlqSubcommand,
+}
+
+#[derive(Subcommand)]
+enum DemoDlqSubcommand {
+    List(DemoDlqListArgs),
+    Replay(DemoDlqReplayArgs),
+    Purge(DemoDlqPurgeArgs),
+}
+
+#[derive(Parser)]
+#[command(
+    about = "List dead-lettered messages.",
+    long_about = "Prints job id, provider, attempts, and failure reason for each DLQ entry."
+)]
+struct DemoDlqListArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+    #[arg(long)]
+    provider: Option<String>,
+    #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+    format: ListFormat,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Re-send dead-lettered messages through the egress pipeline.",
+    long_about = "Removes the selected entries from the DLQ and runs render_plan/encode/send_payload again; failures are dead-lettered anew.",
+    after_help = "Main options:\n  --bundle <DIR>\n  --job-id <ID>... | --all\n\nOptional options:\n  --provider <PROVIDER>\n  --retries <N>\n  --dry-run\n  --runner-binary <PATH>\n  --tenant <TENANT> (default: demo)\n  --team <TEAM> (default: default)"
+)]
+struct DemoDlqReplayArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+    #[arg(long = "job-id")]
+    job_ids: Vec<String>,
+    #[arg(long, conflicts_with = "job_ids")]
+    all: bool,
+    #[arg(long)]
+    provider: Option<String>,
+    #[arg(long, help = "Override the configured retry budget for the replay.")]
+    retries: Option<u32>,
+    #[arg(
+        long,
+        help = "Run render_plan/encode only; keep the entries in the DLQ."
+    )]
+    dry_run: bool,
+    #[arg(long)]
+    runner_binary: Option<PathBuf>,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Delete dead-lettered messages.",
+    long_about = "Removes the selected entries (or all with --all) from the DLQ."
+)]
+struct DemoDlqPurgeArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+    #[arg(long = "job-id")]
+    job_ids: Vec<String>,
+    #[arg(long, conflicts_with = "job_ids")]
+    all: bool,
+    #[arg(long)]
+    provider: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum DemoSubscriptionsSubcommand {
+    Ensure(DemoSubscriptionsEnsureArgs),
+    Status(DemoSubscriptionsStatusArgs),
+    Renew(DemoSubscriptionsRenewArgs),
+    Delete(DemoSubscriptionsDeleteArgs),
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Ensure a subscription binding via a demo provider.",
+    long_about = "Invokes the provider's subscription_ensure flow, persists the binding state, and returns the binding_id."
+)]
+struct DemoSubscriptionsEnsureArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long)]
+    provider: String,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+    #[arg(long)]
+    binding_id: Option<String>,
+    #[arg(long)]
+    resource: Option<String>,
+    #[arg(long = "change-type", action = ArgAction::Append)]
+    change_types: Vec<String>,
+    #[arg(long)]
+    notification_url: Option<String>,
+    #[arg(long)]
+    client_state: Option<String>,
+    #[arg(long)]
+    user_id: Option<String>,
+    #[arg(long)]
+    user_token_key: Option<String>,
+    #[command(flatten)]
+    failure: FailureModeArgs,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "List demo subscription bindings persisted by the operator.",
+    long_about = "Prints provider/tenant/team/binding info for demo-managed subscriptions."
+)]
+struct DemoSubscriptionsStatusArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long)]
+    provider: Option<String>,
+    #[arg(long)]
+    binding_id: Option<String>,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Renew stored subscriptions that are near expiry.",
+    long_about = "Runs the scheduler to renew eligible bindings or a single binding if --binding-id is provided."
+)]
+struct DemoSubscriptionsRenewArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long)]
+    binding_id: Option<String>,
+    #[arg(long)]
+    provider: Option<String>,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+    #[arg(long, default_value = "10")]
+    skew_minutes: u64,
+    #[command(flatten)]
+    failure: FailureModeArgs,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Delete a persisted demo subscription binding through the provider.",
+    long_about = "Invokes subscription_delete for the binding and removes the stored state file."
+)]
+struct DemoSubscriptionsDeleteArgs {
+    #[arg(long)]
+    bundle: PathBuf,
+    #[arg(long)]
+    binding_id: String,
+    #[arg(long)]
+    provider: String,
+    #[arg(long, default_value = "demo")]
+    tenant: String,
+    #[arg(long, default_value = "default")]
+    team: String,
+    #[command(flatten)]
+    failure: FailureModeArgs,
+}
+
+impl DemoSubscriptionsCommand {
+    fn run(self) -> anyhow::Result<()> {
+        match self.command {
+            DemoSubscriptionsSubcommand::Ensure(args) => args.run(),
+            DemoSubscriptionsSubcommand::Status(args) => args.run(),
+            DemoSubscriptionsSubcommand::Renew(args) => args.run(),
+            DemoSubscriptionsSubcommand::Delete(args) => args.run(),
+        }
+    }
+}
+
+impl DemoDestinationsCommand {
+    fn run(self) -> anyhow::Result<()> {
+        match self.command {
+            DemoDestinationsSubcommand::Add(args) => args.run(),
+            DemoDestinationsSubcommand::List(args) => args.run(),
+            DemoDestinationsSubcommand::Remove(args) => args.run(),
+        }
+    }
+}
+
+impl DemoDestinationsAddArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let name = self.name.trim_start_matches('@');
+        let mut book = DestinationBook::load(&self.bundle)?;
+        let previous = book.add(
+            name,
+            &self.provider,
+            DestinationEntry {
+                id: self.id.clone(),
+                kind: self.kind.clone(),
+            },
+        )?;
+        let path = book.save(&self.bundle)?;
+        if let Some(previous) = previous {
+            println!(
+                "{}",
+                operator_i18n::trf(
+                    "cli.destinations.replaced",
+                    "replaced @{} for {} (was {})",
+                    &[name, &self.provider, &previous.id]
+                )
+            );
+        } else {
+            println!(
+                "{}",
+                operator_i18n::trf(
+                    "cli.destinations.added",
+                    "added @{} for {}",
+                    &[name, &self.provider]
+                )
+            );
+        }
+        if demo_debug_enabled() {
+            println!("[demo] destinations written to {}", path.display());
+        }
+        Ok(())
+    }
+}
+
+impl DemoDestinationsListArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let book = DestinationBook::load(&self.bundle)?;
+        let mut printed = false;
+        for (name, entries) in &book.destinations {
+            for (provider, entry) in entries {
+                if self
+                    .provider
+                    .as_deref()
+                    .is_some_and(|value| value != provider)
+                {
+                    continue;
+                }
+                printed = true;
+                println!(
+                    "@{} {} id={} kind={}",
+                    name,
+                    provider,
+                    entry.id,
+                    entry.kind.as_deref().unwrap_or("-")
+                );
+            }
+        }
+        if !printed {
+            println!(
+                "{}",
+                operator_i18n::tr("cli.destinations.none", "no destinations found")
+            );
+        }
+        Ok(())
+    }
+}
+
+impl DemoDestinationsRemoveArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let mut book = DestinationBook::load(&self.bundle)?;
+        if !book.remove(&self.name, self.provider.as_deref()) {
+            return Err(anyhow::anyhow!(
+                "destination {} not found in {}",
+                self.name,
+                destinations::book_path(&self.bundle).display()
+            ));
+        }
+        book.save(&self.bundle)?;
+        println!("{}", operator_i18n::tr("cli.common.ok", "ok"));
+        Ok(())
+    }
+}
+
+impl DemoRunsCommand {
+    fn run(self) -> anyhow::Result<()> {
+        match self.command {
+            DemoRunsSubcommand::List(args) => args.run(),
+            DemoRunsSubcommand::Show(args) => args.run(),
+        }
+    }
+}
+
+impl DemoRunsListArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let query = run_record::RunQuery {
+            provider: self.provider.clone(),
+            status: self.status.map(Into::into),
+            since: self
+                .since
+                .as_deref()
+                .map(|value| run_record::parse_since(value, Utc::now()))
+                .transpose()?,
+        };
+        let records = run_record::list_records(&self.bundle)?
+            .into_iter()
+            .filter(|record| query.matches(record))
+            .take(self.limit)
+            .collect::<Vec<_>>();
+        if matches!(self.format, ListFormat::Json) {
+            println!("{}", serde_json::to_string_pretty(&records)?);
+            return Ok(());
+        }
+        if records.is_empty() {
+            println!("{}", operator_i18n::tr("cli.runs.none", "no runs recorded"));
+            return Ok(());
+        }
+        for record in &records {
+            println!("{}", format_run_summary(record));
+        }
+        Ok(())
+    }
+}
+
+impl DemoRunsShowArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let record = run_record::load_record(&self.bundle, &self.run_id)?;
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&record)?);
+            return Ok(());
+        }
+        println!("{}", format_run_summary(&record));
+        if let Some(error) = &record.error {
+            println!(
+                "{}",
+                operator_i18n::trf("cli.runs.error", "error: {}", &[error])
+            );
+        }
+        for (index, step) in record.steps.iter().enumerate() {
+            let duration = step
+                .duration_ms
+                .map(|ms| format!("{ms}ms"))
+                .unwrap_or_else(|| "-".to_string());
+            println!(
+                "  {}. {} [{}] {}",
+                index + 1,
+                step.node_id,
+                step.status.as_str(),
+                duration
+            );
+            if let Some(input) = &step.input {
+                println!("     input:  {}", serde_json::to_string(input)?);
+            }
+            if let Some(output) = &step.output {
+                println!("     output: {}", serde_json::to_string(output)?);
+            }
+            if let Some(error) = &step.error {
+                println!("     error:  {error}");
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DemoJobsCommand {
+    fn run(self) -> anyhow::Result<()> {
+        match self.command {
+            DemoJobsSubcommand::Submit(args) => args.run(),
+            DemoJobsSubcommand::Status(args) => args.run(),
+            DemoJobsSubcommand::Run(args) => args.run(),
+        }
+    }
+}
+
+impl DemoJobsSubmitArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let job = demo::jobs::submit(&self.bundle, self.kind, self.args)?;
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.jobs.submitted",
+                "Queued job {}: {}",
+                &[&job.id, &job.command_line()]
+            )
+        );
+        if demo::reload::read_process(&self.bundle.join("state"))?.is_none() {
+            println!(
+                "{}",
+                operator_i18n::tr(
+                    "cli.jobs.no_worker_hint",
+                    "No demo start is running for this bundle; run demo jobs run to process the queue."
+                )
+            );
+        }
+        Ok(())
+    }
+}
+
+impl DemoJobsStatusArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let Some(id) = self.id else {
+            let jobs = demo::jobs::list(&self.bundle)?;
+            if matches!(self.format, ListFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&jobs)?);
+                return Ok(());
+            }
+            if jobs.is_empty() {
+                println!("{}", operator_i18n::tr("cli.jobs.none", "no jobs"));
+            }
+            for job in &jobs {
+                println!("{}", format_job_summary(job));
+            }
+            return Ok(());
+        };
+        // Listing first marks a job failed whose worker is gone.
+        demo::jobs::list(&self.bundle)?;
+        let job = demo::jobs::load(&self.bundle, &id)?;
+        if matches!(self.format, ListFormat::Json) {
+            println!("{}", serde_json::to_string_pretty(&job)?);
+            return Ok(());
+        }
+        println!("{}", format_job_summary(&job));
+        if let Some(error) = &job.error {
+            println!(
+                "{}",
+                operator_i18n::trf("cli.runs.error", "error: {}", &[error])
+            );
+        }
+        let log = demo::jobs::log_path(&self.bundle, &job.id);
+        if let Ok(contents) = fs::read_to_string(&log) {
+            println!("--- {} ---", log.display());
+            let lines = contents.lines().collect::<Vec<_>>();
+            for line in &lines[lines.len().saturating_sub(20)..] {
+                println!("{line}");
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DemoJobsRunArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let exe = std::env::current_exe().context("locate the greentic-operator binary")?;
+        let mut ran = 0usize;
+        loop {
+            match demo::jobs::run_next(&self.bundle, &exe)? {
+                Some(job) => {
+                    ran += 1;
+                    println!("{}", format_job_summary(&job));
+                }
+                None if self.watch => std::thread::sleep(demo::jobs::POLL_INTERVAL),
+                None => break,
+            }
+        }
+        println!(
+            "{}",
+            operator_i18n::trf("cli.jobs.ran", "{} job(s) run", &[&ran.to_string()])
+        );
+        Ok(())
+    }
+}
+
+fn format_job_summary(job: &demo::jobs::Job) -> String {
+    let finished = job
+        .finished_at
+        .map(|at| format!(" finished={}", at.to_rfc3339()))
+        .unwrap_or_default();
+    format!(
+        "{} {} {} submitted={}{finished}: {}",
+        job.id,
+        job.status.as_str(),
+        job.kind.as_str(),
+        job.submitted_at.to_rfc3339(),
+        job.command_line()
+    )
+}
+
+fn format_run_summary(record: &run_record::RunRecord) -> String {
+    format!(
+        "{} {} {}/{} op={} tenant={} team={} duration={}ms started={}",
+        record.run_id,
+        record.status.as_str(),
+        record.domain,
+        record.provider,
+        record.op,
+        record.tenant,
+        record.team.as_deref().unwrap_or("default"),
+        record.duration_ms,
+        record.started_at
+    )
+}
+
+impl DemoTimersCommand {
+    fn run(self) -> anyhow::Result<()> {
+        match self.command {
+            DemoTimersSubcommand::List(args) => args.run(),
+            DemoTimersSubcommand::Sends(args) => args.run(),
+            DemoTimersSubcommand::Cancel(args) => args.run(),
+        }
+    }
+}
+
+impl DemoVerifyArgs {
+    fn run(self) -> anyhow::Result<()> {
+        domains::ensure_cbor_packs(&self.bundle)?;
+        let discovery = discovery::discover_with_options(
+            &self.bundle,
+            discovery::DiscoveryOptions { cbor_only: true },
+        )?;
+        let selected = self.domain.resolve_domains(&self.bundle, &discovery)?;
+        let secrets_handle =
+            secrets_gate::resolve_secrets_manager(&self.bundle, &self.tenant, Some(&self.team))?;
+        let runner_host = DemoRunnerHost::new(
+            self.bundle.clone(),
+            &discovery,
+            self.runner_binary.clone(),
+            secrets_handle,
+            false,
+        )?;
+        let paths = RuntimePaths::new(self.bundle.join("state"), &self.tenant, &self.team);
+        let public_base_url = crate::cloudflared::current_public_url(&paths)?;
+        let ctx = OperatorContext {
+            tenant: self.tenant.clone(),
+            team: Some(self.team.clone()),
+            correlation_id: None,
+        };
+        let mut results = Vec::new();
+        for domain in selected {
+            results.extend(demo::verify::verify_domain(
+                &runner_host,
+                &self.bundle,
+                &discovery,
+                domain,
+                self.provider.as_deref(),
+                &ctx,
+                public_base_url.as_deref(),
+            )?);
+        }
+        let count = |status: demo::verify::VerifyStatus| {
+            results
+                .iter()
+                .filter(|result| result.status == status)
+                .count()
+        };
+        let failed = count(demo::verify::VerifyStatus::Failed);
+        match self.format {
+            ListFormat::Json => println!("{}", serde_json::to_string_pretty(&results)?),
+            ListFormat::Text => {
+                for result in &results {
+                    let detail = match (&result.flow, &result.error) {
+                        (_, Some(error)) => format!(": {error}"),
+                        (None, None) => {
+                            operator_i18n::tr("cli.demo_verify.no_verify_flow", " (no verify flow)")
+                        }
+                        (Some(_), None) => String::new(),
+                    };
+                    println!(
+                        "{} {} {} {}{detail}",
+                        result.status.label(),
+                        result.domain,
+                        result.provider,
+                        result.flow.as_deref().unwrap_or("-")
+                    );
+                }
+                println!(
+                    "{}",
+                    operator_i18n::trf(
+                        "cli.demo_verify.summary",
+                        "{} passed, {} failed, {} skipped",
+                        &[
+                            &count(demo::verify::VerifyStatus::Passed).to_string(),
+                            &failed.to_string(),
+                            &count(demo::verify::VerifyStatus::Skipped).to_string()
+                        ]
+                    )
+                );
+            }
+        }
+        if failed > 0 {
+            return Err(anyhow!("{failed} verify flow(s) failed"));
+        }
+        Ok(())
+    }
+}
+
+impl DemoBenchCommand {
+    fn run(self) -> anyhow::Result<()> {
+        match self.command {
+            DemoBenchSubcommand::Invoke(args) => args.run(),
+        }
+    }
+}
+
+impl DemoBenchInvokeArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let payload: JsonValue = serde_json::from_str(&self.payload_json)
+            .with_context(|| "--payload-json is not valid JSON")?;
+        let payload = serde_json::to_vec(&payload)?;
+        let domain = self.domain.resolve(&self.bundle)?;
+        domains::ensure_cbor_packs(&self.bundle)?;
+        let discovery = discovery::discover_with_options(
+            &self.bundle,
+            discovery::DiscoveryOptions { cbor_only: true },
+        )?;
+        let secrets_handle =
+            secrets_gate::resolve_secrets_manager(&self.bundle, &self.tenant, Some(&self.team))?;
+        let runner_host = DemoRunnerHost::new(
+            self.bundle.clone(),
+            &discovery,
+            self.runner_binary.clone(),
+            secrets_handle,
+            false,
+        )?;
+        if !runner_host.supports_op(domain, &self.provider, &self.op) {
+            return Err(anyhow!(
+                "provider {} in domain {} has no op {}",
+                self.provider,
+                domains::domain_name(domain),
+                self.op
+            ));
+        }
+        let ctx = OperatorContext {
+            tenant: self.tenant.clone(),
+            team: Some(self.team.clone()),
+            correlation_id: None,
+        };
+        let report = demo::bench::run_invoke(
+            &runner_host,
+            &demo::bench::InvokeTarget {
+                domain,
+                provider: &self.provider,
+                op: &self.op,
+                payload: &payload,
+                ctx: &ctx,
+            },
+            self.iterations,
+            self.warmup,
+        )?;
+        match self.format {
+            ListFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            ListFormat::Text => print!("{}", demo::bench::render(&report)),
+        }
+        Ok(())
+    }
+}
+
+impl DemoPackCommand {
+    fn run(self) -> anyhow::Result<()> {
+        match self.command {
+            DemoPackSubcommand::Inspect(args) => args.run(),
+        }
+    }
+}
+
+impl DemoPackInspectArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let inspection = domains::inspect_pack(&self.pack)?;
+        println!("{}", serde_json::to_string_pretty(&inspection)?);
+        if let Some(errors) = domains::manifest_schema::describe_errors(&inspection.issues) {
+            return Err(operator_error::manifest_invalid(&self.pack, errors));
+        }
+        Ok(())
+    }
+}
+
+impl DemoGraphArgs {
+    fn run(self) -> anyhow::Result<()> {
+        domains::ensure_cbor_packs(&self.bundle)?;
+        let discovery = discovery::discover_with_options(
+            &self.bundle,
+            discovery::DiscoveryOptions { cbor_only: true },
+        )?;
+        let selected = self.domain.resolve_domains(&self.bundle, &discovery)?;
+        let packs = demo::graph::collect_packs(&self.bundle, &selected)?;
+        let registry = load_capability_registry(&self.bundle, &self.tenant, &self.team)?;
+        let scope = capability_scope(self.env.as_deref(), &self.tenant, &self.team);
+        let graph = demo::graph::build_graph(&packs, &registry, &scope);
+        match self.format {
+            GraphFormat::Dot => print!("{}", graph.to_dot()),
+            GraphFormat::Json => println!("{}", serde_json::to_string_pretty(&graph)?),
+        }
+        Ok(())
+    }
+}
+
+impl DemoExportCommand {
+    fn run(self) -> anyhow::Result<()> {
+        match self.command {
+            DemoExportSubcommand::Compose(args) => args.run(),
+            DemoExportSubcommand::K8s(args) => args.run(),
+        }
+    }
+}
+
+impl DemoExportComposeArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let options = demo::export::ExportOptions {
+            tenant: self.tenant,
+            team: self.team,
+            image: self.image,
+            env: self.env,
+            cloudflared: self.cloudflared,
+        };
+        let compose = demo::export::compose(&self.bundle, &self.out, &options)?;
+        fs::write(&self.out, compose).with_context(|| format!("write {}", self.out.display()))?;
+        let out = self.out.display().to_string();
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.export.written",
+                "Wrote {} for tenant {} team {}",
+                &[&out, &options.tenant, &options.team]
+            )
+        );
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.export.compose.next",
+                "Copy it and the bundle to the server, then run: docker compose -f {} up -d",
+                &[&out]
+            )
+        );
+        Ok(())
+    }
+}
+
+impl DemoExportK8sArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let options = demo::export::ExportOptions {
+            tenant: self.tenant,
+            team: self.team,
+            image: self.image,
+            env: self.env,
+            cloudflared: false,
+        };
+        let k8s_options = demo::export::K8sOptions {
+            name: self.name,
+            namespace: self.namespace,
+            pvc: self.pvc,
+            host: self.host,
+            ingress_class: self.ingress_class,
+            tls_secret: self.tls_secret,
+        };
+        let manifests = demo::export::k8s(&self.bundle, &options, &k8s_options)?;
+        fs::write(&self.out, manifests).with_context(|| format!("write {}", self.out.display()))?;
+        let out = self.out.display().to_string();
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.export.written",
+                "Wrote {} for tenant {} team {}",
+                &[&out, &options.tenant, &options.team]
+            )
+        );
+        if k8s_options.pvc.is_none() {
+            println!(
+                "{}",
+                operator_i18n::tr(
+                    "cli.export.k8s.bake",
+                    "No --pvc given: build an image FROM the operator image that copies the bundle to /bundle, and pass it with --image."
+                )
+            );
+        }
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.export.k8s.next",
+                "Apply it with: kubectl apply -f {}",
+                &[&out]
+            )
+        );
+        Ok(())
+    }
+}
+
+impl DemoEventsCommand {
+    fn run(self) -> anyhow::Result<()> {
+        match self.command {
+            DemoEventsSubcommand::Tail(args) => args.run(),
+            DemoEventsSubcommand::Publish(args) => args.run(),
+        }
+    }
+}
+
+impl DemoEventsTailArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let bus = bus::for_scope(&self.bundle, &self.tenant, &self.team);
+        if bus.kind() == bus::BusKind::InProcess {
+            return Err(anyhow!(
+                "no JetStream bus for {}.{}; start the demo with --nats=jetstream to tail its events",
+                self.tenant,
+                self.team
+            ));
+        }
+        let pattern = demo::event_viewer::tail_pattern(&self.tenant, &self.team);
+        let subscription = bus.subscribe(&pattern)?;
+        let filter = demo::event_viewer::EventFilter {
+            event_type: self.event_type,
+            provider: self.provider,
+            contains: self.contains,
+        };
+        if matches!(self.format, ListFormat::Text) {
+            eprintln!(
+                "{}",
+                operator_i18n::trf(
+                    "cli.demo_events.tailing",
+                    "tailing {} (Ctrl-C to stop)",
+                    &[&pattern]
+                )
+            );
+        }
+        let mut printed = 0usize;
+        while self.limit.is_none_or(|limit| printed < limit) {
+            let Some(message) = subscription
+                .recv_timeout(Duration::from_secs(1))
+                .context("lost the connection to the JetStream bus")?
+            else {
+                continue;
+            };
+            let Some(event) = demo::event_viewer::parse_event(&message.payload) else {
+                continue;
+            };
+            if !filter.matches(&event) {
+                continue;
+            }
+            match self.format {
+                ListFormat::Json => println!("{}", serde_json::to_string(&event)?),
+                ListFormat::Text => println!("{}", demo::event_viewer::format_event(&event)),
+            }
+            printed += 1;
+        }
+        Ok(())
+    }
+}
+
+impl DemoEventsPublishArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let payload: JsonValue = serde_json::from_str(&self.payload_json)
+            .with_context(|| "--payload-json is not valid JSON")?;
+        let ctx = OperatorContext {
+            tenant: self.tenant.clone(),
+            team: Some(self.team.clone()),
+            correlation_id: self.correlation_id.clone(),
+        };
+        let event =
+            demo::event_viewer::synthetic_event(&self.event_type, payload, &self.provider, &ctx);
+        let result = demo::event_viewer::publish(&self.bundle, &ctx, &event, !self.no_route);
+        audit::record(
+            &self.bundle,
+            AuditEvent::new("events.publish", &self.event_type)
+                .scope(&self.tenant, Some(&self.team))
+                .detail(json!({ "provider": self.provider, "event_id": event.event_id }))
+                .result(&result),
+        );
+        let outcome = result?;
+        match self.format {
+            ListFormat::Json => println!("{}", serde_json::to_string_pretty(&outcome)?),
+            ListFormat::Text => println!(
+                "{}",
+                operator_i18n::trf(
+                    "cli.demo_events.published",
+                    "published {} on {} ({}); routed to {} flow(s)",
+                    &[
+                        &outcome.event_id,
+                        &outcome.subject,
+                        outcome.bus,
+                        &outcome.routed.to_string()
+                    ]
+                )
+            ),
+        }
+        Ok(())
+    }
+}
+
+impl DemoGcArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let demo_config = load_demo_config_or_default(&self.bundle.join("greentic.demo.yaml"));
+        let mut runs = run_record::RunRetention::from(&demo_config.runs);
+        if let Some(max_runs) = self.max_runs {
+            runs.max_runs = max_runs;
+        }
+        if let Some(max_age_days) = self.max_age_days {
+            runs.max_age_days = max_age_days;
+        }
+        let options = demo::gc::GcOptions {
+            runs,
+            capture_max_age_days: self.capture_max_age_days,
+            log_max_age_days: self.log_max_age_days,
+        };
+        let items = demo::gc::collect(&self.bundle, &options)?;
+        let total_bytes = items.iter().map(|item| item.bytes).sum::<u64>();
+        if !self.dry_run && !items.is_empty() {
+            let result = demo::gc::remove(&self.bundle, &items);
+            audit::record(
+                &self.bundle,
+                AuditEvent::new("state.gc", self.bundle.display().to_string())
+                    .detail(json!({ "removed": items.len(), "bytes": total_bytes }))
+                    .result(&result),
+            );
+            result?;
+        }
+        if matches!(self.format, ListFormat::Json) {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&json!({
+                    "dry_run": self.dry_run,
+                    "bytes": total_bytes,
+                    "items": items,
+                }))?
+            );
+            return Ok(());
+        }
+        if items.is_empty() {
+            println!(
+                "{}",
+                operator_i18n::tr("cli.gc.nothing", "Nothing to clean up.")
+            );
+            return Ok(());
+        }
+        if self.dry_run {
+            for item in &items {
+                println!(
+                    "{:<18} {:>10}  {}",
+                    item.kind.as_str(),
+                    demo::gc::format_bytes(item.bytes),
+                    item.path.display()
+                );
+            }
+        }
+        let mut by_kind = BTreeMap::<demo::gc::GcKind, (usize, u64)>::new();
+        for item in &items {
+            let entry = by_kind.entry(item.kind).or_default();
+            entry.0 += 1;
+            entry.1 += item.bytes;
+        }
+        for (kind, (count, bytes)) in by_kind {
+            println!(
+                "  {:<18} {:>6}  {}",
+                kind.as_str(),
+                count,
+                demo::gc::format_bytes(bytes)
+            );
+        }
+        let (key, fallback) = if self.dry_run {
+            ("cli.gc.summary_dry_run", "Would remove {} item(s), {}")
+        } else {
+            ("cli.gc.summary", "Removed {} item(s), {}")
+        };
+        println!(
+            "{}",
+            operator_i18n::trf(
+                key,
+                fallback,
+                &[
+                    &items.len().to_string(),
+                    &demo::gc::format_bytes(total_bytes)
+                ]
+            )
+        );
+        Ok(())
+    }
+}
+
+impl DemoCardCommand {
+    fn run(self) -> anyhow::Result<()> {
+        match self.command {
+            DemoCardSubcommand::Validate(args) => args.run(),
+            DemoCardSubcommand::New(args) => args.run(),
+        }
+    }
+}
+
+impl DemoCardValidateArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let contents = fs::read_to_string(&self.card)
+            .with_context(|| format!("failed to read card file {}", self.card.display()))?;
+        let value = serde_json::from_str::<JsonValue>(&contents)
+            .with_context(|| format!("failed to parse card file {}", self.card.display()))?;
+        // Accept the `{"card": {...}}` wrapper provider outputs use as well.
+        let adaptive_card = value.get("card").cloned().unwrap_or(value);
+        let issues = card::validate::validate_card(&adaptive_card);
+
+        let mut providers = Vec::new();
+        if let Some(bundle) = &self.bundle {
+            let names = if self.providers.is_empty() {
+                discovery::discover(bundle)?
+                    .providers
+                    .into_iter()
+                    .filter(|provider| provider.domain == "messaging")
+                    .map(|provider| provider.provider_id)
+                    .collect()
+            } else {
+                self.providers.clone()
+            };
+            for name in names {
+                let mut request = api::SendMessage::new(bundle, &name);
+                request.tenant = self.tenant.clone();
+                request.team = Some(self.team.clone());
+                request.card = Some(adaptive_card.clone());
+                request.runner_binary = self.runner_binary.clone();
+                let checked = match api::plan_message(request) {
+                    Ok(planned) if !planned.plan.ok => Err(planned
+                        .plan
+                        .error
+                        .unwrap_or_else(|| "render_plan returned error".to_string())),
+                    Ok(planned) => {
+                        let support = card::validate::card_support_from_plan(&planned.plan, &name);
+                        let issues =
+                            card::validate::check_provider_support(&adaptive_card, &support);
+                        Ok((support, issues))
+                    }
+                    Err(err) => Err(format!("{err:#}")),
+                };
+                providers.push((name, checked));
+            }
+        }
+
+        let errors = issues
+            .iter()
+            .filter(|issue| issue.severity == card::validate::Severity::Error)
+            .count();
+        if matches!(self.format, ListFormat::Json) {
+            let providers = providers
+                .iter()
+                .map(|(name, checked)| match checked {
+                    Ok((support, issues)) => json!({
+                        "provider": name,
+                        "support": support,
+                        "issues": issues,
+                    }),
+                    Err(error) => json!({"provider": name, "error": error}),
+                })
+                .collect::<Vec<_>>();
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&json!({
+                    "card": self.card,
+                    "valid": errors == 0,
+                    "issues": issues,
+                    "providers": providers,
+                }))?
+            );
+        } else {
+            print_card_issues(&issues, "");
+            if issues.is_empty() {
+                println!(
+                    "{}",
+                    operator_i18n::trf(
+                        "cli.card.valid",
+                        "{}: valid",
+                        &[&self.card.display().to_string()]
+                    )
+                );
+            }
+            for (name, checked) in &providers {
+                match checked {
+                    Ok((support, issues)) => {
+                        let version = support
+                            .max_version
+                            .map(|version| version.to_string())
+                            .unwrap_or_else(|| "?".to_string());
+                        println!(
+                            "{}",
+                            operator_i18n::trf(
+                                "cli.card.provider",
+                                "{} (renders up to {}):",
+                                &[name, &version]
+                            )
+                        );
+                        if issues.is_empty() {
+                            println!("  {}", operator_i18n::tr("cli.common.ok", "ok"));
+                        }
+                        print_card_issues(issues, "  ");
+                    }
+                    Err(error) => println!(
+                        "{}",
+                        operator_i18n::trf(
+                            "cli.card.provider_failed",
+                            "{}: render_plan failed: {}",
+                            &[name, error]
+                        )
+                    ),
+                }
+            }
+        }
+        if errors > 0 {
+            return Err(anyhow!("{} has {errors} error(s)", self.card.display()));
+        }
+        Ok(())
+    }
+}
+
+fn print_card_issues(issues: &[card::validate::CardIssue], indent: &str) {
+    for issue in issues {
+        let severity = match issue.severity {
+            card::validate::Severity::Error => "error",
+            card::validate::Severity::Warning => "warning",
+        };
+        let path = if issue.path.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", issue.path)
+        };
+        println!("{indent}{severity}{path}: {}", issue.message);
+    }
+}
+
+impl DemoCardNewArgs {
+    fn run(self) -> anyhow::Result<()> {
+        if card::validate::SchemaVersion::parse(&self.schema_version).is_none() {
+            return Err(anyhow!(
+                "--version {:?} is not MAJOR.MINOR",
+                self.schema_version
+            ));
+        }
+        if self.out.exists() && !self.force {
+            return Err(anyhow!(
+                "{} already exists; pass --force to overwrite it",
+                self.out.display()
+            ));
+        }
+        let card = card::templates::starter_card(&self.template, &self.title, &self.schema_version)
+            .ok_or_else(|| anyhow!("unknown template {}", self.template))?;
+        if let Some(parent) = self.out.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        fs::write(&self.out, serde_json::to_string_pretty(&card)? + "\n")
+            .with_context(|| format!("failed to write {}", self.out.display()))?;
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.card.created",
+                "Wrote {} card to {}",
+                &[&self.template, &self.out.display().to_string()]
+            )
+        );
+        Ok(())
+    }
+}
+
+impl DemoProvidersCommand {
+    fn run(self) -> anyhow::Result<()> {
+        match self.command {
+            DemoProvidersSubcommand::Capabilities(args) => args.run(),
+        }
+    }
+}
+
+impl DemoProvidersCapabilitiesArgs {
+    fn run(self) -> anyhow::Result<()> {
+        domains::ensure_cbor_packs(&self.bundle)?;
+        let discovery = discovery::discover_with_options(
+            &self.bundle,
+            discovery::DiscoveryOptions { cbor_only: true },
+        )?;
+        let providers = discovery
+            .providers
+            .iter()
+            .filter(|provider| provider.domain == "messaging")
+            .filter(|provider| {
+                self.providers.is_empty() || self.providers.contains(&provider.provider_id)
+            })
+            .map(|provider| provider.provider_id.clone())
+            .collect::<Vec<_>>();
+        if let Some(missing) = self.providers.iter().find(|name| !providers.contains(name)) {
+            return Err(anyhow!("no messaging provider {missing} in bundle"));
+        }
+        let secrets_handle =
+            secrets_gate::resolve_secrets_manager(&self.bundle, &self.tenant, Some(&self.team))?;
+        let runner_host = DemoRunnerHost::new(
+            self.bundle.clone(),
+            &discovery,
+            self.runner_binary.clone(),
+            secrets_handle,
+            false,
+        )?;
+        let context = OperatorContext {
+            tenant: self.tenant.clone(),
+            team: Some(self.team.clone()),
+            correlation_id: None,
+        };
+
+        let rows = providers
+            .iter()
+            .map(|provider| {
+                let requirements = self.requirements(&runner_host, &context, provider);
+                let mut request = api::SendMessage::new(&self.bundle, provider);
+                request.tenant = self.tenant.clone();
+                request.team = Some(self.team.clone());
+                request.env = self.env.clone();
+                request.runner_binary = self.runner_binary.clone();
+                provider_capabilities::probe_message(&mut request);
+                let planned = api::plan_message(request).map_err(|err| format!("{err:#}"));
+                provider_capabilities::merge(
+                    provider,
+                    requirements.as_ref(),
+                    planned
+                        .as_ref()
+                        .map(|planned| &planned.plan)
+                        .map_err(Clone::clone),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if matches!(self.format, ListFormat::Json) {
+            println!("{}", serde_json::to_string_pretty(&rows)?);
+            return Ok(());
+        }
+        if rows.is_empty() {
+            println!(
+                "{}",
+                operator_i18n::tr(
+                    "cli.providers.none",
+                    "no messaging providers found in bundle"
+                )
+            );
+            return Ok(());
+        }
+        let width = rows
+            .iter()
+            .map(|row| row.provider.len())
+            .max()
+            .unwrap_or_default()
+            .max("PROVIDER".len());
+        let mut header = format!("{:width$}", "PROVIDER");
+        for (feature, _) in provider_capabilities::FEATURES {
+            header.push_str(&format!("  {:<11}", feature.to_ascii_uppercase()));
+        }
+        println!("{}", header.trim_end());
+        for row in &rows {
+            let mut line = format!("{:width$}", row.provider);
+            for (feature, _) in provider_capabilities::FEATURES {
+                let mut cell = match row.features.get(*feature).copied().flatten() {
+                    Some(true) => "yes".to_string(),
+                    Some(false) => "no".to_string(),
+                    None => "?".to_string(),
+                };
+                if *feature == "cards"
+                    && let Some(version) = row.card_version
+                {
+                    cell.push_str(&format!(" ({version})"));
+                }
+                line.push_str(&format!("  {cell:<11}"));
+            }
+            println!("{}", line.trim_end());
+        }
+        for row in rows.iter().filter(|row| row.error.is_some()) {
+            println!(
+                "{}",
+                operator_i18n::trf(
+                    "cli.providers.render_plan_failed",
+                    "{}: render_plan failed: {}",
+                    &[&row.provider, row.error.as_deref().unwrap_or_default()]
+                )
+            );
+        }
+        Ok(())
+    }
+
+    /// The provider's `requirements` output, when it has the op and it succeeds.
+    fn requirements(
+        &self,
+        runner_host: &DemoRunnerHost,
+        context: &OperatorContext,
+        provider: &str,
+    ) -> Option<JsonValue> {
+        if !runner_host.supports_op(Domain::Messaging, provider, "requirements") {
+            return None;
+        }
+        let input = build_input_payload(
+            &self.bundle,
+            Domain::Messaging,
+            &self.tenant,
+            Some(&self.team),
+            None,
+            None,
+            None,
+            &self.env,
+        );
+        let bytes = serde_json::to_vec(&input).ok()?;
+        match runner_host.invoke_provider_op(
+            Domain::Messaging,
+            provider,
+            "requirements",
+            &bytes,
+            context,
+        ) {
+            Ok(outcome) if outcome.success => outcome.output,
+            Ok(outcome) => {
+                operator_log::warn(
+                    module_path!(),
+                    format!(
+                        "requirements failed provider={provider}: {}",
+                        outcome.error.unwrap_or_default()
+                    ),
+                );
+                None
+            }
+            Err(err) => {
+                operator_log::warn(
+                    module_path!(),
+                    format!("requirements failed provider={provider}: {err:#}"),
+                );
+                None
+            }
+        }
+    }
+}
+
+impl DemoStateCommand {
+    fn run(self) -> anyhow::Result<()> {
+        match self.command {
+            DemoStateSubcommand::Get(args) => args.run(),
+            DemoStateSubcommand::Set(args) => args.run(),
+            DemoStateSubcommand::List(args) => args.run(),
+        }
+    }
+}
+
+impl DemoStateGetArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let key = ConversationKey::new(&self.tenant, Some(&self.team), &self.provider, &self.chat);
+        let values = conversation_state::load(&self.bundle.join("state"), &key)?;
+        let value = match &self.key {
+            Some(name) => values.get(name).cloned().ok_or_else(|| {
+                anyhow!("no key {name} in state of {}/{}", self.provider, self.chat)
+            })?,
+            None => JsonValue::Object(values),
+        };
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        Ok(())
+    }
+}
+
+impl DemoStateSetArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let key = ConversationKey::new(&self.tenant, Some(&self.team), &self.provider, &self.chat);
+        let value = match &self.value {
+            Some(raw) if !self.unset => serde_json::from_str::<JsonValue>(raw)
+                .unwrap_or_else(|_| JsonValue::String(raw.clone())),
+            _ => JsonValue::Null,
+        };
+        let mut updates = serde_json::Map::new();
+        updates.insert(self.key.clone(), value);
+        let values = conversation_state::apply_updates(&self.bundle.join("state"), &key, &updates)?;
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&JsonValue::Object(values))?
+        );
+        Ok(())
+    }
+}
+
+impl DemoStateListArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let states = conversation_state::list(
+            &self.bundle.join("state"),
+            &self.tenant,
+            &self.team,
+            self.provider.as_deref(),
+        )?;
+        if matches!(self.format, ListFormat::Json) {
+            println!("{}", serde_json::to_string_pretty(&states)?);
+            return Ok(());
+        }
+        if states.is_empty() {
+            println!(
+                "{}",
+                operator_i18n::tr("cli.state.none", "no conversation state stored")
+            );
+            return Ok(());
+        }
+        for state in &states {
+            let keys = state.values.keys().cloned().collect::<Vec<_>>().join(",");
+            println!(
+                "{}/{} updated={} keys={keys}",
+                state.provider,
+                state.chat,
+                state.updated_at.to_rfc3339()
+            );
+        }
+        Ok(())
+    }
+}
+
+impl DemoTimersSendsArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let sends = scheduled_send::list(&self.bundle.join("state"), &self.tenant, &self.team)?;
+        if matches!(self.format, ListFormat::Json) {
+            println!("{}", serde_json::to_string_pretty(&sends)?);
+            return Ok(());
+        }
+        if sends.is_empty() {
+            println!(
+                "{}",
+                operator_i18n::tr("cli.timers.no_sends", "no scheduled sends")
+            );
+            return Ok(());
+        }
+        for send in &sends {
+            let next = send
+                .next_at
+                .map(|at| at.to_rfc3339())
+                .unwrap_or_else(|| "done".to_string());
+            let every = send
+                .every_seconds
+                .map(|seconds| format!("{seconds}s"))
+                .unwrap_or_else(|| "once".to_string());
+            let remaining = send
+                .remaining
+                .map(|left| left.to_string())
+                .unwrap_or_else(|| "unlimited".to_string());
+            let last = match (&send.last_run, &send.last_error) {
+                (None, _) => "never".to_string(),
+                (Some(at), None) => format!("ok at {}", at.to_rfc3339()),
+                (Some(at), Some(error)) => format!("error at {}: {error}", at.to_rfc3339()),
+            };
+            println!(
+                "{} provider={} next={next} every={every} remaining={remaining} runs={} last={last}",
+                send.id, send.provider, send.runs
+            );
+        }
+        Ok(())
+    }
+}
+
+impl DemoTimersCancelArgs {
+    fn run(self) -> anyhow::Result<()> {
+        if !scheduled_send::cancel(
+            &self.bundle.join("state"),
+            &self.tenant,
+            &self.team,
+            &self.id,
+        )? {
+            return Err(anyhow!(
+                "no scheduled send {} for {}/{}",
+                self.id,
+                self.tenant,
+                self.team
+            ));
+        }
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.timers.cancelled",
+                "Cancelled scheduled send {}",
+                &[&self.id]
+            )
+        );
+        Ok(())
+    }
+}
+
+impl DemoTimersListArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let demo_config = load_demo_config_or_default(&self.bundle.join("greentic.demo.yaml"));
+        let discovery = discovery::discover_with_options(
+            &self.bundle,
+            discovery::DiscoveryOptions { cbor_only: true },
+        )?;
+        let handlers = configured_timer_handlers(&demo_config, &discovery)?;
+        let runs = run_record::list_records(&self.bundle)?;
+        let state_dir = self.bundle.join("state");
+        let now = chrono::Utc::now();
+        let rows = handlers
+            .iter()
+            .map(|handler| {
+                let state = read_timer_state(&state_dir, &self.tenant, &self.team, handler)?;
+                // Without persisted timer state, fall back to the newest matching run record.
+                let last_run = match &state {
+                    Some(state) => Some(json!({
+                        "started_at": state.last_fire,
+                        "status": if state.ok { "ok" } else { "error" },
+                        "error": state.error,
+                    })),
+                    None => runs
+                        .iter()
+                        .find(|record| {
+                            record.domain == "events"
+                                && record.provider == handler.provider
+                                && record.op == handler.op_id
+                                && record.tenant == self.tenant
+                                && record.team.as_deref().unwrap_or("default") == self.team
+                        })
+                        .map(|record| {
+                            json!({
+                                "run_id": record.run_id,
+                                "started_at": record.started_at,
+                                "status": record.status.as_str(),
+                                "error": record.error,
+                            })
+                        }),
+                };
+                let missed = state
+                    .as_ref()
+                    .and_then(|state| state.last_fire_at())
+                    .map(|last_fire| handler.schedule.fires_between(last_fire, now, 100).len())
+                    .unwrap_or_default();
+                Ok(json!({
+                    "provider": handler.provider,
+                    "handler_id": handler.handler_id,
+                    "op_id": handler.op_id,
+                    "schedule": handler.schedule.describe(),
+                    "next_fire": handler.schedule.next_after(now).map(|next| next.to_rfc3339()),
+                    "last_run": last_run,
+                    "missed": missed,
+                }))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        if matches!(self.format, ListFormat::Json) {
+            println!("{}", serde_json::to_string_pretty(&rows)?);
+            return Ok(());
+        }
+        if rows.is_empty() {
+            println!(
+                "{}",
+                operator_i18n::tr("cli.timers.none", "no timer handlers found in bundle")
+            );
+            return Ok(());
+        }
+        for row in &rows {
+            let last = match &row["last_run"] {
+                JsonValue::Null => "never".to_string(),
+                last => format!(
+                    "{} at {}",
+                    last["status"].as_str().unwrap_or("unknown"),
+                    last["started_at"].as_str().unwrap_or("?")
+                ),
+            };
+            println!(
+                "{}/{} op={} schedule=\"{}\" next={} last={last} missed={}",
+                row["provider"].as_str().unwrap_or_default(),
+                row["handler_id"].as_str().unwrap_or_default(),
+                row["op_id"].as_str().unwrap_or_default(),
+                row["schedule"].as_str().unwrap_or_default(),
+                row["next_fire"].as_str().unwrap_or("-"),
+                row["missed"],
+            );
+        }
+        Ok(())
+    }
+}
+
+impl DemoSecretsCommand {
+    fn run(self) -> anyhow::Result<()> {
+        match self.command {
+            DemoSecretsSubcommand::Rekey(args) => args.run(),
+            DemoSecretsSubcommand::History(args) => args.run(),
+        }
+    }
+}
+
+impl DemoAuditCommand {
+    fn run(self) -> anyhow::Result<()> {
+        match self.command {
+            DemoAuditSubcommand::Tail(args) => args.run(),
+            DemoAuditSubcommand::Search(args) => args.run(),
+        }
+    }
+}
+
+impl DemoAuditTailArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let events = audit::read_events(&self.bundle)?;
+        let mut seen = events.len();
+        let start = seen.saturating_sub(self.lines);
+        if events.is_empty() && !self.follow {
+            println!(
+                "{}",
+                operator_i18n::tr("cli.audit.none", "no audit events recorded")
+            );
+            return Ok(());
+        }
+        for event in &events[start..] {
+            print_audit_event(event, self.format)?;
+        }
+        while self.follow {
+            std::thread::sleep(Duration::from_secs(1));
+            let events = audit::read_events(&self.bundle)?;
+            for event in events.iter().skip(seen) {
+                print_audit_event(event, self.format)?;
+            }
+            seen = seen.max(events.len());
+        }
+        Ok(())
+    }
+}
+
+impl DemoAuditSearchArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let query = audit::AuditQuery {
+            action: self.action.clone(),
+            actor: self.actor.clone(),
+            tenant: self.tenant.clone(),
+            outcome: self.outcome.map(Into::into),
+            text: self.query.clone(),
+            since: self
+                .since
+                .as_deref()
+                .map(|value| run_record::parse_since(value, Utc::now()))
+                .transpose()?,
+        };
+        let events = audit::read_events(&self.bundle)?
+            .into_iter()
+            .rev()
+            .filter(|event| query.matches(event))
+            .take(self.limit)
+            .collect::<Vec<_>>();
+        if matches!(self.format, ListFormat::Json) {
+            println!("{}", serde_json::to_string_pretty(&events)?);
+            return Ok(());
+        }
+        if events.is_empty() {
+            println!(
+                "{}",
+                operator_i18n::tr("cli.audit.no_match", "no matching audit events")
+            );
+            return Ok(());
+        }
+        for event in &events {
+            print_audit_event(event, self.format)?;
+        }
+        Ok(())
+    }
+}
+
+fn print_audit_event(event: &audit::AuditEvent, format: ListFormat) -> anyhow::Result<()> {
+    if matches!(format, ListFormat::Json) {
+        println!("{}", serde_json::to_string(event)?);
+        return Ok(());
+    }
+    let scope = match (&event.tenant, &event.team) {
+        (Some(tenant), Some(team)) => format!(" tenant={tenant} team={team}"),
+        (Some(tenant), None) => format!(" tenant={tenant}"),
+        _ => String::new(),
+    };
+    let error = event
+        .error
+        .as_deref()
+        .map(|error| format!(" error={error}"))
+        .unwrap_or_default();
+    println!(
+        "{} {} {} {} actor={}{}{}",
+        event.ts,
+        event.outcome.as_str(),
+        event.action,
+        event.target,
+        event.actor.as_deref().unwrap_or("-"),
+        scope,
+        error
+    );
+    Ok(())
+}
+
+impl DemoSecretsRekeyArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let store_path = dev_store_path::find_existing(&self.bundle).ok_or_else(|| {
+            anyhow!(
+                "no dev secrets store found in {}; run demo setup first",
+                self.bundle.display()
+            )
+        })?;
+        operator_auth::authorize(
+            &self.bundle,
+            OperatorAction::SecretsWrite,
+            &store_path.display().to_string(),
+        )?;
+        let demo_config = load_demo_config_or_default(&self.bundle.join("greentic.demo.yaml"));
+        let mut target = demo_config.secrets.dev_store.clone();
+        if let Some(to) = self.to {
+            target.encryption = to.into();
+        }
+        if let Some(passphrase_env) = self.passphrase_env {
+            target.passphrase_env = passphrase_env;
+        }
+        let target = secrets_crypto::KeySource::from_config(&target);
+
+        let env = resolve_env(self.env.as_deref());
+        let discovery = discovery::discover_with_options(
+            &self.bundle,
+            discovery::DiscoveryOptions { cbor_only: true },
+        )?;
+        let mut uris = Vec::new();
+        for provider in &discovery.providers {
+            for key in load_secret_keys_from_pack(&provider.pack_path)? {
+                uris.push(secrets_gate::canonical_secret_uri(
+                    &env,
+                    &self.tenant,
+                    Some(&self.team),
+                    &provider.provider_id,
+                    &key,
+                ));
+            }
+        }
+
+        let runtime = Runtime::new().context("failed to create secrets runtime")?;
+        let report = runtime.block_on(secrets_crypto::rekey(&store_path, uris, target.as_ref()));
+        let target = target
+            .map(|source| source.describe())
+            .unwrap_or_else(|| "plaintext".to_string());
+        audit::record(
+            &self.bundle,
+            AuditEvent::new("secrets.rekey", store_path.display().to_string())
+                .scope(&self.tenant, Some(&self.team))
+                .detail(json!({
+                    "to": target,
+                    "rewritten": report.as_ref().map(|report| report.rewritten.len()).ok(),
+                }))
+                .result(&report),
+        );
+        let report = report?;
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.secrets.rekeyed",
+                "rewrote {} secret(s) in {} as {}",
+                &[
+                    &report.rewritten.len().to_string(),
+                    &store_path.display().to_string(),
+                    &target
+                ]
+            )
+        );
+        Ok(())
+    }
+}
+
+impl DemoSecretsHistoryArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let store_path = dev_store_path::find_existing(&self.bundle).ok_or_else(|| {
+            anyhow!(
+                "no dev secrets store found in {}; run demo setup first",
+                self.bundle.display()
+            )
+        })?;
+        let env = resolve_env(self.env.as_deref());
+        let uri = secrets_gate::canonical_secret_uri(
+            &env,
+            &self.tenant,
+            Some(&self.team),
+            &self.provider,
+            &self.key,
+        );
+        let uri = secrets_gate::split_secret_uri_version(&uri).0.to_string();
+        if let Some(version) = self.activate {
+            operator_auth::authorize(&self.bundle, OperatorAction::SecretsWrite, &uri)?;
+            let runtime = Runtime::new().context("failed to create secrets runtime")?;
+            let result = runtime.block_on(secrets_versions::activate(&store_path, &uri, version));
+            audit::record(
+                &self.bundle,
+                AuditEvent::new("secrets.activate", &uri)
+                    .scope(&self.tenant, Some(&self.team))
+                    .detail(json!({ "version": version }))
+                    .result(&result),
+            );
+            result?;
+            println!(
+                "{}",
+                operator_i18n::trf(
+                    "cli.secrets.activated",
+                    "activated v{} of {}",
+                    &[&version.to_string(), &uri]
+                )
+            );
+            return Ok(());
+        }
+
+        let history = secrets_crypto::read_meta(&store_path)?
+            .versions
+            .remove(&uri)
+            .unwrap_or_default();
+        if matches!(self.format, ListFormat::Json) {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&json!({
+                    "uri": uri,
+                    "active": history.active,
+                    "versions": history.versions,
+                }))?
+            );
+            return Ok(());
+        }
+        if history.versions.is_empty() {
+            println!(
+                "{}",
+                operator_i18n::trf(
+                    "cli.secrets.no_history",
+                    "no recorded versions for {}",
+                    &[&uri]
+                )
+            );
+            return Ok(());
+        }
+        for entry in history.versions.iter().rev() {
+            let marker = if history.active == Some(entry.version) {
+                " (active)"
+            } else {
+                ""
+            };
+            println!(
+                "v{}{marker} written_at={} source={}",
+                entry.version,
+                entry.written_at,
+                entry.source.as_deref().unwrap_or("-"),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl DemoConfigCommand {
+    fn run(self) -> anyhow::Result<()> {
+        match self.command {
+            DemoConfigSubcommand::Show(args) if args.explain => {
+                let entries =
+                    config_gate::explain_inheritance(&args.bundle, &args.tenant, &args.team)?;
+                match args.format {
+                    ConfigFormat::Yaml => print_inheritance(&entries),
+                    ConfigFormat::Json => println!("{}", serde_json::to_string_pretty(&entries)?),
+                }
+                Ok(())
+            }
+            DemoConfigSubcommand::Show(args) => {
+                let effective =
+                    config_gate::effective_config(&args.bundle, &args.tenant, &args.team)?;
+                match args.format {
+                    ConfigFormat::Yaml => print!("{}", effective.to_annotated_yaml()),
+                    ConfigFormat::Json => {
+                        println!("{}", serde_json::to_string_pretty(&effective)?)
+                    }
+                }
+                Ok(())
+            }
+            DemoConfigSubcommand::Validate(args) => args.run(),
+        }
+    }
+}
+
+fn print_inheritance(entries: &[config_gate::InheritedKey]) {
+    if entries.is_empty() {
+        println!(
+            "{}",
+            operator_i18n::tr(
+                "cli.config.explain.none",
+                "No provider config or required secrets found."
+            )
+        );
+        return;
+    }
+    let key_width = entries
+        .iter()
+        .map(|entry| entry.key.len())
+        .max()
+        .unwrap_or(0);
+    let mut provider = None;
+    for entry in entries {
+        if provider != Some(entry.provider.as_str()) {
+            println!("{}", entry.provider);
+            provider = Some(entry.provider.as_str());
+        }
+        let kind = match entry.kind {
+            config_gate::InheritedKind::Config => "config",
+            config_gate::InheritedKind::Secret => "secret",
+        };
+        let level = entry
+            .level
+            .map(|level| level.as_str().to_string())
+            .unwrap_or_else(|| operator_i18n::tr("cli.config.explain.missing", "missing"));
+        let line = format!(
+            "  {kind:<6}  {:<key_width$}  {level:<7}  {}",
+            entry.key,
+            entry.from.as_deref().unwrap_or("")
+        );
+        println!("{}", line.trim_end());
+    }
+}
+
+impl DemoConfigValidateArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let mut reports = Vec::new();
+        for (file, kind) in [
+            ("greentic.yaml", config::ConfigFileKind::Operator),
+            ("greentic.demo.yaml", config::ConfigFileKind::Demo),
+        ] {
+            let path = self.bundle.join(file);
+            if path.exists() {
+                let issues = config::validate_config_file(&path, kind)?;
+                reports.push((path, issues));
+            }
+        }
+        if reports.is_empty() {
+            return Err(anyhow::anyhow!(
+                "no greentic.yaml or greentic.demo.yaml in {}",
+                self.bundle.display()
+            ));
+        }
+        match self.format {
+            ListFormat::Json => {
+                let value = reports
+                    .iter()
+                    .map(|(path, issues)| serde_json::json!({"file": path, "issues": issues}))
+                    .collect::<Vec<_>>();
+                println!("{}", serde_json::to_string_pretty(&value)?);
+            }
+            ListFormat::Text => {
+                for (path, issues) in &reports {
+                    if issues.is_empty() {
+                        println!(
+                            "{}",
+                            operator_i18n::trf(
+                                "cli.demo.config.validate.ok",
+                                "ok {}",
+                                &[&path.display().to_string()]
+                            )
+                        );
+                    }
+                    for issue in issues {
+                        let location = match issue.line {
+                            Some(line) => format!("{}:{line}", path.display()),
+                            None => path.display().to_string(),
+                        };
+                        let severity = if issue.is_error() {
+                            operator_i18n::tr("cli.demo.config.validate.error", "error")
+                        } else {
+                            operator_i18n::tr("cli.demo.config.validate.warning", "warning")
+                        };
+                        println!("{location}: {severity}: {}", issue.message);
+                    }
+                }
+            }
+        }
+        for (path, issues) in &reports {
+            let failing = issues
+                .iter()
+                .filter(|issue| issue.is_error() || self.strict)
+                .count();
+            if failing > 0 {
+                return Err(OperatorError::ConfigInvalid {
+                    path: path.clone(),
+                    detail: format!("{failing} problem(s) found"),
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DemoSnapshotCommand {
+    fn run(self) -> anyhow::Result<()> {
+        match self.command {
+            DemoSnapshotSubcommand::Record(args) => {
+                let results = demo::snapshot::record(&args.options())?;
+                match args.format {
+                    ListFormat::Json => println!("{}", serde_json::to_string_pretty(&results)?),
+                    ListFormat::Text => {
+                        for result in &results {
+                            println!(
+                                "{}",
+                                operator_i18n::trf(
+                                    "cli.demo.snapshot.recorded",
+                                    "recorded {} -> {}",
+                                    &[&result.name, &result.path.display().to_string()]
+                                )
+                            );
+                        }
+                    }
+                }
+                Ok(())
+            }
+            DemoSnapshotSubcommand::Check(args) => {
+                let results = demo::snapshot::check(&args.options())?;
+                match args.format {
+                    ListFormat::Json => println!("{}", serde_json::to_string_pretty(&results)?),
+                    ListFormat::Text => {
+                        for result in &results {
+                            if result.changes.is_empty() {
+                                println!(
+                                    "{}",
+                                    operator_i18n::trf(
+                                        "cli.demo.snapshot.unchanged",
+                                        "ok {}",
+                                        &[&result.name]
+                                    )
+                                );
+                                continue;
+                            }
+                            println!(
+                                "{}",
+                                operator_i18n::trf(
+                                    "cli.demo.snapshot.changed",
+                                    "CHANGED {}",
+                                    &[&result.name]
+                                )
+                            );
+                            for change in &result.changes {
+                                println!(
+                                    "{}",
+                                    operator_i18n::trf(
+                                        "cli.demo.snapshot.change",
+                                        "  {}",
+                                        &[change]
+                                    )
+                                );
+                            }
+                        }
+                    }
+                }
+                let changed = results
+                    .iter()
+                    .filter(|result| !result.changes.is_empty())
+                    .count();
+                if changed > 0 {
+                    return Err(anyhow::anyhow!(
+                        "{changed} of {} snapshot(s) changed; run demo snapshot record to accept",
+                        results.len()
+                    ));
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl DemoSnapshotArgs {
+    fn options(&self) -> demo::snapshot::SnapshotOptions {
+        demo::snapshot::SnapshotOptions {
+            bundle: self.bundle.clone(),
+            provider: self.provider.clone(),
+            corpus: self
+                .corpus
+                .clone()
+                .unwrap_or_else(|| demo::snapshot::default_corpus_path(&self.bundle)),
+            tenant: self.tenant.clone(),
+            team: Some(self.team.clone()).filter(|team| !team.is_empty()),
+            runner_binary: self.runner_binary.clone(),
+        }
+    }
+}
+
+impl DemoDlqCommand {
+    fn run(self) -> anyhow::Result<()> {
+        match self.command {
+            DemoDlqSubcommand::List(args) => args.run(),
+            DemoDlqSubcommand::Replay(args) => args.run(),
+            DemoDlqSubcommand::Purge(args) => args.run(),
+        }
+    }
+}
+
+/// Loads the DLQ records for `tenant`/`team`, optionally narrowed to one provider.
+fn load_dlq_records(
+    bundle: &Path,
+    tenant: &str,
+    team: &str,
+    provider: Option<&str>,
+) -> anyhow::Result<Vec<messaging_dlq::DlqRecord>> {
+    Ok(
+        messaging_dlq::list_records(&bundle.join("state"), provider)?
+            .into_iter()
+            .filter(|record| {
+                record.tenant == tenant && record.team.as_deref().unwrap_or("default") == team
+            })
+            .collect(),
+    )
+}
+
+fn dlq_record_selected(record: &messaging_dlq::DlqRecord, job_ids: &[String], all: bool) -> bool {
+    all || job_ids.iter().any(|wanted| *wanted == record.job_id)
+}
+
+impl DemoDlqListArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let records = load_dlq_records(
+            &self.bundle,
+            &self.tenant,
+            &self.team,
+            self.provider.as_deref(),
+        )?;
+        if matches!(self.format, ListFormat::Json) {
+            println!("{}", serde_json::to_string_pretty(&records)?);
+            return Ok(());
+        }
+        if records.is_empty() {
+            println!("{}", operator_i18n::tr("cli.dlq.empty", "DLQ is empty"));
+            return Ok(());
+        }
+        for record in &records {
+            println!(
+                "{} {} {} attempts={}/{} replays={} reason={}",
+                record.job_id,
+                record.failed_at,
+                record.provider,
+                record.attempt,
+                record.max_attempts,
+                record.replay_count,
+                record.failure_message()
+            );
+        }
+        Ok(())
+    }
+}
+
+impl DemoDlqReplayArgs {
+    fn run(self) -> anyhow::Result<()> {
+        if !self.all && self.job_ids.is_empty() {
+            return Err(anyhow!("pass --job-id <ID> (repeatable) or --all"));
+        }
+        let state_dir = self.bundle.join("state");
+        let selected = load_dlq_records(
+            &self.bundle,
+            &self.tenant,
+            &self.team,
+            self.provider.as_deref(),
+        )?
+        .into_iter()
+        .filter(|record| dlq_record_selected(record, &self.job_ids, self.all))
+        .collect::<Vec<_>>();
+        if selected.is_empty() {
+            println!(
+                "{}",
+                operator_i18n::tr("cli.dlq.nothing_selected", "no DLQ entries selected")
+            );
+            return Ok(());
+        }
+
+        let team = if self.team.is_empty() {
+            None
+        } else {
+            Some(self.team.clone())
+        };
+        let secrets_handle =
+            secrets_gate::resolve_secrets_manager(&self.bundle, &self.tenant, team.as_deref())?;
+        let policy = RetryPolicy::for_bundle(&self.bundle, self.retries);
+        let mut total = egress::EgressSummary::default();
+        for record in selected {
+            // Drop the record first; a delivery that fails again is dead-lettered anew.
+            if !self.dry_run {
+                messaging_dlq::remove_record(&state_dir, &record)?;
+            }
+            let summary = egress::replay_dlq_record(
+                &record,
+                &self.bundle,
+                self.runner_binary.clone(),
+                !self.dry_run,
+                &policy,
+                secrets_handle.clone(),
+            )?;
+            total.delivered += summary.delivered;
+            total.dead_lettered += summary.dead_lettered;
+        }
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.dlq.replay_summary",
+                "replayed: delivered={} dead_lettered={}",
+                &[
+                    &total.delivered.to_string(),
+                    &total.dead_lettered.to_string()
+                ]
+            )
+        );
+        Ok(())
+    }
+}
+
+impl DemoDlqPurgeArgs {
+    fn run(self) -> anyhow::Result<()> {
+        if !self.all && self.job_ids.is_empty() {
+            return Err(anyhow!("pass --job-id <ID> (repeatable) or --all"));
+        }
+        let state_dir = self.bundle.join("state");
+        let mut purged = 0usize;
+        for record in load_dlq_records(
+            &self.bundle,
+            &self.tenant,
+            &self.team,
+            self.provider.as_deref(),
+        )? {
+            if dlq_record_selected(&record, &self.job_ids, self.all) {
+                messaging_dlq::remove_record(&state_dir, &record)?;
+                purged += 1;
+            }
+        }
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.dlq.purged",
+                "purged {} DLQ entries",
+                &[&purged.to_string()]
+            )
+        );
+        Ok(())
+    }
 }
 
 impl DemoCapabilityCommand {
     fn run(self) -> anyhow::Result<()> {
         match self.command {
+            DemoCapabilitySubcommand::List(args) => args.run(),
+            DemoCapabilitySubcommand::Resolve(args) => args.run(),
             DemoCapabilitySubcommand::Invoke(args) => args.run(),
             DemoCapabilitySubcommand::SetupPlan(args) => args.run(),
             DemoCapabilitySubcommand::MarkReady(args) => args.run(),
@@ -1082,6 +5221,13 @@ impl DemoRunArgs {
             Some(value) => Some(demo_input::parse_input(&value)?),
             None => None,
         };
+        let initial_input = parsed_input
+            .as_ref()
+            .map(|parsed| parsed.value.clone())
+            .unwrap_or_else(|| json!({}));
+        if let Some(schema) = pack.input_schema(&flow_id)? {
+            check_flow_input(&schema, &initial_input, &flow_id, &pack_path)?;
+        }
         let team_display = self.team.as_deref().unwrap_or("default");
         let input_desc = match &parsed_input {
             None => "none".to_string(),
@@ -1094,65 +5240,237 @@ impl DemoRunArgs {
                 }
             },
         };
-        println!(
-            "{}",
-            operator_i18n::tr("cli.run.summary_header", "Run summary:")
-        );
-        println!(
-            "{}",
+        // With --no-repl and no --output, stdout carries only the JSON result.
+        let summary_to_stdout = !self.no_repl || self.output.is_some();
+        let summary = [
+            operator_i18n::tr("cli.run.summary_header", "Run summary:"),
             operator_i18n::trf(
                 "cli.run.summary_pack",
                 "  pack: {} ({})",
-                &[&pack.pack_id, &pack_path.display().to_string()]
-            )
-        );
-        println!(
-            "{}",
+                &[&pack.pack_id, &pack_path.display().to_string()],
+            ),
             operator_i18n::trf(
                 "cli.run.summary_tenant_team",
                 "  tenant: {} team: {}",
-                &[&self.tenant, team_display]
-            )
-        );
-        println!(
-            "{}",
-            operator_i18n::trf("cli.run.summary_flow", "  flow: {}", &[&flow_id])
-        );
+                &[&self.tenant, team_display],
+            ),
+            operator_i18n::trf("cli.run.summary_flow", "  flow: {}", &[&flow_id]),
+            operator_i18n::trf("cli.run.summary_input", "  input: {}", &[&input_desc]),
+        ];
+        for line in &summary {
+            if summary_to_stdout {
+                println!("{line}");
+            } else {
+                eprintln!("{line}");
+            }
+        }
+
+        let secrets_manager = if let Some(bundle) = &self.bundle {
+            let secrets_handle =
+                secrets_gate::resolve_secrets_manager(bundle, &self.tenant, self.team.as_deref())?;
+            secrets_handle.runtime_manager(Some(&pack.pack_id))
+        } else {
+            default_manager()?
+        };
+        let mut runner = DemoRunner::with_entry_flow(
+            pack_path,
+            &self.tenant,
+            self.team.clone(),
+            flow_id.clone(),
+            pack.pack_id.clone(),
+            initial_input,
+            secrets_manager,
+        )?;
+        if self.no_stream {
+            flow_stream::disable();
+        }
+        if self.no_repl {
+            let _stream = flow_stream::scoped(Arc::new(|event: &flow_stream::StreamEvent| {
+                eprintln!("  | {}", event.render());
+            }));
+            return run_once_report(&mut runner, &pack.pack_id, &flow_id, self.output.as_deref());
+        }
+        for node in &self.breakpoints {
+            runner.debugger().add_breakpoint(node);
+        }
+        runner.debugger().set_stepping(self.step);
+        let mut repl = DemoRepl::new(runner);
         println!(
             "{}",
-            operator_i18n::trf("cli.run.summary_input", "  input: {}", &[&input_desc])
+            operator_i18n::tr(
+                "cli.run.enter_interactive",
+                "Entering interactive mode (type @help for commands)."
+            )
         );
+        let _stream = flow_stream::scoped(Arc::new(|event: &flow_stream::StreamEvent| {
+            println!("  | {}", event.render());
+        }));
+        repl.run()?;
+        Ok(())
+    }
+}
+
+/// Rejects `input` when it does not match the flow's declared input schema, after
+/// listing each bad field and an input that would match.
+fn check_flow_input(
+    schema: &JsonValue,
+    input: &JsonValue,
+    flow_id: &str,
+    pack_path: &Path,
+) -> anyhow::Result<()> {
+    let errors = demo::input_schema::validate(schema, input).map_err(|err| {
+        operator_error::manifest_invalid(pack_path, format!("flow {flow_id}: {err:#}"))
+    })?;
+    if errors.is_empty() {
+        return Ok(());
+    }
+    eprintln!(
+        "{}",
+        operator_i18n::trf(
+            "cli.run.input_invalid",
+            "Input does not match the input schema of flow {}:",
+            &[flow_id]
+        )
+    );
+    for error in &errors {
+        eprintln!("  {error}");
+    }
+    eprintln!(
+        "{}",
+        operator_i18n::tr("cli.run.input_example", "Example input:")
+    );
+    eprintln!(
+        "{}",
+        serde_json::to_string_pretty(&demo::input_schema::example(schema))?
+    );
+    Err(anyhow!(
+        "input does not match the input schema of flow {flow_id} ({} error(s))",
+        errors.len()
+    ))
+}
+
+/// Runs the flow until it finishes, waits or fails, and reports the outcome as
+/// one JSON document for `demo run --no-repl`. The report is printed or written
+/// before a waiting or failed run returns its error, so the exit code and the file agree.
+fn run_once_report(
+    runner: &mut DemoRunner,
+    pack_id: &str,
+    flow_id: &str,
+    output: Option<&Path>,
+) -> anyhow::Result<()> {
+    let report = RunReport::new(runner.run_until_blocked(), pack_id, flow_id);
+    match output {
+        Some(path) => {
+            report.write(path)?;
+            println!(
+                "{}",
+                operator_i18n::trf(
+                    "cli.run.result_written",
+                    "Run result ({}) written to {}",
+                    &[report.status(), &path.display().to_string()]
+                )
+            );
+        }
+        None => println!("{}", report.render()?),
+    }
+    report.into_result()
+}
+
+/// Carries out control API requests through the `demo send`, `demo allow|forbid`,
+/// and `demo run` code paths, defaulting to the target `demo start` runs. Reloads
+/// are handed to the `demo start` main loop, which owns the services they restart.
+struct DemoControlBackend {
+    bundle: PathBuf,
+    tenant: String,
+    team: Option<String>,
+    env: String,
+    runner_binary: Option<PathBuf>,
+    reload: ReloadTrigger,
+}
 
-        let initial_input = parsed_input
-            .as_ref()
-            .map(|parsed| parsed.value.clone())
-            .unwrap_or_else(|| json!({}));
-        let secrets_manager = if let Some(bundle) = &self.bundle {
-            let secrets_handle =
-                secrets_gate::resolve_secrets_manager(bundle, &self.tenant, self.team.as_deref())?;
-            secrets_handle.runtime_manager(Some(&pack.pack_id))
-        } else {
-            default_manager()?
+impl ControlBackend for DemoControlBackend {
+    fn send(&self, request: SendRequest) -> anyhow::Result<JsonValue> {
+        let args = match request.args {
+            Some(JsonValue::Object(map)) => map,
+            Some(_) => return Err(anyhow!("args must be a JSON object")),
+            None => JsonMap::new(),
         };
-        let runner = DemoRunner::with_entry_flow(
+        let sent = api::send_message(api::SendMessage {
+            bundle: self.bundle.clone(),
+            provider: request.provider,
+            tenant: request.tenant.unwrap_or_else(|| self.tenant.clone()),
+            team: request
+                .team
+                .or_else(|| self.team.clone())
+                .or_else(|| Some(DEMO_DEFAULT_TEAM.to_string())),
+            text: request.text,
+            args,
+            to: request.to,
+            to_kind: request.to_kind,
+            card: None,
+            attachments: Vec::new(),
+            runner_binary: self.runner_binary.clone(),
+            env: self.env.clone(),
+            http: None,
+        })?;
+        Ok(serde_json::to_value(sent)?)
+    }
+
+    fn run_flow(&self, request: RunFlowRequest) -> anyhow::Result<JsonValue> {
+        let tenant = request.tenant.unwrap_or_else(|| self.tenant.clone());
+        let team = request.team.or_else(|| self.team.clone());
+        let packs_dir = self.bundle.join("packs");
+        let pack = pack_resolve::resolve_pack(&packs_dir, &request.pack)?;
+        let pack_path = ensure_pack_within_root(&packs_dir, &pack.pack_path)?;
+        let flow_id = pack.select_flow(request.flow.as_deref())?;
+        let secrets_manager =
+            secrets_gate::resolve_secrets_manager(&self.bundle, &tenant, team.as_deref())?
+                .runtime_manager(Some(&pack.pack_id));
+        let mut runner = DemoRunner::with_entry_flow(
             pack_path,
-            &self.tenant,
-            self.team.clone(),
+            &tenant,
+            team,
             flow_id.clone(),
             pack.pack_id.clone(),
-            initial_input,
+            request.input.unwrap_or_else(|| json!({})),
             secrets_manager,
         )?;
-        let mut repl = DemoRepl::new(runner);
-        println!(
-            "{}",
-            operator_i18n::tr(
-                "cli.run.enter_interactive",
-                "Entering interactive mode (type @help for commands)."
-            )
-        );
-        repl.run()?;
-        Ok(())
+        match runner.run_until_blocked() {
+            demo::DemoBlockedOn::Finished(output) => Ok(json!({
+                "status": "finished",
+                "pack": pack.pack_id,
+                "flow": flow_id,
+                "output": output,
+            })),
+            demo::DemoBlockedOn::Waiting { reason, output, .. } => Ok(json!({
+                "status": "waiting",
+                "pack": pack.pack_id,
+                "flow": flow_id,
+                "reason": reason,
+                "output": output,
+            })),
+            demo::DemoBlockedOn::Error(err) => Err(err),
+        }
+    }
+
+    fn edit_policy(&self, request: PolicyRequest) -> anyhow::Result<JsonValue> {
+        let policy = match request.policy {
+            PolicyAction::Allow => Policy::Public,
+            PolicyAction::Forbid => Policy::Forbidden,
+        };
+        DemoPolicyArgs {
+            bundle: self.bundle.clone(),
+            tenant: request.tenant.unwrap_or_else(|| self.tenant.clone()),
+            team: request.team.or_else(|| self.team.clone()),
+            path: request.path.clone(),
+            dry_run: false,
+        }
+        .run(policy)?;
+        Ok(json!({ "success": true, "path": request.path }))
+    }
+
+    fn reload(&self) -> anyhow::Result<JsonValue> {
+        self.reload.request()
     }
 }
 
@@ -1174,7 +5492,7 @@ fn ensure_pack_within_root(root: &Path, pack_path: &Path) -> anyhow::Result<Path
 
 impl DemoListPacksArgs {
     fn run(self, _ctx: &AppCtx) -> anyhow::Result<()> {
-        let domain = Domain::from(self.domain);
+        let domain = self.domain.resolve(&self.bundle)?;
         let cfg = domains::config(domain);
         let packs = demo_provider_packs(&self.bundle, domain)?;
         let providers_root = self.bundle.join(cfg.providers_dir);
@@ -1260,7 +5578,7 @@ impl DemoListPacksArgs {
 
 impl DemoListFlowsArgs {
     fn run(self, _ctx: &AppCtx) -> anyhow::Result<()> {
-        let domain = Domain::from(self.domain);
+        let domain = self.domain.resolve(&self.bundle)?;
         let pack = demo_provider_pack_by_filter(&self.bundle, domain, &self.pack)?;
         println!(
             "{}",
@@ -1294,109 +5612,38 @@ impl DemoSubscriptionsEnsureArgs {
             client_state,
             user_id,
             user_token_key,
+            failure,
         } = self;
 
-        let team_override = if team.trim().is_empty() {
-            None
-        } else {
-            Some(team)
-        };
-
-        domains::ensure_cbor_packs(&bundle)?;
-        let pack = resolve_demo_provider_pack(
-            &bundle,
-            &tenant,
-            team_override.as_deref(),
-            &provider,
-            Domain::Messaging,
-        )?;
-        let discovery = discovery::discover_with_options(
-            &bundle,
-            discovery::DiscoveryOptions { cbor_only: true },
-        )?;
-        let provider_map = discovery_map(&discovery.providers);
-        let provider_id = provider_id_for_pack(&pack.path, &pack.pack_id, Some(&provider_map));
-
-        let secrets_handle =
-            secrets_gate::resolve_secrets_manager(&bundle, &tenant, team_override.as_deref())?;
-        let runner_host = DemoRunnerHost::new(
-            bundle.clone(),
-            &discovery,
-            None,
-            secrets_handle.clone(),
-            false,
-        )?;
-        let context = OperatorContext {
-            tenant: tenant.clone(),
-            team: team_override.clone(),
-            correlation_id: None,
-        };
-        let service = SubscriptionService::new(runner_host, context);
-
-        let binding_id = binding_id.unwrap_or_else(|| Uuid::new_v4().to_string());
-        let request = build_subscription_request(
-            &binding_id,
+        let ensured = match api::ensure_subscription(api::EnsureSubscription {
+            bundle,
+            provider,
+            tenant,
+            team: Some(team),
+            binding_id,
             resource,
             change_types,
             notification_url,
             client_state,
             user_id,
             user_token_key,
-        );
-        let state = service.ensure_once(&provider_id, &request)?;
-
-        let store = SubscriptionStore::new(state_root(&bundle));
-        store.write_state(&state)?;
-        let state_path = store.state_path(
-            &state.provider,
-            &state.tenant,
-            state.team.as_deref(),
-            &state.binding_id,
-        );
+        }) {
+            Ok(ensured) => ensured,
+            Err(err) if failure.best_effort() => {
+                report_best_effort_failure("subscriptions ensure", &format!("{err:#}"));
+                return Ok(());
+            }
+            Err(err) => return Err(err),
+        };
         println!(
             "subscription binding {} persisted to {}",
-            state.binding_id,
-            state_path.display()
+            ensured.state.binding_id,
+            ensured.state_path.display()
         );
         Ok(())
     }
 }
 
-fn build_subscription_request(
-    binding_id: &str,
-    resource: Option<String>,
-    change_types: Vec<String>,
-    notification_url: Option<String>,
-    client_state: Option<String>,
-    user_id: Option<String>,
-    user_token_key: Option<String>,
-) -> SubscriptionEnsureRequest {
-    let change_types = if change_types.is_empty() {
-        vec!["created".to_string()]
-    } else {
-        change_types
-    };
-    let user = match (user_id, user_token_key) {
-        (Some(user_id), Some(token_key)) => Some(AuthUserRefV1 {
-            user_id,
-            token_key,
-            tenant_id: None,
-            email: None,
-            display_name: None,
-        }),
-        _ => None,
-    };
-    SubscriptionEnsureRequest {
-        binding_id: binding_id.to_string(),
-        resource,
-        change_types,
-        notification_url,
-        client_state,
-        user,
-        expiration_target_unix_ms: None,
-    }
-}
-
 impl DemoSubscriptionsStatusArgs {
     fn run(self) -> anyhow::Result<()> {
         let DemoSubscriptionsStatusArgs {
@@ -1471,6 +5718,7 @@ impl DemoSubscriptionsRenewArgs {
             tenant,
             team,
             skew_minutes,
+            failure,
         } = self;
         let team_override = if team.trim().is_empty() {
             None
@@ -1492,16 +5740,33 @@ impl DemoSubscriptionsRenewArgs {
                 .ok_or_else(|| {
                     anyhow!("subscription {binding} not found for provider {provider}")
                 })?;
-            scheduler.renew_binding(&state)?;
-            println!(
-                "{}",
-                operator_i18n::trf("cli.subscriptions.renewed", "renewed {}", &[&binding])
-            );
+            match scheduler.renew_binding(&state) {
+                Err(err) if failure.best_effort() => {
+                    report_best_effort_failure("subscriptions renew", &format!("{err:#}"));
+                }
+                result => {
+                    result?;
+                    println!(
+                        "{}",
+                        operator_i18n::trf("cli.subscriptions.renewed", "renewed {}", &[&binding])
+                    );
+                }
+            }
             return Ok(());
         }
 
         let skew = Duration::from_secs(skew_minutes * 60);
-        scheduler.renew_due(skew)?;
+        for state in scheduler.due_states(skew)? {
+            let result = scheduler
+                .renew_binding(&state)
+                .with_context(|| format!("renew subscription {}", state.binding_id));
+            match result {
+                Err(err) if failure.best_effort() => {
+                    report_best_effort_failure("subscriptions renew", &format!("{err:#}"));
+                }
+                result => result?,
+            }
+        }
         println!(
             "{}",
             operator_i18n::tr(
@@ -1521,7 +5786,9 @@ impl DemoSubscriptionsDeleteArgs {
             provider,
             tenant,
             team,
+            failure,
         } = self;
+        operator_auth::authorize(&bundle, OperatorAction::SubscriptionsDelete, &binding_id)?;
         let team_override = if team.trim().is_empty() {
             None
         } else {
@@ -1538,7 +5805,21 @@ impl DemoSubscriptionsDeleteArgs {
             .ok_or_else(|| {
                 anyhow!("subscription {binding_id} not found for provider {provider}")
             })?;
-        scheduler.delete_binding(&state)?;
+        let result = scheduler.delete_binding(&state);
+        audit::record(
+            &bundle,
+            AuditEvent::new("subscriptions.delete", &binding_id)
+                .scope(&tenant, team_override.as_deref())
+                .detail(json!({ "provider": provider }))
+                .result(&result),
+        );
+        if let Err(err) = result {
+            if !failure.best_effort() {
+                return Err(err);
+            }
+            report_best_effort_failure("subscriptions delete", &format!("{err:#}"));
+            return Ok(());
+        }
         println!(
             "{}",
             operator_i18n::trf("cli.subscriptions.deleted", "deleted {}", &[&binding_id])
@@ -1547,6 +5828,235 @@ impl DemoSubscriptionsDeleteArgs {
     }
 }
 
+/// Builds the capability registry for a bundle without starting any services.
+fn load_capability_registry(
+    bundle: &Path,
+    tenant: &str,
+    team: &str,
+) -> anyhow::Result<capabilities::CapabilityRegistry> {
+    domains::ensure_cbor_packs(bundle)?;
+    let discovery =
+        discovery::discover_with_options(bundle, discovery::DiscoveryOptions { cbor_only: true })?;
+    let secrets_handle = secrets_gate::resolve_secrets_manager(bundle, tenant, Some(team))?;
+    let runner_host = DemoRunnerHost::new(
+        bundle.to_path_buf(),
+        &discovery,
+        None,
+        secrets_handle,
+        false,
+    )?;
+    Ok(runner_host.capability_registry().clone())
+}
+
+fn capability_scope(env_override: Option<&str>, tenant: &str, team: &str) -> ResolveScope {
+    ResolveScope {
+        env: env_override
+            .map(str::to_string)
+            .or_else(|| env::var("GREENTIC_ENV").ok()),
+        tenant: Some(tenant.to_string()),
+        team: Some(team.to_string()),
+    }
+}
+
+/// `ready`/`failed` from the install record, `pending` without one, `-` when no setup is needed.
+fn capability_setup_status(
+    bundle: &Path,
+    tenant: &str,
+    team: &str,
+    offer: &capabilities::CapabilityOfferRecord,
+) -> String {
+    if !offer.requires_setup {
+        return "-".to_string();
+    }
+    match capabilities::read_install_record(bundle, tenant, Some(team), &offer.stable_id) {
+        Ok(Some(record)) => record.status,
+        Ok(None) => "pending".to_string(),
+        Err(_) => "unreadable".to_string(),
+    }
+}
+
+impl DemoCapabilityListArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let registry = load_capability_registry(&self.bundle, &self.tenant, &self.team)?;
+        let scope = capability_scope(self.env.as_deref(), &self.tenant, &self.team);
+        let cap_ids = registry
+            .cap_ids()
+            .filter(|cap_id| {
+                self.cap_id
+                    .as_deref()
+                    .is_none_or(|wanted| wanted == *cap_id)
+            })
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        let mut rows = Vec::new();
+        for cap_id in &cap_ids {
+            let winner = registry
+                .resolve(cap_id, None, &scope)
+                .map(|binding| binding.stable_id);
+            for offer in registry.offers_for_capability(cap_id) {
+                rows.push(json!({
+                    "cap_id": offer.cap_id,
+                    "stable_id": offer.stable_id,
+                    "pack_id": offer.pack_id,
+                    "domain": domains::domain_name(offer.domain),
+                    "component_ref": offer.provider_component_ref,
+                    "op": offer.provider_op,
+                    "version": offer.version,
+                    "priority": offer.priority,
+                    "scope": offer.scope_summary(),
+                    "setup": capability_setup_status(&self.bundle, &self.tenant, &self.team, offer),
+                    "resolved": winner.as_deref() == Some(offer.stable_id.as_str()),
+                }));
+            }
+        }
+        if matches!(self.format, ListFormat::Json) {
+            println!("{}", serde_json::to_string_pretty(&rows)?);
+            return Ok(());
+        }
+        if rows.is_empty() {
+            println!(
+                "{}",
+                operator_i18n::tr(
+                    "cli.capabilities.none_offered",
+                    "no capability offers found in bundle"
+                )
+            );
+            return Ok(());
+        }
+        let mut current_cap = "";
+        for row in &rows {
+            let cap_id = row["cap_id"].as_str().unwrap_or_default();
+            if cap_id != current_cap {
+                println!("{cap_id}");
+                current_cap = cap_id;
+            }
+            println!(
+                "  {} {} pack={} op={}/{} version={} priority={} {} setup={}",
+                if row["resolved"] == json!(true) {
+                    "*"
+                } else {
+                    " "
+                },
+                row["stable_id"].as_str().unwrap_or_default(),
+                row["pack_id"].as_str().unwrap_or_default(),
+                row["component_ref"].as_str().unwrap_or_default(),
+                row["op"].as_str().unwrap_or_default(),
+                row["version"].as_str().unwrap_or_default(),
+                row["priority"],
+                row["scope"].as_str().unwrap_or_default(),
+                row["setup"].as_str().unwrap_or_default(),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl DemoCapabilityResolveArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let registry = load_capability_registry(&self.bundle, &self.tenant, &self.team)?;
+        let scope = capability_scope(self.env.as_deref(), &self.tenant, &self.team);
+        let explanation = registry.explain_resolve(&self.cap_id, self.version.as_deref(), &scope);
+        if !self.explain {
+            let Some(winner) = explanation
+                .candidates
+                .iter()
+                .find(|candidate| candidate.verdict == CandidateVerdict::Selected)
+            else {
+                anyhow::bail!(
+                    "capability {} did not resolve: {}",
+                    self.cap_id,
+                    explanation.reason
+                );
+            };
+            println!(
+                "{} pack={} op={}/{}",
+                winner.offer.stable_id,
+                winner.offer.pack_id,
+                winner.offer.provider_component_ref,
+                winner.offer.provider_op
+            );
+            return Ok(());
+        }
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.capability.explain.capability",
+                "capability: {}",
+                &[&explanation.cap_id]
+            )
+        );
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.capability.explain.scope",
+                "scope: env={} tenant={} team={}",
+                &[
+                    explanation.scope.env.as_deref().unwrap_or("-"),
+                    explanation.scope.tenant.as_deref().unwrap_or("-"),
+                    explanation.scope.team.as_deref().unwrap_or("-"),
+                ]
+            )
+        );
+        if let Some(version) = &explanation.min_version {
+            println!(
+                "{}",
+                operator_i18n::trf("cli.capability.explain.version", "version: {}", &[version])
+            );
+        }
+        println!(
+            "{}",
+            operator_i18n::tr("cli.capability.explain.candidates", "candidates:")
+        );
+        for (idx, candidate) in explanation.candidates.iter().enumerate() {
+            let verdict = match &candidate.verdict {
+                CandidateVerdict::Selected => {
+                    operator_i18n::tr("cli.capability.explain.selected", "selected")
+                }
+                CandidateVerdict::Outranked { by } => {
+                    operator_i18n::trf("cli.capability.explain.outranked", "outranked by {}", &[by])
+                }
+                CandidateVerdict::VersionMismatch { requested } => operator_i18n::trf(
+                    "cli.capability.explain.version_mismatch",
+                    "skipped: version {} != {}",
+                    &[&candidate.offer.version, requested],
+                ),
+                CandidateVerdict::ScopeMismatch { field } => operator_i18n::trf(
+                    "cli.capability.explain.scope_mismatch",
+                    "skipped: {} not in offer scope",
+                    &[field],
+                ),
+            };
+            println!(
+                "  {}. {} pack={} priority={} {} setup={} -> {verdict}",
+                idx + 1,
+                candidate.offer.stable_id,
+                candidate.offer.pack_id,
+                candidate.offer.priority,
+                candidate.offer.scope_summary(),
+                capability_setup_status(&self.bundle, &self.tenant, &self.team, &candidate.offer),
+            );
+        }
+        let none = operator_i18n::tr("cli.capability.explain.no_winner", "<none>");
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.capability.explain.winner",
+                "winner: {}",
+                &[explanation.winner.as_deref().unwrap_or(&none)]
+            )
+        );
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.capability.explain.reason",
+                "reason: {}",
+                &[&explanation.reason]
+            )
+        );
+        Ok(())
+    }
+}
+
 impl DemoCapabilityInvokeArgs {
     fn run(self) -> anyhow::Result<()> {
         if let Some(env_value) = self.env.as_ref() {
@@ -1562,8 +6072,16 @@ impl DemoCapabilityInvokeArgs {
         )?;
         let secrets_handle =
             secrets_gate::resolve_secrets_manager(&self.bundle, &self.tenant, Some(&self.team))?;
+        let recording = if self.mock {
+            CapabilityRecordingMode::Mock
+        } else if self.record {
+            CapabilityRecordingMode::Record
+        } else {
+            CapabilityRecordingMode::from_env()
+        };
         let runner_host =
-            DemoRunnerHost::new(self.bundle.clone(), &discovery, None, secrets_handle, false)?;
+            DemoRunnerHost::new(self.bundle.clone(), &discovery, None, secrets_handle, false)?
+                .with_capability_recording(recording);
         let ctx = OperatorContext {
             tenant: self.tenant.clone(),
             team: Some(self.team.clone()),
@@ -1755,15 +6273,256 @@ impl Cli {
     pub fn run(self) -> anyhow::Result<()> {
         let selected_locale = operator_i18n::select_locale(self.locale.as_deref());
         operator_i18n::set_locale(&selected_locale);
+        let profiling = self.profile || self.profile_trace.is_some();
+        if profiling {
+            profile::enable();
+        }
         let ctx = AppCtx {};
-        match self.command {
+        let result = match self.command {
             Command::Demo(demo) => demo.run(&ctx),
             Command::Wizard(args) => args.run(),
+            Command::Resolve(resolve) => match resolve.command {
+                ResolveSubcommand::Explain(args) => args.run(),
+            },
+            Command::Workspace(workspace) => workspace.run(),
+            Command::Serve(args) => args.run(),
+            Command::Registry(registry) => match registry.command {
+                RegistrySubcommand::Mirror(args) => args.run(),
+            },
+            Command::Catalog(catalog) => match catalog.command {
+                CatalogSubcommand::Search(args) => args.run(),
+                CatalogSubcommand::Show(args) => args.run(),
+            },
+            Command::Tenant(tenant) => match tenant.command {
+                TenantSubcommand::Clone(args) => args.run(),
+                TenantSubcommand::Export(args) => args.run(),
+                TenantSubcommand::Import(args) => args.run(),
+            },
+            Command::Locale(locale) => match locale.command {
+                LocaleSubcommand::List(args) => args.run(),
+            },
+            Command::Explain(args) => args.run(),
+            Command::Completions(args) => {
+                completions::write_registration(args.shell, &mut std::io::stdout())?;
+                Ok(())
+            }
+            Command::Migrate(args) => args.run(),
+            Command::SelfUpdate(args) => args.run(),
+        };
+        if profiling {
+            eprint!("{}", profile::report());
+            if let Some(path) = &self.profile_trace {
+                match profile::write_chrome_trace(path) {
+                    Ok(()) => eprintln!(
+                        "{}",
+                        operator_i18n::trf(
+                            "cli.profile.trace_written",
+                            "Wrote Chrome trace to {}",
+                            &[&path.display().to_string()]
+                        )
+                    ),
+                    Err(err) => eprintln!(
+                        "{}",
+                        operator_i18n::trf(
+                            "cli.profile.trace_failed",
+                            "Could not write Chrome trace: {}",
+                            &[&format!("{err:#}")]
+                        )
+                    ),
+                }
+            }
         }
+        result
+    }
+}
+
+struct AppCtx {}
+
+impl ExplainArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let text = match self.code.as_deref() {
+            None => explain::render_index(),
+            Some(code) => explain::find(code)
+                .map(explain::Explanation::render)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "unknown error code {code:?}; run `greentic-operator explain` to list them"
+                    )
+                })?,
+        };
+        if self.no_pager {
+            print!("{text}");
+            return Ok(());
+        }
+        page(&text)
+    }
+}
+
+/// Shows `text` through `$PAGER` (default `less -FRX`, which exits at once when the
+/// text fits on the screen) if stdout is a terminal; prints it otherwise, or when the
+/// pager cannot be started.
+fn page(text: &str) -> anyhow::Result<()> {
+    if io::stdout().is_terminal() {
+        let pager = env::var("PAGER")
+            .ok()
+            .filter(|pager| !pager.trim().is_empty())
+            .unwrap_or_else(|| "less -FRX".to_string());
+        let mut parts = pager.split_whitespace();
+        if let Some(program) = parts.next()
+            && let Ok(mut child) = std::process::Command::new(program)
+                .args(parts)
+                .stdin(std::process::Stdio::piped())
+                .spawn()
+        {
+            if let Some(mut stdin) = child.stdin.take() {
+                // Quitting the pager early closes the pipe; that is not a failure.
+                let _ = stdin.write_all(text.as_bytes());
+            }
+            child.wait().context("wait for pager")?;
+            return Ok(());
+        }
+    }
+    print!("{text}");
+    Ok(())
+}
+
+/// The clap command tree with every about/help string, the section headings, and
+/// the exit-code footer looked up through `operator_i18n` for the current locale.
+///
+/// Keys are `cli.help.<subcommand path>.about` for commands and
+/// `cli.help.<subcommand path>.<arg id>` for arguments; global flags and the
+/// generated `help`/`version` entries use `cli.help.option.<arg id>`. Missing keys
+/// keep the English text from the derive attributes.
+pub fn localized_command() -> clap::Command {
+    let mut command = Cli::command()
+        .after_help(exit_codes_help())
+        .mut_subcommand("demo", |demo| demo.after_help(exit_codes_help()));
+    // Build first so the generated help/version flags and help subcommands exist.
+    command.build();
+    localize_command(command, &[])
+}
+
+/// [`localized_command`] with the bundle-aware value completers attached, for
+/// `clap_complete::CompleteEnv`.
+pub fn completion_command() -> clap::Command {
+    completions::with_value_completers(localized_command())
+}
+
+fn localize_command(mut command: clap::Command, path: &[&str]) -> clap::Command {
+    let about_key = match path {
+        [] => "cli.help.about".to_string(),
+        [.., "help"] => "cli.help.command_help".to_string(),
+        _ => format!("cli.help.{}.about", path.join(".")),
+    };
+    if let Some(about) = command.get_about().map(ToString::to_string) {
+        command = command.about(help_text(&about_key, &about));
+    }
+    let usage = operator_i18n::tr("cli.help.heading.usage", "Usage:");
+    let options = operator_i18n::tr("cli.help.heading.options", "Options");
+    let arguments = operator_i18n::tr("cli.help.heading.arguments", "Arguments");
+    command = command
+        .help_template(format!(
+            "{{before-help}}{{about-with-newline}}\n{usage} {{usage}}\n\n{{all-args}}{{after-help}}"
+        ))
+        .subcommand_help_heading(operator_i18n::tr("cli.help.heading.commands", "Commands"))
+        .mut_args(|arg| {
+            let id = arg.get_id().to_string();
+            let key = if arg.is_global_set() || id == "help" || id == "version" {
+                format!("cli.help.option.{id}")
+            } else if path.is_empty() {
+                format!("cli.help.{id}")
+            } else {
+                format!("cli.help.{}.{id}", path.join("."))
+            };
+            let arg = match arg.get_help().map(ToString::to_string) {
+                Some(help) => arg.help(help_text(&key, &help)),
+                None => arg,
+            };
+            if arg.get_help_heading().is_some() {
+                arg
+            } else if arg.is_positional() {
+                arg.help_heading(arguments.clone())
+            } else {
+                arg.help_heading(options.clone())
+            }
+        });
+    let names = command
+        .get_subcommands()
+        .map(|sub| sub.get_name().to_string())
+        .collect::<Vec<_>>();
+    for name in names {
+        let mut sub_path = path.to_vec();
+        sub_path.push(name.as_str());
+        command = command.mut_subcommand(&name, |sub| localize_command(sub, &sub_path));
+    }
+    command
+}
+
+/// An empty translation keeps the English text rather than blanking the entry.
+fn help_text(key: &str, english: &str) -> String {
+    let translated = operator_i18n::tr(key, english);
+    if translated.trim().is_empty() {
+        english.to_string()
+    } else {
+        translated
     }
 }
 
-struct AppCtx {}
+fn exit_codes_help() -> String {
+    let codes = [
+        (0, "cli.main.help.exit_ok", "success"),
+        (
+            operator_error::EXIT_FAILURE,
+            "cli.main.help.exit_failure",
+            "failure without a more specific cause",
+        ),
+        (
+            operator_error::EXIT_USAGE,
+            "cli.main.help.exit_usage",
+            "invalid command line",
+        ),
+        (
+            operator_error::EXIT_VALIDATION,
+            "cli.main.help.exit_validation",
+            "pack, manifest, or config missing or invalid",
+        ),
+        (
+            operator_error::EXIT_FLOW_FAILED,
+            "cli.main.help.exit_flow_failed",
+            "a provider op or flow failed",
+        ),
+        (
+            operator_error::EXIT_SECRET_MISSING,
+            "cli.main.help.exit_secret_missing",
+            "a required secret is missing",
+        ),
+        (
+            operator_error::EXIT_UNAVAILABLE,
+            "cli.main.help.exit_unavailable",
+            "a tunnel or external service is unavailable",
+        ),
+        (
+            operator_error::EXIT_DENIED,
+            "cli.main.help.exit_denied",
+            "denied by operators.yaml",
+        ),
+        (
+            operator_error::EXIT_WAITING,
+            "cli.main.help.exit_waiting",
+            "a --no-repl run stopped waiting for input",
+        ),
+    ];
+    let mut out = operator_i18n::tr("cli.main.help.exit_codes_header", "Exit codes:");
+    for (code, key, fallback) in codes {
+        out.push_str(&format!("\n  {code}  {}", operator_i18n::tr(key, fallback)));
+    }
+    out.push('\n');
+    out.push_str(&operator_i18n::tr(
+        "cli.main.help.exit_best_effort",
+        "Commands stop at the first failure; with --best-effort they report failures and exit 0.",
+    ));
+    out
+}
 
 impl DemoCommand {
     fn run(self, ctx: &AppCtx) -> anyhow::Result<()> {
@@ -1778,23 +6537,54 @@ impl DemoCommand {
                 std::env::set_var("GREENTIC_OPERATOR_DEMO_DEBUG", "1");
             }
         }
+        if self.no_redact {
+            redaction::set_enabled(false);
+        }
+        if let Some(name) = &self.as_operator {
+            operator_auth::set_operator(name);
+        }
         match self.command {
             DemoSubcommand::Build(args) => args.run(ctx),
             DemoSubcommand::Up(args) => args.run_start(ctx),
             DemoSubcommand::Start(args) => args.run_start(ctx),
+            DemoSubcommand::Reload(args) => args.run(),
             DemoSubcommand::Setup(args) => args.run(),
+            DemoSubcommand::Onboard(args) => args.run(),
+            DemoSubcommand::Offboard(args) => args.run(),
             DemoSubcommand::Send(args) => args.run(),
             DemoSubcommand::Ingress(args) => args.run(),
             DemoSubcommand::New(args) => args.run(),
             DemoSubcommand::Status(args) => args.run(),
             DemoSubcommand::Logs(args) => args.run(),
             DemoSubcommand::Doctor(args) => args.run(ctx),
+            DemoSubcommand::Diff(args) => args.run(),
+            DemoSubcommand::Test(args) => args.run(),
+            DemoSubcommand::Snapshot(args) => args.run(),
+            DemoSubcommand::Config(args) => args.run(),
             DemoSubcommand::ListPacks(args) => args.run(ctx),
             DemoSubcommand::ListFlows(args) => args.run(ctx),
             DemoSubcommand::Allow(args) => args.run(Policy::Public),
             DemoSubcommand::Forbid(args) => args.run(Policy::Forbidden),
+            DemoSubcommand::Policy(args) => args.run(),
             DemoSubcommand::Subscriptions(args) => args.run(),
             DemoSubcommand::Capability(args) => args.run(),
+            DemoSubcommand::Destinations(args) => args.run(),
+            DemoSubcommand::Card(args) => args.run(),
+            DemoSubcommand::Providers(args) => args.run(),
+            DemoSubcommand::Runs(args) => args.run(),
+            DemoSubcommand::Jobs(args) => args.run(),
+            DemoSubcommand::Gc(args) => args.run(),
+            DemoSubcommand::Dlq(args) => args.run(),
+            DemoSubcommand::Timers(args) => args.run(),
+            DemoSubcommand::Events(args) => args.run(),
+            DemoSubcommand::Verify(args) => args.run(),
+            DemoSubcommand::Bench(args) => args.run(),
+            DemoSubcommand::Graph(args) => args.run(),
+            DemoSubcommand::Export(args) => args.run(),
+            DemoSubcommand::Pack(args) => args.run(),
+            DemoSubcommand::State(args) => args.run(),
+            DemoSubcommand::Secrets(args) => args.run(),
+            DemoSubcommand::Audit(args) => args.run(),
             DemoSubcommand::Run(args) => args.run(ctx),
             DemoSubcommand::Wizard(args) => args.run(),
             DemoSubcommand::SetupWizard(args) => args.run(),
@@ -1804,6 +6594,7 @@ impl DemoCommand {
 
 impl DemoBuildArgs {
     fn run(self, _ctx: &AppCtx) -> anyhow::Result<()> {
+        progress::set_verbosity(progress::Verbosity::from_flags(self.quiet, self.verbose));
         let root = project_root(self.project_root)?;
         if demo_debug_enabled() {
             println!(
@@ -1823,39 +6614,95 @@ impl DemoBuildArgs {
                 "[demo] skipping doctor gate (skip_doctor flag or GREENTIC_OPERATOR_SKIP_DOCTOR set)"
             );
         }
-        let options = BuildOptions {
-            out_dir: self.out,
+        api::build_bundle(api::BuildBundle {
+            project_root: root,
+            out: self.out,
             tenant: self.tenant,
             team: self.team,
             allow_pack_dirs: self.allow_pack_dirs,
             only_used_providers: self.only_used_providers,
             run_doctor,
-        };
-        let config = config::load_operator_config(&root)?;
-        let pack_command = if options.run_doctor {
-            let explicit = config::binary_override(config.as_ref(), "greentic-pack", &root);
-            Some(bin_resolver::resolve_binary(
-                "greentic-pack",
-                &ResolveCtx {
-                    config_dir: root.clone(),
-                    explicit_path: explicit,
-                },
-            )?)
-        } else {
-            None
-        };
-        demo::build_bundle(&root, options, pack_command.as_deref())
+        })?;
+        Ok(())
     }
 }
 
 impl DemoUpArgs {
     fn run_start(self, _ctx: &AppCtx) -> anyhow::Result<()> {
+        if self.strict_secrets {
+            secrets_gate::enable_strict_secrets();
+        }
         self.run_with_shutdown()
     }
 
+    /// Checks the gateway and NATS ports before anything starts. The chosen ports
+    /// are exported so later config loads and the NATS spawn pick them up.
+    fn preflight_ports(
+        &self,
+        bundle: &Path,
+        demo_config: &config::DemoConfig,
+        nats_mode: demo::NatsMode,
+    ) -> anyhow::Result<demo::ports::DemoPorts> {
+        let gateway = &demo_config.services.gateway;
+        let gateway_port = demo::ports::select_port(
+            "gateway",
+            "gateway-port",
+            &gateway.listen_addr,
+            self.gateway_port.unwrap_or(gateway.port),
+            self.auto_port,
+            demo::ports::port_is_free,
+        )?;
+        let spawns_nats = matches!(nats_mode, demo::NatsMode::On | demo::NatsMode::JetStream)
+            && self.nats_url.is_none();
+        let nats_port = if !spawns_nats {
+            None
+        } else if services::nats_status(bundle)? == ProcessStatus::Running {
+            // Already serving this bundle; its port is expected to be busy.
+            Some(services::nats_port(bundle))
+        } else {
+            Some(demo::ports::select_port(
+                "nats",
+                "nats-port",
+                "127.0.0.1",
+                self.nats_port
+                    .unwrap_or_else(|| services::nats_port(bundle)),
+                self.auto_port,
+                demo::ports::port_is_free,
+            )?)
+        };
+        unsafe {
+            std::env::set_var(config::ENV_GATEWAY_PORT, gateway_port.to_string());
+            if let Some(port) = nats_port {
+                std::env::set_var(services::ENV_NATS_PORT, port.to_string());
+            }
+        }
+        Ok(demo::ports::DemoPorts {
+            gateway_port,
+            nats_port,
+        })
+    }
+
+    fn named_tunnel(
+        &self,
+        demo_config: &config::DemoConfig,
+        base_dir: &Path,
+    ) -> anyhow::Result<Option<crate::cloudflared::NamedTunnel>> {
+        self.cloudflared_tunnel
+            .as_deref()
+            .map(|name| {
+                crate::cloudflared::NamedTunnel::from_config(
+                    &demo_config.services.cloudflared,
+                    name,
+                    base_dir,
+                )
+            })
+            .transpose()
+    }
+
     fn run_with_shutdown(self) -> anyhow::Result<()> {
         let restart: std::collections::BTreeSet<String> =
             self.restart.iter().map(restart_name).collect();
+        progress::set_verbosity(progress::Verbosity::from_flags(self.quiet, self.verbose));
         let log_level = if self.quiet {
             operator_log::Level::Warn
         } else if self.verbose {
@@ -1866,8 +6713,18 @@ impl DemoUpArgs {
         let command_label = "demo start";
         let debug_enabled = self.verbose;
         if let Some(bundle) = self.bundle.clone() {
+            let bundle = match &self.instance {
+                Some(dir) => instance::prepare(&bundle, dir)?,
+                None => bundle,
+            };
             let state_dir = bundle.join("state");
             std::fs::create_dir_all(&state_dir)?;
+            if self.headless && env::var(config::ENV_GATEWAY_LISTEN_ADDR).is_err() {
+                // Exported like the ports so every later config load sees it.
+                unsafe {
+                    std::env::set_var(config::ENV_GATEWAY_LISTEN_ADDR, "0.0.0.0");
+                }
+            }
             let log_dir = self.log_dir.clone().unwrap_or_else(|| bundle.join("logs"));
             let log_dir = operator_log::init(log_dir.clone(), log_level)?;
             let run_targets =
@@ -1929,7 +6786,10 @@ impl DemoUpArgs {
                 ),
             );
             let demo_config_path = bundle.join("greentic.demo.yaml");
-            let demo_config = load_demo_config_or_default(&demo_config_path);
+            let mut demo_config = load_demo_config_or_default(&demo_config_path);
+            let ports = self.preflight_ports(&bundle, &demo_config, nats_mode)?;
+            demo_config.services.gateway.port = ports.gateway_port;
+            demo::ports::write_ports(&state_dir, &ports)?;
             let services = config
                 .as_ref()
                 .and_then(|config| config.services.clone())
@@ -1939,7 +6799,20 @@ impl DemoUpArgs {
                 .enabled
                 .is_enabled(discovery.domains.messaging);
             let explicit_nats_url = self.nats_url.clone();
-            let domains_to_setup = self.domain.resolve_domains(Some(&discovery));
+            let domains_to_setup = self.domain.resolve_domains(&bundle, &discovery)?;
+            let runner_binary = runner_pin::resolve_runner(
+                &bundle,
+                &demo_config.runner,
+                self.runner_binary.clone(),
+            )?;
+            if let Some(runner) = runner_binary.as_deref() {
+                let packs = discovery
+                    .providers
+                    .iter()
+                    .map(|provider| provider.pack_path.clone())
+                    .collect::<Vec<_>>();
+                runner_pin::check_packs(runner, &packs)?;
+            }
 
             let mut cloudflared_config = match self.cloudflared {
                 CloudflaredModeArg::Off => None,
@@ -1954,9 +6827,10 @@ impl DemoUpArgs {
                     )?;
                     Some(crate::cloudflared::CloudflaredConfig {
                         binary,
-                        local_port: 8080,
+                        local_port: demo_config.services.gateway.port,
                         extra_args: Vec::new(),
                         restart: restart.contains("cloudflared"),
+                        tunnel: self.named_tunnel(&demo_config, &bundle)?,
                     })
                 }
             };
@@ -1974,7 +6848,7 @@ impl DemoUpArgs {
                     )?;
                     Some(crate::ngrok::NgrokConfig {
                         binary,
-                        local_port: 8080,
+                        local_port: demo_config.services.gateway.port,
                         extra_args: Vec::new(),
                         restart: restart.contains("ngrok"),
                     })
@@ -1986,12 +6860,15 @@ impl DemoUpArgs {
                 .team
                 .clone()
                 .unwrap_or_else(|| DEMO_DEFAULT_TEAM.to_string());
+            let runtime_paths = RuntimePaths::new(&state_dir, &tenant, &team_id);
+            let recorded_webhooks = demo::webhook_registration::read(&runtime_paths)?;
+            // A recorded registration means the tunnel URL decides whether to re-run setup.
+            let wants_setup = self.setup_input.is_some() || recorded_webhooks.is_some();
             let mut started_tunnel_early = false;
             if public_base_url.is_none()
-                && self.setup_input.is_some()
+                && wants_setup
                 && let Some(cfg) = cloudflared_config.as_mut()
             {
-                let paths = RuntimePaths::new(&state_dir, &tenant, &team_id);
                 let setup_log = operator_log::reserve_service_log(&log_dir, "cloudflared")
                     .with_context(|| "unable to open cloudflared.log")?;
                 operator_log::info(
@@ -2001,12 +6878,14 @@ impl DemoUpArgs {
                         setup_log.display()
                     ),
                 );
-                let handle = crate::cloudflared::start_quick_tunnel(&paths, cfg, &setup_log)?;
+                let handle = crate::cloudflared::start_tunnel(&runtime_paths, cfg, &setup_log)?;
                 operator_log::info(
                     module_path!(),
                     format!(
-                        "cloudflared setup mode ready url={} log={}",
+                        "cloudflared setup mode ready url={} previous_url={:?} changed={} log={}",
                         handle.url,
+                        handle.previous_url,
+                        handle.url_changed(),
                         setup_log.display()
                     ),
                 );
@@ -2028,17 +6907,16 @@ impl DemoUpArgs {
             }
 
             if public_base_url.is_none()
-                && self.setup_input.is_some()
+                && wants_setup
                 && let Some(cfg) = ngrok_config.as_mut()
             {
-                let paths = RuntimePaths::new(&state_dir, &tenant, &team_id);
                 let setup_log = operator_log::reserve_service_log(&log_dir, "ngrok")
                     .with_context(|| "unable to open ngrok.log")?;
                 operator_log::info(
                     module_path!(),
                     format!("starting setup-mode ngrok log={}", setup_log.display()),
                 );
-                let handle = crate::ngrok::start_tunnel(&paths, cfg, &setup_log)?;
+                let handle = crate::ngrok::start_tunnel(&runtime_paths, cfg, &setup_log)?;
                 operator_log::info(
                     module_path!(),
                     format!(
@@ -2071,24 +6949,72 @@ impl DemoUpArgs {
                 cfg.restart = false;
             }
 
-            if let Some(setup_input) = self.setup_input.as_ref() {
-                let tenant_ref = self.tenant.as_deref().unwrap_or(DEMO_DEFAULT_TENANT);
-                let secrets_handle = secrets_gate::resolve_secrets_manager(
-                    &bundle,
-                    tenant_ref,
-                    self.team.as_deref(),
-                )?;
-                run_demo_up_setup(
-                    &bundle,
-                    &domains_to_setup,
-                    setup_input,
-                    self.tenant.clone(),
-                    self.team.clone(),
-                    &self.env,
-                    self.runner_binary.clone(),
-                    public_base_url.clone(),
-                    Some(secrets_handle.manager()),
-                )?;
+            // Up before provider setup, which may already call them.
+            let sidecars = demo::sidecars::Sidecars::start(
+                &bundle,
+                runtime_paths.clone(),
+                &log_dir,
+                &demo_config.services.extra,
+            )?;
+            let tunnel_url = public_base_url.as_deref().filter(|_| started_tunnel_early);
+            let setup_input = match (&self.setup_input, tunnel_url) {
+                (Some(path), _) => Some(path.clone()),
+                (None, Some(url)) => {
+                    demo::webhook_registration::replay_input(recorded_webhooks.as_ref(), url)
+                }
+                (None, None) => None,
+            };
+            if let Some(setup_input) = setup_input.as_ref() {
+                let registration = tunnel_url
+                    .map(|url| {
+                        demo::webhook_registration::WebhookRegistration::new(url, setup_input)
+                    })
+                    .transpose()?;
+                if !self.force_setup && registration.is_some() && registration == recorded_webhooks
+                {
+                    println!(
+                        "{}",
+                        operator_i18n::trf(
+                            "cli.start.webhooks_current",
+                            "Webhooks already registered for {}; skipping provider setup (use --force-setup to re-run it)",
+                            &[tunnel_url.unwrap_or_default()]
+                        )
+                    );
+                } else {
+                    if self.setup_input.is_none() {
+                        println!(
+                            "{}",
+                            operator_i18n::trf(
+                                "cli.start.webhooks_replay",
+                                "Public URL changed to {}; re-registering webhooks with {}",
+                                &[
+                                    tunnel_url.unwrap_or_default(),
+                                    &setup_input.display().to_string()
+                                ]
+                            )
+                        );
+                    }
+                    let tenant_ref = self.tenant.as_deref().unwrap_or(DEMO_DEFAULT_TENANT);
+                    let secrets_handle = secrets_gate::resolve_secrets_manager(
+                        &bundle,
+                        tenant_ref,
+                        self.team.as_deref(),
+                    )?;
+                    run_demo_up_setup(
+                        &bundle,
+                        &domains_to_setup,
+                        setup_input,
+                        self.tenant.clone(),
+                        self.team.clone(),
+                        &self.env,
+                        runner_binary.clone(),
+                        public_base_url.clone(),
+                        Some(secrets_handle.manager()),
+                    )?;
+                    if let Some(registration) = registration.as_ref() {
+                        demo::webhook_registration::write(&runtime_paths, registration)?;
+                    }
+                }
             }
 
             let start_result = {
@@ -2101,6 +7027,7 @@ impl DemoUpArgs {
                             target.team.as_deref(),
                             explicit_nats_url.as_deref(),
                             nats_mode,
+                            &demo_config.services.nats.jetstream,
                             messaging_enabled,
                             cloudflared_config.clone(),
                             ngrok_config.clone(),
@@ -2135,83 +7062,108 @@ impl DemoUpArgs {
                 }
                 guard
             };
-            let mut ingress_server = None;
-            let mut timer_scheduler = None;
+            let mut services = DemoReloadableServices::default();
+            let mut service_ctx = None;
+            let mut webhook_watcher = None;
+            let mut control_api = None;
+            let mut job_worker = None;
+            let (mut reload_listener, reload_trigger) = ReloadListener::new()?;
             if start_result.is_ok() {
+                sidecars.record()?;
                 let ingress_secrets_handle =
                     secrets_gate::resolve_secrets_manager(&bundle, &tenant, self.team.as_deref())?;
-                match start_demo_ingress_server(
-                    &bundle,
-                    &discovery,
-                    &demo_config,
-                    &domains_to_setup,
-                    self.runner_binary.clone(),
+                let ctx = DemoServiceContext {
+                    bundle: &bundle,
+                    discovery: &discovery,
+                    domains: &domains_to_setup,
+                    runner_binary: runner_binary.clone(),
                     debug_enabled,
-                    ingress_secrets_handle.clone(),
-                ) {
-                    Ok(server) => {
-                        println!(
-                            "{}",
-                            operator_i18n::trf(
-                                "cli.start.http_ingress_ready",
-                                "HTTP ingress ready at http://{}:{}",
-                                &[
-                                    &demo_config.services.gateway.listen_addr,
-                                    &demo_config.services.gateway.port.to_string()
-                                ]
-                            )
-                        );
-                        ingress_server = Some(server);
-                    }
-                    Err(err) => {
-                        eprintln!(
-                            "{}",
-                            operator_i18n::trf(
-                                "cli.start.warn_http_ingress_disabled",
-                                "Warning: HTTP ingress disabled: {}",
-                                &[&err.to_string()]
-                            )
-                        );
-                        operator_log::warn(
+                    secrets_handle: ingress_secrets_handle.clone(),
+                    tenant: &tenant,
+                    team: &team_id,
+                };
+                services = DemoReloadableServices::start(&ctx, &demo_config);
+                service_ctx = Some(ctx);
+                // An explicit --public-base-url is not ours to follow.
+                if self.public_base_url.is_none()
+                    && (cloudflared_config.is_some() || ngrok_config.is_some())
+                {
+                    let tunnel_log = cloudflared_config
+                        .as_ref()
+                        .filter(|cfg| cfg.tunnel.is_none())
+                        .map(|_| operator_log::service_log_path(&log_dir, "cloudflared"));
+                    match start_demo_webhook_watcher(
+                        &bundle,
+                        &discovery,
+                        &domains_to_setup,
+                        runner_binary.clone(),
+                        debug_enabled,
+                        ingress_secrets_handle.clone(),
+                        &tenant,
+                        &team_id,
+                        tunnel_log,
+                    ) {
+                        Ok(watcher) => webhook_watcher = watcher,
+                        Err(err) => operator_log::warn(
                             module_path!(),
-                            format!("demo ingress server unavailable: {err}"),
-                        );
+                            format!("webhook watcher unavailable: {err}"),
+                        ),
                     }
                 }
-                match start_demo_timer_scheduler(
-                    &bundle,
-                    &discovery,
-                    &domains_to_setup,
-                    self.runner_binary.clone(),
-                    debug_enabled,
-                    ingress_secrets_handle.clone(),
-                    &tenant,
-                    self.team.as_deref().unwrap_or(DEMO_DEFAULT_TEAM),
-                ) {
-                    Ok(Some(scheduler)) => {
-                        println!(
-                            "{}",
-                            operator_i18n::tr(
-                                "cli.start.events_timer_scheduler_ready",
-                                "events timer scheduler ready"
-                            )
-                        );
-                        timer_scheduler = Some(scheduler);
-                    }
-                    Ok(None) => {}
-                    Err(err) => {
-                        eprintln!(
-                            "{}",
-                            operator_i18n::trf(
-                                "cli.start.warn_events_timer_scheduler_disabled",
-                                "Warning: events timer scheduler disabled: {}",
-                                &[&err.to_string()]
-                            )
-                        );
-                        operator_log::warn(
-                            module_path!(),
-                            format!("demo timer scheduler unavailable: {err}"),
-                        );
+                match demo::jobs::JobWorker::start(bundle.clone()) {
+                    Ok(worker) => job_worker = Some(worker),
+                    Err(err) => operator_log::warn(
+                        module_path!(),
+                        format!("job worker unavailable: {err:#}"),
+                    ),
+                }
+                if let Some(port) = self.control_port {
+                    let backend = DemoControlBackend {
+                        bundle: bundle.clone(),
+                        tenant: tenant.clone(),
+                        team: self.team.clone(),
+                        env: self.env.clone(),
+                        runner_binary: runner_binary.clone(),
+                        reload: reload_trigger.clone(),
+                    };
+                    match ControlApiServer::start(ControlApiConfig {
+                        bind_addr: SocketAddr::from(([127, 0, 0, 1], port)),
+                        bundle: bundle.clone(),
+                        log_dir: log_dir.clone(),
+                        tenant: tenant.clone(),
+                        team: team_id.clone(),
+                        backend: Arc::new(backend),
+                    }) {
+                        Ok(server) => {
+                            println!(
+                                "{}",
+                                operator_i18n::trf(
+                                    "cli.start.control_api_ready",
+                                    "Control API ready at {} (token in {})",
+                                    &[
+                                        &server.endpoint().url,
+                                        &demo::control_api::endpoint_path(&state_dir)
+                                            .display()
+                                            .to_string()
+                                    ]
+                                )
+                            );
+                            control_api = Some(server);
+                        }
+                        Err(err) => {
+                            eprintln!(
+                                "{}",
+                                operator_i18n::trf(
+                                    "cli.start.warn_control_api_disabled",
+                                    "Warning: control API disabled: {}",
+                                    &[&err.to_string()]
+                                )
+                            );
+                            operator_log::warn(
+                                module_path!(),
+                                format!("demo control API unavailable: {err}"),
+                            );
+                        }
                     }
                 }
             }
@@ -2240,13 +7192,58 @@ impl DemoUpArgs {
                     bundle.display(),
                     &target_summary
                 );
-                wait_for_ctrlc()?;
-                if let Some(server) = ingress_server.take() {
+                demo::reload::write_process(&state_dir)?;
+                while let DemoSignal::Reload(reply) = reload_listener.wait()? {
+                    let Some(ctx) = service_ctx.as_ref() else {
+                        continue;
+                    };
+                    let result = reload_demo_bundle(ctx, &run_targets, &mut services);
+                    match &result {
+                        Ok(summary) => {
+                            println!(
+                                "{}",
+                                operator_i18n::trf(
+                                    "cli.start.reloaded",
+                                    "Reloaded greentic.demo.yaml and gmaps; restarted: {}",
+                                    &[&services.names().join(", ")]
+                                )
+                            );
+                            operator_log::info(
+                                module_path!(),
+                                format!("{command_label} reloaded: {summary}"),
+                            );
+                        }
+                        Err(err) => {
+                            eprintln!(
+                                "{}",
+                                operator_i18n::trf(
+                                    "cli.start.warn_reload_failed",
+                                    "Warning: reload failed: {}",
+                                    &[&format!("{err:#}")]
+                                )
+                            );
+                            operator_log::warn(
+                                module_path!(),
+                                format!("{command_label} reload failed: {err:#}"),
+                            );
+                        }
+                    }
+                    if let Some(reply) = reply {
+                        let _ = reply.send(result);
+                    }
+                }
+                demo::reload::remove_process(&state_dir)?;
+                if let Some(server) = control_api.take() {
                     server.stop()?;
                 }
-                if let Some(scheduler) = timer_scheduler.take() {
-                    scheduler.stop()?;
+                services.stop()?;
+                if let Some(watcher) = webhook_watcher.take() {
+                    watcher.stop()?;
                 }
+                if let Some(worker) = job_worker.take() {
+                    worker.stop()?;
+                }
+                sidecars.stop()?;
                 for target in run_targets.iter().rev() {
                     demo::demo_down_runtime(&state_dir, &target.tenant, target.team_id(), false)?;
                 }
@@ -2273,6 +7270,11 @@ impl DemoUpArgs {
             ),
         );
         let demo_config = config::load_demo_config(&config_path)?;
+        let runner_binary = runner_pin::resolve_runner(
+            &config_dir,
+            &demo_config.runner,
+            self.runner_binary.clone(),
+        )?;
         let tenant = demo_config.tenant.clone();
         let team = demo_config.team.clone();
         let cloudflared = match self.cloudflared {
@@ -2291,6 +7293,7 @@ impl DemoUpArgs {
                     local_port: demo_config.services.gateway.port,
                     extra_args: Vec::new(),
                     restart: restart.contains("cloudflared"),
+                    tunnel: self.named_tunnel(&demo_config, &config_dir)?,
                 })
             }
         };
@@ -2315,7 +7318,7 @@ impl DemoUpArgs {
         };
 
         let provider_setup_input = self.setup_input.clone();
-        let timer_runner_binary = self.runner_binary.clone();
+        let timer_runner_binary = runner_binary.clone();
         let provider_options = crate::providers::ProviderSetupOptions {
             providers: if self.providers.is_empty() {
                 None
@@ -2329,7 +7332,7 @@ impl DemoUpArgs {
             allow_contract_change: self.allow_contract_change,
             backup: self.backup,
             setup_input: provider_setup_input.clone(),
-            runner_binary: self.runner_binary,
+            runner_binary,
             continue_on_error: provider_setup_input.is_none(),
         };
 
@@ -2368,6 +7371,7 @@ impl DemoUpArgs {
                 secrets_gate::resolve_secrets_manager(&config_dir, &tenant, Some(&team))?;
             let timer_scheduler = start_demo_timer_scheduler(
                 &config_dir,
+                &demo_config,
                 &discovery,
                 &domains,
                 timer_runner_binary.clone(),
@@ -2393,8 +7397,6 @@ impl DemoUpArgs {
 }
 
 const DEMO_DEFAULT_TENANT: &str = "demo";
-const DEMO_DEFAULT_TEAM: &str = "default";
-
 #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
 struct DemoBundleTarget {
     tenant: String,
@@ -2502,79 +7504,193 @@ fn discover_bundle_run_targets(bundle: &Path) -> anyhow::Result<Vec<DemoBundleTa
 
 impl DemoSetupArgs {
     fn run(self) -> anyhow::Result<()> {
-        domains::ensure_cbor_packs(&self.bundle)?;
-        let discovery = discovery::discover_with_options(
-            &self.bundle,
-            discovery::DiscoveryOptions { cbor_only: true },
-        )?;
-        discovery::persist(&self.bundle, &self.tenant, &discovery)?;
-        let domains = self.domain.resolve_domains(Some(&discovery));
+        progress::set_verbosity(progress::Verbosity::from_flags(self.quiet, self.verbose));
+        if self.no_stream {
+            flow_stream::disable();
+        }
         if demo_debug_enabled() {
             println!(
-                "[demo] setup bundle={} tenant={} team={:?} domains={:?} provider_filter={:?} dry_run={} parallel={} skip_secrets_init={}",
+                "[demo] setup bundle={} tenant={} team={:?} domain={:?} provider_filter={:?} dry_run={} parallel={} skip_secrets_init={}",
                 self.bundle.display(),
                 self.tenant,
                 self.team,
-                domains,
+                self.domain,
                 self.provider,
                 self.dry_run,
                 self.parallel,
                 self.skip_secrets_init
             );
         }
-        let format = match self.format {
+        let domains = match &self.domain {
+            DemoSetupDomainArg::All => Vec::new(),
+            DemoSetupDomainArg::One(domain) => vec![domain.resolve(&self.bundle)?],
+        };
+        let report = api::run_setup(api::RunSetup {
+            bundle: self.bundle,
+            tenant: self.tenant,
+            team: self.team,
+            domains,
+            provider: self.provider,
+            dry_run: self.dry_run,
+            parallel: self.parallel,
+            allow_missing_setup: self.allow_missing_setup,
+            allow_contract_change: self.allow_contract_change,
+            backup: self.backup,
+            online: self.online,
+            secrets_env: if self.skip_secrets_init {
+                None
+            } else {
+                self.secrets_env
+            },
+            state_dir: self.state_dir,
+            runner_binary: self.runner_binary,
+            setup_input: self.setup_input,
+            best_effort: self.failure.best_effort(),
+            adopt: self.adopt,
+        })?;
+        for outcome in &report.adopted {
+            print_adopt_outcome(outcome);
+        }
+        let plan_format = match self.format {
             Format::Text => PlanFormat::Text,
             Format::Json => PlanFormat::Json,
             Format::Yaml => PlanFormat::Yaml,
         };
-        for domain in domains {
-            let discovered_providers = match domain {
-                Domain::Messaging | Domain::Events => Some(
-                    discovery
-                        .providers
-                        .iter()
-                        .filter(|provider| provider.domain == domains::domain_name(domain))
-                        .cloned()
-                        .collect(),
-                ),
-                Domain::Secrets => None,
-            };
-            run_domain_command(DomainRunArgs {
-                root: self.bundle.clone(),
-                state_root: self.state_dir.clone(),
-                domain,
-                action: DomainAction::Setup,
-                tenant: self.tenant.clone(),
-                team: self.team.clone(),
-                provider_filter: self.provider.clone(),
-                dry_run: self.dry_run,
-                format,
-                parallel: self.parallel,
-                allow_missing_setup: self.allow_missing_setup,
-                allow_contract_change: self.allow_contract_change,
-                backup: self.backup,
-                online: self.online,
-                secrets_env: if self.skip_secrets_init {
-                    None
-                } else {
-                    self.secrets_env.clone()
-                },
-                runner_binary: self.runner_binary.clone(),
-                best_effort: self.best_effort,
-                setup_input: self.setup_input.clone(),
-                allowed_providers: None,
-                preloaded_setup_answers: None,
-                public_base_url: None,
-                secrets_manager: None,
-                discovered_providers,
-            })?;
+        for run in &report.runs {
+            print_domain_run_report(run, plan_format)?;
+        }
+        for (domain, err) in &report.failed {
+            report_best_effort_failure(&format!("setup {}", domains::domain_name(*domain)), err);
+        }
+        Ok(())
+    }
+}
+
+fn print_adopt_outcome(outcome: &demo::webhook_adopt::AdoptOutcome) {
+    if let Some(err) = &outcome.error {
+        eprintln!(
+            "{}",
+            operator_i18n::trf(
+                "cli.demo_setup.adopt_failed",
+                "[warn] adopt provider={}: {}",
+                &[&outcome.provider, &redaction::scrub_text(err)]
+            )
+        );
+    }
+    let Some(op) = outcome.op else {
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.demo_setup.adopt_unsupported",
+                "adopt provider={}: no list_webhooks or list_subscriptions op; running setup",
+                &[&outcome.provider]
+            )
+        );
+        return;
+    };
+    println!(
+        "{}",
+        operator_i18n::trf(
+            "cli.demo_setup.adopt_summary",
+            "adopt provider={} op={}: {} listed, {} adopted",
+            &[
+                &outcome.provider,
+                op,
+                &outcome.listed.to_string(),
+                &outcome.adopted.len().to_string()
+            ]
+        )
+    );
+}
+
+impl DemoOnboardArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let mut request = api::OnboardTenant::new(&self.bundle, &self.tenant);
+        request.team = self.team;
+        request.packs = self.packs;
+        request.setup_input = self.setup_input;
+        request.runner_binary = self.runner_binary;
+        let report = api::onboard_tenant(&request)?;
+        if matches!(self.format, ListFormat::Json) {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            return Ok(());
+        }
+        for provider in &report.providers {
+            println!(
+                "{}",
+                operator_i18n::trf(
+                    "cli.onboard.provider",
+                    "{}/{}: allowed, {} secret(s) seeded, setup done",
+                    &[
+                        &provider.domain,
+                        &provider.provider_id,
+                        &provider.secrets.len().to_string()
+                    ]
+                )
+            );
+        }
+        let scope = match &report.team {
+            Some(team) => format!("{}/{team}", report.tenant),
+            None => report.tenant.clone(),
+        };
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.onboard.done",
+                "onboarded {} with {} pack(s)",
+                &[&scope, &report.providers.len().to_string()]
+            )
+        );
+        Ok(())
+    }
+}
+
+impl DemoOffboardArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let mut request = api::OffboardTenant::new(&self.bundle, &self.tenant);
+        request.dry_run = self.dry_run;
+        request.keep_remote = self.keep_remote;
+        request.runner_binary = self.runner_binary;
+        let report = api::offboard_tenant(&request)?;
+        if matches!(self.format, ListFormat::Json) {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            return Ok(());
         }
+        for action in &report.actions {
+            let line = format!(
+                "{} {} {}",
+                action.status.as_str(),
+                action.kind,
+                action.target
+            );
+            match &action.detail {
+                Some(detail) => println!("{line} ({detail})"),
+                None => println!("{line}"),
+            }
+        }
+        let (key, fallback) = if report.dry_run {
+            (
+                "cli.offboard.planned",
+                "dry run for {}: {} action(s) planned, nothing removed",
+            )
+        } else {
+            ("cli.offboard.done", "offboarded {} ({} action(s))")
+        };
+        println!(
+            "{}",
+            operator_i18n::trf(
+                key,
+                fallback,
+                &[&report.tenant, &report.actions.len().to_string()]
+            )
+        );
         Ok(())
     }
 }
 
 impl DemoPolicyArgs {
     fn run(self, policy: Policy) -> anyhow::Result<()> {
+        operator_auth::authorize(&self.bundle, OperatorAction::Policy, &self.path)?;
         let effective_team = if let Some(team) = self.team.clone() {
             Some(team)
         } else if self
@@ -2589,15 +7705,84 @@ impl DemoPolicyArgs {
         } else {
             None
         };
+        if self.dry_run {
+            let preview = project::preview_policy(
+                &self.bundle,
+                &self.tenant,
+                effective_team.as_deref(),
+                &self.path,
+                policy,
+            )?;
+            print!("{}", project::render_policy_preview(&preview));
+            println!(
+                "{}",
+                operator_i18n::tr("cli.demo_policy.dry_run", "dry run: no files were changed")
+            );
+            return Ok(());
+        }
         let gmap_path =
             demo_bundle_gmap_path(&self.bundle, &self.tenant, effective_team.as_deref());
-        gmap::upsert_policy(&gmap_path, &self.path, policy)?;
+        let result = gmap::upsert_policy(&gmap_path, &self.path, policy.clone());
+        audit::record_gmap_edit(
+            &self.bundle,
+            &self.tenant,
+            effective_team.as_deref(),
+            &self.path,
+            &policy,
+            &result,
+        );
+        result?;
         project::sync_project(&self.bundle)?;
         copy_resolved_manifest(&self.bundle, &self.tenant, effective_team.as_deref())?;
         Ok(())
     }
 }
 
+impl DemoPolicyCommand {
+    fn run(self) -> anyhow::Result<()> {
+        match self.command {
+            DemoPolicySubcommand::Rollback(args) => args.run(),
+        }
+    }
+}
+
+impl DemoPolicyRollbackArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let target = resolved_manifest_filename(&self.tenant, self.team.as_deref());
+        operator_auth::authorize(&self.bundle, OperatorAction::Policy, &target)?;
+        let result =
+            project::rollback_resolved_manifest(&self.bundle, &self.tenant, self.team.as_deref());
+        let detail = match &result {
+            Ok(rollback) => json!({
+                "restored": rollback.restored.display().to_string(),
+                "remaining": rollback.remaining,
+            }),
+            Err(_) => JsonValue::Null,
+        };
+        audit::record(
+            &self.bundle,
+            AuditEvent::new("policy.rollback", target)
+                .scope(&self.tenant, self.team.as_deref())
+                .detail(detail)
+                .result(&result),
+        );
+        let rollback = result?;
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.demo_policy.rolled_back",
+                "restored {} from {} ({} older manifest(s) left)",
+                &[
+                    &rollback.manifest.display().to_string(),
+                    &rollback.restored.display().to_string(),
+                    &rollback.remaining.to_string(),
+                ]
+            )
+        );
+        Ok(())
+    }
+}
+
 impl DemoSetupWizardArgs {
     fn run(self) -> anyhow::Result<()> {
         let meta = domains::read_pack_meta(&self.pack)
@@ -2634,67 +7819,747 @@ impl DemoSetupWizardArgs {
             default_manager()?
         };
 
-        // 4. Run the setup flow via DemoRunner
-        let mut runner = DemoRunner::with_entry_flow(
-            self.pack.clone(),
-            &self.tenant,
-            self.team.clone(),
-            setup_flow.clone(),
-            provider_id.clone(),
-            input,
-            secrets_manager,
-        )?;
+        // 4. Run the setup flow via DemoRunner
+        let mut runner = DemoRunner::with_entry_flow(
+            self.pack.clone(),
+            &self.tenant,
+            self.team.clone(),
+            setup_flow.clone(),
+            provider_id.clone(),
+            input,
+            secrets_manager,
+        )?;
+
+        match runner.run_until_blocked() {
+            demo::DemoBlockedOn::Finished(output) => {
+                println!("\nFlow '{setup_flow}' completed:");
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&output).unwrap_or_else(|_| "<invalid>".into())
+                );
+            }
+            demo::DemoBlockedOn::Waiting { reason, output, .. } => {
+                println!(
+                    "\nFlow '{setup_flow}' is waiting for input: {}",
+                    reason.as_deref().unwrap_or("unknown")
+                );
+                println!(
+                    "Output so far: {}",
+                    serde_json::to_string_pretty(&output).unwrap_or_else(|_| "<invalid>".into())
+                );
+            }
+            demo::DemoBlockedOn::Error(err) => {
+                return Err(err.context(format!("flow '{setup_flow}' failed")));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl RegistryMirrorArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let reference = provider_registry_ref(self.provider_registry);
+        std::fs::create_dir_all(&self.out)
+            .with_context(|| format!("create {}", self.out.display()))?;
+        let mirror = provider_registry::mirror_registry(&reference, &self.out)?;
+        if matches!(self.format, ListFormat::Json) {
+            println!("{}", serde_json::to_string_pretty(&mirror)?);
+            return Ok(());
+        }
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.registry.mirror.done",
+                "Mirrored {} ({}) to {}: {} pack(s)",
+                &[
+                    &mirror.registry_ref,
+                    &mirror.registry_digest,
+                    &self.out.display().to_string(),
+                    &mirror.packs.len().to_string()
+                ]
+            )
+        );
+        for pack in &mirror.packs {
+            println!("  {}  {}  {}", pack.pack_id, pack.digest, pack.file);
+        }
+        Ok(())
+    }
+}
+
+/// Writing a tenant's config is a policy change, and carrying its secrets
+/// along is a secrets write, so both need the same grants as the commands
+/// that do them one at a time.
+fn authorize_tenant_apply(
+    bundle: &Path,
+    snapshot: &tenant_transfer::TenantSnapshot,
+) -> anyhow::Result<()> {
+    operator_auth::authorize(bundle, OperatorAction::Policy, &snapshot.tenant)?;
+    if !snapshot.secrets.is_empty() {
+        operator_auth::authorize(bundle, OperatorAction::SecretsWrite, &snapshot.tenant)?;
+    }
+    Ok(())
+}
+
+impl TenantCloneArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let target_bundle = self.to_bundle.unwrap_or_else(|| self.bundle.clone());
+        let snapshot = tenant_transfer::snapshot(&self.bundle, &self.source, !self.no_secrets)?
+            .renamed(&self.target)?;
+        authorize_tenant_apply(&target_bundle, &snapshot)?;
+        let result = tenant_transfer::apply(&target_bundle, &snapshot, self.force);
+        audit::record(
+            &target_bundle,
+            AuditEvent::new("tenant.clone", &self.target)
+                .detail(json!({
+                    "from_bundle": self.bundle,
+                    "from_tenant": self.source,
+                }))
+                .result(&result),
+        );
+        let report = result?;
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.tenant.cloned",
+                "Cloned tenant {} to {} in {}: {} file(s), {} secret(s)",
+                &[
+                    &self.source,
+                    &report.tenant,
+                    &target_bundle.display().to_string(),
+                    &report.files.len().to_string(),
+                    &report.secrets.to_string()
+                ]
+            )
+        );
+        Ok(())
+    }
+}
+
+impl ServeArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let bundle = self
+            .bundle
+            .or_else(|| env::var_os(serve::ENV_BUNDLE).map(PathBuf::from))
+            .ok_or_else(|| anyhow!("pass --bundle or set {}", serve::ENV_BUNDLE))?;
+        let from_env = |name: &str| env::var(name).ok().filter(|value| !value.is_empty());
+        let tenant = self.tenant.or_else(|| from_env(serve::ENV_TENANT));
+        let team = self.team.or_else(|| from_env(serve::ENV_TEAM));
+        let nats_url = self.nats_url.or_else(|| from_env(serve::ENV_NATS_URL));
+        let health_port = match (self.health_port, from_env(serve::ENV_HEALTH_PORT)) {
+            (Some(port), _) => port,
+            (None, Some(value)) => value
+                .parse()
+                .with_context(|| format!("{} is not a port: {value}", serve::ENV_HEALTH_PORT))?,
+            (None, None) => serve::DEFAULT_HEALTH_PORT,
+        };
+        progress::set_verbosity(progress::Verbosity::Quiet);
+        let state_dir = bundle.join("state");
+        std::fs::create_dir_all(&state_dir)?;
+        if env::var(config::ENV_GATEWAY_LISTEN_ADDR).is_err() {
+            // Exported like `demo start --headless` so every later config load sees it.
+            unsafe {
+                std::env::set_var(config::ENV_GATEWAY_LISTEN_ADDR, "0.0.0.0");
+            }
+        }
+        let log_level = if self.verbose {
+            operator_log::Level::Debug
+        } else {
+            operator_log::Level::Info
+        };
+        let log_dir = self.log_dir.unwrap_or_else(|| bundle.join("logs"));
+        let log_dir = operator_log::init(log_dir, log_level)?;
+        let run_targets = select_bundle_run_targets(&bundle, tenant.as_deref(), team.as_deref())?;
+        let tenant = tenant.unwrap_or_else(|| DEMO_DEFAULT_TENANT.to_string());
+        let team_id = team
+            .clone()
+            .unwrap_or_else(|| DEMO_DEFAULT_TEAM.to_string());
+        operator_log::info(
+            module_path!(),
+            format!(
+                "serve (bundle={} targets=[{}]) log_dir={}",
+                bundle.display(),
+                format_bundle_targets(&run_targets),
+                log_dir.display()
+            ),
+        );
+
+        domains::ensure_cbor_packs(&bundle)?;
+        let discovery = discovery::discover_with_options(
+            &bundle,
+            discovery::DiscoveryOptions { cbor_only: true },
+        )?;
+        discovery::persist(&bundle, &tenant, &discovery)?;
+        let demo_config_path = bundle.join("greentic.demo.yaml");
+        let demo_config = load_demo_config_or_default(&demo_config_path);
+        let domains = api::discovered_domains(&discovery);
+        let runner_binary = runner_pin::resolve_runner(&bundle, &demo_config.runner, None)?;
+        if let Some(runner) = runner_binary.as_deref() {
+            let packs = discovery
+                .providers
+                .iter()
+                .map(|provider| provider.pack_path.clone())
+                .collect::<Vec<_>>();
+            runner_pin::check_packs(runner, &packs)?;
+        }
+        for target in &run_targets {
+            let paths = RuntimePaths::new(&state_dir, &target.tenant, target.team_id());
+            match nats_url.as_deref() {
+                Some(url) => {
+                    let binding = jetstream::JetStreamBinding::for_scope(
+                        url,
+                        &target.tenant,
+                        target.team_id(),
+                    );
+                    jetstream::ensure_streams(&binding, &demo_config.services.nats.jetstream)
+                        .with_context(|| format!("failed to set up jetstream streams on {url}"))?;
+                    jetstream::write_binding(&paths, &binding)?;
+                }
+                None => jetstream::remove_binding(&paths)?,
+            }
+        }
+
+        let health = serve::Health::default();
+        let probes = serve::ProbeServer::start(
+            SocketAddr::from(([0, 0, 0, 0], health_port)),
+            health.clone(),
+        )?;
+        let (mut reload_listener, _) = ReloadListener::new()?;
+        let secrets_handle =
+            secrets_gate::resolve_secrets_manager(&bundle, &tenant, team.as_deref())?;
+        let ctx = DemoServiceContext {
+            bundle: &bundle,
+            discovery: &discovery,
+            domains: &domains,
+            runner_binary,
+            debug_enabled: self.verbose,
+            secrets_handle,
+            tenant: &tenant,
+            team: &team_id,
+        };
+        let mut services = DemoReloadableServices::start(&ctx, &demo_config);
+        let mut checks = serve::HealthChecks::start(
+            health.clone(),
+            serve_probes(&demo_config, nats_url.as_deref()),
+        )?;
+        let renewer = if domains.contains(&Domain::Messaging)
+            && matches!(
+                demo_config.services.subscriptions.mode,
+                config::DemoSubscriptionsMode::UniversalOps
+            ) {
+            Some(serve::SubscriptionRenewer::start(
+                &bundle,
+                &tenant,
+                &team_id,
+                &demo_config.services.subscriptions.universal,
+                health.clone(),
+            )?)
+        } else {
+            None
+        };
+        health.set_started(true);
+        demo::reload::write_process(&state_dir)?;
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.serve.running",
+                "Serving {} (targets: {}); probes at http://{}/healthz and /readyz",
+                &[
+                    &bundle.display().to_string(),
+                    &format_bundle_targets(&run_targets),
+                    &probes.addr().to_string()
+                ]
+            )
+        );
+
+        while let DemoSignal::Reload(_) = reload_listener.wait()? {
+            health.set_started(false);
+            checks.stop()?;
+            match reload_demo_bundle(&ctx, &run_targets, &mut services) {
+                Ok(summary) => {
+                    operator_log::info(module_path!(), format!("serve reloaded: {summary}"))
+                }
+                Err(err) => {
+                    operator_log::warn(module_path!(), format!("serve reload failed: {err:#}"))
+                }
+            }
+            let demo_config = load_demo_config_or_default(&demo_config_path);
+            checks = serve::HealthChecks::start(
+                health.clone(),
+                serve_probes(&demo_config, nats_url.as_deref()),
+            )?;
+            health.set_started(true);
+        }
+
+        operator_log::info(module_path!(), "serve shutting down");
+        health.set_started(false);
+        demo::reload::remove_process(&state_dir)?;
+        checks.stop()?;
+        if let Some(renewer) = renewer {
+            renewer.stop()?;
+        }
+        services.stop()?;
+        probes.stop()
+    }
+}
+
+fn serve_probes(demo_config: &config::DemoConfig, nats_url: Option<&str>) -> Vec<serve::TcpProbe> {
+    let gateway = &demo_config.services.gateway;
+    let mut probes = vec![serve::TcpProbe::gateway(&gateway.listen_addr, gateway.port)];
+    probes.extend(nats_url.map(serve::TcpProbe::nats));
+    probes
+}
+
+impl MigrateArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let result = migrate::migrate(&self.bundle, self.dry_run);
+        // Not a bundle: there is no audit log to write to.
+        if !self.dry_run && self.bundle.join("greentic.demo.yaml").exists() {
+            let detail = match &result {
+                Ok(report) => json!({
+                    "from_version": report.from_version,
+                    "to_version": report.to_version,
+                    "backup": report.backup,
+                }),
+                Err(_) => JsonValue::Null,
+            };
+            audit::record(
+                &self.bundle,
+                AuditEvent::new("bundle.migrate", self.bundle.display().to_string())
+                    .detail(detail)
+                    .result(&result),
+            );
+        }
+        let report = result?;
+        match self.format {
+            ListFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            ListFormat::Text => {
+                print!("{}", migrate::render_text(&report));
+                let manual = report
+                    .steps
+                    .iter()
+                    .filter(|step| step.status == migrate::StepStatus::Manual)
+                    .count();
+                if manual > 0 {
+                    eprintln!(
+                        "{}",
+                        operator_i18n::trf(
+                            "cli.migrate.manual_steps",
+                            "{} step(s) need a manual fix; see the details above.",
+                            &[&manual.to_string()]
+                        )
+                    );
+                } else if self.dry_run {
+                    println!(
+                        "{}",
+                        operator_i18n::tr(
+                            "cli.migrate.dry_run_hint",
+                            "Dry run: nothing was changed. Run again without --dry-run to apply."
+                        )
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SelfUpdateArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let release = self_update::fetch_release(&self_update::release_url(self.channel))?;
+        let check = self_update::check(self.channel, &release)?;
+        if self.check || !check.update_available {
+            match self.format {
+                ListFormat::Json => println!("{}", serde_json::to_string_pretty(&check)?),
+                ListFormat::Text if check.update_available => println!(
+                    "{}",
+                    operator_i18n::trf(
+                        "cli.self_update.available",
+                        "greentic-operator {} is available on the {} channel (running {}).",
+                        &[&check.latest, self.channel.as_str(), &check.current]
+                    )
+                ),
+                ListFormat::Text => println!(
+                    "{}",
+                    operator_i18n::trf(
+                        "cli.self_update.up_to_date",
+                        "greentic-operator {} is up to date on the {} channel (latest {}).",
+                        &[&check.current, self.channel.as_str(), &check.latest]
+                    )
+                ),
+            }
+            if self.check && check.update_available {
+                std::process::exit(operator_error::EXIT_FAILURE);
+            }
+            return Ok(());
+        }
+        let exe = std::env::current_exe().context("locate the running greentic-operator")?;
+        self_update::remove_stale_binary(&exe);
+        let installed = self_update::install(&release, self.channel, &exe)?;
+        match self.format {
+            ListFormat::Json => println!("{}", serde_json::to_string_pretty(&check)?),
+            ListFormat::Text => println!(
+                "{}",
+                operator_i18n::trf(
+                    "cli.self_update.updated",
+                    "Updated {} from {} to {}.",
+                    &[
+                        &installed.display().to_string(),
+                        &check.current,
+                        &check.latest
+                    ]
+                )
+            ),
+        }
+        Ok(())
+    }
+}
+
+impl TenantExportArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let snapshot = tenant_transfer::snapshot(&self.bundle, &self.tenant, !self.no_secrets)?;
+        let passphrase = self
+            .passphrase_env
+            .map(|env| secrets_crypto::KeySource::Passphrase { env });
+        tenant_transfer::write_archive(&snapshot, &self.archive, passphrase.as_ref())?;
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.tenant.exported",
+                "Exported tenant {} to {}: {} file(s), {} secret(s)",
+                &[
+                    &snapshot.tenant,
+                    &self.archive.display().to_string(),
+                    &snapshot.files.len().to_string(),
+                    &snapshot.secrets.len().to_string()
+                ]
+            )
+        );
+        if !snapshot.secrets.is_empty() && passphrase.is_none() {
+            eprintln!(
+                "{}",
+                operator_i18n::tr(
+                    "cli.tenant.warn_plaintext_secrets",
+                    "Warning: secrets are stored in plaintext; pass --passphrase-env to encrypt them or --no-secrets to leave them out."
+                )
+            );
+        }
+        Ok(())
+    }
+}
+
+impl TenantImportArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let mut snapshot = tenant_transfer::read_archive(&self.archive)?;
+        let archived = snapshot.tenant.clone();
+        if let Some(name) = self.rename.as_deref() {
+            snapshot = snapshot.renamed(name)?;
+        }
+        if self.no_secrets {
+            snapshot.secrets.clear();
+        }
+        authorize_tenant_apply(&self.bundle, &snapshot)?;
+        let result = tenant_transfer::apply(&self.bundle, &snapshot, self.force);
+        audit::record(
+            &self.bundle,
+            AuditEvent::new("tenant.import", &snapshot.tenant)
+                .detail(json!({
+                    "archive": self.archive,
+                    "archived_tenant": archived,
+                }))
+                .result(&result),
+        );
+        let report = result?;
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.tenant.imported",
+                "Imported tenant {} into {}: {} file(s), {} secret(s)",
+                &[
+                    &report.tenant,
+                    &self.bundle.display().to_string(),
+                    &report.files.len().to_string(),
+                    &report.secrets.to_string()
+                ]
+            )
+        );
+        Ok(())
+    }
+}
+
+/// `--provider-registry`, then `$GTC_PROVIDER_REGISTRY_REF`, then the public registry.
+fn provider_registry_ref(explicit: Option<String>) -> String {
+    explicit
+        .or_else(|| std::env::var("GTC_PROVIDER_REGISTRY_REF").ok())
+        .unwrap_or_else(|| DEFAULT_PROVIDER_REGISTRY_REF.to_string())
+}
+
+/// Catalog file that takes precedence over the provider registry, if any.
+fn wizard_catalog_file(
+    explicit: Option<PathBuf>,
+    mirror: Option<&provider_registry::RegistryMirror>,
+) -> Option<PathBuf> {
+    explicit
+        .or_else(|| {
+            std::env::var("GREENTIC_OPERATOR_WIZARD_CATALOG")
+                .ok()
+                .map(PathBuf::from)
+        })
+        .or_else(|| mirror.map(|mirror| mirror.catalog_path()))
+}
+
+impl CatalogSourceArgs {
+    fn load(&self) -> anyhow::Result<Vec<catalog::CatalogEntry>> {
+        let mirror =
+            provider_registry::RegistryMirror::from_dir_or_env(self.registry_mirror.clone())?;
+        let reference = provider_registry_ref(self.provider_registry.clone());
+        let path = provider_registry::resolve_catalog_path(
+            wizard_catalog_file(self.catalog_file.clone(), mirror.as_ref()),
+            Some(reference.as_str()),
+            self.offline,
+            &self.bundle,
+        )?
+        .ok_or_else(|| {
+            anyhow!(
+                "provider registry is required; set --provider-registry <ref> or GTC_PROVIDER_REGISTRY_REF"
+            )
+        })?;
+        let mut entries = catalog::load_catalog(&path)?;
+        registry_overlay::apply(
+            &self.bundle,
+            self.tenant.iter(),
+            &mut entries,
+            catalog::load_catalog,
+        )?;
+        for entry in &mut entries {
+            catalog::enrich_from_pack(entry, mirror.as_ref())?;
+        }
+        Ok(entries)
+    }
+}
+
+impl CatalogSearchArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let entries = self.source.load()?;
+        let matches = catalog::search(&entries, &self.term);
+        if matches!(self.format, ListFormat::Json) {
+            println!("{}", serde_json::to_string_pretty(&matches)?);
+            return Ok(());
+        }
+        if matches.is_empty() {
+            println!(
+                "{}",
+                operator_i18n::trf(
+                    "cli.catalog.search.none",
+                    "No providers match \"{}\".",
+                    &[&self.term]
+                )
+            );
+            return Ok(());
+        }
+        for entry in matches {
+            println!(
+                "{}  {}  [{}]",
+                entry.id,
+                entry.label,
+                entry.domains.join(", ")
+            );
+            if let Some(description) = entry.description.as_deref() {
+                println!("    {description}");
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CatalogShowArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let entries = self.source.load()?;
+        let entry = catalog::find(&entries, &self.id).ok_or_else(|| {
+            anyhow!(
+                "{}",
+                operator_i18n::trf(
+                    "cli.catalog.show.not_found",
+                    "provider {} is not in the registry; try catalog search",
+                    &[&self.id]
+                )
+            )
+        })?;
+        if matches!(self.format, ListFormat::Json) {
+            println!("{}", serde_json::to_string_pretty(entry)?);
+            return Ok(());
+        }
+        let none = operator_i18n::tr("cli.catalog.show.none", "(none)");
+        let or_none = |values: &[String]| {
+            if values.is_empty() {
+                none.clone()
+            } else {
+                values.join(", ")
+            }
+        };
+        println!("{} ({})", entry.label, entry.id);
+        if let Some(description) = entry.description.as_deref() {
+            println!("  {description}");
+        }
+        println!(
+            "  {}: {}",
+            operator_i18n::tr("cli.catalog.show.domains", "domains"),
+            or_none(&entry.domains)
+        );
+        println!(
+            "  {}: {}",
+            operator_i18n::tr("cli.catalog.show.secrets", "required secrets"),
+            or_none(&entry.required_secrets)
+        );
+        println!(
+            "  {}: {}",
+            operator_i18n::tr("cli.catalog.show.reference", "pack ref"),
+            entry.reference
+        );
+        if let Some(path) = entry.pack_path.as_ref() {
+            println!(
+                "  {}: {}",
+                operator_i18n::tr("cli.catalog.show.pack_path", "local pack"),
+                path.display()
+            );
+        }
+        Ok(())
+    }
+}
+
+impl WizardI18nCommand {
+    fn run(self) -> anyhow::Result<()> {
+        match self.command {
+            WizardI18nSubcommand::Check(args) => args.run(),
+            WizardI18nSubcommand::Export(args) => args.run(),
+            WizardI18nSubcommand::Import(args) => args.run(),
+        }
+    }
+}
 
-        match runner.run_until_blocked() {
-            demo::DemoBlockedOn::Finished(output) => {
-                println!("\nFlow '{setup_flow}' completed:");
-                println!(
-                    "{}",
-                    serde_json::to_string_pretty(&output)
-                        .unwrap_or_else(|_| "<invalid>".into())
-                );
-            }
-            demo::DemoBlockedOn::Waiting { reason, output, .. } => {
-                println!(
-                    "\nFlow '{setup_flow}' is waiting for input: {}",
-                    reason.as_deref().unwrap_or("unknown")
-                );
+impl WizardI18nCheckArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let report = wizard_i18n_review::check(&self.locale)?;
+        if matches!(self.format, ListFormat::Json) {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            for issue in &report.issues {
+                let severity = match issue.severity {
+                    wizard_i18n_review::Severity::Error => "error",
+                    wizard_i18n_review::Severity::Warning => "warning",
+                };
                 println!(
-                    "Output so far: {}",
-                    serde_json::to_string_pretty(&output)
-                        .unwrap_or_else(|_| "<invalid>".into())
+                    "{severity} {}:{}: {} ({:?})",
+                    issue.catalog.as_str(),
+                    issue.key,
+                    issue.message,
+                    issue.english
                 );
             }
-            demo::DemoBlockedOn::Error(err) => {
-                return Err(err.context(format!("flow '{setup_flow}' failed")));
-            }
+            let count = |severity: wizard_i18n_review::Severity| {
+                report
+                    .issues
+                    .iter()
+                    .filter(|issue| issue.severity == severity)
+                    .count()
+                    .to_string()
+            };
+            println!(
+                "{}",
+                operator_i18n::trf(
+                    "cli.wizard.i18n.summary",
+                    "{}: {} keys checked, {} error(s), {} warning(s)",
+                    &[
+                        &report.locale,
+                        &report.checked.to_string(),
+                        &count(wizard_i18n_review::Severity::Error),
+                        &count(wizard_i18n_review::Severity::Warning),
+                    ]
+                )
+            );
+        }
+        if report.has_errors() {
+            return Err(anyhow!(
+                "{} has untranslated or mismatched wizard prompts",
+                report.locale
+            ));
         }
+        Ok(())
+    }
+}
 
+impl WizardI18nExportArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let rows = wizard_i18n_review::export_rows(&self.locale, self.all)?;
+        let csv = wizard_i18n_review::write_csv(&rows);
+        let Some(out) = self.out else {
+            print!("{csv}");
+            return Ok(());
+        };
+        std::fs::write(&out, csv).with_context(|| format!("write {}", out.display()))?;
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.wizard.i18n.exported",
+                "Wrote {} row(s) to {}",
+                &[&(rows.len() - 1).to_string(), &out.display().to_string()]
+            )
+        );
         Ok(())
     }
 }
 
-impl DemoWizardArgs {
+impl WizardI18nImportArgs {
     fn run(self) -> anyhow::Result<()> {
+        let csv = std::fs::read_to_string(&self.csv)
+            .with_context(|| format!("read {}", self.csv.display()))?;
+        let written = wizard_i18n_review::import_csv(&self.locale, &csv, &self.i18n_dir)?;
+        if written.is_empty() {
+            println!(
+                "{}",
+                operator_i18n::tr(
+                    "cli.wizard.i18n.nothing_imported",
+                    "No translated rows to import"
+                )
+            );
+        }
+        for (path, count) in written {
+            println!(
+                "{}",
+                operator_i18n::trf(
+                    "cli.wizard.i18n.imported",
+                    "Updated {} key(s) in {}",
+                    &[&count.to_string(), &path.display().to_string()]
+                )
+            );
+        }
+        Ok(())
+    }
+}
+
+impl DemoWizardArgs {
+    fn run(mut self) -> anyhow::Result<()> {
+        if let Some(DemoWizardSubcommand::I18n(command)) = self.command.take() {
+            return command.run();
+        }
+        progress::set_verbosity(progress::Verbosity::from_flags(self.quiet, self.verbose));
+        if let Some(spec) = self.spec.as_deref() {
+            return self.run_spec(spec);
+        }
         let mode: wizard::WizardMode = self.mode.into();
         let effective_locale = self.locale.clone().unwrap_or_else(detect_system_locale_tag);
-        let provider_registry_ref = self
-            .provider_registry
-            .clone()
-            .or_else(|| std::env::var("GTC_PROVIDER_REGISTRY_REF").ok())
-            .unwrap_or_else(|| DEFAULT_PROVIDER_REGISTRY_REF.to_string());
+        let provider_registry_ref = provider_registry_ref(self.provider_registry.clone());
+        let mirror =
+            provider_registry::RegistryMirror::from_dir_or_env(self.registry_mirror.clone())?;
+        let catalog_file = wizard_catalog_file(self.catalog_file.clone(), mirror.as_ref());
         let qa_catalog_bundle_hint = self.bundle.clone().unwrap_or_else(|| PathBuf::from("."));
         let qa_catalog_path = provider_registry::resolve_catalog_path(
-            self.catalog_file.clone().or_else(|| {
-                std::env::var("GREENTIC_OPERATOR_WIZARD_CATALOG")
-                    .ok()
-                    .map(PathBuf::from)
-            }),
+            catalog_file.clone(),
             Some(provider_registry_ref.as_str()),
             self.offline,
             &qa_catalog_bundle_hint,
         )?;
-        let qa_catalog_entries = {
+        let mut qa_catalog_entries = {
             let path = qa_catalog_path.ok_or_else(|| {
                 anyhow!(
                     "provider registry is required; set --provider-registry <ref> or GTC_PROVIDER_REGISTRY_REF"
@@ -2702,6 +8567,20 @@ impl DemoWizardArgs {
             })?;
             wizard::load_catalog_from_file(&path)?
         };
+        if let Some(bundle) = self.bundle.as_deref() {
+            let cli_tenants = std::iter::once(self.tenant.clone()).chain(
+                self.targets
+                    .iter()
+                    .filter_map(|target| parse_wizard_target(target).ok())
+                    .map(|(tenant, _)| tenant),
+            );
+            registry_overlay::apply(
+                bundle,
+                cli_tenants,
+                &mut qa_catalog_entries,
+                wizard::load_catalog_from_file,
+            )?;
+        }
         let qa_provider_ids = qa_catalog_entries
             .iter()
             .map(|entry| entry.id.clone())
@@ -2727,11 +8606,7 @@ impl DemoWizardArgs {
             .ok_or_else(|| anyhow!("bundle path is required via --bundle or wizard answers"))?;
 
         let catalog_path = provider_registry::resolve_catalog_path(
-            self.catalog_file.clone().or_else(|| {
-                std::env::var("GREENTIC_OPERATOR_WIZARD_CATALOG")
-                    .ok()
-                    .map(PathBuf::from)
-            }),
+            catalog_file,
             Some(provider_registry_ref.as_str()),
             self.offline,
             &bundle,
@@ -2742,7 +8617,7 @@ impl DemoWizardArgs {
                 "provider registry is required; set --provider-registry <ref> or GTC_PROVIDER_REGISTRY_REF"
             )
         })?;
-        let catalog_entries = wizard::load_catalog_from_file(&catalog_path)?;
+        let mut catalog_entries = wizard::load_catalog_from_file(&catalog_path)?;
         if mode != wizard::WizardMode::Create || bundle.exists() {
             if let Some(local_path) = parse_local_registry_ref(provider_registry_ref.as_str()) {
                 if local_path.exists() {
@@ -2760,31 +8635,6 @@ impl DemoWizardArgs {
                 );
             }
         }
-        let by_id = catalog_entries
-            .into_iter()
-            .map(|entry| (entry.id.clone(), entry))
-            .collect::<std::collections::BTreeMap<_, _>>();
-        let mut refs = normalize_pack_refs(&answers.pack_refs);
-        refs.extend(self.pack_refs.clone());
-        let provider_ids = normalize_provider_ids(&answers.providers);
-        for provider_id in &provider_ids {
-            if let Some(item) = by_id.get(provider_id) {
-                refs.push(item.reference.clone());
-            }
-        }
-
-        let mut catalog_ids = normalize_catalog_packs(&answers.catalog_packs);
-        catalog_ids.extend(self.catalog_packs.clone());
-        for id in &catalog_ids {
-            let item = by_id.get(id).ok_or_else(|| {
-                anyhow!(
-                    "unknown --catalog-pack {}; available: {}",
-                    id,
-                    by_id.keys().cloned().collect::<Vec<_>>().join(", ")
-                )
-            })?;
-            refs.push(item.reference.clone());
-        }
 
         let mut tenants = Vec::new();
         let merged_allow_paths = if self.allow_paths.is_empty() {
@@ -2818,6 +8668,38 @@ impl DemoWizardArgs {
             }
         }
 
+        registry_overlay::apply(
+            &bundle,
+            tenants.iter().map(|tenant| tenant.tenant.as_str()),
+            &mut catalog_entries,
+            wizard::load_catalog_from_file,
+        )?;
+        let by_id = catalog_entries
+            .into_iter()
+            .map(|entry| (entry.id.clone(), entry))
+            .collect::<std::collections::BTreeMap<_, _>>();
+        let mut refs = normalize_pack_refs(&answers.pack_refs);
+        refs.extend(self.pack_refs.clone());
+        let provider_ids = normalize_provider_ids(&answers.providers);
+        for provider_id in &provider_ids {
+            if let Some(item) = by_id.get(provider_id) {
+                refs.push(item.reference.clone());
+            }
+        }
+
+        let mut catalog_ids = normalize_catalog_packs(&answers.catalog_packs);
+        catalog_ids.extend(self.catalog_packs.clone());
+        for id in &catalog_ids {
+            let item = by_id.get(id).ok_or_else(|| {
+                anyhow!(
+                    "unknown --catalog-pack {}; available: {}",
+                    id,
+                    by_id.keys().cloned().collect::<Vec<_>>().join(", ")
+                )
+            })?;
+            refs.push(item.reference.clone());
+        }
+
         let update_ops = normalize_update_ops(&answers.update_ops);
         let remove_targets = normalize_remove_targets(&answers.remove_targets);
         let packs_remove = normalize_pack_removes(&answers.packs_remove)?;
@@ -2934,84 +8816,202 @@ impl DemoWizardArgs {
             std::fs::remove_dir_all(&bundle)
                 .with_context(|| format!("remove existing bundle {}", bundle.display()))?;
         }
-
-        let report = wizard_executor::execute(mode, &plan, self.offline)?;
-        let no_op_count = plan
-            .steps
-            .iter()
-            .filter(|step| step.kind == wizard::WizardStepKind::NoOp)
-            .count();
-        println!(
-            "{}",
-            operator_i18n::trf(
-                "cli.wizard.execute_complete",
-                "wizard execute complete bundle={} packs={} manifests={} providers={} no_ops={}",
-                &[
-                    &report.bundle.display().to_string(),
-                    &report.resolved_packs.len().to_string(),
-                    &report.resolved_manifests.len().to_string(),
-                    &report.provider_updates.to_string(),
-                    &no_op_count.to_string()
-                ]
-            )
-        );
-        for manifest in &report.resolved_manifests {
+        let clobbered = clobbered_drift(&plan);
+        if clobbered > 0
+            && !self.overwrite_drift
+            && !prompt_yes_no(
+                &format!(
+                    "{clobbered} manual edit(s) in the bundle would be overwritten. Continue? [y, N]"
+                ),
+                false,
+            )?
+        {
             println!(
                 "{}",
-                operator_i18n::trf(
-                    "cli.wizard.resolved_manifest",
-                    "resolved manifest: {}",
-                    &[&manifest.display().to_string()]
+                operator_i18n::tr(
+                    "cli.wizard.execution_aborted",
+                    "wizard execution aborted by user"
                 )
             );
+            return Ok(());
         }
-        for warning in &report.warnings {
-            println!(
-                "{}",
-                operator_i18n::trf("cli.wizard.warning", "warning: {}", &[warning])
-            );
+
+        execute_wizard_plan(
+            mode,
+            &plan,
+            self.offline,
+            mirror.as_ref(),
+            self.failure.best_effort(),
+            self.run_setup,
+            self.setup_input.as_ref(),
+        )
+    }
+
+    /// `--spec`: the plan comes from the declarative spec and runs without QA or
+    /// prompts; `--dry-run` stops after printing it.
+    fn run_spec(&self, spec_path: &Path) -> anyhow::Result<()> {
+        let mode: wizard::WizardMode = self.mode.into();
+        if mode == wizard::WizardMode::Remove {
+            return Err(anyhow!("--spec supports --mode create and update"));
+        }
+        let spec = wizard_bundle_spec::BundleSpec::load(spec_path)?;
+        let mirror =
+            provider_registry::RegistryMirror::from_dir_or_env(self.registry_mirror.clone())?;
+        let catalog = if spec.needs_catalog() {
+            let provider_registry_ref = provider_registry_ref(self.provider_registry.clone());
+            let hint = self
+                .bundle
+                .clone()
+                .or_else(|| spec.bundle.clone())
+                .unwrap_or_else(|| PathBuf::from("."));
+            let path = provider_registry::resolve_catalog_path(
+                wizard_catalog_file(self.catalog_file.clone(), mirror.as_ref()),
+                Some(provider_registry_ref.as_str()),
+                self.offline,
+                &hint,
+            )?
+            .ok_or_else(|| {
+                anyhow!(
+                    "provider registry is required; set --provider-registry <ref> or GTC_PROVIDER_REGISTRY_REF"
+                )
+            })?;
+            let mut catalog = wizard::load_catalog_from_file(&path)?;
+            registry_overlay::apply(
+                &hint,
+                spec.tenants.keys(),
+                &mut catalog,
+                wizard::load_catalog_from_file,
+            )?;
+            catalog
+        } else {
+            Vec::new()
+        };
+        let request = spec.to_request(self.bundle.clone(), &catalog)?;
+        let plan = wizard_plan_builder::build_plan(mode, &request, self.dry_run)?;
+        wizard::print_plan_summary(&plan);
+        if self.dry_run {
+            return Ok(());
+        }
+        let clobbered = clobbered_drift(&plan);
+        if clobbered > 0 && !self.overwrite_drift {
+            return Err(anyhow!(
+                "{clobbered} manual edit(s) in {} would be overwritten; review the drift above and pass --overwrite-drift to apply the spec anyway",
+                plan.bundle.display()
+            ));
         }
+        let setup_input = self.setup_input.clone().or(spec.setup.input.clone());
+        execute_wizard_plan(
+            mode,
+            &plan,
+            self.offline,
+            mirror.as_ref(),
+            self.failure.best_effort(),
+            self.run_setup || spec.setup.run,
+            setup_input.as_ref(),
+        )
+    }
+}
 
-        if self.run_setup && mode != wizard::WizardMode::Remove {
-            let setup_provider_ids = report
-                .resolved_packs
-                .iter()
-                .filter(|pack| pack.entry_flows.iter().any(|flow| flow == "setup_default"))
-                .map(|pack| pack.pack_id.clone())
-                .collect::<BTreeSet<_>>();
-            let allowed_providers = if setup_provider_ids.is_empty() {
-                None
-            } else {
-                Some(setup_provider_ids)
-            };
-            let preloaded_setup_answers = if let Some(allowed) = allowed_providers.as_ref() {
-                Some(build_wizard_setup_answers(
-                    &plan.bundle,
-                    &report.resolved_packs,
-                    allowed,
-                    self.setup_input.as_ref(),
-                )?)
-            } else {
-                None
-            };
-            for tenant in &plan.metadata.tenants {
-                run_wizard_setup_for_target(
-                    &plan.bundle,
-                    &tenant.tenant,
-                    tenant.team.as_deref(),
-                    self.setup_input.as_ref(),
-                    allowed_providers.clone(),
-                    preloaded_setup_answers.clone(),
-                )?;
-            }
-        } else if self.run_setup && mode == wizard::WizardMode::Remove {
-            println!(
-                "{}",
-                operator_i18n::tr("cli.wizard.skip_setup_remove", "skip setup for remove mode")
-            );
+/// Manual edits the plan would overwrite or delete.
+fn clobbered_drift(plan: &wizard::WizardPlan) -> usize {
+    plan.drift.iter().filter(|item| item.clobbered()).count()
+}
+
+/// Executes a wizard plan, prints the report, and optionally runs provider setup.
+fn execute_wizard_plan(
+    mode: wizard::WizardMode,
+    plan: &wizard::WizardPlan,
+    offline: bool,
+    mirror: Option<&provider_registry::RegistryMirror>,
+    best_effort: bool,
+    run_setup: bool,
+    setup_input: Option<&PathBuf>,
+) -> anyhow::Result<()> {
+    let report = wizard_executor::execute(mode, plan, offline, mirror, best_effort)?;
+    let no_op_count = plan
+        .steps
+        .iter()
+        .filter(|step| step.kind == wizard::WizardStepKind::NoOp)
+        .count();
+    println!(
+        "{}",
+        operator_i18n::trf(
+            "cli.wizard.execute_complete",
+            "wizard execute complete bundle={} packs={} manifests={} providers={} no_ops={}",
+            &[
+                &report.bundle.display().to_string(),
+                &report.resolved_packs.len().to_string(),
+                &report.resolved_manifests.len().to_string(),
+                &report.provider_updates.to_string(),
+                &no_op_count.to_string()
+            ]
+        )
+    );
+    for manifest in &report.resolved_manifests {
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.wizard.resolved_manifest",
+                "resolved manifest: {}",
+                &[&manifest.display().to_string()]
+            )
+        );
+    }
+    for warning in &report.warnings {
+        println!(
+            "{}",
+            operator_i18n::trf("cli.wizard.warning", "warning: {}", &[warning])
+        );
+    }
+
+    if run_setup && mode != wizard::WizardMode::Remove {
+        let setup_provider_ids = report
+            .resolved_packs
+            .iter()
+            .filter(|pack| pack.entry_flows.iter().any(|flow| flow == "setup_default"))
+            .map(|pack| pack.pack_id.clone())
+            .collect::<BTreeSet<_>>();
+        let allowed_providers = if setup_provider_ids.is_empty() {
+            None
+        } else {
+            Some(setup_provider_ids)
+        };
+        let preloaded_setup_answers = if let Some(allowed) = allowed_providers.as_ref() {
+            // The answers are shared by every target; secrets come from the first.
+            let target = plan.metadata.tenants.first();
+            let scope = SecretScope {
+                bundle_root: &plan.bundle,
+                tenant: target.map_or("demo", |target| target.tenant.as_str()),
+                team: target.and_then(|target| target.team.as_deref()),
+            };
+            Some(build_wizard_setup_answers(
+                &plan.bundle,
+                &report.resolved_packs,
+                allowed,
+                setup_input,
+                scope,
+            )?)
+        } else {
+            None
+        };
+        for tenant in &plan.metadata.tenants {
+            run_wizard_setup_for_target(
+                &plan.bundle,
+                &tenant.tenant,
+                tenant.team.as_deref(),
+                setup_input,
+                allowed_providers.clone(),
+                preloaded_setup_answers.clone(),
+                best_effort,
+            )?;
         }
-        Ok(())
+    } else if run_setup && mode == wizard::WizardMode::Remove {
+        println!(
+            "{}",
+            operator_i18n::tr("cli.wizard.skip_setup_remove", "skip setup for remove mode")
+        );
     }
+    Ok(())
 }
 
 fn parse_wizard_target(input: &str) -> anyhow::Result<(String, Option<String>)> {
@@ -4166,9 +10166,15 @@ fn run_wizard_setup_for_target(
     setup_input: Option<&PathBuf>,
     allowed_providers: Option<BTreeSet<String>>,
     preloaded_setup_answers: Option<SetupInputAnswers>,
+    best_effort: bool,
 ) -> anyhow::Result<()> {
-    for domain in [Domain::Messaging, Domain::Events, Domain::Secrets] {
-        run_domain_command(DomainRunArgs {
+    let domains = domains::order_domains_for_setup(
+        bundle,
+        vec![Domain::Messaging, Domain::Events, Domain::Secrets],
+        &config::demo_provider_dependencies(bundle)?,
+    )?;
+    for domain in domains {
+        let report = run_domain_command(DomainRunArgs {
             root: bundle.to_path_buf(),
             state_root: None,
             domain,
@@ -4177,7 +10183,6 @@ fn run_wizard_setup_for_target(
             team: team.map(|value| value.to_string()),
             provider_filter: None,
             dry_run: false,
-            format: PlanFormat::Text,
             parallel: 1,
             allow_missing_setup: true,
             allow_contract_change: false,
@@ -4185,7 +10190,7 @@ fn run_wizard_setup_for_target(
             online: false,
             secrets_env: None,
             runner_binary: None,
-            best_effort: true,
+            best_effort,
             discovered_providers: None,
             setup_input: if preloaded_setup_answers.is_some() {
                 None
@@ -4196,7 +10201,9 @@ fn run_wizard_setup_for_target(
             preloaded_setup_answers: preloaded_setup_answers.clone(),
             public_base_url: None,
             secrets_manager: None,
+            adopted_providers: BTreeSet::new(),
         })?;
+        print_domain_run_report(&report, PlanFormat::Text)?;
     }
     Ok(())
 }
@@ -4206,9 +10213,10 @@ fn build_wizard_setup_answers(
     packs: &[wizard::ResolvedPackInfo],
     allowed: &BTreeSet<String>,
     setup_input: Option<&PathBuf>,
+    scope: SecretScope<'_>,
 ) -> anyhow::Result<SetupInputAnswers> {
     let base_input = if let Some(path) = setup_input {
-        let raw = load_setup_input(path)?;
+        let raw = load_setup_input(path, Some(scope))?;
         Some(SetupInputAnswers::new(raw, allowed.clone())?)
     } else {
         None
@@ -4232,67 +10240,42 @@ fn build_wizard_setup_answers(
 
 impl DemoSendArgs {
     fn run(self) -> anyhow::Result<()> {
+        if self.strict_secrets {
+            secrets_gate::enable_strict_secrets();
+        }
+        // A logger set up earlier in this process keeps receiving the dumps.
+        if demo_debug_enabled() && !operator_log::is_initialized() {
+            let log_dir = operator_log::init(self.bundle.join("logs"), operator_log::Level::Debug)?;
+            eprintln!(
+                "{}",
+                operator_i18n::trf(
+                    "cli.demo_send.debug_log",
+                    "Debug dumps are written to {}",
+                    &[&log_dir.join("operator.log").display().to_string()]
+                )
+            );
+        }
         let team = if self.team.is_empty() {
             None
         } else {
             Some(self.team.as_str())
         };
-        domains::ensure_cbor_packs(&self.bundle)?;
-        let pack = resolve_demo_provider_pack(
-            &self.bundle,
-            &self.tenant,
-            team,
-            &self.provider,
-            Domain::Messaging,
-        )?;
-        let provider_type = primary_provider_type(&pack.path)
-            .context("failed to determine provider type for demo send")?;
-        let discovery = discovery::discover_with_options(
-            &self.bundle,
-            discovery::DiscoveryOptions { cbor_only: true },
-        )?;
-        let provider_map = discovery_map(&discovery.providers);
-        let provider_id = provider_id_for_pack(&pack.path, &pack.pack_id, Some(&provider_map));
-
-        let secrets_handle =
-            secrets_gate::resolve_secrets_manager(&self.bundle, &self.tenant, team)?;
-        let runner_host = DemoRunnerHost::new(
-            self.bundle.clone(),
-            &discovery,
-            self.runner_binary.clone(),
-            secrets_handle.clone(),
-            false,
-        )?;
-        let env = self.env.clone();
-        let context = OperatorContext {
-            tenant: self.tenant.clone(),
-            team: team.map(|value| value.to_string()),
-            correlation_id: None,
+        let domain = match self.domain.resolve(&self.bundle)? {
+            domain @ (Domain::Messaging | Domain::Events) => domain,
+            other => {
+                return Err(anyhow!(
+                    "demo send supports --domain messaging or events, not {}",
+                    domains::domain_name(other)
+                ));
+            }
         };
-
         if self.print_required_args {
-            if let Err(message) = ensure_requirements_flow(&pack) {
+            let provider = self.provider_ops(team, domain)?;
+            if let Err(message) = ensure_requirements_flow(&provider.pack) {
                 eprintln!("{message}");
-                std::process::exit(2);
+                std::process::exit(operator_error::EXIT_VALIDATION);
             }
-            let input = build_input_payload(
-                &self.bundle,
-                Domain::Messaging,
-                &self.tenant,
-                team,
-                Some(&pack.pack_id),
-                None,
-                None,
-                &env,
-            );
-            let input_bytes = serde_json::to_vec(&input)?;
-            let outcome = runner_host.invoke_provider_op(
-                Domain::Messaging,
-                &provider_id,
-                "requirements",
-                &input_bytes,
-                &context,
-            )?;
+            let outcome = self.invoke_requirements(&provider, team)?;
             if !outcome.success {
                 let message = outcome
                     .error
@@ -4311,6 +10294,9 @@ impl DemoSendArgs {
             }
             return Ok(());
         }
+        if domain == Domain::Events {
+            return self.run_events(team);
+        }
 
         let card_payload = if let Some(path) = &self.card {
             let contents = fs::read_to_string(path)
@@ -4322,156 +10308,56 @@ impl DemoSendArgs {
         } else {
             None
         };
-        let mut text_value = self.text.clone();
-        if text_value.is_none() && card_payload.is_some() {
-            text_value = Some("adaptive card".to_string());
-        }
-        let text_ref = text_value.as_deref();
-        if text_ref.is_none() && card_payload.is_none() {
+        let local_attachments = self
+            .attach
+            .iter()
+            .map(|path| attachments::load_attachment(path, self.max_attachment_bytes))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        if self.text.is_none() && card_payload.is_none() && local_attachments.is_empty() {
             return Err(anyhow::anyhow!(
-                "either --text or --card is required unless --print-required-args"
-            ));
-        }
-        let args = merge_args(self.args_json.as_deref(), &self.args)?;
-        let mut config_items = Vec::new();
-        config_items.push(ConfigGateItem::new(
-            "env",
-            Some(env.clone()),
-            ConfigValueSource::Platform("GREENTIC_ENV"),
-            true,
-        ));
-        config_items.push(ConfigGateItem::new(
-            "tenant",
-            Some(self.tenant.clone()),
-            ConfigValueSource::Platform("tenant"),
-            true,
-        ));
-        let team_label = team.unwrap_or("default");
-        config_items.push(ConfigGateItem::new(
-            "team",
-            Some(team_label.to_string()),
-            ConfigValueSource::Platform("team"),
-            true,
-        ));
-        if let Some(text) = &text_value {
-            config_items.push(ConfigGateItem::new(
-                "text",
-                Some(text.clone()),
-                ConfigValueSource::Argument("--text"),
-                true,
-            ));
-        }
-        if let Some(card_path) = &self.card {
-            config_items.push(ConfigGateItem::new(
-                "card",
-                Some(card_path.display().to_string()),
-                ConfigValueSource::Argument("--card"),
-                true,
+                "either --text, --card or --attach is required unless --print-required-args"
             ));
         }
-        let mut arg_entries = args.iter().collect::<Vec<_>>();
-        arg_entries.sort_by(|(a, _), (b, _)| a.cmp(b));
-        for (key, value) in arg_entries {
-            config_items.push(ConfigGateItem::new(
-                key.as_str(),
-                Some(config_value_display(value)),
-                ConfigValueSource::Argument("--arg"),
-                true,
-            ));
+        let request = api::SendMessage {
+            bundle: self.bundle.clone(),
+            provider: self.provider.clone(),
+            tenant: self.tenant.clone(),
+            team: team.map(|value| value.to_string()),
+            text: self.text.clone(),
+            args: merge_args(self.args_json.as_deref(), &self.args)?,
+            to: self.to.clone(),
+            to_kind: self.to_kind.clone(),
+            card: card_payload,
+            attachments: local_attachments,
+            runner_binary: self.runner_binary.clone(),
+            env: self.env.clone(),
+            http: Some(self.http_config()),
+        };
+        if self.dry_run {
+            return self.run_dry_run(request);
         }
-        if !self.to.is_empty() {
-            config_items.push(ConfigGateItem::new(
-                "to",
-                Some(self.to.join(",")),
-                ConfigValueSource::Argument("--to"),
-                true,
-            ));
+        if self.at.is_some() || self.every.is_some() {
+            return self.schedule(request);
         }
-        if let Some(kind) = self.to_kind.as_ref() {
-            config_items.push(ConfigGateItem::new(
-                "to-kind",
-                Some(kind.clone()),
-                ConfigValueSource::Argument("--to-kind"),
-                false,
-            ));
+        if self.to.len() > 1 {
+            return self.run_fanout(request);
         }
-        config_gate::log_config_gate(Domain::Messaging, &self.tenant, team, &env, &config_items);
-        let channel = provider_channel(&self.provider);
-        let message = build_demo_send_message(DemoSendMessageArgs {
-            text: text_ref,
-            args: &args,
-            tenant: &self.tenant,
-            team,
-            destinations: &self.to,
-            to_kind: self.to_kind.as_deref(),
-            provider_id: &self.provider,
-            channel: &channel,
-            card: card_payload.as_ref(),
-        });
-        debug_print_envelope("initial message", &message);
-
-        // Compose a message plan and encode payload directly against the provider component (no flow resolution).
-        let render_plan_input = egress::build_render_plan_input(message.clone());
-        let render_plan_input_value = serde_json::to_value(&render_plan_input)?;
-        let plan_value = run_provider_component_op_json(
-            &runner_host,
-            &pack,
-            &provider_id,
-            &context,
-            "render_plan",
-            render_plan_input_value.clone(),
-        )
-        .with_context(|| "render_plan failed")?;
-        let render_plan_out: RenderPlanOutV1 =
-            serde_json::from_value(plan_value.clone()).context("render_plan output invalid")?;
-        debug_print_render_plan_output(&render_plan_out);
-        if !render_plan_out.ok {
-            let err = render_plan_out
-                .error
-                .unwrap_or_else(|| "render_plan returned error".to_string());
-            return Err(anyhow::anyhow!(err));
+        let sent = match api::send_message(request) {
+            Ok(sent) => sent,
+            Err(err) if self.failure.best_effort() => {
+                report_best_effort_failure("send", &format!("{err:#}"));
+                return Ok(());
+            }
+            Err(err) => {
+                self.offer_troubleshooting(team, &format!("{err:#}"));
+                return Err(err);
+            }
+        };
+        for view in &sent.card_views {
+            print_card_summary(view);
         }
-        let encode_input = egress::build_encode_input(message.clone(), plan_value.clone());
-        debug_print_encode_input(&encode_input);
-        let payload_value = run_provider_component_op_json(
-            &runner_host,
-            &pack,
-            &provider_id,
-            &context,
-            "encode",
-            serde_json::to_value(&encode_input)?,
-        )
-        .with_context(|| "encode failed")?;
-        let encode_out: EncodeOutV1 =
-            serde_json::from_value(payload_value).context("encode output invalid")?;
-        debug_print_encode_output(&encode_out);
-        if !encode_out.ok {
-            let err = encode_out
-                .error
-                .unwrap_or_else(|| "encode returned error".to_string());
-            return Err(anyhow::anyhow!(err));
-        }
-        let payload = encode_out
-            .payload
-            .ok_or_else(|| anyhow::anyhow!("encode output missing payload"))?;
-        let send_input = egress::build_send_payload(
-            payload,
-            provider_type.clone(),
-            self.tenant.clone(),
-            team.map(|value| value.to_string()),
-        );
-        let send_value = serde_json::to_value(&send_input)?;
-        let send_outcome = run_provider_component_op(
-            &runner_host,
-            &pack,
-            &provider_id,
-            &context,
-            "send_payload",
-            send_value,
-        )
-        .context("send_payload failed")?;
         println!("{}", operator_i18n::tr("cli.common.ok", "ok"));
-        let status = if send_outcome.success {
+        let status = if sent.success {
             operator_i18n::tr("cli.common.success", "success")
         } else {
             operator_i18n::tr("cli.common.failed", "failed")
@@ -4480,17 +10366,17 @@ impl DemoSendArgs {
             "{}",
             operator_i18n::trf("cli.demo_send.flow_result", "Flow result: {}", &[&status])
         );
-        if let Some(error) = &send_outcome.error {
+        if let Some(error) = &sent.error {
             println!(
                 "{}",
                 operator_i18n::trf("cli.demo_send.flow_error", "Flow error: {}", &[error])
             );
         }
-        if let Some(value) = send_outcome.output {
+        if let Some(value) = &sent.output {
             if let Ok(parsed) = serde_json::from_value::<SendPayloadOutV1>(value.clone()) {
                 debug_print_send_payload_output(&parsed);
             } else if demo_debug_enabled() {
-                if let Ok(body) = serde_json::to_string_pretty(&value) {
+                if let Ok(body) = redaction::to_string_pretty(value) {
                     println!(
                         "{}",
                         operator_i18n::trf(
@@ -4509,142 +10395,524 @@ impl DemoSendArgs {
                     );
                 }
             }
-            let missing_uris = if payload_contains_secret_error(&value) {
-                gather_missing_secret_uris(
-                    &secrets_handle.manager(),
-                    &env,
-                    &self.tenant,
-                    team,
-                    &pack.path,
-                    &provider_id,
-                    secrets_handle.dev_store_path.as_deref(),
-                    secrets_handle.using_env_fallback,
-                    Some(provider_type.as_str()),
-                )
-            } else {
-                Vec::new()
-            };
-            if !missing_uris.is_empty() {
+            if !sent.missing_secret_uris.is_empty() {
                 println!(
                     "{}",
                     operator_i18n::trf(
                         "cli.demo_send.missing_secret_uris",
                         "missing secret URIs:\n{}",
-                        &[&missing_uris
+                        &[&sent
+                            .missing_secret_uris
                             .iter()
                             .map(|uri| format!("  - {uri}"))
                             .collect::<Vec<_>>()
                             .join("\n")]
                     )
                 );
-                for uri in &missing_uris {
+                for uri in &sent.missing_secret_uris {
                     print_secret_missing_details(
                         uri,
-                        secrets_handle.dev_store_path.as_deref(),
-                        secrets_handle.using_env_fallback,
+                        sent.secrets_store.as_deref(),
+                        sent.using_env_fallback,
                         &self.bundle,
                     );
                 }
             }
-            let enriched = enrich_secret_error_payload(
-                value,
-                &context,
-                &env,
-                &provider_id,
-                &pack.pack_id,
-                &pack.path,
-                &missing_uris,
-                &secrets_handle.selection,
-                secrets_handle.dev_store_path.as_deref(),
+            let json = redaction::to_string_pretty(value)?;
+            println!("{json}");
+            // The provider answered, but without its secrets it cannot have delivered.
+            if let Some(uri) = sent.missing_secret_uris.first() {
+                let err = OperatorError::SecretMissing { uri: uri.clone() };
+                if !self.failure.best_effort() {
+                    self.offer_troubleshooting(team, &err.to_string());
+                    return Err(err.into());
+                }
+                report_best_effort_failure("send", &err.to_string());
+            }
+        } else if let Some(raw) = &sent.raw {
+            println!("{raw}");
+        }
+        if !sent.success {
+            let error = sent
+                .error
+                .clone()
+                .unwrap_or_else(|| operator_i18n::tr("cli.common.failed", "failed"));
+            self.offer_troubleshooting(team, &error);
+        }
+        Ok(())
+    }
+}
+
+/// A send's provider pack with a runner host for invoking its ops directly.
+struct DemoSendProvider {
+    domain: Domain,
+    pack: domains::ProviderPack,
+    provider_id: String,
+    runner_host: DemoRunnerHost,
+    secrets: SecretsManagerHandle,
+}
+
+impl DemoSendArgs {
+    fn provider_ops(&self, team: Option<&str>, domain: Domain) -> anyhow::Result<DemoSendProvider> {
+        domains::ensure_cbor_packs(&self.bundle)?;
+        let pack =
+            resolve_demo_provider_pack(&self.bundle, &self.tenant, team, &self.provider, domain)?;
+        let discovery = discovery::discover_with_options(
+            &self.bundle,
+            discovery::DiscoveryOptions { cbor_only: true },
+        )?;
+        let provider_map = discovery::discovery_map(&discovery.providers);
+        let provider_id =
+            discovery::provider_id_for_pack(&pack.path, &pack.pack_id, Some(&provider_map));
+        let secrets = secrets_gate::resolve_secrets_manager(&self.bundle, &self.tenant, team)?;
+        let runner_host = DemoRunnerHost::new(
+            self.bundle.clone(),
+            &discovery,
+            self.runner_binary.clone(),
+            secrets.clone(),
+            false,
+        )?;
+        Ok(DemoSendProvider {
+            domain,
+            pack,
+            provider_id,
+            runner_host,
+            secrets,
+        })
+    }
+
+    fn invoke_requirements(
+        &self,
+        provider: &DemoSendProvider,
+        team: Option<&str>,
+    ) -> anyhow::Result<FlowOutcome> {
+        let context = OperatorContext {
+            tenant: self.tenant.clone(),
+            team: team.map(|value| value.to_string()),
+            correlation_id: None,
+        };
+        let input = build_input_payload(
+            &self.bundle,
+            provider.domain,
+            &self.tenant,
+            team,
+            Some(&provider.pack.pack_id),
+            None,
+            None,
+            &self.env,
+        );
+        let input_bytes = serde_json::to_vec(&input)?;
+        provider.runner_host.invoke_provider_op(
+            provider.domain,
+            &provider.provider_id,
+            "requirements",
+            &input_bytes,
+            &context,
+        )
+    }
+
+    /// After a failed send on a terminal, offers to check the provider's secrets,
+    /// requirements op, tunnel and dead letters and name the likely cause.
+    fn offer_troubleshooting(&self, team: Option<&str>, send_error: &str) {
+        if !(io::stdin().is_terminal() && io::stdout().is_terminal()) {
+            return;
+        }
+        let prompt = operator_i18n::tr(
+            "cli.demo_troubleshoot.prompt",
+            "The send failed. Diagnose the provider setup? [Y, n]",
+        );
+        if !prompt_yes_no(&prompt, true).unwrap_or(false) {
+            return;
+        }
+        let provider = match self.provider_ops(team, Domain::Messaging) {
+            Ok(provider) => provider,
+            Err(err) => {
+                println!(
+                    "{}",
+                    operator_i18n::trf(
+                        "cli.demo_troubleshoot.unavailable",
+                        "Cannot diagnose {}: {}",
+                        &[&self.provider, &format!("{err:#}")]
+                    )
+                );
+                return;
+            }
+        };
+        let mut checks = Vec::new();
+        let mut report = |check: demo::troubleshoot::Check| {
+            println!("{}", check.render());
+            checks.push(check);
+        };
+        report(demo::troubleshoot::secrets_check(
+            secrets_gate::check_provider_secrets(
+                &provider.secrets.manager(),
+                &self.env,
+                &self.tenant,
+                team,
+                &provider.pack.path,
+                &provider.provider_id,
+                None,
+                provider.secrets.dev_store_path.as_deref(),
+                provider.secrets.using_env_fallback,
+            ),
+        ));
+        report(demo::troubleshoot::requirements_check(
+            ensure_requirements_flow(&provider.pack)
+                .ok()
+                .map(|()| self.invoke_requirements(&provider, team)),
+        ));
+        let paths = RuntimePaths::new(
+            self.bundle.join("state"),
+            &self.tenant,
+            team.unwrap_or("default"),
+        );
+        let public_url = crate::cloudflared::current_public_url(&paths)
+            .ok()
+            .flatten();
+        report(demo::troubleshoot::tunnel_check(public_url.as_deref()));
+        report(demo::troubleshoot::dead_letter_check(
+            &self.bundle.join("state"),
+            &provider.provider_id,
+        ));
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.demo_troubleshoot.likely_cause",
+                "Most likely cause: {}",
+                &[&demo::troubleshoot::likely_cause(&checks, send_error)]
+            )
+        );
+    }
+}
+
+impl DemoSendArgs {
+    /// `--domain events`: publishes one event, with --text and the args as its
+    /// payload, through the provider's `publish` op.
+    fn run_events(&self, team: Option<&str>) -> anyhow::Result<()> {
+        let unsupported = [
+            ("--card", self.card.is_some()),
+            ("--attach", !self.attach.is_empty()),
+            ("--to", !self.to.is_empty()),
+            ("--to-kind", self.to_kind.is_some()),
+            ("--at", self.at.is_some()),
+            ("--every", self.every.is_some()),
+            ("--dry-run", self.dry_run),
+            ("--transport", self.transport.is_some()),
+            ("--record", self.record.is_some()),
+            ("--replay", self.replay.is_some()),
+        ];
+        if let Some((flag, _)) = unsupported.iter().find(|(_, set)| *set) {
+            return Err(anyhow!("{flag} is not supported with --domain events"));
+        }
+        let mut payload = merge_args(self.args_json.as_deref(), &self.args)?;
+        if let Some(text) = &self.text {
+            payload.insert("text".to_string(), json!(text));
+        }
+        let provider = self.provider_ops(team, Domain::Events)?;
+        let context = OperatorContext {
+            tenant: self.tenant.clone(),
+            team: team.map(str::to_string),
+            correlation_id: None,
+        };
+        let event = demo::event_inject::synthetic_event(
+            &provider.provider_id,
+            &self.event_type,
+            JsonValue::Object(payload),
+            &context,
+        );
+        let outcome = demo::event_inject::publish(
+            &provider.runner_host,
+            &provider.provider_id,
+            &event,
+            &context,
+        )
+        .and_then(|outcome| {
+            if outcome.success {
+                return Ok(outcome);
+            }
+            Err(OperatorError::FlowFailed {
+                provider: provider.provider_id.clone(),
+                op: demo::event_inject::PUBLISH_OP.to_string(),
+                detail: outcome
+                    .error
+                    .or(outcome.raw)
+                    .unwrap_or_else(|| operator_i18n::tr("cli.common.failed", "failed")),
+            }
+            .into())
+        });
+        let outcome = match outcome {
+            Ok(outcome) => outcome,
+            Err(err) if self.failure.best_effort() => {
+                report_best_effort_failure("send", &format!("{err:#}"));
+                return Ok(());
+            }
+            Err(err) => return Err(err),
+        };
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.demo_send.event_published",
+                "Published event {} ({}) through {}",
+                &[&event.event_id, &event.event_type, &provider.provider_id]
+            )
+        );
+        if let Some(value) = &outcome.output {
+            println!("{}", redaction::to_string_pretty(value)?);
+        } else if let Some(raw) = &outcome.raw {
+            println!("{raw}");
+        }
+        Ok(())
+    }
+
+    /// The bundle's `http:` settings with the transport flags applied.
+    fn http_config(&self) -> config::DemoHttpConfig {
+        let mut http = http_transport::bundle_http_config(&self.bundle);
+        if let Some(transport) = self.transport {
+            http.transport = transport.into();
+        }
+        if let Some(proxy) = &self.proxy {
+            http.proxy = Some(proxy.clone());
+        }
+        if let Some(ca_cert) = &self.ca_cert {
+            http.ca_cert = Some(ca_cert.clone());
+        }
+        http.insecure_tls |= self.insecure_tls;
+        let cassette = match (&self.record, &self.replay) {
+            (Some(path), _) => Some((path, config::CassetteMode::Record)),
+            (None, Some(path)) => Some((path, config::CassetteMode::Replay)),
+            (None, None) => None,
+        };
+        if let Some((path, mode)) = cassette {
+            http.cassette = Some(config::CassetteConfig {
+                path: path.clone(),
+                mode,
+            });
+        }
+        http
+    }
+
+    /// Leaves the send under `state/timers` for the timer scheduler instead of
+    /// sending it now.
+    fn schedule(&self, request: api::SendMessage) -> anyhow::Result<()> {
+        let now = chrono::Utc::now();
+        let every_seconds = self
+            .every
+            .as_deref()
+            .map(scheduled_send::parse_every)
+            .transpose()?;
+        let next_at = match (self.at.as_deref(), every_seconds) {
+            (Some(at), _) => {
+                let at = scheduled_send::parse_at(at)?;
+                if at <= now {
+                    return Err(anyhow!("--at {} is in the past", at.to_rfc3339()));
+                }
+                at
+            }
+            (None, Some(every)) => {
+                now + chrono::Duration::seconds(i64::try_from(every).unwrap_or(i64::MAX))
+            }
+            (None, None) => now,
+        };
+        if self.count == Some(0) {
+            return Err(anyhow!("--count must be at least 1"));
+        }
+        let attachments = self
+            .attach
+            .iter()
+            .map(|path| {
+                path.canonicalize()
+                    .with_context(|| format!("resolve attachment {}", path.display()))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let send = scheduled_send::ScheduledSend {
+            id: uuid::Uuid::new_v4().simple().to_string()[..12].to_string(),
+            provider: request.provider,
+            tenant: request.tenant,
+            team: request.team.unwrap_or_else(|| "default".to_string()),
+            env: request.env,
+            text: request.text,
+            args: request.args,
+            to: request.to,
+            to_kind: request.to_kind,
+            card: request.card,
+            attachments,
+            max_attachment_bytes: self.max_attachment_bytes,
+            created_at: now,
+            next_at: Some(next_at),
+            every_seconds,
+            remaining: if every_seconds.is_some() {
+                self.count
+            } else {
+                Some(1)
+            },
+            runs: 0,
+            last_run: None,
+            last_error: None,
+        };
+        scheduled_send::save(&self.bundle.join("state"), &send)?;
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.demo_send.scheduled",
+                "Scheduled send {} for {}; it runs while `demo start` runs for {}/{}.",
+                &[&send.id, &next_at.to_rfc3339(), &send.tenant, &send.team]
+            )
+        );
+        Ok(())
+    }
+
+    /// Previews what would be sent, once per `--to` destination.
+    fn run_dry_run(&self, request: api::SendMessage) -> anyhow::Result<()> {
+        let requests = if request.to.len() > 1 {
+            request
+                .to
+                .iter()
+                .map(|destination| {
+                    let mut one = request.clone();
+                    one.to = vec![destination.clone()];
+                    one
+                })
+                .collect()
+        } else {
+            vec![request]
+        };
+        for request in requests {
+            if let Some(destination) = request.to.first() {
+                println!(
+                    "{}",
+                    operator_i18n::trf(
+                        "cli.demo_send.dry_run_destination",
+                        "== {} ==",
+                        &[destination]
+                    )
+                );
+            }
+            let preview = match api::preview_message(request) {
+                Ok(preview) => preview,
+                Err(err) if self.failure.best_effort() => {
+                    report_best_effort_failure("send --dry-run", &format!("{err:#}"));
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            preview.card_views.iter().for_each(print_card_summary);
+            println!(
+                "{}",
+                operator_i18n::trf(
+                    "cli.demo_send.dry_run_payload",
+                    "Encoded payload ({}):",
+                    &[&preview.provider_id]
+                )
+            );
+            println!("{}", redaction::to_string_pretty(&preview.payload)?);
+            let request = &preview.request;
+            println!(
+                "{}",
+                operator_i18n::trf(
+                    "cli.demo_send.dry_run_request",
+                    "HTTP request: {} {}",
+                    &[
+                        &request.method,
+                        &request
+                            .url
+                            .as_deref()
+                            .map(redaction::redact_text)
+                            .unwrap_or_else(|| operator_i18n::tr(
+                                "cli.demo_send.dry_run_url_unknown",
+                                "<url chosen by send_payload>"
+                            )),
+                    ]
+                )
+            );
+            println!(
+                "{}",
+                redaction::to_string_pretty(&json!({
+                    "headers": request.headers,
+                    "body": request.body,
+                }))?
             );
-            let json = serde_json::to_string_pretty(&enriched)?;
-            println!("{json}");
-        } else if let Some(raw) = send_outcome.raw {
-            println!("{raw}");
         }
+        println!(
+            "{}",
+            operator_i18n::tr(
+                "cli.demo_send.dry_run_done",
+                "Dry run: send_payload was not called."
+            )
+        );
         Ok(())
     }
-}
-
-fn run_provider_component_op(
-    runner_host: &DemoRunnerHost,
-    pack: &domains::ProviderPack,
-    provider_id: &str,
-    ctx: &OperatorContext,
-    op: &str,
-    payload: serde_json::Value,
-) -> anyhow::Result<FlowOutcome> {
-    let bytes = serde_json::to_vec(&payload)?;
-    let outcome = runner_host.invoke_provider_component_op_direct(
-        Domain::Messaging,
-        pack,
-        provider_id,
-        op,
-        &bytes,
-        ctx,
-    )?;
-    ensure_provider_op_success(provider_id, op, &outcome)?;
-    if let Some(value) = &outcome.output
-        && let Some(card) = detect_adaptive_card_view(value)
-    {
-        print_card_summary(&card);
-    }
-    Ok(outcome)
-}
-
-fn run_provider_component_op_json(
-    runner_host: &DemoRunnerHost,
-    pack: &domains::ProviderPack,
-    provider_id: &str,
-    ctx: &OperatorContext,
-    op: &str,
-    payload: serde_json::Value,
-) -> anyhow::Result<serde_json::Value> {
-    let outcome = run_provider_component_op(runner_host, pack, provider_id, ctx, op, payload)?;
-    Ok(outcome.output.unwrap_or_else(|| json!({})))
-}
 
-#[allow(clippy::too_many_arguments)]
-fn enrich_secret_error_payload(
-    mut payload: serde_json::Value,
-    ctx: &OperatorContext,
-    env: &str,
-    provider_id: &str,
-    pack_id: &str,
-    pack_path: &Path,
-    missing_uris: &[String],
-    selection: &secrets_manager::SecretsManagerSelection,
-    dev_store_path: Option<&Path>,
-) -> serde_json::Value {
-    let team = secrets_manager::canonical_team(ctx.team.as_deref()).to_string();
-    let selection_desc = selection.description();
-    let dev_store_desc = dev_store_path
-        .map(|path| path.display().to_string())
-        .unwrap_or_else(|| "<default>".to_string());
-    let context_suffix = format!(
-        "env={} tenant={} team={} provider={} pack_id={} pack_path={} secrets_manager={} dev_store={}",
-        env,
-        ctx.tenant,
-        team,
-        provider_id,
-        pack_id,
-        pack_path.display(),
-        selection_desc,
-        dev_store_desc
-    );
-    if let serde_json::Value::Object(map) = &mut payload {
-        for key in ["message", "error"] {
-            if let Some(entry) = map.get_mut(key)
-                && let Some(text) = entry.as_str()
-                && text_contains_secret_error(text)
+    /// One send per destination, one outcome line each.
+    fn run_fanout(&self, request: api::SendMessage) -> anyhow::Result<()> {
+        let total = request.to.len();
+        let deliveries = api::send_fanout(request, self.fanout.into(), !self.failure.best_effort());
+        let mut card_printed = false;
+        for delivery in &deliveries {
+            if let Ok(sent) = &delivery.result
+                && !card_printed
             {
-                let suffix = secret_error_suffix(&context_suffix, missing_uris);
-                let enriched = format!("{text} ({suffix})");
-                *entry = serde_json::Value::String(enriched);
+                sent.card_views.iter().for_each(print_card_summary);
+                card_printed = !sent.card_views.is_empty();
             }
+            let line = match &delivery.result {
+                Ok(_) if delivery.delivered() => {
+                    operator_i18n::trf("cli.demo_send.fanout_ok", "✓ {}", &[&delivery.destination])
+                }
+                Ok(sent) => {
+                    let detail = match sent.missing_secret_uris.first() {
+                        Some(uri) => OperatorError::SecretMissing { uri: uri.clone() }.to_string(),
+                        None => sent
+                            .error
+                            .clone()
+                            .unwrap_or_else(|| operator_i18n::tr("cli.common.failed", "failed")),
+                    };
+                    operator_i18n::trf(
+                        "cli.demo_send.fanout_failed",
+                        "✗ {}: {}",
+                        &[&delivery.destination, &detail],
+                    )
+                }
+                Err(err) => operator_i18n::trf(
+                    "cli.demo_send.fanout_failed",
+                    "✗ {}: {}",
+                    &[&delivery.destination, &format!("{err:#}")],
+                ),
+            };
+            println!("{line}");
+        }
+        let delivered = deliveries
+            .iter()
+            .filter(|delivery| delivery.delivered())
+            .count();
+        let skipped = total - deliveries.len();
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.demo_send.fanout_summary",
+                "Sent to {} of {} destination(s)",
+                &[&delivered.to_string(), &total.to_string()]
+            )
+        );
+        if skipped > 0 {
+            println!(
+                "{}",
+                operator_i18n::trf(
+                    "cli.demo_send.fanout_skipped",
+                    "Skipped {} destination(s) after the first failure; pass --best-effort to try them all.",
+                    &[&skipped.to_string()]
+                )
+            );
         }
+        if delivered == total {
+            return Ok(());
+        }
+        let detail = format!("{} of {total} destination(s) failed", total - delivered);
+        if self.failure.best_effort() {
+            report_best_effort_failure("send", &detail);
+            return Ok(());
+        }
+        Err(anyhow::anyhow!(detail))
     }
-    payload
 }
 
 fn print_secret_missing_details(
@@ -4687,85 +10955,6 @@ fn print_secret_missing_details(
     );
 }
 
-fn payload_contains_secret_error(value: &JsonValue) -> bool {
-    for key in ["message", "error"] {
-        if let Some(text) = value.get(key).and_then(JsonValue::as_str)
-            && text_contains_secret_error(text)
-        {
-            return true;
-        }
-    }
-    false
-}
-
-fn text_contains_secret_error(text: &str) -> bool {
-    let lower = text.to_lowercase();
-    lower.contains("secret store error") || text.contains("SecretsError")
-}
-
-fn secret_error_suffix(context_suffix: &str, missing_uris: &[String]) -> String {
-    if missing_uris.is_empty() {
-        context_suffix.to_string()
-    } else {
-        let missing = missing_uris.join(", ");
-        format!("{context_suffix}; missing secrets: {missing}")
-    }
-}
-
-#[allow(clippy::too_many_arguments)]
-fn gather_missing_secret_uris(
-    manager: &DynSecretsManager,
-    env: &str,
-    tenant: &str,
-    team: Option<&str>,
-    pack_path: &Path,
-    provider_id: &str,
-    store_path: Option<&Path>,
-    using_env_fallback: bool,
-    provider_type: Option<&str>,
-) -> Vec<String> {
-    match secrets_gate::check_provider_secrets(
-        manager,
-        env,
-        tenant,
-        team,
-        pack_path,
-        provider_id,
-        provider_type,
-        store_path,
-        using_env_fallback,
-    ) {
-        Ok(Some(missing)) => missing,
-        Ok(None) => Vec::new(),
-        Err(err) => {
-            operator_log::warn(
-                module_path!(),
-                format!(
-                    "failed to check missing secrets for provider {}: {}",
-                    provider_id, err
-                ),
-            );
-            Vec::new()
-        }
-    }
-}
-
-fn ensure_provider_op_success(
-    provider_id: &str,
-    op: &str,
-    outcome: &FlowOutcome,
-) -> anyhow::Result<()> {
-    if outcome.success {
-        return Ok(());
-    }
-    let message = outcome
-        .error
-        .clone()
-        .or_else(|| outcome.raw.clone())
-        .unwrap_or_else(|| "unknown error".to_string());
-    Err(anyhow::anyhow!("{provider_id}.{op} failed: {message}"))
-}
-
 fn print_capability_outcome(outcome: &FlowOutcome) -> anyhow::Result<()> {
     println!(
         "{}",
@@ -4798,7 +10987,7 @@ fn print_capability_outcome(outcome: &FlowOutcome) -> anyhow::Result<()> {
 #[derive(Parser)]
 #[command(
     about = "Send a synthetic HTTP request through the messaging ingress pipeline.",
-    long_about = "Constructs an HttpInV1 payload, invokes the provider's ingest_http op, and optionally runs the resulting events through the app/outbound flow."
+    long_about = "Constructs an HttpInV1 payload, invokes the provider's ingest_http op, and optionally runs the resulting events through the app/outbound flow. With --domain events the request goes to an events provider, and --event-type injects an event without calling ingest_http."
 )]
 struct DemoIngressArgs {
     #[arg(long)]
@@ -4843,6 +11032,42 @@ struct DemoIngressArgs {
     dry_run: bool,
     #[arg(long)]
     correlation_id: Option<String>,
+    /// YAML file of requests to run in order, each checked against its `expect` block.
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["path", "body", "body_json", "body_raw"]
+    )]
+    scenario: Option<PathBuf>,
+    /// Scenario template variable (`name=value`); overrides the file's `vars`.
+    #[arg(long = "var", value_name = "NAME=VALUE", requires = "scenario")]
+    vars: Vec<String>,
+    /// Send a realistic `<platform>:<event>` webhook (telegram, slack, whatsapp, msgraph).
+    #[arg(
+        long,
+        value_name = "PLATFORM:EVENT",
+        conflicts_with_all = ["body", "body_json", "body_raw", "scenario"]
+    )]
+    emulate: Option<emulate::EmulateSpec>,
+    /// Message text for `--emulate`.
+    #[arg(long, requires = "emulate")]
+    text: Option<String>,
+    /// Sender for `--emulate` (user name, id, or phone number).
+    #[arg(long, requires = "emulate")]
+    from: Option<String>,
+    /// Chat, channel, or phone number id for `--emulate`.
+    #[arg(long, requires = "emulate")]
+    chat: Option<String>,
+    /// Provider domain: messaging, or events to ingest through an events provider.
+    #[arg(long, default_value = "messaging")]
+    domain: DomainArg,
+    /// Inject an event of this type (`--domain events`) instead of calling ingest_http; the body is its payload.
+    #[arg(
+        long,
+        value_name = "TYPE",
+        conflicts_with_all = ["scenario", "emulate", "path", "headers", "queries"]
+    )]
+    event_type: Option<String>,
 }
 
 #[derive(Clone, Copy, Debug, ValueEnum)]
@@ -4883,29 +11108,84 @@ impl DemoIngressPrintMode {
 
 impl DemoIngressArgs {
     fn run(self) -> anyhow::Result<()> {
+        let domain = match self.domain.resolve(&self.bundle)? {
+            domain @ (Domain::Messaging | Domain::Events) => domain,
+            other => {
+                return Err(anyhow!(
+                    "demo ingress supports --domain messaging or events, not {}",
+                    domains::domain_name(other)
+                ));
+            }
+        };
+        if domain == Domain::Events {
+            if self.scenario.is_some() || self.emulate.is_some() {
+                return Err(anyhow!(
+                    "--scenario and --emulate are not supported with --domain events"
+                ));
+            }
+        } else if self.event_type.is_some() {
+            return Err(anyhow!("--event-type requires --domain events"));
+        }
+        if let Some(scenario) = self.scenario.clone() {
+            return self.run_scenario(&scenario);
+        }
         ensure_single_body_field(&self)?;
-        let body_bytes = resolve_ingress_body(
-            self.body.as_deref(),
-            self.body_json.as_deref(),
-            self.body_raw.as_deref(),
-        )?;
+        if let Some(event_type) = &self.event_type {
+            return self.inject_event(event_type);
+        }
+        let (context, secrets_handle) = self.ingress_context()?;
+        let mut method = self.method.as_str();
+        let mut headers = Vec::new();
+        let mut queries = Vec::new();
+        let body_bytes = if let Some(spec) = &self.emulate {
+            let emulated = self.emulate_request(spec, &context, &secrets_handle)?;
+            method = emulated.method;
+            headers = emulated.headers;
+            queries = emulated.query;
+            emulated.body
+        } else {
+            resolve_ingress_body(
+                self.body.as_deref(),
+                self.body_json.as_deref(),
+                self.body_raw.as_deref(),
+            )?
+        };
         let path = self
             .path
             .clone()
             .unwrap_or_else(|| default_ingress_path(&self.provider, self.binding_id.as_deref()));
-        let headers = parse_header_pairs(&self.headers)?;
-        let queries = parse_query_pairs(&self.queries)?;
+        // Explicit --header/--query values come last so they win over emulated ones.
+        headers.extend(parse_header_pairs(&self.headers)?);
+        queries.extend(parse_query_pairs(&self.queries)?);
         let route = derive_route_from_path(&path);
         let full_path = if path.starts_with('/') {
             path.clone()
         } else {
             format!("/{path}")
         };
+        if domain == Domain::Events {
+            let request = demo::ingress_types::IngressRequestV1 {
+                v: 1,
+                domain: "events".to_string(),
+                provider: self.provider.clone(),
+                handler: route,
+                tenant: self.tenant.clone(),
+                team: context.team.clone(),
+                method: method.to_string(),
+                path: full_path,
+                query: queries,
+                headers,
+                body: body_bytes,
+                correlation_id: context.correlation_id.clone(),
+                remote_addr: None,
+            };
+            return self.ingest_events(&request, &context, secrets_handle);
+        }
 
         let request = crate::messaging_universal::ingress::build_ingress_request(
             &self.provider,
             route,
-            self.method.as_str(),
+            method,
             &full_path,
             headers,
             queries,
@@ -4915,22 +11195,6 @@ impl DemoIngressArgs {
             Some(self.team.clone()),
         );
 
-        let team_context = if self.team.is_empty() {
-            None
-        } else {
-            Some(self.team.clone())
-        };
-        let context = OperatorContext {
-            tenant: self.tenant.clone(),
-            team: team_context,
-            correlation_id: self.correlation_id.clone(),
-        };
-        let secrets_handle = secrets_gate::resolve_secrets_manager(
-            &self.bundle,
-            &self.tenant,
-            context.team.as_deref(),
-        )?;
-
         let (response, events) = crate::messaging_universal::ingress::run_ingress(
             &self.bundle,
             &self.provider,
@@ -4957,21 +11221,314 @@ impl DemoIngressArgs {
                 self.app_pack.clone(),
                 self.send,
                 self.dry_run,
-                self.retries.unwrap_or(0),
+                self.retries,
                 secrets_handle.clone(),
             )?;
         }
 
         if self.dlq_tail {
-            let paths = RuntimePaths::new(self.bundle.join("state"), &self.tenant, &self.team);
+            self.print_dlq_location();
+        }
+        Ok(())
+    }
+
+    fn operator_context(&self) -> OperatorContext {
+        let team_context = if self.team.is_empty() {
+            None
+        } else {
+            Some(self.team.clone())
+        };
+        OperatorContext {
+            tenant: self.tenant.clone(),
+            team: team_context,
+            correlation_id: self.correlation_id.clone(),
+        }
+    }
+
+    fn ingress_context(&self) -> anyhow::Result<(OperatorContext, SecretsManagerHandle)> {
+        let context = self.operator_context();
+        let secrets_handle = secrets_gate::resolve_secrets_manager(
+            &self.bundle,
+            &self.tenant,
+            context.team.as_deref(),
+        )?;
+        Ok((context, secrets_handle))
+    }
+
+    /// Builds the `--emulate` request, signing it with the provider's webhook secrets
+    /// when they are in the store.
+    fn emulate_request(
+        &self,
+        spec: &emulate::EmulateSpec,
+        context: &OperatorContext,
+        secrets_handle: &SecretsManagerHandle,
+    ) -> anyhow::Result<emulate::EmulatedRequest> {
+        let env = resolve_env(None);
+        let provider_ids = [
+            self.provider.clone(),
+            format!("messaging-{}", spec.platform),
+        ];
+        let input = emulate::EmulateInput {
+            text: self.text.clone(),
+            from: self.from.clone(),
+            chat: self.chat.clone(),
+            binding_id: self.binding_id.clone(),
+        };
+        emulate::emulate(spec, &input, |key| {
+            provider_ids.iter().find_map(|provider_id| {
+                secrets_gate::read_provider_secret(
+                    secrets_handle,
+                    &env,
+                    &self.tenant,
+                    context.team.as_deref(),
+                    provider_id,
+                    key,
+                )
+                .unwrap_or_else(|err| {
+                    operator_log::warn(
+                        module_path!(),
+                        format!("emulate: {key} for {provider_id} unreadable: {err:#}"),
+                    );
+                    None
+                })
+            })
+        })
+    }
+
+    /// `--domain events`: runs the request through the events provider's ingest_http
+    /// and delivers the events it parses.
+    fn ingest_events(
+        &self,
+        request: &demo::ingress_types::IngressRequestV1,
+        context: &OperatorContext,
+        secrets_handle: SecretsManagerHandle,
+    ) -> anyhow::Result<()> {
+        let discovery = discovery::discover_with_options(
+            &self.bundle,
+            discovery::DiscoveryOptions { cbor_only: true },
+        )?;
+        let runner_host = DemoRunnerHost::new(
+            self.bundle.clone(),
+            &discovery,
+            self.runner_binary.clone(),
+            secrets_handle,
+            false,
+        )?;
+        let result = demo::ingress_dispatch::dispatch_http_ingress(
+            &runner_host,
+            Domain::Events,
+            request,
+            context,
+        )
+        .map_err(|err| OperatorError::FlowFailed {
+            provider: self.provider.clone(),
+            op: "ingest_http".to_string(),
+            detail: format!("{err:#}"),
+        })?;
+        if self.print.should_print_http() {
+            print_http_response(&crate::messaging_universal::dto::HttpOutV1 {
+                v: 1,
+                status: result.response.status,
+                headers: result.response.headers.clone(),
+                body_b64: result
+                    .response
+                    .body
+                    .as_ref()
+                    .map(|body| base64::engine::general_purpose::STANDARD.encode(body)),
+                events: Vec::new(),
+            })?;
+        }
+        self.deliver_events(&result.events, context)
+    }
+
+    /// `--event-type`: injects one event built from the body, as if the provider had
+    /// parsed it from a webhook.
+    fn inject_event(&self, event_type: &str) -> anyhow::Result<()> {
+        let body = resolve_ingress_body(
+            self.body.as_deref(),
+            self.body_json.as_deref(),
+            self.body_raw.as_deref(),
+        )?;
+        let payload = if body.is_empty() {
+            json!({})
+        } else {
+            serde_json::from_slice(&body).context("the event payload must be JSON")?
+        };
+        let context = self.operator_context();
+        let event =
+            demo::event_inject::synthetic_event(&self.provider, event_type, payload, &context);
+        self.deliver_events(&[event], &context)
+    }
+
+    /// Prints `events`, puts them on the bus and, with --end-to-end, runs them through
+    /// the app pack's default flow.
+    fn deliver_events(
+        &self,
+        events: &[demo::ingress_types::EventEnvelopeV1],
+        context: &OperatorContext,
+    ) -> anyhow::Result<()> {
+        if self.print.should_print_events() {
+            print_envelopes(events)?;
+        }
+        let routed = demo::event_inject::deliver(
+            &self.bundle,
+            &self.provider,
+            events,
+            context,
+            self.end_to_end,
+        )?;
+        if self.end_to_end {
+            println!(
+                "{}",
+                operator_i18n::trf(
+                    "cli.ingress.events_routed",
+                    "Routed {} event(s) through the app pack's default flow",
+                    &[&routed.to_string()]
+                )
+            );
+        }
+        Ok(())
+    }
+
+    fn print_dlq_location(&self) {
+        let dlq_dir = messaging_dlq::dlq_root(&self.bundle.join("state")).join(&self.provider);
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.ingress.dlq_records_location",
+                "DLQ records: {}",
+                &[&dlq_dir.display().to_string()]
+            )
+        );
+    }
+
+    /// Runs every step of `--scenario` in order and stops at the first step whose
+    /// expectations fail.
+    fn run_scenario(&self, path: &Path) -> anyhow::Result<()> {
+        let scenario = scenario::load_scenario(path)?;
+        let overrides = self
+            .vars
+            .iter()
+            .map(|raw| {
+                raw.split_once('=')
+                    .map(|(name, value)| (name.trim().to_string(), value.to_string()))
+                    .filter(|(name, _)| !name.is_empty())
+                    .ok_or_else(|| anyhow::anyhow!("invalid --var '{raw}'; expected 'name=value'"))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let vars = scenario.variables(&overrides);
+        let (context, secrets_handle) = self.ingress_context()?;
+        let total = scenario.steps.len();
+
+        for (index, step) in scenario.steps.iter().enumerate() {
+            let step = step
+                .render(index, &vars)
+                .with_context(|| format!("{}: step {}", path.display(), index + 1))?;
             println!(
                 "{}",
                 operator_i18n::trf(
-                    "cli.ingress.dlq_log_location",
-                    "DLQ log location: {}",
-                    &[&paths.dlq_log_path().display().to_string()]
+                    "cli.ingress.scenario_step",
+                    "[{}/{}] {} {}",
+                    &[
+                        &(index + 1).to_string(),
+                        &total.to_string(),
+                        &step.method,
+                        &step.name
+                    ]
                 )
             );
+            let path = step.path.clone().unwrap_or_else(|| {
+                default_ingress_path(&self.provider, self.binding_id.as_deref())
+            });
+            let full_path = if path.starts_with('/') {
+                path
+            } else {
+                format!("/{path}")
+            };
+            let request = crate::messaging_universal::ingress::build_ingress_request(
+                &self.provider,
+                derive_route_from_path(&full_path),
+                &step.method,
+                &full_path,
+                step.headers.clone(),
+                step.query.clone(),
+                &step.body,
+                self.binding_id.clone(),
+                Some(self.tenant.clone()),
+                Some(self.team.clone()),
+            );
+            let (response, events) = crate::messaging_universal::ingress::run_ingress(
+                &self.bundle,
+                &self.provider,
+                &request,
+                &context,
+                self.runner_binary.clone(),
+                secrets_handle.clone(),
+            )
+            .with_context(|| format!("scenario step '{}'", step.name))?;
+
+            if self.print.should_print_http() {
+                print_http_response(&response)?;
+            }
+            if self.print.should_print_events() {
+                print_envelopes(&events)?;
+            }
+
+            let failures = step.expect.failures(&response, events.len());
+            if !failures.is_empty() {
+                for failure in &failures {
+                    eprintln!(
+                        "{}",
+                        operator_i18n::trf(
+                            "cli.ingress.scenario_expect_failed",
+                            "  FAIL {}",
+                            &[failure]
+                        )
+                    );
+                }
+                return Err(OperatorError::FlowFailed {
+                    provider: self.provider.clone(),
+                    op: "ingest_http".to_string(),
+                    detail: format!(
+                        "scenario step {}/{total} '{}': {}",
+                        index + 1,
+                        step.name,
+                        failures.join("; ")
+                    ),
+                }
+                .into());
+            }
+            println!(
+                "{}",
+                operator_i18n::tr("cli.ingress.scenario_step_passed", "  PASS")
+            );
+
+            if self.end_to_end {
+                crate::messaging_universal::egress::run_end_to_end(
+                    events,
+                    &self.provider,
+                    &self.bundle,
+                    &context,
+                    self.runner_binary.clone(),
+                    self.app_pack.clone(),
+                    self.send,
+                    self.dry_run,
+                    self.retries,
+                    secrets_handle.clone(),
+                )?;
+            }
+        }
+
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.ingress.scenario_passed",
+                "scenario passed: {} step(s)",
+                &[&total.to_string()]
+            )
+        );
+        if self.dlq_tail {
+            self.print_dlq_location();
         }
         Ok(())
     }
@@ -5077,9 +11634,14 @@ fn print_http_response(
         )
     );
     for (name, value) in &response.headers {
+        let value = if redaction::enabled() && redaction::is_sensitive_key(name) {
+            redaction::REDACTED.to_string()
+        } else {
+            redaction::redact_text(value)
+        };
         println!(
             "{}",
-            operator_i18n::trf("cli.ingress.http_header", "  {}: {}", &[name, value])
+            operator_i18n::trf("cli.ingress.http_header", "  {}: {}", &[name, &value])
         );
     }
     if let Some(body_b64) = &response.body_b64 {
@@ -5087,7 +11649,11 @@ fn print_http_response(
             if let Ok(text) = std::str::from_utf8(&bytes) {
                 println!(
                     "{}",
-                    operator_i18n::trf("cli.ingress.http_body", "  body: {}", &[text])
+                    operator_i18n::trf(
+                        "cli.ingress.http_body",
+                        "  body: {}",
+                        &[&redaction::redact_text(text)]
+                    )
                 );
             } else {
                 println!(
@@ -5113,9 +11679,9 @@ fn print_http_response(
     Ok(())
 }
 
-fn print_envelopes(envelopes: &[greentic_types::ChannelMessageEnvelope]) -> anyhow::Result<()> {
+fn print_envelopes<T: Serialize>(envelopes: &[T]) -> anyhow::Result<()> {
     for envelope in envelopes {
-        let formatted = serde_json::to_string_pretty(envelope)?;
+        let formatted = redaction::to_string_pretty(envelope)?;
         println!("{formatted}");
     }
     Ok(())
@@ -5235,1541 +11801,1004 @@ fn create_demo_bundle_structure(root: &Path) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn load_demo_config_or_default(path: &Path) -> config::DemoConfig {
-    match config::load_demo_config(path) {
-        Ok(value) => value,
-        Err(err) => {
-            operator_log::warn(
-                module_path!(),
-                format!(
-                    "failed to load {}: {err}; using default values",
-                    path.display()
-                ),
-            );
-            config::DemoConfig::default()
-        }
-    }
-}
-
-fn start_demo_ingress_server(
-    bundle: &Path,
-    discovery: &discovery::DiscoveryResult,
-    demo_config: &config::DemoConfig,
-    domains: &[Domain],
-    runner_binary: Option<PathBuf>,
-    debug_enabled: bool,
-    secrets_handle: SecretsManagerHandle,
-) -> anyhow::Result<HttpIngressServer> {
-    let addr = format!(
-        "{}:{}",
-        demo_config.services.gateway.listen_addr, demo_config.services.gateway.port
-    );
-    let bind_addr: SocketAddr = addr
-        .parse()
-        .with_context(|| format!("invalid gateway listen address {addr}"))?;
-    let runner_host = Arc::new(DemoRunnerHost::new(
-        bundle.to_path_buf(),
-        discovery,
-        runner_binary,
-        secrets_handle.clone(),
-        debug_enabled,
-    )?);
-    HttpIngressServer::start(HttpIngressConfig {
-        bind_addr,
-        domains: domains.to_vec(),
-        runner_host,
-    })
-}
-
 #[allow(clippy::too_many_arguments)]
-fn start_demo_timer_scheduler(
-    bundle: &Path,
-    discovery: &discovery::DiscoveryResult,
-    domains: &[Domain],
+/// What the config-dependent `demo start` services are built from; kept for reloads.
+struct DemoServiceContext<'a> {
+    bundle: &'a Path,
+    discovery: &'a discovery::DiscoveryResult,
+    domains: &'a [Domain],
     runner_binary: Option<PathBuf>,
     debug_enabled: bool,
     secrets_handle: SecretsManagerHandle,
-    tenant: &str,
-    team: &str,
-) -> anyhow::Result<Option<TimerScheduler>> {
-    if !domains.contains(&Domain::Events) {
-        return Ok(None);
-    }
-    let default_interval_seconds = std::env::var("GREENTIC_OPERATOR_TIMER_INTERVAL_SECONDS")
-        .ok()
-        .and_then(|value| value.parse::<u64>().ok())
-        .unwrap_or(60)
-        .max(1);
-    let handlers = discover_timer_handlers(discovery, default_interval_seconds)?;
-    if handlers.is_empty() {
-        return Ok(None);
-    }
-    let runner_host = Arc::new(DemoRunnerHost::new(
-        bundle.to_path_buf(),
-        discovery,
-        runner_binary,
-        secrets_handle,
-        debug_enabled,
-    )?);
-    let scheduler = TimerScheduler::start(TimerSchedulerConfig {
-        runner_host,
-        tenant: tenant.to_string(),
-        team: Some(team.to_string()),
-        handlers,
-        debug_enabled,
-    })?;
-    Ok(Some(scheduler))
-}
-
-fn ensure_dir(path: &Path) -> anyhow::Result<()> {
-    fs::create_dir_all(path)?;
-    Ok(())
-}
-
-fn write_if_missing(path: &Path, contents: &str) -> anyhow::Result<()> {
-    if path.exists() {
-        return Ok(());
-    }
-    if let Some(parent) = path.parent() {
-        ensure_dir(parent)?;
-    }
-    fs::write(path, contents)?;
-    Ok(())
-}
-fn restart_name(target: &RestartTarget) -> String {
-    match target {
-        RestartTarget::All => "all",
-        RestartTarget::Cloudflared => "cloudflared",
-        RestartTarget::Ngrok => "ngrok",
-        RestartTarget::Nats => "nats",
-        RestartTarget::Gateway => "gateway",
-        RestartTarget::Egress => "egress",
-        RestartTarget::Subscriptions => "subscriptions",
-    }
-    .to_string()
-}
-
-fn resolve_demo_config_path(explicit: Option<PathBuf>) -> anyhow::Result<PathBuf> {
-    if let Some(path) = explicit {
-        return Ok(path);
-    }
-    let cwd = std::env::current_dir()?;
-    let demo_path = cwd.join("demo").join("demo.yaml");
-    if demo_path.exists() {
-        return Ok(demo_path);
-    }
-    let fallback = cwd.join("greentic.operator.yaml");
-    if fallback.exists() {
-        return Ok(fallback);
-    }
-    Err(anyhow::anyhow!(
-        "no demo config found; pass --config or create ./demo/demo.yaml"
-    ))
-}
-
-fn wait_for_ctrlc() -> anyhow::Result<()> {
-    let runtime = Runtime::new().context("failed to spawn runtime for Ctrl+C listener")?;
-    runtime.block_on(async {
-        tokio::signal::ctrl_c()
-            .await
-            .map_err(|err| anyhow::anyhow!("failed to wait for Ctrl+C: {err}"))
-    })
-}
-
-impl DemoStatusArgs {
-    fn run(self) -> anyhow::Result<()> {
-        let state_dir = resolve_state_dir(self.state_dir, self.bundle.as_ref());
-        if demo_debug_enabled() {
-            println!(
-                "[demo] status state_dir={} tenant={} team={} verbose={}",
-                state_dir.display(),
-                self.tenant,
-                self.team,
-                self.verbose
-            );
-        }
-        demo::demo_status_runtime(&state_dir, &self.tenant, &self.team, self.verbose)
-    }
-}
-
-impl DemoLogsArgs {
-    fn run(self) -> anyhow::Result<()> {
-        let log_dir = resolve_log_dir(self.log_dir.clone(), self.bundle.as_ref());
-        let state_dir = resolve_state_dir(None, self.bundle.as_ref());
-        if demo_debug_enabled() {
-            println!(
-                "[demo] logs log_dir={} tenant={} team={} service={} tail={}",
-                log_dir.display(),
-                self.tenant,
-                self.team,
-                self.service,
-                self.tail
-            );
-        }
-        demo::demo_logs_runtime(
-            &state_dir,
-            &log_dir,
-            &self.tenant,
-            &self.team,
-            &self.service,
-            self.tail,
-        )
-    }
-}
-
-impl DemoDoctorArgs {
-    fn run(self, _ctx: &AppCtx) -> anyhow::Result<()> {
-        let config = config::load_operator_config(&self.bundle)?;
-        let explicit = config::binary_override(config.as_ref(), "greentic-pack", &self.bundle);
-        let pack_command = bin_resolver::resolve_binary(
-            "greentic-pack",
-            &ResolveCtx {
-                config_dir: self.bundle.clone(),
-                explicit_path: explicit,
-            },
-        )?;
-        if demo_debug_enabled() {
-            println!(
-                "[demo] doctor bundle={} greentic-pack={}",
-                self.bundle.display(),
-                pack_command.display()
-            );
-        }
-        demo::demo_doctor(&self.bundle, &pack_command)
-    }
-}
-
-fn project_root(arg: Option<PathBuf>) -> anyhow::Result<PathBuf> {
-    Ok(arg.unwrap_or(env::current_dir()?))
-}
-
-fn resolve_state_dir(state_dir: Option<PathBuf>, bundle: Option<&PathBuf>) -> PathBuf {
-    if let Some(state_dir) = state_dir {
-        return state_dir;
-    }
-    if let Some(bundle) = bundle {
-        return bundle.join("state");
-    }
-    PathBuf::from("state")
-}
-
-fn resolve_log_dir(log_dir: Option<PathBuf>, bundle: Option<&PathBuf>) -> PathBuf {
-    if let Some(path) = log_dir {
-        return path;
-    }
-    if let Some(bundle) = bundle {
-        return bundle.join("logs");
-    }
-    PathBuf::from("logs")
-}
-
-#[cfg(test)]
-mod cli_tests {
-    use super::*;
-
-    #[test]
-    fn resolves_explicit_log_dir() {
-        let dir = PathBuf::from("/tmp/logs");
-        assert_eq!(resolve_log_dir(Some(dir.clone()), None), dir);
-    }
-
-    #[test]
-    fn resolves_bundle_log_dir() {
-        let bundle = PathBuf::from("/tmp/bundle");
-        assert_eq!(resolve_log_dir(None, Some(&bundle)), bundle.join("logs"));
-    }
-
-    #[test]
-    fn resolves_default_log_dir() {
-        assert_eq!(resolve_log_dir(None, None), PathBuf::from("logs"));
-    }
-}
-
-fn demo_debug_enabled() -> bool {
-    matches!(
-        std::env::var("GREENTIC_OPERATOR_DEMO_DEBUG").as_deref(),
-        Ok("1") | Ok("true") | Ok("yes")
-    )
-}
-
-#[allow(clippy::too_many_arguments)]
-fn run_demo_up_setup(
-    bundle: &Path,
-    domains: &[Domain],
-    setup_input: &Path,
-    tenant_override: Option<String>,
-    team_override: Option<String>,
-    env: &str,
-    runner_binary: Option<PathBuf>,
-    public_base_url: Option<String>,
-    secrets_manager: Option<DynSecretsManager>,
-) -> anyhow::Result<()> {
-    let providers_input = ProvidersInput::load(setup_input)?;
-    for domain in domains {
-        let provider_map = match providers_input.providers_for_domain(*domain) {
-            Some(map) if !map.is_empty() => map,
-            _ => {
+    tenant: &'a str,
+    team: &'a str,
+}
+
+/// The services a reload restarts. NATS, the tunnels, the webhook watcher, and the
+/// control API do not read `greentic.demo.yaml` after start and keep running.
+#[derive(Default)]
+struct DemoReloadableServices {
+    ingress_server: Option<HttpIngressServer>,
+    timer_scheduler: Option<TimerScheduler>,
+    kafka_bridge: Option<KafkaBridge>,
+}
+
+impl DemoReloadableServices {
+    /// Starts what `demo_config` enables; a service that fails to start is reported
+    /// and left off.
+    fn start(ctx: &DemoServiceContext<'_>, demo_config: &config::DemoConfig) -> Self {
+        let mut services = Self::default();
+        match api::start_ingress_server(
+            ctx.bundle,
+            ctx.tenant,
+            ctx.team,
+            ctx.discovery,
+            demo_config,
+            ctx.domains,
+            ctx.runner_binary.clone(),
+            ctx.debug_enabled,
+            ctx.secrets_handle.clone(),
+        ) {
+            Ok((server, _)) => {
                 println!(
-                    "[demo] no providers configured for domain {}; skipping provider setup",
-                    domains::domain_name(*domain)
+                    "{}",
+                    operator_i18n::trf(
+                        "cli.start.http_ingress_ready",
+                        "HTTP ingress ready at http://{}:{}",
+                        &[
+                            &demo_config.services.gateway.listen_addr,
+                            &demo_config.services.gateway.port.to_string()
+                        ]
+                    )
                 );
-                continue;
+                services.ingress_server = Some(server);
             }
-        };
-        let tenants = if let Some(tenant) = tenant_override.as_ref() {
-            vec![tenant.clone()]
-        } else {
-            let discovered = discover_tenants(bundle, *domain)?;
-            if discovered.is_empty() {
+            Err(err) => {
+                eprintln!(
+                    "{}",
+                    operator_i18n::trf(
+                        "cli.start.warn_http_ingress_disabled",
+                        "Warning: HTTP ingress disabled: {}",
+                        &[&err.to_string()]
+                    )
+                );
+                operator_log::warn(
+                    module_path!(),
+                    format!("demo ingress server unavailable: {err}"),
+                );
+            }
+        }
+        match start_demo_timer_scheduler(
+            ctx.bundle,
+            demo_config,
+            ctx.discovery,
+            ctx.domains,
+            ctx.runner_binary.clone(),
+            ctx.debug_enabled,
+            ctx.secrets_handle.clone(),
+            ctx.tenant,
+            ctx.team,
+        ) {
+            Ok(Some(scheduler)) => {
                 println!(
-                    "[demo] no tenants discovered for domain {}; skipping",
-                    domains::domain_name(*domain)
+                    "{}",
+                    operator_i18n::tr(
+                        "cli.start.events_timer_scheduler_ready",
+                        "events timer scheduler ready"
+                    )
+                );
+                services.timer_scheduler = Some(scheduler);
+            }
+            Ok(None) => {}
+            Err(err) => {
+                eprintln!(
+                    "{}",
+                    operator_i18n::trf(
+                        "cli.start.warn_events_timer_scheduler_disabled",
+                        "Warning: events timer scheduler disabled: {}",
+                        &[&err.to_string()]
+                    )
                 );
                 operator_log::warn(
                     module_path!(),
-                    format!(
-                        "no tenants discovered for domain {}; skipping setup",
-                        domains::domain_name(*domain)
-                    ),
+                    format!("demo timer scheduler unavailable: {err}"),
                 );
-                continue;
             }
-            discovered
-        };
-        let provider_keys: BTreeSet<String> = provider_map.keys().cloned().collect();
-        let mut map = serde_json::Map::new();
-        for (provider, value) in provider_map {
-            map.insert(provider.clone(), value.clone());
         }
-        let setup_answers =
-            SetupInputAnswers::new(serde_json::Value::Object(map), provider_keys.clone())?;
-        for tenant in tenants {
-            run_domain_command(DomainRunArgs {
-                root: bundle.to_path_buf(),
-                state_root: None,
-                domain: *domain,
-                action: DomainAction::Setup,
-                tenant,
-                team: team_override.clone(),
-                provider_filter: None,
-                dry_run: false,
-                format: PlanFormat::Text,
-                parallel: 1,
-                allow_missing_setup: true,
-                allow_contract_change: false,
-                backup: false,
-                online: false,
-                secrets_env: Some(env.to_string()),
-                runner_binary: runner_binary.clone(),
-                best_effort: false,
-                discovered_providers: None,
-                setup_input: None,
-                allowed_providers: Some(provider_keys.clone()),
-                preloaded_setup_answers: Some(setup_answers.clone()),
-                public_base_url: public_base_url.clone(),
-                secrets_manager: secrets_manager.clone(),
-            })?;
+        match start_demo_kafka_bridge(ctx.bundle, demo_config, ctx.domains, ctx.tenant, ctx.team) {
+            Ok(Some(bridge)) => {
+                println!(
+                    "{}",
+                    operator_i18n::trf(
+                        "cli.start.kafka_bridge_ready",
+                        "Kafka bridge ready (brokers={})",
+                        &[&demo_config.services.events.kafka.brokers.join(",")]
+                    )
+                );
+                services.kafka_bridge = Some(bridge);
+            }
+            Ok(None) => {}
+            Err(err) => {
+                eprintln!(
+                    "{}",
+                    operator_i18n::trf(
+                        "cli.start.warn_kafka_bridge_disabled",
+                        "Warning: Kafka bridge disabled: {}",
+                        &[&err.to_string()]
+                    )
+                );
+                operator_log::warn(
+                    module_path!(),
+                    format!("demo kafka bridge unavailable: {err}"),
+                );
+            }
         }
+        services
     }
-    Ok(())
-}
 
-fn demo_provider_files(
-    root: &Path,
-    tenant: &str,
-    team: Option<&str>,
-    domain: Domain,
-) -> anyhow::Result<Option<std::collections::BTreeSet<String>>> {
-    let resolved = demo_resolved_manifest_path(root, tenant, team);
-    if !resolved.exists() {
-        return Ok(None);
-    }
-    let contents = std::fs::read_to_string(resolved)?;
-    let manifest: DemoResolvedManifest = serde_yaml_bw::from_str(&contents)?;
-    let key = match domain {
-        Domain::Messaging => "messaging",
-        Domain::Events => "events",
-        Domain::Secrets => "secrets",
-    };
-    let Some(list) = manifest.providers.get(key) else {
-        return Ok(Some(std::collections::BTreeSet::new()));
-    };
-    let mut files = std::collections::BTreeSet::new();
-    for path in list {
-        if let Some(name) = Path::new(path).file_name().and_then(|value| value.to_str()) {
-            files.insert(name.to_string());
+    fn stop(&mut self) -> anyhow::Result<()> {
+        if let Some(server) = self.ingress_server.take() {
+            server.stop()?;
+        }
+        if let Some(scheduler) = self.timer_scheduler.take() {
+            scheduler.stop()?;
         }
+        if let Some(bridge) = self.kafka_bridge.take() {
+            bridge.stop()?;
+        }
+        Ok(())
     }
-    Ok(Some(files))
-}
-
-fn demo_resolved_manifest_path(root: &Path, tenant: &str, team: Option<&str>) -> PathBuf {
-    root.join("resolved")
-        .join(resolved_manifest_filename(tenant, team))
-}
-
-fn demo_state_resolved_manifest_path(root: &Path, tenant: &str, team: Option<&str>) -> PathBuf {
-    root.join("state")
-        .join("resolved")
-        .join(resolved_manifest_filename(tenant, team))
-}
 
-fn resolved_manifest_filename(tenant: &str, team: Option<&str>) -> String {
-    match team {
-        Some(team) => format!("{tenant}.{team}.yaml"),
-        None => format!("{tenant}.yaml"),
+    fn names(&self) -> Vec<&'static str> {
+        [
+            self.ingress_server.as_ref().map(|_| "ingress"),
+            self.timer_scheduler.as_ref().map(|_| "timer-scheduler"),
+            self.kafka_bridge.as_ref().map(|_| "kafka-bridge"),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
     }
 }
 
-fn demo_bundle_gmap_path(bundle: &Path, tenant: &str, team: Option<&str>) -> PathBuf {
-    let mut path = bundle.join("tenants").join(tenant);
-    if let Some(team) = team {
-        path = path.join("teams").join(team).join("team.gmap");
+/// Re-reads `greentic.demo.yaml` and the gmaps of a running `demo start`, refreshes
+/// the resolved manifests, and restarts the services built from the old config.
+/// Nothing is stopped until the new config has loaded and resolved.
+fn reload_demo_bundle(
+    ctx: &DemoServiceContext<'_>,
+    run_targets: &[DemoBundleTarget],
+    services: &mut DemoReloadableServices,
+) -> anyhow::Result<JsonValue> {
+    let config_path = ctx.bundle.join("greentic.demo.yaml");
+    let demo_config = if config_path.exists() {
+        config::load_demo_config(&config_path)?
     } else {
-        path = path.join("tenant.gmap");
-    }
-    path
-}
-
-fn copy_resolved_manifest(bundle: &Path, tenant: &str, team: Option<&str>) -> anyhow::Result<()> {
-    let src = demo_state_resolved_manifest_path(bundle, tenant, team);
-    if !src.exists() {
-        return Err(anyhow::anyhow!(
-            "resolved manifest not found at {}",
-            src.display()
-        ));
-    }
-    let dst = demo_resolved_manifest_path(bundle, tenant, team);
-    if let Some(parent) = dst.parent() {
-        std::fs::create_dir_all(parent)?;
+        let mut config = config::DemoConfig::default();
+        config::apply_gateway_env_overrides(&mut config);
+        config
+    };
+    project::sync_project(ctx.bundle)?;
+    let mut manifests = Vec::new();
+    for target in run_targets {
+        let team = target.team.as_deref();
+        if demo_state_resolved_manifest_path(ctx.bundle, &target.tenant, team).exists() {
+            copy_resolved_manifest(ctx.bundle, &target.tenant, team)?;
+            manifests.push(demo_resolved_manifest_path(
+                ctx.bundle,
+                &target.tenant,
+                team,
+            ));
+        }
     }
-    std::fs::copy(src, dst)?;
-    Ok(())
+    services.stop()?;
+    *services = DemoReloadableServices::start(ctx, &demo_config);
+    Ok(json!({
+        "success": true,
+        "config": config_path,
+        "resolved_manifests": manifests,
+        "restarted": services.names(),
+    }))
 }
 
-pub(crate) fn discovery_map(
-    providers: &[discovery::DetectedProvider],
-) -> std::collections::BTreeMap<PathBuf, discovery::DetectedProvider> {
-    let mut map = std::collections::BTreeMap::new();
-    for provider in providers {
-        map.insert(provider.pack_path.clone(), provider.clone());
+fn start_demo_timer_scheduler(
+    bundle: &Path,
+    demo_config: &config::DemoConfig,
+    discovery: &discovery::DiscoveryResult,
+    domains: &[Domain],
+    runner_binary: Option<PathBuf>,
+    debug_enabled: bool,
+    secrets_handle: SecretsManagerHandle,
+    tenant: &str,
+    team: &str,
+) -> anyhow::Result<Option<TimerScheduler>> {
+    let handlers = if domains.contains(&Domain::Events) {
+        configured_timer_handlers(demo_config, discovery)?
+    } else {
+        Vec::new()
+    };
+    let scheduled_sends = domains.contains(&Domain::Messaging);
+    if handlers.is_empty() && !scheduled_sends {
+        return Ok(None);
     }
-    map
+    let runner_host = Arc::new(DemoRunnerHost::new(
+        bundle.to_path_buf(),
+        discovery,
+        runner_binary,
+        secrets_handle,
+        debug_enabled,
+    )?);
+    let scheduler = TimerScheduler::start(TimerSchedulerConfig {
+        runner_host,
+        tenant: tenant.to_string(),
+        team: Some(team.to_string()),
+        handlers,
+        catch_up: demo_config.services.events.timer_catch_up,
+        debug_enabled,
+        scheduled_sends,
+    })?;
+    Ok(Some(scheduler))
 }
 
-fn provider_filter_matches(pack: &domains::ProviderPack, filter: &str) -> bool {
-    let file_stem = pack
-        .file_name
-        .strip_suffix(".gtpack")
-        .unwrap_or(&pack.file_name);
-    pack.pack_id == filter
-        || pack.file_name == filter
-        || file_stem == filter
-        || pack.pack_id.contains(filter)
-        || pack.file_name.contains(filter)
-        || file_stem.contains(filter)
+/// Timer handlers declared by the provider packs with `services.events.timers`
+/// overrides applied.
+fn configured_timer_handlers(
+    demo_config: &config::DemoConfig,
+    discovery: &discovery::DiscoveryResult,
+) -> anyhow::Result<Vec<TimerHandlerConfig>> {
+    let default_interval_seconds = std::env::var("GREENTIC_OPERATOR_TIMER_INTERVAL_SECONDS")
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(60)
+        .max(1);
+    let handlers = discover_timer_handlers(discovery, default_interval_seconds)?;
+    apply_timer_overrides(handlers, &demo_config.services.events.timers)
 }
 
-pub fn demo_provider_packs(
+#[allow(clippy::too_many_arguments)]
+fn start_demo_webhook_watcher(
     bundle: &Path,
-    domain: Domain,
-) -> anyhow::Result<Vec<domains::ProviderPack>> {
-    let is_demo_bundle = bundle.join("greentic.demo.yaml").exists();
-    if is_demo_bundle {
-        domains::discover_provider_packs_cbor_only(bundle, domain)
-    } else {
-        domains::discover_provider_packs(bundle, domain)
+    discovery: &discovery::DiscoveryResult,
+    domains: &[Domain],
+    runner_binary: Option<PathBuf>,
+    debug_enabled: bool,
+    secrets_handle: SecretsManagerHandle,
+    tenant: &str,
+    team: &str,
+    tunnel_log: Option<PathBuf>,
+) -> anyhow::Result<Option<WebhookWatcher>> {
+    let providers = webhook_watcher::webhook_providers(discovery, domains);
+    if providers.is_empty() {
+        return Ok(None);
     }
+    let runner_host = Arc::new(DemoRunnerHost::new(
+        bundle.to_path_buf(),
+        discovery,
+        runner_binary,
+        secrets_handle,
+        debug_enabled,
+    )?);
+    let watcher = WebhookWatcher::start(WebhookWatcherConfig {
+        runner_host,
+        tenant: tenant.to_string(),
+        team: team.to_string(),
+        providers,
+        tunnel_log,
+        interval: Duration::from_secs(5),
+    })?;
+    Ok(Some(watcher))
 }
 
-pub fn demo_provider_pack_by_filter(
+fn start_demo_kafka_bridge(
     bundle: &Path,
-    domain: Domain,
-    filter: &str,
-) -> anyhow::Result<domains::ProviderPack> {
-    let mut packs = demo_provider_packs(bundle, domain)?;
-    packs.retain(|pack| provider_filter_matches(pack, filter));
-    if packs.is_empty() {
-        return Err(anyhow::anyhow!(
-            "no provider pack matched {} in {}",
-            filter,
-            domains::domain_name(domain)
-        ));
-    }
-    packs.sort_by(|a, b| a.path.cmp(&b.path));
-    if packs.len() > 1 {
-        let names = packs
-            .iter()
-            .map(|pack| pack.file_name.clone())
-            .collect::<Vec<_>>();
-        return Err(anyhow::anyhow!(
-            "multiple provider packs matched {}; specify a more precise --pack: {}",
-            filter,
-            names.join(", ")
-        ));
+    demo_config: &config::DemoConfig,
+    domains: &[Domain],
+    tenant: &str,
+    team: &str,
+) -> anyhow::Result<Option<KafkaBridge>> {
+    let kafka = &demo_config.services.events.kafka;
+    if !kafka.enabled || !domains.contains(&Domain::Events) {
+        return Ok(None);
     }
-    Ok(packs.remove(0))
+    let binary = kafka_bridge::resolve_binary(bundle, kafka)?;
+    let bridge = KafkaBridge::start(KafkaBridgeConfig {
+        bundle: bundle.to_path_buf(),
+        tenant: tenant.to_string(),
+        team: team.to_string(),
+        binary,
+        kafka: kafka.clone(),
+    })?;
+    Ok(Some(bridge))
 }
 
-pub(crate) fn resolve_demo_provider_pack(
-    root: &Path,
-    tenant: &str,
-    team: Option<&str>,
-    provider: &str,
-    domain: Domain,
-) -> anyhow::Result<domains::ProviderPack> {
-    let is_demo_bundle = root.join("greentic.demo.yaml").exists();
-    let mut packs = if is_demo_bundle {
-        domains::discover_provider_packs_cbor_only(root, domain)?
-    } else {
-        domains::discover_provider_packs(root, domain)?
-    };
-    if is_demo_bundle && let Some(allowed) = demo_provider_files(root, tenant, team, domain)? {
-        packs.retain(|pack| allowed.contains(&pack.file_name));
-    }
-    packs.retain(|pack| provider_filter_matches(pack, provider));
-    if packs.is_empty() {
-        return Err(anyhow::anyhow!(
-            "No provider packs matched. Try --provider <pack_id>."
-        ));
-    }
-    packs.sort_by(|a, b| a.path.cmp(&b.path));
-    if packs.len() > 1 {
-        let names = packs
-            .iter()
-            .map(|pack| pack.file_name.clone())
-            .collect::<Vec<_>>();
-        return Err(anyhow::anyhow!(
-            "Multiple provider packs matched: {}. Use a more specific --provider.",
-            names.join(", ")
-        ));
-    }
-    Ok(packs.remove(0))
+fn ensure_dir(path: &Path) -> anyhow::Result<()> {
+    fs::create_dir_all(path)?;
+    Ok(())
 }
 
-fn ensure_requirements_flow(pack: &domains::ProviderPack) -> Result<(), String> {
-    if pack.entry_flows.iter().any(|flow| flow == "requirements") {
+fn write_if_missing(path: &Path, contents: &str) -> anyhow::Result<()> {
+    if path.exists() {
         return Ok(());
     }
-    Err(
-        "requirements flow not found in provider pack; ask the provider pack to include an entry flow named 'requirements'."
-            .to_string(),
-    )
+    if let Some(parent) = path.parent() {
+        ensure_dir(parent)?;
+    }
+    fs::write(path, contents)?;
+    Ok(())
 }
-
-#[derive(serde::Deserialize)]
-struct DemoResolvedManifest {
-    #[serde(default)]
-    providers: std::collections::BTreeMap<String, Vec<String>>,
+fn restart_name(target: &RestartTarget) -> String {
+    match target {
+        RestartTarget::All => "all",
+        RestartTarget::Cloudflared => "cloudflared",
+        RestartTarget::Ngrok => "ngrok",
+        RestartTarget::Nats => "nats",
+        RestartTarget::Gateway => "gateway",
+        RestartTarget::Egress => "egress",
+        RestartTarget::Subscriptions => "subscriptions",
+    }
+    .to_string()
 }
 
-impl From<WizardModeArg> for wizard::WizardMode {
-    fn from(value: WizardModeArg) -> Self {
-        match value {
-            WizardModeArg::Create => wizard::WizardMode::Create,
-            WizardModeArg::Update => wizard::WizardMode::Update,
-            WizardModeArg::Remove => wizard::WizardMode::Remove,
-        }
+fn resolve_demo_config_path(explicit: Option<PathBuf>) -> anyhow::Result<PathBuf> {
+    if let Some(path) = explicit {
+        return Ok(path);
+    }
+    let cwd = std::env::current_dir()?;
+    let demo_path = cwd.join("demo").join("demo.yaml");
+    if demo_path.exists() {
+        return Ok(demo_path);
     }
+    let fallback = cwd.join("greentic.operator.yaml");
+    if fallback.exists() {
+        return Ok(fallback);
+    }
+    Err(anyhow::anyhow!(
+        "no demo config found; pass --config or create ./demo/demo.yaml"
+    ))
 }
 
-struct DomainRunArgs {
-    root: PathBuf,
-    state_root: Option<PathBuf>,
-    domain: Domain,
-    action: DomainAction,
-    tenant: String,
-    team: Option<String>,
-    provider_filter: Option<String>,
-    dry_run: bool,
-    format: PlanFormat,
-    parallel: usize,
-    allow_missing_setup: bool,
-    allow_contract_change: bool,
-    backup: bool,
-    online: bool,
-    secrets_env: Option<String>,
-    runner_binary: Option<PathBuf>,
-    best_effort: bool,
-    discovered_providers: Option<Vec<discovery::DetectedProvider>>,
-    setup_input: Option<PathBuf>,
-    allowed_providers: Option<BTreeSet<String>>,
-    preloaded_setup_answers: Option<SetupInputAnswers>,
-    public_base_url: Option<String>,
-    secrets_manager: Option<DynSecretsManager>,
+fn wait_for_ctrlc() -> anyhow::Result<()> {
+    let runtime = Runtime::new().context("failed to spawn runtime for Ctrl+C listener")?;
+    runtime.block_on(async {
+        tokio::signal::ctrl_c()
+            .await
+            .map_err(|err| anyhow::anyhow!("failed to wait for Ctrl+C: {err}"))
+    })
 }
 
-fn run_domain_command(args: DomainRunArgs) -> anyhow::Result<()> {
-    let is_demo_bundle = args.root.join("greentic.demo.yaml").exists();
-    let mut packs = if is_demo_bundle {
-        domains::discover_provider_packs_cbor_only(&args.root, args.domain)?
-    } else {
-        domains::discover_provider_packs(&args.root, args.domain)?
-    };
-    let provider_map = args.discovered_providers.as_ref().map(|providers| {
-        let mut map = std::collections::BTreeMap::new();
-        for provider in providers {
-            map.insert(provider.pack_path.clone(), provider.clone());
-        }
-        map
-    });
-    if let Some(provider_map) = provider_map.as_ref() {
-        packs.retain(|pack| provider_map.contains_key(&pack.path));
-        packs.sort_by(|a, b| a.path.cmp(&b.path));
-    }
-    if is_demo_bundle
-        && let Some(allowed) =
-            demo_provider_files(&args.root, &args.tenant, args.team.as_deref(), args.domain)?
-    {
-        packs.retain(|pack| allowed.contains(&pack.file_name));
-    }
-    if args.action == DomainAction::Setup {
-        let setup_flow = domains::config(args.domain).setup_flow;
-        let missing: Vec<String> = packs
-            .iter()
-            .filter(|pack| !pack.entry_flows.iter().any(|flow| flow == setup_flow))
-            .map(|pack| pack.file_name.clone())
-            .collect();
-        if !missing.is_empty() && !args.allow_missing_setup {
-            if args.best_effort {
-                println!(
-                    "{}",
-                    operator_i18n::trf(
-                        "cli.domain.best_effort_skipped_missing_setup",
-                        "Best-effort: skipped {} pack(s) missing {}.",
-                        &[&missing.len().to_string(), setup_flow]
-                    )
-                );
-                packs.retain(|pack| pack.entry_flows.iter().any(|flow| flow == setup_flow));
-            } else {
-                return Err(anyhow::anyhow!(
-                    "missing {setup_flow} in packs: {}",
-                    missing.join(", ")
-                ));
+impl DemoReloadArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let state_dir = self.bundle.join("state");
+        if let Some(endpoint) = demo::control_api::read_endpoint(&state_dir)? {
+            let summary = demo::control_api::post(&endpoint, "/v1/reload", &json!({}))?;
+            if matches!(self.format, ListFormat::Json) {
+                println!("{}", serde_json::to_string_pretty(&summary)?);
+                return Ok(());
             }
-        }
-    }
-    if packs.is_empty() {
-        return Ok(());
-    }
-    if let Some(allowed) = args.allowed_providers.as_ref() {
-        let missing = filter_packs_by_allowed(&mut packs, allowed);
-        if !missing.is_empty() {
+            let restarted = summary["restarted"]
+                .as_array()
+                .map(|names| {
+                    names
+                        .iter()
+                        .filter_map(JsonValue::as_str)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .unwrap_or_default();
             println!(
                 "{}",
                 operator_i18n::trf(
-                    "cli.domain.warn_skip_missing_packs",
-                    "[warn] skip setup domain={} missing packs: {}",
-                    &[domains::domain_name(args.domain), &missing.join(", ")]
+                    "cli.demo.reload.done",
+                    "Reloaded {} ({} resolved manifest(s)); restarted: {}",
+                    &[
+                        summary["config"].as_str().unwrap_or("greentic.demo.yaml"),
+                        &summary["resolved_manifests"]
+                            .as_array()
+                            .map_or(0, Vec::len)
+                            .to_string(),
+                        &restarted
+                    ]
                 )
             );
-            operator_log::warn(
-                module_path!(),
-                format!(
-                    "provider filter domain={} removed packs: {}",
-                    domains::domain_name(args.domain),
-                    missing.join(", ")
-                ),
-            );
+            return Ok(());
         }
-    }
-    operator_log::info(
-        module_path!(),
-        format!(
-            "provider selection domain={} packs={}",
-            domains::domain_name(args.domain),
-            packs.len()
-        ),
-    );
-    let setup_answers = if let Some(preloaded) = args.preloaded_setup_answers.clone() {
-        Some(preloaded)
-    } else if let Some(path) = args.setup_input.as_ref() {
-        let provider_keys: BTreeSet<String> =
-            packs.iter().map(|pack| pack.pack_id.clone()).collect();
-        Some(SetupInputAnswers::new(
-            load_setup_input(path)?,
-            provider_keys,
-        )?)
-    } else {
-        None
-    };
-    let interactive = args.setup_input.is_none();
-    let plan = domains::plan_runs(
-        args.domain,
-        args.action,
-        &packs,
-        args.provider_filter.as_deref(),
-        args.allow_missing_setup,
-    )?;
-
-    operator_log::info(
-        module_path!(),
-        format!(
-            "plan domain={} action={:?} items={}",
-            domains::domain_name(args.domain),
-            args.action,
-            plan.len()
-        ),
-    );
-    for item in &plan {
-        operator_log::debug(
-            module_path!(),
-            format!(
-                "plan item domain={} pack={} flow={}",
-                domains::domain_name(args.domain),
-                item.pack.file_name,
-                item.flow_id
-            ),
-        );
-    }
-
-    if plan.is_empty() {
-        if is_demo_bundle {
+        let process = demo::reload::read_process(&state_dir)?
+            .filter(|process| crate::supervisor::is_running(process.pid))
+            .ok_or_else(|| {
+                anyhow!(
+                    "no running demo start found for bundle {}",
+                    self.bundle.display()
+                )
+            })?;
+        demo::reload::signal_reload(process.pid)?;
+        if matches!(self.format, ListFormat::Json) {
             println!(
                 "{}",
-                operator_i18n::tr(
-                    "cli.domain.no_provider_packs_matched",
-                    "No provider packs matched. Try --provider <pack_id>."
-                )
+                serde_json::to_string_pretty(&json!({ "signaled": true, "pid": process.pid }))?
             );
         } else {
             println!(
                 "{}",
-                operator_i18n::tr(
-                    "cli.domain.no_provider_packs_matched_or_project_root",
-                    "No provider packs matched. Try --provider <pack_id> or --project-root."
+                operator_i18n::trf(
+                    "cli.demo.reload.signaled",
+                    "Sent SIGHUP to demo start (pid {}); its output reports the reload result",
+                    &[&process.pid.to_string()]
                 )
             );
         }
-        operator_log::warn(
-            module_path!(),
-            format!(
-                "no provider packs matched domain={} action={:?}",
-                domains::domain_name(args.domain),
-                args.action
-            ),
-        );
-        return Ok(());
+        Ok(())
     }
+}
 
-    if args.dry_run {
-        render_plan(&plan, args.format)?;
-        return Ok(());
+impl DemoStatusArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let state_dir = resolve_state_dir(self.state_dir, self.bundle.as_ref());
+        if demo_debug_enabled() {
+            println!(
+                "[demo] status state_dir={} tenant={} team={} verbose={}",
+                state_dir.display(),
+                self.tenant,
+                self.team,
+                self.verbose
+            );
+        }
+        demo::demo_status_runtime(
+            &state_dir,
+            &self.tenant,
+            &self.team,
+            demo::StatusOptions {
+                verbose: self.verbose,
+                json: self.json,
+                deep: self.deep,
+            },
+        )
     }
+}
 
-    let runner_binary = resolve_demo_runner_binary(&args.root, args.runner_binary)?;
-    let dist_offline = !args.online;
-    let state_root = args.state_root.as_ref().unwrap_or(&args.root);
-    run_plan(
-        &args.root,
-        state_root,
-        args.domain,
-        args.action,
-        &args.tenant,
-        args.team.as_deref(),
-        plan,
-        args.parallel,
-        dist_offline,
-        args.allow_contract_change,
-        args.backup,
-        args.secrets_env.as_deref(),
-        runner_binary,
-        args.best_effort,
-        provider_map,
-        setup_answers,
-        interactive,
-        args.public_base_url.clone(),
-        args.secrets_manager.clone(),
-    )
+impl DemoLogsArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let log_dir = resolve_log_dir(self.log_dir.clone(), self.bundle.as_ref());
+        let state_dir = resolve_state_dir(None, self.bundle.as_ref());
+        if demo_debug_enabled() {
+            println!(
+                "[demo] logs log_dir={} tenant={} team={} service={} tail={}",
+                log_dir.display(),
+                self.tenant,
+                self.team,
+                self.service,
+                self.tail
+            );
+        }
+        demo::demo_logs_runtime(
+            &state_dir,
+            &log_dir,
+            &self.tenant,
+            &self.team,
+            &self.service,
+            self.tail,
+        )
+    }
 }
 
-fn filter_packs_by_allowed(
-    packs: &mut Vec<domains::ProviderPack>,
-    allowed: &BTreeSet<String>,
-) -> Vec<String> {
-    let mut seen = BTreeSet::new();
-    packs.retain(|pack| {
-        if allowed.contains(&pack.pack_id) {
-            seen.insert(pack.pack_id.clone());
-            true
-        } else {
-            false
+impl LocaleListArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let current = operator_i18n::current_locale();
+        let locales = operator_i18n::available_locales();
+        match self.format {
+            ListFormat::Json => {
+                let entries = locales
+                    .iter()
+                    .map(|(locale, source)| {
+                        json!({
+                            "locale": locale,
+                            "current": *locale == current,
+                            "fallback": operator_i18n::fallback_chain(locale),
+                            "source": match source {
+                                operator_i18n::LocaleSource::Embedded => JsonValue::Null,
+                                operator_i18n::LocaleSource::File(path) => json!(path),
+                            },
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                println!("{}", serde_json::to_string_pretty(&entries)?);
+            }
+            ListFormat::Text => {
+                for (locale, source) in &locales {
+                    let marker = if *locale == current { "*" } else { " " };
+                    let source = match source {
+                        operator_i18n::LocaleSource::Embedded => {
+                            operator_i18n::tr("cli.locale.builtin", "built-in")
+                        }
+                        operator_i18n::LocaleSource::File(path) => path.display().to_string(),
+                    };
+                    println!(
+                        "{marker} {locale:<8} {source} ({})",
+                        operator_i18n::fallback_chain(locale).join(" -> ")
+                    );
+                }
+            }
         }
-    });
-    allowed
-        .iter()
-        .filter(|value| !seen.contains(*value))
-        .cloned()
-        .collect()
+        Ok(())
+    }
 }
 
-#[allow(clippy::too_many_arguments)]
-fn run_plan(
-    root: &Path,
-    state_root: &Path,
-    domain: Domain,
-    action: DomainAction,
-    tenant: &str,
-    team: Option<&str>,
-    plan: Vec<domains::PlannedRun>,
-    parallel: usize,
-    dist_offline: bool,
-    allow_contract_change: bool,
-    backup: bool,
-    secrets_env: Option<&str>,
-    runner_binary: Option<PathBuf>,
-    best_effort: bool,
-    provider_map: Option<std::collections::BTreeMap<PathBuf, discovery::DetectedProvider>>,
-    setup_answers: Option<SetupInputAnswers>,
-    interactive: bool,
-    public_base_url: Option<String>,
-    secrets_manager: Option<DynSecretsManager>,
-) -> anyhow::Result<()> {
-    let setup_answers = setup_answers.map(Arc::new);
-    let plan_public_base_url = public_base_url.map(Arc::new);
-    let plan_secrets_manager = secrets_manager;
-    if parallel <= 1 {
-        let mut errors = Vec::new();
-        for item in plan {
-            let result = run_plan_item(
-                root,
-                state_root,
-                domain,
-                action,
-                tenant,
-                team,
-                &item,
-                dist_offline,
-                allow_contract_change,
-                backup,
-                secrets_env,
-                runner_binary.as_deref(),
-                setup_answers.as_deref(),
-                provider_map.as_ref(),
-                interactive,
-                plan_public_base_url.clone(),
-                plan_secrets_manager.clone(),
-            );
-            if let Err(err) = result {
-                if best_effort {
-                    errors.push(err);
-                } else {
-                    return Err(err);
+impl WorkspaceCommand {
+    fn run(self) -> anyhow::Result<()> {
+        let workspace = workspace::Workspace::load(&self.file)?;
+        match self.command {
+            WorkspaceSubcommand::List => {
+                for (name, bundle) in &workspace.file.bundles {
+                    let ports = [
+                        bundle
+                            .gateway_port
+                            .map(|port| format!("gateway_port={port}")),
+                        bundle.nats_port.map(|port| format!("nats_port={port}")),
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>();
+                    println!(
+                        "{name} {} tenant={} {}",
+                        workspace.bundle_path(bundle).display(),
+                        bundle.tenant.as_deref().unwrap_or(DEMO_DEFAULT_TENANT),
+                        if ports.is_empty() {
+                            "ports=auto".to_string()
+                        } else {
+                            ports.join(" ")
+                        }
+                    );
+                }
+            }
+            WorkspaceSubcommand::Start(args) => {
+                let exe = env::current_exe()?;
+                for (name, state, ports) in workspace.start(&exe, &args.names)? {
+                    println!(
+                        "{}",
+                        operator_i18n::trf(
+                            "cli.workspace.started",
+                            "{}: {} (gateway port {}, nats port {})",
+                            &[
+                                &name,
+                                &format!("{state:?}"),
+                                &ports.gateway_port.to_string(),
+                                &ports.nats_port.to_string()
+                            ]
+                        )
+                    );
+                }
+            }
+            WorkspaceSubcommand::Stop(args) => {
+                for (name, state) in workspace.stop(&args.names)? {
+                    println!("{name}: {state:?}");
+                }
+            }
+            WorkspaceSubcommand::Status(args) => {
+                let members = workspace.status()?;
+                if matches!(args.format, ListFormat::Json) {
+                    println!("{}", serde_json::to_string_pretty(&members)?);
+                    return Ok(());
+                }
+                for member in members {
+                    let state = if member.running { "running" } else { "stopped" };
+                    match member.ports {
+                        Some(ports) => println!(
+                            "{} {state} gateway=:{} nats=:{} log={}",
+                            member.name,
+                            ports.gateway_port,
+                            ports.nats_port,
+                            member.log.display()
+                        ),
+                        None => println!("{} {state} log={}", member.name, member.log.display()),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ResolveExplainArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let root = project_root(self.bundle)?;
+        let explanation =
+            project::explain_resolution(&root, &self.tenant, self.team.as_deref(), &self.path)?;
+        match self.format {
+            ListFormat::Json => println!("{}", serde_json::to_string_pretty(&explanation)?),
+            ListFormat::Text => print!("{}", project::render_resolution(&explanation)),
+        }
+        Ok(())
+    }
+}
+
+impl DemoDiffArgs {
+    fn run(self) -> anyhow::Result<()> {
+        let diff = demo::diff::diff_dirs(&self.left, &self.right)?;
+        match self.format {
+            ListFormat::Json => println!("{}", serde_json::to_string_pretty(&diff)?),
+            ListFormat::Text if diff.is_empty() => println!(
+                "{}",
+                operator_i18n::tr("cli.demo.diff.identical", "no differences")
+            ),
+            ListFormat::Text => print!("{}", diff.render_text()),
+        }
+        if self.exit_code && !diff.is_empty() {
+            std::process::exit(1);
+        }
+        Ok(())
+    }
+}
+
+impl DemoTestArgs {
+    fn run(self) -> anyhow::Result<()> {
+        // Capabilities answer from state/capability-recordings instead of live providers.
+        unsafe {
+            env::set_var("GREENTIC_CAPABILITY_RECORDING", "mock");
+        }
+        domains::ensure_cbor_packs(&self.bundle)?;
+        let spec = demo::test_spec::load_spec(&self.spec)?;
+        let options = demo::test_spec::TestOptions {
+            tenant: self.tenant.clone(),
+            team: Some(self.team.clone()).filter(|team| !team.is_empty()),
+            runner_binary: self.runner_binary.clone(),
+            app_pack: self.app_pack.clone(),
+        };
+        let report = demo::test_spec::run_spec(&self.bundle, &spec, &options, &self.cases)?;
+        match self.format {
+            ListFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            ListFormat::Text => {
+                for case in &report.cases {
+                    let line = if case.passed {
+                        operator_i18n::trf("cli.demo.test.case_passed", "PASS {}", &[&case.name])
+                    } else {
+                        operator_i18n::trf("cli.demo.test.case_failed", "FAIL {}", &[&case.name])
+                    };
+                    println!("{line}");
+                    for failure in &case.failures {
+                        println!(
+                            "{}",
+                            operator_i18n::trf("cli.demo.test.failure", "  - {}", &[failure])
+                        );
+                    }
                 }
             }
         }
-        if best_effort && !errors.is_empty() {
+        let failed = report.failed();
+        if failed > 0 {
+            return Err(anyhow::anyhow!(
+                "{failed} of {} case(s) failed",
+                report.cases.len()
+            ));
+        }
+        if matches!(self.format, ListFormat::Text) {
             println!(
                 "{}",
                 operator_i18n::trf(
-                    "cli.domain.best_effort_flows_failed",
-                    "Best-effort: {} flow(s) failed.",
-                    &[&errors.len().to_string()]
+                    "cli.demo.test.all_passed",
+                    "{} case(s) passed",
+                    &[&report.cases.len().to_string()]
                 )
             );
-            return Ok(());
         }
-        return Ok(());
+        Ok(())
+    }
+}
+
+impl DemoDoctorArgs {
+    fn run(self, _ctx: &AppCtx) -> anyhow::Result<()> {
+        let config = config::load_operator_config(&self.bundle)?;
+        let explicit = config::binary_override(config.as_ref(), "greentic-pack", &self.bundle);
+        let pack_command = bin_resolver::resolve_binary(
+            "greentic-pack",
+            &ResolveCtx {
+                config_dir: self.bundle.clone(),
+                explicit_path: explicit,
+            },
+        )?;
+        if demo_debug_enabled() {
+            println!(
+                "[demo] doctor bundle={} greentic-pack={}",
+                self.bundle.display(),
+                pack_command.display()
+            );
+        }
+        demo::demo_doctor(&self.bundle, &pack_command)
+    }
+}
+
+fn project_root(arg: Option<PathBuf>) -> anyhow::Result<PathBuf> {
+    Ok(arg.unwrap_or(env::current_dir()?))
+}
+
+fn resolve_state_dir(state_dir: Option<PathBuf>, bundle: Option<&PathBuf>) -> PathBuf {
+    if let Some(state_dir) = state_dir {
+        return state_dir;
+    }
+    if let Some(bundle) = bundle {
+        return bundle.join("state");
     }
+    PathBuf::from("state")
+}
 
-    let mut handles = Vec::new();
-    let plan = std::sync::Arc::new(std::sync::Mutex::new(plan));
-    let errors = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
-
-    for _ in 0..parallel {
-        let plan = plan.clone();
-        let errors = errors.clone();
-        let root = root.to_path_buf();
-        let state_root = state_root.to_path_buf();
-        let tenant = tenant.to_string();
-        let team = team.map(|value| value.to_string());
-        let secrets_env = secrets_env.map(|value| value.to_string());
-        let runner_binary = runner_binary.clone();
-        let provider_map = provider_map.clone();
-        let setup_answers = setup_answers.clone();
-        let interactive_flag = interactive;
-        let thread_public_base_url = plan_public_base_url.clone();
-        let thread_secrets_manager = plan_secrets_manager.clone();
-        handles.push(std::thread::spawn(move || {
-            loop {
-                let next = {
-                    let mut queue = plan.lock().unwrap();
-                    queue.pop()
-                };
-                let Some(item) = next else {
-                    break;
-                };
-                let result = run_plan_item(
-                    &root,
-                    &state_root,
-                    domain,
-                    action,
-                    &tenant,
-                    team.as_deref(),
-                    &item,
-                    dist_offline,
-                    allow_contract_change,
-                    backup,
-                    secrets_env.as_deref(),
-                    runner_binary.as_deref(),
-                    setup_answers.as_deref(),
-                    provider_map.as_ref(),
-                    interactive_flag,
-                    thread_public_base_url.clone(),
-                    thread_secrets_manager.clone(),
-                );
-                if let Err(err) = result {
-                    errors.lock().unwrap().push(err);
-                }
-            }
-        }));
+fn resolve_log_dir(log_dir: Option<PathBuf>, bundle: Option<&PathBuf>) -> PathBuf {
+    if let Some(path) = log_dir {
+        return path;
+    }
+    if let Some(bundle) = bundle {
+        return bundle.join("logs");
     }
+    PathBuf::from("logs")
+}
+
+#[cfg(test)]
+mod cli_tests {
+    use super::*;
 
-    for handle in handles {
-        let _ = handle.join();
+    #[test]
+    fn resolves_explicit_log_dir() {
+        let dir = PathBuf::from("/tmp/logs");
+        assert_eq!(resolve_log_dir(Some(dir.clone()), None), dir);
     }
 
-    let errors = errors.lock().unwrap();
-    if !errors.is_empty() {
-        if best_effort {
-            println!(
-                "{}",
-                operator_i18n::trf(
-                    "cli.domain.best_effort_flows_failed",
-                    "Best-effort: {} flow(s) failed.",
-                    &[&errors.len().to_string()]
-                )
-            );
-            return Ok(());
-        }
-        return Err(anyhow::anyhow!("{} flow(s) failed.", errors.len()));
+    #[test]
+    fn resolves_bundle_log_dir() {
+        let bundle = PathBuf::from("/tmp/bundle");
+        assert_eq!(resolve_log_dir(None, Some(&bundle)), bundle.join("logs"));
     }
-    Ok(())
-}
 
-fn render_plan(plan: &[domains::PlannedRun], format: PlanFormat) -> anyhow::Result<()> {
-    match format {
-        PlanFormat::Text => {
-            println!("{}", operator_i18n::tr("cli.domain.plan_header", "Plan:"));
-            for item in plan {
-                println!(
-                    "{}",
-                    operator_i18n::trf(
-                        "cli.domain.plan_item",
-                        "  {} -> {}",
-                        &[&item.pack.file_name, &item.flow_id]
-                    )
-                );
-            }
-            Ok(())
-        }
-        PlanFormat::Json => {
-            let json = serde_json::to_string_pretty(plan)?;
-            println!("{json}");
-            Ok(())
-        }
-        PlanFormat::Yaml => {
-            let yaml = serde_yaml_bw::to_string(plan)?;
-            print!("{yaml}");
-            Ok(())
-        }
+    #[test]
+    fn resolves_default_log_dir() {
+        assert_eq!(resolve_log_dir(None, None), PathBuf::from("logs"));
     }
 }
 
+pub(crate) fn demo_debug_enabled() -> bool {
+    matches!(
+        std::env::var("GREENTIC_OPERATOR_DEMO_DEBUG").as_deref(),
+        Ok("1") | Ok("true") | Ok("yes")
+    )
+}
+
 #[allow(clippy::too_many_arguments)]
-fn run_plan_item(
-    root: &Path,
-    state_root: &Path,
-    domain: Domain,
-    action: DomainAction,
-    tenant: &str,
-    team: Option<&str>,
-    item: &domains::PlannedRun,
-    dist_offline: bool,
-    allow_contract_change: bool,
-    backup: bool,
-    secrets_env: Option<&str>,
-    runner_binary: Option<&Path>,
-    setup_answers: Option<&SetupInputAnswers>,
-    provider_map: Option<&std::collections::BTreeMap<PathBuf, discovery::DetectedProvider>>,
-    interactive: bool,
-    public_base_url: Option<Arc<String>>,
+fn run_demo_up_setup(
+    bundle: &Path,
+    domains: &[Domain],
+    setup_input: &Path,
+    tenant_override: Option<String>,
+    team_override: Option<String>,
+    env: &str,
+    runner_binary: Option<PathBuf>,
+    public_base_url: Option<String>,
     secrets_manager: Option<DynSecretsManager>,
 ) -> anyhow::Result<()> {
-    let provider_id = provider_id_for_pack(&item.pack.path, &item.pack.pack_id, provider_map);
-    let env_value = resolve_env(secrets_env);
-
-    if domain == Domain::Messaging
-        && action == DomainAction::Setup
-        && let Some(manager) = secrets_manager.as_ref()
-    {
-        match secrets_gate::check_provider_secrets(
-            manager,
-            &env_value,
-            tenant,
-            team,
-            &item.pack.path,
-            &provider_id,
-            None,
-            None,
-            false,
-        ) {
-            Ok(Some(missing)) => {
-                let formatted = missing
-                    .iter()
-                    .map(|entry| format!("  - {entry}"))
-                    .collect::<Vec<_>>()
-                    .join("\n");
+    let providers_input = ProvidersInput::load(setup_input)?;
+    let domains = domains::order_domains_for_setup(
+        bundle,
+        domains.to_vec(),
+        &config::demo_provider_dependencies(bundle)?,
+    )?;
+    for domain in &domains {
+        let provider_map = match providers_input.providers_for_domain(*domain) {
+            Some(map) if !map.is_empty() => map,
+            _ => {
                 println!(
-                    "{}",
-                    operator_i18n::trf(
-                        "cli.plan.warn_skip_missing_secrets",
-                        "[warn] skip setup domain={} tenant={} provider={}: missing secrets:\n{}",
-                        &[
-                            domains::domain_name(domain),
-                            tenant,
-                            &provider_id,
-                            &formatted
-                        ]
-                    )
+                    "[demo] no providers configured for domain {}; skipping provider setup",
+                    domains::domain_name(*domain)
                 );
-                return Ok(());
+                continue;
             }
-            Ok(None) => {}
-            Err(err) => {
+        };
+        let tenants = if let Some(tenant) = tenant_override.as_ref() {
+            vec![tenant.clone()]
+        } else {
+            let discovered = discover_tenants(bundle, *domain)?;
+            if discovered.is_empty() {
                 println!(
-                    "{}",
-                    operator_i18n::trf(
-                        "cli.plan.warn_skip_secrets_check_failed",
-                        "[warn] skip setup domain={} tenant={} provider={}: secrets check failed: {}",
-                        &[
-                            domains::domain_name(domain),
-                            tenant,
-                            &provider_id,
-                            &err.to_string()
-                        ]
-                    )
+                    "[demo] no tenants discovered for domain {}; skipping",
+                    domains::domain_name(*domain)
                 );
-                return Ok(());
-            }
-        }
-    }
-
-    let (setup_values, qa_form_spec) = if action == DomainAction::Setup {
-        let (answers, form_spec) = qa_setup_wizard::run_qa_setup(
-            &item.pack.path,
-            &item.pack.pack_id,
-            setup_answers,
-            interactive,
-            None, // no pre-built FormSpec; will try setup.yaml fallback
-        )?;
-        (Some(answers), form_spec)
-    } else {
-        (None, None)
-    };
-    let providers_root = state_root
-        .join("state")
-        .join("runtime")
-        .join(tenant)
-        .join("providers");
-    if let Err(err) = crate::provider_config_envelope::ensure_contract_compatible(
-        &providers_root,
-        &provider_id,
-        &item.flow_id,
-        &item.pack.path,
-        allow_contract_change,
-    ) {
-        operator_log::error(module_path!(), err.to_string());
-        return Err(err);
-    }
-    let current_config = crate::provider_config_envelope::read_provider_config_envelope(
-        &providers_root,
-        &provider_id,
-    )?
-    .map(|envelope| envelope.config);
-    let qa_mode = if action == DomainAction::Setup {
-        Some(crate::component_qa_ops::QaMode::Setup)
-    } else {
-        crate::component_qa_ops::qa_mode_for_flow(&item.flow_id)
-    };
-    let qa_answers = if action == DomainAction::Setup {
-        setup_values.clone().unwrap_or_else(|| json!({}))
-    } else {
-        json!({})
-    };
-    let qa_config_override = if let Some(mode) = qa_mode {
-        if let Err(err) = crate::component_qa_ops::persist_answers_artifacts(
-            &providers_root,
-            &provider_id,
-            mode,
-            &qa_answers,
-        ) {
-            operator_log::warn(
-                module_path!(),
-                format!(
-                    "failed to persist qa answers provider={} mode={} flow={}: {err}",
-                    provider_id,
-                    mode.as_str(),
-                    item.flow_id
-                ),
-            );
-        }
-        match crate::component_qa_ops::apply_answers_via_component_qa(
-            root,
-            domain,
-            tenant,
-            team,
-            &item.pack,
-            &provider_id,
-            mode,
-            current_config.as_ref(),
-            &qa_answers,
-        ) {
-            Ok(value) => value,
-            Err(diag) => {
-                operator_log::error(
+                operator_log::warn(
                     module_path!(),
                     format!(
-                        "component qa failed provider={} flow={} code={} message={}",
-                        provider_id,
-                        item.flow_id,
-                        diag.code.as_str(),
-                        diag.message
+                        "no tenants discovered for domain {}; skipping setup",
+                        domains::domain_name(*domain)
                     ),
                 );
-                return Err(anyhow::anyhow!("{diag}"));
+                continue;
             }
+            discovered
+        };
+        let provider_keys: BTreeSet<String> = provider_map.keys().cloned().collect();
+        let mut map = serde_json::Map::new();
+        for (provider, value) in provider_map {
+            map.insert(provider.clone(), value.clone());
+        }
+        let setup_answers =
+            SetupInputAnswers::new(serde_json::Value::Object(map), provider_keys.clone())?;
+        for tenant in tenants {
+            let report = run_domain_command(DomainRunArgs {
+                root: bundle.to_path_buf(),
+                state_root: None,
+                domain: *domain,
+                action: DomainAction::Setup,
+                tenant,
+                team: team_override.clone(),
+                provider_filter: None,
+                dry_run: false,
+                parallel: 1,
+                allow_missing_setup: true,
+                allow_contract_change: false,
+                backup: false,
+                online: false,
+                secrets_env: Some(env.to_string()),
+                runner_binary: runner_binary.clone(),
+                best_effort: false,
+                discovered_providers: None,
+                setup_input: None,
+                allowed_providers: Some(provider_keys.clone()),
+                preloaded_setup_answers: Some(setup_answers.clone()),
+                public_base_url: public_base_url.clone(),
+                secrets_manager: secrets_manager.clone(),
+                adopted_providers: BTreeSet::new(),
+            })?;
+            print_domain_run_report(&report, PlanFormat::Text)?;
         }
+    }
+    Ok(())
+}
+
+fn demo_state_resolved_manifest_path(root: &Path, tenant: &str, team: Option<&str>) -> PathBuf {
+    root.join("state")
+        .join("resolved")
+        .join(resolved_manifest_filename(tenant, team))
+}
+
+fn demo_bundle_gmap_path(bundle: &Path, tenant: &str, team: Option<&str>) -> PathBuf {
+    let mut path = bundle.join("tenants").join(tenant);
+    if let Some(team) = team {
+        path = path.join("teams").join(team).join("team.gmap");
     } else {
-        None
-    };
+        path = path.join("tenant.gmap");
+    }
+    path
+}
+
+fn copy_resolved_manifest(bundle: &Path, tenant: &str, team: Option<&str>) -> anyhow::Result<()> {
+    project::publish_resolved_manifest(bundle, tenant, team)?;
+    Ok(())
+}
 
-    // Persist secrets and config from QA results when FormSpec is available
-    if let Some(ref config) = qa_config_override
-        && let Some(ref form_spec) = qa_form_spec
-        && action == DomainAction::Setup
-        && let Err(err) = crate::qa_persist::persist_qa_config(
-            &providers_root,
-            &provider_id,
-            config,
-            &item.pack.path,
-            form_spec,
-            backup,
+/// Under `--best-effort`, a failure is reported on stderr instead of failing the command.
+fn report_best_effort_failure(what: &str, detail: &str) {
+    eprintln!(
+        "{}",
+        operator_i18n::trf(
+            "cli.common.best_effort_failed",
+            "Best-effort: {} failed: {}",
+            &[what, detail]
         )
-    {
-        operator_log::warn(
-            module_path!(),
-            format!(
-                "failed to persist qa config provider={}: {err}",
-                provider_id
-            ),
+    );
+}
+
+fn ensure_requirements_flow(pack: &domains::ProviderPack) -> Result<(), String> {
+    if pack.entry_flows.iter().any(|flow| flow == "requirements") {
+        return Ok(());
+    }
+    Err(
+        "requirements flow not found in provider pack; ask the provider pack to include an entry flow named 'requirements'."
+            .to_string(),
+    )
+}
+
+impl From<WizardModeArg> for wizard::WizardMode {
+    fn from(value: WizardModeArg) -> Self {
+        match value {
+            WizardModeArg::Create => wizard::WizardMode::Create,
+            WizardModeArg::Update => wizard::WizardMode::Update,
+            WizardModeArg::Remove => wizard::WizardMode::Remove,
+        }
+    }
+}
+
+/// Prints what a domain run skipped, its plan under `--dry-run`, and how many flows
+/// failed under best-effort.
+fn print_domain_run_report(report: &DomainRunReport, format: PlanFormat) -> anyhow::Result<()> {
+    if !report.skipped_missing_setup.is_empty() {
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "cli.domain.best_effort_skipped_missing_setup",
+                "Best-effort: skipped {} pack(s) missing {}.",
+                &[
+                    &report.skipped_missing_setup.len().to_string(),
+                    domains::config(report.domain).setup_flow
+                ]
+            )
         );
     }
-
-    let public_base_url_ref = public_base_url.as_deref().map(|value| value.as_str());
-    let mut input = build_input_payload(
-        state_root,
-        domain,
-        tenant,
-        team,
-        Some(&item.pack.pack_id),
-        setup_values.as_ref(),
-        public_base_url_ref,
-        &env_value,
-    );
-    if let Some(config) = qa_config_override.as_ref() {
-        input["config"] = config.clone();
-    }
-    if demo_debug_enabled() {
+    if !report.missing_packs.is_empty() {
         println!(
-            "[demo] setup input pack={} flow={} input={}",
-            item.pack.file_name,
-            item.flow_id,
-            serde_json::to_string(&input).unwrap_or_else(|_| "<invalid-json>".to_string())
+            "{}",
+            operator_i18n::trf(
+                "cli.domain.warn_skip_missing_packs",
+                "[warn] skip setup domain={} missing packs: {}",
+                &[
+                    domains::domain_name(report.domain),
+                    &report.missing_packs.join(", ")
+                ]
+            )
         );
     }
-    if action == DomainAction::Setup
-        && let Some(config_value) = qa_config_override.as_ref()
-    {
-        let setup_path = providers_root.join(format!("{provider_id}.setup.json"));
-        crate::providers::write_qa_setup_success_record(
-            &setup_path,
-            &provider_id,
-            &item.flow_id,
-            Some(config_value),
-        )?;
-        if let Err(err) = crate::provider_config_envelope::write_provider_config_envelope(
-            &providers_root,
-            &provider_id,
-            &item.flow_id,
-            config_value,
-            &item.pack.path,
-            backup,
-        ) {
-            operator_log::warn(
-                module_path!(),
-                format!(
-                    "failed to write provider config envelope provider={} flow={}: {err}",
-                    provider_id, item.flow_id
-                ),
-            );
-        }
+    for provider_id in &report.adopted {
         println!(
-            "{} {} -> Success (component-qa)",
-            item.pack.file_name, item.flow_id
+            "{}",
+            operator_i18n::trf(
+                "cli.domain.skip_adopted_provider",
+                "skip setup provider={}: existing webhook registrations adopted",
+                &[provider_id]
+            )
         );
-        return Ok(());
     }
-    if let Some(runner_binary) = runner_binary {
-        let run_dir = state_layout::run_dir(state_root, domain, &item.pack.pack_id, &item.flow_id)?;
-        std::fs::create_dir_all(&run_dir)?;
-        let input_path = run_dir.join("input.json");
-        let input_json = serde_json::to_string_pretty(&input)?;
-        std::fs::write(&input_path, input_json)?;
-
-        let runner_flavor = runner_integration::detect_runner_flavor(runner_binary);
-        let output = runner_integration::run_flow_with_options(
-            runner_binary,
-            &item.pack.path,
-            &item.flow_id,
-            &input,
-            runner_integration::RunFlowOptions {
-                dist_offline,
-                tenant: Some(tenant),
-                team,
-                artifacts_dir: Some(&run_dir),
-                runner_flavor,
-            },
-        )?;
-        write_runner_cli_artifacts(&run_dir, &output)?;
-        if action == DomainAction::Setup {
-            let setup_path = providers_root.join(format!("{provider_id}.setup.json"));
-            crate::providers::write_run_output(&setup_path, &provider_id, &item.flow_id, &output)?;
-            if let Some(config_value) = qa_config_override
-                .clone()
-                .or_else(|| extract_config_for_envelope(output.parsed.as_ref()))
-                && let Err(err) = crate::provider_config_envelope::write_provider_config_envelope(
-                    &providers_root,
-                    &provider_id,
-                    &item.flow_id,
-                    &config_value,
-                    &item.pack.path,
-                    backup,
-                )
-            {
-                operator_log::warn(
-                    module_path!(),
-                    format!(
-                        "failed to write provider config envelope provider={} flow={}: {err}",
-                        provider_id, item.flow_id
-                    ),
-                );
-            }
-        }
-        let exit = format_runner_exit(&output);
-        if output.status.success() {
-            println!(
-                "{}",
-                operator_i18n::trf(
-                    "cli.plan.item_result_ok",
-                    "{} {} -> {}",
-                    &[&item.pack.file_name, &item.flow_id, &exit]
-                )
-            );
-        } else if let Some(summary) = summarize_runner_error(&output) {
+    if report.nothing_matched {
+        if report.demo_bundle {
             println!(
                 "{}",
-                operator_i18n::trf(
-                    "cli.plan.item_result_error_with_summary",
-                    "{} {} -> {} ({})",
-                    &[&item.pack.file_name, &item.flow_id, &exit, &summary]
+                operator_i18n::tr(
+                    "cli.domain.no_provider_packs_matched",
+                    "No provider packs matched. Try --provider <pack_id>."
                 )
             );
         } else {
             println!(
                 "{}",
-                operator_i18n::trf(
-                    "cli.plan.item_result_error",
-                    "{} {} -> {}",
-                    &[&item.pack.file_name, &item.flow_id, &exit]
+                operator_i18n::tr(
+                    "cli.domain.no_provider_packs_matched_or_project_root",
+                    "No provider packs matched. Try --provider <pack_id> or --project-root."
                 )
             );
         }
-    } else {
-        let output = runner_exec::run_provider_pack_flow(runner_exec::RunRequest {
-            root: state_root.to_path_buf(),
-            domain,
-            pack_path: item.pack.path.clone(),
-            pack_label: item.pack.pack_id.clone(),
-            flow_id: item.flow_id.clone(),
-            tenant: tenant.to_string(),
-            team: team.map(|value| value.to_string()),
-            input,
-            dist_offline,
-        })
-        .map_err(|err| {
-            let message = err.to_string();
-            if message.contains("manifest.cbor is invalid") {
-                if let Ok(Some(detail)) = domains::manifest_cbor_issue_detail(&item.pack.path) {
-                    return anyhow::anyhow!(
-                        "pack verification failed for {}: {}",
-                        item.pack.path.display(),
-                        detail
-                    );
-                }
-                return anyhow::anyhow!(
-                    "pack verification failed for {}: {message}",
-                    item.pack.path.display()
-                );
-            }
-            err
-        })?;
-        if action == DomainAction::Setup {
-            let setup_path = providers_root.join(format!("{provider_id}.setup.json"));
-            crate::providers::write_run_result(
-                &setup_path,
-                &provider_id,
-                &item.flow_id,
-                &output.result,
-            )?;
-            if let Some(config_value) = qa_config_override.clone().or_else(|| {
-                extract_config_for_envelope(serde_json::to_value(&output.result).ok().as_ref())
-            }) && let Err(err) = crate::provider_config_envelope::write_provider_config_envelope(
-                &providers_root,
-                &provider_id,
-                &item.flow_id,
-                &config_value,
-                &item.pack.path,
-                backup,
-            ) {
-                operator_log::warn(
-                    module_path!(),
-                    format!(
-                        "failed to write provider config envelope provider={} flow={}: {err}",
-                        provider_id, item.flow_id
-                    ),
-                );
-            }
-        }
+    }
+    if let Some(plan) = &report.dry_run_plan {
+        render_plan(plan, format)?;
+    }
+    if report.failed_flows > 0 {
         println!(
-            "{} {} -> {:?}",
-            item.pack.file_name, item.flow_id, output.result.status
+            "{}",
+            operator_i18n::trf(
+                "cli.domain.best_effort_flows_failed",
+                "Best-effort: {} flow(s) failed.",
+                &[&report.failed_flows.to_string()]
+            )
         );
     }
-
-    Ok(())
-}
-
-fn resolve_demo_runner_binary(
-    config_dir: &Path,
-    runner_binary: Option<PathBuf>,
-) -> anyhow::Result<Option<PathBuf>> {
-    let Some(runner_binary) = runner_binary else {
-        return Ok(None);
-    };
-    let runner_str = runner_binary.to_string_lossy();
-    let (name, explicit) = if looks_like_path_str(&runner_str) {
-        let name = runner_binary
-            .file_name()
-            .and_then(|value| value.to_str())
-            .unwrap_or("greentic-runner")
-            .to_string();
-        (name, Some(runner_binary))
-    } else {
-        (runner_str.to_string(), None)
-    };
-    let resolved = bin_resolver::resolve_binary(
-        &name,
-        &ResolveCtx {
-            config_dir: config_dir.to_path_buf(),
-            explicit_path: explicit,
-        },
-    )?;
-    Ok(Some(resolved))
-}
-
-fn write_runner_cli_artifacts(
-    run_dir: &Path,
-    output: &runner_integration::RunnerOutput,
-) -> anyhow::Result<()> {
-    let run_json = run_dir.join("run.json");
-    let summary_path = run_dir.join("summary.txt");
-    let stdout_path = run_dir.join("stdout.txt");
-    let stderr_path = run_dir.join("stderr.txt");
-
-    let json = serde_json::json!({
-        "status": {
-            "success": output.status.success(),
-            "code": output.status.code(),
-        },
-        "stdout": output.stdout,
-        "stderr": output.stderr,
-        "parsed": output.parsed,
-    });
-    let json = serde_json::to_string_pretty(&json)?;
-    std::fs::write(run_json, json)?;
-    std::fs::write(stdout_path, &output.stdout)?;
-    std::fs::write(stderr_path, &output.stderr)?;
-
-    let summary = format!(
-        "success: {}\nexit_code: {}\n",
-        output.status.success(),
-        output
-            .status
-            .code()
-            .map(|code| code.to_string())
-            .unwrap_or_else(|| "signal".to_string())
-    );
-    std::fs::write(summary_path, summary)?;
     Ok(())
 }
 
-fn format_runner_exit(output: &runner_integration::RunnerOutput) -> String {
-    if let Some(code) = output.status.code() {
-        return format!("exit={code}");
-    }
-    if output.status.success() {
-        return "exit=0".to_string();
-    }
-    "exit=signal".to_string()
-}
-
-fn summarize_runner_error(output: &runner_integration::RunnerOutput) -> Option<String> {
-    output
-        .stderr
-        .lines()
-        .map(|line| line.trim())
-        .find(|line| !line.is_empty())
-        .map(|line| line.to_string())
-}
-
-fn extract_config_for_envelope(parsed: Option<&JsonValue>) -> Option<JsonValue> {
-    let value = parsed?;
-    if let Some(config) = value.get("config") {
-        return Some(config.clone());
-    }
-    Some(value.clone())
-}
-
-pub(crate) fn provider_id_for_pack(
-    pack_path: &Path,
-    fallback: &str,
-    provider_map: Option<&std::collections::BTreeMap<PathBuf, discovery::DetectedProvider>>,
-) -> String {
-    provider_map
-        .and_then(|map| map.get(pack_path))
-        .map(|provider| provider.provider_id.clone())
-        .unwrap_or_else(|| fallback.to_string())
-}
-
-fn looks_like_path_str(value: &str) -> bool {
-    value.contains('/') || value.contains('\\') || Path::new(value).is_absolute()
-}
-
-#[allow(clippy::too_many_arguments)]
-fn build_input_payload(
-    root: &Path,
-    domain: Domain,
-    tenant: &str,
-    team: Option<&str>,
-    pack_id: Option<&str>,
-    setup_answers: Option<&serde_json::Value>,
-    public_base_url: Option<&str>,
-    env: &str,
-) -> serde_json::Value {
-    let mut payload = serde_json::json!({
-        "tenant": tenant,
-    });
-    if let Some(team) = team {
-        payload["team"] = serde_json::Value::String(team.to_string());
-    }
-
-    let resolved_public_base_url = public_base_url.map(|value| value.to_string()).or_else(|| {
-        if matches!(domain, Domain::Messaging | Domain::Events) {
-            read_public_base_url(root, tenant, team)
-        } else {
-            None
+fn render_plan(plan: &[domains::PlannedRun], format: PlanFormat) -> anyhow::Result<()> {
+    match format {
+        PlanFormat::Text => {
+            println!("{}", operator_i18n::tr("cli.domain.plan_header", "Plan:"));
+            for item in plan {
+                println!(
+                    "{}",
+                    operator_i18n::trf(
+                        "cli.domain.plan_item",
+                        "  {} -> {}",
+                        &[&item.pack.file_name, &item.flow_id]
+                    )
+                );
+            }
+            Ok(())
         }
-    });
-
-    if matches!(domain, Domain::Messaging | Domain::Events) {
-        let mut config = serde_json::json!({});
-        if let Some(url) = resolved_public_base_url.as_ref() {
-            payload["public_base_url"] = serde_json::Value::String(url.clone());
-            config["public_base_url"] = serde_json::Value::String(url.clone());
+        PlanFormat::Json => {
+            let json = serde_json::to_string_pretty(plan)?;
+            println!("{json}");
+            Ok(())
         }
-        payload["config"] = config;
-    }
-
-    if let Some(pack_id) = pack_id
-        && let Some(config_map) = payload
-            .get_mut("config")
-            .and_then(|value| value.as_object_mut())
-    {
-        config_map.insert(
-            "id".to_string(),
-            serde_json::Value::String(pack_id.to_string()),
-        );
-    }
-    if let Some(pack_id) = pack_id {
-        payload["id"] = serde_json::Value::String(pack_id.to_string());
-    }
-    if let Some(answers) = setup_answers {
-        payload["setup_answers"] = answers.clone();
-        if let Ok(json) = serde_json::to_string(answers) {
-            payload["answers_json"] = serde_json::Value::String(json);
+        PlanFormat::Yaml => {
+            let yaml = serde_yaml_bw::to_string(plan)?;
+            print!("{yaml}");
+            Ok(())
         }
     }
-    let mut tenant_ctx = serde_json::json!({
-        "env": env,
-        "tenant": tenant,
-        "tenant_id": tenant,
-    });
-    if let Some(team) = team {
-        tenant_ctx["team"] = serde_json::Value::String(team.to_string());
-        tenant_ctx["team_id"] = serde_json::Value::String(team.to_string());
-    }
-    let msg_id = pack_id
-        .map(|value| format!("{value}.setup"))
-        .unwrap_or_else(|| "setup".to_string());
-    let mut metadata = serde_json::json!({});
-    if let Some(url) = resolved_public_base_url {
-        metadata["public_base_url"] = serde_json::Value::String(url);
-    }
-    let msg = serde_json::json!({
-        "id": msg_id,
-        "tenant": tenant_ctx,
-        "channel": "setup",
-        "message": {
-            "id": pack_id
-                .map(|value| format!("{value}.setup_default__collect"))
-                .unwrap_or_else(|| "setup_default__collect".to_string()),
-            "text": "Collect inputs for setup_default."
-        },
-        "session_id": "setup",
-        "metadata": metadata,
-        "reply_scope": "",
-        "text": "Collect inputs for setup_default.",
-        "user_id": "operator",
-    });
-    payload["msg"] = msg;
-    let payload_id = pack_id
-        .map(|value| format!("{value}-setup_default"))
-        .unwrap_or_else(|| "setup_default".to_string());
-    payload["payload"] = serde_json::json!({
-        "id": payload_id,
-        "spec_ref": "assets/setup.yaml"
-    });
-    payload
-}
-
-fn read_public_base_url(root: &Path, tenant: &str, team: Option<&str>) -> Option<String> {
-    let team_id = team.unwrap_or("default");
-    let paths = crate::runtime_state::RuntimePaths::new(root.join("state"), tenant, team_id);
-    let path = crate::cloudflared::public_url_path(&paths);
-    let contents = std::fs::read_to_string(path).ok()?;
-    crate::cloudflared::parse_public_url(&contents)
-        .or_else(|| crate::ngrok::parse_public_url(&contents))
 }
 
 fn parse_kv(input: &str) -> anyhow::Result<(String, JsonValue)> {
@@ -6814,172 +12843,11 @@ fn merge_args(
     Ok(merged)
 }
 
-struct DemoSendMessageArgs<'a> {
-    text: Option<&'a str>,
-    args: &'a JsonMap<String, JsonValue>,
-    tenant: &'a str,
-    team: Option<&'a str>,
-    destinations: &'a [String],
-    to_kind: Option<&'a str>,
-    provider_id: &'a str,
-    channel: &'a str,
-    card: Option<&'a JsonValue>,
-}
-
-fn build_demo_send_message(args: DemoSendMessageArgs<'_>) -> JsonValue {
-    let mut metadata = BTreeMap::new();
-    if let Some(card_value) = args.card
-        && let Ok(card_str) = serde_json::to_string(card_value)
-    {
-        metadata.insert("adaptive_card".to_string(), card_str);
-    }
-    for (key, value) in args.args {
-        metadata.insert(key.clone(), value.to_string());
-    }
-    let env_value = std::env::var("GREENTIC_ENV").unwrap_or_else(|_| "local".to_string());
-    let env = EnvId::try_from(env_value.clone())
-        .unwrap_or_else(|_| EnvId::try_from("local").expect("local env invalid"));
-    let tenant_id = TenantId::try_from(args.tenant.to_string())
-        .unwrap_or_else(|_| TenantId::try_from("demo").expect("demo tenant invalid"));
-    let mut tenant_ctx = TenantCtx::new(env, tenant_id.clone());
-    if let Some(team_value) = args.team
-        && let Ok(team_id) = TeamId::try_from(team_value.to_string())
-    {
-        tenant_ctx = tenant_ctx.with_team(Some(team_id));
-    }
-    tenant_ctx = tenant_ctx
-        .with_session(Uuid::new_v4().to_string())
-        .with_flow(Uuid::new_v4().to_string())
-        .with_node("demo".to_string())
-        .with_provider(args.provider_id.to_string())
-        .with_attempt(1);
-
-    let to_kind_owned = args.to_kind.map(|value| value.to_string());
-    let to = args
-        .destinations
-        .iter()
-        .map(|value| Destination {
-            id: value.clone(),
-            kind: to_kind_owned.clone(),
-        })
-        .collect::<Vec<_>>();
-    let envelope = ChannelMessageEnvelope {
-        id: Uuid::new_v4().to_string(),
-        tenant: tenant_ctx,
-        channel: args.channel.to_string(),
-        session_id: Uuid::new_v4().to_string(),
-        reply_scope: None,
-        from: None,
-        to,
-        correlation_id: None,
-        text: args.text.map(|value| value.to_string()),
-        attachments: Vec::new(),
-        metadata,
-    };
-    serde_json::to_value(envelope).unwrap_or(JsonValue::Null)
-}
-
-fn debug_print_envelope(op_label: &str, envelope: &JsonValue) {
-    if !demo_debug_enabled() {
-        return;
-    }
-    match serde_json::to_string_pretty(envelope) {
-        Ok(body) => println!(
-            "{}",
-            operator_i18n::trf(
-                "cli.demo.debug.before_envelope",
-                "[demo] before {} envelope:\n{}",
-                &[op_label, &body]
-            )
-        ),
-        Err(err) => println!(
-            "{}",
-            operator_i18n::trf(
-                "cli.demo.debug.before_envelope_serialize_failed",
-                "[demo] before {} envelope: failed to serialize envelope: {}",
-                &[op_label, &err.to_string()]
-            )
-        ),
-    }
-}
-
-fn debug_print_render_plan_output(output: &RenderPlanOutV1) {
-    if !demo_debug_enabled() {
-        return;
-    }
-    match serde_json::to_string_pretty(&output) {
-        Ok(body) => println!(
-            "{}",
-            operator_i18n::trf(
-                "cli.demo.debug.after_render_plan",
-                "[demo] after render_plan output:\n{}",
-                &[&body]
-            )
-        ),
-        Err(err) => println!(
-            "{}",
-            operator_i18n::trf(
-                "cli.demo.debug.after_render_plan_serialize_failed",
-                "[demo] after render_plan output: failed to serialize output: {}",
-                &[&err.to_string()]
-            )
-        ),
-    }
-}
-
-fn debug_print_encode_input(input: &EncodeInV1) {
-    if !demo_debug_enabled() {
-        return;
-    }
-    match serde_json::to_string_pretty(&input) {
-        Ok(body) => println!(
-            "{}",
-            operator_i18n::trf(
-                "cli.demo.debug.encode_input",
-                "[demo] encode input:\n{}",
-                &[&body]
-            )
-        ),
-        Err(err) => println!(
-            "{}",
-            operator_i18n::trf(
-                "cli.demo.debug.encode_input_serialize_failed",
-                "[demo] encode input: failed to serialize input: {}",
-                &[&err.to_string()]
-            )
-        ),
-    }
-}
-
-fn debug_print_encode_output(output: &EncodeOutV1) {
-    if !demo_debug_enabled() {
-        return;
-    }
-    match serde_json::to_string_pretty(&output) {
-        Ok(body) => println!(
-            "{}",
-            operator_i18n::trf(
-                "cli.demo.debug.after_encode",
-                "[demo] after encode output:\n{}",
-                &[&body]
-            )
-        ),
-        Err(err) => println!(
-            "{}",
-            operator_i18n::trf(
-                "cli.demo.debug.after_encode_serialize_failed",
-                "[demo] after encode output: failed to serialize output: {}",
-                &[&err.to_string()]
-            )
-        ),
-    }
-}
-
 fn debug_print_send_payload_output(output: &SendPayloadOutV1) {
     if !demo_debug_enabled() {
         return;
     }
-    match serde_json::to_string_pretty(&output) {
+    match redaction::to_string_pretty(&output) {
         Ok(body) => println!(
             "{}",
             operator_i18n::trf(
@@ -6999,24 +12867,6 @@ fn debug_print_send_payload_output(output: &SendPayloadOutV1) {
     }
 }
 
-fn provider_channel(provider: &str) -> String {
-    if let Some((domain, suffix)) = provider.split_once('-') {
-        format!("{domain}.{suffix}")
-    } else {
-        provider.replace('-', ".")
-    }
-}
-
-fn config_value_display(value: &JsonValue) -> String {
-    match value {
-        JsonValue::String(text) => text.clone(),
-        JsonValue::Number(number) => number.to_string(),
-        JsonValue::Bool(flag) => flag.to_string(),
-        JsonValue::Null => "<null>".to_string(),
-        other => serde_json::to_string(other).unwrap_or_else(|_| other.to_string()),
-    }
-}
-
 fn format_requirements_output(value: &JsonValue) -> Option<String> {
     let JsonValue::Object(map) = value else {
         return None;
@@ -7078,12 +12928,22 @@ fn format_requirements_item(value: &JsonValue) -> String {
     serde_json::to_string(value).unwrap_or_else(|_| value.to_string())
 }
 
-impl From<DomainArg> for Domain {
-    fn from(value: DomainArg) -> Self {
+impl From<AuditOutcomeArg> for audit::AuditOutcome {
+    fn from(value: AuditOutcomeArg) -> Self {
+        match value {
+            AuditOutcomeArg::Ok => audit::AuditOutcome::Ok,
+            AuditOutcomeArg::Failed => audit::AuditOutcome::Failed,
+            AuditOutcomeArg::Denied => audit::AuditOutcome::Denied,
+        }
+    }
+}
+
+impl From<RunStatusArg> for run_record::RunRecordStatus {
+    fn from(value: RunStatusArg) -> Self {
         match value {
-            DomainArg::Messaging => Domain::Messaging,
-            DomainArg::Events => Domain::Events,
-            DomainArg::Secrets => Domain::Secrets,
+            RunStatusArg::Success => run_record::RunRecordStatus::Success,
+            RunStatusArg::Failed => run_record::RunRecordStatus::Failed,
+            RunStatusArg::Denied => run_record::RunRecordStatus::Denied,
         }
     }
 }
@@ -7144,39 +13004,13 @@ mod tests {
             file_name: "demo.gtpack".to_string(),
             path: PathBuf::from("demo.gtpack"),
             entry_flows: vec!["setup_default".to_string()],
+            depends_on: Vec::new(),
+            verify_flows: Vec::new(),
         };
         let error = ensure_requirements_flow(&pack).unwrap_err();
         assert!(error.contains("requirements flow not found"));
     }
 
-    #[test]
-    fn filter_allowed_providers_moves_missing() {
-        let mut packs = vec![
-            domains::ProviderPack {
-                pack_id: "messaging-telegram".to_string(),
-                file_name: "telegram.gtpack".to_string(),
-                path: PathBuf::from("telegram.gtpack"),
-                entry_flows: vec!["setup_default".to_string()],
-            },
-            domains::ProviderPack {
-                pack_id: "messaging-slack".to_string(),
-                file_name: "slack.gtpack".to_string(),
-                path: PathBuf::from("slack.gtpack"),
-                entry_flows: vec!["setup_default".to_string()],
-            },
-        ];
-        let allowed = vec![
-            "messaging-telegram".to_string(),
-            "messaging-email".to_string(),
-        ]
-        .into_iter()
-        .collect::<BTreeSet<_>>();
-        let missing = filter_packs_by_allowed(&mut packs, &allowed);
-        assert_eq!(packs.len(), 1);
-        assert_eq!(packs[0].pack_id, "messaging-telegram");
-        assert_eq!(missing, vec!["messaging-email".to_string()]);
-    }
-
     #[test]
     fn select_demo_providers_respects_filter() {
         let providers = vec![
@@ -7186,6 +13020,8 @@ mod tests {
                     file_name: "messaging-telegram.gtpack".to_string(),
                     path: PathBuf::from("messaging-telegram.gtpack"),
                     entry_flows: Vec::new(),
+                    depends_on: Vec::new(),
+                    verify_flows: Vec::new(),
                 },
             },
             DemoProviderInfo {
@@ -7194,6 +13030,8 @@ mod tests {
                     file_name: "messaging-slack.gtpack".to_string(),
                     path: PathBuf::from("messaging-slack.gtpack"),
                     entry_flows: Vec::new(),
+                    depends_on: Vec::new(),
+                    verify_flows: Vec::new(),
                 },
             },
         ];
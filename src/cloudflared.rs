@@ -2,11 +2,17 @@ use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
+use anyhow::anyhow;
+
+use crate::config::DemoCloudflaredConfig;
+use crate::operator_error::tunnel_unavailable;
 use crate::runtime_state::{RuntimePaths, atomic_write};
 use crate::supervisor::{self, ServiceId, ServiceSpec};
 
 const SERVICE_ID: &str = "cloudflared";
 const URL_SUFFIX: &str = ".trycloudflare.com";
+/// Logged by cloudflared once a named tunnel has an edge connection.
+const NAMED_READY_MARKER: &str = "Registered tunnel connection";
 
 #[derive(Clone)]
 pub struct CloudflaredConfig {
@@ -14,12 +20,84 @@ pub struct CloudflaredConfig {
     pub local_port: u16,
     pub extra_args: Vec<String>,
     pub restart: bool,
+    /// Run this named tunnel instead of a quick tunnel.
+    pub tunnel: Option<NamedTunnel>,
+}
+
+/// A tunnel created with `cloudflared tunnel create`, reachable at a fixed hostname.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NamedTunnel {
+    pub name: String,
+    pub hostname: String,
+    pub credentials_file: Option<PathBuf>,
+}
+
+impl NamedTunnel {
+    /// Looks up `name` under `services.cloudflared.tunnels`, resolving a relative
+    /// credentials file against `base_dir`.
+    pub fn from_config(
+        config: &DemoCloudflaredConfig,
+        name: &str,
+        base_dir: &Path,
+    ) -> anyhow::Result<Self> {
+        let tunnel = config.tunnels.get(name).ok_or_else(|| {
+            anyhow!(
+                "cloudflared tunnel {name} is not configured; add it under services.cloudflared.tunnels in greentic.demo.yaml"
+            )
+        })?;
+        let hostname = tunnel
+            .hostname
+            .trim()
+            .trim_start_matches("https://")
+            .trim_end_matches('/')
+            .to_string();
+        if hostname.is_empty() {
+            return Err(anyhow!("cloudflared tunnel {name} has an empty hostname"));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            hostname,
+            credentials_file: tunnel.credentials_file.as_ref().map(|path| {
+                if path.is_absolute() {
+                    path.clone()
+                } else {
+                    base_dir.join(path)
+                }
+            }),
+        })
+    }
+
+    pub fn url(&self) -> String {
+        format!("https://{}", self.hostname)
+    }
 }
 
 pub struct CloudflaredHandle {
     pub url: String,
     pub pid: u32,
     pub log_path: PathBuf,
+    /// URL recorded by the previous run for this tenant/team, if any.
+    pub previous_url: Option<String>,
+}
+
+impl CloudflaredHandle {
+    /// Whether webhooks registered against the previous URL now point elsewhere.
+    pub fn url_changed(&self) -> bool {
+        self.previous_url.as_deref() != Some(self.url.as_str())
+    }
+}
+
+/// Starts (or reuses) the named tunnel when one is configured, otherwise a quick tunnel.
+pub fn start_tunnel(
+    paths: &RuntimePaths,
+    config: &CloudflaredConfig,
+    log_path: &Path,
+) -> anyhow::Result<CloudflaredHandle> {
+    match &config.tunnel {
+        Some(tunnel) => start_named_tunnel(paths, config, tunnel, log_path),
+        None => start_quick_tunnel(paths, config, log_path),
+    }
+    .map_err(|err| tunnel_unavailable("cloudflared", err))
 }
 
 pub fn start_quick_tunnel(
@@ -29,7 +107,12 @@ pub fn start_quick_tunnel(
 ) -> anyhow::Result<CloudflaredHandle> {
     let pid_path = paths.pid_path(SERVICE_ID);
     let url_path = public_url_path(paths);
-    if config.restart {
+    let previous_url = read_public_url(&url_path)?;
+    // A running named tunnel does not serve a quick-tunnel URL.
+    let running_named = previous_url
+        .as_deref()
+        .is_some_and(|url| !is_clean_trycloudflare_url(url));
+    if config.restart || running_named {
         let _ = supervisor::stop_pidfile(&pid_path, 2_000);
     }
 
@@ -37,11 +120,12 @@ pub fn start_quick_tunnel(
         && supervisor::is_running(pid)
     {
         let log_path_buf = log_path.to_path_buf();
-        if let Some(url) = read_public_url(&url_path)? {
+        if let Some(url) = previous_url.clone() {
             return Ok(CloudflaredHandle {
                 url,
                 pid,
                 log_path: log_path_buf.clone(),
+                previous_url,
             });
         }
         let url = discover_public_url(&log_path_buf, Duration::from_secs(10))?;
@@ -50,6 +134,7 @@ pub fn start_quick_tunnel(
             url,
             pid,
             log_path: log_path_buf,
+            previous_url,
         });
     }
 
@@ -76,19 +161,118 @@ pub fn start_quick_tunnel(
         url,
         pid: handle.pid,
         log_path: handle.log_path,
+        previous_url,
+    })
+}
+
+/// Runs `cloudflared tunnel run <NAME>`. The URL is the configured hostname, so it is
+/// known up front; startup only waits for the first edge connection.
+pub fn start_named_tunnel(
+    paths: &RuntimePaths,
+    config: &CloudflaredConfig,
+    tunnel: &NamedTunnel,
+    log_path: &Path,
+) -> anyhow::Result<CloudflaredHandle> {
+    let pid_path = paths.pid_path(SERVICE_ID);
+    let url_path = public_url_path(paths);
+    let previous_url = read_public_url(&url_path)?;
+    let url = tunnel.url();
+    // Whatever is running serves a different URL (a quick tunnel or another name).
+    if config.restart || previous_url.as_deref() != Some(url.as_str()) {
+        let _ = supervisor::stop_pidfile(&pid_path, 2_000);
+    }
+
+    if let Some(pid) = read_pid(&pid_path)?
+        && supervisor::is_running(pid)
+    {
+        return Ok(CloudflaredHandle {
+            url,
+            pid,
+            log_path: log_path.to_path_buf(),
+            previous_url,
+        });
+    }
+
+    let spec = ServiceSpec {
+        id: ServiceId::new(SERVICE_ID)?,
+        argv: named_tunnel_argv(config, tunnel),
+        cwd: None,
+        env: BTreeMap::new(),
+    };
+    let handle = supervisor::spawn_service(paths, spec, Some(log_path.to_path_buf()))?;
+    wait_for_log_marker(
+        &handle.log_path,
+        NAMED_READY_MARKER,
+        Duration::from_secs(15),
+    )
+    .map_err(|err| anyhow!("cloudflared tunnel {}: {err}", tunnel.name))?;
+    write_public_url(&url_path, &url)?;
+    Ok(CloudflaredHandle {
+        url,
+        pid: handle.pid,
+        log_path: handle.log_path,
+        previous_url,
     })
 }
 
+fn named_tunnel_argv(config: &CloudflaredConfig, tunnel: &NamedTunnel) -> Vec<String> {
+    let mut argv = vec![
+        config.binary.to_string_lossy().to_string(),
+        "tunnel".to_string(),
+        "--no-autoupdate".to_string(),
+        "run".to_string(),
+        "--url".to_string(),
+        format!("http://127.0.0.1:{}", config.local_port),
+    ];
+    if let Some(credentials) = &tunnel.credentials_file {
+        argv.push("--credentials-file".to_string());
+        argv.push(credentials.to_string_lossy().to_string());
+    }
+    argv.extend(config.extra_args.iter().cloned());
+    argv.push(tunnel.name.clone());
+    argv
+}
+
 pub fn public_url_path(paths: &RuntimePaths) -> PathBuf {
     paths.runtime_root().join("public_base_url.txt")
 }
 
+/// The URL recorded for this tenant/team by the running tunnel, if any.
+pub fn current_public_url(paths: &RuntimePaths) -> anyhow::Result<Option<String>> {
+    read_public_url(&public_url_path(paths))
+}
+
+/// Picks up a quick tunnel that reconnected under a new hostname: the newest
+/// `*.trycloudflare.com` URL in `log_path` replaces the recorded one. Named tunnel URLs
+/// are left alone. Returns the URL when it was updated.
+pub fn refresh_public_url(paths: &RuntimePaths, log_path: &Path) -> anyhow::Result<Option<String>> {
+    let url_path = public_url_path(paths);
+    let recorded = read_public_url(&url_path)?;
+    if recorded
+        .as_deref()
+        .is_some_and(|url| !is_clean_trycloudflare_url(url))
+    {
+        return Ok(None);
+    }
+    if !log_path.exists() {
+        return Ok(None);
+    }
+    let contents = std::fs::read_to_string(log_path)?;
+    match last_url_in_text(&contents) {
+        Some(url) if recorded.as_deref() != Some(url.as_str()) => {
+            write_public_url(&url_path, &url)?;
+            Ok(Some(url))
+        }
+        _ => Ok(None),
+    }
+}
+
 pub fn parse_public_url(contents: &str) -> Option<String> {
     let trimmed = contents.trim();
     if trimmed.is_empty() {
         return None;
     }
-    if is_clean_trycloudflare_url(trimmed) {
+    if is_clean_trycloudflare_url(trimmed) || is_clean_https_url(trimmed) {
         return Some(trimmed.to_string());
     }
     find_url_in_text(contents)
@@ -125,6 +309,22 @@ fn discover_public_url(log_path: &Path, timeout: Duration) -> anyhow::Result<Str
     }
 }
 
+fn wait_for_log_marker(log_path: &Path, marker: &str, timeout: Duration) -> anyhow::Result<()> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if log_path.exists() && std::fs::read_to_string(log_path)?.contains(marker) {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            return Err(anyhow!(
+                "timed out waiting for a tunnel connection in {}",
+                log_path.display()
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
 fn find_url_in_text(contents: &str) -> Option<String> {
     let mut offset = 0;
     while let Some(pos) = contents[offset..].find("https://") {
@@ -143,6 +343,20 @@ fn find_url_in_text(contents: &str) -> Option<String> {
     None
 }
 
+/// The log is appended across restarts, so the newest URL is the last one.
+fn last_url_in_text(contents: &str) -> Option<String> {
+    let mut last = None;
+    let mut rest = contents;
+    while let Some(url) = find_url_in_text(rest) {
+        let end = rest
+            .find(url.as_str())
+            .map_or(rest.len(), |pos| pos + url.len());
+        rest = &rest[end..];
+        last = Some(url);
+    }
+    last
+}
+
 fn is_clean_trycloudflare_url(value: &str) -> bool {
     if !value.starts_with("https://") {
         return false;
@@ -153,6 +367,13 @@ fn is_clean_trycloudflare_url(value: &str) -> bool {
     value.ends_with(URL_SUFFIX)
 }
 
+/// A bare `https://host` as written for named tunnels.
+fn is_clean_https_url(value: &str) -> bool {
+    value
+        .strip_prefix("https://")
+        .is_some_and(|host| !host.is_empty() && !host.contains(char::is_whitespace))
+}
+
 fn read_pid(path: &Path) -> anyhow::Result<Option<u32>> {
     if !path.exists() {
         return Ok(None);
@@ -164,3 +385,87 @@ fn read_pid(path: &Path) -> anyhow::Result<Option<u32>> {
     }
     Ok(Some(trimmed.parse()?))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DemoCloudflaredTunnelConfig;
+
+    #[test]
+    fn named_tunnel_runs_by_name_with_credentials() {
+        let config = DemoCloudflaredConfig {
+            tunnels: BTreeMap::from([(
+                "demo".to_string(),
+                DemoCloudflaredTunnelConfig {
+                    hostname: "https://demo.example.com/".to_string(),
+                    credentials_file: Some(PathBuf::from("tunnel.json")),
+                },
+            )]),
+        };
+        let tunnel = NamedTunnel::from_config(&config, "demo", Path::new("/bundle")).unwrap();
+        assert_eq!(tunnel.url(), "https://demo.example.com");
+        assert!(NamedTunnel::from_config(&config, "other", Path::new("/bundle")).is_err());
+
+        let cloudflared = CloudflaredConfig {
+            binary: PathBuf::from("cloudflared"),
+            local_port: 8081,
+            extra_args: Vec::new(),
+            restart: false,
+            tunnel: Some(tunnel.clone()),
+        };
+        let argv = named_tunnel_argv(&cloudflared, &tunnel);
+        assert_eq!(argv[1..4], ["tunnel", "--no-autoupdate", "run"]);
+        assert!(argv.contains(&"http://127.0.0.1:8081".to_string()));
+        assert!(
+            argv.contains(
+                &Path::new("/bundle")
+                    .join("tunnel.json")
+                    .display()
+                    .to_string()
+            )
+        );
+        assert_eq!(argv.last().map(String::as_str), Some("demo"));
+    }
+
+    #[test]
+    fn named_tunnel_urls_round_trip_through_the_url_file() {
+        assert_eq!(
+            parse_public_url("https://demo.example.com\n").as_deref(),
+            Some("https://demo.example.com")
+        );
+        assert_eq!(
+            parse_public_url("https://abc.trycloudflare.com").as_deref(),
+            Some("https://abc.trycloudflare.com")
+        );
+        assert_eq!(parse_public_url("https://"), None);
+    }
+
+    #[test]
+    fn refresh_takes_the_newest_quick_tunnel_url_from_the_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = RuntimePaths::new(dir.path().join("state"), "demo", "default");
+        let log = dir.path().join("cloudflared.log");
+        std::fs::write(
+            &log,
+            "INF |  https://old-one.trycloudflare.com  |\nINF |  https://new-one.trycloudflare.com  |\n",
+        )
+        .unwrap();
+        write_public_url(
+            &public_url_path(&paths),
+            "https://old-one.trycloudflare.com",
+        )
+        .unwrap();
+        assert_eq!(
+            refresh_public_url(&paths, &log).unwrap().as_deref(),
+            Some("https://new-one.trycloudflare.com")
+        );
+        assert_eq!(refresh_public_url(&paths, &log).unwrap(), None);
+
+        write_public_url(&public_url_path(&paths), "https://demo.example.com").unwrap();
+        assert_eq!(refresh_public_url(&paths, &log).unwrap(), None);
+        assert_eq!(
+            current_public_url(&paths).unwrap().as_deref(),
+            Some("https://demo.example.com")
+        );
+    }
+}
@@ -0,0 +1,204 @@
+//! Shell completion: `greentic-operator completions <shell>` prints a registration
+//! script that calls back into the binary (`COMPLETE=<shell> greentic-operator …`),
+//! so the subcommand tree and the values below stay in sync with the installed binary.
+//!
+//! `--provider`, `--pack`, `--tenant`, and `--team` values are completed by scanning
+//! the bundle named by `--bundle` on the command line being completed, or the
+//! current directory.
+
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use clap_complete::engine::{ArgValueCompleter, CompletionCandidate};
+use clap_complete::env::{Bash, EnvCompleter, Fish, Powershell, Zsh};
+
+use crate::discovery;
+
+/// Environment variable the registration scripts set when asking for completions.
+pub const COMPLETE_ENV: &str = "COMPLETE";
+const BIN_NAME: &str = "greentic-operator";
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+/// The registration script for `shell`, to be sourced from the shell's profile.
+pub fn write_registration(shell: Shell, out: &mut dyn std::io::Write) -> std::io::Result<()> {
+    let completer: &dyn EnvCompleter = match shell {
+        Shell::Bash => &Bash,
+        Shell::Zsh => &Zsh,
+        Shell::Fish => &Fish,
+        Shell::Powershell => &Powershell,
+    };
+    completer.write_registration(COMPLETE_ENV, BIN_NAME, BIN_NAME, BIN_NAME, out)
+}
+
+/// Attaches the bundle-aware value completers to every `provider`, `pack`, `tenant`,
+/// and `team` argument in the tree.
+pub fn with_value_completers(command: clap::Command) -> clap::Command {
+    let command = command.mut_args(|arg| {
+        let completer = match arg.get_id().as_str() {
+            "provider" => ArgValueCompleter::new(complete_providers),
+            "pack" => ArgValueCompleter::new(complete_packs),
+            "tenant" => ArgValueCompleter::new(complete_tenants),
+            "team" => ArgValueCompleter::new(complete_teams),
+            _ => return arg,
+        };
+        arg.add(completer)
+    });
+    let names = command
+        .get_subcommands()
+        .map(|sub| sub.get_name().to_string())
+        .collect::<Vec<_>>();
+    names.into_iter().fold(command, |command, name| {
+        command.mut_subcommand(name, with_value_completers)
+    })
+}
+
+fn complete_providers(current: &OsStr) -> Vec<CompletionCandidate> {
+    let bundle = bundle_from_args(std::env::args());
+    let Ok(found) =
+        discovery::discover_with_options(&bundle, discovery::DiscoveryOptions { cbor_only: true })
+    else {
+        return Vec::new();
+    };
+    let providers = found
+        .providers
+        .into_iter()
+        .map(|provider| (provider.provider_id, Some(provider.domain)));
+    candidates(current, providers)
+}
+
+fn complete_packs(current: &OsStr) -> Vec<CompletionCandidate> {
+    let bundle = bundle_from_args(std::env::args());
+    let mut packs = Vec::new();
+    for dir in [bundle.join("packs"), bundle.join("providers")] {
+        collect_pack_stems(&dir, &mut packs);
+    }
+    candidates(current, packs.into_iter().map(|pack| (pack, None)))
+}
+
+fn complete_tenants(current: &OsStr) -> Vec<CompletionCandidate> {
+    let bundle = bundle_from_args(std::env::args());
+    candidates(
+        current,
+        subdirs(&bundle.join("tenants"))
+            .into_iter()
+            .map(|tenant| (tenant, None)),
+    )
+}
+
+fn complete_teams(current: &OsStr) -> Vec<CompletionCandidate> {
+    let bundle = bundle_from_args(std::env::args());
+    let mut teams = Vec::new();
+    for tenant in subdirs(&bundle.join("tenants")) {
+        let dir = bundle.join("tenants").join(&tenant).join("teams");
+        teams.extend(
+            subdirs(&dir)
+                .into_iter()
+                .map(|team| (team, Some(format!("tenant {tenant}")))),
+        );
+    }
+    candidates(current, teams)
+}
+
+fn candidates(
+    current: &OsStr,
+    values: impl IntoIterator<Item = (String, Option<String>)>,
+) -> Vec<CompletionCandidate> {
+    let prefix = current.to_string_lossy();
+    let mut values = values
+        .into_iter()
+        .filter(|(value, _)| value.starts_with(prefix.as_ref()))
+        .collect::<Vec<_>>();
+    values.sort();
+    values.dedup_by(|a, b| a.0 == b.0);
+    values
+        .into_iter()
+        .map(|(value, help)| CompletionCandidate::new(value).help(help.map(Into::into)))
+        .collect()
+}
+
+/// The `--bundle` value on the command line being completed, else the current directory.
+fn bundle_from_args(args: impl IntoIterator<Item = String>) -> PathBuf {
+    let mut args = args.into_iter();
+    let mut bundle = None;
+    while let Some(arg) = args.next() {
+        if arg == "--bundle" {
+            bundle = args.next();
+        } else if let Some(value) = arg.strip_prefix("--bundle=") {
+            bundle = Some(value.to_string());
+        }
+    }
+    bundle
+        .filter(|value| !value.trim().is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+fn collect_pack_stems(dir: &Path, out: &mut Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        if path.is_dir() {
+            collect_pack_stems(&path, out);
+        } else if path.extension().is_some_and(|ext| ext == "gtpack")
+            && let Some(stem) = path.file_stem().and_then(|stem| stem.to_str())
+        {
+            out.push(stem.to_string());
+        }
+    }
+}
+
+fn subdirs(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundle_is_read_from_the_completed_command_line() {
+        let args = ["greentic-operator", "--", "demo", "send", "--bundle", "b1"];
+        assert_eq!(
+            bundle_from_args(args.map(String::from)),
+            PathBuf::from("b1")
+        );
+        let args = ["greentic-operator", "demo", "send", "--bundle=b2"];
+        assert_eq!(
+            bundle_from_args(args.map(String::from)),
+            PathBuf::from("b2")
+        );
+        assert_eq!(bundle_from_args(Vec::new()), PathBuf::from("."));
+    }
+
+    #[test]
+    fn tenants_and_teams_come_from_the_bundle_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("tenants/demo/teams/default")).unwrap();
+        std::fs::create_dir_all(dir.path().join("tenants/acme")).unwrap();
+        let tenants = subdirs(&dir.path().join("tenants"));
+        let listed = candidates(OsStr::new("d"), tenants.into_iter().map(|t| (t, None)));
+        assert_eq!(
+            listed
+                .iter()
+                .map(|candidate| candidate.get_value().to_string_lossy().to_string())
+                .collect::<Vec<_>>(),
+            vec!["demo".to_string()]
+        );
+    }
+}
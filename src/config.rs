@@ -1,16 +1,26 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
-use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
 
-#[derive(Clone, Debug, Deserialize, Default)]
+use crate::interpolate;
+use crate::operator_error::OperatorError;
+use crate::operator_log;
+
+pub const ENV_GATEWAY_PORT: &str = "GREENTIC_OPERATOR_GATEWAY_PORT";
+pub const ENV_GATEWAY_LISTEN_ADDR: &str = "GREENTIC_OPERATOR_GATEWAY_LISTEN_ADDR";
+
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
 pub struct OperatorConfig {
     #[serde(default)]
     pub services: Option<OperatorServicesConfig>,
     #[serde(default)]
     pub binaries: BTreeMap<String, String>,
 }
-#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum DomainEnabledMode {
     #[default]
@@ -29,7 +39,7 @@ impl DomainEnabledMode {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Default)]
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
 pub struct OperatorServicesConfig {
     #[serde(default)]
     pub messaging: DomainServicesConfig,
@@ -37,7 +47,7 @@ pub struct OperatorServicesConfig {
     pub events: DomainServicesConfig,
 }
 
-#[derive(Clone, Debug, Deserialize, Default)]
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
 pub struct DomainServicesConfig {
     #[serde(default)]
     pub enabled: DomainEnabledMode,
@@ -45,7 +55,7 @@ pub struct DomainServicesConfig {
     pub components: Vec<ServiceComponentConfig>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ServiceComponentConfig {
     pub id: String,
     pub binary: String,
@@ -65,7 +75,12 @@ pub fn load_operator_config(root: &Path) -> anyhow::Result<Option<OperatorConfig
     {
         return Ok(None);
     }
-    let config: OperatorConfig = serde_yaml_bw::from_str(&contents)?;
+    let config: OperatorConfig =
+        serde_yaml_bw::from_str(&contents).map_err(|err| OperatorError::ConfigInvalid {
+            path: path.clone(),
+            detail: err.to_string(),
+        })?;
+    warn_unknown_keys(&path, &contents, &config);
     Ok(Some(config))
 }
 
@@ -77,7 +92,7 @@ pub fn binary_override(
     config.and_then(|config| config_binary_path(config, name, config_dir))
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct DemoConfig {
     #[serde(default = "default_demo_tenant")]
     pub tenant: String,
@@ -87,6 +102,28 @@ pub struct DemoConfig {
     pub services: DemoServicesConfig,
     #[serde(default)]
     pub providers: Option<std::collections::BTreeMap<String, DemoProviderConfig>>,
+    #[serde(default)]
+    pub runs: DemoRunsConfig,
+    #[serde(default)]
+    pub secrets: DemoSecretsConfig,
+    #[serde(default)]
+    pub quotas: DemoQuotasConfig,
+    #[serde(default)]
+    pub limits: DemoLimitsConfig,
+    #[serde(default)]
+    pub http: DemoHttpConfig,
+    #[serde(default)]
+    pub circuit_breaker: DemoCircuitBreakerConfig,
+    /// Domains beyond messaging/events/secrets, by name.
+    #[serde(default)]
+    pub domains: BTreeMap<String, DemoDomainConfig>,
+    /// Refuse packs without a signature from a key in `pack_keys.trusted`.
+    #[serde(default)]
+    pub require_signed_packs: bool,
+    #[serde(default)]
+    pub pack_keys: DemoPackKeysConfig,
+    #[serde(default)]
+    pub runner: DemoRunnerConfig,
 }
 
 impl Default for DemoConfig {
@@ -96,11 +133,573 @@ impl Default for DemoConfig {
             team: default_demo_team(),
             services: DemoServicesConfig::default(),
             providers: None,
+            runs: DemoRunsConfig::default(),
+            secrets: DemoSecretsConfig::default(),
+            quotas: DemoQuotasConfig::default(),
+            limits: DemoLimitsConfig::default(),
+            http: DemoHttpConfig::default(),
+            circuit_breaker: DemoCircuitBreakerConfig::default(),
+            domains: BTreeMap::new(),
+            require_signed_packs: false,
+            pack_keys: DemoPackKeysConfig::default(),
+            runner: DemoRunnerConfig::default(),
+        }
+    }
+}
+
+/// The greentic-runner the bundle is pinned to. Without `version` any runner found
+/// on disk is used as before.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DemoRunnerConfig {
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Download the pinned version into `state/bin` when no matching runner is found.
+    #[serde(default)]
+    pub auto_download: bool,
+    /// Release URL; `{version}` and `{target}` (e.g. `x86_64-linux`) are substituted.
+    #[serde(default)]
+    pub download_url: Option<String>,
+    /// Expected SHA-256 (hex) of the download, by target. Targets without one are
+    /// checked against `<download_url>.sha256`.
+    #[serde(default)]
+    pub sha256: BTreeMap<String, String>,
+    #[serde(default)]
+    pub pool: DemoRunnerPoolConfig,
+}
+
+/// Warm `--runner-binary` processes reused across invocations (see
+/// `demo::runner_pool`). Off unless `enabled`.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct DemoRunnerPoolConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Runners kept per pack; calls beyond this spawn a one-shot runner.
+    #[serde(default = "default_runner_pool_max_workers")]
+    pub max_workers: usize,
+    /// A runner unused this long is stopped.
+    #[serde(default = "default_runner_pool_idle_timeout_secs")]
+    pub idle_timeout_secs: u64,
+    /// How long a new runner has to report that it is ready.
+    #[serde(default = "default_runner_pool_handshake_timeout_secs")]
+    pub handshake_timeout_secs: u64,
+}
+
+impl Default for DemoRunnerPoolConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_workers: default_runner_pool_max_workers(),
+            idle_timeout_secs: default_runner_pool_idle_timeout_secs(),
+            handshake_timeout_secs: default_runner_pool_handshake_timeout_secs(),
+        }
+    }
+}
+
+fn default_runner_pool_max_workers() -> usize {
+    2
+}
+
+fn default_runner_pool_idle_timeout_secs() -> u64 {
+    300
+}
+
+fn default_runner_pool_handshake_timeout_secs() -> u64 {
+    10
+}
+
+/// Keys for signed and encrypted packs, by key id; values are base64.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DemoPackKeysConfig {
+    /// Ed25519 public keys whose pack signatures are accepted.
+    #[serde(default)]
+    pub trusted: BTreeMap<String, String>,
+    /// AES-256 keys that open `*.enc` pack members. Prefer `${ENV}` or
+    /// `${secret:...}` references over inline values.
+    #[serde(default)]
+    pub decryption: BTreeMap<String, String>,
+}
+
+/// Conventions of a custom domain; unset fields follow the built-in domains
+/// (`providers/<name>`, `setup_default`, `diagnostics`, no verify flows).
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct DemoDomainConfig {
+    #[serde(default)]
+    pub providers_dir: Option<String>,
+    #[serde(default)]
+    pub setup_flow: Option<String>,
+    #[serde(default)]
+    pub diagnostics_flow: Option<String>,
+    #[serde(default)]
+    pub verify_flows: Vec<String>,
+}
+
+/// Retention for run records under `state/runs`; `0` disables a limit.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DemoRunsConfig {
+    #[serde(default = "default_runs_max_runs")]
+    pub max_runs: usize,
+    #[serde(default = "default_runs_max_age_days")]
+    pub max_age_days: u64,
+}
+
+impl Default for DemoRunsConfig {
+    fn default() -> Self {
+        Self {
+            max_runs: default_runs_max_runs(),
+            max_age_days: default_runs_max_age_days(),
+        }
+    }
+}
+
+fn default_runs_max_runs() -> usize {
+    500
+}
+
+fn default_runs_max_age_days() -> u64 {
+    14
+}
+
+/// Invocation quotas enforced by the runner host. `default` applies to every tenant;
+/// `tenants.<tenant>` and `tenants.<tenant>.teams.<team>` override it field by field.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DemoQuotasConfig {
+    #[serde(default)]
+    pub default: QuotaLimits,
+    #[serde(default)]
+    pub tenants: BTreeMap<String, TenantQuotaConfig>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct TenantQuotaConfig {
+    #[serde(flatten)]
+    pub limits: QuotaLimits,
+    #[serde(default)]
+    pub teams: BTreeMap<String, QuotaLimits>,
+}
+
+/// Unset fields are unlimited.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaLimits {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_invocations_per_minute: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_payload_bytes: Option<u64>,
+}
+
+impl QuotaLimits {
+    fn or(self, fallback: QuotaLimits) -> QuotaLimits {
+        QuotaLimits {
+            max_invocations_per_minute: self
+                .max_invocations_per_minute
+                .or(fallback.max_invocations_per_minute),
+            max_concurrent: self.max_concurrent.or(fallback.max_concurrent),
+            max_payload_bytes: self.max_payload_bytes.or(fallback.max_payload_bytes),
+        }
+    }
+}
+
+impl DemoQuotasConfig {
+    pub fn limits_for(&self, tenant: &str, team: Option<&str>) -> QuotaLimits {
+        let Some(tenant_config) = self.tenants.get(tenant) else {
+            return self.default;
+        };
+        let tenant_limits = tenant_config.limits.or(self.default);
+        team.and_then(|team| tenant_config.teams.get(team))
+            .map(|limits| limits.or(tenant_limits))
+            .unwrap_or(tenant_limits)
+    }
+}
+
+/// Per-invocation resource limits enforced by the runner host, overridable per
+/// tenant and team like [`DemoQuotasConfig`].
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DemoLimitsConfig {
+    #[serde(default)]
+    pub default: InvocationLimits,
+    #[serde(default)]
+    pub tenants: BTreeMap<String, TenantLimitsConfig>,
+    /// Delegated cgroup v2 directory; when set, spawned runners get a child cgroup
+    /// there and `max_memory_mb` becomes its `memory.max`.
+    #[serde(default)]
+    pub cgroup_parent: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct TenantLimitsConfig {
+    #[serde(flatten)]
+    pub limits: InvocationLimits,
+    #[serde(default)]
+    pub teams: BTreeMap<String, InvocationLimits>,
+}
+
+/// Unset fields are unlimited. `max_cpu_secs` and `max_memory_mb` only apply to
+/// runners spawned from `--runner-binary`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvocationLimits {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_bytes: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cpu_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_memory_mb: Option<u64>,
+}
+
+impl InvocationLimits {
+    fn or(self, fallback: InvocationLimits) -> InvocationLimits {
+        InvocationLimits {
+            timeout_secs: self.timeout_secs.or(fallback.timeout_secs),
+            max_output_bytes: self.max_output_bytes.or(fallback.max_output_bytes),
+            max_cpu_secs: self.max_cpu_secs.or(fallback.max_cpu_secs),
+            max_memory_mb: self.max_memory_mb.or(fallback.max_memory_mb),
+        }
+    }
+}
+
+impl DemoLimitsConfig {
+    pub fn limits_for(&self, tenant: &str, team: Option<&str>) -> InvocationLimits {
+        let Some(tenant_config) = self.tenants.get(tenant) else {
+            return self.default;
+        };
+        let tenant_limits = tenant_config.limits.or(self.default);
+        team.and_then(|team| tenant_config.teams.get(team))
+            .map(|limits| limits.or(tenant_limits))
+            .unwrap_or(tenant_limits)
+    }
+}
+
+/// Per-provider circuit breaker for `send_payload` and `ingest_http`. After
+/// `failure_threshold` consecutive failures the provider is skipped for
+/// `cooldown_secs`; a threshold of `0` disables the breaker.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct DemoCircuitBreakerConfig {
+    #[serde(default = "default_breaker_failure_threshold")]
+    pub failure_threshold: u32,
+    #[serde(default = "default_breaker_cooldown_secs")]
+    pub cooldown_secs: u64,
+}
+
+impl Default for DemoCircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: default_breaker_failure_threshold(),
+            cooldown_secs: default_breaker_cooldown_secs(),
+        }
+    }
+}
+
+fn default_breaker_failure_threshold() -> u32 {
+    5
+}
+
+fn default_breaker_cooldown_secs() -> u64 {
+    60
+}
+
+/// How provider sends reach the network. Relative paths are resolved against the
+/// bundle root.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DemoHttpConfig {
+    #[serde(default)]
+    pub transport: HttpTransportKind,
+    /// Falls back to `HTTPS_PROXY`/`HTTP_PROXY`/`ALL_PROXY`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// PEM file with extra trusted root certificates, for TLS-intercepting proxies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_cert: Option<PathBuf>,
+    #[serde(default)]
+    pub insecure_tls: bool,
+    #[serde(default = "default_http_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Record to or replay from a cassette; implies `transport: operator`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cassette: Option<CassetteConfig>,
+}
+
+impl Default for DemoHttpConfig {
+    fn default() -> Self {
+        Self {
+            transport: HttpTransportKind::default(),
+            proxy: None,
+            ca_cert: None,
+            insecure_tls: false,
+            timeout_secs: default_http_timeout_secs(),
+            cassette: None,
+        }
+    }
+}
+
+fn default_http_timeout_secs() -> u64 {
+    30
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HttpTransportKind {
+    /// The pack's `send_payload` makes the request itself.
+    #[default]
+    Pack,
+    /// The operator makes the request the encoded payload describes.
+    Operator,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CassetteConfig {
+    pub path: PathBuf,
+    pub mode: CassetteMode,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CassetteMode {
+    Record,
+    Replay,
+}
+
+/// Where secrets come from. `pack` (the default) keeps using the backend declared by
+/// the bundle's secrets manager pack.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DemoSecretsConfig {
+    #[serde(default)]
+    pub backend: DemoSecretsBackend,
+    #[serde(default)]
+    pub vault: DemoVaultConfig,
+    #[serde(default)]
+    pub aws: DemoAwsSecretsConfig,
+    #[serde(default)]
+    pub dev_store: DemoDevStoreConfig,
+    /// What to do when the backend cannot be opened and secrets would come from
+    /// `GREENTIC_SECRET__*` env vars instead. Unset means `warn` when
+    /// `GREENTIC_ALLOW_ENV_SECRETS=1` and `deny` otherwise.
+    #[serde(default)]
+    pub fallback: Option<SecretsFallbackPolicy>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SecretsFallbackPolicy {
+    Allow,
+    Warn,
+    Deny,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DemoSecretsBackend {
+    #[default]
+    Pack,
+    DevStore,
+    Env,
+    Vault,
+    Aws,
+}
+
+/// HashiCorp Vault KV backend. Each provider maps to one Vault secret (rendered from
+/// `path_template`) whose fields are the provider's secret keys.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DemoVaultConfig {
+    /// Falls back to `VAULT_ADDR`.
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
+    pub namespace: Option<String>,
+    #[serde(default = "default_vault_mount")]
+    pub mount: String,
+    #[serde(default = "default_vault_kv_version")]
+    pub kv_version: u8,
+    /// Placeholders: `{env}`, `{tenant}`, `{team}`, `{provider}`.
+    #[serde(default = "default_vault_path_template")]
+    pub path_template: String,
+    #[serde(default)]
+    pub auth: DemoVaultAuthConfig,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DemoVaultAuthConfig {
+    #[serde(default)]
+    pub method: VaultAuthMethod,
+    #[serde(default = "default_vault_token_env")]
+    pub token_env: String,
+    #[serde(default)]
+    pub role_id: Option<String>,
+    #[serde(default = "default_vault_role_id_env")]
+    pub role_id_env: String,
+    #[serde(default = "default_vault_secret_id_env")]
+    pub secret_id_env: String,
+    #[serde(default = "default_vault_approle_mount")]
+    pub approle_mount: String,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VaultAuthMethod {
+    #[default]
+    Token,
+    AppRole,
+}
+
+impl Default for DemoVaultConfig {
+    fn default() -> Self {
+        Self {
+            address: None,
+            namespace: None,
+            mount: default_vault_mount(),
+            kv_version: default_vault_kv_version(),
+            path_template: default_vault_path_template(),
+            auth: DemoVaultAuthConfig::default(),
+        }
+    }
+}
+
+impl Default for DemoVaultAuthConfig {
+    fn default() -> Self {
+        Self {
+            method: VaultAuthMethod::default(),
+            token_env: default_vault_token_env(),
+            role_id: None,
+            role_id_env: default_vault_role_id_env(),
+            secret_id_env: default_vault_secret_id_env(),
+            approle_mount: default_vault_approle_mount(),
+        }
+    }
+}
+
+/// AWS Secrets Manager / SSM Parameter Store backend, driven through the `aws` CLI.
+/// Each canonical URI maps to one secret (or parameter) named from `name_template`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DemoAwsSecretsConfig {
+    #[serde(default)]
+    pub service: AwsSecretsService,
+    /// Falls back to the CLI's own resolution (`AWS_REGION`, profile config).
+    #[serde(default)]
+    pub region: Option<String>,
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Placeholders: `{env}`, `{tenant}`, `{team}`, `{provider}`, `{key}`. SSM names
+    /// get a leading `/` when the template lacks one.
+    #[serde(default = "default_aws_name_template")]
+    pub name_template: String,
+    /// How long values read from AWS are reused; `0` disables the cache.
+    #[serde(default = "default_aws_cache_ttl_seconds")]
+    pub cache_ttl_seconds: u64,
+    #[serde(default = "default_aws_binary")]
+    pub binary: String,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AwsSecretsService {
+    #[default]
+    SecretsManager,
+    Ssm,
+}
+
+impl Default for DemoAwsSecretsConfig {
+    fn default() -> Self {
+        Self {
+            service: AwsSecretsService::default(),
+            region: None,
+            profile: None,
+            name_template: default_aws_name_template(),
+            cache_ttl_seconds: default_aws_cache_ttl_seconds(),
+            binary: default_aws_binary(),
+        }
+    }
+}
+
+fn default_aws_name_template() -> String {
+    "greentic/{env}/{tenant}/{team}/{provider}/{key}".to_string()
+}
+
+fn default_aws_cache_ttl_seconds() -> u64 {
+    300
+}
+
+fn default_aws_binary() -> String {
+    "aws".to_string()
+}
+
+/// At-rest encryption for the local dev store. `encryption` is the target applied by
+/// `demo secrets rekey`; once a store is encrypted every write through the operator
+/// stays encrypted. A new store is encrypted on its first write, and a plaintext store
+/// refuses writes until it is rekeyed.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DemoDevStoreConfig {
+    #[serde(default)]
+    pub encryption: DevStoreEncryption,
+    #[serde(default = "default_dev_store_passphrase_env")]
+    pub passphrase_env: String,
+    #[serde(default = "default_dev_store_keychain_service")]
+    pub keychain_service: String,
+    #[serde(default = "default_dev_store_keychain_account")]
+    pub keychain_account: String,
+}
+
+impl Default for DemoDevStoreConfig {
+    fn default() -> Self {
+        Self {
+            encryption: DevStoreEncryption::default(),
+            passphrase_env: default_dev_store_passphrase_env(),
+            keychain_service: default_dev_store_keychain_service(),
+            keychain_account: default_dev_store_keychain_account(),
         }
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DevStoreEncryption {
+    #[default]
+    None,
+    Passphrase,
+    Keychain,
+}
+
+fn default_dev_store_passphrase_env() -> String {
+    "GREENTIC_DEV_SECRETS_PASSPHRASE".to_string()
+}
+
+fn default_dev_store_keychain_service() -> String {
+    "greentic-dev-secrets".to_string()
+}
+
+fn default_dev_store_keychain_account() -> String {
+    "default".to_string()
+}
+
+fn default_vault_mount() -> String {
+    "secret".to_string()
+}
+
+fn default_vault_kv_version() -> u8 {
+    2
+}
+
+fn default_vault_path_template() -> String {
+    "greentic/{env}/{tenant}/{team}/{provider}".to_string()
+}
+
+fn default_vault_token_env() -> String {
+    "VAULT_TOKEN".to_string()
+}
+
+fn default_vault_role_id_env() -> String {
+    "VAULT_ROLE_ID".to_string()
+}
+
+fn default_vault_secret_id_env() -> String {
+    "VAULT_SECRET_ID".to_string()
+}
+
+fn default_vault_approle_mount() -> String {
+    "approle".to_string()
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
 pub struct DemoServicesConfig {
     #[serde(default)]
     pub nats: DemoNatsConfig,
@@ -112,9 +711,130 @@ pub struct DemoServicesConfig {
     pub subscriptions: DemoSubscriptionsConfig,
     #[serde(default)]
     pub events: DemoEventsConfig,
+    #[serde(default)]
+    pub cloudflared: DemoCloudflaredConfig,
+    #[serde(default)]
+    pub ingress: DemoIngressConfig,
+    /// Sidecar processes (a mock API, a database) started with the demo and stopped
+    /// with it, in the order listed.
+    #[serde(default)]
+    pub extra: Vec<DemoExtraService>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DemoExtraService {
+    /// Service id for its pidfile and log (`logs/<name>.log`).
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Relative to the bundle; defaults to the bundle root.
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+    #[serde(default)]
+    pub health: Option<DemoHealthCheck>,
+    #[serde(default)]
+    pub restart: DemoRestartPolicy,
+    /// Restarts allowed before the service is left down.
+    #[serde(default = "default_extra_max_restarts")]
+    pub max_restarts: u32,
+}
+
+/// Readiness probe `demo start` waits for before going on. Give `http` (any 2xx
+/// answers) or `tcp` (`host:port` accepts a connection).
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DemoHealthCheck {
+    #[serde(default)]
+    pub http: Option<String>,
+    #[serde(default)]
+    pub tcp: Option<String>,
+    #[serde(default = "default_health_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum DemoRestartPolicy {
+    #[default]
+    Never,
+    OnFailure,
+    Always,
+}
+
+fn default_extra_max_restarts() -> u32 {
+    5
+}
+
+fn default_health_timeout_secs() -> u64 {
+    30
+}
+
+/// Extra URL shapes for the HTTP ingress, tried before the default
+/// `/v1/{domain}/ingress/{provider}/{tenant}/{team?}/{handler?}` scheme.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DemoIngressConfig {
+    /// Short names accepted wherever a provider id is expected, e.g. `tg: messaging-telegram`.
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+    /// Checked in order; the first match wins.
+    #[serde(default)]
+    pub routes: Vec<DemoIngressRoute>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DemoIngressRoute {
+    /// Path prefix. `{tenant}`, `{team}`, `{provider}` and `{handler}` capture a
+    /// segment; `{token}` must equal the route's token.
+    pub path: String,
+    /// Only match requests for this `Host` (`*.example.com` matches subdomains).
+    #[serde(default)]
+    pub host: Option<String>,
+    #[serde(default = "default_ingress_route_domain")]
+    pub domain: String,
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub tenant: Option<String>,
+    #[serde(default)]
+    pub team: Option<String>,
+    #[serde(default)]
+    pub handler: Option<String>,
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Env var holding the token, so it can stay out of the bundle.
+    #[serde(default)]
+    pub token_env: Option<String>,
+    /// Path handed to the provider instead of the request path; takes the same
+    /// placeholders plus `{rest}` for the segments after the prefix.
+    #[serde(default)]
+    pub rewrite: Option<String>,
+}
+
+fn default_ingress_route_domain() -> String {
+    "messaging".to_string()
 }
 
-#[derive(Clone, Debug, Deserialize)]
+/// Named cloudflared tunnels selectable with `demo start --cloudflared-tunnel <NAME>`.
+/// Unlike quick tunnels they keep the same hostname across restarts.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DemoCloudflaredConfig {
+    #[serde(default)]
+    pub tunnels: BTreeMap<String, DemoCloudflaredTunnelConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DemoCloudflaredTunnelConfig {
+    /// Hostname routed to the tunnel (`cloudflared tunnel route dns`).
+    pub hostname: String,
+    /// Tunnel credentials JSON; relative paths are resolved against the bundle.
+    /// cloudflared falls back to `~/.cloudflared/<tunnel id>.json` when unset.
+    #[serde(default)]
+    pub credentials_file: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct DemoNatsConfig {
     #[serde(default = "default_true")]
     pub enabled: bool,
@@ -122,9 +842,25 @@ pub struct DemoNatsConfig {
     pub url: String,
     #[serde(default)]
     pub spawn: DemoNatsSpawnConfig,
+    #[serde(default)]
+    pub jetstream: DemoJetStreamConfig,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+/// Stream limits applied when `demo start --nats=jetstream` creates the per-tenant
+/// ingress/egress streams. Existing streams are not modified.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DemoJetStreamConfig {
+    #[serde(default = "default_jetstream_storage")]
+    pub storage: String,
+    #[serde(default = "default_jetstream_replicas")]
+    pub replicas: u32,
+    #[serde(default = "default_jetstream_max_msgs")]
+    pub max_msgs: i64,
+    #[serde(default = "default_jetstream_max_age_hours")]
+    pub max_age_hours: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct DemoNatsSpawnConfig {
     #[serde(default = "default_true")]
     pub enabled: bool,
@@ -134,7 +870,7 @@ pub struct DemoNatsSpawnConfig {
     pub args: Vec<String>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct DemoGatewayConfig {
     #[serde(default = "default_gateway_binary")]
     pub binary: String,
@@ -146,15 +882,68 @@ pub struct DemoGatewayConfig {
     pub args: Vec<String>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct DemoEgressConfig {
     #[serde(default = "default_egress_binary")]
     pub binary: String,
     #[serde(default)]
     pub args: Vec<String>,
+    #[serde(default)]
+    pub retry: DemoEgressRetryConfig,
+}
+
+/// Retry policy for operator-driven egress sends (`demo ingress --end-to-end`, DLQ replay).
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DemoEgressRetryConfig {
+    #[serde(default = "default_retry_max_attempts")]
+    pub max_attempts: u32,
+    #[serde(default = "default_retry_base_delay_ms")]
+    pub base_delay_ms: u64,
+    #[serde(default = "default_retry_max_delay_ms")]
+    pub max_delay_ms: u64,
+    #[serde(default = "default_retry_jitter_ms")]
+    pub jitter_ms: u64,
+    /// Node error codes treated as retryable even when the provider does not flag them.
+    #[serde(default = "default_retry_on")]
+    pub retry_on: Vec<String>,
+}
+
+impl Default for DemoEgressRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_retry_max_attempts(),
+            base_delay_ms: default_retry_base_delay_ms(),
+            max_delay_ms: default_retry_max_delay_ms(),
+            jitter_ms: default_retry_jitter_ms(),
+            retry_on: default_retry_on(),
+        }
+    }
+}
+
+fn default_retry_max_attempts() -> u32 {
+    1
+}
+
+fn default_retry_base_delay_ms() -> u64 {
+    500
+}
+
+fn default_retry_max_delay_ms() -> u64 {
+    30_000
 }
 
-#[derive(Clone, Debug, Deserialize)]
+fn default_retry_jitter_ms() -> u64 {
+    250
+}
+
+fn default_retry_on() -> Vec<String> {
+    ["rate-limited", "timeout", "unavailable"]
+        .into_iter()
+        .map(str::to_string)
+        .collect()
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct DemoSubscriptionsConfig {
     #[serde(default = "default_subscriptions_mode")]
     pub mode: DemoSubscriptionsMode,
@@ -174,7 +963,7 @@ impl Default for DemoSubscriptionsConfig {
     }
 }
 
-#[derive(Clone, Copy, Debug, Deserialize, Default)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum DemoSubscriptionsMode {
     #[default]
@@ -186,7 +975,7 @@ fn default_subscriptions_mode() -> DemoSubscriptionsMode {
     DemoSubscriptionsMode::LegacyGsm
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct DemoSubscriptionsUniversalConfig {
     #[serde(default = "default_universal_renew_interval")]
     pub renew_interval_seconds: u64,
@@ -194,6 +983,8 @@ pub struct DemoSubscriptionsUniversalConfig {
     pub renew_skew_minutes: u64,
     #[serde(default)]
     pub desired: Vec<DemoDesiredSubscription>,
+    #[serde(default)]
+    pub alerts: SubscriptionAlertsConfig,
 }
 
 impl Default for DemoSubscriptionsUniversalConfig {
@@ -202,10 +993,46 @@ impl Default for DemoSubscriptionsUniversalConfig {
             renew_interval_seconds: default_universal_renew_interval(),
             renew_skew_minutes: default_universal_renew_skew(),
             desired: Vec::new(),
+            alerts: SubscriptionAlertsConfig::default(),
         }
     }
 }
 
+/// Hooks fired when a stored subscription enters the renew skew window or keeps
+/// failing to renew.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SubscriptionAlertsConfig {
+    /// Consecutive renew failures before `renew_failing` fires; 0 disables it.
+    #[serde(default = "default_renew_failure_threshold")]
+    pub renew_failure_threshold: u32,
+    #[serde(default)]
+    pub hooks: Vec<SubscriptionAlertHook>,
+}
+
+impl Default for SubscriptionAlertsConfig {
+    fn default() -> Self {
+        Self {
+            renew_failure_threshold: default_renew_failure_threshold(),
+            hooks: Vec::new(),
+        }
+    }
+}
+
+fn default_renew_failure_threshold() -> u32 {
+    3
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionAlertHook {
+    /// Program and arguments; the alert is passed in `GREENTIC_ALERT*` variables.
+    Exec(Vec<String>),
+    /// URL the alert is POSTed to as JSON.
+    Webhook(String),
+    /// Subject the alert is published on, over the scope's bus.
+    Nats(String),
+}
+
 fn default_universal_renew_interval() -> u64 {
     60
 }
@@ -214,7 +1041,7 @@ fn default_universal_renew_skew() -> u64 {
     10
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct DemoDesiredSubscription {
     pub provider: String,
     pub resource: String,
@@ -234,21 +1061,121 @@ fn default_change_types() -> Vec<String> {
     vec!["created".to_string()]
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct AuthUserConfig {
     pub user_id: String,
     pub token_key: String,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct DemoEventsConfig {
     #[serde(default)]
     pub enabled: DomainEnabledMode,
     #[serde(default = "default_events_components")]
     pub components: Vec<ServiceComponentConfig>,
+    #[serde(default)]
+    pub kafka: DemoKafkaConfig,
+    /// Per-handler timer schedule overrides keyed by `<provider>/<handler_id>` or
+    /// `<provider>`.
+    #[serde(default)]
+    pub timers: BTreeMap<String, DemoTimerOverride>,
+    /// What `demo start` does about timer windows missed while it was not running.
+    #[serde(default)]
+    pub timer_catch_up: TimerCatchUpPolicy,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TimerCatchUpPolicy {
+    /// Log the missed windows and wait for the next scheduled fire.
+    #[default]
+    Skip,
+    /// Fire once on startup for all missed windows together.
+    RunOnce,
+    /// Fire once per missed window, oldest first.
+    RunAll,
+}
+
+impl TimerCatchUpPolicy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Skip => "skip",
+            Self::RunOnce => "run-once",
+            Self::RunAll => "run-all",
+        }
+    }
 }
 
-#[derive(Clone, Debug, Deserialize)]
+/// Overrides the schedule a provider pack declares for a timer handler. `cron` wins
+/// over `calendar`, which wins over `interval_seconds`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DemoTimerOverride {
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    #[serde(default)]
+    pub interval_seconds: Option<u64>,
+    #[serde(default)]
+    pub cron: Option<String>,
+    #[serde(default)]
+    pub calendar: Option<DemoTimerCalendar>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DemoTimerCalendar {
+    /// UTC times of day, `HH:MM`.
+    #[serde(default)]
+    pub at: Vec<String>,
+    /// Weekday names (`mon`, `tuesday`, ...); empty means every day.
+    #[serde(default)]
+    pub days: Vec<String>,
+}
+
+/// Optional Kafka bridge for the events domain, driven through `kcat`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DemoKafkaConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub brokers: Vec<String>,
+    #[serde(default = "default_kafka_topic_prefix")]
+    pub topic_prefix: String,
+    #[serde(default)]
+    pub direction: KafkaBridgeDirection,
+    #[serde(default = "default_kafka_consumer_group")]
+    pub consumer_group: String,
+    #[serde(default = "default_kafka_binary")]
+    pub binary: String,
+    #[serde(default)]
+    pub auth: DemoKafkaAuthConfig,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KafkaBridgeDirection {
+    /// Mirror events received by the operator to Kafka.
+    #[default]
+    Out,
+    /// Feed Kafka messages into the events default flow.
+    In,
+    Both,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct DemoKafkaAuthConfig {
+    /// librdkafka `security.protocol`, e.g. `SASL_SSL`.
+    #[serde(default)]
+    pub security_protocol: Option<String>,
+    /// librdkafka `sasl.mechanisms`, e.g. `PLAIN` or `SCRAM-SHA-256`.
+    #[serde(default)]
+    pub sasl_mechanism: Option<String>,
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Name of the environment variable holding the SASL password.
+    #[serde(default)]
+    pub password_env: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct DemoMsgraphSubscriptionsConfig {
     #[serde(default = "default_true")]
     pub enabled: bool,
@@ -260,7 +1187,7 @@ pub struct DemoMsgraphSubscriptionsConfig {
     pub args: Vec<String>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct DemoProviderConfig {
     #[serde(default)]
     pub pack: Option<String>,
@@ -268,6 +1195,9 @@ pub struct DemoProviderConfig {
     pub setup_flow: Option<String>,
     #[serde(default)]
     pub verify_flow: Option<String>,
+    /// Providers (keys of `providers`, or pack ids) whose setup must run first.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
 }
 
 impl Default for DemoNatsConfig {
@@ -276,6 +1206,18 @@ impl Default for DemoNatsConfig {
             enabled: true,
             url: default_nats_url(),
             spawn: DemoNatsSpawnConfig::default(),
+            jetstream: DemoJetStreamConfig::default(),
+        }
+    }
+}
+
+impl Default for DemoJetStreamConfig {
+    fn default() -> Self {
+        Self {
+            storage: default_jetstream_storage(),
+            replicas: default_jetstream_replicas(),
+            max_msgs: default_jetstream_max_msgs(),
+            max_age_hours: default_jetstream_max_age_hours(),
         }
     }
 }
@@ -306,6 +1248,7 @@ impl Default for DemoEgressConfig {
         Self {
             binary: default_egress_binary(),
             args: Vec::new(),
+            retry: DemoEgressRetryConfig::default(),
         }
     }
 }
@@ -326,16 +1269,317 @@ impl Default for DemoEventsConfig {
         Self {
             enabled: DomainEnabledMode::Auto,
             components: default_events_components(),
+            kafka: DemoKafkaConfig::default(),
+            timers: BTreeMap::new(),
+            timer_catch_up: TimerCatchUpPolicy::default(),
         }
     }
 }
 
+impl Default for DemoKafkaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            brokers: Vec::new(),
+            topic_prefix: default_kafka_topic_prefix(),
+            direction: KafkaBridgeDirection::default(),
+            consumer_group: default_kafka_consumer_group(),
+            binary: default_kafka_binary(),
+            auth: DemoKafkaAuthConfig::default(),
+        }
+    }
+}
+
+fn default_kafka_topic_prefix() -> String {
+    "greentic.events".to_string()
+}
+
+fn default_kafka_consumer_group() -> String {
+    "greentic-operator".to_string()
+}
+
+fn default_kafka_binary() -> String {
+    "kcat".to_string()
+}
+
+/// `depends_on` of each provider in the bundle's `greentic.demo.yaml`; empty when the
+/// bundle has none.
+pub fn demo_provider_dependencies(root: &Path) -> anyhow::Result<BTreeMap<String, Vec<String>>> {
+    let path = root.join("greentic.demo.yaml");
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let config = load_demo_config(&path)?;
+    Ok(config
+        .providers
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(_, provider)| !provider.depends_on.is_empty())
+        .map(|(name, provider)| (name, provider.depends_on))
+        .collect())
+}
+
+/// Reads `greentic.demo.yaml` with its `${ENV}` references resolved. `${secret:...}`
+/// references are kept as written: they resolve where the value is used, in the
+/// caller's tenant/team (see [`interpolate::interpolate_secrets`]), so loading the
+/// file never opens the secrets store.
 pub fn load_demo_config(path: &Path) -> anyhow::Result<DemoConfig> {
-    let contents = std::fs::read_to_string(path)?;
-    let config: DemoConfig = serde_yaml_bw::from_str(&contents)?;
+    let written = std::fs::read_to_string(path)?;
+    let contents =
+        interpolate::interpolate_env(&written).map_err(|err| OperatorError::ConfigInvalid {
+            path: path.to_path_buf(),
+            detail: format!("{err:#}"),
+        })?;
+    let mut config: DemoConfig =
+        serde_yaml_bw::from_str(&contents).map_err(|err| OperatorError::ConfigInvalid {
+            path: path.to_path_buf(),
+            detail: err.to_string(),
+        })?;
+    warn_unknown_keys(path, &contents, &config);
+    apply_gateway_env_overrides(&mut config);
     Ok(config)
 }
 
+/// [`load_demo_config`], falling back to the defaults (with the gateway env overrides)
+/// and a logged warning when the file is missing or invalid.
+pub(crate) fn load_demo_config_or_default(path: &Path) -> DemoConfig {
+    match load_demo_config(path) {
+        Ok(value) => value,
+        Err(err) => {
+            operator_log::warn(
+                module_path!(),
+                format!(
+                    "failed to load {}: {err}; using default values",
+                    path.display()
+                ),
+            );
+            let mut config = DemoConfig::default();
+            apply_gateway_env_overrides(&mut config);
+            config
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigIssueSeverity {
+    Error,
+    Warning,
+}
+
+/// One problem found by [`validate_config_file`].
+#[derive(Clone, Debug, Serialize)]
+pub struct ConfigIssue {
+    pub severity: ConfigIssueSeverity,
+    /// 1-based line in the file, when it could be located.
+    pub line: Option<usize>,
+    /// Dotted key path, e.g. `services.gateway.listen_adr`.
+    pub key: Option<String>,
+    pub message: String,
+}
+
+impl ConfigIssue {
+    pub fn is_error(&self) -> bool {
+        self.severity == ConfigIssueSeverity::Error
+    }
+}
+
+/// Which schema [`validate_config_file`] checks against.
+#[derive(Clone, Copy, Debug)]
+pub enum ConfigFileKind {
+    /// `greentic.yaml`
+    Operator,
+    /// `greentic.demo.yaml`
+    Demo,
+}
+
+/// Strict check of a config file: YAML syntax and type errors are errors, keys the
+/// schema does not know (and that serde would silently ignore) are warnings.
+pub fn validate_config_file(path: &Path, kind: ConfigFileKind) -> anyhow::Result<Vec<ConfigIssue>> {
+    let contents = std::fs::read_to_string(path)?;
+    Ok(match kind {
+        ConfigFileKind::Operator => validate_contents::<OperatorConfig>(&contents),
+        // Secret references stay as written; only their syntax is checked here.
+        ConfigFileKind::Demo => match interpolate::interpolate_env(&contents) {
+            Ok(contents) => validate_contents::<DemoConfig>(&contents),
+            Err(err) => vec![ConfigIssue {
+                severity: ConfigIssueSeverity::Error,
+                line: None,
+                key: None,
+                message: format!("{err:#}"),
+            }],
+        },
+    })
+}
+
+fn validate_contents<T: DeserializeOwned + Serialize>(contents: &str) -> Vec<ConfigIssue> {
+    if contents
+        .lines()
+        .all(|line| line.trim().is_empty() || line.trim().starts_with('#'))
+    {
+        return Vec::new();
+    }
+    let parse_error = |err: serde_yaml_bw::Error| ConfigIssue {
+        severity: ConfigIssueSeverity::Error,
+        line: err.location().map(|location| location.line()),
+        key: None,
+        message: err.to_string(),
+    };
+    if let Err(err) = serde_yaml_bw::from_str::<JsonValue>(contents) {
+        return vec![parse_error(err)];
+    }
+    match serde_yaml_bw::from_str::<T>(contents) {
+        Ok(config) => unknown_keys(contents, &config),
+        Err(err) => vec![parse_error(err)],
+    }
+}
+
+/// Logs each unknown key once per process; the config is reloaded by many commands
+/// and by `demo start` on every change.
+fn warn_unknown_keys<T: Serialize>(path: &Path, contents: &str, config: &T) {
+    static WARNED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+    for issue in unknown_keys(contents, config) {
+        let message = format!(
+            "{}:{}: {}",
+            path.display(),
+            issue.line.unwrap_or(0),
+            issue.message
+        );
+        let mut warned = WARNED.lock().unwrap_or_else(|err| err.into_inner());
+        if warned.insert(message.clone()) {
+            operator_log::warn(module_path!(), message);
+        }
+    }
+}
+
+/// Keys present in the file but absent when the parsed config is serialized back,
+/// i.e. keys serde dropped.
+fn unknown_keys<T: Serialize>(contents: &str, config: &T) -> Vec<ConfigIssue> {
+    let (Ok(raw), Ok(known)) = (
+        serde_yaml_bw::from_str::<JsonValue>(contents),
+        serde_json::to_value(config),
+    ) else {
+        return Vec::new();
+    };
+    let mut issues = Vec::new();
+    collect_unknown_keys(contents, &mut Vec::new(), &raw, &known, &mut issues);
+    issues
+}
+
+fn collect_unknown_keys(
+    contents: &str,
+    path: &mut Vec<String>,
+    raw: &JsonValue,
+    known: &JsonValue,
+    issues: &mut Vec<ConfigIssue>,
+) {
+    match (raw, known) {
+        (JsonValue::Object(raw), JsonValue::Object(known)) => {
+            for (key, value) in raw {
+                path.push(key.clone());
+                match known.get(key) {
+                    Some(known_value) => {
+                        collect_unknown_keys(contents, path, value, known_value, issues)
+                    }
+                    // `skip_serializing_if` drops unset options; an explicit null is not a typo.
+                    None if value.is_null() => {}
+                    None => {
+                        let suggestion = known
+                            .keys()
+                            .filter(|candidate| edit_distance(key, candidate) <= 2)
+                            .min_by_key(|candidate| edit_distance(key, candidate))
+                            .map(|candidate| format!("; did you mean '{candidate}'?"))
+                            .unwrap_or_default();
+                        issues.push(ConfigIssue {
+                            severity: ConfigIssueSeverity::Warning,
+                            line: key_line(contents, path),
+                            key: Some(path.join(".")),
+                            message: format!(
+                                "unknown key '{}' is ignored{suggestion}",
+                                path.join(".")
+                            ),
+                        });
+                    }
+                }
+                path.pop();
+            }
+        }
+        (JsonValue::Array(raw), JsonValue::Array(known)) => {
+            for (value, known_value) in raw.iter().zip(known) {
+                collect_unknown_keys(contents, path, value, known_value, issues);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Best-effort line of a nested key: each segment must appear, in order, inside
+/// the block of the one before.
+fn key_line(contents: &str, path: &[String]) -> Option<usize> {
+    let mut depth = 0;
+    let mut parent_indent = 0;
+    for (index, line) in contents.lines().enumerate() {
+        let trimmed = line.trim_start().trim_start_matches("- ");
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+        if depth == 0 && indent > 0 {
+            continue;
+        }
+        if depth > 0 && indent <= parent_indent {
+            return None;
+        }
+        let key = trimmed
+            .split_once(':')
+            .map(|(key, _)| key.trim().trim_matches(|ch| ch == '"' || ch == '\''));
+        if key == Some(path[depth].as_str()) {
+            if depth + 1 == path.len() {
+                return Some(index + 1);
+            }
+            depth += 1;
+            parent_indent = indent;
+        }
+    }
+    None
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(current)
+            };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
+
+/// `GREENTIC_OPERATOR_GATEWAY_PORT` (set by `workspace start`) replaces
+/// `services.gateway.port` so several bundles can run side by side.
+/// `GREENTIC_OPERATOR_GATEWAY_LISTEN_ADDR` (set by `demo start --headless`) replaces
+/// `services.gateway.listen_addr`.
+pub fn apply_gateway_env_overrides(config: &mut DemoConfig) {
+    if let Ok(value) = std::env::var(ENV_GATEWAY_PORT)
+        && let Ok(port) = value.trim().parse::<u16>()
+    {
+        config.services.gateway.port = port;
+    }
+    if let Ok(value) = std::env::var(ENV_GATEWAY_LISTEN_ADDR)
+        && !value.trim().is_empty()
+    {
+        config.services.gateway.listen_addr = value.trim().to_string();
+    }
+}
+
 fn config_binary_path(config: &OperatorConfig, name: &str, config_dir: &Path) -> Option<PathBuf> {
     config
         .binaries
@@ -356,8 +1600,11 @@ fn default_demo_tenant() -> String {
     "demo".to_string()
 }
 
+/// Team used when a demo command gets no `--team`.
+pub(crate) const DEMO_DEFAULT_TEAM: &str = "default";
+
 fn default_demo_team() -> String {
-    "default".to_string()
+    DEMO_DEFAULT_TEAM.to_string()
 }
 
 fn default_true() -> bool {
@@ -372,6 +1619,22 @@ pub fn default_receive_nats_url() -> String {
     "nats://127.0.0.1:4347".to_string()
 }
 
+fn default_jetstream_storage() -> String {
+    "file".to_string()
+}
+
+fn default_jetstream_replicas() -> u32 {
+    1
+}
+
+fn default_jetstream_max_msgs() -> i64 {
+    100_000
+}
+
+fn default_jetstream_max_age_hours() -> u64 {
+    72
+}
+
 fn default_nats_binary() -> String {
     "nats-server".to_string()
 }
@@ -407,3 +1670,37 @@ fn default_msgraph_mode() -> String {
 pub(crate) fn default_events_components() -> Vec<ServiceComponentConfig> {
     Vec::new()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_reports_unknown_keys_with_line_and_suggestion() {
+        let issues = validate_contents::<DemoConfig>(
+            "tenant: demo\nservices:\n  gateway:\n    listen_adr: 0.0.0.0\n    port: 8080\n",
+        );
+        assert_eq!(issues.len(), 1);
+        let issue = &issues[0];
+        assert_eq!(issue.severity, ConfigIssueSeverity::Warning);
+        assert_eq!(issue.line, Some(4));
+        assert_eq!(issue.key.as_deref(), Some("services.gateway.listen_adr"));
+        assert!(issue.message.contains("did you mean 'listen_addr'"));
+    }
+
+    #[test]
+    fn validate_reports_type_errors_as_errors() {
+        let issues =
+            validate_contents::<DemoConfig>("services:\n  gateway:\n    port: not-a-port\n");
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].is_error());
+        assert_eq!(issues[0].line, Some(3));
+    }
+
+    #[test]
+    fn validate_accepts_free_form_maps() {
+        let issues =
+            validate_contents::<OperatorConfig>("binaries:\n  greentic-runner: ./bin/runner\n");
+        assert!(issues.is_empty());
+    }
+}
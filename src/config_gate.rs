@@ -1,7 +1,20 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::env;
 use std::fmt;
+use std::path::{Path, PathBuf};
 
+use anyhow::Context;
+use serde::Serialize;
+use serde_json::{Map as JsonMap, Value as JsonValue, json};
+
+use crate::config;
+use crate::discovery;
 use crate::domains::{self, Domain};
+use crate::provider_config_envelope;
+use crate::redaction;
+use crate::scope_chain::{self, ScopeLevel};
+use crate::secret_requirements::load_secret_keys_from_pack;
+use crate::secrets_gate;
 
 #[derive(Clone, Copy, Debug)]
 pub enum ConfigValueSource {
@@ -81,3 +94,398 @@ fn debug_enabled() -> bool {
         Ok("1") | Ok("true") | Ok("yes")
     )
 }
+
+/// Everything a flow in `tenant`/`team` is configured with, for `demo config show`.
+/// `sources` maps each leaf's dotted path to where its value came from.
+#[derive(Debug, Serialize)]
+pub struct EffectiveConfig {
+    pub config: JsonValue,
+    pub sources: BTreeMap<String, String>,
+}
+
+/// Assembles operator config, demo config, `GREENTIC_*` env overrides, and the
+/// provider config envelopes under the bundle's runtime state, with secrets redacted.
+pub fn effective_config(
+    bundle: &Path,
+    tenant: &str,
+    team: &str,
+) -> anyhow::Result<EffectiveConfig> {
+    let mut sources = BTreeMap::new();
+    let secrets_env = crate::secrets_setup::resolve_env(None);
+    let scope = json!({"tenant": tenant, "team": team, "env": secrets_env});
+    annotate("scope", &scope, Some(&scope), "argument", &mut sources);
+
+    let operator_path = bundle.join("greentic.yaml");
+    let operator = config::load_operator_config(bundle)
+        .with_context(|| format!("load {}", operator_path.display()))?
+        .unwrap_or_default();
+    let operator = redaction::redact_json(&serde_json::to_value(&operator)?);
+    let raw_operator = read_raw_yaml(&operator_path)?;
+    annotate(
+        "operator",
+        &operator,
+        raw_operator.as_ref(),
+        "greentic.yaml",
+        &mut sources,
+    );
+
+    let demo_path = bundle.join("greentic.demo.yaml");
+    let demo = if demo_path.exists() {
+        config::load_demo_config(&demo_path)?
+    } else {
+        let mut demo = config::DemoConfig::default();
+        config::apply_gateway_env_overrides(&mut demo);
+        demo
+    };
+    let demo = redaction::redact_json(&serde_json::to_value(&demo)?);
+    let raw_demo = read_raw_yaml(&demo_path)?;
+    annotate(
+        "demo",
+        &demo,
+        raw_demo.as_ref(),
+        "greentic.demo.yaml",
+        &mut sources,
+    );
+    if env::var(config::ENV_GATEWAY_PORT).is_ok_and(|value| value.trim().parse::<u16>().is_ok()) {
+        sources.insert(
+            "demo.services.gateway.port".to_string(),
+            format!("env({})", config::ENV_GATEWAY_PORT),
+        );
+    }
+    if env::var(config::ENV_GATEWAY_LISTEN_ADDR).is_ok_and(|value| !value.trim().is_empty()) {
+        sources.insert(
+            "demo.services.gateway.listen_addr".to_string(),
+            format!("env({})", config::ENV_GATEWAY_LISTEN_ADDR),
+        );
+    }
+
+    let mut env_overrides = JsonMap::new();
+    for (key, value) in env::vars().filter(|(key, _)| key.starts_with("GREENTIC_")) {
+        sources.insert(format!("env.{key}"), "env".to_string());
+        env_overrides.insert(key, JsonValue::String(value));
+    }
+    let env_overrides = redaction::redact_json(&JsonValue::Object(env_overrides));
+
+    let state_dir = bundle.join("state");
+    let mut providers = JsonMap::new();
+    for provider_id in configured_providers(&state_dir, tenant, team) {
+        let Some((level, path, envelope)) =
+            nearest_envelope(&state_dir, tenant, team, &provider_id)?
+        else {
+            continue;
+        };
+        let inherited =
+            provider_config_envelope::read_inherited_config(&state_dir, tenant, team, &provider_id)
+                .with_context(|| format!("read config envelopes for {provider_id}"))?
+                .unwrap_or_else(|| provider_config_envelope::InheritedConfig {
+                    config: envelope.config.clone(),
+                    sources: BTreeMap::new(),
+                });
+        let value = redaction::redact_json(&json!({
+            "config": inherited.config,
+            "component_id": envelope.component_id,
+            "resolved_digest": envelope.resolved_digest,
+            "operation_id": envelope.operation_id,
+            "updated_at": envelope.updated_at,
+        }));
+        let prefix = format!("providers.{provider_id}");
+        annotate(
+            &prefix,
+            &value,
+            Some(&value),
+            &envelope_source(bundle, level, &path),
+            &mut sources,
+        );
+        for (key, source) in &inherited.sources {
+            let item = &value["config"][key];
+            annotate(
+                &format!("{prefix}.config.{key}"),
+                item,
+                Some(item),
+                &envelope_source(bundle, source.level, &source.path),
+                &mut sources,
+            );
+        }
+        providers.insert(provider_id, value);
+    }
+
+    Ok(EffectiveConfig {
+        config: json!({
+            "scope": scope,
+            "operator": operator,
+            "demo": demo,
+            "env": env_overrides,
+            "providers": providers,
+        }),
+        sources,
+    })
+}
+
+/// Providers with a config envelope at any level of the team -> tenant -> bundle
+/// chain.
+fn configured_providers(state_dir: &Path, tenant: &str, team: &str) -> BTreeSet<String> {
+    scope_chain::config_roots(state_dir, tenant, team)
+        .into_iter()
+        .filter_map(|(_, root)| std::fs::read_dir(root).ok())
+        .flat_map(|entries| entries.filter_map(Result::ok))
+        .filter(|entry| entry.path().join("config.envelope.cbor").exists())
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+        .collect()
+}
+
+/// The most specific envelope for `provider_id`, with its level and path.
+fn nearest_envelope(
+    state_dir: &Path,
+    tenant: &str,
+    team: &str,
+    provider_id: &str,
+) -> anyhow::Result<
+    Option<(
+        ScopeLevel,
+        PathBuf,
+        provider_config_envelope::ConfigEnvelope,
+    )>,
+> {
+    for (level, root) in scope_chain::config_roots(state_dir, tenant, team) {
+        if let Some(envelope) =
+            provider_config_envelope::read_provider_config_envelope(&root, provider_id)
+                .with_context(|| format!("read config envelope for {provider_id}"))?
+        {
+            return Ok(Some((
+                level,
+                root.join(provider_id).join("config.envelope.cbor"),
+                envelope,
+            )));
+        }
+    }
+    Ok(None)
+}
+
+fn envelope_source(bundle: &Path, level: ScopeLevel, path: &Path) -> String {
+    let path = path.strip_prefix(bundle).unwrap_or(path);
+    format!("envelope({}: {})", level.as_str(), path.display())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InheritedKind {
+    Config,
+    Secret,
+}
+
+/// Which level of the team -> tenant -> bundle chain supplies one config or secret
+/// key, for `demo config show --explain`.
+#[derive(Clone, Debug, Serialize)]
+pub struct InheritedKey {
+    pub provider: String,
+    pub kind: InheritedKind,
+    pub key: String,
+    /// `None` when no level has the key.
+    pub level: Option<ScopeLevel>,
+    /// Envelope path or secret URI the value was read from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+}
+
+/// Every top-level provider config key and every secret the bundle's provider packs
+/// require, with the level that supplies it for `tenant`/`team`.
+pub fn explain_inheritance(
+    bundle: &Path,
+    tenant: &str,
+    team: &str,
+) -> anyhow::Result<Vec<InheritedKey>> {
+    let state_dir = bundle.join("state");
+    let mut entries = Vec::new();
+    for provider_id in configured_providers(&state_dir, tenant, team) {
+        let Some(inherited) =
+            provider_config_envelope::read_inherited_config(&state_dir, tenant, team, &provider_id)
+                .with_context(|| format!("read config envelopes for {provider_id}"))?
+        else {
+            continue;
+        };
+        for (key, source) in inherited.sources {
+            let path = source.path.strip_prefix(bundle).unwrap_or(&source.path);
+            entries.push(InheritedKey {
+                provider: provider_id.clone(),
+                kind: InheritedKind::Config,
+                key,
+                level: Some(source.level),
+                from: Some(path.display().to_string()),
+            });
+        }
+    }
+
+    let discovered = discovery::discover(bundle)?;
+    let mut required = Vec::new();
+    for provider in &discovered.providers {
+        let keys = load_secret_keys_from_pack(&provider.pack_path).with_context(|| {
+            format!(
+                "read secret requirements from {}",
+                provider.pack_path.display()
+            )
+        })?;
+        required.extend(
+            keys.into_iter()
+                .map(|key| (provider.provider_id.clone(), key)),
+        );
+    }
+    if !required.is_empty() {
+        let handle = secrets_gate::resolve_secrets_manager(bundle, tenant, Some(team))?;
+        let env = crate::secrets_setup::resolve_env(None);
+        for (provider, key) in required {
+            let found = secrets_gate::resolve_provider_secret(
+                &handle,
+                &env,
+                tenant,
+                Some(team),
+                &provider,
+                &key,
+            )?;
+            entries.push(InheritedKey {
+                level: found.as_ref().map(|secret| secret.level),
+                from: found.map(|secret| secret.uri),
+                provider,
+                kind: InheritedKind::Secret,
+                key,
+            });
+        }
+    }
+    entries.sort_by(|a, b| (&a.provider, a.kind, &a.key).cmp(&(&b.provider, b.kind, &b.key)));
+    Ok(entries)
+}
+
+impl EffectiveConfig {
+    /// YAML with each leaf's source as a trailing comment.
+    pub fn to_annotated_yaml(&self) -> String {
+        let mut out = String::new();
+        if let JsonValue::Object(map) = &self.config {
+            self.write_entries(&mut out, "", map, 0);
+        }
+        out
+    }
+
+    fn write_entries(
+        &self,
+        out: &mut String,
+        prefix: &str,
+        map: &JsonMap<String, JsonValue>,
+        indent: usize,
+    ) {
+        let pad = " ".repeat(indent);
+        for (key, value) in map {
+            let path = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{prefix}.{key}")
+            };
+            let comment = self
+                .sources
+                .get(&path)
+                .map(|source| format!("  # {source}"))
+                .unwrap_or_default();
+            let key = yaml_key(key);
+            match value {
+                JsonValue::Object(entries) if !entries.is_empty() => {
+                    out.push_str(&format!("{pad}{key}:\n"));
+                    self.write_entries(out, &path, entries, indent + 2);
+                }
+                JsonValue::Array(items) if !items.is_empty() => {
+                    out.push_str(&format!("{pad}{key}:{comment}\n"));
+                    for (index, item) in items.iter().enumerate() {
+                        match item {
+                            JsonValue::Object(entries) if !entries.is_empty() => {
+                                out.push_str(&format!("{pad}  -\n"));
+                                self.write_entries(
+                                    out,
+                                    &format!("{path}[{index}]"),
+                                    entries,
+                                    indent + 4,
+                                );
+                            }
+                            other => out.push_str(&format!("{pad}  - {other}\n")),
+                        }
+                    }
+                }
+                other => out.push_str(&format!("{pad}{key}: {other}{comment}\n")),
+            }
+        }
+    }
+}
+
+/// Records a source for every leaf of `value`: `file` where `raw` (the document as
+/// written) sets the key, `default` where serde filled it in. Arrays count as one leaf.
+fn annotate(
+    path: &str,
+    value: &JsonValue,
+    raw: Option<&JsonValue>,
+    file: &str,
+    sources: &mut BTreeMap<String, String>,
+) {
+    match value {
+        JsonValue::Object(map) if !map.is_empty() => {
+            for (key, item) in map {
+                let nested = raw.and_then(|raw| raw.get(key));
+                annotate(&format!("{path}.{key}"), item, nested, file, sources);
+            }
+        }
+        _ => {
+            let source = if raw.is_some() { file } else { "default" };
+            sources.insert(path.to_string(), source.to_string());
+        }
+    }
+}
+
+fn read_raw_yaml(path: &Path) -> anyhow::Result<Option<JsonValue>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = std::fs::read_to_string(path)?;
+    let value: Option<JsonValue> =
+        serde_yaml_bw::from_str(&contents).with_context(|| format!("parse {}", path.display()))?;
+    Ok(value.filter(JsonValue::is_object))
+}
+
+fn yaml_key(key: &str) -> String {
+    let plain = key
+        .chars()
+        .next()
+        .is_some_and(|first| first.is_ascii_alphanumeric() || first == '_')
+        && key
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '_' | '-' | '.' | '/'));
+    if plain {
+        key.to_string()
+    } else {
+        JsonValue::String(key.to_string()).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effective_config_annotates_file_and_default_values() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("greentic.demo.yaml"),
+            "tenant: acme\nservices:\n  gateway:\n    listen_addr: 0.0.0.0\n",
+        )
+        .unwrap();
+
+        let effective = effective_config(dir.path(), "acme", "default").unwrap();
+        assert_eq!(effective.config["demo"]["tenant"], "acme");
+        assert_eq!(
+            effective.sources["demo.services.gateway.listen_addr"],
+            "greentic.demo.yaml"
+        );
+        assert_eq!(effective.sources["demo.team"], "default");
+        assert_eq!(effective.sources["scope.tenant"], "argument");
+
+        let yaml = effective.to_annotated_yaml();
+        assert!(yaml.contains("  tenant: \"acme\"  # greentic.demo.yaml\n"));
+        assert!(yaml.contains("    gateway:\n      args: []  # default\n"));
+        let parsed: JsonValue = serde_yaml_bw::from_str(&yaml).unwrap();
+        assert_eq!(parsed["demo"], effective.config["demo"]);
+    }
+}
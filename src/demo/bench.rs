@@ -0,0 +1,275 @@
+//! `demo bench invoke`: latency of one provider op called over and over.
+//!
+//! Each call goes through [`DemoRunnerHost::invoke_provider_op`], the same path
+//! `demo send` takes, and is split into the [`profile`] phases it recorded: `spawn`
+//! (starting the runner binary or loading the pack), `encode` (payload and result
+//! encoding), `io` (run directories, transcripts, run records) and `flow` (the
+//! runner itself). Whatever is left is the host's own bookkeeping: hooks, quotas,
+//! the circuit breaker. Point it at an op that does no work of its own and the
+//! numbers are the cost an in-process runner would have to beat.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, anyhow};
+use serde::Serialize;
+
+use crate::demo::runner_host::{DemoRunnerHost, OperatorContext};
+use crate::domains::Domain;
+use crate::operator_i18n;
+use crate::profile::{self, Phase};
+
+/// The op to call and what to call it with.
+pub struct InvokeTarget<'a> {
+    pub domain: Domain,
+    pub provider: &'a str,
+    pub op: &'a str,
+    pub payload: &'a [u8],
+    pub ctx: &'a OperatorContext,
+}
+
+struct Sample {
+    latency: Duration,
+    phases: Vec<(Phase, Duration)>,
+    error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PhaseCost {
+    pub phase: String,
+    /// Mean self time per call.
+    pub mean_ms: f64,
+    /// Percentage of the mean latency.
+    pub share: f64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BenchReport {
+    pub provider: String,
+    pub op: String,
+    pub iterations: usize,
+    pub failures: usize,
+    pub first_failure: Option<String>,
+    pub min_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    pub mean_ms: f64,
+    /// Per-phase cost, then `other` for time no phase claimed.
+    pub breakdown: Vec<PhaseCost>,
+}
+
+/// Calls `target` `warmup` times without measuring, then `iterations` times.
+/// An op that reports failure is still timed and counted; an invocation error
+/// stops the run.
+pub fn run_invoke(
+    host: &DemoRunnerHost,
+    target: &InvokeTarget<'_>,
+    iterations: usize,
+    warmup: usize,
+) -> anyhow::Result<BenchReport> {
+    if iterations == 0 {
+        return Err(anyhow!("--iterations must be at least 1"));
+    }
+    profile::enable();
+    let invoke = || {
+        host.invoke_provider_op(
+            target.domain,
+            target.provider,
+            target.op,
+            target.payload,
+            target.ctx,
+        )
+    };
+    for call in 1..=warmup {
+        invoke().with_context(|| format!("warm-up call {call} failed"))?;
+    }
+    let mut samples = Vec::with_capacity(iterations);
+    for call in 1..=iterations {
+        let mark = profile::mark();
+        let started = Instant::now();
+        let outcome = invoke().with_context(|| format!("call {call} of {iterations} failed"))?;
+        let latency = started.elapsed();
+        samples.push(Sample {
+            latency,
+            phases: profile::self_times_since(mark),
+            error: (!outcome.success).then(|| {
+                outcome
+                    .error
+                    .unwrap_or_else(|| "op reported failure".to_string())
+            }),
+        });
+    }
+    Ok(summarize(target.provider, target.op, &samples))
+}
+
+fn summarize(provider: &str, op: &str, samples: &[Sample]) -> BenchReport {
+    let mut latencies = samples
+        .iter()
+        .map(|sample| sample.latency)
+        .collect::<Vec<_>>();
+    latencies.sort();
+    let count = samples.len().max(1) as u32;
+    let mean = latencies.iter().sum::<Duration>() / count;
+    let mut phases = BTreeMap::<Phase, Duration>::new();
+    for (phase, self_time) in samples.iter().flat_map(|sample| &sample.phases) {
+        *phases.entry(*phase).or_default() += *self_time;
+    }
+    let share = |duration: Duration| {
+        if mean.is_zero() {
+            0.0
+        } else {
+            duration.as_secs_f64() * 100.0 / mean.as_secs_f64()
+        }
+    };
+    let mut attributed = Duration::ZERO;
+    let mut breakdown = Vec::new();
+    for (phase, total) in phases {
+        let per_call = total / count;
+        attributed += per_call;
+        breakdown.push(PhaseCost {
+            phase: phase.as_str().to_string(),
+            mean_ms: millis(per_call),
+            share: share(per_call),
+        });
+    }
+    let other = mean.saturating_sub(attributed);
+    breakdown.push(PhaseCost {
+        phase: "other".to_string(),
+        mean_ms: millis(other),
+        share: share(other),
+    });
+    let failed = samples
+        .iter()
+        .filter_map(|sample| sample.error.as_ref())
+        .collect::<Vec<_>>();
+    BenchReport {
+        provider: provider.to_string(),
+        op: op.to_string(),
+        iterations: samples.len(),
+        failures: failed.len(),
+        first_failure: failed.first().map(|error| error.to_string()),
+        min_ms: latencies.first().copied().map(millis).unwrap_or_default(),
+        p50_ms: millis(percentile(&latencies, 50)),
+        p95_ms: millis(percentile(&latencies, 95)),
+        max_ms: latencies.last().copied().map(millis).unwrap_or_default(),
+        mean_ms: millis(mean),
+        breakdown,
+    }
+}
+
+/// Nearest-rank percentile of `sorted`.
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (sorted.len() * percent).div_ceil(100).max(1);
+    sorted[rank.min(sorted.len()) - 1]
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_nanos() as f64 / 1_000_000.0
+}
+
+pub fn render(report: &BenchReport) -> String {
+    let mut out = operator_i18n::trf(
+        "cli.demo_bench.heading",
+        "{} {}: {} calls, {} failed",
+        &[
+            &report.provider,
+            &report.op,
+            &report.iterations.to_string(),
+            &report.failures.to_string(),
+        ],
+    );
+    out.push('\n');
+    out.push_str(&operator_i18n::trf(
+        "cli.demo_bench.latency",
+        "latency ms: min {}, p50 {}, p95 {}, max {}, mean {}",
+        &[
+            &format!("{:.1}", report.min_ms),
+            &format!("{:.1}", report.p50_ms),
+            &format!("{:.1}", report.p95_ms),
+            &format!("{:.1}", report.max_ms),
+            &format!("{:.1}", report.mean_ms),
+        ],
+    ));
+    out.push_str(&format!(
+        "\n  {:<12} {:>12} {:>6}",
+        operator_i18n::tr("cli.profile.col_phase", "phase"),
+        operator_i18n::tr("cli.demo_bench.col_mean", "mean ms"),
+        operator_i18n::tr("cli.profile.col_share", "share"),
+    ));
+    for cost in &report.breakdown {
+        let phase = if cost.phase == "other" {
+            operator_i18n::tr("cli.profile.other", "other")
+        } else {
+            cost.phase.clone()
+        };
+        out.push_str(&format!(
+            "\n  {:<12} {:>12.1} {:>5.0}%",
+            phase, cost.mean_ms, cost.share
+        ));
+    }
+    if let Some(error) = &report.first_failure {
+        out.push('\n');
+        out.push_str(&operator_i18n::trf(
+            "cli.demo_bench.first_failure",
+            "first failure: {}",
+            &[error],
+        ));
+    }
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(latency_ms: u64, phases: &[(Phase, u64)]) -> Sample {
+        Sample {
+            latency: Duration::from_millis(latency_ms),
+            phases: phases
+                .iter()
+                .map(|(phase, ms)| (*phase, Duration::from_millis(*ms)))
+                .collect(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn reports_percentiles_and_mean_phase_costs() {
+        let mut samples = (1..=20)
+            .map(|ms| sample(ms * 10, &[(Phase::Spawn, 4), (Phase::Io, 2)]))
+            .collect::<Vec<_>>();
+        samples[3].error = Some("401".to_string());
+        samples[7].error = Some("429".to_string());
+        let report = summarize("telegram", "requirements", &samples);
+        assert_eq!(report.iterations, 20);
+        assert_eq!(report.failures, 2);
+        assert_eq!(report.first_failure.as_deref(), Some("401"));
+        assert_eq!(report.min_ms, 10.0);
+        assert_eq!(report.p50_ms, 100.0);
+        assert_eq!(report.p95_ms, 190.0);
+        assert_eq!(report.max_ms, 200.0);
+        assert_eq!(report.mean_ms, 105.0);
+        let phases = report
+            .breakdown
+            .iter()
+            .map(|cost| (cost.phase.as_str(), cost.mean_ms))
+            .collect::<Vec<_>>();
+        assert_eq!(phases, vec![("spawn", 4.0), ("io", 2.0), ("other", 99.0)]);
+
+        let text = render(&report);
+        assert!(text.contains("p95 190.0"), "{text}");
+    }
+
+    #[test]
+    fn percentile_uses_nearest_rank() {
+        let sorted = [1, 2, 3].map(Duration::from_millis).to_vec();
+        assert_eq!(percentile(&sorted, 50), Duration::from_millis(2));
+        assert_eq!(percentile(&sorted, 95), Duration::from_millis(3));
+        assert_eq!(percentile(&[], 50), Duration::ZERO);
+    }
+}
@@ -1,7 +1,8 @@
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::BTreeSet;
 use std::path::{Path, PathBuf};
 
-use serde::{Deserialize, Serialize};
+use crate::progress;
+use crate::project::{self, ResolvedManifest};
 
 #[derive(Debug, Clone)]
 pub struct BuildOptions {
@@ -13,18 +14,6 @@ pub struct BuildOptions {
     pub run_doctor: bool,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-struct ResolvedManifest {
-    version: String,
-    tenant: String,
-    team: Option<String>,
-    project_root: String,
-    providers: BTreeMap<String, Vec<String>>,
-    packs: Vec<String>,
-    env_passthrough: Vec<String>,
-    policy: serde_yaml_bw::Value,
-}
-
 pub fn build_bundle(
     project_root: &Path,
     options: BuildOptions,
@@ -78,7 +67,7 @@ pub fn build_bundle(
         let manifest = load_manifest(manifest_path)?;
         for packs in manifest.providers.values() {
             for pack in packs {
-                used_provider_paths.insert(pack.clone());
+                used_provider_paths.insert(pack.path().to_string());
             }
         }
         loaded_manifests.push((manifest_path.clone(), manifest));
@@ -97,30 +86,40 @@ pub fn build_bundle(
         )?;
     }
 
+    let phase = progress::Phase::start("build bundle", loaded_manifests.len());
     let mut tenants_to_copy = BTreeSet::new();
     for (manifest_path, mut manifest) in loaded_manifests {
         tenants_to_copy.insert(manifest.tenant.clone());
-
-        let pack_paths = manifest.packs.clone();
+        let item = manifest_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        phase.set_message(item.as_str());
+
+        let pack_paths = manifest
+            .packs
+            .iter()
+            .map(|pack| pack.path().to_string())
+            .collect::<Vec<_>>();
         for pack in pack_paths {
             let pack_path = project_root.join(&pack);
+            phase.detail(format!("  {pack}"));
             if pack.ends_with(".gtpack") {
                 copy_file(&pack_path, &bundle_root.join(&pack))?;
             } else {
                 if !options.allow_pack_dirs {
+                    phase.advance(&item, false);
+                    phase.finish();
                     return Err(anyhow::anyhow!(
                         "Pack directory not allowed in demo bundle: {} (use --allow-pack-dirs)",
                         pack
                     ));
                 }
-                eprintln!(
-                    "{}",
-                    crate::operator_i18n::trf(
-                        "demo.build.warn_copying_pack_directory",
-                        "Warning: copying pack directory into demo bundle (not portable): {}",
-                        &[&pack]
-                    )
-                );
+                phase.warn(crate::operator_i18n::trf(
+                    "demo.build.warn_copying_pack_directory",
+                    "Warning: copying pack directory into demo bundle (not portable): {}",
+                    &[&pack],
+                ));
                 copy_dir(pack_path, bundle_root.join(&pack))?;
             }
         }
@@ -131,7 +130,9 @@ pub fn build_bundle(
             .ok_or_else(|| anyhow::anyhow!("Invalid manifest filename"))?;
         let out_path = bundle_root.join("resolved").join(filename);
         write_manifest(&out_path, &manifest)?;
+        phase.advance(&item, true);
     }
+    phase.finish();
 
     for tenant in tenants_to_copy {
         let tenant_path = project_root.join("tenants").join(&tenant);
@@ -178,18 +179,11 @@ fn select_manifests(
 }
 
 fn load_manifest(path: &Path) -> anyhow::Result<ResolvedManifest> {
-    let contents = std::fs::read_to_string(path)?;
-    let manifest: ResolvedManifest = serde_yaml_bw::from_str(&contents)?;
-    Ok(manifest)
+    project::load_resolved_manifest(path)
 }
 
 fn write_manifest(path: &Path, manifest: &ResolvedManifest) -> anyhow::Result<()> {
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
-    let yaml = serde_yaml_bw::to_string(manifest)?;
-    std::fs::write(path, yaml)?;
-    Ok(())
+    project::write_resolved_manifest(path, manifest)
 }
 
 fn write_demo_metadata(path: &Path) -> anyhow::Result<()> {
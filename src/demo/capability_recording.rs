@@ -0,0 +1,259 @@
+//! Recorded capability invocations for offline demos.
+//!
+//! In record mode `DemoRunnerHost::invoke_capability` stores every request/response
+//! pair under `state/capability-recordings/<cap_id>/<op>/<request_hash>.json`. In mock
+//! mode the provider is never called: the recording with the same request hash
+//! answers, falling back to the newest recording for the capability/op. Recordings
+//! are stored verbatim (no redaction) so they can be replayed byte for byte.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::demo::runner_host::{FlowOutcome, OperatorContext, RunnerExecutionMode};
+use crate::runtime_state;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CapabilityRecordingMode {
+    #[default]
+    Off,
+    Record,
+    Mock,
+}
+
+impl CapabilityRecordingMode {
+    /// `GREENTIC_CAPABILITY_RECORDING=record|mock` turns recording on for every host.
+    pub fn from_env() -> Self {
+        match std::env::var("GREENTIC_CAPABILITY_RECORDING")
+            .unwrap_or_default()
+            .trim()
+        {
+            "record" => Self::Record,
+            "mock" => Self::Mock,
+            _ => Self::Off,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CapabilityRecording {
+    pub cap_id: String,
+    pub op: String,
+    pub stable_id: String,
+    pub tenant: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team: Option<String>,
+    pub request_hash: String,
+    /// Request payload as JSON when it parses, otherwise as a UTF-8 (lossy) string.
+    pub request: JsonValue,
+    pub recorded_at: String,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<JsonValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl CapabilityRecording {
+    pub fn new(
+        cap_id: &str,
+        op: &str,
+        stable_id: &str,
+        payload_bytes: &[u8],
+        ctx: &OperatorContext,
+        outcome: &FlowOutcome,
+    ) -> Self {
+        let request = serde_json::from_slice::<JsonValue>(payload_bytes).unwrap_or_else(|_| {
+            JsonValue::String(String::from_utf8_lossy(payload_bytes).into_owned())
+        });
+        Self {
+            cap_id: cap_id.to_string(),
+            op: op.to_string(),
+            stable_id: stable_id.to_string(),
+            tenant: ctx.tenant.clone(),
+            team: ctx.team.clone(),
+            request_hash: request_hash(payload_bytes),
+            request,
+            recorded_at: Utc::now().to_rfc3339(),
+            success: outcome.success,
+            output: outcome.output.clone(),
+            raw: outcome.raw.clone(),
+            error: outcome.error.clone(),
+        }
+    }
+
+    pub fn to_outcome(&self) -> FlowOutcome {
+        FlowOutcome {
+            success: self.success,
+            output: self.output.clone(),
+            raw: self.raw.clone(),
+            error: self.error.clone(),
+            mode: RunnerExecutionMode::Exec,
+        }
+    }
+}
+
+pub fn recordings_root(bundle_root: &Path) -> PathBuf {
+    bundle_root.join("state").join("capability-recordings")
+}
+
+fn recordings_dir(bundle_root: &Path, cap_id: &str, op: &str) -> PathBuf {
+    let op = if op.trim().is_empty() { "_default" } else { op };
+    recordings_root(bundle_root)
+        .join(path_token(cap_id))
+        .join(path_token(op))
+}
+
+pub fn recording_path(bundle_root: &Path, cap_id: &str, op: &str, hash: &str) -> PathBuf {
+    recordings_dir(bundle_root, cap_id, op).join(format!("{hash}.json"))
+}
+
+/// Overwrites any earlier recording of the same request.
+pub fn write_recording(
+    bundle_root: &Path,
+    recording: &CapabilityRecording,
+) -> anyhow::Result<PathBuf> {
+    let path = recording_path(
+        bundle_root,
+        &recording.cap_id,
+        &recording.op,
+        &recording.request_hash,
+    );
+    runtime_state::write_json(&path, recording)?;
+    Ok(path)
+}
+
+/// Exact request match first, then the newest recording for the capability/op.
+pub fn find_recording(
+    bundle_root: &Path,
+    cap_id: &str,
+    op: &str,
+    payload_bytes: &[u8],
+) -> anyhow::Result<Option<CapabilityRecording>> {
+    let exact = recording_path(bundle_root, cap_id, op, &request_hash(payload_bytes));
+    if let Some(recording) = runtime_state::read_json::<CapabilityRecording>(&exact)? {
+        return Ok(Some(recording));
+    }
+    Ok(list_recordings(bundle_root, cap_id, op)?.into_iter().next())
+}
+
+/// Recordings for a capability/op, newest first. Unreadable files are skipped.
+pub fn list_recordings(
+    bundle_root: &Path,
+    cap_id: &str,
+    op: &str,
+) -> anyhow::Result<Vec<CapabilityRecording>> {
+    let dir = recordings_dir(bundle_root, cap_id, op);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut recordings = Vec::new();
+    for entry in fs::read_dir(&dir).with_context(|| format!("read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        if let Ok(Some(recording)) = runtime_state::read_json::<CapabilityRecording>(&path) {
+            recordings.push(recording);
+        }
+    }
+    recordings.sort_by(|a, b| b.recorded_at.cmp(&a.recorded_at));
+    Ok(recordings)
+}
+
+/// FNV-1a over the raw payload; stable across runs and platforms.
+fn request_hash(payload_bytes: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in payload_bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("{hash:016x}")
+}
+
+fn path_token(value: &str) -> String {
+    value
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.') {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn outcome(output: JsonValue) -> FlowOutcome {
+        FlowOutcome {
+            success: true,
+            output: Some(output),
+            raw: None,
+            error: None,
+            mode: RunnerExecutionMode::Exec,
+        }
+    }
+
+    #[test]
+    fn mock_lookup_prefers_exact_request_then_newest() {
+        let dir = tempdir().expect("tempdir");
+        let ctx = OperatorContext {
+            tenant: "demo".to_string(),
+            team: Some("default".to_string()),
+            correlation_id: None,
+        };
+        let first = br#"{"q":"weather"}"#;
+        let second = br#"{"q":"news"}"#;
+        let mut older = CapabilityRecording::new(
+            "greentic.cap.search",
+            "",
+            "offer.search",
+            first,
+            &ctx,
+            &outcome(json!({"answer": "sunny"})),
+        );
+        older.recorded_at = "2026-01-01T00:00:00+00:00".to_string();
+        write_recording(dir.path(), &older).expect("write");
+        write_recording(
+            dir.path(),
+            &CapabilityRecording::new(
+                "greentic.cap.search",
+                "",
+                "offer.search",
+                second,
+                &ctx,
+                &outcome(json!({"answer": "headlines"})),
+            ),
+        )
+        .expect("write");
+
+        let exact = find_recording(dir.path(), "greentic.cap.search", "", first)
+            .expect("find")
+            .expect("exact recording");
+        assert_eq!(exact.output, Some(json!({"answer": "sunny"})));
+        assert_eq!(exact.request, json!({"q": "weather"}));
+
+        let fallback = find_recording(dir.path(), "greentic.cap.search", "", b"{}")
+            .expect("find")
+            .expect("newest recording");
+        assert_eq!(fallback.output, Some(json!({"answer": "headlines"})));
+
+        assert!(
+            find_recording(dir.path(), "greentic.cap.other", "", first)
+                .expect("find")
+                .is_none()
+        );
+    }
+}
@@ -1,5 +1,7 @@
 mod parse;
 mod show;
+pub mod templates;
+pub mod validate;
 
 pub use parse::{CardView, detect_adaptive_card_view};
 pub use show::print_card_summary;
@@ -0,0 +1,97 @@
+//! Starter cards for `demo card new`.
+
+use serde_json::{Value as JsonValue, json};
+
+/// Template names, in `--template` help order.
+pub const TEMPLATES: &[&str] = &["basic", "form", "approval", "list"];
+
+/// The `name` template with `title`, declared as schema `version`.
+pub fn starter_card(name: &str, title: &str, version: &str) -> Option<JsonValue> {
+    let (body, actions) = match name {
+        "basic" => (
+            json!([
+                {"type": "TextBlock", "text": title, "weight": "Bolder", "size": "Medium", "wrap": true},
+                {"type": "TextBlock", "text": "Describe what this card is for.", "wrap": true}
+            ]),
+            json!([
+                {"type": "Action.Submit", "id": "ok", "title": "OK", "data": {"action": "ok"}}
+            ]),
+        ),
+        "form" => (
+            json!([
+                {"type": "TextBlock", "text": title, "weight": "Bolder", "size": "Medium", "wrap": true},
+                {"type": "Input.Text", "id": "name", "label": "Name", "placeholder": "Your name"},
+                {"type": "Input.Date", "id": "date", "label": "Date"},
+                {
+                    "type": "Input.ChoiceSet",
+                    "id": "guests",
+                    "label": "Guests",
+                    "value": "2",
+                    "choices": [
+                        {"title": "1", "value": "1"},
+                        {"title": "2", "value": "2"},
+                        {"title": "4", "value": "4"}
+                    ]
+                }
+            ]),
+            json!([
+                {"type": "Action.Submit", "id": "submit", "title": "Submit", "data": {"action": "submit"}}
+            ]),
+        ),
+        "approval" => (
+            json!([
+                {"type": "TextBlock", "text": title, "weight": "Bolder", "size": "Medium", "wrap": true},
+                {
+                    "type": "FactSet",
+                    "facts": [
+                        {"title": "Requested by", "value": "Jane Doe"},
+                        {"title": "Amount", "value": "120 EUR"}
+                    ]
+                },
+                {"type": "Input.Text", "id": "comment", "label": "Comment", "isMultiline": true}
+            ]),
+            json!([
+                {"type": "Action.Submit", "id": "approve", "title": "Approve", "data": {"action": "approve"}},
+                {"type": "Action.Submit", "id": "reject", "title": "Reject", "data": {"action": "reject"}}
+            ]),
+        ),
+        "list" => (
+            json!([
+                {"type": "TextBlock", "text": title, "weight": "Bolder", "size": "Medium", "wrap": true},
+                {
+                    "type": "Container",
+                    "items": [
+                        {"type": "TextBlock", "text": "First item", "wrap": true},
+                        {"type": "TextBlock", "text": "Second item", "wrap": true, "separator": true}
+                    ]
+                }
+            ]),
+            json!([
+                {"type": "Action.OpenUrl", "id": "more", "title": "More", "url": "https://example.com"}
+            ]),
+        ),
+        _ => return None,
+    };
+    Some(json!({
+        "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+        "type": "AdaptiveCard",
+        "version": version,
+        "body": body,
+        "actions": actions,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::demo::card::validate::validate_card;
+
+    #[test]
+    fn every_template_validates_at_1_0() {
+        for name in TEMPLATES {
+            let card = starter_card(name, "Title", "1.0").unwrap();
+            assert!(validate_card(&card).is_empty(), "{name}");
+        }
+        assert!(starter_card("missing", "Title", "1.0").is_none());
+    }
+}
@@ -0,0 +1,498 @@
+//! Adaptive card checks for `demo card validate`.
+//!
+//! The structural checks need nothing but the card. Provider checks compare the card
+//! with what a provider's `render_plan` declares (`adaptive_cards` or
+//! `capabilities.adaptive_cards`, and plan `warnings`); providers that declare
+//! nothing fall back to the schema versions their platforms are known to render.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use serde::{Serialize, Serializer};
+use serde_json::Value as JsonValue;
+
+use crate::messaging_universal::dto::RenderPlanOutV1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SchemaVersion(pub u32, pub u32);
+
+impl SchemaVersion {
+    pub fn parse(value: &str) -> Option<Self> {
+        let (major, minor) = value.trim().split_once('.')?;
+        Some(Self(major.parse().ok()?, minor.parse().ok()?))
+    }
+}
+
+impl fmt::Display for SchemaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.0, self.1)
+    }
+}
+
+impl Serialize for SchemaVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CardIssue {
+    pub severity: Severity,
+    /// JSON pointer to the offending element; empty for the card itself.
+    pub path: String,
+    pub message: String,
+}
+
+impl CardIssue {
+    fn error(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+
+    fn warning(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Newest schema version this checker knows.
+pub const LATEST_VERSION: SchemaVersion = SchemaVersion(1, 6);
+
+/// Body elements and the schema version that introduced them.
+const ELEMENTS: &[(&str, SchemaVersion)] = &[
+    ("TextBlock", SchemaVersion(1, 0)),
+    ("Image", SchemaVersion(1, 0)),
+    ("Container", SchemaVersion(1, 0)),
+    ("ColumnSet", SchemaVersion(1, 0)),
+    ("FactSet", SchemaVersion(1, 0)),
+    ("ImageSet", SchemaVersion(1, 0)),
+    ("Input.Text", SchemaVersion(1, 0)),
+    ("Input.Number", SchemaVersion(1, 0)),
+    ("Input.Date", SchemaVersion(1, 0)),
+    ("Input.Time", SchemaVersion(1, 0)),
+    ("Input.Toggle", SchemaVersion(1, 0)),
+    ("Input.ChoiceSet", SchemaVersion(1, 0)),
+    ("Media", SchemaVersion(1, 1)),
+    ("ActionSet", SchemaVersion(1, 2)),
+    ("RichTextBlock", SchemaVersion(1, 2)),
+    ("Table", SchemaVersion(1, 5)),
+];
+
+/// Actions and the schema version that introduced them.
+const ACTIONS: &[(&str, SchemaVersion)] = &[
+    ("Action.OpenUrl", SchemaVersion(1, 0)),
+    ("Action.Submit", SchemaVersion(1, 0)),
+    ("Action.ShowCard", SchemaVersion(1, 0)),
+    ("Action.ToggleVisibility", SchemaVersion(1, 2)),
+    ("Action.Execute", SchemaVersion(1, 4)),
+];
+
+/// Schema versions rendered by platforms whose providers do not declare one.
+const KNOWN_MAX_VERSIONS: &[(&str, SchemaVersion)] = &[
+    ("teams", SchemaVersion(1, 5)),
+    ("webex", SchemaVersion(1, 3)),
+    ("webchat", SchemaVersion(1, 6)),
+    ("directline", SchemaVersion(1, 6)),
+];
+
+/// Checks the card against the schema version it declares.
+pub fn validate_card(card: &JsonValue) -> Vec<CardIssue> {
+    let mut issues = Vec::new();
+    let Some(object) = card.as_object() else {
+        issues.push(CardIssue::error("", "card must be a JSON object"));
+        return issues;
+    };
+    if object.get("type").and_then(JsonValue::as_str) != Some("AdaptiveCard") {
+        issues.push(CardIssue::error("/type", "type must be \"AdaptiveCard\""));
+    }
+    let declared = match object.get("version").and_then(JsonValue::as_str) {
+        Some(raw) => match SchemaVersion::parse(raw) {
+            Some(version) if version > LATEST_VERSION => {
+                issues.push(CardIssue::warning(
+                    "/version",
+                    format!("version {version} is newer than {LATEST_VERSION}, the newest known"),
+                ));
+                Some(version)
+            }
+            Some(version) => Some(version),
+            None => {
+                issues.push(CardIssue::error(
+                    "/version",
+                    format!("version {raw:?} is not MAJOR.MINOR"),
+                ));
+                None
+            }
+        },
+        None => {
+            issues.push(CardIssue::error("/version", "version is required"));
+            None
+        }
+    };
+    let mut walker = Walker {
+        version: declared,
+        target: "card",
+        structure: true,
+        issues: &mut issues,
+        input_ids: BTreeSet::new(),
+    };
+    match object.get("body") {
+        Some(JsonValue::Array(items)) => walker.elements(items, "/body"),
+        Some(_) => walker
+            .issues
+            .push(CardIssue::error("/body", "body must be an array")),
+        None => {}
+    }
+    match object.get("actions") {
+        Some(JsonValue::Array(actions)) => walker.actions(actions, "/actions"),
+        Some(_) => walker
+            .issues
+            .push(CardIssue::error("/actions", "actions must be an array")),
+        None => {}
+    }
+    if object.get("body").is_none() && object.get("actions").is_none() {
+        issues.push(CardIssue::warning("", "card has neither body nor actions"));
+    }
+    issues
+}
+
+/// What a provider's render plan says about adaptive cards.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct CardSupport {
+    /// `None` when neither the plan nor the known platforms say.
+    pub supported: Option<bool>,
+    pub max_version: Option<SchemaVersion>,
+    /// Plan `warnings`, as given.
+    pub warnings: Vec<String>,
+}
+
+pub fn card_support_from_plan(plan: &RenderPlanOutV1, provider: &str) -> CardSupport {
+    let plan_value = plan
+        .plan
+        .as_ref()
+        .and_then(|plan| serde_json::from_str::<JsonValue>(&plan.plan_json).ok())
+        .unwrap_or(JsonValue::Null);
+    let mut support = CardSupport {
+        warnings: plan_value
+            .get("warnings")
+            .and_then(JsonValue::as_array)
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| {
+                        item.as_str()
+                            .or_else(|| item.get("message").and_then(JsonValue::as_str))
+                            .map(str::to_string)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        ..CardSupport::default()
+    };
+    match plan_value
+        .get("adaptive_cards")
+        .or_else(|| plan_value.pointer("/capabilities/adaptive_cards"))
+    {
+        Some(JsonValue::Bool(supported)) => support.supported = Some(*supported),
+        Some(JsonValue::Object(map)) => {
+            support.supported = map.get("supported").and_then(JsonValue::as_bool);
+            support.max_version = map
+                .get("max_version")
+                .and_then(JsonValue::as_str)
+                .and_then(SchemaVersion::parse);
+            if support.supported.is_none() && support.max_version.is_some() {
+                support.supported = Some(true);
+            }
+        }
+        _ => {
+            let provider = provider.to_ascii_lowercase();
+            if let Some((_, version)) = KNOWN_MAX_VERSIONS
+                .iter()
+                .find(|(name, _)| provider.contains(name))
+            {
+                support.supported = Some(true);
+                support.max_version = Some(*version);
+            }
+        }
+    }
+    support
+}
+
+/// Warnings for sending `card` to a provider with `support`.
+pub fn check_provider_support(card: &JsonValue, support: &CardSupport) -> Vec<CardIssue> {
+    let mut issues = support
+        .warnings
+        .iter()
+        .map(|warning| CardIssue::warning("", warning.clone()))
+        .collect::<Vec<_>>();
+    match support.supported {
+        Some(false) => {
+            issues.push(CardIssue::warning(
+                "",
+                "provider does not render adaptive cards; only the fallback text is sent",
+            ));
+            return issues;
+        }
+        None => issues.push(CardIssue::warning(
+            "",
+            "provider does not declare adaptive card support; it may send only the fallback text",
+        )),
+        Some(true) => {}
+    }
+    let Some(max) = support.max_version else {
+        return issues;
+    };
+    if let Some(version) = card
+        .get("version")
+        .and_then(JsonValue::as_str)
+        .and_then(SchemaVersion::parse)
+        && version > max
+    {
+        issues.push(CardIssue::warning(
+            "/version",
+            format!("card declares {version}; provider renders up to {max}"),
+        ));
+    }
+    let mut walker = Walker {
+        version: Some(max),
+        target: "provider",
+        structure: false,
+        issues: &mut issues,
+        input_ids: BTreeSet::new(),
+    };
+    if let Some(JsonValue::Array(items)) = card.get("body") {
+        walker.elements(items, "/body");
+    }
+    if let Some(JsonValue::Array(actions)) = card.get("actions") {
+        walker.actions(actions, "/actions");
+    }
+    issues
+}
+
+struct Walker<'a> {
+    /// Elements newer than this are reported; `None` skips version checks.
+    version: Option<SchemaVersion>,
+    /// Who `version` belongs to, for messages.
+    target: &'static str,
+    /// Structural checks run once, against the card. Against a provider only the
+    /// version gaps matter, and they are warnings.
+    structure: bool,
+    issues: &'a mut Vec<CardIssue>,
+    input_ids: BTreeSet<String>,
+}
+
+impl Walker<'_> {
+    fn elements(&mut self, items: &[JsonValue], path: &str) {
+        for (index, item) in items.iter().enumerate() {
+            self.element(item, &format!("{path}/{index}"));
+        }
+    }
+
+    fn element(&mut self, item: &JsonValue, path: &str) {
+        let Some(kind) = item.get("type").and_then(JsonValue::as_str) else {
+            if self.structure {
+                self.issues
+                    .push(CardIssue::error(path, "element has no type"));
+            }
+            return;
+        };
+        match ELEMENTS.iter().find(|(name, _)| *name == kind) {
+            Some((_, since)) => self.check_version(kind, *since, path),
+            None if self.structure => self.issues.push(CardIssue::warning(
+                path,
+                format!("unknown element type {kind}"),
+            )),
+            None => {}
+        }
+        if self.structure {
+            self.check_element(kind, item, path);
+        }
+        if let Some(JsonValue::Array(children)) = item.get("items") {
+            self.elements(children, &format!("{path}/items"));
+        }
+        if let Some(JsonValue::Array(columns)) = item.get("columns") {
+            for (index, column) in columns.iter().enumerate() {
+                if let Some(JsonValue::Array(children)) = column.get("items") {
+                    self.elements(children, &format!("{path}/columns/{index}/items"));
+                }
+            }
+        }
+        if let Some(JsonValue::Array(actions)) = item.get("actions") {
+            self.actions(actions, &format!("{path}/actions"));
+        }
+    }
+
+    fn check_element(&mut self, kind: &str, item: &JsonValue, path: &str) {
+        match kind {
+            "TextBlock" if item.get("text").and_then(JsonValue::as_str).is_none() => {
+                self.issues
+                    .push(CardIssue::error(path, "TextBlock needs a text"));
+            }
+            "Image" if item.get("url").and_then(JsonValue::as_str).is_none() => {
+                self.issues
+                    .push(CardIssue::error(path, "Image needs a url"));
+            }
+            _ => {}
+        }
+        if kind.starts_with("Input.") {
+            match item.get("id").and_then(JsonValue::as_str) {
+                None | Some("") => self
+                    .issues
+                    .push(CardIssue::error(path, format!("{kind} needs an id"))),
+                Some(id) if !self.input_ids.insert(id.to_string()) => self
+                    .issues
+                    .push(CardIssue::error(path, format!("duplicate input id {id}"))),
+                Some(_) => {}
+            }
+        }
+    }
+
+    fn actions(&mut self, actions: &[JsonValue], path: &str) {
+        for (index, action) in actions.iter().enumerate() {
+            let path = format!("{path}/{index}");
+            let Some(kind) = action.get("type").and_then(JsonValue::as_str) else {
+                if self.structure {
+                    self.issues
+                        .push(CardIssue::error(&path, "action has no type"));
+                }
+                continue;
+            };
+            match ACTIONS.iter().find(|(name, _)| *name == kind) {
+                Some((_, since)) => self.check_version(kind, *since, &path),
+                None if self.structure => self.issues.push(CardIssue::warning(
+                    &path,
+                    format!("unknown action type {kind}"),
+                )),
+                None => {}
+            }
+            if self.structure {
+                self.check_action(kind, action, &path);
+            }
+            if kind == "Action.ShowCard"
+                && let Some(card) = action.get("card")
+            {
+                if let Some(JsonValue::Array(items)) = card.get("body") {
+                    self.elements(items, &format!("{path}/card/body"));
+                }
+                if let Some(JsonValue::Array(actions)) = card.get("actions") {
+                    self.actions(actions, &format!("{path}/card/actions"));
+                }
+            }
+        }
+    }
+
+    fn check_action(&mut self, kind: &str, action: &JsonValue, path: &str) {
+        if action.get("title").and_then(JsonValue::as_str).is_none() {
+            self.issues
+                .push(CardIssue::warning(path, format!("{kind} has no title")));
+        }
+        match kind {
+            "Action.OpenUrl" if action.get("url").and_then(JsonValue::as_str).is_none() => {
+                self.issues
+                    .push(CardIssue::error(path, "Action.OpenUrl needs a url"));
+            }
+            "Action.ShowCard" if action.get("card").is_none() => {
+                self.issues
+                    .push(CardIssue::error(path, "Action.ShowCard needs a card"));
+            }
+            _ => {}
+        }
+    }
+
+    fn check_version(&mut self, kind: &str, since: SchemaVersion, path: &str) {
+        if let Some(version) = self.version
+            && since > version
+        {
+            let message = format!(
+                "{kind} needs schema {since}, {} targets {version}",
+                self.target
+            );
+            self.issues.push(if self.structure {
+                CardIssue::error(path, message)
+            } else {
+                CardIssue::warning(path, message)
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messaging_universal::dto::RenderPlanOutPlan;
+    use serde_json::json;
+
+    fn card() -> JsonValue {
+        json!({
+            "type": "AdaptiveCard",
+            "version": "1.2",
+            "body": [
+                {"type": "TextBlock", "text": "Book a table"},
+                {"type": "Input.Text", "id": "name"},
+                {"type": "Container", "items": [{"type": "Input.Date", "id": "name"}]},
+                {"type": "Table"}
+            ],
+            "actions": [{"type": "Action.OpenUrl", "title": "Menu"}]
+        })
+    }
+
+    #[test]
+    fn reports_structure_and_version_errors() {
+        let issues = validate_card(&card());
+        let messages = issues
+            .iter()
+            .map(|issue| format!("{} {}", issue.path, issue.message))
+            .collect::<Vec<_>>();
+        assert!(messages.contains(&"/body/2/items/0 duplicate input id name".to_string()));
+        assert!(messages.contains(&"/body/3 Table needs schema 1.5, card targets 1.2".to_string()));
+        assert!(messages.contains(&"/actions/0 Action.OpenUrl needs a url".to_string()));
+        assert!(issues.iter().all(|issue| issue.severity == Severity::Error));
+    }
+
+    #[test]
+    fn plan_declaration_beats_known_platform_versions() {
+        let plan = |plan_json: JsonValue| RenderPlanOutV1 {
+            ok: true,
+            plan: Some(RenderPlanOutPlan {
+                plan_json: plan_json.to_string(),
+            }),
+            error: None,
+        };
+        let declared = card_support_from_plan(
+            &plan(json!({
+                "capabilities": {"adaptive_cards": {"max_version": "1.0"}},
+                "warnings": ["inputs are dropped"]
+            })),
+            "messaging-teams",
+        );
+        assert_eq!(declared.max_version, Some(SchemaVersion(1, 0)));
+        assert_eq!(declared.warnings, vec!["inputs are dropped".to_string()]);
+
+        let known = card_support_from_plan(&plan(json!({})), "messaging-webex");
+        assert_eq!(known.max_version, Some(SchemaVersion(1, 3)));
+
+        let issues = check_provider_support(&card(), &declared);
+        assert!(
+            issues
+                .iter()
+                .all(|issue| issue.severity == Severity::Warning)
+        );
+        assert!(
+            issues
+                .iter()
+                .any(|issue| issue.message == "card declares 1.2; provider renders up to 1.0")
+        );
+    }
+}
@@ -0,0 +1,363 @@
+//! Per-provider circuit breaker and error budget for sends and ingress.
+//!
+//! Thresholds come from `circuit_breaker` in greentic.demo.yaml (see
+//! [`DemoCircuitBreakerConfig`]). Every `send_payload` and `ingest_http` outcome is
+//! counted per `tenant.team` and provider in `state/circuits.json`, which is read
+//! and rewritten on each call so separate `demo send` runs and a running `demo
+//! start` share one breaker. After `failure_threshold` consecutive failures the
+//! circuit opens and those ops are refused with `circuit_open` until the cool-down
+//! ends; the next call is then a trial that closes the circuit on success and
+//! reopens it on failure.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
+
+use crate::config::{self, DemoCircuitBreakerConfig};
+use crate::demo::runner_host::FlowOutcome;
+use crate::operator_log;
+use crate::runtime_state::{read_json, write_json};
+
+/// Provider ops the breaker counts and guards.
+pub const GUARDED_OPS: &[&str] = &["send_payload", "ingest_http"];
+
+/// Counters for one provider, as written to `state/circuits.json`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct BreakerState {
+    #[serde(default)]
+    pub consecutive_failures: u32,
+    #[serde(default)]
+    pub successes: u64,
+    #[serde(default)]
+    pub failures: u64,
+    /// Times the circuit opened.
+    #[serde(default)]
+    pub trips: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_until: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_failure_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CircuitStatus {
+    Closed,
+    Open,
+    /// The cool-down is over; the next call decides.
+    HalfOpen,
+}
+
+impl CircuitStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CircuitStatus::Closed => "closed",
+            CircuitStatus::Open => "open",
+            CircuitStatus::HalfOpen => "half-open",
+        }
+    }
+}
+
+impl BreakerState {
+    pub fn status(&self, now: DateTime<Utc>) -> CircuitStatus {
+        match self.open_until {
+            Some(until) if until > now => CircuitStatus::Open,
+            Some(_) => CircuitStatus::HalfOpen,
+            None => CircuitStatus::Closed,
+        }
+    }
+
+    /// Share of calls that failed, the spent part of the error budget.
+    pub fn error_rate(&self) -> f64 {
+        let total = self.successes + self.failures;
+        if total == 0 {
+            0.0
+        } else {
+            self.failures as f64 / total as f64
+        }
+    }
+}
+
+/// Why a call was refused; serialized as the flow output of the refusal.
+#[derive(Clone, Debug, Serialize)]
+pub struct CircuitOpen {
+    pub tenant: String,
+    pub team: String,
+    pub provider: String,
+    pub until: DateTime<Utc>,
+    pub consecutive_failures: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+impl CircuitOpen {
+    pub fn to_json(&self) -> JsonValue {
+        json!({
+            "code": "circuit_open",
+            "error": {
+                "type": "CircuitOpen",
+                "tenant": self.tenant,
+                "team": self.team,
+                "provider": self.provider,
+                "until": self.until.to_rfc3339(),
+                "consecutive_failures": self.consecutive_failures,
+                "last_error": self.last_error,
+            }
+        })
+    }
+}
+
+impl fmt::Display for CircuitOpen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "circuit open for {} in {}.{} until {} after {} consecutive failures",
+            self.provider,
+            self.tenant,
+            self.team,
+            self.until.to_rfc3339(),
+            self.consecutive_failures
+        )
+    }
+}
+
+impl std::error::Error for CircuitOpen {}
+
+/// `tenant.team` → provider → state.
+pub type Circuits = BTreeMap<String, BTreeMap<String, BreakerState>>;
+
+#[derive(Clone)]
+pub struct CircuitBreaker {
+    config: DemoCircuitBreakerConfig,
+    circuits: Arc<Mutex<Circuits>>,
+    path: Option<PathBuf>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: DemoCircuitBreakerConfig, path: Option<PathBuf>) -> Self {
+        Self {
+            config,
+            circuits: Arc::new(Mutex::new(Circuits::new())),
+            path,
+        }
+    }
+
+    /// Reads `circuit_breaker` from the bundle's greentic.demo.yaml.
+    pub fn from_bundle(bundle_root: &Path) -> Self {
+        let config_path = bundle_root.join("greentic.demo.yaml");
+        let config = if config_path.exists() {
+            config::load_demo_config(&config_path)
+                .map(|config| config.circuit_breaker)
+                .unwrap_or_default()
+        } else {
+            DemoCircuitBreakerConfig::default()
+        };
+        Self::new(config, Some(circuits_path(&bundle_root.join("state"))))
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.failure_threshold > 0
+    }
+
+    /// Lets a call through unless the provider's circuit is open at `now`.
+    pub fn admit(
+        &self,
+        tenant: &str,
+        team: Option<&str>,
+        provider: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), CircuitOpen> {
+        if !self.enabled() {
+            return Ok(());
+        }
+        let team_label = team.unwrap_or("default");
+        let mut circuits = self.lock();
+        self.reload(&mut circuits);
+        let Some(state) = circuits
+            .get(&format!("{tenant}.{team_label}"))
+            .and_then(|providers| providers.get(provider))
+        else {
+            return Ok(());
+        };
+        match (state.status(now), state.open_until) {
+            (CircuitStatus::Open, Some(until)) => Err(CircuitOpen {
+                tenant: tenant.to_string(),
+                team: team_label.to_string(),
+                provider: provider.to_string(),
+                until,
+                consecutive_failures: state.consecutive_failures,
+                last_error: state.last_error.clone(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Counts one call; `error` is `None` for a success.
+    pub fn record(
+        &self,
+        tenant: &str,
+        team: Option<&str>,
+        provider: &str,
+        error: Option<&str>,
+        now: DateTime<Utc>,
+    ) {
+        if !self.enabled() {
+            return;
+        }
+        let key = format!("{tenant}.{}", team.unwrap_or("default"));
+        let mut circuits = self.lock();
+        self.reload(&mut circuits);
+        let state = circuits
+            .entry(key)
+            .or_default()
+            .entry(provider.to_string())
+            .or_default();
+        match error {
+            None => {
+                state.successes += 1;
+                state.consecutive_failures = 0;
+                state.open_until = None;
+            }
+            Some(error) => {
+                state.failures += 1;
+                state.consecutive_failures += 1;
+                state.last_error = Some(error.to_string());
+                state.last_failure_at = Some(now);
+                if state.consecutive_failures >= self.config.failure_threshold {
+                    let cooldown = i64::try_from(self.config.cooldown_secs).unwrap_or(i64::MAX);
+                    state.open_until = Some(now + chrono::Duration::seconds(cooldown));
+                    state.trips += 1;
+                    operator_log::warn(
+                        module_path!(),
+                        format!(
+                            "circuit opened for {provider} after {} consecutive failures; cooling down {}s",
+                            state.consecutive_failures, self.config.cooldown_secs
+                        ),
+                    );
+                }
+            }
+        }
+        self.persist(&circuits);
+    }
+
+    pub fn circuits(&self) -> Circuits {
+        let mut circuits = self.lock();
+        self.reload(&mut circuits);
+        circuits.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Circuits> {
+        self.circuits.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Picks up what other processes wrote since the last call.
+    fn reload(&self, circuits: &mut Circuits) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        match read_json::<Circuits>(path) {
+            Ok(Some(stored)) => *circuits = stored,
+            Ok(None) => {}
+            Err(err) => operator_log::warn(
+                module_path!(),
+                format!("failed to read circuits {}: {err}", path.display()),
+            ),
+        }
+    }
+
+    fn persist(&self, circuits: &Circuits) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if let Err(err) = write_json(path, circuits) {
+            operator_log::warn(
+                module_path!(),
+                format!("failed to write circuits {}: {err}", path.display()),
+            );
+        }
+    }
+}
+
+/// Why `outcome` counts as a failure: the op failed, or it ran but the provider
+/// answered `ok: false`.
+pub fn outcome_error(outcome: &FlowOutcome) -> Option<String> {
+    if !outcome.success {
+        return Some(
+            outcome
+                .error
+                .clone()
+                .unwrap_or_else(|| "provider op failed".to_string()),
+        );
+    }
+    let output = outcome.output.as_ref()?;
+    if output.get("ok").and_then(JsonValue::as_bool) != Some(false) {
+        return None;
+    }
+    let message = ["error", "message"]
+        .iter()
+        .find_map(|key| output.get(key).and_then(JsonValue::as_str))
+        .unwrap_or("provider answered ok=false");
+    Some(message.to_string())
+}
+
+pub fn circuits_path(state_dir: &Path) -> PathBuf {
+    state_dir.join("circuits.json")
+}
+
+/// Breaker states last written, keyed by `tenant.team` then provider.
+pub fn read_circuits(state_dir: &Path) -> anyhow::Result<Circuits> {
+    Ok(read_json(&circuits_path(state_dir))?.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_after_threshold_and_closes_after_a_successful_trial() {
+        let breaker = CircuitBreaker::new(
+            DemoCircuitBreakerConfig {
+                failure_threshold: 3,
+                cooldown_secs: 60,
+            },
+            None,
+        );
+        let start = Utc::now();
+        let at = |secs: i64| start + chrono::Duration::seconds(secs);
+        for second in 0..3 {
+            assert!(breaker.admit("demo", None, "telegram", at(second)).is_ok());
+            breaker.record(
+                "demo",
+                None,
+                "telegram",
+                Some("401 Unauthorized"),
+                at(second),
+            );
+        }
+        let open = breaker
+            .admit("demo", None, "telegram", at(10))
+            .expect_err("open after three failures");
+        assert_eq!(open.until, at(62));
+        assert_eq!(open.to_json()["code"], "circuit_open");
+        assert!(breaker.admit("demo", None, "slack", at(10)).is_ok());
+        assert!(breaker.admit("acme", None, "telegram", at(10)).is_ok());
+
+        // Cool-down over: one trial goes through, and its failure reopens at once.
+        assert!(breaker.admit("demo", None, "telegram", at(62)).is_ok());
+        breaker.record("demo", None, "telegram", Some("401 Unauthorized"), at(62));
+        assert!(breaker.admit("demo", None, "telegram", at(63)).is_err());
+
+        breaker.record("demo", None, "telegram", None, at(130));
+        let state = &breaker.circuits()["demo.default"]["telegram"];
+        assert_eq!(state.status(at(130)), CircuitStatus::Closed);
+        assert_eq!((state.failures, state.successes, state.trips), (4, 1, 2));
+        assert!((state.error_rate() - 0.8).abs() < f64::EPSILON);
+    }
+}
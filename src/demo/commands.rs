@@ -1,5 +1,7 @@
 use std::fmt;
 
+use serde_json::Value as JsonValue;
+
 /// Commands that are recognized inside the interactive demo REPL.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum DemoCommand {
@@ -11,6 +13,13 @@ pub enum DemoCommand {
     Back,
     Quit,
     Help,
+    Break { node: Option<String> },
+    Unbreak { node: String },
+    Step,
+    Continue,
+    Payload,
+    Edit { payload: JsonValue },
+    Rerun,
 }
 
 /// Error returned when the input line cannot be parsed.
@@ -51,6 +60,33 @@ pub fn parse_command(line: &str) -> Result<DemoCommand, CommandParseError> {
         "back" => Ok(DemoCommand::Back),
         "help" => Ok(DemoCommand::Help),
         "quit" => Ok(DemoCommand::Quit),
+        "step" => Ok(DemoCommand::Step),
+        "continue" => Ok(DemoCommand::Continue),
+        "payload" => Ok(DemoCommand::Payload),
+        "rerun" => Ok(DemoCommand::Rerun),
+        "break" => Ok(DemoCommand::Break {
+            node: if rest.is_empty() {
+                None
+            } else {
+                Some(rest.to_string())
+            },
+        }),
+        "unbreak" => {
+            if rest.is_empty() {
+                Err(CommandParseError::InvalidFormat(
+                    "@unbreak requires a node id".to_string(),
+                ))
+            } else {
+                Ok(DemoCommand::Unbreak {
+                    node: rest.to_string(),
+                })
+            }
+        }
+        "edit" => serde_json::from_str(rest)
+            .map(|payload| DemoCommand::Edit { payload })
+            .map_err(|err| {
+                CommandParseError::InvalidFormat(format!("@edit requires a JSON payload: {err}"))
+            }),
         "setup" => Ok(DemoCommand::Setup {
             provider: if rest.is_empty() {
                 None
@@ -136,4 +172,32 @@ mod tests {
         let command = parse_command("@back").unwrap();
         assert_eq!(command, DemoCommand::Back);
     }
+
+    #[test]
+    fn parses_debug_commands() {
+        assert_eq!(
+            parse_command("@break render").unwrap(),
+            DemoCommand::Break {
+                node: Some("render".into())
+            }
+        );
+        assert_eq!(
+            parse_command("@break").unwrap(),
+            DemoCommand::Break { node: None }
+        );
+        assert_eq!(
+            parse_command(r#"@edit {"text": "hi"}"#).unwrap(),
+            DemoCommand::Edit {
+                payload: serde_json::json!({"text": "hi"})
+            }
+        );
+        assert!(matches!(
+            parse_command("@edit {oops"),
+            Err(CommandParseError::InvalidFormat(_))
+        ));
+        assert!(matches!(
+            parse_command("@unbreak"),
+            Err(CommandParseError::InvalidFormat(_))
+        ));
+    }
 }
@@ -0,0 +1,553 @@
+//! Local JSON-over-HTTP control API for a running `demo start`.
+//!
+//! `demo start --control-port <PORT>` serves this on 127.0.0.1 so IDE plugins and test
+//! harnesses can drive the demo without shelling out to the CLI for every step. The
+//! URL and a per-session bearer token are written to `state/control.json` (mode 0600
+//! on Unix); every request needs `Authorization: Bearer <token>`.
+//!
+//! Routes:
+//!   GET  /v1/status                  → bundle, target, public URL, ports, services
+//!   GET  /v1/logs/{service}?lines=N  → last N lines of a service log (default 200)
+//!   POST /v1/send                    → `demo send` {provider, text, to, to_kind, args}
+//!   POST /v1/run                     → run a pack flow until it blocks {pack, flow, input}
+//!   POST /v1/policy                  → `demo allow|forbid` {path, policy}
+//!   POST /v1/reload                  → `demo reload`: re-read config, gmaps, manifests
+//!
+//! `send`, `run`, and `policy` accept optional `tenant`/`team` overrides and are
+//! carried out by a [`ControlBackend`] supplied by the CLI.
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+
+use anyhow::{Context, Result};
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    Method, Request, Response, StatusCode,
+    body::{Bytes, Incoming},
+    header::AUTHORIZATION,
+    server::conn::http1::Builder as Http1Builder,
+    service::service_fn,
+};
+use hyper_util::rt::tokio::TokioIo;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
+use tokio::{net::TcpListener, runtime::Runtime, sync::oneshot};
+
+use crate::cloudflared;
+use crate::demo::ingress_router::constant_time_eq;
+use crate::demo::ports;
+use crate::demo::runtime::{demo_log_path, read_last_lines};
+use crate::onboard::api::{error_response, json_ok};
+use crate::operator_log;
+use crate::runtime_state::{RuntimePaths, read_json, write_private_json};
+use crate::supervisor::{self, ServiceId};
+
+const DEFAULT_LOG_LINES: usize = 200;
+const MAX_LOG_LINES: usize = 5000;
+
+type HttpResponse = Response<Full<Bytes>>;
+
+/// Discovery record written to `state/control.json` while the API is up.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ControlEndpoint {
+    pub url: String,
+    pub token: String,
+    pub pid: u32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SendRequest {
+    pub provider: String,
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub to: Vec<String>,
+    #[serde(default)]
+    pub to_kind: Option<String>,
+    /// Extra message metadata, as `demo send --args-json`.
+    #[serde(default)]
+    pub args: Option<JsonValue>,
+    #[serde(default)]
+    pub tenant: Option<String>,
+    #[serde(default)]
+    pub team: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct RunFlowRequest {
+    pub pack: String,
+    #[serde(default)]
+    pub flow: Option<String>,
+    #[serde(default)]
+    pub input: Option<JsonValue>,
+    #[serde(default)]
+    pub tenant: Option<String>,
+    #[serde(default)]
+    pub team: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyAction {
+    Allow,
+    Forbid,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PolicyRequest {
+    pub path: String,
+    pub policy: PolicyAction,
+    #[serde(default)]
+    pub tenant: Option<String>,
+    #[serde(default)]
+    pub team: Option<String>,
+}
+
+/// The mutating operations; implemented by the CLI so they share the code paths of
+/// `demo send`, `demo run`, and `demo allow|forbid`. Calls run on a plain thread and
+/// may block or start their own Tokio runtime.
+pub trait ControlBackend: Send + Sync {
+    fn send(&self, request: SendRequest) -> Result<JsonValue>;
+    fn run_flow(&self, request: RunFlowRequest) -> Result<JsonValue>;
+    fn edit_policy(&self, request: PolicyRequest) -> Result<JsonValue>;
+    fn reload(&self) -> Result<JsonValue>;
+}
+
+#[derive(Clone)]
+pub struct ControlApiConfig {
+    pub bind_addr: SocketAddr,
+    pub bundle: PathBuf,
+    pub log_dir: PathBuf,
+    pub tenant: String,
+    pub team: String,
+    pub backend: Arc<dyn ControlBackend>,
+}
+
+pub struct ControlApiServer {
+    endpoint: ControlEndpoint,
+    endpoint_path: PathBuf,
+    shutdown: Option<oneshot::Sender<()>>,
+    handle: Option<thread::JoinHandle<Result<()>>>,
+}
+
+impl ControlApiServer {
+    pub fn start(config: ControlApiConfig) -> Result<Self> {
+        let std_listener = std::net::TcpListener::bind(config.bind_addr)
+            .with_context(|| format!("failed to bind control API on {}", config.bind_addr))?;
+        std_listener.set_nonblocking(true)?;
+        let addr = std_listener.local_addr()?;
+        let endpoint = ControlEndpoint {
+            url: format!("http://{addr}"),
+            token: uuid::Uuid::new_v4().simple().to_string(),
+            pid: std::process::id(),
+        };
+        let endpoint_path = publish_endpoint(&config.bundle.join("state"), &endpoint)?;
+        let state = Arc::new(ControlState {
+            token: endpoint.token.clone(),
+            config,
+        });
+        let (tx, rx) = oneshot::channel();
+        let handle = thread::Builder::new()
+            .name("demo-control-api".to_string())
+            .spawn(move || -> Result<()> {
+                let runtime = Runtime::new().context("failed to create control API runtime")?;
+                runtime.block_on(async move {
+                    let listener = TcpListener::from_std(std_listener)
+                        .context("failed to register control API listener")?;
+                    operator_log::info(
+                        module_path!(),
+                        format!("demo control API listening on http://{addr}"),
+                    );
+                    let mut shutdown = rx;
+                    loop {
+                        tokio::select! {
+                            _ = &mut shutdown => break,
+                            accept = listener.accept() => match accept {
+                                Ok((stream, _peer)) => {
+                                    let connection_state = state.clone();
+                                    tokio::spawn(async move {
+                                        let service = service_fn(move |req| {
+                                            handle_request(req, connection_state.clone())
+                                        });
+                                        let stream = TokioIo::new(stream);
+                                        if let Err(err) = Http1Builder::new()
+                                            .serve_connection(stream, service)
+                                            .await
+                                        {
+                                            operator_log::error(
+                                                module_path!(),
+                                                format!("control API connection error: {err}"),
+                                            );
+                                        }
+                                    });
+                                }
+                                Err(err) => operator_log::error(
+                                    module_path!(),
+                                    format!("control API accept error: {err}"),
+                                ),
+                            },
+                        }
+                    }
+                    Ok(())
+                })
+            })?;
+        Ok(Self {
+            endpoint,
+            endpoint_path,
+            shutdown: Some(tx),
+            handle: Some(handle),
+        })
+    }
+
+    pub fn endpoint(&self) -> &ControlEndpoint {
+        &self.endpoint
+    }
+
+    pub fn stop(mut self) -> Result<()> {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+        if let Some(handle) = self.handle.take() {
+            handle
+                .join()
+                .map_err(|err| anyhow::anyhow!("control API panicked: {err:?}"))??;
+        }
+        if self.endpoint_path.exists() {
+            std::fs::remove_file(&self.endpoint_path)
+                .with_context(|| format!("failed to remove {}", self.endpoint_path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+pub fn endpoint_path(state_dir: &Path) -> PathBuf {
+    state_dir.join("control.json")
+}
+
+/// Writes `state/control.json`. It carries the bearer token, so only the owner may
+/// read it.
+fn publish_endpoint(state_dir: &Path, endpoint: &ControlEndpoint) -> Result<PathBuf> {
+    let path = endpoint_path(state_dir);
+    write_private_json(&path, endpoint)?;
+    Ok(path)
+}
+
+/// Control endpoint of the demo running from `state_dir`, if one is serving.
+pub fn read_endpoint(state_dir: &Path) -> Result<Option<ControlEndpoint>> {
+    read_json(&endpoint_path(state_dir))
+}
+
+/// POSTs `body` to `path` on a running control API; error responses become errors
+/// carrying the server's message.
+pub fn post(endpoint: &ControlEndpoint, path: &str, body: &JsonValue) -> Result<JsonValue> {
+    let url = format!("{}{path}", endpoint.url.trim_end_matches('/'));
+    let mut response = ureq::post(&url)
+        .config()
+        .http_status_as_error(false)
+        .build()
+        .header("Authorization", &format!("Bearer {}", endpoint.token))
+        .send_json(body)
+        .map_err(|err| anyhow::anyhow!("control API POST {path} failed: {err}"))?;
+    let status = response.status();
+    let body: JsonValue = response
+        .body_mut()
+        .read_json()
+        .with_context(|| format!("control API POST {path} returned invalid JSON"))?;
+    if !status.is_success() {
+        let message = body
+            .get("message")
+            .and_then(JsonValue::as_str)
+            .unwrap_or("no message");
+        return Err(anyhow::anyhow!(
+            "control API POST {path}: {status}: {message}"
+        ));
+    }
+    Ok(body)
+}
+
+struct ControlState {
+    token: String,
+    config: ControlApiConfig,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Endpoint {
+    Status,
+    Logs { service: String, lines: usize },
+    Send,
+    Run,
+    Policy,
+    Reload,
+}
+
+async fn handle_request(
+    req: Request<Incoming>,
+    state: Arc<ControlState>,
+) -> std::result::Result<HttpResponse, Infallible> {
+    Ok(match handle_request_inner(req, state).await {
+        Ok(response) | Err(response) => response,
+    })
+}
+
+async fn handle_request_inner(
+    req: Request<Incoming>,
+    state: Arc<ControlState>,
+) -> std::result::Result<HttpResponse, HttpResponse> {
+    let authorization = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    if !authorized(authorization, &state.token) {
+        return Err(error_response(
+            StatusCode::UNAUTHORIZED,
+            "missing or invalid bearer token",
+        ));
+    }
+    let endpoint = match_endpoint(req.method(), req.uri().path(), req.uri().query())?;
+    operator_log::debug(module_path!(), format!("control API request {endpoint:?}"));
+    let config = &state.config;
+    match endpoint {
+        Endpoint::Status => json_ok(status(config).map_err(internal_error)?),
+        Endpoint::Logs { service, lines } => {
+            json_ok(logs(config, &service, lines).map_err(internal_error)?)
+        }
+        Endpoint::Send => {
+            let request = read_body(req).await?;
+            let backend = Arc::clone(&config.backend);
+            blocking(move || backend.send(request)).await
+        }
+        Endpoint::Run => {
+            let request = read_body(req).await?;
+            let backend = Arc::clone(&config.backend);
+            blocking(move || backend.run_flow(request)).await
+        }
+        Endpoint::Policy => {
+            let request = read_body(req).await?;
+            let backend = Arc::clone(&config.backend);
+            blocking(move || backend.edit_policy(request)).await
+        }
+        Endpoint::Reload => {
+            let backend = Arc::clone(&config.backend);
+            blocking(move || backend.reload()).await
+        }
+    }
+}
+
+fn match_endpoint(
+    method: &Method,
+    path: &str,
+    query: Option<&str>,
+) -> std::result::Result<Endpoint, HttpResponse> {
+    let path = path.trim_end_matches('/');
+    let endpoint = match (method, path) {
+        (&Method::GET, "/v1/status") => Endpoint::Status,
+        (&Method::POST, "/v1/send") => Endpoint::Send,
+        (&Method::POST, "/v1/run") => Endpoint::Run,
+        (&Method::POST, "/v1/policy") => Endpoint::Policy,
+        (&Method::POST, "/v1/reload") => Endpoint::Reload,
+        (&Method::GET, _) if path.starts_with("/v1/logs/") => {
+            let service = &path["/v1/logs/".len()..];
+            ServiceId::new(service)
+                .map_err(|err| error_response(StatusCode::BAD_REQUEST, err.to_string()))?;
+            Endpoint::Logs {
+                service: service.to_string(),
+                lines: log_lines(query)
+                    .map_err(|err| error_response(StatusCode::BAD_REQUEST, err))?,
+            }
+        }
+        _ => {
+            return Err(error_response(
+                StatusCode::NOT_FOUND,
+                format!("unknown control endpoint: {method} {path}"),
+            ));
+        }
+    };
+    Ok(endpoint)
+}
+
+fn authorized(header: Option<&str>, token: &str) -> bool {
+    header
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|value| constant_time_eq(value.trim().as_bytes(), token.as_bytes()))
+}
+
+fn log_lines(query: Option<&str>) -> std::result::Result<usize, String> {
+    let Some(value) = query
+        .unwrap_or_default()
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("lines="))
+    else {
+        return Ok(DEFAULT_LOG_LINES);
+    };
+    value
+        .parse::<usize>()
+        .map(|lines| lines.min(MAX_LOG_LINES))
+        .map_err(|_| format!("invalid lines value: {value}"))
+}
+
+fn status(config: &ControlApiConfig) -> Result<JsonValue> {
+    let state_dir = config.bundle.join("state");
+    let paths = RuntimePaths::new(&state_dir, &config.tenant, &config.team);
+    let services = supervisor::read_status(&paths)?
+        .into_iter()
+        .map(|status| {
+            json!({
+                "id": status.id.as_str(),
+                "running": status.running,
+                "pid": status.pid,
+                "log": status.log_path,
+                "last_error": status.last_error,
+            })
+        })
+        .collect::<Vec<_>>();
+    Ok(json!({
+        "bundle": config.bundle,
+        "tenant": config.tenant,
+        "team": config.team,
+        "pid": std::process::id(),
+        "public_base_url": cloudflared::current_public_url(&paths)?,
+        "ports": ports::read_ports(&state_dir)?,
+        "services": services,
+    }))
+}
+
+fn logs(config: &ControlApiConfig, service: &str, lines: usize) -> Result<JsonValue> {
+    let path = demo_log_path(
+        &config.bundle.join("state"),
+        &config.log_dir,
+        &config.tenant,
+        &config.team,
+        service,
+    )?;
+    let text = read_last_lines(&path, lines)?;
+    Ok(json!({
+        "service": service,
+        "path": path,
+        "lines": text.lines().collect::<Vec<_>>(),
+    }))
+}
+
+async fn read_body<T: serde::de::DeserializeOwned>(
+    req: Request<Incoming>,
+) -> std::result::Result<T, HttpResponse> {
+    let bytes = req
+        .into_body()
+        .collect()
+        .await
+        .map(|collected| collected.to_bytes())
+        .map_err(|err| error_response(StatusCode::BAD_REQUEST, format!("read body: {err}")))?;
+    serde_json::from_slice(&bytes)
+        .map_err(|err| error_response(StatusCode::BAD_REQUEST, format!("invalid request: {err}")))
+}
+
+/// Runs a backend call on the blocking pool so a long send or flow run does not hold
+/// a Tokio worker; the send and flow paths create their own runtimes, which panics
+/// on a worker thread.
+async fn blocking(
+    call: impl FnOnce() -> Result<JsonValue> + Send + 'static,
+) -> std::result::Result<HttpResponse, HttpResponse> {
+    tokio::task::spawn_blocking(call)
+        .await
+        .map_err(|err| internal_error(anyhow::anyhow!("control API handler failed: {err}")))?
+        .map_err(internal_error)
+        .and_then(json_ok)
+}
+
+fn internal_error(err: anyhow::Error) -> HttpResponse {
+    error_response(StatusCode::INTERNAL_SERVER_ERROR, format!("{err:#}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_need_the_session_token() {
+        assert!(authorized(Some("Bearer abc123"), "abc123"));
+        assert!(!authorized(Some("Bearer abc124"), "abc123"));
+        assert!(!authorized(Some("abc123"), "abc123"));
+        assert!(!authorized(None, "abc123"));
+    }
+
+    #[test]
+    fn endpoints_match_method_and_path() {
+        assert_eq!(
+            match_endpoint(&Method::GET, "/v1/status/", None).unwrap(),
+            Endpoint::Status
+        );
+        assert_eq!(
+            match_endpoint(&Method::POST, "/v1/policy", None).unwrap(),
+            Endpoint::Policy
+        );
+        assert_eq!(
+            match_endpoint(&Method::POST, "/v1/reload", None).unwrap(),
+            Endpoint::Reload
+        );
+        assert_eq!(
+            match_endpoint(&Method::GET, "/v1/logs/messaging", Some("lines=20")).unwrap(),
+            Endpoint::Logs {
+                service: "messaging".to_string(),
+                lines: 20
+            }
+        );
+        assert_eq!(
+            match_endpoint(&Method::GET, "/v1/logs/nats", Some("lines=999999")).unwrap(),
+            Endpoint::Logs {
+                service: "nats".to_string(),
+                lines: MAX_LOG_LINES
+            }
+        );
+        for (method, path, query) in [
+            (Method::GET, "/v1/send", None),
+            (Method::GET, "/v1/reload", None),
+            (Method::GET, "/v1/logs/..%2Fsecrets", None),
+            (Method::GET, "/v1/logs/nats", Some("lines=many")),
+            (Method::DELETE, "/v1/status", None),
+        ] {
+            let response = match_endpoint(&method, path, query).unwrap_err();
+            assert!(response.status().is_client_error(), "{method} {path}");
+        }
+    }
+
+    #[test]
+    fn backend_calls_leave_the_worker_free() -> Result<()> {
+        let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+        runtime.block_on(async {
+            let (tx, rx) = std::sync::mpsc::channel::<()>();
+            let call = tokio::spawn(blocking(move || {
+                rx.recv()?;
+                Ok(json!({"ok": true}))
+            }));
+            // The only worker must still be free to run this while the call waits.
+            tokio::spawn(async move { tx.send(()) }).await??;
+            assert!(call.await?.is_ok());
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn endpoint_record_is_readable_from_state() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        assert_eq!(read_endpoint(dir.path())?, None);
+        let endpoint = ControlEndpoint {
+            url: "http://127.0.0.1:7070".to_string(),
+            token: "abc123".to_string(),
+            pid: 42,
+        };
+        let path = publish_endpoint(dir.path(), &endpoint)?;
+        assert_eq!(read_endpoint(dir.path())?, Some(endpoint));
+        // The record carries the bearer token.
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path)?.permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        #[cfg(not(unix))]
+        let _ = path;
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+//! Key/value state per conversation, so multi-turn demos can be seeded and
+//! inspected from the CLI.
+//!
+//! Each conversation is one JSON file at
+//! `state/conversations/<tenant>.<team>/<provider>/<chat>.json`. App flow runs get
+//! the current values as `state` in their input, and the `state_updates` objects
+//! they emit are merged back in; a `null` value removes the key. `demo state
+//! get|set|list` reads and writes the same files.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+use crate::runtime_state;
+
+/// Which conversation a state belongs to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConversationKey {
+    pub tenant: String,
+    pub team: String,
+    pub provider: String,
+    pub chat: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConversationState {
+    pub provider: String,
+    pub chat: String,
+    pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub values: JsonMap<String, JsonValue>,
+}
+
+impl ConversationKey {
+    pub fn new(tenant: &str, team: Option<&str>, provider: &str, chat: &str) -> Self {
+        Self {
+            tenant: tenant.to_string(),
+            team: team.unwrap_or("default").to_string(),
+            provider: provider.to_string(),
+            chat: chat.to_string(),
+        }
+    }
+
+    pub fn path(&self, state_dir: &Path) -> PathBuf {
+        conversations_dir(state_dir, &self.tenant, &self.team)
+            .join(encode_segment(&self.provider))
+            .join(format!("{}.json", encode_segment(&self.chat)))
+    }
+}
+
+/// `state/conversations/<tenant>.<team>`
+pub fn conversations_dir(state_dir: &Path, tenant: &str, team: &str) -> PathBuf {
+    state_dir
+        .join("conversations")
+        .join(format!("{tenant}.{team}"))
+}
+
+/// The stored values, empty when the conversation has none yet.
+pub fn load(state_dir: &Path, key: &ConversationKey) -> anyhow::Result<JsonMap<String, JsonValue>> {
+    let path = key.path(state_dir);
+    Ok(runtime_state::read_json::<ConversationState>(&path)
+        .with_context(|| format!("read conversation state {}", path.display()))?
+        .map(|state| state.values)
+        .unwrap_or_default())
+}
+
+/// Merges `updates` into the stored values; `null` removes a key. Returns the
+/// values after the merge.
+pub fn apply_updates(
+    state_dir: &Path,
+    key: &ConversationKey,
+    updates: &JsonMap<String, JsonValue>,
+) -> anyhow::Result<JsonMap<String, JsonValue>> {
+    let mut values = load(state_dir, key)?;
+    for (name, value) in updates {
+        if value.is_null() {
+            values.remove(name);
+        } else {
+            values.insert(name.clone(), value.clone());
+        }
+    }
+    let path = key.path(state_dir);
+    if values.is_empty() {
+        match std::fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err).with_context(|| format!("remove {}", path.display())),
+        }
+        return Ok(values);
+    }
+    let state = ConversationState {
+        provider: key.provider.clone(),
+        chat: key.chat.clone(),
+        updated_at: Utc::now(),
+        values,
+    };
+    runtime_state::write_json(&path, &state)?;
+    Ok(state.values)
+}
+
+/// Conversations with stored state for `tenant`/`team`, optionally one provider's,
+/// most recently updated first.
+pub fn list(
+    state_dir: &Path,
+    tenant: &str,
+    team: &str,
+    provider: Option<&str>,
+) -> anyhow::Result<Vec<ConversationState>> {
+    let root = conversations_dir(state_dir, tenant, team);
+    let provider_dirs = match provider {
+        Some(provider) => vec![root.join(encode_segment(provider))],
+        None => match std::fs::read_dir(&root) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.is_dir())
+                .collect(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err).with_context(|| format!("read {}", root.display())),
+        },
+    };
+    let mut states = Vec::new();
+    for dir in provider_dirs {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err).with_context(|| format!("read {}", dir.display())),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            if let Some(state) = runtime_state::read_json::<ConversationState>(&path)
+                .with_context(|| format!("read conversation state {}", path.display()))?
+            {
+                states.push(state);
+            }
+        }
+    }
+    states.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    Ok(states)
+}
+
+/// Provider names and chat ids become file names; anything beyond
+/// `[A-Za-z0-9_-]` is `%XX`-escaped so `!room:matrix.org` or `a/b` stay one segment.
+fn encode_segment(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_' {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    if encoded.is_empty() {
+        "%".to_string()
+    } else {
+        encoded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn updates_merge_and_null_removes_keys() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let key = ConversationKey::new("demo", None, "messaging-matrix", "!room:matrix.org");
+        let updates = |value: JsonValue| value.as_object().cloned().unwrap();
+
+        apply_updates(
+            dir.path(),
+            &key,
+            &updates(json!({"step": "date", "guests": 2})),
+        )?;
+        let values = apply_updates(
+            dir.path(),
+            &key,
+            &updates(json!({"step": "confirm", "guests": null})),
+        )?;
+        assert_eq!(JsonValue::Object(values), json!({"step": "confirm"}));
+        assert!(
+            key.path(dir.path())
+                .ends_with("demo.default/messaging-matrix/%21room%3Amatrix%2Eorg.json")
+        );
+
+        let listed = list(dir.path(), "demo", "default", None)?;
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].chat, "!room:matrix.org");
+
+        apply_updates(dir.path(), &key, &updates(json!({"step": null})))?;
+        assert!(!key.path(dir.path()).exists());
+        assert!(load(dir.path(), &key)?.is_empty());
+        Ok(())
+    }
+}
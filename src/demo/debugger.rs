@@ -0,0 +1,482 @@
+//! Breakpoints and node-by-node stepping for `demo run`.
+//!
+//! The debugger watches the flow engine as an execution observer. Before a node
+//! with a breakpoint runs, or before every node while stepping, it holds the flow
+//! and reads debug commands from stdin. Editing the payload or re-running a node
+//! abandons the run in flight: the observer hands the runner an [`Interrupt`] and
+//! keeps the paused node from ever starting, while the runner drops that run and
+//! resumes from a snapshot rebuilt from the node outputs seen so far. Output
+//! already emitted by earlier nodes of that run is not repeated.
+
+use std::collections::BTreeSet;
+use std::error::Error as StdError;
+use std::fmt;
+use std::io::{self, BufRead};
+use std::sync::{Mutex, MutexGuard};
+
+use anyhow::Context;
+use greentic_runner_host::runner::engine::{ExecutionObserver, FlowSnapshot, NodeEvent};
+use serde_json::{Value as JsonValue, json};
+use tokio::sync::oneshot;
+
+use crate::demo::commands::{DemoCommand, parse_command};
+use crate::demo::help::print_help;
+use crate::operator_i18n;
+
+/// Returned by the runner when the user quits while the flow is paused.
+#[derive(Debug)]
+pub struct Stopped;
+
+impl fmt::Display for Stopped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "flow stopped at a breakpoint")
+    }
+}
+
+impl StdError for Stopped {}
+
+/// Sent to the runner when a paused run is abandoned.
+pub enum Interrupt {
+    /// Resume the flow from `snapshot` with `input`.
+    Restart {
+        snapshot: Box<FlowSnapshot>,
+        input: JsonValue,
+    },
+    Quit,
+}
+
+#[derive(Default)]
+pub struct Debugger {
+    state: Mutex<DebugState>,
+}
+
+#[derive(Default)]
+struct DebugState {
+    breakpoints: BTreeSet<String>,
+    stepping: bool,
+    /// Pause before the next node whatever the breakpoints say.
+    pause_next: bool,
+    run: Option<Segment>,
+    /// Where to send the [`Interrupt`] when the current attempt is abandoned.
+    abandon: Option<oneshot::Sender<Interrupt>>,
+}
+
+/// One call of the engine as the debugger saw it, until the flow blocks or ends.
+struct Segment {
+    pack_id: String,
+    flow_id: String,
+    input: JsonValue,
+    /// Serialized engine state when the segment started.
+    base: JsonValue,
+    /// Whether `base` is a fresh run, where `entry` follows the input.
+    fresh: bool,
+    steps: Vec<Step>,
+}
+
+struct Step {
+    flow_id: String,
+    node_id: String,
+    output: JsonValue,
+}
+
+impl Segment {
+    fn new(
+        pack_id: &str,
+        flow_id: &str,
+        snapshot: Option<&FlowSnapshot>,
+        input: &JsonValue,
+    ) -> Self {
+        let resumed = snapshot.and_then(|snapshot| serde_json::to_value(&snapshot.state).ok());
+        let fresh = resumed.is_none();
+        let mut base = resumed.unwrap_or_else(|| {
+            json!({
+                "entry": input,
+                "nodes": {},
+                "egress": [],
+                "redirect_count": 0,
+            })
+        });
+        base["input"] = input.clone();
+        Self {
+            pack_id: pack_id.to_string(),
+            flow_id: flow_id.to_string(),
+            input: input.clone(),
+            base,
+            fresh,
+            steps: Vec::new(),
+        }
+    }
+
+    /// The engine state after the first `upto` steps.
+    fn state_after(&self, upto: usize) -> JsonValue {
+        let mut state = self.base.clone();
+        for step in &self.steps[..upto] {
+            state["nodes"][&step.node_id] =
+                json!({ "ok": true, "payload": step.output, "meta": null });
+            state["last_output"] = step.output.clone();
+        }
+        state
+    }
+
+    fn snapshot(&self, flow_id: &str, next_node: &str) -> anyhow::Result<FlowSnapshot> {
+        serde_json::from_value(json!({
+            "pack_id": self.pack_id,
+            "flow_id": self.flow_id,
+            "next_flow": flow_id,
+            "next_node": next_node,
+            "state": self.state_after(self.steps.len()),
+        }))
+        .context("rebuild flow snapshot")
+    }
+
+    /// Replaces what flows into the next node: the last output, or the input
+    /// when no node has run yet.
+    fn edit(&mut self, payload: JsonValue) {
+        match self.steps.last_mut() {
+            Some(step) => step.output = payload,
+            None => {
+                if self.fresh {
+                    self.base["entry"] = payload.clone();
+                }
+                self.base["input"] = payload.clone();
+                self.input = payload;
+            }
+        }
+    }
+
+    /// Forgets the last step and returns where to run it again.
+    fn rewind(&mut self) -> Option<Step> {
+        self.steps.pop()
+    }
+
+    fn previous(&self) -> Option<&Step> {
+        self.steps.last()
+    }
+}
+
+enum Resume {
+    Continue,
+    Abandon(Interrupt),
+}
+
+impl Debugger {
+    fn lock(&self) -> MutexGuard<'_, DebugState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Whether the next run needs to be observed at all.
+    pub fn is_armed(&self) -> bool {
+        let state = self.lock();
+        state.stepping || state.pause_next || !state.breakpoints.is_empty()
+    }
+
+    /// Starts recording a run of `flow_id` from `snapshot` or from the start.
+    pub fn begin(
+        &self,
+        pack_id: &str,
+        flow_id: &str,
+        snapshot: Option<&FlowSnapshot>,
+        input: &JsonValue,
+    ) {
+        self.lock().run = Some(Segment::new(pack_id, flow_id, snapshot, input));
+    }
+
+    /// Called by the runner before each attempt; resolves if the user abandons it.
+    pub fn watch(&self) -> oneshot::Receiver<Interrupt> {
+        let (sender, receiver) = oneshot::channel();
+        self.lock().abandon = Some(sender);
+        receiver
+    }
+
+    pub fn add_breakpoint(&self, node_id: &str) {
+        self.lock().breakpoints.insert(node_id.to_string());
+    }
+
+    pub fn set_stepping(&self, stepping: bool) {
+        self.lock().stepping = stepping;
+    }
+
+    /// `@break [node]`: sets a breakpoint, or lists them without a node.
+    pub fn break_command(&self, node_id: Option<&str>) {
+        let Some(node_id) = node_id else {
+            let breakpoints = self.lock().breakpoints.clone();
+            if breakpoints.is_empty() {
+                println!(
+                    "{}",
+                    operator_i18n::tr("demo.debug.no_breakpoints", "No breakpoints set.")
+                );
+            } else {
+                let list = breakpoints.into_iter().collect::<Vec<_>>().join(", ");
+                println!(
+                    "{}",
+                    operator_i18n::trf("demo.debug.breakpoints", "Breakpoints: {}", &[&list])
+                );
+            }
+            return;
+        };
+        self.add_breakpoint(node_id);
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "demo.debug.breakpoint_added",
+                "Breakpoint set on '{}'.",
+                &[node_id]
+            )
+        );
+    }
+
+    /// `@unbreak <node>`.
+    pub fn unbreak_command(&self, node_id: &str) {
+        if self.lock().breakpoints.remove(node_id) {
+            println!(
+                "{}",
+                operator_i18n::trf(
+                    "demo.debug.breakpoint_removed",
+                    "Breakpoint on '{}' removed.",
+                    &[node_id]
+                )
+            );
+        } else {
+            println!(
+                "{}",
+                operator_i18n::trf(
+                    "demo.debug.breakpoint_missing",
+                    "No breakpoint on '{}'.",
+                    &[node_id]
+                )
+            );
+        }
+    }
+
+    /// Holds the flow before `event`'s node until the user lets it go on.
+    fn pause(&self, event: &NodeEvent<'_>) -> Resume {
+        println!(
+            "{}",
+            operator_i18n::trf(
+                "demo.debug.paused",
+                "Paused before node '{}' ({}).",
+                &[event.node_id, &event.node.component]
+            )
+        );
+        print_payload(event.payload);
+        let stdin = io::stdin();
+        loop {
+            let mut line = String::new();
+            match stdin.lock().read_line(&mut line) {
+                Ok(0) | Err(_) => return Resume::Abandon(Interrupt::Quit),
+                Ok(_) => {}
+            }
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            match parse_command(trimmed) {
+                Ok(DemoCommand::Step) => {
+                    self.set_stepping(true);
+                    return Resume::Continue;
+                }
+                Ok(DemoCommand::Continue) => {
+                    self.set_stepping(false);
+                    return Resume::Continue;
+                }
+                Ok(DemoCommand::Payload | DemoCommand::Json) => {
+                    print_payload(event.payload);
+                    if let Some(step) = self.lock().run.as_ref().and_then(Segment::previous) {
+                        println!(
+                            "{}",
+                            operator_i18n::trf(
+                                "demo.debug.previous_output",
+                                "Output of '{}':",
+                                &[&step.node_id]
+                            )
+                        );
+                        print_json(&step.output);
+                    }
+                }
+                Ok(DemoCommand::Edit { payload }) => {
+                    let restart = self.restart(|run| {
+                        run.edit(payload);
+                        Some((event.context.flow_id.to_string(), event.node_id.to_string()))
+                    });
+                    match restart {
+                        Ok(Some(interrupt)) => {
+                            println!(
+                                "{}",
+                                operator_i18n::trf(
+                                    "demo.debug.payload_edited",
+                                    "Payload replaced; resuming at '{}'.",
+                                    &[event.node_id]
+                                )
+                            );
+                            return Resume::Abandon(interrupt);
+                        }
+                        Ok(None) => {}
+                        Err(err) => print_restart_error(&err),
+                    }
+                }
+                Ok(DemoCommand::Rerun) => {
+                    let restart =
+                        self.restart(|run| run.rewind().map(|step| (step.flow_id, step.node_id)));
+                    match restart {
+                        Ok(Some(interrupt)) => return Resume::Abandon(interrupt),
+                        Ok(None) => println!(
+                            "{}",
+                            operator_i18n::tr(
+                                "demo.debug.no_previous",
+                                "No node has run yet in this run."
+                            )
+                        ),
+                        Err(err) => print_restart_error(&err),
+                    }
+                }
+                Ok(DemoCommand::Break { node }) => self.break_command(node.as_deref()),
+                Ok(DemoCommand::Unbreak { node }) => self.unbreak_command(&node),
+                Ok(DemoCommand::Quit) => return Resume::Abandon(Interrupt::Quit),
+                Ok(DemoCommand::Help) => print_help(),
+                Ok(_) => println!(
+                    "{}",
+                    operator_i18n::tr(
+                        "demo.debug.paused_commands",
+                        "The flow is paused: use @step, @continue, @payload, @edit <json>, @rerun or @quit."
+                    )
+                ),
+                Err(err) => println!("{err}"),
+            }
+        }
+    }
+
+    /// Applies `change` to the current run and builds the snapshot that resumes
+    /// it at the returned node, pausing there again.
+    fn restart(
+        &self,
+        change: impl FnOnce(&mut Segment) -> Option<(String, String)>,
+    ) -> anyhow::Result<Option<Interrupt>> {
+        let mut state = self.lock();
+        let Some(run) = state.run.as_mut() else {
+            return Ok(None);
+        };
+        let Some((flow_id, node_id)) = change(run) else {
+            return Ok(None);
+        };
+        let snapshot = run.snapshot(&flow_id, &node_id)?;
+        let input = run.input.clone();
+        state.pause_next = true;
+        Ok(Some(Interrupt::Restart {
+            snapshot: Box::new(snapshot),
+            input,
+        }))
+    }
+}
+
+impl ExecutionObserver for Debugger {
+    fn on_node_start(&self, event: &NodeEvent<'_>) {
+        let hold = {
+            let mut state = self.lock();
+            let pause_next = std::mem::take(&mut state.pause_next);
+            pause_next || state.stepping || state.breakpoints.contains(event.node_id)
+        };
+        if !hold {
+            return;
+        }
+        // Waiting on stdin must not hold up the runtime's other tasks.
+        let Resume::Abandon(interrupt) = tokio::task::block_in_place(|| self.pause(event)) else {
+            return;
+        };
+        if let Some(abandon) = self.lock().abandon.take() {
+            let _ = abandon.send(interrupt);
+        }
+        // The engine runs the node as soon as this returns, so the abandoned run
+        // stays parked here. The runner has already moved on and does not wait
+        // for it when it shuts down.
+        tokio::task::block_in_place(|| {
+            loop {
+                std::thread::park();
+            }
+        })
+    }
+
+    fn on_node_end(&self, event: &NodeEvent<'_>, output: &JsonValue) {
+        if let Some(run) = self.lock().run.as_mut() {
+            run.steps.push(Step {
+                flow_id: event.context.flow_id.to_string(),
+                node_id: event.node_id.to_string(),
+                output: output.clone(),
+            });
+        }
+    }
+
+    fn on_node_error(&self, _event: &NodeEvent<'_>, _error: &dyn StdError) {}
+}
+
+fn print_payload(payload: &JsonValue) {
+    println!("{}", operator_i18n::tr("demo.debug.input", "Input:"));
+    print_json(payload);
+}
+
+fn print_json(value: &JsonValue) {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+    );
+}
+
+fn print_restart_error(err: &anyhow::Error) {
+    println!(
+        "{}",
+        operator_i18n::trf(
+            "demo.debug.restart_failed",
+            "Cannot resume from here: {}",
+            &[&format!("{err:#}")]
+        )
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment() -> Segment {
+        let mut run = Segment::new("demo.pack", "main", None, &json!({"text": "hi"}));
+        run.steps.push(Step {
+            flow_id: "main".into(),
+            node_id: "fetch".into(),
+            output: json!({"items": 3}),
+        });
+        run.steps.push(Step {
+            flow_id: "main".into(),
+            node_id: "render".into(),
+            output: json!({"card": "x"}),
+        });
+        run
+    }
+
+    #[test]
+    fn rebuilds_snapshots_the_engine_accepts() {
+        let mut run = segment();
+        run.edit(json!({"card": "edited"}));
+        let snapshot = run.snapshot("main", "send").unwrap();
+        assert_eq!(snapshot.next_node, "send");
+        assert_eq!(snapshot.next_flow.as_deref(), Some("main"));
+        let state = serde_json::to_value(&snapshot.state).unwrap();
+        assert_eq!(state["last_output"], json!({"card": "edited"}));
+        assert_eq!(state["nodes"]["fetch"]["payload"], json!({"items": 3}));
+        assert_eq!(state["input"], json!({"text": "hi"}));
+
+        let rerun = run.rewind().unwrap();
+        assert_eq!(rerun.node_id, "render");
+        let state = run.state_after(run.steps.len());
+        assert_eq!(state["last_output"], json!({"items": 3}));
+        assert!(state["nodes"].get("render").is_none());
+    }
+
+    #[test]
+    fn editing_before_the_first_node_replaces_the_input() {
+        let mut run = Segment::new("demo.pack", "main", None, &json!({"text": "hi"}));
+        run.edit(json!({"text": "bye"}));
+        assert_eq!(run.input, json!({"text": "bye"}));
+        let state = run.state_after(0);
+        assert_eq!(state["entry"], json!({"text": "bye"}));
+        assert_eq!(state["input"], json!({"text": "bye"}));
+        assert!(run.rewind().is_none());
+    }
+}
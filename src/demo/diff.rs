@@ -0,0 +1,468 @@
+//! `demo diff`: compare what two bundles (or a bundle and its project root) contain.
+//!
+//! Each side is reduced to a [`BundleSnapshot`]: pack digests, the provider list per
+//! domain, gmap rules per tenant/team, and the resolved manifests. Project roots keep
+//! manifests under `state/resolved`, bundles under `resolved`; both are accepted.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+
+use anyhow::Context;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use crate::gmap::{self, Policy};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffSection {
+    Packs,
+    Providers,
+    Tenants,
+    Gmap,
+    Manifests,
+}
+
+impl DiffSection {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DiffSection::Packs => "packs",
+            DiffSection::Providers => "providers",
+            DiffSection::Tenants => "tenants",
+            DiffSection::Gmap => "gmap",
+            DiffSection::Manifests => "manifests",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffChange {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl DiffChange {
+    fn marker(self) -> char {
+        match self {
+            DiffChange::Added => '+',
+            DiffChange::Removed => '-',
+            DiffChange::Changed => '~',
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DiffEntry {
+    pub section: DiffSection,
+    pub change: DiffChange,
+    pub key: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl fmt::Display for DiffEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.change.marker(), self.key)?;
+        if let Some(detail) = &self.detail {
+            write!(f, " ({detail})")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BundleDiff {
+    pub left: String,
+    pub right: String,
+    pub entries: Vec<DiffEntry>,
+}
+
+impl BundleDiff {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn render_text(&self) -> String {
+        let mut out = format!("--- {}\n+++ {}\n", self.left, self.right);
+        let mut section = None;
+        for entry in &self.entries {
+            if section != Some(entry.section) {
+                section = Some(entry.section);
+                out.push_str(&format!("{}:\n", entry.section.as_str()));
+            }
+            out.push_str(&format!("  {entry}\n"));
+        }
+        out
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct BundleSnapshot {
+    /// Relative `.gtpack` path (or pack directory) to its sha256.
+    pub packs: BTreeMap<String, String>,
+    /// `domain/provider` ids from `providers/<domain>/*.gtpack`.
+    pub providers: BTreeSet<String>,
+    /// `tenant` or `tenant/team` to its normalized gmap rules.
+    pub gmaps: BTreeMap<String, BTreeSet<String>>,
+    /// Resolved manifest file name to its contents, minus `project_root`.
+    pub manifests: BTreeMap<String, JsonValue>,
+}
+
+impl BundleSnapshot {
+    pub fn load(root: &Path) -> anyhow::Result<Self> {
+        if !root.is_dir() {
+            return Err(anyhow::anyhow!("{} is not a directory", root.display()));
+        }
+        let mut snapshot = Self::default();
+        for dir in ["packs", "providers"] {
+            collect_packs(root, &root.join(dir), &mut snapshot.packs)?;
+        }
+        snapshot.providers = snapshot
+            .packs
+            .keys()
+            .filter_map(|path| provider_id(path))
+            .collect();
+        collect_gmaps(&root.join("tenants"), &mut snapshot.gmaps)?;
+        let resolved = if root.join("resolved").is_dir() {
+            root.join("resolved")
+        } else {
+            root.join("state").join("resolved")
+        };
+        collect_manifests(&resolved, &mut snapshot.manifests)?;
+        Ok(snapshot)
+    }
+}
+
+pub fn diff_dirs(left: &Path, right: &Path) -> anyhow::Result<BundleDiff> {
+    let before = BundleSnapshot::load(left)?;
+    let after = BundleSnapshot::load(right)?;
+    Ok(BundleDiff {
+        left: left.display().to_string(),
+        right: right.display().to_string(),
+        entries: diff_snapshots(&before, &after),
+    })
+}
+
+pub fn diff_snapshots(before: &BundleSnapshot, after: &BundleSnapshot) -> Vec<DiffEntry> {
+    let mut entries = Vec::new();
+    diff_maps(
+        DiffSection::Packs,
+        &before.packs,
+        &after.packs,
+        |old, new| Some(format!("{} -> {}", short_digest(old), short_digest(new))),
+        &mut entries,
+    );
+    for provider in before.providers.difference(&after.providers) {
+        entries.push(entry(DiffSection::Providers, DiffChange::Removed, provider));
+    }
+    for provider in after.providers.difference(&before.providers) {
+        entries.push(entry(DiffSection::Providers, DiffChange::Added, provider));
+    }
+    let tenants = |snapshot: &BundleSnapshot| {
+        snapshot
+            .gmaps
+            .keys()
+            .map(|key| key.split('/').next().unwrap_or(key).to_string())
+            .collect::<BTreeSet<_>>()
+    };
+    let (old_tenants, new_tenants) = (tenants(before), tenants(after));
+    for tenant in old_tenants.difference(&new_tenants) {
+        entries.push(entry(DiffSection::Tenants, DiffChange::Removed, tenant));
+    }
+    for tenant in new_tenants.difference(&old_tenants) {
+        entries.push(entry(DiffSection::Tenants, DiffChange::Added, tenant));
+    }
+    diff_maps(
+        DiffSection::Gmap,
+        &before.gmaps,
+        &after.gmaps,
+        |old, new| {
+            let added = new.difference(old).map(|rule| format!("+{rule}"));
+            let removed = old.difference(new).map(|rule| format!("-{rule}"));
+            Some(removed.chain(added).collect::<Vec<_>>().join(", "))
+        },
+        &mut entries,
+    );
+    diff_maps(
+        DiffSection::Manifests,
+        &before.manifests,
+        &after.manifests,
+        |old, new| {
+            let keys = changed_keys(old, new);
+            (!keys.is_empty()).then(|| keys.join(", "))
+        },
+        &mut entries,
+    );
+    entries
+}
+
+fn diff_maps<V: PartialEq>(
+    section: DiffSection,
+    before: &BTreeMap<String, V>,
+    after: &BTreeMap<String, V>,
+    describe: impl Fn(&V, &V) -> Option<String>,
+    entries: &mut Vec<DiffEntry>,
+) {
+    for (key, old) in before {
+        match after.get(key) {
+            None => entries.push(entry(section, DiffChange::Removed, key)),
+            Some(new) if new != old => entries.push(DiffEntry {
+                detail: describe(old, new),
+                ..entry(section, DiffChange::Changed, key)
+            }),
+            Some(_) => {}
+        }
+    }
+    for key in after.keys().filter(|key| !before.contains_key(*key)) {
+        entries.push(entry(section, DiffChange::Added, key));
+    }
+}
+
+fn entry(section: DiffSection, change: DiffChange, key: &str) -> DiffEntry {
+    DiffEntry {
+        section,
+        change,
+        key: key.to_string(),
+        detail: None,
+    }
+}
+
+/// Top-level manifest fields whose values differ.
+fn changed_keys(old: &JsonValue, new: &JsonValue) -> Vec<String> {
+    let (Some(old), Some(new)) = (old.as_object(), new.as_object()) else {
+        return Vec::new();
+    };
+    old.keys()
+        .chain(new.keys().filter(|key| !old.contains_key(*key)))
+        .filter(|key| old.get(*key) != new.get(*key))
+        .cloned()
+        .collect()
+}
+
+fn short_digest(digest: &str) -> &str {
+    digest.get(..12).unwrap_or(digest)
+}
+
+fn provider_id(path: &str) -> Option<String> {
+    let rest = path.strip_prefix("providers/")?;
+    let (domain, file) = rest.split_once('/')?;
+    let name = file.strip_suffix(".gtpack")?;
+    (!name.contains('/')).then(|| format!("{domain}/{name}"))
+}
+
+fn collect_packs(
+    root: &Path,
+    dir: &Path,
+    packs: &mut BTreeMap<String, String>,
+) -> anyhow::Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_pack_dir = path.is_dir() && path.join("pack.yaml").exists();
+        if is_pack_dir || path.extension().and_then(|ext| ext.to_str()) == Some("gtpack") {
+            packs.insert(relative_path(root, &path), digest_path(&path)?);
+        } else if path.is_dir() {
+            collect_packs(root, &path, packs)?;
+        }
+    }
+    Ok(())
+}
+
+fn collect_gmaps(
+    tenants_dir: &Path,
+    gmaps: &mut BTreeMap<String, BTreeSet<String>>,
+) -> anyhow::Result<()> {
+    if !tenants_dir.is_dir() {
+        return Ok(());
+    }
+    for tenant in std::fs::read_dir(tenants_dir)? {
+        let tenant = tenant?;
+        if !tenant.file_type()?.is_dir() {
+            continue;
+        }
+        let name = tenant.file_name().to_string_lossy().to_string();
+        gmaps.insert(
+            name.clone(),
+            gmap_rules(&tenant.path().join("tenant.gmap"))?,
+        );
+        let teams_dir = tenant.path().join("teams");
+        if !teams_dir.is_dir() {
+            continue;
+        }
+        for team in std::fs::read_dir(teams_dir)? {
+            let team = team?;
+            if team.file_type()?.is_dir() {
+                gmaps.insert(
+                    format!("{name}/{}", team.file_name().to_string_lossy()),
+                    gmap_rules(&team.path().join("team.gmap"))?,
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn gmap_rules(path: &Path) -> anyhow::Result<BTreeSet<String>> {
+    let rules =
+        gmap::parse_file(path).with_context(|| format!("failed to parse {}", path.display()))?;
+    Ok(rules
+        .into_iter()
+        .map(|rule| {
+            let policy = match rule.policy {
+                Policy::Public => "public",
+                Policy::Forbidden => "forbidden",
+            };
+            format!("{} = {policy}", rule.path)
+        })
+        .collect())
+}
+
+fn collect_manifests(
+    resolved_dir: &Path,
+    manifests: &mut BTreeMap<String, JsonValue>,
+) -> anyhow::Result<()> {
+    if !resolved_dir.is_dir() {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(resolved_dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("yaml") {
+            continue;
+        }
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let mut manifest: JsonValue = serde_yaml_bw::from_str(&contents)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+        // Bundles rewrite project_root to "./"; that alone is not a change.
+        if let Some(map) = manifest.as_object_mut() {
+            map.remove("project_root");
+        }
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        manifests.insert(name, manifest);
+    }
+    Ok(())
+}
+
+/// sha256 of a file, or of every file under a pack directory in path order.
+pub(crate) fn digest_path(path: &Path) -> anyhow::Result<String> {
+    let mut context = ring::digest::Context::new(&ring::digest::SHA256);
+    if path.is_dir() {
+        let mut files = Vec::new();
+        list_files(path, &mut files)?;
+        files.sort();
+        for file in files {
+            context.update(relative_path(path, &file).as_bytes());
+            context.update(&std::fs::read(&file)?);
+        }
+    } else {
+        context.update(
+            &std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))?,
+        );
+    }
+    Ok(context
+        .finish()
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+fn list_files(dir: &Path, files: &mut Vec<std::path::PathBuf>) -> anyhow::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            list_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, rel: &str, contents: &str) {
+        let path = root.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn project_and_bundle_differences_are_reported_per_section() -> anyhow::Result<()> {
+        let project = tempfile::tempdir()?;
+        let bundle = tempfile::tempdir()?;
+        let manifest =
+            "version: \"1\"\ntenant: demo\nproject_root: {root}\npacks: [packs/a.gtpack]\n";
+        write(project.path(), "packs/a.gtpack", "a-v1");
+        write(project.path(), "providers/messaging/telegram.gtpack", "tg");
+        write(
+            project.path(),
+            "tenants/demo/tenant.gmap",
+            "packs/a = public\n",
+        );
+        write(
+            project.path(),
+            "state/resolved/demo.yaml",
+            &manifest.replace("{root}", "/work/project"),
+        );
+
+        write(bundle.path(), "packs/a.gtpack", "a-v2");
+        write(bundle.path(), "providers/messaging/slack.gtpack", "slack");
+        write(
+            bundle.path(),
+            "tenants/demo/tenant.gmap",
+            "packs/a = forbidden\n",
+        );
+        write(bundle.path(), "tenants/acme/tenant.gmap", "");
+        write(
+            bundle.path(),
+            "resolved/demo.yaml",
+            &manifest.replace("{root}", "./"),
+        );
+
+        let diff = diff_dirs(project.path(), bundle.path())?;
+        let summary = diff
+            .entries
+            .iter()
+            .map(|entry| format!("{} {}", entry.section.as_str(), entry))
+            .collect::<Vec<_>>();
+        assert!(
+            summary
+                .iter()
+                .any(|line| line.starts_with("packs ~ packs/a.gtpack"))
+        );
+        assert!(summary.contains(&"providers - messaging/telegram".to_string()));
+        assert!(summary.contains(&"providers + messaging/slack".to_string()));
+        assert!(summary.contains(&"tenants + acme".to_string()));
+        assert!(
+            summary.contains(&"gmap ~ demo (-packs/a = public, +packs/a = forbidden)".to_string())
+        );
+        assert!(
+            !diff
+                .entries
+                .iter()
+                .any(|entry| entry.section == DiffSection::Manifests),
+            "project_root rewrite must not count as a change"
+        );
+
+        assert!(diff_dirs(bundle.path(), bundle.path())?.is_empty());
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 use std::path::Path;
 
+use crate::domains::{PackArchive, PackTrust, SignatureStatus};
 use crate::offers::{OfferRegistry, discover_gtpacks};
 
 pub fn demo_doctor(bundle_root: &Path, pack_command: &Path) -> anyhow::Result<()> {
@@ -13,6 +14,7 @@ pub fn demo_doctor(bundle_root: &Path, pack_command: &Path) -> anyhow::Result<()
     if packs.is_empty() {
         return Err(anyhow::anyhow!("No .gtpack files found in bundle."));
     }
+    check_pack_signatures(bundle_root, &packs)?;
 
     for pack in packs {
         let status = std::process::Command::new(pack_command)
@@ -46,6 +48,53 @@ pub fn demo_doctor(bundle_root: &Path, pack_command: &Path) -> anyhow::Result<()
     Ok(())
 }
 
+/// With `require_signed_packs: true`, lists every provider or bundle pack that is
+/// not signed by a trusted key and fails if there is one.
+fn check_pack_signatures(bundle_root: &Path, packs: &[std::path::PathBuf]) -> anyhow::Result<()> {
+    let trust = PackTrust::from_bundle(bundle_root);
+    if !trust.require_signed() {
+        return Ok(());
+    }
+    let mut all = packs.to_vec();
+    let providers_root = bundle_root.join("providers");
+    if providers_root.exists() {
+        collect_gtpacks(&providers_root, &mut all)?;
+    }
+    let mut refused = 0;
+    for pack in &all {
+        let status = PackArchive::open(pack)?.signature()?;
+        let detail = match &status {
+            SignatureStatus::Verified { .. } => continue,
+            SignatureStatus::Unsigned => String::new(),
+            SignatureStatus::Untrusted { key_id } => format!(" key_id={key_id}"),
+            SignatureStatus::Invalid { reason } => format!(" reason={reason:?}"),
+        };
+        refused += 1;
+        println!(
+            "{}",
+            crate::operator_i18n::trf(
+                "demo.doctor.signature_refused",
+                "Pack {} is not signed by a trusted key (status={}){}",
+                &[&pack.display().to_string(), status.label(), &detail]
+            )
+        );
+    }
+    if refused > 0 {
+        return Err(anyhow::anyhow!(
+            "{refused} pack(s) not signed by a trusted key (require_signed_packs is set)"
+        ));
+    }
+    println!(
+        "{}",
+        crate::operator_i18n::trf(
+            "demo.doctor.signatures_verified",
+            "Verified the signatures of {} pack(s)",
+            &[&all.len().to_string()]
+        )
+    );
+    Ok(())
+}
+
 fn collect_gtpacks(dir: &Path, packs: &mut Vec<std::path::PathBuf>) -> anyhow::Result<()> {
     for entry in std::fs::read_dir(dir)? {
         let entry = entry?;
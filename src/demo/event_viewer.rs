@@ -0,0 +1,184 @@
+//! `demo events tail` and `demo events publish`.
+//!
+//! Tail subscribes to `greentic.<tenant>.<team>.ingress.events.>` on the scope's
+//! bus, which is only reachable from another process when `demo start
+//! --nats=jetstream` bound one. Publish builds an [`EventEnvelopeV1`], puts it on
+//! the same subject as provider-ingested events, and routes it into the default
+//! events flow, so packs see it exactly like a real event.
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use crate::bus;
+use crate::demo::event_router::route_events_to_default_flow;
+use crate::demo::ingress_types::{EventEnvelopeV1, EventScopeV1, EventSourceV1};
+use crate::demo::runner_host::OperatorContext;
+
+/// Provider recorded on events made by `demo events publish`.
+pub const SYNTHETIC_PROVIDER: &str = "synthetic";
+
+/// Which events `demo events tail` prints.
+#[derive(Clone, Debug, Default)]
+pub struct EventFilter {
+    /// Exact type, or a prefix ending in `*` such as `order.*`.
+    pub event_type: Option<String>,
+    pub provider: Option<String>,
+    /// Case-insensitive text the serialized event must contain.
+    pub contains: Option<String>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &EventEnvelopeV1) -> bool {
+        let type_matches =
+            self.event_type
+                .as_deref()
+                .is_none_or(|pattern| match pattern.strip_suffix('*') {
+                    Some(prefix) => event.event_type.starts_with(prefix),
+                    None => event.event_type == pattern,
+                });
+        let provider_matches = self
+            .provider
+            .as_deref()
+            .is_none_or(|provider| event.source.provider == provider);
+        let text_matches = self.contains.as_deref().is_none_or(|needle| {
+            serde_json::to_string(event)
+                .unwrap_or_default()
+                .to_lowercase()
+                .contains(&needle.to_lowercase())
+        });
+        type_matches && provider_matches && text_matches
+    }
+}
+
+/// Subject pattern covering every events-domain ingress subject of a scope.
+pub fn tail_pattern(tenant: &str, team: &str) -> String {
+    format!("{}.ingress.events.>", bus::subject_prefix(tenant, team))
+}
+
+/// A bus payload as an event; `None` for anything else on the subject.
+pub fn parse_event(payload: &JsonValue) -> Option<EventEnvelopeV1> {
+    serde_json::from_value(payload.clone()).ok()
+}
+
+/// One line per event: time, type, provider, id, then the compact payload.
+pub fn format_event(event: &EventEnvelopeV1) -> String {
+    let at = DateTime::parse_from_rfc3339(&event.occurred_at)
+        .map(|at| at.with_timezone(&Utc).format("%H:%M:%S").to_string())
+        .unwrap_or_else(|_| event.occurred_at.clone());
+    let correlation = event
+        .correlation_id
+        .as_deref()
+        .map(|id| format!(" correlation={id}"))
+        .unwrap_or_default();
+    format!(
+        "{at} {} provider={} id={}{correlation} {}",
+        event.event_type, event.source.provider, event.event_id, event.payload
+    )
+}
+
+pub fn synthetic_event(
+    event_type: &str,
+    payload: JsonValue,
+    provider: &str,
+    ctx: &OperatorContext,
+) -> EventEnvelopeV1 {
+    EventEnvelopeV1 {
+        event_id: uuid::Uuid::new_v4().to_string(),
+        event_type: event_type.to_string(),
+        occurred_at: Utc::now().to_rfc3339(),
+        source: EventSourceV1 {
+            domain: "events".to_string(),
+            provider: provider.to_string(),
+            handler_id: None,
+        },
+        scope: EventScopeV1 {
+            tenant: ctx.tenant.clone(),
+            team: ctx.team.clone(),
+        },
+        correlation_id: ctx.correlation_id.clone(),
+        payload,
+        http: None,
+        raw: None,
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PublishOutcome {
+    pub event_id: String,
+    pub subject: String,
+    pub bus: &'static str,
+    /// Events delivered to the default events flow; 0 without routing.
+    pub routed: usize,
+}
+
+/// Puts `event` on the scope's bus and, with `route`, runs it through the default
+/// events flow.
+pub fn publish(
+    bundle: &Path,
+    ctx: &OperatorContext,
+    event: &EventEnvelopeV1,
+    route: bool,
+) -> anyhow::Result<PublishOutcome> {
+    let team = ctx.team.as_deref().unwrap_or("default");
+    let bus = bus::for_scope(bundle, &ctx.tenant, team);
+    let subject = bus::ingress_subject(&ctx.tenant, team, "events", &event.source.provider);
+    bus.publish(&subject, &serde_json::to_value(event)?)?;
+    let routed = if route {
+        route_events_to_default_flow(bundle, ctx, std::slice::from_ref(event))?
+    } else {
+        0
+    };
+    Ok(PublishOutcome {
+        event_id: event.event_id.clone(),
+        subject,
+        bus: bus.kind().as_str(),
+        routed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn filters_by_type_prefix_provider_and_text() {
+        let ctx = OperatorContext {
+            tenant: "demo".to_string(),
+            team: Some("default".to_string()),
+            correlation_id: Some("c-1".to_string()),
+        };
+        let event = synthetic_event(
+            "order.created",
+            json!({"sku": "ABC-1"}),
+            SYNTHETIC_PROVIDER,
+            &ctx,
+        );
+        let round_trip = parse_event(&serde_json::to_value(&event).unwrap()).unwrap();
+        assert_eq!(round_trip.scope.team.as_deref(), Some("default"));
+        assert!(parse_event(&json!({"text": "not an event"})).is_none());
+
+        let filter = |event_type: Option<&str>, provider: Option<&str>, contains: Option<&str>| {
+            EventFilter {
+                event_type: event_type.map(str::to_string),
+                provider: provider.map(str::to_string),
+                contains: contains.map(str::to_string),
+            }
+            .matches(&event)
+        };
+        assert!(filter(None, None, None));
+        assert!(filter(Some("order.*"), Some("synthetic"), Some("abc-1")));
+        assert!(!filter(Some("order"), None, None));
+        assert!(!filter(None, Some("timer"), None));
+        assert!(!filter(None, None, Some("XYZ")));
+
+        assert_eq!(
+            tail_pattern("demo", "default"),
+            "greentic.demo.default.ingress.events.>"
+        );
+        assert!(format_event(&event).contains("order.created provider=synthetic"));
+    }
+}
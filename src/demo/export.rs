@@ -0,0 +1,479 @@
+//! Deployment files for running a demo bundle on a server (`demo export`).
+//!
+//! The operator runs from its container image with `demo start --headless` and the
+//! bundle at [`BUNDLE_MOUNT`], next to a NATS server. Compose adds an optional
+//! cloudflared connector for a named tunnel; Kubernetes routes webhooks through an
+//! Ingress instead. Nothing from the local toolchain is needed on the server.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::Serialize;
+use serde_json::{Value as JsonValue, json};
+
+use crate::config::load_demo_config_or_default;
+
+pub const DEFAULT_IMAGE_REPO: &str = "ghcr.io/greenticai/greentic-operator";
+pub const BUNDLE_MOUNT: &str = "/bundle";
+pub const NATS_IMAGE: &str = "nats:2";
+pub const CLOUDFLARED_IMAGE: &str = "cloudflare/cloudflared:latest";
+/// Compose variables the generated file requires when cloudflared is included.
+pub const TUNNEL_TOKEN_VAR: &str = "CLOUDFLARED_TUNNEL_TOKEN";
+pub const PUBLIC_BASE_URL_VAR: &str = "PUBLIC_BASE_URL";
+
+#[derive(Clone, Debug)]
+pub struct ExportOptions {
+    pub tenant: String,
+    pub team: String,
+    /// Defaults to [`DEFAULT_IMAGE_REPO`] tagged with this operator's version.
+    pub image: Option<String>,
+    /// Environment used for secrets lookups (`demo start --env`).
+    pub env: String,
+    /// Compose only: add a cloudflared connector for a named tunnel.
+    pub cloudflared: bool,
+}
+
+/// Kubernetes-specific settings for [`k8s`].
+#[derive(Clone, Debug)]
+pub struct K8sOptions {
+    /// Prefix of every object name.
+    pub name: String,
+    pub namespace: Option<String>,
+    /// Mount the bundle from this PersistentVolumeClaim; otherwise the image must
+    /// contain it at [`BUNDLE_MOUNT`].
+    pub pvc: Option<String>,
+    /// Public host for the Ingress; no Ingress is written without one.
+    pub host: Option<String>,
+    pub ingress_class: Option<String>,
+    /// TLS secret for `host`.
+    pub tls_secret: Option<String>,
+}
+
+impl ExportOptions {
+    fn image(&self) -> String {
+        self.image
+            .clone()
+            .unwrap_or_else(|| format!("{DEFAULT_IMAGE_REPO}:{}", env!("CARGO_PKG_VERSION")))
+    }
+}
+
+/// `greentic-operator` arguments that start the demo inside the operator container.
+pub fn operator_args(
+    options: &ExportOptions,
+    gateway_port: u16,
+    nats_url: &str,
+    public_base_url: Option<&str>,
+) -> Vec<String> {
+    let mut args: Vec<String> = [
+        "demo",
+        "start",
+        "--bundle",
+        BUNDLE_MOUNT,
+        "--tenant",
+        &options.tenant,
+        "--team",
+        &options.team,
+        "--env",
+        &options.env,
+        "--headless",
+        "--cloudflared",
+        "off",
+        "--nats",
+        "external",
+        "--nats-url",
+        nats_url,
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect();
+    args.extend(["--gateway-port".to_string(), gateway_port.to_string()]);
+    if let Some(url) = public_base_url {
+        args.extend(["--public-base-url".to_string(), url.to_string()]);
+    }
+    args
+}
+
+#[derive(Debug, Serialize)]
+struct ComposeFile {
+    services: BTreeMap<&'static str, ComposeService>,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct ComposeService {
+    image: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    entrypoint: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    command: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    ports: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    volumes: Vec<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    environment: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    depends_on: Vec<&'static str>,
+    restart: &'static str,
+}
+
+/// A Docker Compose file for `bundle`, to be written to `out`. The bundle is mounted
+/// by a path relative to `out`'s directory when it lies under it.
+pub fn compose(bundle: &Path, out: &Path, options: &ExportOptions) -> anyhow::Result<String> {
+    let bundle = bundle
+        .canonicalize()
+        .with_context(|| format!("bundle {} not found", bundle.display()))?;
+    let demo_config = load_demo_config_or_default(&bundle.join("greentic.demo.yaml"));
+    let port = demo_config.services.gateway.port;
+    let public_base_url = options.cloudflared.then(|| {
+        format!("${{{PUBLIC_BASE_URL_VAR}:?set {PUBLIC_BASE_URL_VAR} to the tunnel's public URL}}")
+    });
+
+    let mut services = BTreeMap::new();
+    services.insert(
+        "operator",
+        ComposeService {
+            image: options.image(),
+            entrypoint: vec!["greentic-operator".to_string()],
+            command: operator_args(
+                options,
+                port,
+                "nats://nats:4222",
+                public_base_url.as_deref(),
+            ),
+            ports: vec![format!("{port}:{port}")],
+            volumes: vec![format!(
+                "{}:{BUNDLE_MOUNT}",
+                mount_source(&bundle, out)?.display()
+            )],
+            depends_on: vec!["nats"],
+            restart: "unless-stopped",
+            ..Default::default()
+        },
+    );
+    services.insert(
+        "nats",
+        ComposeService {
+            image: NATS_IMAGE.to_string(),
+            command: vec!["-js".to_string()],
+            restart: "unless-stopped",
+            ..Default::default()
+        },
+    );
+    if options.cloudflared {
+        services.insert(
+            "cloudflared",
+            ComposeService {
+                image: CLOUDFLARED_IMAGE.to_string(),
+                command: ["tunnel", "--no-autoupdate", "run"]
+                    .map(str::to_string)
+                    .to_vec(),
+                environment: BTreeMap::from([(
+                    "TUNNEL_TOKEN".to_string(),
+                    format!("${{{TUNNEL_TOKEN_VAR}:?set {TUNNEL_TOKEN_VAR} to the tunnel token}}"),
+                )]),
+                depends_on: vec!["operator"],
+                restart: "unless-stopped",
+                ..Default::default()
+            },
+        );
+    }
+    let yaml = serde_yaml_bw::to_string(&ComposeFile { services })?;
+    Ok(format!(
+        "# Generated by `greentic-operator demo export compose` from {}.\n{yaml}",
+        bundle.display()
+    ))
+}
+
+/// Kubernetes manifests for `bundle`, as one multi-document YAML: a ConfigMap with
+/// `greentic.demo.yaml`, a Secret for env vars (secret fallbacks, store passphrase),
+/// a Deployment running the operator and NATS in one pod, a Service, and, with a
+/// host, an Ingress for the webhook routes.
+pub fn k8s(bundle: &Path, options: &ExportOptions, k8s: &K8sOptions) -> anyhow::Result<String> {
+    let bundle = bundle
+        .canonicalize()
+        .with_context(|| format!("bundle {} not found", bundle.display()))?;
+    let demo_config_path = bundle.join("greentic.demo.yaml");
+    let demo_config = load_demo_config_or_default(&demo_config_path);
+    let port = demo_config.services.gateway.port;
+    let name = &k8s.name;
+    let metadata = |suffix: &str| {
+        let mut metadata = json!({
+            "name": format!("{name}{suffix}"),
+            "labels": { "app.kubernetes.io/name": name, "app.kubernetes.io/part-of": "greentic" },
+        });
+        if let Some(namespace) = &k8s.namespace {
+            metadata["namespace"] = json!(namespace);
+        }
+        metadata
+    };
+    let public_base_url = k8s.host.as_ref().map(|host| format!("https://{host}"));
+
+    let mut documents = Vec::new();
+    let mut volumes = Vec::new();
+    let mut mounts = Vec::new();
+    if let Some(claim) = &k8s.pvc {
+        volumes.push(json!({ "name": "bundle", "persistentVolumeClaim": { "claimName": claim } }));
+        mounts.push(json!({ "name": "bundle", "mountPath": BUNDLE_MOUNT }));
+    }
+    if demo_config_path.exists() {
+        let contents = std::fs::read_to_string(&demo_config_path)
+            .with_context(|| format!("read {}", demo_config_path.display()))?;
+        documents.push(json!({
+            "apiVersion": "v1",
+            "kind": "ConfigMap",
+            "metadata": metadata("-config"),
+            "data": { "greentic.demo.yaml": contents },
+        }));
+        volumes
+            .push(json!({ "name": "config", "configMap": { "name": format!("{name}-config") } }));
+        mounts.push(json!({
+            "name": "config",
+            "mountPath": format!("{BUNDLE_MOUNT}/greentic.demo.yaml"),
+            "subPath": "greentic.demo.yaml",
+        }));
+    }
+    documents.push(json!({
+        "apiVersion": "v1",
+        "kind": "Secret",
+        "metadata": metadata("-secrets"),
+        "type": "Opaque",
+        "stringData": {},
+    }));
+    documents.push(json!({
+        "apiVersion": "apps/v1",
+        "kind": "Deployment",
+        "metadata": metadata(""),
+        "spec": {
+            "replicas": 1,
+            // State lives in the bundle directory; never run two pods on it.
+            "strategy": { "type": "Recreate" },
+            "selector": { "matchLabels": { "app.kubernetes.io/name": name } },
+            "template": {
+                "metadata": { "labels": { "app.kubernetes.io/name": name } },
+                "spec": {
+                    "containers": [
+                        {
+                            "name": "operator",
+                            "image": options.image(),
+                            "command": ["greentic-operator"],
+                            "args": operator_args(
+                                options,
+                                port,
+                                "nats://127.0.0.1:4222",
+                                public_base_url.as_deref(),
+                            ),
+                            "ports": [{ "name": "http", "containerPort": port }],
+                            "envFrom": [
+                                { "secretRef": { "name": format!("{name}-secrets"), "optional": true } }
+                            ],
+                            "readinessProbe": { "tcpSocket": { "port": "http" } },
+                            "volumeMounts": mounts,
+                        },
+                        { "name": "nats", "image": NATS_IMAGE, "args": ["-js"] },
+                    ],
+                    "volumes": volumes,
+                },
+            },
+        },
+    }));
+    documents.push(json!({
+        "apiVersion": "v1",
+        "kind": "Service",
+        "metadata": metadata(""),
+        "spec": {
+            "selector": { "app.kubernetes.io/name": name },
+            "ports": [{ "name": "http", "port": 80, "targetPort": "http" }],
+        },
+    }));
+    if let Some(host) = &k8s.host {
+        let paths: Vec<JsonValue> = webhook_paths(&demo_config)
+            .into_iter()
+            .map(|path| {
+                json!({
+                    "path": path,
+                    "pathType": "Prefix",
+                    "backend": { "service": { "name": name, "port": { "name": "http" } } },
+                })
+            })
+            .collect();
+        let mut spec = json!({ "rules": [{ "host": host, "http": { "paths": paths } }] });
+        if let Some(class) = &k8s.ingress_class {
+            spec["ingressClassName"] = json!(class);
+        }
+        if let Some(secret) = &k8s.tls_secret {
+            spec["tls"] = json!([{ "hosts": [host], "secretName": secret }]);
+        }
+        documents.push(json!({
+            "apiVersion": "networking.k8s.io/v1",
+            "kind": "Ingress",
+            "metadata": metadata(""),
+            "spec": spec,
+        }));
+    }
+
+    let mut out = format!(
+        "# Generated by `greentic-operator demo export k8s` from {}.\n",
+        bundle.display()
+    );
+    for document in documents {
+        out.push_str("---\n");
+        out.push_str(&serde_yaml_bw::to_string(&document)?);
+    }
+    Ok(out)
+}
+
+/// Path prefixes the HTTP ingress takes webhooks on: the `/v1/{domain}/ingress`
+/// scheme, the older `/{domain}/ingress` one, and the fixed part of each
+/// `services.ingress.routes` path.
+fn webhook_paths(demo_config: &crate::config::DemoConfig) -> BTreeSet<String> {
+    let mut paths = BTreeSet::from(["/v1".to_string()]);
+    let domains = ["messaging", "events"]
+        .into_iter()
+        .map(str::to_string)
+        .chain(demo_config.domains.keys().cloned());
+    paths.extend(domains.map(|domain| format!("/{domain}/ingress")));
+    for route in &demo_config.services.ingress.routes {
+        let fixed = route.path.split('{').next().unwrap_or_default();
+        // Prefix matches whole segments, so stop at the last complete one.
+        let fixed = if route.path.contains('{') {
+            fixed
+                .rsplit_once('/')
+                .map(|(head, _)| head)
+                .unwrap_or_default()
+        } else {
+            fixed.trim_end_matches('/')
+        };
+        paths.insert(if fixed.is_empty() {
+            "/".to_string()
+        } else {
+            fixed.to_string()
+        });
+    }
+    paths
+}
+
+fn mount_source(bundle: &Path, out: &Path) -> anyhow::Result<PathBuf> {
+    let out_dir = std::path::absolute(out)?
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let out_dir = out_dir.canonicalize().unwrap_or(out_dir);
+    Ok(match bundle.strip_prefix(&out_dir) {
+        Ok(relative) if relative.as_os_str().is_empty() => PathBuf::from("."),
+        Ok(relative) => Path::new(".").join(relative),
+        Err(_) => bundle.to_path_buf(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(cloudflared: bool) -> ExportOptions {
+        ExportOptions {
+            tenant: "acme".to_string(),
+            team: "default".to_string(),
+            image: Some("operator:test".to_string()),
+            env: "demo".to_string(),
+            cloudflared,
+        }
+    }
+
+    #[test]
+    fn compose_mounts_the_bundle_and_wires_nats() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let bundle = dir.path().join("demo-bundle");
+        std::fs::create_dir_all(&bundle)?;
+        std::fs::write(
+            bundle.join("greentic.demo.yaml"),
+            "services:\n  gateway:\n    port: 8181\n",
+        )?;
+
+        let out = dir.path().join("docker-compose.yaml");
+        let file: serde_json::Value =
+            serde_yaml_bw::from_str(&compose(&bundle, &out, &options(false))?)?;
+        let operator = &file["services"]["operator"];
+        assert_eq!(operator["image"], "operator:test");
+        assert_eq!(operator["ports"][0], "8181:8181");
+        assert_eq!(operator["volumes"][0], "./demo-bundle:/bundle");
+        let command: Vec<&str> = operator["command"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|arg| arg.as_str())
+            .collect();
+        assert!(command.contains(&"--headless"));
+        assert!(
+            command
+                .windows(2)
+                .any(|pair| pair == ["--nats-url", "nats://nats:4222"])
+        );
+        assert!(file["services"]["nats"].is_object());
+        assert!(file["services"].get("cloudflared").is_none());
+
+        let file: serde_json::Value =
+            serde_yaml_bw::from_str(&compose(&bundle, &out, &options(true))?)?;
+        assert_eq!(
+            file["services"]["cloudflared"]["environment"]["TUNNEL_TOKEN"],
+            "${CLOUDFLARED_TUNNEL_TOKEN:?set CLOUDFLARED_TUNNEL_TOKEN to the tunnel token}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn k8s_routes_webhook_paths_through_the_ingress() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(
+            dir.path().join("greentic.demo.yaml"),
+            "services:\n  ingress:\n    routes:\n      - path: /hooks/tg-{token}\n        token: abc\n",
+        )?;
+        let k8s_options = K8sOptions {
+            name: "acme-demo".to_string(),
+            namespace: Some("trial".to_string()),
+            pvc: Some("acme-bundle".to_string()),
+            host: Some("demo.example.com".to_string()),
+            ingress_class: None,
+            tls_secret: None,
+        };
+        let documents: Vec<serde_json::Value> = k8s(dir.path(), &options(false), &k8s_options)?
+            .split("---\n")
+            .skip(1)
+            .map(serde_yaml_bw::from_str)
+            .collect::<Result<_, _>>()?;
+        let kinds: Vec<&str> = documents
+            .iter()
+            .filter_map(|doc| doc["kind"].as_str())
+            .collect();
+        assert_eq!(
+            kinds,
+            ["ConfigMap", "Secret", "Deployment", "Service", "Ingress"]
+        );
+        assert!(
+            documents
+                .iter()
+                .all(|doc| doc["metadata"]["namespace"] == "trial")
+        );
+
+        let pod = &documents[2]["spec"]["template"]["spec"];
+        assert_eq!(
+            pod["volumes"][0]["persistentVolumeClaim"]["claimName"],
+            "acme-bundle"
+        );
+        let args = pod["containers"][0]["args"].as_array().unwrap();
+        assert!(args.contains(&json!("https://demo.example.com")));
+        assert!(args.contains(&json!("nats://127.0.0.1:4222")));
+
+        let paths: Vec<&str> = documents[4]["spec"]["rules"][0]["http"]["paths"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|path| path["path"].as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            ["/events/ingress", "/hooks", "/messaging/ingress", "/v1"]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,389 @@
+//! `demo gc`: remove state a long-lived bundle no longer needs.
+//!
+//! Candidates, all under the bundle:
+//!
+//! - pidfiles in `state/pids/` and `state/demo-process.json` whose process is gone;
+//! - run records in `state/runs/` past the run retention, and setup run
+//!   directories (`state/runs/<domain>/<pack>/<flow>/<ts>`) older than it, keeping
+//!   the newest per flow;
+//! - captures (capability recordings and staged uploads) older than the capture
+//!   retention;
+//! - rotated logs (`*.log.*`) under `logs/` older than the log retention; live
+//!   `.log` files are never touched;
+//! - resolved manifests in `resolved/` for tenants that no longer have a
+//!   `tenants/<tenant>` directory.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::Context;
+use serde::Serialize;
+
+use crate::demo::capability_recording;
+use crate::demo::reload;
+use crate::demo::run_record::{self, RunRetention};
+use crate::domains::Domain;
+use crate::supervisor;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GcKind {
+    Pidfile,
+    Run,
+    Capture,
+    Log,
+    ResolvedManifest,
+}
+
+impl GcKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GcKind::Pidfile => "pidfile",
+            GcKind::Run => "run",
+            GcKind::Capture => "capture",
+            GcKind::Log => "log",
+            GcKind::ResolvedManifest => "resolved_manifest",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GcItem {
+    pub kind: GcKind,
+    pub path: PathBuf,
+    pub bytes: u64,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct GcOptions {
+    pub runs: RunRetention,
+    /// `0` keeps captures forever.
+    pub capture_max_age_days: u64,
+    /// `0` keeps rotated logs forever.
+    pub log_max_age_days: u64,
+}
+
+/// Everything `demo gc` would remove, grouped by kind.
+pub fn collect(bundle: &Path, options: &GcOptions) -> anyhow::Result<Vec<GcItem>> {
+    let mut items = Vec::new();
+    stale_pidfiles(bundle, &mut items)?;
+    for path in run_record::prune_candidates(bundle, options.runs)? {
+        items.push(item(GcKind::Run, path));
+    }
+    old_setup_runs(bundle, options.runs.max_age_days, &mut items)?;
+    if options.capture_max_age_days > 0 {
+        let max_age = days(options.capture_max_age_days);
+        for dir in [
+            capability_recording::recordings_root(bundle),
+            bundle.join("state").join("uploads"),
+        ] {
+            for path in files_older_than(&dir, max_age)? {
+                items.push(item(GcKind::Capture, path));
+            }
+        }
+    }
+    if options.log_max_age_days > 0 {
+        let max_age = days(options.log_max_age_days);
+        for path in files_older_than(&bundle.join("logs"), max_age)? {
+            if is_rotated_log(&path) {
+                items.push(item(GcKind::Log, path));
+            }
+        }
+    }
+    orphaned_manifests(bundle, &mut items)?;
+    items.sort_by(|a, b| (a.kind, &a.path).cmp(&(b.kind, &b.path)));
+    Ok(items)
+}
+
+/// Deletes `items`, then any directories the deletions left empty.
+pub fn remove(bundle: &Path, items: &[GcItem]) -> anyhow::Result<()> {
+    for item in items {
+        let result = if item.path.is_dir() {
+            fs::remove_dir_all(&item.path)
+        } else {
+            fs::remove_file(&item.path)
+        };
+        match result {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => {
+                return Err(err).with_context(|| format!("remove {}", item.path.display()));
+            }
+        }
+        let mut parent = item.path.parent();
+        while let Some(dir) = parent {
+            if dir == bundle || dir == bundle.join("state") || fs::remove_dir(dir).is_err() {
+                break;
+            }
+            parent = dir.parent();
+        }
+    }
+    Ok(())
+}
+
+fn days(count: u64) -> Duration {
+    Duration::from_secs(count.saturating_mul(86_400))
+}
+
+fn item(kind: GcKind, path: PathBuf) -> GcItem {
+    GcItem {
+        bytes: disk_size(&path),
+        kind,
+        path,
+    }
+}
+
+fn disk_size(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| disk_size(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+fn stale_pidfiles(bundle: &Path, items: &mut Vec<GcItem>) -> anyhow::Result<()> {
+    let pids_dir = bundle.join("state").join("pids");
+    for path in files_older_than(&pids_dir, Duration::ZERO)? {
+        if path.extension().and_then(|ext| ext.to_str()) != Some("pid") {
+            continue;
+        }
+        // An unreadable pidfile cannot name a live process either.
+        let alive = supervisor::read_pid(&path)
+            .ok()
+            .flatten()
+            .is_some_and(supervisor::is_running);
+        if !alive {
+            items.push(item(GcKind::Pidfile, path));
+        }
+    }
+    let state_dir = bundle.join("state");
+    if let Ok(Some(process)) = reload::read_process(&state_dir)
+        && !supervisor::is_running(process.pid)
+    {
+        items.push(item(GcKind::Pidfile, reload::process_path(&state_dir)));
+    }
+    Ok(())
+}
+
+/// `state/runs/<domain>/<pack>/<flow>/<timestamp>` directories older than
+/// `max_age_days`, except the newest one per flow.
+fn old_setup_runs(bundle: &Path, max_age_days: u64, items: &mut Vec<GcItem>) -> anyhow::Result<()> {
+    if max_age_days == 0 {
+        return Ok(());
+    }
+    let cutoff = SystemTime::now()
+        .checked_sub(days(max_age_days))
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+    let runs = run_record::runs_dir(bundle);
+    for domain in [Domain::Messaging, Domain::Events, Domain::Secrets] {
+        for pack in subdirs(&runs.join(crate::domains::domain_name(domain)))? {
+            for flow in subdirs(&pack)? {
+                let mut stamped = subdirs(&flow)?
+                    .into_iter()
+                    .filter_map(|dir| {
+                        let stamp = dir.file_name()?.to_str()?.parse::<u64>().ok()?;
+                        Some((stamp, dir))
+                    })
+                    .collect::<Vec<_>>();
+                stamped.sort();
+                stamped.pop();
+                for (stamp, dir) in stamped {
+                    if SystemTime::UNIX_EPOCH + Duration::from_secs(stamp) < cutoff {
+                        items.push(item(GcKind::Run, dir));
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn orphaned_manifests(bundle: &Path, items: &mut Vec<GcItem>) -> anyhow::Result<()> {
+    let resolved = bundle.join("resolved");
+    let tenants = bundle.join("tenants");
+    if !resolved.is_dir() || !tenants.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(&resolved)? {
+        let path = entry?.path();
+        if !path.is_file() || path.extension().and_then(|ext| ext.to_str()) != Some("yaml") {
+            continue;
+        }
+        let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        let tenant = stem.split_once('.').map_or(stem, |(tenant, _)| tenant);
+        // Per-domain `<domain>/tenants` lists do not count: manifests are
+        // resolved from the top-level tenant directories.
+        if !tenants.join(tenant).is_dir() {
+            items.push(item(GcKind::ResolvedManifest, path));
+        }
+    }
+    Ok(())
+}
+
+/// `operator.log.1`, `messaging.log.2026-10-01.gz`, but not `operator.log`.
+fn is_rotated_log(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.contains(".log."))
+}
+
+fn subdirs(dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut dirs = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            dirs.push(path);
+        }
+    }
+    Ok(dirs)
+}
+
+/// Files below `dir`, at any depth, last modified more than `max_age` ago.
+fn files_older_than(dir: &Path, max_age: Duration) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if !dir.is_dir() {
+        return Ok(files);
+    }
+    let now = SystemTime::now();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir).with_context(|| format!("read {}", dir.display()))? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_dir() {
+                pending.push(entry.path());
+                continue;
+            }
+            let old = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .is_some_and(|age| age >= max_age);
+            if old {
+                files.push(entry.path());
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// `1.5 MiB`-style sizes for the gc report.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> GcOptions {
+        GcOptions {
+            runs: RunRetention {
+                max_runs: 0,
+                max_age_days: 0,
+            },
+            capture_max_age_days: 0,
+            log_max_age_days: 0,
+        }
+    }
+
+    #[test]
+    fn dead_pidfiles_and_orphaned_manifests_are_collected() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let bundle = dir.path();
+        let pids = bundle.join("state/pids/demo.default");
+        fs::create_dir_all(&pids)?;
+        fs::write(pids.join("live.pid"), std::process::id().to_string())?;
+        fs::write(pids.join("dead.pid"), "garbage")?;
+        fs::create_dir_all(bundle.join("tenants/demo"))?;
+        fs::create_dir_all(bundle.join("resolved"))?;
+        fs::write(bundle.join("resolved/demo.default.yaml"), "tenant: demo\n")?;
+        fs::write(bundle.join("resolved/gone.yaml"), "tenant: gone\n")?;
+
+        let items = collect(bundle, &options())?;
+        let found = items
+            .iter()
+            .map(|item| {
+                (
+                    item.kind,
+                    item.path.strip_prefix(bundle).unwrap().to_path_buf(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    GcKind::Pidfile,
+                    PathBuf::from("state/pids/demo.default/dead.pid")
+                ),
+                (
+                    GcKind::ResolvedManifest,
+                    PathBuf::from("resolved/gone.yaml")
+                ),
+            ]
+        );
+        assert_eq!(items[1].bytes, 13);
+
+        remove(bundle, &items)?;
+        assert!(pids.join("live.pid").exists());
+        assert!(!pids.join("dead.pid").exists());
+        assert!(bundle.join("resolved/demo.default.yaml").exists());
+        assert!(collect(bundle, &options())?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn manifests_of_top_level_tenants_survive_domain_tenant_lists() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let bundle = dir.path();
+        fs::create_dir_all(bundle.join("tenants/demo"))?;
+        fs::create_dir_all(bundle.join("messaging/tenants/other"))?;
+        fs::create_dir_all(bundle.join("events/tenants/other"))?;
+        fs::create_dir_all(bundle.join("resolved"))?;
+        fs::write(bundle.join("resolved/demo.default.yaml"), "tenant: demo\n")?;
+        fs::write(bundle.join("resolved/other.yaml"), "tenant: other\n")?;
+
+        let items = collect(bundle, &options())?;
+        let found = items
+            .iter()
+            .map(|item| item.path.strip_prefix(bundle).unwrap().to_path_buf())
+            .collect::<Vec<_>>();
+        assert_eq!(found, vec![PathBuf::from("resolved/other.yaml")]);
+        Ok(())
+    }
+
+    #[test]
+    fn only_rotated_logs_count_and_sizes_are_readable() {
+        assert!(is_rotated_log(Path::new("logs/operator.log.1")));
+        assert!(!is_rotated_log(Path::new("logs/operator.log")));
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+}
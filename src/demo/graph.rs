@@ -0,0 +1,390 @@
+//! `demo graph`: how the packs of a bundle depend on each other.
+//!
+//! Nodes are packs, capabilities, and the flows that call into other packs. Edges
+//! come from `meta.depends_on` plus `providers.<id>.depends_on` in
+//! greentic.demo.yaml, from the offers and `requires` of the capabilities
+//! extension, and from `flows[].calls`. Each capability also gets an edge to the
+//! offer it resolves to for the given scope, which shows why a pack is pulled in.
+//! Packs and flows the bundle does not have, and capabilities nobody offers, become
+//! `missing` nodes.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::capabilities::{self, CapabilityRegistry, CapabilityRequirement, ResolveScope};
+use crate::config;
+use crate::domains::{self, Domain, FlowCalls};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    Pack,
+    Flow,
+    Capability,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GraphNode {
+    pub id: String,
+    pub kind: NodeKind,
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain: Option<&'static str>,
+    /// Referenced but not in the bundle.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub missing: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeKind {
+    DependsOn,
+    Offers,
+    Requires,
+    /// The capability resolves to this pack's offer.
+    ResolvesTo,
+    /// The pack contains the flow.
+    Contains,
+    Calls,
+}
+
+impl EdgeKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EdgeKind::DependsOn => "depends_on",
+            EdgeKind::Offers => "offers",
+            EdgeKind::Requires => "requires",
+            EdgeKind::ResolvesTo => "resolves_to",
+            EdgeKind::Contains => "contains",
+            EdgeKind::Calls => "calls",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub kind: EdgeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// What the graph needs to know about one pack.
+#[derive(Clone, Debug)]
+pub struct GraphPack {
+    pub pack_id: String,
+    pub domain: Domain,
+    pub path: PathBuf,
+    pub depends_on: Vec<String>,
+    pub requires: Vec<CapabilityRequirement>,
+    pub flows: Vec<FlowCalls>,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct PackGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+fn pack_node(pack_id: &str) -> String {
+    format!("pack:{pack_id}")
+}
+
+fn flow_node(pack_id: &str, flow_id: &str) -> String {
+    format!("flow:{pack_id}/{flow_id}")
+}
+
+fn capability_node(cap_id: &str) -> String {
+    format!("cap:{cap_id}")
+}
+
+/// Reads the packs of `domains` with their dependencies, requirements and calls.
+pub fn collect_packs(bundle: &Path, domains: &[Domain]) -> anyhow::Result<Vec<GraphPack>> {
+    let extra = config::demo_provider_dependencies(bundle)?;
+    let mut packs = Vec::new();
+    let mut seen = BTreeSet::new();
+    for domain in domains {
+        let mut discovered = domains::discover_provider_packs_cbor_only(bundle, *domain)?;
+        domains::add_dependencies(&mut discovered, &extra);
+        for pack in discovered {
+            if !seen.insert(pack.path.clone()) {
+                continue;
+            }
+            packs.push(GraphPack {
+                requires: capabilities::read_capability_requirements(&pack.path)?,
+                flows: domains::read_flow_calls(&pack.path)?,
+                pack_id: pack.pack_id,
+                domain: *domain,
+                path: pack.path,
+                depends_on: pack.depends_on,
+            });
+        }
+    }
+    Ok(packs)
+}
+
+#[derive(Default)]
+struct Builder {
+    nodes: BTreeMap<String, GraphNode>,
+    edges: BTreeSet<(String, String, EdgeKind, Option<String>)>,
+}
+
+impl Builder {
+    fn node(&mut self, id: String, kind: NodeKind, label: &str) {
+        self.nodes.entry(id.clone()).or_insert(GraphNode {
+            id,
+            kind,
+            label: label.to_string(),
+            domain: None,
+            missing: true,
+        });
+    }
+
+    fn edge(&mut self, from: &str, to: &str, kind: EdgeKind, label: Option<String>) {
+        self.edges
+            .insert((from.to_string(), to.to_string(), kind, label));
+    }
+}
+
+/// Builds the graph; capabilities resolve as they would for `scope`.
+pub fn build_graph(
+    packs: &[GraphPack],
+    registry: &CapabilityRegistry,
+    scope: &ResolveScope,
+) -> PackGraph {
+    let mut builder = Builder::default();
+    for pack in packs {
+        builder.nodes.insert(
+            pack_node(&pack.pack_id),
+            GraphNode {
+                id: pack_node(&pack.pack_id),
+                kind: NodeKind::Pack,
+                label: pack.pack_id.clone(),
+                domain: Some(domains::domain_name(pack.domain)),
+                missing: false,
+            },
+        );
+    }
+    let in_bundle: BTreeSet<&str> = packs.iter().map(|pack| pack.pack_id.as_str()).collect();
+
+    for pack in packs {
+        let from = pack_node(&pack.pack_id);
+        for dep in &pack.depends_on {
+            builder.node(pack_node(dep), NodeKind::Pack, dep);
+            builder.edge(&from, &pack_node(dep), EdgeKind::DependsOn, None);
+        }
+        for requirement in &pack.requires {
+            let cap = capability_node(&requirement.cap_id);
+            builder.node(cap.clone(), NodeKind::Capability, &requirement.cap_id);
+            builder.edge(&from, &cap, EdgeKind::Requires, requirement.version.clone());
+        }
+        for flow in &pack.flows {
+            let flow_id = flow_node(&pack.pack_id, &flow.flow_id);
+            builder.node(flow_id.clone(), NodeKind::Flow, &flow.flow_id);
+            if let Some(node) = builder.nodes.get_mut(&flow_id) {
+                node.missing = false;
+            }
+            builder.edge(&from, &flow_id, EdgeKind::Contains, None);
+            for call in &flow.calls {
+                let target = match call.split_once('/') {
+                    Some((target_pack, target_flow)) => {
+                        let target = flow_node(target_pack, target_flow);
+                        builder.node(target.clone(), NodeKind::Flow, target_flow);
+                        if in_bundle.contains(target_pack) {
+                            builder.nodes.get_mut(&target).expect("just added").missing = false;
+                            builder.edge(
+                                &pack_node(target_pack),
+                                &target,
+                                EdgeKind::Contains,
+                                None,
+                            );
+                        }
+                        target
+                    }
+                    None => {
+                        builder.node(pack_node(call), NodeKind::Pack, call);
+                        pack_node(call)
+                    }
+                };
+                builder.edge(&flow_id, &target, EdgeKind::Calls, None);
+            }
+        }
+    }
+
+    for cap_id in registry.cap_ids() {
+        let cap = capability_node(cap_id);
+        builder.node(cap.clone(), NodeKind::Capability, cap_id);
+        if let Some(node) = builder.nodes.get_mut(&cap) {
+            node.missing = registry.offers_for_capability(cap_id).is_empty();
+        }
+        for offer in registry.offers_for_capability(cap_id) {
+            builder.edge(
+                &pack_node(&offer.pack_id),
+                &cap,
+                EdgeKind::Offers,
+                Some(offer.version.clone()),
+            );
+        }
+        if let Some(binding) = registry.resolve(cap_id, None, scope) {
+            builder.edge(
+                &cap,
+                &pack_node(&binding.pack_id),
+                EdgeKind::ResolvesTo,
+                Some(binding.stable_id),
+            );
+        }
+    }
+
+    PackGraph {
+        nodes: builder.nodes.into_values().collect(),
+        edges: builder
+            .edges
+            .into_iter()
+            .map(|(from, to, kind, label)| GraphEdge {
+                from,
+                to,
+                kind,
+                label,
+            })
+            .collect(),
+    }
+}
+
+impl PackGraph {
+    /// Graphviz source: packs are boxes, flows notes, capabilities ellipses.
+    pub fn to_dot(&self) -> String {
+        let mut out =
+            String::from("digraph bundle {\n  rankdir=LR;\n  node [fontname=\"Helvetica\"];\n");
+        for node in &self.nodes {
+            let shape = match node.kind {
+                NodeKind::Pack => "box",
+                NodeKind::Flow => "note",
+                NodeKind::Capability => "ellipse",
+            };
+            let label = match node.domain {
+                Some(domain) => format!("{}\\n({domain})", escape(&node.label)),
+                None => escape(&node.label),
+            };
+            let missing = if node.missing {
+                ", style=dashed, color=red"
+            } else {
+                ""
+            };
+            let _ = writeln!(
+                out,
+                "  \"{}\" [label=\"{label}\", shape={shape}{missing}];",
+                escape(&node.id)
+            );
+        }
+        for edge in &self.edges {
+            let label = match &edge.label {
+                Some(label) => format!("{} {}", edge.kind.as_str(), escape(label)),
+                None => edge.kind.as_str().to_string(),
+            };
+            let style = match edge.kind {
+                EdgeKind::Offers => ", style=dashed, color=gray40",
+                EdgeKind::ResolvesTo => ", style=bold, color=darkgreen",
+                EdgeKind::Contains => ", arrowhead=none, color=gray60",
+                _ => "",
+            };
+            let _ = writeln!(
+                out,
+                "  \"{}\" -> \"{}\" [label=\"{label}\"{style}];",
+                escape(&edge.from),
+                escape(&edge.to)
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pack(pack_id: &str, depends_on: &[&str], flows: Vec<FlowCalls>) -> GraphPack {
+        GraphPack {
+            pack_id: pack_id.to_string(),
+            domain: Domain::Messaging,
+            path: PathBuf::from(format!("providers/messaging/{pack_id}.gtpack")),
+            depends_on: depends_on.iter().map(|dep| dep.to_string()).collect(),
+            requires: vec![CapabilityRequirement {
+                cap_id: "greentic.cap.state".to_string(),
+                version: None,
+            }],
+            flows,
+        }
+    }
+
+    #[test]
+    fn links_dependencies_requirements_and_calls() {
+        let packs = vec![
+            pack(
+                "messaging-telegram",
+                &["secrets-vault", "events-timer"],
+                vec![FlowCalls {
+                    flow_id: "send".to_string(),
+                    calls: vec!["messaging-webchat/render".to_string(), "cards".to_string()],
+                }],
+            ),
+            pack("messaging-webchat", &[], Vec::new()),
+            pack("secrets-vault", &[], Vec::new()),
+        ];
+        let graph = build_graph(
+            &packs,
+            &CapabilityRegistry::default(),
+            &ResolveScope::default(),
+        );
+        let node = |id: &str| graph.nodes.iter().find(|node| node.id == id).unwrap();
+        assert!(!node("pack:secrets-vault").missing);
+        assert!(node("pack:events-timer").missing);
+        assert!(node("pack:cards").missing);
+        assert!(!node("flow:messaging-webchat/render").missing);
+        assert_eq!(node("cap:greentic.cap.state").kind, NodeKind::Capability);
+        assert!(node("cap:greentic.cap.state").missing);
+        let has_edge = |from: &str, to: &str, kind: EdgeKind| {
+            graph
+                .edges
+                .iter()
+                .any(|edge| edge.from == from && edge.to == to && edge.kind == kind)
+        };
+        assert!(has_edge(
+            "pack:messaging-telegram",
+            "pack:secrets-vault",
+            EdgeKind::DependsOn
+        ));
+        assert!(has_edge(
+            "flow:messaging-telegram/send",
+            "flow:messaging-webchat/render",
+            EdgeKind::Calls
+        ));
+        assert!(has_edge(
+            "pack:messaging-webchat",
+            "flow:messaging-webchat/render",
+            EdgeKind::Contains
+        ));
+        assert!(has_edge(
+            "pack:secrets-vault",
+            "cap:greentic.cap.state",
+            EdgeKind::Requires
+        ));
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph bundle {"));
+        assert!(dot.contains(
+            "\"pack:messaging-telegram\" -> \"pack:secrets-vault\" [label=\"depends_on\"];"
+        ));
+        assert!(dot.contains(
+            "\"pack:events-timer\" [label=\"events-timer\", shape=box, style=dashed, color=red];"
+        ));
+    }
+}
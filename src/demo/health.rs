@@ -0,0 +1,266 @@
+//! Provider-level health for `demo status --deep`.
+//!
+//! Everything here comes from files in the bundle, so it works whether or not the
+//! demo is running:
+//!
+//! - pack digest: the provider's `.gtpack` under `providers/<domain>/`;
+//! - setup state: the newest `<provider>.setup.json` written by `demo setup` or
+//!   `demo start`;
+//! - secret completeness: the pack's required keys looked up in the bundle's
+//!   secrets store;
+//! - last send/ingress: the newest successful `send_payload`/`ingest_http` run
+//!   record under `state/runs`;
+//! - subscriptions: the states under `state/subscriptions`.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::demo::run_record::{self, RunRecord, RunRecordStatus};
+use crate::discovery;
+use crate::provider_config_envelope;
+use crate::runtime_state::{RuntimePaths, read_json};
+use crate::secret_requirements::load_secret_keys_from_pack;
+use crate::secrets_gate;
+use crate::secrets_setup::resolve_env;
+use crate::subscriptions_universal::{demo::state_root, store::SubscriptionStore};
+
+const SEND_OP: &str = "send_payload";
+const INGRESS_OP: &str = "ingest_http";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SetupStatus {
+    Done,
+    Pending,
+    Failed,
+}
+
+impl SetupStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SetupStatus::Done => "done",
+            SetupStatus::Pending => "pending",
+            SetupStatus::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SetupState {
+    pub status: SetupStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flow: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SecretCompleteness {
+    pub required: usize,
+    pub missing: Vec<String>,
+    /// Set when the secrets store could not be read; `missing` is then empty.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ProviderHealth {
+    pub provider: String,
+    pub domain: String,
+    pub pack_path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pack_digest: Option<String>,
+    pub setup: SetupState,
+    pub secrets: SecretCompleteness,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_send: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_ingress: Option<String>,
+    pub subscriptions: usize,
+}
+
+/// The fields of a `<provider>.setup.json` record that status needs.
+#[derive(Deserialize)]
+struct SetupRecord {
+    flow: String,
+    success: bool,
+    timestamp: DateTime<Utc>,
+}
+
+/// Health of every provider pack in `bundle` for `tenant`/`team`.
+pub fn provider_health(
+    bundle: &Path,
+    tenant: &str,
+    team: &str,
+) -> anyhow::Result<Vec<ProviderHealth>> {
+    let found = discovery::discover(bundle)?;
+    let records = run_record::list_records(bundle)?
+        .into_iter()
+        .filter(|record| {
+            record.tenant == tenant
+                && record.team.as_deref().unwrap_or("default") == team
+                && record.status == RunRecordStatus::Success
+        })
+        .collect::<Vec<_>>();
+    let subscriptions = SubscriptionStore::new(state_root(bundle)).list_states()?;
+    let secrets = secrets_gate::resolve_secrets_manager(bundle, tenant, Some(team));
+    let env = resolve_env(None);
+
+    let mut health = Vec::new();
+    for provider in found.providers {
+        let id = provider.provider_id;
+        let secrets = match (&secrets, load_secret_keys_from_pack(&provider.pack_path)) {
+            (_, Err(err)) => SecretCompleteness {
+                required: 0,
+                missing: Vec::new(),
+                error: Some(format!("{err:#}")),
+            },
+            (_, Ok(keys)) if keys.is_empty() => SecretCompleteness {
+                required: 0,
+                missing: Vec::new(),
+                error: None,
+            },
+            (Err(err), Ok(keys)) => SecretCompleteness {
+                required: keys.len(),
+                missing: Vec::new(),
+                error: Some(format!("{err:#}")),
+            },
+            (Ok(handle), Ok(keys)) => {
+                let mut missing = Vec::new();
+                let mut error = None;
+                for key in &keys {
+                    match secrets_gate::read_provider_secret(
+                        handle,
+                        &env,
+                        tenant,
+                        Some(team),
+                        &id,
+                        key,
+                    ) {
+                        Ok(Some(_)) => {}
+                        Ok(None) => missing.push(key.clone()),
+                        Err(err) => {
+                            error = Some(format!("{err:#}"));
+                            missing.clear();
+                            break;
+                        }
+                    }
+                }
+                SecretCompleteness {
+                    required: keys.len(),
+                    missing,
+                    error,
+                }
+            }
+        };
+        health.push(ProviderHealth {
+            pack_digest: provider_config_envelope::pack_digest(&provider.pack_path).ok(),
+            setup: setup_state(bundle, tenant, team, &id)?,
+            secrets,
+            last_send: last_success(&records, &id, SEND_OP),
+            last_ingress: last_success(&records, &id, INGRESS_OP),
+            subscriptions: subscriptions
+                .iter()
+                .filter(|state| {
+                    state.provider == id
+                        && state.tenant == tenant
+                        && state.team.as_deref().unwrap_or("default") == team
+                })
+                .count(),
+            domain: provider.domain,
+            pack_path: provider.pack_path,
+            provider: id,
+        });
+    }
+    Ok(health)
+}
+
+/// `demo start` records setup per tenant and team, `demo setup` per tenant; the
+/// newer record wins.
+fn setup_state(
+    bundle: &Path,
+    tenant: &str,
+    team: &str,
+    provider: &str,
+) -> anyhow::Result<SetupState> {
+    let state_dir = bundle.join("state");
+    let file_name = format!("{provider}.setup.json");
+    let candidates = [
+        RuntimePaths::new(&state_dir, tenant, team)
+            .runtime_root()
+            .join("providers")
+            .join(&file_name),
+        state_dir
+            .join("runtime")
+            .join(tenant)
+            .join("providers")
+            .join(&file_name),
+    ];
+    let mut newest: Option<SetupRecord> = None;
+    for path in candidates {
+        if let Some(record) = read_json::<SetupRecord>(&path)?
+            && newest
+                .as_ref()
+                .is_none_or(|current| record.timestamp > current.timestamp)
+        {
+            newest = Some(record);
+        }
+    }
+    Ok(match newest {
+        Some(record) => SetupState {
+            status: if record.success {
+                SetupStatus::Done
+            } else {
+                SetupStatus::Failed
+            },
+            flow: Some(record.flow),
+            at: Some(record.timestamp),
+        },
+        None => SetupState {
+            status: SetupStatus::Pending,
+            flow: None,
+            at: None,
+        },
+    })
+}
+
+fn last_success(records: &[RunRecord], provider: &str, op: &str) -> Option<String> {
+    records
+        .iter()
+        .filter(|record| record.provider == provider && record.op == op)
+        .map(|record| record.finished_at.clone())
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newest_setup_record_wins_and_missing_is_pending() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let bundle = dir.path();
+        assert_eq!(
+            setup_state(bundle, "demo", "default", "messaging-slack")?.status,
+            SetupStatus::Pending
+        );
+        let team_dir = bundle.join("state/runtime/demo.default/providers");
+        let tenant_dir = bundle.join("state/runtime/demo/providers");
+        std::fs::create_dir_all(&team_dir)?;
+        std::fs::create_dir_all(&tenant_dir)?;
+        std::fs::write(
+            team_dir.join("messaging-slack.setup.json"),
+            r#"{"flow":"setup_default","success":true,"timestamp":"2026-01-01T00:00:00Z"}"#,
+        )?;
+        std::fs::write(
+            tenant_dir.join("messaging-slack.setup.json"),
+            r#"{"flow":"setup_custom","success":false,"timestamp":"2026-02-01T00:00:00Z"}"#,
+        )?;
+        let state = setup_state(bundle, "demo", "default", "messaging-slack")?;
+        assert_eq!(state.status, SetupStatus::Failed);
+        assert_eq!(state.flow.as_deref(), Some("setup_custom"));
+        Ok(())
+    }
+}
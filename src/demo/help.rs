@@ -8,4 +8,11 @@ pub fn print_help() {
             "Available commands:\n  @show              ─ display the last adaptive card summary\n  @json              ─ emit the raw JSON value received from the flow\n  @back              ─ revert to the previous blocked card/inputs\n  @input <k>=<v>     ─ set or override an input field\n  @click <action_id> ─ submit the card with the provided action\n  @setup [provider]  ─ run interactive setup wizard for a provider\n  @help              ─ print this help text\n  @quit              ─ exit the REPL"
         )
     );
+    println!(
+        "{}",
+        operator_i18n::tr(
+            "demo.repl.help_debug",
+            "Debugging:\n  @break [node]      ─ pause before a node runs, or list breakpoints\n  @unbreak <node>    ─ remove a breakpoint\n  @step              ─ run the paused node and pause before the next\n  @continue          ─ run to the next breakpoint\n  @payload           ─ show the paused node's input and the previous output\n  @edit <json>       ─ replace the payload handed to the paused node\n  @rerun             ─ run the previous node again"
+        )
+    );
 }
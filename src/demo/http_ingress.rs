@@ -7,7 +7,7 @@ use http_body_util::{BodyExt, Full};
 use hyper::{
     Method, Request, Response, StatusCode,
     body::{Bytes, Incoming},
-    header::{CONTENT_TYPE, HeaderName, HeaderValue},
+    header::{CONTENT_TYPE, HOST, HeaderName, HeaderValue},
     server::conn::http1::Builder as Http1Builder,
     service::service_fn,
 };
@@ -15,8 +15,10 @@ use hyper_util::rt::tokio::TokioIo;
 use serde_json::json;
 use tokio::{net::TcpListener, runtime::Runtime, sync::oneshot};
 
+use crate::bus;
 use crate::demo::event_router::route_events_to_default_flow;
 use crate::demo::ingress_dispatch::dispatch_http_ingress;
+use crate::demo::ingress_router::IngressRouter;
 use crate::demo::ingress_types::{IngressHttpResponse, IngressRequestV1};
 use crate::demo::runner_host::{DemoRunnerHost, OperatorContext};
 use crate::domains::{self, Domain};
@@ -28,6 +30,7 @@ pub struct HttpIngressConfig {
     pub bind_addr: SocketAddr,
     pub domains: Vec<Domain>,
     pub runner_host: Arc<DemoRunnerHost>,
+    pub router: IngressRouter,
 }
 
 pub struct HttpIngressServer {
@@ -43,6 +46,7 @@ impl HttpIngressServer {
         let state = Arc::new(HttpIngressState {
             runner_host,
             domains,
+            router: config.router,
         });
         let (tx, rx) = oneshot::channel();
         let addr = config.bind_addr;
@@ -135,6 +139,7 @@ impl HttpIngressServer {
 struct HttpIngressState {
     runner_host: Arc<DemoRunnerHost>,
     domains: Vec<Domain>,
+    router: IngressRouter,
 }
 
 async fn handle_request(
@@ -176,14 +181,34 @@ async fn handle_request_inner(
     }
 
     let method = req.method().clone();
-    let parsed = match parse_route_segments(req.uri().path()) {
-        Some(value) => value,
-        None => {
-            return Err(error_response(
-                StatusCode::BAD_REQUEST,
-                "expected /v1/{domain}/ingress/{provider}/{tenant}/{team?}/{handler?}",
-            ));
-        }
+    let host = req
+        .headers()
+        .get(HOST)
+        .and_then(|value| value.to_str().ok());
+    // Configured routes first; they may also rewrite the path the provider sees.
+    let (parsed, path) = match state.router.route(host, &path) {
+        Some(routed) => (
+            ParsedIngressRoute {
+                domain: routed.domain,
+                provider: routed.provider,
+                tenant: routed.tenant,
+                team: routed.team,
+                handler: routed.handler,
+            },
+            routed.path,
+        ),
+        None => match parse_route_segments(&path) {
+            Some(mut value) => {
+                value.provider = state.router.provider(&value.provider).to_string();
+                (value, path)
+            }
+            None => {
+                return Err(error_response(
+                    StatusCode::BAD_REQUEST,
+                    "expected /v1/{domain}/ingress/{provider}/{tenant}/{team?}/{handler?} or a configured ingress route",
+                ));
+            }
+        },
     };
     let domain = parsed.domain;
     if !state.domains.contains(&domain) {
@@ -271,6 +296,28 @@ async fn handle_request_inner(
             ),
         );
     }
+    let bundle_root = state.runner_host.bundle_root();
+    let domain_name = domains::domain_name(domain);
+    for event in &result.events {
+        if let Ok(value) = serde_json::to_value(event) {
+            publish_bus(
+                bundle_root,
+                &context,
+                &bus::ingress_subject(&parsed.tenant, &parsed.team, domain_name, &parsed.provider),
+                &value,
+            );
+        }
+    }
+    for envelope in &result.messaging_envelopes {
+        if let Ok(value) = serde_json::to_value(envelope) {
+            publish_bus(
+                bundle_root,
+                &context,
+                &bus::ingress_subject(&parsed.tenant, &parsed.team, domain_name, &parsed.provider),
+                &value,
+            );
+        }
+    }
     if domain == Domain::Events && !result.events.is_empty() {
         route_events_to_default_flow(state.runner_host.bundle_root(), &context, &result.events)
             .map_err(|err| error_response(StatusCode::BAD_GATEWAY, err.to_string()))?;
@@ -406,11 +453,27 @@ fn route_messaging_envelopes(
                             route_to_card
                         ),
                     );
-                    run_app_flow_safe(bundle, ctx, &app_pack_path, &pack_info, &flow, envelope)
+                    run_app_flow_safe(
+                        bundle,
+                        ctx,
+                        provider,
+                        &app_pack_path,
+                        &pack_info,
+                        &flow,
+                        envelope,
+                    )
                 }
             }
         } else {
-            run_app_flow_safe(bundle, ctx, &app_pack_path, &pack_info, &flow, envelope)
+            run_app_flow_safe(
+                bundle,
+                ctx,
+                provider,
+                &app_pack_path,
+                &pack_info,
+                &flow,
+                envelope,
+            )
         };
 
         for out_envelope in outputs {
@@ -452,8 +515,7 @@ fn route_messaging_envelopes(
                 }
             };
 
-            let provider_type =
-                runner_host.canonical_provider_type(Domain::Messaging, provider);
+            let provider_type = runner_host.canonical_provider_type(Domain::Messaging, provider);
             let send_input =
                 egress::build_send_payload(payload, &provider_type, &ctx.tenant, ctx.team.clone());
             let send_bytes = serde_json::to_vec(&send_input)?;
@@ -473,6 +535,23 @@ fn route_messaging_envelopes(
                 .and_then(|v| v.as_bool())
                 .unwrap_or(false);
 
+            publish_bus(
+                bundle,
+                ctx,
+                &bus::egress_subject(
+                    &ctx.tenant,
+                    ctx.team.as_deref().unwrap_or("default"),
+                    provider,
+                ),
+                &json!({
+                    "envelope_id": out_envelope.id,
+                    "provider": provider,
+                    "ok": outcome.success && provider_ok,
+                    "error": outcome.error,
+                    "output": outcome.output,
+                }),
+            );
+
             if outcome.success && provider_ok {
                 operator_log::info(
                     module_path!(),
@@ -505,11 +584,24 @@ fn route_messaging_envelopes(
     Ok(())
 }
 
+/// Publishes ingress events and egress results on the tenant's bus (JetStream when
+/// `demo start --nats=jetstream` set it up, in-process otherwise). Best effort.
+fn publish_bus(bundle: &Path, ctx: &OperatorContext, subject: &str, payload: &serde_json::Value) {
+    let team = ctx.team.as_deref().unwrap_or("default");
+    let bus = bus::for_scope(bundle, &ctx.tenant, team);
+    if let Err(err) = bus.publish(subject, payload) {
+        operator_log::warn(
+            module_path!(),
+            format!(
+                "[demo ingress] {} bus publish failed subject={subject} err={err}",
+                bus.kind().as_str()
+            ),
+        );
+    }
+}
+
 /// Read a card JSON from the app pack's assets directory.
-fn read_card_from_pack(
-    pack_path: &std::path::Path,
-    card_key: &str,
-) -> Option<serde_json::Value> {
+fn read_card_from_pack(pack_path: &std::path::Path, card_key: &str) -> Option<serde_json::Value> {
     let file = std::fs::File::open(pack_path).ok()?;
     let mut archive = zip::ZipArchive::new(file).ok()?;
     let asset_path = format!("assets/cards/{card_key}.json");
@@ -523,12 +615,21 @@ fn read_card_from_pack(
 fn run_app_flow_safe(
     bundle: &std::path::Path,
     ctx: &crate::demo::runner_host::OperatorContext,
+    provider: &str,
     app_pack_path: &std::path::Path,
     pack_info: &crate::messaging_universal::app::AppPackInfo,
     flow: &crate::messaging_universal::app::AppFlowInfo,
     envelope: &greentic_types::ChannelMessageEnvelope,
 ) -> Vec<greentic_types::ChannelMessageEnvelope> {
-    match app::run_app_flow(bundle, ctx, app_pack_path, &pack_info.pack_id, &flow.id, envelope) {
+    match app::run_app_flow(
+        bundle,
+        ctx,
+        provider,
+        app_pack_path,
+        &pack_info.pack_id,
+        &flow.id,
+        envelope,
+    ) {
         Ok(outputs) => outputs,
         Err(err) => {
             operator_log::error(
@@ -714,12 +815,7 @@ fn collect_queries(query: Option<&str>) -> Vec<(String, String)> {
 }
 
 fn parse_domain(value: &str) -> Option<Domain> {
-    match value.to_lowercase().as_str() {
-        "messaging" => Some(Domain::Messaging),
-        "events" => Some(Domain::Events),
-        "secrets" => Some(Domain::Secrets),
-        _ => None,
-    }
+    domains::parse_domain(value)
 }
 
 #[derive(Clone, Debug)]
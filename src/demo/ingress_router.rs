@@ -0,0 +1,335 @@
+//! Configurable ingress routes (`services.ingress` in greentic.demo.yaml).
+//!
+//! External providers often insist on their own webhook URL shape. A route maps such
+//! a path (optionally for one `Host`) onto a domain/provider/tenant/team, can require
+//! a static token segment, and can rewrite the path the provider sees. Provider
+//! aliases apply to routes and to the default `/v1/...` scheme alike.
+//!
+//! ```yaml
+//! services:
+//!   ingress:
+//!     aliases:
+//!       tg: messaging-telegram
+//!     routes:
+//!       - path: /telegram/{token}
+//!         provider: tg
+//!         tenant: demo
+//!         token_env: TELEGRAM_WEBHOOK_TOKEN
+//!         rewrite: /webhook
+//!       - path: /slack/{tenant}/events
+//!         host: slack.example.com
+//!         provider: messaging-slack
+//! ```
+
+use std::collections::BTreeMap;
+
+use anyhow::{Context, anyhow};
+
+use crate::config::{DemoIngressConfig, DemoIngressRoute};
+use crate::domains::Domain;
+use crate::interpolate::{self, SecretScope};
+
+/// Where a request goes, and the path handed to the provider.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoutedIngress {
+    pub domain: Domain,
+    pub provider: String,
+    pub tenant: String,
+    pub team: String,
+    pub handler: Option<String>,
+    pub path: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct IngressRouter {
+    aliases: BTreeMap<String, String>,
+    routes: Vec<CompiledRoute>,
+}
+
+#[derive(Clone, Debug)]
+struct CompiledRoute {
+    host: Option<String>,
+    segments: Vec<Segment>,
+    domain: Domain,
+    provider: Option<String>,
+    tenant: Option<String>,
+    team: Option<String>,
+    handler: Option<String>,
+    token: Option<String>,
+    rewrite: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Capture(Field),
+    Token,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Field {
+    Tenant,
+    Team,
+    Provider,
+    Handler,
+}
+
+impl IngressRouter {
+    /// `secrets` is where `${secret:...}` route tokens are read, in the route's own
+    /// tenant/team when it sets them.
+    pub fn new(
+        config: &DemoIngressConfig,
+        secrets: Option<SecretScope<'_>>,
+    ) -> anyhow::Result<Self> {
+        let routes = config
+            .routes
+            .iter()
+            .map(|route| compile_route(route, secrets))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self {
+            aliases: config.aliases.clone(),
+            routes,
+        })
+    }
+
+    /// Provider id for `name`, following an alias if one is configured.
+    pub fn provider<'a>(&'a self, name: &'a str) -> &'a str {
+        self.aliases.get(name).map(String::as_str).unwrap_or(name)
+    }
+
+    /// First configured route matching `host` and `path`.
+    pub fn route(&self, host: Option<&str>, path: &str) -> Option<RoutedIngress> {
+        let segments = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>();
+        self.routes
+            .iter()
+            .find_map(|route| self.match_route(route, host, path, &segments))
+    }
+
+    fn match_route(
+        &self,
+        route: &CompiledRoute,
+        host: Option<&str>,
+        path: &str,
+        segments: &[&str],
+    ) -> Option<RoutedIngress> {
+        if let Some(expected) = &route.host
+            && !host.is_some_and(|host| host_matches(expected, host))
+        {
+            return None;
+        }
+        if segments.len() < route.segments.len() {
+            return None;
+        }
+        let mut captured = BTreeMap::new();
+        for (segment, value) in route.segments.iter().zip(segments) {
+            match segment {
+                Segment::Literal(literal) if literal == value => {}
+                Segment::Literal(_) => return None,
+                Segment::Capture(field) => {
+                    captured.insert(*field, (*value).to_string());
+                }
+                Segment::Token => {
+                    let token = route.token.as_deref()?;
+                    if !constant_time_eq(token.as_bytes(), value.as_bytes()) {
+                        return None;
+                    }
+                }
+            }
+        }
+        let pick = |field: Field, fixed: &Option<String>| {
+            captured.get(&field).cloned().or_else(|| fixed.clone())
+        };
+        let provider = pick(Field::Provider, &route.provider)?;
+        let routed = RoutedIngress {
+            domain: route.domain,
+            provider: self.provider(&provider).to_string(),
+            tenant: pick(Field::Tenant, &route.tenant)?,
+            team: pick(Field::Team, &route.team).unwrap_or_else(|| "default".to_string()),
+            handler: pick(Field::Handler, &route.handler),
+            path: path.to_string(),
+        };
+        let rest = segments[route.segments.len()..].join("/");
+        let path = match &route.rewrite {
+            Some(template) => template
+                .replace("{tenant}", &routed.tenant)
+                .replace("{team}", &routed.team)
+                .replace("{provider}", &routed.provider)
+                .replace("{handler}", routed.handler.as_deref().unwrap_or_default())
+                .replace("{rest}", &rest),
+            None => routed.path.clone(),
+        };
+        Some(RoutedIngress { path, ..routed })
+    }
+}
+
+fn compile_route(
+    route: &DemoIngressRoute,
+    secrets: Option<SecretScope<'_>>,
+) -> anyhow::Result<CompiledRoute> {
+    let domain = match route.domain.to_ascii_lowercase().as_str() {
+        "messaging" => Domain::Messaging,
+        "events" => Domain::Events,
+        other => {
+            return Err(anyhow!(
+                "ingress route {}: unsupported domain {other} (expected messaging or events)",
+                route.path
+            ));
+        }
+    };
+    let segments = route
+        .path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| match segment {
+            "{tenant}" => Ok(Segment::Capture(Field::Tenant)),
+            "{team}" => Ok(Segment::Capture(Field::Team)),
+            "{provider}" => Ok(Segment::Capture(Field::Provider)),
+            "{handler}" => Ok(Segment::Capture(Field::Handler)),
+            "{token}" => Ok(Segment::Token),
+            other if other.starts_with('{') => Err(anyhow!(
+                "ingress route {}: unknown placeholder {other}",
+                route.path
+            )),
+            other => Ok(Segment::Literal(other.to_string())),
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    if segments.is_empty() {
+        return Err(anyhow!("ingress route path must not be empty"));
+    }
+    let token = match (&route.token, &route.token_env) {
+        (Some(token), _) => {
+            let scope = secrets.map(|scope| SecretScope {
+                tenant: route.tenant.as_deref().unwrap_or(scope.tenant),
+                team: route.team.as_deref().or(scope.team),
+                ..scope
+            });
+            Some(
+                interpolate::interpolate_secrets(token, scope)
+                    .with_context(|| format!("ingress route {} token", route.path))?,
+            )
+        }
+        (None, Some(var)) => Some(
+            std::env::var(var)
+                .map_err(|_| anyhow!("ingress route {}: token_env {var} is not set", route.path))?,
+        ),
+        (None, None) => None,
+    };
+    let has = |segment: Segment| segments.contains(&segment);
+    if has(Segment::Token) && token.as_deref().is_none_or(str::is_empty) {
+        return Err(anyhow!(
+            "ingress route {} uses {{token}} but sets no token or token_env",
+            route.path
+        ));
+    }
+    if route.provider.is_none() && !has(Segment::Capture(Field::Provider)) {
+        return Err(anyhow!(
+            "ingress route {} needs a provider or a {{provider}} segment",
+            route.path
+        ));
+    }
+    if route.tenant.is_none() && !has(Segment::Capture(Field::Tenant)) {
+        return Err(anyhow!(
+            "ingress route {} needs a tenant or a {{tenant}} segment",
+            route.path
+        ));
+    }
+    Ok(CompiledRoute {
+        host: route.host.as_ref().map(|host| host.to_ascii_lowercase()),
+        segments,
+        domain,
+        provider: route.provider.clone(),
+        tenant: route.tenant.clone(),
+        team: route.team.clone(),
+        handler: route.handler.clone(),
+        token,
+        rewrite: route.rewrite.clone(),
+    })
+}
+
+/// `Host` header without the port against `example.com` or `*.example.com`.
+fn host_matches(expected: &str, host: &str) -> bool {
+    let host = host
+        .rsplit_once(':')
+        .filter(|(_, port)| port.chars().all(|ch| ch.is_ascii_digit()))
+        .map_or(host, |(name, _)| name)
+        .to_ascii_lowercase();
+    match expected.strip_prefix("*.") {
+        Some(suffix) => host
+            .strip_suffix(suffix)
+            .is_some_and(|prefix| prefix.ends_with('.')),
+        None => host == expected,
+    }
+}
+
+pub(crate) fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    left.len() == right.len()
+        && left
+            .iter()
+            .zip(right)
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn router(yaml: &str) -> IngressRouter {
+        let config: DemoIngressConfig = serde_yaml_bw::from_str(yaml).expect("ingress yaml");
+        IngressRouter::new(&config, None).expect("router")
+    }
+
+    #[test]
+    fn routes_token_paths_with_aliases_and_rewrites() {
+        let router = router(
+            "aliases:\n  tg: messaging-telegram\nroutes:\n  - path: /telegram/{token}\n    provider: tg\n    tenant: demo\n    token: s3cret\n    rewrite: /webhook/{rest}\n",
+        );
+        let routed = router
+            .route(None, "/telegram/s3cret/updates")
+            .expect("route should match");
+        assert_eq!(routed.provider, "messaging-telegram");
+        assert_eq!(routed.tenant, "demo");
+        assert_eq!(routed.team, "default");
+        assert_eq!(routed.path, "/webhook/updates");
+        assert_eq!(router.route(None, "/telegram/wrong"), None);
+        assert_eq!(router.provider("tg"), "messaging-telegram");
+        assert_eq!(router.provider("messaging-slack"), "messaging-slack");
+    }
+
+    #[test]
+    fn host_routes_capture_segments() {
+        let router = router(
+            "routes:\n  - path: /slack/{tenant}/{team}/events\n    host: '*.example.com'\n    provider: messaging-slack\n",
+        );
+        let routed = router
+            .route(Some("hooks.example.com:8080"), "/slack/acme/ops/events")
+            .expect("route should match");
+        assert_eq!(routed.tenant, "acme");
+        assert_eq!(routed.team, "ops");
+        assert_eq!(routed.path, "/slack/acme/ops/events");
+        assert_eq!(
+            router.route(Some("example.org"), "/slack/acme/ops/events"),
+            None
+        );
+        assert_eq!(router.route(None, "/slack/acme/ops/events"), None);
+    }
+
+    #[test]
+    fn incomplete_routes_are_rejected() {
+        for yaml in [
+            "routes:\n  - path: /hook/{token}\n    provider: p\n    tenant: t\n",
+            "routes:\n  - path: /hook\n    tenant: t\n",
+            "routes:\n  - path: /hook\n    provider: p\n",
+            "routes:\n  - path: /hook/{secret}\n    provider: p\n    tenant: t\n",
+            "routes:\n  - path: /hook\n    provider: p\n    tenant: t\n    domain: secrets\n",
+            // A secret token needs a bundle to read it from.
+            "routes:\n  - path: /hook/{token}\n    provider: p\n    tenant: t\n    token: '${secret:p/token}'\n",
+        ] {
+            let config: DemoIngressConfig = serde_yaml_bw::from_str(yaml).unwrap();
+            assert!(IngressRouter::new(&config, None).is_err(), "{yaml}");
+        }
+    }
+}
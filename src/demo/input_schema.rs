@@ -0,0 +1,287 @@
+//! Checks `demo run` input against the flow's `flows[].input_schema`.
+//!
+//! Type, `required`, `enum`/`const`, `properties`, `additionalProperties: false` and
+//! `items` are walked by hand so every bad field is reported at its own path
+//! (`$.user.id: required`). Anything the walk does not cover (`pattern`, `minimum`,
+//! `oneOf`, ...) is left to `jsonschema`, whose first error is reported when the walk
+//! finds nothing.
+
+use std::fmt;
+
+use anyhow::anyhow;
+use serde_json::{Map as JsonMap, Value as JsonValue, json};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldError {
+    /// Where in the input, e.g. `$.items[2].sku`.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Every way `input` fails `schema`; empty when it matches. Errors only when
+/// `schema` itself is not a valid JSON Schema.
+pub fn validate(schema: &JsonValue, input: &JsonValue) -> anyhow::Result<Vec<FieldError>> {
+    let validator =
+        jsonschema::validator_for(schema).map_err(|err| anyhow!("invalid input schema: {err}"))?;
+    let mut errors = Vec::new();
+    walk(schema, input, "$", &mut errors);
+    if errors.is_empty()
+        && let Err(err) = validator.validate(input)
+    {
+        errors.push(FieldError {
+            path: "$".to_string(),
+            message: err.to_string(),
+        });
+    }
+    Ok(errors)
+}
+
+/// An input with the shape `schema` describes: `const`, `default`, the first of
+/// `examples` or `enum` where the schema gives one, otherwise a placeholder for the
+/// declared type. Objects list every property, required or not.
+pub fn example(schema: &JsonValue) -> JsonValue {
+    let Some(schema) = schema.as_object() else {
+        return JsonValue::Null;
+    };
+    if let Some(value) = schema.get("const").or_else(|| schema.get("default")) {
+        return value.clone();
+    }
+    if let Some(value) = ["examples", "enum"]
+        .iter()
+        .find_map(|key| schema.get(*key)?.as_array()?.first())
+    {
+        return value.clone();
+    }
+    match declared_type(schema) {
+        Some("object") => JsonValue::Object(
+            schema
+                .get("properties")
+                .and_then(JsonValue::as_object)
+                .map(|properties| {
+                    properties
+                        .iter()
+                        .map(|(key, property)| (key.clone(), example(property)))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        ),
+        Some("array") => match schema.get("items") {
+            Some(items) if items.is_object() => json!([example(items)]),
+            _ => json!([]),
+        },
+        Some("string") => json!("string"),
+        Some("integer" | "number") => json!(0),
+        Some("boolean") => json!(false),
+        _ => JsonValue::Null,
+    }
+}
+
+/// The schema's `type`, the first non-null one of a type list, or `object` when it
+/// only lists `properties`.
+fn declared_type(schema: &JsonMap<String, JsonValue>) -> Option<&str> {
+    match schema.get("type") {
+        Some(JsonValue::String(name)) => Some(name.as_str()),
+        Some(JsonValue::Array(names)) => names
+            .iter()
+            .filter_map(JsonValue::as_str)
+            .find(|name| *name != "null"),
+        _ => schema.contains_key("properties").then_some("object"),
+    }
+}
+
+fn walk(schema: &JsonValue, value: &JsonValue, path: &str, errors: &mut Vec<FieldError>) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+    let allowed: Vec<&str> = match schema.get("type") {
+        Some(JsonValue::String(name)) => vec![name.as_str()],
+        Some(JsonValue::Array(names)) => names.iter().filter_map(JsonValue::as_str).collect(),
+        _ => Vec::new(),
+    };
+    if !allowed.is_empty() && !allowed.iter().any(|name| matches_type(value, name)) {
+        fail(
+            errors,
+            path,
+            format!(
+                "expected {}, found {}",
+                allowed.join(" or "),
+                type_name(value)
+            ),
+        );
+        return;
+    }
+    if let Some(options) = schema.get("enum").and_then(JsonValue::as_array)
+        && !options.contains(value)
+    {
+        let options: Vec<String> = options.iter().map(ToString::to_string).collect();
+        fail(
+            errors,
+            path,
+            format!("must be one of {}", options.join(", ")),
+        );
+    }
+    if let Some(expected) = schema.get("const")
+        && expected != value
+    {
+        fail(errors, path, format!("must be {expected}"));
+    }
+
+    if let Some(map) = value.as_object() {
+        let properties = schema.get("properties").and_then(JsonValue::as_object);
+        for key in schema
+            .get("required")
+            .and_then(JsonValue::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(JsonValue::as_str)
+        {
+            if !map.contains_key(key) {
+                fail(errors, format!("{path}.{key}"), "required");
+            }
+        }
+        if let Some(properties) = properties {
+            for (key, property) in properties {
+                if let Some(field) = map.get(key) {
+                    walk(property, field, &format!("{path}.{key}"), errors);
+                }
+            }
+        }
+        if schema.get("additionalProperties") == Some(&JsonValue::Bool(false)) {
+            let known: Vec<&str> = properties
+                .map(|properties| properties.keys().map(String::as_str).collect())
+                .unwrap_or_default();
+            for key in map.keys() {
+                if !known.contains(&key.as_str()) {
+                    fail(
+                        errors,
+                        format!("{path}.{key}"),
+                        format!("not allowed (expected one of: {})", known.join(", ")),
+                    );
+                }
+            }
+        }
+    }
+    if let (Some(items), Some(item_schema)) = (value.as_array(), schema.get("items"))
+        && item_schema.is_object()
+    {
+        for (idx, item) in items.iter().enumerate() {
+            walk(item_schema, item, &format!("{path}[{idx}]"), errors);
+        }
+    }
+}
+
+fn fail(errors: &mut Vec<FieldError>, path: impl Into<String>, message: impl Into<String>) {
+    errors.push(FieldError {
+        path: path.into(),
+        message: message.into(),
+    });
+}
+
+fn matches_type(value: &JsonValue, expected: &str) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "boolean",
+        JsonValue::Number(number) if number.is_f64() => "number",
+        JsonValue::Number(_) => "integer",
+        JsonValue::String(_) => "string",
+        JsonValue::Array(_) => "array",
+        JsonValue::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> JsonValue {
+        json!({
+            "type": "object",
+            "required": ["chat_id", "text"],
+            "additionalProperties": false,
+            "properties": {
+                "chat_id": {"type": "integer"},
+                "text": {"type": "string", "minLength": 1},
+                "mode": {"enum": ["plain", "markdown"], "default": "plain"},
+                "buttons": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["id"],
+                        "properties": {"id": {"type": "string"}}
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn reports_every_bad_field_at_its_path() {
+        let input = json!({
+            "chat_id": "42",
+            "mode": "html",
+            "buttons": [{"id": "ok"}, {"label": "Cancel"}],
+            "colour": "blue"
+        });
+        let errors: Vec<String> = validate(&schema(), &input)
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                "$.text: required",
+                "$.buttons[1].id: required",
+                "$.chat_id: expected integer, found string",
+                "$.mode: must be one of \"plain\", \"markdown\"",
+                "$.colour: not allowed (expected one of: buttons, chat_id, mode, text)",
+            ]
+        );
+    }
+
+    #[test]
+    fn falls_back_to_jsonschema_for_other_keywords() {
+        let errors = validate(&schema(), &json!({"chat_id": 42, "text": ""})).unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, "$");
+        assert!(
+            validate(&schema(), &json!({"chat_id": 42, "text": "hi"}))
+                .unwrap()
+                .is_empty()
+        );
+        assert!(validate(&json!({"type": 12}), &json!({})).is_err());
+    }
+
+    #[test]
+    fn example_follows_the_schema_shape() {
+        assert_eq!(
+            example(&schema()),
+            json!({
+                "chat_id": 0,
+                "text": "string",
+                "mode": "plain",
+                "buttons": [{"id": "string"}]
+            })
+        );
+        assert!(validate(&schema(), &example(&schema())).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,391 @@
+//! Long-running operator actions queued with `demo jobs submit` instead of run inline.
+//!
+//! Each job is `state/jobs/<id>.json`, with its output in `<id>.log`. A worker runs
+//! queued jobs one at a time, oldest first, as a child `greentic-operator` process
+//! with the bundle as working directory: the [`JobWorker`] inside `demo start`, or
+//! `demo jobs run` in the foreground. A worker claims a job by creating `<id>.pid`
+//! with its own pid, so two workers never run the same job; a running job whose
+//! worker is gone is marked failed.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, anyhow};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::operator_log;
+use crate::runtime_state;
+use crate::services::{self, ProcessStatus};
+
+/// How often an idle worker looks for new jobs.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// `demo build`
+    Build,
+    /// `demo send`
+    Send,
+    /// `registry mirror`
+    Pull,
+    /// `wizard`
+    Wizard,
+}
+
+impl JobKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JobKind::Build => "build",
+            JobKind::Send => "send",
+            JobKind::Pull => "pull",
+            JobKind::Wizard => "wizard",
+        }
+    }
+
+    /// The operator subcommand the job's arguments are passed to.
+    pub fn command(self) -> &'static [&'static str] {
+        match self {
+            JobKind::Build => &["demo", "build"],
+            JobKind::Send => &["demo", "send"],
+            JobKind::Pull => &["registry", "mirror"],
+            JobKind::Wizard => &["wizard"],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Succeeded => "succeeded",
+            JobStatus::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Job {
+    pub id: String,
+    pub kind: JobKind,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub status: JobStatus,
+    pub submitted_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Job {
+    /// The command line the job runs, for display.
+    pub fn command_line(&self) -> String {
+        let mut parts = vec!["greentic-operator".to_string()];
+        parts.extend(self.kind.command().iter().map(|part| part.to_string()));
+        parts.extend(self.args.iter().cloned());
+        parts.join(" ")
+    }
+}
+
+/// `state/jobs`
+pub fn jobs_dir(bundle: &Path) -> PathBuf {
+    bundle.join("state").join("jobs")
+}
+
+pub fn log_path(bundle: &Path, id: &str) -> PathBuf {
+    jobs_dir(bundle).join(format!("{id}.log"))
+}
+
+fn job_path(bundle: &Path, id: &str) -> PathBuf {
+    jobs_dir(bundle).join(format!("{id}.json"))
+}
+
+fn pid_path(bundle: &Path, id: &str) -> PathBuf {
+    jobs_dir(bundle).join(format!("{id}.pid"))
+}
+
+fn save(bundle: &Path, job: &Job) -> anyhow::Result<()> {
+    runtime_state::write_json(&job_path(bundle, &job.id), job)
+}
+
+pub fn submit(bundle: &Path, kind: JobKind, args: Vec<String>) -> anyhow::Result<Job> {
+    let now = Utc::now();
+    let job = Job {
+        // Time first, so ids sort in submission order.
+        id: format!(
+            "{}-{}",
+            now.format("%Y%m%d%H%M%S"),
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        ),
+        kind,
+        args,
+        status: JobStatus::Queued,
+        submitted_at: now,
+        started_at: None,
+        finished_at: None,
+        exit_code: None,
+        error: None,
+    };
+    save(bundle, &job)?;
+    Ok(job)
+}
+
+pub fn load(bundle: &Path, id: &str) -> anyhow::Result<Job> {
+    let path = job_path(bundle, id);
+    runtime_state::read_json::<Job>(&path)
+        .with_context(|| format!("read job {}", path.display()))?
+        .ok_or_else(|| anyhow!("no job {id} in {}", jobs_dir(bundle).display()))
+}
+
+/// All jobs, oldest first. Running jobs whose worker is gone are reported failed.
+pub fn list(bundle: &Path) -> anyhow::Result<Vec<Job>> {
+    let dir = jobs_dir(bundle);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).with_context(|| format!("read {}", dir.display())),
+    };
+    let mut jobs = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        if let Some(job) = runtime_state::read_json::<Job>(&path)
+            .with_context(|| format!("read job {}", path.display()))?
+        {
+            jobs.push(recover(bundle, job)?);
+        }
+    }
+    jobs.sort_by(|a, b| (a.submitted_at, &a.id).cmp(&(b.submitted_at, &b.id)));
+    Ok(jobs)
+}
+
+/// Marks a running job failed when the worker that claimed it no longer runs.
+fn recover(bundle: &Path, job: Job) -> anyhow::Result<Job> {
+    if job.status != JobStatus::Running
+        || services::process_status(&pid_path(bundle, &job.id))? == ProcessStatus::Running
+    {
+        return Ok(job);
+    }
+    // The worker may have finished the job since it was read.
+    let mut job = load(bundle, &job.id)?;
+    if job.status != JobStatus::Running {
+        return Ok(job);
+    }
+    job.status = JobStatus::Failed;
+    job.finished_at = Some(Utc::now());
+    job.error = Some("the worker running this job stopped".to_string());
+    save(bundle, &job)?;
+    let _ = fs::remove_file(pid_path(bundle, &job.id));
+    Ok(job)
+}
+
+/// Creates `<id>.pid` for this process; false when a live worker holds it. The
+/// claim of a worker that died is taken over.
+fn claim(bundle: &Path, id: &str) -> anyhow::Result<bool> {
+    let path = pid_path(bundle, id);
+    for _ in 0..2 {
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                write!(file, "{}", std::process::id())?;
+                return Ok(true);
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                if services::process_status(&path)? == ProcessStatus::Running {
+                    return Ok(false);
+                }
+                let _ = fs::remove_file(&path);
+            }
+            Err(err) => return Err(err).with_context(|| format!("create {}", path.display())),
+        }
+    }
+    Ok(false)
+}
+
+/// Runs the oldest queued job with the operator binary `exe` and returns it
+/// finished; `None` when nothing is queued.
+pub fn run_next(bundle: &Path, exe: &Path) -> anyhow::Result<Option<Job>> {
+    for job in list(bundle)? {
+        if job.status != JobStatus::Queued || !claim(bundle, &job.id)? {
+            continue;
+        }
+        // Re-read: the job may have been taken and finished since it was listed.
+        let mut job = load(bundle, &job.id)?;
+        if job.status != JobStatus::Queued {
+            let _ = fs::remove_file(pid_path(bundle, &job.id));
+            continue;
+        }
+        job.status = JobStatus::Running;
+        job.started_at = Some(Utc::now());
+        save(bundle, &job)?;
+        let outcome = execute(bundle, exe, &job);
+        job.finished_at = Some(Utc::now());
+        match outcome {
+            Ok(0) => {
+                job.status = JobStatus::Succeeded;
+                job.exit_code = Some(0);
+            }
+            Ok(code) => {
+                job.status = JobStatus::Failed;
+                job.exit_code = Some(code);
+                job.error = Some(format!(
+                    "exited with {code}; see {}",
+                    log_path(bundle, &job.id).display()
+                ));
+            }
+            Err(err) => {
+                job.status = JobStatus::Failed;
+                job.error = Some(format!("{err:#}"));
+            }
+        }
+        save(bundle, &job)?;
+        let _ = fs::remove_file(pid_path(bundle, &job.id));
+        return Ok(Some(job));
+    }
+    Ok(None)
+}
+
+fn execute(bundle: &Path, exe: &Path, job: &Job) -> anyhow::Result<i32> {
+    let log = log_path(bundle, &job.id);
+    let stdout = File::create(&log).with_context(|| format!("create {}", log.display()))?;
+    let stderr = stdout.try_clone()?;
+    let status = Command::new(exe)
+        .args(job.kind.command())
+        .args(&job.args)
+        .current_dir(bundle)
+        .stdin(Stdio::null())
+        .stdout(stdout)
+        .stderr(stderr)
+        .status()
+        .with_context(|| format!("run {}", job.command_line()))?;
+    // A job killed by a signal has no code.
+    Ok(status.code().unwrap_or(-1))
+}
+
+/// Runs queued jobs of one bundle in the background of `demo start`.
+pub struct JobWorker {
+    shutdown: Option<mpsc::Sender<()>>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl JobWorker {
+    pub fn start(bundle: PathBuf) -> anyhow::Result<Self> {
+        let exe = std::env::current_exe().context("locate the greentic-operator binary")?;
+        let (tx, rx) = mpsc::channel::<()>();
+        let handle = thread::Builder::new()
+            .name("demo-job-worker".to_string())
+            .spawn(move || {
+                loop {
+                    match run_next(&bundle, &exe) {
+                        Ok(Some(job)) => {
+                            operator_log::info(
+                                module_path!(),
+                                format!(
+                                    "job {} {}: {}",
+                                    job.id,
+                                    job.kind.as_str(),
+                                    job.status.as_str()
+                                ),
+                            );
+                            // Look for the next job right away, unless asked to stop.
+                            if rx.try_recv().is_ok() {
+                                return;
+                            }
+                            continue;
+                        }
+                        Ok(None) => {}
+                        Err(err) => {
+                            operator_log::warn(module_path!(), format!("job worker: {err:#}"))
+                        }
+                    }
+                    if !matches!(
+                        rx.recv_timeout(POLL_INTERVAL),
+                        Err(mpsc::RecvTimeoutError::Timeout)
+                    ) {
+                        return;
+                    }
+                }
+            })
+            .context("spawn job worker thread")?;
+        Ok(Self {
+            shutdown: Some(tx),
+            handle: Some(handle),
+        })
+    }
+
+    /// Stops after the job in progress, if any, finishes.
+    pub fn stop(mut self) -> anyhow::Result<()> {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+        if let Some(handle) = self.handle.take() {
+            handle
+                .join()
+                .map_err(|err| anyhow!("job worker panicked: {err:?}"))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queued_jobs_run_once_in_submission_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path();
+        let first = submit(bundle, JobKind::Build, vec!["--out".into(), "dist".into()]).unwrap();
+        let second = submit(bundle, JobKind::Pull, Vec::new()).unwrap();
+        assert_eq!(
+            first.command_line(),
+            "greentic-operator demo build --out dist"
+        );
+
+        // A claim held by a live worker keeps the job from being taken twice.
+        assert!(claim(bundle, &first.id).unwrap());
+        assert!(!claim(bundle, &first.id).unwrap());
+        fs::remove_file(pid_path(bundle, &first.id)).unwrap();
+
+        let exe = if cfg!(windows) { "cmd" } else { "true" };
+        let ran = run_next(bundle, Path::new(exe)).unwrap().unwrap();
+        assert_eq!(ran.id, first.id);
+        assert_eq!(load(bundle, &first.id).unwrap().status, ran.status);
+        assert!(ran.finished_at.is_some());
+        assert!(!pid_path(bundle, &first.id).exists());
+        let ran = run_next(bundle, Path::new(exe)).unwrap().unwrap();
+        assert_eq!(ran.id, second.id);
+        assert!(run_next(bundle, Path::new(exe)).unwrap().is_none());
+
+        // A running job whose worker is gone is reported failed.
+        let mut orphan = submit(bundle, JobKind::Send, Vec::new()).unwrap();
+        orphan.status = JobStatus::Running;
+        save(bundle, &orphan).unwrap();
+        let listed = list(bundle).unwrap();
+        let orphan = listed.iter().find(|job| job.id == orphan.id).unwrap();
+        assert_eq!(orphan.status, JobStatus::Failed);
+    }
+}
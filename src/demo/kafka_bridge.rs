@@ -0,0 +1,465 @@
+//! Kafka bridge for the events domain.
+//!
+//! Outbound, events seen on the tenant's bus (`greentic.<tenant>.<team>.ingress.events.>`)
+//! are produced to `<topic_prefix>.<tenant>.<provider>`. Inbound, messages consumed
+//! from `<topic_prefix>.<tenant>.inbound` are routed into the default events flow.
+//! Both directions shell out to `kcat` so no Kafka client library is linked.
+
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Context;
+use chrono::Utc;
+use serde_json::Value as JsonValue;
+
+use crate::bus;
+use crate::config::{DemoKafkaConfig, KafkaBridgeDirection};
+use crate::demo::event_router::route_events_to_default_flow;
+use crate::demo::ingress_types::{EventEnvelopeV1, EventScopeV1, EventSourceV1};
+use crate::demo::runner_host::OperatorContext;
+use crate::operator_log;
+
+/// Durable bus consumer of the outbound direction.
+const OUTBOUND_CONSUMER: &str = "kafka-bridge";
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(2);
+
+pub struct KafkaBridgeConfig {
+    pub bundle: PathBuf,
+    pub tenant: String,
+    pub team: String,
+    pub binary: PathBuf,
+    pub kafka: DemoKafkaConfig,
+}
+
+pub struct KafkaBridge {
+    shutdown: Arc<AtomicBool>,
+    consumer: Option<Child>,
+    handles: Vec<thread::JoinHandle<()>>,
+}
+
+impl KafkaBridge {
+    pub fn start(config: KafkaBridgeConfig) -> anyhow::Result<Self> {
+        if config.kafka.brokers.is_empty() {
+            return Err(anyhow::anyhow!(
+                "services.events.kafka.brokers must list at least one broker"
+            ));
+        }
+        let config = Arc::new(config);
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let mut bridge = Self {
+            shutdown: shutdown.clone(),
+            consumer: None,
+            handles: Vec::new(),
+        };
+        let direction = config.kafka.direction;
+        if matches!(
+            direction,
+            KafkaBridgeDirection::Out | KafkaBridgeDirection::Both
+        ) {
+            let subscription = outbound_subscription(&config)?;
+            let config = config.clone();
+            let shutdown = shutdown.clone();
+            bridge.handles.push(
+                thread::Builder::new()
+                    .name("kafka-bridge-out".to_string())
+                    .spawn(move || run_outbound(&config, subscription, &shutdown))
+                    .context("spawn kafka outbound thread")?,
+            );
+        }
+        if matches!(
+            direction,
+            KafkaBridgeDirection::In | KafkaBridgeDirection::Both
+        ) {
+            let mut child = Command::new(&config.binary)
+                .args(consumer_args(&config.kafka, &config.tenant)?)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .with_context(|| format!("failed to spawn {}", config.binary.display()))?;
+            let stdout = child
+                .stdout
+                .take()
+                .ok_or_else(|| anyhow::anyhow!("kcat consumer has no stdout"))?;
+            bridge.consumer = Some(child);
+            let config = config.clone();
+            bridge.handles.push(
+                thread::Builder::new()
+                    .name("kafka-bridge-in".to_string())
+                    .spawn(move || run_inbound(&config, BufReader::new(stdout)))
+                    .context("spawn kafka inbound thread")?,
+            );
+        }
+        Ok(bridge)
+    }
+
+    pub fn stop(mut self) -> anyhow::Result<()> {
+        self.shutdown.store(true, Ordering::SeqCst);
+        if let Some(mut child) = self.consumer.take() {
+            // Killing kcat closes its stdout, which ends the inbound reader thread.
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        for handle in self.handles.drain(..) {
+            handle
+                .join()
+                .map_err(|err| anyhow::anyhow!("kafka bridge panicked: {err:?}"))?;
+        }
+        Ok(())
+    }
+}
+
+fn outbound_subscription(config: &KafkaBridgeConfig) -> anyhow::Result<bus::Subscription> {
+    bus::for_scope(&config.bundle, &config.tenant, &config.team).consume(
+        &format!(
+            "{}.ingress.events.>",
+            bus::subject_prefix(&config.tenant, &config.team)
+        ),
+        OUTBOUND_CONSUMER,
+    )
+}
+
+fn run_outbound(
+    config: &KafkaBridgeConfig,
+    mut subscription: bus::Subscription,
+    shutdown: &AtomicBool,
+) {
+    let mut producers: BTreeMap<String, Producer> = BTreeMap::new();
+    while !shutdown.load(Ordering::SeqCst) {
+        let message = match subscription.recv_timeout(Duration::from_millis(500)) {
+            Ok(Some(message)) => message,
+            Ok(None) => continue,
+            Err(err) => {
+                operator_log::warn(
+                    module_path!(),
+                    format!("kafka bridge lost the bus ({err}); resubscribing"),
+                );
+                match resubscribe(config, shutdown) {
+                    Some(next) => subscription = next,
+                    None => break,
+                }
+                continue;
+            }
+        };
+        let Ok(event) = serde_json::from_value::<EventEnvelopeV1>(message.payload.clone()) else {
+            ack(&subscription, &message);
+            continue;
+        };
+        let topic = outbound_topic(&config.kafka, &config.tenant, &event.source.provider);
+        match produce(config, &mut producers, &topic, &message.payload) {
+            Ok(()) => ack(&subscription, &message),
+            Err(err) => {
+                // Drop the broken producer; the next event for this topic respawns it.
+                // The event is not acked, so the bus delivers it again.
+                producers.remove(&topic);
+                operator_log::warn(
+                    module_path!(),
+                    format!("kafka produce failed topic={topic} err={err}"),
+                );
+            }
+        }
+    }
+    for (_, producer) in producers {
+        producer.close();
+    }
+}
+
+/// A running `kcat -P`. Dropping it kills and reaps the process, so a producer
+/// thrown away after a failed write does not linger as a zombie.
+struct Producer {
+    child: Child,
+    stdin: Option<ChildStdin>,
+}
+
+impl Producer {
+    fn new(mut child: Child) -> anyhow::Result<Self> {
+        let stdin = child.stdin.take();
+        let producer = Self { child, stdin };
+        if producer.stdin.is_none() {
+            return Err(anyhow::anyhow!("kcat producer has no stdin"));
+        }
+        Ok(producer)
+    }
+
+    /// Closes stdin and waits for kcat to flush what it was given.
+    fn close(mut self) {
+        self.stdin.take();
+        let _ = self.child.wait();
+    }
+}
+
+impl Drop for Producer {
+    fn drop(&mut self) {
+        self.stdin.take();
+        if !matches!(self.child.try_wait(), Ok(Some(_))) {
+            let _ = self.child.kill();
+        }
+        let _ = self.child.wait();
+    }
+}
+
+/// Subscribes again, retrying every few seconds; `None` once shutting down.
+fn resubscribe(config: &KafkaBridgeConfig, shutdown: &AtomicBool) -> Option<bus::Subscription> {
+    while !shutdown.load(Ordering::SeqCst) {
+        thread::sleep(RESUBSCRIBE_DELAY);
+        match outbound_subscription(config) {
+            Ok(subscription) => return Some(subscription),
+            Err(err) => operator_log::warn(
+                module_path!(),
+                format!("kafka bridge resubscribe failed: {err}"),
+            ),
+        }
+    }
+    None
+}
+
+fn ack(subscription: &bus::Subscription, message: &bus::BusMessage) {
+    if let Err(err) = subscription.ack(message) {
+        operator_log::warn(
+            module_path!(),
+            format!(
+                "kafka bridge ack failed subject={} err={err}",
+                message.subject
+            ),
+        );
+    }
+}
+
+fn produce(
+    config: &KafkaBridgeConfig,
+    producers: &mut BTreeMap<String, Producer>,
+    topic: &str,
+    payload: &JsonValue,
+) -> anyhow::Result<()> {
+    if !producers.contains_key(topic) {
+        let child = Command::new(&config.binary)
+            .args(producer_args(&config.kafka, topic)?)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("failed to spawn {}", config.binary.display()))?;
+        producers.insert(topic.to_string(), Producer::new(child)?);
+    }
+    let stdin = producers
+        .get_mut(topic)
+        .and_then(|producer| producer.stdin.as_mut())
+        .ok_or_else(|| anyhow::anyhow!("producer for {topic} missing"))?;
+    // kcat -P splits messages on newlines; compact JSON never contains one.
+    writeln!(stdin, "{}", serde_json::to_string(payload)?)?;
+    stdin.flush()?;
+    Ok(())
+}
+
+fn run_inbound(config: &KafkaBridgeConfig, reader: impl BufRead) {
+    let ctx = OperatorContext {
+        tenant: config.tenant.clone(),
+        team: Some(config.team.clone()),
+        correlation_id: None,
+    };
+    for line in reader.lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event = inbound_event(&line, &config.tenant, &config.team);
+        if let Err(err) = route_events_to_default_flow(&config.bundle, &ctx, &[event]) {
+            operator_log::warn(
+                module_path!(),
+                format!("kafka inbound event routing failed: {err}"),
+            );
+        }
+    }
+}
+
+/// Kafka messages that already are event envelopes pass through; anything else is
+/// wrapped as a `kafka.message` event.
+fn inbound_event(line: &str, tenant: &str, team: &str) -> EventEnvelopeV1 {
+    let payload = serde_json::from_str::<JsonValue>(line)
+        .unwrap_or_else(|_| JsonValue::String(line.to_string()));
+    if let Ok(event) = serde_json::from_value::<EventEnvelopeV1>(payload.clone()) {
+        return event;
+    }
+    EventEnvelopeV1 {
+        event_id: uuid::Uuid::new_v4().to_string(),
+        event_type: "kafka.message".to_string(),
+        occurred_at: Utc::now().to_rfc3339(),
+        source: EventSourceV1 {
+            domain: "events".to_string(),
+            provider: "kafka".to_string(),
+            handler_id: None,
+        },
+        scope: EventScopeV1 {
+            tenant: tenant.to_string(),
+            team: Some(team.to_string()),
+        },
+        correlation_id: None,
+        payload,
+        http: None,
+        raw: None,
+    }
+}
+
+pub fn outbound_topic(kafka: &DemoKafkaConfig, tenant: &str, provider: &str) -> String {
+    format!(
+        "{}.{}.{}",
+        kafka.topic_prefix,
+        bus::sanitize_token(tenant),
+        bus::sanitize_token(provider)
+    )
+}
+
+pub fn inbound_topic(kafka: &DemoKafkaConfig, tenant: &str) -> String {
+    format!(
+        "{}.{}.inbound",
+        kafka.topic_prefix,
+        bus::sanitize_token(tenant)
+    )
+}
+
+fn producer_args(kafka: &DemoKafkaConfig, topic: &str) -> anyhow::Result<Vec<String>> {
+    let mut args = vec![
+        "-P".to_string(),
+        "-b".to_string(),
+        kafka.brokers.join(","),
+        "-t".to_string(),
+        topic.to_string(),
+    ];
+    args.extend(auth_args(kafka)?);
+    Ok(args)
+}
+
+fn consumer_args(kafka: &DemoKafkaConfig, tenant: &str) -> anyhow::Result<Vec<String>> {
+    let mut args = vec![
+        "-b".to_string(),
+        kafka.brokers.join(","),
+        "-G".to_string(),
+        kafka.consumer_group.clone(),
+        "-u".to_string(),
+        "-q".to_string(),
+        "-f".to_string(),
+        "%s\n".to_string(),
+    ];
+    args.extend(auth_args(kafka)?);
+    args.push(inbound_topic(kafka, tenant));
+    Ok(args)
+}
+
+fn auth_args(kafka: &DemoKafkaConfig) -> anyhow::Result<Vec<String>> {
+    let auth = &kafka.auth;
+    let mut properties = Vec::new();
+    if let Some(protocol) = &auth.security_protocol {
+        properties.push(format!("security.protocol={protocol}"));
+    }
+    if let Some(mechanism) = &auth.sasl_mechanism {
+        properties.push(format!("sasl.mechanisms={mechanism}"));
+    }
+    if let Some(username) = &auth.username {
+        properties.push(format!("sasl.username={username}"));
+    }
+    if let Some(var) = &auth.password_env {
+        let password = std::env::var(var)
+            .with_context(|| format!("kafka password env var {var} is not set"))?;
+        properties.push(format!("sasl.password={password}"));
+    }
+    Ok(properties
+        .into_iter()
+        .flat_map(|property| ["-X".to_string(), property])
+        .collect())
+}
+
+/// Resolves the `kcat` binary relative to the bundle, like other demo helpers.
+pub fn resolve_binary(bundle: &Path, kafka: &DemoKafkaConfig) -> anyhow::Result<PathBuf> {
+    let explicit = PathBuf::from(&kafka.binary);
+    let explicit_path = (explicit.components().count() > 1).then_some(explicit);
+    crate::bin_resolver::resolve_binary(
+        &kafka.binary,
+        &crate::bin_resolver::ResolveCtx {
+            config_dir: bundle.to_path_buf(),
+            explicit_path,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DemoKafkaAuthConfig;
+
+    fn kafka() -> DemoKafkaConfig {
+        DemoKafkaConfig {
+            enabled: true,
+            brokers: vec!["b1:9092".to_string(), "b2:9092".to_string()],
+            ..DemoKafkaConfig::default()
+        }
+    }
+
+    #[test]
+    fn topics_follow_prefix_tenant_provider() {
+        let kafka = kafka();
+        assert_eq!(
+            outbound_topic(&kafka, "acme", "timer"),
+            "greentic.events.acme.timer"
+        );
+        assert_eq!(
+            inbound_topic(&kafka, "acme"),
+            "greentic.events.acme.inbound"
+        );
+    }
+
+    #[test]
+    fn producer_args_include_sasl_properties() {
+        let mut kafka = kafka();
+        kafka.auth = DemoKafkaAuthConfig {
+            security_protocol: Some("SASL_SSL".to_string()),
+            sasl_mechanism: Some("PLAIN".to_string()),
+            username: Some("demo".to_string()),
+            password_env: None,
+        };
+        let args = producer_args(&kafka, "t").unwrap();
+        assert_eq!(&args[..5], ["-P", "-b", "b1:9092,b2:9092", "-t", "t"]);
+        assert!(args.contains(&"security.protocol=SASL_SSL".to_string()));
+        assert!(args.contains(&"sasl.username=demo".to_string()));
+
+        kafka.auth.password_env = Some("GREENTIC_TEST_KAFKA_PASSWORD_UNSET".to_string());
+        assert!(producer_args(&kafka, "t").is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn dropped_producers_are_reaped() {
+        let child = Command::new("cat")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        drop(Producer::new(child).unwrap());
+        // A zombie would keep its /proc entry until it is waited for.
+        assert!(!Path::new(&format!("/proc/{pid}")).exists());
+    }
+
+    #[test]
+    fn inbound_lines_are_wrapped_unless_already_events() {
+        let wrapped = inbound_event(r#"{"order": 7}"#, "acme", "default");
+        assert_eq!(wrapped.event_type, "kafka.message");
+        assert_eq!(wrapped.payload["order"], 7);
+
+        let raw = inbound_event("plain text", "acme", "default");
+        assert_eq!(raw.payload, JsonValue::String("plain text".to_string()));
+
+        let passthrough = inbound_event(
+            &serde_json::to_string(&wrapped).unwrap(),
+            "other",
+            "default",
+        );
+        assert_eq!(passthrough.event_id, wrapped.event_id);
+    }
+}
@@ -0,0 +1,293 @@
+//! Per-invocation resource limits for the runner host.
+//!
+//! Limits come from `limits` in greentic.demo.yaml (see [`DemoLimitsConfig`]) and
+//! resolve per `tenant.team` like quotas. Every execution path gets the wall-clock
+//! timeout and the output cap: a spawned runner is killed, an in-process flow is
+//! abandoned on its thread, and a component op is cancelled at its next await. CPU
+//! and memory limits need a process of their own, so they only reach runners
+//! spawned from `--runner-binary`. A limit that stops an invocation becomes a
+//! failed [`FlowOutcome`] with a `limit_exceeded` error instead of a hang.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::{Value as JsonValue, json};
+
+use crate::config::{self, DemoLimitsConfig, InvocationLimits};
+use crate::demo::runner_host::{FlowOutcome, RunnerExecutionMode};
+use crate::operator_log;
+use crate::runner_integration::{LimitHit, ProcessLimits};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitKind {
+    TimeoutSecs,
+    OutputBytes,
+    CpuSecs,
+    MemoryMb,
+}
+
+impl LimitKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LimitKind::TimeoutSecs => "timeout_secs",
+            LimitKind::OutputBytes => "output_bytes",
+            LimitKind::CpuSecs => "cpu_secs",
+            LimitKind::MemoryMb => "memory_mb",
+        }
+    }
+}
+
+/// Why an invocation was stopped; serialized as the flow output of the failure.
+#[derive(Clone, Debug, Serialize)]
+pub struct LimitExceeded {
+    pub tenant: String,
+    pub team: String,
+    pub op: String,
+    pub limit_kind: LimitKind,
+    pub limit: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub observed: Option<u64>,
+}
+
+impl LimitExceeded {
+    pub fn to_json(&self) -> JsonValue {
+        json!({
+            "code": "limit_exceeded",
+            "error": {
+                "type": "LimitExceeded",
+                "tenant": self.tenant,
+                "team": self.team,
+                "op": self.op,
+                "limit_kind": self.limit_kind,
+                "limit": self.limit,
+                "observed": self.observed,
+            }
+        })
+    }
+
+    pub fn outcome(&self, mode: RunnerExecutionMode) -> FlowOutcome {
+        operator_log::warn(module_path!(), self.to_string());
+        FlowOutcome {
+            success: false,
+            output: Some(self.to_json()),
+            raw: None,
+            error: Some(self.to_string()),
+            mode,
+        }
+    }
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} for {}.{} stopped by limit {}={}",
+            self.op,
+            self.tenant,
+            self.team,
+            self.limit_kind.as_str(),
+            self.limit
+        )?;
+        if let Some(observed) = self.observed {
+            write!(f, " (observed {observed})")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
+
+/// The limits of one invocation, resolved for its tenant and team.
+#[derive(Clone, Debug)]
+pub struct InvocationScope {
+    pub tenant: String,
+    pub team: String,
+    pub op: String,
+    pub limits: InvocationLimits,
+    cgroup_parent: Option<PathBuf>,
+}
+
+impl InvocationScope {
+    pub fn timeout(&self) -> Option<Duration> {
+        self.limits.timeout_secs.map(Duration::from_secs)
+    }
+
+    /// What a spawned runner is held to.
+    pub fn process_limits(&self) -> ProcessLimits {
+        ProcessLimits {
+            timeout: self.timeout(),
+            max_output_bytes: self.limits.max_output_bytes,
+            max_cpu_secs: self.limits.max_cpu_secs,
+            max_memory_bytes: self.limits.max_memory_mb.map(|mb| mb * 1024 * 1024),
+            cgroup_parent: self.cgroup_parent.clone(),
+        }
+    }
+
+    fn exceeded(&self, limit_kind: LimitKind, observed: Option<u64>) -> Option<LimitExceeded> {
+        let limit = match limit_kind {
+            LimitKind::TimeoutSecs => self.limits.timeout_secs,
+            LimitKind::OutputBytes => self.limits.max_output_bytes,
+            LimitKind::CpuSecs => self.limits.max_cpu_secs,
+            LimitKind::MemoryMb => self.limits.max_memory_mb,
+        }?;
+        Some(LimitExceeded {
+            tenant: self.tenant.clone(),
+            team: self.team.clone(),
+            op: self.op.clone(),
+            limit_kind,
+            limit,
+            observed,
+        })
+    }
+
+    pub fn timed_out(&self) -> LimitExceeded {
+        self.exceeded(LimitKind::TimeoutSecs, None)
+            .expect("timed out without a timeout")
+    }
+
+    /// The limit a spawned runner was stopped by.
+    pub fn process_hit(&self, hit: LimitHit) -> Option<LimitExceeded> {
+        let kind = match hit {
+            LimitHit::Timeout => LimitKind::TimeoutSecs,
+            LimitHit::OutputBytes => LimitKind::OutputBytes,
+            LimitHit::CpuSecs => LimitKind::CpuSecs,
+            LimitHit::MemoryBytes => LimitKind::MemoryMb,
+        };
+        self.exceeded(kind, None)
+    }
+
+    /// Fails an outcome whose output and raw stdout together pass the output cap.
+    pub fn check_output(&self, outcome: &FlowOutcome) -> Option<LimitExceeded> {
+        let max = self.limits.max_output_bytes?;
+        let output = outcome
+            .output
+            .as_ref()
+            .and_then(|value| serde_json::to_vec(value).ok())
+            .map_or(0, |bytes| bytes.len());
+        let size = (output + outcome.raw.as_ref().map_or(0, String::len)) as u64;
+        (size > max)
+            .then(|| self.exceeded(LimitKind::OutputBytes, Some(size)))
+            .flatten()
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct InvocationLimiter {
+    config: Arc<DemoLimitsConfig>,
+}
+
+impl InvocationLimiter {
+    pub fn new(config: DemoLimitsConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+        }
+    }
+
+    /// Reads `limits` from the bundle's greentic.demo.yaml (unlimited when absent).
+    pub fn from_bundle(bundle_root: &Path) -> Self {
+        let config_path = bundle_root.join("greentic.demo.yaml");
+        let config = if config_path.exists() {
+            config::load_demo_config(&config_path)
+                .map(|config| config.limits)
+                .unwrap_or_default()
+        } else {
+            DemoLimitsConfig::default()
+        };
+        Self::new(config)
+    }
+
+    pub fn scope(&self, tenant: &str, team: Option<&str>, op: &str) -> InvocationScope {
+        InvocationScope {
+            tenant: tenant.to_string(),
+            team: team.unwrap_or("default").to_string(),
+            op: op.to_string(),
+            limits: self.config.limits_for(tenant, team),
+            cgroup_parent: self.config.cgroup_parent.clone(),
+        }
+    }
+}
+
+/// Runs `f` on its own thread and stops waiting after `timeout`. The thread cannot
+/// be cancelled; a timed-out call keeps running in the background until it returns.
+pub fn run_with_timeout<T, F>(timeout: Option<Duration>, f: F) -> Option<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let Some(timeout) = timeout else {
+        return Some(f());
+    };
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = tx.send(f());
+    });
+    rx.recv_timeout(timeout).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TenantLimitsConfig;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn resolves_limits_and_reports_what_stopped_a_run() {
+        let limiter = InvocationLimiter::new(DemoLimitsConfig {
+            default: InvocationLimits {
+                timeout_secs: Some(30),
+                max_output_bytes: Some(16),
+                ..InvocationLimits::default()
+            },
+            tenants: BTreeMap::from([(
+                "acme".to_string(),
+                TenantLimitsConfig {
+                    limits: InvocationLimits {
+                        timeout_secs: Some(5),
+                        ..InvocationLimits::default()
+                    },
+                    teams: BTreeMap::new(),
+                },
+            )]),
+            cgroup_parent: None,
+        });
+        let scope = limiter.scope("acme", None, "send_payload");
+        assert_eq!(scope.timeout(), Some(Duration::from_secs(5)));
+        assert_eq!(scope.process_limits().max_output_bytes, Some(16));
+        assert_eq!(
+            scope.process_hit(LimitHit::CpuSecs).map(|err| err.limit),
+            None
+        );
+
+        let outcome = FlowOutcome {
+            success: true,
+            output: Some(json!({"text": "a reply longer than sixteen bytes"})),
+            raw: None,
+            error: None,
+            mode: RunnerExecutionMode::Exec,
+        };
+        let exceeded = scope.check_output(&outcome).unwrap();
+        assert_eq!(exceeded.limit_kind, LimitKind::OutputBytes);
+        let failed = exceeded.outcome(RunnerExecutionMode::Exec);
+        assert!(!failed.success);
+        assert_eq!(failed.output.unwrap()["code"], "limit_exceeded");
+        assert_eq!(
+            scope.timed_out().to_string(),
+            "send_payload for acme.default stopped by limit timeout_secs=5"
+        );
+
+        assert_eq!(run_with_timeout(None, || 7), Some(7));
+        assert_eq!(
+            run_with_timeout(Some(Duration::from_millis(20)), || {
+                thread::sleep(Duration::from_secs(2));
+                7
+            }),
+            None
+        );
+    }
+}
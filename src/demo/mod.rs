@@ -1,23 +1,57 @@
+pub mod bench;
 mod build;
+pub mod capability_recording;
 pub mod card;
+pub mod circuit_breaker;
 pub mod commands;
+pub mod control_api;
+pub mod conversation_state;
+pub mod debugger;
+pub mod diff;
 mod doctor;
+pub mod event_inject;
 pub mod event_router;
+pub mod event_viewer;
+pub mod export;
+pub mod gc;
+pub mod graph;
+pub mod health;
 pub mod help;
 pub mod history;
 pub mod http_ingress;
 pub mod ingress_dispatch;
+pub mod ingress_router;
 pub mod ingress_types;
 pub mod input;
+pub mod input_schema;
+pub mod jobs;
+pub mod kafka_bridge;
+pub mod limits;
 pub mod pack_resolve;
+pub mod ports;
+pub mod provider_capabilities;
 pub mod qa_bridge;
+pub mod quotas;
+pub mod reload;
 pub mod repl;
+pub mod run_record;
+pub mod run_report;
 pub mod runner;
 pub mod runner_host;
+pub mod runner_pool;
 mod runtime;
+pub mod scheduled_send;
 pub mod setup;
+pub mod sidecars;
+pub mod snapshot;
+pub mod test_spec;
 pub mod timer_scheduler;
+pub mod troubleshoot;
 mod types;
+pub mod verify;
+pub mod webhook_adopt;
+pub mod webhook_registration;
+pub mod webhook_watcher;
 
 pub use build::{BuildOptions, build_bundle};
 pub use doctor::demo_doctor;
@@ -26,7 +60,8 @@ pub use repl::DemoRepl;
 pub use runner::DemoRunner;
 pub use runner_host::{DemoRunnerHost, FlowOutcome, OperatorContext};
 pub use runtime::{
-    NatsMode, demo_down_runtime, demo_logs_runtime, demo_status_runtime, demo_up, demo_up_services,
+    NatsMode, StatusOptions, demo_down_runtime, demo_logs_runtime, demo_status_runtime, demo_up,
+    demo_up_services,
 };
 pub use setup::{ProvidersInput, discover_tenants};
 pub use types::{DemoBlockedOn, UserEvent};
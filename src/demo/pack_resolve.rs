@@ -3,6 +3,7 @@ use std::path::{Path, PathBuf};
 use anyhow::{Context, Result, anyhow};
 
 use crate::domains;
+use crate::operator_error::OperatorError;
 
 #[derive(Debug, Clone)]
 pub struct DemoPack {
@@ -47,15 +48,28 @@ impl DemoPack {
         }
         Ok(self.entry_flows[0].clone())
     }
+
+    /// The input schema the manifest declares for `flow_id`, if any.
+    pub fn input_schema(&self, flow_id: &str) -> Result<Option<serde_json::Value>> {
+        domains::read_flow_input_schema(&self.pack_path, flow_id).with_context(|| {
+            format!(
+                "failed to read the input schema of flow {flow_id} from {}",
+                self.pack_path.display()
+            )
+        })
+    }
 }
 
 pub fn resolve_pack(packs_dir: &Path, pack_name: &str) -> Result<DemoPack> {
     let pack_path = packs_dir.join(pack_name);
     if !pack_path.exists() {
-        return Err(anyhow!(
+        return Err(anyhow::Error::from(OperatorError::PackNotFound {
+            reference: pack_name.to_string(),
+        })
+        .context(format!(
             "pack {pack_name} not found under {}",
             packs_dir.display()
-        ));
+        )));
     }
     let meta = domains::read_pack_meta(&pack_path)
         .with_context(|| format!("failed to read manifest for pack {}", pack_path.display()))?;
@@ -0,0 +1,100 @@
+//! Pre-flight port checks for `demo start`.
+//!
+//! The gateway (HTTP ingress) and a spawned NATS server each need a local port. They
+//! are checked before anything starts. A busy port is reported with the flags that
+//! fix it, or replaced by the next free port under `--auto-port`. The ports in use
+//! are written to `state/ports.json` for `demo status`.
+
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+use crate::runtime_state::{read_json, write_json};
+
+/// How far `--auto-port` searches past a busy port.
+const AUTO_PORT_RANGE: u16 = 100;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DemoPorts {
+    pub gateway_port: u16,
+    /// Only set when `demo start` spawns NATS itself.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nats_port: Option<u16>,
+}
+
+pub fn port_is_free(host: &str, port: u16) -> bool {
+    TcpListener::bind((host, port)).is_ok()
+}
+
+/// `port` when it is free, otherwise the next free one under `auto`, otherwise an
+/// error naming the `--<flag>` override.
+pub fn select_port(
+    service: &str,
+    flag: &str,
+    host: &str,
+    port: u16,
+    auto: bool,
+    is_free: impl Fn(&str, u16) -> bool,
+) -> anyhow::Result<u16> {
+    if is_free(host, port) {
+        return Ok(port);
+    }
+    if auto {
+        let last = port.saturating_add(AUTO_PORT_RANGE);
+        return (port.saturating_add(1)..=last)
+            .find(|candidate| is_free(host, *candidate))
+            .ok_or_else(|| anyhow!("no free {service} port between {port} and {last}"));
+    }
+    Err(anyhow!(
+        "{service} port {port} on {host} is already in use; stop the process holding it, pass --{flag} <PORT>, or use --auto-port"
+    ))
+}
+
+pub fn ports_path(state_dir: &Path) -> PathBuf {
+    state_dir.join("ports.json")
+}
+
+pub fn write_ports(state_dir: &Path, ports: &DemoPorts) -> anyhow::Result<()> {
+    write_json(&ports_path(state_dir), ports)
+}
+
+pub fn read_ports(state_dir: &Path) -> anyhow::Result<Option<DemoPorts>> {
+    read_json(&ports_path(state_dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn busy_ports_fail_unless_auto_port_is_set() {
+        let busy = |_: &str, port: u16| !(8080..=8082).contains(&port);
+        let err = select_port("gateway", "gateway-port", "127.0.0.1", 8080, false, busy)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("--gateway-port"));
+        assert_eq!(
+            select_port("gateway", "gateway-port", "127.0.0.1", 8080, true, busy).unwrap(),
+            8083
+        );
+        assert_eq!(
+            select_port("nats", "nats-port", "127.0.0.1", 4222, false, busy).unwrap(),
+            4222
+        );
+    }
+
+    #[test]
+    fn ports_round_trip_through_state() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        assert_eq!(read_ports(dir.path())?, None);
+        let ports = DemoPorts {
+            gateway_port: 8081,
+            nats_port: Some(4223),
+        };
+        write_ports(dir.path(), &ports)?;
+        assert_eq!(read_ports(dir.path())?, Some(ports));
+        Ok(())
+    }
+}
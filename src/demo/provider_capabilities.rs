@@ -0,0 +1,164 @@
+//! The feature matrix behind `demo providers capabilities`.
+//!
+//! Providers declare features in their `requirements` output or their `render_plan`
+//! plan, as a top-level key or under `capabilities`, either as a bare boolean or as an
+//! object with `supported` (the same shapes as `attachments`). The plan answers for
+//! the probe message actually sent, so it wins over `requirements`. Undeclared
+//! features stay unknown.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::Serialize;
+use serde_json::{Map as JsonMap, Value as JsonValue, json};
+
+use crate::api;
+use crate::demo::card::{templates, validate};
+use crate::messaging_universal::attachments::LocalAttachment;
+use crate::messaging_universal::dto::RenderPlanOutV1;
+
+/// Matrix columns and the keys a provider may declare each one under.
+pub const FEATURES: &[(&str, &[&str])] = &[
+    ("cards", &["adaptive_cards", "cards"]),
+    ("buttons", &["buttons", "actions"]),
+    ("attachments", &["attachments"]),
+    ("threads", &["threads", "threading", "replies"]),
+    ("editing", &["editing", "edit", "message_edit"]),
+];
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ProviderCapabilities {
+    pub provider: String,
+    /// `None` when the provider does not say.
+    pub features: BTreeMap<String, Option<bool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub card_version: Option<validate::SchemaVersion>,
+    /// Set when `render_plan` failed; `requirements` may still have answered.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A message exercising every feature: text, a card with buttons, an attachment,
+/// and a thread to reply in.
+pub fn probe_message(request: &mut api::SendMessage) {
+    request.text = Some("greentic capability probe".to_string());
+    request.card = templates::starter_card("approval", "Capability probe", "1.3");
+    request.attachments = vec![LocalAttachment {
+        path: PathBuf::from("probe.txt"),
+        name: "probe.txt".to_string(),
+        mime_type: "text/plain".to_string(),
+        bytes: b"probe".to_vec(),
+    }];
+    request.args = JsonMap::from_iter([
+        ("thread_id".to_string(), json!("probe-thread")),
+        ("reply_to".to_string(), json!("probe-message")),
+    ]);
+}
+
+/// Features declared in one provider output (`requirements` output or plan JSON).
+pub fn declared_features(value: &JsonValue) -> BTreeMap<String, Option<bool>> {
+    let capabilities = value.get("capabilities");
+    FEATURES
+        .iter()
+        .map(|(feature, keys)| {
+            let declared = keys.iter().find_map(|key| {
+                value
+                    .get(key)
+                    .or_else(|| capabilities.and_then(|caps| caps.get(key)))
+                    .and_then(support_flag)
+            });
+            (feature.to_string(), declared)
+        })
+        .collect()
+}
+
+fn support_flag(value: &JsonValue) -> Option<bool> {
+    match value {
+        JsonValue::Bool(supported) => Some(*supported),
+        JsonValue::Object(map) => map
+            .get("supported")
+            .and_then(JsonValue::as_bool)
+            .or(Some(true)),
+        _ => None,
+    }
+}
+
+/// Combines `requirements` and `render_plan` answers for `provider`.
+pub fn merge(
+    provider: &str,
+    requirements: Option<&JsonValue>,
+    plan: Result<&RenderPlanOutV1, String>,
+) -> ProviderCapabilities {
+    let mut features = requirements
+        .map(declared_features)
+        .unwrap_or_else(|| declared_features(&JsonValue::Object(JsonMap::new())));
+    let mut card_version = None;
+    let error = match plan {
+        Ok(plan) if plan.ok => {
+            let plan_value = plan
+                .plan
+                .as_ref()
+                .and_then(|plan| serde_json::from_str::<JsonValue>(&plan.plan_json).ok())
+                .unwrap_or(JsonValue::Null);
+            for (feature, declared) in declared_features(&plan_value) {
+                if declared.is_some() {
+                    features.insert(feature, declared);
+                }
+            }
+            let cards = validate::card_support_from_plan(plan, provider);
+            card_version = cards.max_version;
+            if let Some(supported) = cards.supported {
+                features.insert("cards".to_string(), Some(supported));
+            }
+            None
+        }
+        Ok(plan) => Some(
+            plan.error
+                .clone()
+                .unwrap_or_else(|| "render_plan returned error".to_string()),
+        ),
+        Err(error) => Some(error),
+    };
+    ProviderCapabilities {
+        provider: provider.to_string(),
+        features,
+        card_version,
+        error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messaging_universal::dto::RenderPlanOutPlan;
+
+    #[test]
+    fn plan_answers_override_requirements_and_unknowns_stay_unknown() {
+        let requirements = json!({
+            "capabilities": {"threads": true, "editing": false, "buttons": true}
+        });
+        let plan = RenderPlanOutV1 {
+            ok: true,
+            plan: Some(RenderPlanOutPlan {
+                plan_json: json!({
+                    "buttons": {"supported": false},
+                    "capabilities": {"attachments": {"mode": "upload"}}
+                })
+                .to_string(),
+            }),
+            error: None,
+        };
+        let caps = merge("messaging-webex", Some(&requirements), Ok(&plan));
+        let feature = |name: &str| caps.features[name];
+        assert_eq!(feature("threads"), Some(true));
+        assert_eq!(feature("editing"), Some(false));
+        assert_eq!(feature("buttons"), Some(false));
+        assert_eq!(feature("attachments"), Some(true));
+        assert_eq!(feature("cards"), Some(true));
+        assert_eq!(caps.card_version, Some(validate::SchemaVersion(1, 3)));
+
+        let failed = merge("messaging-x", None, Err("boom".to_string()));
+        assert!(failed.features.values().all(Option::is_none));
+        assert_eq!(failed.error.as_deref(), Some("boom"));
+    }
+}
@@ -0,0 +1,695 @@
+//! Per-tenant/team invocation quotas for the runner host.
+//!
+//! Limits come from `quotas` in greentic.demo.yaml (see [`DemoQuotasConfig`]). Each
+//! `tenant.team` pair gets a sliding one-minute window, an in-flight counter, and a
+//! payload size cap.
+//!
+//! Limits are enforced per process: a running `demo start` and a one-off `demo send`
+//! against the same bundle each count their own invocations. Counters are kept in
+//! memory and mirrored to `state/quotas.json` at most once a second and when the
+//! enforcer goes away, so `demo status` can show them from another process. Every
+//! enforcer writes its own section of that file under `state/quotas.json.lock`, and
+//! [`read_usage`] adds the sections up. A section records its process id; sections
+//! of processes that died without cleaning up only keep their totals, and
+//! `last_minute` is counted from the stored invocation times when the file is read.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
+
+use crate::config::{self, DemoQuotasConfig, QuotaLimits};
+use crate::operator_log;
+use crate::runtime_state::{read_json, write_json};
+use crate::supervisor;
+
+const WINDOW: Duration = Duration::from_secs(60);
+const PERSIST_INTERVAL: Duration = Duration::from_secs(1);
+/// Section that keeps the totals of enforcers that have shut down.
+const RETIRED_WRITER: &str = "retired";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaKind {
+    InvocationsPerMinute,
+    Concurrent,
+    PayloadBytes,
+}
+
+impl QuotaKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            QuotaKind::InvocationsPerMinute => "invocations_per_minute",
+            QuotaKind::Concurrent => "concurrent",
+            QuotaKind::PayloadBytes => "payload_bytes",
+        }
+    }
+}
+
+/// Why an invocation was rejected; serialized as the flow output of the refusal.
+#[derive(Clone, Debug, Serialize)]
+pub struct QuotaExceeded {
+    pub tenant: String,
+    pub team: String,
+    pub quota: QuotaKind,
+    pub limit: u64,
+    pub observed: u64,
+}
+
+impl QuotaExceeded {
+    pub fn to_json(&self) -> JsonValue {
+        json!({
+            "code": "quota_exceeded",
+            "error": {
+                "type": "QuotaExceeded",
+                "tenant": self.tenant,
+                "team": self.team,
+                "quota": self.quota,
+                "limit": self.limit,
+                "observed": self.observed,
+            }
+        })
+    }
+}
+
+impl fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "quota exceeded for {}.{}: {} limit={} observed={}",
+            self.tenant,
+            self.team,
+            self.quota.as_str(),
+            self.limit,
+            self.observed
+        )
+    }
+}
+
+impl std::error::Error for QuotaExceeded {}
+
+/// Counters for one `tenant.team`, as written to `state/quotas.json`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct QuotaCounters {
+    #[serde(default)]
+    pub limits: QuotaLimits,
+    #[serde(default)]
+    pub invocations: u64,
+    #[serde(default)]
+    pub last_minute: u32,
+    #[serde(default)]
+    pub in_flight: u32,
+    /// Rejections keyed by [`QuotaKind::as_str`].
+    #[serde(default)]
+    pub rejected: BTreeMap<String, u64>,
+}
+
+impl QuotaCounters {
+    fn add(&mut self, other: &QuotaCounters) {
+        self.limits = other.limits;
+        self.invocations += other.invocations;
+        self.last_minute += other.last_minute;
+        self.in_flight += other.in_flight;
+        for (quota, count) in &other.rejected {
+            *self.rejected.entry(quota.clone()).or_default() += count;
+        }
+    }
+
+    /// The cumulative part of the counters, without anything that is only true while
+    /// the writing process runs.
+    fn totals(&self) -> QuotaCounters {
+        QuotaCounters {
+            last_minute: 0,
+            in_flight: 0,
+            ..self.clone()
+        }
+    }
+}
+
+/// `state/quotas.json`: the counters of every enforcer, keyed by writer.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct UsageFile {
+    #[serde(default)]
+    writers: BTreeMap<String, WriterSection>,
+}
+
+impl UsageFile {
+    /// Folds the totals of `writer` into the retired section and removes it.
+    fn retire(&mut self, writer: &str) {
+        let Some(section) = self.writers.remove(writer) else {
+            return;
+        };
+        let retired = self.writers.entry(RETIRED_WRITER.to_string()).or_default();
+        for (key, counters) in section.counters {
+            retired
+                .counters
+                .entry(key)
+                .or_default()
+                .add(&counters.totals());
+        }
+    }
+
+    /// Retires the sections of processes that are gone, e.g. after a crash or SIGKILL.
+    fn retire_dead_writers(&mut self) {
+        let dead = self
+            .writers
+            .iter()
+            .filter(|(_, section)| !section.is_alive())
+            .map(|(writer, _)| writer.clone())
+            .collect::<Vec<_>>();
+        for writer in dead {
+            self.retire(&writer);
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct WriterSection {
+    /// Process that writes the section; the retired section has none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pid: Option<u32>,
+    #[serde(default)]
+    counters: BTreeMap<String, QuotaCounters>,
+    /// Unix milliseconds of the invocations admitted in the last minute, per key.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    recent: BTreeMap<String, Vec<u64>>,
+}
+
+impl WriterSection {
+    fn is_alive(&self) -> bool {
+        self.pid
+            .is_none_or(|pid| pid == std::process::id() || supervisor::is_running(pid))
+    }
+}
+
+#[derive(Default)]
+struct Usage {
+    window: VecDeque<Instant>,
+    counters: QuotaCounters,
+}
+
+struct UsageState {
+    entries: HashMap<String, Usage>,
+    /// Section of `state/quotas.json` this enforcer writes.
+    writer: String,
+    usage_path: Option<PathBuf>,
+    last_persist: Option<Instant>,
+    dirty: bool,
+}
+
+impl UsageState {
+    /// What to write when a write is due, marking it written.
+    fn take_due(&mut self, force: bool) -> Option<PendingWrite> {
+        let path = self.usage_path.clone()?;
+        let due = self
+            .last_persist
+            .is_none_or(|last| last.elapsed() >= PERSIST_INTERVAL);
+        if !self.dirty || !(force || due) {
+            return None;
+        }
+        self.dirty = false;
+        self.last_persist = Some(Instant::now());
+        Some(PendingWrite {
+            path,
+            writer: self.writer.clone(),
+            section: WriterSection {
+                pid: Some(std::process::id()),
+                counters: snapshot(&self.entries),
+                recent: recent(&self.entries),
+            },
+        })
+    }
+}
+
+struct PendingWrite {
+    path: PathBuf,
+    writer: String,
+    section: WriterSection,
+}
+
+impl Drop for UsageState {
+    fn drop(&mut self) {
+        let Some(path) = self.usage_path.clone() else {
+            return;
+        };
+        if self.entries.is_empty() {
+            return;
+        }
+        let counters = snapshot(&self.entries);
+        let result = update_usage_file(&path, |file| {
+            file.writers.insert(
+                self.writer.clone(),
+                WriterSection {
+                    counters,
+                    ..WriterSection::default()
+                },
+            );
+            file.retire(&self.writer);
+        });
+        if let Err(err) = result {
+            operator_log::warn(
+                module_path!(),
+                format!("failed to write quota usage {}: {err}", path.display()),
+            );
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct QuotaEnforcer {
+    config: Arc<DemoQuotasConfig>,
+    usage: Arc<Mutex<UsageState>>,
+}
+
+/// Holds one concurrency slot; dropping it releases the slot.
+pub struct QuotaPermit {
+    enforcer: QuotaEnforcer,
+    key: String,
+}
+
+impl Drop for QuotaPermit {
+    fn drop(&mut self) {
+        self.enforcer.release(&self.key);
+    }
+}
+
+impl QuotaEnforcer {
+    pub fn new(config: DemoQuotasConfig, usage_path: Option<PathBuf>) -> Self {
+        static NEXT_WRITER: AtomicU64 = AtomicU64::new(0);
+        let writer = format!(
+            "{}-{}",
+            std::process::id(),
+            NEXT_WRITER.fetch_add(1, Ordering::Relaxed)
+        );
+        Self {
+            config: Arc::new(config),
+            usage: Arc::new(Mutex::new(UsageState {
+                entries: HashMap::new(),
+                writer,
+                usage_path,
+                last_persist: None,
+                dirty: false,
+            })),
+        }
+    }
+
+    /// Reads `quotas` from the bundle's greentic.demo.yaml (unlimited when absent).
+    pub fn from_bundle(bundle_root: &Path) -> Self {
+        let config_path = bundle_root.join("greentic.demo.yaml");
+        let config = if config_path.exists() {
+            config::load_demo_config(&config_path)
+                .map(|config| config.quotas)
+                .unwrap_or_default()
+        } else {
+            DemoQuotasConfig::default()
+        };
+        Self::new(config, Some(usage_path(&bundle_root.join("state"))))
+    }
+
+    /// Admits one invocation or explains which quota it would break.
+    pub fn acquire(
+        &self,
+        tenant: &str,
+        team: Option<&str>,
+        payload_len: usize,
+    ) -> Result<QuotaPermit, QuotaExceeded> {
+        let team_label = team.unwrap_or("default");
+        let key = format!("{tenant}.{team_label}");
+        let limits = self.config.limits_for(tenant, team);
+        let mut usage = self.usage.lock().unwrap_or_else(|err| err.into_inner());
+        usage.dirty = true;
+        let entry = usage.entries.entry(key.clone()).or_default();
+        entry.counters.limits = limits;
+        let now = Instant::now();
+        while entry
+            .window
+            .front()
+            .is_some_and(|started| now.duration_since(*started) >= WINDOW)
+        {
+            entry.window.pop_front();
+        }
+        let rejection = check_limits(
+            limits,
+            payload_len as u64,
+            entry.window.len() as u64,
+            u64::from(entry.counters.in_flight),
+        );
+        if let Some((quota, limit, observed)) = rejection {
+            *entry
+                .counters
+                .rejected
+                .entry(quota.as_str().to_string())
+                .or_default() += 1;
+            let due = usage.take_due(false);
+            drop(usage);
+            self.persist(due);
+            return Err(QuotaExceeded {
+                tenant: tenant.to_string(),
+                team: team_label.to_string(),
+                quota,
+                limit,
+                observed,
+            });
+        }
+        entry.window.push_back(now);
+        entry.counters.invocations += 1;
+        entry.counters.in_flight += 1;
+        let due = usage.take_due(false);
+        drop(usage);
+        self.persist(due);
+        Ok(QuotaPermit {
+            enforcer: self.clone(),
+            key,
+        })
+    }
+
+    pub fn counters(&self) -> BTreeMap<String, QuotaCounters> {
+        let usage = self.usage.lock().unwrap_or_else(|err| err.into_inner());
+        snapshot(&usage.entries)
+    }
+
+    /// Writes the counters to `state/quotas.json` now instead of on the next due write.
+    pub fn flush(&self) {
+        let due = self
+            .usage
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take_due(true);
+        self.persist(due);
+    }
+
+    fn release(&self, key: &str) {
+        let mut usage = self.usage.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(entry) = usage.entries.get_mut(key) {
+            entry.counters.in_flight = entry.counters.in_flight.saturating_sub(1);
+            usage.dirty = true;
+        }
+        let due = usage.take_due(false);
+        drop(usage);
+        self.persist(due);
+    }
+
+    /// Writes counters taken under the usage lock without holding that lock.
+    fn persist(&self, pending: Option<PendingWrite>) {
+        let Some(PendingWrite {
+            path,
+            writer,
+            section,
+        }) = pending
+        else {
+            return;
+        };
+        if let Err(err) = update_usage_file(&path, |file| {
+            file.writers.insert(writer, section);
+        }) {
+            operator_log::warn(
+                module_path!(),
+                format!("failed to write quota usage {}: {err}", path.display()),
+            );
+        }
+    }
+}
+
+/// Read-modify-write of `state/quotas.json` under `state/quotas.json.lock`, so
+/// processes sharing a bundle only replace their own section.
+fn update_usage_file(path: &Path, update: impl FnOnce(&mut UsageFile)) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut lock_path = path.as_os_str().to_owned();
+    lock_path.push(".lock");
+    let lock = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)?;
+    lock.lock()?;
+    // An unreadable file starts over; one from before sections parses as empty.
+    let mut file = read_json::<UsageFile>(path)
+        .unwrap_or_default()
+        .unwrap_or_default();
+    update(&mut file);
+    file.retire_dead_writers();
+    write_json(path, &file)
+}
+
+/// `(quota, limit, observed)` for the first limit the invocation would break.
+fn check_limits(
+    limits: QuotaLimits,
+    payload_len: u64,
+    last_minute: u64,
+    in_flight: u64,
+) -> Option<(QuotaKind, u64, u64)> {
+    if let Some(max) = limits.max_payload_bytes
+        && payload_len > max
+    {
+        return Some((QuotaKind::PayloadBytes, max, payload_len));
+    }
+    if let Some(max) = limits.max_invocations_per_minute
+        && last_minute >= u64::from(max)
+    {
+        return Some((QuotaKind::InvocationsPerMinute, u64::from(max), last_minute));
+    }
+    if let Some(max) = limits.max_concurrent
+        && in_flight >= u64::from(max)
+    {
+        return Some((QuotaKind::Concurrent, u64::from(max), in_flight));
+    }
+    None
+}
+
+/// Counters with `last_minute` taken from the window rather than the last acquire.
+fn snapshot(usage: &HashMap<String, Usage>) -> BTreeMap<String, QuotaCounters> {
+    usage
+        .iter()
+        .map(|(key, entry)| {
+            let last_minute = entry
+                .window
+                .iter()
+                .filter(|started| started.elapsed() < WINDOW)
+                .count();
+            let counters = QuotaCounters {
+                last_minute: last_minute as u32,
+                ..entry.counters.clone()
+            };
+            (key.clone(), counters)
+        })
+        .collect()
+}
+
+/// The window of every key as Unix milliseconds, for other processes to count.
+fn recent(usage: &HashMap<String, Usage>) -> BTreeMap<String, Vec<u64>> {
+    let now = unix_millis(SystemTime::now());
+    usage
+        .iter()
+        .filter(|(_, entry)| !entry.window.is_empty())
+        .map(|(key, entry)| {
+            let times = entry
+                .window
+                .iter()
+                .map(|started| now.saturating_sub(started.elapsed().as_millis() as u64))
+                .collect();
+            (key.clone(), times)
+        })
+        .collect()
+}
+
+fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+pub fn usage_path(state_dir: &Path) -> PathBuf {
+    state_dir.join("quotas.json")
+}
+
+/// Counters last written by every enforcer on the bundle, added up and keyed by
+/// `tenant.team`. Processes that are gone only contribute their totals, and
+/// `last_minute` counts the stored invocation times of the last minute.
+pub fn read_usage(state_dir: &Path) -> anyhow::Result<BTreeMap<String, QuotaCounters>> {
+    let file: UsageFile = read_json(&usage_path(state_dir))?.unwrap_or_default();
+    let window_start = unix_millis(SystemTime::now()).saturating_sub(WINDOW.as_millis() as u64);
+    let mut totals = BTreeMap::<String, QuotaCounters>::new();
+    for section in file.writers.values() {
+        let alive = section.is_alive();
+        for (key, counters) in &section.counters {
+            let mut counted = counters.totals();
+            if alive {
+                counted.in_flight = counters.in_flight;
+                counted.last_minute = section.recent.get(key).map_or(0, |times| {
+                    times.iter().filter(|time| **time > window_start).count() as u32
+                });
+            }
+            totals.entry(key.clone()).or_default().add(&counted);
+        }
+    }
+    Ok(totals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::TenantQuotaConfig;
+
+    fn config() -> DemoQuotasConfig {
+        DemoQuotasConfig {
+            default: QuotaLimits {
+                max_invocations_per_minute: Some(3),
+                max_concurrent: None,
+                max_payload_bytes: Some(64),
+            },
+            tenants: BTreeMap::from([(
+                "acme".to_string(),
+                TenantQuotaConfig {
+                    limits: QuotaLimits {
+                        max_concurrent: Some(1),
+                        ..QuotaLimits::default()
+                    },
+                    teams: BTreeMap::from([(
+                        "support".to_string(),
+                        QuotaLimits {
+                            max_payload_bytes: Some(8),
+                            ..QuotaLimits::default()
+                        },
+                    )]),
+                },
+            )]),
+        }
+    }
+
+    #[test]
+    fn team_limits_override_tenant_and_default_field_by_field() {
+        let limits = config().limits_for("acme", Some("support"));
+        assert_eq!(limits.max_payload_bytes, Some(8));
+        assert_eq!(limits.max_concurrent, Some(1));
+        assert_eq!(limits.max_invocations_per_minute, Some(3));
+        assert_eq!(config().limits_for("other", None).max_concurrent, None);
+    }
+
+    #[test]
+    fn quotas_are_tracked_per_tenant() {
+        let enforcer = QuotaEnforcer::new(config(), None);
+        let permit = enforcer.acquire("acme", None, 10).expect("first call");
+        let busy = enforcer.acquire("acme", None, 10).err().expect("one slot");
+        assert_eq!(busy.quota, QuotaKind::Concurrent);
+        drop(permit);
+
+        for _ in 0..2 {
+            drop(enforcer.acquire("acme", None, 10).expect("within rate"));
+        }
+        let limited = enforcer.acquire("acme", None, 10).err().expect("rate");
+        assert_eq!(limited.quota, QuotaKind::InvocationsPerMinute);
+        assert_eq!(limited.to_json()["code"], "quota_exceeded");
+
+        let oversized = enforcer.acquire("globex", None, 100).err().expect("size");
+        assert_eq!(oversized.quota, QuotaKind::PayloadBytes);
+        drop(enforcer.acquire("globex", None, 10).expect("other tenant"));
+
+        let counters = enforcer.counters();
+        assert_eq!(counters["acme.default"].invocations, 3);
+        assert_eq!(counters["acme.default"].in_flight, 0);
+        assert_eq!(counters["acme.default"].rejected["concurrent"], 1);
+        assert_eq!(counters["globex.default"].rejected["payload_bytes"], 1);
+    }
+
+    #[test]
+    fn enforcers_sharing_a_state_dir_keep_each_others_counters() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = usage_path(dir.path());
+        let start = QuotaEnforcer::new(config(), Some(path.clone()));
+        let send = QuotaEnforcer::new(config(), Some(path));
+
+        let permit = start.acquire("globex", None, 10).expect("start call");
+        drop(start.acquire("globex", None, 10).expect("start call"));
+        drop(send.acquire("globex", None, 10).expect("send call"));
+        assert!(send.acquire("globex", None, 100).is_err());
+        start.flush();
+        send.flush();
+
+        let usage = read_usage(dir.path())?;
+        assert_eq!(usage["globex.default"].invocations, 3);
+        assert_eq!(usage["globex.default"].in_flight, 1);
+        assert_eq!(usage["globex.default"].rejected["payload_bytes"], 1);
+
+        // Each process enforces its own window.
+        assert!(send.acquire("globex", None, 10).is_ok());
+
+        drop(permit);
+        drop(start);
+        drop(send);
+        let usage = read_usage(dir.path())?;
+        assert_eq!(usage["globex.default"].invocations, 4);
+        assert_eq!(usage["globex.default"].in_flight, 0);
+        Ok(())
+    }
+    #[test]
+    fn sections_of_dead_processes_only_keep_their_totals() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = usage_path(dir.path());
+        let now = unix_millis(SystemTime::now());
+        let stale = WriterSection {
+            // No process runs with this id.
+            pid: Some(u32::MAX),
+            counters: BTreeMap::from([(
+                "globex.default".to_string(),
+                QuotaCounters {
+                    invocations: 5,
+                    last_minute: 5,
+                    in_flight: 2,
+                    ..QuotaCounters::default()
+                },
+            )]),
+            recent: BTreeMap::from([("globex.default".to_string(), vec![now; 5])]),
+        };
+        let running = WriterSection {
+            pid: Some(std::process::id()),
+            counters: BTreeMap::from([(
+                "acme.default".to_string(),
+                QuotaCounters {
+                    invocations: 2,
+                    last_minute: 2,
+                    ..QuotaCounters::default()
+                },
+            )]),
+            recent: BTreeMap::from([(
+                "acme.default".to_string(),
+                vec![now - 120_000, now - 1_000],
+            )]),
+        };
+        write_json(
+            &path,
+            &UsageFile {
+                writers: BTreeMap::from([
+                    ("crashed-0".to_string(), stale),
+                    ("running-0".to_string(), running),
+                ]),
+            },
+        )?;
+
+        let usage = read_usage(dir.path())?;
+        assert_eq!(usage["globex.default"].invocations, 5);
+        assert_eq!(usage["globex.default"].in_flight, 0);
+        assert_eq!(usage["globex.default"].last_minute, 0);
+        // Only one of the stored invocations is inside the window.
+        assert_eq!(usage["acme.default"].last_minute, 1);
+
+        let enforcer = QuotaEnforcer::new(config(), Some(path.clone()));
+        drop(enforcer.acquire("globex", None, 10).expect("call"));
+        enforcer.flush();
+        let file = read_json::<UsageFile>(&path)?.unwrap_or_default();
+        assert!(!file.writers.contains_key("crashed-0"));
+        assert_eq!(
+            file.writers[RETIRED_WRITER].counters["globex.default"].invocations,
+            5
+        );
+        assert_eq!(read_usage(dir.path())?["globex.default"].invocations, 6);
+        Ok(())
+    }
+}
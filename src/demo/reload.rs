@@ -0,0 +1,199 @@
+//! Config reload for a running `demo start`.
+//!
+//! `demo start` waits for Ctrl+C or SIGTERM, SIGHUP, or a reload request from the
+//! control API (`POST /v1/reload`, sent by `demo reload`). A reload re-reads
+//! `greentic.demo.yaml`, re-resolves the gmaps into resolved manifests, and restarts the
+//! in-process services that captured the old config; NATS and the tunnels keep running.
+//! The process id is written to `state/demo-process.json` so `demo reload` can signal a
+//! demo started without `--control-port`.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use tokio::{runtime::Runtime, sync::mpsc as async_mpsc};
+
+use crate::runtime_state::{read_json, write_json};
+
+/// Where a control API reload request wants its summary.
+pub type ReloadReply = mpsc::Sender<Result<JsonValue>>;
+
+/// Why [`ReloadListener::wait`] returned.
+pub enum DemoSignal {
+    Shutdown,
+    /// SIGHUP carries no reply; control API requests do.
+    Reload(Option<ReloadReply>),
+}
+
+/// Hands reload requests to the `demo start` main loop; cloned into the control API backend.
+#[derive(Clone)]
+pub struct ReloadTrigger {
+    requests: async_mpsc::UnboundedSender<ReloadReply>,
+}
+
+impl ReloadTrigger {
+    /// Queues a reload and blocks until the main loop has carried it out.
+    pub fn request(&self) -> Result<JsonValue> {
+        let (reply, summary) = mpsc::channel();
+        self.requests
+            .send(reply)
+            .map_err(|_| anyhow!("demo is shutting down"))?;
+        summary
+            .recv()
+            .map_err(|_| anyhow!("demo stopped before the reload finished"))?
+    }
+}
+
+pub struct ReloadListener {
+    runtime: Runtime,
+    requests: async_mpsc::UnboundedReceiver<ReloadReply>,
+    #[cfg(unix)]
+    hangup: tokio::signal::unix::Signal,
+    /// `docker stop` and Kubernetes send SIGTERM; it stops the demo like Ctrl+C.
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+}
+
+impl ReloadListener {
+    pub fn new() -> Result<(Self, ReloadTrigger)> {
+        let runtime = Runtime::new().context("failed to spawn runtime for signal listener")?;
+        #[cfg(unix)]
+        let (hangup, terminate) = {
+            use tokio::signal::unix::{SignalKind, signal};
+            let _guard = runtime.enter();
+            (
+                signal(SignalKind::hangup()).context("failed to listen for SIGHUP")?,
+                signal(SignalKind::terminate()).context("failed to listen for SIGTERM")?,
+            )
+        };
+        let (tx, requests) = async_mpsc::unbounded_channel();
+        Ok((
+            Self {
+                runtime,
+                requests,
+                #[cfg(unix)]
+                hangup,
+                #[cfg(unix)]
+                terminate,
+            },
+            ReloadTrigger { requests: tx },
+        ))
+    }
+
+    /// Blocks until Ctrl+C or SIGTERM, SIGHUP, or a reload request.
+    pub fn wait(&mut self) -> Result<DemoSignal> {
+        let requests = &mut self.requests;
+        #[cfg(unix)]
+        let hangup = &mut self.hangup;
+        #[cfg(unix)]
+        let terminate = &mut self.terminate;
+        self.runtime.block_on(async move {
+            #[cfg(unix)]
+            let (hangup, terminate) = (hangup.recv(), terminate.recv());
+            #[cfg(not(unix))]
+            let (hangup, terminate) = (
+                std::future::pending::<Option<()>>(),
+                std::future::pending::<Option<()>>(),
+            );
+            tokio::select! {
+                result = tokio::signal::ctrl_c() => {
+                    result.map_err(|err| anyhow!("failed to wait for Ctrl+C: {err}"))?;
+                    Ok(DemoSignal::Shutdown)
+                }
+                _ = terminate => Ok(DemoSignal::Shutdown),
+                _ = hangup => Ok(DemoSignal::Reload(None)),
+                Some(reply) = requests.recv() => Ok(DemoSignal::Reload(Some(reply))),
+            }
+        })
+    }
+}
+
+/// Record of the foreground `demo start` process serving a bundle.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DemoProcess {
+    pub pid: u32,
+}
+
+pub fn process_path(state_dir: &Path) -> PathBuf {
+    state_dir.join("demo-process.json")
+}
+
+pub fn write_process(state_dir: &Path) -> Result<()> {
+    write_json(
+        &process_path(state_dir),
+        &DemoProcess {
+            pid: std::process::id(),
+        },
+    )
+}
+
+pub fn read_process(state_dir: &Path) -> Result<Option<DemoProcess>> {
+    read_json(&process_path(state_dir))
+}
+
+pub fn remove_process(state_dir: &Path) -> Result<()> {
+    let path = process_path(state_dir);
+    if path.exists() {
+        std::fs::remove_file(&path).with_context(|| format!("remove {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Sends SIGHUP to a running `demo start`.
+pub fn signal_reload(pid: u32) -> Result<()> {
+    #[cfg(unix)]
+    {
+        let rc = unsafe { libc::kill(pid as i32, libc::SIGHUP) };
+        if rc != 0 {
+            return Err(anyhow!(
+                "failed to signal demo process {pid}: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(())
+    }
+    #[cfg(not(unix))]
+    {
+        Err(anyhow!(
+            "cannot signal demo process {pid} on this platform; start the demo with --control-port"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reload_requests_wake_the_listener_and_get_a_reply() -> Result<()> {
+        let (mut listener, trigger) = ReloadListener::new()?;
+        let requester = std::thread::spawn(move || trigger.request());
+        match listener.wait()? {
+            DemoSignal::Reload(Some(reply)) => {
+                reply.send(Ok(serde_json::json!({ "reloaded": true })))?
+            }
+            _ => panic!("expected a reload request"),
+        }
+        let summary = requester.join().expect("requester thread")?;
+        assert_eq!(summary["reloaded"], true);
+        Ok(())
+    }
+
+    #[test]
+    fn process_record_round_trips() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        assert_eq!(read_process(dir.path())?, None);
+        write_process(dir.path())?;
+        assert_eq!(
+            read_process(dir.path())?,
+            Some(DemoProcess {
+                pid: std::process::id()
+            })
+        );
+        remove_process(dir.path())?;
+        assert_eq!(read_process(dir.path())?, None);
+        Ok(())
+    }
+}
@@ -11,6 +11,7 @@ use serde_json::Value as JsonValue;
 use crate::demo::{
     card::{CardView, detect_adaptive_card_view, print_card_summary},
     commands::{CommandParseError, DemoCommand, parse_command},
+    debugger::Stopped,
     help::print_help,
     history::{DemoHistory, Snapshot},
     runner::DemoRunner,
@@ -122,6 +123,7 @@ impl DemoRepl {
                     );
                     return Ok(());
                 }
+                DemoBlockedOn::Error(err) if err.is::<Stopped>() => return Ok(()),
                 DemoBlockedOn::Error(err) => return Err(err),
             }
         }
@@ -180,6 +182,36 @@ impl DemoRepl {
                 Ok(DemoCommand::Quit) => {
                     return Err(DemoReplQuit.into());
                 }
+                Ok(DemoCommand::Break { node }) => {
+                    self.runner.debugger().break_command(node.as_deref());
+                }
+                Ok(DemoCommand::Unbreak { node }) => {
+                    self.runner.debugger().unbreak_command(&node);
+                }
+                Ok(DemoCommand::Step) => {
+                    self.runner.debugger().set_stepping(true);
+                    println!(
+                        "{}",
+                        operator_i18n::tr(
+                            "demo.debug.step_armed",
+                            "The flow will pause before each node once it continues."
+                        )
+                    );
+                }
+                Ok(
+                    DemoCommand::Continue
+                    | DemoCommand::Payload
+                    | DemoCommand::Edit { .. }
+                    | DemoCommand::Rerun,
+                ) => {
+                    println!(
+                        "{}",
+                        operator_i18n::tr(
+                            "demo.debug.not_paused",
+                            "Only available while the flow is paused at a node."
+                        )
+                    );
+                }
                 Ok(DemoCommand::Input { field, value }) => {
                     if let Some(card) = &self.current_card
                         && !card.inputs.iter().any(|input| input.id == field)
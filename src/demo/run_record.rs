@@ -0,0 +1,558 @@
+//! Per-invocation run records written by `DemoRunnerHost`.
+//!
+//! Every provider op/flow invocation produces `state/runs/<run_id>.json` with the
+//! overall outcome plus one entry per executed node (taken from the runner
+//! transcript when one exists). Inputs and outputs are redacted before they hit
+//! disk so records can be shared when debugging a failed flow.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::Context;
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::config::DemoRunsConfig;
+use crate::demo::runner_host::{FlowOutcome, OperatorContext};
+use crate::domains::{self, Domain};
+use crate::redaction;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunRecordStatus {
+    Success,
+    Failed,
+    Denied,
+}
+
+impl RunRecordStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RunRecordStatus::Success => "success",
+            RunRecordStatus::Failed => "failed",
+            RunRecordStatus::Denied => "denied",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RunStep {
+    pub node_id: String,
+    pub status: RunRecordStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<JsonValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<JsonValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub run_id: String,
+    pub domain: String,
+    pub provider: String,
+    pub op: String,
+    pub tenant: String,
+    #[serde(default)]
+    pub team: Option<String>,
+    #[serde(default)]
+    pub correlation_id: Option<String>,
+    pub started_at: String,
+    pub finished_at: String,
+    pub duration_ms: u64,
+    pub status: RunRecordStatus,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub artifacts_dir: Option<String>,
+    #[serde(default)]
+    pub steps: Vec<RunStep>,
+}
+
+pub fn runs_dir(bundle_root: &Path) -> PathBuf {
+    bundle_root.join("state").join("runs")
+}
+
+pub fn record_path(bundle_root: &Path, run_id: &str) -> PathBuf {
+    runs_dir(bundle_root).join(format!("{run_id}.json"))
+}
+
+/// Captures the start of an invocation; call [`RunRecorder::finish`] once the outcome is known.
+pub struct RunRecorder {
+    run_id: String,
+    domain: Domain,
+    provider: String,
+    op: String,
+    tenant: String,
+    team: Option<String>,
+    correlation_id: Option<String>,
+    input: Option<JsonValue>,
+    started_at: DateTime<Utc>,
+    started: Instant,
+}
+
+impl RunRecorder {
+    pub fn start(
+        domain: Domain,
+        provider: &str,
+        op: &str,
+        payload_bytes: &[u8],
+        ctx: &OperatorContext,
+    ) -> Self {
+        let started_at = Utc::now();
+        Self {
+            run_id: format!(
+                "{}-{}",
+                started_at.format("%Y%m%dT%H%M%S%3f"),
+                &uuid::Uuid::new_v4().simple().to_string()[..8]
+            ),
+            domain,
+            provider: provider.to_string(),
+            op: op.to_string(),
+            tenant: ctx.tenant.clone(),
+            team: ctx.team.clone(),
+            correlation_id: ctx.correlation_id.clone(),
+            input: serde_json::from_slice(payload_bytes).ok(),
+            started_at,
+            started: Instant::now(),
+        }
+    }
+
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    /// Builds the record for `outcome`; `artifacts_dir` is the runner run dir, if any.
+    pub fn finish(self, outcome: &FlowOutcome, artifacts_dir: Option<&Path>) -> RunRecord {
+        let status = outcome_status(outcome);
+        let mut steps = artifacts_dir
+            .map(|dir| transcript_steps(&dir.join("transcript.jsonl")))
+            .unwrap_or_default();
+        if steps.is_empty() {
+            steps.push(RunStep {
+                node_id: self.op.clone(),
+                status,
+                started_at: Some(self.started_at.to_rfc3339()),
+                duration_ms: Some(self.started.elapsed().as_millis() as u64),
+                input: self.input.as_ref().map(redact_value),
+                output: outcome.output.as_ref().map(redact_value),
+                error: outcome.error.clone(),
+            });
+        }
+        let finished_at = Utc::now();
+        RunRecord {
+            run_id: self.run_id,
+            domain: domains::domain_name(self.domain).to_string(),
+            provider: self.provider,
+            op: self.op,
+            tenant: self.tenant,
+            team: self.team,
+            correlation_id: self.correlation_id,
+            started_at: self.started_at.to_rfc3339(),
+            finished_at: finished_at.to_rfc3339(),
+            duration_ms: self.started.elapsed().as_millis() as u64,
+            status,
+            error: outcome.error.clone(),
+            artifacts_dir: artifacts_dir.map(|dir| dir.display().to_string()),
+            steps,
+        }
+    }
+}
+
+fn outcome_status(outcome: &FlowOutcome) -> RunRecordStatus {
+    if outcome.success {
+        RunRecordStatus::Success
+    } else if outcome
+        .error
+        .as_deref()
+        .is_some_and(|error| error.starts_with("operation denied"))
+    {
+        RunRecordStatus::Denied
+    } else {
+        RunRecordStatus::Failed
+    }
+}
+
+pub fn write_record(bundle_root: &Path, record: &RunRecord) -> anyhow::Result<PathBuf> {
+    let dir = runs_dir(bundle_root);
+    fs::create_dir_all(&dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let path = record_path(bundle_root, &record.run_id);
+    let json = serde_json::to_string_pretty(record)?;
+    fs::write(&path, json).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+pub fn load_record(bundle_root: &Path, run_id: &str) -> anyhow::Result<RunRecord> {
+    let path = record_path(bundle_root, run_id);
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("run {run_id} not found at {}", path.display()))?;
+    serde_json::from_str(&contents)
+        .with_context(|| format!("invalid run record {}", path.display()))
+}
+
+/// Loads every record under `state/runs`, newest first. Unreadable files are skipped.
+pub fn list_records(bundle_root: &Path) -> anyhow::Result<Vec<RunRecord>> {
+    let dir = runs_dir(bundle_root);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut records = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let Ok(contents) = fs::read_to_string(&path) else {
+            continue;
+        };
+        if let Ok(record) = serde_json::from_str::<RunRecord>(&contents) {
+            records.push(record);
+        }
+    }
+    records.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+    Ok(records)
+}
+
+/// Filters applied by `demo runs list`.
+#[derive(Clone, Debug, Default)]
+pub struct RunQuery {
+    pub provider: Option<String>,
+    pub status: Option<RunRecordStatus>,
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl RunQuery {
+    pub fn matches(&self, record: &RunRecord) -> bool {
+        if self
+            .provider
+            .as_deref()
+            .is_some_and(|provider| record.provider != provider)
+        {
+            return false;
+        }
+        if self.status.is_some_and(|status| record.status != status) {
+            return false;
+        }
+        if let Some(since) = self.since {
+            let started = DateTime::parse_from_rfc3339(&record.started_at)
+                .map(|value| value.with_timezone(&Utc));
+            if !started.is_ok_and(|started| started >= since) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Parses `--since` as either an RFC 3339 timestamp or a relative age such as
+/// `30m`, `12h`, or `7d`.
+pub fn parse_since(value: &str, now: DateTime<Utc>) -> anyhow::Result<DateTime<Utc>> {
+    if let Ok(parsed) = DateTime::parse_from_rfc3339(value) {
+        return Ok(parsed.with_timezone(&Utc));
+    }
+    let value = value.trim();
+    let split = value
+        .find(|ch: char| !ch.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| anyhow::anyhow!("invalid --since value {value:?}"))?;
+    let delta = match unit {
+        "s" => TimeDelta::try_seconds(amount),
+        "m" => TimeDelta::try_minutes(amount),
+        "h" | "" => TimeDelta::try_hours(amount),
+        "d" => TimeDelta::try_days(amount),
+        "w" => TimeDelta::try_weeks(amount),
+        _ => None,
+    }
+    .ok_or_else(|| anyhow::anyhow!("invalid --since value {value:?}; use e.g. 30m, 12h, 7d"))?;
+    Ok(now - delta)
+}
+
+/// Retention limits for `state/runs`; `0` disables the corresponding limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RunRetention {
+    pub max_runs: usize,
+    pub max_age_days: u64,
+}
+
+impl From<&DemoRunsConfig> for RunRetention {
+    fn from(config: &DemoRunsConfig) -> Self {
+        Self {
+            max_runs: config.max_runs,
+            max_age_days: config.max_age_days,
+        }
+    }
+}
+
+impl Default for RunRetention {
+    fn default() -> Self {
+        Self::from(&DemoRunsConfig::default())
+    }
+}
+
+/// Deletes run records beyond `max_runs` (newest kept) or older than `max_age_days`.
+/// Works on file names and mtimes only so it stays cheap enough to run after every write.
+pub fn prune_records(bundle_root: &Path, retention: RunRetention) -> anyhow::Result<usize> {
+    Ok(prune_candidates(bundle_root, retention)?
+        .iter()
+        .filter(|path| fs::remove_file(path).is_ok())
+        .count())
+}
+
+/// The run records [`prune_records`] would delete.
+pub fn prune_candidates(
+    bundle_root: &Path,
+    retention: RunRetention,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let dir = runs_dir(bundle_root);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut files = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let modified = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        files.push((path, modified));
+    }
+    // Run ids start with a UTC timestamp, so name order is chronological.
+    files.sort_by(|a, b| b.0.cmp(&a.0));
+    let max_age = (retention.max_age_days > 0)
+        .then(|| Duration::from_secs(retention.max_age_days.saturating_mul(86_400)));
+    let now = SystemTime::now();
+    Ok(files
+        .into_iter()
+        .enumerate()
+        .filter(|(index, (_, modified))| {
+            let over_count = retention.max_runs > 0 && *index >= retention.max_runs;
+            let too_old = max_age.is_some_and(|max_age| {
+                now.duration_since(*modified).is_ok_and(|age| age > max_age)
+            });
+            over_count || too_old
+        })
+        .map(|(_, (path, _))| path)
+        .collect())
+}
+
+fn transcript_steps(path: &Path) -> Vec<RunStep> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    contents
+        .lines()
+        .filter_map(|line| serde_json::from_str::<JsonValue>(line).ok())
+        .filter_map(|value| {
+            let node_id = value
+                .get("node_id")
+                .or_else(|| value.get("node"))
+                .and_then(JsonValue::as_str)?
+                .to_string();
+            let error =
+                value
+                    .get("error")
+                    .filter(|error| !error.is_null())
+                    .map(|error| match error.as_str() {
+                        Some(text) => text.to_string(),
+                        None => error.to_string(),
+                    });
+            let started_at = ["started_at", "timestamp", "ts"]
+                .iter()
+                .find_map(|key| value.get(*key))
+                .map(|ts| match ts.as_str() {
+                    Some(text) => text.to_string(),
+                    None => ts.to_string(),
+                });
+            let duration_ms = ["duration_ms", "elapsed_ms"]
+                .iter()
+                .find_map(|key| value.get(*key).and_then(JsonValue::as_u64));
+            let input = value
+                .get("inputs")
+                .or_else(|| value.get("input"))
+                .filter(|input| !input.is_null())
+                .map(redact_value);
+            let output = value
+                .get("outputs")
+                .or_else(|| value.get("output"))
+                .filter(|output| !output.is_null())
+                .map(redact_value);
+            Some(RunStep {
+                node_id,
+                status: if error.is_some() {
+                    RunRecordStatus::Failed
+                } else {
+                    RunRecordStatus::Success
+                },
+                started_at,
+                duration_ms,
+                input,
+                output,
+                error,
+            })
+        })
+        .collect()
+}
+
+/// Masks secret-looking keys and secret values; always on, since records outlive
+/// the command that wrote them.
+pub fn redact_value(value: &JsonValue) -> JsonValue {
+    redaction::scrub_json(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::demo::runner_host::RunnerExecutionMode;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn ctx() -> OperatorContext {
+        OperatorContext {
+            tenant: "demo".to_string(),
+            team: Some("default".to_string()),
+            correlation_id: None,
+        }
+    }
+
+    fn outcome(success: bool, error: Option<&str>) -> FlowOutcome {
+        FlowOutcome {
+            success,
+            output: Some(json!({"ok": success, "access_token": "abc"})),
+            raw: None,
+            error: error.map(str::to_string),
+            mode: RunnerExecutionMode::Exec,
+        }
+    }
+
+    #[test]
+    fn redact_value_masks_nested_secret_keys() {
+        let value = json!({
+            "config": {"bot_token": "123:abc", "chat_id": 42},
+            "headers": [{"Authorization": "Bearer x"}],
+        });
+        let redacted = redact_value(&value);
+        assert_eq!(redacted["config"]["bot_token"], "***");
+        assert_eq!(redacted["config"]["chat_id"], 42);
+        assert_eq!(redacted["headers"][0]["Authorization"], "***");
+    }
+
+    #[test]
+    fn finish_without_transcript_records_single_step() {
+        let recorder = RunRecorder::start(
+            Domain::Messaging,
+            "telegram",
+            "send_payload",
+            br#"{"text":"hi","password":"p"}"#,
+            &ctx(),
+        );
+        let record = recorder.finish(&outcome(false, Some("boom")), None);
+        assert_eq!(record.status, RunRecordStatus::Failed);
+        assert_eq!(record.steps.len(), 1);
+        let step = &record.steps[0];
+        assert_eq!(step.node_id, "send_payload");
+        assert_eq!(step.input.as_ref().unwrap()["password"], "***");
+        assert_eq!(step.output.as_ref().unwrap()["access_token"], "***");
+        assert_eq!(step.error.as_deref(), Some("boom"));
+    }
+
+    #[test]
+    fn query_filters_by_provider_status_and_since() {
+        let recorder = RunRecorder::start(Domain::Messaging, "telegram", "send", b"{}", &ctx());
+        let record = recorder.finish(&outcome(false, Some("boom")), None);
+        let now = Utc::now();
+        assert!(RunQuery::default().matches(&record));
+        assert!(
+            RunQuery {
+                provider: Some("telegram".to_string()),
+                status: Some(RunRecordStatus::Failed),
+                since: Some(parse_since("1h", now).unwrap()),
+            }
+            .matches(&record)
+        );
+        assert!(
+            !RunQuery {
+                status: Some(RunRecordStatus::Success),
+                ..RunQuery::default()
+            }
+            .matches(&record)
+        );
+        assert!(
+            !RunQuery {
+                since: Some(now + TimeDelta::try_hours(1).unwrap()),
+                ..RunQuery::default()
+            }
+            .matches(&record)
+        );
+        assert!(parse_since("7x", now).is_err());
+        assert_eq!(
+            parse_since("2d", now).unwrap(),
+            now - TimeDelta::try_days(2).unwrap()
+        );
+    }
+
+    #[test]
+    fn prune_keeps_newest_runs() {
+        let dir = tempdir().unwrap();
+        let runs = runs_dir(dir.path());
+        fs::create_dir_all(runs.join("messaging")).unwrap();
+        for id in [
+            "20250101T000000000-a",
+            "20250102T000000000-b",
+            "20250103T000000000-c",
+        ] {
+            fs::write(runs.join(format!("{id}.json")), "{}").unwrap();
+        }
+        let removed = prune_records(
+            dir.path(),
+            RunRetention {
+                max_runs: 2,
+                max_age_days: 0,
+            },
+        )
+        .unwrap();
+        assert_eq!(removed, 1);
+        assert!(!runs.join("20250101T000000000-a.json").exists());
+        assert!(runs.join("20250103T000000000-c.json").exists());
+        assert!(runs.join("messaging").exists());
+    }
+
+    #[test]
+    fn finish_reads_transcript_steps_and_round_trips() {
+        let dir = tempdir().unwrap();
+        let artifacts = dir.path().join("artifacts");
+        fs::create_dir_all(&artifacts).unwrap();
+        fs::write(
+            artifacts.join("transcript.jsonl"),
+            [
+                json!({"node_id": "in", "outputs": {"text": "hi"}, "duration_ms": 3}).to_string(),
+                json!({"node_id": "reply", "error": "no route"}).to_string(),
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+        let recorder = RunRecorder::start(Domain::Messaging, "telegram", "main", b"{}", &ctx());
+        let record = recorder.finish(&outcome(true, None), Some(&artifacts));
+        assert_eq!(record.steps.len(), 2);
+        assert_eq!(record.steps[0].duration_ms, Some(3));
+        assert_eq!(record.steps[1].status, RunRecordStatus::Failed);
+
+        write_record(dir.path(), &record).unwrap();
+        let loaded = load_record(dir.path(), &record.run_id).unwrap();
+        assert_eq!(loaded.steps.len(), 2);
+        assert_eq!(list_records(dir.path()).unwrap().len(), 1);
+    }
+}
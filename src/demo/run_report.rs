@@ -0,0 +1,102 @@
+//! The one-shot report written by `demo run --no-repl`.
+//!
+//! A [`RunReport`] turns the [`DemoBlockedOn`] a flow stopped at into the JSON
+//! document scripts read and the exit code the command ends with. Both come from the
+//! same outcome, so the `exit_code` field in the file always matches the process.
+
+use std::path::Path;
+
+use anyhow::Context;
+use serde_json::{Value as JsonValue, json};
+
+use crate::demo::DemoBlockedOn;
+use crate::operator_error::{self, OperatorError};
+
+pub struct RunReport {
+    record: JsonValue,
+    failure: Option<anyhow::Error>,
+}
+
+impl RunReport {
+    pub fn new(blocked: DemoBlockedOn, pack_id: &str, flow_id: &str) -> Self {
+        let (record, failure) = match blocked {
+            DemoBlockedOn::Finished(output) => (
+                json!({ "status": "finished", "pack": pack_id, "flow": flow_id, "output": output }),
+                None,
+            ),
+            DemoBlockedOn::Waiting { reason, output, .. } => (
+                json!({
+                    "status": "waiting",
+                    "pack": pack_id,
+                    "flow": flow_id,
+                    "reason": reason,
+                    "output": output,
+                }),
+                Some(
+                    OperatorError::FlowWaiting {
+                        pack: pack_id.to_string(),
+                        flow: flow_id.to_string(),
+                        reason,
+                    }
+                    .into(),
+                ),
+            ),
+            DemoBlockedOn::Error(err) => {
+                // Keep a typed cause (a missing secret, say); anything else is a flow failure.
+                let err = if operator_error::find(&err).is_some() {
+                    err
+                } else {
+                    OperatorError::FlowFailed {
+                        provider: pack_id.to_string(),
+                        op: flow_id.to_string(),
+                        detail: format!("{err:#}"),
+                    }
+                    .into()
+                };
+                (
+                    json!({
+                        "status": "error",
+                        "pack": pack_id,
+                        "flow": flow_id,
+                        "error": format!("{err:#}"),
+                    }),
+                    Some(err),
+                )
+            }
+        };
+        let mut report = Self { record, failure };
+        report.record["exit_code"] = json!(report.exit_code());
+        report
+    }
+
+    /// `finished`, `waiting` or `error`.
+    pub fn status(&self) -> &str {
+        self.record["status"].as_str().unwrap_or_default()
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        self.failure.as_ref().map_or(0, operator_error::exit_code)
+    }
+
+    pub fn to_json(&self) -> &JsonValue {
+        &self.record
+    }
+
+    pub fn render(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(&self.record)?)
+    }
+
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        let rendered = self.render()?;
+        crate::runtime_state::atomic_write(path, format!("{rendered}\n").as_bytes())
+            .with_context(|| format!("write run result to {}", path.display()))
+    }
+
+    /// `Ok` for a finished run; otherwise the error the command exits with.
+    pub fn into_result(self) -> anyhow::Result<()> {
+        match self.failure {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+}
@@ -2,6 +2,7 @@ use std::{
     collections::HashMap,
     fs::File,
     io::Read,
+    panic,
     path::{Path, PathBuf},
     sync::Arc,
 };
@@ -15,7 +16,9 @@ use greentic_runner_host::{
         WebhookPolicy,
     },
     pack::{ComponentResolution, PackRuntime},
-    runner::engine::{FlowContext, FlowEngine, FlowExecution, FlowSnapshot, FlowStatus},
+    runner::engine::{
+        ExecutionObserver, FlowContext, FlowEngine, FlowExecution, FlowSnapshot, FlowStatus,
+    },
     storage::{DynSessionStore, DynStateStore, new_session_store, new_state_store},
     trace::TraceConfig,
 };
@@ -24,24 +27,40 @@ use serde_json::Value;
 use tokio::runtime::Runtime;
 use zip::ZipArchive;
 
+use crate::demo::debugger::{self, Debugger, Interrupt};
 use crate::demo::types::{DemoBlockedOn, UserEvent};
+use crate::flow_stream;
 use crate::secrets_gate::DynSecretsManager;
 
 pub struct DemoRunner {
-    pack_path: PathBuf,
-    entry_flow: String,
-    pack_id: String,
-    tenant: String,
+    job: FlowJob,
     #[allow(dead_code)]
     team: Option<String>,
     initial_input: Value,
     pending_input: Option<Value>,
     snapshot: Option<FlowSnapshot>,
+    /// Always set; taken on drop to shut down without waiting for parked runs.
+    runtime: Option<Runtime>,
+}
+
+/// Everything one attempt at running the flow needs, owned so the attempt can
+/// run as its own task and be dropped when the debugger abandons it.
+#[derive(Clone)]
+struct FlowJob {
+    pack_path: PathBuf,
+    entry_flow: String,
+    pack_id: String,
+    tenant: String,
     session_store: DynSessionStore,
     state_store: DynStateStore,
     secrets_manager: DynSecretsManager,
     host_config: Arc<HostConfig>,
-    runtime: Runtime,
+    debugger: Arc<Debugger>,
+}
+
+enum Attempt {
+    Finished(anyhow::Result<FlowExecution>),
+    Abandoned(Interrupt),
 }
 
 impl DemoRunner {
@@ -76,28 +95,35 @@ impl DemoRunner {
         let runtime = Runtime::new().context("build demo runner runtime")?;
         let host_config = Arc::new(build_host_config(tenant));
         Ok(Self {
-            pack_path,
-            entry_flow,
-            pack_id,
-            tenant: tenant.to_string(),
+            job: FlowJob {
+                pack_path,
+                entry_flow,
+                pack_id,
+                tenant: tenant.to_string(),
+                session_store: new_session_store(),
+                state_store: new_state_store(),
+                secrets_manager,
+                host_config,
+                debugger: Arc::new(Debugger::default()),
+            },
             team,
             initial_input,
             pending_input: None,
             snapshot: None,
-            session_store: new_session_store(),
-            state_store: new_state_store(),
-            secrets_manager,
-            host_config,
-            runtime,
+            runtime: Some(runtime),
         })
     }
 
     pub fn pack_path(&self) -> &Path {
-        &self.pack_path
+        &self.job.pack_path
     }
 
     pub fn pack_id(&self) -> &str {
-        &self.pack_id
+        &self.job.pack_id
+    }
+
+    pub fn debugger(&self) -> &Debugger {
+        &self.job.debugger
     }
 
     pub fn submit_user_event(&mut self, event: UserEvent) {
@@ -106,9 +132,31 @@ impl DemoRunner {
 
     pub fn run_until_blocked(&mut self) -> DemoBlockedOn {
         let initial_input = self.initial_input.clone();
-        let input = self.pending_input.take().unwrap_or(initial_input);
-        let snapshot = self.snapshot.clone();
-        let result = self.runtime.block_on(self.execute_flow(input, snapshot));
+        let mut input = self.pending_input.take().unwrap_or(initial_input);
+        let mut snapshot = self.snapshot.clone();
+        self.job.debugger.begin(
+            &self.job.pack_id,
+            &self.job.entry_flow,
+            snapshot.as_ref(),
+            &input,
+        );
+        let result = loop {
+            let attempt = {
+                let _live = flow_stream::live();
+                self.attempt(input.clone(), snapshot.clone())
+            };
+            match attempt {
+                Attempt::Finished(result) => break result,
+                Attempt::Abandoned(Interrupt::Restart {
+                    snapshot: resume_at,
+                    input: resume_input,
+                }) => {
+                    snapshot = Some(*resume_at);
+                    input = resume_input;
+                }
+                Attempt::Abandoned(Interrupt::Quit) => break Err(debugger::Stopped.into()),
+            }
+        };
         match result {
             Ok(execution) => match execution.status {
                 FlowStatus::Waiting(wait) => {
@@ -129,8 +177,41 @@ impl DemoRunner {
         }
     }
 
-    async fn execute_flow(
-        &self,
+    /// Runs the flow once, until it blocks, ends, or the debugger abandons it.
+    fn attempt(&self, input: Value, snapshot: Option<FlowSnapshot>) -> Attempt {
+        let runtime = self.runtime.as_ref().expect("demo runner runtime");
+        let abandoned = self.job.debugger.watch();
+        let mut flow = runtime.spawn(self.job.clone().execute(input, snapshot));
+        runtime.block_on(async {
+            tokio::select! {
+                joined = &mut flow => match joined {
+                    Ok(result) => Attempt::Finished(result),
+                    Err(err) if err.is_panic() => panic::resume_unwind(err.into_panic()),
+                    Err(err) => Attempt::Finished(Err(anyhow!("flow run was cancelled: {err}"))),
+                },
+                Ok(interrupt) = abandoned => {
+                    // The debugger keeps the paused node from starting, so
+                    // the abandoned task never gets to run it.
+                    flow.abort();
+                    Attempt::Abandoned(interrupt)
+                }
+            }
+        })
+    }
+}
+
+impl Drop for DemoRunner {
+    fn drop(&mut self) {
+        // Abandoned runs stay parked on their threads, so do not wait for them.
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+impl FlowJob {
+    async fn execute(
+        self,
         input: Value,
         snapshot: Option<FlowSnapshot>,
     ) -> anyhow::Result<FlowExecution> {
@@ -163,7 +244,10 @@ impl DemoRunner {
             provider_id: None,
             retry_config: host_config.retry_config().into(),
             attempt: 1,
-            observer: None,
+            observer: self
+                .debugger
+                .is_armed()
+                .then_some(&*self.debugger as &dyn ExecutionObserver),
             mocks: None,
         };
         match snapshot {
@@ -1,7 +1,6 @@
 use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::fs;
-use std::io::Read;
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
@@ -27,7 +26,6 @@ use greentic_types::decode_pack_manifest;
 use serde::{Deserialize, Serialize};
 use serde_json::{Value as JsonValue, json};
 use tokio::runtime::Runtime as TokioRuntime;
-use zip::ZipArchive;
 
 /// Create a Tokio runtime for blocking async operations.
 /// When called from within an existing runtime (e.g., HTTP ingress handler),
@@ -59,14 +57,23 @@ use crate::runner_integration::RunFlowOptions;
 use crate::runner_integration::RunnerFlavor;
 use crate::runner_integration::run_flow_with_options;
 
+use crate::audit::{self, AuditEvent};
 use crate::capabilities::{
     CapabilityBinding, CapabilityInstallRecord, CapabilityPackRecord, CapabilityRegistry,
     HookStage, ResolveScope, is_binding_ready, write_install_record,
 };
 use crate::cards::CardRenderer;
+use crate::config;
+use crate::demo::capability_recording::{self, CapabilityRecording, CapabilityRecordingMode};
+use crate::demo::circuit_breaker::{self, CircuitBreaker};
+use crate::demo::limits::{self, InvocationLimiter};
+use crate::demo::quotas::{QuotaEnforcer, QuotaExceeded};
+use crate::demo::run_record::{self, RunRecorder, RunRetention};
+use crate::demo::runner_pool::{PoolRequest, RunnerPool};
 use crate::discovery;
-use crate::domains::{self, Domain, ProviderPack};
+use crate::domains::{self, Domain, PackArchive, ProviderPack};
 use crate::operator_log;
+use crate::profile::{self, Phase};
 use crate::secrets_gate::{self, DynSecretsManager, SecretsManagerHandle};
 use crate::secrets_manager;
 use crate::state_layout;
@@ -179,6 +186,12 @@ pub struct DemoRunnerHost {
     secrets_handle: SecretsManagerHandle,
     card_renderer: CardRenderer,
     state_store: DynStateStore,
+    run_retention: RunRetention,
+    capability_recording: CapabilityRecordingMode,
+    quotas: QuotaEnforcer,
+    circuits: CircuitBreaker,
+    limits: InvocationLimiter,
+    runner_pool: Option<Arc<RunnerPool>>,
     debug_enabled: bool,
 }
 
@@ -220,7 +233,11 @@ impl DemoRunnerHost {
             .iter()
             .map(|provider| (provider.pack_path.clone(), provider.provider_id.clone()))
             .collect::<HashMap<_, _>>();
-        for domain in [Domain::Messaging, Domain::Events, Domain::Secrets] {
+        let custom_domains = domains::register_bundle_domains(&bundle_root)?;
+        for domain in [Domain::Messaging, Domain::Events, Domain::Secrets]
+            .into_iter()
+            .chain(custom_domains)
+        {
             let is_demo_bundle = bundle_root.join("greentic.demo.yaml").exists();
             let packs = if is_demo_bundle {
                 domains::discover_provider_packs_cbor_only(&bundle_root, domain)?
@@ -247,6 +264,21 @@ impl DemoRunnerHost {
             }
         }
         let capability_registry = CapabilityRegistry::build_from_pack_index(&pack_index)?;
+        let demo_config_path = bundle_root.join("greentic.demo.yaml");
+        let run_retention = if demo_config_path.exists() {
+            config::load_demo_config(&demo_config_path)
+                .map(|config| RunRetention::from(&config.runs))
+                .unwrap_or_default()
+        } else {
+            RunRetention::default()
+        };
+        let quotas = QuotaEnforcer::from_bundle(&bundle_root);
+        let circuits = CircuitBreaker::from_bundle(&bundle_root);
+        let limits = InvocationLimiter::from_bundle(&bundle_root);
+        let runner_pool = match mode {
+            RunnerMode::Integration { .. } => RunnerPool::from_bundle(&bundle_root),
+            RunnerMode::Exec => None,
+        };
         Ok(Self {
             bundle_root,
             runner_mode: mode,
@@ -256,14 +288,81 @@ impl DemoRunnerHost {
             secrets_handle,
             card_renderer: CardRenderer::new(),
             state_store: new_state_store(),
+            run_retention,
+            capability_recording: CapabilityRecordingMode::from_env(),
+            quotas,
+            circuits,
+            limits,
+            runner_pool,
             debug_enabled,
         })
     }
 
+    /// Overrides the `GREENTIC_CAPABILITY_RECORDING` default for capability invocations.
+    pub fn with_capability_recording(mut self, mode: CapabilityRecordingMode) -> Self {
+        self.capability_recording = mode;
+        self
+    }
+
     pub fn debug_enabled(&self) -> bool {
         self.debug_enabled
     }
 
+    pub fn quotas(&self) -> &QuotaEnforcer {
+        &self.quotas
+    }
+
+    pub fn circuit_breaker(&self) -> &CircuitBreaker {
+        &self.circuits
+    }
+
+    /// The refusal for `op_id` when `provider`'s circuit is open.
+    fn circuit_refusal(
+        &self,
+        provider: &str,
+        op_id: &str,
+        ctx: &OperatorContext,
+    ) -> Option<FlowOutcome> {
+        if !circuit_breaker::GUARDED_OPS.contains(&op_id) {
+            return None;
+        }
+        let err = self
+            .circuits
+            .admit(
+                &ctx.tenant,
+                ctx.team.as_deref(),
+                provider,
+                chrono::Utc::now(),
+            )
+            .err()?;
+        operator_log::warn(module_path!(), err.to_string());
+        Some(FlowOutcome {
+            success: false,
+            output: Some(err.to_json()),
+            raw: None,
+            error: Some(err.to_string()),
+            mode: RunnerExecutionMode::Exec,
+        })
+    }
+
+    fn record_circuit(
+        &self,
+        provider: &str,
+        op_id: &str,
+        ctx: &OperatorContext,
+        error: Option<String>,
+    ) {
+        if circuit_breaker::GUARDED_OPS.contains(&op_id) {
+            self.circuits.record(
+                &ctx.tenant,
+                ctx.team.as_deref(),
+                provider,
+                error.as_deref(),
+                chrono::Utc::now(),
+            );
+        }
+    }
+
     /// Return the canonical `provider_type` stored inside a provider pack manifest
     /// (e.g. `"messaging.webex.bot"`).  Falls back to the lookup key when the pack
     /// is not found or the manifest cannot be read.
@@ -285,6 +384,10 @@ impl DemoRunnerHost {
             .resolve(cap_id, min_version, &scope)
     }
 
+    pub fn capability_registry(&self) -> &CapabilityRegistry {
+        &self.capability_registry
+    }
+
     pub fn resolve_hook_chain(&self, stage: HookStage, op_name: &str) -> Vec<CapabilityBinding> {
         self.capability_registry.resolve_hook_chain(stage, op_name)
     }
@@ -320,7 +423,10 @@ impl DemoRunnerHost {
     ) -> anyhow::Result<PathBuf> {
         let record =
             CapabilityInstallRecord::ready(&binding.cap_id, &binding.stable_id, &binding.pack_id);
-        write_install_record(&self.bundle_root, &ctx.tenant, ctx.team.as_deref(), &record)
+        let result =
+            write_install_record(&self.bundle_root, &ctx.tenant, ctx.team.as_deref(), &record);
+        self.audit_capability_record("capability.mark_ready", ctx, binding, None, &result);
+        result
     }
 
     pub fn mark_capability_failed(
@@ -335,7 +441,37 @@ impl DemoRunnerHost {
             &binding.pack_id,
             failure_key,
         );
-        write_install_record(&self.bundle_root, &ctx.tenant, ctx.team.as_deref(), &record)
+        let result =
+            write_install_record(&self.bundle_root, &ctx.tenant, ctx.team.as_deref(), &record);
+        self.audit_capability_record(
+            "capability.mark_failed",
+            ctx,
+            binding,
+            Some(failure_key),
+            &result,
+        );
+        result
+    }
+
+    fn audit_capability_record(
+        &self,
+        action: &str,
+        ctx: &OperatorContext,
+        binding: &CapabilityBinding,
+        failure_key: Option<&str>,
+        result: &anyhow::Result<PathBuf>,
+    ) {
+        audit::record(
+            &self.bundle_root,
+            AuditEvent::new(action, &binding.cap_id)
+                .scope(&ctx.tenant, ctx.team.as_deref())
+                .detail(json!({
+                    "stable_id": binding.stable_id,
+                    "pack_id": binding.pack_id,
+                    "failure_key": failure_key,
+                }))
+                .result(result),
+        );
     }
 
     pub fn invoke_capability(
@@ -345,6 +481,32 @@ impl DemoRunnerHost {
         payload_bytes: &[u8],
         ctx: &OperatorContext,
     ) -> anyhow::Result<FlowOutcome> {
+        if self.capability_recording == CapabilityRecordingMode::Mock {
+            // Mock mode never touches the provider, so it also works without the pack.
+            return Ok(
+                match capability_recording::find_recording(
+                    &self.bundle_root,
+                    cap_id,
+                    op,
+                    payload_bytes,
+                )? {
+                    Some(recording) => recording.to_outcome(),
+                    None => capability_route_error_outcome(
+                        cap_id,
+                        op,
+                        "mock mode: no recording under state/capability-recordings".to_string(),
+                    ),
+                },
+            );
+        }
+        let _permit =
+            match self
+                .quotas
+                .acquire(&ctx.tenant, ctx.team.as_deref(), payload_bytes.len())
+            {
+                Ok(permit) => permit,
+                Err(err) => return Ok(quota_denied_outcome(&err)),
+            };
         let scope = ResolveScope {
             env: env::var("GREENTIC_ENV").ok(),
             tenant: Some(ctx.tenant.clone()),
@@ -415,6 +577,22 @@ impl DemoRunnerHost {
         let post_chain = self.resolve_hook_chain(HookStage::Post, &envelope.op_name);
         let _ = self.evaluate_hook_chain(&post_chain, HookStage::Post, &mut envelope)?;
         self.emit_post_sub(&envelope);
+        if self.capability_recording == CapabilityRecordingMode::Record {
+            let recording = CapabilityRecording::new(
+                cap_id,
+                op,
+                &binding.stable_id,
+                payload_bytes,
+                ctx,
+                &outcome,
+            );
+            if let Err(err) = capability_recording::write_recording(&self.bundle_root, &recording) {
+                operator_log::warn(
+                    module_path!(),
+                    format!("failed to record capability invocation cap_id={cap_id}: {err}"),
+                );
+            }
+        }
         Ok(outcome)
     }
 
@@ -436,7 +614,28 @@ impl DemoRunnerHost {
         payload_bytes: &[u8],
         ctx: &OperatorContext,
     ) -> anyhow::Result<FlowOutcome> {
-        let mut envelope = OperationEnvelope::new(op_id, payload_bytes, ctx);
+        let _span = profile::span(Phase::Flow, format_args!("{provider_type} {op_id}"));
+        let recorder = RunRecorder::start(domain, provider_type, op_id, payload_bytes, ctx);
+        if let Some(outcome) = self.circuit_refusal(provider_type, op_id, ctx) {
+            self.persist_run_record(recorder, &outcome, None);
+            return Ok(outcome);
+        }
+        let _permit =
+            match self
+                .quotas
+                .acquire(&ctx.tenant, ctx.team.as_deref(), payload_bytes.len())
+            {
+                Ok(permit) => permit,
+                Err(err) => {
+                    let outcome = quota_denied_outcome(&err);
+                    self.persist_run_record(recorder, &outcome, None);
+                    return Ok(outcome);
+                }
+            };
+        let mut envelope = {
+            let _span = profile::span(Phase::Encode, "envelope");
+            OperationEnvelope::new(op_id, payload_bytes, ctx)
+        };
         let pre_chain = self.resolve_hook_chain(HookStage::Pre, op_id);
         let pre_hook_outcome =
             self.evaluate_hook_chain(&pre_chain, HookStage::Pre, &mut envelope)?;
@@ -444,23 +643,49 @@ impl DemoRunnerHost {
         if let HookChainOutcome::Denied(reason) = pre_hook_outcome {
             envelope.status = OperationStatus::Denied;
             self.emit_post_sub(&envelope);
-            return Ok(FlowOutcome {
+            let outcome = FlowOutcome {
                 success: false,
                 output: Some(serde_json::to_value(&envelope).unwrap_or_else(|_| json!({}))),
                 raw: None,
                 error: Some(format!("operation denied by pre-hook: {reason}")),
                 mode: RunnerExecutionMode::Exec,
-            });
+            };
+            self.persist_run_record(recorder, &outcome, None);
+            return Ok(outcome);
         }
 
-        let outcome =
-            self.invoke_provider_op_inner(domain, provider_type, op_id, payload_bytes, ctx)?;
+        let (outcome, artifacts_dir) =
+            match self.invoke_provider_op_inner(domain, provider_type, op_id, payload_bytes, ctx) {
+                Ok(value) => value,
+                Err(err) => {
+                    let failed = FlowOutcome {
+                        success: false,
+                        output: None,
+                        raw: None,
+                        error: Some(err.to_string()),
+                        mode: RunnerExecutionMode::Exec,
+                    };
+                    self.persist_run_record(recorder, &failed, None);
+                    self.record_circuit(provider_type, op_id, ctx, Some(format!("{err:#}")));
+                    return Err(err);
+                }
+            };
+        self.persist_run_record(recorder, &outcome, artifacts_dir.as_deref());
+        self.record_circuit(
+            provider_type,
+            op_id,
+            ctx,
+            circuit_breaker::outcome_error(&outcome),
+        );
         envelope.status = if outcome.success {
             OperationStatus::Ok
         } else {
             OperationStatus::Err
         };
-        envelope.result_cbor = outcome.output.as_ref().and_then(json_to_canonical_cbor);
+        envelope.result_cbor = {
+            let _span = profile::span(Phase::Encode, "result_cbor");
+            outcome.output.as_ref().and_then(json_to_canonical_cbor)
+        };
 
         let post_chain = self.resolve_hook_chain(HookStage::Post, op_id);
         let _ = self.evaluate_hook_chain(&post_chain, HookStage::Post, &mut envelope)?;
@@ -468,6 +693,43 @@ impl DemoRunnerHost {
         Ok(outcome)
     }
 
+    /// Writes the run record for a finished invocation. Failures are logged, never
+    /// propagated, so tracing cannot break a flow.
+    fn persist_run_record(
+        &self,
+        recorder: RunRecorder,
+        outcome: &FlowOutcome,
+        artifacts_dir: Option<&Path>,
+    ) {
+        let _span = profile::span(Phase::Io, "run record");
+        let run_id = recorder.run_id().to_string();
+        let record = recorder.finish(outcome, artifacts_dir);
+        match run_record::write_record(&self.bundle_root, &record) {
+            Ok(path) => {
+                if self.debug_enabled {
+                    operator_log::debug(
+                        module_path!(),
+                        format!(
+                            "[demo dev] run recorded run_id={} path={}",
+                            run_id,
+                            path.display()
+                        ),
+                    );
+                }
+            }
+            Err(err) => operator_log::warn(
+                module_path!(),
+                format!("failed to write run record run_id={run_id}: {err}"),
+            ),
+        }
+        if let Err(err) = run_record::prune_records(&self.bundle_root, self.run_retention) {
+            operator_log::warn(
+                module_path!(),
+                format!("failed to prune run records: {err}"),
+            );
+        }
+    }
+
     fn invoke_provider_op_inner(
         &self,
         domain: Domain,
@@ -475,7 +737,7 @@ impl DemoRunnerHost {
         op_id: &str,
         payload_bytes: &[u8],
         ctx: &OperatorContext,
-    ) -> anyhow::Result<FlowOutcome> {
+    ) -> anyhow::Result<(FlowOutcome, Option<PathBuf>)> {
         let pack = self
             .catalog
             .get(&(domain, provider_type.to_string()))
@@ -505,8 +767,12 @@ impl DemoRunnerHost {
                 );
             }
             let run_dir = state_layout::run_dir(&self.bundle_root, domain, &pack.pack_id, flow_id)?;
-            std::fs::create_dir_all(&run_dir)?;
+            {
+                let _span = profile::span(Phase::Io, run_dir.display());
+                std::fs::create_dir_all(&run_dir)?;
+            }
 
+            let encode = profile::span(Phase::Encode, "payload");
             let render_outcome = self
                 .card_renderer
                 .render_if_needed(provider_type, payload_bytes)?;
@@ -515,8 +781,9 @@ impl DemoRunnerHost {
                     "payload": general_purpose::STANDARD.encode(&render_outcome.bytes)
                 })
             });
+            drop(encode);
 
-            let outcome = match &self.runner_mode {
+            let (outcome, artifacts_dir) = match &self.runner_mode {
                 RunnerMode::Exec => {
                     self.execute_with_runner_exec(domain, pack, flow_id, &payload, ctx, &run_dir)?
                 }
@@ -554,10 +821,18 @@ impl DemoRunnerHost {
                 ),
             );
 
-            return Ok(outcome);
+            return Ok((outcome, Some(artifacts_dir)));
         }
 
-        self.invoke_provider_component_op(domain, pack, provider_type, op_id, payload_bytes, ctx)
+        let outcome = self.invoke_provider_component_op(
+            domain,
+            pack,
+            provider_type,
+            op_id,
+            payload_bytes,
+            ctx,
+        )?;
+        Ok((outcome, None))
     }
 
     fn evaluate_hook_chain(
@@ -669,6 +944,13 @@ impl DemoRunnerHost {
         );
     }
 
+    /// The archive to hand the runner: checked against the bundle's trusted keys
+    /// and, when it has encrypted members, decrypted under state.
+    fn runnable_pack_path(&self, pack: &ProviderPack) -> anyhow::Result<PathBuf> {
+        PackArchive::open(&pack.path)?
+            .runnable_path(&state_layout::decrypted_packs_dir(&self.bundle_root))
+    }
+
     fn execute_with_runner_exec(
         &self,
         domain: Domain,
@@ -676,12 +958,13 @@ impl DemoRunnerHost {
         flow_id: &str,
         payload: &JsonValue,
         ctx: &OperatorContext,
-        _run_dir: &Path,
-    ) -> anyhow::Result<FlowOutcome> {
+        run_dir: &Path,
+    ) -> anyhow::Result<(FlowOutcome, PathBuf)> {
+        let scope = self.limits.scope(&ctx.tenant, ctx.team.as_deref(), flow_id);
         let request = runner_exec::RunRequest {
             root: self.bundle_root.clone(),
             domain,
-            pack_path: pack.path.clone(),
+            pack_path: self.runnable_pack_path(pack)?,
             pack_label: pack.pack_id.clone(),
             flow_id: flow_id.to_string(),
             tenant: ctx.tenant.clone(),
@@ -689,15 +972,28 @@ impl DemoRunnerHost {
             input: payload.clone(),
             dist_offline: true,
         };
-        let run_output = runner_exec::run_provider_pack_flow(request)?;
+        let Some(run_output) = limits::run_with_timeout(scope.timeout(), move || {
+            runner_exec::run_provider_pack_flow(request)
+        }) else {
+            return Ok((
+                scope.timed_out().outcome(RunnerExecutionMode::Exec),
+                run_dir.to_path_buf(),
+            ));
+        };
+        let run_output = run_output?;
         let parsed = read_transcript_outputs(&run_output.run_dir)?;
-        Ok(FlowOutcome {
+        let outcome = FlowOutcome {
             success: run_output.result.status == RunStatus::Success,
             output: parsed,
             raw: None,
             error: run_output.result.error.clone(),
             mode: RunnerExecutionMode::Exec,
-        })
+        };
+        let outcome = match scope.check_output(&outcome) {
+            Some(exceeded) => exceeded.outcome(RunnerExecutionMode::Exec),
+            None => outcome,
+        };
+        Ok((outcome, run_output.run_dir))
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -711,10 +1007,55 @@ impl DemoRunnerHost {
         run_dir: &Path,
         runner_binary: &Path,
         flavor: RunnerFlavor,
-    ) -> anyhow::Result<FlowOutcome> {
+    ) -> anyhow::Result<(FlowOutcome, PathBuf)> {
+        let scope = self.limits.scope(&ctx.tenant, ctx.team.as_deref(), flow_id);
+        let pack_path = self.runnable_pack_path(pack)?;
+        let limits = scope.process_limits();
+        let pooled = self.runner_pool.as_ref().and_then(|pool| {
+            pool.run(&PoolRequest {
+                runner: runner_binary,
+                flavor,
+                pack: &pack_path,
+                flow: flow_id,
+                input: payload,
+                dist_offline: true,
+                tenant: &ctx.tenant,
+                team: ctx.team.as_deref(),
+                artifacts_dir: Some(run_dir),
+                limits: &limits,
+            })
+        });
+        if let Some(pooled) = pooled {
+            let pooled = pooled?;
+            if let Some(exceeded) = pooled.limit_hit.and_then(|hit| scope.process_hit(hit)) {
+                return Ok((
+                    exceeded.outcome(RunnerExecutionMode::Integration),
+                    run_dir.to_path_buf(),
+                ));
+            }
+            let output = match pooled.output {
+                Some(output) => Some(output),
+                None => read_transcript_outputs(run_dir)?,
+            };
+            let raw = (!pooled.stdout.trim().is_empty()).then_some(pooled.stdout);
+            return Ok((
+                FlowOutcome {
+                    success: pooled.success,
+                    output,
+                    raw,
+                    error: if pooled.success {
+                        None
+                    } else {
+                        Some(pooled.error.unwrap_or_default())
+                    },
+                    mode: RunnerExecutionMode::Integration,
+                },
+                run_dir.to_path_buf(),
+            ));
+        }
         let output = run_flow_with_options(
             runner_binary,
-            &pack.path,
+            &pack_path,
             flow_id,
             payload,
             RunFlowOptions {
@@ -723,8 +1064,15 @@ impl DemoRunnerHost {
                 team: ctx.team.as_deref(),
                 artifacts_dir: Some(run_dir),
                 runner_flavor: flavor,
+                limits,
             },
         )?;
+        if let Some(exceeded) = output.limit_hit.and_then(|hit| scope.process_hit(hit)) {
+            return Ok((
+                exceeded.outcome(RunnerExecutionMode::Integration),
+                run_dir.to_path_buf(),
+            ));
+        }
         let mut parsed = output.parsed.clone();
         if parsed.is_none() {
             parsed = read_transcript_outputs(run_dir)?;
@@ -734,17 +1082,20 @@ impl DemoRunnerHost {
         } else {
             Some(output.stdout.clone())
         };
-        Ok(FlowOutcome {
-            success: output.status.success(),
-            output: parsed,
-            raw,
-            error: if output.status.success() {
-                None
-            } else {
-                Some(output.stderr.clone())
+        Ok((
+            FlowOutcome {
+                success: output.status.success(),
+                output: parsed,
+                raw,
+                error: if output.status.success() {
+                    None
+                } else {
+                    Some(output.stderr.clone())
+                },
+                mode: RunnerExecutionMode::Integration,
             },
-            mode: RunnerExecutionMode::Integration,
-        })
+            run_dir.to_path_buf(),
+        ))
     }
 
     pub fn invoke_provider_component_op_direct(
@@ -756,7 +1107,188 @@ impl DemoRunnerHost {
         payload_bytes: &[u8],
         ctx: &OperatorContext,
     ) -> anyhow::Result<FlowOutcome> {
-        self.invoke_provider_component_op(domain, pack, provider_id, op_id, payload_bytes, ctx)
+        self.invoke_provider_component_ops_batch(domain, pack, provider_id, ctx, |invoke| {
+            invoke(op_id, payload_bytes)
+        })
+    }
+
+    /// Runs the provider ops `calls` makes through its `invoke` argument against
+    /// one load of `pack`, so a chain like `render_plan` then `encode` loads the
+    /// component once. Each op is still circuit-guarded, quota-checked, limited and
+    /// recorded on its own, as with [`Self::invoke_provider_component_op_direct`].
+    pub fn invoke_provider_component_ops_batch<T, F>(
+        &self,
+        domain: Domain,
+        pack: &ProviderPack,
+        provider_id: &str,
+        ctx: &OperatorContext,
+        calls: F,
+    ) -> anyhow::Result<T>
+    where
+        F: FnOnce(&mut dyn FnMut(&str, &[u8]) -> anyhow::Result<FlowOutcome>) -> anyhow::Result<T>
+            + Send,
+        T: Send,
+    {
+        make_runtime_or_thread_scope(|runtime| {
+            self.run_ops_batch(
+                domain,
+                provider_id,
+                ctx,
+                |loaded, op_id, payload_bytes| {
+                    self.invoke_loaded_component_op(
+                        runtime,
+                        loaded,
+                        pack,
+                        provider_id,
+                        op_id,
+                        payload_bytes,
+                        ctx,
+                    )
+                },
+                calls,
+            )
+        })
+    }
+
+    /// Hands `calls` an `invoke` that keeps one `loaded` slot for the whole batch
+    /// and wraps every op in its own circuit check, quota permit and run record.
+    fn run_ops_batch<L, T, F>(
+        &self,
+        domain: Domain,
+        provider_id: &str,
+        ctx: &OperatorContext,
+        mut invoke_loaded: impl FnMut(&mut Option<L>, &str, &[u8]) -> anyhow::Result<FlowOutcome>,
+        calls: F,
+    ) -> anyhow::Result<T>
+    where
+        F: FnOnce(&mut dyn FnMut(&str, &[u8]) -> anyhow::Result<FlowOutcome>) -> anyhow::Result<T>,
+    {
+        let mut loaded = None;
+        let mut invoke = |op_id: &str, payload_bytes: &[u8]| {
+            self.invoke_guarded_op(domain, provider_id, op_id, payload_bytes, ctx, || {
+                invoke_loaded(&mut loaded, op_id, payload_bytes)
+            })
+        };
+        calls(&mut invoke)
+    }
+
+    fn invoke_guarded_op(
+        &self,
+        domain: Domain,
+        provider_id: &str,
+        op_id: &str,
+        payload_bytes: &[u8],
+        ctx: &OperatorContext,
+        invoke: impl FnOnce() -> anyhow::Result<FlowOutcome>,
+    ) -> anyhow::Result<FlowOutcome> {
+        let recorder = RunRecorder::start(domain, provider_id, op_id, payload_bytes, ctx);
+        if let Some(outcome) = self.circuit_refusal(provider_id, op_id, ctx) {
+            self.persist_run_record(recorder, &outcome, None);
+            return Ok(outcome);
+        }
+        let _permit =
+            match self
+                .quotas
+                .acquire(&ctx.tenant, ctx.team.as_deref(), payload_bytes.len())
+            {
+                Ok(permit) => permit,
+                Err(err) => {
+                    let outcome = quota_denied_outcome(&err);
+                    self.persist_run_record(recorder, &outcome, None);
+                    return Ok(outcome);
+                }
+            };
+        let outcome = match invoke() {
+            Ok(outcome) => outcome,
+            Err(err) => {
+                self.record_circuit(provider_id, op_id, ctx, Some(format!("{err:#}")));
+                return Err(err);
+            }
+        };
+        self.persist_run_record(recorder, &outcome, None);
+        self.record_circuit(
+            provider_id,
+            op_id,
+            ctx,
+            circuit_breaker::outcome_error(&outcome),
+        );
+        Ok(outcome)
+    }
+
+    /// Loads `pack` for direct provider invocation with the tenant's current secrets.
+    async fn load_provider_component(
+        &self,
+        pack: &ProviderPack,
+        ctx: &OperatorContext,
+    ) -> anyhow::Result<LoadedProvider> {
+        let host_config = Arc::new(build_demo_host_config(&ctx.tenant));
+        // Re-open the dev store on each load so newly-written secrets
+        // (e.g. from QA wizard submit) are visible without restarting the demo.
+        let fresh_secrets = secrets_gate::resolve_secrets_manager(
+            &self.bundle_root,
+            &ctx.tenant,
+            ctx.team.as_deref(),
+        )
+        .unwrap_or_else(|_| self.secrets_handle.clone());
+        let dev_store_display = fresh_secrets
+            .dev_store_path
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| "<default>".to_string());
+        operator_log::info(
+            module_path!(),
+            format!(
+                "secrets backend for wasm: using_env_fallback={} dev_store={}",
+                fresh_secrets.using_env_fallback, dev_store_display,
+            ),
+        );
+        operator_log::info(
+            module_path!(),
+            format!(
+                "exec secrets: dev_store={} env_fallback={}",
+                dev_store_display, fresh_secrets.using_env_fallback,
+            ),
+        );
+        let spawn = profile::span(Phase::Spawn, pack.path.display());
+        let pack_runtime = PackRuntime::load(
+            &pack.path,
+            host_config.clone(),
+            None,
+            Some(&pack.path),
+            None::<DynSessionStore>,
+            Some(self.state_store.clone()),
+            Arc::new(RunnerWasiPolicy::default()),
+            fresh_secrets.runtime_manager(Some(&pack.pack_id)),
+            None,
+            false,
+            ComponentResolution::default(),
+        )
+        .await?;
+        drop(spawn);
+        let provider_type = primary_provider_type(&pack.path)
+            .context("failed to determine provider type for direct invocation")?;
+        let env_value = env::var("GREENTIC_ENV").unwrap_or_else(|_| "<unset>".to_string());
+        let canonical_team = secrets_manager::canonical_team(ctx.team.as_deref()).into_owned();
+        let runner_dev_store_desc = self
+            .secrets_handle
+            .dev_store_path
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| "<none>".to_string());
+        eprintln!(
+            "secrets runner ctx: env={} tenant={} canonical_team={} provider_id={} pack_id={} dev_store_path={} using_env_fallback={}",
+            env_value,
+            ctx.tenant,
+            canonical_team,
+            provider_type,
+            pack.pack_id,
+            runner_dev_store_desc,
+            self.secrets_handle.using_env_fallback,
+        );
+        Ok(LoadedProvider {
+            runtime: pack_runtime,
+            provider_type,
+        })
     }
 
     fn invoke_provider_component_op(
@@ -768,72 +1300,43 @@ impl DemoRunnerHost {
         payload_bytes: &[u8],
         ctx: &OperatorContext,
     ) -> anyhow::Result<FlowOutcome> {
-        let payload = payload_bytes.to_vec();
-        let result = make_runtime_or_thread_scope(|runtime| {
-            runtime.block_on(async {
-            let host_config = Arc::new(build_demo_host_config(&ctx.tenant));
-            // Re-open the dev store on each invocation so newly-written secrets
-            // (e.g. from QA wizard submit) are visible without restarting the demo.
-            let fresh_secrets = secrets_gate::resolve_secrets_manager(
-                &self.bundle_root,
-                &ctx.tenant,
-                ctx.team.as_deref(),
-            )
-            .unwrap_or_else(|_| self.secrets_handle.clone());
-            let dev_store_display = fresh_secrets
-                .dev_store_path
-                .as_ref()
-                .map(|path| path.display().to_string())
-                .unwrap_or_else(|| "<default>".to_string());
-            operator_log::info(
-                module_path!(),
-                format!(
-                    "secrets backend for wasm: using_env_fallback={} dev_store={}",
-                    fresh_secrets.using_env_fallback, dev_store_display,
-                ),
-            );
-            operator_log::info(
-                module_path!(),
-                format!(
-                    "exec secrets: dev_store={} env_fallback={}",
-                    dev_store_display, fresh_secrets.using_env_fallback,
-                ),
-            );
-            let pack_runtime = PackRuntime::load(
-                &pack.path,
-                host_config.clone(),
-                None,
-                Some(&pack.path),
-                None::<DynSessionStore>,
-                Some(self.state_store.clone()),
-                Arc::new(RunnerWasiPolicy::default()),
-                fresh_secrets.runtime_manager(Some(&pack.pack_id)),
-                None,
-                false,
-                ComponentResolution::default(),
+        make_runtime_or_thread_scope(|runtime| {
+            self.invoke_loaded_component_op(
+                runtime,
+                &mut None,
+                pack,
+                provider_id,
+                op_id,
+                payload_bytes,
+                ctx,
             )
-            .await?;
-            let provider_type = primary_provider_type(&pack.path)
-                .context("failed to determine provider type for direct invocation")?;
-            let env_value = env::var("GREENTIC_ENV").unwrap_or_else(|_| "<unset>".to_string());
-            let canonical_team = secrets_manager::canonical_team(ctx.team.as_deref()).into_owned();
-            let runner_dev_store_desc = self
-                .secrets_handle
-                .dev_store_path
-                .as_ref()
-                .map(|path| path.display().to_string())
-                .unwrap_or_else(|| "<none>".to_string());
-            eprintln!(
-                "secrets runner ctx: env={} tenant={} canonical_team={} provider_id={} pack_id={} dev_store_path={} using_env_fallback={}",
-                env_value,
-                ctx.tenant,
-                canonical_team,
-                provider_type,
-                pack.pack_id,
-                runner_dev_store_desc,
-                self.secrets_handle.using_env_fallback,
-            );
-            let binding = pack_runtime.resolve_provider(None, Some(&provider_type))?;
+        })
+    }
+
+    /// Invokes `op_id`, loading `pack` into `loaded` first unless an earlier op of
+    /// the batch already did.
+    #[allow(clippy::too_many_arguments)]
+    fn invoke_loaded_component_op(
+        &self,
+        runtime: &TokioRuntime,
+        loaded: &mut Option<LoadedProvider>,
+        pack: &ProviderPack,
+        provider_id: &str,
+        op_id: &str,
+        payload_bytes: &[u8],
+        ctx: &OperatorContext,
+    ) -> anyhow::Result<FlowOutcome> {
+        let payload = payload_bytes.to_vec();
+        let scope = self.limits.scope(&ctx.tenant, ctx.team.as_deref(), op_id);
+        let timeout = scope.timeout();
+        let invocation = async {
+            if loaded.is_none() {
+                *loaded = Some(self.load_provider_component(pack, ctx).await?);
+            }
+            let loaded = loaded.as_ref().expect("provider component loaded");
+            let binding = loaded
+                .runtime
+                .resolve_provider(None, Some(&loaded.provider_type))?;
             let exec_ctx = ComponentExecCtx {
                 tenant: ComponentTenantCtx {
                     tenant: ctx.tenant.clone(),
@@ -850,20 +1353,35 @@ impl DemoRunnerHost {
                 flow_id: op_id.to_string(),
                 node_id: Some(op_id.to_string()),
             };
-            pack_runtime
+            loaded
+                .runtime
                 .invoke_provider(&binding, exec_ctx, op_id, payload)
                 .await
-        })
-        });
+        };
+        let result = match timeout {
+            Some(timeout) => runtime
+                .block_on(tokio::time::timeout(timeout, invocation))
+                .ok(),
+            None => Some(runtime.block_on(invocation)),
+        };
+        let Some(result) = result else {
+            return Ok(scope.timed_out().outcome(RunnerExecutionMode::Exec));
+        };
 
         match result {
-            Ok(value) => Ok(FlowOutcome {
-                success: true,
-                output: Some(value),
-                raw: None,
-                error: None,
-                mode: RunnerExecutionMode::Exec,
-            }),
+            Ok(value) => {
+                let outcome = FlowOutcome {
+                    success: true,
+                    output: Some(value),
+                    raw: None,
+                    error: None,
+                    mode: RunnerExecutionMode::Exec,
+                };
+                Ok(match scope.check_output(&outcome) {
+                    Some(exceeded) => exceeded.outcome(RunnerExecutionMode::Exec),
+                    None => outcome,
+                })
+            }
             Err(err) => {
                 let err_message = err.to_string();
                 let needs_context = needs_secret_context(&err_message);
@@ -889,17 +1407,16 @@ impl DemoRunnerHost {
     }
 }
 
+/// A provider pack loaded for direct component invocation.
+struct LoadedProvider {
+    runtime: PackRuntime,
+    provider_type: String,
+}
+
 pub fn primary_provider_type(pack_path: &Path) -> anyhow::Result<String> {
-    let file = std::fs::File::open(pack_path)?;
-    let mut archive = ZipArchive::new(file)?;
-    let mut manifest_entry = archive.by_name("manifest.cbor").map_err(|err| {
-        anyhow!(
-            "failed to open manifest.cbor in {}: {err}",
-            pack_path.display()
-        )
-    })?;
-    let mut bytes = Vec::new();
-    manifest_entry.read_to_end(&mut bytes)?;
+    let bytes = PackArchive::open(pack_path)?
+        .manifest_bytes()?
+        .ok_or_else(|| anyhow!("manifest.cbor missing from {}", pack_path.display()))?;
     let manifest = decode_pack_manifest(&bytes)
         .context("failed to decode pack manifest for provider introspection")?;
     let inline = manifest.provider_extension_inline().ok_or_else(|| {
@@ -1028,6 +1545,17 @@ fn capability_not_installed_outcome(cap_id: &str, op_name: &str, stable_id: &str
     }
 }
 
+fn quota_denied_outcome(err: &QuotaExceeded) -> FlowOutcome {
+    operator_log::warn(module_path!(), err.to_string());
+    FlowOutcome {
+        success: false,
+        output: Some(err.to_json()),
+        raw: None,
+        error: Some(format!("operation denied by {err}")),
+        mode: RunnerExecutionMode::Exec,
+    }
+}
+
 fn capability_route_error_outcome(cap_id: &str, op_name: &str, reason: String) -> FlowOutcome {
     FlowOutcome {
         success: false,
@@ -1047,6 +1575,7 @@ fn capability_route_error_outcome(cap_id: &str, op_name: &str, reason: String) -
 }
 
 fn read_transcript_outputs(run_dir: &Path) -> anyhow::Result<Option<JsonValue>> {
+    let _span = profile::span(Phase::Io, "transcript.jsonl");
     let path = run_dir.join("transcript.jsonl");
     if !path.exists() {
         return Ok(None);
@@ -1123,16 +1652,9 @@ fn validate_runner_binary(path: PathBuf) -> Option<PathBuf> {
 }
 
 fn pack_supports_provider_op(pack_path: &Path, op_id: &str) -> anyhow::Result<bool> {
-    let file = std::fs::File::open(pack_path)?;
-    let mut archive = ZipArchive::new(file)?;
-    let mut manifest_entry = archive.by_name("manifest.cbor").map_err(|err| {
-        anyhow!(
-            "failed to open manifest.cbor in {}: {err}",
-            pack_path.display()
-        )
-    })?;
-    let mut bytes = Vec::new();
-    manifest_entry.read_to_end(&mut bytes)?;
+    let bytes = PackArchive::open(pack_path)?
+        .manifest_bytes()?
+        .ok_or_else(|| anyhow!("manifest.cbor missing from {}", pack_path.display()))?;
     let manifest = decode_pack_manifest(&bytes)
         .context("failed to decode pack manifest for op support introspection")?;
     let Some(provider_ext) = manifest.provider_extension_inline() else {
@@ -1175,3 +1697,115 @@ fn payload_preview(bytes: &[u8]) -> String {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{DemoCircuitBreakerConfig, DemoQuotasConfig, QuotaLimits};
+
+    fn success(op_id: &str) -> FlowOutcome {
+        FlowOutcome {
+            success: true,
+            output: Some(json!({ "op": op_id })),
+            raw: None,
+            error: None,
+            mode: RunnerExecutionMode::Exec,
+        }
+    }
+
+    #[test]
+    fn batched_ops_share_one_load_and_are_guarded_one_by_one() -> anyhow::Result<()> {
+        let bundle = tempfile::tempdir()?;
+        let discovery = discovery::DiscoveryResult {
+            domains: discovery::DetectedDomains {
+                messaging: false,
+                events: false,
+                custom: Vec::new(),
+            },
+            providers: Vec::new(),
+        };
+        let secrets = secrets_gate::resolve_secrets_manager(bundle.path(), "demo", None)?;
+        let mut host = DemoRunnerHost::new(
+            bundle.path().to_path_buf(),
+            &discovery,
+            None,
+            secrets,
+            false,
+        )?;
+        host.quotas = QuotaEnforcer::new(
+            DemoQuotasConfig {
+                default: QuotaLimits {
+                    max_invocations_per_minute: Some(3),
+                    ..QuotaLimits::default()
+                },
+                ..DemoQuotasConfig::default()
+            },
+            None,
+        );
+        host.circuits = CircuitBreaker::new(
+            DemoCircuitBreakerConfig {
+                failure_threshold: 1,
+                ..DemoCircuitBreakerConfig::default()
+            },
+            None,
+        );
+        let ctx = OperatorContext {
+            tenant: "demo".to_string(),
+            team: None,
+            correlation_id: None,
+        };
+
+        let mut loads = 0;
+        let mut invoked = Vec::new();
+        let outcomes = host.run_ops_batch(
+            Domain::Messaging,
+            "messaging-telegram",
+            &ctx,
+            |loaded: &mut Option<()>, op_id, _payload| {
+                if loaded.is_none() {
+                    loads += 1;
+                    *loaded = Some(());
+                }
+                invoked.push(op_id.to_string());
+                if op_id == "send_payload" {
+                    anyhow::bail!("provider unreachable");
+                }
+                Ok(success(op_id))
+            },
+            |invoke| {
+                let plan = invoke("render_plan", b"{}")?;
+                let encoded = invoke("encode", b"{}")?;
+                assert!(invoke("send_payload", b"{}").is_err());
+                // One failure opens the circuit, so this send never reaches the pack.
+                let refused = invoke("send_payload", b"{}")?;
+                // The failed send used the last invocation of the minute.
+                let limited = invoke("render_plan", b"{}")?;
+                Ok([plan, encoded, refused, limited])
+            },
+        )?;
+        let [plan, encoded, refused, limited] = outcomes;
+
+        assert_eq!(loads, 1);
+        assert_eq!(invoked, ["render_plan", "encode", "send_payload"]);
+        assert!(plan.success && encoded.success);
+        assert_eq!(refused.output.expect("refusal")["code"], "circuit_open");
+        assert_eq!(limited.output.expect("refusal")["code"], "quota_exceeded");
+        assert_eq!(host.quotas.counters()["demo.default"].invocations, 3);
+
+        let mut recorded = run_record::list_records(bundle.path())?
+            .into_iter()
+            .map(|record| (record.op, record.status.as_str()))
+            .collect::<Vec<_>>();
+        recorded.sort();
+        assert_eq!(
+            recorded,
+            [
+                ("encode".to_string(), "success"),
+                ("render_plan".to_string(), "denied"),
+                ("render_plan".to_string(), "success"),
+                ("send_payload".to_string(), "failed"),
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,569 @@
+//! Warm runner processes for `--runner-binary` (`runner.pool` in greentic.demo.yaml).
+//!
+//! Without the pool every op spawns the runner, which then loads the pack, so each
+//! message pays process start-up. With `runner.pool.enabled` the host keeps up to
+//! `max_workers` runners per pack alive and hands them one invocation at a time as
+//! a JSON line on stdin:
+//!
+//! - A worker starts as [`runner_integration::worker_command`] and must print
+//!   `{"ready": true, "protocol": 1}` as its first line within
+//!   `handshake_timeout_secs`. A runner that cannot start, prints anything else or
+//!   exits does not speak the protocol; its binary is remembered and every call
+//!   spawns a one-shot runner as before.
+//! - A request is `{"id", "flow", "input", "tenant", "team", "artifacts_dir"}` and
+//!   the answer one line `{"id", "ok", "output", "stdout", "error"}`.
+//!   Workers run with `GREENTIC_STREAM=1`; [`flow_stream`] event lines they write
+//!   to stderr during a call go to the caller's sink.
+//! - A worker idle for `idle_timeout_secs` is stopped. One found dead when it is
+//!   checked out, or whose stdin is closed, is replaced and the request goes to the
+//!   new one. One that dies, passes the timeout or answers out of turn mid-request
+//!   is killed and the call fails: the op may already have had effects.
+//!
+//! Invocations with CPU or memory limits or a cgroup, and calls made while every
+//! worker of the pack is busy, also get a one-shot runner.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, anyhow};
+use serde::Deserialize;
+use serde_json::{Value as JsonValue, json};
+
+use crate::config::{self, DemoRunnerPoolConfig};
+use crate::flow_stream::{self, LiveFlow};
+use crate::operator_log;
+use crate::profile::{self, Phase};
+use crate::runner_integration::{self, LimitHit, ProcessLimits, RunnerFlavor};
+
+const PROTOCOL: u64 = 1;
+const REAP_INTERVAL: Duration = Duration::from_secs(30);
+
+/// One invocation for a pooled runner; mirrors [`runner_integration::RunFlowOptions`].
+pub struct PoolRequest<'a> {
+    pub runner: &'a Path,
+    pub flavor: RunnerFlavor,
+    pub pack: &'a Path,
+    pub flow: &'a str,
+    pub input: &'a JsonValue,
+    pub dist_offline: bool,
+    pub tenant: &'a str,
+    pub team: Option<&'a str>,
+    pub artifacts_dir: Option<&'a Path>,
+    pub limits: &'a ProcessLimits,
+}
+
+/// A pooled runner's answer to one invocation.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PooledOutput {
+    pub success: bool,
+    pub output: Option<JsonValue>,
+    pub stdout: String,
+    pub error: Option<String>,
+    /// Set when the call was stopped by one of [`PoolRequest::limits`].
+    pub limit_hit: Option<LimitHit>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    id: u64,
+    ok: bool,
+    #[serde(default)]
+    output: Option<JsonValue>,
+    #[serde(default)]
+    stdout: String,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct WorkerKey {
+    runner: PathBuf,
+    pack: PathBuf,
+    flavor: RunnerFlavor,
+    dist_offline: bool,
+}
+
+struct Worker {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    /// Where stream event lines on stderr go during the current call.
+    stream: Arc<Mutex<Option<flow_stream::Emitter>>>,
+    next_id: u64,
+    last_used: Instant,
+}
+
+/// What became of a request sent to a worker.
+enum Received {
+    /// The worker answered and can take the next call.
+    Answer(PooledOutput),
+    /// The worker is unusable; the call ends with this result.
+    Lost(anyhow::Result<PooledOutput>),
+}
+
+impl Worker {
+    fn start(key: &WorkerKey, handshake_timeout: Duration) -> anyhow::Result<Self> {
+        let _span = profile::span(Phase::Spawn, key.pack.display());
+        let mut command = runner_integration::worker_command(
+            &key.runner,
+            &key.pack,
+            key.flavor,
+            key.dist_offline,
+        );
+        command
+            .env(flow_stream::ENV_VAR, "1")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let mut child = command
+            .spawn()
+            .with_context(|| format!("spawn {}", key.runner.display()))?;
+        let stdin = child.stdin.take().ok_or_else(|| anyhow!("runner stdin"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow!("runner stdout"))?;
+        let (tx, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        let stream = Arc::new(Mutex::new(None::<flow_stream::Emitter>));
+        if let Some(stderr) = child.stderr.take() {
+            let pid = child.id();
+            let stream = stream.clone();
+            thread::spawn(move || {
+                for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                    let emitter = stream.lock().unwrap_or_else(|err| err.into_inner()).clone();
+                    if emitter.is_some_and(|emitter| emitter.emit_line(&line)) {
+                        continue;
+                    }
+                    operator_log::debug(module_path!(), format!("runner {pid}: {line}"));
+                }
+            });
+        }
+        let worker = Self {
+            child,
+            stdin,
+            lines,
+            stream,
+            next_id: 1,
+            last_used: Instant::now(),
+        };
+        let hello = match worker.lines.recv_timeout(handshake_timeout) {
+            Ok(line) => line,
+            Err(RecvTimeoutError::Timeout) => {
+                return Err(anyhow!(
+                    "no handshake within {}s",
+                    handshake_timeout.as_secs()
+                ));
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err(anyhow!("runner exited before the handshake"));
+            }
+        };
+        let ready = serde_json::from_str::<JsonValue>(&hello).is_ok_and(|hello| {
+            hello.get("ready") == Some(&JsonValue::Bool(true))
+                && hello.get("protocol").and_then(JsonValue::as_u64) == Some(PROTOCOL)
+        });
+        if !ready {
+            return Err(anyhow!("unexpected handshake {hello:?}"));
+        }
+        Ok(worker)
+    }
+
+    fn stream_to(&self, live: Option<&LiveFlow>) {
+        *self.stream.lock().unwrap_or_else(|err| err.into_inner()) = live.map(LiveFlow::emitter);
+    }
+
+    fn is_alive(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    fn send(&mut self, line: &str) -> std::io::Result<()> {
+        self.stdin.write_all(line.as_bytes())?;
+        self.stdin.flush()
+    }
+
+    fn receive(&mut self, id: u64, limits: &ProcessLimits) -> Received {
+        let line = match limits.timeout {
+            Some(timeout) => self.lines.recv_timeout(timeout),
+            None => self
+                .lines
+                .recv()
+                .map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        let line = match line {
+            Ok(line) => line,
+            Err(RecvTimeoutError::Timeout) => {
+                return Received::Lost(Ok(PooledOutput {
+                    limit_hit: Some(LimitHit::Timeout),
+                    ..PooledOutput::default()
+                }));
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                let status = self
+                    .child
+                    .wait()
+                    .map(|status| status.to_string())
+                    .unwrap_or_else(|err| err.to_string());
+                return Received::Lost(Ok(PooledOutput {
+                    error: Some(format!("pooled runner exited during the call ({status})")),
+                    ..PooledOutput::default()
+                }));
+            }
+        };
+        self.last_used = Instant::now();
+        let response = match serde_json::from_str::<Response>(&line) {
+            Ok(response) if response.id == id => response,
+            Ok(response) => {
+                return Received::Lost(Err(anyhow!(
+                    "pooled runner answered request {} while {id} was pending",
+                    response.id
+                )));
+            }
+            Err(err) => {
+                return Received::Lost(Err(anyhow!("pooled runner sent an invalid answer: {err}")));
+            }
+        };
+        let over_cap = limits
+            .max_output_bytes
+            .is_some_and(|max| line.len() as u64 > max);
+        Received::Answer(PooledOutput {
+            success: response.ok,
+            output: response.output,
+            stdout: response.stdout,
+            error: response.error,
+            limit_hit: over_cap.then_some(LimitHit::OutputBytes),
+        })
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[derive(Default)]
+struct PoolState {
+    idle: HashMap<WorkerKey, Vec<Worker>>,
+    /// Workers handed out, by key; counted toward `max_workers`.
+    busy: HashMap<WorkerKey, usize>,
+    /// Runner binaries that failed to start or to answer the handshake.
+    unsupported: HashSet<PathBuf>,
+}
+
+pub struct RunnerPool {
+    config: DemoRunnerPoolConfig,
+    state: Mutex<PoolState>,
+}
+
+impl RunnerPool {
+    /// A pool that stops idle workers from a background thread; `None` when the
+    /// config leaves it off.
+    pub fn new(config: DemoRunnerPoolConfig) -> Option<Arc<Self>> {
+        if !config.enabled {
+            return None;
+        }
+        let interval = REAP_INTERVAL.min(Duration::from_secs(config.idle_timeout_secs.max(1)));
+        let pool = Arc::new(Self {
+            config,
+            state: Mutex::new(PoolState::default()),
+        });
+        let weak: Weak<Self> = Arc::downgrade(&pool);
+        thread::spawn(move || {
+            loop {
+                thread::sleep(interval);
+                let Some(pool) = weak.upgrade() else { break };
+                pool.reap_idle();
+            }
+        });
+        Some(pool)
+    }
+
+    /// Reads `runner.pool` from the bundle's greentic.demo.yaml.
+    pub fn from_bundle(bundle_root: &Path) -> Option<Arc<Self>> {
+        let config_path = bundle_root.join("greentic.demo.yaml");
+        if !config_path.exists() {
+            return None;
+        }
+        let config = config::load_demo_config(&config_path)
+            .map(|config| config.runner.pool)
+            .unwrap_or_default();
+        Self::new(config)
+    }
+
+    /// Runs `request` on a warm worker. `None` means the call needs a one-shot
+    /// runner: limits that hold a whole process, a runner without `--stdio`
+    /// support, or every worker of the pack busy.
+    pub fn run(&self, request: &PoolRequest<'_>) -> Option<anyhow::Result<PooledOutput>> {
+        if request.limits.needs_own_process() {
+            return None;
+        }
+        let key = WorkerKey {
+            runner: request.runner.to_path_buf(),
+            pack: request.pack.to_path_buf(),
+            flavor: request.flavor,
+            dist_offline: request.dist_offline,
+        };
+        let mut worker = self.checkout(&key)?;
+        let _span = profile::span(
+            Phase::Flow,
+            format_args!("{} {}", request.pack.display(), request.flow),
+        );
+        let live = flow_stream::live();
+        worker.stream_to(live.as_ref());
+        let id = worker.next_id;
+        worker.next_id += 1;
+        let line = encode_request(id, request);
+        if let Err(err) = worker.send(&line) {
+            // Nothing reached the runner, so a fresh one can take the call.
+            operator_log::warn(
+                module_path!(),
+                format!(
+                    "pooled runner {} for {} stopped reading ({err}); replacing it",
+                    worker.child.id(),
+                    key.pack.display()
+                ),
+            );
+            drop(worker);
+            self.release(&key);
+            worker = self.checkout(&key)?;
+            worker.stream_to(live.as_ref());
+            worker.next_id = id + 1;
+            if let Err(err) = worker.send(&line) {
+                drop(worker);
+                self.release(&key);
+                return Some(Err(anyhow!("pooled runner stopped reading: {err}")));
+            }
+        }
+        match worker.receive(id, request.limits) {
+            Received::Answer(output) => {
+                worker.stream_to(None);
+                self.checkin(key, worker);
+                Some(Ok(output))
+            }
+            Received::Lost(result) => {
+                drop(worker);
+                self.release(&key);
+                Some(result)
+            }
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, PoolState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// An idle live worker, or a new one when the pack is below `max_workers`.
+    fn checkout(&self, key: &WorkerKey) -> Option<Worker> {
+        let mut state = self.lock();
+        if state.unsupported.contains(&key.runner) {
+            return None;
+        }
+        while let Some(mut worker) = state.idle.get_mut(key).and_then(Vec::pop) {
+            if worker.is_alive() {
+                *state.busy.entry(key.clone()).or_default() += 1;
+                return Some(worker);
+            }
+            operator_log::warn(
+                module_path!(),
+                format!(
+                    "pooled runner {} for {} exited while idle; replacing it",
+                    worker.child.id(),
+                    key.pack.display()
+                ),
+            );
+        }
+        let busy = state.busy.entry(key.clone()).or_default();
+        if *busy >= self.config.max_workers {
+            return None;
+        }
+        *busy += 1;
+        drop(state);
+        let handshake_timeout = Duration::from_secs(self.config.handshake_timeout_secs);
+        match Worker::start(key, handshake_timeout) {
+            Ok(worker) => Some(worker),
+            Err(err) => {
+                self.release(key);
+                self.lock().unsupported.insert(key.runner.clone());
+                operator_log::warn(
+                    module_path!(),
+                    format!(
+                        "runner pool off for {}: {err:#}; spawning it per invocation",
+                        key.runner.display()
+                    ),
+                );
+                None
+            }
+        }
+    }
+
+    fn checkin(&self, key: WorkerKey, worker: Worker) {
+        self.release(&key);
+        self.lock().idle.entry(key).or_default().push(worker);
+    }
+
+    fn release(&self, key: &WorkerKey) {
+        if let Some(busy) = self.lock().busy.get_mut(key) {
+            *busy = busy.saturating_sub(1);
+        }
+    }
+
+    fn reap_idle(&self) {
+        let idle_timeout = Duration::from_secs(self.config.idle_timeout_secs);
+        let mut state = self.lock();
+        for workers in state.idle.values_mut() {
+            workers.retain(|worker| worker.last_used.elapsed() < idle_timeout);
+        }
+        state.idle.retain(|_, workers| !workers.is_empty());
+    }
+}
+
+fn encode_request(id: u64, request: &PoolRequest<'_>) -> String {
+    let _span = profile::span(Phase::Encode, "pool request");
+    let mut line = json!({
+        "id": id,
+        "flow": request.flow,
+        "input": request.input,
+        "tenant": request.tenant,
+        "team": request.team,
+        "artifacts_dir": request.artifacts_dir.map(|dir| dir.display().to_string()),
+    })
+    .to_string();
+    line.push('\n');
+    line
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// A runner that speaks the protocol and answers with its pid. With `once` it
+    /// exits after the first answer.
+    const STDIO_RUNNER: &str = r#"#!/bin/sh
+echo '{"ready":true,"protocol":1}'
+while IFS= read -r line; do
+  id=$(printf '%s' "$line" | sed 's/.*"id":\([0-9]*\).*/\1/')
+  case "$line" in
+    *'"flow":"slow"'*) sleep 5 ;;
+  esac
+  echo "{\"id\":$id,\"ok\":true,\"output\":{\"pid\":$$}}"
+  [ "$ONCE" = 1 ] && exit 0
+done
+"#;
+
+    fn script(dir: &Path, name: &str, body: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, body).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    fn pool() -> Arc<RunnerPool> {
+        RunnerPool::new(DemoRunnerPoolConfig {
+            enabled: true,
+            handshake_timeout_secs: 5,
+            ..DemoRunnerPoolConfig::default()
+        })
+        .unwrap()
+    }
+
+    fn call(pool: &RunnerPool, runner: &Path, flow: &str, limits: &ProcessLimits) -> PooledOutput {
+        pool.run(&PoolRequest {
+            runner,
+            flavor: RunnerFlavor::RunSubcommand,
+            pack: Path::new("pack.gtpack"),
+            flow,
+            input: &json!({"text": "hi"}),
+            dist_offline: true,
+            tenant: "demo",
+            team: None,
+            artifacts_dir: None,
+            limits,
+        })
+        .expect("pooled")
+        .unwrap()
+    }
+
+    fn pid(output: &PooledOutput) -> u64 {
+        output.output.as_ref().unwrap()["pid"].as_u64().unwrap()
+    }
+
+    #[test]
+    fn reuses_workers_and_replaces_dead_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let runner = script(dir.path(), "runner", STDIO_RUNNER);
+        let pool = pool();
+        let limits = ProcessLimits::default();
+        let first = call(&pool, &runner, "send", &limits);
+        assert!(first.success);
+        assert_eq!(pid(&call(&pool, &runner, "send", &limits)), pid(&first));
+
+        let limits = ProcessLimits {
+            timeout: Some(Duration::from_millis(200)),
+            ..ProcessLimits::default()
+        };
+        let slow = call(&pool, &runner, "slow", &limits);
+        assert_eq!(slow.limit_hit, Some(LimitHit::Timeout));
+        assert_ne!(pid(&call(&pool, &runner, "send", &limits)), pid(&first));
+
+        let once = script(
+            dir.path(),
+            "runner-once",
+            &STDIO_RUNNER.replace("\"$ONCE\" = 1", "1 = 1"),
+        );
+        let first = call(&pool, &once, "send", &limits);
+        thread::sleep(Duration::from_millis(300));
+        assert_ne!(pid(&call(&pool, &once, "send", &limits)), pid(&first));
+    }
+
+    #[test]
+    fn falls_back_for_runners_without_stdio_and_process_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let legacy = script(
+            dir.path(),
+            "runner",
+            "#!/bin/sh\necho 'unknown flag --stdio'\nexit 2\n",
+        );
+        let pool = pool();
+        let request = |runner: &Path, limits: &ProcessLimits| {
+            pool.run(&PoolRequest {
+                runner,
+                flavor: RunnerFlavor::RunnerCli,
+                pack: Path::new("pack.gtpack"),
+                flow: "send",
+                input: &json!({}),
+                dist_offline: false,
+                tenant: "demo",
+                team: Some("default"),
+                artifacts_dir: None,
+                limits,
+            })
+            .is_none()
+        };
+        assert!(request(&legacy, &ProcessLimits::default()));
+        assert!(pool.lock().unsupported.contains(&legacy));
+
+        let runner = script(dir.path(), "stdio-runner", STDIO_RUNNER);
+        let cpu = ProcessLimits {
+            max_cpu_secs: Some(10),
+            ..ProcessLimits::default()
+        };
+        assert!(request(&runner, &cpu));
+        assert!(!request(&runner, &ProcessLimits::default()));
+    }
+}
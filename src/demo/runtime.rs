@@ -6,6 +6,12 @@ use std::collections::{BTreeMap, BTreeSet};
 
 use anyhow::Context;
 
+use crate::bus;
+use crate::demo::circuit_breaker::{self, BreakerState};
+use crate::demo::health;
+use crate::demo::ports;
+use crate::demo::quotas::{self, QuotaCounters};
+use crate::jetstream;
 use crate::operator_log;
 use crate::runtime_state::{
     RuntimePaths, persist_service_manifest, read_service_manifest, remove_service_manifest,
@@ -15,12 +21,12 @@ use crate::services;
 use crate::supervisor;
 
 use crate::cloudflared::{self, CloudflaredConfig};
-use crate::config::{DemoConfig, DemoSubscriptionsMode};
+use crate::config::{DemoConfig, DemoJetStreamConfig, DemoSubscriptionsMode};
 use crate::ngrok::{self, NgrokConfig};
 
 use crate::subscriptions_universal::{
-    build_runner, ensure_desired_subscriptions, scheduler::Scheduler, service::SubscriptionService,
-    state_root, store::SubscriptionStore,
+    alerts::SubscriptionAlerts, build_runner, ensure_desired_subscriptions, scheduler::Scheduler,
+    service::SubscriptionService, state_root, store::SubscriptionStore,
 };
 
 struct ServiceSummary {
@@ -260,7 +266,12 @@ fn spawn_universal_subscriptions_service(
     let desired = &config.services.subscriptions.universal.desired;
     let (runner_host, context) = build_runner(bundle_root, tenant, team_override.clone())?;
     let store = SubscriptionStore::new(state_root(bundle_root));
-    let scheduler = Scheduler::new(SubscriptionService::new(runner_host, context), store);
+    let alerts = SubscriptionAlerts::new(
+        config.services.subscriptions.universal.alerts.clone(),
+        Some(bus::for_scope(bundle_root, tenant, team)),
+    );
+    let scheduler =
+        Scheduler::new(SubscriptionService::new(runner_host, context), store).with_alerts(alerts);
 
     ensure_desired_subscriptions(
         bundle_root,
@@ -396,6 +407,8 @@ pub enum NatsMode {
     Off,
     On,
     External,
+    /// Local or external NATS with per-tenant JetStream streams for ingress/egress.
+    JetStream,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -405,6 +418,7 @@ pub fn demo_up(
     team: Option<&str>,
     nats_url: Option<&str>,
     nats_mode: NatsMode,
+    jetstream_config: &DemoJetStreamConfig,
     messaging_enabled: bool,
     cloudflared: Option<CloudflaredConfig>,
     ngrok: Option<NgrokConfig>,
@@ -439,7 +453,7 @@ pub fn demo_up(
                 bundle_root.display()
             ),
         );
-        let handle = cloudflared::start_quick_tunnel(&paths, &config, &cloudflared_log)?;
+        let handle = cloudflared::start_tunnel(&paths, &config, &cloudflared_log)?;
         operator_log::info(
             module_path!(),
             format!(
@@ -537,7 +551,7 @@ pub fn demo_up(
     }
 
     let mut resolved_nats_url = nats_url.map(|value| value.to_string());
-    if matches!(nats_mode, NatsMode::On) && resolved_nats_url.is_none() {
+    if matches!(nats_mode, NatsMode::On | NatsMode::JetStream) && resolved_nats_url.is_none() {
         match operator_log::reserve_service_log(log_dir, "nats") {
             Ok(nats_log) => {
                 operator_log::info(
@@ -604,6 +618,49 @@ pub fn demo_up(
         }
     }
 
+    if matches!(nats_mode, NatsMode::JetStream) {
+        match resolved_nats_url.as_deref() {
+            Some(url) => {
+                let binding = jetstream::JetStreamBinding::for_scope(url, tenant, team_id);
+                jetstream::ensure_streams(&binding, jetstream_config)
+                    .with_context(|| format!("failed to set up jetstream streams on {url}"))?;
+                jetstream::write_binding(&paths, &binding)?;
+                operator_log::info(
+                    module_path!(),
+                    format!(
+                        "jetstream ready url={} ingress={} egress={}",
+                        url, binding.ingress.name, binding.egress.name
+                    ),
+                );
+                let mut summary = ServiceSummary::new("jetstream", None);
+                summary.add_detail(format!("url={url}"));
+                summary.add_detail(format!(
+                    "ingress={} ({})",
+                    binding.ingress.name,
+                    binding.ingress.subjects.join(",")
+                ));
+                summary.add_detail(format!(
+                    "egress={} ({})",
+                    binding.egress.name,
+                    binding.egress.subjects.join(",")
+                ));
+                service_summaries.push(summary);
+            }
+            None => {
+                return Err(anyhow::anyhow!(
+                    "--nats=jetstream needs a running NATS server; pass --nats-url or let demo start spawn one"
+                ));
+            }
+        }
+    } else {
+        jetstream::remove_binding(&paths)?;
+        if matches!(nats_mode, NatsMode::Off) {
+            let mut summary = ServiceSummary::new("bus", None);
+            summary.add_detail("in-process (no NATS)".to_string());
+            service_summaries.push(summary);
+        }
+    }
+
     let run_gsm_services = matches!(nats_mode, NatsMode::On);
     if messaging_enabled && run_gsm_services {
         let env_map = build_env(
@@ -728,7 +785,7 @@ pub fn demo_up_services(
             module_path!(),
             format!("starting cloudflared log={}", cloudflared_log.display()),
         );
-        let handle = cloudflared::start_quick_tunnel(&paths, &cfg, &cloudflared_log)?;
+        let handle = cloudflared::start_tunnel(&paths, &cfg, &cloudflared_log)?;
         let mut domain_labels = Vec::new();
         if discovery.domains.messaging {
             domain_labels.push("messaging");
@@ -962,20 +1019,73 @@ pub fn demo_up_services(
     Ok(())
 }
 
+/// How `demo status` reports.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StatusOptions {
+    pub verbose: bool,
+    pub json: bool,
+    /// Adds per-provider health read from the bundle around `state_dir`.
+    pub deep: bool,
+}
+
 pub fn demo_status_runtime(
     state_dir: &Path,
     tenant: &str,
     team: &str,
-    verbose: bool,
+    options: StatusOptions,
 ) -> anyhow::Result<()> {
     let paths = RuntimePaths::new(state_dir, tenant, team);
     let statuses = supervisor::read_status(&paths)?;
+    let providers = if options.deep {
+        let bundle = state_dir.parent().unwrap_or(Path::new("."));
+        Some(health::provider_health(bundle, tenant, team)?)
+    } else {
+        None
+    };
+    if options.json {
+        let services = statuses
+            .iter()
+            .map(|status| {
+                serde_json::json!({
+                    "id": status.id.as_str(),
+                    "running": status.running,
+                    "pid": status.pid,
+                    "log": status.log_path,
+                    "last_error": status.last_error,
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut document = serde_json::json!({
+            "tenant": tenant,
+            "team": team,
+            "services": services,
+            "ports": ports::read_ports(state_dir)?,
+            "quota": quotas::read_usage(state_dir)?.get(&paths.key()),
+            "circuits": circuit_breaker::read_circuits(state_dir)?
+                .remove(&paths.key())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(provider, state)| {
+                    let status = state.status(chrono::Utc::now());
+                    let mut value = serde_json::to_value(&state)?;
+                    value["provider"] = provider.into();
+                    value["status"] = status.as_str().into();
+                    value["error_rate"] = state.error_rate().into();
+                    Ok(value)
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?,
+        });
+        if let Some(providers) = providers {
+            document["providers"] = serde_json::to_value(providers)?;
+        }
+        println!("{}", serde_json::to_string_pretty(&document)?);
+        return Ok(());
+    }
     if statuses.is_empty() {
         println!(
             "{}",
             crate::operator_i18n::tr("demo.runtime.none_running", "none running")
         );
-        return Ok(());
     }
     for status in statuses {
         let state = if status.running {
@@ -987,7 +1097,7 @@ pub fn demo_status_runtime(
             .pid
             .map(|value| value.to_string())
             .unwrap_or_else(|| "-".to_string());
-        if verbose {
+        if options.verbose {
             println!(
                 "{}: {} (pid={}, log={})",
                 status.id.as_str(),
@@ -999,9 +1109,159 @@ pub fn demo_status_runtime(
             println!("{}: {} (pid={})", status.id.as_str(), &state, pid);
         }
     }
+    if let Some(ports) = ports::read_ports(state_dir)? {
+        let gateway = ports.gateway_port.to_string();
+        let line = match ports.nats_port {
+            Some(nats) => crate::operator_i18n::trf(
+                "demo.runtime.ports",
+                "ports: gateway={} nats={}",
+                &[&gateway, &nats.to_string()],
+            ),
+            None => crate::operator_i18n::trf(
+                "demo.runtime.ports_gateway_only",
+                "ports: gateway={}",
+                &[&gateway],
+            ),
+        };
+        println!("{line}");
+    }
+    if let Some(binding) = jetstream::read_binding(&paths)? {
+        print_jetstream_lag(&binding);
+    }
+    if let Some(counters) = quotas::read_usage(state_dir)?.get(&paths.key()) {
+        print_quota_counters(&paths.key(), counters);
+    }
+    if let Some(circuits) = circuit_breaker::read_circuits(state_dir)?.get(&paths.key()) {
+        for (provider, state) in circuits {
+            print_circuit(provider, state);
+        }
+    }
+    for provider in providers.iter().flatten() {
+        print_provider_health(provider, options.verbose);
+    }
     Ok(())
 }
 
+fn print_provider_health(provider: &health::ProviderHealth, verbose: bool) {
+    let setup = match provider.setup.at {
+        Some(at) => format!("{}@{}", provider.setup.status.as_str(), at.to_rfc3339()),
+        None => provider.setup.status.as_str().to_string(),
+    };
+    let secrets = match &provider.secrets.error {
+        Some(_) => "?".to_string(),
+        None => format!(
+            "{}/{}",
+            provider.secrets.required - provider.secrets.missing.len(),
+            provider.secrets.required
+        ),
+    };
+    let digest = provider
+        .pack_digest
+        .as_deref()
+        .map(|digest| digest.get(..12).unwrap_or(digest))
+        .unwrap_or("-");
+    println!(
+        "provider {} ({}): setup={} secrets={} last_send={} last_ingress={} subscriptions={} digest={}",
+        provider.provider,
+        provider.domain,
+        setup,
+        secrets,
+        provider.last_send.as_deref().unwrap_or("-"),
+        provider.last_ingress.as_deref().unwrap_or("-"),
+        provider.subscriptions,
+        digest
+    );
+    if !provider.secrets.missing.is_empty() {
+        println!("  missing secrets: {}", provider.secrets.missing.join(", "));
+    }
+    if let Some(err) = provider.secrets.error.as_ref().filter(|_| verbose) {
+        println!("  secrets unreadable: {err}");
+    }
+}
+
+fn print_quota_counters(key: &str, counters: &QuotaCounters) {
+    let with_limit = |value: u32, limit: Option<u32>| match limit {
+        Some(limit) => format!("{value}/{limit}"),
+        None => value.to_string(),
+    };
+    let rejected = if counters.rejected.is_empty() {
+        "0".to_string()
+    } else {
+        counters
+            .rejected
+            .iter()
+            .map(|(quota, count)| format!("{quota}:{count}"))
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    println!(
+        "quota {}: invocations={} last_minute={} in_flight={} rejected={}",
+        key,
+        counters.invocations,
+        with_limit(
+            counters.last_minute,
+            counters.limits.max_invocations_per_minute
+        ),
+        with_limit(counters.in_flight, counters.limits.max_concurrent),
+        rejected
+    );
+}
+
+fn print_circuit(provider: &str, state: &BreakerState) {
+    let status = state.status(chrono::Utc::now());
+    let until = match (status, state.open_until) {
+        (circuit_breaker::CircuitStatus::Open, Some(until)) => {
+            format!(" until={}", until.to_rfc3339())
+        }
+        _ => String::new(),
+    };
+    println!(
+        "circuit {}: {}{} consecutive_failures={} error_rate={:.1}% ({}/{}) trips={}",
+        provider,
+        status.as_str(),
+        until,
+        state.consecutive_failures,
+        state.error_rate() * 100.0,
+        state.failures,
+        state.successes + state.failures,
+        state.trips
+    );
+    if let Some(error) = &state.last_error {
+        println!("  last error: {}", crate::redaction::redact_text(error));
+    }
+}
+
+fn print_jetstream_lag(binding: &jetstream::JetStreamBinding) {
+    match jetstream::stream_lag(binding) {
+        Ok(lags) => {
+            for lag in lags {
+                println!(
+                    "{}",
+                    crate::operator_i18n::trf(
+                        "demo.runtime.jetstream_lag",
+                        "jetstream {}: messages={} consumer={} pending={} ack_pending={}",
+                        &[
+                            &lag.stream,
+                            &lag.messages.to_string(),
+                            &lag.consumer,
+                            &lag.num_pending.to_string(),
+                            &lag.num_ack_pending.to_string(),
+                        ]
+                    )
+                );
+            }
+        }
+        Err(err) => eprintln!(
+            "{}",
+            crate::operator_i18n::trf(
+                "demo.runtime.warn_jetstream_unreachable",
+                "Warning: unable to read JetStream lag from {}: {}",
+                &[&binding.url, &err.to_string()]
+            )
+        ),
+    }
+}
+
 pub fn demo_logs_runtime(
     state_dir: &Path,
     log_dir: &Path,
@@ -1010,13 +1270,7 @@ pub fn demo_logs_runtime(
     service: &str,
     tail: bool,
 ) -> anyhow::Result<()> {
-    let log_dir = resolve_manifest_log_dir(state_dir, tenant, team, log_dir)?;
-    let log_path = if service == "operator" {
-        log_dir.join("operator.log")
-    } else {
-        let tenant_log_path = tenant_log_path(&log_dir, service, tenant, team)?;
-        select_log_path(&log_dir, service, tenant, &tenant_log_path)
-    };
+    let log_path = demo_log_path(state_dir, log_dir, tenant, team, service)?;
     if tail {
         return services::tail_log(&log_path);
     }
@@ -1027,6 +1281,22 @@ pub fn demo_logs_runtime(
     Ok(())
 }
 
+/// Log file `demo logs` reads for `service` (`operator` or a supervised service).
+pub fn demo_log_path(
+    state_dir: &Path,
+    log_dir: &Path,
+    tenant: &str,
+    team: &str,
+    service: &str,
+) -> anyhow::Result<PathBuf> {
+    let log_dir = resolve_manifest_log_dir(state_dir, tenant, team, log_dir)?;
+    if service == "operator" {
+        return Ok(log_dir.join("operator.log"));
+    }
+    let tenant_log_path = tenant_log_path(&log_dir, service, tenant, team)?;
+    Ok(select_log_path(&log_dir, service, tenant, &tenant_log_path))
+}
+
 pub fn demo_down_runtime(
     state_dir: &Path,
     tenant: &str,
@@ -1274,7 +1544,7 @@ struct DemoEndpoints {
     gateway_port: u16,
 }
 
-fn read_last_lines(path: &Path, count: usize) -> anyhow::Result<String> {
+pub fn read_last_lines(path: &Path, count: usize) -> anyhow::Result<String> {
     if !path.exists() {
         return Err(anyhow::anyhow!(
             "Log file does not exist: {}",
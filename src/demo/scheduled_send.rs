@@ -0,0 +1,321 @@
+//! Messages `demo send --at/--every` schedules instead of sending right away.
+//!
+//! Each one is a JSON file under `state/timers/<tenant>.<team>/sends/<id>.json`. The
+//! timer scheduler of a running `demo start` for that tenant/team sends it when it
+//! is due and records the outcome in the same file. The file stays after the last
+//! run, without `next_at`, so `demo timers sends` can show how it went.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, anyhow};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+use crate::api;
+use crate::messaging_universal::attachments;
+use crate::operator_log;
+use crate::runtime_state;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScheduledSend {
+    pub id: String,
+    pub provider: String,
+    pub tenant: String,
+    pub team: String,
+    pub env: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "JsonMap::is_empty")]
+    pub args: JsonMap<String, JsonValue>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub to: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to_kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub card: Option<JsonValue>,
+    /// Read again at each send, so edits to the files show up.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<PathBuf>,
+    pub max_attachment_bytes: u64,
+    pub created_at: DateTime<Utc>,
+    /// `None` once the last run is done.
+    pub next_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub every_seconds: Option<u64>,
+    /// Runs left, counting the next one; `None` repeats until cancelled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remaining: Option<u32>,
+    #[serde(default)]
+    pub runs: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_run: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+impl ScheduledSend {
+    /// Records a run at `ran_at` and moves `next_at` on. Windows missed while no
+    /// `demo start` was running are skipped rather than sent in a burst.
+    pub fn advance(&mut self, ran_at: DateTime<Utc>, error: Option<String>) {
+        self.runs += 1;
+        self.last_run = Some(ran_at);
+        self.last_error = error;
+        self.remaining = self.remaining.map(|left| left.saturating_sub(1));
+        let next = match (self.next_at, self.every_seconds) {
+            (Some(due), Some(every)) if self.remaining != Some(0) => {
+                let step = chrono::Duration::seconds(i64::try_from(every).unwrap_or(i64::MAX));
+                let mut next = due + step;
+                while next <= ran_at {
+                    next += step;
+                }
+                Some(next)
+            }
+            _ => None,
+        };
+        self.next_at = next;
+    }
+
+    fn request(&self, bundle: &Path) -> anyhow::Result<api::SendMessage> {
+        let attachments = self
+            .attachments
+            .iter()
+            .map(|path| attachments::load_attachment(path, self.max_attachment_bytes))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(api::SendMessage {
+            bundle: bundle.to_path_buf(),
+            provider: self.provider.clone(),
+            tenant: self.tenant.clone(),
+            team: Some(self.team.clone()),
+            text: self.text.clone(),
+            args: self.args.clone(),
+            to: self.to.clone(),
+            to_kind: self.to_kind.clone(),
+            card: self.card.clone(),
+            attachments,
+            runner_binary: None,
+            env: self.env.clone(),
+            http: None,
+        })
+    }
+}
+
+/// `state/timers/<tenant>.<team>/sends`
+pub fn sends_dir(state_dir: &Path, tenant: &str, team: &str) -> PathBuf {
+    state_dir
+        .join("timers")
+        .join(format!("{tenant}.{team}"))
+        .join("sends")
+}
+
+pub fn save(state_dir: &Path, send: &ScheduledSend) -> anyhow::Result<PathBuf> {
+    let path = sends_dir(state_dir, &send.tenant, &send.team).join(format!("{}.json", send.id));
+    runtime_state::write_json(&path, send)?;
+    Ok(path)
+}
+
+/// Scheduled sends for `tenant`/`team`, pending ones first by due time.
+pub fn list(state_dir: &Path, tenant: &str, team: &str) -> anyhow::Result<Vec<ScheduledSend>> {
+    let dir = sends_dir(state_dir, tenant, team);
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).with_context(|| format!("read {}", dir.display())),
+    };
+    let mut sends = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        if let Some(send) = runtime_state::read_json::<ScheduledSend>(&path)
+            .with_context(|| format!("read scheduled send {}", path.display()))?
+        {
+            sends.push(send);
+        }
+    }
+    sends.sort_by_key(|send| (send.next_at.is_none(), send.next_at, send.created_at));
+    Ok(sends)
+}
+
+/// Removes the scheduled send `id`; `false` when there is none.
+pub fn cancel(state_dir: &Path, tenant: &str, team: &str, id: &str) -> anyhow::Result<bool> {
+    let path = sends_dir(state_dir, tenant, team).join(format!("{id}.json"));
+    match std::fs::remove_file(&path) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err).with_context(|| format!("remove {}", path.display())),
+    }
+}
+
+/// Sends everything due at `now` and records each outcome. Returns the earliest
+/// `next_at` still pending, for the scheduler's sleep.
+pub fn run_due(
+    bundle: &Path,
+    tenant: &str,
+    team: &str,
+    now: DateTime<Utc>,
+) -> anyhow::Result<Option<DateTime<Utc>>> {
+    let state_dir = bundle.join("state");
+    let mut next = None;
+    for mut send in list(&state_dir, tenant, team)? {
+        let Some(due) = send.next_at else {
+            continue;
+        };
+        if due > now {
+            next = Some(next.map_or(due, |current: DateTime<Utc>| current.min(due)));
+            continue;
+        }
+        let error = deliver(bundle, &send).err().map(|err| format!("{err:#}"));
+        match &error {
+            Some(error) => operator_log::warn(
+                module_path!(),
+                format!("scheduled send {} failed: {error}", send.id),
+            ),
+            None => operator_log::info(
+                module_path!(),
+                format!("scheduled send {} sent provider={}", send.id, send.provider),
+            ),
+        }
+        send.advance(Utc::now(), error);
+        save(&state_dir, &send)?;
+        if let Some(due) = send.next_at {
+            next = Some(next.map_or(due, |current: DateTime<Utc>| current.min(due)));
+        }
+    }
+    Ok(next)
+}
+
+fn deliver(bundle: &Path, send: &ScheduledSend) -> anyhow::Result<()> {
+    let request = send.request(bundle)?;
+    if request.to.len() > 1 {
+        let failed = api::send_fanout(request, api::Fanout::Serial, false)
+            .into_iter()
+            .filter(|delivery| !delivery.delivered())
+            .map(|delivery| delivery.destination)
+            .collect::<Vec<_>>();
+        if !failed.is_empty() {
+            return Err(anyhow!("failed for {}", failed.join(", ")));
+        }
+        return Ok(());
+    }
+    let sent = api::send_message(request)?;
+    if let Some(uri) = sent.missing_secret_uris.first() {
+        return Err(anyhow!("missing secret {uri}"));
+    }
+    if !sent.success {
+        return Err(anyhow!(
+            sent.error
+                .unwrap_or_else(|| "send_payload failed".to_string())
+        ));
+    }
+    Ok(())
+}
+
+/// RFC 3339 with or without seconds (`2024-07-01T10:00Z`); a time without an offset
+/// is UTC.
+pub fn parse_at(value: &str) -> anyhow::Result<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(parsed) = DateTime::parse_from_rfc3339(value) {
+        return Ok(parsed.with_timezone(&Utc));
+    }
+    for format in ["%Y-%m-%dT%H:%M%:z", "%Y-%m-%d %H:%M%:z"] {
+        if let Ok(parsed) = DateTime::parse_from_str(value, format) {
+            return Ok(parsed.with_timezone(&Utc));
+        }
+    }
+    let naive = value.trim_end_matches(['Z', 'z']);
+    for format in [
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+    ] {
+        if let Ok(parsed) = NaiveDateTime::parse_from_str(naive, format) {
+            return Ok(parsed.and_utc());
+        }
+    }
+    Err(anyhow!(
+        "invalid time {value:?}; expected RFC 3339, for example 2024-07-01T10:00Z"
+    ))
+}
+
+/// `90`, `90s`, `5m`, `2h`, or `1d`, in seconds.
+pub fn parse_every(value: &str) -> anyhow::Result<u64> {
+    let value = value.trim();
+    let (number, unit) = match value.find(|ch: char| !ch.is_ascii_digit()) {
+        Some(index) => value.split_at(index),
+        None => (value, "s"),
+    };
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3_600,
+        "d" => 86_400,
+        _ => return Err(anyhow!("invalid interval {value:?}; use s, m, h, or d")),
+    };
+    let seconds = number
+        .parse::<u64>()
+        .ok()
+        .and_then(|number| number.checked_mul(multiplier))
+        .filter(|seconds| *seconds > 0)
+        .ok_or_else(|| anyhow!("invalid interval {value:?}; expected a positive number"))?;
+    Ok(seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        parse_at(value).unwrap()
+    }
+
+    #[test]
+    fn parses_short_rfc3339_and_intervals() {
+        assert_eq!(at("2024-07-01T10:00Z"), at("2024-07-01T10:00:00+00:00"));
+        assert_eq!(at("2024-07-01T12:00+02:00"), at("2024-07-01T10:00:00Z"));
+        assert_eq!(at("2024-07-01 10:00"), at("2024-07-01T10:00:00Z"));
+        assert!(parse_at("tomorrow").is_err());
+        assert_eq!(parse_every("5m").unwrap(), 300);
+        assert_eq!(parse_every("90").unwrap(), 90);
+        assert!(parse_every("0s").is_err());
+        assert!(parse_every("5w").is_err());
+    }
+
+    #[test]
+    fn advance_counts_down_and_skips_missed_windows() {
+        let start = at("2024-07-01T10:00Z");
+        let mut send = ScheduledSend {
+            id: "s1".to_string(),
+            provider: "messaging-telegram".to_string(),
+            tenant: "demo".to_string(),
+            team: "default".to_string(),
+            env: "demo".to_string(),
+            text: Some("reminder".to_string()),
+            args: JsonMap::new(),
+            to: Vec::new(),
+            to_kind: None,
+            card: None,
+            attachments: Vec::new(),
+            max_attachment_bytes: 1024,
+            created_at: start,
+            next_at: Some(start),
+            every_seconds: Some(300),
+            remaining: Some(3),
+            runs: 0,
+            last_run: None,
+            last_error: None,
+        };
+        send.advance(start, None);
+        assert_eq!(send.next_at, Some(at("2024-07-01T10:05Z")));
+        send.advance(at("2024-07-01T10:17Z"), Some("boom".to_string()));
+        assert_eq!(send.next_at, Some(at("2024-07-01T10:20Z")));
+        assert_eq!(send.last_error.as_deref(), Some("boom"));
+        send.advance(at("2024-07-01T10:20Z"), None);
+        assert_eq!(
+            (send.runs, send.remaining, send.next_at),
+            (3, Some(0), None)
+        );
+    }
+}
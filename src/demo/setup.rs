@@ -73,12 +73,7 @@ fn parse_providers_value(
 }
 
 fn domain_from_str(value: &str) -> Option<Domain> {
-    match value.to_lowercase().as_str() {
-        "messaging" => Some(Domain::Messaging),
-        "events" => Some(Domain::Events),
-        "secrets" => Some(Domain::Secrets),
-        _ => None,
-    }
+    domains::parse_domain(value)
 }
 
 /// Discover tenants inside the bundle for the requested domain.
@@ -0,0 +1,436 @@
+//! Extra services from `services.extra` in greentic.demo.yaml.
+//!
+//! `demo start` spawns them before provider setup, through the same supervisor as NATS
+//! and cloudflared (pidfile under `state/pids`, log at `logs/<name>.log`), waits for
+//! each health check, and watches them while the demo runs: a service that exits is
+//! restarted according to its `restart` policy until `max_restarts` is used up. They
+//! are stopped, in reverse order, before the demo's own services.
+
+use std::collections::BTreeSet;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::process::Child;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, anyhow};
+
+use crate::config::{DemoExtraService, DemoHealthCheck, DemoRestartPolicy};
+use crate::interpolate::{self, SecretScope};
+use crate::operator_log;
+use crate::runtime_state::{
+    RuntimePaths, ServiceEntry, persist_service_manifest, read_service_manifest,
+};
+use crate::supervisor::{self, ServiceId, ServiceSpec};
+
+pub const KIND: &str = "extra";
+/// Ids `demo start` already uses for its own services.
+const RESERVED: [&str; 5] = [
+    "nats",
+    "cloudflared",
+    "ngrok",
+    "messaging",
+    "subscriptions-universal",
+];
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+const STOP_TIMEOUT_MS: u64 = 2_000;
+
+struct Sidecar {
+    config: DemoExtraService,
+    spec: ServiceSpec,
+    log_path: PathBuf,
+    child: Option<Child>,
+    restarts: u32,
+}
+
+/// Running extra services; stopped by [`Sidecars::stop`] or when dropped.
+#[derive(Default)]
+pub struct Sidecars {
+    entries: Vec<(String, PathBuf)>,
+    paths: Option<RuntimePaths>,
+    shutdown: Option<mpsc::Sender<()>>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl Sidecars {
+    /// Starts `services` in order and waits for each to pass its health check. When
+    /// one fails, the ones already started are stopped again. `${secret:...}`
+    /// references in `env` are read in the tenant/team of `paths`.
+    pub fn start(
+        bundle: &Path,
+        paths: RuntimePaths,
+        log_dir: &Path,
+        services: &[DemoExtraService],
+    ) -> anyhow::Result<Self> {
+        if services.is_empty() {
+            return Ok(Self::default());
+        }
+        let scope = SecretScope {
+            bundle_root: bundle,
+            tenant: paths.tenant(),
+            team: Some(paths.team()),
+        };
+        let mut specs = Vec::new();
+        let mut seen = BTreeSet::new();
+        for config in services {
+            let id = ServiceId::new(config.name.clone())
+                .with_context(|| format!("services.extra entry {:?}", config.name))?;
+            if RESERVED.contains(&id.as_str()) {
+                return Err(anyhow!(
+                    "services.extra name '{}' is reserved for a built-in service",
+                    config.name
+                ));
+            }
+            if !seen.insert(config.name.clone()) {
+                return Err(anyhow!(
+                    "services.extra name '{}' is used twice",
+                    config.name
+                ));
+            }
+            if let Some(health) = &config.health
+                && health.http.is_none()
+                && health.tcp.is_none()
+            {
+                return Err(anyhow!(
+                    "services.extra '{}': health needs `http` or `tcp`",
+                    config.name
+                ));
+            }
+            let env = config
+                .env
+                .iter()
+                .map(|(name, value)| {
+                    interpolate::interpolate_secrets(value, Some(scope))
+                        .map(|value| (name.clone(), value))
+                        .with_context(|| format!("services.extra '{}': env {name}", config.name))
+                })
+                .collect::<anyhow::Result<_>>()?;
+            let mut argv = vec![config.command.clone()];
+            argv.extend(config.args.iter().cloned());
+            let spec = ServiceSpec {
+                id,
+                argv,
+                cwd: Some(
+                    config
+                        .cwd
+                        .as_ref()
+                        .map(|cwd| bundle.join(cwd))
+                        .unwrap_or_else(|| bundle.to_path_buf()),
+                ),
+                env,
+            };
+            specs.push((config.clone(), spec));
+        }
+
+        let mut started: Vec<Sidecar> = Vec::new();
+        for (config, spec) in specs {
+            match start_one(&paths, log_dir, config, spec) {
+                Ok(sidecar) => started.push(sidecar),
+                Err(err) => {
+                    stop_all(&paths, &mut started);
+                    return Err(err);
+                }
+            }
+        }
+
+        let entries = started
+            .iter()
+            .map(|sidecar| (sidecar.config.name.clone(), sidecar.log_path.clone()))
+            .collect();
+        let (shutdown, rx) = mpsc::channel::<()>();
+        let monitor_paths = paths.clone();
+        let handle = thread::spawn(move || {
+            loop {
+                match rx.recv_timeout(POLL_INTERVAL) {
+                    Err(mpsc::RecvTimeoutError::Timeout) => {}
+                    _ => break,
+                }
+                for sidecar in &mut started {
+                    watch(&monitor_paths, sidecar);
+                }
+            }
+            stop_all(&monitor_paths, &mut started);
+        });
+        Ok(Self {
+            entries,
+            paths: Some(paths),
+            shutdown: Some(shutdown),
+            handle: Some(handle),
+        })
+    }
+
+    /// Adds the services to the service manifest so `demo status` and `demo logs` see
+    /// them. Call this after `demo_up`, which starts a fresh manifest.
+    pub fn record(&self) -> anyhow::Result<()> {
+        let Some(paths) = &self.paths else {
+            return Ok(());
+        };
+        let mut manifest = read_service_manifest(paths)?.unwrap_or_default();
+        manifest.services.retain(|entry| entry.kind != KIND);
+        let entries = self
+            .entries
+            .iter()
+            .map(|(name, log_path)| ServiceEntry::new(name, KIND, Some(log_path)));
+        manifest.services.extend(entries);
+        persist_service_manifest(paths, &manifest)
+    }
+
+    pub fn stop(mut self) -> anyhow::Result<()> {
+        self.stop_monitor()
+    }
+
+    fn stop_monitor(&mut self) -> anyhow::Result<()> {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+        if let Some(handle) = self.handle.take() {
+            handle
+                .join()
+                .map_err(|err| anyhow!("sidecar monitor panicked: {err:?}"))?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Sidecars {
+    fn drop(&mut self) {
+        let _ = self.stop_monitor();
+    }
+}
+
+fn start_one(
+    paths: &RuntimePaths,
+    log_dir: &Path,
+    config: DemoExtraService,
+    spec: ServiceSpec,
+) -> anyhow::Result<Sidecar> {
+    let log_path = operator_log::reserve_service_log(log_dir, &config.name)?;
+    let (handle, child) =
+        supervisor::spawn_service_child(paths, spec.clone(), Some(log_path.clone()))
+            .with_context(|| format!("start service {}", config.name))?;
+    let mut sidecar = Sidecar {
+        config,
+        spec,
+        log_path,
+        child: Some(child),
+        restarts: 0,
+    };
+    if let Some(health) = sidecar.config.health.clone()
+        && let Err(err) = wait_healthy(&mut sidecar, &health)
+    {
+        stop_all(paths, std::slice::from_mut(&mut sidecar));
+        return Err(err);
+    }
+    println!(
+        "{}",
+        crate::operator_i18n::trf(
+            "demo.sidecars.started",
+            "{}: running (pid={}, log={})",
+            &[
+                &sidecar.config.name,
+                &handle.pid.to_string(),
+                &sidecar.log_path.display().to_string()
+            ]
+        )
+    );
+    Ok(sidecar)
+}
+
+fn wait_healthy(sidecar: &mut Sidecar, health: &DemoHealthCheck) -> anyhow::Result<()> {
+    let deadline = Instant::now() + Duration::from_secs(health.timeout_secs);
+    loop {
+        if let Some(child) = sidecar.child.as_mut()
+            && let Some(status) = child.try_wait()?
+        {
+            sidecar.child = None;
+            return Err(anyhow!(
+                "service {} exited ({status}) before it was ready; see {}",
+                sidecar.config.name,
+                sidecar.log_path.display()
+            ));
+        }
+        if probe(health) {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            return Err(anyhow!(
+                "service {} not ready after {}s; see {}",
+                sidecar.config.name,
+                health.timeout_secs,
+                sidecar.log_path.display()
+            ));
+        }
+        thread::sleep(Duration::from_millis(250));
+    }
+}
+
+fn probe(health: &DemoHealthCheck) -> bool {
+    if let Some(url) = &health.http {
+        return ureq::get(url)
+            .config()
+            .timeout_global(Some(PROBE_TIMEOUT))
+            .build()
+            .call()
+            .is_ok();
+    }
+    if let Some(addr) = &health.tcp {
+        return addr.to_socket_addrs().is_ok_and(|mut addrs| {
+            addrs.any(|addr| TcpStream::connect_timeout(&addr, PROBE_TIMEOUT).is_ok())
+        });
+    }
+    true
+}
+
+/// Reaps a service that exited and restarts it when its policy allows.
+fn watch(paths: &RuntimePaths, sidecar: &mut Sidecar) {
+    let Some(child) = sidecar.child.as_mut() else {
+        return;
+    };
+    let status = match child.try_wait() {
+        Ok(Some(status)) => status,
+        Ok(None) => return,
+        Err(err) => {
+            operator_log::warn(
+                module_path!(),
+                format!("service {}: {err}", sidecar.config.name),
+            );
+            return;
+        }
+    };
+    sidecar.child = None;
+    let name = &sidecar.config.name;
+    let restart = match sidecar.config.restart {
+        DemoRestartPolicy::Never => false,
+        DemoRestartPolicy::OnFailure => !status.success(),
+        DemoRestartPolicy::Always => true,
+    };
+    if !restart {
+        operator_log::warn(module_path!(), format!("service {name} exited ({status})"));
+        return;
+    }
+    if sidecar.restarts >= sidecar.config.max_restarts {
+        operator_log::error(
+            module_path!(),
+            format!(
+                "service {name} exited ({status}); giving up after {} restarts",
+                sidecar.restarts
+            ),
+        );
+        return;
+    }
+    sidecar.restarts += 1;
+    operator_log::warn(
+        module_path!(),
+        format!(
+            "service {name} exited ({status}); restarting ({}/{})",
+            sidecar.restarts, sidecar.config.max_restarts
+        ),
+    );
+    match supervisor::spawn_service_child(
+        paths,
+        sidecar.spec.clone(),
+        Some(sidecar.log_path.clone()),
+    ) {
+        Ok((_, child)) => sidecar.child = Some(child),
+        Err(err) => operator_log::error(
+            module_path!(),
+            format!("service {name}: restart failed: {err:#}"),
+        ),
+    }
+}
+
+fn stop_all(paths: &RuntimePaths, sidecars: &mut [Sidecar]) {
+    for sidecar in sidecars.iter_mut().rev() {
+        if let Err(err) = supervisor::stop_service(paths, &sidecar.spec.id, STOP_TIMEOUT_MS) {
+            operator_log::warn(
+                module_path!(),
+                format!("stop service {}: {err:#}", sidecar.config.name),
+            );
+        }
+        if let Some(mut child) = sidecar.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(name: &str, command: &str, args: &[&str]) -> DemoExtraService {
+        DemoExtraService {
+            name: name.to_string(),
+            command: command.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            env: Default::default(),
+            cwd: None,
+            health: None,
+            restart: DemoRestartPolicy::Never,
+            max_restarts: 5,
+        }
+    }
+
+    #[test]
+    fn rejects_reserved_and_duplicate_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = RuntimePaths::new(dir.path().join("state"), "demo", "default");
+        let start = |services: &[DemoExtraService]| {
+            Sidecars::start(
+                dir.path(),
+                paths.clone(),
+                &dir.path().join("logs"),
+                services,
+            )
+        };
+        assert!(start(&[service("nats", "true", &[])]).is_err());
+        assert!(start(&[service("api", "true", &[]), service("api", "true", &[])]).is_err());
+        assert!(!dir.path().join("state/pids").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn restarts_on_failure_and_records_in_manifest() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let paths = RuntimePaths::new(dir.path().join("state"), "demo", "default");
+        let mut flaky = service("flaky", "sh", &["-c", "echo run >> runs.txt; exit 3"]);
+        flaky.restart = DemoRestartPolicy::OnFailure;
+        flaky.max_restarts = 2;
+        let sleeper = service("mock-api", "sleep", &["30"]);
+
+        let sidecars = Sidecars::start(
+            dir.path(),
+            paths.clone(),
+            &dir.path().join("logs"),
+            &[flaky, sleeper],
+        )?;
+        sidecars.record()?;
+        let manifest = read_service_manifest(&paths)?.unwrap();
+        let ids: Vec<_> = manifest
+            .services
+            .iter()
+            .map(|entry| entry.id.as_str())
+            .collect();
+        assert_eq!(ids, ["flaky", "mock-api"]);
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while std::fs::read_to_string(dir.path().join("runs.txt"))
+            .unwrap_or_default()
+            .lines()
+            .count()
+            < 3
+        {
+            assert!(Instant::now() < deadline, "flaky service was not restarted");
+            thread::sleep(POLL_INTERVAL);
+        }
+        assert!(paths.pid_path("mock-api").exists());
+
+        sidecars.stop()?;
+        assert!(!paths.pid_path("mock-api").exists());
+        let runs = std::fs::read_to_string(dir.path().join("runs.txt"))?;
+        assert_eq!(runs.lines().count(), 3);
+        Ok(())
+    }
+}
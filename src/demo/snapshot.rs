@@ -0,0 +1,416 @@
+//! `demo snapshot record|check`: pin what a provider pack renders and encodes.
+//!
+//! A corpus (`snapshots/corpus.yaml` by default) lists messages. `record` runs each one
+//! through the provider's `render_plan` and `encode` ops and stores both raw outputs
+//! under `snapshots/<provider>/<name>.json`; `check` runs them again and reports every
+//! JSON path whose value changed, so a pack update cannot silently alter what a live
+//! demo sends.
+//!
+//! ```yaml
+//! ignore: [encode.payload.metadata.sent_at]
+//! messages:
+//!   - name: plain
+//!     text: hello
+//!   - name: welcome-card
+//!     card: cards/welcome.json        # relative to the corpus file, or inline JSON
+//!   - name: raw
+//!     message: { ... }                # a full ChannelMessageEnvelope
+//! ```
+//!
+//! Envelopes built from `text`/`card` use fixed ids so they encode the same way on
+//! every run.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, anyhow};
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+use greentic_types::{ChannelMessageEnvelope, Destination, EnvId, TeamId, TenantCtx, TenantId};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
+
+use crate::demo::runner_host::{DemoRunnerHost, OperatorContext};
+use crate::discovery;
+use crate::messaging_universal::egress;
+use crate::runtime_state;
+use crate::secrets_gate;
+
+/// Differences listed per message before the rest are summarized.
+const MAX_REPORTED_CHANGES: usize = 20;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SnapshotCorpus {
+    /// Dotted paths (`encode.payload.metadata.sent_at`) left out of comparisons.
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    pub messages: Vec<CorpusMessage>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CorpusMessage {
+    pub name: String,
+    pub text: Option<String>,
+    /// Adaptive card: a path relative to the corpus file or an inline object.
+    pub card: Option<JsonValue>,
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    #[serde(default)]
+    pub to: Vec<String>,
+    /// A complete envelope; `text`, `card`, `metadata`, and `to` are then ignored.
+    pub message: Option<JsonValue>,
+}
+
+/// Stored per message; `encode` is absent when `render_plan` failed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub render_plan: JsonValue,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encode: Option<JsonValue>,
+    /// `encode.payload.body_b64` decoded, when it is JSON; easier to review in diffs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<JsonValue>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SnapshotOptions {
+    pub bundle: PathBuf,
+    pub provider: String,
+    pub corpus: PathBuf,
+    pub tenant: String,
+    pub team: Option<String>,
+    pub runner_binary: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SnapshotResult {
+    pub name: String,
+    pub path: PathBuf,
+    /// Changed paths for `check`; empty after `record`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<String>,
+}
+
+pub fn default_corpus_path(bundle: &Path) -> PathBuf {
+    bundle.join("snapshots").join("corpus.yaml")
+}
+
+pub fn snapshot_dir(bundle: &Path, provider: &str) -> PathBuf {
+    bundle.join("snapshots").join(provider)
+}
+
+pub fn load_corpus(path: &Path) -> anyhow::Result<SnapshotCorpus> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("read snapshot corpus {}", path.display()))?;
+    let corpus: SnapshotCorpus = serde_yaml_bw::from_str(&raw)
+        .with_context(|| format!("parse snapshot corpus {}", path.display()))?;
+    if corpus.messages.is_empty() {
+        return Err(anyhow!(
+            "snapshot corpus {} has no messages",
+            path.display()
+        ));
+    }
+    Ok(corpus)
+}
+
+/// Renders and encodes every corpus message and writes its snapshot.
+pub fn record(options: &SnapshotOptions) -> anyhow::Result<Vec<SnapshotResult>> {
+    let corpus = load_corpus(&options.corpus)?;
+    let dir = snapshot_dir(&options.bundle, &options.provider);
+    capture_all(options, &corpus)?
+        .into_iter()
+        .map(|(name, snapshot)| {
+            let path = dir.join(format!("{name}.json"));
+            runtime_state::write_json(&path, &snapshot)?;
+            Ok(SnapshotResult {
+                name,
+                path,
+                changes: Vec::new(),
+            })
+        })
+        .collect()
+}
+
+/// Compares fresh outputs with the stored snapshots; a missing snapshot is a change.
+pub fn check(options: &SnapshotOptions) -> anyhow::Result<Vec<SnapshotResult>> {
+    let corpus = load_corpus(&options.corpus)?;
+    let dir = snapshot_dir(&options.bundle, &options.provider);
+    capture_all(options, &corpus)?
+        .into_iter()
+        .map(|(name, snapshot)| {
+            let path = dir.join(format!("{name}.json"));
+            let changes = match runtime_state::read_json::<Snapshot>(&path)? {
+                Some(stored) => snapshot_changes(&stored, &snapshot, &corpus.ignore),
+                None => vec!["no recorded snapshot".to_string()],
+            };
+            Ok(SnapshotResult {
+                name,
+                path,
+                changes,
+            })
+        })
+        .collect()
+}
+
+fn capture_all(
+    options: &SnapshotOptions,
+    corpus: &SnapshotCorpus,
+) -> anyhow::Result<Vec<(String, Snapshot)>> {
+    let found = discovery::discover_with_options(
+        &options.bundle,
+        discovery::DiscoveryOptions { cbor_only: true },
+    )?;
+    let secrets_handle = secrets_gate::resolve_secrets_manager(
+        &options.bundle,
+        &options.tenant,
+        options.team.as_deref(),
+    )?;
+    let runner_host = DemoRunnerHost::new(
+        options.bundle.clone(),
+        &found,
+        options.runner_binary.clone(),
+        secrets_handle,
+        false,
+    )?;
+    let ctx = OperatorContext {
+        tenant: options.tenant.clone(),
+        team: options.team.clone(),
+        correlation_id: None,
+    };
+    let corpus_dir = options.corpus.parent().unwrap_or(Path::new("."));
+    corpus
+        .messages
+        .iter()
+        .map(|entry| {
+            let message = corpus_envelope(entry, corpus_dir, options)
+                .with_context(|| format!("corpus message '{}'", entry.name))?;
+            let snapshot = capture(&runner_host, &ctx, &options.provider, message)
+                .with_context(|| format!("corpus message '{}'", entry.name))?;
+            Ok((entry.name.clone(), snapshot))
+        })
+        .collect()
+}
+
+fn capture(
+    runner_host: &DemoRunnerHost,
+    ctx: &OperatorContext,
+    provider: &str,
+    message: JsonValue,
+) -> anyhow::Result<Snapshot> {
+    let render_plan = egress::render_plan(runner_host, ctx, provider, message.clone())?;
+    if render_plan.get("ok").and_then(JsonValue::as_bool) == Some(false) {
+        return Ok(Snapshot {
+            render_plan,
+            encode: None,
+            body: None,
+        });
+    }
+    let encode = egress::encode_output(runner_host, ctx, provider, message, render_plan.clone())?;
+    let body = encode
+        .pointer("/payload/body_b64")
+        .or_else(|| encode.get("body_b64"))
+        .and_then(JsonValue::as_str)
+        .and_then(|b64| STANDARD.decode(b64).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok());
+    Ok(Snapshot {
+        render_plan,
+        encode: Some(encode),
+        body,
+    })
+}
+
+fn corpus_envelope(
+    entry: &CorpusMessage,
+    corpus_dir: &Path,
+    options: &SnapshotOptions,
+) -> anyhow::Result<JsonValue> {
+    if let Some(message) = &entry.message {
+        return Ok(message.clone());
+    }
+    let mut metadata = entry.metadata.clone();
+    if let Some(card) = &entry.card {
+        let card = match card {
+            JsonValue::String(path) => {
+                let path = corpus_dir.join(path);
+                let raw = std::fs::read_to_string(&path)
+                    .with_context(|| format!("read card {}", path.display()))?;
+                serde_json::from_str::<JsonValue>(&raw)
+                    .with_context(|| format!("parse card {}", path.display()))?
+            }
+            inline => inline.clone(),
+        };
+        metadata.insert("adaptive_card".to_string(), serde_json::to_string(&card)?);
+    }
+    let env = EnvId::try_from("local").map_err(|err| anyhow!("{err}"))?;
+    let tenant = TenantId::try_from(options.tenant.clone()).map_err(|err| anyhow!("{err}"))?;
+    let mut tenant_ctx = TenantCtx::new(env, tenant);
+    if let Some(team) = &options.team {
+        tenant_ctx = tenant_ctx.with_team(Some(
+            TeamId::try_from(team.clone()).map_err(|err| anyhow!("{err}"))?,
+        ));
+    }
+    let tenant_ctx = tenant_ctx
+        .with_session("snapshot".to_string())
+        .with_flow("snapshot".to_string())
+        .with_node("snapshot".to_string())
+        .with_provider(options.provider.clone())
+        .with_attempt(1);
+    let envelope = ChannelMessageEnvelope {
+        id: format!("snapshot-{}", entry.name),
+        tenant: tenant_ctx,
+        channel: options.provider.clone(),
+        session_id: "snapshot".to_string(),
+        reply_scope: None,
+        from: None,
+        to: entry
+            .to
+            .iter()
+            .map(|id| Destination {
+                id: id.clone(),
+                kind: None,
+            })
+            .collect(),
+        correlation_id: None,
+        text: entry.text.clone(),
+        attachments: Vec::new(),
+        metadata,
+    };
+    Ok(serde_json::to_value(envelope)?)
+}
+
+/// Paths whose value differs between `before` and `after`, minus `ignore`.
+pub fn snapshot_changes(before: &Snapshot, after: &Snapshot, ignore: &[String]) -> Vec<String> {
+    let before = json!({"render_plan": before.render_plan, "encode": before.encode});
+    let after = json!({"render_plan": after.render_plan, "encode": after.encode});
+    let mut changes = Vec::new();
+    diff_values("", &before, &after, ignore, &mut changes);
+    if changes.len() > MAX_REPORTED_CHANGES {
+        let hidden = changes.len() - MAX_REPORTED_CHANGES;
+        changes.truncate(MAX_REPORTED_CHANGES);
+        changes.push(format!("... and {hidden} more"));
+    }
+    changes
+}
+
+fn diff_values(
+    path: &str,
+    before: &JsonValue,
+    after: &JsonValue,
+    ignore: &[String],
+    out: &mut Vec<String>,
+) {
+    if ignore.iter().any(|ignored| ignored == path) {
+        return;
+    }
+    let child = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+    match (before, after) {
+        (JsonValue::Object(left), JsonValue::Object(right)) => {
+            let mut keys = left.keys().chain(right.keys()).collect::<Vec<_>>();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let null = JsonValue::Null;
+                diff_values(
+                    &child(key),
+                    left.get(key).unwrap_or(&null),
+                    right.get(key).unwrap_or(&null),
+                    ignore,
+                    out,
+                );
+            }
+        }
+        (JsonValue::Array(left), JsonValue::Array(right)) if left.len() == right.len() => {
+            for (index, (left, right)) in left.iter().zip(right).enumerate() {
+                diff_values(&child(&index.to_string()), left, right, ignore, out);
+            }
+        }
+        (JsonValue::String(left), JsonValue::String(right))
+            if path.ends_with("body_b64") && left != right =>
+        {
+            // Point at the decoded body instead of an opaque base64 change.
+            let decode = |b64: &str| {
+                STANDARD
+                    .decode(b64)
+                    .ok()
+                    .and_then(|bytes| serde_json::from_slice::<JsonValue>(&bytes).ok())
+            };
+            match (decode(left), decode(right)) {
+                (Some(left), Some(right)) => {
+                    diff_values(&format!("{path}(decoded)"), &left, &right, ignore, out)
+                }
+                _ => out.push(format!("{path}: changed")),
+            }
+        }
+        _ if before != after => out.push(format!("{path}: {before} -> {after}")),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(text: &str, sent_at: &str) -> Snapshot {
+        let body = json!({"chat_id": 1, "text": text});
+        Snapshot {
+            render_plan: json!({"ok": true, "plan": {"tier": "basic"}}),
+            encode: Some(json!({
+                "ok": true,
+                "payload": {
+                    "content_type": "application/json",
+                    "body_b64": STANDARD.encode(serde_json::to_vec(&body).unwrap()),
+                    "metadata": {"sent_at": sent_at},
+                },
+            })),
+            body: Some(body),
+        }
+    }
+
+    #[test]
+    fn changes_point_at_decoded_body_fields_and_skip_ignored_paths() {
+        let ignore = vec!["encode.payload.metadata.sent_at".to_string()];
+        assert!(snapshot_changes(&snapshot("hi", "1"), &snapshot("hi", "2"), &ignore).is_empty());
+        assert_eq!(
+            snapshot_changes(&snapshot("hi", "1"), &snapshot("hello", "1"), &ignore),
+            vec![r#"encode.payload.body_b64(decoded).text: "hi" -> "hello""#.to_string()]
+        );
+    }
+
+    #[test]
+    fn corpus_cards_load_relative_to_the_corpus_file() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("card.json"), r#"{"type":"AdaptiveCard"}"#)?;
+        let entry = CorpusMessage {
+            name: "card".to_string(),
+            text: Some("hi".to_string()),
+            card: Some(JsonValue::String("card.json".to_string())),
+            metadata: BTreeMap::new(),
+            to: vec!["42".to_string()],
+            message: None,
+        };
+        let options = SnapshotOptions {
+            bundle: dir.path().to_path_buf(),
+            provider: "messaging-telegram".to_string(),
+            corpus: dir.path().join("corpus.yaml"),
+            tenant: "demo".to_string(),
+            team: Some("default".to_string()),
+            runner_binary: None,
+        };
+        let first = corpus_envelope(&entry, dir.path(), &options)?;
+        assert_eq!(first, corpus_envelope(&entry, dir.path(), &options)?);
+        assert_eq!(first["id"], "snapshot-card");
+        assert_eq!(
+            first["metadata"]["adaptive_card"],
+            r#"{"type":"AdaptiveCard"}"#
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,586 @@
+//! `demo test`: run a bundle as an integration test suite.
+//!
+//! Each case feeds one ingress request (or one timer tick) through the same pipeline
+//! `demo ingress --end-to-end` uses, stopping after `encode` so nothing is sent, and
+//! compares the HTTP response, the encoded provider payloads, and the overall outcome
+//! with the case's `expect` block:
+//!
+//! ```yaml
+//! provider: messaging-telegram
+//! cases:
+//!   - name: echo
+//!     ingress: { emulate: telegram:message, text: hi }
+//!     expect:
+//!       status: 200
+//!       payloads:
+//!         - body: { text: { contains: hi }, chat_id: 100000001 }
+//!   - name: nightly digest
+//!     timer: { provider: events-timer, handler: nightly }
+//!     expect: { outcome: success }
+//! ```
+//!
+//! Payload matchers address the decoded JSON body with dotted paths (`message.chat.id`,
+//! `attachments.0.url`). A plain value must be equal; `{contains: …}`, `{equals: …}`,
+//! and `{exists: bool}` cover the rest.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, anyhow};
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::demo::capability_recording::CapabilityRecordingMode;
+use crate::demo::runner_host::{DemoRunnerHost, OperatorContext};
+use crate::demo::timer_scheduler;
+use crate::discovery;
+use crate::messaging_universal::dto::{HttpOutV1, ProviderPayloadV1};
+use crate::messaging_universal::emulate::{self, EmulateInput, EmulateSpec};
+use crate::messaging_universal::{egress, ingress};
+use crate::secrets_gate::{self, SecretsManagerHandle};
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TestSpec {
+    /// Default provider for ingress cases.
+    pub provider: Option<String>,
+    pub cases: Vec<TestCase>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TestCase {
+    pub name: String,
+    pub provider: Option<String>,
+    pub ingress: Option<IngressInput>,
+    pub timer: Option<TimerInput>,
+    #[serde(default)]
+    pub expect: CaseExpect,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IngressInput {
+    /// `<platform>:<event>`, as for `demo ingress --emulate`.
+    pub emulate: Option<String>,
+    pub text: Option<String>,
+    pub from: Option<String>,
+    pub chat: Option<String>,
+    pub binding_id: Option<String>,
+    pub method: Option<String>,
+    pub path: Option<String>,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub query: BTreeMap<String, String>,
+    pub body: Option<String>,
+    pub body_json: Option<JsonValue>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TimerInput {
+    pub provider: String,
+    pub handler: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CaseExpect {
+    /// Defaults to `success`.
+    #[serde(default)]
+    pub outcome: ExpectedOutcome,
+    pub status: Option<u16>,
+    /// Envelopes produced by `ingest_http`, before the app flow.
+    pub events: Option<usize>,
+    /// Encoded payloads, in order; the count must match too.
+    pub payloads: Option<Vec<PayloadExpect>>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpectedOutcome {
+    #[default]
+    Success,
+    Failure,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PayloadExpect {
+    pub content_type: Option<String>,
+    #[serde(default)]
+    pub body: BTreeMap<String, Matcher>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Matcher {
+    Op(MatchOp),
+    Equals(JsonValue),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MatchOp {
+    pub contains: Option<String>,
+    pub equals: Option<JsonValue>,
+    pub exists: Option<bool>,
+}
+
+/// Where a case runs and how; shared by every case of a spec.
+#[derive(Clone, Debug)]
+pub struct TestOptions {
+    pub tenant: String,
+    pub team: Option<String>,
+    pub runner_binary: Option<PathBuf>,
+    pub app_pack: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CaseResult {
+    pub name: String,
+    pub passed: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct TestReport {
+    pub cases: Vec<CaseResult>,
+}
+
+impl TestReport {
+    pub fn failed(&self) -> usize {
+        self.cases.iter().filter(|case| !case.passed).count()
+    }
+}
+
+pub fn load_spec(path: &Path) -> anyhow::Result<TestSpec> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("read test spec {}", path.display()))?;
+    let spec: TestSpec = serde_yaml_bw::from_str(&raw)
+        .with_context(|| format!("parse test spec {}", path.display()))?;
+    for case in &spec.cases {
+        match (&case.ingress, &case.timer) {
+            (Some(_), None) | (None, Some(_)) => {}
+            _ => {
+                return Err(anyhow!(
+                    "case '{}' needs exactly one of ingress or timer",
+                    case.name
+                ));
+            }
+        }
+    }
+    Ok(spec)
+}
+
+/// Runs the cases whose name is in `only` (all when empty), in file order.
+pub fn run_spec(
+    bundle: &Path,
+    spec: &TestSpec,
+    options: &TestOptions,
+    only: &[String],
+) -> anyhow::Result<TestReport> {
+    let secrets_handle =
+        secrets_gate::resolve_secrets_manager(bundle, &options.tenant, options.team.as_deref())?;
+    let mut report = TestReport::default();
+    for case in &spec.cases {
+        if !only.is_empty() && !only.contains(&case.name) {
+            continue;
+        }
+        let observed = run_case(bundle, spec, case, options, &secrets_handle);
+        let failures = case_failures(&case.expect, &observed);
+        report.cases.push(CaseResult {
+            name: case.name.clone(),
+            passed: failures.is_empty(),
+            failures,
+        });
+    }
+    Ok(report)
+}
+
+/// What a case produced; `result` is the pipeline error, if any.
+#[derive(Debug)]
+pub struct Observed {
+    pub response: Option<HttpOutV1>,
+    pub events: usize,
+    pub payloads: Vec<ProviderPayloadV1>,
+    pub result: Result<(), String>,
+}
+
+impl Default for Observed {
+    fn default() -> Self {
+        Self {
+            response: None,
+            events: 0,
+            payloads: Vec::new(),
+            result: Ok(()),
+        }
+    }
+}
+
+fn run_case(
+    bundle: &Path,
+    spec: &TestSpec,
+    case: &TestCase,
+    options: &TestOptions,
+    secrets_handle: &SecretsManagerHandle,
+) -> Observed {
+    let mut observed = Observed::default();
+    let ctx = OperatorContext {
+        tenant: options.tenant.clone(),
+        team: options.team.clone(),
+        correlation_id: Some(format!("demo-test:{}", case.name)),
+    };
+    let result = if let Some(timer) = &case.timer {
+        run_timer_case(bundle, timer, options, &ctx, secrets_handle)
+    } else {
+        let provider = case.provider.as_deref().or(spec.provider.as_deref());
+        match (provider, &case.ingress) {
+            (Some(provider), Some(input)) => run_ingress_case(
+                bundle,
+                provider,
+                input,
+                options,
+                &ctx,
+                secrets_handle,
+                &mut observed,
+            ),
+            _ => Err(anyhow!("no provider for ingress case")),
+        }
+    };
+    observed.result = result.map_err(|err| format!("{err:#}"));
+    observed
+}
+
+fn run_ingress_case(
+    bundle: &Path,
+    provider: &str,
+    input: &IngressInput,
+    options: &TestOptions,
+    ctx: &OperatorContext,
+    secrets_handle: &SecretsManagerHandle,
+    observed: &mut Observed,
+) -> anyhow::Result<()> {
+    let mut method = input.method.clone().unwrap_or_else(|| "POST".to_string());
+    let mut headers = Vec::new();
+    let mut query = Vec::new();
+    let body = if let Some(raw) = &input.emulate {
+        let spec: EmulateSpec = raw.parse()?;
+        let emulate_input = EmulateInput {
+            text: input.text.clone(),
+            from: input.from.clone(),
+            chat: input.chat.clone(),
+            binding_id: input.binding_id.clone(),
+        };
+        // Unsigned: a test should not depend on which secrets happen to be configured.
+        let emulated = emulate::emulate(&spec, &emulate_input, |_| None)?;
+        method = emulated.method.to_string();
+        headers = emulated.headers;
+        query = emulated.query;
+        emulated.body
+    } else if let Some(json) = &input.body_json {
+        headers.push(("Content-Type".to_string(), "application/json".to_string()));
+        serde_json::to_vec(json)?
+    } else {
+        input.body.clone().unwrap_or_default().into_bytes()
+    };
+    headers.extend(input.headers.clone());
+    query.extend(input.query.clone());
+    let path = input
+        .path
+        .clone()
+        .unwrap_or_else(|| match &input.binding_id {
+            Some(binding) => format!("/ingress/{provider}/{binding}"),
+            None => format!("/ingress/{provider}/webhook"),
+        });
+    let request = ingress::build_ingress_request(
+        provider,
+        None,
+        &method.to_ascii_uppercase(),
+        &path,
+        headers,
+        query,
+        &body,
+        input.binding_id.clone(),
+        Some(options.tenant.clone()),
+        options.team.clone(),
+    );
+    let (response, envelopes) = ingress::run_ingress(
+        bundle,
+        provider,
+        &request,
+        ctx,
+        options.runner_binary.clone(),
+        secrets_handle.clone(),
+    )?;
+    observed.response = Some(response);
+    observed.events = envelopes.len();
+    let outgoing =
+        egress::run_app_flows(envelopes, provider, bundle, ctx, options.app_pack.clone())?;
+    observed.payloads = egress::encode_envelopes(
+        &outgoing,
+        provider,
+        bundle,
+        ctx,
+        options.runner_binary.clone(),
+        secrets_handle.clone(),
+    )?;
+    Ok(())
+}
+
+fn run_timer_case(
+    bundle: &Path,
+    timer: &TimerInput,
+    options: &TestOptions,
+    ctx: &OperatorContext,
+    secrets_handle: &SecretsManagerHandle,
+) -> anyhow::Result<()> {
+    let found =
+        discovery::discover_with_options(bundle, discovery::DiscoveryOptions { cbor_only: true })?;
+    let handler = timer_scheduler::discover_timer_handlers(&found, 60)?
+        .into_iter()
+        .find(|handler| handler.provider == timer.provider && handler.handler_id == timer.handler)
+        .ok_or_else(|| {
+            anyhow!(
+                "no timer handler {}/{} in the bundle",
+                timer.provider,
+                timer.handler
+            )
+        })?;
+    let runner_host = DemoRunnerHost::new(
+        bundle.to_path_buf(),
+        &found,
+        options.runner_binary.clone(),
+        secrets_handle.clone(),
+        false,
+    )?
+    .with_capability_recording(CapabilityRecordingMode::Mock);
+    timer_scheduler::tick_handler(
+        Arc::new(runner_host),
+        &ctx.tenant,
+        ctx.team.as_deref(),
+        handler,
+    )
+}
+
+/// Every expectation `observed` missed, as a readable line each.
+pub fn case_failures(expect: &CaseExpect, observed: &Observed) -> Vec<String> {
+    let mut failures = Vec::new();
+    match (&observed.result, expect.outcome) {
+        (Ok(()), ExpectedOutcome::Failure) => {
+            failures.push("pipeline succeeded (expected failure)".to_string());
+        }
+        (Err(err), ExpectedOutcome::Success) => failures.push(format!("pipeline failed: {err}")),
+        _ => {}
+    }
+    if let Some(status) = expect.status {
+        match &observed.response {
+            Some(response) if response.status != status => {
+                failures.push(format!("status {} (expected {status})", response.status));
+            }
+            Some(_) => {}
+            None => failures.push(format!("no HTTP response (expected status {status})")),
+        }
+    }
+    if let Some(events) = expect.events
+        && observed.events != events
+    {
+        failures.push(format!("{} event(s) (expected {events})", observed.events));
+    }
+    if let Some(payloads) = &expect.payloads {
+        if observed.payloads.len() != payloads.len() {
+            failures.push(format!(
+                "{} payload(s) (expected {})",
+                observed.payloads.len(),
+                payloads.len()
+            ));
+        }
+        for (index, (expected, payload)) in payloads.iter().zip(&observed.payloads).enumerate() {
+            failures.extend(
+                payload_failures(expected, payload)
+                    .into_iter()
+                    .map(|failure| format!("payload {}: {failure}", index + 1)),
+            );
+        }
+    }
+    failures
+}
+
+fn payload_failures(expected: &PayloadExpect, payload: &ProviderPayloadV1) -> Vec<String> {
+    let mut failures = Vec::new();
+    if let Some(content_type) = &expected.content_type
+        && &payload.content_type != content_type
+    {
+        failures.push(format!(
+            "content type {} (expected {content_type})",
+            payload.content_type
+        ));
+    }
+    if expected.body.is_empty() {
+        return failures;
+    }
+    let body = STANDARD
+        .decode(&payload.body_b64)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<JsonValue>(&bytes).ok());
+    let Some(body) = body else {
+        failures.push("body is not JSON".to_string());
+        return failures;
+    };
+    for (path, matcher) in &expected.body {
+        if let Some(failure) = matcher.check(lookup(&body, path)) {
+            failures.push(format!("{path}: {failure}"));
+        }
+    }
+    failures
+}
+
+impl Matcher {
+    /// `None` when `actual` satisfies the matcher, else why not.
+    pub fn check(&self, actual: Option<&JsonValue>) -> Option<String> {
+        let describe = |value: Option<&JsonValue>| {
+            value.map_or_else(|| "missing".to_string(), JsonValue::to_string)
+        };
+        match self {
+            Matcher::Equals(expected) => (actual != Some(expected))
+                .then(|| format!("{} (expected {expected})", describe(actual))),
+            Matcher::Op(op) => {
+                if let Some(exists) = op.exists
+                    && actual.is_some() != exists
+                {
+                    return Some(if exists {
+                        "missing".to_string()
+                    } else {
+                        format!("{} (expected missing)", describe(actual))
+                    });
+                }
+                if let Some(expected) = &op.equals
+                    && actual != Some(expected)
+                {
+                    return Some(format!("{} (expected {expected})", describe(actual)));
+                }
+                if let Some(needle) = &op.contains {
+                    let text = match actual {
+                        Some(JsonValue::String(text)) => text.clone(),
+                        Some(other) => other.to_string(),
+                        None => return Some(format!("missing (expected to contain {needle:?})")),
+                    };
+                    if !text.contains(needle.as_str()) {
+                        return Some(format!("{text:?} does not contain {needle:?}"));
+                    }
+                }
+                None
+            }
+        }
+    }
+}
+
+/// `a.b.0.c` into objects and arrays.
+pub fn lookup<'a>(value: &'a JsonValue, path: &str) -> Option<&'a JsonValue> {
+    path.split('.')
+        .filter(|segment| !segment.is_empty())
+        .try_fold(value, |current, segment| match current {
+            JsonValue::Object(map) => map.get(segment),
+            JsonValue::Array(items) => segment
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| items.get(index)),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn payload(body: JsonValue) -> ProviderPayloadV1 {
+        ProviderPayloadV1 {
+            content_type: "application/json".to_string(),
+            body_b64: STANDARD.encode(serde_json::to_vec(&body).unwrap()),
+            metadata_json: None,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn payload_matchers_address_json_paths() {
+        let expect: CaseExpect = serde_yaml_bw::from_str(
+            r#"
+status: 200
+payloads:
+  - content_type: application/json
+    body:
+      chat_id: 42
+      text: { contains: hi }
+      reply_markup: { exists: false }
+      attachments.0.url: { equals: "https://x" }
+"#,
+        )
+        .unwrap();
+        let observed = Observed {
+            response: Some(HttpOutV1 {
+                v: 1,
+                status: 200,
+                headers: Vec::new(),
+                body_b64: None,
+                events: Vec::new(),
+            }),
+            events: 1,
+            payloads: vec![payload(
+                json!({"chat_id": 42, "text": "oh hi", "attachments": [{"url": "https://x"}]}),
+            )],
+            result: Ok(()),
+        };
+        assert!(case_failures(&expect, &observed).is_empty());
+
+        let observed = Observed {
+            payloads: vec![payload(
+                json!({"chat_id": 7, "text": "bye", "reply_markup": {}}),
+            )],
+            ..observed
+        };
+        assert_eq!(
+            case_failures(&expect, &observed),
+            vec![
+                "payload 1: attachments.0.url: missing (expected \"https://x\")".to_string(),
+                "payload 1: chat_id: 7 (expected 42)".to_string(),
+                "payload 1: reply_markup: {} (expected missing)".to_string(),
+                "payload 1: text: \"bye\" does not contain \"hi\"".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn outcome_and_counts_are_checked() {
+        let expect = CaseExpect {
+            outcome: ExpectedOutcome::Failure,
+            payloads: Some(Vec::new()),
+            ..CaseExpect::default()
+        };
+        let observed = Observed {
+            payloads: vec![payload(json!({}))],
+            ..Observed::default()
+        };
+        assert_eq!(
+            case_failures(&expect, &observed),
+            vec![
+                "pipeline succeeded (expected failure)".to_string(),
+                "1 payload(s) (expected 0)".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn cases_need_exactly_one_input() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tests.yaml");
+        std::fs::write(&path, "cases:\n  - name: empty\n").unwrap();
+        assert!(load_spec(&path).is_err());
+    }
+}
@@ -1,26 +1,264 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::{Arc, mpsc};
 use std::thread;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 use anyhow::Context;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::{Value as JsonValue, json};
 use zip::ZipArchive;
 
+use crate::bus;
+use crate::config::{DemoTimerCalendar, DemoTimerOverride, TimerCatchUpPolicy};
 use crate::demo::event_router::route_events_to_default_flow;
 use crate::demo::ingress_types::EventEnvelopeV1;
 use crate::demo::runner_host::{DemoRunnerHost, OperatorContext};
+use crate::demo::scheduled_send;
 use crate::discovery;
 use crate::domains::Domain;
 use crate::operator_log;
+use crate::runtime_state;
+
+/// Upper bound on catch-up invocations per handler, so a long outage with a short
+/// interval cannot flood the provider on restart.
+const MAX_CATCH_UP_RUNS: usize = 100;
+
+/// How often the scheduler looks for new `demo send --at/--every` entries.
+const SCHEDULED_SEND_POLL: Duration = Duration::from_secs(1);
 
 #[derive(Clone, Debug)]
 pub struct TimerHandlerConfig {
     pub provider: String,
     pub op_id: String,
     pub handler_id: String,
-    pub interval_seconds: u64,
+    pub schedule: TimerSchedule,
+}
+
+impl TimerHandlerConfig {
+    /// `<provider>/<handler_id>`, the key used by `services.events.timers` overrides.
+    pub fn key(&self) -> String {
+        format!("{}/{}", self.provider, self.handler_id)
+    }
+}
+
+/// When a timer handler fires. Cron expressions are evaluated in UTC.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TimerSchedule {
+    Interval {
+        seconds: u64,
+    },
+    /// One or more cron expressions (6-field, seconds first); the earliest match wins.
+    Cron {
+        expressions: Vec<String>,
+    },
+}
+
+impl TimerSchedule {
+    pub fn interval(seconds: u64) -> Self {
+        Self::Interval {
+            seconds: seconds.max(1),
+        }
+    }
+
+    /// Accepts classic 5-field expressions (a `0` seconds field is prepended and
+    /// numeric weekdays are read as 0-7 with Sunday as 0 or 7), the 6/7-field form
+    /// of the `cron` crate (weekdays 1-7, Sunday as 1), and `@hourly`-style
+    /// shortcuts.
+    pub fn cron(expression: &str) -> anyhow::Result<Self> {
+        Ok(Self::Cron {
+            expressions: vec![normalize_cron(expression)?],
+        })
+    }
+
+    /// Calendar schedule: fire at each `HH:MM` in `at` on the listed weekdays (every
+    /// day when `days` is empty).
+    pub fn calendar(calendar: &DemoTimerCalendar) -> anyhow::Result<Self> {
+        if calendar.at.is_empty() {
+            anyhow::bail!("calendar schedule needs at least one `at` time (HH:MM)");
+        }
+        let days = if calendar.days.is_empty() {
+            "*".to_string()
+        } else {
+            calendar
+                .days
+                .iter()
+                .map(|day| parse_weekday(day))
+                .collect::<anyhow::Result<Vec<_>>>()?
+                .join(",")
+        };
+        let mut expressions = Vec::with_capacity(calendar.at.len());
+        for at in &calendar.at {
+            let (hour, minute) = at
+                .split_once(':')
+                .and_then(|(hour, minute)| {
+                    Some((hour.parse::<u32>().ok()?, minute.parse::<u32>().ok()?))
+                })
+                .filter(|(hour, minute)| *hour < 24 && *minute < 60)
+                .ok_or_else(|| anyhow::anyhow!("invalid calendar time {at:?}; expected HH:MM"))?;
+            expressions.push(normalize_cron(&format!("0 {minute} {hour} * * {days}"))?);
+        }
+        Ok(Self::Cron { expressions })
+    }
+
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Interval { seconds } => {
+                Some(after + chrono::Duration::seconds(i64::try_from(*seconds).ok()?))
+            }
+            Self::Cron { expressions } => expressions
+                .iter()
+                .filter_map(|expression| cron::Schedule::from_str(expression).ok())
+                .filter_map(|schedule| schedule.after(&after).next())
+                .min(),
+        }
+    }
+
+    /// Fire times in `(since, until]`, oldest first, at most `limit` of them.
+    pub fn fires_between(
+        &self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        limit: usize,
+    ) -> Vec<DateTime<Utc>> {
+        let mut fires = Vec::new();
+        let mut cursor = since;
+        while fires.len() < limit {
+            match self.next_after(cursor) {
+                Some(next) if next <= until => {
+                    fires.push(next);
+                    cursor = next;
+                }
+                _ => break,
+            }
+        }
+        fires
+    }
+
+    pub fn interval_seconds(&self) -> Option<u64> {
+        match self {
+            Self::Interval { seconds } => Some(*seconds),
+            Self::Cron { .. } => None,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Interval { seconds } => format!("every {seconds}s"),
+            Self::Cron { expressions } => format!("cron {}", expressions.join(" | ")),
+        }
+    }
+}
+
+fn normalize_cron(expression: &str) -> anyhow::Result<String> {
+    let trimmed = expression.trim();
+    let fields = trimmed.split_whitespace().collect::<Vec<_>>();
+    let normalized = if let [minute, hour, day, month, weekday] = fields[..] {
+        let weekday = classic_weekdays(weekday)
+            .with_context(|| format!("invalid cron expression {expression:?}"))?;
+        format!("0 {minute} {hour} {day} {month} {weekday}")
+    } else {
+        trimmed.to_string()
+    };
+    cron::Schedule::from_str(&normalized)
+        .with_context(|| format!("invalid cron expression {expression:?}"))?;
+    Ok(normalized)
+}
+
+/// Rewrites the numeric weekdays of a classic cron field (0-7, Sunday as 0 or 7)
+/// as the `cron` crate numbers them (1-7, Sunday as 1). Ranges and steps are
+/// expanded into lists, since a classic range such as `5-7` wraps in the new
+/// numbering. `*` and day names are left alone.
+fn classic_weekdays(field: &str) -> anyhow::Result<String> {
+    let mut items = Vec::new();
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, Some(step)),
+            None => (item, None),
+        };
+        let numeric = |value: &str| !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit());
+        let (first, last) = range.split_once('-').unwrap_or((range, range));
+        if !numeric(first) || !numeric(last) {
+            items.push(item.to_string());
+            continue;
+        }
+        let (first, last) = (first.parse::<u32>()?, last.parse::<u32>()?);
+        let step = match step {
+            Some(step) => step.parse::<usize>().ok().filter(|step| *step > 0),
+            None => Some(1),
+        }
+        .ok_or_else(|| anyhow::anyhow!("invalid weekday step in {item:?}"))?;
+        if first > last || last > 7 {
+            anyhow::bail!("invalid weekday {item:?}; expected 0-7");
+        }
+        // A single day with a step (`1/2`) means from that day to the end.
+        let last = if step > 1 && first == last { 6 } else { last };
+        for day in (first..=last).step_by(step) {
+            let day = (day % 7 + 1).to_string();
+            if !items.contains(&day) {
+                items.push(day);
+            }
+        }
+    }
+    Ok(items.join(","))
+}
+
+/// A full weekday name or its three-letter abbreviation, in any case.
+fn parse_weekday(day: &str) -> anyhow::Result<&'static str> {
+    const DAYS: [(&str, &str); 7] = [
+        ("Mon", "Monday"),
+        ("Tue", "Tuesday"),
+        ("Wed", "Wednesday"),
+        ("Thu", "Thursday"),
+        ("Fri", "Friday"),
+        ("Sat", "Saturday"),
+        ("Sun", "Sunday"),
+    ];
+    DAYS.iter()
+        .find(|(short, long)| day.eq_ignore_ascii_case(short) || day.eq_ignore_ascii_case(long))
+        .map(|(short, _)| *short)
+        .ok_or_else(|| anyhow::anyhow!("invalid weekday {day:?}"))
+}
+
+/// Applies `services.events.timers` overrides from greentic.demo.yaml. Keys are
+/// `<provider>/<handler_id>` or just `<provider>` for all of its handlers; the more
+/// specific key wins.
+pub fn apply_timer_overrides(
+    handlers: Vec<TimerHandlerConfig>,
+    overrides: &BTreeMap<String, DemoTimerOverride>,
+) -> anyhow::Result<Vec<TimerHandlerConfig>> {
+    let mut applied = Vec::with_capacity(handlers.len());
+    for mut handler in handlers {
+        let Some(entry) = overrides
+            .get(&handler.key())
+            .or_else(|| overrides.get(&handler.provider))
+        else {
+            applied.push(handler);
+            continue;
+        };
+        if entry.enabled == Some(false) {
+            continue;
+        }
+        if let Some(schedule) = schedule_from_override(entry)
+            .with_context(|| format!("services.events.timers.{}", handler.key()))?
+        {
+            handler.schedule = schedule;
+        }
+        applied.push(handler);
+    }
+    Ok(applied)
+}
+
+fn schedule_from_override(entry: &DemoTimerOverride) -> anyhow::Result<Option<TimerSchedule>> {
+    if let Some(expression) = &entry.cron {
+        return TimerSchedule::cron(expression).map(Some);
+    }
+    if let Some(calendar) = &entry.calendar {
+        return TimerSchedule::calendar(calendar).map(Some);
+    }
+    Ok(entry.interval_seconds.map(TimerSchedule::interval))
 }
 
 #[derive(Clone)]
@@ -29,7 +267,56 @@ pub struct TimerSchedulerConfig {
     pub tenant: String,
     pub team: Option<String>,
     pub handlers: Vec<TimerHandlerConfig>,
+    pub catch_up: TimerCatchUpPolicy,
     pub debug_enabled: bool,
+    /// Also run the sends `demo send --at/--every` left under `state/timers`.
+    pub scheduled_sends: bool,
+}
+
+/// Last fire of a timer handler, persisted so restarts can detect missed windows.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TimerState {
+    pub provider: String,
+    pub handler_id: String,
+    pub op_id: String,
+    pub last_fire: String,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl TimerState {
+    pub fn last_fire_at(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.last_fire)
+            .ok()
+            .map(|value| value.with_timezone(&Utc))
+    }
+}
+
+/// `state/timers/<tenant>.<team>/<provider>.<handler_id>.json`
+pub fn timer_state_path(
+    state_dir: &Path,
+    tenant: &str,
+    team: &str,
+    handler: &TimerHandlerConfig,
+) -> PathBuf {
+    state_dir
+        .join("timers")
+        .join(format!("{tenant}.{team}"))
+        .join(format!(
+            "{}.{}.json",
+            bus::sanitize_token(&handler.provider),
+            bus::sanitize_token(&handler.handler_id)
+        ))
+}
+
+pub fn read_timer_state(
+    state_dir: &Path,
+    tenant: &str,
+    team: &str,
+    handler: &TimerHandlerConfig,
+) -> anyhow::Result<Option<TimerState>> {
+    runtime_state::read_json(&timer_state_path(state_dir, tenant, team, handler))
 }
 
 pub struct TimerScheduler {
@@ -66,7 +353,7 @@ impl TimerScheduler {
 #[derive(Clone)]
 struct ScheduledTimer {
     config: TimerHandlerConfig,
-    next_tick: Instant,
+    next_tick: DateTime<Utc>,
     last_run_rfc3339: Option<String>,
 }
 
@@ -80,54 +367,80 @@ struct TimerTickInputV1 {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     team: Option<String>,
     occurred_at: String,
+    /// Configured interval, or the gap to the next fire for cron schedules.
     interval_seconds: u64,
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    schedule: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     last_run: Option<String>,
+    /// Set on catch-up runs: the missed window this tick stands in for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scheduled_for: Option<String>,
 }
 
 fn run_scheduler_loop(config: TimerSchedulerConfig, rx: mpsc::Receiver<()>) -> anyhow::Result<()> {
-    if config.handlers.is_empty() {
+    if config.handlers.is_empty() && !config.scheduled_sends {
         return Ok(());
     }
+    let started = Utc::now();
     let mut timers = config
         .handlers
         .iter()
         .cloned()
         .map(|handler| ScheduledTimer {
-            next_tick: Instant::now() + Duration::from_secs(handler.interval_seconds.max(1)),
-            config: handler,
+            next_tick: handler
+                .schedule
+                .next_after(started)
+                .unwrap_or(DateTime::<Utc>::MAX_UTC),
             last_run_rfc3339: None,
+            config: handler,
         })
         .collect::<Vec<_>>();
 
     operator_log::info(
         module_path!(),
         format!(
-            "events timer scheduler started handlers={} tenant={} team={}",
+            "events timer scheduler started handlers={} tenant={} team={} catch_up={} scheduled_sends={}",
             timers.len(),
             config.tenant,
-            config.team.as_deref().unwrap_or("default")
+            config.team.as_deref().unwrap_or("default"),
+            config.catch_up.as_str(),
+            config.scheduled_sends
         ),
     );
 
+    for timer in &mut timers {
+        catch_up_missed_fires(&config, timer, started);
+    }
+
     loop {
-        let now = Instant::now();
+        let now = Utc::now();
         for timer in &mut timers {
             if now < timer.next_tick {
                 continue;
             }
-            if let Err(err) = run_timer_handler(&config, timer) {
-                operator_log::error(module_path!(), format!("timer handler failed: {err}"));
-            }
-            timer.next_tick = Instant::now() + Duration::from_secs(timer.config.interval_seconds);
+            fire_timer(&config, timer, None);
+            timer.next_tick = timer
+                .config
+                .schedule
+                .next_after(Utc::now())
+                .unwrap_or(DateTime::<Utc>::MAX_UTC);
         }
 
+        let max_sleep = if config.scheduled_sends {
+            run_scheduled_sends(&config);
+            SCHEDULED_SEND_POLL
+        } else {
+            Duration::from_secs(60)
+        };
+
+        let now = Utc::now();
         let sleep_for = timers
             .iter()
-            .map(|timer| timer.next_tick.saturating_duration_since(Instant::now()))
+            .map(|timer| (timer.next_tick - now).to_std().unwrap_or_default())
             .min()
-            .unwrap_or_else(|| Duration::from_millis(200))
-            .max(Duration::from_millis(50));
+            .unwrap_or(max_sleep)
+            .clamp(Duration::from_millis(50), max_sleep);
         if rx.recv_timeout(sleep_for).is_ok() {
             break;
         }
@@ -137,11 +450,130 @@ fn run_scheduler_loop(config: TimerSchedulerConfig, rx: mpsc::Receiver<()>) -> a
     Ok(())
 }
 
+fn run_scheduled_sends(config: &TimerSchedulerConfig) {
+    let team = config.team.as_deref().unwrap_or("default");
+    if let Err(err) = scheduled_send::run_due(
+        config.runner_host.bundle_root(),
+        &config.tenant,
+        team,
+        Utc::now(),
+    ) {
+        operator_log::warn(module_path!(), format!("scheduled sends failed: {err:#}"));
+    }
+}
+
+/// Compares the persisted last fire with the schedule and applies the catch-up policy.
+fn catch_up_missed_fires(
+    config: &TimerSchedulerConfig,
+    timer: &mut ScheduledTimer,
+    started: DateTime<Utc>,
+) {
+    let state_dir = config.runner_host.bundle_root().join("state");
+    let team = config.team.as_deref().unwrap_or("default");
+    let state = match read_timer_state(&state_dir, &config.tenant, team, &timer.config) {
+        Ok(state) => state,
+        Err(err) => {
+            operator_log::warn(
+                module_path!(),
+                format!("timer {} state unreadable: {err}", timer.config.key()),
+            );
+            None
+        }
+    };
+    let Some(state) = state else {
+        return;
+    };
+    timer.last_run_rfc3339 = Some(state.last_fire.clone());
+    let Some(last_fire) = state.last_fire_at() else {
+        return;
+    };
+    let missed = timer
+        .config
+        .schedule
+        .fires_between(last_fire, started, MAX_CATCH_UP_RUNS);
+    if missed.is_empty() {
+        return;
+    }
+    operator_log::warn(
+        module_path!(),
+        format!(
+            "timer {} missed {}{} window(s) since {}; catch_up={}",
+            timer.config.key(),
+            missed.len(),
+            if missed.len() == MAX_CATCH_UP_RUNS {
+                "+"
+            } else {
+                ""
+            },
+            state.last_fire,
+            config.catch_up.as_str()
+        ),
+    );
+    match config.catch_up {
+        TimerCatchUpPolicy::Skip => {}
+        TimerCatchUpPolicy::RunOnce => {
+            fire_timer(config, timer, missed.last().copied());
+        }
+        TimerCatchUpPolicy::RunAll => {
+            for scheduled_for in missed {
+                fire_timer(config, timer, Some(scheduled_for));
+            }
+        }
+    }
+}
+
+/// Runs the handler and persists the outcome as the handler's last fire.
+fn fire_timer(
+    config: &TimerSchedulerConfig,
+    timer: &mut ScheduledTimer,
+    scheduled_for: Option<DateTime<Utc>>,
+) {
+    let fired_at = Utc::now();
+    let result = run_timer_handler(config, timer, scheduled_for);
+    if let Err(err) = &result {
+        operator_log::error(module_path!(), format!("timer handler failed: {err}"));
+    }
+    let state = TimerState {
+        provider: timer.config.provider.clone(),
+        handler_id: timer.config.handler_id.clone(),
+        op_id: timer.config.op_id.clone(),
+        last_fire: fired_at.to_rfc3339(),
+        ok: result.is_ok(),
+        error: result.err().map(|err| err.to_string()),
+    };
+    let path = timer_state_path(
+        &config.runner_host.bundle_root().join("state"),
+        &config.tenant,
+        config.team.as_deref().unwrap_or("default"),
+        &timer.config,
+    );
+    if let Err(err) = runtime_state::write_json(&path, &state) {
+        operator_log::warn(
+            module_path!(),
+            format!("failed to persist timer state {}: {err}", path.display()),
+        );
+    }
+}
+
 fn run_timer_handler(
     scheduler: &TimerSchedulerConfig,
     timer: &mut ScheduledTimer,
+    scheduled_for: Option<DateTime<Utc>>,
 ) -> anyhow::Result<()> {
-    let occurred_at = Utc::now().to_rfc3339();
+    let fired_at = Utc::now();
+    let occurred_at = fired_at.to_rfc3339();
+    let interval_seconds = timer.config.schedule.interval_seconds().unwrap_or_else(|| {
+        timer
+            .config
+            .schedule
+            .next_after(fired_at)
+            .and_then(|next| u64::try_from((next - fired_at).num_seconds()).ok())
+            .unwrap_or_default()
+    });
+    let schedule = match &timer.config.schedule {
+        TimerSchedule::Interval { .. } => None,
+        TimerSchedule::Cron { expressions } => Some(expressions.join(" | ")),
+    };
     let payload = TimerTickInputV1 {
         v: 1,
         domain: "events".to_string(),
@@ -150,8 +582,10 @@ fn run_timer_handler(
         tenant: scheduler.tenant.clone(),
         team: scheduler.team.clone(),
         occurred_at: occurred_at.clone(),
-        interval_seconds: timer.config.interval_seconds,
+        interval_seconds,
+        schedule,
         last_run: timer.last_run_rfc3339.clone(),
+        scheduled_for: scheduled_for.map(|value| value.to_rfc3339()),
     };
     let bytes = greentic_types::cbor::canonical::to_canonical_cbor(&payload)
         .map_err(|err| anyhow::anyhow!("{err}"))?;
@@ -196,12 +630,55 @@ fn run_timer_handler(
     let output = outcome.output.unwrap_or_else(|| json!({}));
     let events = parse_events(&output)?;
     if !events.is_empty() {
+        publish_events(scheduler, &timer.config.provider, &events);
         route_events_to_default_flow(scheduler.runner_host.bundle_root(), &context, &events)?;
     }
     timer.last_run_rfc3339 = Some(occurred_at);
     Ok(())
 }
 
+/// Fires `handler` once, outside the scheduler loop (`demo test` timer cases).
+pub fn tick_handler(
+    runner_host: Arc<DemoRunnerHost>,
+    tenant: &str,
+    team: Option<&str>,
+    handler: TimerHandlerConfig,
+) -> anyhow::Result<()> {
+    let config = TimerSchedulerConfig {
+        runner_host,
+        tenant: tenant.to_string(),
+        team: team.map(str::to_string),
+        handlers: Vec::new(),
+        catch_up: TimerCatchUpPolicy::default(),
+        debug_enabled: false,
+        scheduled_sends: false,
+    };
+    let mut timer = ScheduledTimer {
+        next_tick: Utc::now(),
+        last_run_rfc3339: None,
+        config: handler,
+    };
+    run_timer_handler(&config, &mut timer, None)
+}
+
+/// Timer events go on the bus like HTTP ingress events so bridges (e.g. Kafka) see them.
+fn publish_events(scheduler: &TimerSchedulerConfig, provider: &str, events: &[EventEnvelopeV1]) {
+    let team = scheduler.team.as_deref().unwrap_or("default");
+    let bus = bus::for_scope(scheduler.runner_host.bundle_root(), &scheduler.tenant, team);
+    let subject = bus::ingress_subject(&scheduler.tenant, team, "events", provider);
+    for event in events {
+        let published = serde_json::to_value(event)
+            .map_err(anyhow::Error::from)
+            .and_then(|value| bus.publish(&subject, &value));
+        if let Err(err) = published {
+            operator_log::warn(
+                module_path!(),
+                format!("timer event publish failed subject={subject} err={err}"),
+            );
+        }
+    }
+}
+
 fn parse_events(output: &JsonValue) -> anyhow::Result<Vec<EventEnvelopeV1>> {
     let Some(array) = output.get("events").and_then(JsonValue::as_array) else {
         return Ok(Vec::new());
@@ -251,7 +728,7 @@ pub fn discover_timer_handlers(
                     provider: provider.provider_id.clone(),
                     op_id: op,
                     handler_id,
-                    interval_seconds,
+                    schedule: TimerSchedule::interval(interval_seconds),
                 });
             }
         }
@@ -270,7 +747,7 @@ fn parse_explicit_timer_handlers(
         if let Some(values) = inline.get(key).and_then(JsonValue::as_array) {
             for entry in values {
                 if let Some(handler) =
-                    parse_timer_handler_entry(entry, default_provider, default_interval_seconds)
+                    parse_timer_handler_entry(entry, default_provider, default_interval_seconds)?
                 {
                     handlers.push(handler);
                 }
@@ -293,7 +770,7 @@ fn parse_explicit_timer_handlers(
                             entry,
                             default_provider,
                             default_interval_seconds,
-                        ) {
+                        )? {
                             if handler.provider == default_provider {
                                 handler.provider = provider_type.to_string();
                             }
@@ -307,25 +784,32 @@ fn parse_explicit_timer_handlers(
     Ok(handlers)
 }
 
+/// Handler entries may carry `interval_seconds`, a `cron` (or `schedule`) expression,
+/// or a `calendar: {at: ["09:00"], days: ["mon"]}` block.
 fn parse_timer_handler_entry(
     value: &JsonValue,
     default_provider: &str,
     default_interval_seconds: u64,
-) -> Option<TimerHandlerConfig> {
+) -> anyhow::Result<Option<TimerHandlerConfig>> {
     if let Some(op_id) = value.as_str() {
-        return Some(TimerHandlerConfig {
+        return Ok(Some(TimerHandlerConfig {
             provider: default_provider.to_string(),
             op_id: op_id.to_string(),
             handler_id: "default".to_string(),
-            interval_seconds: default_interval_seconds,
-        });
+            schedule: TimerSchedule::interval(default_interval_seconds),
+        }));
     }
-    let obj = value.as_object()?;
-    let op_id = obj
+    let Some(obj) = value.as_object() else {
+        return Ok(None);
+    };
+    let Some(op_id) = obj
         .get("op_id")
         .and_then(JsonValue::as_str)
-        .or_else(|| obj.get("op").and_then(JsonValue::as_str))?
-        .to_string();
+        .or_else(|| obj.get("op").and_then(JsonValue::as_str))
+        .map(str::to_string)
+    else {
+        return Ok(None);
+    };
     let handler_id = obj
         .get("handler_id")
         .and_then(JsonValue::as_str)
@@ -338,18 +822,32 @@ fn parse_timer_handler_entry(
         .or_else(|| obj.get("provider").and_then(JsonValue::as_str))
         .unwrap_or(default_provider)
         .to_string();
-    let interval_seconds = obj
-        .get("interval_seconds")
-        .and_then(JsonValue::as_u64)
-        .or_else(|| obj.get("interval").and_then(JsonValue::as_u64))
-        .unwrap_or(default_interval_seconds)
-        .max(1);
-    Some(TimerHandlerConfig {
+    let cron = obj
+        .get("cron")
+        .or_else(|| obj.get("schedule"))
+        .and_then(JsonValue::as_str);
+    let schedule = if let Some(expression) = cron {
+        TimerSchedule::cron(expression)
+            .with_context(|| format!("timer handler {provider}/{handler_id}"))?
+    } else if let Some(calendar) = obj.get("calendar") {
+        let calendar: DemoTimerCalendar = serde_json::from_value(calendar.clone())
+            .with_context(|| format!("timer handler {provider}/{handler_id} calendar"))?;
+        TimerSchedule::calendar(&calendar)
+            .with_context(|| format!("timer handler {provider}/{handler_id}"))?
+    } else {
+        TimerSchedule::interval(
+            obj.get("interval_seconds")
+                .and_then(JsonValue::as_u64)
+                .or_else(|| obj.get("interval").and_then(JsonValue::as_u64))
+                .unwrap_or(default_interval_seconds),
+        )
+    };
+    Ok(Some(TimerHandlerConfig {
         provider,
         op_id,
         handler_id,
-        interval_seconds,
-    })
+        schedule,
+    }))
 }
 
 fn parse_provider_ops(manifest_json: &JsonValue, provider_id: &str) -> anyhow::Result<Vec<String>> {
@@ -410,8 +908,14 @@ fn parse_timer_op(op: &str, default_interval_seconds: u64) -> Option<(String, u6
 
 #[cfg(test)]
 mod tests {
-    use super::{parse_explicit_timer_handlers, parse_timer_op};
+    use super::{
+        TimerHandlerConfig, TimerSchedule, apply_timer_overrides, parse_explicit_timer_handlers,
+        parse_timer_op, parse_weekday,
+    };
+    use crate::config::{DemoTimerCalendar, DemoTimerOverride, TimerCatchUpPolicy};
+    use chrono::{TimeZone, Utc};
     use serde_json::json;
+    use std::collections::BTreeMap;
 
     #[test]
     fn timer_op_conventions_are_detected() {
@@ -430,6 +934,17 @@ mod tests {
         assert!(parse_timer_op("ingest_http", 30).is_none());
     }
 
+    #[test]
+    fn weekdays_match_full_names_or_abbreviations() {
+        assert_eq!(parse_weekday("monday").unwrap(), "Mon");
+        assert_eq!(parse_weekday("SAT").unwrap(), "Sat");
+        assert!(parse_weekday("mo").is_err());
+        assert!(parse_weekday("monkey").is_err());
+        assert!(parse_weekday("sunda").is_err());
+        assert!(parse_weekday("mö").is_err());
+        assert!(parse_weekday("möntag").is_err());
+    }
+
     #[test]
     fn parses_explicit_timer_handlers_from_extension() {
         let manifest = json!({
@@ -449,6 +964,133 @@ mod tests {
         assert_eq!(handlers[0].provider, "events-twilio");
         assert_eq!(handlers[0].op_id, "timer_poll");
         assert_eq!(handlers[0].handler_id, "poll");
-        assert_eq!(handlers[0].interval_seconds, 15);
+        assert_eq!(handlers[0].schedule.interval_seconds(), Some(15));
+    }
+
+    #[test]
+    fn parses_cron_and_calendar_handlers() {
+        let manifest = json!({
+            "extensions": {
+                "greentic.provider-extension.v1": {
+                    "inline": {
+                        "timer_handlers": [
+                            {"op_id":"timer_digest","handler_id":"digest","cron":"30 9 * * Mon-Fri"},
+                            {"op_id":"timer_report","handler_id":"report","calendar":{"at":["08:15","17:45"],"days":["sat"]}}
+                        ]
+                    }
+                }
+            }
+        });
+        let handlers =
+            parse_explicit_timer_handlers(&manifest, "events-demo", 60).expect("parse explicit");
+        assert_eq!(
+            handlers[0].schedule,
+            TimerSchedule::Cron {
+                expressions: vec!["0 30 9 * * Mon-Fri".to_string()]
+            }
+        );
+        // Friday 2026-01-02 10:00 UTC: the next weekday 09:30 is Monday.
+        let friday = Utc.with_ymd_and_hms(2026, 1, 2, 10, 0, 0).unwrap();
+        assert_eq!(
+            handlers[0].schedule.next_after(friday),
+            Some(Utc.with_ymd_and_hms(2026, 1, 5, 9, 30, 0).unwrap())
+        );
+        assert_eq!(
+            handlers[1].schedule.next_after(friday),
+            Some(Utc.with_ymd_and_hms(2026, 1, 3, 8, 15, 0).unwrap())
+        );
+
+        let bad = json!({
+            "extensions": {
+                "greentic.provider-extension.v1": {
+                    "inline": {"timer_handlers": [{"op_id":"timer_x","cron":"not a cron"}]}
+                }
+            }
+        });
+        assert!(parse_explicit_timer_handlers(&bad, "events-demo", 60).is_err());
+    }
+
+    #[test]
+    fn missed_fires_are_listed_oldest_first_and_capped() {
+        let since = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        let until = Utc.with_ymd_and_hms(2026, 1, 1, 0, 5, 0).unwrap();
+        let every_minute = TimerSchedule::interval(60);
+        let fires = every_minute.fires_between(since, until, 100);
+        assert_eq!(fires.len(), 5);
+        assert_eq!(fires[0], Utc.with_ymd_and_hms(2026, 1, 1, 0, 1, 0).unwrap());
+        assert_eq!(fires[4], until);
+        assert_eq!(every_minute.fires_between(since, until, 2).len(), 2);
+
+        let daily = TimerSchedule::cron("0 3 * * *").expect("cron");
+        assert!(daily.fires_between(since, until, 100).is_empty());
+    }
+
+    #[test]
+    fn classic_weekday_numbers_count_from_sunday() {
+        use chrono::Datelike;
+        // 2026-01-04 is a Sunday.
+        let since = Utc.with_ymd_and_hms(2026, 1, 4, 0, 0, 0).unwrap();
+        let until = since + chrono::Duration::days(7);
+        let weekdays = |expression: &str| {
+            TimerSchedule::cron(expression)
+                .expect("cron")
+                .fires_between(since, until, 10)
+                .iter()
+                .map(|fire| fire.weekday().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(weekdays("0 9 * * 1-5"), ["Mon", "Tue", "Wed", "Thu", "Fri"]);
+        assert_eq!(weekdays("0 9 * * 0"), ["Sun"]);
+        assert_eq!(weekdays("0 9 * * 7"), ["Sun"]);
+        assert_eq!(weekdays("0 9 * * 5-7"), ["Sun", "Fri", "Sat"]);
+        assert_eq!(weekdays("0 9 * * 0/2"), ["Sun", "Tue", "Thu", "Sat"]);
+        assert_eq!(weekdays("0 9 * * Mon-Fri").len(), 5);
+        assert!(TimerSchedule::cron("0 9 * * 8").is_err());
+    }
+
+    #[test]
+    fn bundle_overrides_replace_or_disable_schedules() {
+        let handler = |handler_id: &str| TimerHandlerConfig {
+            provider: "events-demo".to_string(),
+            op_id: "timer_tick".to_string(),
+            handler_id: handler_id.to_string(),
+            schedule: TimerSchedule::interval(60),
+        };
+        let mut overrides = BTreeMap::new();
+        overrides.insert(
+            "events-demo".to_string(),
+            DemoTimerOverride {
+                interval_seconds: Some(5),
+                ..DemoTimerOverride::default()
+            },
+        );
+        overrides.insert(
+            "events-demo/nightly".to_string(),
+            DemoTimerOverride {
+                calendar: Some(DemoTimerCalendar {
+                    at: vec!["02:00".to_string()],
+                    days: Vec::new(),
+                }),
+                ..DemoTimerOverride::default()
+            },
+        );
+        overrides.insert(
+            "events-demo/off".to_string(),
+            DemoTimerOverride {
+                enabled: Some(false),
+                ..DemoTimerOverride::default()
+            },
+        );
+        let applied = apply_timer_overrides(
+            vec![handler("default"), handler("nightly"), handler("off")],
+            &overrides,
+        )
+        .expect("apply overrides");
+        assert_eq!(applied.len(), 2);
+        assert_eq!(applied[0].schedule, TimerSchedule::interval(5));
+        assert_eq!(
+            applied[1].schedule.describe(),
+            "cron 0 0 2 * * *".to_string()
+        );
     }
 }
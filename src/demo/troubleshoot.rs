@@ -0,0 +1,271 @@
+//! Diagnosis offered after a failed `demo send` on a terminal.
+//!
+//! Stitches together what `demo send --print-required-args`, `demo verify`,
+//! `demo start` and `demo dlq list` would each show for one provider: whether its
+//! secrets are in the store, whether its requirements op passes, whether the
+//! recorded public URL answers, and the newest dead letter. The first problem found,
+//! in that order, is reported as the most likely cause.
+
+use std::path::Path;
+use std::time::Duration;
+
+use crate::demo::runner_host::FlowOutcome;
+use crate::demo::verify::{self, VerifyStatus};
+use crate::messaging_universal::dlq;
+use crate::operator_i18n;
+
+const TUNNEL_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckKind {
+    Secrets,
+    Requirements,
+    Tunnel,
+    DeadLetters,
+}
+
+impl CheckKind {
+    fn name(self) -> String {
+        match self {
+            CheckKind::Secrets => {
+                operator_i18n::tr("cli.demo_troubleshoot.check_secrets", "secrets")
+            }
+            CheckKind::Requirements => {
+                operator_i18n::tr("cli.demo_troubleshoot.check_requirements", "requirements")
+            }
+            CheckKind::Tunnel => operator_i18n::tr("cli.demo_troubleshoot.check_tunnel", "tunnel"),
+            CheckKind::DeadLetters => {
+                operator_i18n::tr("cli.demo_troubleshoot.check_dlq", "dead letters")
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Check {
+    pub kind: CheckKind,
+    pub status: VerifyStatus,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(kind: CheckKind, status: VerifyStatus, detail: String) -> Self {
+        Self {
+            kind,
+            status,
+            detail,
+        }
+    }
+
+    /// One report line, `PASS secrets: ...`.
+    pub fn render(&self) -> String {
+        format!(
+            "{} {}: {}",
+            self.status.label(),
+            self.kind.name(),
+            self.detail
+        )
+    }
+}
+
+/// Judges the result of [`crate::secrets_gate::check_provider_secrets`].
+pub fn secrets_check(result: anyhow::Result<Option<Vec<String>>>) -> Check {
+    match result {
+        Ok(None) => Check::new(
+            CheckKind::Secrets,
+            VerifyStatus::Passed,
+            operator_i18n::tr(
+                "cli.demo_troubleshoot.secrets_ok",
+                "every secret the pack declares is in the store",
+            ),
+        ),
+        Ok(Some(missing)) => {
+            Check::new(CheckKind::Secrets, VerifyStatus::Failed, missing.join(", "))
+        }
+        Err(err) => Check::new(
+            CheckKind::Secrets,
+            VerifyStatus::Skipped,
+            format!("{err:#}"),
+        ),
+    }
+}
+
+/// Judges the provider's `requirements` op; `None` when the pack has no such flow.
+pub fn requirements_check(result: Option<anyhow::Result<FlowOutcome>>) -> Check {
+    let Some(result) = result else {
+        return Check::new(
+            CheckKind::Requirements,
+            VerifyStatus::Skipped,
+            operator_i18n::tr(
+                "cli.demo_troubleshoot.requirements_missing",
+                "the pack has no requirements flow",
+            ),
+        );
+    };
+    match verify::judge(result) {
+        (VerifyStatus::Failed, error) => Check::new(
+            CheckKind::Requirements,
+            VerifyStatus::Failed,
+            error.unwrap_or_default(),
+        ),
+        (status, _) => Check::new(
+            CheckKind::Requirements,
+            status,
+            operator_i18n::tr("cli.demo_troubleshoot.requirements_ok", "the op succeeded"),
+        ),
+    }
+}
+
+/// Requests the public URL recorded by `demo start`. Any HTTP answer, even an
+/// error status, means the tunnel is up; only a transport failure counts.
+pub fn tunnel_check(public_url: Option<&str>) -> Check {
+    let Some(url) = public_url else {
+        return Check::new(
+            CheckKind::Tunnel,
+            VerifyStatus::Skipped,
+            operator_i18n::tr(
+                "cli.demo_troubleshoot.tunnel_none",
+                "no public URL recorded; demo start is not running with a tunnel",
+            ),
+        );
+    };
+    let agent = ureq::Agent::config_builder()
+        .timeout_global(Some(TUNNEL_TIMEOUT))
+        .http_status_as_error(false)
+        .build()
+        .new_agent();
+    match agent.get(url).call() {
+        Ok(response) => Check::new(
+            CheckKind::Tunnel,
+            VerifyStatus::Passed,
+            operator_i18n::trf(
+                "cli.demo_troubleshoot.tunnel_ok",
+                "{} answered with HTTP {}",
+                &[url, &response.status().as_u16().to_string()],
+            ),
+        ),
+        Err(err) => Check::new(
+            CheckKind::Tunnel,
+            VerifyStatus::Failed,
+            format!("{url}: {err}"),
+        ),
+    }
+}
+
+/// Reports the newest dead letter for `provider`, if any.
+pub fn dead_letter_check(state_dir: &Path, provider: &str) -> Check {
+    match dlq::list_records(state_dir, Some(provider)) {
+        Ok(records) => match records.last() {
+            Some(record) => Check::new(
+                CheckKind::DeadLetters,
+                VerifyStatus::Failed,
+                operator_i18n::trf(
+                    "cli.demo_troubleshoot.dlq_last",
+                    "{} failed at {} after {} attempt(s): {}",
+                    &[
+                        &record.job_id,
+                        &record.failed_at,
+                        &record.attempt.to_string(),
+                        record.failure_message(),
+                    ],
+                ),
+            ),
+            None => Check::new(
+                CheckKind::DeadLetters,
+                VerifyStatus::Passed,
+                operator_i18n::tr("cli.demo_troubleshoot.dlq_empty", "none"),
+            ),
+        },
+        Err(err) => Check::new(
+            CheckKind::DeadLetters,
+            VerifyStatus::Skipped,
+            format!("{err:#}"),
+        ),
+    }
+}
+
+/// The most likely cause of the failed send: the first failed check, else the
+/// provider's own error.
+pub fn likely_cause(checks: &[Check], send_error: &str) -> String {
+    let failed = checks
+        .iter()
+        .find(|check| check.status == VerifyStatus::Failed);
+    match failed {
+        Some(check) => match check.kind {
+            CheckKind::Secrets => operator_i18n::trf(
+                "cli.demo_troubleshoot.cause_secrets",
+                "The provider's secrets are missing ({}). Run `greentic-operator demo setup` for this tenant and team, and check that --tenant, --team and --env match the ones used during setup.",
+                &[&check.detail],
+            ),
+            CheckKind::Requirements => operator_i18n::trf(
+                "cli.demo_troubleshoot.cause_requirements",
+                "The provider's requirements op fails ({}), so its configuration is incomplete. Run `greentic-operator demo send --print-required-args` to see what it needs.",
+                &[&check.detail],
+            ),
+            CheckKind::Tunnel => operator_i18n::trf(
+                "cli.demo_troubleshoot.cause_tunnel",
+                "The public URL does not answer ({}). Restart `greentic-operator demo start` so the tunnel and webhooks come back.",
+                &[&check.detail],
+            ),
+            CheckKind::DeadLetters => operator_i18n::trf(
+                "cli.demo_troubleshoot.cause_dlq",
+                "Earlier sends failed the same way: {}. Fix the cause, then resend them with `greentic-operator demo dlq replay`.",
+                &[&check.detail],
+            ),
+        },
+        None => operator_i18n::trf(
+            "cli.demo_troubleshoot.cause_provider",
+            "No local problem found; the provider itself rejected the send: {}. See `greentic-operator explain flow_failed`.",
+            &[send_error],
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::demo::runner_host::RunnerExecutionMode;
+    use serde_json::json;
+
+    #[test]
+    fn first_failed_check_is_the_likely_cause() {
+        let secrets = secrets_check(Ok(Some(vec![
+            "secrets://demo/acme/_/telegram/bot_token".to_string(),
+        ])));
+        assert_eq!(secrets.status, VerifyStatus::Failed);
+        let requirements = requirements_check(Some(Ok(FlowOutcome {
+            success: true,
+            output: Some(json!({"ok": false, "error": "api_base_url not set"})),
+            raw: None,
+            error: None,
+            mode: RunnerExecutionMode::Exec,
+        })));
+        assert_eq!(requirements.detail, "api_base_url not set");
+        let tunnel = tunnel_check(None);
+        assert_eq!(tunnel.status, VerifyStatus::Skipped);
+
+        let cause = likely_cause(
+            &[secrets.clone(), requirements.clone(), tunnel.clone()],
+            "401",
+        );
+        assert!(cause.contains("bot_token"), "{cause}");
+        let cause = likely_cause(&[requirements, tunnel.clone()], "401");
+        assert!(cause.contains("api_base_url not set"), "{cause}");
+        let cause = likely_cause(&[secrets_check(Ok(None)), tunnel], "401 Unauthorized");
+        assert!(cause.contains("401 Unauthorized"), "{cause}");
+    }
+
+    #[test]
+    fn unreachable_tunnel_and_empty_dlq() -> anyhow::Result<()> {
+        assert_eq!(
+            tunnel_check(Some("http://127.0.0.1:1")).status,
+            VerifyStatus::Failed
+        );
+        let dir = tempfile::tempdir()?;
+        assert_eq!(
+            dead_letter_check(dir.path(), "telegram").status,
+            VerifyStatus::Passed
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+//! `demo verify`: runs each provider's verify flows and reports pass/fail.
+//!
+//! A pack's verify flows are its domain's `verify_flows` followed by the ones it
+//! declares in `meta.verify_flows`, as planned by [`domains::plan_runs`] for
+//! [`DomainAction::Verify`]. Each flow is invoked as a provider op through the runner
+//! host, so quotas, hooks and run records apply as for any other op. A flow passes
+//! when it succeeds without answering `ok: false`; providers with no verify flow are
+//! reported as skipped.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::json;
+
+use crate::demo::circuit_breaker;
+use crate::demo::runner_host::{DemoRunnerHost, FlowOutcome, OperatorContext};
+use crate::discovery::DiscoveryResult;
+use crate::domains::{self, Domain, DomainAction};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyStatus {
+    Passed,
+    Failed,
+    /// The provider has no verify flow.
+    Skipped,
+}
+
+impl VerifyStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            VerifyStatus::Passed => "PASS",
+            VerifyStatus::Failed => "FAIL",
+            VerifyStatus::Skipped => "SKIP",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct VerifyResult {
+    pub domain: &'static str,
+    pub provider: String,
+    pub pack: String,
+    /// `None` for a skipped provider.
+    pub flow: Option<String>,
+    pub status: VerifyStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Whether `provider` selects the pack: its provider id, pack id or file stem.
+pub fn matches_provider(provider: &str, provider_id: &str, pack: &domains::ProviderPack) -> bool {
+    let file_stem = pack
+        .file_name
+        .strip_suffix(".gtpack")
+        .unwrap_or(&pack.file_name);
+    provider == provider_id || provider == pack.pack_id || provider == file_stem
+}
+
+/// Status and error of one verify op.
+pub fn judge(result: anyhow::Result<FlowOutcome>) -> (VerifyStatus, Option<String>) {
+    match result {
+        Ok(outcome) => match circuit_breaker::outcome_error(&outcome) {
+            None => (VerifyStatus::Passed, None),
+            Some(error) => (VerifyStatus::Failed, Some(error)),
+        },
+        Err(err) => (VerifyStatus::Failed, Some(format!("{err:#}"))),
+    }
+}
+
+/// Runs the verify flows of every discovered provider of `domain`, optionally only
+/// the one named by `provider`.
+pub fn verify_domain(
+    runner_host: &DemoRunnerHost,
+    bundle: &Path,
+    discovery: &DiscoveryResult,
+    domain: Domain,
+    provider: Option<&str>,
+    ctx: &OperatorContext,
+    public_base_url: Option<&str>,
+) -> anyhow::Result<Vec<VerifyResult>> {
+    let domain_name = domains::domain_name(domain);
+    let provider_ids: BTreeMap<PathBuf, String> = discovery
+        .providers
+        .iter()
+        .filter(|detected| detected.domain == domain_name)
+        .map(|detected| (detected.pack_path.clone(), detected.provider_id.clone()))
+        .collect();
+    let mut packs = domains::discover_provider_packs_cbor_only(bundle, domain)?;
+    packs.retain(|pack| {
+        provider_ids.get(&pack.path).is_some_and(|provider_id| {
+            provider.is_none_or(|provider| matches_provider(provider, provider_id, pack))
+        })
+    });
+    let plan = domains::plan_runs(domain, DomainAction::Verify, &packs, None, true)?;
+
+    let team = ctx.team.as_deref().unwrap_or("default");
+    let mut results = Vec::new();
+    for pack in &packs {
+        let provider_id = &provider_ids[&pack.path];
+        let mut flows = plan
+            .iter()
+            .filter(|item| item.pack.path == pack.path)
+            .map(|item| item.flow_id.as_str())
+            .peekable();
+        if flows.peek().is_none() {
+            results.push(VerifyResult {
+                domain: domain_name,
+                provider: provider_id.clone(),
+                pack: pack.file_name.clone(),
+                flow: None,
+                status: VerifyStatus::Skipped,
+                error: None,
+            });
+            continue;
+        }
+        let payload = json!({
+            "id": provider_id,
+            "tenant": ctx.tenant,
+            "team": team,
+            "public_base_url": public_base_url,
+            "config": {
+                "id": pack.pack_id,
+                "public_base_url": public_base_url,
+            },
+        });
+        let bytes = serde_json::to_vec(&payload)?;
+        for flow in flows {
+            let (status, error) =
+                judge(runner_host.invoke_provider_op(domain, provider_id, flow, &bytes, ctx));
+            results.push(VerifyResult {
+                domain: domain_name,
+                provider: provider_id.clone(),
+                pack: pack.file_name.clone(),
+                flow: Some(flow.to_string()),
+                status,
+                error,
+            });
+        }
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::demo::runner_host::RunnerExecutionMode;
+
+    fn outcome(success: bool, output: serde_json::Value) -> FlowOutcome {
+        FlowOutcome {
+            success,
+            output: Some(output),
+            raw: None,
+            error: (!success).then(|| "exit status 1".to_string()),
+            mode: RunnerExecutionMode::Exec,
+        }
+    }
+
+    #[test]
+    fn judges_outcomes_and_matches_providers() {
+        assert_eq!(
+            judge(Ok(outcome(true, json!({"ok": true})))),
+            (VerifyStatus::Passed, None)
+        );
+        assert_eq!(
+            judge(Ok(outcome(
+                true,
+                json!({"ok": false, "error": "webhook gone"})
+            ))),
+            (VerifyStatus::Failed, Some("webhook gone".to_string()))
+        );
+        assert_eq!(
+            judge(Ok(outcome(false, json!({})))).1.as_deref(),
+            Some("exit status 1")
+        );
+        assert_eq!(
+            judge(Err(anyhow::anyhow!("runner missing"))).0,
+            VerifyStatus::Failed
+        );
+
+        let pack = domains::ProviderPack {
+            pack_id: "messaging-telegram".to_string(),
+            file_name: "telegram.gtpack".to_string(),
+            path: PathBuf::from("providers/messaging/telegram.gtpack"),
+            entry_flows: vec!["verify_webhooks".to_string()],
+            depends_on: Vec::new(),
+            verify_flows: Vec::new(),
+        };
+        assert!(matches_provider("telegram", "telegram", &pack));
+        assert!(matches_provider("messaging-telegram", "tg", &pack));
+        assert!(!matches_provider("tele", "tg", &pack));
+    }
+}
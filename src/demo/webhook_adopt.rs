@@ -0,0 +1,316 @@
+//! Imports webhook registrations a provider already holds (`demo setup --adopt`).
+//!
+//! Re-running setup makes some providers register the same webhook twice. With
+//! `--adopt`, every provider that implements `list_webhooks` (or, failing that,
+//! `list_subscriptions`) is asked for its registrations first. Those pointing at the
+//! recorded public base URL, or carrying a client state the subscription store
+//! already knows, are written to the store as `adopted-<id>` bindings, and the
+//! provider's setup flow is skipped. Providers without a list op, or with nothing
+//! that matches, are set up as usual.
+
+use std::collections::BTreeSet;
+
+use chrono::DateTime;
+use serde::Serialize;
+use serde_json::{Value as JsonValue, json};
+
+use crate::demo::runner_host::{DemoRunnerHost, OperatorContext};
+use crate::demo::webhook_watcher::WebhookProvider;
+use crate::subscriptions_universal::{SubscriptionState, SubscriptionStore};
+
+/// Provider ops tried in order to list existing registrations.
+pub const LIST_OPS: [&str; 2] = ["list_webhooks", "list_subscriptions"];
+
+/// Keys a list op may put its registrations under; a bare array also works.
+const LIST_KEYS: &[&str] = &["webhooks", "subscriptions", "items", "value"];
+
+/// One registration as reported by a list op.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Registration {
+    pub id: Option<String>,
+    pub url: Option<String>,
+    pub client_state: Option<String>,
+    pub resource: Option<String>,
+    pub change_types: Vec<String>,
+    pub expiration_unix_ms: Option<i64>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct AdoptOutcome {
+    pub provider: String,
+    /// `None` when the provider has no list op.
+    pub op: Option<&'static str>,
+    /// Registrations the provider reported.
+    pub listed: usize,
+    /// Binding ids written to the subscription store.
+    pub adopted: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// First of [`LIST_OPS`] the provider supports.
+pub fn select_op(supports: impl Fn(&str) -> bool) -> Option<&'static str> {
+    LIST_OPS.into_iter().find(|op| supports(op))
+}
+
+/// Registrations in a list op's output.
+pub fn parse_registrations(output: &JsonValue) -> Vec<Registration> {
+    let items = match output {
+        JsonValue::Array(items) => Some(items),
+        JsonValue::Object(_) => LIST_KEYS
+            .iter()
+            .find_map(|key| output.get(key).and_then(JsonValue::as_array)),
+        _ => None,
+    };
+    items
+        .into_iter()
+        .flatten()
+        .filter(|item| item.is_object())
+        .map(registration)
+        .collect()
+}
+
+fn registration(item: &JsonValue) -> Registration {
+    let text = |keys: &[&str]| {
+        keys.iter().find_map(|key| {
+            item.get(key)
+                .and_then(|value| match value {
+                    JsonValue::String(text) => Some(text.clone()),
+                    JsonValue::Number(number) => Some(number.to_string()),
+                    _ => None,
+                })
+                .filter(|text| !text.trim().is_empty())
+        })
+    };
+    let change_types = match item.get("change_types").or_else(|| item.get("changeType")) {
+        Some(JsonValue::Array(values)) => values
+            .iter()
+            .filter_map(JsonValue::as_str)
+            .map(str::to_string)
+            .collect(),
+        Some(JsonValue::String(joined)) => joined
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    };
+    let expiration_unix_ms = item
+        .get("expiration_unix_ms")
+        .and_then(JsonValue::as_i64)
+        .or_else(|| {
+            text(&["expirationDateTime", "expiration", "expires_at"])
+                .and_then(|value| DateTime::parse_from_rfc3339(&value).ok())
+                .map(|at| at.timestamp_millis())
+        });
+    Registration {
+        id: text(&["subscription_id", "webhook_id", "id"]),
+        url: text(&[
+            "notification_url",
+            "notificationUrl",
+            "url",
+            "target_url",
+            "targetUrl",
+            "callback_url",
+        ]),
+        client_state: text(&["client_state", "clientState", "secret_token"]),
+        resource: text(&["resource", "resource_id"]),
+        change_types,
+        expiration_unix_ms,
+    }
+}
+
+/// Whether `registration` is ours: its URL lives under `public_base_url`, or it
+/// carries one of `client_states`.
+pub fn matches(
+    registration: &Registration,
+    public_base_url: Option<&str>,
+    client_states: &BTreeSet<String>,
+) -> bool {
+    let url_matches = match (registration.url.as_deref(), public_base_url) {
+        (Some(url), Some(base)) => {
+            let base = base.trim_end_matches('/');
+            !base.is_empty()
+                && url
+                    .strip_prefix(base)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '?']))
+        }
+        _ => false,
+    };
+    url_matches
+        || registration
+            .client_state
+            .as_ref()
+            .is_some_and(|state| client_states.contains(state))
+}
+
+/// Lists and imports the registrations of every provider in `providers`.
+pub fn adopt(
+    runner_host: &DemoRunnerHost,
+    store: &SubscriptionStore,
+    providers: &[WebhookProvider],
+    ctx: &OperatorContext,
+    public_base_url: Option<&str>,
+) -> Vec<AdoptOutcome> {
+    let known = store.list_states().unwrap_or_default();
+    providers
+        .iter()
+        .map(|target| {
+            let client_states = known
+                .iter()
+                .filter(|state| state.provider == target.provider)
+                .filter_map(|state| state.client_state.clone())
+                .collect();
+            adopt_provider(
+                runner_host,
+                store,
+                target,
+                ctx,
+                public_base_url,
+                &client_states,
+            )
+        })
+        .collect()
+}
+
+fn adopt_provider(
+    runner_host: &DemoRunnerHost,
+    store: &SubscriptionStore,
+    target: &WebhookProvider,
+    ctx: &OperatorContext,
+    public_base_url: Option<&str>,
+    client_states: &BTreeSet<String>,
+) -> AdoptOutcome {
+    let mut outcome = AdoptOutcome {
+        provider: target.provider.clone(),
+        op: select_op(|op| runner_host.supports_op(target.domain, &target.provider, op)),
+        listed: 0,
+        adopted: Vec::new(),
+        error: None,
+    };
+    let Some(op) = outcome.op else {
+        return outcome;
+    };
+    let team = ctx.team.as_deref().unwrap_or("default");
+    let payload = json!({
+        "id": target.provider,
+        "tenant": ctx.tenant,
+        "team": team,
+        "public_base_url": public_base_url,
+        "config": {
+            "id": target.provider,
+            "public_base_url": public_base_url,
+        },
+    });
+    let result = serde_json::to_vec(&payload)
+        .map_err(anyhow::Error::from)
+        .and_then(|bytes| {
+            runner_host.invoke_provider_op(target.domain, &target.provider, op, &bytes, ctx)
+        });
+    let output = match result {
+        Ok(flow) if flow.success => flow.output.unwrap_or(JsonValue::Null),
+        Ok(flow) => {
+            outcome.error = Some(flow.error.unwrap_or_else(|| format!("{op} failed")));
+            return outcome;
+        }
+        Err(err) => {
+            outcome.error = Some(format!("{err:#}"));
+            return outcome;
+        }
+    };
+    let registrations = parse_registrations(&output);
+    outcome.listed = registrations.len();
+    for registration in registrations
+        .iter()
+        .filter(|registration| matches(registration, public_base_url, client_states))
+    {
+        let Some(id) = registration.id.as_deref() else {
+            continue;
+        };
+        let state = adopted_state(&target.provider, ctx, id, registration);
+        match store.write_state(&state) {
+            Ok(()) => outcome.adopted.push(state.binding_id),
+            Err(err) => {
+                outcome.error = Some(format!("{err:#}"));
+                break;
+            }
+        }
+    }
+    outcome
+}
+
+fn adopted_state(
+    provider: &str,
+    ctx: &OperatorContext,
+    id: &str,
+    registration: &Registration,
+) -> SubscriptionState {
+    let binding: String = id
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    SubscriptionState {
+        binding_id: format!("adopted-{binding}"),
+        provider: provider.to_string(),
+        tenant: ctx.tenant.clone(),
+        team: ctx.team.clone(),
+        resource: registration.resource.clone(),
+        change_types: registration.change_types.clone(),
+        notification_url: registration.url.clone(),
+        client_state: registration.client_state.clone(),
+        user: None,
+        subscription_id: Some(id.to_string()),
+        expiration_unix_ms: registration.expiration_unix_ms,
+        last_error: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_registrations_under_the_tunnel_url_or_with_a_known_client_state() {
+        let output = json!({
+            "subscriptions": [
+                {
+                    "id": "sub-1",
+                    "notificationUrl": "https://demo.trycloudflare.com/v1/messaging/ingress/msgraph",
+                    "changeType": "created,updated",
+                    "resource": "/chats/getAllMessages",
+                    "expirationDateTime": "2026-01-01T00:00:00Z"
+                },
+                {"id": "sub-2", "notificationUrl": "https://other.example.com/hook", "clientState": "s3cret"},
+                {"id": "sub-3", "notificationUrl": "https://demo.trycloudflare.com.evil.io/hook"},
+                {"notificationUrl": "https://demo.trycloudflare.com/no-id"}
+            ]
+        });
+        let registrations = parse_registrations(&output);
+        assert_eq!(registrations.len(), 4);
+        assert_eq!(registrations[0].change_types, ["created", "updated"]);
+        assert_eq!(registrations[0].expiration_unix_ms, Some(1_767_225_600_000));
+
+        let known = BTreeSet::from(["s3cret".to_string()]);
+        let base = Some("https://demo.trycloudflare.com/");
+        let kept: Vec<_> = registrations
+            .iter()
+            .filter(|registration| matches(registration, base, &known))
+            .map(|registration| registration.id.as_deref())
+            .collect();
+        assert_eq!(kept, [Some("sub-1"), Some("sub-2"), None]);
+        assert!(!matches(&registrations[0], None, &BTreeSet::new()));
+
+        assert_eq!(select_op(|_| true), Some("list_webhooks"));
+        assert_eq!(
+            parse_registrations(&json!([{"id": 7}]))[0].id.as_deref(),
+            Some("7")
+        );
+    }
+}
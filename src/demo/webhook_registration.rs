@@ -0,0 +1,100 @@
+//! Remembers the public URL provider webhooks were last registered against.
+//!
+//! `demo start --setup-input` registers webhooks through the provider setup flows.
+//! With a stable tunnel hostname there is nothing to re-register on the next start,
+//! so the setup is skipped while the URL and the setup input are unchanged. When the
+//! URL does change, the recorded setup input is replayed even if `--setup-input` was
+//! not passed again.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::runtime_state::{RuntimePaths, read_json, write_json};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookRegistration {
+    pub public_base_url: String,
+    pub setup_input: PathBuf,
+    pub setup_input_sha256: String,
+}
+
+impl WebhookRegistration {
+    pub fn new(public_base_url: &str, setup_input: &Path) -> anyhow::Result<Self> {
+        let bytes = std::fs::read(setup_input)
+            .with_context(|| format!("failed to read {}", setup_input.display()))?;
+        let digest = ring::digest::digest(&ring::digest::SHA256, &bytes);
+        Ok(Self {
+            public_base_url: public_base_url.to_string(),
+            setup_input: setup_input
+                .canonicalize()
+                .unwrap_or_else(|_| setup_input.to_path_buf()),
+            setup_input_sha256: digest
+                .as_ref()
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect(),
+        })
+    }
+}
+
+pub fn registration_path(paths: &RuntimePaths) -> PathBuf {
+    paths.runtime_root().join("webhook_registration.json")
+}
+
+pub fn read(paths: &RuntimePaths) -> anyhow::Result<Option<WebhookRegistration>> {
+    read_json(&registration_path(paths))
+}
+
+pub fn write(paths: &RuntimePaths, registration: &WebhookRegistration) -> anyhow::Result<()> {
+    write_json(&registration_path(paths), registration)
+}
+
+/// Setup input to replay for a tunnel URL when `--setup-input` was not given: the
+/// recorded one, if webhooks were registered for a different URL and it still exists.
+pub fn replay_input(previous: Option<&WebhookRegistration>, url: &str) -> Option<PathBuf> {
+    previous
+        .filter(|previous| previous.public_base_url != url)
+        .map(|previous| previous.setup_input.clone())
+        .filter(|path| path.exists())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replays_recorded_input_only_when_the_url_changes() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let input = dir.path().join("setup.yaml");
+        std::fs::write(&input, "messaging: {}\n")?;
+        let paths = RuntimePaths::new(dir.path().join("state"), "demo", "default");
+        assert_eq!(read(&paths)?, None);
+
+        let registration = WebhookRegistration::new("https://demo.example.com", &input)?;
+        write(&paths, &registration)?;
+        let recorded = read(&paths)?;
+        assert_eq!(recorded.as_ref(), Some(&registration));
+        assert_eq!(
+            registration,
+            WebhookRegistration::new("https://demo.example.com", &input)?
+        );
+
+        assert_eq!(
+            replay_input(recorded.as_ref(), "https://demo.example.com"),
+            None
+        );
+        assert_eq!(
+            replay_input(recorded.as_ref(), "https://abc.trycloudflare.com"),
+            Some(registration.setup_input.clone())
+        );
+
+        std::fs::write(&input, "messaging: {telegram: {}}\n")?;
+        assert_ne!(
+            registration,
+            WebhookRegistration::new("https://demo.example.com", &input)?
+        );
+        Ok(())
+    }
+}
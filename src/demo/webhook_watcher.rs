@@ -0,0 +1,307 @@
+//! Re-registers provider webhooks when the public base URL changes mid-session.
+//!
+//! `demo start` runs this next to the tunnel. It polls the recorded public URL,
+//! first refreshing it from the cloudflared log in case a quick tunnel reconnected
+//! under a new hostname. On a change, every provider that implements `update_webhook`
+//! (or, failing that, a `verify_webhooks` flow) is invoked with the new URL. Providers
+//! with neither are logged so they can be set up again by hand.
+
+use std::path::PathBuf;
+use std::sync::{Arc, mpsc};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Context;
+use serde_json::{Value as JsonValue, json};
+
+use crate::cloudflared;
+use crate::demo::runner_host::{DemoRunnerHost, OperatorContext};
+use crate::discovery::DiscoveryResult;
+use crate::domains::{self, Domain};
+use crate::operator_log;
+use crate::runtime_state::RuntimePaths;
+
+/// Provider ops tried in order when the URL changes.
+pub const WEBHOOK_OPS: [&str; 2] = ["update_webhook", "verify_webhooks"];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WebhookProvider {
+    pub domain: Domain,
+    pub provider: String,
+}
+
+#[derive(Clone)]
+pub struct WebhookWatcherConfig {
+    pub runner_host: Arc<DemoRunnerHost>,
+    pub tenant: String,
+    pub team: String,
+    pub providers: Vec<WebhookProvider>,
+    /// cloudflared quick-tunnel log scanned for reconnects; `None` for named tunnels
+    /// and ngrok, whose URL only changes through `public_base_url.txt`.
+    pub tunnel_log: Option<PathBuf>,
+    pub interval: Duration,
+}
+
+pub struct WebhookWatcher {
+    shutdown: Option<mpsc::Sender<()>>,
+    handle: Option<thread::JoinHandle<anyhow::Result<()>>>,
+}
+
+impl WebhookWatcher {
+    pub fn start(config: WebhookWatcherConfig) -> anyhow::Result<Self> {
+        let (tx, rx) = mpsc::channel::<()>();
+        let handle = thread::Builder::new()
+            .name("demo-webhook-watcher".to_string())
+            .spawn(move || run_watcher_loop(config, rx))
+            .context("spawn webhook watcher thread")?;
+        Ok(Self {
+            shutdown: Some(tx),
+            handle: Some(handle),
+        })
+    }
+
+    pub fn stop(mut self) -> anyhow::Result<()> {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+        if let Some(handle) = self.handle.take() {
+            handle
+                .join()
+                .map_err(|err| anyhow::anyhow!("webhook watcher panicked: {err:?}"))??;
+        }
+        Ok(())
+    }
+}
+
+/// Messaging and events providers of the enabled domains; the ones that receive webhooks.
+pub fn webhook_providers(discovery: &DiscoveryResult, domains: &[Domain]) -> Vec<WebhookProvider> {
+    discovery
+        .providers
+        .iter()
+        .filter_map(|provider| {
+            let domain = domains
+                .iter()
+                .copied()
+                .filter(|domain| matches!(domain, Domain::Messaging | Domain::Events))
+                .find(|domain| domains::domain_name(*domain) == provider.domain)?;
+            Some(WebhookProvider {
+                domain,
+                provider: provider.provider_id.clone(),
+            })
+        })
+        .collect()
+}
+
+/// First of [`WEBHOOK_OPS`] the provider supports.
+pub fn select_op(supports: impl Fn(&str) -> bool) -> Option<&'static str> {
+    WEBHOOK_OPS.into_iter().find(|op| supports(op))
+}
+
+fn run_watcher_loop(config: WebhookWatcherConfig, rx: mpsc::Receiver<()>) -> anyhow::Result<()> {
+    let paths = RuntimePaths::new(
+        config.runner_host.bundle_root().join("state"),
+        &config.tenant,
+        &config.team,
+    );
+    let mut current = cloudflared::current_public_url(&paths).unwrap_or_default();
+    operator_log::info(
+        module_path!(),
+        format!(
+            "webhook watcher started providers={} url={}",
+            config.providers.len(),
+            current.as_deref().unwrap_or("<none>")
+        ),
+    );
+    while rx.recv_timeout(config.interval).is_err() {
+        if let Some(log) = &config.tunnel_log
+            && let Err(err) = cloudflared::refresh_public_url(&paths, log)
+        {
+            operator_log::warn(
+                module_path!(),
+                format!("failed to refresh public URL from {}: {err}", log.display()),
+            );
+        }
+        let latest = match cloudflared::current_public_url(&paths) {
+            Ok(Some(url)) => url,
+            Ok(None) => continue,
+            Err(err) => {
+                operator_log::warn(module_path!(), format!("public URL unreadable: {err}"));
+                continue;
+            }
+        };
+        if current.as_deref() == Some(latest.as_str()) {
+            continue;
+        }
+        operator_log::info(
+            module_path!(),
+            format!(
+                "public URL changed from {} to {latest}; re-registering webhooks",
+                current.as_deref().unwrap_or("<none>")
+            ),
+        );
+        println!(
+            "{}",
+            crate::operator_i18n::trf(
+                "demo.webhooks.url_changed",
+                "Public URL changed to {}; re-registering webhooks",
+                &[&latest]
+            )
+        );
+        reregister(&config, current.as_deref(), &latest);
+        current = Some(latest);
+    }
+    operator_log::info(module_path!(), "webhook watcher stopped");
+    Ok(())
+}
+
+fn reregister(config: &WebhookWatcherConfig, previous: Option<&str>, url: &str) {
+    let ctx = OperatorContext {
+        tenant: config.tenant.clone(),
+        team: Some(config.team.clone()),
+        correlation_id: None,
+    };
+    for target in &config.providers {
+        let Some(op) = select_op(|op| {
+            config
+                .runner_host
+                .supports_op(target.domain, &target.provider, op)
+        }) else {
+            operator_log::warn(
+                module_path!(),
+                format!(
+                    "provider {} has no update_webhook or verify_webhooks op; its webhook still points at {}",
+                    target.provider,
+                    previous.unwrap_or("<none>")
+                ),
+            );
+            continue;
+        };
+        let payload = webhook_payload(
+            &target.provider,
+            &config.tenant,
+            &config.team,
+            previous,
+            url,
+        );
+        let result = serde_json::to_vec(&payload)
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| {
+                config.runner_host.invoke_provider_op(
+                    target.domain,
+                    &target.provider,
+                    op,
+                    &bytes,
+                    &ctx,
+                )
+            });
+        match result {
+            Ok(outcome) if outcome.success => operator_log::info(
+                module_path!(),
+                format!(
+                    "webhook re-registered provider={} op={op} url={url}",
+                    target.provider
+                ),
+            ),
+            Ok(outcome) => operator_log::warn(
+                module_path!(),
+                format!(
+                    "webhook re-registration failed provider={} op={op}: {}",
+                    target.provider,
+                    outcome.error.unwrap_or_else(|| "unknown error".to_string())
+                ),
+            ),
+            Err(err) => operator_log::warn(
+                module_path!(),
+                format!(
+                    "webhook re-registration failed provider={} op={op}: {err}",
+                    target.provider
+                ),
+            ),
+        }
+    }
+}
+
+fn webhook_payload(
+    provider: &str,
+    tenant: &str,
+    team: &str,
+    previous: Option<&str>,
+    url: &str,
+) -> JsonValue {
+    let mut payload = json!({
+        "id": provider,
+        "tenant": tenant,
+        "team": team,
+        "public_base_url": url,
+        "config": {
+            "id": provider,
+            "public_base_url": url,
+        },
+    });
+    if let Some(previous) = previous {
+        payload["previous_public_base_url"] = JsonValue::String(previous.to_string());
+    }
+    payload
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::{DetectedDomains, DetectedProvider, ProviderIdSource};
+
+    #[test]
+    fn prefers_update_webhook_over_verify_flow() {
+        assert_eq!(select_op(|_| true), Some("update_webhook"));
+        assert_eq!(
+            select_op(|op| op == "verify_webhooks"),
+            Some("verify_webhooks")
+        );
+        assert_eq!(select_op(|op| op == "setup_default"), None);
+    }
+
+    #[test]
+    fn only_messaging_and_events_providers_are_watched() {
+        let provider = |id: &str, domain: &str| DetectedProvider {
+            provider_id: id.to_string(),
+            domain: domain.to_string(),
+            pack_path: PathBuf::from(format!("providers/{domain}/{id}.gtpack")),
+            id_source: ProviderIdSource::Manifest,
+        };
+        let discovery = DiscoveryResult {
+            domains: DetectedDomains {
+                messaging: true,
+                events: true,
+                custom: Vec::new(),
+            },
+            providers: vec![
+                provider("messaging-telegram", "messaging"),
+                provider("events-webhook", "events"),
+                provider("secrets-vault", "secrets"),
+            ],
+        };
+        let watched = webhook_providers(&discovery, &[Domain::Messaging, Domain::Secrets]);
+        assert_eq!(
+            watched,
+            vec![WebhookProvider {
+                domain: Domain::Messaging,
+                provider: "messaging-telegram".to_string(),
+            }]
+        );
+
+        let payload = webhook_payload(
+            "messaging-telegram",
+            "demo",
+            "default",
+            Some("https://old.trycloudflare.com"),
+            "https://new.trycloudflare.com",
+        );
+        assert_eq!(
+            payload["config"]["public_base_url"],
+            "https://new.trycloudflare.com"
+        );
+        assert_eq!(
+            payload["previous_public_base_url"],
+            "https://old.trycloudflare.com"
+        );
+    }
+}
@@ -0,0 +1,287 @@
+//! Named destination book stored in `<bundle>/destinations.yaml`.
+//!
+//! Maps friendly names (used as `demo send --to @alice`) to provider-specific
+//! destination ids, e.g.:
+//!
+//! ```yaml
+//! destinations:
+//!   alice:
+//!     telegram:
+//!       id: "123456789"
+//!       kind: chat
+//!     slack:
+//!       id: U0123ABCD
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::runtime_state;
+
+pub const DESTINATIONS_FILE: &str = "destinations.yaml";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DestinationBook {
+    #[serde(default)]
+    pub destinations: BTreeMap<String, BTreeMap<String, DestinationEntry>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DestinationEntry {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+}
+
+/// A `--to` value after alias lookup.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolvedDestination {
+    pub id: String,
+    pub kind: Option<String>,
+}
+
+pub fn book_path(bundle: &Path) -> PathBuf {
+    bundle.join(DESTINATIONS_FILE)
+}
+
+impl DestinationBook {
+    pub fn load(bundle: &Path) -> Result<Self> {
+        let path = book_path(bundle);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        if contents.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        serde_yaml_bw::from_str(&contents)
+            .with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, bundle: &Path) -> Result<PathBuf> {
+        let path = book_path(bundle);
+        let contents = serde_yaml_bw::to_string(self)?;
+        // Replaces the file rather than writing through it, so a book an instance
+        // still links from its golden bundle is not edited in place.
+        runtime_state::atomic_write(&path, contents.as_bytes())
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Inserts or replaces the entry for `name` on `provider`; returns the previous entry.
+    pub fn add(
+        &mut self,
+        name: &str,
+        provider: &str,
+        entry: DestinationEntry,
+    ) -> Result<Option<DestinationEntry>> {
+        validate_name(name)?;
+        Ok(self
+            .destinations
+            .entry(name.to_string())
+            .or_default()
+            .insert(provider.to_string(), entry))
+    }
+
+    /// Removes a single provider mapping, or the whole name when `provider` is `None`.
+    pub fn remove(&mut self, name: &str, provider: Option<&str>) -> bool {
+        let name = name.trim_start_matches('@');
+        match provider {
+            None => self.destinations.remove(name).is_some(),
+            Some(provider) => {
+                let Some(entries) = self.destinations.get_mut(name) else {
+                    return false;
+                };
+                let removed = entries.remove(provider).is_some();
+                if entries.is_empty() {
+                    self.destinations.remove(name);
+                }
+                removed
+            }
+        }
+    }
+
+    pub fn lookup(&self, name: &str, provider: &str) -> Option<&DestinationEntry> {
+        self.destinations.get(name)?.get(provider)
+    }
+
+    /// Resolves `--to` values for `provider`; `@name` goes through the book, anything
+    /// else is passed through verbatim with `default_kind`.
+    pub fn resolve_all(
+        &self,
+        values: &[String],
+        provider: &str,
+        default_kind: Option<&str>,
+    ) -> Result<Vec<ResolvedDestination>> {
+        values
+            .iter()
+            .map(|value| {
+                let (explicit_kind, value) = split_kind(value);
+                let default_kind = explicit_kind.or(default_kind);
+                let Some(name) = value.strip_prefix('@') else {
+                    return Ok(ResolvedDestination {
+                        id: value.to_string(),
+                        kind: default_kind.map(str::to_string),
+                    });
+                };
+                let entry = self.lookup(name, provider).ok_or_else(|| {
+                    let known = self
+                        .destinations
+                        .get(name)
+                        .map(|entries| entries.keys().cloned().collect::<Vec<_>>().join(", "))
+                        .filter(|known| !known.is_empty());
+                    match known {
+                        Some(known) => anyhow::anyhow!(
+                            "destination @{name} has no entry for provider {provider} (known: {known})"
+                        ),
+                        None => anyhow::anyhow!(
+                            "unknown destination @{name}; add it with `demo destinations add`"
+                        ),
+                    }
+                })?;
+                Ok(ResolvedDestination {
+                    id: entry.id.clone(),
+                    kind: default_kind
+                        .map(str::to_string)
+                        .or_else(|| entry.kind.clone()),
+                })
+            })
+            .collect()
+    }
+}
+
+/// `channel:C123` or `chat:@alice` -> (`Some(kind)`, rest). Values whose prefix is
+/// not a plain lowercase word (`!room:server`) or that look like URLs are ids as is.
+pub fn split_kind(value: &str) -> (Option<&str>, &str) {
+    match value.split_once(':') {
+        Some((kind, rest))
+            if !kind.is_empty()
+                && !rest.is_empty()
+                && !rest.starts_with("//")
+                && kind
+                    .chars()
+                    .all(|ch| ch.is_ascii_lowercase() || matches!(ch, '_' | '-')) =>
+        {
+            (Some(kind), rest)
+        }
+        _ => (None, value),
+    }
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.'));
+    if !valid {
+        return Err(anyhow::anyhow!(
+            "invalid destination name {name:?}; use letters, digits, '-', '_' or '.'"
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn entry(id: &str, kind: Option<&str>) -> DestinationEntry {
+        DestinationEntry {
+            id: id.to_string(),
+            kind: kind.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn book_round_trips_through_yaml() {
+        let dir = tempdir().unwrap();
+        let mut book = DestinationBook::default();
+        book.add("alice", "telegram", entry("123", Some("chat")))
+            .unwrap();
+        book.add("alice", "slack", entry("U1", None)).unwrap();
+        book.save(dir.path()).unwrap();
+
+        let loaded = DestinationBook::load(dir.path()).unwrap();
+        assert_eq!(loaded, book);
+        assert_eq!(loaded.lookup("alice", "slack"), Some(&entry("U1", None)));
+    }
+
+    #[test]
+    fn resolve_all_expands_aliases_and_passes_raw_ids() {
+        let mut book = DestinationBook::default();
+        book.add("alice", "telegram", entry("123", Some("chat")))
+            .unwrap();
+        let resolved = book
+            .resolve_all(&["@alice".to_string(), "999".to_string()], "telegram", None)
+            .unwrap();
+        assert_eq!(
+            resolved,
+            vec![
+                ResolvedDestination {
+                    id: "123".to_string(),
+                    kind: Some("chat".to_string()),
+                },
+                ResolvedDestination {
+                    id: "999".to_string(),
+                    kind: None,
+                },
+            ]
+        );
+        let err = book
+            .resolve_all(&["@alice".to_string()], "slack", None)
+            .unwrap_err();
+        assert!(err.to_string().contains("known: telegram"));
+        assert!(
+            book.resolve_all(&["@bob".to_string()], "telegram", None)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn kind_prefix_beats_to_kind_and_stored_kind() {
+        let mut book = DestinationBook::default();
+        book.add("alice", "slack", entry("U1", Some("user")))
+            .unwrap();
+        let resolved = book
+            .resolve_all(
+                &[
+                    "channel:C123".to_string(),
+                    "dm:@alice".to_string(),
+                    "!room:matrix.org".to_string(),
+                    "https://hooks.example/x".to_string(),
+                ],
+                "slack",
+                Some("chat"),
+            )
+            .unwrap();
+        let pairs = resolved
+            .iter()
+            .map(|dest| (dest.kind.as_deref(), dest.id.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            pairs,
+            vec![
+                (Some("channel"), "C123"),
+                (Some("dm"), "U1"),
+                (Some("chat"), "!room:matrix.org"),
+                (Some("chat"), "https://hooks.example/x"),
+            ]
+        );
+    }
+
+    #[test]
+    fn remove_drops_empty_names() {
+        let mut book = DestinationBook::default();
+        book.add("alice", "telegram", entry("123", None)).unwrap();
+        assert!(book.remove("@alice", Some("telegram")));
+        assert!(book.destinations.is_empty());
+        assert!(!book.remove("alice", None));
+        assert!(book.add("bad name", "telegram", entry("1", None)).is_err());
+    }
+}
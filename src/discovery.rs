@@ -1,11 +1,11 @@
 use std::path::{Path, PathBuf};
 
+use crate::domains::{self, Domain, PackArchive};
+use crate::operator_error::manifest_invalid;
+use crate::profile::{self, Phase};
+use crate::runtime_state::write_json;
 use serde::Serialize;
 use serde_cbor::Value as CborValue;
-use zip::result::ZipError;
-
-use crate::domains::{self, Domain};
-use crate::runtime_state::write_json;
 
 #[derive(Clone, Debug, Serialize)]
 pub struct DiscoveryResult {
@@ -17,6 +17,9 @@ pub struct DiscoveryResult {
 pub struct DetectedDomains {
     pub messaging: bool,
     pub events: bool,
+    /// Custom domains (see [`domains::registry`]) with at least one provider pack.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub custom: Vec<String>,
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -47,8 +50,13 @@ pub fn discover_with_options(
     root: &Path,
     options: DiscoveryOptions,
 ) -> anyhow::Result<DiscoveryResult> {
+    let _span = profile::span(Phase::Discovery, root.display());
     let mut providers = Vec::new();
-    for domain in [Domain::Messaging, Domain::Events] {
+    let custom_domains = domains::register_bundle_domains(root)?;
+    for domain in [Domain::Messaging, Domain::Events]
+        .into_iter()
+        .chain(custom_domains.iter().copied())
+    {
         let cfg = domains::config(domain);
         let providers_dir = root.join(cfg.providers_dir);
         if !providers_dir.exists() {
@@ -92,6 +100,12 @@ pub fn discover_with_options(
             .iter()
             .any(|provider| provider.domain == "messaging"),
         events: providers.iter().any(|provider| provider.domain == "events"),
+        custom: custom_domains
+            .iter()
+            .map(|domain| domains::domain_name(*domain))
+            .filter(|name| providers.iter().any(|provider| provider.domain == *name))
+            .map(str::to_string)
+            .collect(),
     };
     Ok(DiscoveryResult { domains, providers })
 }
@@ -105,23 +119,40 @@ pub fn persist(root: &Path, tenant: &str, discovery: &DiscoveryResult) -> anyhow
     Ok(())
 }
 
+/// `providers` keyed by their pack path.
+pub(crate) fn discovery_map(
+    providers: &[DetectedProvider],
+) -> std::collections::BTreeMap<PathBuf, DetectedProvider> {
+    let mut map = std::collections::BTreeMap::new();
+    for provider in providers {
+        map.insert(provider.pack_path.clone(), provider.clone());
+    }
+    map
+}
+
+/// The discovered provider id of the pack at `pack_path`, or `fallback` when the pack
+/// was not discovered.
+pub(crate) fn provider_id_for_pack(
+    pack_path: &Path,
+    fallback: &str,
+    provider_map: Option<&std::collections::BTreeMap<PathBuf, DetectedProvider>>,
+) -> String {
+    provider_map
+        .and_then(|map| map.get(pack_path))
+        .map(|provider| provider.provider_id.clone())
+        .unwrap_or_else(|| fallback.to_string())
+}
+
 fn read_pack_id_from_manifest(path: &Path) -> anyhow::Result<Option<String>> {
-    let file = std::fs::File::open(path)?;
-    let mut archive = zip::ZipArchive::new(file)?;
-    if let Some(parsed) = read_manifest_cbor_for_discovery(&mut archive).map_err(|err| {
-        anyhow::anyhow!(
-            "failed to decode manifest.cbor in {}: {err}",
-            path.display()
-        )
-    })? {
+    let archive = PackArchive::open(path)?;
+    if let Some(parsed) = read_manifest_cbor_for_discovery(&archive)
+        .map_err(|err| manifest_invalid(path, format!("failed to decode manifest.cbor: {err}")))?
+    {
         return extract_pack_id(parsed);
     }
-    if let Some(parsed) = read_manifest_json_for_discovery(&mut archive, "pack.manifest.json")
-        .map_err(|err| {
-            anyhow::anyhow!(
-                "failed to decode pack.manifest.json in {}: {err}",
-                path.display()
-            )
+    if let Some(parsed) =
+        read_manifest_json_for_discovery(&archive, "pack.manifest.json").map_err(|err| {
+            manifest_invalid(path, format!("failed to decode pack.manifest.json: {err}"))
         })?
     {
         return extract_pack_id(parsed);
@@ -130,14 +161,10 @@ fn read_pack_id_from_manifest(path: &Path) -> anyhow::Result<Option<String>> {
 }
 
 fn read_pack_id_from_manifest_cbor_only(path: &Path) -> anyhow::Result<Option<String>> {
-    let file = std::fs::File::open(path)?;
-    let mut archive = zip::ZipArchive::new(file)?;
-    if let Some(parsed) = read_manifest_cbor_for_discovery(&mut archive).map_err(|err| {
-        anyhow::anyhow!(
-            "failed to decode manifest.cbor in {}: {err}",
-            path.display()
-        )
-    })? {
+    let archive = PackArchive::open(path)?;
+    if let Some(parsed) = read_manifest_cbor_for_discovery(&archive)
+        .map_err(|err| manifest_invalid(path, format!("failed to decode manifest.cbor: {err}")))?
+    {
         return extract_pack_id(parsed);
     }
     Err(missing_cbor_error(path))
@@ -154,16 +181,11 @@ fn extract_pack_id(parsed: domains::PackManifestForDiscovery) -> anyhow::Result<
 }
 
 fn read_manifest_cbor_for_discovery(
-    archive: &mut zip::ZipArchive<std::fs::File>,
+    archive: &PackArchive,
 ) -> anyhow::Result<Option<domains::PackManifestForDiscovery>> {
-    let mut file = match archive.by_name("manifest.cbor") {
-        Ok(file) => file,
-        Err(ZipError::FileNotFound) => return Ok(None),
-        Err(err) => return Err(err.into()),
+    let Some(value) = archive.manifest_value()? else {
+        return Ok(None);
     };
-    let mut bytes = Vec::new();
-    std::io::Read::read_to_end(&mut file, &mut bytes)?;
-    let value: CborValue = serde_cbor::from_slice(&bytes)?;
     if let Some(pack_id) = extract_pack_id_from_value(&value)? {
         return Ok(Some(domains::PackManifestForDiscovery {
             meta: None,
@@ -174,17 +196,13 @@ fn read_manifest_cbor_for_discovery(
 }
 
 fn read_manifest_json_for_discovery(
-    archive: &mut zip::ZipArchive<std::fs::File>,
+    archive: &PackArchive,
     name: &str,
 ) -> anyhow::Result<Option<domains::PackManifestForDiscovery>> {
-    let mut file = match archive.by_name(name) {
-        Ok(file) => file,
-        Err(ZipError::FileNotFound) => return Ok(None),
-        Err(err) => return Err(err.into()),
+    let Some(bytes) = archive.read_member(name)? else {
+        return Ok(None);
     };
-    let mut contents = String::new();
-    std::io::Read::read_to_string(&mut file, &mut contents)?;
-    let parsed: domains::PackManifestForDiscovery = serde_json::from_str(&contents)?;
+    let parsed: domains::PackManifestForDiscovery = serde_json::from_slice(&bytes)?;
     Ok(Some(parsed))
 }
 
@@ -250,8 +268,8 @@ fn map_get<'a>(
 }
 
 fn missing_cbor_error(path: &Path) -> anyhow::Error {
-    anyhow::anyhow!(
-        "ERROR: demo packs must be CBOR-only (.gtpack must contain manifest.cbor). Rebuild the pack with greentic-pack build (do not use --dev). Missing in {}",
-        path.display()
+    manifest_invalid(
+        path,
+        "demo packs must be CBOR-only (.gtpack must contain manifest.cbor). Rebuild the pack with greentic-pack build (do not use --dev)",
     )
 }
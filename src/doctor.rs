@@ -33,7 +33,12 @@ pub fn run_doctor(
 
     let domains = match scope {
         DoctorScope::One(domain) => vec![domain],
-        DoctorScope::All => vec![Domain::Messaging, Domain::Events, Domain::Secrets],
+        DoctorScope::All => {
+            let mut all = vec![Domain::Messaging, Domain::Events];
+            all.extend(domains::register_bundle_domains(root)?);
+            all.push(Domain::Secrets);
+            all
+        }
     };
 
     let mut runs = Vec::new();
@@ -172,10 +177,9 @@ fn demo_packs(root: &Path, options: &DoctorOptions) -> anyhow::Result<Option<Dem
     if !manifest.exists() {
         return Ok(None);
     }
-    let contents = std::fs::read_to_string(manifest)?;
-    let manifest: ResolvedManifest = serde_yaml_bw::from_str(&contents)?;
+    let manifest = crate::project::load_resolved_manifest(&manifest)?;
     let mut packs = Vec::new();
-    for pack in manifest.packs {
+    for pack in manifest.packs.iter().map(|pack| pack.path()) {
         if pack.ends_with(".gtpack") {
             packs.push(root.join(pack));
         } else {
@@ -219,10 +223,6 @@ fn domain_name(domain: Domain) -> &'static str {
         Domain::Messaging => "messaging",
         Domain::Events => "events",
         Domain::Secrets => "secrets",
+        Domain::Custom(custom) => custom.name(),
     }
 }
-
-#[derive(Debug, serde::Deserialize)]
-struct ResolvedManifest {
-    packs: Vec<String>,
-}
@@ -0,0 +1,1400 @@
+//! Domain actions over a bundle's provider packs: picking the packs for a domain,
+//! planning which flow each one runs (`setup_default`, `verify_webhooks`, ...), and
+//! running the plan with the pack dependencies ordered.
+//!
+//! [`run_domain_command`] backs `demo setup`, the wizard's setup step, and
+//! [`crate::api::run_setup`]. It prints nothing itself: flow progress goes through
+//! [`progress`], and the rest comes back as a [`DomainRunReport`] for the caller to
+//! render.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde_json::{Value as JsonValue, json};
+
+use crate::bin_resolver::{self, ResolveCtx};
+use crate::config;
+use crate::discovery;
+use crate::domains::{self, Domain, DomainAction};
+use crate::flow_stream;
+use crate::interpolate::SecretScope;
+use crate::operator_error::OperatorError;
+use crate::operator_i18n;
+use crate::operator_log;
+use crate::progress;
+use crate::project;
+use crate::qa_setup_wizard;
+use crate::runner_exec;
+use crate::runner_integration;
+use crate::secrets_gate::{self, DynSecretsManager};
+use crate::secrets_setup::resolve_env;
+use crate::setup_input::{SetupInputAnswers, load_setup_input};
+use crate::state_layout;
+
+pub(crate) fn provider_filter_matches(pack: &domains::ProviderPack, filter: &str) -> bool {
+    let file_stem = pack
+        .file_name
+        .strip_suffix(".gtpack")
+        .unwrap_or(&pack.file_name);
+    pack.pack_id == filter
+        || pack.file_name == filter
+        || file_stem == filter
+        || pack.pack_id.contains(filter)
+        || pack.file_name.contains(filter)
+        || file_stem.contains(filter)
+}
+
+pub fn demo_provider_packs(
+    bundle: &Path,
+    domain: Domain,
+) -> anyhow::Result<Vec<domains::ProviderPack>> {
+    let is_demo_bundle = bundle.join("greentic.demo.yaml").exists();
+    if is_demo_bundle {
+        domains::discover_provider_packs_cbor_only(bundle, domain)
+    } else {
+        domains::discover_provider_packs(bundle, domain)
+    }
+}
+
+pub fn demo_provider_pack_by_filter(
+    bundle: &Path,
+    domain: Domain,
+    filter: &str,
+) -> anyhow::Result<domains::ProviderPack> {
+    let mut packs = demo_provider_packs(bundle, domain)?;
+    packs.retain(|pack| provider_filter_matches(pack, filter));
+    if packs.is_empty() {
+        return Err(anyhow::anyhow!(
+            "no provider pack matched {} in {}",
+            filter,
+            domains::domain_name(domain)
+        ));
+    }
+    packs.sort_by(|a, b| a.path.cmp(&b.path));
+    if packs.len() > 1 {
+        let names = packs
+            .iter()
+            .map(|pack| pack.file_name.clone())
+            .collect::<Vec<_>>();
+        return Err(anyhow::anyhow!(
+            "multiple provider packs matched {}; specify a more precise --pack: {}",
+            filter,
+            names.join(", ")
+        ));
+    }
+    Ok(packs.remove(0))
+}
+
+pub(crate) fn resolve_demo_provider_pack(
+    root: &Path,
+    tenant: &str,
+    team: Option<&str>,
+    provider: &str,
+    domain: Domain,
+) -> anyhow::Result<domains::ProviderPack> {
+    let is_demo_bundle = root.join("greentic.demo.yaml").exists();
+    let mut packs = if is_demo_bundle {
+        domains::discover_provider_packs_cbor_only(root, domain)?
+    } else {
+        domains::discover_provider_packs(root, domain)?
+    };
+    if is_demo_bundle && let Some(allowed) = demo_provider_files(root, tenant, team, domain)? {
+        packs.retain(|pack| allowed.contains(&pack.file_name));
+    }
+    packs.retain(|pack| provider_filter_matches(pack, provider));
+    if packs.is_empty() {
+        return Err(anyhow::Error::from(OperatorError::PackNotFound {
+            reference: provider.to_string(),
+        })
+        .context("No provider packs matched. Try --provider <pack_id>."));
+    }
+    packs.sort_by(|a, b| a.path.cmp(&b.path));
+    if packs.len() > 1 {
+        let names = packs
+            .iter()
+            .map(|pack| pack.file_name.clone())
+            .collect::<Vec<_>>();
+        return Err(anyhow::anyhow!(
+            "Multiple provider packs matched: {}. Use a more specific --provider.",
+            names.join(", ")
+        ));
+    }
+    Ok(packs.remove(0))
+}
+
+fn demo_provider_files(
+    root: &Path,
+    tenant: &str,
+    team: Option<&str>,
+    domain: Domain,
+) -> anyhow::Result<Option<BTreeSet<String>>> {
+    let resolved = demo_resolved_manifest_path(root, tenant, team);
+    if !resolved.exists() {
+        return Ok(None);
+    }
+    let manifest = project::load_resolved_manifest(&resolved)?;
+    let key = match domain {
+        Domain::Messaging => "messaging",
+        Domain::Events => "events",
+        Domain::Secrets => "secrets",
+        Domain::Custom(custom) => custom.name(),
+    };
+    let Some(list) = manifest.providers.get(key) else {
+        return Ok(Some(BTreeSet::new()));
+    };
+    let mut files = BTreeSet::new();
+    for entry in list {
+        if let Some(name) = Path::new(entry.path())
+            .file_name()
+            .and_then(|value| value.to_str())
+        {
+            files.insert(name.to_string());
+        }
+    }
+    Ok(Some(files))
+}
+
+pub(crate) fn demo_resolved_manifest_path(
+    root: &Path,
+    tenant: &str,
+    team: Option<&str>,
+) -> PathBuf {
+    root.join("resolved")
+        .join(resolved_manifest_filename(tenant, team))
+}
+
+pub(crate) fn resolved_manifest_filename(tenant: &str, team: Option<&str>) -> String {
+    match team {
+        Some(team) => format!("{tenant}.{team}.yaml"),
+        None => format!("{tenant}.yaml"),
+    }
+}
+
+/// One domain action (setup, verify, ...) over a bundle's provider packs.
+pub(crate) struct DomainRunArgs {
+    pub(crate) root: PathBuf,
+    pub(crate) state_root: Option<PathBuf>,
+    pub(crate) domain: Domain,
+    pub(crate) action: DomainAction,
+    pub(crate) tenant: String,
+    pub(crate) team: Option<String>,
+    pub(crate) provider_filter: Option<String>,
+    pub(crate) dry_run: bool,
+    pub(crate) parallel: usize,
+    pub(crate) allow_missing_setup: bool,
+    pub(crate) allow_contract_change: bool,
+    pub(crate) backup: bool,
+    pub(crate) online: bool,
+    pub(crate) secrets_env: Option<String>,
+    pub(crate) runner_binary: Option<PathBuf>,
+    pub(crate) best_effort: bool,
+    pub(crate) discovered_providers: Option<Vec<discovery::DetectedProvider>>,
+    pub(crate) setup_input: Option<PathBuf>,
+    pub(crate) allowed_providers: Option<BTreeSet<String>>,
+    pub(crate) preloaded_setup_answers: Option<SetupInputAnswers>,
+    pub(crate) public_base_url: Option<String>,
+    pub(crate) secrets_manager: Option<DynSecretsManager>,
+    /// Provider ids whose existing webhook registrations were adopted; their setup
+    /// flow is skipped.
+    pub(crate) adopted_providers: BTreeSet<String>,
+}
+
+/// What [`run_domain_command`] skipped or planned, for the caller to print.
+#[derive(Clone, Debug)]
+pub struct DomainRunReport {
+    pub domain: Domain,
+    /// The bundle has a greentic.demo.yaml (as opposed to a project root).
+    pub demo_bundle: bool,
+    /// Packs left out under `best_effort` because they have no setup flow.
+    pub skipped_missing_setup: Vec<String>,
+    /// `allowed_providers` entries no pack matched.
+    pub missing_packs: Vec<String>,
+    /// Providers skipped because their webhook registrations were adopted.
+    pub adopted: Vec<String>,
+    /// No pack matched the provider filter, so nothing ran.
+    pub nothing_matched: bool,
+    /// Under `dry_run`, the plan that would have run.
+    pub dry_run_plan: Option<Vec<domains::PlannedRun>>,
+    /// Flows that failed under `best_effort`.
+    pub failed_flows: usize,
+}
+
+/// Plans and runs the domain's provider flows. What was skipped, and the plan itself
+/// under `dry_run`, comes back in the report; flow progress goes through
+/// [`progress`].
+pub(crate) fn run_domain_command(args: DomainRunArgs) -> anyhow::Result<DomainRunReport> {
+    let is_demo_bundle = args.root.join("greentic.demo.yaml").exists();
+    let mut report = DomainRunReport {
+        domain: args.domain,
+        demo_bundle: is_demo_bundle,
+        skipped_missing_setup: Vec::new(),
+        missing_packs: Vec::new(),
+        adopted: Vec::new(),
+        nothing_matched: false,
+        dry_run_plan: None,
+        failed_flows: 0,
+    };
+    let mut packs = if is_demo_bundle {
+        domains::discover_provider_packs_cbor_only(&args.root, args.domain)?
+    } else {
+        domains::discover_provider_packs(&args.root, args.domain)?
+    };
+    let provider_map = args
+        .discovered_providers
+        .as_deref()
+        .map(discovery::discovery_map);
+    if let Some(provider_map) = provider_map.as_ref() {
+        packs.retain(|pack| provider_map.contains_key(&pack.path));
+        packs.sort_by(|a, b| a.path.cmp(&b.path));
+    }
+    if is_demo_bundle
+        && let Some(allowed) =
+            demo_provider_files(&args.root, &args.tenant, args.team.as_deref(), args.domain)?
+    {
+        packs.retain(|pack| allowed.contains(&pack.file_name));
+    }
+    domains::add_dependencies(&mut packs, &config::demo_provider_dependencies(&args.root)?);
+    if args.action == DomainAction::Setup {
+        let setup_flow = domains::config(args.domain).setup_flow;
+        let missing: Vec<String> = packs
+            .iter()
+            .filter(|pack| !pack.entry_flows.iter().any(|flow| flow == setup_flow))
+            .map(|pack| pack.file_name.clone())
+            .collect();
+        if !missing.is_empty() && !args.allow_missing_setup {
+            if args.best_effort {
+                report.skipped_missing_setup = missing;
+                packs.retain(|pack| pack.entry_flows.iter().any(|flow| flow == setup_flow));
+            } else {
+                return Err(anyhow::anyhow!(
+                    "missing {setup_flow} in packs: {}",
+                    missing.join(", ")
+                ));
+            }
+        }
+    }
+    if packs.is_empty() {
+        return Ok(report);
+    }
+    if let Some(allowed) = args.allowed_providers.as_ref() {
+        let missing = filter_packs_by_allowed(&mut packs, allowed);
+        if !missing.is_empty() {
+            operator_log::warn(
+                module_path!(),
+                format!(
+                    "provider filter domain={} removed packs: {}",
+                    domains::domain_name(args.domain),
+                    missing.join(", ")
+                ),
+            );
+            report.missing_packs = missing;
+        }
+    }
+    if !args.adopted_providers.is_empty() {
+        packs.retain(|pack| {
+            let provider_id =
+                discovery::provider_id_for_pack(&pack.path, &pack.pack_id, provider_map.as_ref());
+            if !args.adopted_providers.contains(&provider_id) {
+                return true;
+            }
+            report.adopted.push(provider_id);
+            false
+        });
+        if packs.is_empty() {
+            return Ok(report);
+        }
+    }
+    operator_log::info(
+        module_path!(),
+        format!(
+            "provider selection domain={} packs={}",
+            domains::domain_name(args.domain),
+            packs.len()
+        ),
+    );
+    let setup_answers = if let Some(preloaded) = args.preloaded_setup_answers.clone() {
+        Some(preloaded)
+    } else if let Some(path) = args.setup_input.as_ref() {
+        let provider_keys: BTreeSet<String> =
+            packs.iter().map(|pack| pack.pack_id.clone()).collect();
+        let scope = SecretScope {
+            bundle_root: &args.root,
+            tenant: &args.tenant,
+            team: args.team.as_deref(),
+        };
+        Some(SetupInputAnswers::new(
+            load_setup_input(path, Some(scope))?,
+            provider_keys,
+        )?)
+    } else {
+        None
+    };
+    let interactive = args.setup_input.is_none();
+    let plan = domains::plan_runs(
+        args.domain,
+        args.action,
+        &packs,
+        args.provider_filter.as_deref(),
+        args.allow_missing_setup,
+    )?;
+
+    operator_log::info(
+        module_path!(),
+        format!(
+            "plan domain={} action={:?} items={}",
+            domains::domain_name(args.domain),
+            args.action,
+            plan.len()
+        ),
+    );
+    for item in &plan {
+        operator_log::debug(
+            module_path!(),
+            format!(
+                "plan item domain={} pack={} flow={}",
+                domains::domain_name(args.domain),
+                item.pack.file_name,
+                item.flow_id
+            ),
+        );
+    }
+
+    if plan.is_empty() {
+        report.nothing_matched = true;
+        operator_log::warn(
+            module_path!(),
+            format!(
+                "no provider packs matched domain={} action={:?}",
+                domains::domain_name(args.domain),
+                args.action
+            ),
+        );
+        return Ok(report);
+    }
+
+    if args.dry_run {
+        report.dry_run_plan = Some(plan);
+        return Ok(report);
+    }
+
+    let runner_binary = resolve_demo_runner_binary(&args.root, args.runner_binary)?;
+    let dist_offline = !args.online;
+    let state_root = args.state_root.as_ref().unwrap_or(&args.root);
+    report.failed_flows = run_plan(
+        &args.root,
+        state_root,
+        args.domain,
+        args.action,
+        &args.tenant,
+        args.team.as_deref(),
+        plan,
+        args.parallel,
+        dist_offline,
+        args.allow_contract_change,
+        args.backup,
+        args.secrets_env.as_deref(),
+        runner_binary,
+        args.best_effort,
+        provider_map,
+        setup_answers,
+        interactive,
+        args.public_base_url.clone(),
+        args.secrets_manager.clone(),
+    )?;
+    Ok(report)
+}
+
+fn filter_packs_by_allowed(
+    packs: &mut Vec<domains::ProviderPack>,
+    allowed: &BTreeSet<String>,
+) -> Vec<String> {
+    let mut seen = BTreeSet::new();
+    packs.retain(|pack| {
+        if allowed.contains(&pack.pack_id) {
+            seen.insert(pack.pack_id.clone());
+            true
+        } else {
+            false
+        }
+    });
+    allowed
+        .iter()
+        .filter(|value| !seen.contains(*value))
+        .cloned()
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_plan(
+    root: &Path,
+    state_root: &Path,
+    domain: Domain,
+    action: DomainAction,
+    tenant: &str,
+    team: Option<&str>,
+    plan: Vec<domains::PlannedRun>,
+    parallel: usize,
+    dist_offline: bool,
+    allow_contract_change: bool,
+    backup: bool,
+    secrets_env: Option<&str>,
+    runner_binary: Option<PathBuf>,
+    best_effort: bool,
+    provider_map: Option<BTreeMap<PathBuf, discovery::DetectedProvider>>,
+    setup_answers: Option<SetupInputAnswers>,
+    interactive: bool,
+    public_base_url: Option<String>,
+    secrets_manager: Option<DynSecretsManager>,
+) -> anyhow::Result<usize> {
+    let plan_public_base_url = public_base_url.map(Arc::new);
+    let phase = progress::Phase::start(
+        format!(
+            "{} {}",
+            format!("{action:?}").to_ascii_lowercase(),
+            domains::domain_name(domain)
+        ),
+        plan.len(),
+    );
+    let item_label = |item: &domains::PlannedRun| format!("{} {}", item.pack.pack_id, item.flow_id);
+    let print_live: Arc<dyn Fn(&str) + Send + Sync> = Arc::new(phase.live_printer());
+    let stream_item = |label: String| {
+        let print_live = print_live.clone();
+        flow_stream::scoped(Arc::new(move |event: &flow_stream::StreamEvent| {
+            print_live(&format!("  {label}: {}", event.render()));
+        }))
+    };
+    let run_item = |item: &domains::PlannedRun, log: &mut PlanItemLog| {
+        run_plan_item(
+            root,
+            state_root,
+            domain,
+            action,
+            tenant,
+            team,
+            item,
+            dist_offline,
+            allow_contract_change,
+            backup,
+            secrets_env,
+            runner_binary.as_deref(),
+            setup_answers.as_ref(),
+            provider_map.as_ref(),
+            interactive,
+            plan_public_base_url.clone(),
+            secrets_manager.clone(),
+            log,
+        )
+    };
+    if parallel <= 1 || plan.len() <= 1 {
+        let mut errors = Vec::new();
+        let mut queue = PlanQueue::new(plan);
+        while let Some((_, item)) = queue.next_ready() {
+            let label = item_label(&item);
+            phase.set_message(label.clone());
+            let mut log = PlanItemLog::default();
+            let result = {
+                let _stream = stream_item(label.clone());
+                run_item(&item, &mut log)
+            };
+            phase.advance(&label, result.is_ok());
+            log.report(&phase, result.is_ok());
+            queue.finish(&item.pack.pack_id, result.is_ok());
+            if let Err(err) = result {
+                if best_effort {
+                    errors.push(err);
+                } else {
+                    phase.finish();
+                    return Err(err);
+                }
+            }
+        }
+        queue.report_not_started(&phase);
+        phase.finish();
+        return Ok(errors.len());
+    }
+
+    // Workers only advance the progress bar; each flow's own output is buffered and
+    // reported once they are done, in plan order, so the output reads the same
+    // whichever worker finished first.
+    let total = plan.len();
+    let queue = (
+        std::sync::Mutex::new(PlanQueue::new(plan)),
+        std::sync::Condvar::new(),
+    );
+    let outcomes = std::sync::Mutex::new(
+        (0..total)
+            .map(|_| None)
+            .collect::<Vec<Option<PlanItemOutcome>>>(),
+    );
+    let failed = std::sync::atomic::AtomicBool::new(false);
+    std::thread::scope(|scope| {
+        for _ in 0..parallel.min(total) {
+            scope.spawn(|| {
+                loop {
+                    let next = {
+                        let (lock, finished) = &queue;
+                        let mut queue = lock.lock().unwrap();
+                        loop {
+                            // Fail-fast: let in-flight flows finish but start no new ones.
+                            if !best_effort && failed.load(std::sync::atomic::Ordering::SeqCst) {
+                                break None;
+                            }
+                            if let Some(next) = queue.next_ready() {
+                                break Some(next);
+                            }
+                            if queue.waiting.is_empty() {
+                                break None;
+                            }
+                            queue = finished.wait(queue).unwrap();
+                        }
+                    };
+                    let Some((index, item)) = next else {
+                        break;
+                    };
+                    let mut log = PlanItemLog::default();
+                    // A panicking flow fails like any other, so the workers waiting on
+                    // its pack are still woken up.
+                    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        let _stream = stream_item(item_label(&item));
+                        run_item(&item, &mut log)
+                    }))
+                    .unwrap_or_else(|panic| {
+                        let message = panic
+                            .downcast_ref::<&str>()
+                            .map(|message| message.to_string())
+                            .or_else(|| panic.downcast_ref::<String>().cloned())
+                            .unwrap_or_default();
+                        Err(anyhow::anyhow!("flow panicked: {message}"))
+                    });
+                    if result.is_err() {
+                        failed.store(true, std::sync::atomic::Ordering::SeqCst);
+                    }
+                    phase.advance(&item_label(&item), result.is_ok());
+                    let (lock, finished) = &queue;
+                    lock.lock()
+                        .unwrap_or_else(std::sync::PoisonError::into_inner)
+                        .finish(&item.pack.pack_id, result.is_ok());
+                    finished.notify_all();
+                    outcomes.lock().unwrap()[index] = Some(PlanItemOutcome { item, log, result });
+                }
+            });
+        }
+    });
+
+    let mut errors = Vec::new();
+    let outcomes = outcomes.into_inner().unwrap();
+    let queue = queue.0.into_inner().unwrap();
+    for outcome in outcomes {
+        let Some(outcome) = outcome else {
+            continue;
+        };
+        outcome.log.report(&phase, outcome.result.is_ok());
+        if let Err(err) = outcome.result {
+            errors.push(err.context(format!(
+                "{} {}",
+                outcome.item.pack.file_name, outcome.item.flow_id
+            )));
+        }
+    }
+    queue.report_not_started(&phase);
+    phase.finish();
+    if !errors.is_empty() {
+        if best_effort {
+            return Ok(errors.len());
+        }
+        // Keep the first failure in plan order as the root so its exit code survives.
+        let count = errors.len();
+        let first = errors.into_iter().next().expect("errors is not empty");
+        return Err(first.context(format!("{count} flow(s) failed.")));
+    }
+    Ok(0)
+}
+
+/// Output of one plan item. Warnings are shown by default; details (inputs, success
+/// lines) only with `--verbose` or when the item failed.
+#[derive(Default)]
+struct PlanItemLog {
+    warnings: Vec<String>,
+    details: Vec<String>,
+}
+
+impl PlanItemLog {
+    /// Warnings and a failed item's details always print; a successful item's
+    /// details only under `--verbose`.
+    fn report(&self, phase: &progress::Phase, ok: bool) {
+        for line in &self.warnings {
+            phase.warn(line);
+        }
+        for line in &self.details {
+            if ok {
+                phase.detail(line);
+            } else {
+                phase.warn(line);
+            }
+        }
+    }
+}
+
+static SETUP_PROMPT_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// A flow run by a `--parallel` worker, kept until the final report.
+struct PlanItemOutcome {
+    item: domains::PlannedRun,
+    log: PlanItemLog,
+    result: anyhow::Result<()>,
+}
+
+/// Plan items left to run, with their plan index. An item is handed out only once
+/// every pack it depends on has no item queued or running, so the plan's dependency
+/// order holds across `--parallel` workers. Items of a pack that depends on a failed
+/// pack are set aside instead of started.
+struct PlanQueue {
+    waiting: std::collections::VecDeque<(usize, domains::PlannedRun)>,
+    unfinished: BTreeMap<String, usize>,
+    failed: BTreeSet<String>,
+    skipped: Vec<(usize, domains::PlannedRun)>,
+}
+
+impl PlanQueue {
+    fn new(plan: Vec<domains::PlannedRun>) -> Self {
+        let mut unfinished = BTreeMap::new();
+        for item in &plan {
+            *unfinished.entry(item.pack.pack_id.clone()).or_insert(0) += 1;
+        }
+        Self {
+            waiting: plan.into_iter().enumerate().collect(),
+            unfinished,
+            failed: BTreeSet::new(),
+            skipped: Vec::new(),
+        }
+    }
+
+    fn next_ready(&mut self) -> Option<(usize, domains::PlannedRun)> {
+        let index = self.waiting.iter().position(|(_, item)| {
+            item.pack.depends_on.iter().all(|dep| {
+                *dep == item.pack.pack_id
+                    || self.unfinished.get(dep).is_none_or(|count| *count == 0)
+            })
+        })?;
+        self.waiting.remove(index)
+    }
+
+    fn finish(&mut self, pack_id: &str, ok: bool) {
+        if let Some(count) = self.unfinished.get_mut(pack_id) {
+            *count = count.saturating_sub(1);
+        }
+        if ok || !self.failed.insert(pack_id.to_string()) {
+            return;
+        }
+        // A skipped pack counts as failed for the packs that depend on it in turn.
+        loop {
+            let Some(index) = self.waiting.iter().position(|(_, item)| {
+                item.pack
+                    .depends_on
+                    .iter()
+                    .any(|dep| *dep != item.pack.pack_id && self.failed.contains(dep))
+            }) else {
+                break;
+            };
+            let (index, item) = self.waiting.remove(index).expect("index is in range");
+            if let Some(count) = self.unfinished.get_mut(&item.pack.pack_id) {
+                *count = count.saturating_sub(1);
+            }
+            self.failed.insert(item.pack.pack_id.clone());
+            self.skipped.push((index, item));
+        }
+    }
+
+    /// Lists the items skipped for a failed dependency or left queued after a
+    /// fail-fast stop, in plan order.
+    fn report_not_started(&self, phase: &progress::Phase) {
+        let mut not_started = self.skipped.iter().chain(&self.waiting).collect::<Vec<_>>();
+        if not_started.is_empty() {
+            return;
+        }
+        not_started.sort_by_key(|(index, _)| *index);
+        let labels = not_started
+            .iter()
+            .map(|(_, item)| format!("{} {}", item.pack.file_name, item.flow_id))
+            .collect::<Vec<_>>();
+        phase.warn(operator_i18n::trf(
+            "cli.plan.not_started",
+            "Not started after a failure: {}",
+            &[&labels.join(", ")],
+        ));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_plan_item(
+    root: &Path,
+    state_root: &Path,
+    domain: Domain,
+    action: DomainAction,
+    tenant: &str,
+    team: Option<&str>,
+    item: &domains::PlannedRun,
+    dist_offline: bool,
+    allow_contract_change: bool,
+    backup: bool,
+    secrets_env: Option<&str>,
+    runner_binary: Option<&Path>,
+    setup_answers: Option<&SetupInputAnswers>,
+    provider_map: Option<&BTreeMap<PathBuf, discovery::DetectedProvider>>,
+    interactive: bool,
+    public_base_url: Option<Arc<String>>,
+    secrets_manager: Option<DynSecretsManager>,
+    log: &mut PlanItemLog,
+) -> anyhow::Result<()> {
+    let provider_id =
+        discovery::provider_id_for_pack(&item.pack.path, &item.pack.pack_id, provider_map);
+    let env_value = resolve_env(secrets_env);
+
+    if domain == Domain::Messaging
+        && action == DomainAction::Setup
+        && let Some(manager) = secrets_manager.as_ref()
+    {
+        match secrets_gate::check_provider_secrets(
+            manager,
+            &env_value,
+            tenant,
+            team,
+            &item.pack.path,
+            &provider_id,
+            None,
+            None,
+            false,
+        ) {
+            Ok(Some(missing)) => {
+                let formatted = missing
+                    .iter()
+                    .map(|entry| format!("  - {entry}"))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                log.warnings.push(operator_i18n::trf(
+                    "cli.plan.warn_skip_missing_secrets",
+                    "[warn] skip setup domain={} tenant={} provider={}: missing secrets:\n{}",
+                    &[
+                        domains::domain_name(domain),
+                        tenant,
+                        &provider_id,
+                        &formatted,
+                    ],
+                ));
+                return Ok(());
+            }
+            Ok(None) => {}
+            Err(err) => {
+                log.warnings.push(operator_i18n::trf(
+                    "cli.plan.warn_skip_secrets_check_failed",
+                    "[warn] skip setup domain={} tenant={} provider={}: secrets check failed: {}",
+                    &[
+                        domains::domain_name(domain),
+                        tenant,
+                        &provider_id,
+                        &err.to_string(),
+                    ],
+                ));
+                return Ok(());
+            }
+        }
+    }
+
+    let (setup_values, qa_form_spec) = if action == DomainAction::Setup {
+        // `--parallel` workers take turns on the terminal when setup has to prompt.
+        let _prompt = interactive.then(|| {
+            SETUP_PROMPT_LOCK
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+        });
+        let (answers, form_spec) = qa_setup_wizard::run_qa_setup(
+            &item.pack.path,
+            &item.pack.pack_id,
+            setup_answers,
+            interactive,
+            None, // no pre-built FormSpec; will try setup.yaml fallback
+        )?;
+        (Some(answers), form_spec)
+    } else {
+        (None, None)
+    };
+    let providers_root = state_root
+        .join("state")
+        .join("runtime")
+        .join(tenant)
+        .join("providers");
+    if let Err(err) = crate::provider_config_envelope::ensure_contract_compatible(
+        &providers_root,
+        &provider_id,
+        &item.flow_id,
+        &item.pack.path,
+        allow_contract_change,
+    ) {
+        operator_log::error(module_path!(), err.to_string());
+        return Err(err);
+    }
+    let current_config = crate::provider_config_envelope::read_provider_config_envelope(
+        &providers_root,
+        &provider_id,
+    )?
+    .map(|envelope| envelope.config);
+    let qa_mode = if action == DomainAction::Setup {
+        Some(crate::component_qa_ops::QaMode::Setup)
+    } else {
+        crate::component_qa_ops::qa_mode_for_flow(&item.flow_id)
+    };
+    let qa_answers = if action == DomainAction::Setup {
+        setup_values.clone().unwrap_or_else(|| json!({}))
+    } else {
+        json!({})
+    };
+    let qa_config_override = if let Some(mode) = qa_mode {
+        if let Err(err) = crate::component_qa_ops::persist_answers_artifacts(
+            &providers_root,
+            &provider_id,
+            mode,
+            &qa_answers,
+        ) {
+            operator_log::warn(
+                module_path!(),
+                format!(
+                    "failed to persist qa answers provider={} mode={} flow={}: {err}",
+                    provider_id,
+                    mode.as_str(),
+                    item.flow_id
+                ),
+            );
+        }
+        match crate::component_qa_ops::apply_answers_via_component_qa(
+            root,
+            domain,
+            tenant,
+            team,
+            &item.pack,
+            &provider_id,
+            mode,
+            current_config.as_ref(),
+            &qa_answers,
+        ) {
+            Ok(value) => value,
+            Err(diag) => {
+                operator_log::error(
+                    module_path!(),
+                    format!(
+                        "component qa failed provider={} flow={} code={} message={}",
+                        provider_id,
+                        item.flow_id,
+                        diag.code.as_str(),
+                        diag.message
+                    ),
+                );
+                return Err(anyhow::anyhow!("{diag}"));
+            }
+        }
+    } else {
+        None
+    };
+
+    // Persist secrets and config from QA results when FormSpec is available
+    if let Some(ref config) = qa_config_override
+        && let Some(ref form_spec) = qa_form_spec
+        && action == DomainAction::Setup
+        && let Err(err) = crate::qa_persist::persist_qa_config(
+            &providers_root,
+            &provider_id,
+            config,
+            &item.pack.path,
+            form_spec,
+            backup,
+        )
+    {
+        operator_log::warn(
+            module_path!(),
+            format!(
+                "failed to persist qa config provider={}: {err}",
+                provider_id
+            ),
+        );
+    }
+
+    let public_base_url_ref = public_base_url.as_deref().map(|value| value.as_str());
+    let mut input = build_input_payload(
+        state_root,
+        domain,
+        tenant,
+        team,
+        Some(&item.pack.pack_id),
+        setup_values.as_ref(),
+        public_base_url_ref,
+        &env_value,
+    );
+    if let Some(config) = qa_config_override.as_ref() {
+        input["config"] = config.clone();
+    }
+    if demo_debug_enabled() {
+        log.details.push(format!(
+            "[demo] setup input pack={} flow={} input={}",
+            item.pack.file_name,
+            item.flow_id,
+            serde_json::to_string(&input).unwrap_or_else(|_| "<invalid-json>".to_string())
+        ));
+    }
+    if action == DomainAction::Setup
+        && let Some(config_value) = qa_config_override.as_ref()
+    {
+        let setup_path = providers_root.join(format!("{provider_id}.setup.json"));
+        crate::providers::write_qa_setup_success_record(
+            &setup_path,
+            &provider_id,
+            &item.flow_id,
+            Some(config_value),
+        )?;
+        if let Err(err) = crate::provider_config_envelope::write_provider_config_envelope(
+            &providers_root,
+            &provider_id,
+            &item.flow_id,
+            config_value,
+            &item.pack.path,
+            backup,
+        ) {
+            operator_log::warn(
+                module_path!(),
+                format!(
+                    "failed to write provider config envelope provider={} flow={}: {err}",
+                    provider_id, item.flow_id
+                ),
+            );
+        }
+        log.details.push(format!(
+            "{} {} -> Success (component-qa)",
+            item.pack.file_name, item.flow_id
+        ));
+        return Ok(());
+    }
+    if let Some(runner_binary) = runner_binary {
+        let run_dir = state_layout::run_dir(state_root, domain, &item.pack.pack_id, &item.flow_id)?;
+        std::fs::create_dir_all(&run_dir)?;
+        let input_path = run_dir.join("input.json");
+        let input_json = serde_json::to_string_pretty(&input)?;
+        std::fs::write(&input_path, input_json)?;
+
+        let runner_flavor = runner_integration::detect_runner_flavor(runner_binary);
+        let output = runner_integration::run_flow_with_options(
+            runner_binary,
+            &item.pack.path,
+            &item.flow_id,
+            &input,
+            runner_integration::RunFlowOptions {
+                dist_offline,
+                tenant: Some(tenant),
+                team,
+                artifacts_dir: Some(&run_dir),
+                runner_flavor,
+                limits: runner_integration::ProcessLimits::default(),
+            },
+        )?;
+        write_runner_cli_artifacts(&run_dir, &output)?;
+        if action == DomainAction::Setup {
+            let setup_path = providers_root.join(format!("{provider_id}.setup.json"));
+            crate::providers::write_run_output(&setup_path, &provider_id, &item.flow_id, &output)?;
+            if let Some(config_value) = qa_config_override
+                .clone()
+                .or_else(|| extract_config_for_envelope(output.parsed.as_ref()))
+                && let Err(err) = crate::provider_config_envelope::write_provider_config_envelope(
+                    &providers_root,
+                    &provider_id,
+                    &item.flow_id,
+                    &config_value,
+                    &item.pack.path,
+                    backup,
+                )
+            {
+                operator_log::warn(
+                    module_path!(),
+                    format!(
+                        "failed to write provider config envelope provider={} flow={}: {err}",
+                        provider_id, item.flow_id
+                    ),
+                );
+            }
+        }
+        let exit = format_runner_exit(&output);
+        if output.status.success() {
+            log.details.push(operator_i18n::trf(
+                "cli.plan.item_result_ok",
+                "{} {} -> {}",
+                &[&item.pack.file_name, &item.flow_id, &exit],
+            ));
+        } else if let Some(summary) = summarize_runner_error(&output) {
+            log.warnings.push(operator_i18n::trf(
+                "cli.plan.item_result_error_with_summary",
+                "{} {} -> {} ({})",
+                &[&item.pack.file_name, &item.flow_id, &exit, &summary],
+            ));
+        } else {
+            log.warnings.push(operator_i18n::trf(
+                "cli.plan.item_result_error",
+                "{} {} -> {}",
+                &[&item.pack.file_name, &item.flow_id, &exit],
+            ));
+        }
+    } else {
+        let output = runner_exec::run_provider_pack_flow(runner_exec::RunRequest {
+            root: state_root.to_path_buf(),
+            domain,
+            pack_path: item.pack.path.clone(),
+            pack_label: item.pack.pack_id.clone(),
+            flow_id: item.flow_id.clone(),
+            tenant: tenant.to_string(),
+            team: team.map(|value| value.to_string()),
+            input,
+            dist_offline,
+        })
+        .map_err(|err| {
+            let message = err.to_string();
+            if message.contains("manifest.cbor is invalid") {
+                if let Ok(Some(detail)) = domains::manifest_cbor_issue_detail(&item.pack.path) {
+                    return anyhow::anyhow!(
+                        "pack verification failed for {}: {}",
+                        item.pack.path.display(),
+                        detail
+                    );
+                }
+                return anyhow::anyhow!(
+                    "pack verification failed for {}: {message}",
+                    item.pack.path.display()
+                );
+            }
+            err
+        })?;
+        if action == DomainAction::Setup {
+            let setup_path = providers_root.join(format!("{provider_id}.setup.json"));
+            crate::providers::write_run_result(
+                &setup_path,
+                &provider_id,
+                &item.flow_id,
+                &output.result,
+            )?;
+            if let Some(config_value) = qa_config_override.clone().or_else(|| {
+                extract_config_for_envelope(serde_json::to_value(&output.result).ok().as_ref())
+            }) && let Err(err) = crate::provider_config_envelope::write_provider_config_envelope(
+                &providers_root,
+                &provider_id,
+                &item.flow_id,
+                &config_value,
+                &item.pack.path,
+                backup,
+            ) {
+                operator_log::warn(
+                    module_path!(),
+                    format!(
+                        "failed to write provider config envelope provider={} flow={}: {err}",
+                        provider_id, item.flow_id
+                    ),
+                );
+            }
+        }
+        log.details.push(format!(
+            "{} {} -> {:?}",
+            item.pack.file_name, item.flow_id, output.result.status
+        ));
+    }
+
+    Ok(())
+}
+
+fn resolve_demo_runner_binary(
+    config_dir: &Path,
+    runner_binary: Option<PathBuf>,
+) -> anyhow::Result<Option<PathBuf>> {
+    let Some(runner_binary) = runner_binary else {
+        return Ok(None);
+    };
+    let runner_str = runner_binary.to_string_lossy();
+    let (name, explicit) = if looks_like_path_str(&runner_str) {
+        let name = runner_binary
+            .file_name()
+            .and_then(|value| value.to_str())
+            .unwrap_or("greentic-runner")
+            .to_string();
+        (name, Some(runner_binary))
+    } else {
+        (runner_str.to_string(), None)
+    };
+    let resolved = bin_resolver::resolve_binary(
+        &name,
+        &ResolveCtx {
+            config_dir: config_dir.to_path_buf(),
+            explicit_path: explicit,
+        },
+    )?;
+    Ok(Some(resolved))
+}
+
+fn write_runner_cli_artifacts(
+    run_dir: &Path,
+    output: &runner_integration::RunnerOutput,
+) -> anyhow::Result<()> {
+    let run_json = run_dir.join("run.json");
+    let summary_path = run_dir.join("summary.txt");
+    let stdout_path = run_dir.join("stdout.txt");
+    let stderr_path = run_dir.join("stderr.txt");
+
+    let json = serde_json::json!({
+        "status": {
+            "success": output.status.success(),
+            "code": output.status.code(),
+        },
+        "stdout": output.stdout,
+        "stderr": output.stderr,
+        "parsed": output.parsed,
+    });
+    let json = serde_json::to_string_pretty(&json)?;
+    std::fs::write(run_json, json)?;
+    std::fs::write(stdout_path, &output.stdout)?;
+    std::fs::write(stderr_path, &output.stderr)?;
+
+    let summary = format!(
+        "success: {}\nexit_code: {}\n",
+        output.status.success(),
+        output
+            .status
+            .code()
+            .map(|code| code.to_string())
+            .unwrap_or_else(|| "signal".to_string())
+    );
+    std::fs::write(summary_path, summary)?;
+    Ok(())
+}
+
+fn format_runner_exit(output: &runner_integration::RunnerOutput) -> String {
+    if let Some(code) = output.status.code() {
+        return format!("exit={code}");
+    }
+    if output.status.success() {
+        return "exit=0".to_string();
+    }
+    "exit=signal".to_string()
+}
+
+fn summarize_runner_error(output: &runner_integration::RunnerOutput) -> Option<String> {
+    output
+        .stderr
+        .lines()
+        .map(|line| line.trim())
+        .find(|line| !line.is_empty())
+        .map(|line| line.to_string())
+}
+
+fn extract_config_for_envelope(parsed: Option<&JsonValue>) -> Option<JsonValue> {
+    let value = parsed?;
+    if let Some(config) = value.get("config") {
+        return Some(config.clone());
+    }
+    Some(value.clone())
+}
+
+fn looks_like_path_str(value: &str) -> bool {
+    value.contains('/') || value.contains('\\') || Path::new(value).is_absolute()
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn build_input_payload(
+    root: &Path,
+    domain: Domain,
+    tenant: &str,
+    team: Option<&str>,
+    pack_id: Option<&str>,
+    setup_answers: Option<&serde_json::Value>,
+    public_base_url: Option<&str>,
+    env: &str,
+) -> serde_json::Value {
+    let mut payload = serde_json::json!({
+        "tenant": tenant,
+    });
+    if let Some(team) = team {
+        payload["team"] = serde_json::Value::String(team.to_string());
+    }
+
+    let resolved_public_base_url = public_base_url.map(|value| value.to_string()).or_else(|| {
+        if matches!(domain, Domain::Messaging | Domain::Events) {
+            read_public_base_url(root, tenant, team)
+        } else {
+            None
+        }
+    });
+
+    if matches!(domain, Domain::Messaging | Domain::Events) {
+        let mut config = serde_json::json!({});
+        if let Some(url) = resolved_public_base_url.as_ref() {
+            payload["public_base_url"] = serde_json::Value::String(url.clone());
+            config["public_base_url"] = serde_json::Value::String(url.clone());
+        }
+        payload["config"] = config;
+    }
+
+    if let Some(pack_id) = pack_id
+        && let Some(config_map) = payload
+            .get_mut("config")
+            .and_then(|value| value.as_object_mut())
+    {
+        config_map.insert(
+            "id".to_string(),
+            serde_json::Value::String(pack_id.to_string()),
+        );
+    }
+    if let Some(pack_id) = pack_id {
+        payload["id"] = serde_json::Value::String(pack_id.to_string());
+    }
+    if let Some(answers) = setup_answers {
+        payload["setup_answers"] = answers.clone();
+        if let Ok(json) = serde_json::to_string(answers) {
+            payload["answers_json"] = serde_json::Value::String(json);
+        }
+    }
+    let mut tenant_ctx = serde_json::json!({
+        "env": env,
+        "tenant": tenant,
+        "tenant_id": tenant,
+    });
+    if let Some(team) = team {
+        tenant_ctx["team"] = serde_json::Value::String(team.to_string());
+        tenant_ctx["team_id"] = serde_json::Value::String(team.to_string());
+    }
+    let msg_id = pack_id
+        .map(|value| format!("{value}.setup"))
+        .unwrap_or_else(|| "setup".to_string());
+    let mut metadata = serde_json::json!({});
+    if let Some(url) = resolved_public_base_url {
+        metadata["public_base_url"] = serde_json::Value::String(url);
+    }
+    let msg = serde_json::json!({
+        "id": msg_id,
+        "tenant": tenant_ctx,
+        "channel": "setup",
+        "message": {
+            "id": pack_id
+                .map(|value| format!("{value}.setup_default__collect"))
+                .unwrap_or_else(|| "setup_default__collect".to_string()),
+            "text": "Collect inputs for setup_default."
+        },
+        "session_id": "setup",
+        "metadata": metadata,
+        "reply_scope": "",
+        "text": "Collect inputs for setup_default.",
+        "user_id": "operator",
+    });
+    payload["msg"] = msg;
+    let payload_id = pack_id
+        .map(|value| format!("{value}-setup_default"))
+        .unwrap_or_else(|| "setup_default".to_string());
+    payload["payload"] = serde_json::json!({
+        "id": payload_id,
+        "spec_ref": "assets/setup.yaml"
+    });
+    payload
+}
+
+fn read_public_base_url(root: &Path, tenant: &str, team: Option<&str>) -> Option<String> {
+    let team_id = team.unwrap_or("default");
+    let paths = crate::runtime_state::RuntimePaths::new(root.join("state"), tenant, team_id);
+    let path = crate::cloudflared::public_url_path(&paths);
+    let contents = std::fs::read_to_string(path).ok()?;
+    crate::cloudflared::parse_public_url(&contents)
+        .or_else(|| crate::ngrok::parse_public_url(&contents))
+}
+
+fn demo_debug_enabled() -> bool {
+    matches!(
+        std::env::var("GREENTIC_OPERATOR_DEMO_DEBUG").as_deref(),
+        Ok("1") | Ok("true") | Ok("yes")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_allowed_providers_moves_missing() {
+        let mut packs = vec![
+            domains::ProviderPack {
+                pack_id: "messaging-telegram".to_string(),
+                file_name: "telegram.gtpack".to_string(),
+                path: PathBuf::from("telegram.gtpack"),
+                entry_flows: vec!["setup_default".to_string()],
+                depends_on: Vec::new(),
+                verify_flows: Vec::new(),
+            },
+            domains::ProviderPack {
+                pack_id: "messaging-slack".to_string(),
+                file_name: "slack.gtpack".to_string(),
+                path: PathBuf::from("slack.gtpack"),
+                entry_flows: vec!["setup_default".to_string()],
+                depends_on: Vec::new(),
+                verify_flows: Vec::new(),
+            },
+        ];
+        let allowed = vec![
+            "messaging-telegram".to_string(),
+            "messaging-email".to_string(),
+        ]
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+        let missing = filter_packs_by_allowed(&mut packs, &allowed);
+        assert_eq!(packs.len(), 1);
+        assert_eq!(packs[0].pack_id, "messaging-telegram");
+        assert_eq!(missing, vec!["messaging-email".to_string()]);
+    }
+
+    #[test]
+    fn plan_queue_holds_dependents_until_dependencies_finish() {
+        let run = |pack_id: &str, depends_on: &[&str]| domains::PlannedRun {
+            pack: domains::ProviderPack {
+                pack_id: pack_id.to_string(),
+                file_name: format!("{pack_id}.gtpack"),
+                path: PathBuf::from(format!("{pack_id}.gtpack")),
+                entry_flows: vec!["setup_default".to_string()],
+                depends_on: depends_on.iter().map(|dep| dep.to_string()).collect(),
+                verify_flows: Vec::new(),
+            },
+            flow_id: "setup_default".to_string(),
+        };
+        let mut queue = PlanQueue::new(vec![
+            run("secrets-vault", &[]),
+            run("messaging-telegram", &["secrets-vault"]),
+            run("messaging-slack", &[]),
+        ]);
+        let (first, _) = queue.next_ready().unwrap();
+        let (second, _) = queue.next_ready().unwrap();
+        assert_eq!((first, second), (0, 2));
+        assert!(queue.next_ready().is_none());
+        queue.finish("secrets-vault", true);
+        let (third, item) = queue.next_ready().unwrap();
+        assert_eq!(
+            (third, item.pack.pack_id.as_str()),
+            (1, "messaging-telegram")
+        );
+        assert!(queue.waiting.is_empty());
+
+        let mut queue = PlanQueue::new(vec![
+            run("secrets-vault", &[]),
+            run("messaging-telegram", &["secrets-vault"]),
+            run("messaging-webchat", &["messaging-telegram"]),
+            run("messaging-slack", &[]),
+        ]);
+        let (first, _) = queue.next_ready().unwrap();
+        let (second, _) = queue.next_ready().unwrap();
+        assert_eq!((first, second), (0, 3));
+        queue.finish("secrets-vault", false);
+        assert!(queue.next_ready().is_none());
+        assert!(queue.waiting.is_empty());
+        let skipped = queue
+            .skipped
+            .iter()
+            .map(|(index, _)| *index)
+            .collect::<Vec<_>>();
+        assert_eq!(skipped, vec![1, 2]);
+    }
+}
@@ -0,0 +1,370 @@
+//! One shared handle per `.gtpack` archive.
+//!
+//! `ensure_cbor_packs`, discovery and the runner host all read the same packs.
+//! [`PackArchive::open`] hands each of them the same handle for a pack, keyed by its
+//! canonical path and rebuilt when the pack, its `.sig` sidecar or the bundle's
+//! greentic.demo.yaml changes on disk. The handle memoizes the member list, the
+//! signature check, the manifest bytes, their decoded CBOR and the pack metadata; it
+//! keeps no file open, and every other member is read from a fresh handle only when
+//! asked for. At most [`CACHE_CAPACITY`] packs are kept, least recently used first
+//! out, and packs that disappear are dropped on their next lookup.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::SystemTime;
+
+use anyhow::Context;
+use serde_cbor::Value as CborValue;
+use zip::ZipArchive;
+
+use crate::profile::{self, Phase};
+
+use super::signing::{self, ENCRYPTED_SUFFIX, PackTrust, SignatureStatus};
+use super::{PackMeta, build_pack_meta, parse_manifest_cbor_value};
+
+/// Packs kept by [`PackArchive::open`].
+pub const CACHE_CAPACITY: usize = 64;
+
+/// What the cached handle for a path was opened against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Stamp {
+    len: u64,
+    modified: Option<SystemTime>,
+    sidecar: Option<SystemTime>,
+    config: Option<SystemTime>,
+}
+
+impl Stamp {
+    fn of(path: &Path) -> anyhow::Result<Self> {
+        let metadata = fs::metadata(path).with_context(|| format!("open {}", path.display()))?;
+        let modified = |path: &Path| fs::metadata(path).and_then(|meta| meta.modified()).ok();
+        Ok(Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+            sidecar: modified(&signing::sidecar_path(path)),
+            config: signing::bundle_config_for(path).and_then(|config| modified(&config)),
+        })
+    }
+}
+
+/// A value computed on first successful use; failures are not kept.
+struct Memo<T>(Mutex<Option<T>>);
+
+impl<T: Clone> Memo<T> {
+    fn new() -> Self {
+        Self(Mutex::new(None))
+    }
+
+    fn get_or_try(&self, init: impl FnOnce() -> anyhow::Result<T>) -> anyhow::Result<T> {
+        let mut slot = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(value) = slot.as_ref() {
+            return Ok(value.clone());
+        }
+        let value = init()?;
+        *slot = Some(value.clone());
+        Ok(value)
+    }
+}
+
+pub struct PackArchive {
+    path: PathBuf,
+    stamp: Stamp,
+    trust: PackTrust,
+    members: Vec<String>,
+    signature: Memo<SignatureStatus>,
+    manifest_bytes: Memo<Option<Arc<[u8]>>>,
+    manifest_value: Memo<Option<Arc<CborValue>>>,
+    meta: Memo<Option<PackMeta>>,
+    /// Decrypted copies, by the directory they were written to.
+    runnable: Mutex<HashMap<PathBuf, PathBuf>>,
+}
+
+/// Cached packs by canonical path, with the tick they were last used at.
+#[derive(Default)]
+struct Cache {
+    packs: HashMap<PathBuf, (u64, Arc<PackArchive>)>,
+    tick: u64,
+}
+
+impl Cache {
+    fn get(&mut self, key: &Path, stamp: Stamp) -> Option<Arc<PackArchive>> {
+        self.tick += 1;
+        let (used, pack) = self
+            .packs
+            .get_mut(key)
+            .filter(|(_, pack)| pack.stamp == stamp)?;
+        *used = self.tick;
+        Some(pack.clone())
+    }
+
+    /// Adds `pack`, dropping the least recently used packs beyond `capacity`.
+    fn insert(&mut self, key: PathBuf, pack: Arc<PackArchive>, capacity: usize) {
+        self.tick += 1;
+        self.packs.insert(key, (self.tick, pack));
+        while self.packs.len() > capacity {
+            let Some(oldest) = self
+                .packs
+                .iter()
+                .min_by_key(|(_, (used, _))| *used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            self.packs.remove(&oldest);
+        }
+    }
+}
+
+fn cache() -> MutexGuard<'static, Cache> {
+    static CACHE: OnceLock<Mutex<Cache>> = OnceLock::new();
+    CACHE
+        .get_or_init(|| Mutex::new(Cache::default()))
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+}
+
+impl PackArchive {
+    /// The shared handle for the pack at `path`.
+    pub fn open(path: &Path) -> anyhow::Result<Arc<Self>> {
+        let key = match path.canonicalize() {
+            Ok(key) => key,
+            Err(err) => {
+                // A pack that is gone must not keep its slot.
+                if let Some(key) = std::path::absolute(path).ok().and_then(|path| {
+                    Some(path.parent()?.canonicalize().ok()?.join(path.file_name()?))
+                }) {
+                    cache().packs.remove(&key);
+                }
+                return Err(err).with_context(|| format!("open {}", path.display()));
+            }
+        };
+        let stamp = Stamp::of(path)?;
+        let mut cache = cache();
+        if let Some(pack) = cache.get(&key, stamp) {
+            return Ok(pack);
+        }
+        let pack = Arc::new(Self::load(path, stamp)?);
+        cache.insert(key, pack.clone(), CACHE_CAPACITY);
+        Ok(pack)
+    }
+
+    fn load(path: &Path, stamp: Stamp) -> anyhow::Result<Self> {
+        let zip = Self::read_zip(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            stamp,
+            trust: PackTrust::for_pack(path),
+            members: zip.file_names().map(str::to_string).collect(),
+            signature: Memo::new(),
+            manifest_bytes: Memo::new(),
+            manifest_value: Memo::new(),
+            meta: Memo::new(),
+            runnable: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Member names, in archive order.
+    pub fn members(&self) -> &[String] {
+        &self.members
+    }
+
+    pub fn has_encrypted_members(&self) -> bool {
+        self.members
+            .iter()
+            .any(|name| name.ends_with(ENCRYPTED_SUFFIX))
+    }
+
+    /// Signature status under the bundle's keys, without refusing anything.
+    pub fn signature(&self) -> anyhow::Result<SignatureStatus> {
+        self.signature
+            .get_or_try(|| self.trust.check(&mut self.zip()?, &self.path))
+    }
+
+    /// The signature status, failing for a pack the bundle does not accept.
+    pub fn enforce(&self) -> anyhow::Result<SignatureStatus> {
+        self.trust.accept(self.signature()?, &self.path)
+    }
+
+    /// Reads member `name`, decrypting `<name>.enc` when only that is present.
+    pub fn read_member(&self, name: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        self.trust.read_member(&mut self.zip()?, name)
+    }
+
+    /// `manifest.cbor`, decrypted if need be; `None` when the pack has none.
+    pub fn manifest_bytes(&self) -> anyhow::Result<Option<Arc<[u8]>>> {
+        self.manifest_bytes.get_or_try(|| {
+            let _span = profile::span(Phase::Manifest, self.path.display());
+            Ok(self
+                .read_member("manifest.cbor")?
+                .map(|bytes| Arc::from(bytes.into_boxed_slice())))
+        })
+    }
+
+    /// `manifest.cbor` decoded as CBOR, before symbol resolution.
+    pub fn manifest_value(&self) -> anyhow::Result<Option<Arc<CborValue>>> {
+        self.manifest_value.get_or_try(|| {
+            self.manifest_bytes()?
+                .map(|bytes| -> anyhow::Result<Arc<CborValue>> {
+                    let _span = profile::span(Phase::Manifest, self.path.display());
+                    Ok(Arc::new(serde_cbor::from_slice(&bytes)?))
+                })
+                .transpose()
+        })
+    }
+
+    /// Pack id and flow lists from `manifest.cbor`.
+    pub(crate) fn meta(&self) -> anyhow::Result<Option<PackMeta>> {
+        self.meta.get_or_try(|| {
+            self.manifest_value()?
+                .map(|value| -> anyhow::Result<PackMeta> {
+                    let _span = profile::span(Phase::Manifest, self.path.display());
+                    let manifest = parse_manifest_cbor_value(&value)?;
+                    Ok(build_pack_meta(&manifest, &self.path))
+                })
+                .transpose()
+        })
+    }
+
+    /// The archive the runner should load once the signature is accepted: the pack
+    /// itself, or a copy under `dir` with its encrypted members decrypted. The copy
+    /// is made once per `dir`, and again if it has been removed since.
+    pub fn runnable_path(&self, dir: &Path) -> anyhow::Result<PathBuf> {
+        self.enforce()?;
+        if !self.has_encrypted_members() {
+            return Ok(self.path.clone());
+        }
+        let mut runnable = self.runnable.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(copy) = runnable.get(dir).filter(|copy| copy.is_file()) {
+            return Ok(copy.clone());
+        }
+        let copy = self
+            .trust
+            .decrypted_copy(&mut self.zip()?, &self.path, dir)?;
+        runnable.insert(dir.to_path_buf(), copy.clone());
+        Ok(copy)
+    }
+
+    /// A fresh reader over the pack, refused once the pack no longer matches what
+    /// this handle memoized.
+    fn zip(&self) -> anyhow::Result<ZipArchive<File>> {
+        if Stamp::of(&self.path)? != self.stamp {
+            return Err(anyhow::anyhow!(
+                "{} changed on disk; open it again",
+                self.path.display()
+            ));
+        }
+        Self::read_zip(&self.path)
+    }
+
+    fn read_zip(path: &Path) -> anyhow::Result<ZipArchive<File>> {
+        let file = File::open(path).with_context(|| format!("open {}", path.display()))?;
+        ZipArchive::new(file).with_context(|| format!("{} is not a zip archive", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::{FileOptions, ZipWriter};
+
+    fn write_pack(path: &Path, manifest: &[u8]) {
+        let mut writer = ZipWriter::new(File::create(path).unwrap());
+        writer
+            .start_file("manifest.cbor", FileOptions::<()>::default())
+            .unwrap();
+        writer.write_all(manifest).unwrap();
+        writer
+            .start_file("assets/setup.yaml", FileOptions::<()>::default())
+            .unwrap();
+        writer.write_all(b"questions: []").unwrap();
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn shares_handles_until_the_pack_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("telegram.gtpack");
+        let manifest = |pack_id: &str| {
+            serde_cbor::to_vec(&serde_json::json!({
+                "meta": {"pack_id": pack_id, "entry_flows": ["setup_default"]},
+            }))
+            .unwrap()
+        };
+        write_pack(&path, &manifest("messaging-telegram"));
+
+        let first = PackArchive::open(&path).unwrap();
+        assert!(Arc::ptr_eq(&first, &PackArchive::open(&path).unwrap()));
+        assert!(
+            first
+                .members()
+                .iter()
+                .any(|name| name == "assets/setup.yaml")
+        );
+        assert_eq!(first.signature().unwrap(), SignatureStatus::Unsigned);
+        assert_eq!(
+            first.read_member("assets/setup.yaml").unwrap().as_deref(),
+            Some(b"questions: []".as_slice())
+        );
+        let meta = first.meta().unwrap().unwrap();
+        assert_eq!(meta.pack_id, "messaging-telegram");
+        assert_eq!(meta.entry_flows, ["setup_default"]);
+
+        write_pack(&path, &manifest("messaging-telegram-v2-with-a-longer-id"));
+        let second = PackArchive::open(&path).unwrap();
+        assert!(!Arc::ptr_eq(&first, &second));
+        assert_eq!(
+            second.meta().unwrap().unwrap().pack_id,
+            "messaging-telegram-v2-with-a-longer-id"
+        );
+        assert_eq!(first.meta().unwrap().unwrap().pack_id, "messaging-telegram");
+        assert!(first.read_member("assets/setup.yaml").is_err());
+    }
+
+    #[test]
+    fn handles_are_keyed_by_canonical_path_and_keep_no_file_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("webchat.gtpack");
+        write_pack(&path, b"\xa0");
+
+        let first = PackArchive::open(&path).unwrap();
+        let spelled = dir.path().join(".").join("webchat.gtpack");
+        assert!(Arc::ptr_eq(&first, &PackArchive::open(&spelled).unwrap()));
+
+        fs::remove_file(&path).unwrap();
+        assert!(PackArchive::open(&path).is_err());
+        let key = dir.path().canonicalize().unwrap().join("webchat.gtpack");
+        assert!(!cache().packs.contains_key(&key));
+        assert!(first.read_member("assets/setup.yaml").is_err());
+    }
+
+    #[test]
+    fn the_least_recently_used_pack_leaves_a_full_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let load = |name: &str| {
+            let path = dir.path().join(format!("{name}.gtpack"));
+            write_pack(&path, b"\xa0");
+            let stamp = Stamp::of(&path).unwrap();
+            let pack = Arc::new(PackArchive::load(&path, stamp).unwrap());
+            (path, stamp, pack)
+        };
+        let (a, a_stamp, a_pack) = load("a");
+        let (b, b_stamp, b_pack) = load("b");
+        let (c, c_stamp, c_pack) = load("c");
+
+        let mut cache = Cache::default();
+        cache.insert(a.clone(), a_pack, 2);
+        cache.insert(b.clone(), b_pack, 2);
+        assert!(cache.get(&a, a_stamp).is_some());
+        cache.insert(c.clone(), c_pack, 2);
+        assert!(cache.get(&a, a_stamp).is_some());
+        assert!(cache.get(&b, b_stamp).is_none());
+        assert!(cache.get(&c, c_stamp).is_some());
+    }
+}
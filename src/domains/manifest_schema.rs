@@ -0,0 +1,483 @@
+//! Schema checks for a decoded `manifest.cbor`.
+//!
+//! Decoding is lenient: a symbol index that points nowhere resolves to the index
+//! itself, and keys nobody reads are ignored. [`validate_manifest`] reports those
+//! cases instead, each at the CBOR path it was found at (`flows[2].id`,
+//! `symbols.flow_ids[4]`). Errors make [`super::ensure_cbor_packs`] refuse the pack;
+//! warnings are only logged.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use serde::Serialize;
+use serde_cbor::Value as CborValue;
+
+use super::{map_get, symbol_array, symbols_map};
+use crate::runner_pin::VersionReq;
+
+/// Top-level keys written by greentic-pack. Others are reported as warnings.
+const ROOT_KEYS: &[&str] = &[
+    "schema_version",
+    "pack_id",
+    "name",
+    "version",
+    "kind",
+    "publisher",
+    "description",
+    "meta",
+    "components",
+    "flows",
+    "dependencies",
+    "capabilities",
+    "secret_requirements",
+    "signatures",
+    "bootstrap",
+    "extensions",
+    "symbols",
+];
+
+const META_KEYS: &[&str] = &[
+    "pack_id",
+    "entry_flows",
+    "depends_on",
+    "verify_flows",
+    "runner_version",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct SchemaIssue {
+    pub severity: Severity,
+    /// Where in the manifest, e.g. `meta.entry_flows[1]`.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+type CborMap = BTreeMap<CborValue, CborValue>;
+
+#[derive(Default)]
+struct Checker {
+    issues: Vec<SchemaIssue>,
+}
+
+impl Checker {
+    fn error(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.push(Severity::Error, path.into(), message.into());
+    }
+
+    fn warn(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.push(Severity::Warning, path.into(), message.into());
+    }
+
+    fn push(&mut self, severity: Severity, path: String, message: String) {
+        self.issues.push(SchemaIssue {
+            severity,
+            path,
+            message,
+        });
+    }
+
+    fn unknown_keys(&mut self, map: &CborMap, allowed: &[&str], prefix: &str) {
+        for key in map.keys() {
+            match key {
+                CborValue::Text(key) if allowed.contains(&key.as_str()) => {}
+                CborValue::Text(key) => self.warn(join(prefix, key), "unknown key"),
+                other => self.error(prefix_or_root(prefix), format!("non-text key {other:?}")),
+            }
+        }
+    }
+
+    /// A string, or an index into `symbols.<symbol_key>`; returns the resolved text.
+    fn string_or_symbol(
+        &mut self,
+        value: &CborValue,
+        symbols: Option<&CborMap>,
+        symbol_key: &str,
+        path: &str,
+    ) -> Option<String> {
+        match value {
+            CborValue::Text(text) => Some(text.clone()),
+            CborValue::Integer(idx) => {
+                let Some(symbols) = symbols else {
+                    self.error(
+                        path,
+                        format!("symbol index {idx} but the manifest has no symbols table"),
+                    );
+                    return None;
+                };
+                let Some(values) = symbol_array(symbols, symbol_key) else {
+                    self.error(
+                        path,
+                        format!("symbol index {idx} but symbols.{symbol_key} is missing"),
+                    );
+                    return None;
+                };
+                match usize::try_from(*idx).ok().and_then(|idx| values.get(idx)) {
+                    Some(CborValue::Text(text)) => Some(text.clone()),
+                    Some(_) => {
+                        self.error(path, format!("symbols.{symbol_key}[{idx}] is not a string"));
+                        None
+                    }
+                    None => {
+                        self.error(
+                            path,
+                            format!(
+                                "symbol index {idx} out of range for symbols.{symbol_key} ({} entries)",
+                                values.len()
+                            ),
+                        );
+                        None
+                    }
+                }
+            }
+            other => {
+                self.error(
+                    path,
+                    format!("expected string or symbol index, found {}", kind(other)),
+                );
+                None
+            }
+        }
+    }
+
+    /// Resolved entries by position; `None` where an entry is invalid. Indexes that
+    /// miss `symbols.<symbol_key>` may hit `symbols.<fallback_key>`, as in decoding.
+    fn string_array(
+        &mut self,
+        value: &CborValue,
+        symbols: Option<&CborMap>,
+        symbol_key: &str,
+        fallback_key: Option<&str>,
+        path: &str,
+    ) -> Vec<Option<String>> {
+        let CborValue::Array(values) = value else {
+            self.error(path, format!("expected array, found {}", kind(value)));
+            return Vec::new();
+        };
+        values
+            .iter()
+            .enumerate()
+            .map(|(idx, value)| {
+                if lookup(value, symbols, symbol_key).is_none()
+                    && let Some(text) =
+                        fallback_key.and_then(|fallback| lookup(value, symbols, fallback))
+                {
+                    return Some(text);
+                }
+                self.string_or_symbol(value, symbols, symbol_key, &format!("{path}[{idx}]"))
+            })
+            .collect()
+    }
+
+    fn symbols(&mut self, symbols: &CborValue) {
+        let CborValue::Map(symbols) = symbols else {
+            self.error("symbols", format!("expected map, found {}", kind(symbols)));
+            return;
+        };
+        for (key, values) in symbols {
+            let CborValue::Text(key) = key else {
+                self.error("symbols", format!("non-text key {key:?}"));
+                continue;
+            };
+            let CborValue::Array(values) = values else {
+                self.error(
+                    format!("symbols.{key}"),
+                    format!("expected array, found {}", kind(values)),
+                );
+                continue;
+            };
+            let mut seen = BTreeMap::new();
+            for (idx, value) in values.iter().enumerate() {
+                let CborValue::Text(text) = value else {
+                    self.error(
+                        format!("symbols.{key}[{idx}]"),
+                        format!("expected string, found {}", kind(value)),
+                    );
+                    continue;
+                };
+                if let Some(first) = seen.insert(text.as_str(), idx) {
+                    self.warn(
+                        format!("symbols.{key}[{idx}]"),
+                        format!("duplicate symbol '{text}' (also at index {first})"),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Every issue in `value`, in manifest order. An empty result means the manifest
+/// decodes without anything being guessed.
+pub fn validate_manifest(value: &CborValue) -> Vec<SchemaIssue> {
+    let mut checker = Checker::default();
+    let CborValue::Map(map) = value else {
+        checker.error(
+            "$",
+            format!("manifest must be a map, found {}", kind(value)),
+        );
+        return checker.issues;
+    };
+    checker.unknown_keys(map, ROOT_KEYS, "");
+    if let Some(symbols) = map_get(map, "symbols") {
+        checker.symbols(symbols);
+    }
+    let symbols = symbols_map(map);
+
+    let root_pack_id = map_get(map, "pack_id")
+        .and_then(|value| checker.string_or_symbol(value, symbols, "pack_ids", "pack_id"));
+    let mut meta_pack_id = None;
+    let mut entry_flows = Vec::new();
+    let mut verify_flows = Vec::new();
+    if let Some(meta) = map_get(map, "meta") {
+        match meta {
+            CborValue::Map(meta) => {
+                checker.unknown_keys(meta, META_KEYS, "meta");
+                meta_pack_id = map_get(meta, "pack_id").and_then(|value| {
+                    checker.string_or_symbol(value, symbols, "pack_ids", "meta.pack_id")
+                });
+                if let Some(value) = map_get(meta, "entry_flows") {
+                    entry_flows = checker.string_array(
+                        value,
+                        symbols,
+                        "flow_ids",
+                        Some("entrypoints"),
+                        "meta.entry_flows",
+                    );
+                }
+                if let Some(value) = map_get(meta, "depends_on") {
+                    checker.string_array(value, symbols, "pack_ids", None, "meta.depends_on");
+                }
+                if let Some(value) = map_get(meta, "verify_flows") {
+                    verify_flows =
+                        checker.string_array(value, symbols, "flow_ids", None, "meta.verify_flows");
+                }
+                match map_get(meta, "runner_version") {
+                    Some(CborValue::Text(text)) => {
+                        if let Err(err) = VersionReq::parse(text) {
+                            checker.error("meta.runner_version", format!("{err}"));
+                        }
+                    }
+                    Some(other) => checker.error(
+                        "meta.runner_version",
+                        format!("expected string, found {}", kind(other)),
+                    ),
+                    None => {}
+                }
+            }
+            other => checker.error("meta", format!("expected map, found {}", kind(other))),
+        }
+    }
+    if root_pack_id.is_none() && meta_pack_id.is_none() {
+        checker.error("pack_id", "required (at the top level or as meta.pack_id)");
+    }
+    if let (Some(root), Some(meta)) = (&root_pack_id, &meta_pack_id)
+        && root != meta
+    {
+        checker.warn(
+            "meta.pack_id",
+            format!("'{meta}' differs from pack_id '{root}'; pack_id wins"),
+        );
+    }
+
+    let mut flow_names = BTreeSet::new();
+    if let Some(flows) = map_get(map, "flows") {
+        match flows {
+            CborValue::Array(flows) => {
+                let mut ids: BTreeMap<String, usize> = BTreeMap::new();
+                for (idx, flow) in flows.iter().enumerate() {
+                    let path = format!("flows[{idx}]");
+                    let CborValue::Map(flow) = flow else {
+                        checker.error(&path, format!("expected map, found {}", kind(flow)));
+                        continue;
+                    };
+                    match map_get(flow, "id") {
+                        Some(id) => {
+                            if let Some(id) = checker.string_or_symbol(
+                                id,
+                                symbols,
+                                "flow_ids",
+                                &format!("{path}.id"),
+                            ) {
+                                if let Some(first) = ids.insert(id.clone(), idx) {
+                                    checker.error(
+                                        format!("{path}.id"),
+                                        format!(
+                                            "duplicate flow id '{id}' (also at flows[{first}])"
+                                        ),
+                                    );
+                                }
+                                flow_names.insert(id);
+                            }
+                        }
+                        None => checker.error(format!("{path}.id"), "required"),
+                    }
+                    if let Some(entrypoints) = map_get(flow, "entrypoints") {
+                        flow_names.extend(
+                            checker
+                                .string_array(
+                                    entrypoints,
+                                    symbols,
+                                    "entrypoints",
+                                    None,
+                                    &format!("{path}.entrypoints"),
+                                )
+                                .into_iter()
+                                .flatten(),
+                        );
+                    }
+                    if let Some(calls) = map_get(flow, "calls") {
+                        checker.string_array(
+                            calls,
+                            symbols,
+                            "pack_ids",
+                            None,
+                            &format!("{path}.calls"),
+                        );
+                    }
+                    if let Some(schema) = map_get(flow, "input_schema")
+                        && !matches!(schema, CborValue::Map(_))
+                    {
+                        checker.error(
+                            format!("{path}.input_schema"),
+                            format!("expected map, found {}", kind(schema)),
+                        );
+                    }
+                }
+            }
+            other => checker.error("flows", format!("expected array, found {}", kind(other))),
+        }
+    }
+    if !flow_names.is_empty() {
+        for (key, names) in [
+            ("entry_flows", &entry_flows),
+            ("verify_flows", &verify_flows),
+        ] {
+            for (idx, name) in names.iter().enumerate() {
+                if let Some(name) = name
+                    && !flow_names.contains(name)
+                {
+                    checker.warn(
+                        format!("meta.{key}[{idx}]"),
+                        format!("'{name}' is not a flow id or entrypoint in flows"),
+                    );
+                }
+            }
+        }
+    }
+    checker.issues
+}
+
+/// The errors among `issues`, one per line.
+pub fn describe_errors(issues: &[SchemaIssue]) -> Option<String> {
+    let errors: Vec<String> = issues
+        .iter()
+        .filter(|issue| issue.severity == Severity::Error)
+        .map(ToString::to_string)
+        .collect();
+    (!errors.is_empty()).then(|| errors.join("; "))
+}
+
+/// The text a symbol index resolves to, if it does.
+fn lookup(value: &CborValue, symbols: Option<&CborMap>, symbol_key: &str) -> Option<String> {
+    let CborValue::Integer(idx) = value else {
+        return None;
+    };
+    match symbol_array(symbols?, symbol_key)?.get(usize::try_from(*idx).ok()?) {
+        Some(CborValue::Text(text)) => Some(text.clone()),
+        _ => None,
+    }
+}
+
+fn join(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{prefix}.{key}")
+    }
+}
+
+fn prefix_or_root(prefix: &str) -> &str {
+    if prefix.is_empty() { "$" } else { prefix }
+}
+
+fn kind(value: &CborValue) -> &'static str {
+    match value {
+        CborValue::Null => "null",
+        CborValue::Bool(_) => "bool",
+        CborValue::Integer(_) => "integer",
+        CborValue::Float(_) => "float",
+        CborValue::Bytes(_) => "bytes",
+        CborValue::Text(_) => "string",
+        CborValue::Array(_) => "array",
+        CborValue::Map(_) => "map",
+        CborValue::Tag(_, _) => "tagged value",
+        _ => "unknown value",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn cbor(value: serde_json::Value) -> CborValue {
+        serde_cbor::from_slice(&serde_cbor::to_vec(&value).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn reports_each_problem_at_its_path() {
+        let clean = cbor(json!({
+            "pack_id": 0,
+            "meta": {"entry_flows": [0, "setup_default"]},
+            "flows": [{"id": 0}, {"id": "setup_default", "input_schema": {"type": "object"}}],
+            "symbols": {"pack_ids": ["messaging-telegram"], "flow_ids": ["verify_webhooks"]}
+        }));
+        assert_eq!(validate_manifest(&clean), Vec::new());
+
+        let broken = cbor(json!({
+            "meta": {"entry_flows": [3, "missing"], "colour": "blue"},
+            "flows": [
+                {"id": 0},
+                {"id": "verify_webhooks", "input_schema": "object"},
+                {"entrypoints": "main"}
+            ],
+            "symbols": {"flow_ids": ["verify_webhooks"]},
+            "extra": true
+        }));
+        let issues: Vec<String> = validate_manifest(&broken)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                "extra: unknown key",
+                "meta.colour: unknown key",
+                "meta.entry_flows[0]: symbol index 3 out of range for symbols.flow_ids (1 entries)",
+                "pack_id: required (at the top level or as meta.pack_id)",
+                "flows[1].id: duplicate flow id 'verify_webhooks' (also at flows[0])",
+                "flows[1].input_schema: expected map, found string",
+                "flows[2].id: required",
+                "flows[2].entrypoints: expected array, found string",
+                "meta.entry_flows[1]: 'missing' is not a flow id or entrypoint in flows",
+            ]
+        );
+        assert!(
+            describe_errors(&validate_manifest(&broken))
+                .unwrap()
+                .starts_with("meta.entry_flows[0]: symbol index 3")
+        );
+    }
+}
@@ -1,17 +1,32 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
 use serde_cbor::Value as CborValue;
+use serde_json::Value as JsonValue;
 use zip::result::ZipError;
 
+use crate::operator_error::manifest_invalid;
+use crate::operator_log;
+
+pub mod archive;
+pub mod manifest_schema;
+pub mod registry;
+pub mod signing;
+
+pub use archive::PackArchive;
+pub use registry::{CustomDomain, register_bundle_domains};
+pub use signing::{PackTrust, SignatureStatus};
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord, Hash)]
 pub enum Domain {
     Messaging,
     Events,
     Secrets,
+    /// Declared under `domains:` in greentic.demo.yaml (see [`registry`]).
+    Custom(CustomDomain),
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -35,6 +50,13 @@ pub struct ProviderPack {
     pub file_name: String,
     pub path: PathBuf,
     pub entry_flows: Vec<String>,
+    /// Pack ids whose setup must run before this pack's.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// Verify flows the pack declares in `meta.verify_flows`, run after the
+    /// domain's own.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub verify_flows: Vec<String>,
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -63,15 +85,12 @@ pub fn config(domain: Domain) -> DomainConfig {
             diagnostics_flow: "diagnostics",
             verify_flows: &[],
         },
+        Domain::Custom(custom) => registry::config(custom),
     }
 }
 
 pub fn validator_pack_path(root: &Path, domain: Domain) -> Option<PathBuf> {
-    let name = match domain {
-        Domain::Messaging => "validators-messaging.gtpack",
-        Domain::Events => "validators-events.gtpack",
-        Domain::Secrets => "validators-secrets.gtpack",
-    };
+    let name = format!("validators-{}.gtpack", domain_name(domain));
     let path = root.join("validators").join(domain_name(domain)).join(name);
     if path.exists() { Some(path) } else { None }
 }
@@ -88,16 +107,26 @@ pub fn ensure_cbor_packs(root: &Path) -> anyhow::Result<()> {
     }
     for root in roots {
         for pack in collect_gtpacks(&root)? {
-            let file = std::fs::File::open(&pack)?;
-            let mut archive = zip::ZipArchive::new(file)?;
-            let manifest = read_manifest_cbor(&mut archive, &pack).map_err(|err| {
-                anyhow::anyhow!(
-                    "failed to decode manifest.cbor in {}: {err}",
-                    pack.display()
-                )
-            })?;
-            if manifest.is_none() {
-                return Err(missing_cbor_error(&pack));
+            let archive = PackArchive::open(&pack)?;
+            archive.enforce()?;
+            let value = archive
+                .manifest_value()
+                .map_err(|err| {
+                    manifest_invalid(&pack, format!("failed to decode manifest.cbor: {err}"))
+                })?
+                .ok_or_else(|| missing_cbor_error(&pack))?;
+            let issues = manifest_schema::validate_manifest(&value);
+            if let Some(errors) = manifest_schema::describe_errors(&issues) {
+                return Err(manifest_invalid(
+                    &pack,
+                    format!("manifest.cbor fails schema validation: {errors}"),
+                ));
+            }
+            for issue in &issues {
+                operator_log::warn(
+                    module_path!(),
+                    format!("{}: manifest.cbor {issue}", pack.display()),
+                );
             }
         }
     }
@@ -105,25 +134,116 @@ pub fn ensure_cbor_packs(root: &Path) -> anyhow::Result<()> {
 }
 
 pub fn manifest_cbor_issue_detail(path: &Path) -> anyhow::Result<Option<String>> {
-    let file = std::fs::File::open(path)?;
-    let mut archive = zip::ZipArchive::new(file)?;
-    let mut manifest = match archive.by_name("manifest.cbor") {
-        Ok(file) => file,
-        Err(ZipError::FileNotFound) => {
-            return Ok(Some("manifest.cbor missing from archive".to_string()));
+    let archive = PackArchive::open(path)?;
+    if archive.manifest_bytes()?.is_none() {
+        return Ok(Some("manifest.cbor missing from archive".to_string()));
+    }
+    let value = match archive.manifest_value() {
+        Ok(value) => value.expect("manifest.cbor is present"),
+        Err(err) => return Ok(Some(err.to_string())),
+    };
+    Ok(manifest_schema::describe_errors(
+        &manifest_schema::validate_manifest(&value),
+    ))
+}
+
+/// What `demo pack inspect` shows about one pack.
+#[derive(Clone, Debug, Serialize)]
+pub struct PackInspection {
+    pub path: PathBuf,
+    /// `manifest.cbor` or `pack.manifest.json`.
+    pub manifest_file: &'static str,
+    /// Archive members, in archive order; empty for a pack directory.
+    pub members: Vec<String>,
+    /// `None` for a pack directory.
+    pub signature: Option<SignatureStatus>,
+    pub issues: Vec<manifest_schema::SchemaIssue>,
+    /// Pack id and flow lists as discovery resolves them; `None` when they do not
+    /// decode.
+    pub resolved: Option<JsonValue>,
+    /// The manifest as stored, with symbol indexes left in place.
+    pub manifest: JsonValue,
+}
+
+/// Decodes and validates the manifest of a `.gtpack` or pack directory.
+pub fn inspect_pack(path: &Path) -> anyhow::Result<PackInspection> {
+    let (members, signature, cbor, json) = if path.is_dir() {
+        let cbor = path.join("manifest.cbor");
+        (
+            Vec::new(),
+            None,
+            cbor.exists().then(|| fs::read(cbor)).transpose()?,
+            None,
+        )
+    } else {
+        let archive = PackArchive::open(path)?;
+        let cbor = archive.manifest_bytes()?.map(|bytes| bytes.to_vec());
+        let json = match cbor {
+            Some(_) => None,
+            None => archive.read_member("pack.manifest.json")?,
+        };
+        (
+            archive.members().to_vec(),
+            Some(archive.signature()?),
+            cbor,
+            json,
+        )
+    };
+    let (manifest_file, manifest, issues, resolved) = match (cbor, json) {
+        (Some(bytes), _) => {
+            let value = serde_cbor::from_slice::<CborValue>(&bytes).map_err(|err| {
+                manifest_invalid(path, format!("failed to decode manifest.cbor: {err}"))
+            })?;
+            let issues = manifest_schema::validate_manifest(&value);
+            let resolved = decode_manifest_lenient(&value)
+                .ok()
+                .map(|manifest| build_pack_meta(&manifest, path));
+            (
+                "manifest.cbor",
+                serde_json::to_value(&value)?,
+                issues,
+                resolved,
+            )
         }
+        (None, Some(bytes)) => {
+            let manifest: JsonValue = serde_json::from_slice(&bytes).map_err(|err| {
+                manifest_invalid(path, format!("failed to decode pack.manifest.json: {err}"))
+            })?;
+            let resolved = serde_json::from_value::<PackManifest>(manifest.clone())
+                .ok()
+                .map(|decoded| build_pack_meta(&decoded, path));
+            ("pack.manifest.json", manifest, Vec::new(), resolved)
+        }
+        (None, None) => {
+            return Err(manifest_invalid(
+                path,
+                "no manifest (expected manifest.cbor or pack.manifest.json)",
+            ));
+        }
+    };
+    Ok(PackInspection {
+        path: path.to_path_buf(),
+        manifest_file,
+        members,
+        signature,
+        issues,
+        resolved: resolved.map(serde_json::to_value).transpose()?,
+        manifest,
+    })
+}
+
+fn read_member<R: std::io::Read + std::io::Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    name: &str,
+) -> anyhow::Result<Option<Vec<u8>>> {
+    let mut file = match archive.by_name(name) {
+        Ok(file) => file,
+        Err(ZipError::FileNotFound) => return Ok(None),
         Err(err) => return Err(err.into()),
     };
     let mut bytes = Vec::new();
-    std::io::Read::read_to_end(&mut manifest, &mut bytes)?;
-    let value = match serde_cbor::from_slice::<CborValue>(&bytes) {
-        Ok(value) => value,
-        Err(err) => return Ok(Some(err.to_string())),
-    };
-    if let Some(path) = find_manifest_string_type_mismatch(&value) {
-        return Ok(Some(format!("invalid type at {path} (expected string)")));
-    }
-    Ok(None)
+    std::io::Read::read_to_end(&mut file, &mut bytes)?;
+    Ok(Some(bytes))
 }
 
 fn collect_gtpacks(root: &Path) -> anyhow::Result<Vec<PathBuf>> {
@@ -183,12 +303,14 @@ where
     let manifest = read_manifest(&path)?;
     let meta = manifest
         .meta
-        .ok_or_else(|| anyhow::anyhow!("pack manifest missing meta in {}", path.display()))?;
+        .ok_or_else(|| manifest_invalid(path, "missing meta"))?;
     packs.push(ProviderPack {
         pack_id: meta.pack_id,
         file_name,
         path,
         entry_flows: meta.entry_flows,
+        depends_on: meta.depends_on,
+        verify_flows: meta.verify_flows,
     });
     Ok(())
 }
@@ -238,13 +360,8 @@ pub fn plan_runs(
     allow_missing_setup: bool,
 ) -> anyhow::Result<Vec<PlannedRun>> {
     let cfg = config(domain);
-    let flows: Vec<&str> = match action {
-        DomainAction::Setup => vec![cfg.setup_flow],
-        DomainAction::Diagnostics => vec![cfg.diagnostics_flow],
-        DomainAction::Verify => cfg.verify_flows.to_vec(),
-    };
 
-    let mut plan = Vec::new();
+    let mut selected = Vec::new();
     for pack in packs {
         if let Some(filter) = provider_filter {
             let file_stem = pack
@@ -261,7 +378,29 @@ pub fn plan_runs(
                 continue;
             }
         }
+        selected.push(pack);
+    }
+    let selected = order_by_dependencies(
+        selected,
+        |pack| pack.pack_id.as_str(),
+        |pack| pack.depends_on.as_slice(),
+    )?;
 
+    let mut plan = Vec::new();
+    for pack in selected {
+        let flows: Vec<&str> = match action {
+            DomainAction::Setup => vec![cfg.setup_flow],
+            DomainAction::Diagnostics => vec![cfg.diagnostics_flow],
+            DomainAction::Verify => {
+                let mut flows = cfg.verify_flows.to_vec();
+                for flow in &pack.verify_flows {
+                    if !flows.contains(&flow.as_str()) {
+                        flows.push(flow);
+                    }
+                }
+                flows
+            }
+        };
         for flow in &flows {
             let has_flow = pack.entry_flows.iter().any(|entry| entry == flow);
             if !has_flow {
@@ -287,6 +426,140 @@ pub fn plan_runs(
     Ok(plan)
 }
 
+/// Orders `items` so every item comes after the items it depends on, keeping the
+/// given order where dependencies allow. Dependencies on ids outside `items` (another
+/// domain, or a pack filtered out) are ignored; a cycle is an error naming it.
+pub fn order_by_dependencies<T>(
+    items: Vec<T>,
+    id: impl Fn(&T) -> &str,
+    depends_on: impl Fn(&T) -> &[String],
+) -> anyhow::Result<Vec<T>> {
+    let mut pending: Vec<Option<T>> = items.into_iter().map(Some).collect();
+    let mut ordered = Vec::with_capacity(pending.len());
+    let is_pending = |pending: &[Option<T>], wanted: &str| {
+        pending.iter().flatten().any(|item| id(item) == wanted)
+    };
+    while pending.iter().any(Option::is_some) {
+        let ready = pending.iter().position(|slot| {
+            slot.as_ref().is_some_and(|item| {
+                depends_on(item)
+                    .iter()
+                    .all(|dep| dep == id(item) || !is_pending(&pending, dep))
+            })
+        });
+        match ready {
+            Some(index) => ordered.push(pending[index].take().expect("ready item is pending")),
+            None => {
+                return Err(anyhow::anyhow!(
+                    "provider setup dependency cycle: {}",
+                    describe_cycle(&pending, &id, &depends_on)
+                ));
+            }
+        }
+    }
+    Ok(ordered)
+}
+
+/// Adds `depends_on` entries from bundle config (keyed by pack id or file stem) to
+/// `packs`; names that are a pack's file stem are rewritten to its pack id.
+pub fn add_dependencies(packs: &mut [ProviderPack], extra: &BTreeMap<String, Vec<String>>) {
+    if extra.is_empty() {
+        return;
+    }
+    let pack_ids: BTreeMap<String, String> = packs
+        .iter()
+        .map(|pack| (pack_file_stem(pack).to_string(), pack.pack_id.clone()))
+        .collect();
+    for pack in packs.iter_mut() {
+        let pack_id = pack.pack_id.clone();
+        let stem = pack_file_stem(pack).to_string();
+        let configured = extra
+            .get(&pack_id)
+            .into_iter()
+            .chain(extra.get(&stem).filter(|_| stem != pack_id))
+            .flatten();
+        for dep in configured {
+            let dep = pack_ids.get(dep).unwrap_or(dep);
+            if !pack.depends_on.contains(dep) {
+                pack.depends_on.push(dep.clone());
+            }
+        }
+    }
+}
+
+fn pack_file_stem(pack: &ProviderPack) -> &str {
+    pack.file_name
+        .strip_suffix(".gtpack")
+        .unwrap_or(&pack.file_name)
+}
+
+/// Orders `domains` so a domain whose packs depend on packs of another domain (say a
+/// messaging provider on a secrets provider) is set up after it.
+pub fn order_domains_for_setup(
+    root: &Path,
+    domains: Vec<Domain>,
+    extra: &BTreeMap<String, Vec<String>>,
+) -> anyhow::Result<Vec<Domain>> {
+    let mut owners: BTreeMap<String, Domain> = BTreeMap::new();
+    let mut packs_by_domain = Vec::new();
+    for domain in &domains {
+        let mut packs = discover_provider_packs(root, *domain)?;
+        add_dependencies(&mut packs, extra);
+        for pack in &packs {
+            owners.entry(pack.pack_id.clone()).or_insert(*domain);
+        }
+        packs_by_domain.push((*domain, packs));
+    }
+    let staged = packs_by_domain
+        .into_iter()
+        .map(|(domain, packs)| {
+            let needs = packs
+                .iter()
+                .flat_map(|pack| pack.depends_on.iter())
+                .filter_map(|dep| owners.get(dep))
+                .filter(|owner| **owner != domain)
+                .map(|owner| domain_name(*owner).to_string())
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>();
+            (domain, needs)
+        })
+        .collect::<Vec<_>>();
+    Ok(order_by_dependencies(
+        staged,
+        |(domain, _)| domain_name(*domain),
+        |(_, needs)| needs.as_slice(),
+    )?
+    .into_iter()
+    .map(|(domain, _)| domain)
+    .collect())
+}
+
+/// Follows unsatisfied dependencies from the first blocked item until one repeats.
+fn describe_cycle<T>(
+    pending: &[Option<T>],
+    id: &impl Fn(&T) -> &str,
+    depends_on: &impl Fn(&T) -> &[String],
+) -> String {
+    let find = |wanted: &str| pending.iter().flatten().find(|item| id(item) == wanted);
+    let mut path: Vec<&str> = Vec::new();
+    let mut current = pending.iter().flatten().next();
+    while let Some(item) = current {
+        let name = id(item);
+        if let Some(start) = path.iter().position(|seen| *seen == name) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(name);
+            return cycle.join(" -> ");
+        }
+        path.push(name);
+        current = depends_on(item)
+            .iter()
+            .filter(|dep| dep.as_str() != name)
+            .find_map(|dep| find(dep));
+    }
+    path.join(" -> ")
+}
+
 #[derive(Debug, Deserialize)]
 pub(crate) struct PackManifestForDiscovery {
     #[serde(default)]
@@ -305,11 +578,18 @@ struct PackManifest {
     flows: Vec<PackFlow>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub(crate) struct PackMeta {
     pub pack_id: String,
     #[serde(default)]
     pub entry_flows: Vec<String>,
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    #[serde(default)]
+    pub verify_flows: Vec<String>,
+    /// Runner versions the pack works with, e.g. `>=1.4, <2`.
+    #[serde(default)]
+    pub runner_version: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -317,14 +597,82 @@ struct PackFlow {
     id: String,
     #[serde(default)]
     entrypoints: Vec<String>,
+    /// Packs (`<pack_id>`) or flows of other packs (`<pack_id>/<flow_id>`) it calls.
+    #[serde(default)]
+    calls: Vec<String>,
+    /// JSON Schema the flow's input must match.
+    #[serde(default)]
+    input_schema: Option<JsonValue>,
+}
+
+/// A flow and what it calls, from `flows[].calls`.
+#[derive(Clone, Debug, Serialize)]
+pub struct FlowCalls {
+    pub flow_id: String,
+    pub calls: Vec<String>,
+}
+
+/// The flows of a pack that call into other packs.
+pub fn read_flow_calls(path: &Path) -> anyhow::Result<Vec<FlowCalls>> {
+    let archive = PackArchive::open(path)?;
+    let Some(value) = archive.manifest_value()? else {
+        return Ok(Vec::new());
+    };
+    let manifest = parse_manifest_cbor_value(&value)
+        .map_err(|err| manifest_invalid(path, format!("failed to decode manifest.cbor: {err}")))?;
+    Ok(manifest
+        .flows
+        .into_iter()
+        .filter(|flow| !flow.calls.is_empty())
+        .map(|flow| FlowCalls {
+            flow_id: flow.id,
+            calls: flow.calls,
+        })
+        .collect())
+}
+
+/// The input schema `flows[].input_schema` declares for `flow_id` (a flow id, or
+/// an entrypoint when no flow has that id), if the flow declares one.
+pub fn read_flow_input_schema(path: &Path, flow_id: &str) -> anyhow::Result<Option<JsonValue>> {
+    let manifest = if path.is_dir() {
+        read_pack_manifest_from_dir(path)?
+    } else {
+        let archive = PackArchive::open(path)?;
+        match archive.manifest_value()? {
+            Some(value) => parse_manifest_cbor_value(&value).map_err(|err| {
+                manifest_invalid(path, format!("failed to decode manifest.cbor: {err}"))
+            })?,
+            None => match read_manifest_json(&archive, "pack.manifest.json")? {
+                Some(manifest) => manifest,
+                None => return Ok(None),
+            },
+        }
+    };
+    let flow = manifest
+        .flows
+        .iter()
+        .position(|flow| flow.id == flow_id)
+        .or_else(|| {
+            manifest
+                .flows
+                .iter()
+                .position(|flow| flow.entrypoints.iter().any(|entry| entry == flow_id))
+        });
+    Ok(flow.and_then(|idx| manifest.flows.into_iter().nth(idx)?.input_schema))
+}
+
+/// The pack's `meta.runner_version` requirement, if it declares one.
+pub fn read_runner_requirement(path: &Path) -> anyhow::Result<Option<String>> {
+    Ok(PackArchive::open(path)?
+        .meta()?
+        .and_then(|meta| meta.runner_version))
 }
 
 fn read_pack_manifest(path: &Path) -> anyhow::Result<PackManifest> {
-    let file = std::fs::File::open(path)?;
-    let mut archive = zip::ZipArchive::new(file)?;
-    let manifest = read_pack_manifest_data(&mut archive, path)
+    let archive = PackArchive::open(path)?;
+    archive.enforce()?;
+    let meta = read_pack_manifest_data(&archive)
         .with_context(|| format!("failed to read pack manifest from {}", path.display()))?;
-    let meta = build_pack_meta(&manifest, path);
     Ok(PackManifest {
         meta: Some(meta),
         pack_id: None,
@@ -340,22 +688,16 @@ pub(crate) fn read_pack_meta(path: &Path) -> anyhow::Result<PackMeta> {
     }?;
     manifest
         .meta
-        .ok_or_else(|| anyhow::anyhow!("pack manifest missing meta in {}", path.display()))
+        .ok_or_else(|| manifest_invalid(path, "missing meta"))
 }
 
 fn read_pack_manifest_cbor_only(path: &Path) -> anyhow::Result<PackManifest> {
-    let file = std::fs::File::open(path)?;
-    let mut archive = zip::ZipArchive::new(file)?;
-    let manifest = match read_manifest_cbor(&mut archive, path).map_err(|err| {
-        anyhow::anyhow!(
-            "failed to decode manifest.cbor in {}: {err}",
-            path.display()
-        )
-    })? {
-        Some(manifest) => manifest,
-        None => return Err(missing_cbor_error(path)),
-    };
-    let meta = build_pack_meta(&manifest, path);
+    let archive = PackArchive::open(path)?;
+    archive.enforce()?;
+    let meta = archive
+        .meta()
+        .map_err(|err| manifest_invalid(path, format!("failed to decode manifest.cbor: {err}")))?
+        .ok_or_else(|| missing_cbor_error(path))?;
     Ok(PackManifest {
         meta: Some(meta),
         pack_id: None,
@@ -363,73 +705,55 @@ fn read_pack_manifest_cbor_only(path: &Path) -> anyhow::Result<PackManifest> {
     })
 }
 
-fn read_pack_manifest_data(
-    archive: &mut zip::ZipArchive<std::fs::File>,
-    path: &Path,
-) -> anyhow::Result<PackManifest> {
-    match read_manifest_cbor(archive, path) {
-        Ok(Some(manifest)) => return Ok(manifest),
+fn read_pack_manifest_data(archive: &PackArchive) -> anyhow::Result<PackMeta> {
+    let path = archive.path();
+    match archive.meta() {
+        Ok(Some(meta)) => return Ok(meta),
         Ok(None) => {}
         Err(err) => {
-            return Err(anyhow::anyhow!(
-                "failed to decode manifest.cbor in {}: {err}",
-                path.display()
+            return Err(manifest_invalid(
+                path,
+                format!("failed to decode manifest.cbor: {err}"),
             ));
         }
     }
     match read_manifest_json(archive, "pack.manifest.json") {
-        Ok(Some(manifest)) => return Ok(manifest),
+        Ok(Some(manifest)) => return Ok(build_pack_meta(&manifest, path)),
         Ok(None) => {}
         Err(err) => {
-            return Err(anyhow::anyhow!(
-                "failed to decode pack.manifest.json in {}: {err}",
-                path.display()
+            return Err(manifest_invalid(
+                path,
+                format!("failed to decode pack.manifest.json: {err}"),
             ));
         }
     }
-    Err(anyhow::anyhow!(
-        "pack manifest not found in archive {} (expected manifest.cbor or pack.manifest.json)",
-        path.display()
+    Err(manifest_invalid(
+        path,
+        "no manifest in archive (expected manifest.cbor or pack.manifest.json)",
     ))
 }
 
-fn read_manifest_cbor(
-    archive: &mut zip::ZipArchive<std::fs::File>,
-    _path: &Path,
-) -> anyhow::Result<Option<PackManifest>> {
-    let mut file = match archive.by_name("manifest.cbor") {
-        Ok(file) => file,
-        Err(ZipError::FileNotFound) => return Ok(None),
-        Err(err) => return Err(err.into()),
-    };
-    let mut bytes = Vec::new();
-    std::io::Read::read_to_end(&mut file, &mut bytes)?;
-    let manifest = parse_manifest_cbor_bytes(&bytes)?;
-    Ok(Some(manifest))
-}
-
 fn read_pack_manifest_from_dir(path: &Path) -> anyhow::Result<PackManifest> {
     let manifest_path = path.join("manifest.cbor");
     if !manifest_path.exists() {
-        return Err(anyhow::anyhow!(
-            "pack manifest missing manifest.cbor in {}",
-            path.display()
-        ));
+        return Err(manifest_invalid(path, "missing manifest.cbor"));
     }
     let bytes = fs::read(&manifest_path)?;
     parse_manifest_cbor_bytes(&bytes)
 }
 
 fn parse_manifest_cbor_bytes(bytes: &[u8]) -> anyhow::Result<PackManifest> {
-    let value: CborValue = serde_cbor::from_slice(bytes)?;
-    match decode_manifest_lenient(&value) {
+    parse_manifest_cbor_value(&serde_cbor::from_slice(bytes)?)
+}
+
+fn parse_manifest_cbor_value(value: &CborValue) -> anyhow::Result<PackManifest> {
+    match decode_manifest_lenient(value) {
         Ok(manifest) => Ok(manifest),
         Err(decode_err) => {
-            if let Some(err_path) = find_manifest_string_type_mismatch(&value) {
-                return Err(anyhow::anyhow!(
-                    "invalid type at {} (expected string)",
-                    err_path
-                ));
+            if let Some(errors) =
+                manifest_schema::describe_errors(&manifest_schema::validate_manifest(value))
+            {
+                return Err(anyhow::anyhow!("{errors}"));
             }
             Err(anyhow::anyhow!(
                 "manifest.cbor uses symbol table encoding but could not be decoded: {decode_err}"
@@ -474,84 +798,28 @@ fn build_pack_meta(manifest: &PackManifest, path: &Path) -> PackMeta {
     PackMeta {
         pack_id,
         entry_flows,
+        depends_on: manifest
+            .meta
+            .as_ref()
+            .map(|meta| meta.depends_on.clone())
+            .unwrap_or_default(),
+        verify_flows: manifest
+            .meta
+            .as_ref()
+            .map(|meta| meta.verify_flows.clone())
+            .unwrap_or_default(),
+        runner_version: manifest
+            .meta
+            .as_ref()
+            .and_then(|meta| meta.runner_version.clone()),
     }
 }
 
-fn read_manifest_json(
-    archive: &mut zip::ZipArchive<std::fs::File>,
-    name: &str,
-) -> anyhow::Result<Option<PackManifest>> {
-    let mut file = match archive.by_name(name) {
-        Ok(file) => file,
-        Err(ZipError::FileNotFound) => return Ok(None),
-        Err(err) => return Err(err.into()),
-    };
-    let mut contents = String::new();
-    std::io::Read::read_to_string(&mut file, &mut contents)?;
-    let manifest: PackManifest = serde_json::from_str(&contents)?;
-    Ok(Some(manifest))
-}
-
-fn find_manifest_string_type_mismatch(value: &CborValue) -> Option<String> {
-    let CborValue::Map(map) = value else {
-        return None;
-    };
-    let symbols = symbols_map(map);
-
-    if let Some(pack_id) = map_get(map, "pack_id")
-        && !value_is_string_or_symbol(pack_id, symbols, "pack_ids")
-    {
-        return Some("pack_id".to_string());
-    }
-
-    if let Some(meta) = map_get(map, "meta") {
-        let CborValue::Map(meta_map) = meta else {
-            return Some("meta".to_string());
-        };
-        if let Some(pack_id) = map_get(meta_map, "pack_id")
-            && !value_is_string_or_symbol(pack_id, symbols, "pack_ids")
-        {
-            return Some("meta.pack_id".to_string());
-        }
-        if let Some(entry_flows) = map_get(meta_map, "entry_flows") {
-            let CborValue::Array(values) = entry_flows else {
-                return Some("meta.entry_flows".to_string());
-            };
-            for (idx, value) in values.iter().enumerate() {
-                if !value_is_string_or_symbol(value, symbols, "flow_ids") {
-                    return Some(format!("meta.entry_flows[{idx}]"));
-                }
-            }
-        }
-    }
-
-    if let Some(flows) = map_get(map, "flows") {
-        let CborValue::Array(values) = flows else {
-            return Some("flows".to_string());
-        };
-        for (idx, value) in values.iter().enumerate() {
-            let CborValue::Map(flow) = value else {
-                return Some(format!("flows[{idx}]"));
-            };
-            if let Some(id) = map_get(flow, "id")
-                && !value_is_string_or_symbol(id, symbols, "flow_ids")
-            {
-                return Some(format!("flows[{idx}].id"));
-            }
-            if let Some(entrypoints) = map_get(flow, "entrypoints") {
-                let CborValue::Array(values) = entrypoints else {
-                    return Some(format!("flows[{idx}].entrypoints"));
-                };
-                for (jdx, value) in values.iter().enumerate() {
-                    if !value_is_string_or_symbol(value, symbols, "entrypoints") {
-                        return Some(format!("flows[{idx}].entrypoints[{jdx}]"));
-                    }
-                }
-            }
-        }
-    }
-
-    None
+fn read_manifest_json(archive: &PackArchive, name: &str) -> anyhow::Result<Option<PackManifest>> {
+    archive
+        .read_member(name)?
+        .map(|bytes| serde_json::from_slice(&bytes).map_err(anyhow::Error::from))
+        .transpose()
 }
 
 fn map_get<'a>(
@@ -574,54 +842,43 @@ fn symbols_map(
     }
 }
 
-fn value_is_string_or_symbol(
-    value: &CborValue,
-    symbols: Option<&std::collections::BTreeMap<CborValue, CborValue>>,
-    symbol_key: &str,
-) -> bool {
-    if matches!(value, CborValue::Text(_)) {
-        return true;
-    }
-    let CborValue::Integer(idx) = value else {
-        return false;
-    };
-    let symbols = match symbols {
-        Some(symbols) => symbols,
-        None => return true,
-    };
-    let Some(CborValue::Array(values)) = map_get(symbols, symbol_key)
-        .or_else(|| map_get(symbols, symbol_key.strip_suffix('s').unwrap_or(symbol_key)))
-    else {
-        return true;
-    };
-    let idx = match usize::try_from(*idx) {
-        Ok(idx) => idx,
-        Err(_) => return true,
-    };
-    matches!(values.get(idx), Some(CborValue::Text(_)))
-}
-
 fn decode_manifest_lenient(value: &CborValue) -> anyhow::Result<PackManifest> {
     let CborValue::Map(map) = value else {
         return Err(anyhow::anyhow!("manifest is not a map"));
     };
     let symbols = symbols_map(map);
 
-    let (meta_pack_id, meta_entry_flows) = if let Some(meta) = map_get(map, "meta") {
-        let CborValue::Map(meta_map) = meta else {
-            return Err(anyhow::anyhow!("meta is not a map"));
+    let (meta_pack_id, meta_entry_flows, meta_depends_on, meta_verify_flows, meta_runner_version) =
+        if let Some(meta) = map_get(map, "meta") {
+            let CborValue::Map(meta_map) = meta else {
+                return Err(anyhow::anyhow!("meta is not a map"));
+            };
+            let pack_id = resolve_string_symbol(map_get(meta_map, "pack_id"), symbols, "pack_ids")?;
+            let entry_flows = resolve_string_array(
+                map_get(meta_map, "entry_flows"),
+                symbols,
+                "flow_ids",
+                Some("entrypoints"),
+            )?;
+            let depends_on =
+                resolve_string_array(map_get(meta_map, "depends_on"), symbols, "pack_ids", None)?;
+            let verify_flows =
+                resolve_string_array(map_get(meta_map, "verify_flows"), symbols, "flow_ids", None)?;
+            let runner_version = match map_get(meta_map, "runner_version") {
+                Some(CborValue::Text(text)) => Some(text.clone()),
+                Some(_) => return Err(anyhow::anyhow!("meta.runner_version is not a string")),
+                None => None,
+            };
+            (
+                pack_id,
+                entry_flows,
+                depends_on,
+                verify_flows,
+                runner_version,
+            )
+        } else {
+            (None, Vec::new(), Vec::new(), Vec::new(), None)
         };
-        let pack_id = resolve_string_symbol(map_get(meta_map, "pack_id"), symbols, "pack_ids")?;
-        let entry_flows = resolve_string_array(
-            map_get(meta_map, "entry_flows"),
-            symbols,
-            "flow_ids",
-            Some("entrypoints"),
-        )?;
-        (pack_id, entry_flows)
-    } else {
-        (None, Vec::new())
-    };
 
     let pack_id = resolve_string_symbol(map_get(map, "pack_id"), symbols, "pack_ids")?
         .or(meta_pack_id)
@@ -640,7 +897,21 @@ fn decode_manifest_lenient(value: &CborValue) -> anyhow::Result<PackManifest> {
                 .ok_or_else(|| anyhow::anyhow!("flows[{idx}].id missing"))?;
             let entrypoints =
                 resolve_string_array(map_get(flow, "entrypoints"), symbols, "entrypoints", None)?;
-            flows.push(PackFlow { id, entrypoints });
+            let calls = resolve_string_array(map_get(flow, "calls"), symbols, "pack_ids", None)?;
+            let input_schema = match map_get(flow, "input_schema") {
+                Some(schema @ CborValue::Map(_)) => Some(
+                    serde_json::to_value(schema)
+                        .map_err(|err| anyhow::anyhow!("flows[{idx}].input_schema: {err}"))?,
+                ),
+                Some(_) => return Err(anyhow::anyhow!("flows[{idx}].input_schema is not a map")),
+                None => None,
+            };
+            flows.push(PackFlow {
+                id,
+                entrypoints,
+                calls,
+                input_schema,
+            });
         }
     }
 
@@ -648,6 +919,9 @@ fn decode_manifest_lenient(value: &CborValue) -> anyhow::Result<PackManifest> {
         meta: Some(PackMeta {
             pack_id,
             entry_flows: meta_entry_flows,
+            depends_on: meta_depends_on,
+            verify_flows: meta_verify_flows,
+            runner_version: meta_runner_version,
         }),
         pack_id: None,
         flows,
@@ -729,9 +1003,9 @@ fn resolve_string_array(
 }
 
 fn missing_cbor_error(path: &Path) -> anyhow::Error {
-    anyhow::anyhow!(
-        "ERROR: demo packs must be CBOR-only (.gtpack must contain manifest.cbor). Rebuild the pack with greentic-pack build (do not use --dev). Missing in {}",
-        path.display()
+    manifest_invalid(
+        path,
+        "demo packs must be CBOR-only (.gtpack must contain manifest.cbor). Rebuild the pack with greentic-pack build (do not use --dev)",
     )
 }
 
@@ -740,5 +1014,11 @@ pub(crate) fn domain_name(domain: Domain) -> &'static str {
         Domain::Messaging => "messaging",
         Domain::Events => "events",
         Domain::Secrets => "secrets",
+        Domain::Custom(custom) => custom.name(),
     }
 }
+
+/// A built-in or registered custom domain by name, case-insensitively.
+pub fn parse_domain(name: &str) -> Option<Domain> {
+    registry::lookup(name)
+}
@@ -0,0 +1,216 @@
+//! Domains declared by the bundle next to the built-in messaging/events/secrets.
+//!
+//! `domains:` in greentic.demo.yaml maps a name to its providers directory and flow
+//! names. Discovery registers them, after which [`Domain::Custom`] values resolve
+//! through [`super::config`] like the built-in ones. Names and conventions are
+//! interned for the life of the process so [`Domain`] stays `Copy`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::{OnceLock, RwLock};
+
+use anyhow::anyhow;
+
+use super::{Domain, DomainConfig};
+use crate::config::{self, DemoDomainConfig};
+use crate::operator_log;
+
+/// Names no custom domain may take.
+const RESERVED: &[&str] = &["messaging", "events", "secrets", "all", "auto"];
+
+/// Interned name of a registered custom domain.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord, Hash)]
+pub struct CustomDomain(&'static str);
+
+impl CustomDomain {
+    pub fn name(self) -> &'static str {
+        self.0
+    }
+}
+
+impl fmt::Display for CustomDomain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+fn registry() -> &'static RwLock<BTreeMap<&'static str, DomainConfig>> {
+    static REGISTRY: OnceLock<RwLock<BTreeMap<&'static str, DomainConfig>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(BTreeMap::new()))
+}
+
+fn leak(value: String) -> &'static str {
+    Box::leak(value.into_boxed_str())
+}
+
+/// Registers (or updates) the custom domain `name`.
+pub fn register(name: &str, spec: &DemoDomainConfig) -> anyhow::Result<Domain> {
+    validate_name(name)?;
+    let providers_dir = spec
+        .providers_dir
+        .clone()
+        .unwrap_or_else(|| format!("providers/{name}"));
+    if Path::new(&providers_dir).is_absolute() || providers_dir.split('/').any(|part| part == "..")
+    {
+        return Err(anyhow!(
+            "domain {name}: providers_dir must stay inside the bundle, got {providers_dir}"
+        ));
+    }
+    let mut registry = registry().write().unwrap_or_else(|err| err.into_inner());
+    let key = match registry.get_key_value(name) {
+        Some((key, existing)) if matches_spec(existing, &providers_dir, spec) => {
+            return Ok(Domain::Custom(CustomDomain(*key)));
+        }
+        Some((key, _)) => *key,
+        None => leak(name.to_string()),
+    };
+    let verify_flows: Vec<&'static str> = spec.verify_flows.iter().cloned().map(leak).collect();
+    registry.insert(
+        key,
+        DomainConfig {
+            providers_dir: leak(providers_dir),
+            setup_flow: leak(
+                spec.setup_flow
+                    .clone()
+                    .unwrap_or_else(|| "setup_default".to_string()),
+            ),
+            diagnostics_flow: leak(
+                spec.diagnostics_flow
+                    .clone()
+                    .unwrap_or_else(|| "diagnostics".to_string()),
+            ),
+            verify_flows: Box::leak(verify_flows.into_boxed_slice()),
+        },
+    );
+    Ok(Domain::Custom(CustomDomain(key)))
+}
+
+fn matches_spec(existing: &DomainConfig, providers_dir: &str, spec: &DemoDomainConfig) -> bool {
+    existing.providers_dir == providers_dir
+        && existing.setup_flow == spec.setup_flow.as_deref().unwrap_or("setup_default")
+        && existing.diagnostics_flow == spec.diagnostics_flow.as_deref().unwrap_or("diagnostics")
+        && existing.verify_flows == spec.verify_flows.as_slice()
+}
+
+fn validate_name(name: &str) -> anyhow::Result<()> {
+    if RESERVED.contains(&name) {
+        return Err(anyhow!("domain name {name} is reserved"));
+    }
+    let valid = !name.is_empty()
+        && name.starts_with(|ch: char| ch.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '-' || ch == '_');
+    if !valid {
+        return Err(anyhow!(
+            "domain name {name} must start with a lowercase letter and use only a-z, 0-9, - and _"
+        ));
+    }
+    Ok(())
+}
+
+/// Registers every domain in the bundle's greentic.demo.yaml; returns them by name.
+/// A config that does not load registers nothing.
+pub fn register_bundle_domains(root: &Path) -> anyhow::Result<Vec<Domain>> {
+    let path = root.join("greentic.demo.yaml");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let demo_config = match config::load_demo_config(&path) {
+        Ok(demo_config) => demo_config,
+        Err(err) => {
+            operator_log::warn(
+                module_path!(),
+                format!("custom domains not loaded from {}: {err:#}", path.display()),
+            );
+            return Ok(Vec::new());
+        }
+    };
+    demo_config
+        .domains
+        .iter()
+        .map(|(name, spec)| register(name, spec))
+        .collect()
+}
+
+/// Conventions of a registered custom domain.
+pub(super) fn config(domain: CustomDomain) -> DomainConfig {
+    registry()
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .get(domain.name())
+        .cloned()
+        .expect("custom domains only exist once registered")
+}
+
+/// A built-in or registered domain by name.
+pub fn lookup(name: &str) -> Option<Domain> {
+    match name.to_ascii_lowercase().as_str() {
+        "messaging" => Some(Domain::Messaging),
+        "events" => Some(Domain::Events),
+        "secrets" => Some(Domain::Secrets),
+        other => registry()
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .get_key_value(other)
+            .map(|(key, _)| Domain::Custom(CustomDomain(*key))),
+    }
+}
+
+/// Every registered custom domain, by name.
+pub fn custom_domains() -> Vec<Domain> {
+    registry()
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .keys()
+        .map(|key| Domain::Custom(CustomDomain(*key)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registered_domains_resolve_by_name_with_default_conventions() {
+        let payments = register(
+            "payments-test",
+            &DemoDomainConfig {
+                verify_flows: vec!["verify_webhooks".to_string()],
+                ..DemoDomainConfig::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(lookup("payments-test"), Some(payments));
+        assert_eq!(lookup("Events"), Some(Domain::Events));
+        assert_eq!(lookup("crm-test"), None);
+        assert_eq!(crate::domains::domain_name(payments), "payments-test");
+        let cfg = crate::domains::config(payments);
+        assert_eq!(cfg.providers_dir, "providers/payments-test");
+        assert_eq!(cfg.setup_flow, "setup_default");
+        assert_eq!(cfg.verify_flows, ["verify_webhooks"]);
+
+        let moved = register(
+            "payments-test",
+            &DemoDomainConfig {
+                providers_dir: Some("providers/pay".to_string()),
+                ..DemoDomainConfig::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(moved, payments);
+        assert_eq!(
+            crate::domains::config(payments).providers_dir,
+            "providers/pay"
+        );
+
+        assert!(register("secrets", &DemoDomainConfig::default()).is_err());
+        assert!(register("Bad Name", &DemoDomainConfig::default()).is_err());
+        let escape = DemoDomainConfig {
+            providers_dir: Some("../elsewhere".to_string()),
+            ..DemoDomainConfig::default()
+        };
+        assert!(register("crm-test", &escape).is_err());
+    }
+}
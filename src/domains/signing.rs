@@ -0,0 +1,495 @@
+//! Detached signatures and encrypted members of `.gtpack` archives.
+//!
+//! A signed pack carries `signature.json`, or sits next to a `<pack>.sig` file with
+//! the same content: an Ed25519 signature by `key_id` over the pack digest. The
+//! digest is `sha256:<hex>` of one `<sha256 hex>  <name>` line per member, in name
+//! order, leaving out `signature.json` itself. Members ending in `.enc` hold
+//! `nonce || ciphertext` sealed with AES-256-GCM under the key `encryption.json`
+//! names, with the plain member name as associated data; they are signed as stored.
+//!
+//! Keys come from `pack_keys` in the bundle's greentic.demo.yaml. A signature that
+//! does not match the archive is always refused; with `require_signed_packs: true`
+//! so are unsigned packs and packs signed by a key that is not trusted.
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, anyhow};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::digest::{self, SHA256};
+use ring::signature::{ED25519, UnparsedPublicKey};
+use serde::{Deserialize, Serialize};
+use zip::ZipArchive;
+use zip::write::{FileOptions, ZipWriter};
+
+use crate::config::{self, DemoConfig};
+use crate::interpolate::{self, SecretScope};
+use crate::operator_error::manifest_invalid;
+use crate::operator_log;
+
+pub const SIGNATURE_MEMBER: &str = "signature.json";
+pub const ENCRYPTION_MEMBER: &str = "encryption.json";
+pub const ENCRYPTED_SUFFIX: &str = ".enc";
+
+/// Content of `signature.json` or `<pack>.sig`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PackSignature {
+    /// Only `ed25519`.
+    pub algorithm: String,
+    pub key_id: String,
+    /// [`pack_digest`] of the archive.
+    pub digest: String,
+    /// Base64 signature over the `digest` string.
+    pub signature: String,
+}
+
+/// Content of `encryption.json`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PackEncryption {
+    /// Only `aes-256-gcm`.
+    pub algorithm: String,
+    pub key_id: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SignatureStatus {
+    Unsigned,
+    Verified {
+        key_id: String,
+    },
+    /// Signed by a key that is not in `pack_keys.trusted`.
+    Untrusted {
+        key_id: String,
+    },
+    /// The signature does not match the archive.
+    Invalid {
+        reason: String,
+    },
+}
+
+impl SignatureStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SignatureStatus::Unsigned => "unsigned",
+            SignatureStatus::Verified { .. } => "verified",
+            SignatureStatus::Untrusted { .. } => "untrusted",
+            SignatureStatus::Invalid { .. } => "invalid",
+        }
+    }
+}
+
+/// The signing and decryption keys of one bundle.
+#[derive(Clone, Debug, Default)]
+pub struct PackTrust {
+    require_signed: bool,
+    trusted: BTreeMap<String, Vec<u8>>,
+    /// As written, so `${secret:...}` references are only read when a pack needs
+    /// the key.
+    decryption: BTreeMap<String, String>,
+    /// Where those references are read from: the bundle, in its own tenant/team.
+    bundle_root: Option<PathBuf>,
+    tenant: String,
+    team: String,
+}
+
+impl PackTrust {
+    /// Trusted keys that are not valid base64 are logged and left out; decryption keys
+    /// are decoded when a pack needs them.
+    pub fn from_config(config: &DemoConfig) -> Self {
+        let decode = |section: &str, keys: &BTreeMap<String, String>| {
+            keys.iter()
+                .filter_map(|(key_id, value)| match STANDARD.decode(value.trim()) {
+                    Ok(key) => Some((key_id.clone(), key)),
+                    Err(err) => {
+                        operator_log::warn(
+                            module_path!(),
+                            format!("pack_keys.{section}.{key_id} ignored: not base64 ({err})"),
+                        );
+                        None
+                    }
+                })
+                .collect()
+        };
+        Self {
+            require_signed: config.require_signed_packs,
+            trusted: decode("trusted", &config.pack_keys.trusted),
+            decryption: config.pack_keys.decryption.clone(),
+            bundle_root: None,
+            tenant: config.tenant.clone(),
+            team: config.team.clone(),
+        }
+    }
+
+    pub fn from_bundle(bundle_root: &Path) -> Self {
+        let config_path = bundle_root.join("greentic.demo.yaml");
+        if !config_path.exists() {
+            return Self::default();
+        }
+        config::load_demo_config(&config_path)
+            .map(|config| Self {
+                bundle_root: Some(bundle_root.to_path_buf()),
+                ..Self::from_config(&config)
+            })
+            .unwrap_or_default()
+    }
+
+    /// Trust of the bundle holding `pack` (see [`bundle_config_for`]).
+    pub fn for_pack(pack: &Path) -> Self {
+        bundle_config_for(pack)
+            .and_then(|config_path| config_path.parent().map(Self::from_bundle))
+            .unwrap_or_default()
+    }
+
+    pub fn require_signed(&self) -> bool {
+        self.require_signed
+    }
+
+    /// Checks the signature of the archive at `path` without refusing anything.
+    pub fn check<R: Read + Seek>(
+        &self,
+        archive: &mut ZipArchive<R>,
+        path: &Path,
+    ) -> anyhow::Result<SignatureStatus> {
+        let bytes = match super::read_member(archive, SIGNATURE_MEMBER)? {
+            Some(bytes) => bytes,
+            None => match fs::read(sidecar_path(path)) {
+                Ok(bytes) => bytes,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                    return Ok(SignatureStatus::Unsigned);
+                }
+                Err(err) => return Err(err.into()),
+            },
+        };
+        let invalid = |reason: String| Ok(SignatureStatus::Invalid { reason });
+        let signature: PackSignature = match serde_json::from_slice(&bytes) {
+            Ok(signature) => signature,
+            Err(err) => return invalid(format!("malformed signature: {err}")),
+        };
+        if signature.algorithm != "ed25519" {
+            return invalid(format!("unsupported algorithm {}", signature.algorithm));
+        }
+        let digest = pack_digest(archive)?;
+        if digest != signature.digest {
+            return invalid(format!(
+                "archive digest {digest} does not match signed digest {}",
+                signature.digest
+            ));
+        }
+        let Some(public_key) = self.trusted.get(&signature.key_id) else {
+            return Ok(SignatureStatus::Untrusted {
+                key_id: signature.key_id,
+            });
+        };
+        let Ok(raw) = STANDARD.decode(signature.signature.trim()) else {
+            return invalid("signature is not base64".to_string());
+        };
+        match UnparsedPublicKey::new(&ED25519, public_key).verify(digest.as_bytes(), &raw) {
+            Ok(()) => Ok(SignatureStatus::Verified {
+                key_id: signature.key_id,
+            }),
+            Err(_) => invalid(format!(
+                "signature does not verify with key {}",
+                signature.key_id
+            )),
+        }
+    }
+
+    /// Passes `status` through, or fails with `manifest_invalid` for a pack the
+    /// bundle does not accept.
+    pub fn accept(&self, status: SignatureStatus, path: &Path) -> anyhow::Result<SignatureStatus> {
+        let refusal = match &status {
+            SignatureStatus::Invalid { reason } => {
+                Some(format!("pack signature invalid: {reason}"))
+            }
+            SignatureStatus::Unsigned if self.require_signed => {
+                Some("pack is unsigned and require_signed_packs is set".to_string())
+            }
+            SignatureStatus::Untrusted { key_id } if self.require_signed => Some(format!(
+                "pack is signed by untrusted key {key_id} and require_signed_packs is set"
+            )),
+            _ => None,
+        };
+        match refusal {
+            Some(detail) => Err(manifest_invalid(path, detail)),
+            None => Ok(status),
+        }
+    }
+
+    /// Reads member `name`, or decrypts `<name>.enc` when only that is present.
+    pub fn read_member<R: Read + Seek>(
+        &self,
+        archive: &mut ZipArchive<R>,
+        name: &str,
+    ) -> anyhow::Result<Option<Vec<u8>>> {
+        if let Some(bytes) = super::read_member(archive, name)? {
+            return Ok(Some(bytes));
+        }
+        let Some(sealed) = super::read_member(archive, &format!("{name}{ENCRYPTED_SUFFIX}"))?
+        else {
+            return Ok(None);
+        };
+        let key = self.decryption_key(archive)?;
+        open_member(&key, name, sealed).map(Some)
+    }
+
+    /// Writes the archive at `path` with its `.enc` members decrypted to `dir`,
+    /// once per pack digest, and returns the copy.
+    pub fn decrypted_copy<R: Read + Seek>(
+        &self,
+        archive: &mut ZipArchive<R>,
+        path: &Path,
+        dir: &Path,
+    ) -> anyhow::Result<PathBuf> {
+        let names: Vec<String> = archive.file_names().map(str::to_string).collect();
+        let digest = pack_digest(archive)?;
+        let stem = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("pack");
+        let short = digest.trim_start_matches("sha256:").get(..12).unwrap_or("");
+        let target = dir.join(format!("{stem}-{short}.gtpack"));
+        if target.exists() {
+            return Ok(target);
+        }
+        let key = self.decryption_key(archive)?;
+        fs::create_dir_all(dir)?;
+        let partial = target.with_extension("gtpack.partial");
+        let mut writer = ZipWriter::new(File::create(&partial)?);
+        for name in names {
+            if name == SIGNATURE_MEMBER || name == ENCRYPTION_MEMBER || name.ends_with('/') {
+                continue;
+            }
+            let bytes = super::read_member(archive, &name)?.unwrap_or_default();
+            let (name, bytes) = match name.strip_suffix(ENCRYPTED_SUFFIX) {
+                Some(plain) => (plain.to_string(), open_member(&key, plain, bytes)?),
+                None => (name, bytes),
+            };
+            writer.start_file(name, FileOptions::<()>::default())?;
+            writer.write_all(&bytes)?;
+        }
+        writer.finish()?;
+        fs::rename(&partial, &target)?;
+        Ok(target)
+    }
+
+    fn decryption_key<R: Read + Seek>(
+        &self,
+        archive: &mut ZipArchive<R>,
+    ) -> anyhow::Result<LessSafeKey> {
+        let bytes = super::read_member(archive, ENCRYPTION_MEMBER)?
+            .ok_or_else(|| anyhow!("encrypted members without {ENCRYPTION_MEMBER}"))?;
+        let encryption: PackEncryption = serde_json::from_slice(&bytes)
+            .with_context(|| format!("malformed {ENCRYPTION_MEMBER}"))?;
+        if encryption.algorithm != "aes-256-gcm" {
+            return Err(anyhow!(
+                "unsupported pack encryption {}",
+                encryption.algorithm
+            ));
+        }
+        let value = self.decryption.get(&encryption.key_id).ok_or_else(|| {
+            anyhow!(
+                "pack is encrypted with key {} which is not in pack_keys.decryption",
+                encryption.key_id
+            )
+        })?;
+        let scope = self.bundle_root.as_deref().map(|bundle_root| SecretScope {
+            bundle_root,
+            tenant: &self.tenant,
+            team: Some(&self.team),
+        });
+        let value = interpolate::interpolate_secrets(value, scope)
+            .with_context(|| format!("pack_keys.decryption.{}", encryption.key_id))?;
+        let key = STANDARD.decode(value.trim()).map_err(|err| {
+            anyhow!(
+                "pack_keys.decryption.{} is not base64 ({err})",
+                encryption.key_id
+            )
+        })?;
+        let key = UnboundKey::new(&AES_256_GCM, &key).map_err(|_| {
+            anyhow!(
+                "pack_keys.decryption.{} is not a 32-byte key",
+                encryption.key_id
+            )
+        })?;
+        Ok(LessSafeKey::new(key))
+    }
+}
+
+/// greentic.demo.yaml of the nearest directory above `pack` that has one.
+pub fn bundle_config_for(pack: &Path) -> Option<PathBuf> {
+    pack.ancestors()
+        .skip(1)
+        .map(|dir| dir.join("greentic.demo.yaml"))
+        .find(|config_path| config_path.exists())
+}
+
+/// `<pack>.sig` next to `pack`.
+pub fn sidecar_path(pack: &Path) -> PathBuf {
+    let mut name = OsString::from(pack.as_os_str());
+    name.push(".sig");
+    PathBuf::from(name)
+}
+
+/// The digest a pack signature covers.
+pub fn pack_digest<R: Read + Seek>(archive: &mut ZipArchive<R>) -> anyhow::Result<String> {
+    let mut names: Vec<String> = archive
+        .file_names()
+        .filter(|name| *name != SIGNATURE_MEMBER && !name.ends_with('/'))
+        .map(str::to_string)
+        .collect();
+    names.sort();
+    let mut listing = digest::Context::new(&SHA256);
+    for name in names {
+        let bytes = super::read_member(archive, &name)?.unwrap_or_default();
+        let member = digest::digest(&SHA256, &bytes);
+        listing.update(format!("{}  {name}\n", hex(member.as_ref())).as_bytes());
+    }
+    Ok(format!("sha256:{}", hex(listing.finish().as_ref())))
+}
+
+fn open_member(key: &LessSafeKey, name: &str, mut sealed: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+    if sealed.len() < NONCE_LEN {
+        return Err(anyhow!("{name}{ENCRYPTED_SUFFIX} is truncated"));
+    }
+    let mut in_out = sealed.split_off(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(&sealed)
+        .map_err(|_| anyhow!("{name}{ENCRYPTED_SUFFIX} has an invalid nonce"))?;
+    let plaintext = key
+        .open_in_place(nonce, Aad::from(name.as_bytes()), &mut in_out)
+        .map_err(|_| {
+            anyhow!("failed to decrypt {name}{ENCRYPTED_SUFFIX} (wrong key or tampered)")
+        })?;
+    Ok(plaintext.to_vec())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::{SecureRandom, SystemRandom};
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    fn write_pack(path: &Path, members: &[(&str, &[u8])]) {
+        let mut writer = ZipWriter::new(File::create(path).unwrap());
+        for (name, bytes) in members {
+            writer
+                .start_file(*name, FileOptions::<()>::default())
+                .unwrap();
+            writer.write_all(bytes).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    fn open(path: &Path) -> ZipArchive<File> {
+        ZipArchive::new(File::open(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn verifies_signatures_and_decrypts_members() {
+        let dir = tempfile::tempdir().unwrap();
+        let rng = SystemRandom::new();
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        let signer = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let mut secret = [0u8; 32];
+        rng.fill(&mut secret).unwrap();
+        let mut nonce = [0u8; NONCE_LEN];
+        rng.fill(&mut nonce).unwrap();
+        let mut sealed = b"manifest bytes".to_vec();
+        LessSafeKey::new(UnboundKey::new(&AES_256_GCM, &secret).unwrap())
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from("manifest.cbor".as_bytes()),
+                &mut sealed,
+            )
+            .unwrap();
+        let sealed = [nonce.as_slice(), &sealed].concat();
+        let encryption = br#"{"algorithm":"aes-256-gcm","key_id":"k1"}"#;
+
+        let pack = dir.path().join("telegram.gtpack");
+        write_pack(
+            &pack,
+            &[
+                ("manifest.cbor.enc", &sealed),
+                (ENCRYPTION_MEMBER, encryption),
+            ],
+        );
+        let digest = pack_digest(&mut open(&pack)).unwrap();
+        let signature = PackSignature {
+            algorithm: "ed25519".to_string(),
+            key_id: "release".to_string(),
+            signature: STANDARD.encode(signer.sign(digest.as_bytes())),
+            digest,
+        };
+        fs::write(sidecar_path(&pack), serde_json::to_vec(&signature).unwrap()).unwrap();
+
+        let mut config = DemoConfig {
+            require_signed_packs: true,
+            ..DemoConfig::default()
+        };
+        let untrusted = PackTrust::from_config(&config);
+        assert_eq!(
+            untrusted.check(&mut open(&pack), &pack).unwrap(),
+            SignatureStatus::Untrusted {
+                key_id: "release".to_string()
+            }
+        );
+        let status = untrusted.check(&mut open(&pack), &pack).unwrap();
+        assert!(untrusted.accept(status, &pack).is_err());
+        assert!(
+            untrusted
+                .read_member(&mut open(&pack), "manifest.cbor")
+                .is_err()
+        );
+
+        config.pack_keys.trusted.insert(
+            "release".to_string(),
+            STANDARD.encode(signer.public_key().as_ref()),
+        );
+        config
+            .pack_keys
+            .decryption
+            .insert("k1".to_string(), STANDARD.encode(secret));
+        let trust = PackTrust::from_config(&config);
+        assert_eq!(
+            trust.check(&mut open(&pack), &pack).unwrap().label(),
+            "verified"
+        );
+        assert_eq!(
+            trust
+                .read_member(&mut open(&pack), "manifest.cbor")
+                .unwrap()
+                .as_deref(),
+            Some(b"manifest bytes".as_slice())
+        );
+        let copy = trust
+            .decrypted_copy(&mut open(&pack), &pack, &dir.path().join("decrypted"))
+            .unwrap();
+        assert_eq!(
+            super::super::read_member(&mut open(&copy), "manifest.cbor")
+                .unwrap()
+                .as_deref(),
+            Some(b"manifest bytes".as_slice())
+        );
+
+        write_pack(&pack, &[("manifest.cbor", b"swapped")]);
+        assert!(matches!(
+            trust.check(&mut open(&pack), &pack).unwrap(),
+            SignatureStatus::Invalid { .. }
+        ));
+        fs::remove_file(sidecar_path(&pack)).unwrap();
+        assert_eq!(
+            trust.check(&mut open(&pack), &pack).unwrap(),
+            SignatureStatus::Unsigned
+        );
+        assert!(trust.accept(SignatureStatus::Unsigned, &pack).is_err());
+    }
+}
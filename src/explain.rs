@@ -0,0 +1,221 @@
+//! Long-form help for the [`OperatorError`] codes (`explain <code>`).
+//!
+//! Each code has a one-line summary, a paragraph on what causes it, and remediation
+//! steps, looked up through `operator_i18n` as `cli.explain.<code>.summary`,
+//! `.detail`, and `.fix` with the English text below as fallback. Steps in `fix` are
+//! separated by newlines.
+//!
+//! [`OperatorError`]: crate::operator_error::OperatorError
+
+use crate::operator_error;
+use crate::operator_i18n;
+
+pub struct Explanation {
+    pub code: &'static str,
+    pub exit_code: i32,
+    summary: &'static str,
+    detail: &'static str,
+    fix: &'static str,
+}
+
+static EXPLANATIONS: [Explanation; 9] = [
+    Explanation {
+        code: "pack_not_found",
+        exit_code: operator_error::EXIT_VALIDATION,
+        summary: "No pack matched the given name, id, or provider.",
+        detail: "The command looked through the bundle's packs for one whose file name, pack id, or provider id matches --provider (or the pack name given to demo run) and found none. Usually the pack was never added to the bundle, --bundle points at another directory, or the name is misspelled.",
+        fix: "Check that --bundle points at the bundle you mean.\nRun `greentic-operator demo list-packs --bundle <DIR>` to see the packs it contains.\nAdd a missing pack with `greentic-operator wizard --mode update --bundle <DIR>`, then rerun the command.",
+    },
+    Explanation {
+        code: "manifest_invalid",
+        exit_code: operator_error::EXIT_VALIDATION,
+        summary: "A pack manifest is missing or cannot be decoded.",
+        detail: "Every .gtpack carries a manifest with the pack's id, flows, and provider ops. The archive named in the error has no manifest or one this operator cannot decode: the file is truncated, is not a pack, or was built by an incompatible greentic-pack version.",
+        fix: "Inspect the archive with `greentic-operator demo pack inspect <PACK>`.\nFetch the pack again if the download may be incomplete, or rebuild it with a compatible greentic-pack.\nRun `greentic-operator demo doctor --bundle <DIR>` to check the other packs.",
+    },
+    Explanation {
+        code: "config_invalid",
+        exit_code: operator_error::EXIT_VALIDATION,
+        summary: "greentic.yaml or greentic.demo.yaml cannot be parsed.",
+        detail: "The bundle's config file has a YAML syntax error, a value of the wrong type, or a key this operator does not know. The error names the file and the parser's message, usually with a line number.",
+        fix: "Open the file at the line given in the error and correct it.\nIf an older operator wrote the bundle, run `greentic-operator migrate --bundle <DIR>`.\nRun the command again to confirm the file loads.",
+    },
+    Explanation {
+        code: "secret_missing",
+        exit_code: operator_error::EXIT_SECRET_MISSING,
+        summary: "A secret the provider needs is not in the store.",
+        detail: "Provider ops read credentials such as bot tokens and API keys from the bundle's secrets store. The secrets:// URI in the error was not found for this environment, tenant, and team. demo setup normally asks for these values, so they are missing when setup was skipped or ran for another tenant or team.",
+        fix: "Run `greentic-operator demo setup --bundle <DIR> --tenant <TENANT> --provider <PROVIDER>` and enter the missing values.\nCheck that --tenant, --team, and --env match the ones used during setup.\nRun `greentic-operator demo send --print-required-args` with the same flags to list what the provider needs.",
+    },
+    Explanation {
+        code: "flow_failed",
+        exit_code: operator_error::EXIT_FLOW_FAILED,
+        summary: "A provider op or flow ran and reported failure.",
+        detail: "The op named in the error (render_plan, encode, send_payload, ingest_http, ...) ran inside the provider component and returned an error, or the provider answered with ok: false. The text after the op name is the provider's own message; rejected credentials, unknown recipients, and rate limits are the usual causes.",
+        fix: "Read the provider's message, and the recorded run with `greentic-operator demo runs list --bundle <DIR>`.\nRun `greentic-operator demo verify --bundle <DIR> --provider <PROVIDER>` to check credentials and connectivity.\nOnce the cause is fixed, resend dead-lettered messages with `greentic-operator demo dlq replay`.",
+    },
+    Explanation {
+        code: "tunnel_unavailable",
+        exit_code: operator_error::EXIT_UNAVAILABLE,
+        summary: "cloudflared or ngrok did not come up.",
+        detail: "demo start opens a tunnel so providers can reach the local gateway with webhooks. The tunnel binary was not found, exited early, or did not report a public URL in time. Webhooks and setup steps that need a public URL do not work without it.",
+        fix: "Check that cloudflared (or ngrok, with an auth token) is installed and on PATH.\nLook in the bundle's logs/ directory for the reason the tunnel stopped.\nIf webhooks are not needed, start with `--cloudflared off`; to use ngrok instead, pass `--cloudflared off --ngrok on`.",
+    },
+    Explanation {
+        code: "access_denied",
+        exit_code: operator_error::EXIT_DENIED,
+        summary: "operators.yaml denied the action.",
+        detail: "The bundle has an operators.yaml that limits state-changing commands to named operators. The current operator is not allowed the action in the error, or could not be identified from --as or GREENTIC_OPERATOR_TOKEN.",
+        fix: "Run `greentic-operator demo audit search --action access --bundle <DIR>` to see the decision and who it was made for.\nIdentify yourself with `demo --as <name>` or set GREENTIC_OPERATOR_TOKEN.\nAsk an owner of the bundle to grant the action in operators.yaml.",
+    },
+    Explanation {
+        code: "circuit_open",
+        exit_code: operator_error::EXIT_UNAVAILABLE,
+        summary: "The provider's circuit breaker is open after repeated failures.",
+        detail: "After failure_threshold consecutive failed send_payload or ingest_http calls, the operator stops calling the provider until cooldown_secs have passed and then lets one trial call through. The error names the provider and when the circuit may close.",
+        fix: "Run `greentic-operator demo status --bundle <DIR>` to see the breaker state and recent failures.\nFix the underlying provider failure (see `greentic-operator explain flow_failed`).\nWait for the cooldown, or delete state/circuits.json to reset every breaker.",
+    },
+    Explanation {
+        code: "flow_waiting",
+        exit_code: operator_error::EXIT_WAITING,
+        summary: "A flow run with --no-repl stopped waiting for input.",
+        detail: "demo run --no-repl runs the flow once and expects it to finish. The flow instead blocked on an adaptive card, a session wait, or other input that nobody can give without the REPL. The report still has status waiting, the reason, and the output produced so far.",
+        fix: "Read the reason in the run report to see what the flow is waiting for.\nPass the values it needs up front with --input, or run the flow without --no-repl and answer in the REPL.\nIf waiting is an expected outcome for this flow, treat exit code 8 as success in your script.",
+    },
+];
+
+/// The explanation for `code`; case, `-`, and `_` are not significant.
+pub fn find(code: &str) -> Option<&'static Explanation> {
+    let wanted = code.trim().to_ascii_lowercase().replace('-', "_");
+    EXPLANATIONS
+        .iter()
+        .find(|explanation| explanation.code == wanted)
+}
+
+pub fn all() -> &'static [Explanation] {
+    &EXPLANATIONS
+}
+
+impl Explanation {
+    pub fn summary(&self) -> String {
+        self.text("summary", self.summary)
+    }
+
+    /// The full localized page: heading, summary, cause, and numbered fixes.
+    pub fn render(&self) -> String {
+        let mut out = operator_i18n::trf(
+            "cli.explain.heading",
+            "{} (exit code {})",
+            &[self.code, &self.exit_code.to_string()],
+        );
+        out.push_str("\n\n");
+        out.push_str(&self.summary());
+        out.push_str("\n\n");
+        out.push_str(&self.text("detail", self.detail));
+        out.push_str("\n\n");
+        out.push_str(&operator_i18n::tr(
+            "cli.explain.fix_heading",
+            "How to fix it:",
+        ));
+        for (index, step) in self
+            .text("fix", self.fix)
+            .lines()
+            .map(str::trim)
+            .filter(|step| !step.is_empty())
+            .enumerate()
+        {
+            out.push_str(&format!("\n  {}. {step}", index + 1));
+        }
+        out.push('\n');
+        out
+    }
+
+    fn text(&self, part: &str, english: &str) -> String {
+        let translated = operator_i18n::tr(&format!("cli.explain.{}.{part}", self.code), english);
+        if translated.trim().is_empty() {
+            english.to_string()
+        } else {
+            translated
+        }
+    }
+}
+
+/// Every code with its summary, for `explain` without an argument.
+pub fn render_index() -> String {
+    let mut out = operator_i18n::tr(
+        "cli.explain.index",
+        "Error codes (greentic-operator explain <code> for details):",
+    );
+    for explanation in all() {
+        out.push_str(&format!(
+            "\n  {:<20} {}",
+            explanation.code,
+            explanation.summary()
+        ));
+    }
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operator_error::OperatorError;
+
+    #[test]
+    fn every_error_code_is_explained_with_its_exit_code() {
+        let errors = [
+            OperatorError::PackNotFound {
+                reference: String::new(),
+            },
+            OperatorError::ManifestInvalid {
+                path: Default::default(),
+                detail: String::new(),
+            },
+            OperatorError::ConfigInvalid {
+                path: Default::default(),
+                detail: String::new(),
+            },
+            OperatorError::SecretMissing { uri: String::new() },
+            OperatorError::FlowFailed {
+                provider: String::new(),
+                op: String::new(),
+                detail: String::new(),
+            },
+            OperatorError::TunnelUnavailable {
+                tunnel: String::new(),
+                detail: String::new(),
+            },
+            OperatorError::AccessDenied {
+                action: String::new(),
+                detail: String::new(),
+            },
+            OperatorError::CircuitOpen {
+                provider: String::new(),
+                until: String::new(),
+            },
+            OperatorError::FlowWaiting {
+                pack: String::new(),
+                flow: String::new(),
+                reason: None,
+            },
+        ];
+        assert_eq!(errors.len(), all().len());
+        for error in errors {
+            let explanation = find(error.code()).expect(error.code());
+            assert_eq!(explanation.exit_code, error.exit_code(), "{}", error.code());
+        }
+        assert_eq!(
+            find("Secret-Missing").map(|e| e.code),
+            Some("secret_missing")
+        );
+        assert!(find("E0042").is_none());
+    }
+
+    #[test]
+    fn render_numbers_the_fix_steps() {
+        let page = find("circuit_open").unwrap().render();
+        assert!(page.starts_with("circuit_open (exit code 6)"), "{page}");
+        assert!(page.contains("\n  3. "), "{page}");
+    }
+}
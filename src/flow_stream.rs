@@ -0,0 +1,250 @@
+//! Live events from flows that are still running.
+//!
+//! A runner spawned while someone listens gets `GREENTIC_STREAM=1` and may report
+//! on stderr while the flow runs, one event per line:
+//!
+//! ```text
+//! @greentic-stream {"type":"progress","percent":24,"message":"backfilled 1200/5000"}
+//! @greentic-stream {"type":"log","level":"warn","message":"page 13 retried"}
+//! ```
+//!
+//! Those lines are taken out of the runner's stderr and handed to the sink
+//! installed with [`scoped`] on the thread that started the run. `demo setup`
+//! prints them under its progress bar and `demo run` in its REPL. A flow that says
+//! nothing for [`HEARTBEAT`], including every in-process flow, gets a heartbeat
+//! event instead so a long run is visibly alive. Without a sink, or after
+//! [`disable`] (`--no-stream`), runners are not asked to stream and their output
+//! arrives when they finish, as before.
+
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::operator_i18n;
+
+/// Set on spawned runners when their events are wanted.
+pub const ENV_VAR: &str = "GREENTIC_STREAM";
+/// Starts every event line on the runner's stderr.
+pub const LINE_PREFIX: &str = "@greentic-stream ";
+/// How long a flow may stay silent before a heartbeat is sent for it.
+pub const HEARTBEAT: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamEvent {
+    Progress {
+        #[serde(default)]
+        percent: Option<f64>,
+        #[serde(default)]
+        message: Option<String>,
+    },
+    Log {
+        #[serde(default)]
+        level: Option<String>,
+        message: String,
+    },
+    /// Sent by the operator, not the runner, when a flow has been quiet.
+    Heartbeat { elapsed_secs: u64 },
+}
+
+impl StreamEvent {
+    pub fn render(&self) -> String {
+        match self {
+            StreamEvent::Progress { percent, message } => match (percent, message) {
+                (Some(percent), Some(message)) => format!("[{percent:.0}%] {message}"),
+                (Some(percent), None) => format!("[{percent:.0}%]"),
+                (None, Some(message)) => message.clone(),
+                (None, None) => "…".to_string(),
+            },
+            StreamEvent::Log { level, message } => {
+                format!("[{}] {message}", level.as_deref().unwrap_or("info"))
+            }
+            StreamEvent::Heartbeat { elapsed_secs } => operator_i18n::trf(
+                "flow_stream.still_running",
+                "still running ({}s)",
+                &[&elapsed_secs.to_string()],
+            ),
+        }
+    }
+}
+
+/// The event on one line of runner stderr, if the line is an event line.
+/// Malformed event lines are dropped rather than shown as stderr.
+pub fn parse_line(line: &str) -> Option<Result<StreamEvent, serde_json::Error>> {
+    let body = line.trim_end().strip_prefix(LINE_PREFIX)?;
+    Some(serde_json::from_str(body))
+}
+
+pub type Sink = Arc<dyn Fn(&StreamEvent) + Send + Sync>;
+
+static DISABLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static SINK: RefCell<Option<Sink>> = const { RefCell::new(None) };
+}
+
+/// Turns streaming off for the rest of the process (`--no-stream`).
+pub fn disable() {
+    DISABLED.store(true, Ordering::Relaxed);
+}
+
+/// Sends events of flows started on this thread to `sink` until the guard drops.
+pub fn scoped(sink: Sink) -> SinkGuard {
+    let previous = SINK.with(|current| current.replace(Some(sink)));
+    SinkGuard { previous }
+}
+
+pub struct SinkGuard {
+    previous: Option<Sink>,
+}
+
+impl Drop for SinkGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        SINK.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+/// Starts streaming the flow about to run on this thread; `None` when streaming
+/// is off or nobody listens. Heartbeats stop when the returned value drops.
+pub fn live() -> Option<LiveFlow> {
+    if DISABLED.load(Ordering::Relaxed) {
+        return None;
+    }
+    let sink = SINK.with(|current| current.borrow().clone())?;
+    let emitter = Emitter {
+        sink,
+        last: Arc::new(Mutex::new(Instant::now())),
+    };
+    let (stop, stopped) = mpsc::channel::<()>();
+    let heartbeat = emitter.clone();
+    let started = Instant::now();
+    thread::spawn(move || {
+        loop {
+            match stopped.recv_timeout(HEARTBEAT) {
+                Err(RecvTimeoutError::Timeout) => {
+                    if heartbeat.quiet_for() >= HEARTBEAT {
+                        heartbeat.emit(&StreamEvent::Heartbeat {
+                            elapsed_secs: started.elapsed().as_secs(),
+                        });
+                    }
+                }
+                _ => break,
+            }
+        }
+    });
+    Some(LiveFlow {
+        emitter,
+        _stop: stop,
+    })
+}
+
+/// One flow being streamed.
+pub struct LiveFlow {
+    emitter: Emitter,
+    _stop: mpsc::Sender<()>,
+}
+
+impl LiveFlow {
+    /// A handle for the threads reading the runner's output.
+    pub fn emitter(&self) -> Emitter {
+        self.emitter.clone()
+    }
+}
+
+#[derive(Clone)]
+pub struct Emitter {
+    sink: Sink,
+    last: Arc<Mutex<Instant>>,
+}
+
+impl Emitter {
+    pub fn emit(&self, event: &StreamEvent) {
+        *self.last.lock().unwrap_or_else(|err| err.into_inner()) = Instant::now();
+        (self.sink)(event);
+    }
+
+    /// Emits the event on `line` and returns true, or returns false for ordinary
+    /// stderr.
+    pub fn emit_line(&self, line: &str) -> bool {
+        match parse_line(line) {
+            Some(Ok(event)) => {
+                self.emit(&event);
+                true
+            }
+            Some(Err(err)) => {
+                crate::operator_log::debug(
+                    module_path!(),
+                    format!("malformed stream event {line:?}: {err}"),
+                );
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn quiet_for(&self) -> Duration {
+        self.last
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .elapsed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_event_lines_and_leaves_other_stderr() {
+        assert_eq!(
+            parse_line(concat!(
+                r#"@greentic-stream {"type":"progress","percent":24,"message":"1200/5000"}"#,
+                "\n"
+            ))
+            .unwrap()
+            .unwrap(),
+            StreamEvent::Progress {
+                percent: Some(24.0),
+                message: Some("1200/5000".to_string()),
+            }
+        );
+        let log = parse_line(r#"@greentic-stream {"type":"log","message":"page 13"}"#)
+            .unwrap()
+            .unwrap();
+        assert_eq!(log.render(), "[info] page 13");
+        assert!(
+            parse_line(r#"@greentic-stream {"type":"bogus"}"#)
+                .unwrap()
+                .is_err()
+        );
+        assert!(parse_line("warning: slow provider").is_none());
+    }
+
+    #[test]
+    fn live_flows_reach_the_thread_sink() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let recorded = seen.clone();
+        assert!(live().is_none());
+        {
+            let _guard = scoped(Arc::new(move |event: &StreamEvent| {
+                recorded.lock().unwrap().push(event.render());
+            }));
+            let live = live().unwrap();
+            let emitter = live.emitter();
+            thread::spawn(move || {
+                assert!(emitter.emit_line(r#"@greentic-stream {"type":"progress","percent":50}"#));
+                assert!(!emitter.emit_line("plain stderr"));
+            })
+            .join()
+            .unwrap();
+        }
+        assert!(live().is_none());
+        assert_eq!(*seen.lock().unwrap(), vec!["[50%]".to_string()]);
+    }
+}
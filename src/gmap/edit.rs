@@ -8,25 +8,25 @@ pub fn upsert_policy(path: &Path, rule_path: &str, policy: Policy) -> anyhow::Re
     } else {
         String::new()
     };
-    let (updated, has_comments) = if contents.is_empty() {
-        (String::new(), false)
-    } else {
-        let has_comments = contains_comments_or_blanks(&contents);
-        (contents, has_comments)
-    };
+    let updated = upsert_policy_str(&contents, rule_path, policy)?;
+    write_file(path, &updated)?;
+    Ok(())
+}
 
-    if has_comments {
-        let updated = upsert_preserving_lines(&updated, rule_path, policy)?;
-        write_file(path, &updated)?;
-        return Ok(());
+/// The gmap `contents` with the rule upserted, as [`upsert_policy`] would write it.
+pub fn upsert_policy_str(
+    contents: &str,
+    rule_path: &str,
+    policy: Policy,
+) -> anyhow::Result<String> {
+    if !contents.is_empty() && contains_comments_or_blanks(contents) {
+        return upsert_preserving_lines(contents, rule_path, policy);
     }
 
-    let mut rules = parse_str(&updated)?;
+    let mut rules = parse_str(contents)?;
     upsert_rule(&mut rules, rule_path, policy)?;
     rules.sort_by(|a, b| canonical_key(&a.path).cmp(&canonical_key(&b.path)));
-    let rendered = render_rules(&rules);
-    write_file(path, &rendered)?;
-    Ok(())
+    Ok(render_rules(&rules))
 }
 
 fn upsert_rule(
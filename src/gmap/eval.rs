@@ -7,31 +7,29 @@ pub struct MatchDecision {
 }
 
 pub fn eval_policy(rules: &[GmapRule], target: &GmapPath) -> Option<MatchDecision> {
-    let mut best: Option<MatchDecision> = None;
-    let mut best_index = 0usize;
-    for (idx, rule) in rules.iter().enumerate() {
-        if !matches_target(&rule.path, target) {
+    winning_rule(rules, target).map(|rule| MatchDecision {
+        policy: rule.policy.clone(),
+        rank: specificity_rank(&rule.path),
+    })
+}
+
+/// The rule `eval_policy` decides by: the most specific match, later lines winning ties.
+pub fn winning_rule<'a>(rules: &'a [GmapRule], target: &GmapPath) -> Option<&'a GmapRule> {
+    let mut best: Option<(&GmapRule, u8)> = None;
+    for rule in rules {
+        if !rule_matches(rule, target) {
             continue;
         }
         let rank = specificity_rank(&rule.path);
-        let candidate = MatchDecision {
-            policy: rule.policy.clone(),
-            rank,
-        };
-        match best {
-            None => {
-                best = Some(candidate);
-                best_index = idx;
-            }
-            Some(ref current) => {
-                if rank > current.rank || (rank == current.rank && idx > best_index) {
-                    best = Some(candidate);
-                    best_index = idx;
-                }
-            }
+        if best.is_none_or(|(_, best_rank)| rank >= best_rank) {
+            best = Some((rule, rank));
         }
     }
-    best
+    best.map(|(rule, _)| rule)
+}
+
+pub fn rule_matches(rule: &GmapRule, target: &GmapPath) -> bool {
+    matches_target(&rule.path, target)
 }
 
 pub fn eval_with_overlay(
@@ -2,6 +2,6 @@ mod edit;
 mod eval;
 mod parse;
 
-pub use edit::upsert_policy;
-pub use eval::{MatchDecision, eval_policy, eval_with_overlay};
+pub use edit::{upsert_policy, upsert_policy_str};
+pub use eval::{MatchDecision, eval_policy, eval_with_overlay, rule_matches, winning_rule};
 pub use parse::{GmapPath, GmapRule, Policy, parse_file, parse_path, parse_rule_line, parse_str};
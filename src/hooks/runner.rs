@@ -388,6 +388,8 @@ fn offer_pack(path: PathBuf, pack_id: String) -> anyhow::Result<ProviderPack> {
         file_name,
         path,
         entry_flows: Vec::new(),
+        depends_on: Vec::new(),
+        verify_flows: Vec::new(),
     })
 }
 
@@ -0,0 +1,333 @@
+//! Copy-on-write instances of a read-only ("golden") bundle (`demo start --instance`).
+//!
+//! An instance directory looks like a bundle to every other command: each top-level
+//! entry of the golden bundle is a symlink into it, except the mutable ones. `state/`
+//! and `logs/` start empty; `greentic.demo.yaml`, `tenants/` (policies and tenant
+//! config), `resolved/`, `state/resolved/`, `destinations.yaml`, `snapshots/` and
+//! `.greentic/` (the dev secrets store and its metadata) are copied from the golden
+//! bundle when the instance is created and belong to the instance from then on.
+//! Several instances can run from one golden bundle at the same time, and none of
+//! them writes into it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::runtime_state;
+
+pub const MARKER_FILE: &str = ".greentic-instance.json";
+const MARKER_FORMAT: &str = "greentic-instance@1";
+
+/// Top-level entries an instance owns instead of linking.
+const OWNED: [&str; 8] = [
+    "state",
+    "logs",
+    "greentic.demo.yaml",
+    "tenants",
+    "resolved",
+    "destinations.yaml",
+    "snapshots",
+    ".greentic",
+];
+/// Copied from the golden bundle when the instance is created.
+const SEEDED: [&str; 7] = [
+    "greentic.demo.yaml",
+    "tenants",
+    "resolved",
+    "state/resolved",
+    "destinations.yaml",
+    "snapshots",
+    ".greentic",
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Marker {
+    format: String,
+    golden: PathBuf,
+}
+
+/// Creates `instance` for `golden`, or brings an existing one up to date with the
+/// golden bundle's top-level entries. Returns the instance root, which is then used
+/// as the bundle path.
+pub fn prepare(golden: &Path, instance: &Path) -> anyhow::Result<PathBuf> {
+    let golden = golden
+        .canonicalize()
+        .with_context(|| format!("golden bundle {} not found", golden.display()))?;
+    if golden.join(MARKER_FILE).exists() {
+        return Err(anyhow!(
+            "{} is itself an instance; pass its golden bundle instead",
+            golden.display()
+        ));
+    }
+    check_outside(&golden, &std::path::absolute(instance)?)?;
+    fs::create_dir_all(instance)
+        .with_context(|| format!("create instance {}", instance.display()))?;
+    let instance = instance.canonicalize()?;
+    check_outside(&golden, &instance)?;
+
+    let marker_path = instance.join(MARKER_FILE);
+    match runtime_state::read_json::<Marker>(&marker_path)? {
+        Some(marker) if marker.golden == golden => {
+            // Instances created before an entry became owned still link it.
+            for relative in SEEDED {
+                let path = instance.join(relative);
+                if fs::symlink_metadata(&path).is_ok_and(|meta| meta.is_symlink()) {
+                    remove_link(&path)?;
+                    seed(&golden, &instance, relative)?;
+                }
+            }
+        }
+        Some(marker) => {
+            return Err(anyhow!(
+                "{} is an instance of {}, not {}",
+                instance.display(),
+                marker.golden.display(),
+                golden.display()
+            ));
+        }
+        None => {
+            if fs::read_dir(&instance)?.next().is_some() {
+                return Err(anyhow!(
+                    "{} is not empty and is not a bundle instance",
+                    instance.display()
+                ));
+            }
+            for relative in SEEDED {
+                seed(&golden, &instance, relative)?;
+            }
+            runtime_state::write_json(
+                &marker_path,
+                &Marker {
+                    format: MARKER_FORMAT.to_string(),
+                    golden: golden.clone(),
+                },
+            )?;
+        }
+    }
+    for dir in ["state", "logs"] {
+        fs::create_dir_all(instance.join(dir))?;
+    }
+    link_entries(&golden, &instance)?;
+    Ok(instance)
+}
+
+fn check_outside(golden: &Path, instance: &Path) -> anyhow::Result<()> {
+    if instance.starts_with(golden) || golden.starts_with(instance) {
+        return Err(anyhow!(
+            "instance {} must be outside the golden bundle {}",
+            instance.display(),
+            golden.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Links every golden entry the instance does not own, and drops links whose target
+/// is gone.
+fn link_entries(golden: &Path, instance: &Path) -> anyhow::Result<()> {
+    for entry in fs::read_dir(instance)? {
+        let path = entry?.path();
+        if fs::symlink_metadata(&path)?.file_type().is_symlink() && !path.exists() {
+            fs::remove_file(&path).with_context(|| format!("remove {}", path.display()))?;
+        }
+    }
+    for entry in fs::read_dir(golden)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if OWNED.iter().any(|owned| name == *owned) {
+            continue;
+        }
+        let link = instance.join(&name);
+        if fs::symlink_metadata(&link).is_ok() {
+            continue;
+        }
+        symlink(&entry.path(), &link, entry.file_type()?.is_dir())
+            .with_context(|| format!("link {} to {}", link.display(), entry.path().display()))?;
+    }
+    Ok(())
+}
+
+fn seed(golden: &Path, instance: &Path, relative: &str) -> anyhow::Result<()> {
+    let source = golden.join(relative);
+    let dest = instance.join(relative);
+    if source.is_dir() {
+        copy_dir(&source, &dest)?;
+    } else if source.is_file() {
+        fs::copy(&source, &dest).with_context(|| format!("copy {}", source.display()))?;
+    }
+    Ok(())
+}
+
+fn copy_dir(source: &Path, dest: &Path) -> anyhow::Result<()> {
+    fs::create_dir_all(dest)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let target = dest.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)
+                .with_context(|| format!("copy {}", entry.path().display()))?;
+        }
+    }
+    Ok(())
+}
+
+fn remove_link(path: &Path) -> anyhow::Result<()> {
+    // Windows directory symlinks are removed like directories.
+    fs::remove_file(path)
+        .or_else(|_| fs::remove_dir(path))
+        .with_context(|| format!("remove {}", path.display()))
+}
+
+#[cfg(unix)]
+fn symlink(target: &Path, link: &Path, _is_dir: bool) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn symlink(target: &Path, link: &Path, is_dir: bool) -> std::io::Result<()> {
+    if is_dir {
+        std::os::windows::fs::symlink_dir(target, link)
+    } else {
+        std::os::windows::fs::symlink_file(target, link)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn instances_link_golden_entries_and_own_mutable_state() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let golden = dir.path().join("golden");
+        write(&golden.join("greentic.demo.yaml"), "version: '1'\n");
+        write(&golden.join("packs/messaging.gtpack"), "pack");
+        write(&golden.join("resolved/demo.yaml"), "tenant: demo\n");
+        write(&golden.join(".greentic/dev/.dev.secrets.env"), "seeded");
+        write(&golden.join("state/pids/nats.pid"), "42");
+
+        let first = prepare(&golden, &dir.path().join("runs/a"))?;
+        let second = prepare(&golden, &dir.path().join("runs/b"))?;
+        assert!(fs::symlink_metadata(first.join("packs"))?.is_symlink());
+        assert_eq!(
+            fs::read_to_string(first.join("greentic.demo.yaml"))?,
+            "version: '1'\n"
+        );
+        assert!(!first.join("state/pids").exists());
+
+        fs::write(first.join("resolved/demo.yaml"), "tenant: changed\n")?;
+        fs::write(first.join(".greentic/dev/.dev.secrets.env"), "written")?;
+        assert_eq!(
+            fs::read_to_string(golden.join("resolved/demo.yaml"))?,
+            "tenant: demo\n"
+        );
+        assert_eq!(
+            fs::read_to_string(second.join(".greentic/dev/.dev.secrets.env"))?,
+            "seeded"
+        );
+
+        write(&golden.join("providers/events.gtpack"), "pack");
+        assert_eq!(prepare(&golden, &first)?, first);
+        assert!(first.join("providers/events.gtpack").exists());
+        assert_eq!(
+            fs::read_to_string(first.join("resolved/demo.yaml"))?,
+            "tenant: changed\n"
+        );
+
+        assert!(prepare(&first, &dir.path().join("runs/c")).is_err());
+        assert!(prepare(&golden, &golden.join("runs")).is_err());
+        assert!(!golden.join("runs").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn policy_edits_in_an_instance_leave_the_golden_bundle_alone() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let golden = dir.path().join("golden");
+        write(&golden.join("greentic.demo.yaml"), "version: '1'\n");
+        write(&golden.join("tenants/demo/tenant.gmap"), "_ = forbidden\n");
+
+        let instance = prepare(&golden, &dir.path().join("runs/a"))?;
+        assert!(!fs::symlink_metadata(instance.join("tenants"))?.is_symlink());
+        assert!(!fs::symlink_metadata(instance.join("greentic.demo.yaml"))?.is_symlink());
+        fs::write(instance.join("tenants/demo/tenant.gmap"), "_ = public\n")?;
+        fs::write(instance.join("greentic.demo.yaml"), "version: '2'\n")?;
+        assert_eq!(
+            fs::read_to_string(golden.join("tenants/demo/tenant.gmap"))?,
+            "_ = forbidden\n"
+        );
+        assert_eq!(
+            fs::read_to_string(golden.join("greentic.demo.yaml"))?,
+            "version: '1'\n"
+        );
+
+        // Instances that still link `tenants/` get their own copy on the next start.
+        let legacy = dir.path().join("runs/b");
+        prepare(&golden, &legacy)?;
+        fs::remove_dir_all(legacy.join("tenants"))?;
+        symlink(&golden.join("tenants"), &legacy.join("tenants"), true)?;
+        prepare(&golden, &legacy)?;
+        assert!(!fs::symlink_metadata(legacy.join("tenants"))?.is_symlink());
+        assert!(legacy.join("tenants/demo/tenant.gmap").is_file());
+        Ok(())
+    }
+
+    #[test]
+    fn destination_and_snapshot_writes_stay_in_the_instance() -> anyhow::Result<()> {
+        use crate::destinations::{DestinationBook, DestinationEntry};
+
+        let dir = tempfile::tempdir()?;
+        let golden = dir.path().join("golden");
+        write(&golden.join("greentic.demo.yaml"), "version: '1'\n");
+        write(&golden.join("destinations.yaml"), "destinations: {}\n");
+        write(&golden.join("snapshots/corpus.yaml"), "messages: []\n");
+
+        let instance = prepare(&golden, &dir.path().join("runs/a"))?;
+        assert!(!fs::symlink_metadata(instance.join("destinations.yaml"))?.is_symlink());
+        assert!(!fs::symlink_metadata(instance.join("snapshots"))?.is_symlink());
+        assert!(instance.join("snapshots/corpus.yaml").is_file());
+
+        let mut book = DestinationBook::load(&instance)?;
+        book.add(
+            "alice",
+            "telegram",
+            DestinationEntry {
+                id: "42".to_string(),
+                kind: None,
+            },
+        )?;
+        book.save(&instance)?;
+        runtime_state::write_json(&instance.join("snapshots/telegram/hello.json"), &"{}")?;
+        assert_eq!(
+            fs::read_to_string(golden.join("destinations.yaml"))?,
+            "destinations: {}\n"
+        );
+        assert!(!golden.join("snapshots/telegram").exists());
+
+        // A book that is still a link to the golden file is replaced, not written through.
+        let legacy = dir.path().join("runs/b");
+        prepare(&golden, &legacy)?;
+        fs::remove_file(legacy.join("destinations.yaml"))?;
+        symlink(
+            &golden.join("destinations.yaml"),
+            &legacy.join("destinations.yaml"),
+            false,
+        )?;
+        book.save(&legacy)?;
+        assert!(!fs::symlink_metadata(legacy.join("destinations.yaml"))?.is_symlink());
+        assert_eq!(
+            fs::read_to_string(golden.join("destinations.yaml"))?,
+            "destinations: {}\n"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,224 @@
+//! `${...}` references in bundle config files (`greentic.demo.yaml`, setup-input
+//! files), so bundles can be committed without hostnames and tokens:
+//!
+//! - `${NAME}` is the environment variable `NAME`; unset is an error.
+//! - `${NAME:-fallback}` uses `fallback` when `NAME` is unset or empty.
+//! - `${secret:provider/key}` reads `key` for `provider` from the bundle's secrets
+//!   manager in the caller's tenant/team; `${secret:secrets://env/tenant/team/provider/key}`
+//!   reads that exact URI.
+//! - `$${` is a literal `${`.
+//!
+//! Setup input is resolved when it is loaded. `greentic.demo.yaml` only has its env
+//! references resolved on load; the values that may hold secrets go through
+//! [`interpolate_secrets`] where they are used, in the tenant/team using them.
+//!
+//! Substitution is textual, so quote references whose values may contain YAML
+//! syntax (`"${secret:messaging-slack/slack_bot_token}"`). Full-line comments are
+//! left alone.
+
+use std::path::Path;
+
+use anyhow::{Context, anyhow};
+
+use crate::secrets_gate::{self, SecretsManagerHandle};
+use crate::secrets_setup::resolve_env;
+
+const SECRET_PREFIX: &str = "secret:";
+
+/// Where `${secret:provider/key}` references are looked up.
+#[derive(Clone, Copy, Debug)]
+pub struct SecretScope<'a> {
+    pub bundle_root: &'a Path,
+    pub tenant: &'a str,
+    pub team: Option<&'a str>,
+}
+
+/// Resolves env and secret references; secret references without a `scope` are an error.
+pub fn interpolate(text: &str, scope: Option<SecretScope<'_>>) -> anyhow::Result<String> {
+    resolve_references(text, scope, true)
+}
+
+/// Resolves the `${secret:...}` references [`interpolate_env`] kept, and leaves any
+/// other `${` alone.
+pub fn interpolate_secrets(text: &str, scope: Option<SecretScope<'_>>) -> anyhow::Result<String> {
+    if !text.contains("${secret:") {
+        return Ok(text.to_string());
+    }
+    resolve_references(text, scope, false)
+}
+
+fn resolve_references(
+    text: &str,
+    scope: Option<SecretScope<'_>>,
+    env_references: bool,
+) -> anyhow::Result<String> {
+    if !text.contains("${") {
+        return Ok(text.to_string());
+    }
+    let mut handle: Option<SecretsManagerHandle> = None;
+    let env = resolve_env(None);
+    replace_references(text, &mut |reference| {
+        let Some(body) = reference.strip_prefix(SECRET_PREFIX) else {
+            if !env_references {
+                return Ok(None);
+            }
+            return resolve_env_reference(reference).map(Some);
+        };
+        let scope =
+            scope.ok_or_else(|| anyhow!("${{{reference}}} needs a bundle to read secrets from"))?;
+        if handle.is_none() {
+            handle = Some(secrets_gate::resolve_secrets_manager(
+                scope.bundle_root,
+                scope.tenant,
+                scope.team,
+            )?);
+        }
+        let handle = handle.as_ref().expect("secrets manager initialized");
+        let value = match secrets_gate::parse_canonical_secret_uri(body) {
+            Some(uri) => secrets_gate::read_provider_secret(
+                handle,
+                &uri.env,
+                &uri.tenant,
+                Some(&uri.team),
+                &uri.provider,
+                &uri.key,
+            )?,
+            None => {
+                let (provider, key) = body.split_once('/').ok_or_else(|| {
+                    anyhow!(
+                        "${{{reference}}}: expected secret:<provider>/<key> or a secrets:// URI"
+                    )
+                })?;
+                secrets_gate::read_provider_secret(
+                    handle,
+                    &env,
+                    scope.tenant,
+                    scope.team,
+                    provider,
+                    key,
+                )?
+            }
+        };
+        value
+            .map(Some)
+            .ok_or_else(|| anyhow!("${{{reference}}}: secret not found"))
+    })
+}
+
+/// Resolves env references only and keeps `${secret:...}` as written, for checks
+/// that must not touch the secrets store.
+pub fn interpolate_env(text: &str) -> anyhow::Result<String> {
+    if !text.contains("${") {
+        return Ok(text.to_string());
+    }
+    replace_references(text, &mut |reference| {
+        if reference.starts_with(SECRET_PREFIX) {
+            Ok(None)
+        } else {
+            resolve_env_reference(reference).map(Some)
+        }
+    })
+}
+
+fn resolve_env_reference(reference: &str) -> anyhow::Result<String> {
+    let (name, fallback) = match reference.split_once(":-") {
+        Some((name, fallback)) => (name, Some(fallback)),
+        None => (reference, None),
+    };
+    let name = name.trim();
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+    {
+        return Err(anyhow!("${{{reference}}}: invalid variable name"));
+    }
+    match (std::env::var(name), fallback) {
+        (Ok(value), Some(fallback)) if value.is_empty() => Ok(fallback.to_string()),
+        (Ok(value), _) => Ok(value),
+        (Err(_), Some(fallback)) => Ok(fallback.to_string()),
+        (Err(_), None) => Err(anyhow!("environment variable {name} is not set")),
+    }
+}
+
+/// Calls `resolve` with the body of every `${...}`; `None` keeps the reference as is.
+fn replace_references(
+    text: &str,
+    resolve: &mut dyn FnMut(&str) -> anyhow::Result<Option<String>>,
+) -> anyhow::Result<String> {
+    let mut out = String::with_capacity(text.len());
+    for (index, line) in text.split_inclusive('\n').enumerate() {
+        if line.trim_start().starts_with('#') {
+            out.push_str(line);
+            continue;
+        }
+        let mut rest = line;
+        while let Some(start) = rest.find("${") {
+            if rest[..start].ends_with('$') {
+                out.push_str(&rest[..start - 1]);
+                out.push_str("${");
+                rest = &rest[start + 2..];
+                continue;
+            }
+            out.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let end = after
+                .find('}')
+                .ok_or_else(|| anyhow!("line {}: unclosed '${{'", index + 1))?;
+            let reference = &after[..end];
+            match resolve(reference.trim()).with_context(|| format!("line {}", index + 1))? {
+                Some(value) => out.push_str(&value),
+                None => out.push_str(&rest[start..start + 2 + end + 1]),
+            }
+            rest = &after[end + 1..];
+        }
+        out.push_str(rest);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_references_use_values_fallbacks_and_escapes() {
+        unsafe {
+            std::env::set_var("GREENTIC_TEST_INTERPOLATE_HOST", "example.test");
+        }
+        let text = "host: ${GREENTIC_TEST_INTERPOLATE_HOST}\n\
+                    port: ${GREENTIC_TEST_INTERPOLATE_UNSET:-8080}\n\
+                    literal: $${HOME}\n\
+                    # note: ${GREENTIC_TEST_INTERPOLATE_UNSET}\n";
+        assert_eq!(
+            interpolate(text, None).unwrap(),
+            "host: example.test\nport: 8080\nliteral: ${HOME}\n# note: ${GREENTIC_TEST_INTERPOLATE_UNSET}\n"
+        );
+    }
+
+    #[test]
+    fn unset_env_and_unscoped_secrets_are_errors() {
+        let err = interpolate("a: ${GREENTIC_TEST_INTERPOLATE_UNSET}\n", None).unwrap_err();
+        assert!(format!("{err:#}").contains("GREENTIC_TEST_INTERPOLATE_UNSET is not set"));
+        let err = interpolate("\nb: ${secret:messaging-slack/token}\n", None).unwrap_err();
+        assert!(format!("{err:#}").starts_with("line 2"));
+    }
+
+    #[test]
+    fn secrets_only_keeps_other_references() {
+        assert_eq!(
+            interpolate_secrets("url: ${HOME}/x", None).unwrap(),
+            "url: ${HOME}/x"
+        );
+        let err = interpolate_secrets("token: ${secret:messaging-slack/token}", None).unwrap_err();
+        assert!(format!("{err:#}").contains("needs a bundle"));
+    }
+
+    #[test]
+    fn env_only_keeps_secret_references() {
+        assert_eq!(
+            interpolate_env("token: \"${secret:messaging-slack/slack_bot_token}\"").unwrap(),
+            "token: \"${secret:messaging-slack/slack_bot_token}\""
+        );
+    }
+}
@@ -1,25 +1,42 @@
+pub mod api;
+pub mod audit;
 pub mod bin_resolver;
+pub mod bus;
 pub mod capabilities;
 pub mod cards;
+pub mod catalog;
 pub mod cli;
 pub mod cloudflared;
+pub mod completions;
 pub mod component_qa_ops;
 pub mod config;
 pub mod config_gate;
 pub mod demo;
+pub mod destinations;
 pub mod dev_store_path;
 pub mod discovery;
 pub mod doctor;
+pub mod domain_run;
 pub mod domains;
+pub mod explain;
+pub mod flow_stream;
 pub mod gmap;
 pub mod hooks;
 pub mod ingress;
+pub mod instance;
+pub mod interpolate;
+pub mod jetstream;
 pub mod messaging_universal;
+pub mod migrate;
 pub mod ngrok;
 pub mod offers;
 pub mod onboard;
+pub mod operator_auth;
+pub mod operator_error;
 pub mod operator_i18n;
 pub mod operator_log;
+pub mod profile;
+pub mod progress;
 pub mod project;
 pub mod provider_config_envelope;
 pub mod provider_registry;
@@ -27,25 +44,40 @@ pub mod providers;
 pub mod qa_flow_handler;
 pub mod qa_persist;
 pub mod qa_setup_wizard;
+pub mod redaction;
+pub mod registry_overlay;
 pub mod runner_exec;
 pub mod runner_integration;
+pub mod runner_pin;
 pub mod runtime_state;
+pub mod scope_chain;
 pub mod secret_name;
 pub mod secret_requirements;
 pub mod secret_value;
+pub mod secrets_aws;
 pub mod secrets_backend;
 pub mod secrets_client;
+pub mod secrets_crypto;
 pub mod secrets_gate;
 pub mod secrets_manager;
 pub mod secrets_setup;
+pub mod secrets_vault;
+pub mod secrets_versions;
+pub mod self_update;
+pub mod serve;
 pub mod services;
 pub mod setup_input;
 pub mod setup_to_formspec;
 pub mod state_layout;
 pub mod subscriptions_universal;
 pub mod supervisor;
+pub mod tenant_transfer;
 pub mod wizard;
+pub mod wizard_bundle_spec;
+pub mod wizard_drift;
 pub mod wizard_executor;
 pub mod wizard_i18n;
+pub mod wizard_i18n_review;
 pub mod wizard_plan_builder;
 pub mod wizard_spec_builder;
+pub mod workspace;
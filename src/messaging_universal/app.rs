@@ -12,6 +12,7 @@ use serde_json::Value as JsonValue;
 use serde_json::json;
 use zip::ZipArchive;
 
+use crate::demo::conversation_state::{self, ConversationKey};
 use crate::demo::runner_host::OperatorContext;
 use crate::runner_exec::{self, RunRequest};
 
@@ -105,14 +106,30 @@ pub fn select_app_flow(info: &AppPackInfo) -> Result<&AppFlowInfo> {
     bail!("APP_FLOW_NOT_RESOLVED; available flows: {available}");
 }
 
+/// Runs `flow_id` for one ingress message. The conversation's stored state goes in
+/// as `state`, and the flow's `state_updates` are written back afterwards.
 pub fn run_app_flow(
     bundle: &Path,
     ctx: &OperatorContext,
+    provider: &str,
     pack_path: &Path,
     pack_id: &str,
     flow_id: &str,
     envelope: &ChannelMessageEnvelope,
 ) -> Result<Vec<ChannelMessageEnvelope>> {
+    let state_dir = bundle.join("state");
+    let conversation = (!envelope.session_id.is_empty()).then(|| {
+        ConversationKey::new(
+            &ctx.tenant,
+            ctx.team.as_deref(),
+            provider,
+            &envelope.session_id,
+        )
+    });
+    let state = match &conversation {
+        Some(key) => conversation_state::load(&state_dir, key)?,
+        None => Default::default(),
+    };
     let request = RunRequest {
         root: bundle.to_path_buf(),
         domain: crate::domains::Domain::Messaging,
@@ -126,11 +143,18 @@ pub fn run_app_flow(
             "tenant": ctx.tenant,
             "team": ctx.team,
             "correlation_id": ctx.correlation_id,
+            "state": state,
         }),
         dist_offline: true,
     };
 
     let output = runner_exec::run_provider_pack_flow(request)?;
+    if let Some(key) = &conversation {
+        let updates = collect_state_updates(&output.run_dir)?;
+        if !updates.is_empty() {
+            conversation_state::apply_updates(&state_dir, key, &updates)?;
+        }
+    }
     // Check if the envelope contains AC action routing metadata (routeToCardId/toCardId).
     // If so, select the matching card node output from the transcript instead of the default.
     let target_node = envelope
@@ -213,6 +237,26 @@ fn extract_text_from_map(map: &BTreeMap<CborValue, CborValue>, key: &str) -> Opt
         })
 }
 
+/// Every node's `state_updates` in transcript order, later nodes winning.
+fn collect_state_updates(run_dir: &Path) -> Result<serde_json::Map<String, JsonValue>> {
+    let path = run_dir.join("transcript.jsonl");
+    let mut updates = serde_json::Map::new();
+    if !path.exists() {
+        return Ok(updates);
+    }
+    let contents = std::fs::read_to_string(path)?;
+    for line in contents.lines() {
+        if let Ok(value) = serde_json::from_str::<JsonValue>(line)
+            && let Some(JsonValue::Object(node_updates)) = value
+                .get("outputs")
+                .and_then(|outputs| outputs.get("state_updates"))
+        {
+            updates.extend(node_updates.clone());
+        }
+    }
+    Ok(updates)
+}
+
 fn collect_transcript_outputs(
     run_dir: &Path,
     target_node_id: Option<&str>,
@@ -0,0 +1,400 @@
+//! Attachment loading and delivery helpers for the demo send pipeline.
+//!
+//! Files passed via `demo send --attach` are read once, validated against a size
+//! limit and MIME-sniffed. After `render_plan` the provider's attachment
+//! capabilities decide whether the bytes are embedded as a base64 `data:` URL or
+//! staged on disk for the provider to upload.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde_json::{Value as JsonValue, json};
+use uuid::Uuid;
+
+use crate::messaging_universal::dto::RenderPlanOutV1;
+
+/// Upper bound applied before the provider has had a chance to declare its own limit.
+pub const DEFAULT_MAX_ATTACHMENT_BYTES: u64 = 10 * 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct LocalAttachment {
+    pub path: PathBuf,
+    pub name: String,
+    pub mime_type: String,
+    pub bytes: Vec<u8>,
+}
+
+impl LocalAttachment {
+    pub fn size_bytes(&self) -> u64 {
+        self.bytes.len() as u64
+    }
+
+    /// Envelope entry used for `render_plan`, before the delivery mode is known.
+    pub fn descriptor(&self) -> JsonValue {
+        json!({
+            "name": self.name,
+            "mime_type": self.mime_type,
+            "size_bytes": self.size_bytes(),
+            "url": file_url(&self.path),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttachmentDelivery {
+    /// Embed the bytes as a `data:<mime>;base64,...` URL.
+    Inline,
+    /// Stage the file under the bundle state dir and hand the provider a `file://` URL.
+    Upload,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttachmentCapabilities {
+    pub supported: bool,
+    pub delivery: AttachmentDelivery,
+    pub max_bytes: Option<u64>,
+    pub mime_types: Vec<String>,
+}
+
+impl Default for AttachmentCapabilities {
+    fn default() -> Self {
+        Self {
+            supported: true,
+            delivery: AttachmentDelivery::Inline,
+            max_bytes: None,
+            mime_types: Vec::new(),
+        }
+    }
+}
+
+impl AttachmentCapabilities {
+    fn accepts_mime(&self, mime_type: &str) -> bool {
+        if self.mime_types.is_empty() {
+            return true;
+        }
+        self.mime_types.iter().any(|allowed| {
+            if allowed == "*/*" || allowed == mime_type {
+                return true;
+            }
+            allowed
+                .strip_suffix("/*")
+                .and_then(|prefix| mime_type.strip_prefix(prefix))
+                .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+}
+
+pub fn load_attachment(path: &Path, max_bytes: u64) -> anyhow::Result<LocalAttachment> {
+    let metadata = fs::metadata(path)
+        .with_context(|| format!("failed to read attachment {}", path.display()))?;
+    if !metadata.is_file() {
+        return Err(anyhow::anyhow!(
+            "attachment {} is not a regular file",
+            path.display()
+        ));
+    }
+    if metadata.len() > max_bytes {
+        return Err(anyhow::anyhow!(
+            "attachment {} is {} bytes, exceeding the {} byte limit",
+            path.display(),
+            metadata.len(),
+            max_bytes
+        ));
+    }
+    let bytes =
+        fs::read(path).with_context(|| format!("failed to read attachment {}", path.display()))?;
+    let name = path
+        .file_name()
+        .map(|value| value.to_string_lossy().to_string())
+        .unwrap_or_else(|| "attachment".to_string());
+    let mime_type = detect_mime(&name, &bytes);
+    Ok(LocalAttachment {
+        path: path.to_path_buf(),
+        name,
+        mime_type,
+        bytes,
+    })
+}
+
+/// Sniffs well-known magic numbers first and falls back to the file extension.
+pub fn detect_mime(name: &str, bytes: &[u8]) -> String {
+    const MAGIC: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+        (b"PK\x03\x04", "application/zip"),
+    ];
+    if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        return "image/webp".to_string();
+    }
+    let sniffed = MAGIC
+        .iter()
+        .find(|(magic, _)| bytes.starts_with(magic))
+        .map(|(_, mime)| *mime);
+    let extension = Path::new(name)
+        .extension()
+        .map(|value| value.to_string_lossy().to_ascii_lowercase());
+    let by_extension = extension.as_deref().and_then(mime_for_extension);
+    match (sniffed, by_extension) {
+        // Office documents are zip containers; prefer the more specific extension match.
+        (Some("application/zip"), Some(ext)) => ext.to_string(),
+        (Some(mime), _) => mime.to_string(),
+        (None, Some(ext)) => ext.to_string(),
+        (None, None) => "application/octet-stream".to_string(),
+    }
+}
+
+fn mime_for_extension(extension: &str) -> Option<&'static str> {
+    let mime = match extension {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "json" => "application/json",
+        "txt" | "log" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "mp3" => "audio/mpeg",
+        "mp4" => "video/mp4",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+/// Reads attachment capabilities from the render plan.
+///
+/// Providers may declare them either as `attachments` or `capabilities.attachments`,
+/// as a bare boolean or as an object with `supported`, `mode`, `max_bytes` and
+/// `mime_types`. Plans without a declaration get the inline defaults.
+pub fn capabilities_from_plan(plan: &RenderPlanOutV1) -> AttachmentCapabilities {
+    let Some(plan_value) = plan
+        .plan
+        .as_ref()
+        .and_then(|plan| serde_json::from_str::<JsonValue>(&plan.plan_json).ok())
+    else {
+        return AttachmentCapabilities::default();
+    };
+    let declared = plan_value
+        .get("attachments")
+        .or_else(|| plan_value.pointer("/capabilities/attachments"));
+    let mut caps = AttachmentCapabilities::default();
+    match declared {
+        Some(JsonValue::Bool(supported)) => caps.supported = *supported,
+        Some(JsonValue::Object(map)) => {
+            if let Some(supported) = map.get("supported").and_then(JsonValue::as_bool) {
+                caps.supported = supported;
+            }
+            if let Some(mode) = map.get("mode").and_then(JsonValue::as_str) {
+                caps.delivery = match mode {
+                    "upload" | "url" => AttachmentDelivery::Upload,
+                    _ => AttachmentDelivery::Inline,
+                };
+            }
+            caps.max_bytes = map.get("max_bytes").and_then(JsonValue::as_u64);
+            if let Some(JsonValue::Array(items)) = map.get("mime_types") {
+                caps.mime_types = items
+                    .iter()
+                    .filter_map(JsonValue::as_str)
+                    .map(str::to_string)
+                    .collect();
+            }
+        }
+        _ => {}
+    }
+    caps
+}
+
+/// Builds the final envelope attachment entries according to the provider capabilities.
+pub fn deliver_attachments(
+    attachments: &[LocalAttachment],
+    caps: &AttachmentCapabilities,
+    staging_dir: &Path,
+) -> anyhow::Result<Vec<JsonValue>> {
+    if attachments.is_empty() {
+        return Ok(Vec::new());
+    }
+    if !caps.supported {
+        return Err(anyhow::anyhow!(
+            "provider render plan does not support attachments"
+        ));
+    }
+    let mut entries = Vec::with_capacity(attachments.len());
+    for attachment in attachments {
+        if let Some(max_bytes) = caps.max_bytes
+            && attachment.size_bytes() > max_bytes
+        {
+            return Err(anyhow::anyhow!(
+                "attachment {} is {} bytes, provider limit is {} bytes",
+                attachment.name,
+                attachment.size_bytes(),
+                max_bytes
+            ));
+        }
+        if !caps.accepts_mime(&attachment.mime_type) {
+            return Err(anyhow::anyhow!(
+                "attachment {} has MIME type {} which the provider does not accept ({})",
+                attachment.name,
+                attachment.mime_type,
+                caps.mime_types.join(", ")
+            ));
+        }
+        let url = match caps.delivery {
+            AttachmentDelivery::Inline => format!(
+                "data:{};base64,{}",
+                attachment.mime_type,
+                STANDARD.encode(&attachment.bytes)
+            ),
+            AttachmentDelivery::Upload => {
+                fs::create_dir_all(staging_dir)
+                    .with_context(|| format!("failed to create {}", staging_dir.display()))?;
+                let staged = staging_dir.join(format!("{}-{}", Uuid::new_v4(), attachment.name));
+                fs::write(&staged, &attachment.bytes)
+                    .with_context(|| format!("failed to stage {}", staged.display()))?;
+                file_url(&staged)
+            }
+        };
+        entries.push(json!({
+            "name": attachment.name,
+            "mime_type": attachment.mime_type,
+            "size_bytes": attachment.size_bytes(),
+            "url": url,
+        }));
+    }
+    Ok(entries)
+}
+
+/// Replaces the `attachments` array of a serialized `ChannelMessageEnvelope`.
+pub fn set_message_attachments(message: &mut JsonValue, attachments: Vec<JsonValue>) {
+    if let Some(map) = message.as_object_mut() {
+        map.insert("attachments".to_string(), JsonValue::Array(attachments));
+    }
+}
+
+pub fn staging_dir(bundle: &Path, tenant: &str) -> PathBuf {
+    bundle.join("state").join("uploads").join(tenant)
+}
+
+fn file_url(path: &Path) -> String {
+    let absolute = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    format!("file://{}", absolute.display())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messaging_universal::dto::RenderPlanOutPlan;
+    use tempfile::tempdir;
+
+    fn plan(plan_json: JsonValue) -> RenderPlanOutV1 {
+        RenderPlanOutV1 {
+            ok: true,
+            plan: Some(RenderPlanOutPlan {
+                plan_json: plan_json.to_string(),
+            }),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn detect_mime_prefers_magic_over_extension() {
+        assert_eq!(
+            detect_mime("photo.txt", b"\x89PNG\r\n\x1a\n...."),
+            "image/png"
+        );
+        assert_eq!(detect_mime("report.pdf", b"plain"), "application/pdf");
+        assert_eq!(
+            detect_mime("sheet.xlsx", b"PK\x03\x04rest"),
+            "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+        );
+        assert_eq!(detect_mime("blob", b"\x00\x01"), "application/octet-stream");
+    }
+
+    #[test]
+    fn load_attachment_enforces_size_limit() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("big.bin");
+        fs::write(&path, vec![0u8; 32]).unwrap();
+        assert!(load_attachment(&path, 16).is_err());
+        let loaded = load_attachment(&path, 32).unwrap();
+        assert_eq!(loaded.size_bytes(), 32);
+        assert_eq!(loaded.name, "big.bin");
+    }
+
+    #[test]
+    fn capabilities_from_plan_reads_nested_declaration() {
+        let caps = capabilities_from_plan(&plan(json!({
+            "capabilities": {
+                "attachments": {
+                    "mode": "upload",
+                    "max_bytes": 1024,
+                    "mime_types": ["image/*"]
+                }
+            }
+        })));
+        assert!(caps.supported);
+        assert_eq!(caps.delivery, AttachmentDelivery::Upload);
+        assert_eq!(caps.max_bytes, Some(1024));
+        assert!(caps.accepts_mime("image/png"));
+        assert!(!caps.accepts_mime("application/pdf"));
+
+        let disabled = capabilities_from_plan(&plan(json!({"attachments": false})));
+        assert!(!disabled.supported);
+    }
+
+    #[test]
+    fn deliver_inline_embeds_base64_data_url() {
+        let dir = tempdir().unwrap();
+        let attachment = LocalAttachment {
+            path: dir.path().join("hello.txt"),
+            name: "hello.txt".to_string(),
+            mime_type: "text/plain".to_string(),
+            bytes: b"hello".to_vec(),
+        };
+        let entries = deliver_attachments(
+            std::slice::from_ref(&attachment),
+            &AttachmentCapabilities::default(),
+            dir.path(),
+        )
+        .unwrap();
+        assert_eq!(entries[0]["url"], "data:text/plain;base64,aGVsbG8=");
+        assert_eq!(entries[0]["size_bytes"], 5);
+
+        let caps = AttachmentCapabilities {
+            max_bytes: Some(4),
+            ..AttachmentCapabilities::default()
+        };
+        assert!(deliver_attachments(&[attachment], &caps, dir.path()).is_err());
+    }
+
+    #[test]
+    fn deliver_upload_stages_file() {
+        let dir = tempdir().unwrap();
+        let attachment = LocalAttachment {
+            path: dir.path().join("doc.pdf"),
+            name: "doc.pdf".to_string(),
+            mime_type: "application/pdf".to_string(),
+            bytes: b"%PDF-1.7".to_vec(),
+        };
+        let caps = AttachmentCapabilities {
+            delivery: AttachmentDelivery::Upload,
+            ..AttachmentCapabilities::default()
+        };
+        let staging = dir.path().join("uploads");
+        let entries = deliver_attachments(&[attachment], &caps, &staging).unwrap();
+        let url = entries[0]["url"].as_str().unwrap();
+        assert!(url.starts_with("file://"));
+        assert_eq!(fs::read_dir(&staging).unwrap().count(), 1);
+    }
+}
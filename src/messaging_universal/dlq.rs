@@ -1,10 +1,19 @@
 //! Dead-letter queue helpers for the universal pipeline.
+//!
+//! Failed egress sends are persisted as one JSON record per message under
+//! `state/dlq/<provider>/<job_id>.json`, carrying the envelope that failed so it
+//! can be replayed verbatim. The jsonl helpers remain for the legacy `dlq.log`.
 
+use anyhow::Context;
 use chrono::Utc;
+use greentic_types::ChannelMessageEnvelope;
+use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use std::fs::OpenOptions;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use crate::messaging_universal::dto::ProviderPayloadV1;
 
 /// Append a DLQ entry to the jsonl log.
 pub fn append_dlq_entry(path: &Path, entry: &Value) -> anyhow::Result<()> {
@@ -44,3 +53,106 @@ pub fn build_dlq_entry(
         "message_summary": message_summary,
     })
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DlqRecord {
+    pub job_id: String,
+    pub provider: String,
+    pub tenant: String,
+    #[serde(default)]
+    pub team: Option<String>,
+    #[serde(default)]
+    pub correlation_id: Option<String>,
+    pub attempt: u32,
+    pub max_attempts: u32,
+    /// When the first delivery attempt started (RFC 3339).
+    pub enqueued_at: String,
+    /// When the final attempt failed (RFC 3339).
+    pub failed_at: String,
+    /// Node error details (`code`, `message`, `retryable`, ...).
+    pub failure: Value,
+    /// The envelope exactly as it entered the egress pipeline.
+    pub envelope: ChannelMessageEnvelope,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<ProviderPayloadV1>,
+    #[serde(default)]
+    pub replay_count: u32,
+}
+
+impl DlqRecord {
+    pub fn failure_message(&self) -> &str {
+        self.failure
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("-")
+    }
+}
+
+pub fn dlq_root(state_dir: &Path) -> PathBuf {
+    state_dir.join("dlq")
+}
+
+pub fn record_path(state_dir: &Path, provider: &str, job_id: &str) -> PathBuf {
+    dlq_root(state_dir)
+        .join(provider)
+        .join(format!("{job_id}.json"))
+}
+
+pub fn write_record(state_dir: &Path, record: &DlqRecord) -> anyhow::Result<PathBuf> {
+    let path = record_path(state_dir, &record.provider, &record.job_id);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(record)?)
+        .with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+pub fn remove_record(state_dir: &Path, record: &DlqRecord) -> anyhow::Result<()> {
+    let path = record_path(state_dir, &record.provider, &record.job_id);
+    std::fs::remove_file(&path).with_context(|| format!("failed to remove {}", path.display()))?;
+    if let Some(parent) = path.parent() {
+        // Drop the provider dir once it is empty; ignore "not empty" errors.
+        let _ = std::fs::remove_dir(parent);
+    }
+    Ok(())
+}
+
+/// Load all records, optionally for a single provider, oldest failure first.
+/// Unreadable files are skipped.
+pub fn list_records(state_dir: &Path, provider: Option<&str>) -> anyhow::Result<Vec<DlqRecord>> {
+    let root = dlq_root(state_dir);
+    if !root.exists() {
+        return Ok(Vec::new());
+    }
+    let provider_dirs = match provider {
+        Some(provider) => vec![root.join(provider)],
+        None => std::fs::read_dir(&root)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_dir())
+            .collect(),
+    };
+    let mut records = Vec::new();
+    for dir in provider_dirs {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let Ok(contents) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            if let Ok(record) = serde_json::from_str::<DlqRecord>(&contents) {
+                records.push(record);
+            }
+        }
+    }
+    records.sort_by(|a, b| a.failed_at.cmp(&b.failed_at));
+    Ok(records)
+}
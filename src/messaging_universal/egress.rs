@@ -3,6 +3,7 @@
 use anyhow::Context;
 use base64::Engine;
 use base64::engine::general_purpose::STANDARD;
+use chrono::Utc;
 use greentic_types::ChannelMessageEnvelope;
 use rand::{RngExt, rng};
 use serde_json::{Value as JsonValue, json};
@@ -15,11 +16,10 @@ use crate::domains::Domain;
 use crate::messaging_universal::app;
 use crate::messaging_universal::dlq;
 use crate::messaging_universal::dto::{
-    EncodeInV1, ProviderPayloadV1, RenderPlanInV1, SendPayloadInV1, TenantHint,
+    EncodeInV1, ProviderPayloadV1, RenderPlanInV1, SendPayloadInV1, SendPayloadOutV1, TenantHint,
 };
 use crate::messaging_universal::retry::{EgressJob, RetryPolicy};
 use crate::operator_log;
-use crate::runtime_state::RuntimePaths;
 use crate::secrets_gate::SecretsManagerHandle;
 
 pub fn build_render_plan_input(message: serde_json::Value) -> RenderPlanInV1 {
@@ -64,12 +64,39 @@ pub fn run_end_to_end(
     app_pack: Option<String>,
     send_payload_flag: bool,
     dry_run: bool,
-    retries: u32,
+    retries: Option<u32>,
     secrets_handle: SecretsManagerHandle,
 ) -> anyhow::Result<()> {
     if envelopes.is_empty() {
         return Ok(());
     }
+    let processed_envelopes = run_app_flows(envelopes, provider, bundle, ctx, app_pack)?;
+    let policy = RetryPolicy::for_bundle(bundle, retries);
+    deliver_envelopes(
+        processed_envelopes,
+        provider,
+        bundle,
+        ctx,
+        runner_binary,
+        send_payload_flag && !dry_run,
+        &policy,
+        secrets_handle,
+    )?;
+    Ok(())
+}
+
+/// Runs each ingress envelope through the tenant's app flow; an envelope the flow
+/// answers with nothing passes through unchanged.
+pub fn run_app_flows(
+    envelopes: Vec<ChannelMessageEnvelope>,
+    provider: &str,
+    bundle: &Path,
+    ctx: &OperatorContext,
+    app_pack: Option<String>,
+) -> anyhow::Result<Vec<ChannelMessageEnvelope>> {
+    if envelopes.is_empty() {
+        return Ok(envelopes);
+    }
     let team = ctx.team.as_deref();
     let app_pack_path = app::resolve_app_pack_path(bundle, &ctx.tenant, team, app_pack.as_deref())
         .context("failed to resolve app pack")?;
@@ -96,6 +123,7 @@ pub fn run_end_to_end(
         let mut outputs = app::run_app_flow(
             bundle,
             ctx,
+            provider,
             &app_pack_path,
             &pack_info.pack_id,
             &flow.id,
@@ -108,7 +136,22 @@ pub fn run_end_to_end(
             processed_envelopes.append(&mut outputs);
         }
     }
-    let envelopes = processed_envelopes;
+    Ok(processed_envelopes)
+}
+
+/// render_plan → encode for each envelope without sending; unlike the delivery path,
+/// a failing encode is an error rather than a fallback payload.
+pub fn encode_envelopes(
+    envelopes: &[ChannelMessageEnvelope],
+    provider: &str,
+    bundle: &Path,
+    ctx: &OperatorContext,
+    runner_binary: Option<PathBuf>,
+    secrets_handle: SecretsManagerHandle,
+) -> anyhow::Result<Vec<ProviderPayloadV1>> {
+    if envelopes.is_empty() {
+        return Ok(Vec::new());
+    }
     let discovery = crate::discovery::discover_with_options(
         bundle,
         crate::discovery::DiscoveryOptions { cbor_only: true },
@@ -117,22 +160,115 @@ pub fn run_end_to_end(
         bundle.to_path_buf(),
         &discovery,
         runner_binary,
-        secrets_handle.clone(),
+        secrets_handle,
         false,
     )?;
-    let policy = RetryPolicy {
-        max_attempts: retries.saturating_add(1).max(1),
-        ..Default::default()
+    envelopes
+        .iter()
+        .map(|envelope| {
+            let message = serde_json::to_value(envelope)?;
+            let plan = render_plan(&runner_host, ctx, provider, message.clone())?;
+            encode_payload(&runner_host, ctx, provider, message, plan)
+        })
+        .collect()
+}
+
+/// Outcome counts for [`deliver_envelopes`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EgressSummary {
+    pub delivered: usize,
+    pub dead_lettered: usize,
+}
+
+/// Runs render_plan → encode → send_payload for each envelope, retrying according to
+/// `policy` and dead-lettering the envelope once the attempt budget is spent or the
+/// failure is not retryable. With `send == false` the pipeline stops after encode.
+#[allow(clippy::too_many_arguments)]
+pub fn deliver_envelopes(
+    envelopes: Vec<ChannelMessageEnvelope>,
+    provider: &str,
+    bundle: &Path,
+    ctx: &OperatorContext,
+    runner_binary: Option<PathBuf>,
+    send: bool,
+    policy: &RetryPolicy,
+    secrets_handle: SecretsManagerHandle,
+) -> anyhow::Result<EgressSummary> {
+    let jobs = envelopes
+        .into_iter()
+        .map(|envelope| (envelope, 0))
+        .collect();
+    deliver_jobs(
+        jobs,
+        provider,
+        bundle,
+        ctx,
+        runner_binary,
+        send,
+        policy,
+        secrets_handle,
+    )
+}
+
+/// Re-injects the stored envelope of a dead-lettered message through the egress
+/// pipeline. The caller removes the record first; a repeated failure writes a new
+/// record with `replay_count` bumped.
+pub fn replay_dlq_record(
+    record: &dlq::DlqRecord,
+    bundle: &Path,
+    runner_binary: Option<PathBuf>,
+    send: bool,
+    policy: &RetryPolicy,
+    secrets_handle: SecretsManagerHandle,
+) -> anyhow::Result<EgressSummary> {
+    let ctx = OperatorContext {
+        tenant: record.tenant.clone(),
+        team: record.team.clone(),
+        correlation_id: record.correlation_id.clone(),
     };
-    let runtime_paths = RuntimePaths::new(
-        bundle.join("state"),
-        &ctx.tenant,
-        ctx.team.clone().unwrap_or_else(|| "default".to_string()),
-    );
+    deliver_jobs(
+        vec![(record.envelope.clone(), record.replay_count + 1)],
+        &record.provider,
+        bundle,
+        &ctx,
+        runner_binary,
+        send,
+        policy,
+        secrets_handle,
+    )
+}
 
-    for envelope in envelopes {
+#[allow(clippy::too_many_arguments)]
+fn deliver_jobs(
+    jobs: Vec<(ChannelMessageEnvelope, u32)>,
+    provider: &str,
+    bundle: &Path,
+    ctx: &OperatorContext,
+    runner_binary: Option<PathBuf>,
+    send: bool,
+    policy: &RetryPolicy,
+    secrets_handle: SecretsManagerHandle,
+) -> anyhow::Result<EgressSummary> {
+    let mut summary = EgressSummary::default();
+    if jobs.is_empty() {
+        return Ok(summary);
+    }
+    let discovery = crate::discovery::discover_with_options(
+        bundle,
+        crate::discovery::DiscoveryOptions { cbor_only: true },
+    )?;
+    let runner_host = DemoRunnerHost::new(
+        bundle.to_path_buf(),
+        &discovery,
+        runner_binary,
+        secrets_handle.clone(),
+        false,
+    )?;
+
+    for (envelope, replay_count) in jobs {
         let mut job = EgressJob::new(provider, envelope.clone(), policy.max_attempts);
         let message_value = serde_json::to_value(&envelope)?;
+        let enqueued_at = Utc::now().to_rfc3339();
         let mut rng = rng();
         loop {
             job.increment_attempt();
@@ -148,6 +284,7 @@ pub fn run_end_to_end(
                 }
             };
             job.with_plan(plan.clone());
+            let last_plan = plan.clone();
 
             let payload =
                 match encode_payload(&runner_host, ctx, provider, message_value.clone(), plan) {
@@ -170,12 +307,12 @@ pub fn run_end_to_end(
                     }
                 };
 
-            if !send_payload_flag || dry_run {
+            if !send {
                 operator_log::info(
                     module_path!(),
                     format!(
-                        "[demo ingress] dry-run mode (send={}) provider={} attempt={} payload={}",
-                        send_payload_flag, provider, job.attempt, payload.content_type
+                        "[demo ingress] dry-run mode provider={} attempt={} payload={}",
+                        provider, job.attempt, payload.content_type
                     ),
                 );
                 break;
@@ -186,7 +323,7 @@ pub fn run_end_to_end(
             let send_input = SendPayloadInV1 {
                 v: 1,
                 provider_type: canonical_type,
-                payload,
+                payload: payload.clone(),
                 tenant: tenant_hint(ctx),
                 reply_scope: None,
             };
@@ -198,7 +335,12 @@ pub fn run_end_to_end(
                 serde_json::to_value(&send_input)?,
             )?;
 
-            if send_outcome.success {
+            let node_error = if send_outcome.success {
+                NodeErrorDetails::from_send_output(send_outcome.output.as_ref())
+            } else {
+                Some(NodeErrorDetails::from_outcome(&send_outcome))
+            };
+            let Some(node_error) = node_error else {
                 operator_log::info(
                     module_path!(),
                     format!(
@@ -206,12 +348,12 @@ pub fn run_end_to_end(
                         provider, job.attempt
                     ),
                 );
+                summary.delivered += 1;
                 break;
-            }
-
-            let node_error = NodeErrorDetails::from_outcome(&send_outcome);
+            };
             job.record_error(node_error.message.clone());
-            if job.attempt >= job.max_attempts || !node_error.retryable {
+            let retryable = policy.is_retryable(&node_error.code, node_error.retryable);
+            if job.attempt >= job.max_attempts || !retryable {
                 operator_log::error(
                     module_path!(),
                     format!(
@@ -219,19 +361,32 @@ pub fn run_end_to_end(
                         provider, job.attempt, node_error.message
                     ),
                 );
-                let entry = dlq::build_dlq_entry(
-                    &job.job_id.to_string(),
-                    provider,
-                    &ctx.tenant,
-                    ctx.team.as_deref(),
-                    None,
-                    ctx.correlation_id.as_deref(),
-                    job.attempt,
-                    job.max_attempts,
-                    node_error.to_json(),
-                    message_summary(&envelope),
+                let record = dlq::DlqRecord {
+                    job_id: job.job_id.to_string(),
+                    provider: provider.to_string(),
+                    tenant: ctx.tenant.clone(),
+                    team: ctx.team.clone(),
+                    correlation_id: ctx.correlation_id.clone(),
+                    attempt: job.attempt,
+                    max_attempts: job.max_attempts,
+                    enqueued_at: enqueued_at.clone(),
+                    failed_at: Utc::now().to_rfc3339(),
+                    failure: node_error.to_json(),
+                    envelope: envelope.clone(),
+                    plan: Some(last_plan),
+                    payload: Some(payload),
+                    replay_count,
+                };
+                let path = dlq::write_record(&bundle.join("state"), &record)?;
+                operator_log::info(
+                    module_path!(),
+                    format!(
+                        "[demo ingress] dead-lettered job={} path={}",
+                        record.job_id,
+                        path.display()
+                    ),
                 );
-                dlq::append_dlq_entry(&runtime_paths.dlq_log_path(), &entry)?;
+                summary.dead_lettered += 1;
                 break;
             }
             let delay = node_error.backoff_ms.map_or_else(
@@ -250,7 +405,7 @@ pub fn run_end_to_end(
             thread::sleep(delay);
         }
     }
-    Ok(())
+    Ok(summary)
 }
 
 pub fn render_plan(
@@ -278,6 +433,21 @@ pub fn encode_payload(
     message: JsonValue,
     plan: JsonValue,
 ) -> anyhow::Result<ProviderPayloadV1> {
+    let value = encode_output(runner_host, ctx, provider, message, plan)?;
+    // Providers wrap the payload in {"ok": true, "payload": {...}} — unwrap if present.
+    let payload_value = value.get("payload").cloned().unwrap_or(value);
+    serde_json::from_value(payload_value)
+        .context("failed to parse ProviderPayloadV1 from encode output")
+}
+
+/// The provider's raw `encode` output, before the payload is unwrapped.
+pub fn encode_output(
+    runner_host: &DemoRunnerHost,
+    ctx: &OperatorContext,
+    provider: &str,
+    message: JsonValue,
+    plan: JsonValue,
+) -> anyhow::Result<JsonValue> {
     let input = build_encode_input(message, plan);
     let outcome = invoke_flow(
         runner_host,
@@ -287,11 +457,7 @@ pub fn encode_payload(
         serde_json::to_value(&input)?,
     )?;
     let validated = ensure_success(&outcome, provider, "encode")?;
-    let value = validated.output.clone().unwrap_or_else(|| json!({}));
-    // Providers wrap the payload in {"ok": true, "payload": {...}} — unwrap if present.
-    let payload_value = value.get("payload").cloned().unwrap_or(value);
-    serde_json::from_value(payload_value)
-        .context("failed to parse ProviderPayloadV1 from encode output")
+    Ok(validated.output.clone().unwrap_or_else(|| json!({})))
 }
 
 fn invoke_flow(
@@ -336,17 +502,6 @@ fn tenant_hint(ctx: &OperatorContext) -> TenantHint {
     }
 }
 
-fn message_summary(envelope: &ChannelMessageEnvelope) -> JsonValue {
-    json!({
-        "id": envelope.id,
-        "channel": envelope.channel,
-        "session_id": envelope.session_id,
-        "text": envelope.text,
-        "attachments_count": envelope.attachments.len(),
-        "correlation_id": envelope.correlation_id,
-    })
-}
-
 struct NodeErrorDetails {
     code: String,
     message: String,
@@ -360,6 +515,28 @@ impl NodeErrorDetails {
         parse_node_error(outcome)
     }
 
+    /// `send_payload` can complete successfully while reporting `{"ok": false}`.
+    fn from_send_output(output: Option<&JsonValue>) -> Option<Self> {
+        let output = output?;
+        let parsed: SendPayloadOutV1 = serde_json::from_value(output.clone()).ok()?;
+        if parsed.ok {
+            return None;
+        }
+        Some(NodeErrorDetails {
+            code: if parsed.retryable {
+                "send-retryable".to_string()
+            } else {
+                "send-failed".to_string()
+            },
+            message: parsed
+                .message
+                .unwrap_or_else(|| "send_payload reported ok=false".to_string()),
+            retryable: parsed.retryable,
+            backoff_ms: None,
+            details: Some(output.clone()),
+        })
+    }
+
     fn to_json(&self) -> JsonValue {
         json!({
             "code": self.code,
@@ -0,0 +1,325 @@
+//! Synthetic webhook payloads for `demo ingress --emulate <platform>:<event>`.
+//!
+//! Each emulator builds the body a platform would POST to the provider's ingress path,
+//! shaped like the real thing, plus the headers the provider checks. Signature headers
+//! are only added when the signing secret is configured; otherwise the request goes out
+//! unsigned, like a webhook whose verification is disabled.
+//!
+//! | Platform | Events | Secret | What it signs |
+//! | --- | --- | --- | --- |
+//! | `telegram` | `message`, `callback_query` | `telegram_webhook_secret` | `X-Telegram-Bot-Api-Secret-Token` |
+//! | `slack` | `message`, `app_mention`, `url_verification` | `slack_signing_secret` | `X-Slack-Signature` |
+//! | `whatsapp` | `message`, `status`, `verify` | `whatsapp_app_secret`, `whatsapp_verify_token` | `X-Hub-Signature-256`, `hub.verify_token` |
+//! | `msgraph` | `message`, `validation` | `graph_client_state` | `clientState` in the notification |
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use ring::hmac;
+use serde_json::{Value as JsonValue, json};
+use uuid::Uuid;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Platform {
+    Telegram,
+    Slack,
+    WhatsApp,
+    MsGraph,
+}
+
+impl Platform {
+    /// Secret keys the emulator reads for this platform.
+    pub fn secret_keys(self) -> &'static [&'static str] {
+        match self {
+            Platform::Telegram => &["telegram_webhook_secret"],
+            Platform::Slack => &["slack_signing_secret"],
+            Platform::WhatsApp => &["whatsapp_app_secret", "whatsapp_verify_token"],
+            Platform::MsGraph => &["graph_client_state"],
+        }
+    }
+
+    fn events(self) -> &'static [&'static str] {
+        match self {
+            Platform::Telegram => &["message", "callback_query"],
+            Platform::Slack => &["message", "app_mention", "url_verification"],
+            Platform::WhatsApp => &["message", "status", "verify"],
+            Platform::MsGraph => &["message", "validation"],
+        }
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Platform::Telegram => "telegram",
+            Platform::Slack => "slack",
+            Platform::WhatsApp => "whatsapp",
+            Platform::MsGraph => "msgraph",
+        })
+    }
+}
+
+/// `<platform>[:<event>]`; the event defaults to `message`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EmulateSpec {
+    pub platform: Platform,
+    pub event: String,
+}
+
+impl FromStr for EmulateSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(raw: &str) -> anyhow::Result<Self> {
+        let (platform, event) = raw.split_once(':').unwrap_or((raw, "message"));
+        let platform = match platform.trim().to_ascii_lowercase().as_str() {
+            "telegram" => Platform::Telegram,
+            "slack" => Platform::Slack,
+            "whatsapp" => Platform::WhatsApp,
+            "msgraph" | "graph" | "teams" => Platform::MsGraph,
+            other => {
+                return Err(anyhow!(
+                    "unknown platform '{other}'; expected telegram, slack, whatsapp, or msgraph"
+                ));
+            }
+        };
+        let event = event.trim().to_ascii_lowercase();
+        if !platform.events().contains(&event.as_str()) {
+            return Err(anyhow!(
+                "{platform} cannot emulate '{event}'; expected one of {}",
+                platform.events().join(", ")
+            ));
+        }
+        Ok(Self { platform, event })
+    }
+}
+
+/// Values the emulated payload is built from; anything unset gets a demo default.
+#[derive(Clone, Debug, Default)]
+pub struct EmulateInput {
+    pub text: Option<String>,
+    /// Sender: a user name, id, or phone number depending on the platform.
+    pub from: Option<String>,
+    /// Chat, channel, or phone number id the message arrives in.
+    pub chat: Option<String>,
+    /// MS Graph subscription id.
+    pub binding_id: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct EmulatedRequest {
+    pub method: &'static str,
+    pub headers: Vec<(String, String)>,
+    pub query: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+/// Builds the request for `spec`; `secret` looks up one of [`Platform::secret_keys`].
+pub fn emulate(
+    spec: &EmulateSpec,
+    input: &EmulateInput,
+    secret: impl Fn(&str) -> Option<String>,
+) -> anyhow::Result<EmulatedRequest> {
+    let now = chrono::Utc::now();
+    let text = input.text.clone().unwrap_or_else(|| "hello".to_string());
+    let mut request = EmulatedRequest {
+        method: "POST",
+        headers: Vec::new(),
+        query: Vec::new(),
+        body: Vec::new(),
+    };
+    match (spec.platform, spec.event.as_str()) {
+        (Platform::Telegram, event) => {
+            let chat_id = numeric_or_text(input.chat.as_deref().unwrap_or("100000001"));
+            let from = input.from.as_deref().unwrap_or("demo_user");
+            let message = json!({
+                "message_id": now.timestamp_subsec_micros(),
+                "date": now.timestamp(),
+                "from": {"id": 100000001, "is_bot": false, "first_name": from, "username": from},
+                "chat": {"id": chat_id, "type": "private"},
+                "text": text,
+            });
+            let update = if event == "callback_query" {
+                json!({
+                    "update_id": now.timestamp(),
+                    "callback_query": {
+                        "id": Uuid::new_v4().simple().to_string(),
+                        "from": message["from"].clone(),
+                        "chat_instance": "demo",
+                        "message": message,
+                        "data": text,
+                    },
+                })
+            } else {
+                json!({"update_id": now.timestamp(), "message": message})
+            };
+            request.body = serde_json::to_vec(&update)?;
+            request.headers.push(json_content_type());
+            if let Some(token) = secret("telegram_webhook_secret") {
+                request
+                    .headers
+                    .push(("X-Telegram-Bot-Api-Secret-Token".to_string(), token));
+            }
+        }
+        (Platform::Slack, event) => {
+            let payload = if event == "url_verification" {
+                json!({
+                    "token": "demo",
+                    "challenge": Uuid::new_v4().simple().to_string(),
+                    "type": "url_verification",
+                })
+            } else {
+                let ts = format!("{}.{:06}", now.timestamp(), now.timestamp_subsec_micros());
+                let text = if event == "app_mention" {
+                    format!("<@U0DEMOBOT> {text}")
+                } else {
+                    text
+                };
+                json!({
+                    "token": "demo",
+                    "team_id": "T0DEMO",
+                    "api_app_id": "A0DEMO",
+                    "event": {
+                        "type": event,
+                        "channel": input.chat.as_deref().unwrap_or("C0DEMO"),
+                        "user": input.from.as_deref().unwrap_or("U0DEMO"),
+                        "text": text,
+                        "ts": ts,
+                        "event_ts": ts,
+                        "channel_type": "channel",
+                    },
+                    "type": "event_callback",
+                    "event_id": format!("Ev{}", Uuid::new_v4().simple()),
+                    "event_time": now.timestamp(),
+                })
+            };
+            request.body = serde_json::to_vec(&payload)?;
+            request.headers.push(json_content_type());
+            let timestamp = now.timestamp().to_string();
+            if let Some(signing_secret) = secret("slack_signing_secret") {
+                request.headers.push((
+                    "X-Slack-Signature".to_string(),
+                    slack_signature(&signing_secret, &timestamp, &request.body),
+                ));
+            }
+            request
+                .headers
+                .push(("X-Slack-Request-Timestamp".to_string(), timestamp));
+        }
+        (Platform::WhatsApp, "verify") => {
+            request.method = "GET";
+            let token = secret("whatsapp_verify_token").unwrap_or_else(|| "demo".to_string());
+            request.query = vec![
+                ("hub.mode".to_string(), "subscribe".to_string()),
+                ("hub.verify_token".to_string(), token),
+                (
+                    "hub.challenge".to_string(),
+                    now.timestamp_subsec_nanos().to_string(),
+                ),
+            ];
+        }
+        (Platform::WhatsApp, event) => {
+            let phone_number_id = input.chat.as_deref().unwrap_or("100000000000000");
+            let wa_id = input.from.as_deref().unwrap_or("15550000001");
+            let message_id = format!("wamid.{}", Uuid::new_v4().simple());
+            let timestamp = now.timestamp().to_string();
+            let mut value = json!({
+                "messaging_product": "whatsapp",
+                "metadata": {
+                    "display_phone_number": "15550000000",
+                    "phone_number_id": phone_number_id,
+                },
+            });
+            if event == "status" {
+                value["statuses"] = json!([{
+                    "id": message_id,
+                    "status": "delivered",
+                    "timestamp": timestamp,
+                    "recipient_id": wa_id,
+                }]);
+            } else {
+                value["contacts"] = json!([{"profile": {"name": "Demo User"}, "wa_id": wa_id}]);
+                value["messages"] = json!([{
+                    "from": wa_id,
+                    "id": message_id,
+                    "timestamp": timestamp,
+                    "type": "text",
+                    "text": {"body": text},
+                }]);
+            }
+            let payload = json!({
+                "object": "whatsapp_business_account",
+                "entry": [{"id": "0", "changes": [{"field": "messages", "value": value}]}],
+            });
+            request.body = serde_json::to_vec(&payload)?;
+            request.headers.push(json_content_type());
+            if let Some(app_secret) = secret("whatsapp_app_secret") {
+                request.headers.push((
+                    "X-Hub-Signature-256".to_string(),
+                    format!("sha256={}", hmac_sha256_hex(&app_secret, &request.body)),
+                ));
+            }
+        }
+        (Platform::MsGraph, "validation") => {
+            request.query = vec![(
+                "validationToken".to_string(),
+                Uuid::new_v4().simple().to_string(),
+            )];
+        }
+        (Platform::MsGraph, _) => {
+            let chat = input
+                .chat
+                .clone()
+                .unwrap_or_else(|| "19:demo@thread.v2".to_string());
+            let message_id = now.timestamp_millis().to_string();
+            let mut notification = json!({
+                "subscriptionId": input
+                    .binding_id
+                    .clone()
+                    .unwrap_or_else(|| Uuid::new_v4().to_string()),
+                "subscriptionExpirationDateTime": (now + chrono::Duration::hours(1)).to_rfc3339(),
+                "changeType": "created",
+                "resource": format!("chats('{chat}')/messages('{message_id}')"),
+                "resourceData": {
+                    "@odata.type": "#Microsoft.Graph.chatMessage",
+                    "@odata.id": format!("chats('{chat}')/messages('{message_id}')"),
+                    "id": message_id,
+                },
+                "tenantId": Uuid::nil().to_string(),
+            });
+            if let Some(client_state) = secret("graph_client_state") {
+                notification["clientState"] = JsonValue::String(client_state);
+            }
+            request.body = serde_json::to_vec(&json!({"value": [notification]}))?;
+            request.headers.push(json_content_type());
+        }
+    }
+    Ok(request)
+}
+
+/// `v0=<hex hmac>` over `v0:<timestamp>:<body>`, as Slack signs requests.
+pub fn slack_signature(signing_secret: &str, timestamp: &str, body: &[u8]) -> String {
+    let mut base = format!("v0:{timestamp}:").into_bytes();
+    base.extend_from_slice(body);
+    format!("v0={}", hmac_sha256_hex(signing_secret, &base))
+}
+
+pub fn hmac_sha256_hex(secret: &str, data: &[u8]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    hmac::sign(&key, data)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn json_content_type() -> (String, String) {
+    ("Content-Type".to_string(), "application/json".to_string())
+}
+
+fn numeric_or_text(value: &str) -> JsonValue {
+    value
+        .parse::<i64>()
+        .map(JsonValue::from)
+        .unwrap_or_else(|_| JsonValue::String(value.to_string()))
+}
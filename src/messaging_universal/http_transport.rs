@@ -0,0 +1,440 @@
+//! Operator-managed HTTP for provider sends.
+//!
+//! With `http.transport: operator` in `greentic.demo.yaml` (or `demo send --transport
+//! operator`), the operator makes the request a provider's encoded payload
+//! describes instead of running the pack's `send_payload`. Providers describe it in
+//! the payload metadata (`method`, `url`, `headers`); the body is `body_b64`. That
+//! puts proxy and TLS settings in one place, and lets sends be recorded to a
+//! cassette and replayed offline. Providers that do not declare a URL keep sending
+//! through `send_payload`.
+//!
+//! Cassettes are JSON files of request/response pairs. Secrets are masked before
+//! they are written, so a cassette can be checked in; a replayed request matches a
+//! recording on method, masked URL and body, falling back to the next unused
+//! recording for the same method and URL.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, anyhow};
+use base64::{Engine as _, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value as JsonValue, json};
+
+use crate::config::{self, CassetteMode, DemoHttpConfig, HttpTransportKind};
+use crate::messaging_universal::dto::ProviderPayloadV1;
+use crate::profile::{self, Phase};
+use crate::redaction;
+use crate::runtime_state;
+
+/// The request an encoded payload describes.
+#[derive(Clone, Debug)]
+pub struct HttpRequest {
+    pub method: String,
+    /// `None` when the provider leaves the URL to `send_payload`.
+    pub url: Option<String>,
+    pub headers: BTreeMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    pub fn from_payload(payload: &ProviderPayloadV1) -> Self {
+        let metadata = payload
+            .metadata
+            .clone()
+            .or_else(|| {
+                payload
+                    .metadata_json
+                    .as_deref()
+                    .and_then(|raw| serde_json::from_str(raw).ok())
+            })
+            .unwrap_or(JsonValue::Null);
+        let field = |keys: &[&str]| {
+            keys.iter()
+                .find_map(|key| metadata.get(key).and_then(JsonValue::as_str))
+                .map(str::to_string)
+        };
+        let mut headers = BTreeMap::new();
+        match metadata.get("headers") {
+            Some(JsonValue::Object(map)) => {
+                for (name, value) in map {
+                    let value = value
+                        .as_str()
+                        .map(str::to_string)
+                        .unwrap_or_else(|| value.to_string());
+                    headers.insert(name.clone(), value);
+                }
+            }
+            Some(JsonValue::Array(pairs)) => {
+                for pair in pairs {
+                    if let (Some(name), Some(value)) = (
+                        pair.get(0).and_then(JsonValue::as_str),
+                        pair.get(1).and_then(JsonValue::as_str),
+                    ) {
+                        headers.insert(name.to_string(), value.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+        if !headers
+            .keys()
+            .any(|name| name.eq_ignore_ascii_case("content-type"))
+        {
+            headers.insert("Content-Type".to_string(), payload.content_type.clone());
+        }
+        Self {
+            method: field(&["method", "http_method"])
+                .unwrap_or_else(|| "POST".to_string())
+                .to_ascii_uppercase(),
+            url: field(&["url", "endpoint", "api_url"]),
+            headers,
+            body: general_purpose::STANDARD
+                .decode(payload.body_b64.as_bytes())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Parsed JSON when `bytes` are JSON, otherwise text.
+pub fn body_value(bytes: &[u8]) -> JsonValue {
+    serde_json::from_slice::<JsonValue>(bytes).unwrap_or_else(|_| {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => JsonValue::String(text.to_string()),
+            Err(_) => JsonValue::String(format!("<{} bytes>", bytes.len())),
+        }
+    })
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HttpResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    pub body: JsonValue,
+}
+
+impl HttpResponse {
+    pub fn success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Stands in for the `send_payload` output the pack would have returned.
+    pub fn to_output(&self) -> JsonValue {
+        json!({
+            "ok": self.success(),
+            "status": self.status,
+            "headers": self.headers,
+            "body": self.body,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Interaction {
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    pub body: JsonValue,
+    pub response: HttpResponse,
+    pub recorded_at: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Cassette {
+    #[serde(default)]
+    pub interactions: Vec<Interaction>,
+}
+
+struct LoadedCassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    cassette: Cassette,
+    /// Replay consumes recordings in order, so repeated identical sends get
+    /// successive responses.
+    used: Vec<bool>,
+}
+
+pub struct HttpTransport {
+    kind: HttpTransportKind,
+    agent: Option<ureq::Agent>,
+    cassette: Option<LoadedCassette>,
+}
+
+impl HttpTransport {
+    pub fn new(config: &DemoHttpConfig) -> anyhow::Result<Self> {
+        let cassette = match &config.cassette {
+            Some(cassette) => {
+                let loaded = match runtime_state::read_json::<Cassette>(&cassette.path)
+                    .with_context(|| format!("read cassette {}", cassette.path.display()))?
+                {
+                    Some(loaded) => loaded,
+                    None if cassette.mode == CassetteMode::Replay => {
+                        return Err(anyhow!(
+                            "cassette {} does not exist; record it first",
+                            cassette.path.display()
+                        ));
+                    }
+                    None => Cassette::default(),
+                };
+                Some(LoadedCassette {
+                    path: cassette.path.clone(),
+                    mode: cassette.mode,
+                    used: vec![false; loaded.interactions.len()],
+                    cassette: loaded,
+                })
+            }
+            None => None,
+        };
+        let replaying = cassette
+            .as_ref()
+            .is_some_and(|cassette| cassette.mode == CassetteMode::Replay);
+        let kind = if cassette.is_some() {
+            HttpTransportKind::Operator
+        } else {
+            config.transport
+        };
+        let agent = if kind == HttpTransportKind::Operator && !replaying {
+            Some(build_agent(config)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            kind,
+            agent,
+            cassette,
+        })
+    }
+
+    /// `false` leaves sending to the pack's `send_payload`.
+    pub fn operator_managed(&self) -> bool {
+        self.kind == HttpTransportKind::Operator
+    }
+
+    pub fn replaying(&self) -> bool {
+        self.cassette
+            .as_ref()
+            .is_some_and(|cassette| cassette.mode == CassetteMode::Replay)
+    }
+
+    /// Performs (or replays) `request`; in record mode the exchange is appended to
+    /// the cassette.
+    pub fn execute(&mut self, request: &HttpRequest) -> anyhow::Result<HttpResponse> {
+        let url = request
+            .url
+            .as_deref()
+            .ok_or_else(|| anyhow!("the encoded payload does not declare a request URL"))?;
+        let masked_url = redaction::scrub_text(url);
+        let masked_body = redaction::scrub_json(&body_value(&request.body));
+        if let Some(cassette) = self
+            .cassette
+            .as_mut()
+            .filter(|cassette| cassette.mode == CassetteMode::Replay)
+        {
+            return cassette.replay(&request.method, &masked_url, &masked_body);
+        }
+        let agent = self
+            .agent
+            .as_ref()
+            .ok_or_else(|| anyhow!("operator HTTP transport is not enabled"))?;
+        let response = {
+            let _span = profile::span(Phase::Http, format_args!("{} {masked_url}", request.method));
+            send(agent, &request.method, url, &request.headers, &request.body)?
+        };
+        if let Some(cassette) = self.cassette.as_mut() {
+            let headers = redaction::scrub_json(&json!(request.headers));
+            cassette.cassette.interactions.push(Interaction {
+                method: request.method.clone(),
+                url: masked_url,
+                headers: serde_json::from_value(headers).unwrap_or_default(),
+                body: masked_body,
+                response: HttpResponse {
+                    status: response.status,
+                    headers: response.headers.clone(),
+                    body: redaction::scrub_json(&response.body),
+                },
+                recorded_at: chrono::Utc::now().to_rfc3339(),
+            });
+            cassette.used.push(true);
+            runtime_state::write_json(&cassette.path, &cassette.cassette)?;
+        }
+        Ok(response)
+    }
+}
+
+impl LoadedCassette {
+    fn replay(
+        &mut self,
+        method: &str,
+        url: &str,
+        body: &JsonValue,
+    ) -> anyhow::Result<HttpResponse> {
+        let unused = |index: &usize| !self.used[*index];
+        let same_target = |index: &usize| {
+            let interaction = &self.cassette.interactions[*index];
+            interaction.method.eq_ignore_ascii_case(method) && interaction.url == url
+        };
+        let candidates = (0..self.cassette.interactions.len())
+            .filter(unused)
+            .filter(same_target)
+            .collect::<Vec<_>>();
+        let index = candidates
+            .iter()
+            .copied()
+            .find(|index| self.cassette.interactions[*index].body == *body)
+            .or_else(|| candidates.first().copied())
+            .ok_or_else(|| {
+                anyhow!(
+                    "no recorded response for {method} {url} in cassette {}",
+                    self.path.display()
+                )
+            })?;
+        self.used[index] = true;
+        Ok(self.cassette.interactions[index].response.clone())
+    }
+}
+
+fn build_agent(config: &DemoHttpConfig) -> anyhow::Result<ureq::Agent> {
+    let proxy = match config.proxy.as_deref() {
+        Some(url) => {
+            Some(ureq::Proxy::new(url).with_context(|| format!("invalid proxy URL {url}"))?)
+        }
+        None => ureq::Proxy::try_from_env(),
+    };
+    let mut tls = ureq::tls::TlsConfig::builder().disable_verification(config.insecure_tls);
+    if let Some(path) = &config.ca_cert {
+        let pem = std::fs::read(path)
+            .with_context(|| format!("read CA certificate {}", path.display()))?;
+        let certs = ureq::tls::parse_pem(&pem)
+            .filter_map(|item| match item {
+                Ok(ureq::tls::PemItem::Certificate(cert)) => Some(cert.to_owned()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if certs.is_empty() {
+            return Err(anyhow!("no certificates found in {}", path.display()));
+        }
+        tls = tls.root_certs(ureq::tls::RootCerts::new_with_certs(&certs));
+    }
+    Ok(ureq::Agent::config_builder()
+        .proxy(proxy)
+        .tls_config(tls.build())
+        .timeout_global(Some(Duration::from_secs(config.timeout_secs.max(1))))
+        .http_status_as_error(false)
+        .build()
+        .new_agent())
+}
+
+fn send(
+    agent: &ureq::Agent,
+    method: &str,
+    url: &str,
+    headers: &BTreeMap<String, String>,
+    body: &[u8],
+) -> anyhow::Result<HttpResponse> {
+    let mut builder = ureq::http::Request::builder().method(method).uri(url);
+    for (name, value) in headers {
+        builder = builder.header(name, value);
+    }
+    let request = builder
+        .body(body.to_vec())
+        .with_context(|| format!("invalid request {method} {}", redaction::scrub_text(url)))?;
+    let mut response = agent
+        .run(request)
+        .map_err(|err| anyhow!("{method} {} failed: {err}", redaction::scrub_text(url)))?;
+    let headers = response
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| {
+            value
+                .to_str()
+                .ok()
+                .map(|value| (name.as_str().to_string(), value.to_string()))
+        })
+        .collect();
+    let bytes = response
+        .body_mut()
+        .read_to_vec()
+        .context("read response body")?;
+    Ok(HttpResponse {
+        status: response.status().as_u16(),
+        headers,
+        body: body_value(&bytes),
+    })
+}
+
+/// The bundle's `http:` settings with relative paths resolved against `bundle`.
+pub fn bundle_http_config(bundle: &Path) -> DemoHttpConfig {
+    let path = bundle.join("greentic.demo.yaml");
+    let mut http = if path.exists() {
+        crate::config::load_demo_config_or_default(&path).http
+    } else {
+        config::DemoHttpConfig::default()
+    };
+    if let Some(ca_cert) = http.ca_cert.as_mut()
+        && ca_cert.is_relative()
+    {
+        *ca_cert = bundle.join(&*ca_cert);
+    }
+    if let Some(cassette) = http.cassette.as_mut()
+        && cassette.path.is_relative()
+    {
+        cassette.path = bundle.join(&cassette.path);
+    }
+    http
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CassetteConfig;
+
+    #[test]
+    fn replay_matches_method_url_and_body_in_order() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("telegram.json");
+        let response = |text: &str| HttpResponse {
+            status: 200,
+            headers: BTreeMap::new(),
+            body: json!({"ok": true, "result": {"text": text}}),
+        };
+        let interaction = |body: JsonValue, text: &str| Interaction {
+            method: "POST".to_string(),
+            url: "https://api.example.test/send".to_string(),
+            headers: BTreeMap::new(),
+            body,
+            response: response(text),
+            recorded_at: "2024-07-01T10:00:00Z".to_string(),
+        };
+        runtime_state::write_json(
+            &path,
+            &Cassette {
+                interactions: vec![
+                    interaction(json!({"text": "first"}), "first"),
+                    interaction(json!({"text": "second"}), "second"),
+                ],
+            },
+        )?;
+        let mut transport = HttpTransport::new(&DemoHttpConfig {
+            cassette: Some(CassetteConfig {
+                path,
+                mode: CassetteMode::Replay,
+            }),
+            ..DemoHttpConfig::default()
+        })?;
+        assert!(transport.operator_managed() && transport.replaying());
+        let request = |text: &str| HttpRequest {
+            method: "POST".to_string(),
+            url: Some("https://api.example.test/send".to_string()),
+            headers: BTreeMap::new(),
+            body: serde_json::to_vec(&json!({"text": text})).unwrap(),
+        };
+        assert_eq!(transport.execute(&request("second"))?, response("second"));
+        // No exact match left: the next unused recording for the URL answers.
+        assert_eq!(transport.execute(&request("other"))?, response("first"));
+        assert!(transport.execute(&request("second")).is_err());
+        Ok(())
+    }
+}
@@ -1,10 +1,14 @@
 pub mod app;
+pub mod attachments;
 pub mod dlq;
 pub mod dto;
 pub mod egress;
+pub mod emulate;
+pub mod http_transport;
 pub mod ingress;
 pub mod provider;
 pub mod retry;
+pub mod scenario;
 pub mod tests;
 
 pub use dlq::*;
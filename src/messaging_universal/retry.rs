@@ -4,15 +4,20 @@ use chrono::Utc;
 use greentic_types::ChannelMessageEnvelope;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::path::Path;
 use std::time::Duration;
 use uuid::Uuid;
 
+use crate::config::{self, DemoEgressRetryConfig};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RetryPolicy {
     pub max_attempts: u32,
     pub base_delay_ms: u64,
     pub max_delay_ms: u64,
     pub jitter_ms: u64,
+    #[serde(default)]
+    pub retry_on: Vec<String>,
 }
 
 impl Default for RetryPolicy {
@@ -22,11 +27,46 @@ impl Default for RetryPolicy {
             base_delay_ms: 500,
             max_delay_ms: 30_000,
             jitter_ms: 250,
+            retry_on: Vec::new(),
+        }
+    }
+}
+
+impl From<&DemoEgressRetryConfig> for RetryPolicy {
+    fn from(config: &DemoEgressRetryConfig) -> Self {
+        Self {
+            max_attempts: config.max_attempts.max(1),
+            base_delay_ms: config.base_delay_ms,
+            max_delay_ms: config.max_delay_ms,
+            jitter_ms: config.jitter_ms,
+            retry_on: config.retry_on.clone(),
         }
     }
 }
 
 impl RetryPolicy {
+    /// Policy from `services.egress.retry` in the bundle's `greentic.demo.yaml`,
+    /// with `retries` (if given) overriding the attempt budget.
+    pub fn for_bundle(bundle: &Path, retries: Option<u32>) -> Self {
+        let config_path = bundle.join("greentic.demo.yaml");
+        let mut policy = if config_path.exists() {
+            config::load_demo_config(&config_path)
+                .map(|config| RetryPolicy::from(&config.services.egress.retry))
+                .unwrap_or_else(|_| RetryPolicy::from(&DemoEgressRetryConfig::default()))
+        } else {
+            RetryPolicy::from(&DemoEgressRetryConfig::default())
+        };
+        if let Some(retries) = retries {
+            policy.max_attempts = retries.saturating_add(1);
+        }
+        policy
+    }
+
+    /// A failure is retried when the provider flags it retryable or its code is listed in `retry_on`.
+    pub fn is_retryable(&self, code: &str, flagged_retryable: bool) -> bool {
+        flagged_retryable || self.retry_on.iter().any(|candidate| candidate == code)
+    }
+
     pub fn backoff_ms(&self, attempt: u32) -> u64 {
         let shift = attempt.saturating_sub(1).min(63);
         let multiplier = 1u64 << shift;
@@ -109,6 +149,29 @@ mod tests {
         assert_eq!(policy.backoff_ms(4), 400);
     }
 
+    #[test]
+    fn retryable_classification_uses_flag_or_code_list() {
+        let policy = RetryPolicy::from(&DemoEgressRetryConfig::default());
+        assert_eq!(policy.max_attempts, 1);
+        assert!(policy.is_retryable("node-error", true));
+        assert!(policy.is_retryable("rate-limited", false));
+        assert!(!policy.is_retryable("node-error", false));
+    }
+
+    #[test]
+    fn for_bundle_reads_config_and_applies_override() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("greentic.demo.yaml"),
+            "services:\n  egress:\n    retry:\n      max_attempts: 4\n      base_delay_ms: 10\n",
+        )
+        .unwrap();
+        let policy = RetryPolicy::for_bundle(dir.path(), None);
+        assert_eq!(policy.max_attempts, 4);
+        assert_eq!(policy.base_delay_ms, 10);
+        assert_eq!(RetryPolicy::for_bundle(dir.path(), Some(0)).max_attempts, 1);
+    }
+
     #[test]
     fn delay_injects_jitter() {
         let policy = RetryPolicy::default();
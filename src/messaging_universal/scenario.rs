@@ -0,0 +1,238 @@
+//! Multi-step ingress simulations for `demo ingress --scenario <FILE>`.
+//!
+//! A scenario is a YAML list of synthetic requests run in order against the same
+//! provider, each with optional expectations on the HTTP response and the number of
+//! events the provider emitted:
+//!
+//! ```yaml
+//! vars:
+//!   challenge: abc123
+//! steps:
+//!   - name: url verification
+//!     body_json: { type: url_verification, challenge: "{{challenge}}" }
+//!     expect: { status: 200, body_contains: "{{challenge}}", events: 0 }
+//!   - name: first message
+//!     headers: { X-Slack-Request-Timestamp: "{{timestamp}}" }
+//!     body_json: { type: event_callback, event: { type: message, text: hi } }
+//!     expect: { status: 200, events: 1 }
+//! ```
+//!
+//! `{{name}}` in paths, headers, query values, and bodies is replaced from `vars`,
+//! `--var name=value` (which wins), or the built-ins `uuid`, `timestamp`, and
+//! `timestamp_ms`.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Context, anyhow};
+use base64::Engine as _;
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+
+use super::dto::HttpOutV1;
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IngressScenario {
+    #[serde(default)]
+    pub vars: BTreeMap<String, String>,
+    pub steps: Vec<ScenarioStep>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioStep {
+    pub name: Option<String>,
+    /// Defaults to `POST`.
+    pub method: Option<String>,
+    /// Defaults to the path `demo ingress` would use without `--path`.
+    pub path: Option<String>,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub query: BTreeMap<String, String>,
+    /// Raw body; mutually exclusive with `body_json`.
+    pub body: Option<String>,
+    /// JSON body; string values are templated.
+    pub body_json: Option<JsonValue>,
+    pub expect: Option<StepExpect>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StepExpect {
+    pub status: Option<u16>,
+    pub body_contains: Option<String>,
+    /// Number of message envelopes the ingested request produced.
+    pub events: Option<usize>,
+}
+
+/// A step with every template filled in, ready to become an `HttpInV1`.
+#[derive(Clone, Debug)]
+pub struct RenderedStep {
+    pub name: String,
+    pub method: String,
+    pub path: Option<String>,
+    pub headers: Vec<(String, String)>,
+    pub query: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub expect: StepExpect,
+}
+
+pub fn load_scenario(path: &Path) -> anyhow::Result<IngressScenario> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("read ingress scenario {}", path.display()))?;
+    let scenario: IngressScenario = serde_yaml_bw::from_str(&raw)
+        .with_context(|| format!("parse ingress scenario {}", path.display()))?;
+    if scenario.steps.is_empty() {
+        return Err(anyhow!("ingress scenario {} has no steps", path.display()));
+    }
+    Ok(scenario)
+}
+
+impl IngressScenario {
+    /// Variables for rendering: built-ins, then the file's `vars`, then `overrides`.
+    pub fn variables(&self, overrides: &[(String, String)]) -> BTreeMap<String, String> {
+        let now = chrono::Utc::now();
+        let mut vars = BTreeMap::from([
+            ("uuid".to_string(), uuid::Uuid::new_v4().to_string()),
+            ("timestamp".to_string(), now.timestamp().to_string()),
+            (
+                "timestamp_ms".to_string(),
+                now.timestamp_millis().to_string(),
+            ),
+        ]);
+        vars.extend(self.vars.clone());
+        vars.extend(overrides.iter().cloned());
+        vars
+    }
+}
+
+impl ScenarioStep {
+    pub fn render(
+        &self,
+        index: usize,
+        vars: &BTreeMap<String, String>,
+    ) -> anyhow::Result<RenderedStep> {
+        let name = self
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("step {}", index + 1));
+        let body = match (&self.body, &self.body_json) {
+            (Some(_), Some(_)) => {
+                return Err(anyhow!("{name}: only one of body or body_json can be set"));
+            }
+            (Some(body), None) => render_template(body, vars)?.into_bytes(),
+            (None, Some(json)) => serde_json::to_vec(&render_json(json, vars)?)?,
+            (None, None) => Vec::new(),
+        };
+        let render_pairs = |pairs: &BTreeMap<String, String>| {
+            pairs
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), render_template(value, vars)?)))
+                .collect::<anyhow::Result<Vec<_>>>()
+        };
+        let mut headers = render_pairs(&self.headers)?;
+        if self.body_json.is_some()
+            && !headers
+                .iter()
+                .any(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+        {
+            headers.push(("Content-Type".to_string(), "application/json".to_string()));
+        }
+        Ok(RenderedStep {
+            method: self
+                .method
+                .as_deref()
+                .unwrap_or("POST")
+                .to_ascii_uppercase(),
+            path: self
+                .path
+                .as_deref()
+                .map(|path| render_template(path, vars))
+                .transpose()
+                .with_context(|| format!("{name}: path"))?,
+            headers,
+            query: render_pairs(&self.query)?,
+            body,
+            expect: StepExpect {
+                body_contains: self
+                    .expect
+                    .as_ref()
+                    .and_then(|expect| expect.body_contains.as_deref())
+                    .map(|needle| render_template(needle, vars))
+                    .transpose()?,
+                ..self.expect.clone().unwrap_or_default()
+            },
+            name,
+        })
+    }
+}
+
+impl StepExpect {
+    /// Every expectation the response missed, as a readable line each.
+    pub fn failures(&self, response: &HttpOutV1, event_count: usize) -> Vec<String> {
+        let mut failures = Vec::new();
+        if let Some(status) = self.status
+            && response.status != status
+        {
+            failures.push(format!("status {} (expected {status})", response.status));
+        }
+        if let Some(needle) = &self.body_contains {
+            let body = response
+                .body_b64
+                .as_deref()
+                .and_then(|b64| base64::engine::general_purpose::STANDARD.decode(b64).ok())
+                .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+                .unwrap_or_default();
+            if !body.contains(needle.as_str()) {
+                failures.push(format!("body does not contain {needle:?}"));
+            }
+        }
+        if let Some(events) = self.events
+            && event_count != events
+        {
+            failures.push(format!("{event_count} event(s) (expected {events})"));
+        }
+        failures
+    }
+}
+
+/// Replaces `{{name}}` (surrounding whitespace allowed); unknown names are an error.
+pub fn render_template(template: &str, vars: &BTreeMap<String, String>) -> anyhow::Result<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| anyhow!("unclosed '{{{{' in {template:?}"))?;
+        let name = after[..end].trim();
+        let value = vars
+            .get(name)
+            .ok_or_else(|| anyhow!("unknown scenario variable {name:?}"))?;
+        out.push_str(value);
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn render_json(value: &JsonValue, vars: &BTreeMap<String, String>) -> anyhow::Result<JsonValue> {
+    Ok(match value {
+        JsonValue::String(text) => JsonValue::String(render_template(text, vars)?),
+        JsonValue::Array(items) => JsonValue::Array(
+            items
+                .iter()
+                .map(|item| render_json(item, vars))
+                .collect::<anyhow::Result<_>>()?,
+        ),
+        JsonValue::Object(map) => JsonValue::Object(
+            map.iter()
+                .map(|(key, item)| Ok((key.clone(), render_json(item, vars)?)))
+                .collect::<anyhow::Result<_>>()?,
+        ),
+        other => other.clone(),
+    })
+}
@@ -1,7 +1,9 @@
 #[cfg(test)]
 mod suite {
     use crate::messaging_universal::dlq;
-    use crate::messaging_universal::dto::{HttpInV1, ProviderPayloadV1};
+    use crate::messaging_universal::dto::{HttpInV1, HttpOutV1, ProviderPayloadV1};
+    use crate::messaging_universal::emulate::{self, EmulateInput, EmulateSpec, Platform};
+    use crate::messaging_universal::scenario;
     use base64::Engine;
     use base64::engine::general_purpose::STANDARD;
     use serde_json::json;
@@ -98,4 +100,186 @@ mod suite {
         assert_eq!(parsed["job_id"], "job-xyz");
         Ok(())
     }
+
+    #[test]
+    fn dlq_records_round_trip_per_provider() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let envelope: greentic_types::ChannelMessageEnvelope = serde_json::from_value(json!({
+            "id": "env-1",
+            "tenant": {"env": "dev", "tenant": "demo", "tenant_id": "demo", "attempt": 0},
+            "channel": "telegram",
+            "session_id": "sess-1",
+            "text": "hi"
+        }))?;
+        let record = dlq::DlqRecord {
+            job_id: "job-1".to_string(),
+            provider: "telegram".to_string(),
+            tenant: "demo".to_string(),
+            team: None,
+            correlation_id: None,
+            attempt: 3,
+            max_attempts: 3,
+            enqueued_at: "2025-01-01T00:00:00Z".to_string(),
+            failed_at: "2025-01-01T00:00:05Z".to_string(),
+            failure: json!({"code": "node-error", "message": "boom"}),
+            envelope,
+            plan: None,
+            payload: None,
+            replay_count: 0,
+        };
+        let path = dlq::write_record(dir.path(), &record)?;
+        assert!(path.ends_with("dlq/telegram/job-1.json"));
+        let listed = dlq::list_records(dir.path(), Some("telegram"))?;
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].failure_message(), "boom");
+        assert_eq!(listed[0].envelope.text.as_deref(), Some("hi"));
+        assert!(dlq::list_records(dir.path(), Some("slack"))?.is_empty());
+        dlq::remove_record(dir.path(), &listed[0])?;
+        assert!(dlq::list_records(dir.path(), None)?.is_empty());
+        assert!(!dir.path().join("dlq").join("telegram").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn scenario_steps_render_templates_and_check_expectations() -> anyhow::Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("scenario.yaml");
+        std::fs::write(
+            &path,
+            r#"
+vars:
+  challenge: abc123
+steps:
+  - name: verify
+    path: /ingress/slack/{{ challenge }}
+    body_json: { type: url_verification, challenge: "{{challenge}}" }
+    expect: { status: 200, body_contains: "{{challenge}}", events: 0 }
+  - method: get
+    query: { hub.challenge: "{{who}}" }
+"#,
+        )?;
+        let loaded = scenario::load_scenario(&path)?;
+        let vars = loaded.variables(&[("who".to_string(), "me".to_string())]);
+        assert!(vars.contains_key("uuid"));
+
+        let first = loaded.steps[0].render(0, &vars)?;
+        assert_eq!(first.method, "POST");
+        assert_eq!(first.path.as_deref(), Some("/ingress/slack/abc123"));
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&first.body)?,
+            json!({"type": "url_verification", "challenge": "abc123"})
+        );
+        assert!(
+            first
+                .headers
+                .contains(&("Content-Type".to_string(), "application/json".to_string()))
+        );
+
+        let second = loaded.steps[1].render(1, &vars)?;
+        assert_eq!(second.name, "step 2");
+        assert_eq!(second.method, "GET");
+        assert_eq!(
+            second.query,
+            vec![("hub.challenge".to_string(), "me".to_string())]
+        );
+
+        let response = HttpOutV1 {
+            v: 1,
+            status: 401,
+            headers: Vec::new(),
+            body_b64: Some(STANDARD.encode("nope")),
+            events: Vec::new(),
+        };
+        assert_eq!(
+            first.expect.failures(&response, 1),
+            vec![
+                "status 401 (expected 200)".to_string(),
+                "body does not contain \"abc123\"".to_string(),
+                "1 event(s) (expected 0)".to_string(),
+            ]
+        );
+        assert!(second.expect.failures(&response, 1).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn scenario_templates_reject_unknown_variables() {
+        let vars = std::collections::BTreeMap::new();
+        let err = scenario::render_template("{{missing}}", &vars).unwrap_err();
+        assert!(err.to_string().contains("missing"));
+    }
+
+    #[test]
+    fn emulate_spec_defaults_to_message_and_rejects_unknown_events() {
+        assert_eq!(
+            "Telegram".parse::<EmulateSpec>().unwrap(),
+            EmulateSpec {
+                platform: Platform::Telegram,
+                event: "message".to_string(),
+            }
+        );
+        assert_eq!(
+            "teams:validation".parse::<EmulateSpec>().unwrap().platform,
+            Platform::MsGraph
+        );
+        assert!("slack:reaction".parse::<EmulateSpec>().is_err());
+        assert!("discord:message".parse::<EmulateSpec>().is_err());
+    }
+
+    #[test]
+    fn emulated_webhooks_are_signed_only_when_secrets_exist() -> anyhow::Result<()> {
+        // RFC 4231 test case 2.
+        assert_eq!(
+            emulate::hmac_sha256_hex("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        let input = EmulateInput {
+            text: Some("hi".to_string()),
+            ..EmulateInput::default()
+        };
+        let header = |request: &emulate::EmulatedRequest, name: &str| {
+            request
+                .headers
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.clone())
+        };
+
+        let slack = "slack:message".parse()?;
+        let unsigned = emulate::emulate(&slack, &input, |_| None)?;
+        assert!(header(&unsigned, "X-Slack-Signature").is_none());
+        let signed = emulate::emulate(&slack, &input, |key| {
+            (key == "slack_signing_secret").then(|| "s3cret".to_string())
+        })?;
+        let timestamp = header(&signed, "X-Slack-Request-Timestamp").unwrap();
+        assert_eq!(
+            header(&signed, "X-Slack-Signature").unwrap(),
+            emulate::slack_signature("s3cret", &timestamp, &signed.body)
+        );
+        let body: serde_json::Value = serde_json::from_slice(&signed.body)?;
+        assert_eq!(body["event"]["text"], "hi");
+
+        let whatsapp = emulate::emulate(&"whatsapp:message".parse()?, &input, |key| {
+            (key == "whatsapp_app_secret").then(|| "app".to_string())
+        })?;
+        assert_eq!(
+            header(&whatsapp, "X-Hub-Signature-256").unwrap(),
+            format!("sha256={}", emulate::hmac_sha256_hex("app", &whatsapp.body))
+        );
+
+        let telegram = emulate::emulate(&"telegram".parse()?, &input, |key| {
+            (key == "telegram_webhook_secret").then(|| "tok".to_string())
+        })?;
+        assert_eq!(
+            header(&telegram, "X-Telegram-Bot-Api-Secret-Token").as_deref(),
+            Some("tok")
+        );
+        let body: serde_json::Value = serde_json::from_slice(&telegram.body)?;
+        assert_eq!(body["message"]["text"], "hi");
+
+        let verify = emulate::emulate(&"whatsapp:verify".parse()?, &input, |_| None)?;
+        assert_eq!(verify.method, "GET");
+        assert!(verify.body.is_empty());
+        Ok(())
+    }
 }
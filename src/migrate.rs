@@ -0,0 +1,526 @@
+//! `migrate`: upgrades a bundle written by an older operator in place.
+//!
+//! The bundle layout version is recorded in `.greentic/bundle.json`; bundles from
+//! before that record are version 0. Each step checks one part of the bundle on its
+//! own, so a partly upgraded bundle is handled too:
+//!
+//! - resolved manifests below the current schema (or that no longer parse) are
+//!   resolved again and republished,
+//! - a dev secrets store at the old `.greentic/state/dev/` path moves to
+//!   `.greentic/dev/`, together with its `.meta.json` key and version metadata,
+//! - config keys the current schema rejects or ignores are listed for manual review;
+//!   they are not rewritten.
+//!
+//! Every file a step replaces is first copied to `state/migrations/<timestamp>/`.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, anyhow};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{self, ConfigFileKind, ConfigIssueSeverity};
+use crate::project::{self, RESOLVED_SCHEMA_VERSION};
+use crate::runtime_state::atomic_write;
+use crate::secrets_crypto;
+
+/// Layout version this operator writes.
+pub const BUNDLE_SCHEMA_VERSION: u32 = 1;
+
+const LEGACY_DEV_STORE: &str = ".greentic/state/dev/.dev.secrets.env";
+const DEV_STORE: &str = ".greentic/dev/.dev.secrets.env";
+/// Keys the wizard scaffold writes into `greentic.demo.yaml` for other tools.
+const SCAFFOLD_KEYS: [&str; 3] = ["version", "project_root", "bundle_name"];
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct BundleRecord {
+    schema_version: u32,
+    operator_version: String,
+    migrated_at: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MigrationReport {
+    pub bundle: PathBuf,
+    pub from_version: u32,
+    pub to_version: u32,
+    pub dry_run: bool,
+    /// Where replaced files were copied; unset when nothing was replaced.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backup: Option<PathBuf>,
+    pub steps: Vec<MigrationStep>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MigrationStep {
+    pub id: &'static str,
+    pub status: StepStatus,
+    pub details: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    UpToDate,
+    /// Would be applied; only in dry runs.
+    Pending,
+    Applied,
+    /// Needs a manual fix; see the step's details.
+    Manual,
+}
+
+impl StepStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StepStatus::UpToDate => "up to date",
+            StepStatus::Pending => "pending",
+            StepStatus::Applied => "applied",
+            StepStatus::Manual => "manual",
+        }
+    }
+}
+
+fn record_path(bundle: &Path) -> PathBuf {
+    bundle.join(".greentic").join("bundle.json")
+}
+
+/// The layout version recorded in the bundle; 0 when there is no record.
+pub fn bundle_schema_version(bundle: &Path) -> anyhow::Result<u32> {
+    let path = record_path(bundle);
+    if !path.exists() {
+        return Ok(0);
+    }
+    let raw = std::fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+    let record: BundleRecord =
+        serde_json::from_str(&raw).with_context(|| format!("parse {}", path.display()))?;
+    Ok(record.schema_version)
+}
+
+/// Upgrades `bundle` to [`BUNDLE_SCHEMA_VERSION`]; with `dry_run` only reports what
+/// would change.
+pub fn migrate(bundle: &Path, dry_run: bool) -> anyhow::Result<MigrationReport> {
+    if !bundle.join("greentic.demo.yaml").exists() {
+        return Err(anyhow!(
+            "{} is not a bundle (greentic.demo.yaml not found)",
+            bundle.display()
+        ));
+    }
+    let from_version = bundle_schema_version(bundle)?;
+    if from_version > BUNDLE_SCHEMA_VERSION {
+        return Err(anyhow!(
+            "bundle {} has layout version {from_version}; this operator supports up to {BUNDLE_SCHEMA_VERSION}, upgrade the operator instead",
+            bundle.display()
+        ));
+    }
+    let mut backup = Backup::new(bundle, dry_run);
+    let steps = vec![
+        migrate_resolved_manifests(bundle, &mut backup)?,
+        migrate_dev_store(bundle, dry_run)?,
+        check_config(bundle)?,
+        write_record(bundle, from_version, dry_run)?,
+    ];
+    Ok(MigrationReport {
+        bundle: bundle.to_path_buf(),
+        from_version,
+        to_version: BUNDLE_SCHEMA_VERSION,
+        dry_run,
+        backup: backup.used.then_some(backup.dir),
+        steps,
+    })
+}
+
+/// Copies files into `state/migrations/<timestamp>/` before they are replaced.
+struct Backup<'a> {
+    bundle: &'a Path,
+    dir: PathBuf,
+    dry_run: bool,
+    used: bool,
+}
+
+impl<'a> Backup<'a> {
+    fn new(bundle: &'a Path, dry_run: bool) -> Self {
+        let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        Self {
+            bundle,
+            dir: bundle.join("state").join("migrations").join(stamp),
+            dry_run,
+            used: false,
+        }
+    }
+
+    fn save(&mut self, path: &Path) -> anyhow::Result<()> {
+        if self.dry_run || !path.is_file() {
+            return Ok(());
+        }
+        let relative = path.strip_prefix(self.bundle).unwrap_or(path);
+        let target = self.dir.join(relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(path, &target)
+            .with_context(|| format!("back up {} to {}", path.display(), target.display()))?;
+        self.used = true;
+        Ok(())
+    }
+}
+
+fn migrate_resolved_manifests(
+    bundle: &Path,
+    backup: &mut Backup<'_>,
+) -> anyhow::Result<MigrationStep> {
+    let mut outdated = Vec::new();
+    let mut details = Vec::new();
+    for dir in ["state/resolved", "resolved"] {
+        let dir = bundle.join(dir);
+        if !dir.is_dir() {
+            continue;
+        }
+        let mut files = std::fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("yaml"))
+            .collect::<Vec<_>>();
+        files.sort();
+        for path in files {
+            let relative = relative(bundle, &path);
+            if let Some(version) = declared_schema(&path)
+                && version > RESOLVED_SCHEMA_VERSION
+            {
+                return Err(anyhow!(
+                    "{relative} has schema_version {version}; it was written by a newer operator"
+                ));
+            }
+            match project::load_resolved_manifest(&path) {
+                Ok(manifest) if manifest.schema_version >= RESOLVED_SCHEMA_VERSION => {}
+                Ok(manifest) => {
+                    details.push(format!(
+                        "{relative}: schema_version {} -> {RESOLVED_SCHEMA_VERSION}",
+                        manifest.schema_version
+                    ));
+                    outdated.push(path);
+                }
+                Err(err) => {
+                    details.push(format!("{relative}: unreadable ({err:#})"));
+                    outdated.push(path);
+                }
+            }
+        }
+    }
+    let step = |status, details| MigrationStep {
+        id: "resolved_manifests",
+        status,
+        details,
+    };
+    if outdated.is_empty() {
+        return Ok(step(StepStatus::UpToDate, details));
+    }
+    if backup.dry_run {
+        return Ok(step(StepStatus::Pending, details));
+    }
+    for path in &outdated {
+        backup.save(path)?;
+    }
+    project::sync_project(bundle)?;
+    // The resolver only writes manifests of tenants/teams that still exist.
+    let resolved = |path: &Path| {
+        project::load_resolved_manifest(path)
+            .ok()
+            .filter(|manifest| manifest.schema_version >= RESOLVED_SCHEMA_VERSION)
+    };
+    let state = bundle.join("state").join("resolved");
+    for path in &outdated {
+        let fresh = state.join(path.file_name().unwrap_or_default());
+        match resolved(&fresh) {
+            Some(manifest) if !path.starts_with(&state) => {
+                project::publish_resolved_manifest(
+                    bundle,
+                    &manifest.tenant,
+                    manifest.team.as_deref(),
+                )?;
+            }
+            Some(_) => {}
+            None => details.push(format!(
+                "{}: its tenant/team no longer exists; left as is",
+                relative(bundle, path)
+            )),
+        }
+    }
+    Ok(step(StepStatus::Applied, details))
+}
+
+/// The `schema_version` a manifest claims, read without the full manifest schema.
+fn declared_schema(path: &Path) -> Option<u32> {
+    #[derive(Deserialize)]
+    struct Probe {
+        schema_version: Option<u32>,
+    }
+    let raw = std::fs::read_to_string(path).ok()?;
+    serde_yaml_bw::from_str::<Probe>(&raw).ok()?.schema_version
+}
+
+fn migrate_dev_store(bundle: &Path, dry_run: bool) -> anyhow::Result<MigrationStep> {
+    let legacy = bundle.join(LEGACY_DEV_STORE);
+    let current = bundle.join(DEV_STORE);
+    let step = |status, details| MigrationStep {
+        id: "dev_secrets_store",
+        status,
+        details,
+    };
+    if !legacy.exists() {
+        return Ok(step(StepStatus::UpToDate, Vec::new()));
+    }
+    if current.exists() || secrets_crypto::meta_path(&current).exists() {
+        return Ok(step(
+            StepStatus::Manual,
+            vec![format!(
+                "both {LEGACY_DEV_STORE} and {DEV_STORE} exist; {DEV_STORE} is used, merge any secrets only the old store has and delete it"
+            )],
+        ));
+    }
+    // The sidecar holds the store's key metadata; the store is unreadable without it.
+    let legacy_meta = secrets_crypto::meta_path(&legacy);
+    let current_meta = secrets_crypto::meta_path(&current);
+    let mut details = vec![format!("{LEGACY_DEV_STORE} -> {DEV_STORE}")];
+    if legacy_meta.exists() {
+        details.push(format!(
+            "{} -> {}",
+            relative(bundle, &legacy_meta),
+            relative(bundle, &current_meta)
+        ));
+    }
+    if dry_run {
+        return Ok(step(StepStatus::Pending, details));
+    }
+    if let Some(parent) = current.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::rename(&legacy, &current)
+        .with_context(|| format!("move {} to {}", legacy.display(), current.display()))?;
+    if legacy_meta.exists()
+        && let Err(err) = std::fs::rename(&legacy_meta, &current_meta)
+    {
+        let _ = std::fs::rename(&current, &legacy);
+        return Err(err).with_context(|| {
+            format!(
+                "move {} to {}",
+                legacy_meta.display(),
+                current_meta.display()
+            )
+        });
+    }
+    Ok(step(StepStatus::Applied, details))
+}
+
+fn check_config(bundle: &Path) -> anyhow::Result<MigrationStep> {
+    let mut details = Vec::new();
+    for (file, kind) in [
+        ("greentic.demo.yaml", ConfigFileKind::Demo),
+        ("greentic.yaml", ConfigFileKind::Operator),
+    ] {
+        let path = bundle.join(file);
+        if !path.exists() {
+            continue;
+        }
+        for issue in config::validate_config_file(&path, kind)? {
+            let scaffold = matches!(kind, ConfigFileKind::Demo)
+                && issue.severity == ConfigIssueSeverity::Warning
+                && issue
+                    .key
+                    .as_deref()
+                    .is_some_and(|key| SCAFFOLD_KEYS.contains(&key));
+            if !scaffold {
+                details.push(format!(
+                    "{file}:{}: {}",
+                    issue.line.unwrap_or(0),
+                    issue.message
+                ));
+            }
+        }
+    }
+    let status = if details.is_empty() {
+        StepStatus::UpToDate
+    } else {
+        StepStatus::Manual
+    };
+    Ok(MigrationStep {
+        id: "config_keys",
+        status,
+        details,
+    })
+}
+
+fn write_record(bundle: &Path, from_version: u32, dry_run: bool) -> anyhow::Result<MigrationStep> {
+    let step = |status| MigrationStep {
+        id: "layout_version",
+        status,
+        details: vec![format!("{from_version} -> {BUNDLE_SCHEMA_VERSION}")],
+    };
+    if from_version == BUNDLE_SCHEMA_VERSION {
+        return Ok(MigrationStep {
+            id: "layout_version",
+            status: StepStatus::UpToDate,
+            details: Vec::new(),
+        });
+    }
+    if dry_run {
+        return Ok(step(StepStatus::Pending));
+    }
+    let record = BundleRecord {
+        schema_version: BUNDLE_SCHEMA_VERSION,
+        operator_version: env!("CARGO_PKG_VERSION").to_string(),
+        migrated_at: Utc::now().to_rfc3339(),
+    };
+    let path = record_path(bundle);
+    atomic_write(&path, &serde_json::to_vec_pretty(&record)?)
+        .with_context(|| format!("write {}", path.display()))?;
+    Ok(step(StepStatus::Applied))
+}
+
+fn relative(bundle: &Path, path: &Path) -> String {
+    path.strip_prefix(bundle)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+pub fn render_text(report: &MigrationReport) -> String {
+    let mut out = format!(
+        "{}: layout version {} -> {}{}\n",
+        report.bundle.display(),
+        report.from_version,
+        report.to_version,
+        if report.dry_run { " (dry run)" } else { "" }
+    );
+    for step in &report.steps {
+        out.push_str(&format!("  [{}] {}\n", step.status.as_str(), step.id));
+        for detail in &step.details {
+            out.push_str(&format!("      {detail}\n"));
+        }
+    }
+    if let Some(backup) = &report.backup {
+        out.push_str(&format!("  backup: {}\n", backup.display()));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    const V1_MANIFEST: &str = "version: '1'
+tenant: demo
+project_root: /old/bundle
+packs:
+  - packs/old.gtpack
+policy:
+  source:
+    tenant_gmap: tenants/demo/tenant.gmap
+  default: forbidden
+";
+
+    #[test]
+    fn upgrades_manifests_and_dev_store_with_a_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path();
+        fs::write(bundle.join("greentic.demo.yaml"), "tenant: demo\n").unwrap();
+        fs::create_dir_all(bundle.join("tenants/demo")).unwrap();
+        fs::write(bundle.join("tenants/demo/tenant.gmap"), "_ = forbidden\n").unwrap();
+        for dir in ["resolved", "state/resolved"] {
+            fs::create_dir_all(bundle.join(dir)).unwrap();
+            fs::write(bundle.join(dir).join("demo.yaml"), V1_MANIFEST).unwrap();
+        }
+        let legacy = bundle.join(LEGACY_DEV_STORE);
+        fs::create_dir_all(legacy.parent().unwrap()).unwrap();
+        fs::write(&legacy, "KEY=value\n").unwrap();
+
+        let preview = migrate(bundle, true).unwrap();
+        assert_eq!(preview.from_version, 0);
+        assert!(
+            preview
+                .steps
+                .iter()
+                .all(|step| step.status != StepStatus::Applied)
+        );
+        assert_eq!(preview.steps[0].status, StepStatus::Pending);
+        assert!(!bundle.join(DEV_STORE).exists());
+
+        let report = migrate(bundle, false).unwrap();
+        let statuses = report
+            .steps
+            .iter()
+            .map(|step| (step.id, step.status))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            [
+                ("resolved_manifests", StepStatus::Applied),
+                ("dev_secrets_store", StepStatus::Applied),
+                ("config_keys", StepStatus::UpToDate),
+                ("layout_version", StepStatus::Applied),
+            ]
+        );
+        let live = project::load_resolved_manifest(&bundle.join("resolved/demo.yaml")).unwrap();
+        assert_eq!(live.schema_version, RESOLVED_SCHEMA_VERSION);
+        let backup = report.backup.unwrap();
+        assert_eq!(
+            fs::read_to_string(backup.join("resolved/demo.yaml")).unwrap(),
+            V1_MANIFEST
+        );
+        assert!(bundle.join(DEV_STORE).exists());
+        assert_eq!(
+            bundle_schema_version(bundle).unwrap(),
+            BUNDLE_SCHEMA_VERSION
+        );
+
+        let again = migrate(bundle, false).unwrap();
+        assert!(
+            again
+                .steps
+                .iter()
+                .all(|step| step.status == StepStatus::UpToDate)
+        );
+        assert!(again.backup.is_none());
+    }
+
+    #[test]
+    fn encrypted_dev_stores_move_with_their_key_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path();
+        let legacy = bundle.join(LEGACY_DEV_STORE);
+        fs::create_dir_all(legacy.parent().unwrap()).unwrap();
+        fs::write(&legacy, "KEY=value\n").unwrap();
+        unsafe {
+            std::env::set_var("GREENTIC_TEST_MIGRATE_PASS", "correct horse");
+        }
+        let (cipher, info) =
+            secrets_crypto::DevStoreCipher::create(&secrets_crypto::KeySource::Passphrase {
+                env: "GREENTIC_TEST_MIGRATE_PASS".to_string(),
+            })
+            .unwrap();
+        let uri = "secrets://dev/demo/_/messaging-slack/slack_bot_token";
+        let sealed = cipher.seal(uri, b"xoxb-test").unwrap();
+        secrets_crypto::write_meta(
+            &legacy,
+            &secrets_crypto::DevStoreMeta {
+                encryption: Some(info),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let preview = migrate_dev_store(bundle, true).unwrap();
+        assert_eq!(preview.status, StepStatus::Pending);
+        assert_eq!(preview.details.len(), 2);
+        assert!(preview.details[1].ends_with(".dev.secrets.env.meta.json"));
+
+        let applied = migrate_dev_store(bundle, false).unwrap();
+        assert_eq!(applied.status, StepStatus::Applied);
+        let current = bundle.join(DEV_STORE);
+        assert!(!secrets_crypto::meta_path(&legacy).exists());
+        let moved = secrets_crypto::DevStoreCipher::for_store(&current)
+            .unwrap()
+            .expect("encrypted store keeps its key metadata");
+        assert_eq!(moved.open(uri, sealed.as_bytes()).unwrap(), b"xoxb-test");
+    }
+}
@@ -2,6 +2,7 @@ use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
+use crate::operator_error::tunnel_unavailable;
 use crate::runtime_state::{RuntimePaths, atomic_write};
 use crate::supervisor::{self, ServiceId, ServiceSpec};
 
@@ -25,6 +26,14 @@ pub fn start_tunnel(
     paths: &RuntimePaths,
     config: &NgrokConfig,
     log_path: &Path,
+) -> anyhow::Result<NgrokHandle> {
+    spawn_tunnel(paths, config, log_path).map_err(|err| tunnel_unavailable("ngrok", err))
+}
+
+fn spawn_tunnel(
+    paths: &RuntimePaths,
+    config: &NgrokConfig,
+    log_path: &Path,
 ) -> anyhow::Result<NgrokHandle> {
     let pid_path = paths.pid_path(SERVICE_ID);
     let url_path = public_url_path(paths);
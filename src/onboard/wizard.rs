@@ -810,15 +810,12 @@ fn find_provider_pack(
 #[allow(clippy::result_large_err)]
 fn parse_domain(body: &Value) -> Result<Domain, Response<Full<Bytes>>> {
     let domain_str = body["domain"].as_str().unwrap_or("messaging");
-    match domain_str {
-        "messaging" => Ok(Domain::Messaging),
-        "events" => Ok(Domain::Events),
-        "secrets" => Ok(Domain::Secrets),
-        _ => Err(error_response(
+    domains::parse_domain(domain_str).ok_or_else(|| {
+        error_response(
             StatusCode::BAD_REQUEST,
             format!("unknown domain: {domain_str}"),
-        )),
-    }
+        )
+    })
 }
 
 fn parse_mode(body: &Value) -> QaMode {
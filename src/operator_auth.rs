@@ -0,0 +1,310 @@
+//! Optional operator access control for shared demo bundles.
+//!
+//! A bundle with an `operators.yaml` restricts destructive demo commands to named
+//! operators. The caller identifies with `demo --as <name>` or by setting
+//! `GREENTIC_OPERATOR_TOKEN` to a token whose SHA-256 matches an operator's
+//! `token_sha256`. Every decision is recorded as an `access.check` audit event.
+//! Bundles without the file are unrestricted.
+//!
+//! ```yaml
+//! operators:
+//!   alice:
+//!     actions: ["*"]
+//!   bob:
+//!     actions: [setup, policy]
+//!     token_sha256: 5e884898da28047151d0e56f8dc6292773603d0d6aabbdd62a11ef721d1542d8
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::audit::{self, AuditEvent};
+use crate::operator_error::OperatorError;
+
+pub const OPERATORS_FILE: &str = "operators.yaml";
+pub const ENV_OPERATOR_TOKEN: &str = "GREENTIC_OPERATOR_TOKEN";
+const ALL_ACTIONS: &str = "*";
+
+/// Who this process acts as: `demo --as`, or the operator a token matched.
+static OPERATOR: OnceLock<String> = OnceLock::new();
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OperatorAction {
+    Setup,
+    /// `demo allow` / `demo forbid`.
+    Policy,
+    SecretsWrite,
+    SubscriptionsDelete,
+}
+
+impl OperatorAction {
+    pub const ALL: [OperatorAction; 4] = [
+        OperatorAction::Setup,
+        OperatorAction::Policy,
+        OperatorAction::SecretsWrite,
+        OperatorAction::SubscriptionsDelete,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OperatorAction::Setup => "setup",
+            OperatorAction::Policy => "policy",
+            OperatorAction::SecretsWrite => "secrets-write",
+            OperatorAction::SubscriptionsDelete => "subscriptions-delete",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct OperatorsFile {
+    #[serde(default)]
+    pub operators: BTreeMap<String, OperatorEntry>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct OperatorEntry {
+    /// Action names from [`OperatorAction::as_str`], or `"*"` for all of them.
+    #[serde(default)]
+    pub actions: Vec<String>,
+    /// Hex SHA-256 of the operator's token. When set, `--as` alone is not enough.
+    #[serde(default)]
+    pub token_sha256: Option<String>,
+}
+
+impl OperatorEntry {
+    fn allows(&self, action: OperatorAction) -> bool {
+        self.actions
+            .iter()
+            .any(|granted| granted == ALL_ACTIONS || granted == action.as_str())
+    }
+
+    fn token_matches(&self, token: &str) -> bool {
+        self.token_sha256
+            .as_deref()
+            .is_some_and(|expected| expected.eq_ignore_ascii_case(&sha256_hex(token)))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Decision {
+    Allowed {
+        operator: String,
+    },
+    Denied {
+        operator: Option<String>,
+        reason: String,
+    },
+}
+
+/// `demo --as <name>`: the operator identity for this process. Only the first
+/// identity sticks.
+pub fn set_operator(name: &str) {
+    let _ = OPERATOR.set(name.to_string());
+}
+
+pub fn current_operator() -> Option<&'static str> {
+    OPERATOR.get().map(String::as_str)
+}
+
+pub fn operators_path(bundle: &Path) -> PathBuf {
+    bundle.join(OPERATORS_FILE)
+}
+
+pub fn load_operators(bundle: &Path) -> Result<Option<OperatorsFile>> {
+    let path = operators_path(bundle);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    let file: OperatorsFile = serde_yaml_bw::from_str(&contents)
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+    for (name, entry) in &file.operators {
+        for granted in &entry.actions {
+            if granted != ALL_ACTIONS
+                && !OperatorAction::ALL
+                    .iter()
+                    .any(|action| action.as_str() == granted)
+            {
+                return Err(anyhow!(
+                    "{}: operator {name} has unknown action {granted:?}",
+                    path.display()
+                ));
+            }
+        }
+    }
+    Ok(Some(file))
+}
+
+/// Checks `action` against the bundle's operators.yaml and records the decision.
+/// `target` describes what is being changed (provider, gmap path, secret URI...).
+pub fn authorize(bundle: &Path, action: OperatorAction, target: &str) -> Result<()> {
+    let Some(file) = load_operators(bundle)? else {
+        return Ok(());
+    };
+    let token = std::env::var(ENV_OPERATOR_TOKEN).ok();
+    let decision = decide(&file, current_operator(), token.as_deref(), action);
+    if let Decision::Allowed { operator } = &decision {
+        // Token callers have no --as; later audit events should still name them.
+        set_operator(operator);
+    }
+    audit::record(bundle, access_event(action, target, &decision));
+    match decision {
+        Decision::Allowed { .. } => Ok(()),
+        Decision::Denied { reason, .. } => Err(OperatorError::AccessDenied {
+            action: action.as_str().to_string(),
+            detail: format!(
+                "{target}: {reason} (see {})",
+                operators_path(bundle).display()
+            ),
+        }
+        .into()),
+    }
+}
+
+pub fn decide(
+    file: &OperatorsFile,
+    as_name: Option<&str>,
+    token: Option<&str>,
+    action: OperatorAction,
+) -> Decision {
+    let token = token.map(str::trim).filter(|token| !token.is_empty());
+    let identity = match (as_name, token) {
+        (Some(name), token) => match file.operators.get(name) {
+            None => Err((None, format!("unknown operator {name:?}"))),
+            Some(entry)
+                if entry.token_sha256.is_some()
+                    && !token.is_some_and(|token| entry.token_matches(token)) =>
+            {
+                Err((
+                    Some(name.to_string()),
+                    format!("operator {name} requires a matching ${ENV_OPERATOR_TOKEN}"),
+                ))
+            }
+            Some(entry) => Ok((name.to_string(), entry)),
+        },
+        (None, Some(token)) => file
+            .operators
+            .iter()
+            .find(|(_, entry)| entry.token_matches(token))
+            .map(|(name, entry)| (name.clone(), entry))
+            .ok_or_else(|| (None, format!("${ENV_OPERATOR_TOKEN} matches no operator"))),
+        (None, None) => Err((
+            None,
+            format!("no operator given; pass demo --as <name> or set ${ENV_OPERATOR_TOKEN}"),
+        )),
+    };
+    match identity {
+        Ok((operator, entry)) if entry.allows(action) => Decision::Allowed { operator },
+        Ok((operator, _)) => Decision::Denied {
+            reason: format!("operator {operator} is not allowed to {}", action.as_str()),
+            operator: Some(operator),
+        },
+        Err((operator, reason)) => Decision::Denied { operator, reason },
+    }
+}
+
+fn access_event(action: OperatorAction, target: &str, decision: &Decision) -> AuditEvent {
+    let event = AuditEvent::new("access.check", target).detail(json!({
+        "requested": action.as_str(),
+    }));
+    match decision {
+        Decision::Allowed { operator } => AuditEvent {
+            actor: Some(operator.clone()),
+            ..event
+        },
+        Decision::Denied { operator, reason } => AuditEvent {
+            actor: operator.clone(),
+            ..event
+        }
+        .denied(reason),
+    }
+}
+
+fn sha256_hex(value: &str) -> String {
+    ring::digest::digest(&ring::digest::SHA256, value.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn operators() -> OperatorsFile {
+        serde_yaml_bw::from_str(&format!(
+            "operators:\n  alice:\n    actions: [\"*\"]\n  bob:\n    actions: [setup]\n    token_sha256: {}\n",
+            sha256_hex("bob-token")
+        ))
+        .expect("operators yaml")
+    }
+
+    #[test]
+    fn operators_are_identified_by_name_or_token() {
+        let file = operators();
+        assert_eq!(
+            decide(&file, Some("alice"), None, OperatorAction::SecretsWrite),
+            Decision::Allowed {
+                operator: "alice".to_string()
+            }
+        );
+        assert_eq!(
+            decide(&file, None, Some("bob-token"), OperatorAction::Setup),
+            Decision::Allowed {
+                operator: "bob".to_string()
+            }
+        );
+        assert!(matches!(
+            decide(&file, Some("bob"), None, OperatorAction::Setup),
+            Decision::Denied { .. }
+        ));
+        assert!(matches!(
+            decide(&file, None, None, OperatorAction::Setup),
+            Decision::Denied { operator: None, .. }
+        ));
+    }
+
+    #[test]
+    fn actions_outside_the_grant_are_denied_and_logged() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(
+            operators_path(dir.path()),
+            "operators:\n  carol:\n    actions: [policy]\n",
+        )?;
+        let file = load_operators(dir.path())?.expect("operators file");
+        let decision = decide(
+            &file,
+            Some("carol"),
+            None,
+            OperatorAction::SubscriptionsDelete,
+        );
+        assert!(matches!(
+            &decision,
+            Decision::Denied { operator: Some(name), .. } if name == "carol"
+        ));
+        audit::record(
+            dir.path(),
+            access_event(OperatorAction::SubscriptionsDelete, "binding-1", &decision),
+        );
+        let events = audit::read_events(dir.path())?;
+        assert_eq!(events[0].action, "access.check");
+        assert_eq!(events[0].actor.as_deref(), Some("carol"));
+        assert_eq!(events[0].outcome, audit::AuditOutcome::Denied);
+        assert_eq!(events[0].detail["requested"], "subscriptions-delete");
+
+        std::fs::write(
+            operators_path(dir.path()),
+            "operators:\n  dave:\n    actions: [deploy]\n",
+        )?;
+        assert!(load_operators(dir.path()).is_err());
+        Ok(())
+    }
+}
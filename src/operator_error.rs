@@ -0,0 +1,192 @@
+//! Typed failures for the causes scripts need to tell apart.
+//!
+//! Code paths raise an [`OperatorError`] through `anyhow` (`Err(OperatorError::… .into())`
+//! or as the root of a `.context(…)` chain); `main` looks it up with [`find`] and exits
+//! with [`OperatorError::exit_code`] instead of a generic failure. The [`code`] strings
+//! are stable and never translated.
+//!
+//! [`code`]: OperatorError::code
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Any failure without a more specific cause.
+pub const EXIT_FAILURE: i32 = 1;
+/// Invalid command line (clap's own exit code).
+pub const EXIT_USAGE: i32 = 2;
+/// A pack, manifest, or config file is missing or malformed.
+pub const EXIT_VALIDATION: i32 = 3;
+/// A provider op or flow ran and failed.
+pub const EXIT_FLOW_FAILED: i32 = 4;
+/// A secret the provider needs is not in the store.
+pub const EXIT_SECRET_MISSING: i32 = 5;
+/// A tunnel or other external service could not be reached.
+pub const EXIT_UNAVAILABLE: i32 = 6;
+/// operators.yaml denied the action.
+pub const EXIT_DENIED: i32 = 7;
+/// A `demo run --no-repl` flow stopped waiting for input instead of finishing.
+pub const EXIT_WAITING: i32 = 8;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OperatorError {
+    /// No pack matched `reference` (a pack file name, pack id, or provider filter).
+    PackNotFound {
+        reference: String,
+    },
+    ManifestInvalid {
+        path: PathBuf,
+        detail: String,
+    },
+    /// greentic.yaml or greentic.demo.yaml could not be parsed.
+    ConfigInvalid {
+        path: PathBuf,
+        detail: String,
+    },
+    SecretMissing {
+        uri: String,
+    },
+    FlowFailed {
+        provider: String,
+        op: String,
+        detail: String,
+    },
+    TunnelUnavailable {
+        tunnel: String,
+        detail: String,
+    },
+    AccessDenied {
+        action: String,
+        detail: String,
+    },
+    /// The provider's circuit breaker is open after repeated failures.
+    CircuitOpen {
+        provider: String,
+        until: String,
+    },
+    /// A flow run once (`demo run --no-repl`) blocked on a card or other input.
+    FlowWaiting {
+        pack: String,
+        flow: String,
+        reason: Option<String>,
+    },
+}
+
+impl OperatorError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            OperatorError::PackNotFound { .. } => "pack_not_found",
+            OperatorError::ManifestInvalid { .. } => "manifest_invalid",
+            OperatorError::ConfigInvalid { .. } => "config_invalid",
+            OperatorError::SecretMissing { .. } => "secret_missing",
+            OperatorError::FlowFailed { .. } => "flow_failed",
+            OperatorError::TunnelUnavailable { .. } => "tunnel_unavailable",
+            OperatorError::AccessDenied { .. } => "access_denied",
+            OperatorError::CircuitOpen { .. } => "circuit_open",
+            OperatorError::FlowWaiting { .. } => "flow_waiting",
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            OperatorError::PackNotFound { .. }
+            | OperatorError::ManifestInvalid { .. }
+            | OperatorError::ConfigInvalid { .. } => EXIT_VALIDATION,
+            OperatorError::FlowFailed { .. } => EXIT_FLOW_FAILED,
+            OperatorError::SecretMissing { .. } => EXIT_SECRET_MISSING,
+            OperatorError::TunnelUnavailable { .. } | OperatorError::CircuitOpen { .. } => {
+                EXIT_UNAVAILABLE
+            }
+            OperatorError::AccessDenied { .. } => EXIT_DENIED,
+            OperatorError::FlowWaiting { .. } => EXIT_WAITING,
+        }
+    }
+}
+
+impl fmt::Display for OperatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OperatorError::PackNotFound { reference } => {
+                write!(f, "no pack matched {reference}")
+            }
+            OperatorError::ManifestInvalid { path, detail } => {
+                write!(f, "invalid pack manifest in {}: {detail}", path.display())
+            }
+            OperatorError::ConfigInvalid { path, detail } => {
+                write!(f, "invalid config {}: {detail}", path.display())
+            }
+            OperatorError::SecretMissing { uri } => write!(f, "secret not found: {uri}"),
+            OperatorError::FlowFailed {
+                provider,
+                op,
+                detail,
+            } => write!(f, "{provider}.{op} failed: {detail}"),
+            OperatorError::TunnelUnavailable { tunnel, detail } => {
+                write!(f, "{tunnel} tunnel unavailable: {detail}")
+            }
+            OperatorError::AccessDenied { action, detail } => {
+                write!(f, "{action} denied: {detail}")
+            }
+            OperatorError::CircuitOpen { provider, until } => {
+                write!(f, "circuit open for {provider} until {until}")
+            }
+            OperatorError::FlowWaiting { pack, flow, reason } => {
+                write!(f, "{pack}.{flow} is waiting for input")?;
+                match reason {
+                    Some(reason) => write!(f, ": {reason}"),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+impl std::error::Error for OperatorError {}
+
+pub(crate) fn manifest_invalid(path: &Path, detail: impl Into<String>) -> anyhow::Error {
+    OperatorError::ManifestInvalid {
+        path: path.to_path_buf(),
+        detail: detail.into(),
+    }
+    .into()
+}
+
+pub(crate) fn tunnel_unavailable(tunnel: &str, err: anyhow::Error) -> anyhow::Error {
+    OperatorError::TunnelUnavailable {
+        tunnel: tunnel.to_string(),
+        detail: format!("{err:#}"),
+    }
+    .into()
+}
+
+/// The first [`OperatorError`] in `err`'s chain.
+pub fn find(err: &anyhow::Error) -> Option<&OperatorError> {
+    err.chain()
+        .find_map(|cause| cause.downcast_ref::<OperatorError>())
+}
+
+/// Process exit code for a failed command.
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    find(err).map_or(EXIT_FAILURE, OperatorError::exit_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn exit_code_finds_the_typed_cause_under_context() {
+        let err = Err::<(), _>(anyhow::Error::from(OperatorError::SecretMissing {
+            uri: "secrets://demo/default/telegram/bot_token".to_string(),
+        }))
+        .context("send_payload failed")
+        .unwrap_err();
+        assert_eq!(exit_code(&err), EXIT_SECRET_MISSING);
+        assert_eq!(find(&err).map(OperatorError::code), Some("secret_missing"));
+    }
+
+    #[test]
+    fn untyped_errors_exit_with_the_generic_code() {
+        assert_eq!(exit_code(&anyhow::anyhow!("boom")), EXIT_FAILURE);
+    }
+}
@@ -1,14 +1,26 @@
+//! CLI translations: the locales embedded from `i18n/operator_cli`, overlaid by
+//! `<locale>.json` / `<locale>.ftl` files found in [`LOCALE_DIR_ENV`] or `./locales`.
+//!
+//! A locale resolves key by key along its fallback chain (`pt-BR` → `pt` → `en`);
+//! at each step a file on disk wins over the embedded one.
+
 use anyhow::Context;
 use include_dir::{Dir, include_dir};
 use once_cell::sync::Lazy;
 use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::sync::RwLock;
 use unic_langid::LanguageIdentifier;
 
 pub type Map = BTreeMap<String, String>;
 
+/// Directory with extra locale files, searched before `./locales`.
+pub const LOCALE_DIR_ENV: &str = "GREENTIC_LOCALE_DIR";
+const DEFAULT_LOCALE_DIR: &str = "locales";
+
 static OPERATOR_CLI_I18N: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/i18n/operator_cli");
 static CURRENT_LOCALE: Lazy<RwLock<String>> = Lazy::new(|| RwLock::new(select_locale(None)));
+static LOADED: Lazy<RwLock<BTreeMap<String, Map>>> = Lazy::new(|| RwLock::new(BTreeMap::new()));
 
 pub fn select_locale(cli_locale: Option<&str>) -> String {
     let supported = supported_locales();
@@ -72,36 +84,175 @@ pub fn tr_for_locale(key: &str, fallback: &str, locale: &str) -> String {
     }
 }
 
+/// The merged map for `locale`, cached per process.
 pub fn load_cli(locale: &str) -> anyhow::Result<Map> {
-    for candidate in locale_candidates(locale) {
-        if let Some(file) = OPERATOR_CLI_I18N.get_file(&candidate) {
+    if let Some(map) = LOADED
+        .read()
+        .ok()
+        .and_then(|cache| cache.get(locale).cloned())
+    {
+        return Ok(map);
+    }
+    let map = load_chain(locale, &locale_dirs())?;
+    if let Ok(mut cache) = LOADED.write() {
+        cache.insert(locale.to_string(), map.clone());
+    }
+    Ok(map)
+}
+
+/// Only the keys `locale` translates itself, without the English base `load_cli`
+/// merges them onto.
+pub fn load_cli_translated(locale: &str) -> anyhow::Result<Map> {
+    let chain = fallback_chain(locale)
+        .into_iter()
+        .filter(|candidate| candidate != "en")
+        .collect::<Vec<_>>();
+    load_candidates(&chain, &locale_dirs())
+}
+
+fn load_chain(locale: &str, dirs: &[PathBuf]) -> anyhow::Result<Map> {
+    load_candidates(&fallback_chain(locale), dirs)
+}
+
+fn load_candidates(candidates: &[String], dirs: &[PathBuf]) -> anyhow::Result<Map> {
+    let mut merged = Map::new();
+    // Most general first, so more specific locales override key by key.
+    for candidate in candidates.iter().rev() {
+        if let Some(file) = OPERATOR_CLI_I18N.get_file(format!("{candidate}.json")) {
             let raw = file.contents_utf8().ok_or_else(|| {
-                anyhow::anyhow!("operator cli i18n file is not valid UTF-8: {candidate}")
+                anyhow::anyhow!("operator cli i18n file is not valid UTF-8: {candidate}.json")
             })?;
-            return serde_json::from_str(raw)
-                .with_context(|| format!("parse embedded operator cli i18n map {candidate}"));
+            let map: Map = serde_json::from_str(raw).with_context(|| {
+                format!("parse embedded operator cli i18n map {candidate}.json")
+            })?;
+            merged.extend(map);
+        }
+        for dir in dirs.iter().rev() {
+            if let Some(path) = disk_locale_file(dir, candidate) {
+                merged.extend(read_locale_file(&path)?);
+            }
         }
     }
-    Ok(Map::new())
+    Ok(merged)
 }
 
-fn locale_candidates(locale: &str) -> Vec<String> {
+/// `pt-BR` → `["pt-BR", "pt", "en"]`.
+pub fn fallback_chain(locale: &str) -> Vec<String> {
     let mut out = Vec::new();
     let mut push_candidate = |candidate: String| {
-        if !out.iter().any(|existing| existing == &candidate) {
+        if !candidate.is_empty() && !out.iter().any(|existing| existing == &candidate) {
             out.push(candidate);
         }
     };
-    let trimmed = locale.trim();
-    if !trimmed.is_empty() {
-        push_candidate(format!("{}.json", trimmed));
-        let primary = greentic_i18n::normalize_locale(trimmed);
-        push_candidate(format!("{}.json", primary));
+    if let Some(tag) = normalize_locale_tag(locale) {
+        let mut subtags = tag.split('-').collect::<Vec<_>>();
+        while !subtags.is_empty() {
+            push_candidate(subtags.join("-"));
+            subtags.pop();
+        }
     }
-    push_candidate("en.json".to_string());
+    push_candidate("en".to_string());
     out
 }
 
+/// Locale directories in lookup order: [`LOCALE_DIR_ENV`], then `./locales`.
+pub fn locale_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Ok(dir) = std::env::var(LOCALE_DIR_ENV)
+        && !dir.trim().is_empty()
+    {
+        dirs.push(PathBuf::from(dir));
+    }
+    let default_dir = PathBuf::from(DEFAULT_LOCALE_DIR);
+    if default_dir.is_dir() {
+        dirs.push(default_dir);
+    }
+    dirs
+}
+
+fn disk_locale_file(dir: &Path, locale: &str) -> Option<PathBuf> {
+    ["json", "ftl"]
+        .iter()
+        .map(|ext| dir.join(format!("{locale}.{ext}")))
+        .find(|path| path.is_file())
+}
+
+fn read_locale_file(path: &Path) -> anyhow::Result<Map> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("read locale file {}", path.display()))?;
+    if path.extension().is_some_and(|ext| ext == "ftl") {
+        return Ok(parse_ftl(&raw));
+    }
+    serde_json::from_str(&raw).with_context(|| format!("parse locale file {}", path.display()))
+}
+
+/// The `key = value` subset of Fluent: `#` comments, and indented lines continue
+/// the previous value. Keys may contain dots so they match the JSON maps.
+fn parse_ftl(raw: &str) -> Map {
+    let mut map = Map::new();
+    let mut current: Option<String> = None;
+    for line in raw.lines() {
+        if line.trim_start().starts_with('#') || line.trim().is_empty() {
+            current = None;
+            continue;
+        }
+        if line.starts_with([' ', '\t'])
+            && let Some(key) = &current
+            && let Some(value) = map.get_mut(key)
+        {
+            if !value.is_empty() {
+                value.push('\n');
+            }
+            value.push_str(line.trim());
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            let key = key.trim().to_string();
+            map.insert(key.clone(), value.trim().to_string());
+            current = Some(key);
+        }
+    }
+    map
+}
+
+/// Where a locale listed by [`available_locales`] comes from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LocaleSource {
+    Embedded,
+    File(PathBuf),
+}
+
+/// Every locale that can be selected, with the file that takes precedence for it.
+pub fn available_locales() -> Vec<(String, LocaleSource)> {
+    let mut out = BTreeMap::new();
+    for locale in embedded_locales() {
+        out.insert(locale, LocaleSource::Embedded);
+    }
+    for dir in locale_dirs().iter().rev() {
+        for (locale, path) in disk_locales(dir) {
+            out.insert(locale, LocaleSource::File(path));
+        }
+    }
+    out.into_iter().collect()
+}
+
+fn disk_locales(dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == "json" || ext == "ftl")
+        })
+        .filter_map(|path| {
+            let stem = path.file_stem()?.to_str()?.to_string();
+            Some((stem, path))
+        })
+        .collect()
+}
+
 fn normalize_locale_tag(raw: &str) -> Option<String> {
     let mut cleaned = raw.trim();
     if cleaned.is_empty() {
@@ -145,6 +296,13 @@ fn resolve_supported(candidate: &str, supported: &[String]) -> Option<String> {
 }
 
 fn supported_locales() -> Vec<String> {
+    available_locales()
+        .into_iter()
+        .map(|(locale, _)| locale)
+        .collect()
+}
+
+fn embedded_locales() -> Vec<String> {
     let mut out = OPERATOR_CLI_I18N
         .files()
         .filter_map(|file| {
@@ -173,6 +331,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn fallback_chain_drops_subtags_then_english() {
+        assert_eq!(fallback_chain("pt_BR.UTF-8"), vec!["pt-BR", "pt", "en"]);
+        assert_eq!(fallback_chain("en"), vec!["en"]);
+    }
+
+    #[test]
+    fn disk_locale_overrides_keys_and_falls_back_per_key() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("pt-BR.ftl"),
+            "# Brazilian overrides\ncli.common.ok = beleza\n",
+        )
+        .unwrap();
+        let map = load_chain("pt-BR", &[dir.path().to_path_buf()]).unwrap();
+        assert_eq!(map.get("cli.common.ok").map(String::as_str), Some("beleza"));
+        // Not in pt-BR.ftl: comes from the embedded pt map.
+        assert_eq!(
+            map.get("cli.common.success").map(String::as_str),
+            Some("sucesso")
+        );
+    }
+
+    #[test]
+    fn parse_ftl_joins_continuation_lines() {
+        let map = parse_ftl("cli.help =\n    first\n    second\nother = x\n");
+        assert_eq!(
+            map.get("cli.help").map(String::as_str),
+            Some("first\nsecond")
+        );
+        assert_eq!(map.get("other").map(String::as_str), Some("x"));
+    }
+
     #[test]
     fn normalize_locale_tag_handles_common_system_forms() {
         assert_eq!(
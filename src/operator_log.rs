@@ -10,6 +10,8 @@ use anyhow::Context;
 use chrono::Utc;
 use std::sync::OnceLock;
 
+use crate::redaction;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Level {
     Trace,
@@ -82,6 +84,17 @@ fn try_open_operator_log(log_dir: &Path) -> io::Result<File> {
         .open(&operator_path)
 }
 
+/// Whether [`init`] already ran in this process.
+pub fn is_initialized() -> bool {
+    LOGGER.get().is_some()
+}
+
+/// Whether a message at `level` would be written, so callers can skip
+/// building expensive messages.
+pub fn enabled(level: Level) -> bool {
+    LOGGER.get().is_some_and(|logger| level >= logger.min_level)
+}
+
 pub fn log(level: Level, target: &str, message: String) {
     let logger = match LOGGER.get() {
         Some(logger) => logger,
@@ -100,7 +113,7 @@ pub fn log(level: Level, target: &str, message: String) {
         "{timestamp} [{level:?}] {target} - {message}",
         level = level,
         target = target,
-        message = message
+        message = redaction::redact_text(&message)
     )
     .is_err()
     {
@@ -0,0 +1,370 @@
+//! `--profile`: wall-clock timing of a command's major phases.
+//!
+//! Instrumented code opens a [`span`] for the phase it is in and the guard records
+//! it when dropped. Nothing is recorded until [`enable`] is called, so a span costs
+//! one atomic load otherwise. Phases nest (discovery reads manifests, a flow reads
+//! secrets), so [`report`] charges each phase only its self time: its spans minus
+//! the spans that ran inside them on the same thread. [`write_chrome_trace`] writes
+//! every span in the Chrome trace event format, for chrome://tracing or Perfetto.
+
+use std::cell::Cell;
+use std::fmt::Display;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use serde_json::{Value as JsonValue, json};
+
+use crate::operator_i18n;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Phase {
+    /// Finding the bundle's provider packs.
+    Discovery,
+    /// Reading and decoding `manifest.cbor`.
+    Manifest,
+    /// Opening the secrets backend and looking secrets up.
+    Secrets,
+    /// Running a provider op or flow in the runner.
+    Flow,
+    /// Starting the runner: spawning the runner binary, or loading the pack into
+    /// the in-process runtime.
+    Spawn,
+    /// Encoding op payloads and results (JSON, CBOR envelopes).
+    Encode,
+    /// Run directories, transcripts and run records on disk.
+    Io,
+    /// A provider request made by the operator HTTP transport.
+    Http,
+}
+
+impl Phase {
+    const ALL: [Phase; 8] = [
+        Phase::Discovery,
+        Phase::Manifest,
+        Phase::Secrets,
+        Phase::Flow,
+        Phase::Spawn,
+        Phase::Encode,
+        Phase::Io,
+        Phase::Http,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Phase::Discovery => "discovery",
+            Phase::Manifest => "manifest",
+            Phase::Secrets => "secrets",
+            Phase::Flow => "flow",
+            Phase::Spawn => "spawn",
+            Phase::Encode => "encode",
+            Phase::Io => "io",
+            Phase::Http => "http",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Span {
+    phase: Phase,
+    name: String,
+    thread: u64,
+    /// Offset from [`enable`].
+    start: Duration,
+    duration: Duration,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static EPOCH: OnceLock<Instant> = OnceLock::new();
+static SPANS: Mutex<Vec<Span>> = Mutex::new(Vec::new());
+static NEXT_THREAD: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    static THREAD: Cell<u64> = const { Cell::new(0) };
+}
+
+fn thread_id() -> u64 {
+    THREAD.with(|id| {
+        if id.get() == 0 {
+            id.set(NEXT_THREAD.fetch_add(1, Ordering::Relaxed));
+        }
+        id.get()
+    })
+}
+
+/// Starts recording spans; wall time is measured from the first call.
+pub fn enable() {
+    EPOCH.get_or_init(Instant::now);
+    ENABLED.store(true, Ordering::Release);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Acquire)
+}
+
+/// Records the time until it is dropped.
+#[must_use]
+pub struct SpanGuard(Option<(Phase, String, Instant)>);
+
+/// Times `phase` until the guard is dropped. `name` labels the span in the trace
+/// (a pack path, an op id, a URL) and is only formatted while profiling.
+pub fn span(phase: Phase, name: impl Display) -> SpanGuard {
+    if !enabled() {
+        return SpanGuard(None);
+    }
+    SpanGuard(Some((phase, name.to_string(), Instant::now())))
+}
+
+impl Drop for SpanGuard {
+    fn drop(&mut self) {
+        let Some((phase, name, started)) = self.0.take() else {
+            return;
+        };
+        let Some(epoch) = EPOCH.get() else {
+            return;
+        };
+        let span = Span {
+            phase,
+            name,
+            thread: thread_id(),
+            start: started.saturating_duration_since(*epoch),
+            duration: started.elapsed(),
+        };
+        SPANS
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .push(span);
+    }
+}
+
+fn recorded() -> Vec<Span> {
+    SPANS.lock().unwrap_or_else(|err| err.into_inner()).clone()
+}
+
+/// A position in the recorded spans, to time one slice of a command.
+#[derive(Clone, Copy, Debug)]
+pub struct Mark(usize);
+
+pub fn mark() -> Mark {
+    Mark(SPANS.lock().unwrap_or_else(|err| err.into_inner()).len())
+}
+
+/// Self time per phase of the spans recorded since `mark`, leaving out phases
+/// with no span.
+pub fn self_times_since(mark: Mark) -> Vec<(Phase, Duration)> {
+    let spans = SPANS.lock().unwrap_or_else(|err| err.into_inner());
+    let since = spans.get(mark.0..).unwrap_or_default();
+    totals(since)
+        .into_iter()
+        .filter(|total| total.calls > 0)
+        .map(|total| (total.phase, total.self_time))
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct PhaseTotal {
+    phase: Phase,
+    calls: usize,
+    total: Duration,
+    self_time: Duration,
+}
+
+/// Per-phase call counts, total time and self time.
+fn totals(spans: &[Span]) -> Vec<PhaseTotal> {
+    let mut order = (0..spans.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| {
+        (
+            spans[a].thread,
+            spans[a].start,
+            std::cmp::Reverse(spans[a].duration),
+        )
+            .cmp(&(
+                spans[b].thread,
+                spans[b].start,
+                std::cmp::Reverse(spans[b].duration),
+            ))
+    });
+    let mut nested = vec![Duration::ZERO; spans.len()];
+    let mut open: Vec<usize> = Vec::new();
+    for index in order {
+        let span = &spans[index];
+        while let Some(&top) = open.last() {
+            let parent = &spans[top];
+            if parent.thread == span.thread
+                && span.start + span.duration <= parent.start + parent.duration
+            {
+                break;
+            }
+            open.pop();
+        }
+        if let Some(&parent) = open.last() {
+            nested[parent] += span.duration;
+        }
+        open.push(index);
+    }
+    Phase::ALL
+        .iter()
+        .map(|&phase| {
+            let mut total = PhaseTotal {
+                phase,
+                calls: 0,
+                total: Duration::ZERO,
+                self_time: Duration::ZERO,
+            };
+            for (index, span) in spans.iter().enumerate() {
+                if span.phase == phase {
+                    total.calls += 1;
+                    total.total += span.duration;
+                    total.self_time += span.duration.saturating_sub(nested[index]);
+                }
+            }
+            total
+        })
+        .collect()
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.1}", duration.as_secs_f64() * 1000.0)
+}
+
+fn render(spans: &[Span], wall: Duration) -> String {
+    let totals = totals(spans);
+    let mut out = operator_i18n::trf(
+        "cli.profile.heading",
+        "Profile (wall time {} ms):",
+        &[&millis(wall)],
+    );
+    out.push_str(&format!(
+        "\n  {:<12} {:>6} {:>12} {:>12} {:>6}",
+        operator_i18n::tr("cli.profile.col_phase", "phase"),
+        operator_i18n::tr("cli.profile.col_calls", "calls"),
+        operator_i18n::tr("cli.profile.col_total", "total ms"),
+        operator_i18n::tr("cli.profile.col_self", "self ms"),
+        operator_i18n::tr("cli.profile.col_share", "share"),
+    ));
+    let share = |duration: Duration| {
+        if wall.is_zero() {
+            0.0
+        } else {
+            duration.as_secs_f64() * 100.0 / wall.as_secs_f64()
+        }
+    };
+    let mut attributed = Duration::ZERO;
+    for total in totals.iter().filter(|total| total.calls > 0) {
+        attributed += total.self_time;
+        out.push_str(&format!(
+            "\n  {:<12} {:>6} {:>12} {:>12} {:>5.0}%",
+            total.phase.as_str(),
+            total.calls,
+            millis(total.total),
+            millis(total.self_time),
+            share(total.self_time),
+        ));
+    }
+    let other = wall.saturating_sub(attributed);
+    out.push_str(&format!(
+        "\n  {:<12} {:>6} {:>12} {:>12} {:>5.0}%\n",
+        operator_i18n::tr("cli.profile.other", "other"),
+        "",
+        "",
+        millis(other),
+        share(other),
+    ));
+    out
+}
+
+/// The per-phase summary printed at the end of a profiled command.
+pub fn report() -> String {
+    let wall = EPOCH.get().map(Instant::elapsed).unwrap_or_default();
+    render(&recorded(), wall)
+}
+
+fn chrome_trace(spans: &[Span]) -> JsonValue {
+    let pid = std::process::id();
+    let mut events = vec![json!({
+        "name": "process_name",
+        "ph": "M",
+        "pid": pid,
+        "args": {"name": "greentic-operator"},
+    })];
+    events.extend(spans.iter().map(|span| {
+        json!({
+            "name": span.name,
+            "cat": span.phase.as_str(),
+            "ph": "X",
+            "ts": span.start.as_nanos() as f64 / 1000.0,
+            "dur": span.duration.as_nanos() as f64 / 1000.0,
+            "pid": pid,
+            "tid": span.thread,
+        })
+    }));
+    json!({"traceEvents": events, "displayTimeUnit": "ms"})
+}
+
+/// Writes every recorded span to `path` as a Chrome trace.
+pub fn write_chrome_trace(path: &Path) -> anyhow::Result<()> {
+    let trace = chrome_trace(&recorded());
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+    }
+    std::fs::write(path, serde_json::to_vec(&trace)?)
+        .with_context(|| format!("write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorded_span(phase: Phase, thread: u64, start_ms: u64, duration_ms: u64) -> Span {
+        Span {
+            phase,
+            name: phase.as_str().to_string(),
+            thread,
+            start: Duration::from_millis(start_ms),
+            duration: Duration::from_millis(duration_ms),
+        }
+    }
+
+    #[test]
+    fn nested_spans_count_toward_their_own_phase_only() {
+        let spans = [
+            recorded_span(Phase::Manifest, 1, 10, 30),
+            recorded_span(Phase::Discovery, 1, 0, 100),
+            recorded_span(Phase::Manifest, 1, 50, 20),
+            recorded_span(Phase::Flow, 1, 100, 200),
+            recorded_span(Phase::Secrets, 1, 120, 50),
+            // Same window on another thread: not nested in the flow.
+            recorded_span(Phase::Secrets, 2, 150, 40),
+        ];
+        let totals = totals(&spans);
+        let of = |phase: Phase| totals.iter().find(|total| total.phase == phase).unwrap();
+        assert_eq!(of(Phase::Discovery).self_time, Duration::from_millis(50));
+        assert_eq!(of(Phase::Discovery).total, Duration::from_millis(100));
+        assert_eq!(of(Phase::Manifest).calls, 2);
+        assert_eq!(of(Phase::Manifest).self_time, Duration::from_millis(50));
+        assert_eq!(of(Phase::Flow).self_time, Duration::from_millis(150));
+        assert_eq!(of(Phase::Secrets).self_time, Duration::from_millis(90));
+        assert_eq!(of(Phase::Http).calls, 0);
+
+        let table = render(&spans, Duration::from_millis(400));
+        assert!(table.contains("discovery"), "{table}");
+        assert!(!table.contains("http"), "{table}");
+    }
+
+    #[test]
+    fn chrome_trace_has_one_complete_event_per_span() {
+        let trace = chrome_trace(&[recorded_span(Phase::Http, 3, 2, 5)]);
+        let events = trace["traceEvents"].as_array().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1]["ph"], "X");
+        assert_eq!(events[1]["cat"], "http");
+        assert_eq!(events[1]["ts"], 2000.0);
+        assert_eq!(events[1]["dur"], 5000.0);
+        assert_eq!(events[1]["tid"], 3);
+    }
+}
@@ -0,0 +1,221 @@
+//! Progress reporting for long operations: bundle builds, pack resolution, and
+//! setup runs.
+//!
+//! Each phase draws one bar on stderr while it runs and prints a one-line summary
+//! when it finishes. Bars only draw when stderr is a terminal; otherwise every
+//! finished item prints a `[3/10] label ✓` line instead. `--quiet` hides both and
+//! keeps warnings and errors, and `--verbose` also prints the per-item details the
+//! default output leaves out.
+
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+use crate::operator_i18n;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+impl Verbosity {
+    pub fn from_flags(quiet: bool, verbose: bool) -> Self {
+        if quiet {
+            Verbosity::Quiet
+        } else if verbose {
+            Verbosity::Verbose
+        } else {
+            Verbosity::Normal
+        }
+    }
+}
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+/// Sets the verbosity for the rest of the process; commands call this once from
+/// their `--quiet`/`--verbose` flags.
+pub fn set_verbosity(verbosity: Verbosity) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+}
+
+pub fn verbosity() -> Verbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        2 => Verbosity::Verbose,
+        _ => Verbosity::Normal,
+    }
+}
+
+/// One long-running phase. Safe to share between worker threads.
+pub struct Phase {
+    label: String,
+    total: usize,
+    bar: Option<ProgressBar>,
+    done: AtomicUsize,
+    failed: AtomicUsize,
+    started: Instant,
+}
+
+impl Phase {
+    pub fn start(label: impl Into<String>, total: usize) -> Self {
+        let label = label.into();
+        let bar = (verbosity() != Verbosity::Quiet && std::io::stderr().is_terminal()).then(|| {
+            let bar = ProgressBar::new(total as u64);
+            bar.set_style(
+                ProgressStyle::with_template(
+                    "{spinner} {prefix} [{bar:30}] {pos}/{len} {wide_msg}",
+                )
+                .unwrap_or_else(|_| ProgressStyle::default_bar())
+                .progress_chars("=> "),
+            );
+            bar.set_prefix(label.clone());
+            bar.enable_steady_tick(Duration::from_millis(120));
+            bar
+        });
+        Self {
+            label,
+            total,
+            bar,
+            done: AtomicUsize::new(0),
+            failed: AtomicUsize::new(0),
+            started: Instant::now(),
+        }
+    }
+
+    /// Shows what the phase is working on now (bar only).
+    pub fn set_message(&self, message: impl Into<String>) {
+        if let Some(bar) = self.bar.as_ref() {
+            bar.set_message(message.into());
+        }
+    }
+
+    /// Counts one finished item.
+    pub fn advance(&self, item: &str, ok: bool) {
+        let done = self.done.fetch_add(1, Ordering::SeqCst) + 1;
+        if !ok {
+            self.failed.fetch_add(1, Ordering::SeqCst);
+        }
+        match self.bar.as_ref() {
+            Some(bar) => {
+                bar.inc(1);
+                if !ok {
+                    bar.suspend(|| eprintln!("{}", item_line(done, self.total, item, ok)));
+                }
+            }
+            None if verbosity() != Verbosity::Quiet => {
+                eprintln!("{}", item_line(done, self.total, item, ok));
+            }
+            None => {}
+        }
+    }
+
+    /// Prints a line that should be seen by default.
+    pub fn println(&self, line: impl AsRef<str>) {
+        if verbosity() != Verbosity::Quiet {
+            self.print(line.as_ref());
+        }
+    }
+
+    /// Prints a warning or failure detail to stderr, even under `--quiet`.
+    pub fn warn(&self, line: impl AsRef<str>) {
+        let line = line.as_ref();
+        match self.bar.as_ref() {
+            Some(bar) => bar.suspend(|| eprintln!("{line}")),
+            None => eprintln!("{line}"),
+        }
+    }
+
+    /// Prints a line only under `--verbose`.
+    pub fn detail(&self, line: impl AsRef<str>) {
+        if verbosity() == Verbosity::Verbose {
+            self.print(line.as_ref());
+        }
+    }
+
+    /// Prints to stderr above the bar from code that cannot borrow the phase, such
+    /// as a running flow's stream sink. Nothing is printed under `--quiet`.
+    pub fn live_printer(&self) -> impl Fn(&str) + Send + Sync + 'static {
+        let bar = self.bar.clone();
+        move |line| {
+            if verbosity() == Verbosity::Quiet {
+                return;
+            }
+            match bar.as_ref() {
+                Some(bar) => bar.suspend(|| eprintln!("{line}")),
+                None => eprintln!("{line}"),
+            }
+        }
+    }
+
+    fn print(&self, line: &str) {
+        match self.bar.as_ref() {
+            Some(bar) => bar.suspend(|| println!("{line}")),
+            None => println!("{line}"),
+        }
+    }
+
+    /// Clears the bar and prints the summary line.
+    pub fn finish(self) {
+        if let Some(bar) = self.bar.as_ref() {
+            bar.finish_and_clear();
+        }
+        if verbosity() == Verbosity::Quiet {
+            return;
+        }
+        let done = self.done.load(Ordering::SeqCst);
+        let failed = self.failed.load(Ordering::SeqCst);
+        let elapsed = format!("{:.1}s", self.started.elapsed().as_secs_f64());
+        let summary = if failed == 0 {
+            operator_i18n::trf(
+                "progress.summary_ok",
+                "✓ {}: {} done in {}",
+                &[&self.label, &done.to_string(), &elapsed],
+            )
+        } else {
+            operator_i18n::trf(
+                "progress.summary_failed",
+                "✗ {}: {} done, {} failed in {}",
+                &[
+                    &self.label,
+                    &(done - failed).to_string(),
+                    &failed.to_string(),
+                    &elapsed,
+                ],
+            )
+        };
+        eprintln!("{summary}");
+    }
+}
+
+fn item_line(done: usize, total: usize, item: &str, ok: bool) -> String {
+    let (key, fallback) = if ok {
+        ("progress.item_ok", "[{}/{}] {} ✓")
+    } else {
+        ("progress.item_failed", "[{}/{}] {} ✗")
+    };
+    operator_i18n::trf(
+        key,
+        fallback,
+        &[&done.to_string(), &total.to_string(), item],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_map_to_verbosity() {
+        assert_eq!(Verbosity::from_flags(true, false), Verbosity::Quiet);
+        assert_eq!(Verbosity::from_flags(false, true), Verbosity::Verbose);
+        assert_eq!(Verbosity::from_flags(false, false), Verbosity::Normal);
+        assert_eq!(
+            item_line(3, 10, "messaging-telegram", true),
+            "[3/10] messaging-telegram ✓"
+        );
+    }
+}
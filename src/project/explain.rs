@@ -0,0 +1,342 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use crate::domains;
+use crate::gmap::{self, GmapPath, GmapRule, Policy};
+
+#[derive(Debug, Serialize)]
+pub struct ResolveExplanation {
+    pub tenant: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub team: Option<String>,
+    pub target: String,
+    pub manifest: String,
+    pub manifest_found: bool,
+    /// Pack that provides `target`'s pack id, if any pack in the tree does.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pack: Option<PackSource>,
+    /// Every gmap rule matching `target`, tenant layer first.
+    pub rules: Vec<ExplainedRule>,
+    /// `team`, `tenant`, or `default` (no rule matched).
+    pub decided_by: String,
+    pub policy: String,
+    /// Listed in the manifest, flow declared by the pack, and allowed by the gmaps.
+    pub present: bool,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PackSource {
+    pub path: String,
+    pub pack_id: String,
+    pub flows: Vec<String>,
+    pub listed_in_manifest: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExplainedRule {
+    pub layer: String,
+    pub file: String,
+    pub line: usize,
+    pub rule: String,
+    pub policy: String,
+    pub winner: bool,
+}
+
+pub fn explain(
+    root: &Path,
+    tenant: &str,
+    team: Option<&str>,
+    target: &str,
+) -> anyhow::Result<ResolveExplanation> {
+    let target_path = gmap::parse_path(target, 0)?;
+    let manifest_name = match team {
+        Some(team) => format!("{tenant}.{team}.yaml"),
+        None => format!("{tenant}.yaml"),
+    };
+    let resolved_dir = if root.join("resolved").is_dir() {
+        root.join("resolved")
+    } else {
+        root.join("state").join("resolved")
+    };
+    let manifest_path = resolved_dir.join(&manifest_name);
+    let manifest = read_manifest(&manifest_path)?;
+    let listed = manifest.as_ref().map(listed_packs).unwrap_or_default();
+
+    let pack = target_path
+        .pack
+        .as_deref()
+        .and_then(|pack_id| find_pack(root, pack_id, &listed));
+
+    let tenant_gmap = root.join("tenants").join(tenant).join("tenant.gmap");
+    let tenant_rules = gmap::parse_file(&tenant_gmap)?;
+    let team_gmap = team.map(|team| {
+        root.join("tenants")
+            .join(tenant)
+            .join("teams")
+            .join(team)
+            .join("team.gmap")
+    });
+    let team_rules = match &team_gmap {
+        Some(path) => gmap::parse_file(path)?,
+        None => Vec::new(),
+    };
+    // Same precedence as eval_with_overlay: any team match shadows the tenant gmap.
+    let team_winner = gmap::winning_rule(&team_rules, &target_path);
+    let tenant_winner = gmap::winning_rule(&tenant_rules, &target_path);
+    let (decided_by, winner) = match (team_winner, tenant_winner) {
+        (Some(rule), _) => ("team", Some(rule)),
+        (None, Some(rule)) => ("tenant", Some(rule)),
+        (None, None) => ("default", None),
+    };
+    let mut rules = explain_layer(
+        "tenant",
+        root,
+        &tenant_gmap,
+        &tenant_rules,
+        &target_path,
+        winner,
+    );
+    if let Some(path) = &team_gmap {
+        rules.extend(explain_layer(
+            "team",
+            root,
+            path,
+            &team_rules,
+            &target_path,
+            winner,
+        ));
+    }
+    let policy = match winner {
+        Some(rule) => policy_label(&rule.policy).to_string(),
+        None => manifest
+            .as_ref()
+            .and_then(|manifest| manifest.pointer("/policy/default"))
+            .and_then(JsonValue::as_str)
+            .unwrap_or("forbidden")
+            .to_string(),
+    };
+
+    // Reasons pushed before the policy line are the ones that keep the entry out.
+    let mut reasons = Vec::new();
+    if manifest.is_none() {
+        reasons.push(format!(
+            "{} does not exist; run `greentic-operator dev sync` or rebuild the bundle",
+            relative(root, &manifest_path)
+        ));
+    }
+    match (&pack, &target_path.pack) {
+        (None, Some(pack_id)) => reasons.push(format!(
+            "no pack under packs/ or providers/ has id {pack_id}"
+        )),
+        (Some(pack), _) if !pack.listed_in_manifest && manifest.is_some() => {
+            reasons.push(format!("{} is not listed in {manifest_name}", pack.path))
+        }
+        (Some(pack), _) => {
+            if let Some(flow) = target_path.flow.as_deref().filter(|flow| *flow != "_")
+                && !pack.flows.is_empty()
+                && !pack.flows.iter().any(|declared| declared == flow)
+            {
+                reasons.push(format!(
+                    "pack {} does not declare flow {flow} (flows: {})",
+                    pack.pack_id,
+                    pack.flows.join(", ")
+                ));
+            }
+        }
+        (None, None) => {}
+    }
+    let present = reasons.is_empty() && policy == "public";
+    match winner {
+        Some(rule) => reasons.push(format!(
+            "{decided_by} gmap rule `{} = {}` (line {}) decides {policy}",
+            rule.path,
+            policy_label(&rule.policy),
+            rule.line
+        )),
+        None => reasons.push(format!(
+            "no gmap rule matches; the manifest default is {policy}"
+        )),
+    }
+
+    Ok(ResolveExplanation {
+        tenant: tenant.to_string(),
+        team: team.map(str::to_string),
+        target: target_path.to_string(),
+        manifest: relative(root, &manifest_path),
+        manifest_found: manifest.is_some(),
+        pack,
+        rules,
+        decided_by: decided_by.to_string(),
+        policy,
+        present,
+        reasons,
+    })
+}
+
+pub fn render_text(explanation: &ResolveExplanation) -> String {
+    let scope = match &explanation.team {
+        Some(team) => format!("{}.{team}", explanation.tenant),
+        None => explanation.tenant.clone(),
+    };
+    let mut out = format!(
+        "{} for {scope}: {}\n",
+        explanation.target,
+        if explanation.present {
+            "present"
+        } else {
+            "absent"
+        }
+    );
+    out.push_str(&format!(
+        "  manifest: {}{}\n",
+        explanation.manifest,
+        if explanation.manifest_found {
+            ""
+        } else {
+            " (missing)"
+        }
+    ));
+    if let Some(pack) = &explanation.pack {
+        out.push_str(&format!(
+            "  pack: {} from {}{}\n",
+            pack.pack_id,
+            pack.path,
+            if pack.listed_in_manifest {
+                ""
+            } else {
+                " (not in manifest)"
+            }
+        ));
+    }
+    for rule in &explanation.rules {
+        out.push_str(&format!(
+            "  {} {}:{} {} = {}{}\n",
+            if rule.winner { "*" } else { " " },
+            rule.file,
+            rule.line,
+            rule.rule,
+            rule.policy,
+            if rule.winner { " (winner)" } else { "" }
+        ));
+    }
+    out.push_str(&format!(
+        "  decided by: {} -> {}\n",
+        explanation.decided_by, explanation.policy
+    ));
+    for reason in &explanation.reasons {
+        out.push_str(&format!("  - {reason}\n"));
+    }
+    out
+}
+
+fn explain_layer(
+    layer: &str,
+    root: &Path,
+    file: &Path,
+    rules: &[GmapRule],
+    target: &GmapPath,
+    winner: Option<&GmapRule>,
+) -> Vec<ExplainedRule> {
+    rules
+        .iter()
+        .filter(|rule| gmap::rule_matches(rule, target))
+        .map(|rule| ExplainedRule {
+            layer: layer.to_string(),
+            file: relative(root, file),
+            line: rule.line,
+            rule: rule.path.to_string(),
+            policy: policy_label(&rule.policy).to_string(),
+            winner: winner.is_some_and(|winner| std::ptr::eq(winner, rule)),
+        })
+        .collect()
+}
+
+fn read_manifest(path: &Path) -> anyhow::Result<Option<JsonValue>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = std::fs::read_to_string(path)?;
+    Ok(Some(serde_yaml_bw::from_str(&contents)?))
+}
+
+/// `packs` plus every provider pack listed in a resolved manifest.
+fn listed_packs(manifest: &JsonValue) -> Vec<String> {
+    let packs = manifest
+        .get("packs")
+        .and_then(JsonValue::as_array)
+        .into_iter()
+        .flatten();
+    let providers = manifest
+        .get("providers")
+        .and_then(JsonValue::as_object)
+        .into_iter()
+        .flat_map(|domains| domains.values())
+        .filter_map(JsonValue::as_array)
+        .flatten();
+    // v1 lists paths; v2 lists packs with a `path` field.
+    packs
+        .chain(providers)
+        .filter_map(|entry| entry.as_str().or_else(|| entry.get("path")?.as_str()))
+        .map(str::to_string)
+        .collect()
+}
+
+fn find_pack(root: &Path, pack_id: &str, listed: &[String]) -> Option<PackSource> {
+    let mut candidates = Vec::new();
+    collect_pack_paths(&root.join("packs"), &mut candidates);
+    collect_pack_paths(&root.join("providers"), &mut candidates);
+    candidates.sort();
+    candidates.into_iter().find_map(|path| {
+        let meta = domains::read_pack_meta(&path).ok();
+        let stem = path.file_stem().and_then(|stem| stem.to_str());
+        let matches = match &meta {
+            Some(meta) => meta.pack_id == pack_id,
+            None => stem == Some(pack_id),
+        };
+        if !matches {
+            return None;
+        }
+        let rel = relative(root, &path);
+        Some(PackSource {
+            listed_in_manifest: listed.contains(&rel),
+            pack_id: pack_id.to_string(),
+            flows: meta.map(|meta| meta.entry_flows).unwrap_or_default(),
+            path: rel,
+        })
+    })
+}
+
+fn collect_pack_paths(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) == Some("gtpack") {
+            out.push(path);
+        } else if path.is_dir() {
+            if dir.ends_with("packs") {
+                out.push(path);
+            } else {
+                collect_pack_paths(&path, out);
+            }
+        }
+    }
+}
+
+pub(super) fn policy_label(policy: &Policy) -> &'static str {
+    match policy {
+        Policy::Public => "public",
+        Policy::Forbidden => "forbidden",
+    }
+}
+
+fn relative(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
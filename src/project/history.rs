@@ -0,0 +1,167 @@
+//! Publishing resolved manifests into `resolved/` and rolling them back.
+//!
+//! The resolver writes `state/resolved/<tenant>[.<team>].yaml`; publishing copies it
+//! over the live `resolved/` manifest with a temp file + rename, so a reader never
+//! sees a half-written file. The manifest being replaced is kept under
+//! `state/resolved/history/<tenant>[.<team>]/`, newest last, up to
+//! [`HISTORY_DEPTH`] entries. Rolling back restores the newest entry and drops it
+//! from the history, so repeated rollbacks walk further back.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, anyhow};
+use chrono::Utc;
+use serde::Serialize;
+
+use crate::runtime_state::atomic_write;
+
+/// Prior manifests kept per tenant/team.
+pub const HISTORY_DEPTH: usize = 10;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Rollback {
+    pub manifest: PathBuf,
+    /// The history entry that was restored.
+    pub restored: PathBuf,
+    /// History entries still available for further rollbacks.
+    pub remaining: usize,
+}
+
+fn manifest_filename(tenant: &str, team: Option<&str>) -> String {
+    match team {
+        Some(team) => format!("{tenant}.{team}.yaml"),
+        None => format!("{tenant}.yaml"),
+    }
+}
+
+fn history_dir(root: &Path, tenant: &str, team: Option<&str>) -> PathBuf {
+    let name = match team {
+        Some(team) => format!("{tenant}.{team}"),
+        None => tenant.to_string(),
+    };
+    root.join("state")
+        .join("resolved")
+        .join("history")
+        .join(name)
+}
+
+/// History entries for `tenant`/`team`, oldest first.
+fn list_history(root: &Path, tenant: &str, team: Option<&str>) -> anyhow::Result<Vec<PathBuf>> {
+    let dir = history_dir(root, tenant, team);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut entries = fs::read_dir(&dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_file() && path.extension().and_then(|ext| ext.to_str()) == Some("yaml")
+        })
+        .collect::<Vec<_>>();
+    entries.sort();
+    Ok(entries)
+}
+
+/// Copies the resolver output over the live manifest, archiving the one it replaces.
+pub fn publish(root: &Path, tenant: &str, team: Option<&str>) -> anyhow::Result<PathBuf> {
+    let filename = manifest_filename(tenant, team);
+    let src = root.join("state").join("resolved").join(&filename);
+    if !src.exists() {
+        return Err(anyhow!("resolved manifest not found at {}", src.display()));
+    }
+    let next = fs::read(&src).with_context(|| format!("read {}", src.display()))?;
+    let dst = root.join("resolved").join(&filename);
+    if let Ok(current) = fs::read(&dst) {
+        if current == next {
+            return Ok(dst);
+        }
+        archive(root, tenant, team, &current)?;
+    }
+    atomic_write(&dst, &next).with_context(|| format!("write {}", dst.display()))?;
+    Ok(dst)
+}
+
+/// Restores the newest history entry as the live manifest.
+///
+/// The state copy is restored too, so a reload does not republish the manifest
+/// that was rolled back; the gmaps are left as they are.
+pub fn rollback(root: &Path, tenant: &str, team: Option<&str>) -> anyhow::Result<Rollback> {
+    let mut entries = list_history(root, tenant, team)?;
+    let Some(restored) = entries.pop() else {
+        return Err(anyhow!(
+            "no previous resolved manifest for {} in {}",
+            manifest_filename(tenant, team),
+            history_dir(root, tenant, team).display()
+        ));
+    };
+    let bytes = fs::read(&restored).with_context(|| format!("read {}", restored.display()))?;
+    let filename = manifest_filename(tenant, team);
+    let manifest = root.join("resolved").join(&filename);
+    atomic_write(&manifest, &bytes).with_context(|| format!("write {}", manifest.display()))?;
+    let state = root.join("state").join("resolved").join(&filename);
+    atomic_write(&state, &bytes).with_context(|| format!("write {}", state.display()))?;
+    fs::remove_file(&restored).with_context(|| format!("remove {}", restored.display()))?;
+    Ok(Rollback {
+        manifest,
+        restored,
+        remaining: entries.len(),
+    })
+}
+
+fn archive(root: &Path, tenant: &str, team: Option<&str>, bytes: &[u8]) -> anyhow::Result<()> {
+    let dir = history_dir(root, tenant, team);
+    let stamp = Utc::now().format("%Y%m%dT%H%M%S%.6fZ").to_string();
+    // The counter keeps names unique and sortable within one timestamp.
+    let mut n = 0;
+    let mut path = dir.join(format!("{stamp}-{n:03}.yaml"));
+    while path.exists() {
+        n += 1;
+        path = dir.join(format!("{stamp}-{n:03}.yaml"));
+    }
+    atomic_write(&path, bytes).with_context(|| format!("write {}", path.display()))?;
+    let entries = list_history(root, tenant, team)?;
+    let excess = entries.len().saturating_sub(HISTORY_DEPTH);
+    for old in &entries[..excess] {
+        let _ = fs::remove_file(old);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn publish_keeps_history_and_rollback_walks_it_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let state = root.join("state").join("resolved").join("acme.yaml");
+        let live = root.join("resolved").join("acme.yaml");
+        fs::create_dir_all(state.parent().unwrap()).unwrap();
+
+        for version in ["v1", "v2", "v2", "v3"] {
+            fs::write(&state, version).unwrap();
+            publish(root, "acme", None).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&live).unwrap(), "v3");
+        // Republishing an unchanged manifest adds nothing.
+        assert_eq!(list_history(root, "acme", None).unwrap().len(), 2);
+
+        let first = rollback(root, "acme", None).unwrap();
+        assert_eq!(fs::read_to_string(&live).unwrap(), "v2");
+        assert_eq!(fs::read_to_string(&state).unwrap(), "v2");
+        assert_eq!(first.remaining, 1);
+        rollback(root, "acme", None).unwrap();
+        assert_eq!(fs::read_to_string(&live).unwrap(), "v1");
+        assert!(rollback(root, "acme", None).is_err());
+
+        for n in 0..HISTORY_DEPTH + 3 {
+            fs::write(&state, format!("n{n}")).unwrap();
+            publish(root, "acme", None).unwrap();
+        }
+        assert_eq!(
+            list_history(root, "acme", None).unwrap().len(),
+            HISTORY_DEPTH
+        );
+    }
+}
@@ -1,10 +1,21 @@
+mod explain;
+mod history;
 mod layout;
+mod preview;
 mod resolve;
 mod scan;
 mod tenants;
 
 use std::path::Path;
 
+pub use explain::{ExplainedRule, PackSource, ResolveExplanation};
+pub use history::{HISTORY_DEPTH, Rollback};
+pub use preview::{FlowChange, ManifestPreview, PolicyPreview};
+pub use resolve::SCHEMA_VERSION as RESOLVED_SCHEMA_VERSION;
+pub use resolve::{
+    PolicySection, PolicySource, ResolvedFlow, ResolvedManifest, ResolvedOffer, ResolvedPack,
+    ResolvedPackEntry,
+};
 pub use scan::ScanFormat;
 
 pub fn init_project(root: &Path) -> anyhow::Result<()> {
@@ -20,6 +31,63 @@ pub fn sync_project(root: &Path) -> anyhow::Result<()> {
     resolve::resolve(root)
 }
 
+/// Atomically copies `state/resolved/<tenant>[.<team>].yaml` over the live
+/// `resolved/` manifest, keeping the replaced one in the manifest history.
+pub fn publish_resolved_manifest(
+    root: &Path,
+    tenant: &str,
+    team: Option<&str>,
+) -> anyhow::Result<std::path::PathBuf> {
+    history::publish(root, tenant, team)
+}
+
+/// Restores the previously published manifest for `tenant`/`team`.
+pub fn rollback_resolved_manifest(
+    root: &Path,
+    tenant: &str,
+    team: Option<&str>,
+) -> anyhow::Result<Rollback> {
+    history::rollback(root, tenant, team)
+}
+
+/// What `demo allow`/`forbid` would change for `rule_path`, without writing anything.
+pub fn preview_policy(
+    root: &Path,
+    tenant: &str,
+    team: Option<&str>,
+    rule_path: &str,
+    policy: crate::gmap::Policy,
+) -> anyhow::Result<PolicyPreview> {
+    preview::preview_policy(root, tenant, team, rule_path, policy)
+}
+
+pub fn render_policy_preview(preview: &PolicyPreview) -> String {
+    preview::render_text(preview)
+}
+
+/// Loads a resolved manifest; v1 manifests load with `schema_version: 1`.
+pub fn load_resolved_manifest(path: &Path) -> anyhow::Result<ResolvedManifest> {
+    resolve::load_manifest(path)
+}
+
+pub fn write_resolved_manifest(path: &Path, manifest: &ResolvedManifest) -> anyhow::Result<()> {
+    resolve::write_manifest(path, manifest)
+}
+
+/// Why `target` (`PACK[/FLOW[/NODE]]`) is or isn't usable from a resolved manifest.
+pub fn explain_resolution(
+    root: &Path,
+    tenant: &str,
+    team: Option<&str>,
+    target: &str,
+) -> anyhow::Result<ResolveExplanation> {
+    explain::explain(root, tenant, team, target)
+}
+
+pub fn render_resolution(explanation: &ResolveExplanation) -> String {
+    explain::render_text(explanation)
+}
+
 pub fn add_tenant(root: &Path, tenant: &str) -> anyhow::Result<()> {
     tenants::add_tenant(root, tenant)
 }
@@ -0,0 +1,303 @@
+//! Dry-run preview of a gmap edit: what `demo allow`/`forbid` would write and how
+//! the resolved manifests would change, computed without touching any file.
+//!
+//! The edited gmap is resolved as an overlay (see [`GmapOverlay`]) and each
+//! resulting manifest is compared against the one in `state/resolved/`. Flows are
+//! reported when their allowed state flips; a flow missing from the current
+//! manifest counts as forbidden, like the resolver's default.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use super::explain::policy_label;
+use super::resolve::{self, GmapOverlay, ResolvedManifest, ResolvedPackEntry};
+use crate::gmap::{self, Policy};
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PolicyPreview {
+    /// The gmap that would be edited, relative to the bundle.
+    pub gmap: String,
+    /// The rule line that would be written.
+    pub rule: String,
+    /// The existing rule for the same path, with its line number.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaces: Option<String>,
+    pub manifests: Vec<ManifestPreview>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ManifestPreview {
+    /// `state/resolved/` file name.
+    pub manifest: String,
+    /// Whether the manifest exists today.
+    pub exists: bool,
+    pub flows: Vec<FlowChange>,
+    /// `path: before -> after` for every manifest value that would change.
+    pub changes: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct FlowChange {
+    /// `packs` or `providers/<domain>`.
+    pub source: String,
+    pub pack: String,
+    pub flow: String,
+    /// True when the flow becomes allowed, false when it becomes forbidden.
+    pub allowed: bool,
+}
+
+pub fn preview_policy(
+    root: &Path,
+    tenant: &str,
+    team: Option<&str>,
+    rule_path: &str,
+    policy: Policy,
+) -> anyhow::Result<PolicyPreview> {
+    let gmap_rel = match team {
+        Some(team) => format!("tenants/{tenant}/teams/{team}/team.gmap"),
+        None => format!("tenants/{tenant}/tenant.gmap"),
+    };
+    let gmap_path = root.join(&gmap_rel);
+    let current = if gmap_path.exists() {
+        std::fs::read_to_string(&gmap_path)?
+    } else {
+        String::new()
+    };
+    let target = gmap::parse_path(rule_path, 0)?;
+    let replaces = gmap::parse_str(&current)?
+        .into_iter()
+        .find(|rule| rule.path == target)
+        .map(|rule| {
+            format!(
+                "{} = {} (line {})",
+                rule.path,
+                policy_label(&rule.policy),
+                rule.line
+            )
+        });
+    let rule = format!("{rule_path} = {}", policy_label(&policy));
+    let updated = gmap::upsert_policy_str(&current, rule_path, policy)?;
+    let overlay = GmapOverlay {
+        path: &gmap_path,
+        contents: &updated,
+    };
+
+    let resolved_dir = root.join("state").join("resolved");
+    let mut manifests = Vec::new();
+    for (filename, after) in resolve::preview(root, tenant, team, &overlay)? {
+        let path = resolved_dir.join(&filename);
+        let before = if path.exists() {
+            Some(resolve::load_manifest(&path)?)
+        } else {
+            None
+        };
+        manifests.push(compare(filename, before.as_ref(), &after)?);
+    }
+    Ok(PolicyPreview {
+        gmap: gmap_rel,
+        rule,
+        replaces,
+        manifests,
+    })
+}
+
+fn compare(
+    manifest: String,
+    before: Option<&ResolvedManifest>,
+    after: &ResolvedManifest,
+) -> anyhow::Result<ManifestPreview> {
+    let old = before.map(flow_policies).unwrap_or_default();
+    let flows = flow_policies(after)
+        .into_iter()
+        .filter_map(|((source, pack, flow), policy)| {
+            let allowed = policy == "public";
+            let was_allowed = old
+                .get(&(source.clone(), pack.clone(), flow.clone()))
+                .is_some_and(|policy| policy == "public");
+            (allowed != was_allowed).then_some(FlowChange {
+                source,
+                pack,
+                flow,
+                allowed,
+            })
+        })
+        .collect();
+    let mut changes = Vec::new();
+    if let Some(before) = before {
+        // The bundle path as typed is not a change.
+        let value = |manifest: &ResolvedManifest| {
+            serde_json::to_value(manifest).map(|mut value| {
+                if let Some(map) = value.as_object_mut() {
+                    map.remove("project_root");
+                }
+                value
+            })
+        };
+        diff_values("", &value(before)?, &value(after)?, &mut changes);
+    }
+    Ok(ManifestPreview {
+        manifest,
+        exists: before.is_some(),
+        flows,
+        changes,
+    })
+}
+
+type FlowKey = (String, String, String);
+
+fn flow_policies(manifest: &ResolvedManifest) -> BTreeMap<FlowKey, String> {
+    let sources = manifest
+        .providers
+        .iter()
+        .map(|(domain, entries)| (format!("providers/{domain}"), entries))
+        .chain(std::iter::once(("packs".to_string(), &manifest.packs)));
+    let mut policies = BTreeMap::new();
+    for (source, entries) in sources {
+        for entry in entries {
+            let ResolvedPackEntry::Pack(pack) = entry else {
+                continue;
+            };
+            let name = pack.pack_id.clone().unwrap_or_else(|| pack.path.clone());
+            for flow in &pack.flows {
+                policies.insert(
+                    (source.clone(), name.clone(), flow.id.clone()),
+                    flow.policy.clone(),
+                );
+            }
+        }
+    }
+    policies
+}
+
+fn diff_values(path: &str, before: &JsonValue, after: &JsonValue, out: &mut Vec<String>) {
+    let child = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+    match (before, after) {
+        (JsonValue::Object(left), JsonValue::Object(right)) => {
+            let mut keys = left.keys().chain(right.keys()).collect::<Vec<_>>();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let null = JsonValue::Null;
+                diff_values(
+                    &child(key),
+                    left.get(key).unwrap_or(&null),
+                    right.get(key).unwrap_or(&null),
+                    out,
+                );
+            }
+        }
+        (JsonValue::Array(left), JsonValue::Array(right)) if left.len() == right.len() => {
+            for (index, (left, right)) in left.iter().zip(right).enumerate() {
+                diff_values(&child(&index.to_string()), left, right, out);
+            }
+        }
+        (left, right) if left != right => out.push(format!("{path}: {left} -> {right}")),
+        _ => {}
+    }
+}
+
+pub fn render_text(preview: &PolicyPreview) -> String {
+    let mut out = format!("{}: would write `{}`", preview.gmap, preview.rule);
+    if let Some(replaces) = &preview.replaces {
+        out.push_str(&format!(", replacing `{replaces}`"));
+    }
+    out.push('\n');
+    for manifest in &preview.manifests {
+        out.push_str(&format!(
+            "state/resolved/{}{}:\n",
+            manifest.manifest,
+            if manifest.exists { "" } else { " (new)" }
+        ));
+        for (allowed, label) in [(true, "newly allowed"), (false, "newly forbidden")] {
+            let flows = manifest
+                .flows
+                .iter()
+                .filter(|flow| flow.allowed == allowed)
+                .collect::<Vec<_>>();
+            if flows.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("  {label}:\n"));
+            for flow in flows {
+                out.push_str(&format!(
+                    "    {} {}/{} ({})\n",
+                    if allowed { '+' } else { '-' },
+                    flow.pack,
+                    flow.flow,
+                    flow.source
+                ));
+            }
+        }
+        if manifest.changes.is_empty() {
+            if manifest.exists {
+                out.push_str("  no manifest changes\n");
+            }
+        } else {
+            out.push_str("  manifest changes:\n");
+            for change in &manifest.changes {
+                out.push_str(&format!("    ~ {change}\n"));
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(policy: &str) -> ResolvedManifest {
+        serde_yaml_bw::from_str(&format!(
+            "schema_version: 2
+version: '1'
+tenant: demo
+project_root: /bundle
+packs:
+  - path: packs/support.gtpack
+    pack_id: support
+    flows:
+      - id: main
+        policy: {policy}
+policy:
+  source:
+    tenant_gmap: tenants/demo/tenant.gmap
+  default: forbidden
+"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn reports_flipped_flows_and_changed_values() {
+        let preview = compare(
+            "demo.yaml".to_string(),
+            Some(&manifest("forbidden")),
+            &manifest("public"),
+        )
+        .unwrap();
+        assert_eq!(preview.flows.len(), 1);
+        assert!(preview.flows[0].allowed);
+        assert_eq!(preview.flows[0].pack, "support");
+        assert_eq!(
+            preview.changes,
+            vec![r#"packs.0.flows.0.policy: "forbidden" -> "public""#.to_string()]
+        );
+
+        let unchanged = compare(
+            "demo.yaml".to_string(),
+            Some(&manifest("public")),
+            &manifest("public"),
+        )
+        .unwrap();
+        assert!(unchanged.flows.is_empty() && unchanged.changes.is_empty());
+    }
+}
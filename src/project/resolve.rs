@@ -1,8 +1,23 @@
+//! The resolver: one manifest per tenant/team under `state/resolved/`.
+//!
+//! Schema v2 lists every pack with its digest, entry flows (each with the gmap
+//! decision for this tenant/team), and capability offers. v1 manifests, which only
+//! list pack paths, still load: [`ResolvedPackEntry::Path`] covers both.
+
 use std::collections::BTreeMap;
 use std::path::Path;
 
-use serde::Serialize;
+use anyhow::{Context, anyhow};
+use serde::{Deserialize, Serialize};
+
+use super::explain::policy_label;
+use crate::demo::diff::digest_path;
+use crate::domains;
+use crate::gmap::{self, GmapPath, GmapRule};
+use crate::offers::registry::load_pack_offers;
+use crate::operator_log;
 
+pub const SCHEMA_VERSION: u32 = 2;
 const VERSION: &str = "1";
 const DEFAULT_POLICY: &str = "forbidden";
 const ENV_PASSTHROUGH: [&str; 3] = [
@@ -11,30 +26,95 @@ const ENV_PASSTHROUGH: [&str; 3] = [
     "RUST_LOG",
 ];
 
-#[derive(Debug, Serialize)]
-struct ResolvedManifest {
-    version: String,
-    tenant: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    team: Option<String>,
-    project_root: String,
-    providers: BTreeMap<String, Vec<String>>,
-    packs: Vec<String>,
-    env_passthrough: Vec<String>,
-    policy: PolicySection,
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ResolvedManifest {
+    /// Absent in manifests written before v2.
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: u32,
+    pub version: String,
+    pub tenant: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team: Option<String>,
+    pub project_root: String,
+    #[serde(default)]
+    pub providers: BTreeMap<String, Vec<ResolvedPackEntry>>,
+    #[serde(default)]
+    pub packs: Vec<ResolvedPackEntry>,
+    #[serde(default)]
+    pub env_passthrough: Vec<String>,
+    pub policy: PolicySection,
+}
+
+/// A pack path (v1) or a pack with its metadata (v2).
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ResolvedPackEntry {
+    Path(String),
+    Pack(ResolvedPack),
+}
+
+impl ResolvedPackEntry {
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Path(path) => path,
+            Self::Pack(pack) => &pack.path,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ResolvedPack {
+    pub path: String,
+    /// Unset when the pack manifest could not be read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pack_id: Option<String>,
+    /// `sha256:<hex>` of the `.gtpack`, or of every file of a pack directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+    #[serde(default)]
+    pub flows: Vec<ResolvedFlow>,
+    #[serde(default)]
+    pub offers: Vec<ResolvedOffer>,
+}
+
+/// An entry flow and whether the tenant/team gmaps allow it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ResolvedFlow {
+    pub id: String,
+    pub policy: String,
+    /// `tenants/<tenant>/tenant.gmap:<line>` of the deciding rule; unset when the
+    /// manifest default applies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ResolvedOffer {
+    pub id: String,
+    pub kind: String,
+    pub provider_op: String,
+    pub priority: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stage: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PolicySection {
+    pub source: PolicySource,
+    pub default: String,
 }
 
-#[derive(Debug, Serialize)]
-struct PolicySection {
-    source: PolicySource,
-    default: String,
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PolicySource {
+    pub tenant_gmap: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team_gmap: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
-struct PolicySource {
-    tenant_gmap: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    team_gmap: Option<String>,
+fn legacy_schema_version() -> u32 {
+    1
 }
 
 #[derive(Debug)]
@@ -43,68 +123,175 @@ struct TenantEntry {
     teams: Vec<String>,
 }
 
-pub fn resolve(root: &Path) -> anyhow::Result<()> {
-    let providers = scan_providers(root)?;
-    let packs = scan_packs(root)?;
-    let tenants = scan_tenants(root)?;
-    let project_root = root.to_string_lossy().to_string();
+/// Tenant-independent facts about a pack; flows get their policy per tenant/team.
+struct ScannedPack {
+    path: String,
+    pack_id: Option<String>,
+    digest: Option<String>,
+    entry_flows: Vec<String>,
+    offers: Vec<ResolvedOffer>,
+}
+
+/// A gmap whose contents replace the file on disk while resolving.
+pub struct GmapOverlay<'a> {
+    pub path: &'a Path,
+    pub contents: &'a str,
+}
+
+impl GmapOverlay<'_> {
+    fn rules(overlay: Option<&Self>, path: &Path) -> anyhow::Result<Vec<GmapRule>> {
+        match overlay {
+            Some(overlay) if overlay.path == path => gmap::parse_str(overlay.contents),
+            _ => gmap::parse_file(path),
+        }
+    }
+}
 
+pub fn resolve(root: &Path) -> anyhow::Result<()> {
     let resolved_dir = root.join("state").join("resolved");
     std::fs::create_dir_all(&resolved_dir)?;
 
+    // Build every manifest before writing any, so a failed resolve leaves the
+    // previous set untouched rather than half-updated.
+    let manifests = build_manifests(root, None, None)?;
+    for (filename, manifest) in &manifests {
+        write_manifest(&resolved_dir.join(filename), manifest)?;
+    }
+
+    Ok(())
+}
+
+/// The manifests of `tenant` (only `team`'s, when given) as they would resolve
+/// with `overlay` in place of its gmap file, keyed by manifest file name. Nothing
+/// is written; a tenant or team that does not exist yet resolves as if it did.
+pub fn preview(
+    root: &Path,
+    tenant: &str,
+    team: Option<&str>,
+    overlay: &GmapOverlay<'_>,
+) -> anyhow::Result<Vec<(String, ResolvedManifest)>> {
+    build_manifests(root, Some(overlay), Some((tenant, team)))
+}
+
+fn build_manifests(
+    root: &Path,
+    overlay: Option<&GmapOverlay<'_>>,
+    only: Option<(&str, Option<&str>)>,
+) -> anyhow::Result<Vec<(String, ResolvedManifest)>> {
+    let providers = scan_providers(root)?
+        .into_iter()
+        .map(|(domain, paths)| (domain, scan_pack_details(root, &paths)))
+        .collect::<BTreeMap<_, _>>();
+    let packs = scan_pack_details(root, &scan_packs(root)?);
+    let mut tenants = scan_tenants(root)?;
+    if let Some((tenant, team)) = only {
+        tenants.retain(|entry| entry.name == tenant);
+        if tenants.is_empty() {
+            tenants.push(TenantEntry {
+                name: tenant.to_string(),
+                teams: Vec::new(),
+            });
+        }
+        if let Some(team) = team {
+            tenants[0].teams = vec![team.to_string()];
+        }
+    }
+    let project_root = root.to_string_lossy().to_string();
+
+    let mut manifests = Vec::new();
     for tenant in tenants {
+        let build = |team: Option<&str>| {
+            build_manifest(
+                &tenant.name,
+                team,
+                &project_root,
+                &providers,
+                &packs,
+                root,
+                overlay,
+            )
+        };
         if tenant.teams.is_empty() {
-            let manifest =
-                build_manifest(&tenant.name, None, &project_root, &providers, &packs, root);
-            let filename = resolved_dir.join(format!("{}.yaml", tenant.name));
-            write_manifest(&filename, &manifest)?;
+            manifests.push((format!("{}.yaml", tenant.name), build(None)?));
         } else {
-            for team in tenant.teams {
-                let manifest = build_manifest(
-                    &tenant.name,
-                    Some(&team),
-                    &project_root,
-                    &providers,
-                    &packs,
-                    root,
-                );
-                let filename = resolved_dir.join(format!("{}.{}.yaml", tenant.name, team));
-                write_manifest(&filename, &manifest)?;
+            for team in &tenant.teams {
+                let filename = format!("{}.{}.yaml", tenant.name, team);
+                manifests.push((filename, build(Some(team))?));
             }
         }
     }
+    Ok(manifests)
+}
 
-    Ok(())
+/// Reads a resolved manifest of any schema version this operator understands.
+pub fn load_manifest(path: &Path) -> anyhow::Result<ResolvedManifest> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("read resolved manifest {}", path.display()))?;
+    let manifest: ResolvedManifest = serde_yaml_bw::from_str(&contents)
+        .with_context(|| format!("parse resolved manifest {}", path.display()))?;
+    if manifest.schema_version > SCHEMA_VERSION {
+        return Err(anyhow!(
+            "resolved manifest {} has schema_version {}; this operator reads up to {SCHEMA_VERSION}",
+            path.display(),
+            manifest.schema_version
+        ));
+    }
+    Ok(manifest)
+}
+
+pub fn write_manifest(path: &Path, manifest: &ResolvedManifest) -> anyhow::Result<()> {
+    let yaml = serde_yaml_bw::to_string(manifest)?;
+    crate::runtime_state::atomic_write(path, yaml.as_bytes())
 }
 
 fn build_manifest(
     tenant: &str,
     team: Option<&str>,
     project_root: &str,
-    providers: &BTreeMap<String, Vec<String>>,
-    packs: &[String],
+    providers: &BTreeMap<String, Vec<ScannedPack>>,
+    packs: &[ScannedPack],
     root: &Path,
-) -> ResolvedManifest {
-    let tenant_gmap = relative_path(root, &root.join("tenants").join(tenant).join("tenant.gmap"));
-    let team_gmap = team.map(|team| {
-        relative_path(
-            root,
-            &root
-                .join("tenants")
-                .join(tenant)
-                .join("teams")
-                .join(team)
-                .join("team.gmap"),
-        )
+    overlay: Option<&GmapOverlay<'_>>,
+) -> anyhow::Result<ResolvedManifest> {
+    let tenant_gmap_path = root.join("tenants").join(tenant).join("tenant.gmap");
+    let team_gmap_path = team.map(|team| {
+        root.join("tenants")
+            .join(tenant)
+            .join("teams")
+            .join(team)
+            .join("team.gmap")
     });
+    let tenant_gmap = relative_path(root, &tenant_gmap_path);
+    let team_gmap = team_gmap_path
+        .as_ref()
+        .map(|path| relative_path(root, path));
+    let gmaps = Gmaps {
+        tenant_rules: GmapOverlay::rules(overlay, &tenant_gmap_path)?,
+        tenant_file: tenant_gmap.clone(),
+        team_rules: match &team_gmap_path {
+            Some(path) => GmapOverlay::rules(overlay, path)?,
+            None => Vec::new(),
+        },
+        team_file: team_gmap.clone().unwrap_or_default(),
+    };
+    let resolve_packs = |packs: &[ScannedPack]| {
+        packs
+            .iter()
+            .map(|pack| ResolvedPackEntry::Pack(gmaps.resolve_pack(pack)))
+            .collect::<Vec<_>>()
+    };
 
-    ResolvedManifest {
+    Ok(ResolvedManifest {
+        schema_version: SCHEMA_VERSION,
         version: VERSION.to_string(),
         tenant: tenant.to_string(),
         team: team.map(|value| value.to_string()),
         project_root: project_root.to_string(),
-        providers: providers.clone(),
-        packs: packs.to_vec(),
+        providers: providers
+            .iter()
+            .map(|(domain, packs)| (domain.clone(), resolve_packs(packs)))
+            .collect(),
+        packs: resolve_packs(packs),
         env_passthrough: ENV_PASSTHROUGH
             .iter()
             .map(|value| value.to_string())
@@ -116,16 +303,98 @@ fn build_manifest(
             },
             default: DEFAULT_POLICY.to_string(),
         },
-    }
+    })
+}
+
+struct Gmaps {
+    tenant_rules: Vec<GmapRule>,
+    tenant_file: String,
+    team_rules: Vec<GmapRule>,
+    team_file: String,
 }
 
-fn write_manifest(path: &Path, manifest: &ResolvedManifest) -> anyhow::Result<()> {
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)?;
+impl Gmaps {
+    fn resolve_pack(&self, pack: &ScannedPack) -> ResolvedPack {
+        let flows = pack
+            .entry_flows
+            .iter()
+            .map(|flow| {
+                let target = GmapPath {
+                    pack: pack.pack_id.clone(),
+                    flow: Some(flow.clone()),
+                    node: None,
+                };
+                // Same precedence as eval_with_overlay: any team match shadows the tenant gmap.
+                let decision = gmap::winning_rule(&self.team_rules, &target)
+                    .map(|rule| (rule, &self.team_file))
+                    .or_else(|| {
+                        gmap::winning_rule(&self.tenant_rules, &target)
+                            .map(|rule| (rule, &self.tenant_file))
+                    });
+                ResolvedFlow {
+                    id: flow.clone(),
+                    policy: decision
+                        .map(|(rule, _)| policy_label(&rule.policy))
+                        .unwrap_or(DEFAULT_POLICY)
+                        .to_string(),
+                    rule: decision.map(|(rule, file)| format!("{file}:{}", rule.line)),
+                }
+            })
+            .collect();
+        ResolvedPack {
+            path: pack.path.clone(),
+            pack_id: pack.pack_id.clone(),
+            digest: pack.digest.clone(),
+            flows,
+            offers: pack.offers.clone(),
+        }
     }
-    let yaml = serde_yaml_bw::to_string(manifest)?;
-    std::fs::write(path, yaml)?;
-    Ok(())
+}
+
+/// Metadata for each pack; a pack that cannot be read keeps its path and is logged.
+fn scan_pack_details(root: &Path, paths: &[String]) -> Vec<ScannedPack> {
+    paths
+        .iter()
+        .map(|rel| {
+            let path = root.join(rel);
+            let meta = domains::read_pack_meta(&path)
+                .map_err(|err| {
+                    operator_log::warn(
+                        module_path!(),
+                        format!("resolve: cannot read pack metadata of {rel}: {err}"),
+                    )
+                })
+                .ok();
+            let offers = if path.is_file() {
+                load_pack_offers(&path)
+                    .map(|pack| {
+                        pack.offers
+                            .into_iter()
+                            .map(|offer| ResolvedOffer {
+                                id: offer.id,
+                                kind: offer.kind.as_str().to_string(),
+                                provider_op: offer.provider_op,
+                                priority: offer.priority,
+                                stage: offer.stage,
+                                contract: offer.contract,
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            } else {
+                Vec::new()
+            };
+            ScannedPack {
+                path: rel.clone(),
+                digest: digest_path(&path)
+                    .ok()
+                    .map(|digest| format!("sha256:{digest}")),
+                pack_id: meta.as_ref().map(|meta| meta.pack_id.clone()),
+                entry_flows: meta.map(|meta| meta.entry_flows).unwrap_or_default(),
+                offers,
+            }
+        })
+        .collect()
 }
 
 fn scan_providers(root: &Path) -> anyhow::Result<BTreeMap<String, Vec<String>>> {
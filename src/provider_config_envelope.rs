@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::Read;
 use std::path::{Path, PathBuf};
@@ -16,6 +17,7 @@ use serde_json::{Value as JsonValue, json};
 use zip::ZipArchive;
 
 use crate::runtime_state::atomic_write;
+use crate::scope_chain::{self, ScopeLevel};
 
 const ABI_VERSION: &str = "greentic:component@0.6.0";
 
@@ -104,6 +106,68 @@ pub fn read_provider_config_envelope(
     Ok(Some(envelope))
 }
 
+/// A provider's config merged along the team -> tenant -> bundle chain: top-level
+/// keys at a more specific level override the same keys below it.
+#[derive(Debug, Clone, Serialize)]
+pub struct InheritedConfig {
+    pub config: JsonValue,
+    /// Where each top-level key came from.
+    pub sources: BTreeMap<String, ConfigKeySource>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigKeySource {
+    pub level: ScopeLevel,
+    pub path: PathBuf,
+}
+
+/// Reads and merges `provider_id`'s envelopes for `tenant`/`team` under `state_dir`;
+/// `None` when no level has one.
+pub fn read_inherited_config(
+    state_dir: &Path,
+    tenant: &str,
+    team: &str,
+    provider_id: &str,
+) -> anyhow::Result<Option<InheritedConfig>> {
+    let mut merged: Option<InheritedConfig> = None;
+    for (level, root) in scope_chain::config_roots(state_dir, tenant, team)
+        .into_iter()
+        .rev()
+    {
+        let Some(envelope) = read_provider_config_envelope(&root, provider_id)? else {
+            continue;
+        };
+        let source = ConfigKeySource {
+            level,
+            path: root.join(provider_id).join("config.envelope.cbor"),
+        };
+        if let Some(current) = merged.as_mut()
+            && let (JsonValue::Object(base), JsonValue::Object(overrides)) =
+                (&mut current.config, &envelope.config)
+        {
+            for (key, value) in overrides {
+                base.insert(key.clone(), value.clone());
+                current.sources.insert(key.clone(), source.clone());
+            }
+        } else {
+            let sources = envelope
+                .config
+                .as_object()
+                .map(|map| {
+                    map.keys()
+                        .map(|key| (key.clone(), source.clone()))
+                        .collect()
+                })
+                .unwrap_or_default();
+            merged = Some(InheritedConfig {
+                config: envelope.config,
+                sources,
+            });
+        }
+    }
+    Ok(merged)
+}
+
 pub fn resolved_describe_hash(
     pack_path: &Path,
     fallback_component_id: &str,
@@ -134,6 +198,13 @@ pub fn ensure_contract_compatible(
     Ok(())
 }
 
+/// Digest of a pack file, as recorded in `resolved_digest`.
+pub fn pack_digest(pack_path: &Path) -> anyhow::Result<String> {
+    let bytes =
+        std::fs::read(pack_path).with_context(|| format!("read pack {}", pack_path.display()))?;
+    Ok(digest_hex(&bytes))
+}
+
 fn write_contract_cache_entry(
     providers_root: &Path,
     provenance: &PackProvenance,
@@ -347,6 +418,59 @@ mod tests {
         assert!(err.to_string().contains("OP_CONTRACT_DRIFT"));
     }
 
+    #[test]
+    fn team_config_overrides_tenant_and_bundle_keys() {
+        let temp = tempdir().unwrap();
+        let pack = temp.path().join("provider.gtpack");
+        write_test_pack(&pack).unwrap();
+        let state_dir = temp.path().join("state");
+        for (dir, config) in [
+            (
+                "_",
+                json!({"api_base_url": "https://api.example", "timeout_ms": 1000}),
+            ),
+            (
+                "acme",
+                json!({"timeout_ms": 5000, "webhook": "https://acme.example"}),
+            ),
+            (
+                "acme.support",
+                json!({"webhook": "https://support.example"}),
+            ),
+        ] {
+            write_provider_config_envelope(
+                &state_dir.join("runtime").join(dir).join("providers"),
+                "messaging-telegram",
+                "setup_default",
+                &config,
+                &pack,
+                false,
+            )
+            .unwrap();
+        }
+
+        let inherited = read_inherited_config(&state_dir, "acme", "support", "messaging-telegram")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            inherited.config,
+            json!({
+                "api_base_url": "https://api.example",
+                "timeout_ms": 5000,
+                "webhook": "https://support.example",
+            })
+        );
+        let level = |key: &str| inherited.sources[key].level;
+        assert_eq!(level("api_base_url"), ScopeLevel::Bundle);
+        assert_eq!(level("timeout_ms"), ScopeLevel::Tenant);
+        assert_eq!(level("webhook"), ScopeLevel::Team);
+        assert!(
+            read_inherited_config(&state_dir, "globex", "default", "messaging-telegram")
+                .unwrap()
+                .is_some_and(|config| config.sources["timeout_ms"].level == ScopeLevel::Bundle)
+        );
+    }
+
     fn write_test_pack(path: &Path) -> anyhow::Result<()> {
         let file = File::create(path)?;
         let mut zip = zip::ZipWriter::new(file);
@@ -82,6 +82,178 @@ pub fn cache_registry_file(
     Ok(destination)
 }
 
+/// Directory given to `wizard` when `--registry-mirror` is not set.
+pub const ENV_REGISTRY_MIRROR: &str = "GTC_PROVIDER_REGISTRY_MIRROR";
+
+const MIRROR_FILE: &str = "mirror.json";
+const MIRROR_CATALOG_FILE: &str = "catalog.json";
+const MIRROR_SCHEMA_VERSION: u32 = 1;
+
+/// A provider registry pinned to disk by `registry mirror`: the catalog file, every
+/// pack it references under `packs/`, and `mirror.json` recording their digests.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegistryMirror {
+    pub schema_version: u32,
+    pub registry_ref: String,
+    /// Digest the registry resolved to when it was mirrored.
+    pub registry_digest: String,
+    /// sha256 of `catalog.json`.
+    pub catalog_sha256: String,
+    pub created_at: String,
+    pub packs: Vec<MirroredPack>,
+    #[serde(skip)]
+    pub root: PathBuf,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MirroredPack {
+    /// Catalog entry ids listing this reference.
+    pub ids: Vec<String>,
+    pub reference: String,
+    pub pack_id: String,
+    /// Digest the reference resolved to.
+    pub digest: String,
+    /// sha256 of the mirrored file.
+    pub sha256: String,
+    /// Path relative to the mirror root.
+    pub file: String,
+}
+
+impl RegistryMirror {
+    /// Loads `<root>/mirror.json` and checks the catalog against its recorded digest.
+    pub fn load(root: &Path) -> anyhow::Result<Self> {
+        let path = root.join(MIRROR_FILE);
+        let raw = std::fs::read_to_string(&path)
+            .with_context(|| format!("read registry mirror {}", path.display()))?;
+        let mut mirror: RegistryMirror = serde_json::from_str(&raw)
+            .with_context(|| format!("parse registry mirror {}", path.display()))?;
+        if mirror.schema_version > MIRROR_SCHEMA_VERSION {
+            return Err(anyhow!(
+                "registry mirror {} has schema_version {}; this operator reads up to {}",
+                path.display(),
+                mirror.schema_version,
+                MIRROR_SCHEMA_VERSION
+            ));
+        }
+        mirror.root = root.to_path_buf();
+        let catalog_sha256 = crate::demo::diff::digest_path(&mirror.catalog_path())?;
+        if catalog_sha256 != mirror.catalog_sha256 {
+            return Err(anyhow!(
+                "registry mirror catalog {} does not match mirror.json (sha256 {catalog_sha256}, expected {})",
+                mirror.catalog_path().display(),
+                mirror.catalog_sha256
+            ));
+        }
+        Ok(mirror)
+    }
+
+    /// The mirror at `dir`, else at `$GTC_PROVIDER_REGISTRY_MIRROR`, if either is set.
+    pub fn from_dir_or_env(dir: Option<PathBuf>) -> anyhow::Result<Option<Self>> {
+        dir.or_else(|| {
+            std::env::var(ENV_REGISTRY_MIRROR)
+                .ok()
+                .filter(|value| !value.trim().is_empty())
+                .map(PathBuf::from)
+        })
+        .map(|dir| Self::load(&dir))
+        .transpose()
+    }
+
+    pub fn catalog_path(&self) -> PathBuf {
+        self.root.join(MIRROR_CATALOG_FILE)
+    }
+
+    /// The mirrored file for `reference`, once its sha256 matches `mirror.json`.
+    pub fn pack(&self, reference: &str) -> anyhow::Result<Option<(PathBuf, &MirroredPack)>> {
+        let Some(pack) = self
+            .packs
+            .iter()
+            .find(|pack| pack.reference == reference.trim())
+        else {
+            return Ok(None);
+        };
+        let path = self.root.join(&pack.file);
+        let sha256 = crate::demo::diff::digest_path(&path)
+            .with_context(|| format!("mirrored pack {reference}"))?;
+        if sha256 != pack.sha256 {
+            return Err(anyhow!(
+                "mirrored pack {} does not match mirror.json (sha256 {sha256}, expected {})",
+                path.display(),
+                pack.sha256
+            ));
+        }
+        Ok(Some((path, pack)))
+    }
+}
+
+/// Downloads the registry at `reference` and every pack its catalog references
+/// into `out`, replacing an earlier mirror there.
+pub fn mirror_registry(reference: &str, out: &Path) -> anyhow::Result<RegistryMirror> {
+    let reference = reference.trim();
+    let (bytes, registry_digest) = match parse_local_registry_ref(reference) {
+        Some(path) => {
+            let bytes = std::fs::read(&path)
+                .with_context(|| format!("read provider registry {}", path.display()))?;
+            let digest = format!("sha256:{}", crate::demo::diff::digest_path(&path)?);
+            (bytes, digest)
+        }
+        None => fetch_remote_registry(reference)?,
+    };
+    let packs_dir = out.join("packs");
+    if packs_dir.exists() {
+        std::fs::remove_dir_all(&packs_dir)
+            .with_context(|| format!("clear {}", packs_dir.display()))?;
+    }
+    std::fs::create_dir_all(&packs_dir)
+        .with_context(|| format!("create {}", packs_dir.display()))?;
+    let catalog_path = out.join(MIRROR_CATALOG_FILE);
+    std::fs::write(&catalog_path, &bytes)
+        .with_context(|| format!("write {}", catalog_path.display()))?;
+
+    let mut ids_by_ref: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for entry in crate::wizard::load_catalog_from_file(&catalog_path)? {
+        ids_by_ref
+            .entry(entry.reference.trim().to_string())
+            .or_default()
+            .push(entry.id);
+    }
+    let refs = ids_by_ref.keys().cloned().collect::<Vec<_>>();
+    let resolved = crate::wizard::resolve_pack_refs(&refs, false, None)
+        .context("fetch packs referenced by the provider registry")?;
+    let mut packs = Vec::new();
+    for pack in resolved {
+        let destination = out.join(&pack.output_path);
+        std::fs::copy(&pack.cached_path, &destination).with_context(|| {
+            format!(
+                "copy {} to {}",
+                pack.cached_path.display(),
+                destination.display()
+            )
+        })?;
+        packs.push(MirroredPack {
+            ids: ids_by_ref.remove(&pack.source_ref).unwrap_or_default(),
+            sha256: crate::demo::diff::digest_path(&destination)?,
+            file: pack.output_path.to_string_lossy().replace('\\', "/"),
+            reference: pack.source_ref,
+            pack_id: pack.pack_id,
+            digest: pack.resolved_digest,
+        });
+    }
+    let mirror = RegistryMirror {
+        schema_version: MIRROR_SCHEMA_VERSION,
+        registry_ref: reference.to_string(),
+        registry_digest,
+        catalog_sha256: crate::demo::diff::digest_path(&catalog_path)?,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        packs,
+        root: out.to_path_buf(),
+    };
+    let manifest = serde_json::to_string_pretty(&mirror)?;
+    std::fs::write(out.join(MIRROR_FILE), manifest)
+        .with_context(|| format!("write {}", out.join(MIRROR_FILE).display()))?;
+    Ok(mirror)
+}
+
 fn parse_local_registry_ref(reference: &str) -> Option<PathBuf> {
     if let Some(path) = reference.strip_prefix("file://") {
         let trimmed = path.trim();
@@ -134,6 +306,12 @@ fn resolve_cached_by_digest(bundle: &Path, reference: &str) -> anyhow::Result<Op
 }
 
 fn fetch_remote_registry_to_cache(bundle: &Path, reference: &str) -> anyhow::Result<PathBuf> {
+    let (bytes, digest) = fetch_remote_registry(reference)?;
+    cache_remote_registry_file(bundle, reference, &digest, bytes)
+}
+
+/// Registry file bytes and their resolved digest.
+fn fetch_remote_registry(reference: &str) -> anyhow::Result<(Vec<u8>, String)> {
     use greentic_distributor_client::{
         OciPackFetcher, PackFetchOptions, oci_packs::DefaultRegistryClient,
     };
@@ -150,20 +328,15 @@ fn fetch_remote_registry_to_cache(bundle: &Path, reference: &str) -> anyhow::Res
         ..PackFetchOptions::default()
     });
     match rt.block_on(fetcher.fetch_pack_to_cache(&mapped)) {
-        Ok(fetched) => cache_remote_registry_file(
-            bundle,
-            reference,
-            fetched.resolved_digest.as_str(),
+        Ok(fetched) => Ok((
             std::fs::read(&fetched.path)
                 .with_context(|| format!("read fetched registry {}", fetched.path.display()))?,
-        ),
-        Err(primary_err) => {
-            let (bytes, digest) = rt
-                .block_on(fetch_registry_bytes_via_oci(&mapped))
-                .with_context(|| format!("fetch provider registry {reference}"))
-                .with_context(|| format!("primary fetch error: {primary_err}"))?;
-            cache_remote_registry_file(bundle, reference, &digest, bytes)
-        }
+            fetched.resolved_digest,
+        )),
+        Err(primary_err) => rt
+            .block_on(fetch_registry_bytes_via_oci(&mapped))
+            .with_context(|| format!("fetch provider registry {reference}"))
+            .with_context(|| format!("primary fetch error: {primary_err}")),
     }
 }
 
@@ -356,4 +529,50 @@ mod tests {
         .unwrap();
         assert_eq!(resolved, digest_path);
     }
+
+    #[test]
+    fn mirror_checks_catalog_and_pack_digests() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("packs")).unwrap();
+        std::fs::write(root.join(MIRROR_CATALOG_FILE), "[]").unwrap();
+        std::fs::write(root.join("packs/telegram.gtpack"), "pack bytes").unwrap();
+        let reference = "oci://ghcr.io/greenticai/packs/messaging-telegram:latest";
+        let mirror = RegistryMirror {
+            schema_version: MIRROR_SCHEMA_VERSION,
+            registry_ref: "oci://ghcr.io/greenticai/registries/providers:latest".to_string(),
+            registry_digest: "sha256:abc123".to_string(),
+            catalog_sha256: crate::demo::diff::digest_path(&root.join(MIRROR_CATALOG_FILE))
+                .unwrap(),
+            created_at: "2026-01-01T00:00:00Z".to_string(),
+            packs: vec![MirroredPack {
+                ids: vec!["messaging-telegram".to_string()],
+                reference: reference.to_string(),
+                pack_id: "messaging-telegram".to_string(),
+                digest: "sha256:def456".to_string(),
+                sha256: crate::demo::diff::digest_path(&root.join("packs/telegram.gtpack"))
+                    .unwrap(),
+                file: "packs/telegram.gtpack".to_string(),
+            }],
+            root: PathBuf::new(),
+        };
+        std::fs::write(
+            root.join(MIRROR_FILE),
+            serde_json::to_string(&mirror).unwrap(),
+        )
+        .unwrap();
+
+        let loaded = RegistryMirror::load(root).unwrap();
+        assert_eq!(loaded.catalog_path(), root.join(MIRROR_CATALOG_FILE));
+        let (path, pack) = loaded.pack(reference).unwrap().unwrap();
+        assert_eq!(path, root.join("packs/telegram.gtpack"));
+        assert_eq!(pack.digest, "sha256:def456");
+        assert!(loaded.pack("oci://ghcr.io/other:latest").unwrap().is_none());
+
+        std::fs::write(root.join("packs/telegram.gtpack"), "tampered").unwrap();
+        let err = loaded.pack(reference).unwrap_err().to_string();
+        assert!(err.contains("does not match mirror.json"), "{err}");
+        std::fs::write(root.join(MIRROR_CATALOG_FILE), "[{}]").unwrap();
+        assert!(RegistryMirror::load(root).is_err());
+    }
 }
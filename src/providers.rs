@@ -11,6 +11,7 @@ use serde_json::{Value, json};
 use crate::bin_resolver::{self, ResolveCtx};
 use crate::config::{DemoConfig, DemoProviderConfig};
 use crate::domains::Domain;
+use crate::interpolate::SecretScope;
 use crate::operator_log;
 use crate::runner_integration;
 use crate::runtime_state::RuntimePaths;
@@ -38,7 +39,7 @@ pub fn run_provider_setup(
     public_base_url: Option<&str>,
     options: ProviderSetupOptions,
 ) -> anyhow::Result<()> {
-    let providers = resolve_providers(config, options.providers);
+    let providers = resolve_providers(config, options.providers)?;
     if providers.is_empty() || options.skip_setup {
         return Ok(());
     }
@@ -75,8 +76,13 @@ pub fn run_provider_setup(
     std::fs::create_dir_all(&providers_root)?;
     let provider_keys: BTreeSet<String> = providers.iter().map(|(name, _)| name.clone()).collect();
     let setup_input_answers = if let Some(path) = options.setup_input.as_ref() {
+        let scope = SecretScope {
+            bundle_root: config_dir,
+            tenant: &config.tenant,
+            team: Some(&config.team),
+        };
         Some(SetupInputAnswers::new(
-            load_setup_input(path)?,
+            load_setup_input(path, Some(scope))?,
             provider_keys.clone(),
         )?)
     } else {
@@ -126,12 +132,13 @@ pub fn run_provider_setup(
                         ),
                     );
                 }
-                let current_config =
-                    crate::provider_config_envelope::read_provider_config_envelope(
-                        &providers_root,
-                        &provider,
-                    )?
-                    .map(|envelope| envelope.config);
+                let current_config = crate::provider_config_envelope::read_inherited_config(
+                    &config_dir.join("state"),
+                    &config.tenant,
+                    &config.team,
+                    &provider,
+                )?
+                .map(|inherited| inherited.config);
                 crate::provider_config_envelope::ensure_contract_compatible(
                     &providers_root,
                     &provider,
@@ -153,6 +160,8 @@ pub fn run_provider_setup(
                             .to_string(),
                         path: pack_path.clone(),
                         entry_flows: Vec::new(),
+                        depends_on: Vec::new(),
+                        verify_flows: Vec::new(),
                     },
                     &provider,
                     mode,
@@ -315,13 +324,14 @@ pub(crate) fn write_qa_setup_success_record(
     Ok(())
 }
 
+/// Configured providers passing `filter`, ordered so `depends_on` providers set up first.
 fn resolve_providers(
     config: &DemoConfig,
     filter: Option<Vec<String>>,
-) -> Vec<(String, DemoProviderConfig)> {
+) -> anyhow::Result<Vec<(String, DemoProviderConfig)>> {
     let mut selected = Vec::new();
     let Some(map) = config.providers.as_ref() else {
-        return selected;
+        return Ok(selected);
     };
     let filter_set = filter.map(|list| {
         list.into_iter()
@@ -336,7 +346,11 @@ fn resolve_providers(
         }
         selected.push((name.clone(), cfg.clone()));
     }
-    selected
+    crate::domains::order_by_dependencies(
+        selected,
+        |(name, _)| name.as_str(),
+        |(_, cfg)| cfg.depends_on.as_slice(),
+    )
 }
 
 fn resolve_runner_binary(config_dir: &Path, explicit: Option<PathBuf>) -> anyhow::Result<PathBuf> {
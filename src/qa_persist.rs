@@ -12,16 +12,21 @@ use greentic_secrets_lib::{
     ApplyOptions, DevStore, SecretFormat, SeedDoc, SeedEntry, SeedValue, apply_seed,
 };
 use qa_spec::FormSpec;
-use serde_json::{Map as JsonMap, Value};
+use serde_json::{Map as JsonMap, Value, json};
 
+use crate::audit::{self, AuditEvent};
+use crate::secrets_crypto::{self, KeySource};
 use crate::secrets_gate::canonical_secret_uri;
 use crate::secrets_setup::resolve_env;
 
 /// Extract secret fields from the QA config output and write them to the dev store.
 ///
 /// Returns a list of secret keys that were persisted.
+#[allow(clippy::too_many_arguments)]
 pub async fn persist_qa_secrets(
     store: &DevStore,
+    store_path: &Path,
+    key_source: Option<&KeySource>,
     env: &str,
     tenant: &str,
     team: Option<&str>,
@@ -57,6 +62,9 @@ pub async fn persist_qa_secrets(
             if text.is_empty() || text == "null" {
                 continue;
             }
+            if form_spec.questions.iter().any(|q| q.id == key && q.secret) {
+                crate::redaction::register_secret(text.as_bytes());
+            }
             let uri = canonical_secret_uri(env, tenant, team, provider_id, key);
             entries.push(SeedEntry {
                 uri,
@@ -72,6 +80,7 @@ pub async fn persist_qa_secrets(
         return Ok(vec![]);
     }
 
+    let entries = secrets_crypto::prepare_seed_entries(store_path, key_source, entries)?;
     let report = apply_seed(store, &SeedDoc { entries }, ApplyOptions::default()).await;
 
     if !report.failed.is_empty() {
@@ -158,8 +167,27 @@ pub async fn persist_qa_results(
         )
     })?;
 
-    let saved_secrets =
-        persist_qa_secrets(&store, &env, tenant, team, provider_id, config, form_spec).await?;
+    let key_source = secrets_crypto::configured_key_source(bundle_root)?;
+    let saved_secrets = persist_qa_secrets(
+        &store,
+        &store_path,
+        key_source.as_ref(),
+        &env,
+        tenant,
+        team,
+        provider_id,
+        config,
+        form_spec,
+    )
+    .await;
+    audit::record(
+        bundle_root,
+        AuditEvent::new("secrets.write", provider_id)
+            .scope(tenant, team)
+            .detail(json!({ "source": "qa", "keys": saved_secrets.as_ref().ok() }))
+            .result(&saved_secrets),
+    );
+    let saved_secrets = saved_secrets?;
 
     let config_written = if config.as_object().is_some_and(|m| !m.is_empty()) {
         persist_qa_config(
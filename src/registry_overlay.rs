@@ -0,0 +1,182 @@
+//! Tenant-scoped provider registry overlays.
+//!
+//! A tenant can keep `tenants/<tenant>/registry.yaml` in the bundle, in either
+//! provider registry shape (a `registry_version`/`items` document or a bare list
+//! of listings). Its entries are merged over the catalog the shared registry
+//! resolved to: an entry with a known id replaces it, a new id is added. The
+//! shared registry ref and its cache are left alone, so a customer's private packs
+//! live only in that customer's bundle.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+
+use crate::catalog::CatalogEntry;
+use crate::wizard::PackListing;
+
+pub const OVERLAY_FILE: &str = "registry.yaml";
+
+/// A catalog entry an overlay can add or replace.
+pub trait RegistryEntry {
+    fn id(&self) -> &str;
+    fn reference(&self) -> &str;
+}
+
+impl RegistryEntry for PackListing {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn reference(&self) -> &str {
+        &self.reference
+    }
+}
+
+impl RegistryEntry for CatalogEntry {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn reference(&self) -> &str {
+        &self.reference
+    }
+}
+
+pub fn overlay_path(bundle: &Path, tenant: &str) -> PathBuf {
+    bundle.join("tenants").join(tenant).join(OVERLAY_FILE)
+}
+
+/// Merges the overlays of `tenants` in `bundle` over `catalog`, reading each
+/// overlay file with `load`. Tenants without an overlay are skipped.
+///
+/// Two tenants overlaying one id with different refs is an error: a wizard run
+/// resolves each catalog id once for all of its targets.
+pub fn apply<T, I, S, L>(
+    bundle: &Path,
+    tenants: I,
+    catalog: &mut Vec<T>,
+    load: L,
+) -> anyhow::Result<()>
+where
+    T: RegistryEntry,
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+    L: Fn(&Path) -> anyhow::Result<Vec<T>>,
+{
+    let tenants = tenants
+        .into_iter()
+        .map(|tenant| tenant.as_ref().to_string())
+        .collect::<BTreeSet<_>>();
+    let mut claimed: BTreeMap<String, (String, String)> = BTreeMap::new();
+    for tenant in tenants {
+        let path = overlay_path(bundle, &tenant);
+        if !path.is_file() {
+            continue;
+        }
+        for entry in load(&path)? {
+            if let Some((other, reference)) = claimed.get(entry.id())
+                && reference != entry.reference()
+            {
+                return Err(anyhow!(
+                    "catalog id {} is overlaid by tenants {other} ({reference}) and {tenant} ({}); run the wizard for one of them at a time",
+                    entry.id(),
+                    entry.reference()
+                ));
+            }
+            claimed.insert(
+                entry.id().to_string(),
+                (tenant.clone(), entry.reference().to_string()),
+            );
+            match catalog
+                .iter_mut()
+                .find(|existing| existing.id() == entry.id())
+            {
+                Some(existing) => *existing = entry,
+                None => catalog.push(entry),
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wizard::load_catalog_from_file;
+
+    fn listing(id: &str, reference: &str) -> PackListing {
+        PackListing {
+            id: id.to_string(),
+            label: id.to_string(),
+            reference: reference.to_string(),
+        }
+    }
+
+    #[test]
+    fn tenant_entries_replace_and_extend_the_catalog() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path();
+        let write = |tenant: &str, contents: &str| {
+            let path = overlay_path(bundle, tenant);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        };
+        write(
+            "acme",
+            "registry_version: '1'
+items:
+  - id: messaging-slack
+    label: { fallback: Slack (Acme fork) }
+    ref: oci://registry.acme.example/packs/slack:2.0.0
+  - id: acme-crm
+    label: { fallback: Acme CRM }
+    ref: oci://registry.acme.example/packs/crm:1.0.0
+",
+        );
+        write(
+            "globex",
+            "- id: messaging-slack
+  label: Slack (Globex)
+  reference: oci://registry.globex.example/slack:1.0.0
+",
+        );
+
+        let mut catalog = vec![
+            listing("messaging-telegram", "repo://telegram@latest"),
+            listing("messaging-slack", "repo://slack@latest"),
+        ];
+        apply(
+            bundle,
+            ["acme", "other"],
+            &mut catalog,
+            load_catalog_from_file,
+        )
+        .unwrap();
+        let refs = catalog
+            .iter()
+            .map(|entry| (entry.id.as_str(), entry.reference.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            refs,
+            [
+                ("messaging-telegram", "repo://telegram@latest"),
+                (
+                    "messaging-slack",
+                    "oci://registry.acme.example/packs/slack:2.0.0"
+                ),
+                ("acme-crm", "oci://registry.acme.example/packs/crm:1.0.0"),
+            ]
+        );
+
+        let mut conflicting = vec![listing("messaging-slack", "repo://slack@latest")];
+        let err = apply(
+            bundle,
+            ["acme", "globex"],
+            &mut conflicting,
+            load_catalog_from_file,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("messaging-slack"));
+    }
+}
@@ -4,6 +4,8 @@ use greentic_runner_desktop::{RunOptions, RunResult, TenantContext};
 use serde_json::Value as JsonValue;
 
 use crate::domains::Domain;
+use crate::flow_stream;
+use crate::profile::{self, Phase};
 use crate::state_layout;
 
 pub struct RunOutput {
@@ -24,16 +26,25 @@ pub struct RunRequest {
 }
 
 pub fn run_provider_pack_flow(request: RunRequest) -> anyhow::Result<RunOutput> {
+    let _span = profile::span(
+        Phase::Flow,
+        format_args!("{} {}", request.pack_label, request.flow_id),
+    );
     let run_dir = state_layout::run_dir(
         &request.root,
         request.domain,
         &request.pack_label,
         &request.flow_id,
     )?;
-    std::fs::create_dir_all(&run_dir)?;
-    let input_path = run_dir.join("input.json");
-    let input_json = serde_json::to_string_pretty(&request.input)?;
-    std::fs::write(&input_path, input_json)?;
+    let input_json = {
+        let _span = profile::span(Phase::Encode, "input.json");
+        serde_json::to_string_pretty(&request.input)?
+    };
+    {
+        let _span = profile::span(Phase::Io, run_dir.display());
+        std::fs::create_dir_all(&run_dir)?;
+        std::fs::write(run_dir.join("input.json"), input_json)?;
+    }
 
     let opts = RunOptions {
         entry_flow: Some(request.flow_id.clone()),
@@ -49,8 +60,14 @@ pub fn run_provider_pack_flow(request: RunRequest) -> anyhow::Result<RunOutput>
         ..RunOptions::default()
     };
 
-    let result = greentic_runner_desktop::run_pack_with_options(&request.pack_path, opts)?;
-    write_run_artifacts(&run_dir, &result)?;
+    let result = {
+        let _live = flow_stream::live();
+        greentic_runner_desktop::run_pack_with_options(&request.pack_path, opts)?
+    };
+    {
+        let _span = profile::span(Phase::Io, run_dir.display());
+        write_run_artifacts(&run_dir, &result)?;
+    }
 
     Ok(RunOutput { result, run_dir })
 }
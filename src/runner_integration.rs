@@ -1,9 +1,17 @@
-use std::path::Path;
-use std::process::Command;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use serde_json::Value;
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+use crate::flow_stream;
+use crate::profile::{self, Phase};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum RunnerFlavor {
     RunSubcommand,
     RunnerCli,
@@ -15,6 +23,42 @@ pub struct RunFlowOptions<'a> {
     pub team: Option<&'a str>,
     pub artifacts_dir: Option<&'a Path>,
     pub runner_flavor: RunnerFlavor,
+    pub limits: ProcessLimits,
+}
+
+/// Limits on one spawned runner. Unset fields are unlimited.
+#[derive(Clone, Debug, Default)]
+pub struct ProcessLimits {
+    /// Wall-clock time before the runner is killed.
+    pub timeout: Option<Duration>,
+    /// Bytes of stdout plus stderr before the runner is killed.
+    pub max_output_bytes: Option<u64>,
+    /// `RLIMIT_CPU`, in seconds of CPU time.
+    pub max_cpu_secs: Option<u64>,
+    /// `memory.max` of the runner's cgroup when `cgroup_parent` is set, otherwise
+    /// `RLIMIT_AS`.
+    pub max_memory_bytes: Option<u64>,
+    /// A delegated cgroup v2 directory; each runner gets a child cgroup in it.
+    pub cgroup_parent: Option<PathBuf>,
+}
+
+impl ProcessLimits {
+    /// CPU and memory limits and cgroups hold a whole process, so a runner shared
+    /// by several invocations cannot enforce them for one.
+    pub fn needs_own_process(&self) -> bool {
+        self.max_cpu_secs.is_some()
+            || self.max_memory_bytes.is_some()
+            || self.cgroup_parent.is_some()
+    }
+}
+
+/// Which limit stopped the runner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LimitHit {
+    Timeout,
+    OutputBytes,
+    CpuSecs,
+    MemoryBytes,
 }
 
 pub struct RunnerOutput {
@@ -22,6 +66,8 @@ pub struct RunnerOutput {
     pub stdout: String,
     pub stderr: String,
     pub parsed: Option<Value>,
+    /// Set when the runner was stopped by one of [`RunFlowOptions::limits`].
+    pub limit_hit: Option<LimitHit>,
 }
 
 pub fn run_flow(
@@ -41,6 +87,7 @@ pub fn run_flow(
             team: None,
             artifacts_dir: None,
             runner_flavor: RunnerFlavor::RunSubcommand,
+            limits: ProcessLimits::default(),
         },
     )
 }
@@ -52,7 +99,11 @@ pub fn run_flow_with_options(
     input: &Value,
     options: RunFlowOptions<'_>,
 ) -> anyhow::Result<RunnerOutput> {
-    let input_str = serde_json::to_string(input)?;
+    let _span = profile::span(Phase::Flow, format_args!("{} {flow}", pack.display()));
+    let input_str = {
+        let _span = profile::span(Phase::Encode, "input");
+        serde_json::to_string(input)?
+    };
     let mut command = Command::new(runner);
     match options.runner_flavor {
         RunnerFlavor::RunSubcommand => {
@@ -85,20 +136,271 @@ pub fn run_flow_with_options(
             }
         }
     }
-    let output = command.output()?;
+    let (status, stdout, stderr, limit_hit) = run_limited(command, &options.limits)?;
 
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-    let parsed = serde_json::from_str(&stdout).ok();
+    let stdout = String::from_utf8_lossy(&stdout).to_string();
+    let stderr = String::from_utf8_lossy(&stderr).to_string();
+    let parsed = {
+        let _span = profile::span(Phase::Encode, "stdout");
+        serde_json::from_str(&stdout).ok()
+    };
 
     Ok(RunnerOutput {
-        status: output.status,
+        status,
         stdout,
         stderr,
         parsed,
+        limit_hit,
+    })
+}
+
+/// A long-lived runner for `pack` that reads invocations as JSON lines on stdin
+/// (`--stdio`); flow, input, tenant and team come with each request.
+pub fn worker_command(
+    runner: &Path,
+    pack: &Path,
+    flavor: RunnerFlavor,
+    dist_offline: bool,
+) -> Command {
+    let mut command = Command::new(runner);
+    if flavor == RunnerFlavor::RunSubcommand {
+        command.arg("run");
+    }
+    command.arg("--pack").arg(pack).arg("--stdio");
+    if dist_offline {
+        command.arg("--offline");
+    }
+    command
+}
+
+/// Longer stderr lines are never taken for stream events.
+const MAX_EVENT_LINE: u64 = 64 * 1024;
+
+type LimitedOutput = (ExitStatus, Vec<u8>, Vec<u8>, Option<LimitHit>);
+
+/// Runs `command` to completion under `limits`, killing it when the timeout or
+/// the output cap is reached.
+fn run_limited(mut command: Command, limits: &ProcessLimits) -> anyhow::Result<LimitedOutput> {
+    command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    apply_rlimits(&mut command, limits);
+    let live = flow_stream::live();
+    if live.is_some() {
+        command.env(flow_stream::ENV_VAR, "1");
+    }
+    let mut child = {
+        let _span = profile::span(Phase::Spawn, Path::new(command.get_program()).display());
+        command.spawn()?
+    };
+    let cgroup = limits
+        .cgroup_parent
+        .as_deref()
+        .and_then(|parent| enter_cgroup(parent, &child, limits.max_memory_bytes));
+
+    let overflow = Arc::new(AtomicBool::new(false));
+    let budget = Arc::new(AtomicU64::new(limits.max_output_bytes.unwrap_or(u64::MAX)));
+    let stdout = drain(child.stdout.take(), budget.clone(), overflow.clone(), None);
+    let stderr = drain(
+        child.stderr.take(),
+        budget,
+        overflow.clone(),
+        live.as_ref().map(flow_stream::LiveFlow::emitter),
+    );
+
+    let started = Instant::now();
+    let mut limit_hit = None;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if overflow.load(Ordering::Relaxed) {
+            limit_hit = Some(LimitHit::OutputBytes);
+        } else if limits
+            .timeout
+            .is_some_and(|timeout| started.elapsed() >= timeout)
+        {
+            limit_hit = Some(LimitHit::Timeout);
+        }
+        if limit_hit.is_some() {
+            let _ = child.kill();
+            break child.wait()?;
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+    if limit_hit.is_none() {
+        limit_hit = if overflow.load(Ordering::Relaxed) {
+            Some(LimitHit::OutputBytes)
+        } else {
+            killed_by_limit(&status, limits, cgroup.as_deref())
+        };
+    }
+    if let Some(cgroup) = cgroup {
+        let _ = std::fs::remove_dir(cgroup);
+    }
+    Ok((status, stdout, stderr, limit_hit))
+}
+
+/// Collects a pipe until EOF. Bytes past the shared budget are read and dropped so
+/// the runner never blocks on a full pipe; `overflow` is raised instead. With an
+/// `emitter` the pipe is read by line and stream event lines go to it instead.
+fn drain(
+    pipe: Option<impl Read + Send + 'static>,
+    budget: Arc<AtomicU64>,
+    overflow: Arc<AtomicBool>,
+    emitter: Option<flow_stream::Emitter>,
+) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut collected = Vec::new();
+        let Some(mut pipe) = pipe else {
+            return collected;
+        };
+        if let Some(emitter) = emitter {
+            let mut reader = BufReader::new(pipe);
+            let mut line = Vec::new();
+            while let Ok(read) = (&mut reader)
+                .take(MAX_EVENT_LINE)
+                .read_until(b'\n', &mut line)
+            {
+                if read == 0 {
+                    break;
+                }
+                let event =
+                    line.ends_with(b"\n") && emitter.emit_line(&String::from_utf8_lossy(&line));
+                if !event {
+                    keep_within_budget(&mut collected, &line, &budget, &overflow);
+                }
+                line.clear();
+            }
+            return collected;
+        }
+        let mut buf = [0u8; 8192];
+        while let Ok(read) = pipe.read(&mut buf) {
+            if read == 0 {
+                break;
+            }
+            keep_within_budget(&mut collected, &buf[..read], &budget, &overflow);
+        }
+        collected
     })
 }
 
+fn keep_within_budget(
+    collected: &mut Vec<u8>,
+    chunk: &[u8],
+    budget: &AtomicU64,
+    overflow: &AtomicBool,
+) {
+    let read = chunk.len();
+    let taken = budget
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |left| {
+            Some(left.saturating_sub(read as u64))
+        })
+        .unwrap_or(0);
+    let keep = (read as u64).min(taken) as usize;
+    collected.extend_from_slice(&chunk[..keep]);
+    if keep < read {
+        overflow.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(unix)]
+fn apply_rlimits(command: &mut Command, limits: &ProcessLimits) {
+    use std::os::unix::process::CommandExt;
+
+    let cpu = limits.max_cpu_secs;
+    // With a cgroup the memory limit is set there; RLIMIT_AS also counts address
+    // space a runtime only reserves.
+    let memory = limits
+        .max_memory_bytes
+        .filter(|_| limits.cgroup_parent.is_none());
+    if cpu.is_none() && memory.is_none() {
+        return;
+    }
+    unsafe {
+        command.pre_exec(move || {
+            let set = |resource, value: u64| {
+                let limit = libc::rlimit {
+                    rlim_cur: value as libc::rlim_t,
+                    rlim_max: value as libc::rlim_t,
+                };
+                if libc::setrlimit(resource, &limit) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            };
+            if let Some(secs) = cpu {
+                set(libc::RLIMIT_CPU, secs)?;
+            }
+            if let Some(bytes) = memory {
+                set(libc::RLIMIT_AS, bytes)?;
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+fn apply_rlimits(_: &mut Command, _: &ProcessLimits) {}
+
+/// Moves the runner into a fresh child of `parent`. A cgroup that cannot be set up
+/// is logged and skipped; the runner then runs without it.
+fn enter_cgroup(parent: &Path, child: &Child, max_memory: Option<u64>) -> Option<PathBuf> {
+    let dir = parent.join(format!("greentic-runner-{}", child.id()));
+    let result = (|| -> std::io::Result<()> {
+        std::fs::create_dir(&dir)?;
+        if let Some(bytes) = max_memory {
+            std::fs::write(dir.join("memory.max"), bytes.to_string())?;
+        }
+        std::fs::write(dir.join("cgroup.procs"), child.id().to_string())
+    })();
+    match result {
+        Ok(()) => Some(dir),
+        Err(err) => {
+            crate::operator_log::warn(
+                module_path!(),
+                format!("runner cgroup {} not applied: {err}", dir.display()),
+            );
+            let _ = std::fs::remove_dir(&dir);
+            None
+        }
+    }
+}
+
+/// A runner that died from `RLIMIT_CPU`, or was OOM-killed in its cgroup.
+#[cfg(unix)]
+fn killed_by_limit(
+    status: &ExitStatus,
+    limits: &ProcessLimits,
+    cgroup: Option<&Path>,
+) -> Option<LimitHit> {
+    use std::os::unix::process::ExitStatusExt;
+
+    match status.signal() {
+        Some(libc::SIGXCPU) if limits.max_cpu_secs.is_some() => Some(LimitHit::CpuSecs),
+        Some(libc::SIGKILL) if cgroup.is_some_and(oom_killed) => Some(LimitHit::MemoryBytes),
+        _ => None,
+    }
+}
+
+#[cfg(not(unix))]
+fn killed_by_limit(_: &ExitStatus, _: &ProcessLimits, _: Option<&Path>) -> Option<LimitHit> {
+    None
+}
+
+#[cfg(unix)]
+fn oom_killed(cgroup: &Path) -> bool {
+    std::fs::read_to_string(cgroup.join("memory.events"))
+        .ok()
+        .and_then(|events| {
+            events.lines().find_map(|line| {
+                line.strip_prefix("oom_kill ")
+                    .and_then(|count| count.trim().parse::<u64>().ok())
+            })
+        })
+        .is_some_and(|count| count > 0)
+}
+
 pub fn detect_runner_flavor(runner: &Path) -> RunnerFlavor {
     let name = runner
         .file_name()
@@ -110,3 +412,62 @@ pub fn detect_runner_flavor(runner: &Path) -> RunnerFlavor {
         RunnerFlavor::RunSubcommand
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn sh(script: &str) -> Command {
+        let mut command = Command::new("sh");
+        command.args(["-c", script]);
+        command
+    }
+
+    #[test]
+    fn kills_runners_past_their_limits() {
+        let (_, stdout, _, hit) = run_limited(sh("echo ok"), &ProcessLimits::default()).unwrap();
+        assert_eq!((stdout.as_slice(), hit), (b"ok\n".as_slice(), None));
+
+        let started = Instant::now();
+        let limits = ProcessLimits {
+            timeout: Some(Duration::from_millis(100)),
+            ..ProcessLimits::default()
+        };
+        let (status, _, _, hit) = run_limited(sh("sleep 5"), &limits).unwrap();
+        assert_eq!(hit, Some(LimitHit::Timeout));
+        assert!(!status.success());
+        assert!(started.elapsed() < Duration::from_secs(4));
+
+        let limits = ProcessLimits {
+            max_output_bytes: Some(1000),
+            ..ProcessLimits::default()
+        };
+        let (_, stdout, stderr, hit) = run_limited(
+            sh("head -c 100000 /dev/zero; head -c 10 /dev/zero >&2"),
+            &limits,
+        )
+        .unwrap();
+        assert_eq!(hit, Some(LimitHit::OutputBytes));
+        assert!(stdout.len() + stderr.len() <= 1000);
+    }
+
+    #[test]
+    fn streams_event_lines_from_stderr() {
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let _sink = flow_stream::scoped(Arc::new(move |event: &flow_stream::StreamEvent| {
+            recorded.lock().unwrap().push(event.clone());
+        }));
+        let script = r#"echo '@greentic-stream {"type":"log","message":"page 1"}' >&2; echo slow >&2; echo "$GREENTIC_STREAM""#;
+        let (_, stdout, stderr, _) = run_limited(sh(script), &ProcessLimits::default()).unwrap();
+        assert_eq!(stdout, b"1\n");
+        assert_eq!(stderr, b"slow\n");
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![flow_stream::StreamEvent::Log {
+                level: None,
+                message: "page 1".to_string(),
+            }]
+        );
+    }
+}
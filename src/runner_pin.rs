@@ -0,0 +1,527 @@
+//! Pinning a bundle to one greentic-runner version.
+//!
+//! `runner.version` in greentic.demo.yaml names the runner a bundle was built and
+//! tested against. [`resolve_runner`] finds it: a copy already downloaded into
+//! `state/bin`, then `--runner-binary`, the environment override, `bin/` and `PATH`
+//! as [`bin_resolver::resolve_binary`] searches them, asking each candidate for
+//! `--version`. When none matches and `runner.auto_download` is set, the pinned
+//! release is downloaded from `runner.download_url` and checked against its SHA-256
+//! before it is used. Packs can also state which runners they work with in
+//! `meta.runner_version`; [`check_packs`] refuses a runner outside any of those
+//! ranges.
+
+use std::fmt;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, anyhow};
+use ring::digest;
+
+use crate::bin_resolver::{self, ResolveCtx};
+use crate::config::DemoRunnerConfig;
+use crate::domains;
+use crate::operator_log;
+use crate::state_layout;
+
+const RUNNER_BINARY: &str = "greentic-runner";
+
+/// A `major.minor.patch[-pre]` runner version.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RunnerVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub pre: Option<String>,
+}
+
+impl RunnerVersion {
+    /// Parses `1.4.2`, `v1.4.2` or `1.5.0-rc.1`.
+    pub fn parse(value: &str) -> anyhow::Result<Self> {
+        let value = value.trim();
+        let value = value.strip_prefix('v').unwrap_or(value);
+        let (core, pre) = match value.split_once('-') {
+            Some((core, pre)) => (core, Some(pre.to_string())),
+            None => (value, None),
+        };
+        let parts = parse_parts(core)?;
+        let [major, minor, patch] = parts[..] else {
+            return Err(anyhow!(
+                "invalid version '{value}': expected MAJOR.MINOR.PATCH"
+            ));
+        };
+        Ok(Self {
+            major,
+            minor,
+            patch,
+            pre,
+        })
+    }
+
+    /// The first version in `--version` output such as `greentic-runner 1.4.2`.
+    pub fn from_output(output: &str) -> Option<Self> {
+        output
+            .split_whitespace()
+            .find_map(|token| Self::parse(token).ok())
+    }
+
+    fn triple(&self) -> (u64, u64, u64) {
+        (self.major, self.minor, self.patch)
+    }
+}
+
+impl fmt::Display for RunnerVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre) = &self.pre {
+            write!(f, "-{pre}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Op {
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Caret,
+    Tilde,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Comparator {
+    op: Op,
+    /// One to three components; missing ones act as wildcards.
+    parts: Vec<u64>,
+}
+
+impl Comparator {
+    fn parse(value: &str) -> anyhow::Result<Self> {
+        let value = value.trim();
+        let (op, rest) = [
+            (">=", Op::GreaterEq),
+            ("<=", Op::LessEq),
+            (">", Op::Greater),
+            ("<", Op::Less),
+            ("=", Op::Exact),
+            ("^", Op::Caret),
+            ("~", Op::Tilde),
+        ]
+        .into_iter()
+        .find_map(|(prefix, op)| value.strip_prefix(prefix).map(|rest| (op, rest)))
+        .unwrap_or((Op::Caret, value));
+        let rest = rest.trim();
+        let parts = parse_parts(rest.strip_prefix('v').unwrap_or(rest))?;
+        if parts.len() > 3 {
+            return Err(anyhow!("invalid version '{rest}'"));
+        }
+        Ok(Self { op, parts })
+    }
+
+    fn lower(&self) -> (u64, u64, u64) {
+        let part = |idx: usize| self.parts.get(idx).copied().unwrap_or(0);
+        (part(0), part(1), part(2))
+    }
+
+    /// First version past the range of `=`, `^` and `~`.
+    fn upper(&self) -> (u64, u64, u64) {
+        let (major, minor, patch) = self.lower();
+        match (self.op, self.parts.len()) {
+            (Op::Caret, _) if major > 0 => (major + 1, 0, 0),
+            (Op::Caret, 1) => (1, 0, 0),
+            (Op::Caret, 2) | (Op::Caret, 3) if minor > 0 => (0, minor + 1, 0),
+            (Op::Caret, 2) => (0, 1, 0),
+            (Op::Caret, _) => (0, 0, patch + 1),
+            (_, 1) => (major + 1, 0, 0),
+            (Op::Tilde, _) | (_, 2) => (major, minor + 1, 0),
+            _ => (major, minor, patch + 1),
+        }
+    }
+
+    fn matches(&self, version: &RunnerVersion) -> bool {
+        let version = version.triple();
+        match self.op {
+            Op::Greater => {
+                // `>1.4` excludes every 1.4.x.
+                let mut bound = self.lower();
+                match self.parts.len() {
+                    1 => return version.0 > bound.0,
+                    2 => bound = (bound.0, bound.1, u64::MAX),
+                    _ => {}
+                }
+                version > bound
+            }
+            Op::GreaterEq => version >= self.lower(),
+            Op::Less => version < self.lower(),
+            Op::LessEq => version < self.upper_exact(),
+            Op::Exact | Op::Caret | Op::Tilde => version >= self.lower() && version < self.upper(),
+        }
+    }
+
+    /// First version past `<=`: everything the written prefix covers is included.
+    fn upper_exact(&self) -> (u64, u64, u64) {
+        Comparator {
+            op: Op::Exact,
+            parts: self.parts.clone(),
+        }
+        .upper()
+    }
+}
+
+/// A comma-separated range such as `>=1.4, <2` or `^1.4`, as in Cargo; a bare
+/// version is a caret range.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionReq {
+    text: String,
+    comparators: Vec<Comparator>,
+}
+
+impl VersionReq {
+    pub fn parse(value: &str) -> anyhow::Result<Self> {
+        let comparators = value
+            .split(',')
+            .map(Comparator::parse)
+            .collect::<anyhow::Result<Vec<_>>>()
+            .with_context(|| format!("invalid runner version range '{value}'"))?;
+        Ok(Self {
+            text: value.trim().to_string(),
+            comparators,
+        })
+    }
+
+    pub fn matches(&self, version: &RunnerVersion) -> bool {
+        self.comparators
+            .iter()
+            .all(|comparator| comparator.matches(version))
+    }
+}
+
+impl fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+fn parse_parts(value: &str) -> anyhow::Result<Vec<u64>> {
+    if value.is_empty() {
+        return Err(anyhow!("empty version"));
+    }
+    value
+        .split('.')
+        .map(|part| {
+            part.parse::<u64>()
+                .map_err(|_| anyhow!("invalid version '{value}'"))
+        })
+        .collect()
+}
+
+/// The version `binary --version` reports.
+pub fn runner_version(binary: &Path) -> anyhow::Result<RunnerVersion> {
+    let output = Command::new(binary)
+        .arg("--version")
+        .output()
+        .with_context(|| format!("failed to run {} --version", binary.display()))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    RunnerVersion::from_output(&stdout)
+        .or_else(|| RunnerVersion::from_output(&String::from_utf8_lossy(&output.stderr)))
+        .ok_or_else(|| {
+            anyhow!(
+                "{} --version printed no version: {}",
+                binary.display(),
+                stdout.trim()
+            )
+        })
+}
+
+/// `<arch>-<os>` of this operator build, as substituted for `{target}`.
+pub fn current_target() -> String {
+    format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS)
+}
+
+fn binary_file_name() -> String {
+    if cfg!(windows) {
+        format!("{RUNNER_BINARY}.exe")
+    } else {
+        RUNNER_BINARY.to_string()
+    }
+}
+
+/// The runner the bundle should use. Without `runner.version` this is `explicit`
+/// unchanged; with it, a runner of exactly that version or an error saying how to
+/// get one.
+pub fn resolve_runner(
+    bundle: &Path,
+    config: &DemoRunnerConfig,
+    explicit: Option<PathBuf>,
+) -> anyhow::Result<Option<PathBuf>> {
+    let Some(pinned) = config.version.as_deref() else {
+        return Ok(explicit);
+    };
+    let want = RunnerVersion::parse(pinned).context("invalid runner.version")?;
+    let cached = state_layout::runner_bin_dir(bundle, &want.to_string()).join(binary_file_name());
+    if cached.is_file() {
+        return Ok(Some(cached));
+    }
+
+    let found = bin_resolver::resolve_binary(
+        RUNNER_BINARY,
+        &ResolveCtx {
+            config_dir: bundle.to_path_buf(),
+            explicit_path: explicit,
+        },
+    );
+    let mismatch = match found {
+        Ok(path) => match runner_version(&path) {
+            Ok(version) if version == want => return Ok(Some(path)),
+            Ok(version) => format!("{} is {version}", path.display()),
+            Err(err) => format!("{err:#}"),
+        },
+        Err(err) => format!("{err:#}"),
+    };
+
+    if !config.auto_download {
+        return Err(anyhow!(
+            "greentic.demo.yaml pins greentic-runner {want}, but {mismatch}\nSuggestions:\n  - install greentic-runner {want} or pass --runner-binary\n  - set runner.auto_download: true and runner.download_url to fetch it into {}",
+            cached.parent().unwrap_or(bundle).display()
+        ));
+    }
+    operator_log::info(
+        module_path!(),
+        format!("downloading greentic-runner {want}: {mismatch}"),
+    );
+    download_runner(config, &want, &cached)?;
+    Ok(Some(cached))
+}
+
+/// Downloads the runner of `version` to `dest`, refusing it on a checksum mismatch.
+pub fn download_runner(
+    config: &DemoRunnerConfig,
+    version: &RunnerVersion,
+    dest: &Path,
+) -> anyhow::Result<()> {
+    let target = current_target();
+    let template = config
+        .download_url
+        .as_deref()
+        .ok_or_else(|| anyhow!("runner.auto_download is set but runner.download_url is not"))?;
+    let url = template
+        .replace("{version}", &version.to_string())
+        .replace("{target}", &target);
+    let expected = match config.sha256.get(&target) {
+        Some(sum) => sum.trim().to_ascii_lowercase(),
+        None => fetch_checksum(&format!("{url}.sha256"))?,
+    };
+
+    let dir = dest
+        .parent()
+        .ok_or_else(|| anyhow!("invalid runner path {}", dest.display()))?;
+    fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    let partial = dest.with_extension("partial");
+    let mut response = ureq::get(&url)
+        .call()
+        .map_err(|err| anyhow!("download {url} failed: {err}"))?;
+    let mut reader = response.body_mut().as_reader();
+    let mut file =
+        fs::File::create(&partial).with_context(|| format!("create {}", partial.display()))?;
+    let mut context = digest::Context::new(&digest::SHA256);
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let read = reader
+            .read(&mut buf)
+            .with_context(|| format!("download {url}"))?;
+        if read == 0 {
+            break;
+        }
+        context.update(&buf[..read]);
+        file.write_all(&buf[..read])?;
+    }
+    file.sync_all()?;
+    drop(file);
+
+    let actual = hex(context.finish().as_ref());
+    if actual != expected {
+        let _ = fs::remove_file(&partial);
+        return Err(anyhow!(
+            "checksum mismatch for {url}: expected sha256 {expected}, got {actual}"
+        ));
+    }
+    make_executable(&partial)?;
+    fs::rename(&partial, dest)
+        .with_context(|| format!("move {} to {}", partial.display(), dest.display()))?;
+    Ok(())
+}
+
+fn fetch_checksum(url: &str) -> anyhow::Result<String> {
+    let body = ureq::get(url)
+        .call()
+        .map_err(|err| {
+            anyhow!(
+                "no sha256 configured for {} and {url} failed: {err}",
+                current_target()
+            )
+        })?
+        .body_mut()
+        .read_to_string()
+        .with_context(|| format!("read {url}"))?;
+    body.split_whitespace()
+        .next()
+        .filter(|sum| sum.len() == 64 && sum.chars().all(|ch| ch.is_ascii_hexdigit()))
+        .map(str::to_ascii_lowercase)
+        .ok_or_else(|| anyhow!("{url} does not start with a sha256 checksum"))
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(unix)]
+pub(crate) fn make_executable(path: &Path) -> anyhow::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+        .with_context(|| format!("chmod {}", path.display()))
+}
+
+#[cfg(not(unix))]
+pub(crate) fn make_executable(_: &Path) -> anyhow::Result<()> {
+    Ok(())
+}
+
+/// A pack whose `meta.runner_version` the runner does not satisfy.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Incompatible {
+    pub pack: PathBuf,
+    pub requirement: String,
+}
+
+/// Packs under `packs` whose `meta.runner_version` excludes `version`.
+pub fn incompatible_packs(
+    packs: &[PathBuf],
+    version: &RunnerVersion,
+) -> anyhow::Result<Vec<Incompatible>> {
+    let mut incompatible = Vec::new();
+    for pack in packs {
+        let Some(requirement) = domains::read_runner_requirement(pack)? else {
+            continue;
+        };
+        let req = VersionReq::parse(&requirement)
+            .with_context(|| format!("meta.runner_version of {}", pack.display()))?;
+        if !req.matches(version) {
+            incompatible.push(Incompatible {
+                pack: pack.clone(),
+                requirement,
+            });
+        }
+    }
+    Ok(incompatible)
+}
+
+/// Fails when `runner` is outside the range any of `packs` declares. A runner that
+/// reports no version is only warned about.
+pub fn check_packs(runner: &Path, packs: &[PathBuf]) -> anyhow::Result<()> {
+    let mut requirements = Vec::new();
+    for pack in packs {
+        if domains::read_runner_requirement(pack)?.is_some() {
+            requirements.push(pack.clone());
+        }
+    }
+    if requirements.is_empty() {
+        return Ok(());
+    }
+    let version = match runner_version(runner) {
+        Ok(version) => version,
+        Err(err) => {
+            operator_log::warn(
+                module_path!(),
+                format!("skipping pack runner_version checks: {err:#}"),
+            );
+            return Ok(());
+        }
+    };
+    let incompatible = incompatible_packs(&requirements, &version)?;
+    if incompatible.is_empty() {
+        return Ok(());
+    }
+    let lines = incompatible
+        .iter()
+        .map(|pack| format!("  - {} needs {}", pack.pack.display(), pack.requirement))
+        .collect::<Vec<_>>()
+        .join("\n");
+    Err(anyhow!(
+        "greentic-runner {version} at {} is incompatible with:\n{lines}",
+        runner.display()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(value: &str) -> RunnerVersion {
+        RunnerVersion::parse(value).unwrap()
+    }
+
+    fn req(value: &str) -> VersionReq {
+        VersionReq::parse(value).unwrap()
+    }
+
+    #[test]
+    fn parses_versions_and_matches_ranges() {
+        assert_eq!(
+            RunnerVersion::from_output("greentic-runner v1.4.2\n"),
+            Some(version("1.4.2"))
+        );
+        assert_eq!(version("1.5.0-rc.1").to_string(), "1.5.0-rc.1");
+        assert!(RunnerVersion::parse("1.4").is_err());
+
+        assert!(req("1.4").matches(&version("1.9.0")));
+        assert!(!req("1.4").matches(&version("2.0.0")));
+        assert!(!req("^0.4.1").matches(&version("0.5.0")));
+        assert!(req("~1.4").matches(&version("1.4.9")));
+        assert!(!req("~1.4").matches(&version("1.5.0")));
+        assert!(req("=1.4").matches(&version("1.4.7")));
+        assert!(!req("=1.4.2").matches(&version("1.4.3")));
+        assert!(req(">=1.4, <2").matches(&version("1.10.0")));
+        assert!(!req(">=1.4, <2").matches(&version("2.0.0")));
+        assert!(!req(">1.4").matches(&version("1.4.9")));
+        assert!(req(">1.4").matches(&version("1.5.0")));
+        assert!(req("<=1.4").matches(&version("1.4.9")));
+        assert!(!req("<=1.4").matches(&version("1.5.0")));
+        assert!(VersionReq::parse(">=one").is_err());
+    }
+
+    #[test]
+    fn unpinned_bundles_keep_the_explicit_runner() {
+        let dir = tempfile::tempdir().unwrap();
+        let explicit = Some(dir.path().join("runner"));
+        assert_eq!(
+            resolve_runner(dir.path(), &DemoRunnerConfig::default(), explicit.clone()).unwrap(),
+            explicit
+        );
+
+        let cached = state_layout::runner_bin_dir(dir.path(), "1.4.2").join(binary_file_name());
+        fs::create_dir_all(cached.parent().unwrap()).unwrap();
+        fs::write(&cached, b"").unwrap();
+        let pinned = DemoRunnerConfig {
+            version: Some("v1.4.2".to_string()),
+            ..DemoRunnerConfig::default()
+        };
+        assert_eq!(
+            resolve_runner(dir.path(), &pinned, None).unwrap(),
+            Some(cached)
+        );
+        let err = resolve_runner(
+            dir.path(),
+            &DemoRunnerConfig {
+                version: Some("1.5.0".to_string()),
+                ..DemoRunnerConfig::default()
+            },
+            explicit,
+        )
+        .unwrap_err();
+        assert!(format!("{err:#}").contains("pins greentic-runner 1.5.0"));
+    }
+}
@@ -3,6 +3,7 @@ use std::path::{Path, PathBuf};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
+#[derive(Clone, Debug)]
 pub struct RuntimePaths {
     state_dir: PathBuf,
     log_root: PathBuf,
@@ -34,6 +35,14 @@ impl RuntimePaths {
         format!("{}.{}", self.tenant, self.team)
     }
 
+    pub fn tenant(&self) -> &str {
+        &self.tenant
+    }
+
+    pub fn team(&self) -> &str {
+        &self.team
+    }
+
     pub fn runtime_root(&self) -> PathBuf {
         self.state_dir.join("runtime").join(self.key())
     }
@@ -103,15 +112,39 @@ pub fn read_json<T: DeserializeOwned>(path: &Path) -> anyhow::Result<Option<T>>
     Ok(Some(value))
 }
 
+/// Like [`write_json`], for files that hold credentials: on Unix the file is created
+/// readable by its owner only (0600) before anything is written to it.
+pub fn write_private_json<T: Serialize>(path: &Path, value: &T) -> anyhow::Result<()> {
+    let bytes = serde_json::to_vec_pretty(value)?;
+    write_atomically(path, &bytes, true)
+}
+
 pub fn atomic_write(path: &Path, bytes: &[u8]) -> anyhow::Result<()> {
+    write_atomically(path, bytes, false)
+}
+
+fn write_atomically(path: &Path, bytes: &[u8], private: bool) -> anyhow::Result<()> {
     use std::io::Write;
 
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
     }
+    // Unique per writer, so concurrent writes of one path (parallel setup) don't
+    // share a temp file; the last rename wins.
+    static NEXT_TMP: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
     let mut tmp = path.to_path_buf();
-    tmp.set_extension("tmp");
-    let mut file = std::fs::File::create(&tmp)?;
+    tmp.set_extension(format!(
+        "{}.{}.tmp",
+        std::process::id(),
+        NEXT_TMP.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+    ));
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    if private {
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    }
+    let mut file = options.open(&tmp)?;
     file.write_all(bytes)?;
     file.sync_all()?;
     std::fs::rename(&tmp, path)?;
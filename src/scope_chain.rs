@@ -0,0 +1,109 @@
+//! The inheritance chain for secrets and provider config: team, then tenant, then
+//! bundle default.
+//!
+//! A team reads its own value first, then its tenant's, then the bundle's, so a
+//! team only stores the keys it overrides. Secrets use `_` for "any": tenant-level
+//! secrets live at `secrets://env/<tenant>/_/...` and bundle defaults at
+//! `secrets://env/_/_/...`. Provider config envelopes live under
+//! `state/runtime/<tenant>.<team>`, `state/runtime/<tenant>`, and `state/runtime/_`.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::secrets_manager;
+
+/// Tenant or team segment that matches any tenant or team.
+pub const ANY: &str = "_";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScopeLevel {
+    Team,
+    Tenant,
+    Bundle,
+}
+
+impl ScopeLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ScopeLevel::Team => "team",
+            ScopeLevel::Tenant => "tenant",
+            ScopeLevel::Bundle => "bundle",
+        }
+    }
+}
+
+/// One level of the chain and the tenant/team segments it is stored under.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScopeLink {
+    pub level: ScopeLevel,
+    pub tenant: String,
+    pub team: String,
+}
+
+/// Secret levels for `tenant`/`team`, most specific first. No team (or `default`)
+/// starts at tenant level; tenant `_` is the bundle level alone.
+pub fn secret_chain(tenant: &str, team: Option<&str>) -> Vec<ScopeLink> {
+    let team = secrets_manager::canonical_team(team);
+    let mut links = Vec::new();
+    if tenant != ANY {
+        if team != ANY {
+            links.push(ScopeLink {
+                level: ScopeLevel::Team,
+                tenant: tenant.to_string(),
+                team: team.into_owned(),
+            });
+        }
+        links.push(ScopeLink {
+            level: ScopeLevel::Tenant,
+            tenant: tenant.to_string(),
+            team: ANY.to_string(),
+        });
+    }
+    links.push(ScopeLink {
+        level: ScopeLevel::Bundle,
+        tenant: ANY.to_string(),
+        team: ANY.to_string(),
+    });
+    links
+}
+
+/// Provider config directories for `tenant`/`team`, most specific first. Unlike
+/// secrets, the `default` team has a directory of its own (`demo start` writes
+/// there), so it is always the first level.
+pub fn config_roots(state_dir: &Path, tenant: &str, team: &str) -> Vec<(ScopeLevel, PathBuf)> {
+    let runtime = state_dir.join("runtime");
+    vec![
+        (
+            ScopeLevel::Team,
+            runtime.join(format!("{tenant}.{team}")).join("providers"),
+        ),
+        (ScopeLevel::Tenant, runtime.join(tenant).join("providers")),
+        (ScopeLevel::Bundle, runtime.join(ANY).join("providers")),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secret_chain_skips_levels_the_scope_does_not_have() {
+        let levels = |tenant, team| {
+            secret_chain(tenant, team)
+                .into_iter()
+                .map(|link| format!("{}:{}/{}", link.level.as_str(), link.tenant, link.team))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            levels("acme", Some("support")),
+            vec!["team:acme/support", "tenant:acme/_", "bundle:_/_"]
+        );
+        assert_eq!(
+            levels("acme", Some("default")),
+            vec!["tenant:acme/_", "bundle:_/_"]
+        );
+        assert_eq!(levels("_", None), vec!["bundle:_/_"]);
+    }
+}
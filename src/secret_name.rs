@@ -27,8 +27,28 @@ fn normalize_char(ch: char) -> Option<char> {
     }
 }
 
+/// Splits a `@v<N>` version suffix off a secret name (`bot_token@v2` -> `bot_token`, 2).
+pub fn split_version(raw: &str) -> (&str, Option<u32>) {
+    if let Some((name, version)) = raw.rsplit_once("@v")
+        && let Ok(version) = version.parse::<u32>()
+        && version > 0
+    {
+        return (name, Some(version));
+    }
+    (raw, None)
+}
+
+pub fn versioned_name(name: &str, version: Option<u32>) -> String {
+    match version {
+        Some(version) => format!("{name}@v{version}"),
+        None => name.to_string(),
+    }
+}
+
 /// Convert a raw secret name (e.g. TELEGRAM_BOT_TOKEN) into the store-friendly canonical form.
+/// A `@v<N>` version suffix is kept as-is after the canonical name.
 pub fn canonical_secret_name(raw: &str) -> String {
+    let (raw, version) = split_version(raw);
     let mut result = String::with_capacity(raw.len());
     let mut prev_underscore = false;
 
@@ -53,7 +73,7 @@ pub fn canonical_secret_name(raw: &str) -> String {
         trimmed
     };
     trace_if_needed(raw, &canonical);
-    canonical
+    versioned_name(&canonical, version)
 }
 
 /// Apply [`canonical_secret_name`] to each segment of a slash-delimited key path.
@@ -0,0 +1,402 @@
+//! AWS Secrets Manager / SSM Parameter Store backend for the secrets manager.
+//!
+//! Like the other cloud helpers this shells out to the `aws` CLI, so credentials,
+//! SSO sessions, and profiles behave exactly as they do in the user's terminal. A
+//! canonical `secrets://env/tenant/team/provider/key` URI maps to one secret (or
+//! SecureString parameter) named from `secrets.aws.name_template`. Values are
+//! passed to the CLI through `--cli-input-json`, never on the command line: on
+//! stdin where the platform has `/dev/stdin`, otherwise in a file in the user's
+//! temp directory that is removed when the CLI exits. The CLI runs on tokio's
+//! blocking pool.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result as AnyhowResult};
+use async_trait::async_trait;
+use greentic_secrets_lib::{Result as SecretResult, SecretError, SecretsManager};
+use serde_json::{Value as JsonValue, json};
+
+use crate::bin_resolver::{self, ResolveCtx};
+use crate::config::{AwsSecretsService, DemoAwsSecretsConfig};
+use crate::secrets_gate::parse_canonical_secret_uri;
+
+pub struct AwsSecretsManager {
+    client: Arc<AwsClient>,
+}
+
+/// The synchronous side of [`AwsSecretsManager`], shared with blocking tasks.
+struct AwsClient {
+    binary: PathBuf,
+    config: DemoAwsSecretsConfig,
+    cache: Mutex<HashMap<String, (Instant, Vec<u8>)>>,
+}
+
+enum AwsCallError {
+    NotFound,
+    Failed(String),
+}
+
+impl AwsSecretsManager {
+    pub fn new(bundle_root: &Path, config: &DemoAwsSecretsConfig) -> AnyhowResult<Self> {
+        let explicit = PathBuf::from(&config.binary);
+        let explicit_path = (explicit.components().count() > 1).then_some(explicit);
+        let binary = bin_resolver::resolve_binary(
+            &config.binary,
+            &ResolveCtx {
+                config_dir: bundle_root.to_path_buf(),
+                explicit_path,
+            },
+        )
+        .context("aws secrets backend needs the aws CLI")?;
+        Ok(Self {
+            client: Arc::new(AwsClient {
+                binary,
+                config: config.clone(),
+                cache: Mutex::new(HashMap::new()),
+            }),
+        })
+    }
+
+    /// Secret name (Secrets Manager) or parameter path (SSM) for a canonical URI.
+    pub fn name_for(&self, uri: &str) -> Option<String> {
+        name_for(&self.client.config, uri)
+    }
+
+    fn name_or_err(&self, uri: &str) -> SecretResult<String> {
+        self.name_for(uri).ok_or_else(|| {
+            SecretError::Backend(format!("not a canonical secrets URI: {uri}").into())
+        })
+    }
+
+    /// Runs `call` on tokio's blocking pool so the aws CLI does not stall the
+    /// executor.
+    async fn blocking<T: Send + 'static>(
+        &self,
+        call: impl FnOnce(&AwsClient) -> SecretResult<T> + Send + 'static,
+    ) -> SecretResult<T> {
+        let client = self.client.clone();
+        tokio::task::spawn_blocking(move || call(&client))
+            .await
+            .map_err(|err| SecretError::Backend(format!("aws CLI task failed: {err}").into()))?
+    }
+}
+
+impl AwsClient {
+    fn cached(&self, name: &str) -> Option<Vec<u8>> {
+        let ttl = Duration::from_secs(self.config.cache_ttl_seconds);
+        let cache = self.cache.lock().ok()?;
+        let (stored_at, value) = cache.get(name)?;
+        (stored_at.elapsed() < ttl).then(|| value.clone())
+    }
+
+    fn store_cached(&self, name: &str, value: Option<&[u8]>) {
+        if self.config.cache_ttl_seconds == 0 {
+            return;
+        }
+        if let Ok(mut cache) = self.cache.lock() {
+            match value {
+                Some(value) => {
+                    cache.insert(name.to_string(), (Instant::now(), value.to_vec()));
+                }
+                None => {
+                    cache.remove(name);
+                }
+            }
+        }
+    }
+
+    /// Runs the CLI with `args`; `input` goes in through `--cli-input-json`.
+    fn run(&self, args: &[String], input: Option<&JsonValue>) -> Result<String, AwsCallError> {
+        let input = input
+            .map(CliInput::new)
+            .transpose()
+            .map_err(|err| AwsCallError::Failed(format!("prepare aws CLI input: {err}")))?;
+        let mut command = Command::new(&self.binary);
+        command.args(args).args(self.scope_args());
+        if let Some(input) = &input {
+            command.args(["--cli-input-json", &input.arg]);
+        }
+        let stdin = input.as_ref().and_then(|input| input.stdin.as_deref());
+        command
+            .stdin(if stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let mut child = command
+            .spawn()
+            .map_err(|err| AwsCallError::Failed(format!("spawn aws CLI: {err}")))?;
+        if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+            pipe.write_all(input.as_bytes())
+                .map_err(|err| AwsCallError::Failed(format!("write aws CLI input: {err}")))?;
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|err| AwsCallError::Failed(format!("wait for aws CLI: {err}")))?;
+        drop(input);
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        if stderr.contains("ResourceNotFoundException") || stderr.contains("ParameterNotFound") {
+            Err(AwsCallError::NotFound)
+        } else {
+            Err(AwsCallError::Failed(stderr))
+        }
+    }
+
+    fn scope_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(region) = &self.config.region {
+            args.extend(["--region".to_string(), region.clone()]);
+        }
+        if let Some(profile) = &self.config.profile {
+            args.extend(["--profile".to_string(), profile.clone()]);
+        }
+        args
+    }
+
+    fn read_value(&self, name: &str) -> Result<Vec<u8>, AwsCallError> {
+        let output = self.run(&read_args(self.config.service, name), None)?;
+        // `--output text` appends a newline; values themselves keep any inner whitespace.
+        let value = output.strip_suffix('\n').unwrap_or(&output);
+        Ok(value.as_bytes().to_vec())
+    }
+
+    fn write_value(&self, name: &str, value: &[u8]) -> Result<(), AwsCallError> {
+        let value = String::from_utf8_lossy(value).into_owned();
+        match self.config.service {
+            AwsSecretsService::Ssm => {
+                let input = json!({
+                    "Name": name,
+                    "Value": value,
+                    "Type": "SecureString",
+                    "Overwrite": true,
+                });
+                self.run(&input_args("ssm", "put-parameter"), Some(&input))?;
+            }
+            AwsSecretsService::SecretsManager => {
+                let input = json!({ "SecretId": name, "SecretString": value });
+                match self.run(
+                    &input_args("secretsmanager", "put-secret-value"),
+                    Some(&input),
+                ) {
+                    Err(AwsCallError::NotFound) => {
+                        let input = json!({ "Name": name, "SecretString": value });
+                        self.run(&input_args("secretsmanager", "create-secret"), Some(&input))?;
+                    }
+                    other => {
+                        other?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn delete_value(&self, name: &str) -> Result<(), AwsCallError> {
+        let args = match self.config.service {
+            AwsSecretsService::Ssm => vec![
+                "ssm".to_string(),
+                "delete-parameter".to_string(),
+                "--name".to_string(),
+                name.to_string(),
+            ],
+            AwsSecretsService::SecretsManager => vec![
+                "secretsmanager".to_string(),
+                "delete-secret".to_string(),
+                "--secret-id".to_string(),
+                name.to_string(),
+                "--force-delete-without-recovery".to_string(),
+            ],
+        };
+        match self.run(&args, None) {
+            Ok(_) | Err(AwsCallError::NotFound) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Where the CLI reads `--cli-input-json` from.
+struct CliInput {
+    arg: String,
+    /// Written to the CLI's stdin.
+    stdin: Option<String>,
+    /// Removed on drop.
+    file: Option<PathBuf>,
+}
+
+impl CliInput {
+    #[cfg(unix)]
+    fn new(input: &JsonValue) -> std::io::Result<Self> {
+        Ok(Self {
+            arg: "file:///dev/stdin".to_string(),
+            stdin: Some(input.to_string()),
+            file: None,
+        })
+    }
+
+    /// Windows has no `/dev/stdin`; the CLI reads a file in the user's own temp
+    /// directory instead.
+    #[cfg(not(unix))]
+    fn new(input: &JsonValue) -> std::io::Result<Self> {
+        let path = std::env::temp_dir().join(format!(
+            "greentic-aws-{}-{}.json",
+            std::process::id(),
+            uuid::Uuid::new_v4().simple()
+        ));
+        let cli_input = Self {
+            arg: format!("file://{}", path.display()),
+            stdin: None,
+            file: Some(path.clone()),
+        };
+        std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?
+            .write_all(input.to_string().as_bytes())?;
+        Ok(cli_input)
+    }
+}
+
+impl Drop for CliInput {
+    fn drop(&mut self) {
+        if let Some(path) = &self.file {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[async_trait]
+impl SecretsManager for AwsSecretsManager {
+    async fn read(&self, path: &str) -> SecretResult<Vec<u8>> {
+        let name = self.name_or_err(path)?;
+        if let Some(value) = self.client.cached(&name) {
+            return Ok(value);
+        }
+        let path = path.to_string();
+        self.blocking(move |client| match client.read_value(&name) {
+            Ok(value) => {
+                client.store_cached(&name, Some(&value));
+                Ok(value)
+            }
+            Err(AwsCallError::NotFound) => Err(SecretError::NotFound(path)),
+            Err(AwsCallError::Failed(err)) => Err(SecretError::Backend(
+                format!("aws read {name} failed: {err}").into(),
+            )),
+        })
+        .await
+    }
+
+    async fn write(&self, path: &str, value: &[u8]) -> SecretResult<()> {
+        let name = self.name_or_err(path)?;
+        let (path, value) = (path.to_string(), value.to_vec());
+        self.blocking(move |client| match client.write_value(&name, &value) {
+            Ok(()) => {
+                client.store_cached(&name, Some(&value));
+                Ok(())
+            }
+            Err(AwsCallError::NotFound) => Err(SecretError::NotFound(path)),
+            Err(AwsCallError::Failed(err)) => Err(SecretError::Backend(
+                format!("aws write {name} failed: {err}").into(),
+            )),
+        })
+        .await
+    }
+
+    async fn delete(&self, path: &str) -> SecretResult<()> {
+        let name = self.name_or_err(path)?;
+        self.client.store_cached(&name, None);
+        let path = path.to_string();
+        self.blocking(move |client| {
+            client.delete_value(&name).map_err(|err| match err {
+                AwsCallError::NotFound => SecretError::NotFound(path),
+                AwsCallError::Failed(err) => {
+                    SecretError::Backend(format!("aws delete {name} failed: {err}").into())
+                }
+            })
+        })
+        .await
+    }
+}
+
+fn name_for(config: &DemoAwsSecretsConfig, uri: &str) -> Option<String> {
+    let uri = parse_canonical_secret_uri(uri)?;
+    let name = config
+        .name_template
+        .replace("{env}", &uri.env)
+        .replace("{tenant}", &uri.tenant)
+        .replace("{team}", &uri.team)
+        .replace("{provider}", &uri.provider)
+        .replace("{key}", &uri.key);
+    match config.service {
+        AwsSecretsService::Ssm if !name.starts_with('/') => Some(format!("/{name}")),
+        _ => Some(name),
+    }
+}
+
+fn read_args(service: AwsSecretsService, name: &str) -> Vec<String> {
+    let args: &[&str] = match service {
+        AwsSecretsService::SecretsManager => &[
+            "secretsmanager",
+            "get-secret-value",
+            "--secret-id",
+            name,
+            "--query",
+            "SecretString",
+            "--output",
+            "text",
+        ],
+        AwsSecretsService::Ssm => &[
+            "ssm",
+            "get-parameter",
+            "--name",
+            name,
+            "--with-decryption",
+            "--query",
+            "Parameter.Value",
+            "--output",
+            "text",
+        ],
+    };
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+fn input_args(service: &str, operation: &str) -> Vec<String> {
+    vec![service.to_string(), operation.to_string()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_uri_maps_to_secret_name_or_parameter_path() {
+        let uri = "secrets://dev/acme/_/telegram/bot_token";
+        let mut config = DemoAwsSecretsConfig::default();
+        assert_eq!(
+            name_for(&config, uri).as_deref(),
+            Some("greentic/dev/acme/_/telegram/bot_token")
+        );
+        config.service = AwsSecretsService::Ssm;
+        assert_eq!(
+            name_for(&config, uri).as_deref(),
+            Some("/greentic/dev/acme/_/telegram/bot_token")
+        );
+        assert!(name_for(&config, "secrets://dev/acme/telegram").is_none());
+    }
+
+    #[test]
+    fn ssm_reads_decrypt_secure_strings() {
+        let args = read_args(AwsSecretsService::Ssm, "/greentic/x");
+        assert_eq!(&args[..2], ["ssm", "get-parameter"]);
+        assert!(args.contains(&"--with-decryption".to_string()));
+    }
+}
@@ -1,9 +1,21 @@
-use std::{fs::File, io::Read, path::Path};
+use std::{
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use anyhow::{Context, Result, anyhow};
+use greentic_secrets_lib::env::EnvSecretsManager;
 use serde::Deserialize;
 use zip::ZipArchive;
 
+use crate::config::{DemoAwsSecretsConfig, DemoSecretsBackend, DemoSecretsConfig, DemoVaultConfig};
+use crate::secrets_aws::AwsSecretsManager;
+use crate::secrets_client::SecretsClient;
+use crate::secrets_gate::DynSecretsManager;
+use crate::secrets_vault::VaultSecretsManager;
+
 const BACKEND_CONFIG_PATHS: &[&str] = &[
     "assets/secrets_backend.json",
     "assets/secrets-backend.json",
@@ -15,6 +27,8 @@ const BACKEND_CONFIG_PATHS: &[&str] = &[
 pub enum SecretsBackendKind {
     DevStore,
     Env,
+    Vault,
+    Aws,
 }
 
 impl std::fmt::Display for SecretsBackendKind {
@@ -22,11 +36,122 @@ impl std::fmt::Display for SecretsBackendKind {
         let label = match self {
             SecretsBackendKind::DevStore => "dev-store",
             SecretsBackendKind::Env => "env",
+            SecretsBackendKind::Vault => "vault",
+            SecretsBackendKind::Aws => "aws",
         };
         f.write_str(label)
     }
 }
 
+impl SecretsBackendKind {
+    /// Backend forced by `secrets.backend` in greentic.demo.yaml; `None` defers to the pack.
+    pub fn from_config(config: &DemoSecretsConfig) -> Option<Self> {
+        match config.backend {
+            DemoSecretsBackend::Pack => None,
+            DemoSecretsBackend::DevStore => Some(Self::DevStore),
+            DemoSecretsBackend::Env => Some(Self::Env),
+            DemoSecretsBackend::Vault => Some(Self::Vault),
+            DemoSecretsBackend::Aws => Some(Self::Aws),
+        }
+    }
+}
+
+/// An opened backend: the manager plus the dev store file when there is one.
+pub struct OpenedSecretsBackend {
+    pub manager: DynSecretsManager,
+    pub dev_store_path: Option<PathBuf>,
+}
+
+/// A place secrets can be read from and written to. Implementations only have to
+/// produce a `SecretsManager`; logging, team fallback, and env fallback are layered on
+/// top by `secrets_gate`.
+pub trait SecretsBackend {
+    fn kind(&self) -> SecretsBackendKind;
+    fn open(&self, bundle_root: &Path) -> Result<OpenedSecretsBackend>;
+}
+
+pub struct DevStoreBackend;
+
+impl SecretsBackend for DevStoreBackend {
+    fn kind(&self) -> SecretsBackendKind {
+        SecretsBackendKind::DevStore
+    }
+
+    fn open(&self, bundle_root: &Path) -> Result<OpenedSecretsBackend> {
+        let client = SecretsClient::open(bundle_root)?;
+        let dev_store_path = client.store_path().map(Path::to_path_buf);
+        Ok(OpenedSecretsBackend {
+            manager: Arc::new(client),
+            dev_store_path,
+        })
+    }
+}
+
+pub struct EnvBackend;
+
+impl SecretsBackend for EnvBackend {
+    fn kind(&self) -> SecretsBackendKind {
+        SecretsBackendKind::Env
+    }
+
+    fn open(&self, _bundle_root: &Path) -> Result<OpenedSecretsBackend> {
+        Ok(OpenedSecretsBackend {
+            manager: Arc::new(EnvSecretsManager),
+            dev_store_path: None,
+        })
+    }
+}
+
+pub struct VaultBackend {
+    config: DemoVaultConfig,
+}
+
+impl SecretsBackend for VaultBackend {
+    fn kind(&self) -> SecretsBackendKind {
+        SecretsBackendKind::Vault
+    }
+
+    fn open(&self, _bundle_root: &Path) -> Result<OpenedSecretsBackend> {
+        Ok(OpenedSecretsBackend {
+            manager: Arc::new(VaultSecretsManager::connect(&self.config)?),
+            dev_store_path: None,
+        })
+    }
+}
+
+pub struct AwsBackend {
+    config: DemoAwsSecretsConfig,
+}
+
+impl SecretsBackend for AwsBackend {
+    fn kind(&self) -> SecretsBackendKind {
+        SecretsBackendKind::Aws
+    }
+
+    fn open(&self, bundle_root: &Path) -> Result<OpenedSecretsBackend> {
+        Ok(OpenedSecretsBackend {
+            manager: Arc::new(AwsSecretsManager::new(bundle_root, &self.config)?),
+            dev_store_path: None,
+        })
+    }
+}
+
+pub fn backend_for(
+    kind: SecretsBackendKind,
+    config: &DemoSecretsConfig,
+) -> Box<dyn SecretsBackend> {
+    match kind {
+        SecretsBackendKind::DevStore => Box::new(DevStoreBackend),
+        SecretsBackendKind::Env => Box::new(EnvBackend),
+        SecretsBackendKind::Vault => Box::new(VaultBackend {
+            config: config.vault.clone(),
+        }),
+        SecretsBackendKind::Aws => Box::new(AwsBackend {
+            config: config.aws.clone(),
+        }),
+    }
+}
+
 #[derive(Deserialize)]
 struct PackBackendConfig {
     backend: Option<String>,
@@ -49,6 +174,8 @@ pub fn backend_kind_from_pack(pack_path: &Path) -> Result<SecretsBackendKind> {
                 return match kind.trim().to_ascii_lowercase().as_str() {
                     "" | "default" | "dev-store" | "devstore" => Ok(SecretsBackendKind::DevStore),
                     "env" | "environment" => Ok(SecretsBackendKind::Env),
+                    "vault" | "hashicorp-vault" => Ok(SecretsBackendKind::Vault),
+                    "aws" | "aws-secrets-manager" | "aws-ssm" => Ok(SecretsBackendKind::Aws),
                     other => Err(anyhow!(
                         "unsupported secrets backend '{other}' in pack {}",
                         pack_path.display()
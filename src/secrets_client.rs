@@ -1,4 +1,5 @@
 use crate::dev_store_path;
+use crate::secrets_crypto::{self, DevStoreCipher};
 use anyhow::{Result as AnyhowResult, anyhow};
 use async_trait::async_trait;
 use greentic_secrets_lib::{
@@ -13,6 +14,9 @@ use std::{
 pub struct SecretsClient {
     store: Arc<DevStore>,
     store_path: Option<PathBuf>,
+    /// Key for encrypted stores. A key that cannot be loaded only fails reads of
+    /// sealed values, so plaintext stores never need a passphrase.
+    cipher: Result<Option<DevStoreCipher>, String>,
 }
 
 impl SecretsClient {
@@ -24,20 +28,17 @@ impl SecretsClient {
             return Self::open_with_path(path);
         }
         let store_path = dev_store_path::ensure_path(bundle_root)?;
-        let store = DevStore::with_path(store_path.clone())
-            .map_err(|err| anyhow!("failed to open dev secrets store: {err}"))?;
-        Ok(Self {
-            store: Arc::new(store),
-            store_path: Some(store_path),
-        })
+        Self::open_with_path(store_path)
     }
 
     pub fn open_with_path(path: PathBuf) -> AnyhowResult<Self> {
         let store = DevStore::with_path(path.clone())
             .map_err(|err| anyhow!("failed to open dev secrets store: {err}"))?;
+        let cipher = DevStoreCipher::for_store(&path).map_err(|err| format!("{err:#}"));
         Ok(Self {
             store: Arc::new(store),
             store_path: Some(path),
+            cipher,
         })
     }
 
@@ -51,6 +52,15 @@ impl SecretsManager for SecretsClient {
     async fn read(&self, path: &str) -> SecretResult<Vec<u8>> {
         let result = self.store.get(path).await;
         match result {
+            Ok(value) if secrets_crypto::is_sealed(&value) => match &self.cipher {
+                Ok(Some(cipher)) => cipher
+                    .open(path, &value)
+                    .map_err(|err| SecretError::Backend(err.to_string().into())),
+                Ok(None) => Err(SecretError::Backend(
+                    format!("{path} is encrypted but the dev store has no key metadata").into(),
+                )),
+                Err(err) => Err(SecretError::Backend(err.clone().into())),
+            },
             Ok(value) => Ok(value),
             Err(CoreError::NotFound { entity }) => Err(SecretError::NotFound(entity)),
             Err(err) => Err(SecretError::Backend(err.to_string().into())),
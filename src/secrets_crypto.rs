@@ -0,0 +1,594 @@
+//! At-rest encryption for the dev secrets store.
+//!
+//! The dev store only knows about text values, so encryption sits one level up:
+//! sealed values are stored as `enc:v1:<base64(nonce || ciphertext)>` and
+//! `SecretsClient` opens them on read. The AES-256-GCM key is derived with
+//! PBKDF2-SHA256 from a passphrase (env var) or an OS keychain entry. The salt, the
+//! key source, and a check value live in `<store>.meta.json` next to the store; the
+//! same file lists every URI the operator wrote so `demo secrets rekey` can find them,
+//! plus the per-key version history kept by `secrets_versions`.
+//!
+//! `rekey` records the new key as pending before it rewrites any value and only
+//! promotes it once every value is rewritten. Until then values open with either key,
+//! so a rekey that fails or is interrupted half way leaves the store readable and can
+//! simply be run again.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result, anyhow};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use greentic_secrets_lib::core::Error as CoreError;
+use greentic_secrets_lib::{
+    ApplyOptions, DevStore, SecretFormat, SecretsStore, SeedDoc, SeedEntry, SeedValue, apply_seed,
+};
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{DemoDevStoreConfig, DevStoreEncryption};
+use crate::runtime_state;
+use crate::secrets_versions::{self, SecretHistory};
+
+pub const SEALED_PREFIX: &str = "enc:v1:";
+const PBKDF2_ITERATIONS: u32 = 210_000;
+const SALT_LEN: usize = 16;
+const CHECK_AAD: &str = "greentic-dev-store:check";
+const CHECK_PLAINTEXT: &[u8] = b"greentic-dev-store";
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DevStoreMeta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<DevStoreKeyInfo>,
+    /// Key of a `rekey` that has not finished yet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_encryption: Option<DevStoreKeyInfo>,
+    #[serde(default)]
+    pub uris: BTreeSet<String>,
+    /// Keyed by the unversioned URI.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub versions: BTreeMap<String, SecretHistory>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DevStoreKeyInfo {
+    pub source: KeySource,
+    pub iterations: u32,
+    pub salt: String,
+    /// A known plaintext sealed with the key, so a wrong passphrase fails up front.
+    pub check: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum KeySource {
+    Passphrase { env: String },
+    Keychain { service: String, account: String },
+}
+
+impl KeySource {
+    /// Target key source from `secrets.dev_store`; `None` means plaintext.
+    pub fn from_config(config: &DemoDevStoreConfig) -> Option<Self> {
+        match config.encryption {
+            DevStoreEncryption::None => None,
+            DevStoreEncryption::Passphrase => Some(Self::Passphrase {
+                env: config.passphrase_env.clone(),
+            }),
+            DevStoreEncryption::Keychain => Some(Self::Keychain {
+                service: config.keychain_service.clone(),
+                account: config.keychain_account.clone(),
+            }),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Passphrase { env } => format!("passphrase (${env})"),
+            Self::Keychain { service, account } => {
+                format!("keychain (service={service} account={account})")
+            }
+        }
+    }
+
+    fn secret(&self) -> Result<String> {
+        let secret = match self {
+            Self::Passphrase { env } => env::var(env)
+                .with_context(|| format!("dev store is encrypted with a passphrase; set ${env}"))?,
+            Self::Keychain { service, account } => keychain_lookup(service, account)?,
+        };
+        if secret.trim().is_empty() {
+            return Err(anyhow!("empty key material from {}", self.describe()));
+        }
+        Ok(secret)
+    }
+}
+
+/// Key source `secrets.dev_store` in the bundle's greentic.demo.yaml asks for; `None`
+/// for plaintext or a bundle without a demo config.
+pub fn configured_key_source(bundle: &Path) -> Result<Option<KeySource>> {
+    let path = bundle.join("greentic.demo.yaml");
+    if !path.exists() {
+        return Ok(None);
+    }
+    let config = crate::config::load_demo_config(&path)?;
+    Ok(KeySource::from_config(&config.secrets.dev_store))
+}
+
+pub fn meta_path(store_path: &Path) -> PathBuf {
+    let mut name = store_path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .unwrap_or_default();
+    name.push(".meta.json");
+    store_path.with_file_name(name)
+}
+
+pub fn read_meta(store_path: &Path) -> Result<DevStoreMeta> {
+    Ok(runtime_state::read_json(&meta_path(store_path))?.unwrap_or_default())
+}
+
+pub fn write_meta(store_path: &Path, meta: &DevStoreMeta) -> Result<()> {
+    runtime_state::write_json(&meta_path(store_path), meta)
+}
+
+pub fn is_sealed(value: &[u8]) -> bool {
+    value.starts_with(SEALED_PREFIX.as_bytes())
+}
+
+pub struct DevStoreCipher {
+    key: LessSafeKey,
+    /// The key of an unfinished rekey, tried when `key` cannot open a value.
+    pending: Option<LessSafeKey>,
+}
+
+impl DevStoreCipher {
+    /// Cipher for an encrypted store; `None` when the store holds plaintext.
+    pub fn for_store(store_path: &Path) -> Result<Option<Self>> {
+        Self::for_meta(&read_meta(store_path)?)
+    }
+
+    fn for_meta(meta: &DevStoreMeta) -> Result<Option<Self>> {
+        let current = meta
+            .encryption
+            .as_ref()
+            .map(Self::from_key_info)
+            .transpose()?;
+        let pending = meta
+            .pending_encryption
+            .as_ref()
+            .map(Self::from_key_info)
+            .transpose()?;
+        Ok(match (current, pending) {
+            (Some(mut current), Some(pending)) => {
+                current.pending = Some(pending.key);
+                Some(current)
+            }
+            (current, pending) => current.or(pending),
+        })
+    }
+
+    pub fn from_key_info(info: &DevStoreKeyInfo) -> Result<Self> {
+        let salt = STANDARD
+            .decode(&info.salt)
+            .context("dev store metadata has an invalid salt")?;
+        let cipher = Self::derive(&info.source.secret()?, &salt, info.iterations)?;
+        cipher
+            .open(CHECK_AAD, info.check.as_bytes())
+            .ok()
+            .filter(|value| value == CHECK_PLAINTEXT)
+            .ok_or_else(|| anyhow!("wrong key for dev store ({})", info.source.describe()))?;
+        Ok(cipher)
+    }
+
+    /// Fresh salt and key for `source`, plus the metadata describing them.
+    pub fn create(source: &KeySource) -> Result<(Self, DevStoreKeyInfo)> {
+        let mut salt = [0u8; SALT_LEN];
+        SystemRandom::new()
+            .fill(&mut salt)
+            .map_err(|_| anyhow!("system random generator failed"))?;
+        let cipher = Self::derive(&source.secret()?, &salt, PBKDF2_ITERATIONS)?;
+        let info = DevStoreKeyInfo {
+            source: source.clone(),
+            iterations: PBKDF2_ITERATIONS,
+            salt: STANDARD.encode(salt),
+            check: cipher.seal(CHECK_AAD, CHECK_PLAINTEXT)?,
+        };
+        Ok((cipher, info))
+    }
+
+    fn derive(secret: &str, salt: &[u8], iterations: u32) -> Result<Self> {
+        let iterations =
+            NonZeroU32::new(iterations).ok_or_else(|| anyhow!("pbkdf2 iterations must be > 0"))?;
+        let mut key = [0u8; 32];
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            iterations,
+            salt,
+            secret.as_bytes(),
+            &mut key,
+        );
+        let key = UnboundKey::new(&AES_256_GCM, &key).map_err(|_| anyhow!("invalid AES key"))?;
+        Ok(Self {
+            key: LessSafeKey::new(key),
+            pending: None,
+        })
+    }
+
+    /// The URI is bound as associated data, so a sealed value cannot be moved to
+    /// another key.
+    pub fn seal(&self, uri: &str, plaintext: &[u8]) -> Result<String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| anyhow!("system random generator failed"))?;
+        let mut in_out = plaintext.to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(uri.as_bytes()),
+                &mut in_out,
+            )
+            .map_err(|_| anyhow!("failed to encrypt {uri}"))?;
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&in_out);
+        Ok(format!("{SEALED_PREFIX}{}", STANDARD.encode(sealed)))
+    }
+
+    pub fn open(&self, uri: &str, sealed: &[u8]) -> Result<Vec<u8>> {
+        match (Self::open_with(&self.key, uri, sealed), &self.pending) {
+            (Err(_), Some(pending)) => Self::open_with(pending, uri, sealed),
+            (opened, _) => opened,
+        }
+    }
+
+    fn open_with(key: &LessSafeKey, uri: &str, sealed: &[u8]) -> Result<Vec<u8>> {
+        let encoded = sealed
+            .strip_prefix(SEALED_PREFIX.as_bytes())
+            .ok_or_else(|| anyhow!("{uri} is not an encrypted value"))?;
+        let mut data = STANDARD
+            .decode(encoded)
+            .with_context(|| format!("{uri} has a malformed encrypted value"))?;
+        if data.len() < NONCE_LEN {
+            return Err(anyhow!("{uri} has a truncated encrypted value"));
+        }
+        let mut in_out = data.split_off(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(&data)
+            .map_err(|_| anyhow!("{uri} has an invalid nonce"))?;
+        let plaintext = key
+            .open_in_place(nonce, Aad::from(uri.as_bytes()), &mut in_out)
+            .map_err(|_| anyhow!("failed to decrypt {uri} (wrong key or tampered value)"))?;
+        Ok(plaintext.to_vec())
+    }
+}
+
+/// Turns entries into what should be written: adds the version copies, seals text
+/// values when the store is encrypted, and records URIs and history in the store
+/// metadata. Call right before `apply_seed`.
+///
+/// `target` is the key source the config asks for ([`configured_key_source`]). A new
+/// store without a key gets one on its first write; a store that already holds
+/// plaintext values is refused until `demo secrets rekey` encrypts it.
+pub fn prepare_seed_entries(
+    store_path: &Path,
+    target: Option<&KeySource>,
+    entries: Vec<SeedEntry>,
+) -> Result<Vec<SeedEntry>> {
+    let mut meta = read_meta(store_path)?;
+    let cipher = match (meta.encryption.as_ref(), target) {
+        (Some(info), _) => Some(DevStoreCipher::from_key_info(info)?),
+        (None, Some(source)) if is_fresh_store(store_path, &meta) => {
+            let (cipher, info) = DevStoreCipher::create(source)?;
+            meta.encryption = Some(info);
+            Some(cipher)
+        }
+        (None, Some(source)) => {
+            return Err(anyhow!(
+                "secrets.dev_store asks for {} encryption but {} is not encrypted yet; \
+                 run `demo secrets rekey` before writing secrets",
+                source.describe(),
+                store_path.display()
+            ));
+        }
+        (None, None) => None,
+    };
+    let mut entries = secrets_versions::expand_versions(&mut meta.versions, entries);
+    for entry in entries.iter_mut() {
+        if let (Some(cipher), SeedValue::Text { text }) = (cipher.as_ref(), &mut entry.value) {
+            *text = cipher.seal(&entry.uri, text.as_bytes())?;
+        }
+        meta.uris.insert(entry.uri.clone());
+    }
+    write_meta(store_path, &meta)?;
+    Ok(entries)
+}
+
+/// No values written yet, so a key can be set up without a rekey.
+fn is_fresh_store(store_path: &Path, meta: &DevStoreMeta) -> bool {
+    meta.uris.is_empty()
+        && meta.pending_encryption.is_none()
+        && std::fs::metadata(store_path).map_or(0, |file| file.len()) == 0
+}
+
+#[derive(Debug, Default)]
+pub struct RekeyReport {
+    pub rewritten: Vec<String>,
+    pub missing: Vec<String>,
+}
+
+/// Re-encrypts every known value under `target` (`None` decrypts the store). URIs
+/// come from the store metadata plus `extra_uris`, which covers stores written before
+/// the metadata existed. The new key is saved as pending before anything is
+/// rewritten; running again after a failure resumes with that key.
+pub async fn rekey(
+    store_path: &Path,
+    extra_uris: impl IntoIterator<Item = String>,
+    target: Option<&KeySource>,
+) -> Result<RekeyReport> {
+    let mut meta = read_meta(store_path)?;
+    let current = DevStoreCipher::for_meta(&meta)?;
+    let next = match (target, meta.pending_encryption.as_ref()) {
+        (Some(source), Some(pending)) if pending.source == *source => {
+            Some((DevStoreCipher::from_key_info(pending)?, pending.clone()))
+        }
+        (Some(_), Some(pending)) => {
+            return Err(anyhow!(
+                "an earlier rekey to {} did not finish; run it again with that key first",
+                pending.source.describe()
+            ));
+        }
+        (Some(source), None) => Some(DevStoreCipher::create(source)?),
+        (None, _) => None,
+    };
+    if let Some((_, info)) = &next {
+        meta.pending_encryption = Some(info.clone());
+        write_meta(store_path, &meta)?;
+    }
+    let store = DevStore::with_path(store_path).map_err(|err| {
+        anyhow!(
+            "failed to open dev secrets store {}: {err}",
+            store_path.display()
+        )
+    })?;
+
+    let mut uris = meta.uris.clone();
+    uris.extend(extra_uris);
+    let mut report = RekeyReport::default();
+    let mut entries = Vec::new();
+    for uri in uris {
+        let value = match store.get(&uri).await {
+            Ok(value) => value,
+            Err(CoreError::NotFound { .. }) => {
+                report.missing.push(uri);
+                continue;
+            }
+            Err(err) => return Err(anyhow!("failed to read secret {uri}: {err}")),
+        };
+        let plaintext = if is_sealed(&value) {
+            current
+                .as_ref()
+                .ok_or_else(|| anyhow!("{uri} is encrypted but the store has no key metadata"))?
+                .open(&uri, &value)?
+        } else {
+            value
+        };
+        let text = match next.as_ref() {
+            Some((cipher, _)) => cipher.seal(&uri, &plaintext)?,
+            None => String::from_utf8(plaintext)
+                .map_err(|_| anyhow!("{uri} is not valid UTF-8 and cannot be stored as text"))?,
+        };
+        entries.push(SeedEntry {
+            uri: uri.clone(),
+            format: SecretFormat::Text,
+            value: SeedValue::Text { text },
+            description: None,
+        });
+        report.rewritten.push(uri);
+    }
+
+    if !entries.is_empty() {
+        let result = apply_seed(&store, &SeedDoc { entries }, ApplyOptions::default()).await;
+        if !result.failed.is_empty() {
+            return Err(anyhow!("failed to rewrite secrets: {:?}", result.failed));
+        }
+    }
+    meta.encryption = next.map(|(_, info)| info);
+    meta.pending_encryption = None;
+    meta.uris = report.rewritten.iter().cloned().collect();
+    write_meta(store_path, &meta)?;
+    Ok(report)
+}
+
+fn keychain_lookup(service: &str, account: &str) -> Result<String> {
+    let (program, args): (&str, Vec<&str>) = if cfg!(target_os = "macos") {
+        (
+            "security",
+            vec!["find-generic-password", "-s", service, "-a", account, "-w"],
+        )
+    } else if cfg!(target_os = "windows") {
+        return Err(anyhow!(
+            "keychain-backed dev store encryption is not supported on Windows; use a passphrase"
+        ));
+    } else {
+        (
+            "secret-tool",
+            vec!["lookup", "service", service, "account", account],
+        )
+    };
+    let output = Command::new(program)
+        .args(&args)
+        .output()
+        .with_context(|| format!("run {program} to read the dev store key"))?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "no keychain entry service={service} account={account} ({program}: {})",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let secret = String::from_utf8_lossy(&output.stdout);
+    Ok(secret.trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use tokio::runtime::Runtime;
+
+    const PASSPHRASE_ENV: &str = "GREENTIC_TEST_DEV_STORE_PASS";
+
+    fn passphrase() -> KeySource {
+        unsafe {
+            env::set_var(PASSPHRASE_ENV, "correct horse battery staple");
+        }
+        KeySource::Passphrase {
+            env: PASSPHRASE_ENV.to_string(),
+        }
+    }
+
+    fn seed_store(store_path: &Path, uri: &str, text: &str) -> Result<DevStore> {
+        let store = DevStore::with_path(store_path)?;
+        let seed = SeedDoc {
+            entries: vec![SeedEntry {
+                uri: uri.to_string(),
+                format: SecretFormat::Text,
+                value: SeedValue::Text {
+                    text: text.to_string(),
+                },
+                description: None,
+            }],
+        };
+        Runtime::new()?.block_on(apply_seed(&store, &seed, ApplyOptions::default()));
+        Ok(store)
+    }
+
+    #[test]
+    fn sealed_values_are_bound_to_their_uri() -> Result<()> {
+        let (cipher, _) = DevStoreCipher::create(&passphrase())?;
+        let sealed = cipher.seal("secrets://dev/acme/_/telegram/bot_token", b"123:abc")?;
+        assert!(is_sealed(sealed.as_bytes()));
+        assert!(!sealed.contains("123:abc"));
+        assert_eq!(
+            cipher.open("secrets://dev/acme/_/telegram/bot_token", sealed.as_bytes())?,
+            b"123:abc".to_vec()
+        );
+        assert!(
+            cipher
+                .open("secrets://dev/acme/_/slack/bot_token", sealed.as_bytes())
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rekey_encrypts_and_decrypts_existing_store() -> Result<()> {
+        let dir = tempdir()?;
+        let store_path = dir.path().join(".dev.secrets.env");
+        let uri = "secrets://dev/acme/_/telegram/bot_token".to_string();
+        let runtime = Runtime::new()?;
+        let store = seed_store(&store_path, &uri, "123:abc")?;
+
+        let source = passphrase();
+        let report = runtime.block_on(rekey(&store_path, [uri.clone()], Some(&source)))?;
+        assert_eq!(report.rewritten, vec![uri.clone()]);
+        let sealed = runtime.block_on(store.get(&uri))?;
+        assert!(is_sealed(&sealed));
+        let cipher = DevStoreCipher::for_store(&store_path)?.expect("encrypted store");
+        assert_eq!(cipher.open(&uri, &sealed)?, b"123:abc".to_vec());
+
+        runtime.block_on(rekey(&store_path, Vec::<String>::new(), None))?;
+        assert_eq!(runtime.block_on(store.get(&uri))?, b"123:abc".to_vec());
+        assert!(read_meta(&store_path)?.encryption.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn interrupted_rekey_stays_readable_and_resumes() -> Result<()> {
+        let dir = tempdir()?;
+        let store_path = dir.path().join(".dev.secrets.env");
+        let uri = "secrets://dev/acme/_/telegram/bot_token".to_string();
+        let runtime = Runtime::new()?;
+        let store = seed_store(&store_path, &uri, "123:abc")?;
+        let source = passphrase();
+
+        // As if the process died after the pending key was saved and the value rewritten.
+        let (cipher, info) = DevStoreCipher::create(&source)?;
+        let mut meta = read_meta(&store_path)?;
+        meta.pending_encryption = Some(info.clone());
+        write_meta(&store_path, &meta)?;
+        let sealed = cipher.seal(&uri, b"123:abc")?;
+        let seed = SeedDoc {
+            entries: vec![SeedEntry {
+                uri: uri.clone(),
+                format: SecretFormat::Text,
+                value: SeedValue::Text { text: sealed },
+                description: None,
+            }],
+        };
+        runtime.block_on(apply_seed(&store, &seed, ApplyOptions::default()));
+
+        let reader = DevStoreCipher::for_store(&store_path)?.expect("pending key");
+        let value = runtime.block_on(store.get(&uri))?;
+        assert_eq!(reader.open(&uri, &value)?, b"123:abc".to_vec());
+
+        let other = KeySource::Passphrase {
+            env: "GREENTIC_TEST_OTHER_PASS".to_string(),
+        };
+        assert!(
+            runtime
+                .block_on(rekey(&store_path, Vec::<String>::new(), Some(&other)))
+                .is_err()
+        );
+
+        runtime.block_on(rekey(&store_path, [uri.clone()], Some(&source)))?;
+        let meta = read_meta(&store_path)?;
+        assert!(meta.pending_encryption.is_none());
+        assert_eq!(meta.encryption.map(|info| info.salt), Some(info.salt));
+        let cipher = DevStoreCipher::for_store(&store_path)?.expect("encrypted store");
+        let value = runtime.block_on(store.get(&uri))?;
+        assert_eq!(cipher.open(&uri, &value)?, b"123:abc".to_vec());
+        Ok(())
+    }
+
+    #[test]
+    fn configured_encryption_never_writes_plaintext() -> Result<()> {
+        let dir = tempdir()?;
+        let runtime = Runtime::new()?;
+        let source = passphrase();
+        let uri = "secrets://dev/acme/_/telegram/bot_token";
+        let entry = || SeedEntry {
+            uri: uri.to_string(),
+            format: SecretFormat::Text,
+            value: SeedValue::Text {
+                text: "123:abc".to_string(),
+            },
+            description: None,
+        };
+
+        // A new store gets its key on the first write.
+        let store_path = dir.path().join("fresh/.dev.secrets.env");
+        let entries = prepare_seed_entries(&store_path, Some(&source), vec![entry()])?;
+        let SeedValue::Text { text } = &entries[0].value else {
+            panic!("text entry");
+        };
+        assert!(is_sealed(text.as_bytes()));
+        let cipher = DevStoreCipher::for_store(&store_path)?.expect("key was set up");
+        assert_eq!(cipher.open(uri, text.as_bytes())?, b"123:abc".to_vec());
+
+        // A plaintext store has to be rekeyed first.
+        let store_path = dir.path().join("plain/.dev.secrets.env");
+        std::fs::create_dir_all(store_path.parent().unwrap())?;
+        let store = seed_store(&store_path, uri, "old")?;
+        let err = prepare_seed_entries(&store_path, Some(&source), vec![entry()])
+            .err()
+            .expect("plaintext store is refused");
+        assert!(err.to_string().contains("demo secrets rekey"));
+        assert!(read_meta(&store_path)?.encryption.is_none());
+        assert_eq!(runtime.block_on(store.get(uri))?, b"old".to_vec());
+        Ok(())
+    }
+}
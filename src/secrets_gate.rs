@@ -18,12 +18,17 @@ use tokio::runtime::Builder;
 use tracing::info;
 use zip::{ZipArchive, result::ZipError};
 
+use crate::config::{self, DemoSecretsConfig, SecretsFallbackPolicy};
+use crate::discovery;
 use crate::operator_log;
+use crate::profile::{self, Phase};
+use crate::redaction;
+use crate::scope_chain::{self, ScopeLevel};
 use crate::secret_name;
 use crate::secret_value::SecretValue;
-use crate::secrets_backend::SecretsBackendKind;
-use crate::secrets_client::SecretsClient;
+use crate::secrets_backend::{self, SecretsBackendKind};
 use crate::secrets_manager;
+use crate::secrets_setup;
 
 type CborMap = BTreeMap<CborValue, CborValue>;
 
@@ -64,6 +69,7 @@ impl SecretsManager for LoggingSecretsManager {
         );
         match self.inner.read(path).await {
             Ok(value) => {
+                redaction::register_secret(&value);
                 operator_log::debug(
                     module_path!(),
                     format!(
@@ -74,28 +80,23 @@ impl SecretsManager for LoggingSecretsManager {
                 Ok(value)
             }
             Err(err) => {
-                // Fallback: if team-specific secret not found, try team="_" (wildcard).
-                // Secrets saved at tenant-level (no team) live under "_" but runtime
-                // may read with a specific team from the routing context.
-                if let Some(fallback_path) = team_wildcard_fallback(path) {
+                // Fall back along the team -> tenant -> bundle chain: a team only
+                // stores the secrets it overrides.
+                for fallback_path in inherited_fallback_uris(path) {
                     operator_log::info(
                         module_path!(),
-                        format!(
-                            "WASM secrets read fallback: team-specific not found, trying uri={fallback_path}",
-                        ),
+                        format!("WASM secrets read fallback: trying inherited uri={fallback_path}",),
                     );
-                    match self.inner.read(&fallback_path).await {
-                        Ok(value) => {
-                            operator_log::debug(
-                                module_path!(),
-                                format!(
-                                    "WASM secrets read fallback resolved uri={fallback_path}; value={}",
-                                    SecretValue::new(value.as_slice()),
-                                ),
-                            );
-                            return Ok(value);
-                        }
-                        Err(_) => {}
+                    if let Ok(value) = self.inner.read(&fallback_path).await {
+                        redaction::register_secret(&value);
+                        operator_log::debug(
+                            module_path!(),
+                            format!(
+                                "WASM secrets read fallback resolved uri={fallback_path}; value={}",
+                                SecretValue::new(value.as_slice()),
+                            ),
+                        );
+                        return Ok(value);
                     }
                 }
                 Err(err)
@@ -112,24 +113,44 @@ impl SecretsManager for LoggingSecretsManager {
     }
 }
 
-/// If `path` is `secrets://env/tenant/TEAM/provider/key` and TEAM != "_",
-/// return the same URI with TEAM replaced by "_".
-fn team_wildcard_fallback(path: &str) -> Option<String> {
-    let trimmed = path.strip_prefix("secrets://")?;
-    let segments: Vec<&str> = trimmed.split('/').collect();
-    if segments.len() != 5 {
-        return None;
+/// The less specific URIs `path` inherits from, in lookup order: the tenant-level
+/// and bundle-default URIs for a team secret, the bundle default for a tenant one.
+fn inherited_fallback_uris(path: &str) -> Vec<String> {
+    let Some(uri) = parse_canonical_secret_uri(path) else {
+        return Vec::new();
+    };
+    scope_chain::secret_chain(&uri.tenant, Some(&uri.team))
+        .into_iter()
+        .filter(|link| link.tenant != uri.tenant || link.team != uri.team)
+        .map(|link| {
+            format!(
+                "secrets://{}/{}/{}/{}/{}",
+                uri.env, link.tenant, link.team, uri.provider, uri.key
+            )
+        })
+        .collect()
+}
+const ENV_ALLOW_ENV_SECRETS: &str = "GREENTIC_ALLOW_ENV_SECRETS";
+const ENV_STRICT_SECRETS: &str = "GREENTIC_STRICT_SECRETS";
+
+/// `--strict-secrets`: deny the env fallback for this process, whatever the bundle
+/// config says.
+pub fn enable_strict_secrets() {
+    unsafe {
+        env::set_var(ENV_STRICT_SECRETS, "1");
     }
-    let team = segments[2];
-    if team == "_" || team.is_empty() {
-        return None; // Already wildcard, no fallback needed
+}
+
+fn fallback_policy(config: &DemoSecretsConfig, allow_env: bool) -> SecretsFallbackPolicy {
+    if matches!(env::var(ENV_STRICT_SECRETS).as_deref(), Ok("1")) {
+        return SecretsFallbackPolicy::Deny;
     }
-    Some(format!(
-        "secrets://{}/{}/{}/{}/{}",
-        segments[0], segments[1], "_", segments[3], segments[4]
-    ))
+    config.fallback.unwrap_or(if allow_env {
+        SecretsFallbackPolicy::Warn
+    } else {
+        SecretsFallbackPolicy::Deny
+    })
 }
-const ENV_ALLOW_ENV_SECRETS: &str = "GREENTIC_ALLOW_ENV_SECRETS";
 
 #[derive(Clone)]
 pub struct SecretsManagerHandle {
@@ -159,6 +180,7 @@ pub fn resolve_secrets_manager(
     tenant: &str,
     team: Option<&str>,
 ) -> AnyhowResult<SecretsManagerHandle> {
+    let _span = profile::span(Phase::Secrets, "open secrets backend");
     let canonical_team = secrets_manager::canonical_team(team);
     let team_owned = canonical_team.into_owned();
     let selection = secrets_manager::select_secrets_manager(bundle_root, tenant, &team_owned)?;
@@ -168,7 +190,12 @@ pub fn resolve_secrets_manager(
         .as_ref()
         .map(|path| path.display().to_string())
         .unwrap_or_else(|| "<none>".to_string());
-    let backend_kind_result = selection.kind();
+    let secrets_config = load_secrets_config(bundle_root)?;
+    // An explicit `secrets.backend` in the bundle config wins over the pack's choice.
+    let backend_kind_result = match SecretsBackendKind::from_config(&secrets_config) {
+        Some(kind) => Ok(kind),
+        None => selection.kind(),
+    };
     let backend_label = match &backend_kind_result {
         Ok(kind) => kind.to_string(),
         Err(_) => "<unknown>".to_string(),
@@ -190,11 +217,17 @@ pub fn resolve_secrets_manager(
             dev_secrets_path,
         ),
     );
+    let fallback = EnvFallback {
+        policy: fallback_policy(&secrets_config, allow_env),
+        bundle_root,
+        tenant,
+        team: &team_owned,
+        pack_desc: &pack_desc,
+    };
     let (manager, store_path, using_env_fallback) = instantiate_manager_from_selection(
         bundle_root,
-        &selection,
-        allow_env,
-        &pack_desc,
+        &secrets_config,
+        &fallback,
         backend_kind_result,
     )?;
     operator_log::info(
@@ -242,58 +275,111 @@ pub fn resolve_secrets_manager(
 
 fn instantiate_manager_from_selection(
     bundle_root: &Path,
-    selection: &secrets_manager::SecretsManagerSelection,
-    allow_env: bool,
-    pack_desc: &str,
+    secrets_config: &DemoSecretsConfig,
+    fallback: &EnvFallback<'_>,
     backend_kind_result: Result<SecretsBackendKind, AnyhowError>,
 ) -> AnyhowResult<(DynSecretsManager, Option<PathBuf>, bool)> {
     match backend_kind_result {
-        Ok(kind) => match instantiate_manager_for_backend(bundle_root, selection, kind) {
-            Ok((manager, path)) => Ok((manager, path, false)),
-            Err(err) => fallback_to_env(allow_env, kind.to_string(), pack_desc, err),
+        Ok(kind) => match secrets_backend::backend_for(kind, secrets_config).open(bundle_root) {
+            Ok(opened) => Ok((opened.manager, opened.dev_store_path, false)),
+            Err(err) => fallback.apply(&kind.to_string(), err),
         },
-        Err(err) => fallback_to_env(allow_env, "<unknown>".to_string(), pack_desc, err),
+        Err(err) => fallback.apply("<unknown>", err),
     }
 }
 
-fn fallback_to_env(
-    allow_env: bool,
-    kind_label: String,
-    pack_desc: &str,
-    err: AnyhowError,
-) -> AnyhowResult<(DynSecretsManager, Option<PathBuf>, bool)> {
-    if allow_env {
-        operator_log::warn(
-            module_path!(),
-            format!(
-                "secrets backend {kind} ({pack}) failed to initialize; falling back to env secrets backend: {err}",
-                kind = kind_label,
-                pack = pack_desc,
-            ),
+/// Context for deciding (and explaining) a fallback to `EnvSecretsManager`.
+struct EnvFallback<'a> {
+    policy: SecretsFallbackPolicy,
+    bundle_root: &'a Path,
+    tenant: &'a str,
+    team: &'a str,
+    pack_desc: &'a str,
+}
+
+impl EnvFallback<'_> {
+    fn apply(
+        &self,
+        kind_label: &str,
+        err: AnyhowError,
+    ) -> AnyhowResult<(DynSecretsManager, Option<PathBuf>, bool)> {
+        let summary = format!(
+            "secrets backend {kind_label} ({}) failed to initialize: {err}",
+            self.pack_desc
         );
+        match self.policy {
+            SecretsFallbackPolicy::Deny => {
+                return Err(anyhow!(
+                    "{summary}\nenv fallback is denied (secrets.fallback: deny or --strict-secrets); these secrets would have come from the environment:\n{}",
+                    self.env_sourced_list()
+                ));
+            }
+            SecretsFallbackPolicy::Warn => {
+                operator_log::warn(
+                    module_path!(),
+                    format!("{summary}; falling back to env secrets backend"),
+                );
+                eprintln!(
+                    "warning: {summary}\nfalling back to env secrets; these secrets will come from the environment:\n{}",
+                    self.env_sourced_list()
+                );
+            }
+            SecretsFallbackPolicy::Allow => {
+                operator_log::info(
+                    module_path!(),
+                    format!("{summary}; falling back to env secrets backend"),
+                );
+            }
+        }
         Ok((Arc::new(EnvSecretsManager) as DynSecretsManager, None, true))
-    } else {
-        Err(err)
+    }
+
+    fn env_sourced_list(&self) -> String {
+        let entries = env_sourced_secrets(self.bundle_root, self.tenant, self.team);
+        if entries.is_empty() {
+            return "  (no pack in the bundle declares secret requirements)".to_string();
+        }
+        entries
+            .into_iter()
+            .map(|(uri, var)| format!("  - {uri} (${var})"))
+            .collect::<Vec<_>>()
+            .join("\n")
     }
 }
 
-fn instantiate_manager_for_backend(
-    bundle_root: &Path,
-    _selection: &secrets_manager::SecretsManagerSelection,
-    backend_kind: SecretsBackendKind,
-) -> AnyhowResult<(DynSecretsManager, Option<PathBuf>)> {
-    match backend_kind {
-        SecretsBackendKind::DevStore => open_dev_store_manager(bundle_root),
-        SecretsBackendKind::Env => Ok((Arc::new(EnvSecretsManager) as DynSecretsManager, None)),
+/// Secret URIs required by the bundle's provider packs, paired with the env var the
+/// env backend reads each one from.
+fn env_sourced_secrets(bundle_root: &Path, tenant: &str, team: &str) -> Vec<(String, String)> {
+    let Ok(discovery) = discovery::discover(bundle_root) else {
+        return Vec::new();
+    };
+    let env = secrets_setup::resolve_env(None);
+    let mut entries = Vec::new();
+    for provider in &discovery.providers {
+        let Ok(keys) = load_secret_keys_from_pack(&provider.pack_path) else {
+            continue;
+        };
+        for key in keys {
+            let uri = canonical_secret_uri(&env, tenant, Some(team), &provider.provider_id, &key);
+            if let Some(var) = canonical_secret_store_key(&uri) {
+                entries.push((uri, var));
+            }
+        }
     }
+    entries.sort();
+    entries.dedup();
+    entries
 }
 
-fn open_dev_store_manager(
-    bundle_root: &Path,
-) -> AnyhowResult<(DynSecretsManager, Option<PathBuf>)> {
-    let client = SecretsClient::open(bundle_root)?;
-    let path = client.store_path().map(|path| path.to_path_buf());
-    Ok((Arc::new(client) as DynSecretsManager, path))
+/// `secrets:` section of greentic.demo.yaml; defaults when the bundle has none.
+/// A file that does not parse is an error rather than the defaults, so a configured
+/// backend is never quietly swapped for the dev store.
+fn load_secrets_config(bundle_root: &Path) -> AnyhowResult<DemoSecretsConfig> {
+    let path = bundle_root.join("greentic.demo.yaml");
+    if !path.exists() {
+        return Ok(DemoSecretsConfig::default());
+    }
+    Ok(config::load_demo_config(&path)?.secrets)
 }
 
 /// Build the canonical secrets URI for the provided identity.
@@ -317,6 +403,50 @@ pub fn canonical_secret_uri(
     )
 }
 
+/// Segments of a canonical `secrets://env/tenant/team/provider/key` URI. `key` keeps
+/// any `@v<N>` suffix, so each version is a separate entry in every backend.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CanonicalSecretUri {
+    pub env: String,
+    pub tenant: String,
+    pub team: String,
+    pub provider: String,
+    pub key: String,
+}
+
+impl CanonicalSecretUri {
+    pub fn version(&self) -> Option<u32> {
+        secret_name::split_version(&self.key).1
+    }
+}
+
+/// `secrets://.../key@v2` -> (`secrets://.../key`, 2); unversioned URIs pass through.
+pub fn split_secret_uri_version(uri: &str) -> (&str, Option<u32>) {
+    secret_name::split_version(uri)
+}
+
+pub fn versioned_secret_uri(uri: &str, version: u32) -> String {
+    secret_name::versioned_name(split_secret_uri_version(uri).0, Some(version))
+}
+
+pub fn parse_canonical_secret_uri(uri: &str) -> Option<CanonicalSecretUri> {
+    let trimmed = uri.strip_prefix("secrets://")?;
+    let segments: Vec<&str> = trimmed.split('/').collect();
+    let [env, tenant, team, provider, key] = segments.as_slice() else {
+        return None;
+    };
+    if segments.iter().any(|segment| segment.is_empty()) {
+        return None;
+    }
+    Some(CanonicalSecretUri {
+        env: env.to_string(),
+        tenant: tenant.to_string(),
+        team: team.to_string(),
+        provider: provider.to_string(),
+        key: key.to_string(),
+    })
+}
+
 pub fn canonical_secret_store_key(uri: &str) -> Option<String> {
     let trimmed = uri.strip_prefix("secrets://")?;
     let segments: Vec<&str> = trimmed.split('/').collect();
@@ -346,6 +476,26 @@ fn normalize_store_segment(segment: &str) -> String {
     normalized
 }
 
+/// `canonical_secret_uri` for each level of the team -> tenant -> bundle chain,
+/// most specific first.
+pub fn inherited_secret_uris(
+    env: &str,
+    tenant: &str,
+    team: Option<&str>,
+    provider: &str,
+    key: &str,
+) -> Vec<(ScopeLevel, String)> {
+    scope_chain::secret_chain(tenant, team)
+        .into_iter()
+        .map(|link| {
+            (
+                link.level,
+                canonical_secret_uri(env, &link.tenant, Some(&link.team), provider, key),
+            )
+        })
+        .collect()
+}
+
 fn secret_uri_candidates(
     env: &str,
     tenant: &str,
@@ -353,9 +503,10 @@ fn secret_uri_candidates(
     key: &str,
     provider_id: &str,
 ) -> Vec<String> {
-    let normalized_key = secret_name::canonical_secret_name(key);
-    let prefix = format!("secrets://{}/{}/{}/", env, tenant, canonical_team);
-    vec![format!("{prefix}{provider_id}/{normalized_key}")]
+    inherited_secret_uris(env, tenant, Some(canonical_team), provider_id, key)
+        .into_iter()
+        .map(|(_, uri)| uri)
+        .collect()
 }
 
 fn display_secret_candidates(
@@ -370,6 +521,61 @@ fn display_secret_candidates(
     vec![format!("{prefix}{provider_id}/{normalized_key}")]
 }
 
+/// A secret found along the inheritance chain, with the level that supplied it.
+#[derive(Clone, Debug)]
+pub struct InheritedSecret {
+    pub level: ScopeLevel,
+    pub uri: String,
+    pub value: String,
+}
+
+/// Reads `key` for `provider_id` as text; `None` when the store has no value for it.
+pub fn read_provider_secret(
+    handle: &SecretsManagerHandle,
+    env: &str,
+    tenant: &str,
+    team: Option<&str>,
+    provider_id: &str,
+    key: &str,
+) -> anyhow::Result<Option<String>> {
+    Ok(
+        resolve_provider_secret(handle, env, tenant, team, provider_id, key)?
+            .map(|secret| secret.value),
+    )
+}
+
+/// Like [`read_provider_secret`], but also says which level of the team -> tenant ->
+/// bundle chain the value came from.
+pub fn resolve_provider_secret(
+    handle: &SecretsManagerHandle,
+    env: &str,
+    tenant: &str,
+    team: Option<&str>,
+    provider_id: &str,
+    key: &str,
+) -> anyhow::Result<Option<InheritedSecret>> {
+    let _span = profile::span(Phase::Secrets, format_args!("{provider_id} {key}"));
+    let manager = handle.manager();
+    let runtime = Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("build secrets runtime")?;
+    runtime.block_on(async {
+        for (level, uri) in inherited_secret_uris(env, tenant, team, provider_id, key) {
+            match manager.read(&uri).await {
+                Ok(bytes) => {
+                    let value = String::from_utf8(bytes)
+                        .with_context(|| format!("secret {uri} is not valid UTF-8"))?;
+                    return Ok(Some(InheritedSecret { level, uri, value }));
+                }
+                Err(SecretError::NotFound(_)) => {}
+                Err(err) => return Err(anyhow!("read secret {uri}: {err}")),
+            }
+        }
+        Ok(None)
+    })
+}
+
 /// Check that the required secrets for the provider exist.
 #[allow(clippy::too_many_arguments)]
 pub fn check_provider_secrets(
@@ -383,6 +589,7 @@ pub fn check_provider_secrets(
     store_path: Option<&Path>,
     using_env_fallback: bool,
 ) -> anyhow::Result<Option<Vec<String>>> {
+    let _span = profile::span(Phase::Secrets, format_args!("check {provider_id}"));
     let keys = load_secret_keys_from_pack(pack_path)?;
     if keys.is_empty() {
         return Ok(None);
@@ -789,6 +996,37 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn team_inherits_tenant_and_bundle_secrets() -> anyhow::Result<()> {
+        let mut values = HashMap::new();
+        values.insert(
+            "secrets://demo/_/_/messaging-telegram/telegram_bot_token".to_string(),
+            b"token".to_vec(),
+        );
+        let manager: DynSecretsManager = Arc::new(FakeManager::new(values));
+        let result = check_provider_secrets(
+            &manager,
+            "demo",
+            "tenant",
+            Some("support"),
+            &telegram_pack_path(),
+            "messaging-telegram",
+            Some("messaging.telegram.bot"),
+            None,
+            false,
+        )?;
+        assert!(result.is_none());
+        assert_eq!(
+            inherited_fallback_uris("secrets://demo/tenant/support/messaging-telegram/token@v2"),
+            vec![
+                "secrets://demo/tenant/_/messaging-telegram/token@v2",
+                "secrets://demo/_/_/messaging-telegram/token@v2",
+            ]
+        );
+        assert!(inherited_fallback_uris("secrets://demo/_/_/messaging-telegram/token").is_empty());
+        Ok(())
+    }
+
     #[test]
     fn reads_provider_namespace_secret() -> anyhow::Result<()> {
         let dir = tempdir().unwrap();
@@ -1010,6 +1248,52 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn fallback_policy_from_config_is_overridden_by_strict_flag() -> anyhow::Result<()> {
+        let bundle_root = tempdir()?;
+        let tenant = "demo";
+        let team = "default";
+        let pack_dir = secrets_pack_dir(bundle_root.path(), tenant, team);
+        let _ = write_secrets_pack(&pack_dir, "bad-backend.gtpack", r#"{"backend":"vault"}"#)?;
+        fs::write(
+            bundle_root.path().join("greentic.demo.yaml"),
+            "secrets:\n  fallback: allow\n",
+        )?;
+        let env_guard = ENV_VAR_LOCK.lock().unwrap();
+        unsafe {
+            env::remove_var(ENV_ALLOW_ENV_SECRETS);
+            env::remove_var(ENV_STRICT_SECRETS);
+        }
+        let allowed = resolve_secrets_manager(bundle_root.path(), tenant, Some(team));
+        enable_strict_secrets();
+        let strict = resolve_secrets_manager(bundle_root.path(), tenant, Some(team));
+        unsafe {
+            env::remove_var(ENV_STRICT_SECRETS);
+        }
+        drop(env_guard);
+        assert!(allowed?.using_env_fallback);
+        let err = strict.err().expect("strict secrets deny the env fallback");
+        assert!(
+            err.to_string()
+                .contains("would have come from the environment")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unreadable_secrets_config_is_an_error() -> anyhow::Result<()> {
+        let bundle_root = tempdir()?;
+        fs::write(
+            bundle_root.path().join("greentic.demo.yaml"),
+            "secrets:\n  backend: [vault\n",
+        )?;
+        let err = resolve_secrets_manager(bundle_root.path(), "demo", Some("default"))
+            .err()
+            .expect("a broken greentic.demo.yaml must not fall back to the dev store");
+        assert!(format!("{err:#}").contains("greentic.demo.yaml"));
+        Ok(())
+    }
+
     fn write_secrets_pack(dir: &Path, name: &str, backend_config: &str) -> anyhow::Result<PathBuf> {
         fs::create_dir_all(dir)?;
         let pack_path = dir.join(name);
@@ -29,7 +29,10 @@ use serde_yaml_bw;
 use tracing::{debug, info};
 
 use crate::{
-    dev_store_path, secret_requirements::load_secret_keys_from_pack,
+    audit::{self, AuditEvent},
+    dev_store_path,
+    secret_requirements::load_secret_keys_from_pack,
+    secrets_crypto,
     secrets_gate::canonical_secret_uri,
 };
 
@@ -43,6 +46,7 @@ pub fn resolve_env(override_env: Option<&str>) -> String {
 pub struct SecretsSetup {
     store: DevStore,
     store_path: PathBuf,
+    bundle_root: PathBuf,
     env: String,
     tenant: String,
     team: Option<String>,
@@ -63,6 +67,7 @@ impl SecretsSetup {
         Ok(Self {
             store,
             store_path,
+            bundle_root: bundle_root.to_path_buf(),
             env: env.to_string(),
             tenant: tenant.to_string(),
             team: team.map(|value| value.to_string()),
@@ -113,16 +118,32 @@ impl SecretsSetup {
         if missing.is_empty() {
             return Ok(());
         }
+        let seeded = missing
+            .iter()
+            .map(|entry| entry.uri.clone())
+            .collect::<Vec<_>>();
+        let key_source = secrets_crypto::configured_key_source(&self.bundle_root)?;
+        let missing =
+            secrets_crypto::prepare_seed_entries(&self.store_path, key_source.as_ref(), missing)?;
         let report = apply_seed(
             &self.store,
             &SeedDoc { entries: missing },
             ApplyOptions::default(),
         )
         .await;
-        if !report.failed.is_empty() {
-            return Err(anyhow!("failed to seed secrets: {:?}", report.failed));
-        }
-        Ok(())
+        let result = if report.failed.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!("failed to seed secrets: {:?}", report.failed))
+        };
+        audit::record(
+            &self.bundle_root,
+            AuditEvent::new("secrets.seed", provider_id)
+                .scope(&self.tenant, self.team.as_deref())
+                .detail(serde_json::json!({ "uris": seeded }))
+                .result(&result),
+        );
+        result
     }
 }
 
@@ -0,0 +1,374 @@
+//! HashiCorp Vault KV backend for the secrets manager.
+//!
+//! A canonical `secrets://env/tenant/team/provider/key` URI maps to the Vault secret
+//! rendered from `secrets.vault.path_template` under the configured KV mount, and the
+//! URI's `key` is a field inside that secret. KV v2 (`<mount>/data/<path>`) is the
+//! default; set `kv_version: 1` for legacy mounts.
+//!
+//! Requests go through blocking `ureq` calls on tokio's blocking pool. The token is
+//! cached until its lease runs out; a 403 drops it, logs in again and retries once.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result as AnyhowResult, anyhow};
+use async_trait::async_trait;
+use greentic_secrets_lib::{Result as SecretResult, SecretError, SecretsManager};
+use serde_json::{Map as JsonMap, Value as JsonValue, json};
+
+use crate::config::{DemoVaultConfig, VaultAuthMethod};
+use crate::secrets_gate::{CanonicalSecretUri, parse_canonical_secret_uri};
+
+pub struct VaultSecretsManager {
+    client: Arc<VaultClient>,
+}
+
+struct VaultClient {
+    address: String,
+    config: DemoVaultConfig,
+    /// Token from the environment, or the AppRole login result (fetched lazily).
+    token: Mutex<Option<CachedToken>>,
+}
+
+#[derive(Clone, Debug)]
+struct CachedToken {
+    value: String,
+    /// Renew once this passes; `None` for tokens without a lease.
+    renew_at: Option<Instant>,
+}
+
+impl CachedToken {
+    /// Renews at 90% of the lease so a request never starts with an expiring token.
+    fn new(value: String, lease: Duration) -> Self {
+        let renew_at = (!lease.is_zero()).then(|| Instant::now() + lease.mul_f64(0.9));
+        Self { value, renew_at }
+    }
+
+    fn is_fresh(&self) -> bool {
+        self.renew_at
+            .is_none_or(|renew_at| Instant::now() < renew_at)
+    }
+}
+
+/// Where a canonical URI lives in Vault.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultLocation {
+    /// Path after `/v1/`, e.g. `secret/data/greentic/dev/demo/_/telegram`.
+    pub api_path: String,
+    pub field: String,
+}
+
+impl VaultSecretsManager {
+    pub fn connect(config: &DemoVaultConfig) -> AnyhowResult<Self> {
+        let address = config
+            .address
+            .clone()
+            .or_else(|| env::var("VAULT_ADDR").ok())
+            .filter(|value| !value.trim().is_empty())
+            .ok_or_else(|| anyhow!("vault backend needs secrets.vault.address or VAULT_ADDR"))?;
+        let client = VaultClient {
+            address: address.trim_end_matches('/').to_string(),
+            config: config.clone(),
+            token: Mutex::new(None),
+        };
+        if config.auth.method == VaultAuthMethod::Token {
+            // Fail up front when the token variable is missing.
+            client.token()?;
+        }
+        Ok(Self {
+            client: Arc::new(client),
+        })
+    }
+
+    pub fn locate(&self, uri: &str) -> Option<VaultLocation> {
+        self.client.locate(uri)
+    }
+
+    /// Runs `call` on tokio's blocking pool so `ureq` does not stall the executor.
+    async fn blocking<T: Send + 'static>(
+        &self,
+        call: impl FnOnce(&VaultClient) -> SecretResult<T> + Send + 'static,
+    ) -> SecretResult<T> {
+        let client = self.client.clone();
+        tokio::task::spawn_blocking(move || call(&client))
+            .await
+            .map_err(|err| {
+                SecretError::Backend(format!("vault request task failed: {err}").into())
+            })?
+    }
+}
+
+impl VaultClient {
+    fn locate(&self, uri: &str) -> Option<VaultLocation> {
+        locate(&self.config, &parse_canonical_secret_uri(uri)?)
+    }
+
+    fn token(&self) -> AnyhowResult<String> {
+        let mut cached = self
+            .token
+            .lock()
+            .map_err(|_| anyhow!("vault token lock poisoned"))?;
+        if let Some(token) = cached.as_ref().filter(|token| token.is_fresh()) {
+            return Ok(token.value.clone());
+        }
+        let token = self.login()?;
+        let value = token.value.clone();
+        *cached = Some(token);
+        Ok(value)
+    }
+
+    /// Drops `token` from the cache unless another request already replaced it.
+    fn forget(&self, token: &str) {
+        if let Ok(mut cached) = self.token.lock()
+            && cached.as_ref().is_some_and(|cached| cached.value == token)
+        {
+            *cached = None;
+        }
+    }
+
+    /// Runs `call` with the cached token; on a 403 logs in again and retries once.
+    fn with_token<T>(
+        &self,
+        call: impl Fn(&str) -> Result<T, ureq::Error>,
+    ) -> AnyhowResult<Result<T, ureq::Error>> {
+        let token = self.token()?;
+        match call(&token) {
+            Err(ureq::Error::StatusCode(403)) => {
+                self.forget(&token);
+                Ok(call(&self.token()?))
+            }
+            result => Ok(result),
+        }
+    }
+
+    fn login(&self) -> AnyhowResult<CachedToken> {
+        match self.config.auth.method {
+            VaultAuthMethod::Token => {
+                let token_env = &self.config.auth.token_env;
+                let value = env::var(token_env)
+                    .with_context(|| format!("vault token auth needs ${token_env}"))?;
+                Ok(CachedToken::new(value, Duration::ZERO))
+            }
+            VaultAuthMethod::AppRole => self.approle_login(),
+        }
+    }
+
+    fn approle_login(&self) -> AnyhowResult<CachedToken> {
+        let auth = &self.config.auth;
+        let role_id = auth
+            .role_id
+            .clone()
+            .or_else(|| env::var(&auth.role_id_env).ok())
+            .ok_or_else(|| {
+                anyhow!(
+                    "vault approle auth needs secrets.vault.auth.role_id or ${}",
+                    auth.role_id_env
+                )
+            })?;
+        let secret_id = env::var(&auth.secret_id_env)
+            .with_context(|| format!("vault approle auth needs ${}", auth.secret_id_env))?;
+        let url = format!("{}/v1/auth/{}/login", self.address, auth.approle_mount);
+        let mut request = ureq::post(&url);
+        if let Some(namespace) = &self.config.namespace {
+            request = request.header("X-Vault-Namespace", namespace);
+        }
+        let mut response = request
+            .send_json(json!({ "role_id": role_id, "secret_id": secret_id }))
+            .map_err(|err| anyhow!("vault approle login failed: {err}"))?;
+        let body: JsonValue = response
+            .body_mut()
+            .read_json()
+            .context("vault approle login returned invalid JSON")?;
+        let value = body
+            .pointer("/auth/client_token")
+            .and_then(JsonValue::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("vault approle login response has no auth.client_token"))?;
+        let lease = body
+            .pointer("/auth/lease_duration")
+            .and_then(JsonValue::as_u64)
+            .unwrap_or_default();
+        Ok(CachedToken::new(value, Duration::from_secs(lease)))
+    }
+
+    /// Field map of the Vault secret; `None` when the secret does not exist.
+    fn read_fields(&self, api_path: &str) -> AnyhowResult<Option<JsonMap<String, JsonValue>>> {
+        let url = format!("{}/v1/{api_path}", self.address);
+        let response = self.with_token(|token| {
+            let mut request = ureq::get(&url).header("X-Vault-Token", token);
+            if let Some(namespace) = &self.config.namespace {
+                request = request.header("X-Vault-Namespace", namespace);
+            }
+            request.call()
+        })?;
+        let mut response = match response {
+            Ok(response) => response,
+            Err(ureq::Error::StatusCode(404)) => return Ok(None),
+            Err(err) => return Err(anyhow!("vault GET {api_path} failed: {err}")),
+        };
+        let body: JsonValue = response
+            .body_mut()
+            .read_json()
+            .with_context(|| format!("vault GET {api_path} returned invalid JSON"))?;
+        let data = if self.config.kv_version == 1 {
+            body.get("data")
+        } else {
+            body.pointer("/data/data")
+        };
+        Ok(data.and_then(JsonValue::as_object).cloned())
+    }
+
+    fn write_fields(&self, api_path: &str, fields: JsonMap<String, JsonValue>) -> AnyhowResult<()> {
+        let url = format!("{}/v1/{api_path}", self.address);
+        let body = if self.config.kv_version == 1 {
+            JsonValue::Object(fields)
+        } else {
+            json!({ "data": fields })
+        };
+        self.with_token(|token| {
+            let mut request = ureq::post(&url).header("X-Vault-Token", token);
+            if let Some(namespace) = &self.config.namespace {
+                request = request.header("X-Vault-Namespace", namespace);
+            }
+            request.send_json(&body)
+        })?
+        .map_err(|err| anyhow!("vault POST {api_path} failed: {err}"))?;
+        Ok(())
+    }
+
+    fn location_or_err(&self, uri: &str) -> SecretResult<VaultLocation> {
+        self.locate(uri).ok_or_else(|| {
+            SecretError::Backend(format!("not a canonical secrets URI: {uri}").into())
+        })
+    }
+}
+
+impl VaultClient {
+    fn read(&self, path: &str) -> SecretResult<Vec<u8>> {
+        let location = self.location_or_err(path)?;
+        let fields = self
+            .read_fields(&location.api_path)
+            .map_err(|err| SecretError::Backend(err.to_string().into()))?;
+        match fields.and_then(|mut fields| fields.remove(&location.field)) {
+            Some(JsonValue::String(value)) => Ok(value.into_bytes()),
+            Some(value) => Ok(value.to_string().into_bytes()),
+            None => Err(SecretError::NotFound(path.to_string())),
+        }
+    }
+
+    fn write(&self, path: &str, value: &[u8]) -> SecretResult<()> {
+        let location = self.location_or_err(path)?;
+        // KV writes replace the whole secret, so merge with the fields already there.
+        let mut fields = self
+            .read_fields(&location.api_path)
+            .map_err(|err| SecretError::Backend(err.to_string().into()))?
+            .unwrap_or_default();
+        fields.insert(
+            location.field,
+            JsonValue::String(String::from_utf8_lossy(value).into_owned()),
+        );
+        self.write_fields(&location.api_path, fields)
+            .map_err(|err| SecretError::Backend(err.to_string().into()))
+    }
+
+    fn delete(&self, path: &str) -> SecretResult<()> {
+        let location = self.location_or_err(path)?;
+        let Some(mut fields) = self
+            .read_fields(&location.api_path)
+            .map_err(|err| SecretError::Backend(err.to_string().into()))?
+        else {
+            return Ok(());
+        };
+        if fields.remove(&location.field).is_none() {
+            return Ok(());
+        }
+        self.write_fields(&location.api_path, fields)
+            .map_err(|err| SecretError::Backend(err.to_string().into()))
+    }
+}
+
+#[async_trait]
+impl SecretsManager for VaultSecretsManager {
+    async fn read(&self, path: &str) -> SecretResult<Vec<u8>> {
+        let path = path.to_string();
+        self.blocking(move |client| client.read(&path)).await
+    }
+
+    async fn write(&self, path: &str, value: &[u8]) -> SecretResult<()> {
+        let (path, value) = (path.to_string(), value.to_vec());
+        self.blocking(move |client| client.write(&path, &value))
+            .await
+    }
+
+    async fn delete(&self, path: &str) -> SecretResult<()> {
+        let path = path.to_string();
+        self.blocking(move |client| client.delete(&path)).await
+    }
+}
+
+fn locate(config: &DemoVaultConfig, uri: &CanonicalSecretUri) -> Option<VaultLocation> {
+    let placeholders = BTreeMap::from([
+        ("{env}", uri.env.as_str()),
+        ("{tenant}", uri.tenant.as_str()),
+        ("{team}", uri.team.as_str()),
+        ("{provider}", uri.provider.as_str()),
+    ]);
+    let mut secret_path = config.path_template.clone();
+    for (placeholder, value) in placeholders {
+        secret_path = secret_path.replace(placeholder, value);
+    }
+    let secret_path = secret_path.trim_matches('/');
+    let mount = config.mount.trim_matches('/');
+    if secret_path.is_empty() || mount.is_empty() {
+        return None;
+    }
+    let api_path = if config.kv_version == 1 {
+        format!("{mount}/{secret_path}")
+    } else {
+        format!("{mount}/data/{secret_path}")
+    };
+    Some(VaultLocation {
+        api_path,
+        field: uri.key.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_uri_maps_to_kv_path_and_field() {
+        let uri = parse_canonical_secret_uri("secrets://dev/acme/_/telegram/bot_token")
+            .expect("canonical uri");
+        let mut config = DemoVaultConfig::default();
+        assert_eq!(
+            locate(&config, &uri),
+            Some(VaultLocation {
+                api_path: "secret/data/greentic/dev/acme/_/telegram".to_string(),
+                field: "bot_token".to_string(),
+            })
+        );
+
+        config.kv_version = 1;
+        config.mount = "kv/".to_string();
+        config.path_template = "{tenant}/{provider}".to_string();
+        assert_eq!(
+            locate(&config, &uri).map(|location| location.api_path),
+            Some("kv/acme/telegram".to_string())
+        );
+    }
+
+    #[test]
+    fn tokens_are_renewed_before_their_lease_ends() {
+        assert!(CachedToken::new("t".to_string(), Duration::ZERO).is_fresh());
+        assert!(CachedToken::new("t".to_string(), Duration::from_secs(3600)).is_fresh());
+        let expired = CachedToken {
+            value: "t".to_string(),
+            renew_at: Instant::now().checked_sub(Duration::from_secs(1)),
+        };
+        assert!(!expired.is_fresh());
+    }
+}
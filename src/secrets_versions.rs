@@ -0,0 +1,223 @@
+//! Per-key version history for the dev secrets store.
+//!
+//! Every write of `secrets://.../key` through the operator also stores the value as
+//! `secrets://.../key@v<N>` and makes version N active. Writing an explicit
+//! `key@v<N>` (for example from seeds.yaml) stages that version without touching the
+//! active value. `demo secrets history` lists versions, and `--activate` copies a
+//! version back onto the unversioned URI, which is what providers read.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use chrono::Utc;
+use greentic_secrets_lib::core::Error as CoreError;
+use greentic_secrets_lib::{
+    ApplyOptions, DevStore, SecretFormat, SecretsStore, SeedDoc, SeedEntry, SeedValue, apply_seed,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::secrets_crypto::{self, DevStoreCipher};
+use crate::secrets_gate::{split_secret_uri_version, versioned_secret_uri};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SecretHistory {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active: Option<u32>,
+    #[serde(default)]
+    pub versions: Vec<SecretVersion>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SecretVersion {
+    pub version: u32,
+    pub written_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+impl SecretHistory {
+    pub fn next_version(&self) -> u32 {
+        self.versions
+            .iter()
+            .map(|entry| entry.version)
+            .max()
+            .unwrap_or(0)
+            + 1
+    }
+
+    pub fn get(&self, version: u32) -> Option<&SecretVersion> {
+        self.versions.iter().find(|entry| entry.version == version)
+    }
+
+    fn record(&mut self, version: u32, source: Option<String>) {
+        self.versions.retain(|entry| entry.version != version);
+        self.versions.push(SecretVersion {
+            version,
+            written_at: Utc::now().to_rfc3339(),
+            source,
+        });
+        self.versions.sort_by_key(|entry| entry.version);
+    }
+}
+
+/// Adds a `@v<N>` copy for every unversioned entry and records the history.
+/// `versions` is keyed by the unversioned URI.
+pub fn expand_versions(
+    versions: &mut BTreeMap<String, SecretHistory>,
+    entries: Vec<SeedEntry>,
+) -> Vec<SeedEntry> {
+    let mut expanded = Vec::with_capacity(entries.len() * 2);
+    for entry in entries {
+        let (base, version) = split_secret_uri_version(&entry.uri);
+        let history = versions.entry(base.to_string()).or_default();
+        match version {
+            Some(version) => {
+                history.record(version, entry.description.clone());
+            }
+            None => {
+                let version = history.next_version();
+                history.record(version, entry.description.clone());
+                history.active = Some(version);
+                expanded.push(SeedEntry {
+                    uri: versioned_secret_uri(&entry.uri, version),
+                    format: entry.format.clone(),
+                    value: entry.value.clone(),
+                    description: entry.description.clone(),
+                });
+            }
+        }
+        expanded.push(entry);
+    }
+    expanded
+}
+
+/// Copies `uri@v<version>` onto `uri` and marks it active (rollback or promotion).
+pub async fn activate(store_path: &Path, uri: &str, version: u32) -> Result<()> {
+    let base = split_secret_uri_version(uri).0.to_string();
+    let mut meta = secrets_crypto::read_meta(store_path)?;
+    if meta
+        .versions
+        .get(&base)
+        .and_then(|history| history.get(version))
+        .is_none()
+    {
+        return Err(anyhow!("{base} has no version {version}"));
+    }
+    let store = DevStore::with_path(store_path).map_err(|err| {
+        anyhow!(
+            "failed to open dev secrets store {}: {err}",
+            store_path.display()
+        )
+    })?;
+    let versioned = versioned_secret_uri(&base, version);
+    let value = match store.get(&versioned).await {
+        Ok(value) => value,
+        Err(CoreError::NotFound { .. }) => {
+            return Err(anyhow!(
+                "{versioned} is recorded but missing from the store"
+            ));
+        }
+        Err(err) => return Err(anyhow!("failed to read secret {versioned}: {err}")),
+    };
+    // Sealed values are bound to their URI, so re-seal for the unversioned one.
+    let text = match DevStoreCipher::for_store(store_path)? {
+        Some(cipher) if secrets_crypto::is_sealed(&value) => {
+            cipher.seal(&base, &cipher.open(&versioned, &value)?)?
+        }
+        _ => String::from_utf8(value)
+            .map_err(|_| anyhow!("{versioned} is not valid UTF-8 and cannot be stored as text"))?,
+    };
+    let seed = SeedDoc {
+        entries: vec![SeedEntry {
+            uri: base.clone(),
+            format: SecretFormat::Text,
+            value: SeedValue::Text { text },
+            description: Some(format!("activated v{version}")),
+        }],
+    };
+    let report = apply_seed(&store, &seed, ApplyOptions::default()).await;
+    if !report.failed.is_empty() {
+        return Err(anyhow!(
+            "failed to activate {versioned}: {:?}",
+            report.failed
+        ));
+    }
+    if let Some(history) = meta.versions.get_mut(&base) {
+        history.active = Some(version);
+    }
+    meta.uris.insert(base);
+    secrets_crypto::write_meta(store_path, &meta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use tokio::runtime::Runtime;
+
+    fn text_entry(uri: &str, text: &str) -> SeedEntry {
+        SeedEntry {
+            uri: uri.to_string(),
+            format: SecretFormat::Text,
+            value: SeedValue::Text {
+                text: text.to_string(),
+            },
+            description: None,
+        }
+    }
+
+    #[test]
+    fn writes_are_versioned_and_rollback_restores_the_old_value() -> Result<()> {
+        let dir = tempdir()?;
+        let store_path = dir.path().join(".dev.secrets.env");
+        let uri = "secrets://dev/acme/_/telegram/bot_token";
+        let runtime = Runtime::new()?;
+        let store = DevStore::with_path(&store_path)?;
+        for value in ["first-token", "second-token"] {
+            let entries = secrets_crypto::prepare_seed_entries(
+                &store_path,
+                None,
+                vec![text_entry(uri, value)],
+            )?;
+            runtime.block_on(apply_seed(
+                &store,
+                &SeedDoc { entries },
+                ApplyOptions::default(),
+            ));
+        }
+        let staged = secrets_crypto::prepare_seed_entries(
+            &store_path,
+            None,
+            vec![text_entry(&format!("{uri}@v5"), "staged-token")],
+        )?;
+        runtime.block_on(apply_seed(
+            &store,
+            &SeedDoc { entries: staged },
+            ApplyOptions::default(),
+        ));
+
+        let history = secrets_crypto::read_meta(&store_path)?.versions[uri].clone();
+        assert_eq!(history.active, Some(2));
+        assert_eq!(
+            history
+                .versions
+                .iter()
+                .map(|entry| entry.version)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 5]
+        );
+        assert_eq!(runtime.block_on(store.get(uri))?, b"second-token".to_vec());
+
+        runtime.block_on(activate(&store_path, uri, 1))?;
+        assert_eq!(runtime.block_on(store.get(uri))?, b"first-token".to_vec());
+        runtime.block_on(activate(&store_path, uri, 5))?;
+        assert_eq!(runtime.block_on(store.get(uri))?, b"staged-token".to_vec());
+        assert_eq!(
+            secrets_crypto::read_meta(&store_path)?.versions[uri].active,
+            Some(5)
+        );
+        assert!(runtime.block_on(activate(&store_path, uri, 9)).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,417 @@
+//! `self-update`: replaces the running operator with the latest release of a channel.
+//!
+//! Each channel publishes a `release.json` (by default as an asset of the
+//! `channel-<channel>` release of this repository; `GREENTIC_OPERATOR_RELEASE_URL`
+//! overrides it, with `{channel}` substituted):
+//!
+//! ```json
+//! {
+//!   "version": "0.4.33",
+//!   "assets": { "x86_64-unknown-linux-gnu": { "url": "https://…/….tgz", "sha256": "…" } },
+//!   "key_id": "release-2026",
+//!   "signature": "<base64 Ed25519>"
+//! }
+//! ```
+//!
+//! The signature covers [`signed_checksums`]: a line with the version and channel
+//! followed by one `<sha256 hex>  <target>` line per asset, in target order, so a
+//! beta release cannot be served as stable. It must verify with a release key
+//! built into the binary (`GREENTIC_OPERATOR_RELEASE_KEYS` at compile time, as
+//! `key_id=<base64>` pairs separated by commas). Debug builds also read the same
+//! variable at run time, for testing against locally signed releases. The
+//! downloaded asset must then match its signed checksum.
+//! Assets are the `.tgz`/`.zip` release archives or a bare binary.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, anyhow};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use clap::ValueEnum;
+use ring::digest::{self, SHA256};
+use ring::signature::{ED25519, UnparsedPublicKey};
+use serde::{Deserialize, Serialize};
+
+use crate::runner_pin::{self, RunnerVersion};
+
+pub const RELEASE_URL_ENV: &str = "GREENTIC_OPERATOR_RELEASE_URL";
+pub const RELEASE_KEYS_ENV: &str = "GREENTIC_OPERATOR_RELEASE_KEYS";
+const BINARY: &str = "greentic-operator";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    #[default]
+    Stable,
+    Beta,
+}
+
+impl Channel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Channel::Stable => "stable",
+            Channel::Beta => "beta",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ReleaseManifest {
+    pub version: String,
+    /// By Rust target triple (`x86_64-unknown-linux-gnu`) or `<arch>-<os>`.
+    pub assets: BTreeMap<String, ReleaseAsset>,
+    pub key_id: String,
+    /// Base64 Ed25519 signature over [`signed_checksums`].
+    pub signature: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ReleaseAsset {
+    pub url: String,
+    pub sha256: String,
+}
+
+/// Result of comparing the running operator with a channel's latest release.
+#[derive(Clone, Debug, Serialize)]
+pub struct UpdateCheck {
+    pub channel: Channel,
+    pub current: String,
+    pub latest: String,
+    pub update_available: bool,
+    /// Asset key for this platform; unset when the release has no build for it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+}
+
+pub fn release_url(channel: Channel) -> String {
+    let template = std::env::var(RELEASE_URL_ENV).unwrap_or_else(|_| {
+        format!(
+            "{}/releases/download/channel-{{channel}}/release.json",
+            env!("CARGO_PKG_REPOSITORY")
+        )
+    });
+    template.replace("{channel}", channel.as_str())
+}
+
+pub fn fetch_release(url: &str) -> anyhow::Result<ReleaseManifest> {
+    let body = ureq::get(url)
+        .call()
+        .map_err(|err| anyhow!("fetch release manifest {url} failed: {err}"))?
+        .body_mut()
+        .read_to_string()
+        .with_context(|| format!("read {url}"))?;
+    serde_json::from_str(&body).with_context(|| format!("parse release manifest {url}"))
+}
+
+pub fn check(channel: Channel, release: &ReleaseManifest) -> anyhow::Result<UpdateCheck> {
+    let current = RunnerVersion::parse(env!("CARGO_PKG_VERSION"))?;
+    let latest = RunnerVersion::parse(&release.version)
+        .with_context(|| format!("release manifest version {}", release.version))?;
+    Ok(UpdateCheck {
+        channel,
+        current: current.to_string(),
+        update_available: is_newer(&latest, &current),
+        latest: latest.to_string(),
+        target: asset_key(release).map(str::to_string),
+    })
+}
+
+/// Whether `candidate` is a later release than `current`; a release is later than
+/// its own pre-releases.
+pub fn is_newer(candidate: &RunnerVersion, current: &RunnerVersion) -> bool {
+    let triple = |version: &RunnerVersion| (version.major, version.minor, version.patch);
+    match triple(candidate).cmp(&triple(current)) {
+        std::cmp::Ordering::Greater => true,
+        std::cmp::Ordering::Less => false,
+        std::cmp::Ordering::Equal => match (&candidate.pre, &current.pre) {
+            (None, Some(_)) => true,
+            (Some(candidate), Some(current)) => {
+                compare_pre(candidate, current) == std::cmp::Ordering::Greater
+            }
+            _ => false,
+        },
+    }
+}
+
+/// Semver pre-release precedence: dot-separated identifiers left to right,
+/// numeric ones as numbers and below alphanumeric ones, and a shorter list
+/// first when all its identifiers match.
+fn compare_pre(left: &str, right: &str) -> std::cmp::Ordering {
+    let mut left = left.split('.');
+    let mut right = right.split('.');
+    loop {
+        let ordering = match (left.next(), right.next()) {
+            (None, None) => return std::cmp::Ordering::Equal,
+            (None, Some(_)) => return std::cmp::Ordering::Less,
+            (Some(_), None) => return std::cmp::Ordering::Greater,
+            (Some(left), Some(right)) => match (left.parse::<u64>(), right.parse::<u64>()) {
+                (Ok(left), Ok(right)) => left.cmp(&right),
+                (Ok(_), Err(_)) => std::cmp::Ordering::Less,
+                (Err(_), Ok(_)) => std::cmp::Ordering::Greater,
+                (Err(_), Err(_)) => left.cmp(right),
+            },
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+}
+
+/// Rust target triple of this build, as release archives are named.
+pub fn target_triple() -> String {
+    let arch = std::env::consts::ARCH;
+    match std::env::consts::OS {
+        "linux" => format!("{arch}-unknown-linux-gnu"),
+        "macos" => format!("{arch}-apple-darwin"),
+        "windows" => format!("{arch}-pc-windows-msvc"),
+        os => format!("{arch}-unknown-{os}"),
+    }
+}
+
+fn asset_key(release: &ReleaseManifest) -> Option<&str> {
+    [target_triple(), runner_pin::current_target()]
+        .into_iter()
+        .find_map(|key| {
+            release
+                .assets
+                .get_key_value(&key)
+                .map(|(key, _)| key.as_str())
+        })
+}
+
+/// The text the release signature covers.
+pub fn signed_checksums(release: &ReleaseManifest, channel: Channel) -> String {
+    let mut text = format!("{BINARY} {} {}\n", release.version, channel.as_str());
+    for (target, asset) in &release.assets {
+        text.push_str(&format!(
+            "{}  {target}\n",
+            asset.sha256.trim().to_ascii_lowercase()
+        ));
+    }
+    text
+}
+
+/// Release keys built in at compile time. Debug builds also trust keys from the
+/// environment at run time; release builds never do.
+pub fn release_keys() -> BTreeMap<String, Vec<u8>> {
+    let runtime = if cfg!(debug_assertions) {
+        std::env::var(RELEASE_KEYS_ENV).ok()
+    } else {
+        None
+    };
+    [
+        option_env!("GREENTIC_OPERATOR_RELEASE_KEYS"),
+        runtime.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .flat_map(|value| value.split(','))
+    .filter_map(|pair| {
+        let (key_id, key) = pair.split_once('=')?;
+        Some((key_id.trim().to_string(), STANDARD.decode(key.trim()).ok()?))
+    })
+    .collect()
+}
+
+pub fn verify_release(
+    release: &ReleaseManifest,
+    channel: Channel,
+    keys: &BTreeMap<String, Vec<u8>>,
+) -> anyhow::Result<()> {
+    let key = keys.get(&release.key_id).ok_or_else(|| {
+        anyhow!(
+            "release {} is signed with key {}, which is not a trusted release key of this build",
+            release.version,
+            release.key_id
+        )
+    })?;
+    let signature = STANDARD
+        .decode(release.signature.trim())
+        .map_err(|_| anyhow!("release signature is not base64"))?;
+    UnparsedPublicKey::new(&ED25519, key)
+        .verify(signed_checksums(release, channel).as_bytes(), &signature)
+        .map_err(|_| {
+            anyhow!(
+                "release {} signature does not verify with key {} for the {} channel",
+                release.version,
+                release.key_id,
+                channel.as_str()
+            )
+        })
+}
+
+/// Downloads this platform's asset of a verified `release` and installs it over
+/// `exe`. Returns the replaced binary's path.
+pub fn install(release: &ReleaseManifest, channel: Channel, exe: &Path) -> anyhow::Result<PathBuf> {
+    verify_release(release, channel, &release_keys())?;
+    let key = asset_key(release).ok_or_else(|| {
+        anyhow!(
+            "release {} has no build for {}; available: {}",
+            release.version,
+            target_triple(),
+            release
+                .assets
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    })?;
+    let asset = &release.assets[key];
+    let mut bytes = Vec::new();
+    ureq::get(&asset.url)
+        .call()
+        .map_err(|err| anyhow!("download {} failed: {err}", asset.url))?
+        .body_mut()
+        .as_reader()
+        .read_to_end(&mut bytes)
+        .with_context(|| format!("download {}", asset.url))?;
+    let actual = runner_pin::hex(digest::digest(&SHA256, &bytes).as_ref());
+    let expected = asset.sha256.trim().to_ascii_lowercase();
+    if actual != expected {
+        return Err(anyhow!(
+            "checksum mismatch for {}: expected sha256 {expected}, got {actual}",
+            asset.url
+        ));
+    }
+    let binary = extract_binary(&asset.url, bytes)?;
+    replace_binary(exe, &binary)?;
+    Ok(exe.to_path_buf())
+}
+
+fn binary_file_name() -> String {
+    if cfg!(windows) {
+        format!("{BINARY}.exe")
+    } else {
+        BINARY.to_string()
+    }
+}
+
+/// The operator binary from a `.tgz`/`.tar.gz` or `.zip` archive; any other asset
+/// is the binary itself.
+fn extract_binary(url: &str, bytes: Vec<u8>) -> anyhow::Result<Vec<u8>> {
+    let name = binary_file_name();
+    let is_binary = |path: &Path| path.file_name().is_some_and(|file| file == name.as_str());
+    if url.ends_with(".tgz") || url.ends_with(".tar.gz") {
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(Cursor::new(bytes)));
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if is_binary(&entry.path()?) {
+                let mut binary = Vec::new();
+                entry.read_to_end(&mut binary)?;
+                return Ok(binary);
+            }
+        }
+    } else if url.ends_with(".zip") {
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes))?;
+        for index in 0..archive.len() {
+            let mut file = archive.by_index(index)?;
+            if file.enclosed_name().is_some_and(|path| is_binary(&path)) {
+                let mut binary = Vec::new();
+                file.read_to_end(&mut binary)?;
+                return Ok(binary);
+            }
+        }
+    } else {
+        return Ok(bytes);
+    }
+    Err(anyhow!("{url} does not contain {name}"))
+}
+
+/// Puts `binary` in place of `exe`. The new file is written next to it first. On
+/// Unix it is renamed over the running binary; Windows cannot replace a running
+/// executable, so the old one is moved aside to `<exe>.old` and removed by the next
+/// update.
+fn replace_binary(exe: &Path, binary: &[u8]) -> anyhow::Result<()> {
+    let staged = exe.with_extension("new");
+    fs::write(&staged, binary).with_context(|| format!("write {}", staged.display()))?;
+    runner_pin::make_executable(&staged)?;
+    if cfg!(windows) {
+        let old = exe.with_extension("old");
+        let _ = fs::remove_file(&old);
+        fs::rename(exe, &old)
+            .with_context(|| format!("move {} to {}", exe.display(), old.display()))?;
+        if let Err(err) = fs::rename(&staged, exe) {
+            let _ = fs::rename(&old, exe);
+            return Err(err).with_context(|| format!("install {}", exe.display()));
+        }
+    } else {
+        fs::rename(&staged, exe).with_context(|| {
+            format!(
+                "replace {} (if it is not writable, rerun with the permissions that installed it)",
+                exe.display()
+            )
+        })?;
+    }
+    Ok(())
+}
+
+/// Removes the binary a previous update on Windows moved aside.
+pub fn remove_stale_binary(exe: &Path) {
+    let _ = fs::remove_file(exe.with_extension("old"));
+}
+
+#[cfg(test)]
+mod tests {
+    use ring::rand::SystemRandom;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    use super::*;
+
+    #[test]
+    fn verifies_signed_checksums_and_orders_versions() {
+        let rng = SystemRandom::new();
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        let signer = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        let mut release = ReleaseManifest {
+            version: "0.5.0".to_string(),
+            assets: BTreeMap::from([(
+                "x86_64-unknown-linux-gnu".to_string(),
+                ReleaseAsset {
+                    url: "https://example.com/greentic-operator.tgz".to_string(),
+                    sha256: "AB".repeat(32),
+                },
+            )]),
+            key_id: "release".to_string(),
+            signature: String::new(),
+        };
+        release.signature = STANDARD.encode(
+            signer
+                .sign(signed_checksums(&release, Channel::Beta).as_bytes())
+                .as_ref(),
+        );
+        let keys = BTreeMap::from([("release".to_string(), signer.public_key().as_ref().to_vec())]);
+        verify_release(&release, Channel::Beta, &keys).unwrap();
+        assert!(verify_release(&release, Channel::Stable, &keys).is_err());
+
+        let mut tampered = release.clone();
+        tampered
+            .assets
+            .get_mut("x86_64-unknown-linux-gnu")
+            .unwrap()
+            .sha256 = "00".repeat(32);
+        assert!(verify_release(&tampered, Channel::Beta, &keys).is_err());
+        assert!(verify_release(&release, Channel::Beta, &BTreeMap::new()).is_err());
+
+        let version = |value| RunnerVersion::parse(value).unwrap();
+        assert!(is_newer(&version("0.5.0"), &version("0.4.32")));
+        assert!(is_newer(&version("0.5.0"), &version("0.5.0-beta.1")));
+        assert!(is_newer(&version("0.5.0-beta.2"), &version("0.5.0-beta.1")));
+        assert!(is_newer(
+            &version("0.5.0-beta.10"),
+            &version("0.5.0-beta.9")
+        ));
+        assert!(!is_newer(
+            &version("0.5.0-beta.9"),
+            &version("0.5.0-beta.10")
+        ));
+        assert!(is_newer(&version("0.5.0-beta"), &version("0.5.0-alpha.3")));
+        assert!(is_newer(&version("0.5.0-beta.1"), &version("0.5.0-beta")));
+        assert!(is_newer(&version("0.5.0-rc"), &version("0.5.0-9")));
+        assert!(!is_newer(&version("0.5.0-beta.1"), &version("0.5.0")));
+        assert!(!is_newer(&version("0.4.32"), &version("0.4.32")));
+    }
+}
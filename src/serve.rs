@@ -0,0 +1,480 @@
+//! Unattended runtime pieces for `greentic-operator serve`.
+//!
+//! `serve` runs the in-process services of `demo start` (HTTP ingress, timer
+//! scheduler, Kafka bridge) plus universal subscription renewal, without tunnels,
+//! provider setup, or prompts. This module holds what only `serve` needs: the shared
+//! [`Health`] record, the loop that probes the gateway and NATS, the subscription
+//! renewer, and the probe server:
+//!
+//!   GET /healthz → 200 while the health check loop is running (liveness)
+//!   GET /readyz  → 200 once every component passed its last check, 503 otherwise
+//!
+//! Both answer with the component states as JSON and need no token, so bind the probe
+//! port where only the orchestrator can reach it.
+
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc};
+use http_body_util::Full;
+use hyper::{
+    Method, Request, Response, StatusCode,
+    body::{Bytes, Incoming},
+    server::conn::http1::Builder as Http1Builder,
+    service::service_fn,
+};
+use hyper_util::rt::tokio::TokioIo;
+use serde::Serialize;
+use serde_json::{Value as JsonValue, json};
+use tokio::{net::TcpListener, runtime::Runtime, sync::oneshot};
+
+use crate::bus;
+use crate::config::DemoSubscriptionsUniversalConfig;
+use crate::onboard::api::{error_response, json_response};
+use crate::operator_log;
+use crate::subscriptions_universal::{
+    alerts::SubscriptionAlerts, build_runner, ensure_desired_subscriptions, scheduler::Scheduler,
+    service::SubscriptionService, state_root, store::SubscriptionStore,
+};
+
+pub const ENV_BUNDLE: &str = "GREENTIC_OPERATOR_BUNDLE";
+pub const ENV_TENANT: &str = "GREENTIC_OPERATOR_TENANT";
+pub const ENV_TEAM: &str = "GREENTIC_OPERATOR_TEAM";
+pub const ENV_NATS_URL: &str = "GREENTIC_OPERATOR_NATS_URL";
+pub const ENV_HEALTH_PORT: &str = "GREENTIC_OPERATOR_HEALTH_PORT";
+pub const DEFAULT_HEALTH_PORT: u16 = 9091;
+
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+/// Liveness fails once the check loop has not run for this long.
+const LIVENESS_GRACE: Duration = Duration::from_secs(60);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+const SUBSCRIPTIONS: &str = "subscriptions";
+
+type HttpResponse = Response<Full<Bytes>>;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ComponentHealth {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct HealthState {
+    components: BTreeMap<String, ComponentHealth>,
+    /// False until every service started, and again while a reload restarts them.
+    started: bool,
+    last_check: Option<Instant>,
+}
+
+/// Component states shared by the services, the check loop, and the probe server.
+#[derive(Clone, Default)]
+pub struct Health {
+    state: Arc<Mutex<HealthState>>,
+}
+
+impl Health {
+    pub fn report(&self, component: &str, result: &Result<()>) {
+        let health = ComponentHealth {
+            ok: result.is_ok(),
+            error: result.as_ref().err().map(|err| format!("{err:#}")),
+            checked_at: Utc::now(),
+        };
+        self.lock().components.insert(component.to_string(), health);
+    }
+
+    pub fn set_started(&self, started: bool) {
+        self.lock().started = started;
+    }
+
+    /// Forgets the probed components; the next check loop reports them again.
+    pub fn clear(&self, components: &[String]) {
+        let mut state = self.lock();
+        for component in components {
+            state.components.remove(component);
+        }
+    }
+
+    fn checked(&self) {
+        self.lock().last_check = Some(Instant::now());
+    }
+
+    pub fn live(&self) -> bool {
+        self.lock()
+            .last_check
+            .is_some_and(|at| at.elapsed() <= LIVENESS_GRACE)
+    }
+
+    pub fn ready(&self) -> bool {
+        let state = self.lock();
+        state.started && state.components.values().all(|component| component.ok)
+    }
+
+    pub fn snapshot(&self) -> JsonValue {
+        let state = self.lock();
+        json!({
+            "started": state.started,
+            "components": state.components,
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HealthState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// A component that is healthy while `addr` accepts TCP connections.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TcpProbe {
+    pub component: String,
+    pub addr: String,
+}
+
+impl TcpProbe {
+    /// Probes the gateway through loopback when it listens on all interfaces.
+    pub fn gateway(listen_addr: &str, port: u16) -> Self {
+        let host = match listen_addr {
+            "0.0.0.0" | "" => "127.0.0.1",
+            "::" | "[::]" => "[::1]",
+            other => other,
+        };
+        Self {
+            component: "ingress".to_string(),
+            addr: format!("{host}:{port}"),
+        }
+    }
+
+    pub fn nats(url: &str) -> Self {
+        let authority = url.split_once("://").map_or(url, |(_, rest)| rest);
+        let authority = authority.split('/').next().unwrap_or_default();
+        let host_port = authority.rsplit('@').next().unwrap_or_default();
+        let addr = if host_port
+            .rsplit_once(':')
+            .is_some_and(|(_, port)| !port.is_empty() && port.chars().all(|c| c.is_ascii_digit()))
+        {
+            host_port.to_string()
+        } else {
+            format!("{host_port}:4222")
+        };
+        Self {
+            component: "nats".to_string(),
+            addr,
+        }
+    }
+
+    fn check(&self) -> Result<()> {
+        let addrs = self
+            .addr
+            .to_socket_addrs()
+            .with_context(|| format!("resolve {}", self.addr))?;
+        let mut last_err = anyhow!("{} resolved to no address", self.addr);
+        for addr in addrs {
+            match TcpStream::connect_timeout(&addr, PROBE_TIMEOUT) {
+                Ok(_) => return Ok(()),
+                Err(err) => last_err = anyhow!("connect {addr}: {err}"),
+            }
+        }
+        Err(last_err)
+    }
+}
+
+/// Runs the TCP probes every [`CHECK_INTERVAL`] and records the results.
+pub struct HealthChecks {
+    probes: Vec<String>,
+    health: Health,
+    shutdown: Option<mpsc::Sender<()>>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl HealthChecks {
+    pub fn start(health: Health, probes: Vec<TcpProbe>) -> Result<Self> {
+        let names = probes.iter().map(|probe| probe.component.clone()).collect();
+        let (tx, rx) = mpsc::channel::<()>();
+        let loop_health = health.clone();
+        let handle = thread::Builder::new()
+            .name("serve-health".to_string())
+            .spawn(move || {
+                loop {
+                    for probe in &probes {
+                        let result = probe.check();
+                        if let Err(err) = &result {
+                            operator_log::warn(
+                                module_path!(),
+                                format!("health check {} failed: {err:#}", probe.component),
+                            );
+                        }
+                        loop_health.report(&probe.component, &result);
+                    }
+                    loop_health.checked();
+                    if !matches!(
+                        rx.recv_timeout(CHECK_INTERVAL),
+                        Err(RecvTimeoutError::Timeout)
+                    ) {
+                        break;
+                    }
+                }
+            })?;
+        Ok(Self {
+            probes: names,
+            health,
+            shutdown: Some(tx),
+            handle: Some(handle),
+        })
+    }
+
+    pub fn stop(mut self) -> Result<()> {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+        if let Some(handle) = self.handle.take() {
+            handle
+                .join()
+                .map_err(|err| anyhow!("health checks panicked: {err:?}"))?;
+        }
+        self.health.clear(&self.probes);
+        Ok(())
+    }
+}
+
+/// Ensures `services.subscriptions.universal.desired` and renews due subscriptions
+/// every `renew_interval_seconds`. Unlike the `demo start` scheduler it stops on
+/// shutdown and reports each pass to [`Health`]; a failed ensure is retried on the
+/// next pass.
+pub struct SubscriptionRenewer {
+    shutdown: Option<mpsc::Sender<()>>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl SubscriptionRenewer {
+    pub fn start(
+        bundle: &Path,
+        tenant: &str,
+        team: &str,
+        config: &DemoSubscriptionsUniversalConfig,
+        health: Health,
+    ) -> Result<Self> {
+        let team_override = (!team.trim().is_empty()).then(|| team.to_string());
+        let (runner_host, context) = build_runner(bundle, tenant, team_override.clone())?;
+        let alerts = SubscriptionAlerts::new(
+            config.alerts.clone(),
+            Some(bus::for_scope(bundle, tenant, team)),
+        );
+        let scheduler = Scheduler::new(
+            SubscriptionService::new(runner_host, context),
+            SubscriptionStore::new(state_root(bundle)),
+        )
+        .with_alerts(alerts);
+        let interval = Duration::from_secs(config.renew_interval_seconds.max(1));
+        let skew = Duration::from_secs(config.renew_skew_minutes.max(1).saturating_mul(60));
+        let mut desired = config.desired.clone();
+        let bundle = PathBuf::from(bundle);
+        let tenant = tenant.to_string();
+        let (tx, rx) = mpsc::channel::<()>();
+        let handle = thread::Builder::new()
+            .name("serve-subscriptions".to_string())
+            .spawn(move || {
+                loop {
+                    let ensured = if desired.is_empty() {
+                        Ok(())
+                    } else {
+                        ensure_desired_subscriptions(
+                            &bundle,
+                            &tenant,
+                            team_override.clone(),
+                            &desired,
+                            &scheduler,
+                        )
+                        .map(|()| desired.clear())
+                    };
+                    let result = ensured.and_then(|()| scheduler.renew_due(skew));
+                    if let Err(err) = &result {
+                        operator_log::error(
+                            module_path!(),
+                            format!("subscription renewal failed: {err:#}"),
+                        );
+                    }
+                    health.report(SUBSCRIPTIONS, &result);
+                    if !matches!(rx.recv_timeout(interval), Err(RecvTimeoutError::Timeout)) {
+                        break;
+                    }
+                }
+            })?;
+        Ok(Self {
+            shutdown: Some(tx),
+            handle: Some(handle),
+        })
+    }
+
+    pub fn stop(mut self) -> Result<()> {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+        if let Some(handle) = self.handle.take() {
+            handle
+                .join()
+                .map_err(|err| anyhow!("subscription renewer panicked: {err:?}"))?;
+        }
+        Ok(())
+    }
+}
+
+pub struct ProbeServer {
+    addr: SocketAddr,
+    shutdown: Option<oneshot::Sender<()>>,
+    handle: Option<thread::JoinHandle<Result<()>>>,
+}
+
+impl ProbeServer {
+    pub fn start(bind_addr: SocketAddr, health: Health) -> Result<Self> {
+        let std_listener = std::net::TcpListener::bind(bind_addr)
+            .with_context(|| format!("failed to bind probe server on {bind_addr}"))?;
+        std_listener.set_nonblocking(true)?;
+        let addr = std_listener.local_addr()?;
+        let (tx, rx) = oneshot::channel();
+        let handle = thread::Builder::new()
+            .name("serve-probes".to_string())
+            .spawn(move || -> Result<()> {
+                let runtime = Runtime::new().context("failed to create probe server runtime")?;
+                runtime.block_on(async move {
+                    let listener = TcpListener::from_std(std_listener)
+                        .context("failed to register probe server listener")?;
+                    operator_log::info(
+                        module_path!(),
+                        format!("probe server listening on http://{addr}"),
+                    );
+                    let mut shutdown = rx;
+                    loop {
+                        tokio::select! {
+                            _ = &mut shutdown => break,
+                            accept = listener.accept() => match accept {
+                                Ok((stream, _peer)) => {
+                                    let health = health.clone();
+                                    tokio::spawn(async move {
+                                        let service = service_fn(move |req| {
+                                            let health = health.clone();
+                                            async move { handle_request(req, &health) }
+                                        });
+                                        if let Err(err) = Http1Builder::new()
+                                            .serve_connection(TokioIo::new(stream), service)
+                                            .await
+                                        {
+                                            operator_log::error(
+                                                module_path!(),
+                                                format!("probe server connection error: {err}"),
+                                            );
+                                        }
+                                    });
+                                }
+                                Err(err) => operator_log::error(
+                                    module_path!(),
+                                    format!("probe server accept error: {err}"),
+                                ),
+                            },
+                        }
+                    }
+                    Ok(())
+                })
+            })?;
+        Ok(Self {
+            addr,
+            shutdown: Some(tx),
+            handle: Some(handle),
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn stop(mut self) -> Result<()> {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+        if let Some(handle) = self.handle.take() {
+            handle
+                .join()
+                .map_err(|err| anyhow!("probe server panicked: {err:?}"))??;
+        }
+        Ok(())
+    }
+}
+
+fn handle_request(
+    req: Request<Incoming>,
+    health: &Health,
+) -> std::result::Result<HttpResponse, Infallible> {
+    Ok(probe_response(req.method(), req.uri().path(), health))
+}
+
+fn probe_response(method: &Method, path: &str, health: &Health) -> HttpResponse {
+    let passed = match (method, path.trim_end_matches('/')) {
+        (&Method::GET, "/healthz") => health.live(),
+        (&Method::GET, "/readyz") => health.ready(),
+        _ => return error_response(StatusCode::NOT_FOUND, format!("no route for {path}")),
+    };
+    let status = if passed {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let mut body = health.snapshot();
+    body["ok"] = JsonValue::Bool(passed);
+    json_response(status, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probe_addresses_come_from_listen_addr_and_nats_url() {
+        assert_eq!(TcpProbe::gateway("0.0.0.0", 8080).addr, "127.0.0.1:8080");
+        assert_eq!(TcpProbe::gateway("10.0.0.5", 8080).addr, "10.0.0.5:8080");
+        assert_eq!(
+            TcpProbe::nats("nats://user:pw@nats.internal:4223").addr,
+            "nats.internal:4223"
+        );
+        assert_eq!(TcpProbe::nats("nats://nats").addr, "nats:4222");
+    }
+
+    #[test]
+    fn readiness_needs_a_started_runtime_and_passing_components() {
+        let health = Health::default();
+        let status = |path| probe_response(&Method::GET, path, &health).status();
+        assert_eq!(status("/healthz"), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(status("/readyz"), StatusCode::SERVICE_UNAVAILABLE);
+
+        health.checked();
+        health.report("ingress", &Ok(()));
+        assert_eq!(status("/healthz"), StatusCode::OK);
+        assert_eq!(status("/readyz"), StatusCode::SERVICE_UNAVAILABLE);
+
+        health.set_started(true);
+        assert_eq!(status("/readyz/"), StatusCode::OK);
+
+        health.report(SUBSCRIPTIONS, &Err(anyhow!("provider unreachable")));
+        assert_eq!(status("/readyz"), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            health.snapshot()["components"][SUBSCRIPTIONS]["error"],
+            "provider unreachable"
+        );
+        assert_eq!(status("/healthz"), StatusCode::OK);
+
+        health.clear(&[SUBSCRIPTIONS.to_string()]);
+        assert_eq!(status("/readyz"), StatusCode::OK);
+        assert_eq!(
+            probe_response(&Method::POST, "/readyz", &health).status(),
+            StatusCode::NOT_FOUND
+        );
+    }
+}
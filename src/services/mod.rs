@@ -3,5 +3,11 @@ mod nats;
 mod runner;
 
 pub use components::{ComponentSpec, component_status, start_component, stop_component};
-pub use nats::{nats_status, nats_url, start_nats, start_nats_with_log, stop_nats, tail_nats_logs};
-pub use runner::{ProcessStatus, ServiceState, tail_log};
+pub use nats::{
+    ENV_NATS_PORT, nats_port, nats_status, nats_url, start_nats, start_nats_with_log, stop_nats,
+    tail_nats_logs,
+};
+pub use runner::{
+    ProcessStatus, ServiceState, log_path, pid_path, process_status, start_process, stop_process,
+    tail_log,
+};
@@ -6,6 +6,8 @@ use std::str::FromStr;
 use super::runner::{ProcessStatus, ServiceState, log_path, pid_path};
 
 const NATS_CONTAINER_PREFIX: &str = "greentic-operator-nats";
+/// Host port for the spawned NATS container (set by `demo start --nats-port`).
+pub const ENV_NATS_PORT: &str = "GREENTIC_OPERATOR_NATS_PORT";
 
 pub fn start_nats(root: &Path) -> anyhow::Result<ServiceState> {
     start_nats_with_log(root, None)
@@ -25,7 +27,8 @@ pub fn start_nats_with_log(
         "--name".to_string(),
         container,
         "-p".to_string(),
-        format!("{port}:{port}"),
+        // nats:2 listens on 4222 inside the container whatever the host port is.
+        format!("{port}:4222"),
         "nats:2".to_string(),
         "-js".to_string(),
     ];
@@ -62,8 +65,8 @@ fn container_name(root: &Path) -> String {
     format!("{NATS_CONTAINER_PREFIX}-{hash:08x}")
 }
 
-fn nats_port(root: &Path) -> u16 {
-    if let Ok(value) = std::env::var("GREENTIC_OPERATOR_NATS_PORT")
+pub fn nats_port(root: &Path) -> u16 {
+    if let Ok(value) = std::env::var(ENV_NATS_PORT)
         && let Ok(port) = u16::from_str(&value)
     {
         return port;
@@ -12,6 +12,8 @@ use serde::Deserialize;
 use serde_json::{Map as JsonMap, Value};
 use zip::{ZipArchive, result::ZipError};
 
+use crate::interpolate::{self, SecretScope};
+
 /// Answers loaded from a user-provided `--setup-input` file.
 #[derive(Clone)]
 pub struct SetupInputAnswers {
@@ -42,8 +44,12 @@ impl SetupInputAnswers {
 }
 
 /// Reads a JSON/YAML answers file (mirrors the fixtures shipped with the packs).
-pub fn load_setup_input(path: &Path) -> anyhow::Result<Value> {
+/// `${ENV_VAR}` and `${secret:...}` references are resolved first; `scope` is where
+/// secrets are read from.
+pub fn load_setup_input(path: &Path, scope: Option<SecretScope<'_>>) -> anyhow::Result<Value> {
     let raw = fs::read_to_string(path)?;
+    let raw = interpolate::interpolate(&raw, scope)
+        .with_context(|| format!("resolve references in setup input {}", path.display()))?;
     serde_json::from_str(&raw)
         .or_else(|_| serde_yaml_bw::from_str(&raw))
         .with_context(|| format!("parse setup input {}", path.display()))
@@ -20,6 +20,19 @@ pub fn run_dir(
         .join(format!("{timestamp}")))
 }
 
+/// Where the runner host keeps decrypted copies of encrypted packs.
+pub fn decrypted_packs_dir(root: &Path) -> PathBuf {
+    root.join("state").join("packs").join("decrypted")
+}
+
+/// Where a downloaded runner of `version` is cached for the bundle.
+pub fn runner_bin_dir(root: &Path, version: &str) -> PathBuf {
+    root.join("state")
+        .join("bin")
+        .join("greentic-runner")
+        .join(version)
+}
+
 pub fn secrets_log_path(root: &Path, action: &str) -> anyhow::Result<PathBuf> {
     let timestamp = timestamp_secs()?;
     Ok(root
@@ -34,6 +47,7 @@ fn domain_name(domain: Domain) -> &'static str {
         Domain::Messaging => "messaging",
         Domain::Events => "events",
         Domain::Secrets => "secrets",
+        Domain::Custom(custom) => custom.name(),
     }
 }
 
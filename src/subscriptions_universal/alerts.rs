@@ -0,0 +1,269 @@
+//! Alerts for subscriptions about to lapse.
+//!
+//! The universal scheduler reports two conditions to the hooks configured under
+//! `services.subscriptions.universal.alerts`: `expiring`, once per binding and
+//! expiration, when a stored subscription enters the renew skew window, and
+//! `renew_failing` when renewal has failed `renew_failure_threshold` times in a
+//! row. A successful renewal re-arms both. Hook failures are logged and never stop
+//! the scheduler.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, anyhow};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use crate::bus::MessageBus;
+use crate::config::{SubscriptionAlertHook, SubscriptionAlertsConfig};
+use crate::operator_log;
+use crate::redaction;
+use crate::subscriptions_universal::store::SubscriptionState;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    Expiring,
+    RenewFailing,
+}
+
+impl AlertKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AlertKind::Expiring => "expiring",
+            AlertKind::RenewFailing => "renew_failing",
+        }
+    }
+}
+
+/// What the hooks receive, as JSON body, NATS payload or `GREENTIC_ALERT`.
+#[derive(Clone, Debug, Serialize)]
+pub struct SubscriptionAlert {
+    pub kind: AlertKind,
+    pub binding_id: String,
+    pub provider: String,
+    pub tenant: String,
+    pub team: Option<String>,
+    pub subscription_id: Option<String>,
+    pub expiration_unix_ms: Option<i64>,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+}
+
+impl SubscriptionAlert {
+    fn new(kind: AlertKind, state: &SubscriptionState) -> Self {
+        Self {
+            kind,
+            binding_id: state.binding_id.clone(),
+            provider: state.provider.clone(),
+            tenant: state.tenant.clone(),
+            team: state.team.clone(),
+            subscription_id: state.subscription_id.clone(),
+            expiration_unix_ms: state.expiration_unix_ms,
+            consecutive_failures: 0,
+            last_error: None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Tracker {
+    /// `(binding, expiration)` pairs already reported as expiring.
+    expiring: BTreeSet<(String, Option<i64>)>,
+    failures: BTreeMap<String, u32>,
+}
+
+pub struct SubscriptionAlerts {
+    config: SubscriptionAlertsConfig,
+    bus: Option<Arc<dyn MessageBus>>,
+    tracker: Mutex<Tracker>,
+}
+
+impl SubscriptionAlerts {
+    /// `bus` carries `nats` hooks; without one they are logged and skipped.
+    pub fn new(config: SubscriptionAlertsConfig, bus: Option<Arc<dyn MessageBus>>) -> Self {
+        Self {
+            config,
+            bus,
+            tracker: Mutex::new(Tracker::default()),
+        }
+    }
+
+    /// `state` is inside the skew window; returns the alert when this is news.
+    pub fn expiring(&self, state: &SubscriptionState) -> Option<SubscriptionAlert> {
+        let key = (binding_key(state), state.expiration_unix_ms);
+        if !self.lock().expiring.insert(key) {
+            return None;
+        }
+        let alert = SubscriptionAlert::new(AlertKind::Expiring, state);
+        self.fire(&alert);
+        Some(alert)
+    }
+
+    /// Renewal of `state` failed; returns the alert once failures reach the threshold.
+    pub fn renew_failed(
+        &self,
+        state: &SubscriptionState,
+        error: &str,
+    ) -> Option<SubscriptionAlert> {
+        let failures = {
+            let mut tracker = self.lock();
+            let failures = tracker.failures.entry(binding_key(state)).or_default();
+            *failures += 1;
+            *failures
+        };
+        if self.config.renew_failure_threshold == 0
+            || failures != self.config.renew_failure_threshold
+        {
+            return None;
+        }
+        let mut alert = SubscriptionAlert::new(AlertKind::RenewFailing, state);
+        alert.consecutive_failures = failures;
+        alert.last_error = Some(redaction::scrub_text(error));
+        self.fire(&alert);
+        Some(alert)
+    }
+
+    /// Renewal of `state` succeeded; the next expiry or failure streak alerts again.
+    pub fn renewed(&self, state: &SubscriptionState) {
+        let key = binding_key(state);
+        let mut tracker = self.lock();
+        tracker.failures.remove(&key);
+        tracker.expiring.retain(|(binding, _)| *binding != key);
+    }
+
+    fn fire(&self, alert: &SubscriptionAlert) {
+        operator_log::warn(
+            module_path!(),
+            format!(
+                "subscription alert {} binding={} provider={}",
+                alert.kind.as_str(),
+                alert.binding_id,
+                alert.provider
+            ),
+        );
+        let payload = match serde_json::to_value(alert) {
+            Ok(payload) => payload,
+            Err(err) => {
+                operator_log::error(module_path!(), format!("encode alert failed: {err}"));
+                return;
+            }
+        };
+        for hook in &self.config.hooks {
+            if let Err(err) = self.run_hook(hook, alert, &payload) {
+                operator_log::error(
+                    module_path!(),
+                    format!(
+                        "subscription alert hook failed binding={}: {err:#}",
+                        alert.binding_id
+                    ),
+                );
+            }
+        }
+    }
+
+    fn run_hook(
+        &self,
+        hook: &SubscriptionAlertHook,
+        alert: &SubscriptionAlert,
+        payload: &JsonValue,
+    ) -> anyhow::Result<()> {
+        match hook {
+            SubscriptionAlertHook::Exec(argv) => {
+                let (program, args) = argv
+                    .split_first()
+                    .ok_or_else(|| anyhow!("exec hook has no command"))?;
+                let status = Command::new(program)
+                    .args(args)
+                    .env("GREENTIC_ALERT", payload.to_string())
+                    .env("GREENTIC_ALERT_KIND", alert.kind.as_str())
+                    .env("GREENTIC_ALERT_PROVIDER", &alert.provider)
+                    .env("GREENTIC_ALERT_BINDING", &alert.binding_id)
+                    .status()
+                    .with_context(|| format!("run {program}"))?;
+                if !status.success() {
+                    return Err(anyhow!("{program} exited with {status}"));
+                }
+                Ok(())
+            }
+            SubscriptionAlertHook::Webhook(url) => ureq::post(url)
+                .send_json(payload)
+                .map(|_| ())
+                .map_err(|err| anyhow!("POST {} failed: {err}", redaction::scrub_text(url))),
+            SubscriptionAlertHook::Nats(subject) => match &self.bus {
+                Some(bus) => bus.publish(subject, payload),
+                None => Err(anyhow!("no bus to publish {subject} on")),
+            },
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Tracker> {
+        self.tracker.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+fn binding_key(state: &SubscriptionState) -> String {
+    format!(
+        "{}/{}/{}/{}",
+        state.provider,
+        state.tenant,
+        state.team.as_deref().unwrap_or("default"),
+        state.binding_id
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::InProcessBus;
+
+    fn state(expiration_unix_ms: i64) -> SubscriptionState {
+        SubscriptionState {
+            binding_id: "chats".to_string(),
+            provider: "msgraph".to_string(),
+            tenant: "demo".to_string(),
+            team: None,
+            resource: None,
+            change_types: Vec::new(),
+            notification_url: None,
+            client_state: None,
+            user: None,
+            subscription_id: Some("sub-1".to_string()),
+            expiration_unix_ms: Some(expiration_unix_ms),
+            last_error: None,
+        }
+    }
+
+    #[test]
+    fn alerts_once_per_condition_until_renewed() {
+        let bus = Arc::new(InProcessBus::new());
+        let alerts_feed = bus.subscribe("alerts.>").unwrap();
+        let alerts = SubscriptionAlerts::new(
+            SubscriptionAlertsConfig {
+                renew_failure_threshold: 2,
+                hooks: vec![SubscriptionAlertHook::Nats(
+                    "alerts.subscriptions".to_string(),
+                )],
+            },
+            Some(bus),
+        );
+        let expiring = state(1_000);
+        assert!(alerts.expiring(&expiring).is_some());
+        assert!(alerts.expiring(&expiring).is_none());
+        assert_eq!(alerts_feed.try_recv().unwrap().payload["kind"], "expiring");
+
+        assert!(alerts.renew_failed(&expiring, "401").is_none());
+        let failing = alerts.renew_failed(&expiring, "401").unwrap();
+        assert_eq!(failing.consecutive_failures, 2);
+        assert!(alerts.renew_failed(&expiring, "401").is_none());
+        assert_eq!(
+            alerts_feed.try_recv().unwrap().payload["kind"],
+            "renew_failing"
+        );
+
+        alerts.renewed(&expiring);
+        assert!(alerts.expiring(&expiring).is_some());
+        assert!(alerts.expiring(&state(2_000)).is_some());
+    }
+}
@@ -3,10 +3,10 @@ use std::path::{Path, PathBuf};
 use anyhow::Result;
 use uuid::Uuid;
 
-use crate::cli::{discovery_map, provider_id_for_pack, resolve_demo_provider_pack};
 use crate::config::DemoDesiredSubscription;
 use crate::demo::runner_host::{DemoRunnerHost, OperatorContext};
-use crate::discovery;
+use crate::discovery::{self, discovery_map, provider_id_for_pack};
+use crate::domain_run::resolve_demo_provider_pack;
 use crate::domains::Domain;
 use crate::secrets_gate;
 use crate::subscriptions_universal::scheduler::Scheduler;
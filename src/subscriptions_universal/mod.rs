@@ -1,3 +1,4 @@
+pub mod alerts;
 pub mod demo;
 pub mod scheduler;
 pub mod service;
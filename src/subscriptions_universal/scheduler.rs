@@ -4,6 +4,7 @@ use anyhow::Result;
 use chrono::Utc;
 
 use crate::operator_log;
+use crate::subscriptions_universal::alerts::SubscriptionAlerts;
 use crate::subscriptions_universal::service::{
     ProviderRunner, SubscriptionDeleteRequest, SubscriptionEnsureRequest, SubscriptionRenewRequest,
     SubscriptionService,
@@ -15,11 +16,22 @@ const DEFAULT_RENEW_EXTENSION_MS: u64 = 86_400_000;
 pub struct Scheduler<R: ProviderRunner> {
     service: SubscriptionService<R>,
     store: SubscriptionStore,
+    alerts: Option<SubscriptionAlerts>,
 }
 
 impl<R: ProviderRunner> Scheduler<R> {
     pub fn new(service: SubscriptionService<R>, store: SubscriptionStore) -> Self {
-        Self { service, store }
+        Self {
+            service,
+            store,
+            alerts: None,
+        }
+    }
+
+    /// Reports expiring bindings and failing renewals from [`Scheduler::renew_due`].
+    pub fn with_alerts(mut self, alerts: SubscriptionAlerts) -> Self {
+        self.alerts = Some(alerts);
+        self
     }
 
     pub fn ensure_once(&self, provider: &str, request: &SubscriptionEnsureRequest) -> Result<()> {
@@ -27,16 +39,19 @@ impl<R: ProviderRunner> Scheduler<R> {
         self.store.write_state(&state)
     }
 
+    /// Renews every binding in [`Scheduler::due_states`], logging failures and moving on.
     pub fn renew_due(&self, skew: Duration) -> Result<()> {
-        let now = Utc::now().timestamp_millis();
-        let skew_ms = skew.as_millis() as i64;
-        let states = self.store.list_states()?;
-        for state in states {
-            if let Some(expiration) = state.expiration_unix_ms {
-                let renew_at = expiration.saturating_sub(skew_ms);
-                if now >= renew_at
-                    && let Err(err) = self.renew_binding(&state)
-                {
+        for state in self.due_states(skew)? {
+            if let Some(alerts) = &self.alerts {
+                alerts.expiring(&state);
+            }
+            match self.renew_binding(&state) {
+                Ok(()) => {
+                    if let Some(alerts) = &self.alerts {
+                        alerts.renewed(&state);
+                    }
+                }
+                Err(err) => {
                     operator_log::error(
                         module_path!(),
                         format!(
@@ -44,12 +59,31 @@ impl<R: ProviderRunner> Scheduler<R> {
                             state.binding_id, state.provider, err
                         ),
                     );
+                    if let Some(alerts) = &self.alerts {
+                        alerts.renew_failed(&state, &format!("{err:#}"));
+                    }
                 }
             }
         }
         Ok(())
     }
 
+    /// Stored bindings that expire within `skew`.
+    pub fn due_states(&self, skew: Duration) -> Result<Vec<SubscriptionState>> {
+        let now = Utc::now().timestamp_millis();
+        let skew_ms = skew.as_millis() as i64;
+        Ok(self
+            .store
+            .list_states()?
+            .into_iter()
+            .filter(|state| {
+                state
+                    .expiration_unix_ms
+                    .is_some_and(|expiration| now >= expiration.saturating_sub(skew_ms))
+            })
+            .collect())
+    }
+
     pub fn renew_binding(&self, state: &SubscriptionState) -> Result<()> {
         let request = SubscriptionRenewRequest {
             binding_id: state.binding_id.clone(),
@@ -1,6 +1,6 @@
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
+use std::process::{Child, Command, Stdio};
 use std::time::{Duration, Instant};
 
 use chrono::{DateTime, Utc};
@@ -74,6 +74,16 @@ pub fn spawn_service(
     spec: ServiceSpec,
     log_path_override: Option<PathBuf>,
 ) -> anyhow::Result<ServiceHandle> {
+    spawn_service_child(paths, spec, log_path_override).map(|(handle, _child)| handle)
+}
+
+/// Like [`spawn_service`], but keeps the [`Child`] so the caller can wait on it and
+/// see how it exited.
+pub fn spawn_service_child(
+    paths: &RuntimePaths,
+    spec: ServiceSpec,
+    log_path_override: Option<PathBuf>,
+) -> anyhow::Result<(ServiceHandle, Child)> {
     if spec.argv.is_empty() {
         return Err(anyhow::anyhow!("service argv cannot be empty"));
     }
@@ -123,12 +133,15 @@ pub fn spawn_service(
     };
     write_json(&paths.resolved_path(spec.id.as_str()), &resolved)?;
 
-    Ok(ServiceHandle {
-        id: spec.id,
-        pid,
-        started_at: Utc::now(),
-        log_path,
-    })
+    Ok((
+        ServiceHandle {
+            id: spec.id,
+            pid,
+            started_at: Utc::now(),
+            log_path,
+        },
+        child,
+    ))
 }
 
 pub fn stop_service(
@@ -211,7 +224,7 @@ pub fn is_running(pid: u32) -> bool {
     system.process(pid).is_some()
 }
 
-fn read_pid(pid_path: &Path) -> anyhow::Result<Option<u32>> {
+pub fn read_pid(pid_path: &Path) -> anyhow::Result<Option<u32>> {
     if !pid_path.exists() {
         return Ok(None);
     }
@@ -0,0 +1,899 @@
+//! Copying a tenant between bundles or projects: `tenant clone`, `tenant export`,
+//! and `tenant import`, and deleting one (`demo offboard`).
+//!
+//! A tenant is its directories (`tenants/<tenant>` and `<domain>/tenants/<tenant>`,
+//! with the gmaps and team structure inside), its resolved manifests
+//! (`resolved/<tenant>[.<team>].yaml` and the same under `state/resolved`), and the
+//! dev-store secrets whose URI names it. Renaming rewrites all three. Secrets are
+//! written through [`secrets_crypto::prepare_seed_entries`], so they are sealed with
+//! the destination store's key, if it has one or its config asks for one, and start a
+//! fresh version history.
+//!
+//! An export archive is a `.tar.gz` with `tenant.json`, `files/...`, and, when
+//! secrets are included, `secrets.json`. With a passphrase the secret values in the
+//! archive are sealed with a key derived from it; otherwise they are plaintext.
+
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, anyhow};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use greentic_secrets_lib::core::Error as CoreError;
+use greentic_secrets_lib::{
+    ApplyOptions, DevStore, SecretFormat, SecretsStore, SeedDoc, SeedEntry, SeedValue, apply_seed,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::domains::{self, Domain};
+use crate::project::{self, ResolvedManifest};
+use crate::secrets_crypto::{self, DevStoreCipher, DevStoreKeyInfo, KeySource};
+use crate::secrets_gate;
+use crate::{dev_store_path, runtime_state};
+
+const ARCHIVE_FORMAT: &str = "greentic-tenant@1";
+const RESOLVED_DIRS: [&str; 2] = ["resolved", "state/resolved"];
+
+/// Everything that belongs to one tenant, detached from any bundle.
+#[derive(Clone, Debug)]
+pub struct TenantSnapshot {
+    pub tenant: String,
+    /// Relative to the bundle root.
+    pub files: Vec<(PathBuf, Vec<u8>)>,
+    pub secrets: Vec<TenantSecret>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TenantSecret {
+    pub uri: String,
+    pub value: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct TransferReport {
+    pub tenant: String,
+    pub files: Vec<PathBuf>,
+    pub secrets: usize,
+}
+
+/// What [`remove`] deleted, or would delete under `dry_run`.
+#[derive(Debug, Default, Serialize)]
+pub struct RemovalReport {
+    pub tenant: String,
+    /// Directories and files, relative to the bundle root.
+    pub paths: Vec<PathBuf>,
+    /// Dev-store URIs, including their `@v<N>` versions.
+    pub secrets: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ArchiveHeader {
+    format: String,
+    tenant: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    secrets_key: Option<DevStoreKeyInfo>,
+}
+
+/// Reads `tenant` from `bundle`; secrets only when `with_secrets`.
+pub fn snapshot(bundle: &Path, tenant: &str, with_secrets: bool) -> anyhow::Result<TenantSnapshot> {
+    validate_name(tenant)?;
+    let mut files = Vec::new();
+    let mut found = false;
+    for dir in tenant_dirs(tenant) {
+        let root = bundle.join(&dir);
+        if root.is_dir() {
+            found = true;
+            collect_files(bundle, &root, &mut files)?;
+        }
+    }
+    if !found {
+        return Err(anyhow!(
+            "tenant {tenant} not found in {} (no tenants/{tenant} directory)",
+            bundle.display()
+        ));
+    }
+    for dir in RESOLVED_DIRS {
+        let dir = bundle.join(dir);
+        if !dir.is_dir() {
+            continue;
+        }
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_file() && manifest_tenant(&path).as_deref() == Some(tenant) {
+                let bytes = fs::read(&path).with_context(|| format!("read {}", path.display()))?;
+                files.push((relative(bundle, &path)?, bytes));
+            }
+        }
+    }
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    let secrets = if with_secrets {
+        read_tenant_secrets(bundle, tenant)?
+    } else {
+        Vec::new()
+    };
+    Ok(TenantSnapshot {
+        tenant: tenant.to_string(),
+        files,
+        secrets,
+    })
+}
+
+impl TenantSnapshot {
+    /// Checks that every file and secret belongs to this tenant, so applying the
+    /// snapshot cannot touch bundle config or another tenant.
+    pub fn verify(&self) -> anyhow::Result<()> {
+        validate_name(&self.tenant)?;
+        let dirs = tenant_dirs(&self.tenant);
+        for (path, _) in &self.files {
+            let in_dir = dirs.iter().any(|dir| path.starts_with(dir) && path != dir);
+            let is_manifest = RESOLVED_DIRS
+                .iter()
+                .any(|dir| path.parent() == Some(Path::new(dir)))
+                && manifest_tenant(path).as_deref() == Some(self.tenant.as_str());
+            if !in_dir && !is_manifest {
+                return Err(anyhow!(
+                    "{} does not belong to tenant {}",
+                    path.display(),
+                    self.tenant
+                ));
+            }
+        }
+        for secret in &self.secrets {
+            let owner = secrets_gate::parse_canonical_secret_uri(&secret.uri)
+                .ok_or_else(|| anyhow!("invalid secret uri {}", secret.uri))?
+                .tenant;
+            if owner != self.tenant {
+                return Err(anyhow!(
+                    "secret {} does not belong to tenant {}",
+                    secret.uri,
+                    self.tenant
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The same tenant under another name.
+    pub fn renamed(self, to: &str) -> anyhow::Result<Self> {
+        validate_name(to)?;
+        let from = self.tenant.clone();
+        if from == to {
+            return Ok(self);
+        }
+        let files = self
+            .files
+            .into_iter()
+            .map(|(path, bytes)| rename_file(&path, bytes, &from, to))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let secrets = self
+            .secrets
+            .into_iter()
+            .map(|secret| {
+                let mut uri = secrets_gate::parse_canonical_secret_uri(&secret.uri)
+                    .ok_or_else(|| anyhow!("invalid secret uri {}", secret.uri))?;
+                uri.tenant = to.to_string();
+                Ok(TenantSecret {
+                    uri: format!(
+                        "secrets://{}/{}/{}/{}/{}",
+                        uri.env, uri.tenant, uri.team, uri.provider, uri.key
+                    ),
+                    value: secret.value,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self {
+            tenant: to.to_string(),
+            files,
+            secrets,
+        })
+    }
+}
+
+/// Writes `snapshot` into `bundle`. An existing tenant of that name is an error
+/// unless `overwrite`, in which case its files are replaced.
+pub fn apply(
+    bundle: &Path,
+    snapshot: &TenantSnapshot,
+    overwrite: bool,
+) -> anyhow::Result<TransferReport> {
+    snapshot.verify()?;
+    let existing = project::list_tenants(bundle)?;
+    if existing.contains(&snapshot.tenant) {
+        if !overwrite {
+            return Err(anyhow!(
+                "tenant {} already exists in {} (pass --force to replace it)",
+                snapshot.tenant,
+                bundle.display()
+            ));
+        }
+        for dir in tenant_dirs(&snapshot.tenant) {
+            let dir = bundle.join(dir);
+            if dir.is_dir() {
+                fs::remove_dir_all(&dir).with_context(|| format!("remove {}", dir.display()))?;
+            }
+        }
+    }
+    let mut report = TransferReport {
+        tenant: snapshot.tenant.clone(),
+        ..TransferReport::default()
+    };
+    for (path, bytes) in &snapshot.files {
+        let target = bundle.join(path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        runtime_state::atomic_write(&target, bytes)?;
+        report.files.push(path.clone());
+    }
+    if !snapshot.secrets.is_empty() {
+        write_secrets(bundle, &snapshot.secrets)?;
+        report.secrets = snapshot.secrets.len();
+    }
+    Ok(report)
+}
+
+/// Deletes `tenant` from `bundle`: its directories, its resolved manifests and
+/// their history, and every dev-store secret whose URI names it. Nothing is
+/// touched under `dry_run`.
+pub fn remove(bundle: &Path, tenant: &str, dry_run: bool) -> anyhow::Result<RemovalReport> {
+    validate_name(tenant)?;
+    let mut paths = tenant_dirs(tenant)
+        .into_iter()
+        .filter(|dir| bundle.join(dir).is_dir())
+        .collect::<Vec<_>>();
+    for dir in RESOLVED_DIRS {
+        let dir = bundle.join(dir);
+        if !dir.is_dir() {
+            continue;
+        }
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_file() && manifest_tenant(&path).as_deref() == Some(tenant) {
+                paths.push(relative(bundle, &path)?);
+            }
+        }
+    }
+    let history = bundle.join("state/resolved/history");
+    if history.is_dir() {
+        for entry in fs::read_dir(&history)? {
+            let path = entry?.path();
+            let name = path.file_name().and_then(|name| name.to_str());
+            let owner = name.map(|name| name.split_once('.').map_or(name, |(tenant, _)| tenant));
+            if path.is_dir() && owner == Some(tenant) {
+                paths.push(relative(bundle, &path)?);
+            }
+        }
+    }
+    paths.sort();
+    if paths.is_empty() {
+        return Err(anyhow!(
+            "tenant {tenant} not found in {} (no tenants/{tenant} directory or resolved manifest)",
+            bundle.display()
+        ));
+    }
+    let secrets = remove_tenant_secrets(bundle, tenant, dry_run)?;
+    if !dry_run {
+        for path in &paths {
+            let path = bundle.join(path);
+            if path.is_dir() {
+                fs::remove_dir_all(&path).with_context(|| format!("remove {}", path.display()))?;
+            } else {
+                fs::remove_file(&path).with_context(|| format!("remove {}", path.display()))?;
+            }
+        }
+    }
+    Ok(RemovalReport {
+        tenant: tenant.to_string(),
+        paths,
+        secrets,
+    })
+}
+
+/// Writes `snapshot` as a `.tar.gz`; `passphrase` seals the secret values.
+pub fn write_archive(
+    snapshot: &TenantSnapshot,
+    archive: &Path,
+    passphrase: Option<&KeySource>,
+) -> anyhow::Result<()> {
+    let (cipher, secrets_key) = match passphrase {
+        Some(source) if !snapshot.secrets.is_empty() => {
+            let (cipher, info) = DevStoreCipher::create(source)?;
+            (Some(cipher), Some(info))
+        }
+        _ => (None, None),
+    };
+    let header = ArchiveHeader {
+        format: ARCHIVE_FORMAT.to_string(),
+        tenant: snapshot.tenant.clone(),
+        secrets_key,
+    };
+    if let Some(parent) = archive
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)?;
+    }
+    let file =
+        fs::File::create(archive).with_context(|| format!("create {}", archive.display()))?;
+    let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    append_bytes(
+        &mut tar,
+        "tenant.json",
+        &serde_json::to_vec_pretty(&header)?,
+    )?;
+    for (path, bytes) in &snapshot.files {
+        append_bytes(&mut tar, &Path::new("files").join(path), bytes)?;
+    }
+    if !snapshot.secrets.is_empty() {
+        let secrets = snapshot
+            .secrets
+            .iter()
+            .map(|secret| {
+                Ok(TenantSecret {
+                    uri: secret.uri.clone(),
+                    value: match cipher.as_ref() {
+                        Some(cipher) => cipher.seal(&secret.uri, secret.value.as_bytes())?,
+                        None => secret.value.clone(),
+                    },
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        append_bytes(
+            &mut tar,
+            "secrets.json",
+            &serde_json::to_vec_pretty(&secrets)?,
+        )?;
+    }
+    tar.into_inner()?.finish()?;
+    Ok(())
+}
+
+/// Reads an archive written by [`write_archive`]; sealed secrets are opened with
+/// the key source recorded in it.
+pub fn read_archive(archive: &Path) -> anyhow::Result<TenantSnapshot> {
+    let file = fs::File::open(archive).with_context(|| format!("open {}", archive.display()))?;
+    let mut tar = tar::Archive::new(GzDecoder::new(file));
+    let mut header: Option<ArchiveHeader> = None;
+    let mut files = Vec::new();
+    let mut secrets: Vec<TenantSecret> = Vec::new();
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes)?;
+        if path == Path::new("tenant.json") {
+            header = Some(serde_json::from_slice(&bytes).context("parse tenant.json")?);
+        } else if path == Path::new("secrets.json") {
+            secrets = serde_json::from_slice(&bytes).context("parse secrets.json")?;
+        } else if let Ok(relative) = path.strip_prefix("files") {
+            if relative
+                .components()
+                .any(|component| !matches!(component, Component::Normal(_)))
+            {
+                return Err(anyhow!(
+                    "archive entry {} escapes the bundle",
+                    path.display()
+                ));
+            }
+            files.push((relative.to_path_buf(), bytes));
+        }
+    }
+    let header = header.ok_or_else(|| anyhow!("{} has no tenant.json", archive.display()))?;
+    if header.format != ARCHIVE_FORMAT {
+        return Err(anyhow!(
+            "{} is a {} archive; expected {ARCHIVE_FORMAT}",
+            archive.display(),
+            header.format
+        ));
+    }
+    if let Some(info) = header.secrets_key.as_ref() {
+        let cipher = DevStoreCipher::from_key_info(info)?;
+        for secret in secrets.iter_mut() {
+            let plaintext = cipher.open(&secret.uri, secret.value.as_bytes())?;
+            secret.value = String::from_utf8(plaintext)
+                .map_err(|_| anyhow!("{} is not valid UTF-8", secret.uri))?;
+        }
+    }
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    let snapshot = TenantSnapshot {
+        tenant: header.tenant,
+        files,
+        secrets,
+    };
+    snapshot
+        .verify()
+        .with_context(|| format!("{} is not a valid tenant archive", archive.display()))?;
+    Ok(snapshot)
+}
+
+fn tenant_dirs(tenant: &str) -> Vec<PathBuf> {
+    let mut dirs = vec![Path::new("tenants").join(tenant)];
+    for domain in [Domain::Messaging, Domain::Events] {
+        dirs.push(
+            Path::new(domains::domain_name(domain))
+                .join("tenants")
+                .join(tenant),
+        );
+    }
+    dirs
+}
+
+/// `acme.yaml` and `acme.support.yaml` belong to `acme`.
+fn manifest_tenant(path: &Path) -> Option<String> {
+    if path.extension().and_then(|ext| ext.to_str()) != Some("yaml") {
+        return None;
+    }
+    let stem = path.file_stem()?.to_str()?;
+    Some(
+        stem.split_once('.')
+            .map_or(stem, |(tenant, _)| tenant)
+            .to_string(),
+    )
+}
+
+fn rename_file(
+    path: &Path,
+    bytes: Vec<u8>,
+    from: &str,
+    to: &str,
+) -> anyhow::Result<(PathBuf, Vec<u8>)> {
+    let is_manifest = RESOLVED_DIRS
+        .iter()
+        .any(|dir| path.parent() == Some(Path::new(dir)));
+    if !is_manifest {
+        // tenants/<from>/... or <domain>/tenants/<from>/...
+        let renamed = path
+            .components()
+            .scan(false, |after_tenants, component| {
+                let name = component.as_os_str();
+                let replaced = if *after_tenants && name == from {
+                    std::ffi::OsString::from(to)
+                } else {
+                    name.to_os_string()
+                };
+                *after_tenants = name == "tenants";
+                Some(replaced)
+            })
+            .collect::<PathBuf>();
+        return Ok((renamed, bytes));
+    }
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| anyhow!("invalid manifest path {}", path.display()))?;
+    let renamed = path.with_file_name(format!("{to}{}", &file_name[from.len()..]));
+    let raw = String::from_utf8(bytes).with_context(|| format!("read {}", path.display()))?;
+    let mut manifest: ResolvedManifest =
+        serde_yaml_bw::from_str(&raw).with_context(|| format!("parse {}", path.display()))?;
+    manifest.tenant = to.to_string();
+    let from_prefix = format!("tenants/{from}/");
+    for gmap in [
+        Some(&mut manifest.policy.source.tenant_gmap),
+        manifest.policy.source.team_gmap.as_mut(),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if let Some(rest) = gmap.strip_prefix(&from_prefix) {
+            *gmap = format!("tenants/{to}/{rest}");
+        }
+    }
+    Ok((renamed, serde_yaml_bw::to_string(&manifest)?.into_bytes()))
+}
+
+fn validate_name(tenant: &str) -> anyhow::Result<()> {
+    if tenant.is_empty()
+        || !tenant
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_')
+    {
+        return Err(anyhow!(
+            "invalid tenant name '{tenant}'; use alphanumeric, '-' or '_'"
+        ));
+    }
+    Ok(())
+}
+
+fn relative(bundle: &Path, path: &Path) -> anyhow::Result<PathBuf> {
+    Ok(path
+        .strip_prefix(bundle)
+        .with_context(|| format!("{} is outside {}", path.display(), bundle.display()))?
+        .to_path_buf())
+}
+
+fn collect_files(
+    bundle: &Path,
+    dir: &Path,
+    files: &mut Vec<(PathBuf, Vec<u8>)>,
+) -> anyhow::Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(bundle, &path, files)?;
+        } else {
+            let bytes = fs::read(&path).with_context(|| format!("read {}", path.display()))?;
+            files.push((relative(bundle, &path)?, bytes));
+        }
+    }
+    Ok(())
+}
+
+fn append_bytes<W: std::io::Write>(
+    tar: &mut tar::Builder<W>,
+    path: impl AsRef<Path>,
+    bytes: &[u8],
+) -> anyhow::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(bytes.len() as u64);
+    header.set_mode(0o600);
+    header.set_mtime(0);
+    header.set_cksum();
+    tar.append_data(&mut header, path, bytes)?;
+    Ok(())
+}
+
+/// Active (unversioned) values of every recorded dev-store URI for `tenant`.
+fn read_tenant_secrets(bundle: &Path, tenant: &str) -> anyhow::Result<Vec<TenantSecret>> {
+    let Some(store_path) = dev_store_path::find_existing(bundle) else {
+        return Ok(Vec::new());
+    };
+    let meta = secrets_crypto::read_meta(&store_path)?;
+    let cipher = DevStoreCipher::for_store(&store_path)?;
+    let store = open_store(&store_path)?;
+    let runtime = secrets_runtime()?;
+    let mut secrets = Vec::new();
+    for uri in &meta.uris {
+        let Some(parsed) = secrets_gate::parse_canonical_secret_uri(uri) else {
+            continue;
+        };
+        if parsed.tenant != tenant || parsed.version().is_some() {
+            continue;
+        }
+        let value = match runtime.block_on(store.get(uri)) {
+            Ok(value) => value,
+            Err(CoreError::NotFound { .. }) => continue,
+            Err(err) => return Err(anyhow!("failed to read secret {uri}: {err}")),
+        };
+        let value = match cipher.as_ref() {
+            Some(cipher) if secrets_crypto::is_sealed(&value) => cipher.open(uri, &value)?,
+            _ => value,
+        };
+        secrets.push(TenantSecret {
+            uri: uri.clone(),
+            value: String::from_utf8(value)
+                .map_err(|_| anyhow!("{uri} is not valid UTF-8 and cannot be copied"))?,
+        });
+    }
+    Ok(secrets)
+}
+
+/// The dev store has no delete, so the entries of every other tenant are copied,
+/// still sealed, into a fresh store that then replaces the old file. Only URIs
+/// recorded in the store metadata are carried over.
+fn remove_tenant_secrets(
+    bundle: &Path,
+    tenant: &str,
+    dry_run: bool,
+) -> anyhow::Result<Vec<String>> {
+    let Some(store_path) = dev_store_path::find_existing(bundle) else {
+        return Ok(Vec::new());
+    };
+    let mut meta = secrets_crypto::read_meta(&store_path)?;
+    let owned = |uri: &str| {
+        secrets_gate::parse_canonical_secret_uri(uri).is_some_and(|parsed| parsed.tenant == tenant)
+    };
+    let (removed, kept): (Vec<String>, Vec<String>) =
+        meta.uris.iter().cloned().partition(|uri| owned(uri));
+    if removed.is_empty() || dry_run {
+        return Ok(removed);
+    }
+
+    let runtime = secrets_runtime()?;
+    let store = open_store(&store_path)?;
+    let mut entries = Vec::new();
+    for uri in &kept {
+        let value = match runtime.block_on(store.get(uri)) {
+            Ok(value) => value,
+            Err(CoreError::NotFound { .. }) => continue,
+            Err(err) => return Err(anyhow!("failed to read secret {uri}: {err}")),
+        };
+        entries.push(SeedEntry {
+            uri: uri.clone(),
+            format: SecretFormat::Text,
+            value: SeedValue::Text {
+                text: String::from_utf8(value)
+                    .map_err(|_| anyhow!("{uri} is not valid UTF-8 and cannot be kept"))?,
+            },
+            description: None,
+        });
+    }
+    drop(store);
+
+    if entries.is_empty() {
+        fs::remove_file(&store_path).with_context(|| format!("remove {}", store_path.display()))?;
+    } else {
+        let mut name = store_path.file_name().unwrap_or_default().to_os_string();
+        name.push(".rebuild");
+        let fresh = store_path.with_file_name(name);
+        if fresh.exists() {
+            fs::remove_file(&fresh)?;
+        }
+        let report = runtime.block_on(apply_seed(
+            &open_store(&fresh)?,
+            &SeedDoc { entries },
+            ApplyOptions::default(),
+        ));
+        if !report.failed.is_empty() {
+            let _ = fs::remove_file(&fresh);
+            return Err(anyhow!("failed to rewrite secrets: {:?}", report.failed));
+        }
+        fs::rename(&fresh, &store_path)
+            .with_context(|| format!("replace {}", store_path.display()))?;
+    }
+    meta.uris = kept.into_iter().collect();
+    meta.versions.retain(|uri, _| !owned(uri));
+    secrets_crypto::write_meta(&store_path, &meta)?;
+    Ok(removed)
+}
+
+fn write_secrets(bundle: &Path, secrets: &[TenantSecret]) -> anyhow::Result<()> {
+    let store_path = dev_store_path::ensure_path(bundle)?;
+    let entries = secrets
+        .iter()
+        .map(|secret| SeedEntry {
+            uri: secret.uri.clone(),
+            format: SecretFormat::Text,
+            value: SeedValue::Text {
+                text: secret.value.clone(),
+            },
+            description: Some("tenant transfer".to_string()),
+        })
+        .collect();
+    let key_source = secrets_crypto::configured_key_source(bundle)?;
+    let entries = secrets_crypto::prepare_seed_entries(&store_path, key_source.as_ref(), entries)?;
+    let store = open_store(&store_path)?;
+    let report = secrets_runtime()?.block_on(apply_seed(
+        &store,
+        &SeedDoc { entries },
+        ApplyOptions::default(),
+    ));
+    if !report.failed.is_empty() {
+        return Err(anyhow!("failed to write secrets: {:?}", report.failed));
+    }
+    Ok(())
+}
+
+fn open_store(store_path: &Path) -> anyhow::Result<DevStore> {
+    DevStore::with_path(store_path).map_err(|err| {
+        anyhow!(
+            "failed to open dev secrets store {}: {err}",
+            store_path.display()
+        )
+    })
+}
+
+fn secrets_runtime() -> anyhow::Result<tokio::runtime::Runtime> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("build secrets runtime")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = "\
+version: '1'
+tenant: acme
+team: support
+project_root: ./
+policy:
+  source:
+    tenant_gmap: tenants/acme/tenant.gmap
+    team_gmap: tenants/acme/teams/support/team.gmap
+  default: forbidden
+";
+
+    fn write_tenant(bundle: &Path) {
+        project::add_tenant(bundle, "acme").unwrap();
+        project::add_team(bundle, "acme", "support").unwrap();
+        fs::create_dir_all(bundle.join("resolved")).unwrap();
+        fs::write(bundle.join("resolved/acme.support.yaml"), MANIFEST).unwrap();
+        fs::write(bundle.join("resolved/acmex.yaml"), "tenant: acmex\n").unwrap();
+    }
+
+    #[test]
+    fn clone_renames_directories_and_manifests() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        write_tenant(dir.path());
+        let report = apply(
+            dir.path(),
+            &snapshot(dir.path(), "acme", false)?.renamed("globex")?,
+            false,
+        )?;
+        assert_eq!(
+            report.files,
+            vec![
+                PathBuf::from("resolved/globex.support.yaml"),
+                PathBuf::from("tenants/globex/teams/support/team.gmap"),
+                PathBuf::from("tenants/globex/tenant.gmap"),
+            ]
+        );
+        let manifest =
+            project::load_resolved_manifest(&dir.path().join("resolved/globex.support.yaml"))?;
+        assert_eq!(manifest.tenant, "globex");
+        assert_eq!(
+            manifest.policy.source.tenant_gmap,
+            "tenants/globex/tenant.gmap"
+        );
+        assert!(
+            apply(
+                dir.path(),
+                &snapshot(dir.path(), "acme", false)?.renamed("globex")?,
+                false
+            )
+            .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn remove_deletes_files_and_secrets_of_one_tenant() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        write_tenant(dir.path());
+        let acme = "secrets://dev/acme/_/messaging-slack/slack_bot_token";
+        let globex = "secrets://dev/globex/_/messaging-slack/slack_bot_token";
+        write_secrets(
+            dir.path(),
+            &[
+                TenantSecret {
+                    uri: acme.to_string(),
+                    value: "xoxb-acme".to_string(),
+                },
+                TenantSecret {
+                    uri: globex.to_string(),
+                    value: "xoxb-globex".to_string(),
+                },
+            ],
+        )?;
+
+        let planned = remove(dir.path(), "acme", true)?;
+        assert_eq!(
+            planned.paths,
+            vec![
+                PathBuf::from("resolved/acme.support.yaml"),
+                PathBuf::from("tenants/acme"),
+            ]
+        );
+        assert_eq!(planned.secrets, vec![acme.to_string()]);
+        assert!(dir.path().join("tenants/acme").is_dir());
+
+        remove(dir.path(), "acme", false)?;
+        assert!(!dir.path().join("tenants/acme").exists());
+        assert!(!dir.path().join("resolved/acme.support.yaml").exists());
+        assert!(dir.path().join("resolved/acmex.yaml").exists());
+        assert!(read_tenant_secrets(dir.path(), "acme")?.is_empty());
+        let kept = read_tenant_secrets(dir.path(), "globex")?;
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].value, "xoxb-globex");
+        assert!(remove(dir.path(), "acme", false).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn archives_round_trip_with_sealed_secrets() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        write_tenant(dir.path());
+        let mut snapshot = snapshot(dir.path(), "acme", false)?;
+        snapshot.secrets.push(TenantSecret {
+            uri: "secrets://dev/acme/_/messaging-slack/slack_bot_token".to_string(),
+            value: "xoxb-test".to_string(),
+        });
+        unsafe {
+            std::env::set_var("GREENTIC_TEST_TENANT_ARCHIVE_PASS", "correct horse");
+        }
+        let source = KeySource::Passphrase {
+            env: "GREENTIC_TEST_TENANT_ARCHIVE_PASS".to_string(),
+        };
+        let archive = dir.path().join("out/acme.tar.gz");
+        write_archive(&snapshot, &archive, Some(&source))?;
+
+        let mut raw = String::new();
+        tar::Archive::new(GzDecoder::new(fs::File::open(&archive)?))
+            .entries()?
+            .filter_map(Result::ok)
+            .for_each(|mut entry| {
+                entry.read_to_string(&mut raw).ok();
+            });
+        assert!(!raw.contains("xoxb-test"));
+
+        let restored = read_archive(&archive)?.renamed("initech")?;
+        assert_eq!(restored.tenant, "initech");
+        assert_eq!(restored.files.len(), snapshot.files.len());
+        assert_eq!(
+            restored.secrets[0].uri,
+            "secrets://dev/initech/_/messaging-slack/slack_bot_token"
+        );
+        assert_eq!(restored.secrets[0].value, "xoxb-test");
+        Ok(())
+    }
+
+    /// An archive as a hostile exporter could write it.
+    fn raw_archive(
+        dir: &Path,
+        tenant: &str,
+        files: &[&str],
+        secrets: &[&str],
+    ) -> anyhow::Result<PathBuf> {
+        let archive = dir.join("raw.tar.gz");
+        let mut tar = tar::Builder::new(GzEncoder::new(
+            fs::File::create(&archive)?,
+            Compression::default(),
+        ));
+        let header = ArchiveHeader {
+            format: ARCHIVE_FORMAT.to_string(),
+            tenant: tenant.to_string(),
+            secrets_key: None,
+        };
+        append_bytes(&mut tar, "tenant.json", &serde_json::to_vec(&header)?)?;
+        for file in files {
+            append_bytes(&mut tar, Path::new("files").join(file), b"x")?;
+        }
+        let secrets = secrets
+            .iter()
+            .map(|uri| TenantSecret {
+                uri: uri.to_string(),
+                value: "x".to_string(),
+            })
+            .collect::<Vec<_>>();
+        append_bytes(&mut tar, "secrets.json", &serde_json::to_vec(&secrets)?)?;
+        tar.into_inner()?.finish()?;
+        Ok(archive)
+    }
+
+    #[test]
+    fn archives_may_only_carry_their_own_tenant() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let own_secret = "secrets://dev/acme/_/messaging-slack/slack_bot_token";
+        let archive = raw_archive(
+            dir.path(),
+            "acme",
+            &[
+                "tenants/acme/tenant.gmap",
+                "messaging/tenants/acme/teams/support/team.gmap",
+                "resolved/acme.support.yaml",
+            ],
+            &[own_secret],
+        )?;
+        assert_eq!(read_archive(&archive)?.files.len(), 3);
+
+        assert!(
+            raw_archive(dir.path(), "../acme", &[], &[])
+                .and_then(|a| read_archive(&a))
+                .is_err()
+        );
+        for file in [
+            "operators.yaml",
+            "greentic.demo.yaml",
+            "packs/messaging-slack.gtpack",
+            "tenants/globex/tenant.gmap",
+            "tenants/acme",
+            "resolved/globex.yaml",
+            "resolved/history/acme.yaml",
+        ] {
+            let archive = raw_archive(dir.path(), "acme", &[file], &[])?;
+            assert!(read_archive(&archive).is_err(), "{file} was accepted");
+        }
+        let archive = raw_archive(
+            dir.path(),
+            "acme",
+            &[],
+            &["secrets://dev/globex/_/messaging-slack/slack_bot_token"],
+        )?;
+        assert!(read_archive(&archive).is_err());
+        Ok(())
+    }
+}
@@ -5,8 +5,11 @@ use std::str::FromStr;
 use anyhow::{Context, anyhow};
 use serde::{Deserialize, Serialize};
 
+use crate::audit;
 use crate::gmap::{self, Policy};
 use crate::project;
+use crate::provider_registry::RegistryMirror;
+use crate::wizard_drift::DriftItem;
 
 #[derive(Clone, Debug, Serialize)]
 pub struct QaQuestion {
@@ -45,6 +48,9 @@ pub struct WizardPlan {
     pub bundle: PathBuf,
     pub steps: Vec<WizardPlanStep>,
     pub metadata: WizardPlanMetadata,
+    /// Manual edits found in the bundle by an update plan.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub drift: Vec<DriftItem>,
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -81,6 +87,7 @@ pub enum WizardStepKind {
     RunResolver,
     CopyResolvedManifest,
     ValidateBundle,
+    ReconcileDrift,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -457,6 +464,7 @@ pub fn apply_create(request: &WizardCreateRequest, dry_run: bool) -> anyhow::Res
             tenants_remove: request.tenants_remove.clone(),
             access_changes: request.access_changes.clone(),
         },
+        drift: Vec::new(),
     })
 }
 
@@ -670,6 +678,7 @@ pub fn apply_update(request: &WizardCreateRequest, dry_run: bool) -> anyhow::Res
             tenants_remove: request.tenants_remove.clone(),
             access_changes: request.access_changes.clone(),
         },
+        drift: Vec::new(),
     })
 }
 
@@ -790,6 +799,7 @@ pub fn apply_remove(request: &WizardCreateRequest, dry_run: bool) -> anyhow::Res
             tenants_remove: request.tenants_remove.clone(),
             access_changes: request.access_changes.clone(),
         },
+        drift: Vec::new(),
     })
 }
 
@@ -823,15 +833,34 @@ pub fn normalize_request_for_plan(
     Ok(normalized)
 }
 
+/// With `best_effort`, a pack or tenant that fails to apply is reported in the
+/// report's warnings and the rest of the plan still runs. Pack refs found in
+/// `mirror` are taken from it instead of being fetched.
 pub fn execute_plan(
     mode: WizardMode,
     plan: &WizardPlan,
     offline: bool,
+    mirror: Option<&RegistryMirror>,
+    best_effort: bool,
 ) -> anyhow::Result<WizardExecutionReport> {
     match mode {
-        WizardMode::Create => execute_create_plan(plan, offline),
-        WizardMode::Update => execute_update_plan(plan, offline),
-        WizardMode::Remove => execute_remove_plan(plan),
+        WizardMode::Create => execute_create_plan(plan, offline, mirror, best_effort),
+        WizardMode::Update => execute_update_plan(plan, offline, mirror, best_effort),
+        WizardMode::Remove => execute_remove_plan(plan, best_effort),
+    }
+}
+
+fn keep_going(
+    best_effort: bool,
+    result: anyhow::Result<()>,
+    warnings: &mut Vec<String>,
+) -> anyhow::Result<()> {
+    match result {
+        Err(err) if best_effort => {
+            warnings.push(format!("best-effort: {err:#}"));
+            Ok(())
+        }
+        result => result,
     }
 }
 
@@ -879,6 +908,28 @@ pub fn print_plan_summary(plan: &WizardPlan) {
             localized_step_description(&step.description)
         );
     }
+    if !plan.drift.is_empty() {
+        println!(
+            "{}",
+            crate::operator_i18n::tr("cli.wizard.drift_header", "drift since last wizard run:")
+        );
+    }
+    for item in &plan.drift {
+        let cause = item
+            .cause
+            .as_deref()
+            .map(|cause| format!(" <- {cause}"))
+            .unwrap_or_default();
+        println!(
+            "  {} {} ({}){cause}",
+            item.action.as_str(),
+            item.path,
+            item.change.as_str()
+        );
+        for rule in &item.rules {
+            println!("      {rule}");
+        }
+    }
 }
 
 fn localized_step_description(description: &str) -> String {
@@ -917,6 +968,10 @@ fn localized_step_description(description: &str) -> String {
             "cli.wizard.step.validate_bundle",
             "Validate bundle is loadable by internal demo pipeline",
         ),
+        "Reconcile manual edits found in the bundle" => crate::operator_i18n::tr(
+            "cli.wizard.step.reconcile_drift",
+            "Reconcile manual edits found in the bundle",
+        ),
         _ => description.to_string(),
     }
 }
@@ -924,6 +979,8 @@ fn localized_step_description(description: &str) -> String {
 pub fn execute_create_plan(
     plan: &WizardPlan,
     offline: bool,
+    mirror: Option<&RegistryMirror>,
+    best_effort: bool,
 ) -> anyhow::Result<WizardExecutionReport> {
     if plan.mode != WizardMode::Create.as_str() {
         return Err(anyhow!("unsupported wizard mode: {}", plan.mode));
@@ -939,16 +996,19 @@ pub fn execute_create_plan(
     create_demo_bundle_structure(&plan.bundle, plan.metadata.bundle_name.as_deref())?;
 
     let mut resolved_packs = Vec::new();
+    let mut warnings = Vec::new();
     if !plan.metadata.pack_refs.is_empty() {
-        let mut resolved = resolve_pack_refs(&plan.metadata.pack_refs, offline)
+        let mut resolved = resolve_pack_refs(&plan.metadata.pack_refs, offline, mirror)
             .context("resolve pack refs via distributor-client")?;
         assign_pack_ids_and_persist_metadata(&plan.bundle, &mut resolved)?;
-        for item in resolved {
-            copy_pack_into_bundle(&plan.bundle, &item)?;
-            resolved_packs.push(item);
-        }
+        copy_packs_into_bundle(
+            &plan.bundle,
+            resolved,
+            best_effort,
+            &mut resolved_packs,
+            &mut warnings,
+        )?;
     }
-    let mut warnings = Vec::new();
     let mut provider_updates = upsert_provider_registry(&plan.bundle, &resolved_packs)?;
     if !plan.metadata.default_assignments.is_empty() {
         apply_default_assignments(
@@ -981,6 +1041,8 @@ pub fn execute_create_plan(
 pub fn execute_update_plan(
     plan: &WizardPlan,
     offline: bool,
+    mirror: Option<&RegistryMirror>,
+    best_effort: bool,
 ) -> anyhow::Result<WizardExecutionReport> {
     if plan.mode != WizardMode::Update.as_str() {
         return Err(anyhow!("unsupported wizard mode: {}", plan.mode));
@@ -1021,13 +1083,16 @@ pub fn execute_update_plan(
     }
 
     if ops.contains(&WizardUpdateOp::PacksAdd) && !plan.metadata.pack_refs.is_empty() {
-        let mut resolved = resolve_pack_refs(&plan.metadata.pack_refs, offline)
+        let mut resolved = resolve_pack_refs(&plan.metadata.pack_refs, offline, mirror)
             .context("resolve pack refs via distributor-client")?;
         assign_pack_ids_and_persist_metadata(&plan.bundle, &mut resolved)?;
-        for item in resolved {
-            copy_pack_into_bundle(&plan.bundle, &item)?;
-            resolved_packs.push(item);
-        }
+        copy_packs_into_bundle(
+            &plan.bundle,
+            resolved,
+            best_effort,
+            &mut resolved_packs,
+            &mut warnings,
+        )?;
     }
     if !plan.metadata.default_assignments.is_empty() {
         apply_default_assignments(
@@ -1038,7 +1103,8 @@ pub fn execute_update_plan(
     }
     if ops.contains(&WizardUpdateOp::PacksRemove) {
         for selection in &plan.metadata.packs_remove {
-            apply_pack_remove(&plan.bundle, selection, &mut warnings)?;
+            let result = apply_pack_remove(&plan.bundle, selection, &mut warnings);
+            keep_going(best_effort, result, &mut warnings)?;
         }
     }
     let mut provider_updates = upsert_provider_registry(&plan.bundle, &resolved_packs)?;
@@ -1053,12 +1119,14 @@ pub fn execute_update_plan(
     }
     if ops.contains(&WizardUpdateOp::TenantsAdd) {
         for tenant in &plan.metadata.tenants {
-            ensure_tenant_and_team(&plan.bundle, tenant)?;
+            let result = ensure_tenant_and_team(&plan.bundle, tenant);
+            keep_going(best_effort, result, &mut warnings)?;
         }
     }
     if ops.contains(&WizardUpdateOp::TenantsRemove) {
         for tenant in &plan.metadata.tenants_remove {
-            remove_tenant_or_team(&plan.bundle, tenant, &mut warnings)?;
+            let result = remove_tenant_or_team(&plan.bundle, tenant, &mut warnings);
+            keep_going(best_effort, result, &mut warnings)?;
         }
     }
 
@@ -1080,7 +1148,10 @@ pub fn execute_update_plan(
     })
 }
 
-pub fn execute_remove_plan(plan: &WizardPlan) -> anyhow::Result<WizardExecutionReport> {
+pub fn execute_remove_plan(
+    plan: &WizardPlan,
+    best_effort: bool,
+) -> anyhow::Result<WizardExecutionReport> {
     if plan.mode != WizardMode::Remove.as_str() {
         return Err(anyhow!("unsupported wizard mode: {}", plan.mode));
     }
@@ -1102,7 +1173,8 @@ pub fn execute_remove_plan(plan: &WizardPlan) -> anyhow::Result<WizardExecutionR
 
     if targets.contains(&WizardRemoveTarget::Packs) {
         for selection in &plan.metadata.packs_remove {
-            apply_pack_remove(&plan.bundle, selection, &mut warnings)?;
+            let result = apply_pack_remove(&plan.bundle, selection, &mut warnings);
+            keep_going(best_effort, result, &mut warnings)?;
         }
     }
     let mut provider_updates = 0usize;
@@ -1112,7 +1184,8 @@ pub fn execute_remove_plan(plan: &WizardPlan) -> anyhow::Result<WizardExecutionR
     }
     if targets.contains(&WizardRemoveTarget::TenantsTeams) {
         for tenant in &plan.metadata.tenants_remove {
-            remove_tenant_or_team(&plan.bundle, tenant, &mut warnings)?;
+            let result = remove_tenant_or_team(&plan.bundle, tenant, &mut warnings);
+            keep_going(best_effort, result, &mut warnings)?;
         }
     }
     Ok(WizardExecutionReport {
@@ -1230,7 +1303,11 @@ fn resolved_manifest_filename(tenant: &str, team: Option<&str>) -> String {
     }
 }
 
-fn resolve_pack_refs(pack_refs: &[String], offline: bool) -> anyhow::Result<Vec<ResolvedPackInfo>> {
+pub(crate) fn resolve_pack_refs(
+    pack_refs: &[String],
+    offline: bool,
+    mirror: Option<&RegistryMirror>,
+) -> anyhow::Result<Vec<ResolvedPackInfo>> {
     use greentic_distributor_client::{
         OciPackFetcher, PackFetchOptions, oci_packs::DefaultRegistryClient,
     };
@@ -1250,43 +1327,85 @@ fn resolve_pack_refs(pack_refs: &[String], offline: bool) -> anyhow::Result<Vec<
     }
     let fetcher: OciPackFetcher<DefaultRegistryClient> = OciPackFetcher::new(opts);
 
+    let phase = crate::progress::Phase::start("resolve packs", pack_refs.len());
     let mut resolved = Vec::new();
     for reference in pack_refs {
-        if let Some(local_path) = parse_local_pack_ref(reference) {
-            let meta = crate::domains::read_pack_meta(&local_path)
-                .with_context(|| format!("read pack meta from {}", local_path.display()))?;
-            let digest = local_pack_digest(&local_path)?;
-            let file_name = deterministic_pack_file_name(reference, &digest);
-            resolved.push(ResolvedPackInfo {
-                source_ref: reference.clone(),
-                mapped_ref: local_path.display().to_string(),
-                resolved_digest: digest,
-                pack_id: meta.pack_id,
-                entry_flows: meta.entry_flows,
-                cached_path: local_path,
-                output_path: PathBuf::from("packs").join(file_name),
-            });
-            continue;
+        phase.set_message(reference.as_str());
+        match resolve_pack_ref(reference, &rt, &fetcher, mirror) {
+            Ok(info) => {
+                phase.advance(reference, true);
+                resolved.push(info);
+            }
+            Err(err) => {
+                phase.advance(reference, false);
+                phase.finish();
+                return Err(err);
+            }
         }
-        let mapped_ref = map_pack_reference(reference)?;
-        let fetched = rt
-            .block_on(fetcher.fetch_pack_to_cache(&mapped_ref))
-            .with_context(|| format!("fetch pack reference {reference}"))?;
-        let meta = crate::domains::read_pack_meta(&fetched.path)
-            .with_context(|| format!("read pack meta from {}", fetched.path.display()))?;
-        let file_name = deterministic_pack_file_name(reference, &fetched.resolved_digest);
-        resolved.push(ResolvedPackInfo {
-            source_ref: reference.clone(),
-            mapped_ref,
-            resolved_digest: fetched.resolved_digest,
+    }
+    phase.finish();
+    resolved.sort_by(|a, b| a.source_ref.cmp(&b.source_ref));
+    Ok(resolved)
+}
+
+/// A local path, a mirrored copy, or a fetch into the pack cache, in that order.
+fn resolve_pack_ref(
+    reference: &str,
+    rt: &tokio::runtime::Runtime,
+    fetcher: &greentic_distributor_client::OciPackFetcher<
+        greentic_distributor_client::oci_packs::DefaultRegistryClient,
+    >,
+    mirror: Option<&RegistryMirror>,
+) -> anyhow::Result<ResolvedPackInfo> {
+    if let Some(local_path) = parse_local_pack_ref(reference) {
+        let meta = crate::domains::read_pack_meta(&local_path)
+            .with_context(|| format!("read pack meta from {}", local_path.display()))?;
+        let digest = local_pack_digest(&local_path)?;
+        let file_name = deterministic_pack_file_name(reference, &digest);
+        return Ok(ResolvedPackInfo {
+            source_ref: reference.to_string(),
+            mapped_ref: local_path.display().to_string(),
+            resolved_digest: digest,
             pack_id: meta.pack_id,
             entry_flows: meta.entry_flows,
-            cached_path: fetched.path,
+            cached_path: local_path,
             output_path: PathBuf::from("packs").join(file_name),
         });
     }
-    resolved.sort_by(|a, b| a.source_ref.cmp(&b.source_ref));
-    Ok(resolved)
+    if let Some((path, pack)) = mirror
+        .map(|mirror| mirror.pack(reference))
+        .transpose()?
+        .flatten()
+    {
+        let meta = crate::domains::read_pack_meta(&path)
+            .with_context(|| format!("read pack meta from {}", path.display()))?;
+        return Ok(ResolvedPackInfo {
+            source_ref: reference.to_string(),
+            mapped_ref: path.display().to_string(),
+            resolved_digest: pack.digest.clone(),
+            pack_id: meta.pack_id,
+            entry_flows: meta.entry_flows,
+            output_path: PathBuf::from("packs")
+                .join(deterministic_pack_file_name(reference, &pack.digest)),
+            cached_path: path,
+        });
+    }
+    let mapped_ref = map_pack_reference(reference)?;
+    let fetched = rt
+        .block_on(fetcher.fetch_pack_to_cache(&mapped_ref))
+        .with_context(|| format!("fetch pack reference {reference}"))?;
+    let meta = crate::domains::read_pack_meta(&fetched.path)
+        .with_context(|| format!("read pack meta from {}", fetched.path.display()))?;
+    let file_name = deterministic_pack_file_name(reference, &fetched.resolved_digest);
+    Ok(ResolvedPackInfo {
+        source_ref: reference.to_string(),
+        mapped_ref,
+        resolved_digest: fetched.resolved_digest,
+        pack_id: meta.pack_id,
+        entry_flows: meta.entry_flows,
+        cached_path: fetched.path,
+        output_path: PathBuf::from("packs").join(file_name),
+    })
 }
 
 fn parse_local_pack_ref(reference: &str) -> Option<PathBuf> {
@@ -1382,6 +1501,23 @@ fn deterministic_pack_file_name(reference: &str, digest: &str) -> String {
     format!("{slug}-{short_digest}.gtpack")
 }
 
+/// Copies each pack, keeping only those that made it into the bundle.
+fn copy_packs_into_bundle(
+    bundle: &Path,
+    packs: Vec<ResolvedPackInfo>,
+    best_effort: bool,
+    copied: &mut Vec<ResolvedPackInfo>,
+    warnings: &mut Vec<String>,
+) -> anyhow::Result<()> {
+    for pack in packs {
+        match copy_pack_into_bundle(bundle, &pack) {
+            Ok(()) => copied.push(pack),
+            result => keep_going(best_effort, result, warnings)?,
+        }
+    }
+    Ok(())
+}
+
 fn copy_pack_into_bundle(bundle: &Path, pack: &ResolvedPackInfo) -> anyhow::Result<()> {
     let src = pack.cached_path.clone();
     if !src.exists() {
@@ -1835,12 +1971,11 @@ where
             ));
             continue;
         }
-        let dst = bundle.join("resolved").join(&filename);
-        if let Some(parent) = dst.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-        std::fs::copy(&src, &dst)?;
-        copied.push(dst);
+        copied.push(project::publish_resolved_manifest(
+            bundle,
+            &tenant,
+            team.as_deref(),
+        )?);
     }
     Ok(copied)
 }
@@ -1860,7 +1995,16 @@ fn apply_access_and_sync(
                 continue;
             }
             let gmap_path = demo_bundle_gmap_path(bundle, &tenant.tenant, tenant.team.as_deref());
-            gmap::upsert_policy(&gmap_path, path, Policy::Public)?;
+            let result = gmap::upsert_policy(&gmap_path, path, Policy::Public);
+            audit::record_gmap_edit(
+                bundle,
+                &tenant.tenant,
+                tenant.team.as_deref(),
+                path,
+                &Policy::Public,
+                &result,
+            );
+            result?;
         }
     }
     for change in access_changes {
@@ -1874,7 +2018,17 @@ fn apply_access_and_sync(
         )?;
         copy_targets.insert((change.tenant_id.clone(), change.team_id.clone()));
         let gmap_path = demo_bundle_gmap_path(bundle, &change.tenant_id, change.team_id.as_deref());
-        gmap::upsert_policy(&gmap_path, &change.pack_id, change.operation.policy())?;
+        let policy = change.operation.policy();
+        let result = gmap::upsert_policy(&gmap_path, &change.pack_id, policy.clone());
+        audit::record_gmap_edit(
+            bundle,
+            &change.tenant_id,
+            change.team_id.as_deref(),
+            &change.pack_id,
+            &policy,
+            &result,
+        );
+        result?;
     }
     if copy_targets.is_empty() {
         return Ok(Vec::new());
@@ -2004,6 +2158,27 @@ fn write_packs_metadata(bundle: &Path, metadata: &PacksMetadata) -> anyhow::Resu
     Ok(())
 }
 
+/// The id a pack ref is stored under in `packs/` when the plan executes.
+pub(crate) fn planned_pack_id(bundle: &Path, reference: &str) -> anyhow::Result<String> {
+    let metadata = load_packs_metadata(bundle)?;
+    if let Some(record) = metadata
+        .packs
+        .iter()
+        .find(|record| record.original_ref == reference)
+    {
+        return Ok(record.pack_id.clone());
+    }
+    let used_ids = metadata
+        .packs
+        .iter()
+        .map(|record| record.pack_id.clone())
+        .collect();
+    Ok(allocate_unique_pack_id(
+        &derive_pack_id_from_reference(reference),
+        &used_ids,
+    ))
+}
+
 fn allocate_unique_pack_id(base: &str, used_ids: &BTreeSet<String>) -> String {
     if !used_ids.contains(base) {
         return base.to_string();
@@ -2179,7 +2354,7 @@ mod tests {
             access_changes: Vec::new(),
         };
         let plan = apply_create(&req, false).unwrap();
-        let report = execute_create_plan(&plan, true).unwrap();
+        let report = execute_create_plan(&plan, true, None, false).unwrap();
         assert!(report.bundle.exists());
         assert!(
             bundle
@@ -2214,7 +2389,7 @@ mod tests {
             access_changes: Vec::new(),
         };
         let create_plan = apply_create(&create_req, false).unwrap();
-        let _ = execute_create_plan(&create_plan, true).unwrap();
+        let _ = execute_create_plan(&create_plan, true, None, false).unwrap();
 
         let req = WizardCreateRequest {
             bundle: bundle.clone(),
@@ -2236,7 +2411,7 @@ mod tests {
         };
         let plan = apply_update(&req, false).unwrap();
         assert_eq!(plan.mode, "update");
-        let report = execute_update_plan(&plan, true).unwrap();
+        let report = execute_update_plan(&plan, true, None, false).unwrap();
         assert!(report.bundle.exists());
     }
 
@@ -2263,7 +2438,7 @@ mod tests {
             access_changes: Vec::new(),
         };
         let create_plan = apply_create(&create_req, false).unwrap();
-        let _ = execute_create_plan(&create_plan, true).unwrap();
+        let _ = execute_create_plan(&create_plan, true, None, false).unwrap();
 
         let remove_req = WizardCreateRequest {
             bundle: bundle.clone(),
@@ -2284,7 +2459,7 @@ mod tests {
             access_changes: Vec::new(),
         };
         let remove_plan = apply_remove(&remove_req, false).unwrap();
-        let _ = execute_remove_plan(&remove_plan).unwrap();
+        let _ = execute_remove_plan(&remove_plan, false).unwrap();
         assert!(
             !bundle
                 .join("tenants")
@@ -2506,11 +2681,20 @@ mod tests {
             access_changes: Vec::new(),
         };
         let plan = apply_remove(&request, false).unwrap();
-        let report = execute_remove_plan(&plan).unwrap();
+        let report = execute_remove_plan(&plan, false).unwrap();
         assert_eq!(report.provider_updates, 0);
         assert!(!report.warnings.is_empty());
     }
 
+    #[test]
+    fn best_effort_turns_item_failures_into_warnings() {
+        let mut warnings = Vec::new();
+        keep_going(true, Err(anyhow!("copy failed")), &mut warnings).unwrap();
+        assert_eq!(warnings, vec!["best-effort: copy failed".to_string()]);
+        assert!(keep_going(false, Err(anyhow!("copy failed")), &mut warnings).is_err());
+        assert_eq!(warnings.len(), 1);
+    }
+
     #[test]
     fn update_applies_global_default_assignment_and_bundle_name_written() {
         let temp = tempfile::tempdir().unwrap();
@@ -2534,7 +2718,7 @@ mod tests {
             access_changes: Vec::new(),
         };
         let create_plan = apply_create(&create_request, false).unwrap();
-        let _create_report = execute_create_plan(&create_plan, true).unwrap();
+        let _create_report = execute_create_plan(&create_plan, true, None, false).unwrap();
         std::fs::create_dir_all(bundle.join("packs")).unwrap();
         std::fs::write(bundle.join("packs").join("sales.gtpack"), "dummy").unwrap();
 
@@ -2560,7 +2744,7 @@ mod tests {
             access_changes: Vec::new(),
         };
         let update_plan = apply_update(&update_request, false).unwrap();
-        let _report = execute_update_plan(&update_plan, true).unwrap();
+        let _report = execute_update_plan(&update_plan, true, None, false).unwrap();
         let default_raw = std::fs::read_to_string(bundle.join("default.gtpack")).unwrap();
         assert!(default_raw.contains("packs/sales.gtpack"));
         let demo_yaml = std::fs::read_to_string(bundle.join("greentic.demo.yaml")).unwrap();
@@ -0,0 +1,315 @@
+//! Declarative bundle spec for `wizard --spec bundle.yaml`.
+//!
+//! One file lists the packs, tenants/teams, access matrix, default packs, and
+//! providers of a bundle. It converts straight into a [`WizardCreateRequest`]
+//! with no QA round, so CI can stamp out the same bundle every time. Relative
+//! paths in the spec (`bundle`, `setup.input`) are taken from the spec's directory.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, anyhow};
+use serde::Deserialize;
+
+use crate::wizard::{
+    AccessChangeSelection, AccessOperation, PackDefaultSelection, PackListing, PackScope,
+    TenantSelection, WizardCreateRequest,
+};
+
+/// Key of the access matrix row that applies to every target.
+const ALL_TARGETS: &str = "*";
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BundleSpec {
+    #[serde(default)]
+    pub bundle: Option<PathBuf>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub packs: Vec<SpecPack>,
+    /// Tenant to its teams; an empty list means the tenant has no teams.
+    #[serde(default)]
+    pub tenants: BTreeMap<String, Vec<String>>,
+    /// `*`, `tenant`, or `tenant:team` to the `PACK[/FLOW[/NODE]]` paths it may use.
+    /// A tenant row applies to each of its teams.
+    #[serde(default)]
+    pub access: BTreeMap<String, Vec<String>>,
+    /// Catalog ids of providers to add (and set up with `setup.run`).
+    #[serde(default)]
+    pub providers: Vec<String>,
+    #[serde(default)]
+    pub setup: SpecSetup,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpecPack {
+    /// Pack ref (`oci://`, `repo://`, `store://`, `file://`, or a local path).
+    #[serde(default, rename = "ref")]
+    pub reference: Option<String>,
+    /// Catalog id, resolved to its ref through the provider registry.
+    #[serde(default)]
+    pub catalog: Option<String>,
+    #[serde(default)]
+    pub access: SpecPackAccess,
+    /// `global`, `tenant`, or `tenant:team` the pack becomes the default for.
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+/// Who may use a pack: `all_tenants` (default), `none`, or a list of targets.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum SpecPackAccess {
+    Scope(String),
+    Targets(Vec<String>),
+}
+
+impl Default for SpecPackAccess {
+    fn default() -> Self {
+        Self::Scope("all_tenants".to_string())
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SpecSetup {
+    /// Run the providers' setup flows after the bundle is written.
+    #[serde(default)]
+    pub run: bool,
+    /// setup-input file passed to the setup runner.
+    #[serde(default)]
+    pub input: Option<PathBuf>,
+}
+
+impl BundleSpec {
+    /// Reads a YAML (or JSON) spec and resolves its relative paths.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("read bundle spec {}", path.display()))?;
+        let mut spec: BundleSpec = serde_yaml_bw::from_str(&raw)
+            .with_context(|| format!("parse bundle spec {}", path.display()))?;
+        let base = path.parent().unwrap_or(Path::new("."));
+        spec.bundle = spec.bundle.map(|bundle| base.join(bundle));
+        spec.setup.input = spec.setup.input.map(|input| base.join(input));
+        Ok(spec)
+    }
+
+    /// Whether converting the spec needs the provider registry's catalog.
+    pub fn needs_catalog(&self) -> bool {
+        !self.providers.is_empty() || self.packs.iter().any(|pack| pack.catalog.is_some())
+    }
+
+    /// Every tenant/team the bundle gets, by tenant then team.
+    pub fn targets(&self) -> Vec<(String, Option<String>)> {
+        let mut targets = Vec::new();
+        for (tenant, teams) in &self.tenants {
+            if teams.is_empty() {
+                targets.push((tenant.clone(), None));
+            }
+            for team in teams {
+                targets.push((tenant.clone(), Some(team.clone())));
+            }
+        }
+        targets
+    }
+
+    /// The wizard request for `bundle` (`--bundle`, else the spec's own `bundle`).
+    pub fn to_request(
+        &self,
+        bundle: Option<PathBuf>,
+        catalog: &[PackListing],
+    ) -> anyhow::Result<WizardCreateRequest> {
+        let bundle = bundle.or_else(|| self.bundle.clone()).ok_or_else(|| {
+            anyhow!("bundle path is required via --bundle or `bundle` in the spec")
+        })?;
+        let targets = self.targets();
+        if targets.is_empty() {
+            return Err(anyhow!("the spec must declare at least one tenant"));
+        }
+        let known = targets.iter().cloned().collect::<BTreeSet<_>>();
+        let parse_target = |value: &str| -> anyhow::Result<(String, Option<String>)> {
+            let (tenant, team) = match value.split_once(':') {
+                Some((tenant, team)) => (tenant.trim(), Some(team.trim().to_string())),
+                None => (value.trim(), None),
+            };
+            let declared = match &team {
+                Some(team) => known.contains(&(tenant.to_string(), Some(team.clone()))),
+                None => self.tenants.contains_key(tenant),
+            };
+            if !declared {
+                return Err(anyhow!(
+                    "{value} is not a tenant or tenant:team of the spec"
+                ));
+            }
+            Ok((tenant.to_string(), team))
+        };
+        let catalog_ref = |id: &str| {
+            catalog
+                .iter()
+                .find(|entry| entry.id == id)
+                .map(|entry| entry.reference.clone())
+                .ok_or_else(|| {
+                    anyhow!(
+                        "unknown catalog id {id}; available: {}",
+                        catalog
+                            .iter()
+                            .map(|entry| entry.id.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                })
+        };
+
+        let mut pack_refs = Vec::new();
+        let mut access_changes = Vec::new();
+        let mut default_assignments = Vec::new();
+        for pack in &self.packs {
+            let reference = match (&pack.reference, &pack.catalog) {
+                (Some(reference), None) => reference.trim().to_string(),
+                (None, Some(id)) => catalog_ref(id)?,
+                _ => return Err(anyhow!("each pack needs exactly one of `ref` or `catalog`")),
+            };
+            let allowed = match &pack.access {
+                SpecPackAccess::Scope(scope) if scope == "all_tenants" => targets.clone(),
+                SpecPackAccess::Scope(scope) if scope == "none" => Vec::new(),
+                SpecPackAccess::Scope(scope) => vec![parse_target(scope)?],
+                SpecPackAccess::Targets(values) => values
+                    .iter()
+                    .map(|value| parse_target(value))
+                    .collect::<anyhow::Result<_>>()?,
+            };
+            for (tenant_id, team_id) in allowed {
+                access_changes.push(AccessChangeSelection {
+                    pack_id: reference.clone(),
+                    operation: AccessOperation::AllowAdd,
+                    tenant_id,
+                    team_id,
+                });
+            }
+            if let Some(default) = pack.default.as_deref() {
+                let scope = match default.trim() {
+                    "global" => PackScope::Global,
+                    value => match parse_target(value)? {
+                        (tenant_id, None) => PackScope::Tenant { tenant_id },
+                        (tenant_id, Some(team_id)) => PackScope::Team { tenant_id, team_id },
+                    },
+                };
+                default_assignments.push(PackDefaultSelection {
+                    pack_identifier: reference.clone(),
+                    scope,
+                });
+            }
+            pack_refs.push(reference);
+        }
+        for id in &self.providers {
+            pack_refs.push(catalog_ref(id)?);
+        }
+
+        for key in self.access.keys() {
+            if key != ALL_TARGETS {
+                parse_target(key)?;
+            }
+        }
+        let row = |key: &str| self.access.get(key).into_iter().flatten().cloned();
+        let tenants = targets
+            .into_iter()
+            .map(|(tenant, team)| {
+                let mut allow_paths = row(ALL_TARGETS).chain(row(&tenant)).collect::<Vec<_>>();
+                if let Some(team) = &team {
+                    allow_paths.extend(row(&format!("{tenant}:{team}")));
+                }
+                TenantSelection {
+                    tenant,
+                    team,
+                    allow_paths,
+                }
+            })
+            .collect();
+
+        Ok(WizardCreateRequest {
+            bundle,
+            bundle_name: self.name.clone(),
+            pack_refs,
+            tenants,
+            default_assignments,
+            providers: self.providers.clone(),
+            update_ops: BTreeSet::new(),
+            remove_targets: BTreeSet::new(),
+            packs_remove: Vec::new(),
+            providers_remove: Vec::new(),
+            tenants_remove: Vec::new(),
+            access_changes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_spec_into_wizard_request() {
+        let spec: BundleSpec = serde_yaml_bw::from_str(
+            r#"
+bundle: out/customer-a
+name: Customer A
+packs:
+  - ref: oci://ghcr.io/acme/support-app:1.2.0
+    default: acme
+  - catalog: kb
+    access: [acme:support]
+tenants:
+  acme: [support, sales]
+  globex: []
+access:
+  "*": [support-app/main]
+  acme:sales: [support-app/triage]
+providers: [messaging-telegram]
+"#,
+        )
+        .unwrap();
+        let catalog = ["kb", "messaging-telegram"]
+            .map(|id| PackListing {
+                id: id.to_string(),
+                label: id.to_string(),
+                reference: format!("repo://{id}@latest"),
+            })
+            .to_vec();
+        assert!(spec.needs_catalog());
+
+        let request = spec.to_request(None, &catalog).unwrap();
+        assert_eq!(request.bundle, PathBuf::from("out/customer-a"));
+        assert_eq!(
+            request.pack_refs,
+            [
+                "oci://ghcr.io/acme/support-app:1.2.0",
+                "repo://kb@latest",
+                "repo://messaging-telegram@latest"
+            ]
+        );
+        // support-app goes to all three targets, kb to acme:support only.
+        assert_eq!(request.access_changes.len(), 4);
+        assert_eq!(
+            request.default_assignments[0].scope,
+            PackScope::Tenant {
+                tenant_id: "acme".to_string()
+            }
+        );
+        let sales = request
+            .tenants
+            .iter()
+            .find(|target| target.team.as_deref() == Some("sales"))
+            .unwrap();
+        assert_eq!(
+            sales.allow_paths,
+            ["support-app/main", "support-app/triage"]
+        );
+        assert_eq!(request.tenants.len(), 3);
+
+        let mut unknown = spec.clone();
+        unknown.access.insert("acme:ops".to_string(), Vec::new());
+        assert!(unknown.to_request(None, &catalog).is_err());
+    }
+}
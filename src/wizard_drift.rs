@@ -0,0 +1,515 @@
+//! Drift between a bundle on disk and what the wizard last wrote to it.
+//!
+//! Every wizard execution records the digests of the files it manages (packs,
+//! the provider registry, tenant gmaps and default links, resolved manifests) in
+//! `.greentic/wizard-state.json`. An update plan compares the bundle against that
+//! baseline and classifies each changed file by what the plan would do to it:
+//! keep it, merge into it, overwrite it, or delete it. Overwriting or deleting a
+//! manual edit needs `--overwrite-drift`.
+//!
+//! Bundles written before the baseline existed have nothing to compare against;
+//! for them only resolved manifests that differ from the resolver's
+//! `state/resolved/` copy and gmap rules the plan would flip are reported.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::Context;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::demo::diff::digest_path;
+use crate::gmap::{self, Policy};
+use crate::runtime_state::atomic_write;
+use crate::wizard::{
+    PackScope, WizardPlan, WizardPlanMetadata, WizardPlanStep, WizardStepKind, WizardUpdateOp,
+};
+
+/// Bundle entries the wizard writes, relative to the bundle root.
+const TRACKED: [&str; 5] = [
+    "packs",
+    "providers",
+    "tenants",
+    "resolved",
+    "default.gtpack",
+];
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct WizardBaseline {
+    pub recorded_at: String,
+    /// Bundle-relative path to the sha256 of the file.
+    pub files: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftChange {
+    Modified,
+    Added,
+    Removed,
+}
+
+impl DriftChange {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DriftChange::Modified => "modified",
+            DriftChange::Added => "added",
+            DriftChange::Removed => "removed",
+        }
+    }
+}
+
+/// What executing the plan does to a drifted file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftAction {
+    /// Not touched by the plan; the manual edit survives.
+    Keep,
+    /// Rewritten by the plan with its existing entries carried over.
+    Merge,
+    /// Replaced (or recreated) by the plan; the manual edit is lost.
+    Overwrite,
+    /// Deleted by the plan.
+    Delete,
+}
+
+impl DriftAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DriftAction::Keep => "keep",
+            DriftAction::Merge => "merge",
+            DriftAction::Overwrite => "overwrite",
+            DriftAction::Delete => "delete",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DriftItem {
+    /// Bundle-relative path.
+    pub path: String,
+    pub change: DriftChange,
+    pub action: DriftAction,
+    /// The plan operation behind a non-`keep` action.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cause: Option<String>,
+    /// Gmap rules the plan would flip, as `path = before (line N) -> after`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<String>,
+}
+
+impl DriftItem {
+    /// Whether executing the plan loses this manual edit.
+    pub fn clobbered(&self) -> bool {
+        matches!(self.action, DriftAction::Overwrite | DriftAction::Delete)
+    }
+}
+
+fn baseline_path(bundle: &Path) -> std::path::PathBuf {
+    bundle.join(".greentic").join("wizard-state.json")
+}
+
+/// Records the current state of the bundle as the wizard's baseline.
+pub fn record_baseline(bundle: &Path) -> anyhow::Result<()> {
+    let baseline = WizardBaseline {
+        recorded_at: Utc::now().to_rfc3339(),
+        files: snapshot(bundle)?,
+    };
+    let path = baseline_path(bundle);
+    let bytes = serde_json::to_vec_pretty(&baseline)?;
+    atomic_write(&path, &bytes).with_context(|| format!("write {}", path.display()))
+}
+
+pub fn load_baseline(bundle: &Path) -> anyhow::Result<Option<WizardBaseline>> {
+    let path = baseline_path(bundle);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let raw = std::fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+    serde_json::from_str(&raw)
+        .map(Some)
+        .with_context(|| format!("parse {}", path.display()))
+}
+
+fn snapshot(bundle: &Path) -> anyhow::Result<BTreeMap<String, String>> {
+    let mut files = BTreeMap::new();
+    for entry in TRACKED {
+        collect(bundle, &bundle.join(entry), &mut files)?;
+    }
+    Ok(files)
+}
+
+fn collect(bundle: &Path, path: &Path, files: &mut BTreeMap<String, String>) -> anyhow::Result<()> {
+    if path.is_dir() {
+        for entry in std::fs::read_dir(path)? {
+            collect(bundle, &entry?.path(), files)?;
+        }
+    } else if path.is_file() {
+        files.insert(relative(bundle, path), digest_path(path)?);
+    }
+    Ok(())
+}
+
+fn relative(bundle: &Path, path: &Path) -> String {
+    path.strip_prefix(bundle)
+        .unwrap_or(path)
+        .components()
+        .map(|part| part.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Drift in `bundle` and what an update plan with `metadata` would do to it.
+pub fn scan(bundle: &Path, metadata: &WizardPlanMetadata) -> anyhow::Result<Vec<DriftItem>> {
+    if !bundle.exists() {
+        return Ok(Vec::new());
+    }
+    let touched = Touched::from_plan(bundle, metadata)?;
+    let mut changed = BTreeMap::new();
+    match load_baseline(bundle)? {
+        Some(baseline) => {
+            let current = snapshot(bundle)?;
+            for (path, digest) in &current {
+                match baseline.files.get(path) {
+                    None => {
+                        changed.insert(path.clone(), DriftChange::Added);
+                    }
+                    Some(recorded) if recorded != digest => {
+                        changed.insert(path.clone(), DriftChange::Modified);
+                    }
+                    Some(_) => {}
+                }
+            }
+            for path in baseline.files.keys() {
+                if !current.contains_key(path) {
+                    changed.insert(path.clone(), DriftChange::Removed);
+                }
+            }
+        }
+        None => {
+            let resolved = bundle.join("resolved");
+            if resolved.is_dir() {
+                for entry in std::fs::read_dir(&resolved)? {
+                    let path = entry?.path();
+                    let state = bundle
+                        .join("state")
+                        .join("resolved")
+                        .join(path.file_name().unwrap_or_default());
+                    if path.is_file()
+                        && state.is_file()
+                        && std::fs::read(&path)? != std::fs::read(&state)?
+                    {
+                        changed.insert(relative(bundle, &path), DriftChange::Modified);
+                    }
+                }
+            }
+            for gmap in touched.gmaps.keys() {
+                if bundle.join(gmap).is_file() {
+                    changed.insert(gmap.clone(), DriftChange::Modified);
+                }
+            }
+        }
+    }
+
+    let mut items = Vec::new();
+    let has_baseline = baseline_path(bundle).exists();
+    for (path, change) in changed {
+        let (action, cause, rules) = touched.classify(bundle, &path)?;
+        // Without a baseline a gmap only counts as drift when the plan flips its rules.
+        if !has_baseline && touched.gmaps.contains_key(&path) && rules.is_empty() {
+            continue;
+        }
+        items.push(DriftItem {
+            path,
+            change,
+            action,
+            cause,
+            rules,
+        });
+    }
+    Ok(items)
+}
+
+/// Puts a reconcile step after the initial validation when drift was found.
+pub fn add_reconcile_step(plan: &mut WizardPlan) {
+    if plan.drift.is_empty() {
+        return;
+    }
+    let clobbered = plan.drift.iter().filter(|item| item.clobbered()).count();
+    let details = BTreeMap::from([
+        ("drifted".to_string(), plan.drift.len().to_string()),
+        ("overwritten".to_string(), clobbered.to_string()),
+    ]);
+    plan.steps.insert(
+        1.min(plan.steps.len()),
+        WizardPlanStep {
+            kind: WizardStepKind::ReconcileDrift,
+            description: "Reconcile manual edits found in the bundle".to_string(),
+            details,
+        },
+    );
+}
+
+/// The files an update plan writes, by what it does to them.
+#[derive(Default)]
+struct Touched {
+    files: BTreeMap<String, (DriftAction, String)>,
+    /// `packs/` entries by pack id.
+    packs: BTreeMap<String, (DriftAction, String)>,
+    /// Directories deleted with everything under them.
+    dirs: Vec<(String, String)>,
+    /// Gmap to the rules the plan upserts into it.
+    gmaps: BTreeMap<String, Vec<(String, Policy, String)>>,
+}
+
+impl Touched {
+    fn from_plan(bundle: &Path, metadata: &WizardPlanMetadata) -> anyhow::Result<Self> {
+        let ops = &metadata.update_ops;
+        let mut touched = Touched::default();
+        if ops.contains(&WizardUpdateOp::PacksAdd) {
+            for reference in &metadata.pack_refs {
+                let pack_id = crate::wizard::planned_pack_id(bundle, reference)?;
+                touched.packs.insert(
+                    pack_id,
+                    (DriftAction::Overwrite, format!("packs_add {reference}")),
+                );
+            }
+        }
+        for assignment in &metadata.default_assignments {
+            if let Some(path) = default_link(&assignment.scope) {
+                touched.files.insert(
+                    path,
+                    (
+                        DriftAction::Overwrite,
+                        format!("default {}", assignment.pack_identifier),
+                    ),
+                );
+            }
+        }
+        if ops.contains(&WizardUpdateOp::PacksRemove) {
+            for selection in &metadata.packs_remove {
+                let cause = format!("packs_remove {}", selection.pack_identifier);
+                touched.packs.insert(
+                    selection.pack_identifier.clone(),
+                    (DriftAction::Delete, cause.clone()),
+                );
+                if let Some(path) = selection.scope.as_ref().and_then(default_link) {
+                    touched.files.insert(path, (DriftAction::Delete, cause));
+                }
+            }
+        }
+        if [
+            WizardUpdateOp::PacksAdd,
+            WizardUpdateOp::ProvidersAdd,
+            WizardUpdateOp::ProvidersRemove,
+        ]
+        .iter()
+        .any(|op| ops.contains(op))
+        {
+            touched.files.insert(
+                "providers/providers.json".to_string(),
+                (DriftAction::Merge, "providers".to_string()),
+            );
+        }
+        if ops.contains(&WizardUpdateOp::TenantsRemove) {
+            for tenant in &metadata.tenants_remove {
+                let (dir, label) = match &tenant.team {
+                    Some(team) => (
+                        format!("tenants/{}/teams/{team}/", tenant.tenant),
+                        format!("{}:{team}", tenant.tenant),
+                    ),
+                    None => (format!("tenants/{}/", tenant.tenant), tenant.tenant.clone()),
+                };
+                touched.dirs.push((dir, format!("tenants_remove {label}")));
+            }
+        }
+        if ops.contains(&WizardUpdateOp::AccessChange) {
+            let rules = metadata
+                .tenants
+                .iter()
+                .map(|tenant| {
+                    let paths = tenant
+                        .allow_paths
+                        .iter()
+                        .filter(|path| !path.trim().is_empty())
+                        .map(|path| (path.clone(), Policy::Public))
+                        .collect::<Vec<_>>();
+                    (tenant.tenant.clone(), tenant.team.clone(), paths)
+                })
+                .chain(metadata.access_changes.iter().map(|change| {
+                    (
+                        change.tenant_id.clone(),
+                        change.team_id.clone(),
+                        vec![(change.pack_id.clone(), change.operation.policy())],
+                    )
+                }));
+            for (tenant, team, paths) in rules {
+                let label = match &team {
+                    Some(team) => format!("{tenant}:{team}"),
+                    None => tenant.clone(),
+                };
+                let (gmap, manifest) = match &team {
+                    Some(team) => (
+                        format!("tenants/{tenant}/teams/{team}/team.gmap"),
+                        format!("resolved/{tenant}.{team}.yaml"),
+                    ),
+                    None => (
+                        format!("tenants/{tenant}/tenant.gmap"),
+                        format!("resolved/{tenant}.yaml"),
+                    ),
+                };
+                touched.files.insert(
+                    manifest,
+                    (DriftAction::Overwrite, format!("republish {label}")),
+                );
+                let entry = touched.gmaps.entry(gmap).or_default();
+                for (path, policy) in paths {
+                    entry.push((path, policy, format!("access_change {label}")));
+                }
+            }
+        }
+        Ok(touched)
+    }
+
+    fn classify(
+        &self,
+        bundle: &Path,
+        path: &str,
+    ) -> anyhow::Result<(DriftAction, Option<String>, Vec<String>)> {
+        if let Some((_, cause)) = self.dirs.iter().find(|(dir, _)| path.starts_with(dir)) {
+            return Ok((DriftAction::Delete, Some(cause.clone()), Vec::new()));
+        }
+        if let Some((action, cause)) = self.files.get(path) {
+            return Ok((*action, Some(cause.clone()), Vec::new()));
+        }
+        if let Some(name) = path.strip_prefix("packs/") {
+            let name = name.split('/').next().unwrap_or(name);
+            if let Some((action, cause)) = self.packs.iter().find_map(|(id, value)| {
+                (name == id || name.starts_with(&format!("{id}."))).then_some(value)
+            }) {
+                return Ok((*action, Some(cause.clone()), Vec::new()));
+            }
+        }
+        let Some(upserts) = self.gmaps.get(path).filter(|upserts| !upserts.is_empty()) else {
+            return Ok((DriftAction::Keep, None, Vec::new()));
+        };
+        let file = bundle.join(path);
+        let existing = if file.is_file() {
+            gmap::parse_file(&file)?
+        } else {
+            Vec::new()
+        };
+        let mut rules = Vec::new();
+        for (rule_path, policy, _) in upserts {
+            let target = gmap::parse_path(rule_path, 0)?;
+            if let Some(rule) = existing
+                .iter()
+                .find(|rule| rule.path == target && rule.policy != *policy)
+            {
+                rules.push(format!(
+                    "{} = {} (line {}) -> {}",
+                    rule.path,
+                    policy_label(&rule.policy),
+                    rule.line,
+                    policy_label(policy)
+                ));
+            }
+        }
+        let action = if rules.is_empty() {
+            DriftAction::Merge
+        } else {
+            DriftAction::Overwrite
+        };
+        Ok((action, Some(upserts[0].2.clone()), rules))
+    }
+}
+
+fn default_link(scope: &PackScope) -> Option<String> {
+    match scope {
+        PackScope::Bundle => None,
+        PackScope::Global => Some("default.gtpack".to_string()),
+        PackScope::Tenant { tenant_id } => Some(format!("tenants/{tenant_id}/default.gtpack")),
+        PackScope::Team { tenant_id, team_id } => Some(format!(
+            "tenants/{tenant_id}/teams/{team_id}/default.gtpack"
+        )),
+    }
+}
+
+fn policy_label(policy: &Policy) -> &'static str {
+    match policy {
+        Policy::Public => "public",
+        Policy::Forbidden => "forbidden",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::fs;
+
+    use super::*;
+    use crate::wizard::{
+        AccessChangeSelection, AccessOperation, WizardCreateRequest, apply_update,
+    };
+
+    #[test]
+    fn flags_manual_edits_the_update_would_clobber() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path();
+        let gmap = bundle.join("tenants/acme/tenant.gmap");
+        fs::create_dir_all(gmap.parent().unwrap()).unwrap();
+        fs::create_dir_all(bundle.join("packs")).unwrap();
+        fs::write(&gmap, "support = public\n").unwrap();
+        fs::write(bundle.join("packs/support.gtpack"), "v1").unwrap();
+        fs::write(bundle.join("packs/kb.gtpack"), "v1").unwrap();
+        record_baseline(bundle).unwrap();
+
+        fs::write(&gmap, "support = public\nsupport/admin = public\n").unwrap();
+        fs::write(bundle.join("packs/kb.gtpack"), "patched").unwrap();
+        fs::write(bundle.join("packs/notes.txt"), "mine").unwrap();
+
+        let request = WizardCreateRequest {
+            bundle: bundle.to_path_buf(),
+            bundle_name: None,
+            pack_refs: Vec::new(),
+            tenants: Vec::new(),
+            default_assignments: Vec::new(),
+            providers: Vec::new(),
+            update_ops: BTreeSet::new(),
+            remove_targets: BTreeSet::new(),
+            packs_remove: Vec::new(),
+            providers_remove: Vec::new(),
+            tenants_remove: Vec::new(),
+            access_changes: vec![AccessChangeSelection {
+                pack_id: "support/admin".to_string(),
+                operation: AccessOperation::AllowRemove,
+                tenant_id: "acme".to_string(),
+                team_id: None,
+            }],
+        };
+        let mut plan = apply_update(&request, true).unwrap();
+        plan.drift = scan(bundle, &plan.metadata).unwrap();
+        let by_path = plan
+            .drift
+            .iter()
+            .map(|item| (item.path.as_str(), item))
+            .collect::<BTreeMap<_, _>>();
+        assert_eq!(by_path.len(), 3);
+
+        let gmap_drift = by_path["tenants/acme/tenant.gmap"];
+        assert_eq!(gmap_drift.action, DriftAction::Overwrite);
+        assert_eq!(
+            gmap_drift.rules,
+            ["support/admin = public (line 2) -> forbidden"]
+        );
+        assert_eq!(by_path["packs/kb.gtpack"].action, DriftAction::Keep);
+        assert_eq!(by_path["packs/notes.txt"].change, DriftChange::Added);
+
+        add_reconcile_step(&mut plan);
+        assert_eq!(plan.steps[1].kind, WizardStepKind::ReconcileDrift);
+        assert_eq!(plan.steps[1].details["overwritten"], "1");
+
+        record_baseline(bundle).unwrap();
+        assert!(scan(bundle, &plan.metadata).unwrap().is_empty());
+    }
+}
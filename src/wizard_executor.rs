@@ -1,9 +1,20 @@
+use crate::provider_registry::RegistryMirror;
 use crate::wizard::{WizardExecutionReport, WizardMode, WizardPlan};
 
+/// Records the bundle as the drift baseline for later update plans once the plan
+/// has run.
 pub fn execute(
     mode: WizardMode,
     plan: &WizardPlan,
     offline: bool,
+    mirror: Option<&RegistryMirror>,
+    best_effort: bool,
 ) -> anyhow::Result<WizardExecutionReport> {
-    crate::wizard::execute_plan(mode, plan, offline)
+    let mut report = crate::wizard::execute_plan(mode, plan, offline, mirror, best_effort)?;
+    if let Err(err) = crate::wizard_drift::record_baseline(&plan.bundle) {
+        report
+            .warnings
+            .push(format!("wizard drift baseline not recorded: {err:#}"));
+    }
+    Ok(report)
 }
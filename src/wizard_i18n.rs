@@ -6,17 +6,39 @@ static WIZARD_I18N: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/i18n/operator_wi
 
 pub fn load(locale: &str) -> anyhow::Result<ResolvedI18nMap> {
     for candidate in locale_candidates(locale) {
-        if let Some(file) = WIZARD_I18N.get_file(&candidate) {
-            let raw = file.contents_utf8().ok_or_else(|| {
-                anyhow::anyhow!("wizard i18n file is not valid UTF-8: {candidate}")
-            })?;
-            return serde_json::from_str(raw)
-                .with_context(|| format!("parse embedded wizard i18n map {candidate}"));
+        if let Some(map) = read_embedded(&candidate)? {
+            return Ok(map);
         }
     }
     Ok(ResolvedI18nMap::new())
 }
 
+/// The map `load` picks for `locale` before falling back to English; empty when the
+/// wizard has no file for the locale.
+pub fn load_translated(locale: &str) -> anyhow::Result<ResolvedI18nMap> {
+    for candidate in locale_candidates(locale) {
+        if candidate == "en-GB.json" || candidate == "en.json" {
+            break;
+        }
+        if let Some(map) = read_embedded(&candidate)? {
+            return Ok(map);
+        }
+    }
+    Ok(ResolvedI18nMap::new())
+}
+
+fn read_embedded(candidate: &str) -> anyhow::Result<Option<ResolvedI18nMap>> {
+    let Some(file) = WIZARD_I18N.get_file(candidate) else {
+        return Ok(None);
+    };
+    let raw = file
+        .contents_utf8()
+        .ok_or_else(|| anyhow::anyhow!("wizard i18n file is not valid UTF-8: {candidate}"))?;
+    serde_json::from_str(raw)
+        .map(Some)
+        .with_context(|| format!("parse embedded wizard i18n map {candidate}"))
+}
+
 fn locale_candidates(locale: &str) -> Vec<String> {
     let mut out = Vec::new();
     let mut push_candidate = |candidate: String| {
@@ -96,6 +118,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn translated_map_excludes_english_fallback() {
+        assert!(load_translated("zz-ZZ").expect("load zz-ZZ").is_empty());
+        assert_eq!(
+            load_translated("de-DE")
+                .expect("load de-DE")
+                .get("wizard.create.bundle_name")
+                .map(String::as_str),
+            Some("Bundle-Name")
+        );
+    }
+
     #[test]
     fn falls_back_to_english_for_unknown_locale() {
         let map = load("zz-ZZ").expect("load fallback");
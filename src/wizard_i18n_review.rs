@@ -0,0 +1,558 @@
+//! Translation review for the wizard (`wizard i18n check|export|import`).
+//!
+//! The wizard's prompts come from two catalogs: question titles from
+//! `i18n/operator_wizard` (keyed by the spec's `title_i18n`) and the `cli.wizard.*`
+//! messages from `i18n/operator_cli`. A key a locale does not translate silently
+//! falls back to English, so `check` lists those, translations whose `{}` placeholder
+//! count differs from English, and spec text that has no key at all. `export` and
+//! `import` move the same entries through a CSV file for translators.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, anyhow};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::operator_i18n;
+use crate::wizard::WizardMode;
+use crate::wizard_i18n;
+use crate::wizard_spec_builder;
+
+const CSV_HEADER: [&str; 4] = ["catalog", "key", "english", "translation"];
+const CLI_PREFIX: &str = "cli.wizard.";
+
+type Catalogs = BTreeMap<Catalog, BTreeMap<String, String>>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Catalog {
+    /// `i18n/operator_wizard`: question titles.
+    Wizard,
+    /// `i18n/operator_cli`: the `cli.wizard.*` messages.
+    Cli,
+    /// Text in the wizard spec that has no translation key.
+    Spec,
+}
+
+impl Catalog {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Catalog::Wizard => "wizard",
+            Catalog::Cli => "cli",
+            Catalog::Spec => "spec",
+        }
+    }
+
+    fn parse(raw: &str) -> Option<Self> {
+        match raw.trim() {
+            "wizard" => Some(Catalog::Wizard),
+            "cli" => Some(Catalog::Cli),
+            _ => None,
+        }
+    }
+
+    fn dir_name(self) -> Option<&'static str> {
+        match self {
+            Catalog::Wizard => Some("operator_wizard"),
+            Catalog::Cli => Some("operator_cli"),
+            Catalog::Spec => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Issue {
+    pub severity: Severity,
+    pub catalog: Catalog,
+    /// Translation key, or the question path (`update.packs_remove.scope`) for spec
+    /// text without one.
+    pub key: String,
+    pub english: String,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Report {
+    pub locale: String,
+    /// Keyed entries compared against English.
+    pub checked: usize,
+    pub issues: Vec<Issue>,
+}
+
+impl Report {
+    pub fn has_errors(&self) -> bool {
+        self.issues
+            .iter()
+            .any(|issue| issue.severity == Severity::Error)
+    }
+}
+
+/// One translatable string and the locale's own translation of it, if any.
+#[derive(Clone, Debug)]
+struct Entry {
+    catalog: Catalog,
+    key: String,
+    english: String,
+    translation: Option<String>,
+}
+
+/// A question or list field of the wizard spec.
+struct SpecText {
+    path: String,
+    title: String,
+    key: Option<String>,
+    choices: Vec<String>,
+}
+
+pub fn check(locale: &str) -> anyhow::Result<Report> {
+    ensure_translatable(locale)?;
+    let entries = entries(locale)?;
+    let mut issues = entries.iter().filter_map(entry_issue).collect::<Vec<_>>();
+    issues.extend(unkeyed_spec_text(&spec_texts()));
+    Ok(Report {
+        locale: locale.to_string(),
+        checked: entries.len(),
+        issues,
+    })
+}
+
+/// CSV rows (header first) for `locale`: the entries `check` flags, or every keyed
+/// entry with `all`. The translation column holds the current translation.
+pub fn export_rows(locale: &str, all: bool) -> anyhow::Result<Vec<Vec<String>>> {
+    ensure_translatable(locale)?;
+    let header = CSV_HEADER.iter().map(|field| field.to_string()).collect();
+    let rows = entries(locale)?
+        .into_iter()
+        .filter(|entry| all || entry_issue(entry).is_some())
+        .map(|entry| {
+            vec![
+                entry.catalog.as_str().to_string(),
+                entry.key,
+                entry.english,
+                entry.translation.unwrap_or_default(),
+            ]
+        });
+    Ok(std::iter::once(header).chain(rows).collect())
+}
+
+/// Merges the translated rows of `csv` into `<i18n_dir>/operator_wizard/<locale>.json`
+/// and `<i18n_dir>/operator_cli/<locale>.json`. Rows with an empty translation are
+/// skipped; an unknown key or a placeholder mismatch rejects the whole file before
+/// anything is written. Returns each file written with the number of keys merged.
+pub fn import_csv(
+    locale: &str,
+    csv: &str,
+    i18n_dir: &Path,
+) -> anyhow::Result<Vec<(PathBuf, usize)>> {
+    ensure_translatable(locale)?;
+    let updates = parse_import(csv, &english_catalogs()?)?;
+    write_updates(locale, &updates, i18n_dir)
+}
+
+fn ensure_translatable(locale: &str) -> anyhow::Result<()> {
+    let locale = locale.trim();
+    if locale.is_empty()
+        || !locale
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(anyhow!("invalid locale {locale:?}"));
+    }
+    let language = locale.split(['-', '_']).next().unwrap_or(locale);
+    if language.eq_ignore_ascii_case("en") {
+        return Err(anyhow!(
+            "{locale} is the source language; pass the locale to review"
+        ));
+    }
+    Ok(())
+}
+
+fn english_catalogs() -> anyhow::Result<Catalogs> {
+    let mut wizard = wizard_i18n::load("en")?
+        .into_iter()
+        .collect::<BTreeMap<String, String>>();
+    for text in spec_texts() {
+        if let Some(key) = text.key {
+            wizard.entry(key).or_insert(text.title);
+        }
+    }
+    let cli = operator_i18n::load_cli("en")?
+        .into_iter()
+        .filter(|(key, _)| key.starts_with(CLI_PREFIX))
+        .collect();
+    Ok(Catalogs::from([
+        (Catalog::Wizard, wizard),
+        (Catalog::Cli, cli),
+    ]))
+}
+
+fn entries(locale: &str) -> anyhow::Result<Vec<Entry>> {
+    let translated = Catalogs::from([
+        (
+            Catalog::Wizard,
+            wizard_i18n::load_translated(locale)?.into_iter().collect(),
+        ),
+        (Catalog::Cli, operator_i18n::load_cli_translated(locale)?),
+    ]);
+    Ok(collect_entries(&english_catalogs()?, &translated))
+}
+
+fn collect_entries(english: &Catalogs, translated: &Catalogs) -> Vec<Entry> {
+    english
+        .iter()
+        .flat_map(|(catalog, keys)| {
+            keys.iter().map(|(key, source)| Entry {
+                catalog: *catalog,
+                key: key.clone(),
+                english: source.clone(),
+                translation: translated
+                    .get(catalog)
+                    .and_then(|map| map.get(key))
+                    .filter(|value| !value.trim().is_empty())
+                    .cloned(),
+            })
+        })
+        .collect()
+}
+
+fn entry_issue(entry: &Entry) -> Option<Issue> {
+    let (severity, message) = match &entry.translation {
+        None => (
+            Severity::Error,
+            "not translated; falls back to English".to_string(),
+        ),
+        Some(translation) => {
+            let expected = placeholders(&entry.english);
+            let found = placeholders(translation);
+            if expected != found {
+                (
+                    Severity::Error,
+                    format!("has {found} {{}} placeholder(s), English has {expected}"),
+                )
+            } else if translation == &entry.english {
+                (Severity::Warning, "same as English".to_string())
+            } else {
+                return None;
+            }
+        }
+    };
+    Some(Issue {
+        severity,
+        catalog: entry.catalog,
+        key: entry.key.clone(),
+        english: entry.english.clone(),
+        message,
+    })
+}
+
+fn placeholders(text: &str) -> usize {
+    text.matches("{}").count()
+}
+
+fn spec_texts() -> Vec<SpecText> {
+    let mut out = Vec::new();
+    for mode in [WizardMode::Create, WizardMode::Update, WizardMode::Remove] {
+        let form = wizard_spec_builder::build_validation_form(mode);
+        collect_questions(form.get("questions"), mode.as_str(), &mut out);
+    }
+    out
+}
+
+fn collect_questions(questions: Option<&Value>, prefix: &str, out: &mut Vec<SpecText>) {
+    for question in questions.and_then(Value::as_array).into_iter().flatten() {
+        let id = question.get("id").and_then(Value::as_str).unwrap_or("?");
+        let path = format!("{prefix}.{id}");
+        out.push(SpecText {
+            path: path.clone(),
+            title: question
+                .get("title")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            key: question
+                .pointer("/title_i18n/key")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            choices: question
+                .get("choices")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+        });
+        collect_questions(question.pointer("/list/fields"), &path, out);
+    }
+}
+
+fn unkeyed_spec_text(texts: &[SpecText]) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    for text in texts {
+        if text.key.is_none() && !text.title.is_empty() {
+            issues.push(Issue {
+                severity: Severity::Warning,
+                catalog: Catalog::Spec,
+                key: text.path.clone(),
+                english: text.title.clone(),
+                message: "title has no i18n key; always shown in English".to_string(),
+            });
+        }
+        if !text.choices.is_empty() {
+            issues.push(Issue {
+                severity: Severity::Warning,
+                catalog: Catalog::Spec,
+                key: format!("{}.choices", text.path),
+                english: text.choices.join(", "),
+                message: "choices have no i18n keys; always shown in English".to_string(),
+            });
+        }
+    }
+    issues
+}
+
+fn parse_import(csv: &str, english: &Catalogs) -> anyhow::Result<Catalogs> {
+    let mut rows = parse_csv(csv)?.into_iter();
+    let header = rows.next().ok_or_else(|| anyhow!("CSV is empty"))?;
+    if header != CSV_HEADER {
+        return Err(anyhow!(
+            "expected CSV header {}, found {}",
+            CSV_HEADER.join(","),
+            header.join(",")
+        ));
+    }
+    let mut updates = Catalogs::new();
+    let mut problems = Vec::new();
+    for (index, row) in rows.enumerate() {
+        let number = index + 1;
+        let [catalog, key, _, translation] = row.as_slice() else {
+            problems.push(format!(
+                "row {number}: expected 4 columns, found {}",
+                row.len()
+            ));
+            continue;
+        };
+        if translation.trim().is_empty() {
+            continue;
+        }
+        let Some(catalog) = Catalog::parse(catalog) else {
+            problems.push(format!("row {number}: unknown catalog {catalog:?}"));
+            continue;
+        };
+        let Some(source) = english.get(&catalog).and_then(|keys| keys.get(key)) else {
+            problems.push(format!(
+                "row {number}: {key} is not a {} key",
+                catalog.as_str()
+            ));
+            continue;
+        };
+        let (expected, found) = (placeholders(source), placeholders(translation));
+        if expected != found {
+            problems.push(format!(
+                "row {number}: {key} has {found} {{}} placeholder(s), English has {expected}"
+            ));
+            continue;
+        }
+        updates
+            .entry(catalog)
+            .or_default()
+            .insert(key.clone(), translation.clone());
+    }
+    if !problems.is_empty() {
+        return Err(anyhow!(
+            "{} row(s) rejected, nothing written:\n  {}",
+            problems.len(),
+            problems.join("\n  ")
+        ));
+    }
+    Ok(updates)
+}
+
+fn write_updates(
+    locale: &str,
+    updates: &Catalogs,
+    i18n_dir: &Path,
+) -> anyhow::Result<Vec<(PathBuf, usize)>> {
+    let mut written = Vec::new();
+    for (catalog, entries) in updates {
+        let Some(dir_name) = catalog.dir_name() else {
+            continue;
+        };
+        let dir = i18n_dir.join(dir_name);
+        let path = dir.join(format!("{}.json", locale.trim()));
+        let mut map = if path.exists() {
+            let raw = std::fs::read_to_string(&path)
+                .with_context(|| format!("read {}", path.display()))?;
+            serde_json::from_str::<BTreeMap<String, String>>(&raw)
+                .with_context(|| format!("parse {}", path.display()))?
+        } else {
+            BTreeMap::new()
+        };
+        map.extend(entries.clone());
+        std::fs::create_dir_all(&dir).with_context(|| format!("create {}", dir.display()))?;
+        let mut raw = serde_json::to_string_pretty(&map)?;
+        raw.push('\n');
+        std::fs::write(&path, raw).with_context(|| format!("write {}", path.display()))?;
+        written.push((path, entries.len()));
+    }
+    Ok(written)
+}
+
+pub fn write_csv(rows: &[Vec<String>]) -> String {
+    let mut out = String::new();
+    for row in rows {
+        let fields = row.iter().map(|field| csv_field(field)).collect::<Vec<_>>();
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// RFC 4180 records: quoted fields may hold commas, newlines and `""` escapes.
+/// Blank lines and a leading byte-order mark (added by some spreadsheets) are ignored.
+fn parse_csv(raw: &str) -> anyhow::Result<Vec<Vec<String>>> {
+    let raw = raw.strip_prefix('\u{feff}').unwrap_or(raw);
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = raw.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(anyhow!("CSV ends inside a quoted field"));
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows.retain(|row| !(row.len() == 1 && row[0].is_empty()));
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalogs(wizard: &[(&str, &str)], cli: &[(&str, &str)]) -> Catalogs {
+        let map = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect()
+        };
+        Catalogs::from([(Catalog::Wizard, map(wizard)), (Catalog::Cli, map(cli))])
+    }
+
+    #[test]
+    fn csv_round_trips_quotes_commas_and_newlines() -> anyhow::Result<()> {
+        let rows = vec![
+            vec!["cli".to_string(), "cli.wizard.a".to_string()],
+            vec!["say \"hi\", {}".to_string(), "two\nlines".to_string()],
+        ];
+        let csv = write_csv(&rows);
+        assert_eq!(parse_csv(&csv)?, rows);
+        assert_eq!(
+            parse_csv(&format!("\u{feff}{}\r\n\r\n", csv.trim_end()))?,
+            rows
+        );
+        assert!(parse_csv("a,\"open").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn reports_missing_and_mismatched_translations() {
+        let english = catalogs(
+            &[("wizard.a", "Bundle path"), ("wizard.b", "Locale")],
+            &[("cli.wizard.c", "Wrote {} to {}")],
+        );
+        let translated = catalogs(
+            &[("wizard.b", "Locale")],
+            &[("cli.wizard.c", "{} geschreven")],
+        );
+        let issues = collect_entries(&english, &translated)
+            .iter()
+            .filter_map(entry_issue)
+            .map(|issue| (issue.key, issue.severity))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            issues,
+            vec![
+                ("wizard.a".to_string(), Severity::Error),
+                ("wizard.b".to_string(), Severity::Warning),
+                ("cli.wizard.c".to_string(), Severity::Error),
+            ]
+        );
+
+        let unkeyed = unkeyed_spec_text(&spec_texts());
+        assert!(
+            unkeyed
+                .iter()
+                .any(|issue| issue.key == "update.packs_remove.scope.choices")
+        );
+    }
+
+    #[test]
+    fn import_rejects_bad_rows_and_merges_good_ones() -> anyhow::Result<()> {
+        let english = catalogs(
+            &[("wizard.a", "Bundle path")],
+            &[("cli.wizard.c", "Wrote {}")],
+        );
+        let bad = "catalog,key,english,translation\ncli,cli.wizard.c,\"Wrote {}\",Geschreven\nwizard,wizard.z,,Z\n";
+        let err = parse_import(bad, &english).unwrap_err().to_string();
+        assert!(err.contains("2 row(s) rejected"), "{err}");
+
+        let dir = tempfile::tempdir()?;
+        let existing = dir.path().join("operator_wizard/nl.json");
+        std::fs::create_dir_all(existing.parent().unwrap())?;
+        std::fs::write(&existing, "{\"wizard.old\": \"Oud\"}")?;
+        let good = "catalog,key,english,translation\nwizard,wizard.a,Bundle path,Bundelpad\ncli,cli.wizard.c,\"Wrote {}\",\n";
+        let written = write_updates("nl", &parse_import(good, &english)?, dir.path())?;
+        assert_eq!(written, vec![(existing.clone(), 1)]);
+        let merged: BTreeMap<String, String> =
+            serde_json::from_str(&std::fs::read_to_string(&existing)?)?;
+        assert_eq!(
+            merged.get("wizard.a").map(String::as_str),
+            Some("Bundelpad")
+        );
+        assert_eq!(merged.get("wizard.old").map(String::as_str), Some("Oud"));
+        Ok(())
+    }
+}
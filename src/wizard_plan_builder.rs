@@ -1,10 +1,16 @@
 use crate::wizard::{WizardCreateRequest, WizardMode, WizardPlan};
 
+/// Update plans also scan the bundle for manual edits they would overwrite.
 pub fn build_plan(
     mode: WizardMode,
     request: &WizardCreateRequest,
     dry_run: bool,
 ) -> anyhow::Result<WizardPlan> {
     let normalized = crate::wizard::normalize_request_for_plan(request)?;
-    crate::wizard::apply(mode, &normalized, dry_run)
+    let mut plan = crate::wizard::apply(mode, &normalized, dry_run)?;
+    if mode == WizardMode::Update {
+        plan.drift = crate::wizard_drift::scan(&plan.bundle, &plan.metadata)?;
+        crate::wizard_drift::add_reconcile_step(&mut plan);
+    }
+    Ok(plan)
 }
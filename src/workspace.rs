@@ -0,0 +1,362 @@
+//! Multi-bundle workspaces: run several demo bundles side by side.
+//!
+//! A `workspace.yaml` names bundles and, optionally, their ports. `workspace start`
+//! gives every bundle a gateway port and a NATS port that no other member (or
+//! another process) is using, then runs `demo start` for it in the background with
+//! `GREENTIC_OPERATOR_GATEWAY_PORT` / `GREENTIC_OPERATOR_NATS_PORT` set. Pids, logs,
+//! and the ports in use live under `.greentic-workspace/` next to the file.
+//!
+//! ```yaml
+//! ports:
+//!   gateway_base: 8080
+//!   nats_base: 4222
+//! bundles:
+//!   acme:
+//!     path: bundles/acme
+//!     tenant: acme
+//!   globex:
+//!     path: bundles/globex
+//!     gateway_port: 9090
+//!     args: ["--nats", "jetstream", "--cloudflared", "off"]
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, anyhow};
+use serde::{Deserialize, Serialize};
+
+use crate::config::ENV_GATEWAY_PORT;
+use crate::demo::ports::port_is_free;
+use crate::runtime_state::{read_json, write_json};
+use crate::services::{self, ENV_NATS_PORT, ProcessStatus, ServiceState};
+
+pub const WORKSPACE_FILE: &str = "workspace.yaml";
+const STATE_DIR: &str = ".greentic-workspace";
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct WorkspaceFile {
+    #[serde(default)]
+    pub ports: WorkspacePorts,
+    #[serde(default)]
+    pub bundles: BTreeMap<String, WorkspaceBundle>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct WorkspacePorts {
+    #[serde(default = "default_gateway_base")]
+    pub gateway_base: u16,
+    #[serde(default = "default_nats_base")]
+    pub nats_base: u16,
+}
+
+impl Default for WorkspacePorts {
+    fn default() -> Self {
+        Self {
+            gateway_base: default_gateway_base(),
+            nats_base: default_nats_base(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct WorkspaceBundle {
+    /// Relative to the directory holding workspace.yaml.
+    pub path: PathBuf,
+    #[serde(default)]
+    pub tenant: Option<String>,
+    #[serde(default)]
+    pub team: Option<String>,
+    #[serde(default)]
+    pub gateway_port: Option<u16>,
+    #[serde(default)]
+    pub nats_port: Option<u16>,
+    /// Extra `demo start` arguments.
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortAssignment {
+    pub gateway_port: u16,
+    pub nats_port: u16,
+}
+
+pub struct Workspace {
+    pub root: PathBuf,
+    pub file: WorkspaceFile,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MemberStatus {
+    pub name: String,
+    pub bundle: PathBuf,
+    pub running: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ports: Option<PortAssignment>,
+    pub log: PathBuf,
+}
+
+impl Workspace {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let file: WorkspaceFile = serde_yaml_bw::from_str(&contents)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+        let root = path
+            .parent()
+            .map(Path::to_path_buf)
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or_else(|| PathBuf::from("."));
+        // Members run with the workspace as cwd, so keep bundle paths absolute.
+        let root = root.canonicalize().unwrap_or(root);
+        Ok(Self { root, file })
+    }
+
+    pub fn bundle_path(&self, bundle: &WorkspaceBundle) -> PathBuf {
+        if bundle.path.is_absolute() {
+            bundle.path.clone()
+        } else {
+            self.root.join(&bundle.path)
+        }
+    }
+
+    fn state_root(&self) -> PathBuf {
+        self.root.join(STATE_DIR)
+    }
+
+    fn ports_path(&self) -> PathBuf {
+        self.state_root().join("state").join("ports.json")
+    }
+
+    pub fn log_path(&self, name: &str) -> PathBuf {
+        services::log_path(&self.state_root(), name)
+    }
+
+    fn pid_path(&self, name: &str) -> PathBuf {
+        services::pid_path(&self.state_root(), name)
+    }
+
+    fn is_running(&self, name: &str) -> anyhow::Result<bool> {
+        Ok(services::process_status(&self.pid_path(name))? == ProcessStatus::Running)
+    }
+
+    fn read_ports(&self) -> anyhow::Result<BTreeMap<String, PortAssignment>> {
+        Ok(read_json(&self.ports_path())?.unwrap_or_default())
+    }
+
+    /// Selected members (all when `names` is empty), rejecting unknown names.
+    fn select<'a>(
+        &'a self,
+        names: &[String],
+    ) -> anyhow::Result<Vec<(&'a str, &'a WorkspaceBundle)>> {
+        for name in names {
+            if !self.file.bundles.contains_key(name) {
+                return Err(anyhow!("workspace has no bundle named {name}"));
+            }
+        }
+        Ok(self
+            .file
+            .bundles
+            .iter()
+            .filter(|(name, _)| names.is_empty() || names.contains(name))
+            .map(|(name, bundle)| (name.as_str(), bundle))
+            .collect())
+    }
+
+    pub fn status(&self) -> anyhow::Result<Vec<MemberStatus>> {
+        let ports = self.read_ports()?;
+        self.file
+            .bundles
+            .iter()
+            .map(|(name, bundle)| {
+                let running = self.is_running(name)?;
+                Ok(MemberStatus {
+                    name: name.clone(),
+                    bundle: self.bundle_path(bundle),
+                    running,
+                    ports: if running {
+                        ports.get(name).copied()
+                    } else {
+                        None
+                    },
+                    log: self.log_path(name),
+                })
+            })
+            .collect()
+    }
+
+    /// Starts the selected members with `exe demo start`, returning their ports.
+    pub fn start(
+        &self,
+        exe: &Path,
+        names: &[String],
+    ) -> anyhow::Result<Vec<(String, ServiceState, PortAssignment)>> {
+        let selected = self.select(names)?;
+        let mut recorded = self.read_ports()?;
+        let mut running = BTreeMap::new();
+        for name in self.file.bundles.keys() {
+            if self.is_running(name)?
+                && let Some(ports) = recorded.get(name)
+            {
+                running.insert(name.clone(), *ports);
+            }
+        }
+        let assigned = allocate(&self.file, &running, |port| port_is_free("127.0.0.1", port))?;
+        let mut started = Vec::new();
+        for (name, bundle) in selected {
+            let ports = assigned[name];
+            let mut args = vec![
+                "demo".to_string(),
+                "start".to_string(),
+                "--bundle".to_string(),
+                self.bundle_path(bundle).display().to_string(),
+            ];
+            if let Some(tenant) = &bundle.tenant {
+                args.extend(["--tenant".to_string(), tenant.clone()]);
+            }
+            if let Some(team) = &bundle.team {
+                args.extend(["--team".to_string(), team.clone()]);
+            }
+            args.extend(bundle.args.iter().cloned());
+            let envs = [
+                (ENV_GATEWAY_PORT, ports.gateway_port.to_string()),
+                (ENV_NATS_PORT, ports.nats_port.to_string()),
+            ];
+            let state = services::start_process(
+                &exe.display().to_string(),
+                &args,
+                &envs,
+                &self.pid_path(name),
+                &self.log_path(name),
+                Some(&self.root),
+            )?;
+            if state == ServiceState::Started {
+                recorded.insert(name.to_string(), ports);
+            }
+            started.push((
+                name.to_string(),
+                state,
+                recorded.get(name).copied().unwrap_or(ports),
+            ));
+        }
+        write_json(&self.ports_path(), &recorded)?;
+        Ok(started)
+    }
+
+    pub fn stop(&self, names: &[String]) -> anyhow::Result<Vec<(String, ServiceState)>> {
+        let mut recorded = self.read_ports()?;
+        let mut stopped = Vec::new();
+        for (name, _) in self.select(names)? {
+            let state = services::stop_process(&self.pid_path(name))?;
+            recorded.remove(name);
+            stopped.push((name.to_string(), state));
+        }
+        write_json(&self.ports_path(), &recorded)?;
+        Ok(stopped)
+    }
+}
+
+/// Gives every bundle a gateway and NATS port. Running members keep theirs, explicit
+/// ports are honored, and the rest take the next port from the bases that is neither
+/// claimed by another member nor reported busy by `is_free`.
+pub fn allocate(
+    file: &WorkspaceFile,
+    running: &BTreeMap<String, PortAssignment>,
+    is_free: impl Fn(u16) -> bool,
+) -> anyhow::Result<BTreeMap<String, PortAssignment>> {
+    let mut taken = BTreeSet::new();
+    let mut claim = |port: u16, owner: &str| {
+        if taken.insert(port) {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "port {port} of bundle {owner} is used twice in the workspace"
+            ))
+        }
+    };
+    for (name, ports) in running {
+        claim(ports.gateway_port, name)?;
+        claim(ports.nats_port, name)?;
+    }
+    for (name, bundle) in &file.bundles {
+        if running.contains_key(name) {
+            continue;
+        }
+        for port in [bundle.gateway_port, bundle.nats_port]
+            .into_iter()
+            .flatten()
+        {
+            claim(port, name)?;
+        }
+    }
+    let next = |base: u16, taken: &mut BTreeSet<u16>| -> anyhow::Result<u16> {
+        let port = (base..=u16::MAX)
+            .find(|port| !taken.contains(port) && is_free(*port))
+            .ok_or_else(|| anyhow!("no free port at or above {base}"))?;
+        taken.insert(port);
+        Ok(port)
+    };
+    let mut assigned = BTreeMap::new();
+    for (name, bundle) in &file.bundles {
+        let ports = match running.get(name) {
+            Some(ports) => *ports,
+            None => PortAssignment {
+                gateway_port: match bundle.gateway_port {
+                    Some(port) => port,
+                    None => next(file.ports.gateway_base, &mut taken)?,
+                },
+                nats_port: match bundle.nats_port {
+                    Some(port) => port,
+                    None => next(file.ports.nats_base, &mut taken)?,
+                },
+            },
+        };
+        assigned.insert(name.clone(), ports);
+    }
+    Ok(assigned)
+}
+
+fn default_gateway_base() -> u16 {
+    8080
+}
+
+fn default_nats_base() -> u16 {
+    4222
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace() -> WorkspaceFile {
+        serde_yaml_bw::from_str(
+            "bundles:\n  acme:\n    path: acme\n  globex:\n    path: globex\n    gateway_port: 8081\n  initech:\n    path: initech\n",
+        )
+        .expect("workspace yaml")
+    }
+
+    #[test]
+    fn ports_skip_explicit_running_and_busy_ports() {
+        let running = BTreeMap::from([(
+            "initech".to_string(),
+            PortAssignment {
+                gateway_port: 8080,
+                nats_port: 4300,
+            },
+        )]);
+        let ports = allocate(&workspace(), &running, |port| port != 8082).expect("allocate");
+        assert_eq!(ports["initech"].gateway_port, 8080);
+        assert_eq!(ports["globex"].gateway_port, 8081);
+        assert_eq!(ports["acme"].gateway_port, 8083);
+        assert_eq!(ports["acme"].nats_port, 4222);
+        assert_eq!(ports["globex"].nats_port, 4223);
+    }
+
+    #[test]
+    fn duplicate_explicit_ports_are_rejected() {
+        let mut file = workspace();
+        file.bundles.get_mut("acme").unwrap().gateway_port = Some(8081);
+        assert!(allocate(&file, &BTreeMap::new(), |_| true).is_err());
+    }
+}
@@ -77,3 +77,34 @@ fn top_level_help_is_localized_with_cli_locale() {
         "expected localized options header, got: {stdout}"
     );
 }
+
+#[test]
+fn demo_help_is_rendered_by_clap_with_localized_strings() {
+    let output = Command::new(operator_bin())
+        .arg("--locale")
+        .arg("de-DE")
+        .arg("demo")
+        .arg("--help")
+        .output()
+        .expect("run greentic-operator demo --help");
+
+    assert!(
+        output.status.success(),
+        "help should exit successfully, stderr={}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("list-packs")
+            && stdout.contains("Aufgelöste Packs aus einem Bundle auflisten"),
+        "expected localized subcommand about, got: {stdout}"
+    );
+    assert!(
+        stdout.contains("setup-wizard"),
+        "expected every demo subcommand to be listed, got: {stdout}"
+    );
+    assert!(
+        stdout.contains("Verwendung: greentic-operator demo"),
+        "expected localized usage label, got: {stdout}"
+    );
+}
@@ -13,6 +13,7 @@ fn cloudflared_discovers_public_url() {
         local_port: 8080,
         extra_args: Vec::new(),
         restart: true,
+        tunnel: None,
     };
     let paths = RuntimePaths::new(temp.path(), "demo", "default");
     let log_path = temp.path().join("logs").join("cloudflared.log");
@@ -64,11 +64,11 @@ fn demo_build_writes_bundle_with_relative_paths() {
     assert!(
         packs
             .iter()
-            .any(|item| item.as_str() == Some("packs/pack1"))
+            .any(|item| item.get("path").and_then(Value::as_str) == Some("packs/pack1"))
     );
     assert!(
         packs
             .iter()
-            .any(|item| item.as_str() == Some("packs/pack2.gtpack"))
+            .any(|item| item.get("path").and_then(Value::as_str) == Some("packs/pack2.gtpack"))
     );
 }
@@ -1,11 +1,20 @@
+#[path = "support/blocking_pack.rs"]
+mod blocking_pack;
+
 use std::{fs, path::Path};
 
 use anyhow::Result;
-use serde_json::json;
+use serde_json::{Value, json};
 use tempfile::{NamedTempFile, tempdir};
 
+use blocking_pack::create_blocking_pack;
 use greentic_operator::demo::input as demo_input;
+use greentic_operator::demo::input_schema as demo_input_schema;
 use greentic_operator::demo::pack_resolve;
+use greentic_operator::demo::run_report::RunReport;
+use greentic_operator::demo::{DemoBlockedOn, DemoRunner, UserEvent};
+use greentic_operator::operator_error::{self, OperatorError};
+use greentic_runner_host::secrets::default_manager;
 
 #[test]
 fn resolve_pack_uses_entry_flows_as_default() -> Result<()> {
@@ -40,6 +49,50 @@ fn resolve_pack_default_flow_missing_mentions_field() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn flow_input_schema_is_read_from_the_manifest() -> Result<()> {
+    let root = tempdir()?;
+    let pack_dir = root.path().join("demo-pack");
+    fs::create_dir_all(&pack_dir)?;
+    let manifest = json!({
+        "schema_version": "greentic.pack-v1",
+        "pack_id": "demo-pack-id",
+        "meta": { "pack_id": "demo-pack-id", "entry_flows": ["send", "setup"] },
+        "flows": [
+            {
+                "id": "send",
+                "entrypoints": ["default"],
+                "input_schema": {
+                    "type": "object",
+                    "required": ["text"],
+                    "properties": { "text": { "type": "string" } }
+                }
+            },
+            { "id": "setup" }
+        ],
+    });
+    fs::write(
+        pack_dir.join("manifest.cbor"),
+        serde_cbor::to_vec(&manifest)?,
+    )?;
+    let pack = pack_resolve::resolve_pack(root.path(), "demo-pack")?;
+
+    let schema = pack.input_schema("send")?.expect("send declares a schema");
+    assert_eq!(pack.input_schema("default")?, Some(schema.clone()));
+    assert_eq!(pack.input_schema("setup")?, None);
+
+    let errors = demo_input_schema::validate(&schema, &json!({ "text": 7 }))?;
+    assert_eq!(
+        errors.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        ["$.text: expected string, found integer"]
+    );
+    assert_eq!(
+        demo_input_schema::example(&schema),
+        json!({ "text": "string" })
+    );
+    Ok(())
+}
+
 #[test]
 fn parse_input_json_yaml_and_file() -> Result<()> {
     let inline_json = r#"{"foo": "bar"}"#;
@@ -71,6 +124,125 @@ fn parse_input_json_yaml_and_file() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn no_repl_report_for_a_waiting_flow_exits_with_flow_waiting() -> Result<()> {
+    let root = tempdir()?;
+    let pack_path = create_blocking_pack(root.path(), "demo-blocking")?;
+    let mut runner = DemoRunner::new(
+        pack_path,
+        "demo",
+        Some("default".to_string()),
+        json!({"trigger": "start"}),
+        default_manager()?,
+    )?;
+
+    let report = RunReport::new(runner.run_until_blocked(), "demo-blocking", "demo.wait");
+    let written = write_report(&report, root.path())?;
+    assert_eq!(written["status"], json!("waiting"));
+    assert!(written["reason"].is_string(), "{written}");
+    assert_eq!(written["exit_code"], json!(operator_error::EXIT_WAITING));
+    assert_eq!(report.exit_code(), operator_error::EXIT_WAITING);
+
+    let err = report.into_result().unwrap_err();
+    assert_eq!(
+        operator_error::exit_code(&err),
+        operator_error::EXIT_WAITING
+    );
+    assert_eq!(
+        operator_error::find(&err).map(OperatorError::code),
+        Some("flow_waiting")
+    );
+
+    // Answering the wait lets the same flow finish, which exits 0.
+    runner.submit_user_event(UserEvent::raw(json!({"response": "ok"})));
+    let report = RunReport::new(runner.run_until_blocked(), "demo-blocking", "demo.wait");
+    let written = write_report(&report, root.path())?;
+    assert_eq!(written["status"], json!("finished"), "{written}");
+    assert_eq!(written["exit_code"], json!(0));
+    assert_eq!(report.exit_code(), 0);
+    report.into_result()?;
+    Ok(())
+}
+
+#[test]
+fn no_repl_report_for_a_finished_flow_exits_zero() -> Result<()> {
+    let root = tempdir()?;
+    let report = RunReport::new(
+        DemoBlockedOn::Finished(json!({"status": "done"})),
+        "demo-pack",
+        "default",
+    );
+    let written = write_report(&report, root.path())?;
+    assert_eq!(
+        written,
+        json!({
+            "status": "finished",
+            "pack": "demo-pack",
+            "flow": "default",
+            "output": {"status": "done"},
+            "exit_code": 0,
+        })
+    );
+    report.into_result()?;
+    Ok(())
+}
+
+#[test]
+fn no_repl_report_for_a_failed_flow_records_the_error_exit_code() -> Result<()> {
+    let root = tempdir()?;
+    let report = RunReport::new(
+        DemoBlockedOn::Error(anyhow::anyhow!("node render failed")),
+        "demo-pack",
+        "default",
+    );
+    let written = write_report(&report, root.path())?;
+    assert_eq!(written["status"], json!("error"));
+    assert!(
+        written["error"]
+            .as_str()
+            .is_some_and(|error| error.contains("node render failed")),
+        "{written}"
+    );
+    assert_eq!(
+        written["exit_code"],
+        json!(operator_error::EXIT_FLOW_FAILED)
+    );
+    let err = report.into_result().unwrap_err();
+    assert_eq!(
+        operator_error::exit_code(&err),
+        operator_error::EXIT_FLOW_FAILED
+    );
+
+    // A typed cause keeps its own exit code in the file and the process.
+    let report = RunReport::new(
+        DemoBlockedOn::Error(
+            OperatorError::SecretMissing {
+                uri: "secrets://demo/default/demo-pack/token".to_string(),
+            }
+            .into(),
+        ),
+        "demo-pack",
+        "default",
+    );
+    let written = write_report(&report, root.path())?;
+    assert_eq!(
+        written["exit_code"],
+        json!(operator_error::EXIT_SECRET_MISSING)
+    );
+    let err = report.into_result().unwrap_err();
+    assert_eq!(
+        operator_error::exit_code(&err),
+        operator_error::EXIT_SECRET_MISSING
+    );
+    Ok(())
+}
+
+fn write_report(report: &RunReport, dir: &Path) -> Result<Value> {
+    let path = dir.join("result.json");
+    report.write(&path)?;
+    Ok(serde_json::from_slice(&fs::read(&path)?)?)
+}
+
 fn write_manifest(
     pack_dir: &Path,
     pack_id: &str,
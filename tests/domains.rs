@@ -5,6 +5,15 @@ use std::path::Path;
 use greentic_operator::domains::{self, Domain, DomainAction};
 
 fn write_pack(path: &Path, pack_id: &str, entry_flows: &[&str]) -> anyhow::Result<()> {
+    write_pack_with_deps(path, pack_id, entry_flows, &[])
+}
+
+fn write_pack_with_deps(
+    path: &Path,
+    pack_id: &str,
+    entry_flows: &[&str],
+    depends_on: &[&str],
+) -> anyhow::Result<()> {
     let file = File::create(path)?;
     let mut zip = zip::ZipWriter::new(file);
     let options = zip::write::FileOptions::<()>::default();
@@ -13,6 +22,7 @@ fn write_pack(path: &Path, pack_id: &str, entry_flows: &[&str]) -> anyhow::Resul
         "meta": {
             "pack_id": pack_id,
             "entry_flows": entry_flows,
+            "depends_on": depends_on,
         }
     });
     zip.write_all(serde_json::to_string(&manifest)?.as_bytes())?;
@@ -69,3 +79,107 @@ fn plan_generation_respects_flow_presence() {
         domains::plan_runs(Domain::Events, DomainAction::Setup, &packs, None, false);
     assert!(missing_setup.is_err());
 }
+
+#[test]
+fn setup_plan_runs_dependencies_first() {
+    let temp = tempfile::tempdir().unwrap();
+    let root = temp.path();
+    let providers = root.join("providers").join("messaging");
+    std::fs::create_dir_all(&providers).unwrap();
+
+    write_pack_with_deps(
+        &providers.join("a-webchat.gtpack"),
+        "messaging-webchat",
+        &["setup_default"],
+        &["messaging-telegram"],
+    )
+    .unwrap();
+    write_pack_with_deps(
+        &providers.join("b-telegram.gtpack"),
+        "messaging-telegram",
+        &["setup_default"],
+        &["secrets-vault"],
+    )
+    .unwrap();
+    write_pack(
+        &providers.join("c-slack.gtpack"),
+        "messaging-slack",
+        &["setup_default"],
+    )
+    .unwrap();
+
+    let packs = domains::discover_provider_packs(root, Domain::Messaging).unwrap();
+    let plan =
+        domains::plan_runs(Domain::Messaging, DomainAction::Setup, &packs, None, false).unwrap();
+    let order: Vec<&str> = plan.iter().map(|item| item.pack.pack_id.as_str()).collect();
+    assert_eq!(
+        order,
+        vec!["messaging-telegram", "messaging-webchat", "messaging-slack"]
+    );
+}
+
+#[test]
+fn setup_plan_reports_dependency_cycles() {
+    let temp = tempfile::tempdir().unwrap();
+    let root = temp.path();
+    let providers = root.join("providers").join("messaging");
+    std::fs::create_dir_all(&providers).unwrap();
+
+    write_pack_with_deps(
+        &providers.join("a.gtpack"),
+        "pack-a",
+        &["setup_default"],
+        &["pack-b"],
+    )
+    .unwrap();
+    write_pack_with_deps(
+        &providers.join("b.gtpack"),
+        "pack-b",
+        &["setup_default"],
+        &["pack-a"],
+    )
+    .unwrap();
+
+    let packs = domains::discover_provider_packs(root, Domain::Messaging).unwrap();
+    let err = domains::plan_runs(Domain::Messaging, DomainAction::Setup, &packs, None, false)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "provider setup dependency cycle: pack-a -> pack-b -> pack-a"
+    );
+}
+
+#[test]
+fn verify_plan_adds_pack_declared_flows() {
+    let temp = tempfile::tempdir().unwrap();
+    let root = temp.path();
+    let providers = root.join("providers").join("secrets");
+    std::fs::create_dir_all(&providers).unwrap();
+
+    let file = File::create(providers.join("vault.gtpack")).unwrap();
+    let mut zip = zip::ZipWriter::new(file);
+    zip.start_file(
+        "pack.manifest.json",
+        zip::write::FileOptions::<()>::default(),
+    )
+    .unwrap();
+    let manifest = serde_json::json!({
+        "meta": {
+            "pack_id": "secrets-vault",
+            "entry_flows": ["setup_default", "verify_token", "verify_mounts"],
+            "verify_flows": ["verify_token", "verify_mounts", "verify_missing"],
+        }
+    });
+    zip.write_all(manifest.to_string().as_bytes()).unwrap();
+    zip.finish().unwrap();
+
+    let packs = domains::discover_provider_packs(root, Domain::Secrets).unwrap();
+    assert_eq!(
+        packs[0].verify_flows,
+        vec!["verify_token", "verify_mounts", "verify_missing"]
+    );
+    let plan =
+        domains::plan_runs(Domain::Secrets, DomainAction::Verify, &packs, None, true).unwrap();
+    let flows: Vec<&str> = plan.iter().map(|item| item.flow_id.as_str()).collect();
+    assert_eq!(flows, vec!["verify_token", "verify_mounts"]);
+}
@@ -24,8 +24,10 @@ fn provider_setup_writes_outputs() {
                 pack: Some(pack_path.to_string_lossy().to_string()),
                 setup_flow: Some("setup_default".to_string()),
                 verify_flow: Some("verify_webhooks".to_string()),
+                depends_on: Vec::new(),
             },
         )])),
+        ..Default::default()
     };
 
     let options = ProviderSetupOptions {
@@ -0,0 +1,65 @@
+use std::fs;
+
+#[test]
+fn explain_reports_winning_overlay_and_missing_packs() {
+    let temp = tempfile::tempdir().unwrap();
+    let root = temp.path();
+
+    fs::create_dir_all(root.join("packs").join("demo-pack")).unwrap();
+    fs::create_dir_all(root.join("tenants").join("alpha").join("teams").join("ops")).unwrap();
+    fs::write(
+        root.join("tenants").join("alpha").join("tenant.gmap"),
+        "_ = forbidden\ndemo-pack = public\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("tenants")
+            .join("alpha")
+            .join("teams")
+            .join("ops")
+            .join("team.gmap"),
+        "demo-pack/main = forbidden\n",
+    )
+    .unwrap();
+    fs::create_dir_all(root.join("tenants").join("beta")).unwrap();
+    fs::write(
+        root.join("tenants").join("beta").join("tenant.gmap"),
+        "_ = forbidden\ndemo-pack = public\n",
+    )
+    .unwrap();
+    greentic_operator::project::sync_project(root).unwrap();
+
+    let tenant =
+        greentic_operator::project::explain_resolution(root, "beta", None, "demo-pack/main")
+            .unwrap();
+    assert!(tenant.manifest_found);
+    assert_eq!(tenant.decided_by, "tenant");
+    assert_eq!(tenant.policy, "public");
+    assert!(tenant.present);
+    assert_eq!(tenant.pack.as_ref().unwrap().path, "packs/demo-pack");
+    let winners = tenant
+        .rules
+        .iter()
+        .filter(|rule| rule.winner)
+        .map(|rule| rule.line)
+        .collect::<Vec<_>>();
+    assert_eq!(winners, vec![2]);
+
+    let team = greentic_operator::project::explain_resolution(
+        root,
+        "alpha",
+        Some("ops"),
+        "demo-pack/main",
+    )
+    .unwrap();
+    assert_eq!(team.decided_by, "team");
+    assert_eq!(team.policy, "forbidden");
+    assert!(!team.present);
+    assert_eq!(team.rules.len(), 3);
+
+    let missing =
+        greentic_operator::project::explain_resolution(root, "beta", None, "other-pack").unwrap();
+    assert!(missing.pack.is_none());
+    assert!(!missing.present);
+    assert!(missing.reasons[0].contains("other-pack"));
+}
@@ -81,8 +81,14 @@ fn resolve_manifests_for_tenants_and_teams() {
     assert!(
         messaging
             .iter()
-            .any(|value| value.as_str() == Some("providers/messaging/provider.gtpack"))
+            .any(|value| {
+                value.get("path").and_then(Value::as_str)
+                    == Some("providers/messaging/provider.gtpack")
+            })
     );
+    assert_eq!(team_value.get("schema_version").unwrap().as_u64(), Some(2));
+    let digest = messaging[0].get("digest").unwrap().as_str().unwrap();
+    assert!(digest.starts_with("sha256:"));
 
     let tenant_manifest = fs::read_to_string(
         project_root
@@ -97,11 +103,33 @@ fn resolve_manifests_for_tenants_and_teams() {
     assert!(
         packs
             .iter()
-            .any(|value| value.as_str() == Some("packs/pack1"))
+            .any(|value| value.get("path").and_then(Value::as_str) == Some("packs/pack1"))
     );
     assert!(
         packs
             .iter()
-            .any(|value| value.as_str() == Some("packs/pack2.gtpack"))
+            .any(|value| value.get("path").and_then(Value::as_str) == Some("packs/pack2.gtpack"))
     );
 }
+
+#[test]
+fn v1_resolved_manifests_still_load() {
+    let temp = tempfile::tempdir().unwrap();
+    let path = temp.path().join("demo.default.yaml");
+    fs::write(
+        &path,
+        "version: \"1\"\ntenant: demo\nteam: default\nproject_root: ./\n\
+         providers:\n  messaging:\n  - providers/messaging/messaging-telegram.gtpack\n\
+         packs:\n- packs/app.gtpack\nenv_passthrough: []\n\
+         policy:\n  source:\n    tenant_gmap: tenants/demo/tenant.gmap\n  default: forbidden\n",
+    )
+    .unwrap();
+
+    let manifest = greentic_operator::project::load_resolved_manifest(&path).unwrap();
+    assert_eq!(manifest.schema_version, 1);
+    assert_eq!(
+        manifest.providers["messaging"][0].path(),
+        "providers/messaging/messaging-telegram.gtpack"
+    );
+    assert_eq!(manifest.packs[0].path(), "packs/app.gtpack");
+}
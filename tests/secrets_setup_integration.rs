@@ -108,6 +108,7 @@ fn provider_setup_seeds_and_resolves_secret() -> Result<()> {
                 pack: Some("provider-packs/messaging-telegram.gtpack".to_string()),
                 setup_flow: Some("setup_default".to_string()),
                 verify_flow: None,
+                depends_on: Vec::new(),
             },
         )])),
         ..Default::default()